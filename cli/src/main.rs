@@ -0,0 +1,503 @@
+// Bubblefish 命令行工具 - 不依赖Tauri/WebView，直接链接core的api层
+// 结果输出到stdout（--json时为单个JSON对象），进度和日志输出到stderr
+mod plugin_host;
+mod project;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use bubblefish_core::api;
+use bubblefish_core::common::{set_console_log, ConsoleLog, ImageId, MarkerId, ProjectId};
+use bubblefish_core::service::io::labelplus::{LabelplusCoordinateOrigin, LabelplusMergeOptions};
+use serde_json::{json, Value};
+
+const USAGE: &str = "\
+Usage: bubblefish-cli <command> [options]
+
+Commands:
+  convert <dir-or-zip> -o <out.bf> [--name <name>]    create a project from images
+  import-lp <project.bf> <file.txt> [-o <out.bf>] [--origin <corner>]
+                                                       merge a LabelPlus file into a project; corner is
+                                                       top_left (default), top_right, bottom_left or bottom_right
+  export-lp <project.bf> [-o <out.txt>]                export a project as LabelPlus text
+  stats <project.bf>                                   print image, marker and text statistics
+  ocr <project.bf> --service <id> --plugin <lib> [--plugin <lib>...] [--timeout <secs>] [-o <out.bf>]
+                                                       run OCR on every marker through a native plugin
+
+Options:
+  --json       print a machine-readable JSON result to stdout
+  --verbose    print core and plugin logs to stderr
+  -h, --help   show this help
+
+Exit codes: 0 success, 1 command failed, 2 invalid usage";
+
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+// 带值的选项，其余以-开头的参数视为开关
+const VALUE_OPTIONS: &[&str] = &["-o", "--output", "--name", "--service", "--plugin", "--timeout", "--origin"];
+
+#[derive(Debug)]
+pub struct CliError {
+    code: u8,
+    message: String,
+}
+
+impl CliError {
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self { code: EXIT_USAGE, message: message.into() }
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self { code: EXIT_FAILED, message: message.into() }
+    }
+}
+
+#[derive(Debug)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+    json: bool,
+    verbose: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse(raw: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = Args { positional: Vec::new(), options: HashMap::new(), json: false, verbose: false, help: false };
+        let mut raw = raw.peekable();
+        while let Some(arg) = raw.next() {
+            match arg.as_str() {
+                "--json" => args.json = true,
+                "--verbose" | "-v" => args.verbose = true,
+                "-h" | "--help" => args.help = true,
+                option if VALUE_OPTIONS.contains(&option) => {
+                    let value = raw.next().ok_or_else(|| CliError::usage(format!("{} requires a value", option)))?;
+                    // -o 与 --output 等价
+                    let key = if option == "-o" { "--output" } else { option };
+                    args.options.entry(key.to_string()).or_default().push(value);
+                }
+                option if option.starts_with('-') && option.len() > 1 => {
+                    return Err(CliError::usage(format!("Unknown option: {}", option)));
+                }
+                _ => args.positional.push(arg),
+            }
+        }
+        Ok(args)
+    }
+
+    fn positional(&self, index: usize, what: &str) -> Result<&str, CliError> {
+        self.positional.get(index)
+            .map(String::as_str)
+            .ok_or_else(|| CliError::usage(format!("Missing {}", what)))
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|values| values.last()).map(String::as_str)
+    }
+
+    fn option_all(&self, name: &str) -> &[String] {
+        self.options.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn output(&self) -> Option<PathBuf> {
+        self.option("--output").map(PathBuf::from)
+    }
+}
+
+/// 命令的结果：JSON数据和给人看的文本
+#[derive(Debug)]
+struct Outcome {
+    data: Value,
+    text: String,
+    /// 部分失败时仍输出结果，但退出码为1
+    partial_failure: bool,
+}
+
+impl Outcome {
+    fn ok(data: Value, text: impl Into<String>) -> Self {
+        Self { data, text: text.into(), partial_failure: false }
+    }
+}
+
+fn report_progress(message: &str) {
+    eprintln!("{}", message);
+}
+
+fn cmd_convert(args: &Args) -> Result<Outcome, CliError> {
+    let input = PathBuf::from(args.positional(1, "input directory or archive")?);
+    let output = args.output().unwrap_or_else(|| input.with_extension("bf"));
+    let report = project::convert(&input, args.option("--name").map(str::to_string), &report_progress)?;
+    project::save_bf(report.project_id, &output)?;
+
+    let text = format!(
+        "Created {} with {} images ({} skipped)",
+        output.display(),
+        report.images.len(),
+        report.skipped.len()
+    );
+    let mut data = serde_json::to_value(&report).unwrap_or_default();
+    data["output"] = json!(output);
+    Ok(Outcome::ok(data, text))
+}
+
+fn cmd_import_lp(args: &Args) -> Result<Outcome, CliError> {
+    let bf_path = PathBuf::from(args.positional(1, "project file")?);
+    let lp_path = Path::new(args.positional(2, "LabelPlus file")?);
+    let content = std::fs::read_to_string(lp_path)
+        .map_err(|e| CliError::failed(format!("Cannot read {}: {}", lp_path.display(), e)))?;
+    let output = args.output().unwrap_or_else(|| bf_path.clone());
+    let origin: LabelplusCoordinateOrigin = match args.option("--origin") {
+        Some(value) => serde_json::from_value(json!(value))
+            .map_err(|_| CliError::usage(format!("Invalid --origin: {}", value)))?,
+        None => LabelplusCoordinateOrigin::default(),
+    };
+
+    let project_id = project::open_bf(&bf_path, &report_progress)?;
+    let options = LabelplusMergeOptions { origin, ..Default::default() };
+    let report = api::import_labelplus_merge(project_id, &content, options)
+        .map_err(CliError::failed)?;
+    project::save_bf(project_id, &output)?;
+
+    let text = format!(
+        "Imported {} markers into {} ({} skipped, {} unmatched images)",
+        report.added,
+        output.display(),
+        report.skipped.len(),
+        report.unmatched_images.len()
+    );
+    let mut data = serde_json::to_value(&report).unwrap_or_default();
+    data["output"] = json!(output);
+    Ok(Outcome::ok(data, text))
+}
+
+fn cmd_export_lp(args: &Args) -> Result<Outcome, CliError> {
+    let bf_path = PathBuf::from(args.positional(1, "project file")?);
+    let project_id = project::open_bf(&bf_path, &report_progress)?;
+    let content = api::export_labelplus_data(project_id).map_err(CliError::failed)?;
+
+    match args.output() {
+        Some(output) => {
+            std::fs::write(&output, &content)
+                .map_err(|e| CliError::failed(format!("Cannot write {}: {}", output.display(), e)))?;
+            Ok(Outcome::ok(json!({"output": output}), format!("Exported {}", output.display())))
+        }
+        // 没有指定输出文件时直接把内容写到stdout
+        None => Ok(Outcome::ok(json!({"output": null, "content": &content}), content)),
+    }
+}
+
+fn cmd_stats(args: &Args) -> Result<Outcome, CliError> {
+    let bf_path = PathBuf::from(args.positional(1, "project file")?);
+    let project_id = project::open_bf(&bf_path, &report_progress)?;
+    let counts = api::get_project_stats(project_id)
+        .ok_or_else(|| CliError::failed("Project not found"))?;
+    let text_stats = api::get_text_stats(project_id).map_err(CliError::failed)?;
+
+    let text = format!(
+        "Images: {}\nMarkers: {}\nSource: {} chars / {} words\nTranslated: {} chars / {} words",
+        counts.image_count,
+        counts.marker_count,
+        text_stats.source_chars,
+        text_stats.source_words,
+        text_stats.translated_chars,
+        text_stats.translated_words
+    );
+    let data = json!({
+        "images": counts.image_count,
+        "markers": counts.marker_count,
+        "text": text_stats,
+    });
+    Ok(Outcome::ok(data, text))
+}
+
+fn cmd_ocr(args: &Args) -> Result<Outcome, CliError> {
+    let bf_path = PathBuf::from(args.positional(1, "project file")?);
+    let service_id = args.option("--service").ok_or_else(|| CliError::usage("Missing --service"))?.to_string();
+    if args.option_all("--plugin").is_empty() {
+        return Err(CliError::usage("Missing --plugin"));
+    }
+    let timeout = match args.option("--timeout") {
+        Some(value) => value.parse::<u64>().map_err(|_| CliError::usage(format!("Invalid --timeout: {}", value)))?,
+        None => 60,
+    };
+    let output = args.output().unwrap_or_else(|| bf_path.clone());
+
+    let host = plugin_host::init_plugin_host(args.verbose);
+    let events = plugin_host::subscribe_bunny_events();
+    for plugin in args.option_all("--plugin") {
+        let metadata = host.load_plugin(Path::new(plugin)).map_err(CliError::failed)?;
+        eprintln!("loaded plugin {} {}", metadata.id, metadata.version);
+    }
+    let plugin_id = api::get_available_ocr_services(None).into_iter()
+        .find(|service| service["id"] == service_id.as_str())
+        .and_then(|service| service["plugin_id"].as_str().map(str::to_string))
+        .ok_or_else(|| CliError::failed(format!("OCR service '{}' is not provided by the loaded plugins", service_id)))?;
+
+    let project_id = project::open_bf(&bf_path, &report_progress)?;
+    let markers = project::project_markers(project_id);
+
+    let mut pending: HashMap<String, u32> = HashMap::new();
+    let mut failures = Vec::new();
+    for &(image_id, marker_id) in &markers {
+        match api::request_ocr(MarkerId(marker_id), ImageId(image_id), ProjectId(project_id), service_id.clone()) {
+            Ok(task_id) => {
+                pending.insert(task_id, marker_id);
+            }
+            Err(error) => failures.push(json!({"marker_id": marker_id, "error": error})),
+        }
+    }
+
+    // 处理core发出的事件：转发请求给插件，收集完成/失败结果
+    let total = pending.len();
+    let mut results = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    while !pending.is_empty() {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        let Ok((event_name, data)) = events.recv_timeout(remaining) else {
+            break;
+        };
+        let task_id = data["task_id"].as_str().unwrap_or_default().to_string();
+        match event_name.as_str() {
+            "bunny:request_plugin_ocr" => {
+                let message = json!({
+                    "type": "ocr_request",
+                    "task_id": &task_id,
+                    "image_data": &data["cropped_image_data"],
+                    "image_format": &data["image_format"],
+                    "options": {
+                        "source_language": &data["source_language"],
+                        "pixel_geometry": &data["pixel_geometry"],
+                    },
+                });
+                if let Err(error) = host.send_message(&plugin_id, "bunny", &message) {
+                    let _ = api::handle_task_failed(task_id, error);
+                }
+            }
+            "bunny:ocr_completed" => {
+                if let Some(marker_id) = pending.remove(&task_id) {
+                    eprintln!("[{}/{}] marker {}", total - pending.len(), total, marker_id);
+                    results.push(json!({"marker_id": marker_id, "text": &data["original_text"]}));
+                }
+            }
+            "bunny:task_failed" => {
+                if let Some(marker_id) = pending.remove(&task_id) {
+                    failures.push(json!({"marker_id": marker_id, "error": &data["error"]}));
+                }
+            }
+            _ => {}
+        }
+    }
+    for (_, marker_id) in pending.drain() {
+        failures.push(json!({"marker_id": marker_id, "error": "timed out"}));
+    }
+    host.unload_all();
+
+    project::save_bf(project_id, &output)?;
+    let text = format!(
+        "OCR completed for {}/{} markers, saved to {}",
+        results.len(),
+        markers.len(),
+        output.display()
+    );
+    let partial_failure = !failures.is_empty();
+    let data = json!({
+        "service": service_id,
+        "output": output,
+        "completed": results,
+        "failed": failures,
+    });
+    Ok(Outcome { data, text, partial_failure })
+}
+
+// 插件可能直接向stdout打印，命令执行期间把stdout重定向到stderr，保证--json输出可解析
+struct StdoutToStderr {
+    #[cfg(unix)]
+    saved: i32,
+}
+
+impl StdoutToStderr {
+    fn redirect() -> Self {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        #[cfg(unix)]
+        unsafe {
+            let saved = libc::dup(1);
+            if saved >= 0 {
+                libc::dup2(2, 1);
+            }
+            Self { saved }
+        }
+        #[cfg(not(unix))]
+        Self {}
+    }
+}
+
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        use std::io::Write;
+        let _ = std::io::stdout().flush();
+        #[cfg(unix)]
+        if self.saved >= 0 {
+            unsafe {
+                libc::dup2(self.saved, 1);
+                libc::close(self.saved);
+            }
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<Outcome, CliError> {
+    match args.positional.first().map(String::as_str) {
+        Some("convert") => cmd_convert(args),
+        Some("import-lp") => cmd_import_lp(args),
+        Some("export-lp") => cmd_export_lp(args),
+        Some("stats") => cmd_stats(args),
+        Some("ocr") => cmd_ocr(args),
+        Some(command) => Err(CliError::usage(format!("Unknown command: {}", command))),
+        None => Err(CliError::usage("Missing command")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error.message, USAGE);
+            return ExitCode::from(error.code);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    // stdout只留给命令结果
+    set_console_log(if args.verbose { ConsoleLog::Stderr } else { ConsoleLog::Off });
+
+    let result = {
+        let _redirect = StdoutToStderr::redirect();
+        run(&args)
+    };
+    match result {
+        Ok(outcome) => {
+            if args.json {
+                let mut data = outcome.data;
+                data["ok"] = json!(!outcome.partial_failure);
+                println!("{}", data);
+            } else {
+                println!("{}", outcome.text);
+            }
+            if outcome.partial_failure { ExitCode::from(EXIT_FAILED) } else { ExitCode::SUCCESS }
+        }
+        Err(error) => {
+            if args.json {
+                println!("{}", json!({"ok": false, "error": error.message}));
+            } else {
+                eprintln!("error: {}", error.message);
+            }
+            if error.code == EXIT_USAGE && !args.json {
+                eprintln!("\n{}", USAGE);
+            }
+            ExitCode::from(error.code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, CliError> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["ocr", "a.bf", "--json", "--plugin", "x.so", "--service", "s", "--plugin", "y.so", "-o", "b.bf"]).unwrap();
+        assert_eq!(args.positional, vec!["ocr", "a.bf"]);
+        assert!(args.json);
+        assert_eq!(args.option_all("--plugin"), ["x.so", "y.so"]);
+        assert_eq!(args.output(), Some(PathBuf::from("b.bf")));
+
+        assert_eq!(parse(&["stats", "--output"]).unwrap_err().code, EXIT_USAGE);
+        assert_eq!(parse(&["stats", "--bogus"]).unwrap_err().code, EXIT_USAGE);
+        assert_eq!(run(&parse(&["frobnicate"]).unwrap()).unwrap_err().code, EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_parse_flags_and_repeated_options() {
+        let args = parse(&["export-lp", "a.bf", "--output", "x.txt", "-v", "-o", "y.txt", "-h", "-"]).unwrap();
+        // 单独的-是位置参数，同一选项给了多次时取最后一个
+        assert_eq!(args.positional, vec!["export-lp", "a.bf", "-"]);
+        assert_eq!(args.output(), Some(PathBuf::from("y.txt")));
+        assert_eq!(args.option_all("--output"), ["x.txt", "y.txt"]);
+        assert!(args.verbose && args.help && !args.json);
+        assert!(args.option("--name").is_none());
+        assert!(args.option_all("--plugin").is_empty());
+
+        assert_eq!(run(&parse(&[]).unwrap()).unwrap_err().code, EXIT_USAGE);
+        assert_eq!(run(&parse(&["stats"]).unwrap()).unwrap_err().code, EXIT_USAGE);
+        assert_eq!(run(&parse(&["import-lp", "a.bf"]).unwrap()).unwrap_err().code, EXIT_USAGE);
+    }
+
+    // 内容不同的两张RGB PNG（4x4和8x4），打开bf时图片按校验和匹配
+    const PNG: &[u8] = &[
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 4, 0, 0, 0, 4, 8, 2, 0, 0, 0, 38, 147, 9,
+        41, 0, 0, 0, 16, 73, 68, 65, 84, 120, 156, 99, 56, 33, 39, 7, 71, 12, 196, 113, 0, 177, 99, 16, 65, 59, 75, 99,
+        130, 0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+    ];
+    const WIDE_PNG: &[u8] = &[
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 8, 0, 0, 0, 4, 8, 2, 0, 0, 0, 60, 175, 233,
+        167, 0, 0, 0, 17, 73, 68, 65, 84, 120, 156, 99, 144, 147, 59, 129, 21, 49, 80, 79, 2, 0, 68, 126, 32, 129, 53, 91, 224, 154, 0, 0,
+        0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+    ];
+
+    fn run_cli(args: &[&str]) -> Result<Outcome, CliError> {
+        run(&parse(args).unwrap())
+    }
+
+    #[test]
+    fn test_convert_import_export_stats_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bf_cli_round_trip_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("page_01.png"), PNG).unwrap();
+        std::fs::write(dir.join("page_02.png"), WIDE_PNG).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an image").unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let bf = path("chapter.bf");
+
+        let outcome = run_cli(&["convert", &path(""), "-o", &bf, "--name", "chapter"]).unwrap();
+        assert_eq!(outcome.data["images"], json!(["page_01.png", "page_02.png"]));
+        assert_eq!(outcome.data["skipped"], json!(["notes.txt"]));
+        assert!(Path::new(&bf).is_file());
+
+        let stats = run_cli(&["stats", &bf]).unwrap();
+        assert_eq!((stats.data["images"].clone(), stats.data["markers"].clone()), (json!(2), json!(0)));
+
+        let lp = path("chapter.txt");
+        std::fs::write(&lp, "1,0\n-\n框内\n框外\n-\n\n>>>>>>>>[page_01.png]<<<<<<<<\n----------------[1]----------------[0.25,0.5,1]\n你好\n\n>>>>>>>>[page_02.png]<<<<<<<<\n----------------[1]----------------[0.5,0.5,2]\n世界\n").unwrap();
+        assert_eq!(run_cli(&["import-lp", &bf, &lp, "--origin", "middle"]).unwrap_err().code, EXIT_USAGE);
+        let imported = run_cli(&["import-lp", &bf, &lp, "--origin", "top_left"]).unwrap();
+        assert_eq!(imported.data["added"], json!(2));
+
+        // 导入结果写回了bf，重新打开后仍然存在
+        let stats = run_cli(&["stats", &bf, "--json"]).unwrap();
+        assert_eq!(stats.data["markers"], json!(2));
+        assert!(stats.text.contains("Markers: 2"));
+
+        let exported = path("exported.txt");
+        run_cli(&["export-lp", &bf, "-o", &exported]).unwrap();
+        let content = std::fs::read_to_string(&exported).unwrap();
+        assert!(content.contains(">>>>>>>>[page_02.png]<<<<<<<<") && content.contains("世界"));
+        // 不指定输出文件时内容作为结果返回
+        let printed = run_cli(&["export-lp", &bf]).unwrap();
+        assert_eq!(printed.text, content);
+        assert_eq!(printed.data["content"], json!(content));
+
+        assert_eq!(run_cli(&["stats", &path("missing.bf")]).unwrap_err().code, EXIT_FAILED);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+// 最小的原生插件宿主 - 与桌面端加载器提供相同的HostCallbacks
+// 只实现OCR/翻译需要的服务：bunny、events、config（内存中）、notifications
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use libloading::{Library, Symbol};
+use serde_json::Value;
+
+use bubblefish_core::common::{Event, EventEmitter, EventType, EVENT_SYSTEM};
+use bubblefish_core::plugin::ServiceInterface;
+use bubblefish_core::plugin::service_registry::adapters::NotificationServiceAdapter;
+
+/// Callbacks provided to plugins（内存布局必须与插件SDK一致）
+#[repr(C)]
+pub struct HostCallbacks {
+    pub call_service: extern "C" fn(
+        plugin_id: *const c_char,
+        service: *const c_char,
+        method: *const c_char,
+        params: *const c_char,
+    ) -> *mut c_char,
+    pub read_image_file: extern "C" fn(
+        file_path: *const c_char,
+        data_ptr: *mut *mut u8,
+        data_len: *mut usize,
+    ) -> i32,
+    pub free_host_memory: extern "C" fn(ptr: *mut c_void),
+    pub log_message: extern "C" fn(level: i32, message: *const c_char),
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PluginMetadata {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+struct LoadedPlugin {
+    library: Library,
+}
+
+pub struct PluginHost {
+    plugins: Mutex<HashMap<String, LoadedPlugin>>,
+    // 插件配置只保存在内存中，命令结束即丢弃
+    configs: Mutex<HashMap<String, Value>>,
+    verbose: bool,
+}
+
+static PLUGIN_HOST: OnceLock<PluginHost> = OnceLock::new();
+
+pub fn init_plugin_host(verbose: bool) -> &'static PluginHost {
+    PLUGIN_HOST.get_or_init(|| PluginHost {
+        plugins: Mutex::new(HashMap::new()),
+        configs: Mutex::new(HashMap::new()),
+        verbose,
+    })
+}
+
+fn plugin_host() -> Option<&'static PluginHost> {
+    PLUGIN_HOST.get()
+}
+
+impl PluginHost {
+    /// 加载并激活插件，返回插件元数据
+    pub fn load_plugin(&self, path: &Path) -> Result<PluginMetadata, String> {
+        unsafe {
+            let library = Library::new(path)
+                .map_err(|e| format!("Failed to load plugin library {}: {}", path.display(), e))?;
+
+            let set_callbacks: Symbol<extern "C" fn(HostCallbacks)> = library
+                .get(b"plugin_set_host_callbacks")
+                .map_err(|e| format!("Failed to find plugin_set_host_callbacks: {}", e))?;
+            set_callbacks(HostCallbacks {
+                call_service: host_call_service,
+                read_image_file: host_read_image_file,
+                free_host_memory: host_free_host_memory,
+                log_message: host_log_message,
+            });
+
+            // 与桌面端相同，从文件名推导插件ID：libdummy_ocr_plugin.so -> dummy-ocr-plugin
+            let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+            let plugin_id = file_stem.strip_prefix("lib").unwrap_or(file_stem).replace('_', "-");
+            let plugin_id_c = CString::new(plugin_id).map_err(|e| format!("Invalid plugin ID: {}", e))?;
+
+            let init: Symbol<extern "C" fn(*const c_char) -> i32> = library
+                .get(b"plugin_init")
+                .map_err(|e| format!("Failed to find plugin_init: {}", e))?;
+            let result = init(plugin_id_c.as_ptr());
+            if result != 0 {
+                return Err(format!("Plugin initialization failed with code: {}", result));
+            }
+
+            let get_metadata: Symbol<extern "C" fn() -> *mut c_char> = library
+                .get(b"plugin_get_metadata")
+                .map_err(|e| format!("Failed to find plugin_get_metadata: {}", e))?;
+            let metadata_ptr = get_metadata();
+            if metadata_ptr.is_null() {
+                return Err("Failed to get plugin metadata".to_string());
+            }
+            let metadata_str = CStr::from_ptr(metadata_ptr).to_string_lossy().into_owned();
+            let free_string: Symbol<extern "C" fn(*mut c_char)> = library
+                .get(b"plugin_free_string")
+                .map_err(|e| format!("Failed to find plugin_free_string: {}", e))?;
+            free_string(metadata_ptr);
+            let metadata: PluginMetadata = serde_json::from_str(&metadata_str)
+                .map_err(|e| format!("Failed to parse plugin metadata: {}", e))?;
+
+            // 激活时插件会通过call_service注册OCR/翻译服务，这里不能持有plugins锁
+            let activate: Symbol<extern "C" fn() -> i32> = library
+                .get(b"plugin_activate")
+                .map_err(|e| format!("Failed to find plugin_activate: {}", e))?;
+            let activate_result = activate();
+            if activate_result != 0 {
+                return Err(format!("Plugin activation failed with code: {}", activate_result));
+            }
+
+            self.plugins.lock().unwrap().insert(metadata.id.clone(), LoadedPlugin { library });
+            Ok(metadata)
+        }
+    }
+
+    /// 向插件发送消息（OCR/翻译请求）
+    pub fn send_message(&self, to: &str, from: &str, message: &Value) -> Result<(), String> {
+        let plugins = self.plugins.lock().unwrap();
+        let plugin = plugins.get(to).ok_or_else(|| format!("Plugin '{}' is not loaded", to))?;
+        unsafe {
+            let on_message: Symbol<extern "C" fn(*const c_char, *const c_char) -> i32> = plugin.library
+                .get(b"plugin_on_message")
+                .map_err(|e| format!("Failed to find plugin_on_message: {}", e))?;
+            let from_c = CString::new(from).map_err(|e| format!("Invalid from string: {}", e))?;
+            let message_c = CString::new(serde_json::to_string(message).map_err(|e| e.to_string())?)
+                .map_err(|e| format!("Invalid message JSON: {}", e))?;
+            let result = on_message(from_c.as_ptr(), message_c.as_ptr());
+            if result != 0 {
+                return Err(format!("Message handling failed with code: {}", result));
+            }
+        }
+        Ok(())
+    }
+
+    /// 停用并卸载所有插件
+    pub fn unload_all(&self) {
+        let mut plugins = self.plugins.lock().unwrap();
+        for (_, plugin) in plugins.drain() {
+            unsafe {
+                if let Ok(deactivate) = plugin.library.get::<extern "C" fn() -> i32>(b"plugin_deactivate") {
+                    let _ = deactivate();
+                }
+                if let Ok(destroy) = plugin.library.get::<extern "C" fn()>(b"plugin_destroy") {
+                    destroy();
+                }
+            }
+        }
+    }
+
+    fn call_service(&self, plugin_id: &str, service: &str, method: &str, params: &Value) -> Result<Value, String> {
+        match service {
+            "bunny" => self.handle_bunny_service(method, params),
+            "events" => self.handle_events_service(method, params),
+            "config" => self.handle_config_service(plugin_id, method, params),
+            "notifications" => NotificationServiceAdapter::new().call(method, params.clone()),
+            _ => Err(format!("Service '{}' is not available in the command line host", service)),
+        }
+    }
+
+    fn handle_bunny_service(&self, method: &str, params: &Value) -> Result<Value, String> {
+        use bubblefish_core::service::bunny::{DetectionServiceInfo, OCRServiceInfo, TranslationServiceInfo, BUNNY_SERVICE_REGISTRY};
+
+        let mut registry = BUNNY_SERVICE_REGISTRY.write()
+            .map_err(|e| format!("Failed to acquire write lock: {}", e))?;
+        match method {
+            "register_ocr_service" => {
+                let plugin_id = params["plugin_id"].as_str().ok_or("Missing plugin_id")?;
+                let info: OCRServiceInfo = serde_json::from_value(params["service_info"].clone())
+                    .map_err(|e| format!("Invalid service info: {}", e))?;
+                registry.register_ocr_service(plugin_id.to_string(), info)?;
+            }
+            "register_translation_service" => {
+                let plugin_id = params["plugin_id"].as_str().ok_or("Missing plugin_id")?;
+                let info: TranslationServiceInfo = serde_json::from_value(params["service_info"].clone())
+                    .map_err(|e| format!("Invalid service info: {}", e))?;
+                registry.register_translation_service(plugin_id.to_string(), info)?;
+            }
+            "register_detection_service" => {
+                let plugin_id = params["plugin_id"].as_str().ok_or("Missing plugin_id")?;
+                let info: DetectionServiceInfo = serde_json::from_value(params["service_info"].clone())
+                    .map_err(|e| format!("Invalid service info: {}", e))?;
+                registry.register_detection_service(plugin_id.to_string(), info)?;
+            }
+            "unregister_service" => {
+                let service_id = params["service_id"].as_str().ok_or("Missing service_id")?;
+                registry.unregister_service(service_id)?;
+            }
+            "get_ocr_services" => return Ok(Value::Array(registry.get_ocr_services())),
+            "get_translation_services" => return Ok(Value::Array(registry.get_translation_services())),
+            "get_detection_services" => return Ok(Value::Array(registry.get_detection_services())),
+            _ => return Err(format!("Unknown bunny method: {}", method)),
+        }
+        Ok(serde_json::json!({"success": true}))
+    }
+
+    fn handle_events_service(&self, method: &str, params: &Value) -> Result<Value, String> {
+        use bubblefish_core::api::bunny;
+        use bubblefish_core::service::bunny::TASK_MANAGER;
+
+        match method {
+            "emit_business_event" => {
+                let event_name = params["event_name"].as_str().ok_or("Missing event_name")?;
+                let data = &params["data"];
+                let task = data["task_id"].as_str()
+                    .and_then(|task_id| TASK_MANAGER.get_task(task_id).ok().flatten());
+                // 插件的结果事件直接交给core处理，和桌面端一致
+                match (event_name, task) {
+                    ("plugin:ocr_result", Some(task)) => {
+                        let text = data["text"].as_str().unwrap_or_default().to_string();
+                        let model = data["model"].as_str().unwrap_or_default().to_string();
+                        let regions = serde_json::from_value(data["regions"].clone()).unwrap_or_default();
+                        bunny::handle_ocr_completed(task.task_id.clone(), task.marker_id, text, model, regions)?;
+                    }
+                    ("plugin:translation_result", Some(task)) => {
+                        let text = data["translated_text"].as_str().unwrap_or_default().to_string();
+                        let service = data["service"].as_str().unwrap_or_default().to_string();
+                        let warnings = serde_json::from_value(data["warnings"].clone()).unwrap_or_default();
+                        let metadata = data.get("metadata").cloned();
+                        bunny::handle_translation_completed(task.task_id.clone(), task.marker_id, text, service, warnings, metadata)?;
+                    }
+                    ("plugin:detect_result", Some(task)) => {
+                        let model = data["model"].as_str().unwrap_or_default().to_string();
+                        let regions = serde_json::from_value(data["regions"].clone()).unwrap_or_default();
+                        bunny::handle_detection_completed(task.task_id.clone(), model, regions)?;
+                    }
+                    ("plugin:detect_error", Some(task)) => {
+                        let error = data["error"].as_str().unwrap_or("Detection failed").to_string();
+                        bunny::handle_task_failed(task.task_id.clone(), error)?;
+                    }
+                    _ => {}
+                }
+                EVENT_SYSTEM.emit_business_event(event_name.to_string(), data.clone())?;
+                Ok(serde_json::json!({"success": true}))
+            }
+            "emit_log_event" => {
+                let message = params["message"].as_str().ok_or("Missing message")?;
+                if self.verbose {
+                    eprintln!("[plugin {}] {}", params["level"].as_str().unwrap_or("info"), message);
+                }
+                Ok(serde_json::json!({"success": true}))
+            }
+            _ => Err(format!("Unknown events method: {}", method)),
+        }
+    }
+
+    fn handle_config_service(&self, plugin_id: &str, method: &str, params: &Value) -> Result<Value, String> {
+        let mut configs = self.configs.lock().unwrap();
+        let config = configs.entry(plugin_id.to_string()).or_insert_with(|| serde_json::json!({}));
+        match method {
+            "get" => Ok(match params["key"].as_str() {
+                Some(key) => config.get(key).cloned().unwrap_or(Value::Null),
+                None => config.clone(),
+            }),
+            "set" => {
+                if let Some(new_config) = params.get("config") {
+                    *config = new_config.clone();
+                } else if let (Some(key), Some(value)) = (params["key"].as_str(), params.get("value")) {
+                    config[key] = value.clone();
+                } else {
+                    return Err("Either config or key-value pair required".to_string());
+                }
+                Ok(serde_json::json!({"success": true}))
+            }
+            "delete" => {
+                *config = serde_json::json!({});
+                Ok(serde_json::json!({"success": true}))
+            }
+            _ => Err(format!("Unknown config method: {}", method)),
+        }
+    }
+}
+
+// 把bunny事件转到命令行的处理循环；不能在emit里直接调用插件，插件回调时会重入事件系统
+struct BunnyEventRelay {
+    sender: Mutex<Sender<(String, Value)>>,
+}
+
+impl EventEmitter for BunnyEventRelay {
+    fn emit(&self, event: Event) -> Result<(), String> {
+        if matches!(event.event_type, EventType::Business) && event.event_name.starts_with("bunny:") {
+            let _ = self.sender.lock().unwrap().send((event.event_name, event.data));
+        }
+        Ok(())
+    }
+}
+
+/// 订阅core发出的bunny事件（请求插件处理、任务完成/失败）
+pub fn subscribe_bunny_events() -> Receiver<(String, Value)> {
+    let (sender, receiver) = mpsc::channel();
+    EVENT_SYSTEM.register_emitter("cli".to_string(), Box::new(BunnyEventRelay { sender: Mutex::new(sender) }));
+    receiver
+}
+
+// Host callback implementations
+extern "C" fn host_call_service(
+    plugin_id: *const c_char,
+    service: *const c_char,
+    method: *const c_char,
+    params: *const c_char,
+) -> *mut c_char {
+    let (plugin_id, service, method, params_str) = unsafe {
+        (
+            CStr::from_ptr(plugin_id).to_string_lossy().into_owned(),
+            CStr::from_ptr(service).to_string_lossy().into_owned(),
+            CStr::from_ptr(method).to_string_lossy().into_owned(),
+            CStr::from_ptr(params).to_string_lossy().into_owned(),
+        )
+    };
+    let Ok(params) = serde_json::from_str::<Value>(&params_str) else {
+        return std::ptr::null_mut();
+    };
+    let Some(host) = plugin_host() else {
+        return std::ptr::null_mut();
+    };
+    match host.call_service(&plugin_id, &service, &method, &params) {
+        Ok(result) => CString::new(serde_json::to_string(&result).unwrap_or_default())
+            .map(CString::into_raw)
+            .unwrap_or(std::ptr::null_mut()),
+        Err(e) => {
+            if host.verbose {
+                eprintln!("[plugin host] service={} method={} error={}", service, method, e);
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
+extern "C" fn host_read_image_file(
+    file_path: *const c_char,
+    data_ptr: *mut *mut u8,
+    data_len: *mut usize,
+) -> i32 {
+    unsafe {
+        let file_path = CStr::from_ptr(file_path).to_string_lossy();
+        let Ok(data) = std::fs::read(&*file_path) else {
+            return -1;
+        };
+        // 插件通过free_host_memory释放，必须用malloc分配
+        let allocated = libc::malloc(data.len()) as *mut u8;
+        if allocated.is_null() {
+            return -1;
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), allocated, data.len());
+        *data_ptr = allocated;
+        *data_len = data.len();
+        0
+    }
+}
+
+extern "C" fn host_free_host_memory(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        unsafe { libc::free(ptr) };
+    }
+}
+
+extern "C" fn host_log_message(level: i32, message: *const c_char) {
+    let verbose = plugin_host().is_some_and(|host| host.verbose);
+    // 警告和错误总是输出到stderr，其余只在--verbose时输出
+    if level >= 2 || verbose {
+        let message = unsafe { CStr::from_ptr(message).to_string_lossy() };
+        let label = match level {
+            0 => "DEBUG",
+            1 => "INFO",
+            2 => "WARN",
+            _ => "ERROR",
+        };
+        eprintln!("[Plugin {}] {}", label, message);
+    }
+}
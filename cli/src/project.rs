@@ -0,0 +1,154 @@
+// 项目的打开、创建和保存 - 走与前端相同的临时项目流程（创建 -> 添加图片 -> flush -> finalize）
+use std::path::{Path, PathBuf};
+use bubblefish_core::api;
+use bubblefish_core::common::sort_natural_by_key;
+use serde::Serialize;
+
+use crate::CliError;
+
+#[derive(Debug, Serialize)]
+pub struct ConvertReport {
+    pub project_id: u32,
+    pub project_name: String,
+    pub images: Vec<String>,
+    /// 跳过的文件（不是图片或无法读取）
+    pub skipped: Vec<String>,
+}
+
+fn project_name_of(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("project")
+        .to_string()
+}
+
+fn finalize(project_id: u32) -> Result<u32, CliError> {
+    api::flush_opening_project_images(project_id);
+    if !api::finalize_opening_project(project_id) {
+        api::delete_opening_project(project_id);
+        return Err(CliError::failed("Failed to finalize project"));
+    }
+    Ok(project_id)
+}
+
+// 在bf文件所在目录查找图片，文件名不完全一致时按不含扩展名的名称匹配（与桌面端扫描规则一致）
+fn find_image(dir: &Path, name: &str, candidates: &[PathBuf]) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if exact.is_file() {
+        return Some(exact);
+    }
+    let stem = |value: &str| value.rsplit_once('.').map(|(stem, _)| stem.to_string()).unwrap_or_else(|| value.to_string());
+    let wanted = stem(name);
+    candidates.iter()
+        .find(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| stem(n) == wanted))
+        .cloned()
+}
+
+fn image_files(dir: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| CliError::failed(format!("Cannot read directory {}: {}", dir.display(), e)))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    sort_natural_by_key(&mut files, |path| path.file_name().and_then(|n| n.to_str()).unwrap_or_default());
+    Ok(files)
+}
+
+/// 打开.bf项目，图片从bf文件所在目录加载
+pub fn open_bf(path: &Path, progress: &dyn Fn(&str)) -> Result<u32, CliError> {
+    if !path.is_file() {
+        return Err(CliError::failed(format!("Project file not found: {}", path.display())));
+    }
+    let project_id = api::create_opening_project_from_path(path.to_path_buf(), project_name_of(path))
+        .map_err(CliError::failed)?;
+    let info = api::get_opening_project_info(project_id)
+        .ok_or_else(|| CliError::failed("Failed to read opening project"))?;
+
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let candidates = image_files(dir)?;
+    let total = info.pending_images.len();
+    for (index, name) in info.pending_images.iter().enumerate() {
+        progress(&format!("[{}/{}] loading {}", index + 1, total, name));
+        if let Some(image_path) = find_image(dir, name, &candidates) {
+            api::add_image_from_path_to_project(project_id, image_path);
+        }
+    }
+    api::flush_opening_project_images(project_id);
+
+    let info = api::get_opening_project_info(project_id)
+        .ok_or_else(|| CliError::failed("Failed to read opening project"))?;
+    if !info.is_complete {
+        api::delete_opening_project(project_id);
+        return Err(CliError::failed(format!(
+            "Missing images next to {}: {}",
+            path.display(),
+            info.pending_images.join(", ")
+        )));
+    }
+    finalize(project_id)
+}
+
+/// 从图片目录或zip/cbz压缩包创建项目
+pub fn convert(input: &Path, name: Option<String>, progress: &dyn Fn(&str)) -> Result<ConvertReport, CliError> {
+    let project_name = name.unwrap_or_else(|| project_name_of(input));
+
+    if input.is_dir() {
+        let project_id = api::create_empty_opening_project(project_name.clone(), None).map_err(CliError::failed)?;
+        let files = image_files(input)?;
+        let total = files.len();
+        let mut images = Vec::new();
+        let mut skipped = Vec::new();
+        for (index, file) in files.into_iter().enumerate() {
+            let file_name = file.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+            progress(&format!("[{}/{}] adding {}", index + 1, total, file_name));
+            let is_image = api::validate_image_file(&file.to_string_lossy()).is_ok();
+            if is_image && api::add_image_from_path_to_project(project_id, file).is_some() {
+                images.push(file_name);
+            } else {
+                skipped.push(file_name);
+            }
+        }
+        if images.is_empty() {
+            api::delete_opening_project(project_id);
+            return Err(CliError::failed(format!("No images found in {}", input.display())));
+        }
+        let project_id = finalize(project_id)?;
+        return Ok(ConvertReport { project_id, project_name, images, skipped });
+    }
+
+    if input.is_file() {
+        progress(&format!("extracting {}", input.display()));
+        let report = api::create_opening_project_from_archive_path(input.to_path_buf(), project_name.clone(), None)
+            .map_err(CliError::failed)?;
+        let project_id = finalize(report.project_id)?;
+        let mut skipped = report.skipped_entries;
+        skipped.extend(report.failed_entries.into_iter().map(|(entry, reason)| format!("{} ({})", entry, reason)));
+        return Ok(ConvertReport { project_id, project_name, images: report.added_images, skipped });
+    }
+
+    Err(CliError::failed(format!("Input not found: {}", input.display())))
+}
+
+/// 把项目保存为.bf文件
+pub fn save_bf(project_id: u32, path: &Path) -> Result<(), CliError> {
+    let data = api::save_project_to_path(project_id, &path.to_string_lossy()).map_err(CliError::failed)?;
+    std::fs::write(path, data)
+        .map_err(|e| CliError::failed(format!("Cannot write {}: {}", path.display(), e)))
+}
+
+/// 按图片顺序列出项目中的所有标记 (image_id, marker_id)
+pub fn project_markers(project_id: u32) -> Vec<(u32, u32)> {
+    let Some(project) = api::get_project_info(project_id) else {
+        return Vec::new();
+    };
+    project.image_ids.iter()
+        .flat_map(|image_id| {
+            let image_id = image_id.0;
+            api::get_image_marker_ids(image_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |marker_id| (image_id, marker_id))
+        })
+        .collect()
+}
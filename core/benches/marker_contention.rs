@@ -0,0 +1,107 @@
+// 标记存储锁竞争基准：一个线程反复批量编辑一张图片的标记，其他线程读取别的图片
+// 对比单个RwLock<MarkerStorage>与按图片分片的ShardedMarkerStorage下读线程的吞吐
+// 运行：cargo bench -p bubblefish-core --bench marker_contention
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use bubblefish_core::common::{ImageId, MarkerId};
+use bubblefish_core::storage::marker::Marker;
+use bubblefish_core::storage::state::{MarkerStorage, ShardedMarkerStorage};
+use bubblefish_core::storage::traits::Storage;
+
+const IMAGES: u32 = 8;
+// 被批量编辑的图片0标记很多，被读取的其他图片是普通大小
+const BATCH_MARKERS: u32 = 20000;
+const MARKERS_PER_IMAGE: u32 = 100;
+const READERS: u32 = 3;
+const DURATION: Duration = Duration::from_millis(500);
+// 模拟批量操作持锁期间较慢的处理（正则替换、导入解析等），单核机器上也能体现锁等待
+const BATCH_HOLD: Duration = Duration::from_millis(2);
+
+fn marker(image: u32, n: u32) -> Marker {
+    Marker::new_point(MarkerId(image * BATCH_MARKERS + n), ImageId(image), 0.5, 0.5, n + 1)
+}
+
+// 批量编辑：改写图片0上所有标记的译文
+fn batch_edit(storage: &mut MarkerStorage, round: usize) {
+    let ids: Vec<MarkerId> = storage.get_by_image(&ImageId(0)).iter().map(|m| m.id).collect();
+    for id in ids {
+        if let Some(marker) = storage.get_mut(&id) {
+            marker.translation = round.to_string();
+        }
+    }
+    std::thread::sleep(BATCH_HOLD);
+}
+
+fn run(name: &str, write: impl Fn(usize) + Send + Sync + 'static, read: impl Fn(u32) -> usize + Send + Sync + 'static) {
+    let write = Arc::new(write);
+    let read = Arc::new(read);
+    let stop = Arc::new(AtomicBool::new(false));
+    let reads = Arc::new(AtomicUsize::new(0));
+    let max_wait_us = Arc::new(AtomicUsize::new(0));
+    let writes = Arc::new(AtomicUsize::new(0));
+
+    let mut handles = Vec::new();
+    {
+        let (write, stop, writes) = (write.clone(), stop.clone(), writes.clone());
+        handles.push(std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                write(writes.fetch_add(1, Ordering::Relaxed));
+            }
+        }));
+    }
+    for reader in 0..READERS {
+        let (read, stop, reads, max_wait_us) = (read.clone(), stop.clone(), reads.clone(), max_wait_us.clone());
+        handles.push(std::thread::spawn(move || {
+            let image = 1 + reader % (IMAGES - 1);
+            while !stop.load(Ordering::Relaxed) {
+                let started = Instant::now();
+                assert_eq!(read(image), MARKERS_PER_IMAGE as usize);
+                max_wait_us.fetch_max(started.elapsed().as_micros() as usize, Ordering::Relaxed);
+                reads.fetch_add(1, Ordering::Relaxed);
+            }
+        }));
+    }
+
+    let started = Instant::now();
+    std::thread::sleep(DURATION);
+    stop.store(true, Ordering::Relaxed);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let seconds = started.elapsed().as_secs_f64();
+    println!(
+        "{:<10} reads/s: {:>10.0}  max read latency: {:>8}us  batch edits/s: {:>6.0}",
+        name,
+        reads.load(Ordering::Relaxed) as f64 / seconds,
+        max_wait_us.load(Ordering::Relaxed),
+        writes.load(Ordering::Relaxed) as f64 / seconds
+    );
+}
+
+fn main() {
+    let single = Arc::new(RwLock::new(MarkerStorage::default()));
+    let sharded = Arc::new(ShardedMarkerStorage::default());
+    for image in 0..IMAGES {
+        let count = if image == 0 { BATCH_MARKERS } else { MARKERS_PER_IMAGE };
+        for n in 0..count {
+            single.write().unwrap().insert_with_image(marker(image, n)).unwrap();
+            sharded.insert_with_image(marker(image, n)).unwrap();
+        }
+    }
+
+    let (writer, reader) = (single.clone(), single);
+    run(
+        "single",
+        move |round| batch_edit(&mut writer.write().unwrap(), round),
+        move |image| reader.read().unwrap().get_by_image(&ImageId(image)).len(),
+    );
+
+    let (writer, reader) = (sharded.clone(), sharded);
+    run(
+        "sharded",
+        move |round| batch_edit(&mut writer.write_image(ImageId(0)).unwrap(), round),
+        move |image| reader.read_image(ImageId(image)).unwrap().get_by_image(&ImageId(image)).len(),
+    );
+}
@@ -1,21 +1,46 @@
 // Bunny (海兔) API - OCR and Translation functionality
-use crate::service::bunny::{BUNNY_SERVICE_REGISTRY, BunnyService};
-use crate::common::{MarkerId, ImageId, ProjectId};
+use crate::service::bunny::{AcceptMachineTranslationReport, BUNNY_SERVICE_REGISTRY, BunnyPipeline, BunnyService, ImageTaskProgress, OcrBatchItem, PipelineBatchItem, ServiceTaskMetrics, TranslationResult, TaskHistoryEntry, TASK_MANAGER};
+use crate::common::{log_function_call, register_operation, DetectedRegion, MarkerId, ImageId, OcrRegion, ProjectId, TranslationWarning};
+use crate::storage::project::get_project_storage;
 
 /// Get available OCR services from plugins (with plugin_id included)
-pub fn get_available_ocr_services() -> Vec<serde_json::Value> {
-    BUNNY_SERVICE_REGISTRY
+/// 传入project_id时只返回该项目启用的服务
+pub fn get_available_ocr_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    let services = BUNNY_SERVICE_REGISTRY
         .read()
         .map(|registry| registry.get_ocr_services())
-        .unwrap_or_default()
+        .unwrap_or_default();
+    filter_project_services(services, project_id)
 }
 
 /// Get available translation services from plugins (with plugin_id included)
-pub fn get_available_translation_services() -> Vec<serde_json::Value> {
-    BUNNY_SERVICE_REGISTRY
+/// 传入project_id时只返回该项目启用的服务
+pub fn get_available_translation_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    let services = BUNNY_SERVICE_REGISTRY
         .read()
         .map(|registry| registry.get_translation_services())
-        .unwrap_or_default()
+        .unwrap_or_default();
+    filter_project_services(services, project_id)
+}
+
+/// Get available bubble/text detection services from plugins (with plugin_id included)
+/// 传入project_id时只返回该项目启用的服务
+pub fn get_available_detection_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    let services = BUNNY_SERVICE_REGISTRY
+        .read()
+        .map(|registry| registry.get_detection_services())
+        .unwrap_or_default();
+    filter_project_services(services, project_id)
+}
+
+// 项目不存在时不过滤
+fn filter_project_services(services: Vec<serde_json::Value>, project_id: Option<u32>) -> Vec<serde_json::Value> {
+    let Some(project) = project_id.and_then(|id| get_project_storage(ProjectId::from(id)).ok().flatten()) else {
+        return services;
+    };
+    services.into_iter()
+        .filter(|service| service["id"].as_str().is_none_or(|id| project.default_services.is_enabled(id)))
+        .collect()
 }
 
 /// Request OCR processing for a marker
@@ -24,22 +49,110 @@ pub fn request_ocr(marker_id: MarkerId, image_id: ImageId, project_id: ProjectId
     service.request_ocr(marker_id, image_id, project_id, service_id)
 }
 
+/// 批量请求OCR，裁剪在core的线程池中并行完成；返回每个标记的任务ID或错误
+pub fn request_ocr_batch(marker_ids: Vec<MarkerId>, project_id: ProjectId, service_id: String) -> Result<Vec<OcrBatchItem>, String> {
+    let service = BunnyService::new();
+    service.request_ocr_batch(marker_ids, project_id, service_id)
+}
+
+/// 先OCR再用识别出的原文机翻，返回流水线ID；进度见bunny:pipeline_advanced/completed/failed
+pub fn request_pipeline(marker_id: MarkerId, image_id: ImageId, project_id: ProjectId, ocr_service: String, translation_service: String) -> Result<String, String> {
+    log_function_call("request_pipeline", Some(serde_json::json!({
+        "marker_id": marker_id.0,
+        "ocr_service": &ocr_service,
+        "translation_service": &translation_service
+    })));
+    BunnyService::new().request_pipeline(marker_id, image_id, project_id, ocr_service, translation_service)
+}
+
+/// 为图片上的所有标记创建OCR→翻译流水线，返回每个标记的流水线ID或错误
+pub fn request_pipeline_batch(image_id: ImageId, ocr_service: String, translation_service: String) -> Result<Vec<PipelineBatchItem>, String> {
+    log_function_call("request_pipeline_batch", Some(serde_json::json!({
+        "image_id": image_id.0,
+        "ocr_service": &ocr_service,
+        "translation_service": &translation_service
+    })));
+    BunnyService::new().request_pipeline_batch(image_id, ocr_service, translation_service)
+}
+
+/// 流水线当前的阶段和状态，UI据此显示两段进度
+pub fn get_pipeline(pipeline_id: String) -> Result<Option<BunnyPipeline>, String> {
+    TASK_MANAGER.get_pipeline(&pipeline_id)
+}
+
+/// Get all OCR→translation pipelines of a project, oldest first
+pub fn get_project_pipelines(project_id: ProjectId) -> Result<Vec<BunnyPipeline>, String> {
+    TASK_MANAGER.get_project_pipelines(project_id)
+}
+
 /// Request translation processing for a marker
 pub fn request_translation(marker_id: MarkerId, image_id: ImageId, project_id: ProjectId, service_id: String, text: String) -> Result<String, String> {
     let service = BunnyService::new();
     service.request_translation(marker_id, image_id, project_id, service_id, text)
 }
 
+/// 翻译项目中所有有原文但没有译文的标记，返回排队的任务ID；传入operation_id时可通过cancel_operation停止排队
+pub fn translate_all_untranslated(project_id: ProjectId, service_id: String, operation_id: Option<String>) -> Result<Vec<String>, String> {
+    log_function_call("translate_all_untranslated", Some(serde_json::json!({
+        "project_id": project_id.0,
+        "service_id": &service_id,
+        "operation_id": &operation_id
+    })));
+    let operation = register_operation(operation_id);
+    BunnyService::new().translate_all_untranslated(project_id, service_id, operation.token())
+}
+
+/// Translate text synchronously, waiting for the plugin result (for CLI/scripting)
+pub fn translate_sync(marker_id: MarkerId, service_id: String, text: String) -> Result<String, String> {
+    let service = BunnyService::new();
+    service.translate_sync(marker_id, service_id, text)
+}
+
 /// Handle OCR completion from plugin (called by frontend relay)
-pub fn handle_ocr_completed(task_id: String, marker_id: MarkerId, text: String, model: String) -> Result<(), String> {
+/// regions为插件返回的文本行位置（不支持的插件不提供），随原文一起存入缓存
+pub fn handle_ocr_completed(task_id: String, marker_id: MarkerId, text: String, model: String, regions: Vec<OcrRegion>) -> Result<(), String> {
     let service = BunnyService::new();
-    service.handle_ocr_completed(task_id, marker_id, text, model)
+    service.handle_ocr_completed(task_id, marker_id, text, model, regions)
+}
+
+/// 按OCR文本行把标记拆成每行一个矩形标记（一次撤销），regions为None时使用最近一次OCR返回的区域
+pub fn split_marker_by_ocr_regions(marker_id: MarkerId, regions: Option<Vec<OcrRegion>>) -> Result<Vec<MarkerId>, String> {
+    log_function_call("split_marker_by_ocr_regions", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "regions": regions.as_ref().map(Vec::len)
+    })));
+    let regions = match regions {
+        Some(regions) => regions,
+        None => crate::storage::bunny_cache::get_bunny_cache_storage(marker_id)
+            .map_err(|e| e.to_string())?
+            .map(|cache| cache.ocr_regions)
+            .unwrap_or_default(),
+    };
+    BunnyService::new().split_marker_by_ocr_regions(marker_id, regions)
+}
+
+/// 请求检测插件找出图片中的文本框，返回任务ID；结果以bunny:detection_completed发出
+pub fn request_detection(image_id: ImageId, service_id: String) -> Result<String, String> {
+    log_function_call("request_detection", Some(serde_json::json!({"image_id": image_id, "service_id": service_id})));
+    BunnyService::new().request_detection(image_id, service_id)
+}
+
+/// Handle detection result from plugin (called by frontend relay)
+pub fn handle_detection_completed(task_id: String, model: String, regions: Vec<DetectedRegion>) -> Result<(), String> {
+    BunnyService::new().handle_detection_completed(task_id, model, regions)
+}
+
+/// 把检测区域（原图像素）生成矩形标记，整体为一次撤销
+pub fn create_markers_from_detection(image_id: ImageId, regions: Vec<DetectedRegion>) -> Result<Vec<MarkerId>, String> {
+    log_function_call("create_markers_from_detection", Some(serde_json::json!({"image_id": image_id, "regions": regions.len()})));
+    BunnyService::new().create_markers_from_detection(image_id, regions)
 }
 
 /// Handle translation completion from plugin (called by frontend relay)
-pub fn handle_translation_completed(task_id: String, marker_id: MarkerId, translated_text: String, service_id: String) -> Result<(), String> {
+/// warnings为插件校验译文时发现的术语问题，metadata为插件附带的模型、提示词哈希等信息，都随机翻一起存入缓存
+pub fn handle_translation_completed(task_id: String, marker_id: MarkerId, translated_text: String, service_id: String, warnings: Vec<TranslationWarning>, metadata: Option<serde_json::Value>) -> Result<(), String> {
     let service = BunnyService::new();
-    service.handle_translation_completed(task_id, marker_id, translated_text, service_id)
+    service.handle_translation_completed(task_id, marker_id, translated_text, service_id, warnings, metadata)
 }
 
 /// Handle task failure from plugin (called by frontend relay)
@@ -48,6 +161,31 @@ pub fn handle_task_failed(task_id: String, error: String) -> Result<(), String>
     service.handle_task_failed(task_id, error)
 }
 
+/// Get recently finished OCR/translation tasks of a project, newest first
+pub fn get_task_history(project_id: ProjectId, limit: Option<usize>) -> Result<Vec<TaskHistoryEntry>, String> {
+    TASK_MANAGER.get_task_history(project_id, limit)
+}
+
+/// Get per-service success/failure counts and durations from the task history of a project
+pub fn get_task_metrics(project_id: ProjectId) -> Result<Vec<ServiceTaskMetrics>, String> {
+    TASK_MANAGER.get_task_metrics(project_id)
+}
+
+/// Get the queued/running/done/failed task counts of an image
+pub fn get_image_task_progress(image_id: ImageId) -> ImageTaskProgress {
+    TASK_MANAGER.get_image_task_progress(image_id)
+}
+
+/// Flush throttled bunny:image_progress events; called periodically by the host
+pub fn tick_bunny_progress() {
+    TASK_MANAGER.tick();
+}
+
+/// Clear the task history of a project
+pub fn clear_task_history(project_id: ProjectId) -> Result<(), String> {
+    TASK_MANAGER.clear_task_history(project_id)
+}
+
 /// Get bunny cache data for a marker
 pub fn get_bunny_cache(marker_id: MarkerId) -> Result<Option<crate::storage::bunny_cache::BunnyCacheData>, String> {
     crate::storage::bunny_cache::get_bunny_cache_storage(marker_id)
@@ -57,15 +195,41 @@ pub fn get_bunny_cache(marker_id: MarkerId) -> Result<Option<crate::storage::bun
 /// Update original text in bunny cache
 pub fn update_original_text(marker_id: MarkerId, text: String, model: String) -> Result<(), String> {
     crate::storage::bunny_cache::update_original_text_storage(marker_id, text, model)
-        .map_err(|e| format!("Failed to update original text: {:?}", e))
+        .map_err(|e| format!("Failed to update original text: {:?}", e))?;
+    crate::service::get_service().stats_service.notify_marker_text_changed(marker_id);
+    Ok(())
 }
 
 /// Update machine translation in bunny cache
 pub fn update_machine_translation(marker_id: MarkerId, text: String, service: String) -> Result<(), String> {
-    crate::storage::bunny_cache::update_machine_translation_storage(marker_id, text, service)
+    // 手动修改后的机翻不再带有插件的校验结果和元数据
+    crate::storage::bunny_cache::update_machine_translation_storage(marker_id, text, service, Vec::new(), None)
         .map_err(|e| format!("Failed to update machine translation: {:?}", e))
 }
 
+/// 获取标记的机器翻译、插件校验时发现的术语问题和插件附带的元数据，没有机翻时返回None
+pub fn get_translation_result(marker_id: MarkerId) -> Result<Option<TranslationResult>, String> {
+    let service = BunnyService::new();
+    service.get_translation_result(marker_id)
+}
+
+/// 把缓存的机器翻译写入标记译文（可撤销），译文在机翻之后被修改过时拒绝
+pub fn accept_machine_translation(marker_id: MarkerId) -> Result<bool, String> {
+    log_function_call("accept_machine_translation", Some(serde_json::json!({"marker_id": marker_id})));
+    BunnyService::new().accept_machine_translation(marker_id)
+}
+
+/// 接受图片上所有标记的机器翻译（一次撤销），only_empty时只写入还没有译文的标记
+pub fn accept_all_for_image(image_id: ImageId, only_empty: bool) -> Result<AcceptMachineTranslationReport, String> {
+    log_function_call("accept_all_for_image", Some(serde_json::json!({"image_id": image_id, "only_empty": only_empty})));
+    BunnyService::new().accept_all_for_image(image_id, only_empty)
+}
+
+/// 丢弃标记缓存的机器翻译，原文保留
+pub fn reject_machine_translation(marker_id: MarkerId) -> Result<bool, String> {
+    BunnyService::new().reject_machine_translation(marker_id)
+}
+
 /// Clear bunny cache for a marker
 pub fn clear_bunny_cache(marker_id: MarkerId) -> Result<(), String> {
     crate::storage::bunny_cache::clear_bunny_cache_storage(marker_id)
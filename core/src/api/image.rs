@@ -1,8 +1,10 @@
 use std::path::PathBuf;
-use crate::common::{log_function_call, ProjectId, ImageId};
-use crate::common::dto::image::{ImageDTO, ImageDataDTO, ImageFormat};
+use crate::common::{log_function_call, register_operation, sort_natural_by_key, ProjectId, ImageId, MarkerId, ThumbnailOrder};
+use crate::common::dto::image::{ImageDTO, ImageDataDTO, ImageFormat, ImageMetadataDTO, ImageSourceKind, ImageViewState};
 use crate::common::dto::marker::MarkerDTO;
 use crate::service::{get_service, events::DomainEvent};
+use crate::service::image::paste::{pasted_image_name, validate_pasted_image, PASTED_NAME_PREFIX};
+use crate::service::image::{ThumbnailCacheStats, ImageByteCacheStats, ImageCandidate, ImageLease, MarkerThumbnail, validate_image_candidate, validate_image_content, DownscaleSettings};
 use crate::service::undo_redo::{ActionType, UndoRedoAction};
 use crate::storage::image::get_image_storage;
 use crate::storage::project::get_project_storage;
@@ -51,7 +53,10 @@ pub fn add_image_from_path_to_project(project_id: u32, path: PathBuf) -> Option<
     let result = if service.opening_project_service.is_opening_project(project_id) {
         // 临时项目路径
         if let Some(image_id) = service.image_service.add_image_from_path(path.clone(), name.clone()) {
-            service.opening_project_service.add_image_to_opening_project(project_id, image_id, name.clone(), Some(path.clone()));
+            if !service.opening_project_service.add_image_to_opening_project(project_id, image_id, name.clone(), Some(path.clone())) {
+                // 打开项目已被取消，图片已删除
+                return None;
+            }
             service.event_bus.publish(DomainEvent::ImageAddedToProject(ProjectId::from(project_id), ImageId::from(image_id)));
             
             // Record undo/redo action
@@ -147,7 +152,10 @@ pub fn add_image_from_binary_to_project(project_id: u32, format: ImageFormat, da
     if service.opening_project_service.is_opening_project(project_id) {
         // 临时项目路径
         if let Some(image_id) = service.image_service.add_image_from_binary(format, data, name.clone()) {
-            service.opening_project_service.add_image_to_opening_project(project_id, image_id, name, None);
+            if !service.opening_project_service.add_image_to_opening_project(project_id, image_id, name, None) {
+                // 打开项目已被取消，图片已删除
+                return None;
+            }
             service.event_bus.publish(DomainEvent::ImageAddedToProject(ProjectId::from(project_id), ImageId::from(image_id)));
             
             // Record undo/redo action
@@ -204,6 +212,47 @@ pub fn add_image_from_binary_to_project(project_id: u32, format: ImageFormat, da
     }
 }
 
+/// 把剪贴板中的截图作为新页面追加到项目末尾（可撤销），返回新图片ID
+/// 只接受PNG/JPEG，名称自动生成且不与项目中已有图片重名，失败时错误为ImportError的JSON
+pub fn add_image_from_clipboard_bytes(project_id: u32, bytes: Vec<u8>, suggested_name: Option<String>) -> Result<u32, String> {
+    log_function_call("add_image_from_clipboard_bytes", Some(serde_json::json!({
+        "project_id": project_id,
+        "data_size": bytes.len(),
+        "suggested_name": suggested_name
+    })));
+
+    let format = validate_pasted_image(&bytes).map_err(|e| e.to_json_string())?;
+    add_named_image(project_id, format, bytes, suggested_name)
+}
+
+/// 把内存中的图片数据（粘贴、拖入）追加为新页面（可撤销），返回新图片ID
+/// 格式按文件头识别，扩展名以实际格式为准；与已有图片重名时自动加序号，失败时错误为ImportError的JSON
+pub fn add_image_from_bytes(project_id: u32, bytes: Vec<u8>, name: Option<String>) -> Result<u32, String> {
+    log_function_call("add_image_from_bytes", Some(serde_json::json!({
+        "project_id": project_id,
+        "data_size": bytes.len(),
+        "name": name
+    })));
+
+    let display_name = name.as_deref().unwrap_or(PASTED_NAME_PREFIX);
+    let format = validate_image_content(display_name, &bytes).map_err(|e| e.to_json_string())?;
+    add_named_image(project_id, format, bytes, name)
+}
+
+fn add_named_image(project_id: u32, format: ImageFormat, bytes: Vec<u8>, suggested_name: Option<String>) -> Result<u32, String> {
+    let project = get_project_storage(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Project with id {} not found", project_id))?;
+    let existing: Vec<String> = project.image_ids.iter()
+        .filter_map(|&image_id| get_image_storage(image_id).ok().flatten())
+        .filter_map(|image| image.metadata.name.clone())
+        .collect();
+    let name = pasted_image_name(&existing, suggested_name.as_deref(), format);
+
+    add_image_from_binary_to_project(project_id, format, bytes, Some(name))
+        .ok_or_else(|| "Failed to add image to project".to_string())
+}
+
 /// 获取图片信息
 pub fn get_image_info(image_id: u32) -> Option<ImageDTO> {
     log_function_call("get_image_info", Some(serde_json::json!({"image_id": image_id})));
@@ -211,6 +260,53 @@ pub fn get_image_info(image_id: u32) -> Option<ImageDTO> {
     service.image_service.get_image(image_id)
 }
 
+/// 获取图片元数据（供页面信息面板显示DPI、颜色模式、文件大小等）
+pub fn get_image_metadata(image_id: u32) -> Option<ImageMetadataDTO> {
+    log_function_call("get_image_metadata", Some(serde_json::json!({"image_id": image_id})));
+    let service = get_service();
+    service.image_service.get_image_metadata(image_id)
+}
+
+/// 清空桌面端缩略图磁盘缓存，返回删除的缓存文件数（WASM端没有磁盘缓存，返回0）
+pub fn clear_thumbnail_cache() -> Result<usize, String> {
+    log_function_call("clear_thumbnail_cache", None);
+    crate::service::image::thumbnail_cache::clear()
+        .map_err(|e| e.to_string())
+}
+
+/// 获取缩略图缓存统计（命中/未命中次数、占用大小）
+pub fn get_thumbnail_cache_stats() -> ThumbnailCacheStats {
+    log_function_call("get_thumbnail_cache_stats", None);
+    crate::service::image::thumbnail_cache::stats()
+}
+
+/// 设置原图字节缓存容量（MB），0表示关闭缓存
+pub fn set_image_cache_limit(limit_mb: u32) -> Result<(), String> {
+    log_function_call("set_image_cache_limit", Some(serde_json::json!({"limit_mb": limit_mb})));
+    crate::service::image::byte_cache::set_limit_mb(limit_mb as u64)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取原图字节缓存统计（命中/未命中次数、占用大小）
+pub fn get_image_cache_stats() -> ImageByteCacheStats {
+    log_function_call("get_image_cache_stats", None);
+    crate::service::image::byte_cache::stats()
+}
+
+/// 获取标记的预览小图（JPEG，长边不超过max_edge_px），供翻译侧栏显示
+pub fn get_marker_thumbnail(marker_id: u32, max_edge_px: u32) -> Result<Vec<u8>, String> {
+    log_function_call("get_marker_thumbnail", Some(serde_json::json!({"marker_id": marker_id, "max_edge_px": max_edge_px})));
+    crate::service::image::marker_thumbnail::get_marker_thumbnail(MarkerId::from(marker_id), max_edge_px)
+        .map_err(|e| e.to_string())
+}
+
+/// 批量获取图片上所有标记的预览小图，整页只解码一次
+pub fn get_marker_thumbnails(image_id: u32, max_edge_px: u32) -> Result<Vec<MarkerThumbnail>, String> {
+    log_function_call("get_marker_thumbnails", Some(serde_json::json!({"image_id": image_id, "max_edge_px": max_edge_px})));
+    crate::service::image::marker_thumbnail::get_marker_thumbnails(ImageId::from(image_id), max_edge_px)
+        .map_err(|e| e.to_string())
+}
+
 /// 获取图片二进制数据
 pub fn get_image_binary_data(image_id: u32) -> Result<Vec<u8>, String> {
     log_function_call("get_image_binary_data", Some(serde_json::json!({"image_id": image_id})));
@@ -218,6 +314,33 @@ pub fn get_image_binary_data(image_id: u32) -> Result<Vec<u8>, String> {
     service.image_service.get_image_binary_data(image_id)
 }
 
+/// 把图片重新编码为指定格式（导出页面图片用）：JPEG的quality为质量1-100，PNG为压缩级别0-9
+pub fn encode_image(image_id: u32, format: ImageFormat, quality: Option<u8>) -> Result<Vec<u8>, String> {
+    log_function_call("encode_image", Some(serde_json::json!({"image_id": image_id, "format": format, "quality": quality})));
+    let service = get_service();
+    service.image_service.encode_image(image_id, format, quality)
+}
+
+/// 获取图片原始字节的共享引用，同一图片多次调用不重复读盘或复制（供插件宿主使用）
+pub fn get_image_bytes_shared(image_id: u32) -> Result<std::sync::Arc<Vec<u8>>, String> {
+    log_function_call("get_image_bytes_shared", Some(serde_json::json!({"image_id": image_id})));
+    crate::service::image::image_lease::shared_image_bytes(ImageId::from(image_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 租借图片字节：返回指针和长度，native宿主可不复制地交给插件，用完须release_image_lease
+pub fn lease_image_data(image_id: u32) -> Result<ImageLease, String> {
+    log_function_call("lease_image_data", Some(serde_json::json!({"image_id": image_id})));
+    crate::service::image::image_lease::lease_image_bytes(ImageId::from(image_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 归还租借的图片字节
+pub fn release_image_lease(lease_id: u64) -> bool {
+    log_function_call("release_image_lease", Some(serde_json::json!({"lease_id": lease_id})));
+    crate::service::image::image_lease::release_image_lease(lease_id)
+}
+
 /// 获取图片文件路径（用于高效访问）
 pub fn get_image_file_path(image_id: u32) -> Option<String> {
     log_function_call("get_image_file_path", Some(serde_json::json!({"image_id": image_id})));
@@ -225,6 +348,14 @@ pub fn get_image_file_path(image_id: u32) -> Option<String> {
     service.image_service.get_image_file_path(image_id)
 }
 
+/// 获取图片数据的存储方式（FilePath/Binary/SharedBuffer）
+/// 非FilePath的图片没有磁盘路径，需要通过get_image_binary_data获取字节
+pub fn get_image_source_kind(image_id: u32) -> Option<ImageSourceKind> {
+    log_function_call("get_image_source_kind", Some(serde_json::json!({"image_id": image_id})));
+    let service = get_service();
+    service.image_service.get_image_source_kind(image_id)
+}
+
 /// 获取图片MIME类型
 pub fn get_image_mime_type(image_id: u32) -> Option<String> {
     log_function_call("get_image_mime_type", Some(serde_json::json!({"image_id": image_id})));
@@ -260,6 +391,18 @@ pub fn remove_image_from_project(project_id: u32, image_id: u32) -> bool {
         "image_id": image_id
     })));
     
+    let service = get_service();
+    let Some(action_type) = detach_image_from_project(project_id, image_id) else {
+        return false;
+    };
+    
+    let action = UndoRedoAction::new(action_type, ProjectId::from(project_id));
+    let _ = service.undo_redo_service.record_action(action);
+    true
+}
+
+/// 从项目中移除并删除图片，返回用于撤销的RemoveImage操作（由调用方记录）
+pub(crate) fn detach_image_from_project(project_id: u32, image_id: u32) -> Option<ActionType> {
     let service = get_service();
     
     // Get image and its position before deletion for undo/redo
@@ -279,27 +422,20 @@ pub fn remove_image_from_project(project_id: u32, image_id: u32) -> bool {
     let removed_from_project = service.project_service.remove_image_from_project(project_id, image_id);
     let removed_image = service.image_service.remove_image(image_id);
     
-    if removed_from_project && removed_image {
-        service.event_bus.publish(DomainEvent::ImageRemovedFromProject(
-            ProjectId::from(project_id),
-            ImageId::from(image_id)
-        ));
-        
-        // Record undo/redo action with the image and markers
-        if let Some(img) = image {
-            let action = UndoRedoAction::new(
-                ActionType::RemoveImage {
-                    image: (*img).clone(),
-                    position,
-                    markers, // Must save markers since we cleared them from storage
-                },
-                ProjectId::from(project_id),
-            );
-            let _ = service.undo_redo_service.record_action(action);
-        }
+    if !(removed_from_project && removed_image) {
+        return None;
     }
+    service.event_bus.publish(DomainEvent::ImageRemovedFromProject(
+        ProjectId::from(project_id),
+        ImageId::from(image_id)
+    ));
     
-    removed_from_project && removed_image
+    // Must save markers since we cleared them from storage
+    image.map(|img| ActionType::RemoveImage {
+        image: (*img).clone(),
+        position,
+        markers,
+    })
 }
 
 /// 重新排序项目中的图片
@@ -335,6 +471,179 @@ pub fn reorder_project_images(project_id: u32, image_ids: Vec<u32>) -> bool {
     result
 }
 
+/// 按图片名称自然排序项目中的图片（1, 2, 10而不是1, 10, 2），作为一次可撤销的重排操作
+/// 传入operation_id时可以通过cancel_operation取消，取消时不修改顺序并返回false
+pub fn reorder_images_natural(project_id: u32, operation_id: Option<String>) -> bool {
+    log_function_call("reorder_images_natural", Some(serde_json::json!({
+        "project_id": project_id,
+        "operation_id": operation_id
+    })));
+    let operation = register_operation(operation_id);
+    
+    let Ok(Some(project)) = get_project_storage(ProjectId::from(project_id)) else {
+        return false;
+    };
+    
+    let mut named: Vec<(String, u32)> = project.image_ids.iter()
+        .map(|&image_id| {
+            let name = get_image_storage(image_id).ok().flatten()
+                .and_then(|image| image.metadata.name.clone().or_else(|| match &image.data {
+                    crate::storage::ImageData::FilePath(path) => path.file_name()
+                        .map(|name| name.to_string_lossy().to_string()),
+                    _ => None,
+                }))
+                .unwrap_or_default();
+            (name, image_id.0)
+        })
+        .collect();
+    sort_natural_by_key(&mut named, |(name, _)| name.as_str());
+    if operation.token().is_cancelled() {
+        return false;
+    }
+    
+    let new_order: Vec<u32> = named.into_iter().map(|(_, id)| id).collect();
+    if new_order.iter().zip(&project.image_ids).all(|(&new, old)| new == old.0) {
+        return true;
+    }
+    reorder_project_images(project_id, new_order)
+}
+
+/// 导入前校验图片文件（扩展名、文件头、能否解码、大小），失败时错误为ImportError的JSON
+#[cfg(not(feature = "wasm"))]
+pub fn validate_image_file(path: &str) -> Result<ImageFormat, String> {
+    log_function_call("validate_image_file", Some(serde_json::json!({"path": path})));
+    validate_image_candidate(ImageCandidate::Path(std::path::Path::new(path)))
+        .map_err(|e| e.to_json_string())
+}
+
+/// 导入前校验图片数据（Web端），失败时错误为ImportError的JSON
+pub fn validate_image_data(name: &str, data: &[u8]) -> Result<ImageFormat, String> {
+    log_function_call("validate_image_data", Some(serde_json::json!({"name": name, "data_size": data.len()})));
+    validate_image_candidate(ImageCandidate::Bytes { name, data })
+        .map_err(|e| e.to_json_string())
+}
+
+/// 图片文件在磁盘上变化后重新读取（桌面端文件监听调用），返回是否确实刷新了图片
+pub fn invalidate_image(image_id: u32) -> Result<bool, String> {
+    log_function_call("invalidate_image", Some(serde_json::json!({"image_id": image_id})));
+    let service = get_service();
+    service.image_service.invalidate_image(ImageId::from(image_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 固定/取消固定图片到缩略图栏顶部
+pub fn set_image_pinned(image_id: u32, pinned: bool) -> bool {
+    log_function_call("set_image_pinned", Some(serde_json::json!({
+        "image_id": image_id,
+        "pinned": pinned
+    })));
+    let service = get_service();
+    service.image_service.set_image_pinned(image_id, pinned)
+}
+
+/// 标记图片已校对/未校对，可附带校对备注
+pub fn set_image_reviewed(image_id: u32, reviewed: bool, note: Option<String>) -> bool {
+    log_function_call("set_image_reviewed", Some(serde_json::json!({
+        "image_id": image_id,
+        "reviewed": reviewed,
+        "note": note
+    })));
+    let service = get_service();
+    service.image_service.set_image_reviewed(image_id, reviewed, note)
+}
+
+/// 保存图片的查看状态（缩放、平移、最后选中的标记），None清除
+pub fn set_image_view_state(image_id: u32, view_state: Option<ImageViewState>) -> Result<bool, String> {
+    log_function_call("set_image_view_state", Some(serde_json::json!({
+        "image_id": image_id,
+        "view_state": view_state
+    })));
+    let service = get_service();
+    service.image_service.set_view_state(ImageId::from(image_id), view_state).map_err(|e| e.to_string())
+}
+
+/// 获取图片保存的查看状态，没有时返回None
+pub fn get_image_view_state(image_id: u32) -> Result<Option<ImageViewState>, String> {
+    let service = get_service();
+    service.image_service.get_view_state(ImageId::from(image_id)).map_err(|e| e.to_string())
+}
+
+/// 切换到图片时调用，有保存的查看状态时发送image:view_restored并返回
+pub fn select_image(image_id: u32) -> Result<Option<ImageViewState>, String> {
+    log_function_call("select_image", Some(serde_json::json!({
+        "image_id": image_id
+    })));
+    let service = get_service();
+    service.image_service.select_image(ImageId::from(image_id)).map_err(|e| e.to_string())
+}
+
+/// 锁定/解锁图片（带撤销功能），锁定后不能新建、修改、删除或移动图片上的标记
+/// 图片不在任何项目中时返回false，状态不变时不记录撤销
+pub fn set_image_locked(image_id: u32, locked: bool) -> Result<bool, String> {
+    log_function_call("set_image_locked", Some(serde_json::json!({
+        "image_id": image_id,
+        "locked": locked
+    })));
+    let service = get_service();
+    let image_id = ImageId::from(image_id);
+    let Some(old) = service.image_service.set_image_locked(image_id, locked).map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+    if old != locked
+        && let Ok(Some(project_id)) = crate::storage::project::find_project_by_image_storage(image_id)
+    {
+        let action = UndoRedoAction::new(ActionType::SetImageLocked { id: image_id, locked }, project_id);
+        let _ = service.undo_redo_service.record_action(action);
+    }
+    Ok(true)
+}
+
+/// 重命名图片（带撤销功能），名称不能为空，也不能与项目中的其它图片重名
+/// 图片不在任何项目中时返回false，名称不变时不记录撤销
+pub fn rename_image(image_id: u32, new_name: String) -> Result<bool, String> {
+    log_function_call("rename_image", Some(serde_json::json!({
+        "image_id": image_id,
+        "new_name": new_name
+    })));
+    let service = get_service();
+    let image_id = ImageId::from(image_id);
+    let new_name = Some(new_name.trim().to_string());
+    let Some(old_name) = service.image_service.rename_image(image_id, new_name.clone()).map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+    if old_name != new_name
+        && let Ok(Some(project_id)) = crate::storage::project::find_project_by_image_storage(image_id)
+    {
+        let action = UndoRedoAction::new(ActionType::UpdateImage { id: image_id, old_name, new_name }, project_id);
+        let _ = service.undo_redo_service.record_action(action);
+    }
+    Ok(true)
+}
+
+/// 设置项目缩略图栏的排序方式
+pub fn set_thumbnail_sort(project_id: u32, order: ThumbnailOrder) -> bool {
+    log_function_call("set_thumbnail_sort", Some(serde_json::json!({
+        "project_id": project_id,
+        "order": order
+    })));
+    let service = get_service();
+    service.image_service.set_thumbnail_sort(project_id, order)
+}
+
+/// 获取导入时缩小超大图片的设置
+pub fn get_downscale_settings() -> Result<DownscaleSettings, String> {
+    log_function_call("get_downscale_settings", None);
+    crate::service::image::get_downscale_settings()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置导入时缩小超大图片（默认关闭），只影响之后导入的图片
+pub fn set_downscale_settings(settings: DownscaleSettings) -> Result<(), String> {
+    log_function_call("set_downscale_settings", Some(serde_json::json!({"settings": settings})));
+    crate::service::image::set_downscale_settings(settings)
+        .map_err(|e| e.to_string())
+}
+
 /// 获取图片的所有标记
 pub fn get_image_markers(image_id: u32) -> Vec<MarkerDTO> {
     log_function_call("get_image_markers", Some(serde_json::json!({"image_id": image_id})));
@@ -354,4 +663,167 @@ pub fn get_image_marker_ids(image_id: u32) -> Result<Vec<u32>, String> {
     let service = get_service();
     service.image_service.get_image_marker_ids(image_id)
         .map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::image::{
+        add_image_from_path_storage, add_image_from_binary_storage, add_image_from_shared_buffer_storage,
+    };
+    use crate::storage::ImageFormat as StorageImageFormat;
+
+    #[test]
+    fn locked_image_rejects_marker_edits_until_undone() {
+        use crate::api::marker::{add_point_marker_to_image, get_marker_info, replace_in_translations, update_marker_translation};
+        let project_id = crate::storage::project::create_project_storage("lock_test".to_string()).unwrap();
+        let mut image_ids = Vec::new();
+        for _ in 0..2 {
+            let image_id = add_image_from_binary_storage(StorageImageFormat::Png, vec![0u8; 16], None).unwrap();
+            crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+            image_ids.push(image_id.0);
+        }
+        let locked_marker = add_point_marker_to_image(image_ids[0], 10.0, 10.0, Some("旧".to_string()), None).unwrap().unwrap();
+        let open_marker = add_point_marker_to_image(image_ids[1], 10.0, 10.0, Some("旧".to_string()), None).unwrap().unwrap();
+
+        assert_eq!(set_image_locked(image_ids[0], true), Ok(true));
+        let error = update_marker_translation(locked_marker, "新".to_string()).unwrap_err();
+        let error: serde_json::Value = serde_json::from_str(&error).unwrap();
+        assert_eq!(error["kind"], "image_locked");
+        assert_eq!(error["image_id"], image_ids[0]);
+        assert!(add_point_marker_to_image(image_ids[0], 20.0, 20.0, None, None).is_err());
+        // 读取不受影响
+        assert_eq!(get_marker_info(locked_marker).unwrap().translation, "旧");
+        assert!(get_image_info(image_ids[0]).unwrap().metadata.locked);
+
+        // 批量替换只改未锁定的图片
+        let report = replace_in_translations(project_id.0, "旧".to_string(), "新".to_string(), false, false, None).unwrap();
+        assert_eq!(report.changed_marker_ids, vec![crate::common::MarkerId(open_marker)]);
+        assert_eq!(report.skipped_image_ids, vec![ImageId(image_ids[0])]);
+
+        // 撤销替换后再撤销锁定，图片恢复可编辑
+        crate::api::undo_redo::undo(project_id.0);
+        crate::api::undo_redo::undo(project_id.0);
+        assert!(!crate::service::image::is_image_locked(ImageId(image_ids[0])));
+        assert_eq!(update_marker_translation(locked_marker, "新".to_string()), Ok(true));
+    }
+
+    #[test]
+    fn duplicate_names_are_suffixed_and_rename_is_undoable() {
+        let service = get_service();
+        let project_id = crate::storage::project::create_project_storage("rename_test".to_string()).unwrap();
+        let mut image_ids = Vec::new();
+        for _ in 0..3 {
+            let image_id = add_image_from_binary_storage(StorageImageFormat::Png, vec![0u8; 16], Some("01.png".to_string())).unwrap();
+            assert!(service.project_service.add_image_to_project(project_id.0, image_id.0));
+            image_ids.push(image_id.0);
+        }
+        let names: Vec<String> = image_ids.iter().map(|id| get_image_info(*id).unwrap().metadata.name.unwrap()).collect();
+        assert_eq!(names, vec!["01.png", "01 (2).png", "01 (3).png"]);
+        assert_eq!(get_image_info(image_ids[1]).unwrap().metadata.original_name.as_deref(), Some("01.png"));
+        assert_eq!(get_image_info(image_ids[0]).unwrap().metadata.original_name, None);
+
+        // 重名和空名称被拒绝
+        assert!(rename_image(image_ids[2], "01.png".to_string()).unwrap_err().contains("01.png"));
+        assert!(rename_image(image_ids[2], "  ".to_string()).is_err());
+
+        assert_eq!(rename_image(image_ids[2], " 02.png ".to_string()), Ok(true));
+        assert_eq!(get_image_info(image_ids[2]).unwrap().metadata.name.as_deref(), Some("02.png"));
+        crate::api::undo_redo::undo(project_id.0);
+        assert_eq!(get_image_info(image_ids[2]).unwrap().metadata.name.as_deref(), Some("01 (3).png"));
+        crate::api::undo_redo::redo(project_id.0);
+        assert_eq!(get_image_info(image_ids[2]).unwrap().metadata.name.as_deref(), Some("02.png"));
+    }
+
+    #[test]
+    fn source_kind_for_file_path_image() {
+        let path = std::env::temp_dir().join("bubblefish_source_kind_test.png");
+        let id = add_image_from_path_storage(path.clone(), None).unwrap();
+
+        assert_eq!(get_image_source_kind(id.0), Some(ImageSourceKind::FilePath));
+        assert_eq!(get_image_file_path(id.0), Some(path.to_string_lossy().to_string()));
+    }
+
+    #[test]
+    fn source_kind_for_binary_image() {
+        let id = add_image_from_binary_storage(StorageImageFormat::Png, vec![0u8; 16], None).unwrap();
+
+        assert_eq!(get_image_source_kind(id.0), Some(ImageSourceKind::Binary));
+        assert_eq!(get_image_file_path(id.0), None);
+        assert_eq!(get_image_binary_data(id.0).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn source_kind_for_shared_buffer_image() {
+        let id = add_image_from_shared_buffer_storage(StorageImageFormat::Jpeg, 7, None).unwrap();
+
+        assert_eq!(get_image_source_kind(id.0), Some(ImageSourceKind::SharedBuffer));
+        assert_eq!(get_image_file_path(id.0), None);
+    }
+
+    #[test]
+    fn source_kind_for_missing_image() {
+        assert_eq!(get_image_source_kind(u32::MAX), None);
+    }
+
+    #[test]
+    fn reorder_images_natural_is_undoable() {
+        let project_id = crate::storage::project::create_project_storage("natural".to_string()).unwrap();
+        let mut ids = Vec::new();
+        for name in ["10.jpg", "2.jpg", "1.jpg"] {
+            let id = add_image_from_binary_storage(StorageImageFormat::Jpeg, vec![0u8; 16], Some(name.to_string())).unwrap();
+            crate::storage::project::add_image_to_project_storage(project_id, id).unwrap();
+            ids.push(id);
+        }
+        let service = get_service();
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        assert!(reorder_images_natural(project_id.0, None));
+        let order = get_project_storage(project_id).unwrap().unwrap().image_ids.clone();
+        assert_eq!(order, vec![ids[2], ids[1], ids[0]]);
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        let order = get_project_storage(project_id).unwrap().unwrap().image_ids.clone();
+        assert_eq!(order, ids);
+    }
+
+    #[test]
+    fn add_image_from_bytes_detects_format_and_renames() {
+        let project_id = crate::storage::project::create_project_storage("bytes".to_string()).unwrap();
+        let mut gif = Vec::new();
+        image::RgbaImage::new(2, 2).write_to(&mut std::io::Cursor::new(&mut gif), image::ImageFormat::Gif).unwrap();
+
+        // 扩展名以文件头为准，重名时加序号
+        let first = add_image_from_bytes(project_id.0, gif.clone(), Some("drop.jpg".to_string())).unwrap();
+        let second = add_image_from_bytes(project_id.0, gif, Some("drop.gif".to_string())).unwrap();
+        let name = |id: u32| get_image_storage(ImageId::from(id)).unwrap().unwrap().metadata.name.clone();
+        assert_eq!(name(first).as_deref(), Some("drop.gif"));
+        assert_eq!(name(second).as_deref(), Some("drop-001.gif"));
+
+        let error = add_image_from_bytes(project_id.0, b"plain text".to_vec(), None).unwrap_err();
+        assert!(error.contains("not_an_image"));
+    }
+
+    #[test]
+    fn invalidate_image_rereads_replaced_file() {
+        let path = std::env::temp_dir().join(format!("bubblefish_invalidate_{}.png", std::process::id()));
+        let write_png = |width: u32, height: u32| {
+            image::RgbImage::new(width, height).save_with_format(&path, image::ImageFormat::Png).unwrap();
+        };
+        write_png(4, 3);
+        let id = add_image_from_path_storage(path.clone(), None).unwrap();
+        assert_eq!(get_image_storage(id).unwrap().unwrap().metadata.width, Some(4));
+
+        // 内容未变化时不刷新
+        assert_eq!(invalidate_image(id.0), Ok(false));
+
+        write_png(8, 6);
+        assert_eq!(invalidate_image(id.0), Ok(true));
+        let metadata = get_image_storage(id).unwrap().unwrap().metadata.clone();
+        assert_eq!((metadata.width, metadata.height), (Some(8), Some(6)));
+
+        // 原子替换过程中文件暂时不存在
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(invalidate_image(id.0), Ok(false));
+        assert!(invalidate_image(u32::MAX).is_err());
+    }
+}
@@ -1,14 +1,34 @@
-use crate::common::{log_function_call, ProjectId};
+use crate::common::{log_function_call, ImageId, ProjectId};
+use crate::common::dto::image::MissingImageDTO;
+#[cfg(not(feature = "wasm"))]
+use crate::common::dto::image::RelinkResultDTO;
+use crate::service::get_service;
 use crate::service::io::labelplus::{
     LabelplusData,
+    LabelplusMergeOptions,
+    LabelplusMergeReport,
     validate_labelplus_file as service_validate_labelplus_file,
-    import_labelplus_data_direct as service_import_labelplus_data_direct,
     export_labelplus_data as service_export_labelplus_data,
+    export_labelplus_data_range as service_export_labelplus_data_range,
 };
 use crate::service::io::bf::{
+    BfSummary,
     save_project as service_save_project,
+    save_project_range as service_save_project_range,
     save_project_to_path as service_save_project_to_path,
 };
+use crate::service::io::annotate::{
+    AnnotateOptions,
+    export_annotated_images as service_export_annotated_images,
+};
+use crate::service::io::typesetting::{
+    TypesettingFormat,
+    TypesettingOptions,
+    TypesettingImportOptions,
+    TypesettingImportReport,
+    export_typesetting_data as service_export_typesetting_data,
+    import_typesetting_data as service_import_typesetting_data,
+};
 use crate::storage::project::update_project_file_path_storage;
 
 pub fn validate_labelplus_file(content: &str) -> Result<LabelplusData, String> {
@@ -17,17 +37,64 @@ pub fn validate_labelplus_file(content: &str) -> Result<LabelplusData, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 打开前预检bf文件，失败时错误为BfFormatError的JSON
+pub fn validate_bf_data(data: &[u8]) -> Result<BfSummary, String> {
+    log_function_call("validate_bf_data", Some(serde_json::json!({"data_size": data.len()})));
+    get_service().io_service.validate_bf(data)
+        .map_err(|e| e.to_json_string())
+}
+
+/// 打开前预检磁盘上的bf文件（桌面端），失败时错误为BfFormatError的JSON
+#[cfg(not(feature = "wasm"))]
+pub fn validate_bf_file(path: &str) -> Result<BfSummary, String> {
+    log_function_call("validate_bf_file", Some(serde_json::json!({"path": path})));
+    let data = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    get_service().io_service.validate_bf(&data)
+        .map_err(|e| e.to_json_string())
+}
+
+/// 导出恢复快照：不含图片数据的bf内容，供前端定期保存
+pub fn export_recovery_snapshot(project_id: u32) -> Result<Vec<u8>, String> {
+    log_function_call("export_recovery_snapshot", Some(serde_json::json!({"project_id": project_id})));
+    get_service().io_service.export_recovery_snapshot(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 从恢复快照重建项目，图片为丢失状态，返回新项目ID
+pub fn import_recovery_snapshot(data: Vec<u8>) -> Result<u32, String> {
+    log_function_call("import_recovery_snapshot", Some(serde_json::json!({"data_len": data.len()})));
+    get_service().io_service.import_recovery_snapshot(data)
+        .map(|project_id| project_id.0)
+        .map_err(|e| e.to_string())
+}
+
+/// 项目中文件已不存在的图片（项目文件夹被移动后）
+pub fn get_missing_images(project_id: u32) -> Vec<MissingImageDTO> {
+    log_function_call("get_missing_images", Some(serde_json::json!({"project_id": project_id})));
+    get_service().io_service.get_missing_images(ProjectId::from(project_id))
+        .unwrap_or_default()
+}
+
+/// 把丢失的图片重新链接到新的文件路径（桌面端）
+#[cfg(not(feature = "wasm"))]
+pub fn relink_image(image_id: u32, path: &str) -> Result<RelinkResultDTO, String> {
+    log_function_call("relink_image", Some(serde_json::json!({"image_id": image_id, "path": path})));
+    get_service().io_service.relink_image(ImageId::from(image_id), std::path::Path::new(path))
+        .map_err(|e| e.to_string())
+}
+
 pub fn import_labelplus_data(project_id: u32, content: &str) -> Result<(), String> {
     log_function_call("import_labelplus_data", Some(serde_json::json!({"project_id": project_id, "content_len": content.len()})));
-    match service_validate_labelplus_file(content) {
-        Ok(data) => {
-            service_import_labelplus_data_direct(
-                ProjectId::from(project_id),
-                data
-            ).map_err(|e| e.to_string())
-        }
-        Err(e) => Err(e.to_string())
-    }
+    get_service().io_service.import_labelplus(ProjectId::from(project_id), content)
+        .map_err(|e| e.to_string())
+}
+
+/// 合并导入LabelPlus文件，不覆盖已有标记，返回新增和跳过的标记
+pub fn import_labelplus_merge(project_id: u32, content: &str, options: LabelplusMergeOptions) -> Result<LabelplusMergeReport, String> {
+    log_function_call("import_labelplus_merge", Some(serde_json::json!({"project_id": project_id, "content_len": content.len(), "options": options})));
+    get_service().io_service.import_labelplus_merge(ProjectId::from(project_id), content, &options)
+        .map_err(|e| e.to_string())
 }
 
 pub fn export_labelplus_data(project_id: u32) -> Result<String, String> {
@@ -36,12 +103,28 @@ pub fn export_labelplus_data(project_id: u32) -> Result<String, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 只导出指定图片的标记（如一个章节），按项目中的顺序，类型编号按这些图片用到的样式分配
+pub fn export_labelplus_data_range(project_id: u32, image_ids: Vec<u32>) -> Result<String, String> {
+    log_function_call("export_labelplus_data_range", Some(serde_json::json!({"project_id": project_id, "image_ids": image_ids})));
+    let image_ids: Vec<ImageId> = image_ids.into_iter().map(ImageId::from).collect();
+    service_export_labelplus_data_range(ProjectId::from(project_id), &image_ids)
+        .map_err(|e| e.to_string())
+}
+
 pub fn save_project(project_id: u32) -> Result<Vec<u8>, String> {
     log_function_call("save_project", Some(serde_json::json!({"project_id": project_id})));
     service_save_project(ProjectId::from(project_id))
         .map_err(|e| e.to_string())
 }
 
+/// 只把指定图片导出为bf数据，不修改项目的保存路径
+pub fn save_project_range(project_id: u32, image_ids: Vec<u32>) -> Result<Vec<u8>, String> {
+    log_function_call("save_project_range", Some(serde_json::json!({"project_id": project_id, "image_ids": image_ids})));
+    let image_ids: Vec<ImageId> = image_ids.into_iter().map(ImageId::from).collect();
+    service_save_project_range(ProjectId::from(project_id), &image_ids)
+        .map_err(|e| e.to_string())
+}
+
 pub fn save_project_to_path(project_id: u32, path: &str) -> Result<Vec<u8>, String> {
     log_function_call("save_project_to_path", Some(serde_json::json!({"project_id": project_id, "path": path})));
     service_save_project_to_path(ProjectId::from(project_id), path)
@@ -52,4 +135,78 @@ pub fn update_project_file_path(project_id: u32, file_path: Option<String>) -> R
     log_function_call("update_project_file_path", Some(serde_json::json!({"project_id": project_id, "file_path": file_path})));
     update_project_file_path_storage(ProjectId::from(project_id), file_path)
         .map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+/// 导出PS嵌字脚本使用的CSV/JSON（像素坐标）
+pub fn export_typesetting_data(project_id: u32, format: TypesettingFormat, options: TypesettingOptions) -> Result<String, String> {
+    log_function_call("export_typesetting_data", Some(serde_json::json!({"project_id": project_id, "format": format, "options": options})));
+    service_export_typesetting_data(ProjectId::from(project_id), format, &options)
+        .map_err(|e| e.to_string())
+}
+
+/// 项目内容哈希（标记、样式、译文和图片顺序），不含内部ID，可用于判断是否需要重新导出
+pub fn get_project_content_hash(project_id: u32) -> Result<String, String> {
+    log_function_call("get_project_content_hash", Some(serde_json::json!({"project_id": project_id})));
+    get_service().io_service.content_hash(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 把改过译文的嵌字CSV/JSON导回项目，整体为一次撤销操作
+pub fn import_typesetting_data(project_id: u32, content: &str, format: TypesettingFormat, options: TypesettingImportOptions) -> Result<TypesettingImportReport, String> {
+    log_function_call("import_typesetting_data", Some(serde_json::json!({"project_id": project_id, "content_len": content.len(), "format": format, "options": options})));
+    service_import_typesetting_data(ProjectId::from(project_id), content, format, &options)
+        .map_err(|e| e.to_string())
+}
+
+/// 导出带标记编号的嵌字参考图，返回(文件名, 图片数据)列表
+pub fn export_annotated_images(project_id: u32, options: AnnotateOptions) -> Result<Vec<(String, Vec<u8>)>, String> {
+    log_function_call("export_annotated_images", Some(serde_json::json!({"project_id": project_id, "options": options})));
+    service_export_annotated_images(ProjectId::from(project_id), &options)
+        .map_err(|e| e.to_string())
+}
+
+/// 导出参考图并逐张写入目录，返回写入的文件路径
+#[cfg(not(feature = "wasm"))]
+pub fn export_annotated_images_to_dir(project_id: u32, dir: &str, options: AnnotateOptions) -> Result<Vec<String>, String> {
+    log_function_call("export_annotated_images_to_dir", Some(serde_json::json!({"project_id": project_id, "dir": dir, "options": options})));
+    crate::service::io::annotate::export_annotated_images_to_dir(ProjectId::from(project_id), std::path::Path::new(dir), &options)
+        .map_err(|e| e.to_string())
+}
+
+/// 校验路径存在并返回规范化后的绝对路径（用于在文件管理器中显示）
+#[cfg(not(feature = "wasm"))]
+pub fn resolve_existing_path(path: &str) -> Result<String, String> {
+    log_function_call("resolve_existing_path", Some(serde_json::json!({"path": path})));
+    if path.trim().is_empty() {
+        return Err("路径为空".to_string());
+    }
+    let resolved = std::fs::canonicalize(path)
+        .map_err(|e| format!("路径不存在: {} ({})", path, e))?
+        .to_string_lossy()
+        .to_string();
+    // Windows下canonicalize返回\\?\前缀的路径，资源管理器无法识别
+    Ok(resolved.strip_prefix(r"\\?\").map(str::to_string).unwrap_or(resolved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_resolve_existing_path() {
+        let dir = std::env::temp_dir().join(format!("bf_reveal_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let file = dir.join("chapter.bf");
+        std::fs::write(&file, b"{}").unwrap();
+
+        // 相对段被消去，得到与canonicalize一致的绝对路径
+        let indirect = dir.join("sub").join("..").join("chapter.bf");
+        let resolved = resolve_existing_path(&indirect.to_string_lossy()).unwrap();
+        assert!(std::path::Path::new(&resolved).is_absolute() && !resolved.contains(".."));
+        assert_eq!(std::fs::canonicalize(&resolved).unwrap(), std::fs::canonicalize(&file).unwrap());
+
+        assert!(resolve_existing_path("  ").is_err());
+        assert!(resolve_existing_path(&dir.join("missing.bf").to_string_lossy()).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+use crate::common::log_function_call;
+use crate::service::keymap::{self, KeyBinding};
+
+/// 获取所有可配置操作的快捷键
+pub fn get_keymap() -> Result<Vec<KeyBinding>, String> {
+    log_function_call("get_keymap", None);
+    keymap::get_keymap().map_err(|e| e.to_string())
+}
+
+/// 修改部分操作的快捷键（值为null表示取消绑定），存在冲突时整体不生效
+pub fn set_keymap(bindings: HashMap<String, Option<String>>) -> Result<Vec<KeyBinding>, String> {
+    log_function_call("set_keymap", Some(serde_json::json!({"bindings": &bindings})));
+    keymap::set_keymap(bindings).map_err(|e| e.to_string())
+}
+
+/// 恢复默认快捷键
+pub fn reset_keymap() -> Result<Vec<KeyBinding>, String> {
+    log_function_call("reset_keymap", None);
+    keymap::reset_keymap().map_err(|e| e.to_string())
+}
+
+/// 加载持久化的快捷键JSON（WASM端启动时由JS传入），返回警告列表
+pub fn load_keymap(json: String) -> Result<Vec<String>, String> {
+    log_function_call("load_keymap", Some(serde_json::json!({"size": json.len()})));
+    keymap::load_keymap_json(&json).map_err(|e| e.to_string())
+}
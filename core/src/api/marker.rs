@@ -1,85 +1,108 @@
-use crate::common::{Logger, log_function_call, ImageId, MarkerId};
-use crate::common::dto::marker::MarkerDTO;
+use crate::common::{Logger, log_function_call, register_operation, ImageId, MarkerId, ProjectId};
+use crate::common::dto::marker::{MarkerDTO, MarkerGeometryDTO};
+use crate::storage::marker::{CoordinateMode, MarkerGeometry};
+use crate::storage::TranslationHistoryEntry;
+use crate::service::image::{ensure_image_unlocked, ensure_marker_unlocked};
+use crate::service::marker::{ConsistencyConfig, ReplaceReport, TranslationIssue};
 use crate::service::{get_service, events::DomainEvent};
 
-/// 为图片添加点型标记
-pub fn add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>) -> Option<u32> {
+// 锁定图片上的标记不能新建、修改、删除或移动，错误为ImageLockedError的JSON
+fn check_image_unlocked(image_id: u32) -> Result<(), String> {
+    ensure_image_unlocked(ImageId::from(image_id)).map_err(|e| e.to_json_string())
+}
+
+fn check_marker_unlocked(marker_id: u32) -> Result<(), String> {
+    ensure_marker_unlocked(MarkerId::from(marker_id)).map_err(|e| e.to_json_string())
+}
+
+// 把新建的标记挂到图片上并发布事件（记录撤销）；失败时回滚
+fn attach_new_marker(image_id: u32, marker_id: u32, kind: &str) -> Option<u32> {
+    let service = get_service();
+    if service.image_service.add_marker_to_image(image_id, marker_id) {
+        service.event_bus.publish(DomainEvent::MarkerAddedToImage(
+            ImageId::from(image_id),
+            MarkerId::from(marker_id)
+        ));
+        Some(marker_id)
+    } else {
+        // 回滚操作
+        service.marker_service.remove_marker(marker_id);
+        Logger::error(&format!("Failed to add {} marker to image {}", kind, image_id));
+        None
+    }
+}
+
+/// 为图片添加点型标记；mode为越界坐标的处理方式（默认拒绝），坐标无效时返回错误说明
+pub fn add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<CoordinateMode>) -> Result<Option<u32>, String> {
     log_function_call("add_point_marker_to_image", Some(serde_json::json!({
         "image_id": image_id,
         "x": x,
         "y": y,
-        "translation": translation
-    })));
-    
-    let service = get_service();
-    
-    // API层直接处理业务逻辑
-    let result = if service.image_service.image_exists(image_id) {
-        if let Some(marker_id) = service.marker_service.add_point_marker(image_id, x, y, translation) {
-            if service.image_service.add_marker_to_image(image_id, marker_id) {
-                service.event_bus.publish(DomainEvent::MarkerAddedToImage(
-                    ImageId::from(image_id),
-                    MarkerId::from(marker_id)
-                ));
-                Some(marker_id)
-            } else {
-                // 回滚操作
-                service.marker_service.remove_marker(marker_id);
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+        "translation": translation,
+        "mode": mode
+    })));
+    check_image_unlocked(image_id)?;
     
-    if result.is_none() {
-        Logger::error(&format!("Failed to add point marker to image {}", image_id));
+    let service = get_service();
+    if !service.image_service.image_exists(image_id) {
+        Logger::error(&format!("Failed to add point marker to image {}: image not found", image_id));
+        return Ok(None);
     }
-    
-    result
+    let marker_id = service.marker_service
+        .add_point_marker_to_image(ImageId::from(image_id), x, y, translation, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    Ok(attach_new_marker(image_id, marker_id.0, "point"))
 }
 
-/// 为图片添加矩形型标记
-pub fn add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> Option<u32> {
+/// 为图片添加矩形型标记；mode为越界坐标的处理方式（默认拒绝），坐标无效时返回错误说明
+pub fn add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<CoordinateMode>) -> Result<Option<u32>, String> {
     log_function_call("add_rectangle_marker_to_image", Some(serde_json::json!({
         "image_id": image_id,
         "x": x,
         "y": y,
         "width": width,
         "height": height,
-        "translation": translation
-    })));
-    
-    let service = get_service();
-    
-    // API层直接处理业务逻辑
-    let result = if service.image_service.image_exists(image_id) {
-        if let Some(marker_id) = service.marker_service.add_rectangle_marker(image_id, x, y, width, height, translation) {
-            if service.image_service.add_marker_to_image(image_id, marker_id) {
-                service.event_bus.publish(DomainEvent::MarkerAddedToImage(
-                    ImageId::from(image_id),
-                    MarkerId::from(marker_id)
-                ));
-                Some(marker_id)
-            } else {
-                // 回滚操作
-                service.marker_service.remove_marker(marker_id);
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+        "translation": translation,
+        "mode": mode
+    })));
+    check_image_unlocked(image_id)?;
     
-    if result.is_none() {
-        Logger::error(&format!("Failed to add rectangle marker to image {}", image_id));
+    let service = get_service();
+    if !service.image_service.image_exists(image_id) {
+        Logger::error(&format!("Failed to add rectangle marker to image {}: image not found", image_id));
+        return Ok(None);
+    }
+    // 译文为空才是新画的标记；复制、移动等带着原译文，不触发自动OCR
+    let freshly_drawn = translation.as_deref().is_none_or(|t| t.trim().is_empty());
+    let marker_id = service.marker_service
+        .add_rectangle_marker_to_image(ImageId::from(image_id), x, y, width, height, translation, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    let result = attach_new_marker(image_id, marker_id.0, "rectangle");
+    if result.is_some() && freshly_drawn {
+        service.bunny_service.auto_ocr_for_new_marker(marker_id, ImageId::from(image_id));
     }
+    Ok(result)
+}
+
+/// 为图片添加多边形标记（斜向文字），顶点为百分比坐标，至少3个；顶点无效时返回错误说明
+pub fn add_polygon_marker_to_image(image_id: u32, points: Vec<(f64, f64)>, translation: Option<String>, mode: Option<CoordinateMode>) -> Result<Option<u32>, String> {
+    log_function_call("add_polygon_marker_to_image", Some(serde_json::json!({
+        "image_id": image_id,
+        "points": points,
+        "translation": translation,
+        "mode": mode
+    })));
+    check_image_unlocked(image_id)?;
     
-    result
+    let service = get_service();
+    if !service.image_service.image_exists(image_id) {
+        Logger::error(&format!("Failed to add polygon marker to image {}: image not found", image_id));
+        return Ok(None);
+    }
+    let marker_id = service.marker_service
+        .add_polygon_marker_to_image(ImageId::from(image_id), points, translation, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    Ok(attach_new_marker(image_id, marker_id.0, "polygon"))
 }
 
 /// 获取标记信息
@@ -97,136 +120,353 @@ pub fn get_markers_for_image(image_id: u32) -> Vec<MarkerDTO> {
 }
 
 /// 更新点型标记位置
-pub fn update_point_marker_position(marker_id: u32, x: f64, y: f64) -> bool {
+pub fn update_point_marker_position(marker_id: u32, x: f64, y: f64, mode: Option<CoordinateMode>) -> Result<bool, String> {
     log_function_call("update_point_marker_position", Some(serde_json::json!({
         "marker_id": marker_id,
         "x": x,
-        "y": y
+        "y": y,
+        "mode": mode
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_point_marker_position(marker_id, x, y)
+    service.marker_service.update_point_marker_position(marker_id, x, y, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())
 }
 
 /// 更新矩形型标记几何
-pub fn update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64) -> bool {
+pub fn update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64, mode: Option<CoordinateMode>) -> Result<bool, String> {
     log_function_call("update_rectangle_marker_geometry", Some(serde_json::json!({
         "marker_id": marker_id,
         "x": x,
         "y": y,
         "width": width,
-        "height": height
+        "height": height,
+        "mode": mode
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_rectangle_marker_geometry(marker_id, x, y, width, height)
+    service.marker_service.update_rectangle_marker_geometry(marker_id, x, y, width, height, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 更新多边形标记的顶点
+pub fn update_polygon_marker_geometry(marker_id: u32, points: Vec<(f64, f64)>, mode: Option<CoordinateMode>) -> Result<bool, String> {
+    log_function_call("update_polygon_marker_geometry", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "points": points,
+        "mode": mode
+    })));
+    check_marker_unlocked(marker_id)?;
+    
+    let service = get_service();
+    service.marker_service.update_polygon_marker_geometry(marker_id, points, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 开始拖拽标记：之后的位置更新不单独记录撤销，事件节流发出
+pub fn begin_marker_drag(marker_id: u32) -> Result<bool, String> {
+    log_function_call("begin_marker_drag", Some(serde_json::json!({
+        "marker_id": marker_id
+    })));
+    check_marker_unlocked(marker_id)?;
+    
+    let service = get_service();
+    Ok(service.marker_service.begin_marker_drag(MarkerId::from(marker_id)).unwrap_or(false))
+}
+
+/// 结束拖拽标记：写入最终几何，整个拖拽记录为一次撤销操作
+pub fn end_marker_drag(marker_id: u32, final_geometry: MarkerGeometryDTO) -> Result<bool, String> {
+    log_function_call("end_marker_drag", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "final_geometry": final_geometry
+    })));
+    check_marker_unlocked(marker_id)?;
+    
+    let service = get_service();
+    service.marker_service
+        .end_marker_drag(MarkerId::from(marker_id), MarkerGeometry::from_dto(final_geometry))
+        .map_err(|e| e.to_string())
 }
 
 /// 更新标记翻译
-pub fn update_marker_translation(marker_id: u32, translation: String) -> bool {
+pub fn update_marker_translation(marker_id: u32, translation: String) -> Result<bool, String> {
     log_function_call("update_marker_translation", Some(serde_json::json!({
         "marker_id": marker_id,
         "translation": &translation
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_marker_translation(marker_id, translation)
+    Ok(service.marker_service.update_marker_translation(marker_id, translation))
 }
 
 /// 更新标记样式
-pub fn update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> bool {
+pub fn update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> Result<bool, String> {
     log_function_call("update_marker_style", Some(serde_json::json!({
         "marker_id": marker_id,
         "overlay_text": overlay_text,
         "horizontal": horizontal
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_marker_style(marker_id, overlay_text, horizontal)
+    Ok(service.marker_service.update_marker_style(marker_id, overlay_text, horizontal))
+}
+
+/// 设置标记的相对字号（1.0为正常），None清除；可撤销
+pub fn update_marker_size_hint(marker_id: u32, size_hint: Option<f32>) -> Result<bool, String> {
+    log_function_call("update_marker_size_hint", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "size_hint": size_hint
+    })));
+    check_marker_unlocked(marker_id)?;
+    get_service().marker_service.update_marker_size_hint(MarkerId::from(marker_id), size_hint)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置标记的分类标签（thought/shout/narration等），None或空字符串清除；可撤销
+pub fn update_marker_tag(marker_id: u32, tag: Option<String>) -> Result<bool, String> {
+    log_function_call("update_marker_tag", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "tag": &tag
+    })));
+    check_marker_unlocked(marker_id)?;
+    get_service().marker_service.update_marker_tag(MarkerId::from(marker_id), tag)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置标记的译者备注（与译文分开保存），None或空字符串清除；可撤销
+pub fn update_marker_note(marker_id: u32, note: Option<String>) -> Result<bool, String> {
+    log_function_call("update_marker_note", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "note": &note
+    })));
+    check_marker_unlocked(marker_id)?;
+    get_service().marker_service.update_marker_note(MarkerId::from(marker_id), note)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取标记最近的译文历史（最多10条，最旧的在前），包含时间和来源（manual/machine/import）
+pub fn get_translation_history(marker_id: u32) -> Vec<TranslationHistoryEntry> {
+    crate::storage::translation_history::get_translation_history_storage(MarkerId::from(marker_id))
+        .unwrap_or_default()
+}
+
+/// 把译文改回历史中的第history_index条，可撤销
+pub fn revert_translation(marker_id: u32, history_index: usize) -> Result<bool, String> {
+    log_function_call("revert_translation", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "history_index": history_index
+    })));
+    check_marker_unlocked(marker_id)?;
+    get_service().marker_service.revert_translation(MarkerId::from(marker_id), history_index)
+        .map_err(|e| e.to_string())
+}
+
+/// 把标记移到同一项目的另一张图片，position为空时追加到末尾
+pub fn move_marker_to_image(marker_id: u32, target_image_id: u32, geometry: MarkerGeometryDTO, position: Option<u32>) -> Result<bool, String> {
+    log_function_call("move_marker_to_image", Some(serde_json::json!({
+        "marker_id": marker_id,
+        "target_image_id": target_image_id,
+        "geometry": &geometry,
+        "position": position
+    })));
+    check_marker_unlocked(marker_id)?;
+    check_image_unlocked(target_image_id)?;
+    get_service().marker_service
+        .move_marker_to_image(MarkerId::from(marker_id), ImageId::from(target_image_id), MarkerGeometry::from_dto(geometry), position)
+        .map_err(|e| e.to_string())
 }
 
 /// 移动标记在图片内的顺序
-pub fn move_marker_order(marker_id: u32, new_index: u32) -> bool {
+pub fn move_marker_order(marker_id: u32, new_index: u32) -> Result<bool, String> {
     log_function_call("move_marker_order", Some(serde_json::json!({
         "marker_id": marker_id,
         "new_index": new_index
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.move_marker_order(marker_id, new_index)
+    Ok(service.marker_service.move_marker_order(marker_id, new_index))
+}
+
+/// 把图片的标记序号压缩为连续的1..N（一次撤销操作），返回改了序号的标记数
+pub fn compact_marker_indices(image_id: u32) -> Result<usize, String> {
+    log_function_call("compact_marker_indices", Some(serde_json::json!({"image_id": image_id})));
+    check_image_unlocked(image_id)?;
+    get_service().marker_service.compact_indices(ImageId::from(image_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 压缩项目内所有图片的标记序号（一次撤销操作）
+pub fn compact_project_marker_indices(project_id: u32) -> Result<usize, String> {
+    log_function_call("compact_project_marker_indices", Some(serde_json::json!({"project_id": project_id})));
+    get_service().marker_service.compact_project_indices(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 按项目的阅读方向给图片的标记重新编号（一次撤销操作），返回改了序号的标记数
+pub fn renumber_markers_by_reading_order(image_id: u32) -> Result<usize, String> {
+    log_function_call("renumber_markers_by_reading_order", Some(serde_json::json!({"image_id": image_id})));
+    check_image_unlocked(image_id)?;
+    get_service().marker_service.renumber_by_reading_order(ImageId::from(image_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 把同一图片上的多个矩形标记合并为外接矩形（一次撤销操作），译文按阅读顺序拼接，返回新标记ID
+pub fn merge_rectangle_markers(marker_ids: Vec<u32>) -> Result<u32, String> {
+    log_function_call("merge_rectangle_markers", Some(serde_json::json!({"marker_ids": marker_ids})));
+    for marker_id in &marker_ids {
+        check_marker_unlocked(*marker_id)?;
+    }
+    let ids: Vec<MarkerId> = marker_ids.into_iter().map(MarkerId::from).collect();
+    get_service().marker_service.merge_rectangles(&ids)
+        .map(|id| id.0)
+        .map_err(|e| e.to_string())
+}
+
+/// 按阅读顺序选择下一个标记，backward为true时选择上一个；没有当前标记时从头（尾）开始，到头时返回None
+pub fn select_next_marker(image_id: u32, current_marker_id: Option<u32>, backward: bool) -> Result<Option<u32>, String> {
+    get_service().marker_service.select_next_marker(ImageId::from(image_id), current_marker_id.map(MarkerId::from), backward)
+        .map(|id| id.map(|id| id.0))
+        .map_err(|e| e.to_string())
 }
 
 /// 更新点型标记完整信息
-pub fn update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>) -> bool {
+pub fn update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<CoordinateMode>) -> Result<bool, String> {
     log_function_call("update_point_marker_full", Some(serde_json::json!({
         "marker_id": marker_id,
         "x": x,
         "y": y,
-        "translation": translation
+        "translation": translation,
+        "mode": mode
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_point_marker_full(marker_id, x, y, translation)
+    service.marker_service.update_point_marker_full(marker_id, x, y, translation, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())
 }
 
 /// 更新矩形型标记完整信息
-pub fn update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> bool {
+pub fn update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<CoordinateMode>) -> Result<bool, String> {
     log_function_call("update_rectangle_marker_full", Some(serde_json::json!({
         "marker_id": marker_id,
         "x": x,
         "y": y,
         "width": width,
         "height": height,
-        "translation": translation
+        "translation": translation,
+        "mode": mode
     })));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.update_rectangle_marker_full(marker_id, x, y, width, height, translation)
+    service.marker_service.update_rectangle_marker_full(marker_id, x, y, width, height, translation, mode.unwrap_or_default())
+        .map_err(|e| e.to_string())
 }
 
 /// 从图片中移除标记
-pub fn remove_marker_from_image(image_id: u32, marker_id: u32) -> bool {
+pub fn remove_marker_from_image(image_id: u32, marker_id: u32) -> Result<bool, String> {
     log_function_call("remove_marker_from_image", Some(serde_json::json!({
         "image_id": image_id,
         "marker_id": marker_id
     })));
+    check_image_unlocked(image_id)?;
+    check_marker_unlocked(marker_id)?;
 
     let service = get_service();
 
     let removed_from_image = service.image_service.remove_marker_from_image(image_id, marker_id);
     let removed_marker = service.marker_service.remove_marker(marker_id);
 
-    // Clear bunny cache for this marker
+    // Clear bunny cache and translation history for this marker
     if removed_marker {
         let _ = crate::storage::bunny_cache::clear_bunny_cache_storage(crate::common::MarkerId(marker_id));
+        let _ = crate::storage::translation_history::clear_translation_history_storage(crate::common::MarkerId(marker_id));
     }
 
-    removed_from_image && removed_marker
+    Ok(removed_from_image && removed_marker)
 }
 
 /// 清空图片的所有标记
-pub fn clear_image_markers(image_id: u32) -> bool {
+pub fn clear_image_markers(image_id: u32) -> Result<bool, String> {
     log_function_call("clear_image_markers", Some(serde_json::json!({"image_id": image_id})));
+    check_image_unlocked(image_id)?;
     
     let service = get_service();
     
     // 发布清空图片标记事件，MarkerService会处理实际的清理工作
     service.event_bus.publish(DomainEvent::ImageMarkersClearing(ImageId::from(image_id)));
-    true
+    Ok(true)
 }
 
 /// 将矩形marker转换为点型marker（使用矩形上边的中点）
-pub fn convert_rectangle_to_point_marker(marker_id: u32) -> bool {
+pub fn convert_rectangle_to_point_marker(marker_id: u32) -> Result<bool, String> {
     log_function_call("convert_rectangle_to_point_marker", Some(serde_json::json!({"marker_id": marker_id})));
+    check_marker_unlocked(marker_id)?;
     
     let service = get_service();
-    service.marker_service.convert_rectangle_to_point(marker_id)
+    Ok(service.marker_service.convert_rectangle_to_point(marker_id))
 }
 
 /// 将点型marker转换为矩形marker（5% x 5%的矩形，点为上边中点）
-pub fn convert_point_to_rectangle_marker(marker_id: u32) -> bool {
+pub fn convert_point_to_rectangle_marker(marker_id: u32) -> Result<bool, String> {
     log_function_call("convert_point_to_rectangle_marker", Some(serde_json::json!({"marker_id": marker_id})));
+    check_marker_unlocked(marker_id)?;
+    
+    let service = get_service();
+    Ok(service.marker_service.convert_point_to_rectangle(marker_id))
+}
+
+/// 将多边形marker转换为其外接矩形
+pub fn convert_polygon_to_rectangle_marker(marker_id: u32) -> Result<bool, String> {
+    log_function_call("convert_polygon_to_rectangle_marker", Some(serde_json::json!({"marker_id": marker_id})));
+    check_marker_unlocked(marker_id)?;
+    
+    let service = get_service();
+    Ok(service.marker_service.convert_polygon_to_rectangle(marker_id))
+}
+
+/// 在项目所有标记的翻译中查找替换，返回被修改的标记ID（整体为一次撤销操作）
+/// 传入operation_id时可以通过cancel_operation中途取消，已替换的部分保留
+pub fn replace_in_translations(project_id: u32, find: String, replace: String, case_sensitive: bool, whole_word: bool, operation_id: Option<String>) -> Result<ReplaceReport, String> {
+    log_function_call("replace_in_translations", Some(serde_json::json!({
+        "project_id": project_id,
+        "find": find,
+        "replace": replace,
+        "case_sensitive": case_sensitive,
+        "whole_word": whole_word,
+        "operation_id": operation_id
+    })));
+    
+    let operation = register_operation(operation_id);
+    let service = get_service();
+    service.marker_service
+        .replace_in_translations(ProjectId::from(project_id), &find, &replace, case_sensitive, whole_word, operation.token())
+        .map_err(|e| e.to_string())
+}
+
+/// 检查项目翻译一致性（空翻译、同一原文不同译法、多余空白）
+/// source_similarity为"同一原文"的相似度阈值（0~1），不传时要求原文完全相同
+pub fn analyze_translations(project_id: u32, source_similarity: Option<f64>) -> Result<Vec<TranslationIssue>, String> {
+    log_function_call("analyze_translations", Some(serde_json::json!({
+        "project_id": project_id,
+        "source_similarity": source_similarity
+    })));
+    
+    let mut config = ConsistencyConfig::default();
+    if let Some(threshold) = source_similarity {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(format!("source_similarity must be between 0 and 1, got {}", threshold));
+        }
+        config.source_similarity = threshold;
+    }
     
     let service = get_service();
-    service.marker_service.convert_point_to_rectangle(marker_id)
-}
\ No newline at end of file
+    service.marker_service
+        .analyze_translations(ProjectId::from(project_id), &config)
+        .map_err(|e| e.to_string())
+}
@@ -8,6 +8,12 @@ pub mod undo_redo;
 pub mod thumbnail;
 pub mod io;
 pub mod bunny;
+pub mod keymap;
+pub mod operation;
+pub mod ui;
+pub mod plugin;
+// 快照的函数名较通用，不展开到api命名空间
+pub mod snapshot;
 
 pub use opening_project::*;
 pub use project::*;
@@ -18,4 +24,8 @@ pub use cleanup::*;
 pub use undo_redo::*;
 pub use thumbnail::*;
 pub use io::*;
-pub use bunny::*;
\ No newline at end of file
+pub use bunny::*;
+pub use keymap::*;
+pub use operation::*;
+pub use ui::*;
+pub use plugin::*;
\ No newline at end of file
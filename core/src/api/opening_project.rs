@@ -1,18 +1,21 @@
 // This file contains the opening project API functions
-use crate::common::{Logger, log_function_call};
+use crate::common::{Logger, log_function_call, register_operation};
 use crate::common::dto::opening_project::OpeningProjectDTO;
+use crate::service::io::archive::ArchiveImportReport;
+use crate::service::io::labelplus::{LabelplusCoordinateOrigin, LabelplusOriginPreview};
 use crate::service::get_service;
-#[cfg(feature = "tauri")]
+#[cfg(not(feature = "wasm"))]
 use std::path::PathBuf;
 
-/// 创建空的临时项目（用于新建项目）
-pub fn create_empty_opening_project(project_name: String) -> Result<u32, String> {
+/// 创建空的临时项目（用于新建项目），可选套用项目模板
+pub fn create_empty_opening_project(project_name: String, template: Option<String>) -> Result<u32, String> {
     log_function_call("create_empty_opening_project", Some(serde_json::json!({
-        "project_name": &project_name
+        "project_name": &project_name,
+        "template": &template
     })));
     
     let service = get_service();
-    let result = service.opening_project_service.create_empty_opening_project(project_name);
+    let result = service.opening_project_service.create_empty_opening_project(project_name, template);
     
     if let Err(ref e) = result {
         Logger::error_with_data(
@@ -25,7 +28,7 @@ pub fn create_empty_opening_project(project_name: String) -> Result<u32, String>
 }
 
 /// 通过文件路径创建临时项目（自动检测文件类型）
-#[cfg(feature = "tauri")]
+#[cfg(not(feature = "wasm"))]
 pub fn create_opening_project_from_path(path: PathBuf, project_name: String) -> Result<u32, String> {
     log_function_call("create_opening_project_from_path", Some(serde_json::json!({
         "project_name": &project_name,
@@ -45,6 +48,25 @@ pub fn create_opening_project_from_path(path: PathBuf, project_name: String) ->
     result
 }
 
+/// 通过从URL下载的图片创建临时项目，格式按文件头、Content-Type、URL扩展名依次确定
+/// project_name为空时使用图片文件名
+pub fn create_opening_project_from_url_data(url: String, content_type: Option<String>, data: Vec<u8>, project_name: String) -> Result<u32, String> {
+    log_function_call("create_opening_project_from_url_data", Some(serde_json::json!({
+        "url": &url,
+        "content_type": &content_type,
+        "data_size": data.len()
+    })));
+
+    let (format, name) = crate::service::image::remote::validate_remote_image(&url, content_type.as_deref(), &data)
+        .map_err(|e| e.to_json_string())?;
+    let project_name = if project_name.trim().is_empty() {
+        name.rsplit_once('.').map_or(name.clone(), |(stem, _)| stem.to_string())
+    } else {
+        project_name
+    };
+    create_opening_project_from_binary(data, format.extension().to_string(), project_name)
+}
+
 /// 通过二进制数据创建临时项目（需要提供文件扩展名）
 pub fn create_opening_project_from_binary(data: Vec<u8>, file_extension: String, project_name: String) -> Result<u32, String> {
     log_function_call("create_opening_project_from_binary", Some(serde_json::json!({
@@ -66,6 +88,54 @@ pub fn create_opening_project_from_binary(data: Vec<u8>, file_extension: String,
     result
 }
 
+/// 通过zip/cbz压缩包数据创建临时项目，返回导入报告（包含跳过的非图片条目）
+/// 压缩包损坏或加密时，错误字符串为序列化的ArchiveError（archive/kind/message）
+/// 传入operation_id时可以通过cancel_operation中途取消，报告的status为cancelled
+pub fn create_opening_project_from_archive(data: Vec<u8>, archive_name: String, project_name: String, operation_id: Option<String>) -> Result<ArchiveImportReport, String> {
+    log_function_call("create_opening_project_from_archive", Some(serde_json::json!({
+        "project_name": &project_name,
+        "archive_name": &archive_name,
+        "data_size": data.len(),
+        "operation_id": &operation_id
+    })));
+    
+    let operation = register_operation(operation_id);
+    let service = get_service();
+    let result = service.opening_project_service.create_opening_project_from_archive(data, archive_name, project_name, operation.token());
+    
+    if let Err(ref e) = result {
+        Logger::error_with_data(
+            "从压缩包创建临时项目失败",
+            serde_json::json!({"error": e})
+        );
+    }
+    
+    result
+}
+
+/// 通过压缩包路径创建临时项目（桌面端）
+#[cfg(not(feature = "wasm"))]
+pub fn create_opening_project_from_archive_path(path: PathBuf, project_name: String, operation_id: Option<String>) -> Result<ArchiveImportReport, String> {
+    log_function_call("create_opening_project_from_archive_path", Some(serde_json::json!({
+        "project_name": &project_name,
+        "path": path.to_string_lossy(),
+        "operation_id": &operation_id
+    })));
+    
+    let operation = register_operation(operation_id);
+    let service = get_service();
+    let result = service.opening_project_service.create_opening_project_from_archive_path(path, project_name, operation.token());
+    
+    if let Err(ref e) = result {
+        Logger::error_with_data(
+            "从压缩包创建临时项目失败",
+            serde_json::json!({"error": e})
+        );
+    }
+    
+    result
+}
+
 /// 获取临时项目信息
 pub fn get_opening_project_info(project_id: u32) -> Option<OpeningProjectDTO> {
     log_function_call("get_opening_project_info", Some(serde_json::json!({"project_id": project_id})));
@@ -82,6 +152,39 @@ pub fn flush_opening_project_images(project_id: u32) -> bool {
     service.opening_project_service.flush_opening_project_images(project_id)
 }
 
+/// 设置是否保持图片上传顺序（默认在finalize时按文件名自然排序）
+pub fn set_opening_project_preserve_order(project_id: u32, preserve_order: bool) -> bool {
+    log_function_call("set_opening_project_preserve_order", Some(serde_json::json!({
+        "project_id": project_id,
+        "preserve_order": preserve_order
+    })));
+    
+    let service = get_service();
+    service.opening_project_service.set_preserve_order(project_id, preserve_order)
+}
+
+/// 设置导入labelplus标记时的坐标原点（默认左上角），在finalize时生效
+pub fn set_opening_project_labelplus_origin(project_id: u32, origin: LabelplusCoordinateOrigin) -> bool {
+    log_function_call("set_opening_project_labelplus_origin", Some(serde_json::json!({
+        "project_id": project_id,
+        "origin": origin
+    })));
+    
+    let service = get_service();
+    service.opening_project_service.set_labelplus_origin(project_id, origin)
+}
+
+/// 预览按某个坐标原点导入时翻译文件中第一个标记的位置，没有labelplus数据时返回None
+pub fn preview_opening_project_labelplus_origin(project_id: u32, origin: LabelplusCoordinateOrigin) -> Option<LabelplusOriginPreview> {
+    log_function_call("preview_opening_project_labelplus_origin", Some(serde_json::json!({
+        "project_id": project_id,
+        "origin": origin
+    })));
+    
+    let service = get_service();
+    service.opening_project_service.preview_labelplus_origin(project_id, origin)
+}
+
 /// 将临时项目转为正式项目
 pub fn finalize_opening_project(project_id: u32) -> bool {
     log_function_call("finalize_opening_project", Some(serde_json::json!({"project_id": project_id})));
@@ -90,6 +193,15 @@ pub fn finalize_opening_project(project_id: u32) -> bool {
     service.opening_project_service.finalize_opening_project(project_id)
 }
 
+/// 取消正在打开的项目，已加入的图片会被删除
+/// 项目已经开始finalize或不存在时返回false，此时以打开完成为准
+pub fn cancel_opening_project(opening_id: u32) -> bool {
+    log_function_call("cancel_opening_project", Some(serde_json::json!({"opening_id": opening_id})));
+    
+    let service = get_service();
+    service.opening_project_service.cancel_opening_project(opening_id)
+}
+
 /// 删除临时项目
 pub fn delete_opening_project(project_id: u32) -> bool {
     log_function_call("delete_opening_project", Some(serde_json::json!({"project_id": project_id})));
@@ -0,0 +1,9 @@
+// 长时间操作（批量导入、重排、查找替换）的取消
+use crate::common::log_function_call;
+
+/// 取消传入了operation_id的进行中操作，操作会在下一个检查点停止并返回部分结果
+/// 操作不存在（未开始或已结束）时返回false
+pub fn cancel_operation(operation_id: String) -> bool {
+    log_function_call("cancel_operation", Some(serde_json::json!({"operation_id": &operation_id})));
+    crate::common::cancel_operation(&operation_id)
+}
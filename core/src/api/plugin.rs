@@ -0,0 +1,25 @@
+use crate::common::log_function_call;
+use crate::plugin::audit::{self, PluginAuditEntry};
+
+/// 审计记录默认返回的条数
+pub const DEFAULT_AUDIT_LOG_LIMIT: usize = 200;
+
+/// 开关插件服务调用审计，打开后每次调用都写入日志缓冲区（敏感字段已脱敏）
+pub fn set_audit_enabled(enabled: bool) {
+    log_function_call("set_audit_enabled", Some(serde_json::json!({"enabled": enabled})));
+    audit::set_audit_enabled(enabled);
+}
+
+pub fn is_audit_enabled() -> bool {
+    audit::is_audit_enabled()
+}
+
+/// 最近的插件调用记录（按时间顺序），plugin_id为空时返回所有插件的
+pub fn get_plugin_audit_log(plugin_id: Option<String>, limit: Option<usize>) -> Vec<PluginAuditEntry> {
+    audit::get_audit_log(plugin_id.as_deref(), limit.unwrap_or(DEFAULT_AUDIT_LOG_LIMIT))
+}
+
+/// 登记插件的配置schema，其中password类型的字段在审计记录中脱敏
+pub fn register_plugin_config_schema(plugin_id: String, schema: serde_json::Value) {
+    audit::register_config_schema(&plugin_id, &schema);
+}
@@ -1,7 +1,9 @@
-use crate::common::{log_function_call, ProjectId, Language};
+use crate::common::{log_function_call, ProjectId, Language, ProjectGlossary, ReadingDirection};
 use crate::common::dto::project::ProjectDTO;
 use crate::common::dto::image::ImageDTO;
 use crate::service::{get_service, events::DomainEvent};
+use crate::service::project::{DuplicateGroup, DuplicateKeepStrategy, IntegrityReport, ProjectTemplate, TemplateList};
+use crate::service::undo_redo::{ActionType, UndoRedoAction};
 
 /// 获取项目信息
 pub fn get_project_info(project_id: u32) -> Option<ProjectDTO> {
@@ -138,4 +140,227 @@ pub fn get_project_images_metadata(project_id: u32) -> Vec<crate::common::dto::i
     log_function_call("get_project_images_metadata", Some(serde_json::json!({"project_id": project_id})));
     let service = get_service();
     service.project_service.get_project_images_metadata(project_id)
-}
\ No newline at end of file
+}
+/// 开关桌面端对项目图片文件变化的监听
+pub fn set_project_file_watch(project_id: u32, enabled: bool) -> bool {
+    log_function_call("set_project_file_watch", Some(serde_json::json!({"project_id": project_id, "enabled": enabled})));
+    let service = get_service();
+    service.project_service.set_file_watch(ProjectId::from(project_id), enabled)
+        .unwrap_or(false)
+}
+
+/// 在项目中启用/停用OCR/翻译服务，停用的服务不会出现在该项目的可用服务列表中
+pub fn set_service_enabled(project_id: u32, service_id: String, enabled: bool) -> bool {
+    log_function_call("set_service_enabled", Some(serde_json::json!({"project_id": project_id, "service_id": &service_id, "enabled": enabled})));
+    let service = get_service();
+    service.project_service.set_service_enabled(ProjectId::from(project_id), &service_id, enabled)
+        .unwrap_or(false)
+}
+
+/// 获取项目术语表（术语译法和禁用词）
+pub fn get_project_glossary(project_id: u32) -> Option<ProjectGlossary> {
+    crate::storage::project::get_project_storage(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+        .map(|project| project.glossary)
+}
+
+/// 替换项目术语表，翻译请求会把术语表交给插件，插件校验译文后返回警告
+pub fn set_project_glossary(project_id: u32, glossary: ProjectGlossary) -> bool {
+    log_function_call("set_project_glossary", Some(serde_json::json!({
+        "project_id": project_id,
+        "entries": glossary.entries.len(),
+        "forbidden_terms": glossary.forbidden_terms.len()
+    })));
+    let service = get_service();
+    service.project_service.set_glossary(ProjectId::from(project_id), glossary)
+        .unwrap_or(false)
+}
+
+/// 获取项目备注
+pub fn get_project_notes(project_id: u32) -> Option<String> {
+    get_service().project_service.get_notes(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+}
+
+/// 设置项目备注（保存在bf中，LabelPlus导出时写入项目注释）
+pub fn set_project_notes(project_id: u32, notes: String) -> bool {
+    log_function_call("set_project_notes", Some(serde_json::json!({"project_id": project_id, "length": notes.len()})));
+    get_service().project_service.set_notes(ProjectId::from(project_id), notes)
+        .unwrap_or(false)
+}
+
+/// 获取新建矩形标记时是否自动OCR
+pub fn get_auto_ocr_on_create(project_id: u32) -> Option<bool> {
+    get_service().project_service.get_auto_ocr_on_create(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+}
+
+/// 开关新建矩形标记时的自动OCR（使用项目默认OCR服务，未设置时不触发）
+pub fn set_auto_ocr_on_create(project_id: u32, enabled: bool) -> bool {
+    log_function_call("set_auto_ocr_on_create", Some(serde_json::json!({"project_id": project_id, "enabled": enabled})));
+    get_service().project_service.set_auto_ocr_on_create(ProjectId::from(project_id), enabled)
+        .unwrap_or(false)
+}
+
+/// 设置OCR/翻译任务失败时依次改用的服务（按顺序），传空列表关闭
+pub fn set_service_fallbacks(project_id: u32, ocr: Vec<String>, translation: Vec<String>) -> bool {
+    log_function_call("set_service_fallbacks", Some(serde_json::json!({"project_id": project_id, "ocr": &ocr, "translation": &translation})));
+    get_service().project_service.set_service_fallbacks(ProjectId::from(project_id), ocr, translation)
+        .unwrap_or(false)
+}
+
+/// 获取是否把译文历史保存到bf中
+pub fn get_keep_translation_history(project_id: u32) -> Option<bool> {
+    get_service().project_service.get_keep_translation_history(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+}
+
+/// 开关是否把译文历史保存到bf中（默认关闭）
+pub fn set_keep_translation_history(project_id: u32, enabled: bool) -> bool {
+    log_function_call("set_keep_translation_history", Some(serde_json::json!({"project_id": project_id, "enabled": enabled})));
+    get_service().project_service.set_keep_translation_history(ProjectId::from(project_id), enabled)
+        .unwrap_or(false)
+}
+
+/// 获取打开项目时是否把灰度图片重新编码为灰度PNG
+pub fn get_grayscale_reencode(project_id: u32) -> Option<bool> {
+    get_service().project_service.get_grayscale_reencode(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+}
+
+/// 开关打开项目时灰度图片的重新编码（默认关闭，只处理内存中的图片，磁盘上的原图不动）
+pub fn set_grayscale_reencode(project_id: u32, enabled: bool) -> bool {
+    log_function_call("set_grayscale_reencode", Some(serde_json::json!({"project_id": project_id, "enabled": enabled})));
+    get_service().project_service.set_grayscale_reencode(ProjectId::from(project_id), enabled)
+        .unwrap_or(false)
+}
+
+/// 获取项目的阅读方向
+pub fn get_reading_direction(project_id: u32) -> Option<ReadingDirection> {
+    get_service().project_service.get_reading_direction(ProjectId::from(project_id))
+        .ok()
+        .flatten()
+}
+
+/// 设置项目的阅读方向（带撤销功能），只影响派生的标记顺序，不修改标记数据
+pub fn set_reading_direction(project_id: u32, direction: ReadingDirection) -> bool {
+    log_function_call("set_reading_direction", Some(serde_json::json!({"project_id": project_id, "direction": direction})));
+    let service = get_service();
+    let project_id = ProjectId::from(project_id);
+    match service.project_service.set_reading_direction(project_id, direction) {
+        Ok(Some(old_direction)) => {
+            if old_direction != direction {
+                let action = UndoRedoAction::new(
+                    ActionType::UpdateReadingDirection { old_direction, new_direction: direction },
+                    project_id,
+                );
+                let _ = service.undo_redo_service.record_action(action);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// 检查项目中图片、标记和翻译缓存之间的引用是否一致
+/// repair为true时丢弃悬空ID、重新编号重复序号（可撤销），报告中fixed为false的问题需要手动处理
+pub fn check_integrity(project_id: u32, repair: bool) -> Result<IntegrityReport, String> {
+    log_function_call("check_integrity", Some(serde_json::json!({"project_id": project_id, "repair": repair})));
+    let service = get_service();
+    service.project_service.check_integrity(ProjectId::from(project_id), repair)
+        .map_err(|e| e.to_string())
+}
+
+/// 复制项目：设置、图片（共享图片数据）、标记和翻译缓存都复制到新项目，返回新项目ID
+/// 副本没有文件路径，撤销记录为空，之后的修改与原项目互不影响
+pub fn duplicate_project(project_id: u32, new_name: String) -> Result<u32, String> {
+    log_function_call("duplicate_project", Some(serde_json::json!({"project_id": project_id, "new_name": &new_name})));
+    let service = get_service();
+    service.project_service.duplicate_project(ProjectId::from(project_id), new_name)
+        .map(|project_id| project_id.0)
+        .map_err(|e| e.to_string())
+}
+
+/// 查找项目中的重复图片（感知哈希），threshold为允许的最大汉明距离，不传时使用默认值
+/// 计算过程中发送image:duplicate_scan_progress事件
+pub fn find_duplicate_images(project_id: u32, threshold: Option<u32>) -> Result<Vec<DuplicateGroup>, String> {
+    log_function_call("find_duplicate_images", Some(serde_json::json!({"project_id": project_id, "threshold": threshold})));
+    let service = get_service();
+    service.project_service.find_duplicate_images(ProjectId::from(project_id), threshold)
+        .map_err(|e| e.to_string())
+}
+
+/// 删除重复图片，每组按keep_strategy保留一张，作为一次撤销操作，返回删除的图片ID
+/// threshold应与查找时相同；要删除的图片带有标记时报错，force为true时连同标记一起删除
+pub fn remove_duplicate_images(project_id: u32, keep_strategy: DuplicateKeepStrategy, threshold: Option<u32>, force: bool) -> Result<Vec<u32>, String> {
+    log_function_call("remove_duplicate_images", Some(serde_json::json!({
+        "project_id": project_id,
+        "keep_strategy": keep_strategy,
+        "threshold": threshold,
+        "force": force
+    })));
+    let service = get_service();
+    let groups = service.project_service.find_duplicate_images(ProjectId::from(project_id), threshold)
+        .map_err(|e| e.to_string())?;
+    let to_remove = crate::service::project::duplicate_images::plan_duplicate_removal(&groups, keep_strategy, force)
+        .map_err(|e| e.to_string())?;
+    
+    let mut removed = Vec::new();
+    let mut actions = Vec::new();
+    for image_id in to_remove {
+        if let Some(action) = super::image::detach_image_from_project(project_id, image_id.0) {
+            removed.push(image_id.0);
+            actions.push(action);
+        }
+    }
+    if !actions.is_empty() {
+        let action_type = if actions.len() == 1 { actions.remove(0) } else { ActionType::Composite { actions } };
+        let _ = service.undo_redo_service.record_action(UndoRedoAction::new(action_type, ProjectId::from(project_id)));
+    }
+    Ok(removed)
+}
+
+/// 将项目设置保存为模板（不含图片和标记）
+pub fn save_as_template(project_id: u32, name: String) -> Result<ProjectTemplate, String> {
+    log_function_call("save_as_template", Some(serde_json::json!({"project_id": project_id, "name": &name})));
+    let service = get_service();
+    service.project_service.save_as_template(ProjectId::from(project_id), name)
+        .map_err(|e| e.to_string())
+}
+
+/// 按模板新建项目，返回项目ID
+pub fn create_project_from_template(name: String, template: String) -> Result<u32, String> {
+    log_function_call("create_project_from_template", Some(serde_json::json!({"name": &name, "template": &template})));
+    let service = get_service();
+    service.project_service.create_from_template(name, &template)
+        .map(|id| id.0)
+        .map_err(|e| e.to_string())
+}
+
+/// 列出所有模板（内置模板在前），warnings为加载模板时忽略的未知字段等
+pub fn list_templates() -> Result<TemplateList, String> {
+    log_function_call("list_templates", None);
+    let service = get_service();
+    service.project_service.list_templates()
+        .map_err(|e| e.to_string())
+}
+
+/// 删除模板
+pub fn delete_template(name: String) -> Result<bool, String> {
+    log_function_call("delete_template", Some(serde_json::json!({"name": &name})));
+    let service = get_service();
+    service.project_service.delete_template(&name)
+        .map_err(|e| e.to_string())
+}
+
+/// 加载持久化的模板JSON数组（WASM端启动时由JS传入），返回警告列表
+pub fn load_templates(json: String) -> Result<Vec<String>, String> {
+    log_function_call("load_templates", Some(serde_json::json!({"size": json.len()})));
+    crate::service::project::template::load_templates_json(&json)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,38 @@
+// 命名快照（还原点）：保存项目的标记、设置、术语表和海兔缓存，不含图片
+use crate::common::{log_function_call, ProjectId};
+use crate::service::project::snapshot::{self, SnapshotInfo};
+
+/// 创建快照；内容与该项目最近一个快照相同时返回那个快照，不重复创建
+pub fn create(project_id: u32, name: String) -> Result<SnapshotInfo, String> {
+    log_function_call("snapshot::create", Some(serde_json::json!({"project_id": project_id, "name": &name})));
+    snapshot::create_snapshot(ProjectId::from(project_id), name).map_err(|e| e.to_string())
+}
+
+/// 项目的快照，最新的在前
+pub fn list(project_id: u32) -> Result<Vec<SnapshotInfo>, String> {
+    log_function_call("snapshot::list", Some(serde_json::json!({"project_id": project_id})));
+    snapshot::list_snapshots(ProjectId::from(project_id)).map_err(|e| e.to_string())
+}
+
+/// 还原到快照（可撤销），发送project:snapshot_restored
+pub fn restore(project_id: u32, snapshot_id: String) -> Result<(), String> {
+    log_function_call("snapshot::restore", Some(serde_json::json!({"project_id": project_id, "snapshot_id": &snapshot_id})));
+    snapshot::restore_snapshot(ProjectId::from(project_id), &snapshot_id).map_err(|e| e.to_string())
+}
+
+pub fn delete(snapshot_id: String) -> Result<bool, String> {
+    log_function_call("snapshot::delete", Some(serde_json::json!({"snapshot_id": &snapshot_id})));
+    snapshot::delete_snapshot(&snapshot_id).map_err(|e| e.to_string())
+}
+
+/// 每个项目保留的快照数（默认20），超出时删除最旧的
+pub fn set_limit(limit: usize) -> Result<(), String> {
+    log_function_call("snapshot::set_limit", Some(serde_json::json!({"limit": limit})));
+    snapshot::set_snapshot_limit(limit).map_err(|e| e.to_string())
+}
+
+/// 加载一个持久化的快照（WASM端启动时由JS逐个传入）
+pub fn load(data: Vec<u8>) -> Result<SnapshotInfo, String> {
+    log_function_call("snapshot::load", Some(serde_json::json!({"data_len": data.len()})));
+    snapshot::load_snapshot_data(data).map_err(|e| e.to_string())
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use crate::common::log_function_call;
+use crate::common::{log_function_call, ProjectId};
+use crate::service::stats::{TextCountConfig, TextStats};
 use crate::service::get_service;
 
 /// 获取项目统计信息
@@ -34,4 +35,127 @@ pub fn get_project_stats(project_id: u32) -> Option<SingleProjectStats> {
     
     let service = get_service();
     service.stats_service.get_project_stats(project_id)
-}
\ No newline at end of file
+}
+/// 获取项目的原文/译文字数统计（含每张图片的明细）
+pub fn get_text_stats(project_id: u32) -> Result<TextStats, String> {
+    log_function_call("get_text_stats", Some(serde_json::json!({"project_id": project_id})));
+    
+    let service = get_service();
+    service.stats_service.text_stats(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 设置CJK字符的计词方式：true为每个字计一词，false为连续的CJK文字计一词
+pub fn set_text_count_config(cjk_char_as_word: bool) -> Result<(), String> {
+    log_function_call("set_text_count_config", Some(serde_json::json!({"cjk_char_as_word": cjk_char_as_word})));
+    
+    let service = get_service();
+    service.stats_service.set_text_count_config(TextCountConfig { cjk_char_as_word })
+        .map_err(|e| e.to_string())
+}
+
+/// 核心的心跳和内存占用，JS侧定期轮询，用于发现WASM worker崩溃
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoreHealth {
+    pub tick: u64,
+    #[serde(rename = "linearMemoryBytes")]
+    pub linear_memory_bytes: Option<u64>,
+    #[serde(rename = "imageCacheBytes")]
+    pub image_cache_bytes: u64,
+    #[serde(rename = "imageStorageBytes")]
+    pub image_storage_bytes: u64,
+    #[serde(rename = "markerCount")]
+    pub marker_count: usize,
+}
+
+/// 获取心跳，不记录调用本身（频繁轮询）
+pub fn get_health() -> CoreHealth {
+    get_service().stats_service.health()
+}
+
+/// 获取事件通道统计（队列长度、丢弃/积压次数、拆分的大事件），供调试窗口显示
+/// 桌面端事件直接走Tauri，不经过该通道，统计均为0
+pub fn get_event_channel_stats() -> crate::common::EventChannelStats {
+    crate::common::event_queue::event_channel_stats()
+}
+
+/// 设置前端订阅的业务事件（精确名称或"bunny:*"前缀），未订阅的事件不再发给前端；空列表恢复为全部发送
+/// 错误、通知、项目生命周期等关键事件总是发送
+pub fn set_frontend_event_filter(patterns: Vec<String>) {
+    log_function_call("set_frontend_event_filter", Some(serde_json::json!({"patterns": patterns})));
+    crate::common::EVENT_SYSTEM.set_frontend_event_filter(patterns);
+}
+
+/// 获取前端事件过滤的模式和被拦下的事件计数，供调试窗口显示
+pub fn get_frontend_event_filter_stats() -> crate::common::events::filter::FrontendEventFilterStats {
+    crate::common::EVENT_SYSTEM.frontend_event_filter_stats()
+}
+
+/// 没有跨源隔离时降级的功能：线程池退回顺序执行，插件服务调用改为异步消息（拿不到返回值），图片按数组传给插件
+pub const CROSS_ORIGIN_DEGRADED_FEATURES: &[&str] = &["thread_pool", "plugin_sync_calls", "plugin_shared_images"];
+
+static CROSS_ORIGIN_WARNING_SENT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 记录页面是否跨源隔离，不隔离时第一次调用发送system:cross_origin_isolation_unavailable，返回isolated
+pub fn report_cross_origin_isolation(isolated: bool) -> bool {
+    use crate::common::events::schema::{BusinessEvent, CrossOriginIsolationUnavailableEvent};
+
+    if !isolated && !CROSS_ORIGIN_WARNING_SENT.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        crate::common::Logger::warn("Page is not cross-origin isolated, SharedArrayBuffer features are disabled");
+        let degraded = CROSS_ORIGIN_DEGRADED_FEATURES.iter().map(|feature| feature.to_string()).collect();
+        let _ = CrossOriginIsolationUnavailableEvent::new(degraded).emit();
+    }
+    isolated
+}
+
+/// 获取内存中最近的日志（按时间顺序），level_filter为最低级别，供调试窗口显示
+/// 不记录调用本身，避免刷新日志时产生新的日志
+pub fn get_recent_logs(level_filter: Option<crate::common::LogLevel>, limit: usize) -> Vec<crate::common::LogRecord> {
+    crate::common::logger::get_recent_logs(level_filter, limit)
+}
+
+/// 运行时修改记录的最低日志级别
+pub fn set_log_level(level: crate::common::LogLevel) {
+    log_function_call("set_log_level", Some(serde_json::json!({"level": level})));
+    crate::common::logger::set_log_level(level);
+}
+
+/// 当前记录的最低日志级别
+pub fn get_log_level() -> crate::common::LogLevel {
+    crate::common::logger::get_log_level()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::common::events::{Event, EventEmitter};
+    use crate::common::EVENT_SYSTEM;
+
+    struct IsolationRecorder(Arc<Mutex<Vec<serde_json::Value>>>);
+
+    impl EventEmitter for IsolationRecorder {
+        fn emit(&self, event: Event) -> Result<(), String> {
+            if event.event_name == "system:cross_origin_isolation_unavailable" {
+                self.0.lock().unwrap().push(event.data);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_missing_isolation_warns_once() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        EVENT_SYSTEM.register_emitter("isolation_test".to_string(), Box::new(IsolationRecorder(events.clone())));
+
+        assert!(report_cross_origin_isolation(true));
+        assert!(events.lock().unwrap().is_empty());
+
+        // 只有第一次检测到不隔离时发送警告
+        assert!(!report_cross_origin_isolation(false));
+        assert!(!report_cross_origin_isolation(false));
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["degraded"], serde_json::json!(CROSS_ORIGIN_DEGRADED_FEATURES));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::common::{log_function_call, ImageId};
+use crate::common::{log_function_call, ImageId, ProjectId};
 use crate::service::image::{ThumbnailData, request_thumbnail as service_request_thumbnail, 
                             request_thumbnails_batch as service_request_thumbnails_batch,
                             get_thumbnail as service_get_thumbnail,
-                            has_thumbnail as service_has_thumbnail};
+                            has_thumbnail as service_has_thumbnail,
+                            get_thumbnail_order as service_get_thumbnail_order,
+                            prefetch_around as service_prefetch_around,
+                            DEFAULT_PREFETCH_RADIUS};
 
 pub fn request_thumbnail(image_id: u32) -> Result<(), String> {
     log_function_call("request_thumbnail", Some(serde_json::json!({"image_id": image_id})));
@@ -28,4 +31,19 @@ pub fn has_thumbnail(image_id: u32) -> bool {
     log_function_call("has_thumbnail", Some(serde_json::json!({"image_id": image_id})));
     service_has_thumbnail(ImageId::from(image_id))
         .unwrap_or(false)
-}
\ No newline at end of file
+}
+
+pub fn get_thumbnail_order(project_id: u32) -> Vec<u32> {
+    log_function_call("get_thumbnail_order", Some(serde_json::json!({"project_id": project_id})));
+    service_get_thumbnail_order(ProjectId::from(project_id))
+        .map(|ids| ids.into_iter().map(|id| id.0).collect())
+        .unwrap_or_default()
+}
+
+/// 选中图片时调用，预取前后radius张（默认DEFAULT_PREFETCH_RADIUS）的缩略图
+pub fn prefetch_thumbnails_around(image_id: u32, radius: Option<u32>) -> Result<(), String> {
+    log_function_call("prefetch_thumbnails_around", Some(serde_json::json!({"image_id": image_id, "radius": radius})));
+    let radius = radius.map(|r| r as usize).unwrap_or(DEFAULT_PREFETCH_RADIUS);
+    service_prefetch_around(ImageId::from(image_id), radius)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,18 @@
+use crate::common::log_function_call;
+use crate::service::menu::{self, MenuSection};
+use crate::service::keymap;
+
+/// 菜单结构（分区、菜单项、当前快捷键），原生菜单和虚拟菜单栏共用
+pub fn get_menu_definition() -> Result<Vec<MenuSection>, String> {
+    log_function_call("get_menu_definition", None);
+    menu::get_menu_definition().map_err(|e| e.to_string())
+}
+
+/// 修改单个菜单项的快捷键（None表示取消绑定），返回更新后的菜单结构
+/// 失败时错误为AcceleratorError的JSON，冲突时包含占用该快捷键的菜单项
+pub fn set_accelerator(item_id: &str, accelerator: Option<String>) -> Result<Vec<MenuSection>, String> {
+    log_function_call("set_accelerator", Some(serde_json::json!({"item_id": item_id, "accelerator": &accelerator})));
+    keymap::set_accelerator(item_id, accelerator)
+        .map_err(|e| e.to_json_string())?;
+    menu::get_menu_definition().map_err(|e| e.to_string())
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::common::{Logger, log_function_call};
+use crate::common::{Logger, ProjectId, log_function_call};
 use crate::service::get_service;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +53,38 @@ pub fn redo(project_id: u32) -> UndoRedoResult {
     result
 }
 
+/// 开始撤销分组，到end_undo_group为止的操作合并为一次撤销
+/// 嵌套调用并入最外层分组，只有最外层的说明生效
+pub fn begin_undo_group(project_id: u32, description: String) -> Result<(), String> {
+    log_function_call("begin_undo_group", Some(serde_json::json!({ "project_id": project_id, "description": description })));
+    get_service().undo_redo_service.begin_group(ProjectId::from(project_id), description)
+        .map_err(|e| e.to_string())
+}
+
+/// 结束一层撤销分组，没有进行中的分组时报错
+pub fn end_undo_group(project_id: u32) -> Result<(), String> {
+    log_function_call("end_undo_group", Some(serde_json::json!({ "project_id": project_id })));
+    get_service().undo_redo_service.end_group(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 放弃撤销分组并撤回分组内的修改（前端的批量操作中途失败时调用），返回是否有进行中的分组
+pub fn abort_undo_group(project_id: u32) -> Result<bool, String> {
+    log_function_call("abort_undo_group", Some(serde_json::json!({ "project_id": project_id })));
+    get_service().undo_redo_service.abort_group(ProjectId::from(project_id))
+        .map_err(|e| e.to_string())
+}
+
+/// 设置撤销合并窗口（毫秒）：连续修改同一标记的位置或译文、间隔小于窗口时合并为一次撤销，0表示不合并
+pub fn set_undo_coalesce_window(window_ms: u32) {
+    log_function_call("set_undo_coalesce_window", Some(serde_json::json!({ "window_ms": window_ms })));
+    get_service().undo_redo_service.set_coalesce_window(window_ms as u64);
+}
+
+pub fn get_undo_coalesce_window() -> u32 {
+    get_service().undo_redo_service.coalesce_window().min(u32::MAX as u64) as u32
+}
+
 /// 清空撤销重做历史
 pub fn clear_undo_redo_history(project_id: u32) {
     log_function_call("clear_undo_redo_history", Some(serde_json::json!({ "project_id": project_id })));
@@ -65,4 +97,27 @@ pub fn clear_all_undo_redo_history() {
     log_function_call("clear_all_undo_redo_history", None);
     let service = get_service();
     service.undo_redo_service.clear_all_history();
-}
\ No newline at end of file
+}
+
+/// 记录项目已保存（写入文件成功后调用），用于判断是否有未保存的修改
+pub fn mark_project_saved(project_id: u32) -> bool {
+    log_function_call("mark_project_saved", Some(serde_json::json!({ "project_id": project_id })));
+    get_service().undo_redo_service.mark_saved(ProjectId::from(project_id)).is_ok()
+}
+
+/// 项目自上次保存后是否有修改
+pub fn has_unsaved_changes(project_id: u32) -> bool {
+    log_function_call("has_unsaved_changes", Some(serde_json::json!({ "project_id": project_id })));
+    get_service().undo_redo_service.has_unsaved_changes(ProjectId::from(project_id))
+}
+
+/// 所有有未保存修改的项目，退出应用前确认用
+pub fn get_unsaved_project_ids() -> Vec<u32> {
+    log_function_call("get_unsaved_project_ids", None);
+    let service = get_service();
+    service.project_service.get_all_projects()
+        .into_iter()
+        .filter(|project| service.undo_redo_service.has_unsaved_changes(project.id))
+        .map(|project| project.id.0)
+        .collect()
+}
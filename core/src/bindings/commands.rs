@@ -0,0 +1,396 @@
+// 命令清单 - 桌面端webview可调用的core命令都在这里登记一次
+// invoke handler（generate_core_commands!/generate_all_commands!）和命令清单都由这张表生成，不会再出现两处列表不一致
+// 添加新命令：在bindings.rs实现tauri_xxx命令后，在__core_command_table!中加一行 名称(参数) "说明";
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandParam {
+    /// 前端调用时的参数名（Tauri会把snake_case参数转为camelCase）
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandInfo {
+    pub name: String,
+    pub params: Vec<CommandParam>,
+    pub description: String,
+    /// 当前构建是否允许webview调用
+    pub enabled: bool,
+}
+
+/// 命令过滤：用于构建只读/展示版本等受限的桌面端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFilter {
+    All,
+    Allow(&'static [&'static str]),
+    Deny(&'static [&'static str]),
+}
+
+impl CommandFilter {
+    pub fn permits(&self, command: &str) -> bool {
+        match self {
+            CommandFilter::All => true,
+            CommandFilter::Allow(names) => names.contains(&command),
+            CommandFilter::Deny(names) => !names.contains(&command),
+        }
+    }
+}
+
+static ACTIVE_FILTER: OnceCell<CommandFilter> = OnceCell::new();
+
+/// 由generate_all_commands!在创建handler时设置，只有第一次设置生效
+pub fn set_active_filter(filter: CommandFilter) {
+    let _ = ACTIVE_FILTER.set(filter);
+}
+
+pub fn active_filter() -> CommandFilter {
+    ACTIVE_FILTER.get().copied().unwrap_or(CommandFilter::All)
+}
+
+fn to_camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// stringify!输出的类型带空格（Option < String >），整理成常见写法
+fn tidy_type(ty: &str) -> String {
+    ty.replace(" <", "<").replace("< ", "<").replace(" >", ">")
+}
+
+/// 所有core命令的名称、参数和说明，enabled按当前生效的过滤规则计算
+pub fn command_manifest() -> Vec<CommandInfo> {
+    let filter = active_filter();
+    crate::__core_command_table!(manifest)
+        .iter()
+        .map(|(name, params, description)| CommandInfo {
+            name: name.to_string(),
+            params: params.iter()
+                .map(|(param, ty)| CommandParam { name: to_camel_case(param), ty: tidy_type(ty) })
+                .collect(),
+            description: description.to_string(),
+            enabled: filter.permits(name),
+        })
+        .collect()
+}
+
+/// 固定generate_handler!生成的闭包的参数类型，使其可以被另一个闭包包装
+#[cfg(feature = "tauri")]
+pub fn typed_handler<R, F>(handler: F) -> F
+where
+    R: tauri::Runtime,
+    F: Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+{
+    handler
+}
+
+/// core命令表，参数类型仅用于清单展示
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __core_command_table {
+    ($($mode:tt)*) => {
+        $crate::__core_command_expand! { ($($mode)*)
+            // 临时项目命令
+            tauri_create_empty_opening_project(project_name: String, template: Option<String>) "创建空的临时项目（用于新建项目），可选套用项目模板";
+            tauri_create_opening_project_from_path(path: String, project_name: String) "通过文件路径创建临时项目（自动检测文件类型）";
+            tauri_create_opening_project_from_binary(data: Vec<u8>, file_extension: String, project_name: String) "通过二进制数据创建临时项目（需要提供文件扩展名）";
+            tauri_create_opening_project_from_archive(data: Vec<u8>, archive_name: String, project_name: String, operation_id: Option<String>) "通过zip/cbz压缩包数据创建临时项目，返回导入报告（包含跳过的非图片条目），可取消";
+            tauri_create_opening_project_from_archive_path(path: String, project_name: String, operation_id: Option<String>) "通过压缩包路径创建临时项目（桌面端），可取消";
+            tauri_get_opening_project_info(project_id: u32) "获取临时项目信息";
+            tauri_flush_opening_project_images(project_id: u32) "刷新临时项目的图片";
+            tauri_finalize_opening_project(project_id: u32) "将临时项目转为正式项目";
+            tauri_set_opening_project_preserve_order(project_id: u32, preserve_order: bool) "设置是否保持图片上传顺序（默认在finalize时按文件名自然排序）";
+            tauri_set_opening_project_labelplus_origin(project_id: u32, origin: LabelplusCoordinateOrigin) "设置导入labelplus标记时的坐标原点（默认左上角），在finalize时生效";
+            tauri_preview_opening_project_labelplus_origin(project_id: u32, origin: LabelplusCoordinateOrigin) "预览按某个坐标原点导入时翻译文件中第一个标记的位置";
+            tauri_delete_opening_project(project_id: u32) "删除临时项目";
+            tauri_cancel_opening_project(opening_id: u32) "取消正在打开的项目，已加入的图片会被删除";
+
+            // 项目命令
+            tauri_get_project_info(project_id: u32) "获取项目信息";
+            tauri_get_all_projects_info() "获取所有项目";
+            tauri_update_project_name(project_id: u32, name: String) "更新项目名称（带撤销功能）";
+            tauri_update_project_languages(project_id: u32, source_language: Language, target_language: Language) "更新项目语言（带撤销功能）";
+            tauri_delete_project(project_id: u32) "删除项目";
+            tauri_set_project_file_watch(project_id: u32, enabled: bool) "开关桌面端对项目图片文件变化的监听";
+            tauri_set_service_enabled(project_id: u32, service_id: String, enabled: bool) "在项目中启用/停用OCR/翻译服务（保存在bf中）";
+            tauri_get_project_glossary(project_id: u32) "获取项目术语表（术语译法和禁用词）";
+            tauri_set_project_glossary(project_id: u32, glossary: ProjectGlossary) "替换项目术语表（保存在bf中），翻译插件据此校验译文";
+            tauri_get_project_notes(project_id: u32) "获取项目备注";
+            tauri_set_project_notes(project_id: u32, notes: String) "设置项目备注（保存在bf中，LabelPlus导出时写入项目注释），发送project:settings_changed";
+            tauri_set_service_fallbacks(project_id: u32, ocr: Vec<String>, translation: Vec<String>) "设置OCR/翻译失败时依次改用的服务，发送project:settings_changed";
+            tauri_get_keep_translation_history(project_id: u32) "获取是否把译文历史保存到bf中";
+            tauri_set_keep_translation_history(project_id: u32, enabled: bool) "开关是否把译文历史保存到bf中（默认关闭），发送project:settings_changed";
+            tauri_get_grayscale_reencode(project_id: u32) "获取打开项目时是否把灰度图片重新编码为灰度PNG";
+            tauri_set_grayscale_reencode(project_id: u32, enabled: bool) "开关打开项目时灰度图片的重新编码（默认关闭），发送project:settings_changed";
+            tauri_get_auto_ocr_on_create(project_id: u32) "获取新建矩形标记时是否自动OCR";
+            tauri_set_auto_ocr_on_create(project_id: u32, enabled: bool) "开关新建矩形标记时的自动OCR（使用项目默认OCR服务），发送project:settings_changed";
+            tauri_get_reading_direction(project_id: u32) "获取项目的阅读方向（leftToRight/rightToLeft）";
+            tauri_set_reading_direction(project_id: u32, direction: ReadingDirection) "设置项目的阅读方向（可撤销），只影响派生的标记顺序";
+            tauri_check_integrity(project_id: u32, repair: bool) "检查项目中图片、标记和翻译缓存的引用是否一致，repair为true时修复可安全修复的问题（可撤销）";
+            tauri_duplicate_project(project_id: u32, new_name: String) "复制为独立的新项目（共享图片数据，无文件路径），返回新项目ID";
+            tauri_find_duplicate_images(project_id: u32, threshold: Option<u32>) "按感知哈希查找重复图片，返回分组和相似度，计算中发送image:duplicate_scan_progress";
+            tauri_remove_duplicate_images(project_id: u32, keep_strategy: DuplicateKeepStrategy, threshold: Option<u32>, force: bool) "删除重复图片（每组保留一张，可撤销），重复图片带标记时需force";
+            tauri_save_as_template(project_id: u32, name: String) "将项目设置保存为模板（不含图片和标记）";
+            tauri_create_project_from_template(name: String, template: String) "按模板新建项目";
+            tauri_list_templates() "列出所有模板，warnings为加载模板时忽略的未知字段等";
+            tauri_delete_template(name: String) "删除模板";
+            tauri_create_snapshot(project_id: u32, name: String) "创建命名快照（标记、设置、术语表和海兔缓存，不含图片），内容未变时返回上一个快照";
+            tauri_list_snapshots(project_id: u32) "列出项目的快照，最新的在前";
+            tauri_restore_snapshot(project_id: u32, snapshot_id: String) "还原到快照（可撤销），发送project:snapshot_restored";
+            tauri_delete_snapshot(snapshot_id: String) "删除快照";
+            tauri_set_snapshot_limit(limit: usize) "设置每个项目保留的快照数，超出时删除最旧的";
+            tauri_get_keymap() "获取所有可配置操作的快捷键";
+            tauri_set_keymap(bindings: HashMap<String, Option<String>>) "修改部分操作的快捷键（值为null表示取消绑定），存在冲突时整体不生效";
+            tauri_reset_keymap() "恢复默认快捷键";
+            tauri_get_menu_definition() "菜单结构（分区、菜单项、当前快捷键），原生菜单和虚拟菜单栏共用";
+            tauri_set_accelerator(item_id: String, accelerator: Option<String>) "修改单个菜单项的快捷键，冲突时错误为AcceleratorError的JSON";
+            tauri_get_project_images(project_id: u32) "获取项目的所有图片";
+            tauri_get_project_images_metadata(project_id: u32) "获取项目的图片元数据（不包含二进制数据）";
+            tauri_add_image_from_path_to_project(project_id: u32, path: String) "为项目添加图片（从文件路径）- 支持正式项目和临时项目";
+            tauri_add_image_from_binary_to_project(project_id: u32, format_str: String, data: Vec<u8>, name: Option<String>) "为项目添加图片（从二进制数据）- 支持正式项目和临时项目";
+            tauri_add_image_from_clipboard_bytes(project_id: u32, bytes: Vec<u8>, suggested_name: Option<String>) "把剪贴板中的PNG/JPEG截图追加为新页面，名称自动生成不重名";
+            tauri_add_image_from_bytes(project_id: u32, bytes: Vec<u8>, name: Option<String>) "把内存中的图片数据（粘贴、拖入）追加为新页面，格式按文件头识别，重名时自动加序号";
+            tauri_get_image_info(image_id: u32) "获取图片信息";
+            tauri_update_image_info(image_id: u32, name: Option<String>) "更新图片信息";
+            tauri_update_image_data_from_path(image_id: u32, path: String) "用文件替换图片数据";
+            tauri_update_image_data_from_binary(image_id: u32, format_str: String, data: Vec<u8>) "用二进制数据替换图片数据";
+            tauri_remove_image_from_project(project_id: u32, image_id: u32) "从项目中移除图片";
+            tauri_reorder_project_images(project_id: u32, image_ids: Vec<u32>) "重新排序项目中的图片";
+            tauri_reorder_images_natural(project_id: u32, operation_id: Option<String>) "按图片名称自然排序项目中的图片（1, 2, 10而不是1, 10, 2），作为一次可撤销的重排操作";
+            tauri_get_image_markers(image_id: u32) "获取图片的所有标记";
+            tauri_add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<CoordinateMode>) "为图片添加点型标记，mode为越界坐标的处理方式（reject/clamp，默认reject）";
+            tauri_add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<CoordinateMode>) "为图片添加矩形型标记";
+            tauri_add_polygon_marker_to_image(image_id: u32, points: Vec<(f64, f64)>, translation: Option<String>, mode: Option<CoordinateMode>) "为图片添加多边形标记（斜向文字），至少3个顶点";
+            tauri_get_marker_info(marker_id: u32) "获取标记信息";
+            tauri_update_point_marker_position(marker_id: u32, x: f64, y: f64, mode: Option<CoordinateMode>) "更新点型标记位置";
+            tauri_update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64, mode: Option<CoordinateMode>) "更新矩形型标记几何";
+            tauri_update_polygon_marker_geometry(marker_id: u32, points: Vec<(f64, f64)>, mode: Option<CoordinateMode>) "更新多边形标记的顶点";
+            tauri_begin_marker_drag(marker_id: u32) "开始拖拽标记：之后的位置更新不单独记录撤销，事件节流发出";
+            tauri_end_marker_drag(marker_id: u32, final_geometry: MarkerGeometryDTO) "结束拖拽标记：写入最终几何，整个拖拽记录为一次撤销操作";
+            tauri_move_marker_to_image(marker_id: u32, target_image_id: u32, geometry: MarkerGeometryDTO, position: Option<u32>) "把标记移到同项目的另一张图片（原图片重新编号，一次撤销操作），position为空时追加到末尾";
+            tauri_update_marker_translation(marker_id: u32, translation: String) "更新标记翻译";
+            tauri_update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) "更新标记样式";
+            tauri_update_marker_size_hint(marker_id: u32, size_hint: Option<f32>) "设置标记的相对字号（1.0为正常），null清除";
+            tauri_update_marker_tag(marker_id: u32, tag: Option<String>) "设置标记的分类标签（thought/shout/narration等），null清除";
+            tauri_update_marker_note(marker_id: u32, note: Option<String>) "设置标记的译者备注（与译文分开，可撤销），null清除";
+            tauri_get_translation_history(marker_id: u32) "获取标记最近的译文历史（最多10条，最旧的在前，带时间和来源）";
+            tauri_revert_translation(marker_id: u32, history_index: usize) "把译文改回历史中的某一条（可撤销）";
+            tauri_move_marker_order(marker_id: u32, new_index: u32) "移动标记在图片内的顺序";
+            tauri_compact_marker_indices(image_id: u32) "把图片的标记序号压缩为连续的1..N（一次撤销操作），返回改了序号的标记数";
+            tauri_compact_project_marker_indices(project_id: u32) "压缩项目内所有图片的标记序号（一次撤销操作）";
+            tauri_renumber_markers_by_reading_order(image_id: u32) "按项目的阅读方向给图片的标记重新编号（一次撤销操作），返回改了序号的标记数";
+            tauri_merge_rectangle_markers(marker_ids: Vec<u32>) "把同一图片上的多个矩形标记合并为外接矩形（一次撤销操作），返回新标记ID";
+            tauri_select_next_marker(image_id: u32, current_marker_id: Option<u32>, backward: bool) "按阅读顺序选择下一个（backward时为上一个）标记，到头时返回null";
+            tauri_update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<CoordinateMode>) "更新点型标记完整信息";
+            tauri_update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<CoordinateMode>) "更新矩形型标记完整信息";
+            tauri_remove_marker_from_image(image_id: u32, marker_id: u32) "从图片中移除标记";
+            tauri_clear_image_markers(image_id: u32) "清空图片的所有标记";
+            tauri_convert_rectangle_to_point_marker(marker_id: u32) "将矩形标记转换为点型标记（使用矩形上边的中点）";
+            tauri_convert_point_to_rectangle_marker(marker_id: u32) "将点型标记转换为矩形标记";
+            tauri_convert_polygon_to_rectangle_marker(marker_id: u32) "将多边形标记转换为其外接矩形";
+            tauri_replace_in_translations(project_id: u32, find: String, replace: String, case_sensitive: bool, whole_word: bool, operation_id: Option<String>) "在项目所有标记的翻译中查找替换，返回被修改的标记ID（整体为一次撤销操作），可取消";
+            tauri_cancel_operation(operation_id: String) "取消传入了operation_id的进行中操作（批量导入、重排、查找替换），操作返回部分结果";
+            tauri_analyze_translations(project_id: u32, source_similarity: Option<f64>) "检查项目翻译一致性（空翻译、同一原文不同译法、多余空白）";
+            tauri_get_stats() "获取所有项目的统计";
+            tauri_get_health() "心跳：递增的tick和内存占用（线性内存、图片缓存、标记数）";
+            tauri_get_event_channel_stats() "获取事件通道统计（队列长度、丢弃/积压次数、拆分的大事件），供调试窗口显示";
+            tauri_set_frontend_event_filter(patterns: Vec<String>) "设置前端订阅的业务事件（精确名称或bunny:*前缀），空列表恢复为全部发送；错误、通知、项目生命周期事件总是发送";
+            tauri_get_frontend_event_filter_stats() "获取前端事件过滤的模式和被拦下的事件计数，供调试窗口显示";
+            tauri_get_recent_logs(level_filter: Option<LogLevel>, limit: usize) "获取内存中最近的日志（按时间顺序），level_filter为最低级别，供调试窗口显示";
+            tauri_set_log_level(level: LogLevel) "运行时修改记录的最低日志级别";
+            tauri_get_log_level() "获取当前记录的最低日志级别";
+            tauri_set_audit_enabled(enabled: bool) "开关插件服务调用审计（默认关闭），记录写入日志缓冲区";
+            tauri_is_audit_enabled() "获取插件服务调用审计是否打开";
+            tauri_get_plugin_audit_log(plugin_id: Option<String>, limit: Option<usize>) "获取最近的插件服务调用记录（参数已脱敏），plugin_id为空时返回所有插件的";
+            tauri_register_plugin_config_schema(plugin_id: String, schema: Value) "登记插件配置schema，其中password字段在审计记录中脱敏";
+            tauri_get_command_manifest() "获取桌面端注册的命令清单（参数和说明），供调试窗口显示";
+            tauri_get_project_stats(project_id: u32) "获取单个项目的统计";
+            tauri_get_text_stats(project_id: u32) "获取项目的原文/译文字数统计（含每张图片的明细）";
+            tauri_set_text_count_config(cjk_char_as_word: bool) "设置CJK字符的计词方式：true为每个字计一词，false为连续的CJK文字计一词";
+            tauri_clear_all_data() "清空所有数据";
+            tauri_clear_project_data(project_id: u32) "清空特定项目的所有数据";
+            tauri_get_image_binary_data(image_id: u32) "获取图片二进制数据";
+            tauri_get_image_mime_type(image_id: u32) "获取图片MIME类型";
+            tauri_encode_image(image_id: u32, format_str: String, quality: Option<u8>) "把图片重新编码为指定格式，JPEG的quality为质量1-100，PNG为压缩级别0-9";
+            tauri_get_image_metadata(image_id: u32) "获取图片元数据（供页面信息面板显示DPI、颜色模式、文件大小等）";
+            tauri_get_image_file_path(image_id: u32) "获取图片文件路径（用于高效访问）";
+            tauri_get_image_source_kind(image_id: u32) "获取图片数据的存储方式（FilePath/Binary/SharedBuffer）";
+
+            // 缩略图命令
+            tauri_request_thumbnail(image_id: u32) "请求生成缩略图，完成后发出thumbnail_ready事件";
+            tauri_get_thumbnail(image_id: u32) "获取已生成的缩略图";
+            tauri_has_thumbnail(image_id: u32) "是否已有缩略图";
+            tauri_prefetch_thumbnails_around(image_id: u32, radius: Option<u32>) "选中图片时调用，预取前后radius张（默认DEFAULT_PREFETCH_RADIUS）的缩略图";
+            tauri_get_thumbnail_order(project_id: u32) "获取缩略图栏的图片顺序";
+            tauri_clear_thumbnail_cache() "清空桌面端缩略图磁盘缓存，返回删除的缓存文件数（WASM端没有磁盘缓存，返回0）";
+            tauri_get_thumbnail_cache_stats() "获取缩略图缓存统计（命中/未命中次数、占用大小）";
+            tauri_set_image_cache_limit(limit_mb: u32) "设置原图字节缓存容量（MB），0表示关闭缓存";
+            tauri_get_image_cache_stats() "获取原图字节缓存统计（命中/未命中次数、占用大小）";
+            tauri_get_marker_thumbnail(marker_id: u32, max_edge_px: u32) "获取标记的预览小图（JPEG，长边不超过max_edge_px），供翻译侧栏显示";
+            tauri_get_marker_thumbnails(image_id: u32, max_edge_px: u32) "批量获取图片上所有标记的预览小图，整页只解码一次";
+            tauri_invalidate_image(image_id: u32) "图片文件在磁盘上变化后重新读取（桌面端文件监听调用），返回是否确实刷新了图片";
+            tauri_validate_image_file(path: String) "导入前校验图片文件（扩展名、文件头、能否解码、大小），失败时错误为ImportError的JSON";
+            tauri_validate_image_data(name: String, data: Vec<u8>) "导入前校验图片数据（Web端），失败时错误为ImportError的JSON";
+            tauri_set_image_pinned(image_id: u32, pinned: bool) "固定/取消固定图片到缩略图栏顶部";
+            tauri_set_image_reviewed(image_id: u32, reviewed: bool, note: Option<String>) "标记图片已校对并设置校对备注";
+            tauri_set_image_locked(image_id: u32, locked: bool) "锁定/解锁图片（可撤销），锁定后修改其中的标记返回ImageLockedError的JSON";
+            tauri_rename_image(image_id: u32, new_name: String) "重命名图片（可撤销），名称为空或与项目中其它图片重名时报错";
+            tauri_set_image_view_state(image_id: u32, view_state: Option<ImageViewState>) "保存图片的缩放/平移和最后选中的标记，null清除";
+            tauri_get_image_view_state(image_id: u32) "获取图片保存的查看状态";
+            tauri_select_image(image_id: u32) "切换到图片，有保存的查看状态时发送image:view_restored并返回";
+            tauri_set_thumbnail_sort(project_id: u32, order: ThumbnailOrder) "设置项目缩略图栏的排序方式";
+            tauri_get_downscale_settings() "获取导入时缩小超大图片的设置";
+            tauri_set_downscale_settings(settings: DownscaleSettings) "设置导入时缩小超大图片（默认关闭），只影响之后导入的图片";
+
+            // 撤销重做命令
+            tauri_undo(project_id: u32) "执行撤销操作";
+            tauri_redo(project_id: u32) "执行重做操作";
+            tauri_clear_undo_redo_history(project_id: u32) "清空撤销重做历史";
+            tauri_begin_undo_group(project_id: u32, description: String) "开始撤销分组，期间的操作合并为一次撤销";
+            tauri_end_undo_group(project_id: u32) "结束撤销分组";
+            tauri_abort_undo_group(project_id: u32) "放弃撤销分组并撤回分组内的修改";
+            tauri_clear_all_undo_redo_history() "清空所有项目的撤销重做历史";
+            tauri_set_undo_coalesce_window(window_ms: u32) "设置撤销合并窗口（毫秒），连续修改同一标记的位置或译文合并为一次撤销，0表示不合并";
+            tauri_get_undo_coalesce_window() "获取撤销合并窗口（毫秒）";
+            tauri_mark_project_saved(project_id: u32) "记录项目已保存（写入文件成功后调用）";
+            tauri_has_unsaved_changes(project_id: u32) "项目自上次保存后是否有修改";
+            tauri_get_unsaved_project_ids() "所有有未保存修改的项目，退出应用前确认用";
+
+            // bf文件预检命令
+            tauri_validate_bf_file(path: String) "打开前预检bf文件（格式版本、图片和标记数、是否截断），失败时错误为BfFormatError的JSON";
+            tauri_validate_bf_data(data: Vec<u8>) "打开前预检bf文件数据，失败时错误为BfFormatError的JSON";
+
+            // 丢失图片命令
+            tauri_get_missing_images(project_id: u32) "项目中文件已不存在的图片（项目文件夹被移动后）";
+            tauri_relink_image(image_id: u32, path: String) "把丢失的图片重新链接到新路径，返回新文件的校验和是否与原来一致";
+
+            // LabelPlus文件命令
+            tauri_validate_labelplus_file(content: String) "校验LabelPlus文件（不导入）";
+            tauri_import_labelplus_data(project_id: u32, content: String) "导入LabelPlus文件";
+            tauri_import_labelplus_merge(project_id: u32, content: String, options: Option<LabelplusMergeOptions>) "合并导入LabelPlus文件，不覆盖已有标记，返回新增和跳过的标记";
+            tauri_export_labelplus_data(project_id: u32) "导出LabelPlus文件";
+            tauri_export_labelplus_data_range(project_id: u32, image_ids: Vec<u32>) "只导出指定图片（如一个章节）的LabelPlus文件";
+            tauri_export_annotated_images(project_id: u32, dir: String, options: Option<AnnotateOptions>) "导出参考图并逐张写入目录，返回写入的文件路径";
+            tauri_export_typesetting_data(project_id: u32, format: TypesettingFormat, options: Option<TypesettingOptions>) "导出PS嵌字脚本使用的CSV/JSON（像素坐标）";
+            tauri_get_project_content_hash(project_id: u32) "项目内容哈希（标记、样式、译文和图片顺序），不含内部ID，可用于判断是否需要重新导出";
+            tauri_import_typesetting_data(project_id: u32, content: String, format: TypesettingFormat, options: Option<TypesettingImportOptions>) "把改过译文的嵌字CSV/JSON导回项目，整体为一次撤销操作";
+
+            // 项目保存命令
+            tauri_save_project(project_id: u32) "保存项目为BF数据";
+            tauri_save_project_range(project_id: u32, image_ids: Vec<u32>) "只把指定图片导出为BF数据";
+            tauri_export_recovery_snapshot(project_id: u32) "导出恢复快照（不含图片的BF数据）";
+            tauri_import_recovery_snapshot(data: Vec<u8>) "从恢复快照重建项目，图片为丢失状态，返回新项目ID";
+
+            // Bunny (海兔) OCR and translation commands
+            tauri_get_available_ocr_services(project_id: Option<u32>) "Get available OCR services from plugins (with plugin_id included)，传入project_id时只返回该项目启用的服务";
+            tauri_get_available_translation_services(project_id: Option<u32>) "Get available translation services from plugins (with plugin_id included)，传入project_id时只返回该项目启用的服务";
+            tauri_request_ocr(marker_id: u32, image_id: u32, project_id: u32, service_id: String) "Request OCR processing for a marker";
+            tauri_request_ocr_batch(marker_ids: Vec<u32>, project_id: u32, service_id: String) "批量请求OCR，返回每个标记的任务ID或错误";
+            tauri_request_pipeline(marker_id: u32, image_id: u32, project_id: u32, ocr_service: String, translation_service: String) "先OCR再用识别出的原文机翻，返回流水线ID，进度见bunny:pipeline_advanced/completed/failed";
+            tauri_request_pipeline_batch(image_id: u32, ocr_service: String, translation_service: String) "为图片上的所有标记创建OCR→翻译流水线，返回每个标记的流水线ID或错误";
+            tauri_get_pipeline(pipeline_id: String) "获取流水线的当前阶段和状态";
+            tauri_get_project_pipelines(project_id: u32) "获取项目的所有OCR→翻译流水线";
+            tauri_translate_all_untranslated(project_id: u32, service_id: String, operation_id: Option<String>) "翻译项目中所有有原文没译文的标记，发送bunny:batch_progress，可用cancel_operation停止排队";
+            tauri_request_translation(marker_id: u32, image_id: u32, project_id: u32, service_id: String, text: String) "Request translation processing for a marker";
+            tauri_handle_ocr_completed(task_id: String, marker_id: u32, text: String, model: String, regions: Option<Vec<OcrRegion>>) "Handle OCR completion from plugin (called by frontend relay)，regions为插件返回的文本行位置";
+            tauri_split_marker_by_ocr_regions(marker_id: u32, regions: Option<Vec<OcrRegion>>) "按OCR文本行把标记拆成每行一个矩形标记（一次撤销），regions为空时使用最近一次OCR返回的区域";
+            tauri_handle_translation_completed(task_id: String, marker_id: u32, translated_text: String, service: String, warnings: Option<Vec<TranslationWarning>>, metadata: Option<Value>) "Handle translation completion from plugin (called by frontend relay)，warnings为插件的术语校验结果，metadata为插件附带的模型等信息";
+            tauri_handle_task_failed(task_id: String, error: String) "Handle task failure from plugin (called by frontend relay)";
+            tauri_get_available_detection_services(project_id: Option<u32>) "获取插件提供的文本框检测服务，传入project_id时只返回该项目启用的服务";
+            tauri_request_detection(image_id: u32, service_id: String) "请求检测插件找出图片中的文本框，结果以bunny:detection_completed发出";
+            tauri_handle_detection_completed(task_id: String, model: String, regions: Vec<DetectedRegion>) "Handle detection result from plugin (called by frontend relay)";
+            tauri_create_markers_from_detection(image_id: u32, regions: Vec<DetectedRegion>) "把检测区域生成矩形标记（一次撤销），返回新标记ID";
+            tauri_get_bunny_cache(marker_id: u32) "Get bunny cache data for a marker";
+            tauri_get_translation_result(marker_id: u32) "获取机器翻译及插件校验出的术语问题";
+            tauri_accept_machine_translation(marker_id: u32) "把机器翻译写入标记译文（可撤销），译文在机翻之后被修改过时拒绝";
+            tauri_accept_all_for_image(image_id: u32, only_empty: bool) "接受图片上所有标记的机器翻译（一次撤销），返回接受和跳过的标记";
+            tauri_reject_machine_translation(marker_id: u32) "丢弃标记的机器翻译";
+            tauri_update_original_text(marker_id: u32, text: String, model: String) "Update original text in bunny cache";
+            tauri_update_machine_translation(marker_id: u32, text: String, service: String) "Update machine translation in bunny cache";
+            tauri_clear_bunny_cache(marker_id: u32) "Clear bunny cache for a marker";
+            tauri_get_task_history(project_id: u32, limit: Option<usize>) "Get recently finished OCR/translation tasks of a project, newest first";
+            tauri_get_task_metrics(project_id: u32) "Get per-service success/failure counts and durations from the task history";
+            tauri_get_image_task_progress(image_id: u32) "获取图片上排队/进行中/完成/失败的任务数";
+            tauri_clear_task_history(project_id: u32) "Clear the task history of a project";
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __core_command_expand {
+    ((handler [$($extra:path),*]) $( $name:ident ( $($param:ident : $ty:ty),* ) $desc:literal; )*) => {
+        tauri::generate_handler![
+            $($extra,)*
+            $( $crate::bindings::tauri::$name ),*
+        ]
+    };
+    ((manifest) $( $name:ident ( $($param:ident : $ty:ty),* ) $desc:literal; )*) => {
+        [ $( (stringify!($name), &[ $( (stringify!($param), stringify!($ty)) ),* ] as &[(&str, &str)], $desc) ),* ]
+    };
+}
+
+/// 生成带过滤的handler：被过滤的命令直接拒绝，不会进入命令实现
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __filtered_core_handler {
+    ($filter:expr, [$($extra:path),*]) => {{
+        let filter: $crate::bindings::commands::CommandFilter = $filter;
+        $crate::bindings::commands::set_active_filter(filter);
+        let handler = $crate::bindings::commands::typed_handler($crate::__core_command_table!(handler [$($extra),*]));
+        move |invoke: tauri::ipc::Invoke<_>| -> bool {
+            if !filter.permits(invoke.message.command()) {
+                let command = invoke.message.command().to_string();
+                invoke.resolver.reject(format!("Command '{}' is not available in this build", command));
+                return true;
+            }
+            handler(invoke)
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_lists_commands_with_params() {
+        let manifest = command_manifest();
+        let names: Vec<&str> = manifest.iter().map(|c| c.name.as_str()).collect();
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), names.len(), "duplicate command in table");
+
+        let add_point = manifest.iter().find(|c| c.name == "tauri_add_point_marker_to_image").unwrap();
+        let params: Vec<(&str, &str)> = add_point.params.iter().map(|p| (p.name.as_str(), p.ty.as_str())).collect();
+        assert_eq!(params, vec![("imageId", "u32"), ("x", "f64"), ("y", "f64"), ("translation", "Option<String>"), ("mode", "Option<CoordinateMode>")]);
+        assert!(!add_point.description.is_empty());
+        assert!(manifest.iter().all(|c| c.enabled));
+    }
+
+    #[test]
+    fn test_filter_allow_and_deny() {
+        let allow = CommandFilter::Allow(&["tauri_get_project_info", "tauri_get_image_markers"]);
+        assert!(allow.permits("tauri_get_project_info"));
+        assert!(!allow.permits("tauri_delete_project"));
+
+        let deny = CommandFilter::Deny(&["tauri_delete_project"]);
+        assert!(!deny.permits("tauri_delete_project"));
+        assert!(deny.permits("tauri_get_project_info"));
+        assert!(CommandFilter::All.permits("anything"));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod commands;
+
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
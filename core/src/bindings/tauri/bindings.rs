@@ -11,24 +11,28 @@ use crate::api::{create_opening_project_from_path, create_opening_project_from_b
 use crate::api::marker::{
     add_point_marker_to_image, add_rectangle_marker_to_image,
     update_point_marker_position, update_rectangle_marker_geometry,
+    begin_marker_drag, end_marker_drag,
     update_point_marker_full, update_rectangle_marker_full,
-    convert_rectangle_to_point_marker, convert_point_to_rectangle_marker
+    convert_rectangle_to_point_marker, convert_point_to_rectangle_marker,
+    add_polygon_marker_to_image, update_polygon_marker_geometry, convert_polygon_to_rectangle_marker
 };
 #[cfg(feature = "tauri")]
 use crate::api::bunny::{
     get_available_ocr_services, get_available_translation_services,
-    request_ocr, request_translation,
+    request_ocr, request_ocr_batch, request_translation, translate_all_untranslated,
+    request_pipeline, request_pipeline_batch, get_pipeline, get_project_pipelines,
     handle_ocr_completed, handle_translation_completed, handle_task_failed,
-    get_bunny_cache, update_original_text, update_machine_translation, clear_bunny_cache
+    get_bunny_cache, get_translation_result, update_original_text, update_machine_translation, clear_bunny_cache,
+    get_task_history, get_task_metrics, clear_task_history, get_image_task_progress
 };
 #[cfg(feature = "tauri")]
-use crate::common::dto::image::{ImageDataDTO, ImageFormat};
+use crate::common::dto::image::{ImageDataDTO, ImageFormat, ImageSourceKind};
 
 // 临时项目相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_create_empty_opening_project(project_name: String) -> Result<u32, String> {
-    create_empty_opening_project(project_name)
+pub fn tauri_create_empty_opening_project(project_name: String, template: Option<String>) -> Result<u32, String> {
+    create_empty_opening_project(project_name, template)
 }
 
 #[cfg(feature = "tauri")]
@@ -44,6 +48,18 @@ pub fn tauri_create_opening_project_from_binary(data: Vec<u8>, file_extension: S
     create_opening_project_from_binary(data, file_extension, project_name)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command(async)]
+pub fn tauri_create_opening_project_from_archive(data: Vec<u8>, archive_name: String, project_name: String, operation_id: Option<String>) -> Result<crate::service::io::archive::ArchiveImportReport, String> {
+    create_opening_project_from_archive(data, archive_name, project_name, operation_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command(async)]
+pub fn tauri_create_opening_project_from_archive_path(path: String, project_name: String, operation_id: Option<String>) -> Result<crate::service::io::archive::ArchiveImportReport, String> {
+    create_opening_project_from_archive_path(PathBuf::from(path), project_name, operation_id)
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_get_opening_project_info(project_id: u32) -> Option<crate::common::dto::opening_project::OpeningProjectDTO> {
@@ -62,12 +78,36 @@ pub fn tauri_finalize_opening_project(project_id: u32) -> bool {
     finalize_opening_project(project_id)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_opening_project_preserve_order(project_id: u32, preserve_order: bool) -> bool {
+    set_opening_project_preserve_order(project_id, preserve_order)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_opening_project_labelplus_origin(project_id: u32, origin: crate::service::io::labelplus::LabelplusCoordinateOrigin) -> bool {
+    set_opening_project_labelplus_origin(project_id, origin)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_preview_opening_project_labelplus_origin(project_id: u32, origin: crate::service::io::labelplus::LabelplusCoordinateOrigin) -> Option<crate::service::io::labelplus::LabelplusOriginPreview> {
+    preview_opening_project_labelplus_origin(project_id, origin)
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_delete_opening_project(project_id: u32) -> bool {
     delete_opening_project(project_id)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_cancel_opening_project(opening_id: u32) -> bool {
+    cancel_opening_project(opening_id)
+}
+
 // 项目相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
@@ -99,6 +139,208 @@ pub fn tauri_delete_project(project_id: u32) -> bool {
     delete_project(project_id)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_project_file_watch(project_id: u32, enabled: bool) -> bool {
+    set_project_file_watch(project_id, enabled)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_service_enabled(project_id: u32, service_id: String, enabled: bool) -> bool {
+    set_service_enabled(project_id, service_id, enabled)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_project_glossary(project_id: u32) -> Option<crate::common::ProjectGlossary> {
+    get_project_glossary(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_project_glossary(project_id: u32, glossary: crate::common::ProjectGlossary) -> bool {
+    set_project_glossary(project_id, glossary)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_project_notes(project_id: u32) -> Option<String> {
+    get_project_notes(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_project_notes(project_id: u32, notes: String) -> bool {
+    set_project_notes(project_id, notes)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_service_fallbacks(project_id: u32, ocr: Vec<String>, translation: Vec<String>) -> bool {
+    set_service_fallbacks(project_id, ocr, translation)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_keep_translation_history(project_id: u32) -> Option<bool> {
+    get_keep_translation_history(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_keep_translation_history(project_id: u32, enabled: bool) -> bool {
+    set_keep_translation_history(project_id, enabled)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_grayscale_reencode(project_id: u32) -> Option<bool> {
+    get_grayscale_reencode(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_grayscale_reencode(project_id: u32, enabled: bool) -> bool {
+    set_grayscale_reencode(project_id, enabled)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_auto_ocr_on_create(project_id: u32) -> Option<bool> {
+    get_auto_ocr_on_create(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_auto_ocr_on_create(project_id: u32, enabled: bool) -> bool {
+    set_auto_ocr_on_create(project_id, enabled)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_reading_direction(project_id: u32) -> Option<crate::common::ReadingDirection> {
+    get_reading_direction(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_reading_direction(project_id: u32, direction: crate::common::ReadingDirection) -> bool {
+    set_reading_direction(project_id, direction)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_check_integrity(project_id: u32, repair: bool) -> Result<crate::service::project::IntegrityReport, String> {
+    check_integrity(project_id, repair)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_duplicate_project(project_id: u32, new_name: String) -> Result<u32, String> {
+    duplicate_project(project_id, new_name)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_find_duplicate_images(project_id: u32, threshold: Option<u32>) -> Result<Vec<crate::service::project::DuplicateGroup>, String> {
+    find_duplicate_images(project_id, threshold)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_remove_duplicate_images(project_id: u32, keep_strategy: crate::service::project::DuplicateKeepStrategy, threshold: Option<u32>, force: bool) -> Result<Vec<u32>, String> {
+    remove_duplicate_images(project_id, keep_strategy, threshold, force)
+}
+
+// 项目模板命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_save_as_template(project_id: u32, name: String) -> Result<crate::service::project::ProjectTemplate, String> {
+    save_as_template(project_id, name)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_create_project_from_template(name: String, template: String) -> Result<u32, String> {
+    create_project_from_template(name, template)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_list_templates() -> Result<crate::service::project::TemplateList, String> {
+    list_templates()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_delete_template(name: String) -> Result<bool, String> {
+    delete_template(name)
+}
+
+// 命名快照命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_create_snapshot(project_id: u32, name: String) -> Result<crate::service::project::snapshot::SnapshotInfo, String> {
+    crate::api::snapshot::create(project_id, name)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_list_snapshots(project_id: u32) -> Result<Vec<crate::service::project::snapshot::SnapshotInfo>, String> {
+    crate::api::snapshot::list(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_restore_snapshot(project_id: u32, snapshot_id: String) -> Result<(), String> {
+    crate::api::snapshot::restore(project_id, snapshot_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_delete_snapshot(snapshot_id: String) -> Result<bool, String> {
+    crate::api::snapshot::delete(snapshot_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_snapshot_limit(limit: usize) -> Result<(), String> {
+    crate::api::snapshot::set_limit(limit)
+}
+
+// 快捷键配置命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_keymap() -> Result<Vec<crate::service::keymap::KeyBinding>, String> {
+    get_keymap()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_keymap(bindings: std::collections::HashMap<String, Option<String>>) -> Result<Vec<crate::service::keymap::KeyBinding>, String> {
+    set_keymap(bindings)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_reset_keymap() -> Result<Vec<crate::service::keymap::KeyBinding>, String> {
+    reset_keymap()
+}
+
+// 菜单定义命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_menu_definition() -> Result<Vec<crate::service::menu::MenuSection>, String> {
+    get_menu_definition()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_accelerator(item_id: String, accelerator: Option<String>) -> Result<Vec<crate::service::menu::MenuSection>, String> {
+    set_accelerator(&item_id, accelerator)
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_get_project_images(project_id: u32) -> Vec<crate::common::dto::image::ImageDTO> {
@@ -136,6 +378,18 @@ pub fn tauri_add_image_from_binary_to_project(project_id: u32, format_str: Strin
     }
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_add_image_from_clipboard_bytes(project_id: u32, bytes: Vec<u8>, suggested_name: Option<String>) -> Result<u32, String> {
+    add_image_from_clipboard_bytes(project_id, bytes, suggested_name)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_add_image_from_bytes(project_id: u32, bytes: Vec<u8>, name: Option<String>) -> Result<u32, String> {
+    add_image_from_bytes(project_id, bytes, name)
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_get_image_info(image_id: u32) -> Option<crate::common::dto::image::ImageDTO> {
@@ -186,6 +440,12 @@ pub fn tauri_reorder_project_images(project_id: u32, image_ids: Vec<u32>) -> boo
     reorder_project_images(project_id, image_ids)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command(async)]
+pub fn tauri_reorder_images_natural(project_id: u32, operation_id: Option<String>) -> bool {
+    reorder_images_natural(project_id, operation_id)
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_get_image_markers(image_id: u32) -> Vec<crate::common::dto::marker::MarkerDTO> {
@@ -195,15 +455,22 @@ pub fn tauri_get_image_markers(image_id: u32) -> Vec<crate::common::dto::marker:
 // 标记相关命令 - 点型marker
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>) -> Option<u32> {
-    add_point_marker_to_image(image_id, x, y, translation)
+pub fn tauri_add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<Option<u32>, String> {
+    add_point_marker_to_image(image_id, x, y, translation, mode)
 }
 
 // 标记相关命令 - 矩形型marker
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> Option<u32> {
-    add_rectangle_marker_to_image(image_id, x, y, width, height, translation)
+pub fn tauri_add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<Option<u32>, String> {
+    add_rectangle_marker_to_image(image_id, x, y, width, height, translation, mode)
+}
+
+// 标记相关命令 - 多边形marker
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_add_polygon_marker_to_image(image_id: u32, points: Vec<(f64, f64)>, translation: Option<String>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<Option<u32>, String> {
+    add_polygon_marker_to_image(image_id, points, translation, mode)
 }
 
 #[cfg(feature = "tauri")]
@@ -214,338 +481,852 @@ pub fn tauri_get_marker_info(marker_id: u32) -> Option<crate::common::dto::marke
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_point_marker_position(marker_id: u32, x: f64, y: f64) -> bool {
-    update_point_marker_position(marker_id, x, y)
+pub fn tauri_update_point_marker_position(marker_id: u32, x: f64, y: f64, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<bool, String> {
+    update_point_marker_position(marker_id, x, y, mode)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64) -> bool {
-    update_rectangle_marker_geometry(marker_id, x, y, width, height)
+pub fn tauri_update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<bool, String> {
+    update_rectangle_marker_geometry(marker_id, x, y, width, height, mode)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_marker_translation(marker_id: u32, translation: String) -> bool {
-    update_marker_translation(marker_id, translation)
+pub fn tauri_begin_marker_drag(marker_id: u32) -> Result<bool, String> {
+    begin_marker_drag(marker_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> bool {
-    update_marker_style(marker_id, overlay_text, horizontal)
+pub fn tauri_update_polygon_marker_geometry(marker_id: u32, points: Vec<(f64, f64)>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<bool, String> {
+    update_polygon_marker_geometry(marker_id, points, mode)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_move_marker_order(marker_id: u32, new_index: u32) -> bool {
-    marker::move_marker_order(marker_id, new_index)
+pub fn tauri_end_marker_drag(marker_id: u32, final_geometry: crate::common::dto::marker::MarkerGeometryDTO) -> Result<bool, String> {
+    end_marker_drag(marker_id, final_geometry)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>) -> bool {
-    update_point_marker_full(marker_id, x, y, translation)
+pub fn tauri_move_marker_to_image(marker_id: u32, target_image_id: u32, geometry: crate::common::dto::marker::MarkerGeometryDTO, position: Option<u32>) -> Result<bool, String> {
+    crate::api::marker::move_marker_to_image(marker_id, target_image_id, geometry, position)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> bool {
-    update_rectangle_marker_full(marker_id, x, y, width, height, translation)
+pub fn tauri_update_marker_translation(marker_id: u32, translation: String) -> Result<bool, String> {
+    update_marker_translation(marker_id, translation)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_remove_marker_from_image(image_id: u32, marker_id: u32) -> bool {
-    remove_marker_from_image(image_id, marker_id)
+pub fn tauri_update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> Result<bool, String> {
+    update_marker_style(marker_id, overlay_text, horizontal)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_clear_image_markers(image_id: u32) -> bool {
-    clear_image_markers(image_id)
+pub fn tauri_update_marker_size_hint(marker_id: u32, size_hint: Option<f32>) -> Result<bool, String> {
+    update_marker_size_hint(marker_id, size_hint)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_convert_rectangle_to_point_marker(marker_id: u32) -> bool {
-    convert_rectangle_to_point_marker(marker_id)
+pub fn tauri_update_marker_tag(marker_id: u32, tag: Option<String>) -> Result<bool, String> {
+    update_marker_tag(marker_id, tag)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_convert_point_to_rectangle_marker(marker_id: u32) -> bool {
-    convert_point_to_rectangle_marker(marker_id)
+pub fn tauri_update_marker_note(marker_id: u32, note: Option<String>) -> Result<bool, String> {
+    update_marker_note(marker_id, note)
 }
 
-// 统计相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_stats() -> ProjectStats {
-    get_stats()
+pub fn tauri_get_translation_history(marker_id: u32) -> Vec<crate::storage::TranslationHistoryEntry> {
+    get_translation_history(marker_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_project_stats(project_id: u32) -> Option<SingleProjectStats> {
-    get_project_stats(project_id)
+pub fn tauri_revert_translation(marker_id: u32, history_index: usize) -> Result<bool, String> {
+    revert_translation(marker_id, history_index)
 }
 
-// 数据清理命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_clear_all_data() {
-    clear_all_data()
+pub fn tauri_move_marker_order(marker_id: u32, new_index: u32) -> Result<bool, String> {
+    marker::move_marker_order(marker_id, new_index)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_image_binary_data(image_id: u32) -> Result<Vec<u8>, String> {
-    get_image_binary_data(image_id)
+pub fn tauri_compact_marker_indices(image_id: u32) -> Result<usize, String> {
+    marker::compact_marker_indices(image_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_image_mime_type(image_id: u32) -> Option<String> {
-    get_image_mime_type(image_id)
+pub fn tauri_compact_project_marker_indices(project_id: u32) -> Result<usize, String> {
+    marker::compact_project_marker_indices(project_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_image_file_path(image_id: u32) -> Option<String> {
-    get_image_file_path(image_id)
+pub fn tauri_renumber_markers_by_reading_order(image_id: u32) -> Result<usize, String> {
+    marker::renumber_markers_by_reading_order(image_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_clear_project_data(project_id: u32) -> bool {
-    clear_project_data(project_id)
+pub fn tauri_merge_rectangle_markers(marker_ids: Vec<u32>) -> Result<u32, String> {
+    marker::merge_rectangle_markers(marker_ids)
 }
 
-// 缩略图相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_request_thumbnail(image_id: u32) -> Result<(), String> {
-    request_thumbnail(image_id)
+pub fn tauri_select_next_marker(image_id: u32, current_marker_id: Option<u32>, backward: bool) -> Result<Option<u32>, String> {
+    marker::select_next_marker(image_id, current_marker_id, backward)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_thumbnail(image_id: u32) -> Option<crate::service::image::ThumbnailData> {
-    get_thumbnail(image_id)
+pub fn tauri_update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<bool, String> {
+    update_point_marker_full(marker_id, x, y, translation, mode)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_has_thumbnail(image_id: u32) -> bool {
-    has_thumbnail(image_id)
+pub fn tauri_update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: Option<crate::storage::marker::CoordinateMode>) -> Result<bool, String> {
+    update_rectangle_marker_full(marker_id, x, y, width, height, translation, mode)
 }
 
-// 撤销重做相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_undo(project_id: u32) -> UndoRedoResult {
-    undo(project_id)
+pub fn tauri_remove_marker_from_image(image_id: u32, marker_id: u32) -> Result<bool, String> {
+    remove_marker_from_image(image_id, marker_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_redo(project_id: u32) -> UndoRedoResult {
-    redo(project_id)
+pub fn tauri_clear_image_markers(image_id: u32) -> Result<bool, String> {
+    clear_image_markers(image_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_clear_undo_redo_history(project_id: u32) {
-    clear_undo_redo_history(project_id)
+pub fn tauri_convert_rectangle_to_point_marker(marker_id: u32) -> Result<bool, String> {
+    convert_rectangle_to_point_marker(marker_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_clear_all_undo_redo_history() {
-    clear_all_undo_redo_history()
+pub fn tauri_convert_point_to_rectangle_marker(marker_id: u32) -> Result<bool, String> {
+    convert_point_to_rectangle_marker(marker_id)
 }
 
-// LabelPlus文件相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_validate_labelplus_file(content: String) -> Result<crate::service::io::labelplus::LabelplusData, String> {
-    validate_labelplus_file(&content)
+pub fn tauri_convert_polygon_to_rectangle_marker(marker_id: u32) -> Result<bool, String> {
+    convert_polygon_to_rectangle_marker(marker_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command(async)]
+pub fn tauri_replace_in_translations(project_id: u32, find: String, replace: String, case_sensitive: bool, whole_word: bool, operation_id: Option<String>) -> Result<crate::service::marker::ReplaceReport, String> {
+    replace_in_translations(project_id, find, replace, case_sensitive, whole_word, operation_id)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_import_labelplus_data(project_id: u32, content: String) -> Result<(), String> {
-    import_labelplus_data(project_id, &content)
+pub fn tauri_analyze_translations(project_id: u32, source_similarity: Option<f64>) -> Result<Vec<crate::service::marker::TranslationIssue>, String> {
+    analyze_translations(project_id, source_similarity)
 }
 
+// 统计相关命令
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_export_labelplus_data(project_id: u32) -> Result<String, String> {
-    export_labelplus_data(project_id)
+pub fn tauri_get_stats() -> ProjectStats {
+    get_stats()
 }
 
+// 可取消的命令在后台线程执行（command(async)），主线程才能收到取消请求
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_save_project(project_id: u32) -> Result<Vec<u8>, String> {
-    save_project(project_id)
+pub fn tauri_cancel_operation(operation_id: String) -> bool {
+    cancel_operation(operation_id)
 }
 
-// Tauri 命令注册辅助函数
 #[cfg(feature = "tauri")]
-pub fn register_data_commands<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
-    builder.invoke_handler(tauri::generate_handler![
-        // 临时项目命令
-        tauri_create_empty_opening_project,
-        tauri_create_opening_project_from_path,
-        tauri_create_opening_project_from_binary,
-        tauri_get_opening_project_info,
-        tauri_flush_opening_project_images,
-        tauri_finalize_opening_project,
-        tauri_delete_opening_project,
-        // 项目命令
-        tauri_get_project_info,
-        tauri_get_all_projects_info,
-        tauri_update_project_name,
-        tauri_delete_project,
-        tauri_get_project_images,
-        tauri_get_project_images_metadata,
-        // 图片命令
-        tauri_add_image_from_path_to_project,
-        tauri_add_image_from_binary_to_project,
-        tauri_get_image_info,
-        tauri_update_image_info,
-        tauri_update_image_data_from_path,
-        tauri_update_image_data_from_binary,
-        tauri_remove_image_from_project,
-        tauri_reorder_project_images,
-        tauri_get_image_markers,
-        // 标记命令
-        tauri_add_point_marker_to_image,
-        tauri_add_rectangle_marker_to_image,
-        tauri_get_marker_info,
-        tauri_update_point_marker_position,
-        tauri_update_rectangle_marker_geometry,
-        tauri_update_marker_translation,
-        tauri_update_marker_style,
-        tauri_move_marker_order,
-        tauri_update_point_marker_full,
-        tauri_update_rectangle_marker_full,
-        tauri_remove_marker_from_image,
-        tauri_clear_image_markers,
-        tauri_convert_rectangle_to_point_marker,
-        tauri_convert_point_to_rectangle_marker,
-        // 统计命令
-        tauri_get_stats,
-        tauri_get_project_stats,
-        // 清理命令
-        tauri_clear_all_data,
-        tauri_clear_project_data,
-        // 图片数据获取命令
-        tauri_get_image_binary_data,
-        tauri_get_image_mime_type,
-        tauri_get_image_file_path,
-        // 缩略图命令
-        tauri_request_thumbnail,
-        tauri_get_thumbnail,
-        tauri_has_thumbnail,
-        // 撤销重做命令
-        tauri_undo,
-        tauri_redo,
-        tauri_clear_undo_redo_history,
-        tauri_clear_all_undo_redo_history,
-        // LabelPlus文件命令
-        tauri_validate_labelplus_file,
-        tauri_import_labelplus_data,
-        tauri_export_labelplus_data,
-        tauri_save_project,
-        // Bunny (海兔) OCR and translation commands
-        tauri_get_available_ocr_services,
-        tauri_get_available_translation_services,
-        tauri_request_ocr,
-        tauri_request_translation,
-        tauri_handle_ocr_completed,
-        tauri_handle_translation_completed,
-        tauri_handle_task_failed,
-        tauri_get_bunny_cache,
-        tauri_update_original_text,
-        tauri_update_machine_translation,
-        tauri_clear_bunny_cache
-    ])
+#[tauri::command]
+pub fn tauri_get_health() -> CoreHealth {
+    get_health()
 }
 
-// Bunny (海兔) OCR and translation commands
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_available_ocr_services() -> Vec<serde_json::Value> {
-    get_available_ocr_services()
+pub fn tauri_get_event_channel_stats() -> crate::common::EventChannelStats {
+    get_event_channel_stats()
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_available_translation_services() -> Vec<serde_json::Value> {
-    get_available_translation_services()
+pub fn tauri_set_frontend_event_filter(patterns: Vec<String>) {
+    set_frontend_event_filter(patterns)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_get_bunny_cache(marker_id: u32) -> Result<Option<crate::storage::bunny_cache::BunnyCacheData>, String> {
-    get_bunny_cache(crate::common::MarkerId(marker_id))
+pub fn tauri_get_frontend_event_filter_stats() -> crate::common::events::filter::FrontendEventFilterStats {
+    get_frontend_event_filter_stats()
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_original_text(marker_id: u32, text: String, model: String) -> Result<(), String> {
-    update_original_text(crate::common::MarkerId(marker_id), text, model)
+pub fn tauri_get_recent_logs(level_filter: Option<crate::common::LogLevel>, limit: usize) -> Vec<crate::common::LogRecord> {
+    get_recent_logs(level_filter, limit)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_update_machine_translation(marker_id: u32, text: String, service: String) -> Result<(), String> {
-    update_machine_translation(crate::common::MarkerId(marker_id), text, service)
+pub fn tauri_set_log_level(level: crate::common::LogLevel) {
+    set_log_level(level)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_request_ocr(marker_id: u32, image_id: u32, project_id: u32, service_id: String) -> Result<String, String> {
-    request_ocr(
-        crate::common::MarkerId(marker_id),
-        crate::common::ImageId(image_id),
-        crate::common::ProjectId(project_id),
-        service_id
-    )
+pub fn tauri_get_log_level() -> crate::common::LogLevel {
+    get_log_level()
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_request_translation(marker_id: u32, image_id: u32, project_id: u32, service_id: String, text: String) -> Result<String, String> {
-    request_translation(
-        crate::common::MarkerId(marker_id),
-        crate::common::ImageId(image_id),
-        crate::common::ProjectId(project_id),
-        service_id,
-        text
-    )
+pub fn tauri_set_audit_enabled(enabled: bool) {
+    set_audit_enabled(enabled)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_handle_ocr_completed(task_id: String, marker_id: u32, text: String, model: String) -> Result<(), String> {
-    handle_ocr_completed(task_id, crate::common::MarkerId(marker_id), text, model)
+pub fn tauri_is_audit_enabled() -> bool {
+    is_audit_enabled()
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_handle_translation_completed(task_id: String, marker_id: u32, translated_text: String, service: String) -> Result<(), String> {
-    handle_translation_completed(task_id, crate::common::MarkerId(marker_id), translated_text, service)
+pub fn tauri_get_plugin_audit_log(plugin_id: Option<String>, limit: Option<usize>) -> Vec<crate::plugin::PluginAuditEntry> {
+    get_plugin_audit_log(plugin_id, limit)
 }
 
 #[cfg(feature = "tauri")]
 #[tauri::command]
-pub fn tauri_handle_task_failed(task_id: String, error: String) -> Result<(), String> {
+pub fn tauri_register_plugin_config_schema(plugin_id: String, schema: serde_json::Value) {
+    register_plugin_config_schema(plugin_id, schema)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_command_manifest() -> Vec<crate::bindings::commands::CommandInfo> {
+    crate::bindings::commands::command_manifest()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_project_stats(project_id: u32) -> Option<SingleProjectStats> {
+    get_project_stats(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_text_stats(project_id: u32) -> Result<crate::service::stats::TextStats, String> {
+    get_text_stats(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_text_count_config(cjk_char_as_word: bool) -> Result<(), String> {
+    set_text_count_config(cjk_char_as_word)
+}
+
+// 数据清理命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_all_data() {
+    clear_all_data()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_binary_data(image_id: u32) -> Result<Vec<u8>, String> {
+    get_image_binary_data(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_encode_image(image_id: u32, format_str: String, quality: Option<u8>) -> Result<Vec<u8>, String> {
+    let format = ImageFormat::from_extension(&format_str).ok_or_else(|| format!("Unsupported image format: {}", format_str))?;
+    encode_image(image_id, format, quality)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_metadata(image_id: u32) -> Option<crate::common::dto::image::ImageMetadataDTO> {
+    get_image_metadata(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_mime_type(image_id: u32) -> Option<String> {
+    get_image_mime_type(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_file_path(image_id: u32) -> Option<String> {
+    get_image_file_path(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_source_kind(image_id: u32) -> Option<ImageSourceKind> {
+    get_image_source_kind(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_project_data(project_id: u32) -> bool {
+    clear_project_data(project_id)
+}
+
+// 缩略图相关命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_thumbnail(image_id: u32) -> Result<(), String> {
+    request_thumbnail(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_thumbnail(image_id: u32) -> Option<crate::service::image::ThumbnailData> {
+    get_thumbnail(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_has_thumbnail(image_id: u32) -> bool {
+    has_thumbnail(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_prefetch_thumbnails_around(image_id: u32, radius: Option<u32>) -> Result<(), String> {
+    prefetch_thumbnails_around(image_id, radius)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_thumbnail_cache() -> Result<usize, String> {
+    clear_thumbnail_cache()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_thumbnail_cache_stats() -> crate::service::image::ThumbnailCacheStats {
+    get_thumbnail_cache_stats()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_image_cache_limit(limit_mb: u32) -> Result<(), String> {
+    set_image_cache_limit(limit_mb)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_cache_stats() -> crate::service::image::ImageByteCacheStats {
+    get_image_cache_stats()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_marker_thumbnail(marker_id: u32, max_edge_px: u32) -> Result<Vec<u8>, String> {
+    get_marker_thumbnail(marker_id, max_edge_px)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_marker_thumbnails(image_id: u32, max_edge_px: u32) -> Result<Vec<crate::service::image::MarkerThumbnail>, String> {
+    get_marker_thumbnails(image_id, max_edge_px)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_thumbnail_order(project_id: u32) -> Vec<u32> {
+    get_thumbnail_order(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_validate_image_file(path: String) -> Result<ImageFormat, String> {
+    validate_image_file(&path)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_validate_image_data(name: String, data: Vec<u8>) -> Result<ImageFormat, String> {
+    validate_image_data(&name, &data)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_invalidate_image(image_id: u32) -> Result<bool, String> {
+    invalidate_image(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_image_pinned(image_id: u32, pinned: bool) -> bool {
+    set_image_pinned(image_id, pinned)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_image_reviewed(image_id: u32, reviewed: bool, note: Option<String>) -> bool {
+    set_image_reviewed(image_id, reviewed, note)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_image_locked(image_id: u32, locked: bool) -> Result<bool, String> {
+    set_image_locked(image_id, locked)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_rename_image(image_id: u32, new_name: String) -> Result<bool, String> {
+    rename_image(image_id, new_name)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_image_view_state(image_id: u32, view_state: Option<crate::common::dto::image::ImageViewState>) -> Result<bool, String> {
+    set_image_view_state(image_id, view_state)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_view_state(image_id: u32) -> Result<Option<crate::common::dto::image::ImageViewState>, String> {
+    get_image_view_state(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_select_image(image_id: u32) -> Result<Option<crate::common::dto::image::ImageViewState>, String> {
+    select_image(image_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_thumbnail_sort(project_id: u32, order: crate::common::ThumbnailOrder) -> bool {
+    set_thumbnail_sort(project_id, order)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_downscale_settings() -> Result<crate::service::image::DownscaleSettings, String> {
+    get_downscale_settings()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_downscale_settings(settings: crate::service::image::DownscaleSettings) -> Result<(), String> {
+    set_downscale_settings(settings)
+}
+
+// 撤销重做相关命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_undo(project_id: u32) -> UndoRedoResult {
+    undo(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_redo(project_id: u32) -> UndoRedoResult {
+    redo(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_undo_redo_history(project_id: u32) {
+    clear_undo_redo_history(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_begin_undo_group(project_id: u32, description: String) -> Result<(), String> {
+    begin_undo_group(project_id, description)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_end_undo_group(project_id: u32) -> Result<(), String> {
+    end_undo_group(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_abort_undo_group(project_id: u32) -> Result<bool, String> {
+    abort_undo_group(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_all_undo_redo_history() {
+    clear_all_undo_redo_history()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_set_undo_coalesce_window(window_ms: u32) {
+    set_undo_coalesce_window(window_ms)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_undo_coalesce_window() -> u32 {
+    get_undo_coalesce_window()
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_mark_project_saved(project_id: u32) -> bool {
+    mark_project_saved(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_has_unsaved_changes(project_id: u32) -> bool {
+    has_unsaved_changes(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_unsaved_project_ids() -> Vec<u32> {
+    get_unsaved_project_ids()
+}
+
+// bf文件预检
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_validate_bf_file(path: String) -> Result<crate::service::io::bf::BfSummary, String> {
+    validate_bf_file(&path)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_validate_bf_data(data: Vec<u8>) -> Result<crate::service::io::bf::BfSummary, String> {
+    validate_bf_data(&data)
+}
+
+// 丢失图片的检查和重新链接
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_missing_images(project_id: u32) -> Vec<crate::common::dto::image::MissingImageDTO> {
+    get_missing_images(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_relink_image(image_id: u32, path: String) -> Result<crate::common::dto::image::RelinkResultDTO, String> {
+    relink_image(image_id, &path)
+}
+
+// LabelPlus文件相关命令
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_validate_labelplus_file(content: String) -> Result<crate::service::io::labelplus::LabelplusData, String> {
+    validate_labelplus_file(&content)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_import_labelplus_data(project_id: u32, content: String) -> Result<(), String> {
+    import_labelplus_data(project_id, &content)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_import_labelplus_merge(project_id: u32, content: String, options: Option<crate::service::io::labelplus::LabelplusMergeOptions>) -> Result<crate::service::io::labelplus::LabelplusMergeReport, String> {
+    import_labelplus_merge(project_id, &content, options.unwrap_or_default())
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_export_labelplus_data(project_id: u32) -> Result<String, String> {
+    export_labelplus_data(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_export_labelplus_data_range(project_id: u32, image_ids: Vec<u32>) -> Result<String, String> {
+    export_labelplus_data_range(project_id, image_ids)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_export_typesetting_data(project_id: u32, format: crate::service::io::typesetting::TypesettingFormat, options: Option<crate::service::io::typesetting::TypesettingOptions>) -> Result<String, String> {
+    export_typesetting_data(project_id, format, options.unwrap_or_default())
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_project_content_hash(project_id: u32) -> Result<String, String> {
+    get_project_content_hash(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_import_typesetting_data(project_id: u32, content: String, format: crate::service::io::typesetting::TypesettingFormat, options: Option<crate::service::io::typesetting::TypesettingImportOptions>) -> Result<crate::service::io::typesetting::TypesettingImportReport, String> {
+    import_typesetting_data(project_id, &content, format, options.unwrap_or_default())
+}
+
+// 导出会逐张解码编码图片，放到后台线程执行避免阻塞主线程
+#[cfg(feature = "tauri")]
+#[tauri::command(async)]
+pub fn tauri_export_annotated_images(project_id: u32, dir: String, options: Option<crate::service::io::annotate::AnnotateOptions>) -> Result<Vec<String>, String> {
+    export_annotated_images_to_dir(project_id, &dir, options.unwrap_or_default())
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_save_project(project_id: u32) -> Result<Vec<u8>, String> {
+    save_project(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_save_project_range(project_id: u32, image_ids: Vec<u32>) -> Result<Vec<u8>, String> {
+    save_project_range(project_id, image_ids)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_export_recovery_snapshot(project_id: u32) -> Result<Vec<u8>, String> {
+    export_recovery_snapshot(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_import_recovery_snapshot(data: Vec<u8>) -> Result<u32, String> {
+    import_recovery_snapshot(data)
+}
+
+// Tauri 命令注册辅助函数
+#[cfg(feature = "tauri")]
+pub fn register_data_commands<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
+    builder.invoke_handler(crate::generate_core_commands!())
+}
+
+// Bunny (海兔) OCR and translation commands
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_available_ocr_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    get_available_ocr_services(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_available_translation_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    get_available_translation_services(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_bunny_cache(marker_id: u32) -> Result<Option<crate::storage::bunny_cache::BunnyCacheData>, String> {
+    get_bunny_cache(crate::common::MarkerId(marker_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_translation_result(marker_id: u32) -> Result<Option<crate::service::bunny::TranslationResult>, String> {
+    get_translation_result(crate::common::MarkerId(marker_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_accept_machine_translation(marker_id: u32) -> Result<bool, String> {
+    crate::api::bunny::accept_machine_translation(crate::common::MarkerId(marker_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_accept_all_for_image(image_id: u32, only_empty: bool) -> Result<crate::service::bunny::AcceptMachineTranslationReport, String> {
+    crate::api::bunny::accept_all_for_image(crate::common::ImageId(image_id), only_empty)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_reject_machine_translation(marker_id: u32) -> Result<bool, String> {
+    crate::api::bunny::reject_machine_translation(crate::common::MarkerId(marker_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_update_original_text(marker_id: u32, text: String, model: String) -> Result<(), String> {
+    update_original_text(crate::common::MarkerId(marker_id), text, model)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_update_machine_translation(marker_id: u32, text: String, service: String) -> Result<(), String> {
+    update_machine_translation(crate::common::MarkerId(marker_id), text, service)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_ocr(marker_id: u32, image_id: u32, project_id: u32, service_id: String) -> Result<String, String> {
+    request_ocr(
+        crate::common::MarkerId(marker_id),
+        crate::common::ImageId(image_id),
+        crate::common::ProjectId(project_id),
+        service_id
+    )
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_ocr_batch(marker_ids: Vec<u32>, project_id: u32, service_id: String) -> Result<Vec<crate::service::bunny::OcrBatchItem>, String> {
+    request_ocr_batch(
+        marker_ids.into_iter().map(crate::common::MarkerId).collect(),
+        crate::common::ProjectId(project_id),
+        service_id
+    )
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_pipeline(marker_id: u32, image_id: u32, project_id: u32, ocr_service: String, translation_service: String) -> Result<String, String> {
+    request_pipeline(
+        crate::common::MarkerId(marker_id),
+        crate::common::ImageId(image_id),
+        crate::common::ProjectId(project_id),
+        ocr_service,
+        translation_service
+    )
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_pipeline_batch(image_id: u32, ocr_service: String, translation_service: String) -> Result<Vec<crate::service::bunny::PipelineBatchItem>, String> {
+    request_pipeline_batch(crate::common::ImageId(image_id), ocr_service, translation_service)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_pipeline(pipeline_id: String) -> Result<Option<crate::service::bunny::BunnyPipeline>, String> {
+    get_pipeline(pipeline_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_project_pipelines(project_id: u32) -> Result<Vec<crate::service::bunny::BunnyPipeline>, String> {
+    get_project_pipelines(crate::common::ProjectId(project_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_translate_all_untranslated(project_id: u32, service_id: String, operation_id: Option<String>) -> Result<Vec<String>, String> {
+    translate_all_untranslated(crate::common::ProjectId(project_id), service_id, operation_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_translation(marker_id: u32, image_id: u32, project_id: u32, service_id: String, text: String) -> Result<String, String> {
+    request_translation(
+        crate::common::MarkerId(marker_id),
+        crate::common::ImageId(image_id),
+        crate::common::ProjectId(project_id),
+        service_id,
+        text
+    )
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_handle_ocr_completed(task_id: String, marker_id: u32, text: String, model: String, regions: Option<Vec<crate::common::OcrRegion>>) -> Result<(), String> {
+    handle_ocr_completed(task_id, crate::common::MarkerId(marker_id), text, model, regions.unwrap_or_default())
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_split_marker_by_ocr_regions(marker_id: u32, regions: Option<Vec<crate::common::OcrRegion>>) -> Result<Vec<crate::common::MarkerId>, String> {
+    crate::api::bunny::split_marker_by_ocr_regions(crate::common::MarkerId(marker_id), regions)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_available_detection_services(project_id: Option<u32>) -> Vec<serde_json::Value> {
+    crate::api::bunny::get_available_detection_services(project_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_request_detection(image_id: u32, service_id: String) -> Result<String, String> {
+    crate::api::bunny::request_detection(crate::common::ImageId(image_id), service_id)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_handle_detection_completed(task_id: String, model: String, regions: Vec<crate::common::DetectedRegion>) -> Result<(), String> {
+    crate::api::bunny::handle_detection_completed(task_id, model, regions)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_create_markers_from_detection(image_id: u32, regions: Vec<crate::common::DetectedRegion>) -> Result<Vec<crate::common::MarkerId>, String> {
+    crate::api::bunny::create_markers_from_detection(crate::common::ImageId(image_id), regions)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_handle_translation_completed(task_id: String, marker_id: u32, translated_text: String, service: String, warnings: Option<Vec<crate::common::TranslationWarning>>, metadata: Option<serde_json::Value>) -> Result<(), String> {
+    handle_translation_completed(task_id, crate::common::MarkerId(marker_id), translated_text, service, warnings.unwrap_or_default(), metadata)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_handle_task_failed(task_id: String, error: String) -> Result<(), String> {
     handle_task_failed(task_id, error)
 }
 
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_task_history(project_id: u32, limit: Option<usize>) -> Result<Vec<crate::service::bunny::TaskHistoryEntry>, String> {
+    get_task_history(crate::common::ProjectId(project_id), limit)
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_task_metrics(project_id: u32) -> Result<Vec<crate::service::bunny::ServiceTaskMetrics>, String> {
+    get_task_metrics(crate::common::ProjectId(project_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_get_image_task_progress(image_id: u32) -> crate::service::bunny::ImageTaskProgress {
+    get_image_task_progress(crate::common::ImageId(image_id))
+}
+
+#[cfg(feature = "tauri")]
+#[tauri::command]
+pub fn tauri_clear_task_history(project_id: u32) -> Result<(), String> {
+    clear_task_history(crate::common::ProjectId(project_id))
+}
+
 #[cfg(feature = "tauri")]
 #[tauri::command]
 pub fn tauri_clear_bunny_cache(marker_id: u32) -> Result<(), String> {
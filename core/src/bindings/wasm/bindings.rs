@@ -15,15 +15,19 @@ use crate::common::Logger;
 use crate::api::marker::{
     add_point_marker_to_image, add_rectangle_marker_to_image,
     update_point_marker_position, update_rectangle_marker_geometry,
+    begin_marker_drag, end_marker_drag,
     update_point_marker_full, update_rectangle_marker_full,
-    convert_rectangle_to_point_marker, convert_point_to_rectangle_marker
+    convert_rectangle_to_point_marker, convert_point_to_rectangle_marker,
+    add_polygon_marker_to_image, update_polygon_marker_geometry, convert_polygon_to_rectangle_marker
 };
 #[cfg(feature = "wasm")]
 use crate::api::bunny::{
     get_available_ocr_services, get_available_translation_services,
-    request_ocr, request_translation,
+    request_ocr, request_ocr_batch, request_translation, translate_all_untranslated,
+    request_pipeline, request_pipeline_batch, get_pipeline, get_project_pipelines,
     handle_ocr_completed, handle_translation_completed, handle_task_failed,
-    get_bunny_cache, update_original_text, update_machine_translation, clear_bunny_cache
+    get_bunny_cache, get_translation_result, update_original_text, update_machine_translation, clear_bunny_cache,
+    get_task_history, get_task_metrics, clear_task_history, get_image_task_progress
 };
 #[cfg(feature = "wasm")]
 use crate::common::dto::image::{ImageDataDTO, ImageFormat};
@@ -31,8 +35,8 @@ use crate::common::dto::image::{ImageDataDTO, ImageFormat};
 // 临时项目相关
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_create_empty_opening_project(project_name: String) -> JsValue {
-    match create_empty_opening_project(project_name) {
+pub fn wasm_create_empty_opening_project(project_name: String, template: Option<String>) -> JsValue {
+    match create_empty_opening_project(project_name, template) {
         Ok(project_id) => to_value(&project_id).unwrap_or(JsValue::NULL),
         Err(e) => {
             Logger::error(&format!("Failed to create empty opening project: {}", e));
@@ -59,6 +63,73 @@ pub fn wasm_create_opening_project_from_binary(data: Vec<u8>, file_extension: St
     }
 }
 
+// Core可能运行在Worker中，fetch要从当前的全局对象上取
+#[cfg(feature = "wasm")]
+fn fetch_request(request: &web_sys::Request) -> Result<js_sys::Promise, JsValue> {
+    let global = js_sys::global();
+    if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        Ok(window.fetch_with_request(request))
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        Ok(worker.fetch_with_request(request))
+    } else {
+        Err(JsValue::from_str("当前环境不支持fetch"))
+    }
+}
+
+/// 下载URL指向的图片并创建临时项目，之后的流程与from_binary相同
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn wasm_create_opening_project_from_url(url: String, project_name: String) -> Result<u32, JsValue> {
+    use wasm_bindgen_futures::JsFuture;
+
+    let init = web_sys::RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(web_sys::RequestMode::Cors);
+    let request = web_sys::Request::new_with_str_and_init(&url, &init)
+        .map_err(|_| JsValue::from_str(&format!("无效的图片地址：{}", url)))?;
+
+    // 浏览器对CORS拒绝和网络错误都只给出TypeError，无法区分
+    let response = JsFuture::from(fetch_request(&request)?).await.map_err(|e| {
+        Logger::error(&format!("Failed to fetch {}: {:?}", url, e));
+        JsValue::from_str(&format!(
+            "无法下载图片：{}。可能是网络错误，或该站点不允许跨域访问（CORS），请先下载到本地再导入",
+            url
+        ))
+    })?;
+    let response: web_sys::Response = response.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "下载图片失败：{}（HTTP {} {}）", url, response.status(), response.status_text()
+        )));
+    }
+
+    let content_type = response.headers().get("content-type").ok().flatten();
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    let data = js_sys::Uint8Array::new(&buffer).to_vec();
+
+    create_opening_project_from_url_data(url, content_type, data, project_name)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_create_opening_project_from_archive(data: Vec<u8>, archive_name: String, project_name: String, operation_id: Option<String>) -> JsValue {
+    match create_opening_project_from_archive(data, archive_name, project_name, operation_id) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn wasm_create_opening_project_from_archive_shared_buffer(archive_name: String, project_name: String) -> Result<JsValue, JsValue> {
+    super::shared_buffer::create_opening_project_from_archive_shared_buffer_impl(archive_name, project_name).await
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_get_opening_project_info(project_id: u32) -> JsValue {
@@ -80,12 +151,43 @@ pub fn wasm_finalize_opening_project(project_id: u32) -> bool {
     finalize_opening_project(project_id)
 }
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_opening_project_preserve_order(project_id: u32, preserve_order: bool) -> bool {
+    set_opening_project_preserve_order(project_id, preserve_order)
+}
+
+// origin为"top_left"、"top_right"、"bottom_left"或"bottom_right"
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_opening_project_labelplus_origin(project_id: u32, origin: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value(origin) {
+        Ok(origin) => set_opening_project_labelplus_origin(project_id, origin),
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_preview_opening_project_labelplus_origin(project_id: u32, origin: JsValue) -> JsValue {
+    serde_wasm_bindgen::from_value(origin).ok()
+        .and_then(|origin| preview_opening_project_labelplus_origin(project_id, origin))
+        .and_then(|preview| to_value(&preview).ok())
+        .unwrap_or(JsValue::NULL)
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_delete_opening_project(project_id: u32) -> bool {
     delete_opening_project(project_id)
 }
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_cancel_opening_project(opening_id: u32) -> bool {
+    cancel_opening_project(opening_id)
+}
+
 // 项目相关
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
@@ -141,345 +243,1411 @@ pub fn wasm_delete_project(project_id: u32) -> bool {
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_project_images(project_id: u32) -> JsValue {
-    to_value(&get_project_images(project_id)).unwrap_or(JsValue::NULL)
+pub fn wasm_set_project_file_watch(project_id: u32, enabled: bool) -> bool {
+    set_project_file_watch(project_id, enabled)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_project_images_metadata(project_id: u32) -> JsValue {
-    to_value(&get_project_images_metadata(project_id)).unwrap_or(JsValue::NULL)
+pub fn wasm_set_service_enabled(project_id: u32, service_id: String, enabled: bool) -> bool {
+    set_service_enabled(project_id, service_id, enabled)
 }
 
-// 图片相关
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_add_image_from_binary_to_project(project_id: u32, format_str: String, data: Vec<u8>, name: Option<String>) -> Option<u32> {
-    if let Some(format) = match format_str.as_str() {
-        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
-        "png" => Some(ImageFormat::Png),
-        "gif" => Some(ImageFormat::Gif),
-        "webp" => Some(ImageFormat::Webp),
-        "bmp" => Some(ImageFormat::Bmp),
-        _ => None,
-    } {
-        add_image_from_binary_to_project(project_id, format, data, name)
-    } else {
-        None
+pub fn wasm_get_project_glossary(project_id: u32) -> JsValue {
+    match get_project_glossary(project_id) {
+        Some(glossary) => to_value(&glossary).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
     }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_image_info(image_id: u32) -> JsValue {
-    match get_image_info(image_id) {
-        Some(image) => to_value(&image).unwrap_or(JsValue::NULL),
-        None => JsValue::NULL,
+pub fn wasm_set_project_glossary(project_id: u32, glossary: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value(glossary) {
+        Ok(glossary) => set_project_glossary(project_id, glossary),
+        Err(e) => {
+            Logger::error(&format!("Invalid glossary: {}", e));
+            false
+        }
     }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_image_info(image_id: u32, name: Option<String>) -> bool {
-    update_image_info(image_id, None, name)
+pub fn wasm_get_project_notes(project_id: u32) -> Option<String> {
+    get_project_notes(project_id)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_image_data_from_binary(image_id: u32, format_str: String, data: Vec<u8>) -> bool {
-    if let Some(format) = match format_str.as_str() {
-        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
-        "png" => Some(ImageFormat::Png),
-        "gif" => Some(ImageFormat::Gif),
-        "webp" => Some(ImageFormat::Webp),
-        "bmp" => Some(ImageFormat::Bmp),
-        _ => None,
-    } {
-        let image_data = ImageDataDTO::Binary { format, data: Arc::new(data) };
-        update_image_info(image_id, Some(image_data), None)
-    } else {
-        false
-    }
+pub fn wasm_set_project_notes(project_id: u32, notes: String) -> bool {
+    set_project_notes(project_id, notes)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_remove_image_from_project(project_id: u32, image_id: u32) -> bool {
-    remove_image_from_project(project_id, image_id)
+pub fn wasm_set_service_fallbacks(project_id: u32, ocr: JsValue, translation: JsValue) -> bool {
+    match (serde_wasm_bindgen::from_value(ocr), serde_wasm_bindgen::from_value(translation)) {
+        (Ok(ocr), Ok(translation)) => set_service_fallbacks(project_id, ocr, translation),
+        _ => {
+            Logger::error("Invalid service fallbacks");
+            false
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_reorder_project_images(project_id: u32, image_ids: Vec<u32>) -> bool {
-    reorder_project_images(project_id, image_ids)
+pub fn wasm_get_keep_translation_history(project_id: u32) -> Option<bool> {
+    get_keep_translation_history(project_id)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_image_binary_data(image_id: u32) -> Result<Vec<u8>, JsValue> {
-    match get_image_binary_data(image_id) {
-        Ok(data) => Ok(data),
-        Err(e) => Err(JsValue::from_str(&e)),
-    }
+pub fn wasm_set_keep_translation_history(project_id: u32, enabled: bool) -> bool {
+    set_keep_translation_history(project_id, enabled)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_image_mime_type(image_id: u32) -> Option<String> {
-    get_image_mime_type(image_id)
+pub fn wasm_get_grayscale_reencode(project_id: u32) -> Option<bool> {
+    get_grayscale_reencode(project_id)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_image_markers(image_id: u32) -> JsValue {
-    to_value(&get_image_markers(image_id)).unwrap_or(JsValue::NULL)
+pub fn wasm_set_grayscale_reencode(project_id: u32, enabled: bool) -> bool {
+    set_grayscale_reencode(project_id, enabled)
 }
 
-// 标记相关 - 点型marker
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>) -> Option<u32> {
-    add_point_marker_to_image(image_id, x, y, translation)
+pub fn wasm_get_auto_ocr_on_create(project_id: u32) -> Option<bool> {
+    get_auto_ocr_on_create(project_id)
 }
 
-// 标记相关 - 矩形型marker
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> Option<u32> {
-    add_rectangle_marker_to_image(image_id, x, y, width, height, translation)
+pub fn wasm_set_auto_ocr_on_create(project_id: u32, enabled: bool) -> bool {
+    set_auto_ocr_on_create(project_id, enabled)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_marker_info(marker_id: u32) -> JsValue {
-    match get_marker_info(marker_id) {
-        Some(marker) => to_value(&marker).unwrap_or(JsValue::NULL),
+pub fn wasm_get_reading_direction(project_id: u32) -> JsValue {
+    match get_reading_direction(project_id) {
+        Some(direction) => to_value(&direction).unwrap_or(JsValue::NULL),
         None => JsValue::NULL,
     }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_point_marker_position(marker_id: u32, x: f64, y: f64) -> bool {
-    update_point_marker_position(marker_id, x, y)
+pub fn wasm_set_reading_direction(project_id: u32, direction: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value(direction) {
+        Ok(direction) => set_reading_direction(project_id, direction),
+        Err(e) => {
+            Logger::error(&format!("Invalid reading direction: {}", e));
+            false
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64) -> bool {
-    update_rectangle_marker_geometry(marker_id, x, y, width, height)
+pub fn wasm_check_integrity(project_id: u32, repair: bool) -> JsValue {
+    match check_integrity(project_id, repair) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_marker_translation(marker_id: u32, translation: String) -> bool {
-    update_marker_translation(marker_id, translation)
+pub fn wasm_find_duplicate_images(project_id: u32, threshold: Option<u32>) -> Result<JsValue, JsValue> {
+    let groups = find_duplicate_images(project_id, threshold).map_err(|e| JsValue::from_str(&e))?;
+    to_value(&groups).map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> bool {
-    update_marker_style(marker_id, overlay_text, horizontal)
+pub fn wasm_remove_duplicate_images(project_id: u32, keep_strategy: JsValue, threshold: Option<u32>, force: bool) -> Result<Vec<u32>, JsValue> {
+    let keep_strategy = serde_wasm_bindgen::from_value(keep_strategy)
+        .map_err(|e| JsValue::from_str(&format!("Invalid keep strategy: {}", e)))?;
+    remove_duplicate_images(project_id, keep_strategy, threshold, force).map_err(|e| JsValue::from_str(&e))
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_move_marker_order(marker_id: u32, new_index: u32) -> bool {
-    marker::move_marker_order(marker_id, new_index)
+pub fn wasm_duplicate_project(project_id: u32, new_name: String) -> JsValue {
+    match duplicate_project(project_id, new_name) {
+        Ok(new_project_id) => JsValue::from(new_project_id),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
+// 项目模板
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>) -> bool {
-    update_point_marker_full(marker_id, x, y, translation)
+pub fn wasm_save_as_template(project_id: u32, name: String) -> JsValue {
+    match save_as_template(project_id, name) {
+        Ok(template) => to_value(&template).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> bool {
-    update_rectangle_marker_full(marker_id, x, y, width, height, translation)
+pub fn wasm_create_project_from_template(name: String, template: String) -> JsValue {
+    match create_project_from_template(name, template) {
+        Ok(project_id) => JsValue::from(project_id),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_remove_marker_from_image(image_id: u32, marker_id: u32) -> bool {
-    remove_marker_from_image(image_id, marker_id)
+pub fn wasm_list_templates() -> JsValue {
+    match list_templates() {
+        Ok(list) => to_value(&list).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_clear_image_markers(image_id: u32) -> bool {
-    clear_image_markers(image_id)
+pub fn wasm_delete_template(name: String) -> bool {
+    delete_template(name).unwrap_or(false)
 }
 
+// 命名快照
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_convert_rectangle_to_point_marker(marker_id: u32) -> bool {
-    convert_rectangle_to_point_marker(marker_id)
+pub fn wasm_create_snapshot(project_id: u32, name: String) -> JsValue {
+    match crate::api::snapshot::create(project_id, name) {
+        Ok(info) => to_value(&info).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_convert_point_to_rectangle_marker(marker_id: u32) -> bool {
-    convert_point_to_rectangle_marker(marker_id)
+pub fn wasm_list_snapshots(project_id: u32) -> JsValue {
+    match crate::api::snapshot::list(project_id) {
+        Ok(list) => to_value(&list).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
 }
 
-// 统计相关
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_stats() -> JsValue {
-    to_value(&get_stats()).unwrap_or(JsValue::NULL)
+pub fn wasm_restore_snapshot(project_id: u32, snapshot_id: String) -> JsValue {
+    match crate::api::snapshot::restore(project_id, snapshot_id) {
+        Ok(()) => JsValue::TRUE,
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_project_stats(project_id: u32) -> JsValue {
-    match get_project_stats(project_id) {
-        Some(stats) => to_value(&stats).unwrap_or(JsValue::NULL),
-        None => JsValue::NULL,
-    }
+pub fn wasm_delete_snapshot(snapshot_id: String) -> bool {
+    crate::api::snapshot::delete(snapshot_id).unwrap_or(false)
 }
 
-// 数据清理
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_clear_all_data() {
-    clear_all_data()
+pub fn wasm_set_snapshot_limit(limit: usize) -> bool {
+    crate::api::snapshot::set_limit(limit).is_ok()
 }
 
+/// 启动时由JS逐个传入之前持久化的快照
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_clear_project_data(project_id: u32) -> bool {
-    clear_project_data(project_id)
+pub fn wasm_load_snapshot(data: Vec<u8>) -> bool {
+    crate::api::snapshot::load(data).is_ok()
 }
 
-// 撤销重做相关函数
+/// 快照变更时回调JS，参数为快照ID和压缩后的快照（Uint8Array，删除时为null）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_undo(project_id: u32) -> JsValue {
-    let result = undo(project_id);
-    to_value(&result).unwrap_or(JsValue::NULL)
+pub fn wasm_set_snapshot_persist_callback(callback: js_sys::Function) {
+    crate::service::project::snapshot::set_snapshot_persist_callback(move |id: String, data: Option<Vec<u8>>| {
+        let data = data.map_or(JsValue::NULL, |data| js_sys::Uint8Array::from(data.as_slice()).into());
+        if let Err(e) = callback.call2(&JsValue::NULL, &JsValue::from_str(&id), &data) {
+            Logger::error(&format!("Snapshot persist callback error: {:?}", e));
+        }
+    });
 }
 
+/// 启动时由JS传入之前持久化的模板JSON数组，返回加载警告
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_redo(project_id: u32) -> JsValue {
-    let result = redo(project_id);
-    to_value(&result).unwrap_or(JsValue::NULL)
+pub fn wasm_load_templates(json: String) -> JsValue {
+    match load_templates(json) {
+        Ok(warnings) => to_value(&warnings).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
+/// 模板变更时回调JS，参数为所有模板的JSON数组字符串
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_clear_undo_redo_history(project_id: u32) {
-    clear_undo_redo_history(project_id)
+pub fn wasm_set_template_persist_callback(callback: js_sys::Function) {
+    crate::service::project::template::set_template_persist_callback(move |json: String| {
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+            Logger::error(&format!("Template persist callback error: {:?}", e));
+        }
+    });
 }
 
+// 快捷键配置
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_clear_all_undo_redo_history() {
-    clear_all_undo_redo_history()
+pub fn wasm_get_keymap() -> JsValue {
+    match get_keymap() {
+        Ok(bindings) => to_value(&bindings).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
 }
 
-// 缩略图相关函数
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_request_thumbnail(image_id: u32) -> JsValue {
-    match request_thumbnail(image_id) {
-        Ok(_) => JsValue::from_str("ok"),
-        Err(e) => JsValue::from_str(&e),
+pub fn wasm_set_keymap(bindings: JsValue) -> JsValue {
+    let result = serde_wasm_bindgen::from_value(bindings)
+        .map_err(|e| format!("Invalid keymap: {}", e))
+        .and_then(set_keymap);
+    match result {
+        Ok(bindings) => to_value(&bindings).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
     }
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_request_thumbnails_batch(image_ids: &[u32]) -> JsValue {
-    match request_thumbnails_batch(image_ids.to_vec()) {
-        Ok(_) => JsValue::from_str("ok"),
-        Err(e) => JsValue::from_str(&e),
+pub fn wasm_reset_keymap() -> JsValue {
+    match reset_keymap() {
+        Ok(bindings) => to_value(&bindings).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
     }
 }
 
+// 菜单定义
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_thumbnail(image_id: u32) -> JsValue {
-    match get_thumbnail(image_id) {
-        Some(thumbnail) => to_value(&thumbnail).unwrap_or(JsValue::NULL),
-        None => JsValue::NULL,
+pub fn wasm_get_menu_definition() -> JsValue {
+    match get_menu_definition() {
+        Ok(sections) => to_value(&sections).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
     }
 }
 
+/// 修改单个菜单项的快捷键，失败时返回 { error, kind }
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_has_thumbnail(image_id: u32) -> bool {
-    has_thumbnail(image_id)
+pub fn wasm_set_accelerator(item_id: &str, accelerator: Option<String>) -> JsValue {
+    let result = crate::service::keymap::set_accelerator(item_id, accelerator)
+        .and_then(|_| crate::service::menu::get_menu_definition()
+            .map_err(|e| crate::service::keymap::AcceleratorError::Storage { message: e.to_string() }));
+    match result {
+        Ok(sections) => to_value(&sections).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(e.message())).unwrap();
+            js_sys::Reflect::set(&error_obj, &"kind".into(), &JsValue::from_str(e.kind())).unwrap();
+            if let crate::service::keymap::AcceleratorError::Conflict { conflicting_item, .. } = &e {
+                js_sys::Reflect::set(&error_obj, &"conflicting_item".into(), &JsValue::from_str(conflicting_item)).unwrap();
+            }
+            error_obj.into()
+        }
+    }
 }
 
-// SharedArrayBuffer support
+/// 启动时由JS传入之前持久化的快捷键JSON，返回加载警告
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_init_shared_buffer(buffer: js_sys::SharedArrayBuffer) -> Result<(), JsValue> {
-    super::shared_buffer::init_shared_buffer_impl(buffer)
+pub fn wasm_load_keymap(json: String) -> JsValue {
+    match load_keymap(json) {
+        Ok(warnings) => to_value(&warnings).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
 }
 
+/// 快捷键变更时回调JS，参数为用户修改过的绑定JSON字符串
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub async fn wasm_add_image_from_shared_buffer(project_id: u32, name: Option<String>) -> Result<u32, JsValue> {
-    super::shared_buffer::add_image_from_shared_buffer_impl(project_id, name).await
+pub fn wasm_set_keymap_persist_callback(callback: js_sys::Function) {
+    crate::service::keymap::set_keymap_persist_callback(move |json: String| {
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+            Logger::error(&format!("Keymap persist callback error: {:?}", e));
+        }
+    });
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_cleanup_orphaned_images() -> u32 {
-    // For now, return 0 as we're not tracking orphaned images in memory
-    // This will be implemented when we add proper image lifecycle management
-    0
+pub fn wasm_get_project_images(project_id: u32) -> JsValue {
+    to_value(&get_project_images(project_id)).unwrap_or(JsValue::NULL)
 }
 
-// 事件系统初始化
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_init_event_system() {
-    // 标记当前线程为主线程
-    crate::common::events::mark_as_main_thread();
-    
-    // 在WASM环境中初始化事件发射器
-    let event_emitter = crate::common::events::WasmEventEmitter::new();
-    crate::common::EVENT_SYSTEM.register_emitter(
-        "wasm".to_string(),
-        Box::new(event_emitter)
-    );
-    Logger::info("WASM event system initialized");
+pub fn wasm_get_project_images_metadata(project_id: u32) -> JsValue {
+    to_value(&get_project_images_metadata(project_id)).unwrap_or(JsValue::NULL)
 }
 
-// 设置Worker事件回调
+// 图片相关
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_set_event_callback(callback: js_sys::Function) {
-    use wasm_bindgen::JsValue;
-    
-    let callback_clone = callback.clone();
-    crate::common::events::set_worker_event_callback(move |event_name: String, event_data: serde_json::Value| {
-        let event_name_js = JsValue::from_str(&event_name);
-        let event_data_js = match js_sys::JSON::parse(&event_data.to_string()) {
-            Ok(value) => value,
-            Err(_) => {
-                Logger::error("Failed to parse event data JSON for callback");
-                return;
-            }
-        };
-
+pub fn wasm_add_image_from_binary_to_project(project_id: u32, format_str: String, data: Vec<u8>, name: Option<String>) -> Option<u32> {
+    if let Some(format) = match format_str.as_str() {
+        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+        "png" => Some(ImageFormat::Png),
+        "gif" => Some(ImageFormat::Gif),
+        "webp" => Some(ImageFormat::Webp),
+        "bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    } {
+        add_image_from_binary_to_project(project_id, format, data, name)
+    } else {
+        None
+    }
+}
+
+// 粘贴的截图，小图直接传数据，大图走wasm_add_image_from_clipboard_shared_buffer
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_add_image_from_clipboard_bytes(project_id: u32, data: Vec<u8>, suggested_name: Option<String>) -> Result<u32, JsValue> {
+    add_image_from_clipboard_bytes(project_id, data, suggested_name).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_add_image_from_bytes(project_id: u32, data: Vec<u8>, name: Option<String>) -> Result<u32, JsValue> {
+    add_image_from_bytes(project_id, data, name).map_err(|e| JsValue::from_str(&e))
+}
+
+// 校验通过返回null，否则返回 { error, kind, name } 供界面提示具体原因
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_validate_image_data(name: String, data: &[u8]) -> JsValue {
+    use crate::service::image::{ImageCandidate, validate_image_candidate};
+    match validate_image_candidate(ImageCandidate::Bytes { name: &name, data }) {
+        Ok(_) => JsValue::NULL,
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(e.message())).unwrap();
+            js_sys::Reflect::set(&error_obj, &"kind".into(), &JsValue::from_str(e.kind())).unwrap();
+            js_sys::Reflect::set(&error_obj, &"name".into(), &JsValue::from_str(&name)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_info(image_id: u32) -> JsValue {
+    match get_image_info(image_id) {
+        Some(image) => to_value(&image).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_image_info(image_id: u32, name: Option<String>) -> bool {
+    update_image_info(image_id, None, name)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_image_data_from_binary(image_id: u32, format_str: String, data: Vec<u8>) -> bool {
+    if let Some(format) = match format_str.as_str() {
+        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+        "png" => Some(ImageFormat::Png),
+        "gif" => Some(ImageFormat::Gif),
+        "webp" => Some(ImageFormat::Webp),
+        "bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    } {
+        let image_data = ImageDataDTO::Binary { format, data: Arc::new(data) };
+        update_image_info(image_id, Some(image_data), None)
+    } else {
+        false
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_remove_image_from_project(project_id: u32, image_id: u32) -> bool {
+    remove_image_from_project(project_id, image_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_reorder_project_images(project_id: u32, image_ids: Vec<u32>) -> bool {
+    reorder_project_images(project_id, image_ids)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_reorder_images_natural(project_id: u32, operation_id: Option<String>) -> bool {
+    reorder_images_natural(project_id, operation_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_binary_data(image_id: u32) -> Result<Vec<u8>, JsValue> {
+    match get_image_binary_data(image_id) {
+        Ok(data) => Ok(data),
+        Err(e) => Err(JsValue::from_str(&e)),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_encode_image(image_id: u32, format_str: String, quality: Option<u8>) -> Result<Vec<u8>, JsValue> {
+    let format = ImageFormat::from_extension(&format_str)
+        .ok_or_else(|| JsValue::from_str(&format!("Unsupported image format: {}", format_str)))?;
+    encode_image(image_id, format, quality).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_mime_type(image_id: u32) -> Option<String> {
+    get_image_mime_type(image_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_metadata(image_id: u32) -> JsValue {
+    match get_image_metadata(image_id) {
+        Some(metadata) => to_value(&metadata).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_source_kind(image_id: u32) -> JsValue {
+    match get_image_source_kind(image_id) {
+        Some(kind) => to_value(&kind).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_markers(image_id: u32) -> JsValue {
+    to_value(&get_image_markers(image_id)).unwrap_or(JsValue::NULL)
+}
+
+// mode为"reject"或"clamp"，未传时按默认（拒绝越界坐标）
+#[cfg(feature = "wasm")]
+fn coordinate_mode(mode: JsValue) -> Option<crate::storage::marker::CoordinateMode> {
+    serde_wasm_bindgen::from_value(mode).unwrap_or(None)
+}
+
+// 标记相关 - 点型marker
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_add_point_marker_to_image(image_id: u32, x: f64, y: f64, translation: Option<String>, mode: JsValue) -> Result<Option<u32>, JsValue> {
+    add_point_marker_to_image(image_id, x, y, translation, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+// 标记相关 - 矩形型marker
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_add_rectangle_marker_to_image(image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: JsValue) -> Result<Option<u32>, JsValue> {
+    add_rectangle_marker_to_image(image_id, x, y, width, height, translation, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_marker_info(marker_id: u32) -> JsValue {
+    match get_marker_info(marker_id) {
+        Some(marker) => to_value(&marker).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_point_marker_position(marker_id: u32, x: f64, y: f64, mode: JsValue) -> Result<bool, JsValue> {
+    update_point_marker_position(marker_id, x, y, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_rectangle_marker_geometry(marker_id: u32, x: f64, y: f64, width: f64, height: f64, mode: JsValue) -> Result<bool, JsValue> {
+    update_rectangle_marker_geometry(marker_id, x, y, width, height, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+// 多边形顶点以扁平数组 [x0, y0, x1, y1, ...] 传入
+#[cfg(feature = "wasm")]
+fn polygon_points(points: &[f64]) -> Vec<(f64, f64)> {
+    points.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_add_polygon_marker_to_image(image_id: u32, points: Vec<f64>, translation: Option<String>, mode: JsValue) -> Result<Option<u32>, JsValue> {
+    add_polygon_marker_to_image(image_id, polygon_points(&points), translation, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_polygon_marker_geometry(marker_id: u32, points: Vec<f64>, mode: JsValue) -> Result<bool, JsValue> {
+    update_polygon_marker_geometry(marker_id, polygon_points(&points), coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_end_polygon_marker_drag(marker_id: u32, points: Vec<f64>) -> bool {
+    use crate::common::dto::marker::MarkerGeometryDTO;
+    end_marker_drag(marker_id, MarkerGeometryDTO::Polygon { points: polygon_points(&points) }).unwrap_or(false)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_begin_marker_drag(marker_id: u32) -> Result<bool, JsValue> {
+    begin_marker_drag(marker_id).map_err(|e| JsValue::from_str(&e))
+}
+
+// width/height为空时表示点型marker
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_end_marker_drag(marker_id: u32, x: f64, y: f64, width: Option<f64>, height: Option<f64>) -> bool {
+    use crate::common::dto::marker::MarkerGeometryDTO;
+    let final_geometry = match (width, height) {
+        (Some(width), Some(height)) => MarkerGeometryDTO::Rectangle { x, y, width, height },
+        _ => MarkerGeometryDTO::Point { x, y },
+    };
+    end_marker_drag(marker_id, final_geometry).unwrap_or(false)
+}
+
+// geometry为MarkerGeometryDTO对象（百分比坐标）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_move_marker_to_image(marker_id: u32, target_image_id: u32, geometry: JsValue, position: Option<u32>) -> Result<bool, JsValue> {
+    let geometry = serde_wasm_bindgen::from_value(geometry)
+        .map_err(|e| JsValue::from_str(&format!("Invalid geometry: {}", e)))?;
+    crate::api::marker::move_marker_to_image(marker_id, target_image_id, geometry, position)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_marker_translation(marker_id: u32, translation: String) -> Result<bool, JsValue> {
+    update_marker_translation(marker_id, translation).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_marker_style(marker_id: u32, overlay_text: bool, horizontal: bool) -> Result<bool, JsValue> {
+    update_marker_style(marker_id, overlay_text, horizontal).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_marker_size_hint(marker_id: u32, size_hint: Option<f32>) -> Result<bool, JsValue> {
+    update_marker_size_hint(marker_id, size_hint).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_marker_tag(marker_id: u32, tag: Option<String>) -> Result<bool, JsValue> {
+    update_marker_tag(marker_id, tag).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_marker_note(marker_id: u32, note: Option<String>) -> Result<bool, JsValue> {
+    update_marker_note(marker_id, note).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_translation_history(marker_id: u32) -> JsValue {
+    to_value(&get_translation_history(marker_id)).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_revert_translation(marker_id: u32, history_index: usize) -> Result<bool, JsValue> {
+    revert_translation(marker_id, history_index).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_move_marker_order(marker_id: u32, new_index: u32) -> Result<bool, JsValue> {
+    marker::move_marker_order(marker_id, new_index).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_compact_marker_indices(image_id: u32) -> JsValue {
+    match marker::compact_marker_indices(image_id) {
+        Ok(count) => JsValue::from_f64(count as f64),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_compact_project_marker_indices(project_id: u32) -> JsValue {
+    match marker::compact_project_marker_indices(project_id) {
+        Ok(count) => JsValue::from_f64(count as f64),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_renumber_markers_by_reading_order(image_id: u32) -> Result<usize, JsValue> {
+    marker::renumber_markers_by_reading_order(image_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_merge_rectangle_markers(marker_ids: Vec<u32>) -> Result<u32, JsValue> {
+    marker::merge_rectangle_markers(marker_ids).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_select_next_marker(image_id: u32, current_marker_id: Option<u32>, backward: bool) -> Result<Option<u32>, JsValue> {
+    marker::select_next_marker(image_id, current_marker_id, backward).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_point_marker_full(marker_id: u32, x: f64, y: f64, translation: Option<String>, mode: JsValue) -> Result<bool, JsValue> {
+    update_point_marker_full(marker_id, x, y, translation, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_update_rectangle_marker_full(marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: JsValue) -> Result<bool, JsValue> {
+    update_rectangle_marker_full(marker_id, x, y, width, height, translation, coordinate_mode(mode)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_remove_marker_from_image(image_id: u32, marker_id: u32) -> Result<bool, JsValue> {
+    remove_marker_from_image(image_id, marker_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_image_markers(image_id: u32) -> Result<bool, JsValue> {
+    clear_image_markers(image_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_convert_rectangle_to_point_marker(marker_id: u32) -> Result<bool, JsValue> {
+    convert_rectangle_to_point_marker(marker_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_convert_point_to_rectangle_marker(marker_id: u32) -> Result<bool, JsValue> {
+    convert_point_to_rectangle_marker(marker_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_convert_polygon_to_rectangle_marker(marker_id: u32) -> Result<bool, JsValue> {
+    convert_polygon_to_rectangle_marker(marker_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_replace_in_translations(project_id: u32, find: String, replace: String, case_sensitive: bool, whole_word: bool, operation_id: Option<String>) -> JsValue {
+    match replace_in_translations(project_id, find, replace, case_sensitive, whole_word, operation_id) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_analyze_translations(project_id: u32, source_similarity: Option<f64>) -> JsValue {
+    match analyze_translations(project_id, source_similarity) {
+        Ok(issues) => to_value(&issues).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+// 统计相关
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_stats() -> JsValue {
+    to_value(&get_stats()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_cancel_operation(operation_id: String) -> bool {
+    cancel_operation(operation_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_event_channel_stats() -> JsValue {
+    to_value(&get_event_channel_stats()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_frontend_event_filter(patterns: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value(patterns) {
+        Ok(patterns) => {
+            set_frontend_event_filter(patterns);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_frontend_event_filter_stats() -> JsValue {
+    to_value(&get_frontend_event_filter_stats()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_recent_logs(level_filter: JsValue, limit: usize) -> JsValue {
+    let level_filter = serde_wasm_bindgen::from_value(level_filter).unwrap_or(None);
+    to_value(&get_recent_logs(level_filter, limit)).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_log_level(level: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value(level) {
+        Ok(level) => {
+            set_log_level(level);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_log_level() -> JsValue {
+    to_value(&get_log_level()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_audit_enabled(enabled: bool) {
+    set_audit_enabled(enabled)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_is_audit_enabled() -> bool {
+    is_audit_enabled()
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_plugin_audit_log(plugin_id: Option<String>, limit: Option<usize>) -> JsValue {
+    to_value(&get_plugin_audit_log(plugin_id, limit)).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_register_plugin_config_schema(plugin_id: String, schema: JsValue) {
+    match serde_wasm_bindgen::from_value(schema) {
+        Ok(schema) => register_plugin_config_schema(plugin_id, schema),
+        Err(e) => Logger::error(&format!("Invalid config schema: {}", e)),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_project_stats(project_id: u32) -> JsValue {
+    match get_project_stats(project_id) {
+        Some(stats) => to_value(&stats).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_text_stats(project_id: u32) -> JsValue {
+    match get_text_stats(project_id) {
+        Ok(stats) => to_value(&stats).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_text_count_config(cjk_char_as_word: bool) -> bool {
+    set_text_count_config(cjk_char_as_word).is_ok()
+}
+
+// 数据清理
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_all_data() {
+    clear_all_data()
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_project_data(project_id: u32) -> bool {
+    clear_project_data(project_id)
+}
+
+// 撤销重做相关函数
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_undo(project_id: u32) -> JsValue {
+    let result = undo(project_id);
+    to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_redo(project_id: u32) -> JsValue {
+    let result = redo(project_id);
+    to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_undo_redo_history(project_id: u32) {
+    clear_undo_redo_history(project_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_begin_undo_group(project_id: u32, description: String) -> Result<(), JsValue> {
+    begin_undo_group(project_id, description).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_end_undo_group(project_id: u32) -> Result<(), JsValue> {
+    end_undo_group(project_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_abort_undo_group(project_id: u32) -> Result<bool, JsValue> {
+    abort_undo_group(project_id).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_all_undo_redo_history() {
+    clear_all_undo_redo_history()
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_undo_coalesce_window(window_ms: u32) {
+    set_undo_coalesce_window(window_ms)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_undo_coalesce_window() -> u32 {
+    get_undo_coalesce_window()
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_mark_project_saved(project_id: u32) -> bool {
+    mark_project_saved(project_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_has_unsaved_changes(project_id: u32) -> bool {
+    has_unsaved_changes(project_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_unsaved_project_ids() -> Vec<u32> {
+    get_unsaved_project_ids()
+}
+
+// 缩略图相关函数
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_thumbnail(image_id: u32) -> JsValue {
+    match request_thumbnail(image_id) {
+        Ok(_) => JsValue::from_str("ok"),
+        Err(e) => JsValue::from_str(&e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_thumbnails_batch(image_ids: &[u32]) -> JsValue {
+    match request_thumbnails_batch(image_ids.to_vec()) {
+        Ok(_) => JsValue::from_str("ok"),
+        Err(e) => JsValue::from_str(&e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_thumbnail(image_id: u32) -> JsValue {
+    match get_thumbnail(image_id) {
+        Some(thumbnail) => to_value(&thumbnail).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_has_thumbnail(image_id: u32) -> bool {
+    has_thumbnail(image_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_prefetch_thumbnails_around(image_id: u32, radius: Option<u32>) -> JsValue {
+    match prefetch_thumbnails_around(image_id, radius) {
+        Ok(_) => JsValue::from_str("ok"),
+        Err(e) => JsValue::from_str(&e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_thumbnail_cache() -> JsValue {
+    match clear_thumbnail_cache() {
+        Ok(count) => JsValue::from_f64(count as f64),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_thumbnail_cache_stats() -> JsValue {
+    to_value(&get_thumbnail_cache_stats()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_image_cache_limit(limit_mb: u32) -> JsValue {
+    match set_image_cache_limit(limit_mb) {
+        Ok(_) => JsValue::from_str("ok"),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_cache_stats() -> JsValue {
+    to_value(&get_image_cache_stats()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_marker_thumbnail(marker_id: u32, max_edge_px: u32) -> Result<Vec<u8>, JsValue> {
+    get_marker_thumbnail(marker_id, max_edge_px).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_marker_thumbnails(image_id: u32, max_edge_px: u32) -> Result<JsValue, JsValue> {
+    let thumbnails = get_marker_thumbnails(image_id, max_edge_px).map_err(|e| JsValue::from_str(&e))?;
+    to_value(&thumbnails).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// 把图片所有标记的预览图依次写入共享缓冲区（需先wasm_init_shared_buffer），避免逐个复制
+/// 返回[marker_id, offset, length, ...]三元组，JS端按偏移从数据区切出每张图
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_write_marker_thumbnails_to_shared_buffer(image_id: u32, max_edge_px: u32) -> Result<Vec<u32>, JsValue> {
+    use super::shared_buffer::{write_data_to_shared_buffer, DataFormatEnum};
+
+    let thumbnails = get_marker_thumbnails(image_id, max_edge_px).map_err(|e| JsValue::from_str(&e))?;
+    let mut packed = Vec::new();
+    let mut layout = Vec::with_capacity(thumbnails.len() * 3);
+    for thumbnail in thumbnails {
+        layout.extend([thumbnail.marker_id.0, packed.len() as u32, thumbnail.data.len() as u32]);
+        packed.extend_from_slice(&thumbnail.data);
+    }
+    write_data_to_shared_buffer(image_id, DataFormatEnum::Jpeg, &packed).map_err(|e| JsValue::from_str(&e))?;
+    Ok(layout)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_thumbnail_order(project_id: u32) -> Vec<u32> {
+    get_thumbnail_order(project_id)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_image_pinned(image_id: u32, pinned: bool) -> bool {
+    set_image_pinned(image_id, pinned)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_image_reviewed(image_id: u32, reviewed: bool, note: Option<String>) -> bool {
+    set_image_reviewed(image_id, reviewed, note)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_image_locked(image_id: u32, locked: bool) -> Result<bool, JsValue> {
+    set_image_locked(image_id, locked).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_rename_image(image_id: u32, new_name: String) -> Result<bool, JsValue> {
+    rename_image(image_id, new_name).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_image_view_state(image_id: u32, view_state: JsValue) -> Result<bool, JsValue> {
+    let view_state = if view_state.is_null() || view_state.is_undefined() {
+        None
+    } else {
+        Some(serde_wasm_bindgen::from_value(view_state)
+            .map_err(|e| JsValue::from_str(&format!("Invalid view state: {}", e)))?)
+    };
+    set_image_view_state(image_id, view_state).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_view_state(image_id: u32) -> JsValue {
+    match get_image_view_state(image_id) {
+        Ok(state) => to_value(&state).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_select_image(image_id: u32) -> JsValue {
+    match select_image(image_id) {
+        Ok(state) => to_value(&state).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_downscale_settings() -> JsValue {
+    match get_downscale_settings() {
+        Ok(settings) => to_value(&settings).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_downscale_settings(settings: JsValue) -> JsValue {
+    let result = serde_wasm_bindgen::from_value(settings)
+        .map_err(|e| format!("Invalid settings: {}", e))
+        .and_then(set_downscale_settings);
+    match result {
+        Ok(()) => JsValue::NULL,
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_thumbnail_sort(project_id: u32, order: String) -> bool {
+    use crate::common::ThumbnailOrder;
+    
+    let order = match order.as_str() {
+        "name" => ThumbnailOrder::Name,
+        "completion" => ThumbnailOrder::Completion,
+        _ => ThumbnailOrder::Manual,
+    };
+    
+    set_thumbnail_sort(project_id, order)
+}
+
+// SharedArrayBuffer support
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_init_shared_buffer(buffer: js_sys::SharedArrayBuffer) -> Result<(), JsValue> {
+    super::shared_buffer::init_shared_buffer_impl(buffer)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn wasm_add_image_from_shared_buffer(project_id: u32, name: Option<String>) -> Result<u32, JsValue> {
+    super::shared_buffer::add_image_from_shared_buffer_impl(project_id, name).await
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn wasm_add_image_from_clipboard_shared_buffer(project_id: u32, suggested_name: Option<String>) -> Result<u32, JsValue> {
+    super::shared_buffer::add_image_from_clipboard_shared_buffer_impl(project_id, suggested_name).await
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_cleanup_orphaned_images() -> u32 {
+    // For now, return 0 as we're not tracking orphaned images in memory
+    // This will be implemented when we add proper image lifecycle management
+    0
+}
+
+// 页面是否跨源隔离（SharedArrayBuffer可用），不隔离时第一次调用发送降级警告事件
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_is_cross_origin_isolated() -> bool {
+    let global = js_sys::global();
+    let isolated = js_sys::Reflect::get(&global, &"crossOriginIsolated".into())
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+        && js_sys::Reflect::has(&global, &"SharedArrayBuffer".into()).unwrap_or(false);
+    crate::api::stats::report_cross_origin_isolation(isolated)
+}
+
+// 事件系统初始化
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_init_event_system() {
+    // 标记当前线程为主线程
+    crate::common::events::mark_as_main_thread();
+    
+    // 在WASM环境中初始化事件发射器
+    let event_emitter = crate::common::events::WasmEventEmitter::new();
+    crate::common::EVENT_SYSTEM.register_emitter(
+        "wasm".to_string(),
+        Box::new(event_emitter)
+    );
+    Logger::info("WASM event system initialized");
+}
+
+// 设置Worker事件回调
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_set_event_callback(callback: js_sys::Function) {
+    use wasm_bindgen::JsValue;
+    
+    let callback_clone = callback.clone();
+    crate::common::events::set_worker_event_callback(move |event_name: String, event_data: serde_json::Value| {
+        let event_name_js = JsValue::from_str(&event_name);
+        let event_data_js = match js_sys::JSON::parse(&event_data.to_string()) {
+            Ok(value) => value,
+            Err(_) => {
+                Logger::error("Failed to parse event data JSON for callback");
+                return;
+            }
+        };
+
         if let Err(e) = callback_clone.call2(&JsValue::NULL, &event_name_js, &event_data_js) {
             Logger::error(&format!("Event callback error: {:?}", e));
         }
-    });
+    });
+}
+
+// bf文件预检，失败时返回 { error, kind }
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_validate_bf_data(data: &[u8]) -> JsValue {
+    match crate::service::io::bf::validate_bf(data) {
+        Ok(summary) => to_value(&summary).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(e.message())).unwrap();
+            js_sys::Reflect::set(&error_obj, &"kind".into(), &JsValue::from_str(e.kind())).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+// 项目中文件已不存在的图片
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_missing_images(project_id: u32) -> JsValue {
+    to_value(&get_missing_images(project_id)).unwrap_or(JsValue::NULL)
+}
+
+// 崩溃检测和恢复握手（JS侧协议）
+// 1. 主线程定期调用wasm_get_health，tick不再增长或调用抛出异常即认为worker已崩溃；
+//    linearMemoryBytes接近上限时可以提示用户，避免继续加载大图
+// 2. 项目有修改时（防抖）调用wasm_export_recovery_snapshot，把返回的Uint8Array按项目存入IndexedDB；
+//    快照只含元数据和标记，不含图片；正常关闭或保存项目后删除快照
+// 3. 重启worker后若IndexedDB中还有快照，询问用户是否恢复；恢复时调用wasm_import_recovery_snapshot，
+//    返回新项目ID。图片都是占位的丢失状态（会收到image:missing），
+//    用户重新选择文件后用wasm_update_image_data_from_binary逐张替换
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_health() -> JsValue {
+    to_value(&get_health()).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_export_recovery_snapshot(project_id: u32) -> JsValue {
+    match export_recovery_snapshot(project_id) {
+        Ok(data) => {
+            let uint8_array = js_sys::Uint8Array::new_with_length(data.len() as u32);
+            uint8_array.copy_from(&data);
+            uint8_array.into()
+        },
+        Err(e) => {
+            Logger::error(&format!("Failed to export recovery snapshot: {}", e));
+            JsValue::NULL
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_import_recovery_snapshot(data: Vec<u8>) -> JsValue {
+    match import_recovery_snapshot(data) {
+        Ok(project_id) => to_value(&project_id).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            Logger::error(&format!("Failed to import recovery snapshot: {}", e));
+            JsValue::NULL
+        }
+    }
+}
+
+// LabelPlus文件验证
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_validate_labelplus_file(content: String) -> JsValue {
+    match validate_labelplus_file(&content) {
+        Ok(data) => to_value(&data).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+// 导入LabelPlus数据
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_import_labelplus_data(project_id: u32, content: String) -> JsValue {
+    match import_labelplus_data(project_id, &content) {
+        Ok(()) => JsValue::from_str("ok"),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+// 合并导入LabelPlus数据，options可为空
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_import_labelplus_merge(project_id: u32, content: String, options: JsValue) -> JsValue {
+    let error = |message: String| -> JsValue {
+        let error_obj = js_sys::Object::new();
+        js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&message)).unwrap();
+        error_obj.into()
+    };
+    let options = if options.is_undefined() || options.is_null() {
+        Default::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(e) => return error(format!("Invalid options: {}", e)),
+        }
+    };
+    match import_labelplus_merge(project_id, &content, options) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => error(e),
+    }
 }
 
-// LabelPlus文件验证
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_validate_labelplus_file(content: String) -> JsValue {
-    match validate_labelplus_file(&content) {
-        Ok(data) => to_value(&data).unwrap_or(JsValue::NULL),
+pub fn wasm_export_labelplus_data(project_id: u32) -> JsValue {
+    match export_labelplus_data(project_id) {
+        Ok(content) => JsValue::from_str(&content),
         Err(e) => {
             let error_obj = js_sys::Object::new();
             js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
@@ -488,12 +1656,11 @@ pub fn wasm_validate_labelplus_file(content: String) -> JsValue {
     }
 }
 
-// 导入LabelPlus数据
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_import_labelplus_data(project_id: u32, content: String) -> JsValue {
-    match import_labelplus_data(project_id, &content) {
-        Ok(()) => JsValue::from_str("ok"),
+pub fn wasm_export_labelplus_data_range(project_id: u32, image_ids: Vec<u32>) -> JsValue {
+    match export_labelplus_data_range(project_id, image_ids) {
+        Ok(content) => JsValue::from_str(&content),
         Err(e) => {
             let error_obj = js_sys::Object::new();
             js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
@@ -504,9 +1671,98 @@ pub fn wasm_import_labelplus_data(project_id: u32, content: String) -> JsValue {
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_export_labelplus_data(project_id: u32) -> JsValue {
-    match export_labelplus_data(project_id) {
+pub fn wasm_export_typesetting_data(project_id: u32, format: JsValue, options: JsValue) -> JsValue {
+    let error = |message: String| -> JsValue {
+        let error_obj = js_sys::Object::new();
+        js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&message)).unwrap();
+        error_obj.into()
+    };
+    let format = match serde_wasm_bindgen::from_value(format) {
+        Ok(format) => format,
+        Err(e) => return error(format!("Invalid format: {}", e)),
+    };
+    let options = if options.is_undefined() || options.is_null() {
+        Default::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(e) => return error(format!("Invalid options: {}", e)),
+        }
+    };
+    match export_typesetting_data(project_id, format, options) {
         Ok(content) => JsValue::from_str(&content),
+        Err(e) => error(e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_project_content_hash(project_id: u32) -> JsValue {
+    match get_project_content_hash(project_id) {
+        Ok(hash) => JsValue::from_str(&hash),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_import_typesetting_data(project_id: u32, content: String, format: JsValue, options: JsValue) -> JsValue {
+    let error = |message: String| -> JsValue {
+        let error_obj = js_sys::Object::new();
+        js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&message)).unwrap();
+        error_obj.into()
+    };
+    let format = match serde_wasm_bindgen::from_value(format) {
+        Ok(format) => format,
+        Err(e) => return error(format!("Invalid format: {}", e)),
+    };
+    let options = if options.is_undefined() || options.is_null() {
+        Default::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(e) => return error(format!("Invalid options: {}", e)),
+        }
+    };
+    match import_typesetting_data(project_id, &content, format, options) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => error(e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_export_annotated_images(project_id: u32, options: JsValue) -> JsValue {
+    let options = if options.is_undefined() || options.is_null() {
+        Default::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(e) => {
+                let error_obj = js_sys::Object::new();
+                js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&format!("Invalid options: {}", e))).unwrap();
+                return error_obj.into();
+            }
+        }
+    };
+    match export_annotated_images(project_id, options) {
+        Ok(images) => {
+            // 返回 [{ name, data: Uint8Array }]，由JS端打包为zip下载
+            let result = js_sys::Array::new();
+            for (name, data) in images {
+                let item = js_sys::Object::new();
+                let uint8_array = js_sys::Uint8Array::new_with_length(data.len() as u32);
+                uint8_array.copy_from(&data);
+                js_sys::Reflect::set(&item, &"name".into(), &JsValue::from_str(&name)).unwrap();
+                js_sys::Reflect::set(&item, &"data".into(), &uint8_array).unwrap();
+                result.push(&item);
+            }
+            result.into()
+        }
         Err(e) => {
             let error_obj = js_sys::Object::new();
             js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
@@ -519,15 +1775,15 @@ pub fn wasm_export_labelplus_data(project_id: u32) -> JsValue {
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_available_ocr_services() -> JsValue {
-    let services = get_available_ocr_services();
+pub fn wasm_get_available_ocr_services(project_id: Option<u32>) -> JsValue {
+    let services = get_available_ocr_services(project_id);
     to_value(&services).unwrap_or(JsValue::NULL)
 }
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_get_available_translation_services() -> JsValue {
-    let services = get_available_translation_services();
+pub fn wasm_get_available_translation_services(project_id: Option<u32>) -> JsValue {
+    let services = get_available_translation_services(project_id);
     to_value(&services).unwrap_or(JsValue::NULL)
 }
 
@@ -545,6 +1801,59 @@ pub fn wasm_get_bunny_cache(marker_id: u32) -> JsValue {
     }
 }
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_translation_result(marker_id: u32) -> JsValue {
+    match get_translation_result(crate::common::MarkerId(marker_id)) {
+        Ok(Some(result)) => to_value(&result).unwrap_or(JsValue::NULL),
+        Ok(None) => JsValue::NULL,
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_accept_machine_translation(marker_id: u32) -> JsValue {
+    match crate::api::bunny::accept_machine_translation(crate::common::MarkerId(marker_id)) {
+        Ok(changed) => JsValue::from_bool(changed),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_accept_all_for_image(image_id: u32, only_empty: bool) -> JsValue {
+    match crate::api::bunny::accept_all_for_image(crate::common::ImageId(image_id), only_empty) {
+        Ok(report) => to_value(&report).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_reject_machine_translation(marker_id: u32) -> JsValue {
+    match crate::api::bunny::reject_machine_translation(crate::common::MarkerId(marker_id)) {
+        Ok(cleared) => JsValue::from_bool(cleared),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_update_original_text(marker_id: u32, text: String, model: String) -> JsValue {
@@ -589,6 +1898,36 @@ pub fn wasm_request_ocr(marker_id: u32, image_id: u32, project_id: u32, service_
     }
 }
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_ocr_batch(marker_ids: Vec<u32>, project_id: u32, service_id: String) -> JsValue {
+    match request_ocr_batch(
+        marker_ids.into_iter().map(crate::common::MarkerId).collect(),
+        crate::common::ProjectId(project_id),
+        service_id
+    ) {
+        Ok(items) => to_value(&items).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_translate_all_untranslated(project_id: u32, service_id: String, operation_id: Option<String>) -> JsValue {
+    match translate_all_untranslated(crate::common::ProjectId(project_id), service_id, operation_id) {
+        Ok(task_ids) => to_value(&task_ids).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_request_translation(marker_id: u32, image_id: u32, project_id: u32, service_id: String, text: String) -> JsValue {
@@ -610,8 +1949,66 @@ pub fn wasm_request_translation(marker_id: u32, image_id: u32, project_id: u32,
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_handle_ocr_completed(task_id: String, marker_id: u32, text: String, model: String) -> JsValue {
-    match handle_ocr_completed(task_id, crate::common::MarkerId(marker_id), text, model) {
+pub fn wasm_handle_ocr_completed(task_id: String, marker_id: u32, text: String, model: String, regions: JsValue) -> JsValue {
+    // 不支持文本行位置的插件不返回regions
+    let regions = serde_wasm_bindgen::from_value::<Option<Vec<crate::common::OcrRegion>>>(regions)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    match handle_ocr_completed(task_id, crate::common::MarkerId(marker_id), text, model, regions) {
+        Ok(_) => JsValue::undefined(),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_split_marker_by_ocr_regions(marker_id: u32, regions: JsValue) -> JsValue {
+    // 不传regions时使用最近一次OCR返回的区域
+    let regions = serde_wasm_bindgen::from_value::<Option<Vec<crate::common::OcrRegion>>>(regions)
+        .ok()
+        .flatten();
+    match crate::api::bunny::split_marker_by_ocr_regions(crate::common::MarkerId(marker_id), regions) {
+        Ok(ids) => to_value(&ids).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_available_detection_services(project_id: Option<u32>) -> JsValue {
+    let services = crate::api::bunny::get_available_detection_services(project_id);
+    to_value(&services).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_detection(image_id: u32, service_id: String) -> JsValue {
+    match crate::api::bunny::request_detection(crate::common::ImageId(image_id), service_id) {
+        Ok(task_id) => task_id.into(),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_handle_detection_completed(task_id: String, model: String, regions: JsValue) -> JsValue {
+    let result = serde_wasm_bindgen::from_value::<Vec<crate::common::DetectedRegion>>(regions)
+        .map_err(|e| format!("Invalid regions: {}", e))
+        .and_then(|regions| crate::api::bunny::handle_detection_completed(task_id, model, regions));
+    match result {
         Ok(_) => JsValue::undefined(),
         Err(e) => {
             let error_obj = js_sys::Object::new();
@@ -623,8 +2020,30 @@ pub fn wasm_handle_ocr_completed(task_id: String, marker_id: u32, text: String,
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn wasm_handle_translation_completed(task_id: String, marker_id: u32, translated_text: String, service: String) -> JsValue {
-    match handle_translation_completed(task_id, crate::common::MarkerId(marker_id), translated_text, service) {
+pub fn wasm_create_markers_from_detection(image_id: u32, regions: JsValue) -> JsValue {
+    let result = serde_wasm_bindgen::from_value::<Vec<crate::common::DetectedRegion>>(regions)
+        .map_err(|e| format!("Invalid regions: {}", e))
+        .and_then(|regions| crate::api::bunny::create_markers_from_detection(crate::common::ImageId(image_id), regions));
+    match result {
+        Ok(ids) => to_value(&ids).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_handle_translation_completed(task_id: String, marker_id: u32, translated_text: String, service: String, warnings: JsValue, metadata: JsValue) -> JsValue {
+    // 旧插件不返回warnings和metadata，解析失败时当作没有
+    let warnings = serde_wasm_bindgen::from_value::<Option<Vec<crate::common::TranslationWarning>>>(warnings)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let metadata = serde_wasm_bindgen::from_value::<Option<serde_json::Value>>(metadata).ok().flatten();
+    match handle_translation_completed(task_id, crate::common::MarkerId(marker_id), translated_text, service, warnings, metadata) {
         Ok(_) => JsValue::undefined(),
         Err(e) => {
             let error_obj = js_sys::Object::new();
@@ -660,6 +2079,101 @@ pub fn wasm_clear_bunny_cache(marker_id: u32) -> JsValue {
     }
 }
 
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_pipeline(marker_id: u32, image_id: u32, project_id: u32, ocr_service: String, translation_service: String) -> JsValue {
+    match request_pipeline(
+        crate::common::MarkerId(marker_id),
+        crate::common::ImageId(image_id),
+        crate::common::ProjectId(project_id),
+        ocr_service,
+        translation_service
+    ) {
+        Ok(pipeline_id) => pipeline_id.into(),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_request_pipeline_batch(image_id: u32, ocr_service: String, translation_service: String) -> JsValue {
+    match request_pipeline_batch(crate::common::ImageId(image_id), ocr_service, translation_service) {
+        Ok(items) => to_value(&items).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_pipeline(pipeline_id: String) -> JsValue {
+    match get_pipeline(pipeline_id) {
+        Ok(pipeline) => to_value(&pipeline).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_project_pipelines(project_id: u32) -> JsValue {
+    match get_project_pipelines(crate::common::ProjectId(project_id)) {
+        Ok(pipelines) => to_value(&pipelines).unwrap_or(JsValue::NULL),
+        Err(_) => JsValue::NULL,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_task_history(project_id: u32, limit: Option<u32>) -> JsValue {
+    match get_task_history(crate::common::ProjectId(project_id), limit.map(|limit| limit as usize)) {
+        Ok(history) => to_value(&history).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_task_metrics(project_id: u32) -> JsValue {
+    match get_task_metrics(crate::common::ProjectId(project_id)) {
+        Ok(metrics) => to_value(&metrics).unwrap_or(JsValue::NULL),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_get_image_task_progress(image_id: u32) -> JsValue {
+    to_value(&get_image_task_progress(crate::common::ImageId(image_id))).unwrap_or(JsValue::NULL)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_clear_task_history(project_id: u32) -> JsValue {
+    match clear_task_history(crate::common::ProjectId(project_id)) {
+        Ok(_) => JsValue::undefined(),
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &e.into()).unwrap();
+            error_obj.into()
+        }
+    }
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_save_project(project_id: u32) -> JsValue {
@@ -677,4 +2191,21 @@ pub fn wasm_save_project(project_id: u32) -> JsValue {
             error_obj.into()
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn wasm_save_project_range(project_id: u32, image_ids: Vec<u32>) -> JsValue {
+    match save_project_range(project_id, image_ids) {
+        Ok(data) => {
+            let uint8_array = js_sys::Uint8Array::new_with_length(data.len() as u32);
+            uint8_array.copy_from(&data);
+            uint8_array.into()
+        },
+        Err(e) => {
+            let error_obj = js_sys::Object::new();
+            js_sys::Reflect::set(&error_obj, &"error".into(), &JsValue::from_str(&e)).unwrap();
+            error_obj.into()
+        }
+    }
+}
@@ -1,9 +1,5 @@
 // WASM环境下的事件通道，用于跨线程通信
 #[cfg(feature = "wasm")]
-use std::sync::{Arc, Mutex};
-#[cfg(feature = "wasm")]
-use std::collections::VecDeque;
-#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 #[cfg(feature = "wasm")]
 use serde_json::Value;
@@ -11,47 +7,41 @@ use serde_json::Value;
 use js_sys;
 
 #[cfg(feature = "wasm")]
-pub struct EventMessage {
-    pub event_name: String,
-    pub event_data: Value,
-}
+use crate::common::event_queue::{EVENT_CHANNEL, EventFrame, SendOutcome};
 
+/// 每次轮询最多取出的帧数，消费方处理不过来时剩下的留到下一次
 #[cfg(feature = "wasm")]
-pub struct EventChannel {
-    queue: Arc<Mutex<VecDeque<EventMessage>>>,
-}
+pub const MAX_EVENTS_PER_POLL: usize = 256;
+
+/// 事件通道句柄，实际的有界队列见common::event_queue
+#[cfg(feature = "wasm")]
+#[derive(Clone, Default)]
+pub struct EventChannel;
 
 #[cfg(feature = "wasm")]
 impl EventChannel {
     pub fn new() -> Self {
-        Self {
-            queue: Arc::new(Mutex::new(VecDeque::new())),
-        }
+        Self
     }
 
-    /// 发送事件到队列（线程安全）
-    pub fn send(&self, event_name: String, event_data: Value) {
-        if let Ok(mut queue) = self.queue.lock() {
-            queue.push_back(EventMessage { event_name, event_data });
+    /// 发送事件到队列（线程安全），超过高水位时返回WouldBlock
+    pub fn send(&self, event_name: String, event_data: Value) -> SendOutcome {
+        match EVENT_CHANNEL.lock() {
+            Ok(mut queue) => queue.send(event_name, event_data),
+            Err(_) => SendOutcome::Dropped,
         }
     }
 
-    /// 获取所有待处理的事件
-    pub fn drain_events(&self) -> Vec<EventMessage> {
-        if let Ok(mut queue) = self.queue.lock() {
-            queue.drain(..).collect()
-        } else {
-            Vec::new()
+    /// 取出一批待处理的事件
+    pub fn drain_events(&self) -> Vec<EventFrame> {
+        match EVENT_CHANNEL.lock() {
+            Ok(mut queue) => queue.drain(MAX_EVENTS_PER_POLL),
+            Err(_) => Vec::new(),
         }
     }
-}
 
-#[cfg(feature = "wasm")]
-impl Clone for EventChannel {
-    fn clone(&self) -> Self {
-        Self {
-            queue: self.queue.clone(),
-        }
+    pub fn is_backpressured(&self) -> bool {
+        EVENT_CHANNEL.lock().map(|queue| queue.is_backpressured()).unwrap_or(false)
     }
 }
 
@@ -65,6 +55,8 @@ lazy_static::lazy_static! {
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn wasm_poll_events() -> JsValue {
+    // WASM下没有定时器，借轮询发出被节流的任务进度
+    crate::api::bunny::tick_bunny_progress();
     let events = WASM_EVENT_CHANNEL.drain_events();
     
     if events.is_empty() {
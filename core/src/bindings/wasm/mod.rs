@@ -15,34 +15,11 @@ pub fn wasm_main() {
     // 设置panic hook以便在浏览器控制台中显示panic信息
     console_error_panic_hook::set_once();
     
-    // 设置自定义panic处理器以通过logger记录更详细的信息
+    // 设置自定义panic处理器：通过logger记录详细信息，并通知前端显示恢复对话框
     std::panic::set_hook(Box::new(|panic_info| {
-        let msg = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
-            s.to_string()
-        } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
-            s.clone()
-        } else {
-            "Unknown panic payload".to_string()
-        };
-        
-        let location = if let Some(location) = panic_info.location() {
-            format!(" at {}:{}:{}", location.file(), location.line(), location.column())
-        } else {
-            String::new()
-        };
-        
-        let panic_msg = format!("PANIC: {}{}", msg, location);
-        
-        // 通过我们的logger系统记录，使用log_error_trace!来包含堆栈信息
-        crate::common::log_error_trace!("Rust panic occurred", serde_json::json!({
-            "panic_message": msg,
-            "location": location,
-            "full_message": panic_msg,
-            "type": "rust_panic"
-        }));
-        
+        crate::common::report_panic(panic_info);
         // 同时输出到控制台以便立即可见
-        Logger::error(&panic_msg);
+        console_error_panic_hook::hook(panic_info);
     }));
     
     // 初始化日志
@@ -20,7 +20,7 @@ pub mod wasm_stream {
         Error = 4,
     }
     
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
     pub enum DataFormatEnum {
         // Image formats
         Jpeg = 0,
@@ -31,6 +31,8 @@ pub mod wasm_stream {
         // Project formats
         Labelplus = 10,
         Bubblefish = 11,
+        // Archive formats (zip/cbz)
+        Archive = 12,
     }
     
     impl DataFormatEnum {
@@ -209,6 +211,22 @@ pub mod wasm_stream {
             Ok(chunk)
         }
         
+        /// 一次性写入完整数据并标记为完成（core -> JS方向），JS端在调用返回后直接读取数据区
+        pub fn write_all(&self, data_id: u32, format: DataFormatEnum, data: &[u8]) -> Result<(), String> {
+            if data.len() > self.buffer_data_size {
+                return Err(format!("Data size {} exceeds shared buffer size {}", data.len(), self.buffer_data_size));
+            }
+            self.data_array.subarray(0, data.len() as u32).copy_from(data);
+            self.atomic_store(0, data.len() as i32);
+            self.atomic_store(1, 0);
+            self.atomic_store(2, data.len() as i32);
+            self.atomic_store(3, data.len() as i32);
+            self.atomic_store(5, data_id as i32);
+            self.atomic_store(6, format as i32);
+            self.atomic_store(4, StreamStatus::Complete as i32);
+            Ok(())
+        }
+
         /// Atomic load operation
         pub fn atomic_load(&self, index: usize) -> i32 {
             self.header_array.get_index(index as u32)
@@ -311,6 +329,7 @@ pub async fn read_data_from_shared_buffer() -> Result<(u32, DataFormatEnum, Vec<
         3 => DataFormatEnum::Webp,
         4 => DataFormatEnum::Bmp,
         10 => DataFormatEnum::Labelplus,
+        12 => DataFormatEnum::Archive,
         _ => return Err("Invalid data format".to_string()),
     };
     
@@ -387,6 +406,17 @@ pub async fn read_data_from_shared_buffer() -> Result<(u32, DataFormatEnum, Vec<
     Ok((data_id, format, data))
 }
 
+/// Write data to the shared buffer for JS to read
+#[cfg(feature = "wasm")]
+pub fn write_data_to_shared_buffer(data_id: u32, format: DataFormatEnum, data: &[u8]) -> Result<(), String> {
+    SHARED_BUFFER_READER.with(|r| {
+        let reader_ref = r.borrow();
+        let reader = reader_ref.as_ref()
+            .ok_or("Shared buffer not initialized")?;
+        reader.write_all(data_id, format, data)
+    })
+}
+
 /// Add image from SharedArrayBuffer (internal implementation)
 #[cfg(feature = "wasm")]
 pub async fn add_image_from_shared_buffer_impl(project_id: u32, name: Option<String>) -> Result<u32, JsValue> {
@@ -404,6 +434,16 @@ pub async fn add_image_from_shared_buffer_impl(project_id: u32, name: Option<Str
     Ok(image_id)
 }
 
+/// Add a pasted image from SharedArrayBuffer (internal implementation)
+/// 格式以文件头为准，不使用流里声明的格式
+#[cfg(feature = "wasm")]
+pub async fn add_image_from_clipboard_shared_buffer_impl(project_id: u32, suggested_name: Option<String>) -> Result<u32, JsValue> {
+    let (_, _, data) = read_data_from_shared_buffer().await
+        .map_err(|e| JsValue::from_str(&e))?;
+    crate::api::add_image_from_clipboard_bytes(project_id, data, suggested_name)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
 
 /// Create opening project from SharedArrayBuffer with file type detection (internal implementation)
 #[cfg(feature = "wasm")]
@@ -422,3 +462,18 @@ pub async fn create_opening_project_from_shared_buffer_with_type_impl(file_exten
         .map_err(|e| JsValue::from_str(&e))
 }
 
+/// Create opening project from a zip/cbz archive in SharedArrayBuffer (internal implementation)
+#[cfg(feature = "wasm")]
+pub async fn create_opening_project_from_archive_shared_buffer_impl(archive_name: String, project_name: String) -> Result<JsValue, JsValue> {
+    // Read data from shared buffer
+    let (_, format, data) = read_data_from_shared_buffer().await
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    if format != DataFormatEnum::Archive {
+        return Err(JsValue::from_str("Not an archive format"));
+    }
+    
+    let report = crate::api::create_opening_project_from_archive(data, archive_name, project_name, None)
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+}
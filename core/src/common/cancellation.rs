@@ -0,0 +1,105 @@
+// 长时间操作的协作式取消 - 操作定期检查令牌，被取消时停止并返回已完成的部分结果
+// 调用方传入自己生成的operation_id，另一个调用通过cancel_operation(operation_id)取消
+// 注意：WASM端的核心跑在单线程Worker里，操作执行期间收不到取消请求，实际只对桌面端有效
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref OPERATIONS: Mutex<HashMap<String, CancellationToken>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 可取消操作的结束状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStatus {
+    #[default]
+    Completed,
+    /// 被取消，结果只包含取消前完成的部分
+    Cancelled,
+}
+
+impl OperationStatus {
+    pub fn of(token: &CancellationToken) -> Self {
+        if token.is_cancelled() { Self::Cancelled } else { Self::Completed }
+    }
+}
+
+/// 已登记的操作，离开作用域时自动注销
+pub struct OperationHandle {
+    operation_id: Option<String>,
+    token: CancellationToken,
+}
+
+impl OperationHandle {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for OperationHandle {
+    fn drop(&mut self) {
+        if let Some(operation_id) = &self.operation_id
+            && let Ok(mut operations) = OPERATIONS.lock()
+        {
+            operations.remove(operation_id);
+        }
+    }
+}
+
+/// 登记一个可取消的操作；没有operation_id时返回不能从外部取消的令牌
+pub fn register_operation(operation_id: Option<String>) -> OperationHandle {
+    let token = CancellationToken::new();
+    if let Some(operation_id) = &operation_id
+        && let Ok(mut operations) = OPERATIONS.lock()
+    {
+        operations.insert(operation_id.clone(), token.clone());
+    }
+    OperationHandle { operation_id, token }
+}
+
+/// 取消正在进行的操作，操作不存在（未开始或已结束）时返回false
+pub fn cancel_operation(operation_id: &str) -> bool {
+    match OPERATIONS.lock() {
+        Ok(operations) => operations.get(operation_id).map(CancellationToken::cancel).is_some(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_cancel_and_unregister() {
+        let handle = register_operation(Some("op-cancel-test".to_string()));
+        assert_eq!(OperationStatus::of(handle.token()), OperationStatus::Completed);
+        assert!(cancel_operation("op-cancel-test"));
+        assert_eq!(OperationStatus::of(handle.token()), OperationStatus::Cancelled);
+
+        drop(handle);
+        assert!(!cancel_operation("op-cancel-test"));
+        // 匿名操作不会登记
+        let anonymous = register_operation(None);
+        assert!(!anonymous.token().is_cancelled());
+    }
+}
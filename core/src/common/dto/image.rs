@@ -45,6 +45,28 @@ impl ImageFormat {
     }
 }
 
+/// 图片的颜色模式（从文件头读取）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ColorMode {
+    Grayscale,
+    GrayscaleAlpha,
+    Rgb,
+    Rgba,
+    Indexed,
+    Cmyk,
+}
+
+impl ColorMode {
+    pub fn channels(&self) -> u8 {
+        match self {
+            Self::Grayscale | Self::Indexed => 1,
+            Self::GrayscaleAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba | Self::Cmyk => 4,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageMetadataDTO {
     pub id: ImageId,
@@ -54,6 +76,39 @@ pub struct ImageMetadataDTO {
     pub format: Option<ImageFormat>,
     pub size: Option<u64>,
     pub checksum: Option<String>,
+    // 格式不记录DPI时为None
+    #[serde(default)]
+    pub dpi_x: Option<u32>,
+    #[serde(default)]
+    pub dpi_y: Option<u32>,
+    #[serde(default)]
+    pub color_mode: Option<ColorMode>,
+    #[serde(default)]
+    pub bit_depth: Option<u8>,
+    // 导入时缩小过的图片的原始尺寸
+    #[serde(default)]
+    pub original_width: Option<u32>,
+    #[serde(default)]
+    pub original_height: Option<u32>,
+    #[serde(default)]
+    pub reviewed: bool,
+    #[serde(default)]
+    pub review_note: Option<String>,
+    #[serde(default)]
+    pub locked: bool,
+    // 为消除项目内重名加了后缀时的原文件名
+    #[serde(default)]
+    pub original_name: Option<String>,
+}
+
+/// 图片的查看状态：缩放、平移和最后选中的标记，切换回图片时恢复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageViewState {
+    pub zoom: f64,
+    pub pan_x: f64,
+    pub pan_y: f64,
+    #[serde(default)]
+    pub last_selected_marker: Option<MarkerId>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,9 +118,42 @@ pub enum ImageDataDTO {
     SharedBuffer { format: ImageFormat, buffer_id: u32 },
 }
 
+/// 图片数据的存储方式，调用方据此决定如何获取字节
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ImageSourceKind {
+    FilePath,
+    Binary,
+    SharedBuffer,
+}
+
+impl ImageDataDTO {
+    pub fn source_kind(&self) -> ImageSourceKind {
+        match self {
+            ImageDataDTO::FilePath(_) => ImageSourceKind::FilePath,
+            ImageDataDTO::Binary { .. } => ImageSourceKind::Binary,
+            ImageDataDTO::SharedBuffer { .. } => ImageSourceKind::SharedBuffer,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageDTO {
     pub metadata: ImageMetadataDTO,
     pub data: ImageDataDTO,
     pub marker_ids: Vec<MarkerId>,
-}
\ No newline at end of file
+}
+/// 文件已不存在的图片（项目文件夹被移动后），path为项目中记录的原路径
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingImageDTO {
+    pub image_id: ImageId,
+    pub name: Option<String>,
+    pub path: String,
+}
+
+/// 重新链接图片的结果，checksum_matches为false表示新文件和原文件内容不同
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelinkResultDTO {
+    pub image_id: ImageId,
+    pub path: String,
+    pub checksum_matches: bool,
+}
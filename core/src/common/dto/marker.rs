@@ -6,6 +6,8 @@ use crate::common::{MarkerId, ImageId};
 pub enum MarkerGeometryDTO {
     Point { x: f64, y: f64 },
     Rectangle { x: f64, y: f64, width: f64, height: f64 },
+    /// 顶点为百分比坐标 [x, y]
+    Polygon { points: Vec<(f64, f64)> },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,6 +16,10 @@ pub struct MarkerStyleDTO {
     pub overlay_text: bool,
     #[serde(rename = "horizontal")]
     pub horizontal: bool,
+    #[serde(rename = "sizeHint", default)]
+    pub size_hint: Option<f32>,
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 impl Default for MarkerStyleDTO {
@@ -21,6 +27,8 @@ impl Default for MarkerStyleDTO {
         Self {
             overlay_text: false,
             horizontal: false,
+            size_hint: None,
+            tag: None,
         }
     }
 }
@@ -34,4 +42,199 @@ pub struct MarkerDTO {
     pub style: MarkerStyleDTO,
     #[serde(rename = "imageIndex")]
     pub image_index: u32,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub note: Option<String>,
+}
+/// 像素坐标下的标记几何（以图片左上角为原点）
+/// 多边形在像素坐标下取外接矩形，插件协议保持不变；需要顶点时用 polygon_to_pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PixelGeometry {
+    Point { x: u32, y: u32 },
+    Rectangle { x: u32, y: u32, width: u32, height: u32 },
+}
+
+// 百分比 -> 像素，四舍五入并限制在 [0, size] 内
+fn percent_to_pixel(value: f64, size: u32) -> u32 {
+    (value / 100.0 * size as f64).round().clamp(0.0, size as f64) as u32
+}
+
+fn pixel_to_percent(value: u32, size: u32) -> f64 {
+    if size == 0 {
+        return 0.0;
+    }
+    value as f64 / size as f64 * 100.0
+}
+
+impl MarkerGeometryDTO {
+    /// 转换为像素坐标
+    ///
+    /// 舍入规则：坐标四舍五入到最近的像素并限制在图片范围内；矩形的宽高由
+    /// 四舍五入后的左上角和右下角相减得到，至少为1像素，且不超出图片边界。
+    pub fn to_pixels(&self, image_width: u32, image_height: u32) -> PixelGeometry {
+        match *self {
+            MarkerGeometryDTO::Polygon { ref points } => {
+                let (x, y, width, height) = polygon_bounding_box(points);
+                MarkerGeometryDTO::Rectangle { x, y, width, height }.to_pixels(image_width, image_height)
+            }
+            MarkerGeometryDTO::Point { x, y } => PixelGeometry::Point {
+                x: percent_to_pixel(x, image_width),
+                y: percent_to_pixel(y, image_height),
+            },
+            MarkerGeometryDTO::Rectangle { x, y, width, height } => {
+                let left = percent_to_pixel(x, image_width).min(image_width.saturating_sub(1));
+                let top = percent_to_pixel(y, image_height).min(image_height.saturating_sub(1));
+                let right = percent_to_pixel(x + width, image_width);
+                let bottom = percent_to_pixel(y + height, image_height);
+                PixelGeometry::Rectangle {
+                    x: left,
+                    y: top,
+                    width: right.saturating_sub(left).max(1).min(image_width.saturating_sub(left).max(1)),
+                    height: bottom.saturating_sub(top).max(1).min(image_height.saturating_sub(top).max(1)),
+                }
+            }
+        }
+    }
+
+    /// 从像素坐标转换回百分比坐标（精确换算，不做舍入）
+    pub fn from_pixels(pixels: &PixelGeometry, image_width: u32, image_height: u32) -> Self {
+        match *pixels {
+            PixelGeometry::Point { x, y } => MarkerGeometryDTO::Point {
+                x: pixel_to_percent(x, image_width),
+                y: pixel_to_percent(y, image_height),
+            },
+            PixelGeometry::Rectangle { x, y, width, height } => MarkerGeometryDTO::Rectangle {
+                x: pixel_to_percent(x, image_width),
+                y: pixel_to_percent(y, image_height),
+                width: pixel_to_percent(width, image_width),
+                height: pixel_to_percent(height, image_height),
+            },
+        }
+    }
+}
+
+/// 多边形的外接矩形 (x, y, width, height)
+pub fn polygon_bounding_box(points: &[(f64, f64)]) -> (f64, f64, f64, f64) {
+    if points.is_empty() {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+    let (mut left, mut top, mut right, mut bottom) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for &(x, y) in points {
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x);
+        bottom = bottom.max(y);
+    }
+    (left, top, right - left, bottom - top)
+}
+
+/// 多边形顶点的像素坐标，四舍五入并限制在图片范围内
+pub fn polygon_to_pixels(points: &[(f64, f64)], image_width: u32, image_height: u32) -> Vec<(u32, u32)> {
+    points.iter()
+        .map(|&(x, y)| (percent_to_pixel(x, image_width), percent_to_pixel(y, image_height)))
+        .collect()
+}
+
+impl MarkerDTO {
+    pub fn to_pixels(&self, image_width: u32, image_height: u32) -> PixelGeometry {
+        self.geometry.to_pixels(image_width, image_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 简单的线性同余生成器，保证测试可复现
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next_f64(&mut self) -> f64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    const IMAGE_SIZES: &[(u32, u32)] = &[
+        (1, 1), (2, 3), (7, 13), (100, 100), (799, 1201), (1080, 1920), (1920, 1080), (4096, 2731), (10000, 65),
+    ];
+
+    fn within_one_pixel(original: f64, converted: f64, size: u32) -> bool {
+        ((original - converted) / 100.0 * size as f64).abs() <= 1.0
+    }
+
+    #[test]
+    fn test_point_roundtrip_within_one_pixel() {
+        let mut rng = Lcg(42);
+        for &(w, h) in IMAGE_SIZES {
+            for _ in 0..500 {
+                let geometry = MarkerGeometryDTO::Point { x: rng.next_f64() * 100.0, y: rng.next_f64() * 100.0 };
+                let back = MarkerGeometryDTO::from_pixels(&geometry.to_pixels(w, h), w, h);
+                match (geometry, back) {
+                    (MarkerGeometryDTO::Point { x, y }, MarkerGeometryDTO::Point { x: bx, y: by }) => {
+                        assert!(within_one_pixel(x, bx, w), "x {} -> {} on {}x{}", x, bx, w, h);
+                        assert!(within_one_pixel(y, by, h), "y {} -> {} on {}x{}", y, by, w, h);
+                    }
+                    _ => panic!("geometry type changed"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rectangle_roundtrip_within_one_pixel() {
+        let mut rng = Lcg(7);
+        for &(w, h) in IMAGE_SIZES {
+            for _ in 0..500 {
+                let x = rng.next_f64() * 90.0;
+                let y = rng.next_f64() * 90.0;
+                let geometry = MarkerGeometryDTO::Rectangle {
+                    x,
+                    y,
+                    width: rng.next_f64() * (100.0 - x),
+                    height: rng.next_f64() * (100.0 - y),
+                };
+                let pixels = geometry.to_pixels(w, h);
+                if let PixelGeometry::Rectangle { x: px, y: py, width: pw, height: ph } = pixels {
+                    assert!(pw >= 1 && ph >= 1);
+                    assert!(px + pw <= w && py + ph <= h, "{:?} out of {}x{}", pixels, w, h);
+                }
+                let back = MarkerGeometryDTO::from_pixels(&pixels, w, h);
+                match (geometry.clone(), back) {
+                    (
+                        MarkerGeometryDTO::Rectangle { x, y, width, height },
+                        MarkerGeometryDTO::Rectangle { x: bx, y: by, width: bw, height: bh },
+                    ) => {
+                        assert!(within_one_pixel(x, bx, w));
+                        assert!(within_one_pixel(y, by, h));
+                        assert!(within_one_pixel(width, bw, w), "{:?} -> {:?} on {}x{}", geometry, pixels, w, h);
+                        assert!(within_one_pixel(height, bh, h), "{:?} -> {:?} on {}x{}", geometry, pixels, w, h);
+                    }
+                    _ => panic!("geometry type changed"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rounding_rules() {
+        let geometry = MarkerGeometryDTO::Rectangle { x: 10.25, y: 0.0, width: 0.01, height: 150.0 };
+        assert_eq!(
+            geometry.to_pixels(200, 100),
+            PixelGeometry::Rectangle { x: 21, y: 0, width: 1, height: 100 }
+        );
+        let point = MarkerGeometryDTO::Point { x: -5.0, y: 120.0 };
+        assert_eq!(point.to_pixels(100, 100), PixelGeometry::Point { x: 0, y: 100 });
+        // 多边形退化为外接矩形
+        let polygon = MarkerGeometryDTO::Polygon { points: vec![(10.0, 20.0), (50.0, 10.0), (40.0, 60.0)] };
+        assert_eq!(polygon.to_pixels(100, 100), PixelGeometry::Rectangle { x: 10, y: 10, width: 40, height: 50 });
+    }
+
+    #[test]
+    fn test_polygon_serialization() {
+        let polygon = MarkerGeometryDTO::Polygon { points: vec![(1.0, 2.0), (3.0, 4.0), (5.0, 6.5)] };
+        let json = serde_json::to_value(&polygon).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "polygon", "points": [[1.0, 2.0], [3.0, 4.0], [5.0, 6.5]] }));
+        assert_eq!(serde_json::from_value::<MarkerGeometryDTO>(json).unwrap(), polygon);
+    }
+}
@@ -4,8 +4,8 @@ pub mod project;
 pub mod project_format;
 pub mod opening_project;
 
-pub use image::{ImageDTO, ImageMetadataDTO, ImageFormat, ImageDataDTO};
-pub use marker::{MarkerDTO, MarkerStyleDTO};
+pub use image::{ImageDTO, ImageMetadataDTO, ImageFormat, ImageDataDTO, ImageSourceKind, ColorMode};
+pub use marker::{MarkerDTO, MarkerStyleDTO, MarkerGeometryDTO, PixelGeometry};
 pub use project::ProjectDTO;
 pub use project_format::ProjectFormat;
 pub use opening_project::OpeningProjectDTO;
\ No newline at end of file
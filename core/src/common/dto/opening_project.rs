@@ -16,4 +16,7 @@ pub struct OpeningProjectDTO {
     pub uploaded_images: Vec<String>,
     #[serde(rename = "isComplete")]
     pub is_complete: bool,
+    /// 打开文件时检查出的数据问题（如重复图片、指向不存在标记的缓存）
+    #[serde(default)]
+    pub warnings: Vec<crate::service::project::IntegrityIssue>,
 }
\ No newline at end of file
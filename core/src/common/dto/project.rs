@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::common::{ProjectId, ImageId, Language};
+use crate::common::{ProjectId, ImageId, Language, ThumbnailOrder, ReadingDirection, MarkerTypePreset, ServiceDefaults, ProjectGlossary};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectDTO {
@@ -13,4 +13,26 @@ pub struct ProjectDTO {
     pub source_language: Language,
     #[serde(rename = "targetLanguage", default = "Language::default_target")]
     pub target_language: Language,
+    #[serde(rename = "thumbnailOrder", default)]
+    pub thumbnail_order: ThumbnailOrder,
+    #[serde(rename = "pinnedImages", default)]
+    pub pinned_images: Vec<ImageId>,
+    #[serde(rename = "watchFiles", default = "crate::storage::project::default_watch_files")]
+    pub watch_files: bool,
+    #[serde(rename = "readingDirection", default)]
+    pub reading_direction: ReadingDirection,
+    #[serde(rename = "markerTypes", default)]
+    pub marker_types: Vec<MarkerTypePreset>,
+    #[serde(rename = "defaultServices", default)]
+    pub default_services: ServiceDefaults,
+    #[serde(default)]
+    pub glossary: ProjectGlossary,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(rename = "autoOcrOnCreate", default)]
+    pub auto_ocr_on_create: bool,
+    #[serde(rename = "keepTranslationHistory", default)]
+    pub keep_translation_history: bool,
+    #[serde(rename = "grayscaleReencode", default)]
+    pub grayscale_reencode: bool,
 }
\ No newline at end of file
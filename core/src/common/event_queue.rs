@@ -0,0 +1,283 @@
+// 有界事件队列 - WASM中Rayon线程发出的事件先进入这里，由主Worker轮询取出
+// 超过高水位时向发送方返回WouldBlock；队列满时丢弃最早的非关键事件（普通日志、进度），关键事件不丢弃
+// 单条超过帧大小上限的事件拆成多个续传帧（core:event_chunk），由前端按id拼回
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 续传帧的事件名
+pub const CHUNK_EVENT_NAME: &str = "core:event_chunk";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventQueueConfig {
+    /// 队列最多容纳的帧数
+    pub capacity: usize,
+    /// 达到该帧数后发送方收到WouldBlock
+    pub high_water: usize,
+    /// 单帧最大字节数（序列化后），超过时拆分
+    pub max_frame_bytes: usize,
+}
+
+impl Default for EventQueueConfig {
+    fn default() -> Self {
+        Self { capacity: 2048, high_water: 1536, max_frame_bytes: 256 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventPriority {
+    /// 可以丢弃的事件：非错误级别的日志、进度（后面的进度会覆盖前面的）
+    Droppable,
+    Critical,
+}
+
+/// 区分事件能否丢弃；缩略图就绪等业务事件丢了前端无法恢复，一律视为关键事件
+pub fn event_priority(event_name: &str, event_data: &Value) -> EventPriority {
+    let is_error_log = event_name == "core-log" && event_data["level"] == "Error";
+    if (event_name == "core-log" && !is_error_log) || event_name.contains("progress") {
+        EventPriority::Droppable
+    } else {
+        EventPriority::Critical
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    Queued,
+    /// 已入队，但队列超过高水位，发送方应暂缓
+    WouldBlock,
+    /// 队列已满且该事件可丢弃
+    Dropped,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventFrame {
+    pub event_name: String,
+    pub event_data: Value,
+    priority: EventPriority,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventChannelStats {
+    pub queued: usize,
+    pub capacity: usize,
+    pub high_water: usize,
+    /// 出现过的最大队列长度
+    pub peak_queued: usize,
+    pub sent: u64,
+    pub delivered: u64,
+    pub dropped: u64,
+    pub would_block: u64,
+    /// 被拆分成续传帧的事件数
+    pub chunked: u64,
+    pub largest_event_bytes: usize,
+}
+
+pub struct BoundedEventQueue {
+    config: EventQueueConfig,
+    frames: VecDeque<EventFrame>,
+    next_chunk_id: u64,
+    stats: EventChannelStats,
+}
+
+impl BoundedEventQueue {
+    pub fn new(config: EventQueueConfig) -> Self {
+        Self {
+            config,
+            frames: VecDeque::new(),
+            next_chunk_id: 1,
+            stats: EventChannelStats {
+                capacity: config.capacity,
+                high_water: config.high_water,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn send(&mut self, event_name: String, event_data: Value) -> SendOutcome {
+        self.stats.sent += 1;
+        let priority = event_priority(&event_name, &event_data);
+        let frames = self.frame(event_name, event_data, priority);
+
+        // 先腾出这个事件所有帧的空间；关键事件腾不出来也保留
+        while self.frames.len() + frames.len() > self.config.capacity {
+            if !self.evict_droppable() {
+                if priority == EventPriority::Droppable {
+                    self.stats.dropped += 1;
+                    return SendOutcome::Dropped;
+                }
+                break;
+            }
+        }
+        self.frames.extend(frames);
+
+        self.stats.peak_queued = self.stats.peak_queued.max(self.frames.len());
+        if self.frames.len() >= self.config.high_water {
+            self.stats.would_block += 1;
+            SendOutcome::WouldBlock
+        } else {
+            SendOutcome::Queued
+        }
+    }
+
+    // 丢弃最早的一个可丢弃帧；属于同一事件的续传帧一起丢弃，避免前端收到不完整的事件
+    fn evict_droppable(&mut self) -> bool {
+        let Some(position) = self.frames.iter().position(|f| f.priority == EventPriority::Droppable) else {
+            return false;
+        };
+        let frame = self.frames.remove(position).expect("position in range");
+        if frame.event_name == CHUNK_EVENT_NAME {
+            let id = frame.event_data["id"].clone();
+            self.frames.retain(|f| f.event_name != CHUNK_EVENT_NAME || f.event_data["id"] != id);
+        }
+        self.stats.dropped += 1;
+        true
+    }
+
+    fn frame(&mut self, event_name: String, event_data: Value, priority: EventPriority) -> Vec<EventFrame> {
+        let serialized = serde_json::to_string(&event_data).unwrap_or_default();
+        self.stats.largest_event_bytes = self.stats.largest_event_bytes.max(serialized.len());
+        if serialized.len() <= self.config.max_frame_bytes {
+            return vec![EventFrame { event_name, event_data, priority }];
+        }
+
+        let chunks = split_on_char_boundary(&serialized, self.config.max_frame_bytes.max(4));
+        let id = self.next_chunk_id;
+        self.next_chunk_id += 1;
+        self.stats.chunked += 1;
+        let total = chunks.len();
+        chunks.into_iter().enumerate().map(|(seq, payload)| EventFrame {
+            event_name: CHUNK_EVENT_NAME.to_string(),
+            event_data: serde_json::json!({
+                "id": id,
+                "seq": seq,
+                "total": total,
+                "event_name": event_name,
+                "payload": payload,
+            }),
+            priority,
+        }).collect()
+    }
+
+    /// 取出最多limit帧，消费方处理不过来时每次只取一批
+    pub fn drain(&mut self, limit: usize) -> Vec<EventFrame> {
+        let count = limit.min(self.frames.len());
+        self.stats.delivered += count as u64;
+        self.frames.drain(..count).collect()
+    }
+
+    pub fn is_backpressured(&self) -> bool {
+        self.frames.len() >= self.config.high_water
+    }
+
+    pub fn stats(&self) -> EventChannelStats {
+        EventChannelStats { queued: self.frames.len(), ..self.stats.clone() }
+    }
+}
+
+fn split_on_char_boundary(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = max_bytes.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// 全局事件通道（WASM中Rayon线程 -> 主Worker）
+pub static EVENT_CHANNEL: Lazy<Mutex<BoundedEventQueue>> =
+    Lazy::new(|| Mutex::new(BoundedEventQueue::new(EventQueueConfig::default())));
+
+pub fn event_channel_stats() -> EventChannelStats {
+    EVENT_CHANNEL.lock().map(|queue| queue.stats()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_queue() -> BoundedEventQueue {
+        BoundedEventQueue::new(EventQueueConfig { capacity: 10, high_water: 8, max_frame_bytes: 64 })
+    }
+
+    #[test]
+    fn test_slow_consumer_drops_oldest_droppable_events() {
+        let mut queue = small_queue();
+        queue.send("project_saved".to_string(), serde_json::json!({"id": 1}));
+        let mut outcomes = Vec::new();
+        // 消费方一直没有轮询时的进度事件突发
+        for done in 0..300 {
+            outcomes.push(queue.send("ocr_progress".to_string(), serde_json::json!({"done": done})));
+        }
+        assert!(outcomes.contains(&SendOutcome::WouldBlock));
+        assert!(queue.is_backpressured());
+
+        let stats = queue.stats();
+        assert_eq!(stats.queued, 10);
+        assert_eq!(stats.dropped, 291);
+
+        // 关键事件保留，留下的是最新的进度
+        let frames = queue.drain(usize::MAX);
+        assert_eq!(frames[0].event_name, "project_saved");
+        assert_eq!(frames.last().unwrap().event_data["done"], 299);
+        assert!(!queue.is_backpressured());
+    }
+
+    #[test]
+    fn test_thumbnail_burst_signals_backpressure() {
+        let mut queue = small_queue();
+        let outcomes: Vec<_> = (0..300)
+            .map(|page| queue.send("thumbnail_ready".to_string(), serde_json::json!({"image_id": page})))
+            .collect();
+        assert_eq!(outcomes[0], SendOutcome::Queued);
+        assert!(outcomes[7..].iter().all(|o| *o == SendOutcome::WouldBlock));
+        // 缩略图事件不丢弃，消费方慢慢取完
+        let mut delivered = 0;
+        while !queue.drain(32).is_empty() {
+            delivered += 1;
+        }
+        assert_eq!(delivered, 10);
+        assert_eq!(queue.stats().delivered, 300);
+        assert_eq!(queue.stats().dropped, 0);
+    }
+
+    #[test]
+    fn test_critical_events_never_dropped() {
+        let mut queue = small_queue();
+        for i in 0..20 {
+            assert_ne!(queue.send("marker_updated".to_string(), serde_json::json!({"id": i})), SendOutcome::Dropped);
+        }
+        assert_eq!(queue.send("core-log".to_string(), serde_json::json!({"level": "Info"})), SendOutcome::Dropped);
+        assert_ne!(queue.send("core-log".to_string(), serde_json::json!({"level": "Error"})), SendOutcome::Dropped);
+        // 分批消费
+        assert_eq!(queue.drain(5).len(), 5);
+        assert_eq!(queue.drain(100).len(), 16);
+        assert_eq!(queue.stats().delivered, 21);
+    }
+
+    #[test]
+    fn test_oversized_event_is_chunked() {
+        let mut queue = small_queue();
+        let text = "识别结果".repeat(40);
+        queue.send("ocr_result".to_string(), serde_json::json!({"text": text}));
+        let frames = queue.drain(usize::MAX);
+        assert!(frames.len() > 1);
+        assert!(frames.iter().all(|f| f.event_name == CHUNK_EVENT_NAME));
+
+        let payload: String = frames.iter().map(|f| f.event_data["payload"].as_str().unwrap()).collect();
+        let restored: Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(restored["text"], text);
+        assert_eq!(frames[0].event_data["event_name"], "ocr_result");
+        assert_eq!(frames[0].event_data["total"], frames.len());
+        assert_eq!(queue.stats().chunked, 1);
+    }
+}
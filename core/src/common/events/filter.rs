@@ -0,0 +1,147 @@
+// 前端事件过滤 - 前端按需订阅业务事件，减少高频事件（进度、缓存更新等）的序列化和跨线程传递
+// 只作用于前端发射器（Tauri/WASM），插件中继等其他发射器照常收到全部事件；日志事件不受影响
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// 不受过滤影响、总是发给前端的事件前缀
+/// 错误/通知/崩溃提示丢了用户无法感知，项目生命周期和快捷键事件桌面端宿主也在监听
+const ALWAYS_DELIVERED_PREFIXES: &[&str] = &[
+    "ui:notification",
+    "system:",
+    "project:",
+    "opening_project:",
+    "keymap:changed",
+];
+
+/// 不受过滤影响的关键事件
+pub fn is_always_delivered(event_name: &str) -> bool {
+    ALWAYS_DELIVERED_PREFIXES.iter().any(|prefix| event_name.starts_with(prefix))
+        || event_name.ends_with("error")
+        || event_name.ends_with("failed")
+}
+
+/// 事件名是否匹配模式：精确匹配，或以`*`结尾的前缀匹配（"bunny:*"、"*"）
+pub fn pattern_matches(pattern: &str, event_name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event_name.starts_with(prefix),
+        None => pattern == event_name,
+    }
+}
+
+/// 过滤状态和被拦下的事件计数，供调试窗口显示
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrontendEventFilterStats {
+    /// 当前订阅的模式，None表示全部发送
+    pub patterns: Option<Vec<String>>,
+    pub suppressed_total: u64,
+    pub suppressed_by_event: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct FrontendEventFilter {
+    patterns: Option<Vec<String>>,
+    suppressed_total: u64,
+    suppressed_by_event: BTreeMap<String, u64>,
+}
+
+impl FrontendEventFilter {
+    /// 设置订阅的模式，空列表恢复为全部发送
+    pub fn set_patterns(&mut self, patterns: Vec<String>) {
+        let patterns: Vec<String> = patterns.into_iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+        self.patterns = if patterns.is_empty() { None } else { Some(patterns) };
+    }
+
+    /// 判断事件是否发给前端，不发送时计数
+    pub fn should_deliver(&mut self, event_name: &str) -> bool {
+        let Some(patterns) = &self.patterns else {
+            return true;
+        };
+        if is_always_delivered(event_name) || patterns.iter().any(|pattern| pattern_matches(pattern, event_name)) {
+            return true;
+        }
+        self.suppressed_total += 1;
+        *self.suppressed_by_event.entry(event_name.to_string()).or_default() += 1;
+        false
+    }
+
+    pub fn stats(&self) -> FrontendEventFilterStats {
+        FrontendEventFilterStats {
+            patterns: self.patterns.clone(),
+            suppressed_total: self.suppressed_total,
+            suppressed_by_event: self.suppressed_by_event.clone(),
+        }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.suppressed_total = 0;
+        self.suppressed_by_event.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matches_patterns_and_keeps_critical_events() {
+        let mut filter = FrontendEventFilter::default();
+        assert!(filter.should_deliver("bunny:batch_progress"));
+
+        filter.set_patterns(vec!["bunny:*".to_string(), "image:missing".to_string()]);
+        assert!(filter.should_deliver("bunny:batch_progress"));
+        assert!(filter.should_deliver("image:missing"));
+        assert!(!filter.should_deliver("image:evicted"));
+        assert!(!filter.should_deliver("image:evicted"));
+        assert!(!filter.should_deliver("io:import_progress"));
+        // 关键事件总是发送
+        assert!(filter.should_deliver("ui:notification"));
+        assert!(filter.should_deliver("project:created"));
+        assert!(filter.should_deliver("system:panic"));
+
+        let stats = filter.stats();
+        assert_eq!(stats.suppressed_total, 3);
+        assert_eq!(stats.suppressed_by_event["image:evicted"], 2);
+
+        filter.set_patterns(Vec::new());
+        assert!(filter.should_deliver("image:evicted"));
+        assert_eq!(filter.stats().patterns, None);
+    }
+
+    struct Recorder {
+        frontend: bool,
+        names: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl crate::common::events::EventEmitter for Recorder {
+        fn emit(&self, event: crate::common::events::Event) -> Result<(), String> {
+            self.names.lock().unwrap().push(event.event_name);
+            Ok(())
+        }
+
+        fn is_frontend(&self) -> bool {
+            self.frontend
+        }
+    }
+
+    #[test]
+    fn test_filter_only_applies_to_frontend_emitters() {
+        let system = crate::common::events::EventSystem::new();
+        let frontend = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let relay = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        system.register_emitter("frontend".to_string(), Box::new(Recorder { frontend: true, names: frontend.clone() }));
+        system.register_emitter("relay".to_string(), Box::new(Recorder { frontend: false, names: relay.clone() }));
+
+        system.set_frontend_event_filter(vec!["bunny:*".to_string()]);
+        system.emit_business_event("image:evicted".to_string(), serde_json::json!({})).unwrap();
+        system.emit_business_event("bunny:task_fellback".to_string(), serde_json::json!({})).unwrap();
+
+        assert_eq!(*frontend.lock().unwrap(), vec!["bunny:task_fellback"]);
+        assert_eq!(relay.lock().unwrap().len(), 2);
+        assert_eq!(system.frontend_event_filter_stats().suppressed_total, 1);
+        system.reset_frontend_event_filter_stats();
+        assert_eq!(system.frontend_event_filter_stats().suppressed_total, 0);
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+pub mod filter;
+pub mod schema;
+
 /// 跨平台的时间戳获取函数
 pub fn get_timestamp_millis() -> u64 {
     #[cfg(feature = "wasm")]
@@ -35,16 +38,28 @@ pub struct Event {
 
 pub trait EventEmitter: Send + Sync {
     fn emit(&self, event: Event) -> Result<(), String>;
+
+    /// 下游处理不过来时返回true，发送方可以据此暂缓非必要的事件
+    fn is_backpressured(&self) -> bool {
+        false
+    }
+
+    /// 是否发往前端，前端发射器的业务事件受前端事件过滤影响
+    fn is_frontend(&self) -> bool {
+        false
+    }
 }
 
 pub struct EventSystem {
     emitters: Arc<Mutex<HashMap<String, Box<dyn EventEmitter>>>>,
+    frontend_filter: Mutex<filter::FrontendEventFilter>,
 }
 
 impl EventSystem {
     pub fn new() -> Self {
         Self {
             emitters: Arc::new(Mutex::new(HashMap::new())),
+            frontend_filter: Mutex::new(filter::FrontendEventFilter::default()),
         }
     }
 
@@ -54,8 +69,15 @@ impl EventSystem {
     }
 
     pub fn emit_event(&self, event: Event) -> Result<(), String> {
+        self.emit_to(event, true)
+    }
+
+    fn emit_to(&self, event: Event, include_frontend: bool) -> Result<(), String> {
         let emitters = self.emitters.lock().unwrap();
         for (name, emitter) in emitters.iter() {
+            if !include_frontend && emitter.is_frontend() {
+                continue;
+            }
             if let Err(e) = emitter.emit(event.clone()) {
                 eprintln!("Failed to emit event via {}: {}", name, e);
             }
@@ -63,6 +85,35 @@ impl EventSystem {
         Ok(())
     }
 
+    /// 设置前端订阅的业务事件（精确名称或"bunny:*"这样的前缀），空列表恢复为全部发送
+    /// 错误、通知、项目生命周期等关键事件不受影响
+    pub fn set_frontend_event_filter(&self, patterns: Vec<String>) {
+        self.frontend_filter.lock().unwrap().set_patterns(patterns);
+    }
+
+    pub fn frontend_event_filter_stats(&self) -> filter::FrontendEventFilterStats {
+        self.frontend_filter.lock().unwrap().stats()
+    }
+
+    pub fn reset_frontend_event_filter_stats(&self) {
+        self.frontend_filter.lock().unwrap().reset_stats();
+    }
+
+    /// 发送业务事件，下游有积压时返回WouldBlock（事件已入队）
+    pub fn try_emit_business_event(&self, event_name: String, data: serde_json::Value) -> Result<crate::common::SendOutcome, String> {
+        self.emit_business_event(event_name, data)?;
+        Ok(if self.is_backpressured() {
+            crate::common::SendOutcome::WouldBlock
+        } else {
+            crate::common::SendOutcome::Queued
+        })
+    }
+
+    pub fn is_backpressured(&self) -> bool {
+        let emitters = self.emitters.lock().unwrap();
+        emitters.values().any(|emitter| emitter.is_backpressured())
+    }
+
     pub fn emit_log(&self, level: LogLevel, message: String, data: Option<serde_json::Value>) -> Result<(), String> {
         let event = Event {
             event_type: EventType::Log,
@@ -78,17 +129,31 @@ impl EventSystem {
     }
 
     pub fn emit_business_event(&self, event_name: String, data: serde_json::Value) -> Result<(), String> {
+        // 先过滤，前端没订阅且没有其他发射器时不必构造和序列化事件
+        let include_frontend = self.frontend_filter.lock().unwrap().should_deliver(&event_name);
+        if !include_frontend {
+            let emitters = self.emitters.lock().unwrap();
+            if emitters.values().all(|emitter| emitter.is_frontend()) {
+                return Ok(());
+            }
+        }
+        // 调试构建中检查载荷结构，及时发现仍在发送旧结构的代码
+        #[cfg(debug_assertions)]
+        if let Err(e) = schema::validate_event(&event_name, &data) {
+            crate::common::Logger::warn(&format!("Event schema mismatch: {}", e));
+        }
         let event = Event {
             event_type: EventType::Business,
             event_name,
             data,
             timestamp: get_timestamp_millis(),
         };
-        self.emit_event(event)
+        self.emit_to(event, include_frontend)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 日志级别，按严重程度从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum LogLevel {
     Debug,
     Info,
@@ -110,6 +175,10 @@ impl TauriEventEmitter {
 
 #[cfg(feature = "tauri")]
 impl EventEmitter for TauriEventEmitter {
+    fn is_frontend(&self) -> bool {
+        true
+    }
+
     fn emit(&self, event: Event) -> Result<(), String> {
         use tauri::Emitter;
         
@@ -137,6 +206,14 @@ impl WasmEventEmitter {
 
 #[cfg(feature = "wasm")]
 impl EventEmitter for WasmEventEmitter {
+    fn is_backpressured(&self) -> bool {
+        crate::bindings::wasm::event_channel::WASM_EVENT_CHANNEL.is_backpressured()
+    }
+
+    fn is_frontend(&self) -> bool {
+        true
+    }
+
     fn emit(&self, event: Event) -> Result<(), String> {
         // 在WASM环境中，优先尝试Worker回调，然后回退到window事件
         #[cfg(feature = "wasm")]
@@ -158,11 +235,14 @@ impl EventEmitter for WasmEventEmitter {
                     },
                 };
                 
-                crate::bindings::wasm::event_channel::WASM_EVENT_CHANNEL.send(
+                // 队列满时可丢弃的事件会被丢掉，WouldBlock时事件已入队
+                return match crate::bindings::wasm::event_channel::WASM_EVENT_CHANNEL.send(
                     channel_event_name,
                     channel_event_data
-                );
-                return Ok(());
+                ) {
+                    crate::common::SendOutcome::Dropped => Err("Event channel full, event dropped".to_string()),
+                    _ => Ok(()),
+                };
             }
             
             // 在主Worker线程中，直接调用回调
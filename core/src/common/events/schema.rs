@@ -0,0 +1,631 @@
+// 业务事件的结构化定义 - 所有发往前端/插件的业务事件载荷都从这里的结构体生成
+// 每个载荷带schema_version，前端的TypeScript类型由同一份定义导出，避免两边的字段悄悄对不上
+// 消费方必须容忍未知的额外字段，新版本只增不改
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::common::{ImageId, MarkerId, ProjectId};
+use crate::common::dto::image::MissingImageDTO;
+use crate::common::dto::marker::PixelGeometry;
+use crate::common::{DetectedRegion, Language, OcrRegion, ProjectGlossary, ReadingDirection, TranslationWarning};
+
+/// 当前的事件结构版本，字段有不兼容的变化时递增
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+fn current_schema_version() -> u32 {
+    EVENT_SCHEMA_VERSION
+}
+
+/// 有结构定义的业务事件
+pub trait BusinessEvent: Serialize {
+    const EVENT_NAME: &'static str;
+    /// (字段名, TypeScript类型)，不含schema_version
+    const FIELDS: &'static [(&'static str, &'static str)];
+
+    fn to_payload(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// 通过全局事件系统发送
+    fn emit(&self) -> Result<(), String> {
+        crate::common::EVENT_SYSTEM.emit_business_event(Self::EVENT_NAME.to_string(), self.to_payload())
+    }
+}
+
+struct EventDefinition {
+    event_name: &'static str,
+    type_name: &'static str,
+    fields: &'static [(&'static str, &'static str)],
+}
+
+// 定义事件结构体：生成带schema_version的serde结构体、构造函数和字段的TypeScript类型
+macro_rules! event_schema {
+    ($(
+        $(#[doc = $doc:literal])*
+        $event:literal => $name:ident { $( $field:ident : $ty:ty => $ts:literal ),* $(,)? }
+    )*) => {
+        $(
+            $(#[doc = $doc])*
+            #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+            pub struct $name {
+                #[serde(default = "current_schema_version")]
+                pub schema_version: u32,
+                $( pub $field: $ty, )*
+            }
+
+            impl $name {
+                #[allow(clippy::too_many_arguments, clippy::new_without_default)]
+                pub fn new($( $field: $ty ),*) -> Self {
+                    Self { schema_version: EVENT_SCHEMA_VERSION, $( $field ),* }
+                }
+            }
+
+            impl BusinessEvent for $name {
+                const EVENT_NAME: &'static str = $event;
+                const FIELDS: &'static [(&'static str, &'static str)] = &[ $( (stringify!($field), $ts) ),* ];
+            }
+        )*
+
+        const EVENT_DEFINITIONS: &[EventDefinition] = &[
+            $( EventDefinition { event_name: $event, type_name: stringify!($name), fields: <$name as BusinessEvent>::FIELDS } ),*
+        ];
+    };
+}
+
+const LANGUAGE_TS: &str = "'japanese' | 'english' | 'simplifiedChinese' | 'traditionalChinese'";
+
+event_schema! {
+    /// 请求前端把OCR任务转交给插件，附带裁剪后的图片
+    "bunny:request_plugin_ocr" => RequestPluginOcrEvent {
+        task_id: String => "string",
+        marker_id: MarkerId => "number",
+        cropped_image_data: Vec<u8> => "number[]",
+        image_format: String => "string",
+        service_id: String => "string",
+        source_language: Language => "'japanese' | 'english' | 'simplifiedChinese' | 'traditionalChinese'",
+        pixel_geometry: Option<PixelGeometry> => "{ type: 'point' | 'rectangle'; x: number; y: number; width?: number; height?: number } | null",
+    }
+    /// 请求前端把翻译任务转交给插件
+    /// context为按阅读顺序排在该标记之前的几条原文，供插件参考
+    "bunny:request_plugin_translation" => RequestPluginTranslationEvent {
+        task_id: String => "string",
+        marker_id: MarkerId => "number",
+        image_id: ImageId => "number",
+        service_id: String => "string",
+        text: String => "string",
+        source_language: Language => "'japanese' | 'english' | 'simplifiedChinese' | 'traditionalChinese'",
+        target_language: Language => "'japanese' | 'english' | 'simplifiedChinese' | 'traditionalChinese'",
+        glossary: ProjectGlossary => "{ entries?: { source: string; target: string }[]; forbiddenTerms?: string[] }",
+        context: Vec<String> => "string[]",
+    }
+    "bunny:ocr_completed" => OcrCompletedEvent {
+        task_id: String => "string",
+        marker_id: MarkerId => "number",
+        original_text: String => "string",
+        model: String => "string",
+        regions: Vec<OcrRegion> => "{ text: string; x: number; y: number; w: number; h: number }[]",
+    }
+    "bunny:translation_completed" => TranslationCompletedEvent {
+        task_id: String => "string",
+        marker_id: MarkerId => "number",
+        machine_translation: String => "string",
+        service: String => "string",
+        warnings: Vec<TranslationWarning> => "({ kind: 'missing_term'; source: string; target: string } | { kind: 'forbidden_term'; term: string })[]",
+    }
+    /// 请求前端把检测任务转交给插件，附带整张图片；width/height为0表示尺寸未知
+    "bunny:request_plugin_detection" => RequestPluginDetectionEvent {
+        task_id: String => "string",
+        image_id: ImageId => "number",
+        image_data: Vec<u8> => "number[]",
+        image_format: String => "string",
+        width: u32 => "number",
+        height: u32 => "number",
+        service_id: String => "string",
+    }
+    /// 检测完成，regions为原图像素坐标的候选框，由前端确认后再生成标记
+    "bunny:detection_completed" => DetectionCompletedEvent {
+        task_id: String => "string",
+        image_id: ImageId => "number",
+        model: String => "string",
+        regions: Vec<DetectedRegion> => "{ x: number; y: number; w: number; h: number; score?: number; label?: string }[]",
+    }
+    /// 机器翻译被接受为译文，marker_ids为本次写入的标记（批量接受时为同一图片上的多个）
+    "bunny:machine_translation_accepted" => MachineTranslationAcceptedEvent {
+        image_id: ImageId => "number",
+        marker_ids: Vec<MarkerId> => "number[]",
+    }
+    /// 机器翻译被拒绝，缓存中的机翻已清除
+    "bunny:machine_translation_rejected" => MachineTranslationRejectedEvent {
+        marker_id: MarkerId => "number",
+    }
+    /// 新建了OCR/翻译/检测任务，字段与任务历史中的任务一致
+    "bunny:task_created" => TaskCreatedEvent {
+        task_id: String => "string",
+        project_id: ProjectId => "number",
+        marker_id: MarkerId => "number",
+        image_id: ImageId => "number",
+        task_type: String => "'ocr' | 'translation' | 'detection'",
+        service_id: String => "string",
+        status: String => "'queued' | 'processing' | 'completed' | 'failed'",
+        created_at: u64 => "number",
+        started_at: Option<u64> => "number | null",
+        completed_at: Option<u64> => "number | null",
+        error: Option<String> => "string | null",
+        tried_services: Vec<String> => "string[]",
+        parent_pipeline_id: Option<String> => "string | null",
+    }
+    "bunny:task_failed" => TaskFailedEvent {
+        task_id: String => "string",
+        error: String => "string",
+    }
+    /// 任务失败后改用项目的下一个后备服务重发，task_id为失败的任务，new_task_id为重发的任务
+    "bunny:task_fellback" => TaskFellbackEvent {
+        task_id: String => "string",
+        new_task_id: String => "string",
+        marker_id: MarkerId => "number",
+        from: String => "string",
+        to: String => "string",
+        error: String => "string",
+    }
+    /// 流水线的OCR完成，用识别出的原文发起了翻译任务
+    "bunny:pipeline_advanced" => PipelineAdvancedEvent {
+        pipeline_id: String => "string",
+        marker_id: MarkerId => "number",
+        ocr_task_id: String => "string",
+        translation_task_id: String => "string",
+    }
+    "bunny:pipeline_completed" => PipelineCompletedEvent {
+        pipeline_id: String => "string",
+        marker_id: MarkerId => "number",
+        translation_task_id: String => "string",
+    }
+    /// 流水线失败，stage为失败的阶段
+    "bunny:pipeline_failed" => PipelineFailedEvent {
+        pipeline_id: String => "string",
+        marker_id: MarkerId => "number",
+        stage: String => "'ocr' | 'translation'",
+        error: String => "string",
+    }
+    /// 批量翻译每排队一个任务发送一次，done为已排队数，total为待翻译的标记数
+    "bunny:batch_progress" => BatchProgressEvent {
+        project_id: ProjectId => "number",
+        done: usize => "number",
+        total: usize => "number",
+    }
+    /// 图片上任务的进度，同一图片至多每250ms一次，任务全部结束时再发最后一次；done不含failed
+    "bunny:image_progress" => ImageProgressEvent {
+        image_id: ImageId => "number",
+        done: usize => "number",
+        total: usize => "number",
+        failed: usize => "number",
+    }
+    /// 通知中心消息，level为 info/success/warning/error
+    "ui:notification" => NotificationEvent {
+        id: Option<String> => "string | null",
+        title: Option<String> => "string | null",
+        message: String => "string",
+        level: String => "'info' | 'success' | 'warning' | 'error'",
+        toast: Option<bool> => "boolean | null",
+        sticky: Option<bool> => "boolean | null",
+        auto_close: Option<u64> => "number | null",
+        source: Option<String> => "string | null",
+        actions: Option<Value> => "{ label: string; href?: string }[] | null",
+        extra: Option<Value> => "Record<string, unknown> | null",
+        from: Option<String> => "string | null",
+    }
+    "ui:notification:dismiss" => NotificationDismissEvent {
+        id: String => "string",
+    }
+    "ui:notification:clear" => NotificationClearEvent {}
+    "stats:text_stats_changed" => TextStatsChangedEvent {
+        project_id: ProjectId => "number",
+    }
+    "io:annotated_export_progress" => AnnotatedExportProgressEvent {
+        project_id: ProjectId => "number",
+        current: usize => "number",
+        total: usize => "number",
+        name: String => "string",
+    }
+    /// LabelPlus导入进度，每处理完一张图片发送一次
+    "io:import_progress" => ImportProgressEvent {
+        project_id: ProjectId => "number",
+        done: usize => "number",
+        total: usize => "number",
+    }
+    /// LabelPlus导入结束，unmatched_images为项目中找不到的图片名，locked_images为已锁定而跳过的图片名
+    "io:import_complete" => ImportCompleteEvent {
+        project_id: ProjectId => "number",
+        imported_markers: usize => "number",
+        unmatched_images: Vec<String> => "string[]",
+        locked_images: Vec<String> => "string[]",
+    }
+    /// 项目加载后发现文件已不存在的图片，前端据此提示重新链接
+    "image:missing" => ImageMissingEvent {
+        project_id: ProjectId => "number",
+        images: Vec<MissingImageDTO> => "{ image_id: number; name: string | null; path: string }[]",
+    }
+    /// 图片重新链接到新路径
+    "image:relinked" => ImageRelinkedEvent {
+        image_id: ImageId => "number",
+        path: String => "string",
+        checksum_matches: bool => "boolean",
+    }
+    /// 图片字节已离开core的缓存（容量淘汰、数据替换、图片删除），插件应丢弃自己缓存的该图片数据
+    "image:evicted" => ImageEvictedEvent {
+        image_id: ImageId => "number",
+        reason: String => "string",
+    }
+    /// 图片的校对状态或备注变化
+    "image:review_changed" => ImageReviewChangedEvent {
+        project_id: ProjectId => "number",
+        image_id: ImageId => "number",
+        reviewed: bool => "boolean",
+        review_note: Option<String> => "string | null",
+    }
+    /// 图片锁定或解锁
+    "image:locked_changed" => ImageLockedChangedEvent {
+        project_id: ProjectId => "number",
+        image_id: ImageId => "number",
+        locked: bool => "boolean",
+    }
+    /// 图片改名（手动改名、撤销/重做或为消除重名自动加后缀）
+    "image:renamed" => ImageRenamedEvent {
+        project_id: ProjectId => "number",
+        image_id: ImageId => "number",
+        name: Option<String> => "string | null",
+    }
+    /// 选中图片时发送保存的查看状态，前端据此恢复缩放/平移和选中标记
+    "image:view_restored" => ImageViewRestoredEvent {
+        project_id: ProjectId => "number",
+        image_id: ImageId => "number",
+        zoom: f64 => "number",
+        pan_x: f64 => "number",
+        pan_y: f64 => "number",
+        last_selected_marker: Option<MarkerId> => "number | null",
+    }
+    /// 查找重复图片时每算完一张图片的哈希发送一次
+    "image:duplicate_scan_progress" => DuplicateScanProgressEvent {
+        project_id: ProjectId => "number",
+        done: usize => "number",
+        total: usize => "number",
+    }
+    /// 新项目已创建，source_project_id为复制来源
+    "project:created" => ProjectCreatedEvent {
+        project_id: ProjectId => "number",
+        name: String => "string",
+        source_project_id: Option<ProjectId> => "number | null",
+    }
+    /// 项目的阅读方向变化（包括撤销/重做），只影响派生的标记顺序
+    "project:reading_direction_changed" => ReadingDirectionChangedEvent {
+        project_id: ProjectId => "number",
+        reading_direction: ReadingDirection => "'leftToRight' | 'rightToLeft'",
+    }
+    /// 项目设置变化，setting为设置名（目前有"notes"），value为新值
+    "project:settings_changed" => ProjectSettingsChangedEvent {
+        project_id: ProjectId => "number",
+        setting: String => "string",
+        value: Value => "unknown",
+    }
+    /// 项目中启用或停用了某个OCR/翻译服务
+    "project:services_changed" => ServicesChangedEvent {
+        project_id: ProjectId => "number",
+        service_id: String => "string",
+        enabled: bool => "boolean",
+    }
+    /// 项目术语表被替换，entries和forbidden_terms为替换后的条目数
+    "project:glossary_changed" => GlossaryChangedEvent {
+        project_id: ProjectId => "number",
+        entries: usize => "number",
+        forbidden_terms: usize => "number",
+    }
+    /// 打开项目时每加入一张需要的图片发送一次
+    "opening_project:progress" => OpeningProjectProgressEvent {
+        project_id: ProjectId => "number",
+        uploaded: usize => "number",
+        total: usize => "number",
+        estimated_savings_bytes: u64 => "number",
+    }
+    /// 打开项目被取消，已加入的图片已删除
+    "opening_project:cancelled" => OpeningProjectCancelledEvent {
+        project_id: ProjectId => "number",
+        removed_images: usize => "number",
+    }
+    /// 原生插件的调用超过时限，插件已被停用；operation为超时的调用（event/message）
+    "plugin:timeout" => PluginTimeoutEvent {
+        plugin_id: String => "string",
+        operation: String => "string",
+        timeout_ms: u64 => "number",
+    }
+    /// core发生panic，前端据此提示用户工作已保存在快照中并提供导出
+    "system:panic" => SystemPanicEvent {
+        message: String => "string",
+        location: Option<String> => "string | null",
+    }
+    /// 项目还原到命名快照，undone为true表示撤销了这次还原；前端据此重新加载项目的标记和设置
+    "project:snapshot_restored" => SnapshotRestoredEvent {
+        project_id: ProjectId => "number",
+        snapshot_id: String => "string",
+        name: String => "string",
+        undone: bool => "boolean",
+    }
+    /// Web端页面没有跨源隔离（缺少COOP/COEP响应头），SharedArrayBuffer不可用，degraded为降级的功能
+    "system:cross_origin_isolation_unavailable" => CrossOriginIsolationUnavailableEvent {
+        degraded: Vec<String> => "string[]",
+    }
+}
+
+impl NotificationEvent {
+    /// 核心内部发出的通知，其余字段用前端的默认值
+    pub fn simple(title: &str, message: String, level: &str, source: &str, extra: Option<Value>) -> Self {
+        Self::new(None, Some(title.to_string()), message, level.to_string(), None, None, None, Some(source.to_string()), None, extra, None)
+    }
+}
+
+// 单个备选类型是否匹配；数组、对象和字符串字面量都逐层检查，对象中多出来的字段视为合法
+fn matches_alternative(ts: &str, value: &Value) -> bool {
+    match ts {
+        "null" => value.is_null(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "unknown" => true,
+        _ if ts.starts_with('\'') => value.as_str() == Some(ts.trim_matches('\'')),
+        _ if ts.ends_with("[]") => {
+            let element = ts[..ts.len() - 2].trim();
+            let element = element.strip_prefix('(').and_then(|e| e.strip_suffix(')')).unwrap_or(element);
+            value.as_array().is_some_and(|items| items.iter().all(|item| matches_ts_type(element, item)))
+        }
+        _ if ts.starts_with("Record<string, ") => {
+            let inner = &ts["Record<string, ".len()..ts.len() - 1];
+            value.as_object().is_some_and(|object| object.values().all(|v| matches_ts_type(inner, v)))
+        }
+        _ if ts.starts_with('{') => value.as_object().is_some_and(|object| {
+            object_fields(ts).into_iter().all(|(field, optional, field_ts)| match object.get(field) {
+                None => optional || matches_ts_type(field_ts, &Value::Null),
+                Some(v) => matches_ts_type(field_ts, v),
+            })
+        }),
+        _ => false,
+    }
+}
+
+// 按顶层的分隔符拆分，括号内的不拆
+fn split_top_level(ts: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in ts.char_indices() {
+        match c {
+            '{' | '(' | '<' => depth += 1,
+            '}' | ')' | '>' => depth -= 1,
+            _ if c == separator && depth == 0 => {
+                parts.push(ts[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(ts[start..].trim());
+    parts
+}
+
+// 对象字面量 { a: T; b?: U } 的字段：(字段名, 是否可选, 类型)
+fn object_fields(ts: &str) -> Vec<(&str, bool, &str)> {
+    let body = ts.trim().strip_prefix('{').and_then(|b| b.strip_suffix('}')).unwrap_or(ts);
+    split_top_level(body, ';').into_iter()
+        .filter_map(|field| field.split_once(':'))
+        .map(|(name, field_ts)| {
+            let name = name.trim();
+            match name.strip_suffix('?') {
+                Some(name) => (name, true, field_ts.trim()),
+                None => (name, false, field_ts.trim()),
+            }
+        })
+        .collect()
+}
+
+fn matches_ts_type(ts: &str, value: &Value) -> bool {
+    split_top_level(ts, '|').into_iter().any(|alternative| matches_alternative(alternative, value))
+}
+
+/// 检查事件载荷是否符合结构定义，用于调试构建中发现发送旧结构的代码
+/// 没有结构定义的事件和多出来的字段都视为合法
+pub fn validate_event(event_name: &str, payload: &Value) -> Result<(), String> {
+    let Some(definition) = EVENT_DEFINITIONS.iter().find(|d| d.event_name == event_name) else {
+        return Ok(());
+    };
+    let object = payload.as_object()
+        .ok_or_else(|| format!("{}: payload must be an object", event_name))?;
+    if !object.get("schema_version").is_some_and(Value::is_u64) {
+        return Err(format!("{}: missing schema_version", event_name));
+    }
+    for (field, ts) in definition.fields {
+        let nullable = matches_ts_type(ts, &Value::Null);
+        match object.get(*field) {
+            None if nullable => {}
+            None => return Err(format!("{}: missing field {}", event_name, field)),
+            Some(value) if !matches_ts_type(ts, value) => {
+                return Err(format!("{}: field {} should be {}", event_name, field, ts));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// 生成前端使用的TypeScript类型定义
+pub fn typescript_definitions() -> String {
+    let mut out = String::new();
+    out.push_str("// 由 core/src/common/events/schema.rs 生成，请勿手动修改\n");
+    out.push_str("// 更新：UPDATE_EVENT_TYPES=1 cargo test -p bubblefish-core event_types\n\n");
+    out.push_str(&format!("export const EVENT_SCHEMA_VERSION = {};\n\n", EVENT_SCHEMA_VERSION));
+    out.push_str(&format!("export type EventLanguage = {};\n\n", LANGUAGE_TS));
+    for definition in EVENT_DEFINITIONS {
+        out.push_str(&format!("/** {} */\n", definition.event_name));
+        out.push_str(&format!("export interface {} {{\n", definition.type_name));
+        out.push_str("\tschema_version: number;\n");
+        for (field, ts) in definition.fields {
+            let ts = if *ts == LANGUAGE_TS { "EventLanguage" } else { ts };
+            out.push_str(&format!("\t{}: {};\n", field, ts));
+        }
+        out.push_str("\t// 新版本可能增加字段\n\t[key: string]: unknown;\n}\n\n");
+    }
+    out.push_str("export interface BusinessEventPayloads {\n");
+    for definition in EVENT_DEFINITIONS {
+        out.push_str(&format!("\t'{}': {};\n", definition.event_name, definition.type_name));
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_payloads_validate() {
+        let event = OcrCompletedEvent::new("t1".to_string(), MarkerId(3), "こんにちは".to_string(), "manga-ocr".to_string(), Vec::new());
+        let payload = event.to_payload();
+        assert_eq!(payload["schema_version"], EVENT_SCHEMA_VERSION);
+        assert!(validate_event(OcrCompletedEvent::EVENT_NAME, &payload).is_ok());
+
+        let notification = NotificationEvent::simple("标题", "内容".to_string(), "info", "image", None);
+        assert!(validate_event(NotificationEvent::EVENT_NAME, &notification.to_payload()).is_ok());
+        assert!(validate_event(NotificationClearEvent::EVENT_NAME, &NotificationClearEvent::new().to_payload()).is_ok());
+    }
+
+    fn sample<E: BusinessEvent>(event: E) -> (&'static str, Value) {
+        (E::EVENT_NAME, event.to_payload())
+    }
+
+    // 每个事件一份字段都有值的样例（Option为Some、数组非空），用真实的serde输出检查手写的TypeScript类型
+    fn sample_payloads() -> Vec<(&'static str, Value)> {
+        use crate::common::GlossaryEntry;
+        let text = || "t".to_string();
+        vec![
+            sample(RequestPluginOcrEvent::new(text(), MarkerId(1), vec![1, 2], "png".to_string(), text(), Language::Japanese,
+                Some(PixelGeometry::Rectangle { x: 1, y: 2, width: 3, height: 4 }))),
+            sample(RequestPluginTranslationEvent::new(text(), MarkerId(1), ImageId(2), text(), text(), Language::Japanese, Language::SimplifiedChinese,
+                ProjectGlossary { entries: vec![GlossaryEntry { source: "先輩".to_string(), target: "前辈".to_string() }], forbidden_terms: vec![text()] },
+                vec![text()])),
+            sample(OcrCompletedEvent::new(text(), MarkerId(1), text(), text(),
+                vec![OcrRegion { text: text(), x: 1.0, y: 2.0, w: 3.0, h: 4.0 }])),
+            sample(TranslationCompletedEvent::new(text(), MarkerId(1), text(), text(), vec![
+                TranslationWarning::MissingTerm { source: "先輩".to_string(), target: "前辈".to_string() },
+                TranslationWarning::ForbiddenTerm { term: text() },
+            ])),
+            sample(RequestPluginDetectionEvent::new(text(), ImageId(2), vec![1], "png".to_string(), 10, 20, text())),
+            sample(DetectionCompletedEvent::new(text(), ImageId(2), text(),
+                vec![DetectedRegion { x: 1.0, y: 2.0, w: 3.0, h: 4.0, score: Some(0.9), label: Some("text".to_string()) }])),
+            sample(MachineTranslationAcceptedEvent::new(ImageId(2), vec![MarkerId(1)])),
+            sample(MachineTranslationRejectedEvent::new(MarkerId(1))),
+            sample(TaskCreatedEvent::new(text(), ProjectId(3), MarkerId(1), ImageId(2), "ocr".to_string(), text(), "queued".to_string(),
+                1, Some(2), Some(3), Some(text()), vec![text()], Some(text()))),
+            sample(TaskFailedEvent::new(text(), text())),
+            sample(TaskFellbackEvent::new(text(), text(), MarkerId(1), text(), text(), text())),
+            sample(PipelineAdvancedEvent::new(text(), MarkerId(1), text(), text())),
+            sample(PipelineCompletedEvent::new(text(), MarkerId(1), text())),
+            sample(PipelineFailedEvent::new(text(), MarkerId(1), "ocr".to_string(), text())),
+            sample(BatchProgressEvent::new(ProjectId(3), 1, 2)),
+            sample(ImageProgressEvent::new(ImageId(2), 1, 2, 0)),
+            sample(NotificationEvent::new(Some(text()), Some(text()), text(), "warning".to_string(), Some(true), Some(false), Some(5000),
+                Some(text()), Some(serde_json::json!([{"label": "打开", "href": "https://example.com"}])), Some(serde_json::json!({"a": 1})), Some(text()))),
+            sample(NotificationDismissEvent::new(text())),
+            sample(NotificationClearEvent::new()),
+            sample(TextStatsChangedEvent::new(ProjectId(3))),
+            sample(AnnotatedExportProgressEvent::new(ProjectId(3), 1, 2, text())),
+            sample(ImportProgressEvent::new(ProjectId(3), 1, 2)),
+            sample(ImportCompleteEvent::new(ProjectId(3), 1, vec![text()], vec![text()])),
+            sample(ImageMissingEvent::new(ProjectId(3), vec![MissingImageDTO { image_id: ImageId(2), name: Some(text()), path: text() }])),
+            sample(ImageRelinkedEvent::new(ImageId(2), text(), true)),
+            sample(ImageEvictedEvent::new(ImageId(2), text())),
+            sample(ImageReviewChangedEvent::new(ProjectId(3), ImageId(2), true, Some(text()))),
+            sample(ImageLockedChangedEvent::new(ProjectId(3), ImageId(2), true)),
+            sample(ImageRenamedEvent::new(ProjectId(3), ImageId(2), Some(text()))),
+            sample(ImageViewRestoredEvent::new(ProjectId(3), ImageId(2), 1.5, 10.0, 20.0, Some(MarkerId(1)))),
+            sample(DuplicateScanProgressEvent::new(ProjectId(3), 1, 2)),
+            sample(ProjectCreatedEvent::new(ProjectId(3), text(), Some(ProjectId(4)))),
+            sample(ReadingDirectionChangedEvent::new(ProjectId(3), ReadingDirection::RightToLeft)),
+            sample(ProjectSettingsChangedEvent::new(ProjectId(3), "notes".to_string(), serde_json::json!("n"))),
+            sample(ServicesChangedEvent::new(ProjectId(3), text(), false)),
+            sample(GlossaryChangedEvent::new(ProjectId(3), 2, 1)),
+            sample(OpeningProjectProgressEvent::new(ProjectId(3), 1, 2, 1024)),
+            sample(OpeningProjectCancelledEvent::new(ProjectId(3), 1)),
+            sample(PluginTimeoutEvent::new(text(), "event".to_string(), 500)),
+            sample(SystemPanicEvent::new(text(), Some(text()))),
+            sample(SnapshotRestoredEvent::new(ProjectId(3), text(), text(), false)),
+            sample(CrossOriginIsolationUnavailableEvent::new(vec![text()])),
+        ]
+    }
+
+    #[test]
+    fn test_declared_types_match_serialized_samples() {
+        let samples = sample_payloads();
+        for definition in EVENT_DEFINITIONS {
+            let (_, payload) = samples.iter().find(|(name, _)| *name == definition.event_name)
+                .unwrap_or_else(|| panic!("no sample payload for {}", definition.event_name));
+            let object = payload.as_object().unwrap();
+            // 载荷里的字段都要有声明，声明的类型要接受serde的实际输出
+            for key in object.keys().filter(|key| *key != "schema_version") {
+                assert!(definition.fields.iter().any(|(field, _)| field == key), "{}: field {} not declared", definition.event_name, key);
+            }
+            if let Err(e) = validate_event(definition.event_name, payload) {
+                panic!("{} (payload: {})", e, payload);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_shapes_checked() {
+        let warnings = |warning: Value| serde_json::json!({
+            "schema_version": 1, "task_id": "t", "marker_id": 1, "machine_translation": "m", "service": "s", "warnings": [warning],
+        });
+        let ok = warnings(serde_json::json!({"kind": "forbidden_term", "term": "x"}));
+        assert!(validate_event(TranslationCompletedEvent::EVENT_NAME, &ok).is_ok());
+        // 标签写法不对、缺少变体字段都算不匹配
+        let camel = warnings(serde_json::json!({"kind": "forbiddenTerm", "term": "x"}));
+        assert!(validate_event(TranslationCompletedEvent::EVENT_NAME, &camel).is_err());
+        let missing = warnings(serde_json::json!({"kind": "missing_term", "source": "x"}));
+        assert!(validate_event(TranslationCompletedEvent::EVENT_NAME, &missing).is_err());
+
+        let level = serde_json::json!({"schema_version": 1, "message": "m", "level": "fatal"});
+        assert!(validate_event(NotificationEvent::EVENT_NAME, &level).is_err());
+    }
+
+    #[test]
+    fn test_stale_shapes_rejected_extra_fields_tolerated() {
+        // 旧的手写载荷：没有schema_version
+        let stale = serde_json::json!({"task_id": "t1", "error": "boom"});
+        assert!(validate_event("bunny:task_failed", &stale).is_err());
+
+        let wrong_type = serde_json::json!({"schema_version": 1, "task_id": "t1", "error": 42});
+        assert!(validate_event("bunny:task_failed", &wrong_type).unwrap_err().contains("error"));
+
+        let missing = serde_json::json!({"schema_version": 1, "task_id": "t1"});
+        assert!(validate_event("bunny:task_failed", &missing).is_err());
+
+        // 新版本多出的字段和没有定义的事件都放行
+        let newer = serde_json::json!({"schema_version": 2, "task_id": "t1", "error": "boom", "retryable": true});
+        assert!(validate_event("bunny:task_failed", &newer).is_ok());
+        assert!(validate_event("some:unknown_event", &serde_json::json!(null)).is_ok());
+
+        // 可选字段缺失时按null处理
+        let parsed: NotificationEvent = serde_json::from_value(serde_json::json!({"message": "m", "level": "info"})).unwrap();
+        assert_eq!(parsed.schema_version, EVENT_SCHEMA_VERSION);
+        assert_eq!(parsed.title, None);
+    }
+
+    #[test]
+    fn test_event_types_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../frontend/src/lib/types/events.generated.ts");
+        let generated = typescript_definitions();
+        if std::env::var("UPDATE_EVENT_TYPES").is_ok() {
+            std::fs::write(&path, &generated).unwrap();
+            return;
+        }
+        // 只有完整仓库里才有前端目录
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            assert_eq!(existing, generated, "events.generated.ts is stale, rerun with UPDATE_EVENT_TYPES=1");
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use crate::common::events::{EVENT_SYSTEM, LogLevel};
+use crate::common::events::{EVENT_SYSTEM, LogLevel, get_timestamp_millis};
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::VecDeque;
 use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 // WASM特定的导入
 #[cfg(feature = "wasm")]
@@ -27,49 +31,122 @@ extern "C" {
     fn error(s: &str);
 }
 
+/// 非WASM环境下日志在控制台的输出位置，命令行工具用stdout输出结果时需要改到stderr或关闭
+#[cfg(not(feature = "wasm"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLog {
+    Stdout,
+    Stderr,
+    Off,
+}
+
+#[cfg(not(feature = "wasm"))]
+static CONSOLE_LOG: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(0);
+
+#[cfg(not(feature = "wasm"))]
+pub fn set_console_log(target: ConsoleLog) {
+    let value = match target {
+        ConsoleLog::Stdout => 0,
+        ConsoleLog::Stderr => 1,
+        ConsoleLog::Off => 2,
+    };
+    CONSOLE_LOG.store(value, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// 内存中保留的最近日志，调试窗口打开时可以直接取到之前的日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub timestamp: u64,
+    pub message: String,
+    pub data: Option<Value>,
+    /// 分类日志（如插件审计）的类别，普通日志为None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'static str>,
+}
+
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
+static LOG_BUFFER: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+
+// 低于该级别的日志直接丢弃，默认调试构建记录全部、发布构建只记录错误
+static MIN_LOG_LEVEL: AtomicU8 = AtomicU8::new(if cfg!(debug_assertions) { 0 } else { 3 });
+
+fn level_to_u8(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Debug => 0,
+        LogLevel::Info => 1,
+        LogLevel::Warn => 2,
+        LogLevel::Error => 3,
+    }
+}
+
+/// 运行时修改记录的最低级别（控制台、日志事件和缓冲区都按此过滤）
+pub fn set_log_level(level: LogLevel) {
+    MIN_LOG_LEVEL.store(level_to_u8(level), Ordering::Relaxed);
+}
+
+pub fn get_log_level() -> LogLevel {
+    match MIN_LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Debug,
+        1 => LogLevel::Info,
+        2 => LogLevel::Warn,
+        _ => LogLevel::Error,
+    }
+}
+
+/// 最近的日志，按时间顺序，最多limit条；level_filter为最低级别
+pub fn get_recent_logs(level_filter: Option<LogLevel>, limit: usize) -> Vec<LogRecord> {
+    find_recent_logs(limit, |record| level_filter.is_none_or(|min| record.level >= min))
+}
+
+/// 满足条件的最近日志，按时间顺序，最多limit条
+pub fn find_recent_logs(limit: usize, matches: impl Fn(&LogRecord) -> bool) -> Vec<LogRecord> {
+    let buffer = LOG_BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+    let mut records: Vec<LogRecord> = buffer.iter()
+        .rev()
+        .filter(|record| matches(record))
+        .take(limit)
+        .cloned()
+        .collect();
+    records.reverse();
+    records
+}
+
+fn push_record(record: LogRecord) {
+    // panic hook里也会记录日志，锁被污染时继续使用
+    let mut buffer = LOG_BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+    if buffer.len() >= LOG_BUFFER_CAPACITY {
+        buffer.pop_front();
+    }
+    buffer.push_back(record);
+}
+
 pub struct Logger;
 
 impl Logger {
     pub fn debug(message: &str) {
-        if cfg!(debug_assertions) {
-            // 仅在调试模式下记录调试信息
-            Self::log_with_data(LogLevel::Debug, message, None);
-        }
+        Self::log_with_data(LogLevel::Debug, message, None);
     }
 
     pub fn debug_with_data(message: &str, data: Value) {
-        if cfg!(debug_assertions) {
-            // 仅在调试模式下记录调试信息
-            Self::log_with_data(LogLevel::Debug, message, Some(data));
-        }
+        Self::log_with_data(LogLevel::Debug, message, Some(data));
     }
 
     pub fn info(message: &str) {
-        if cfg!(debug_assertions) {
-            // 在调试模式下记录信息
-            Self::log_with_data(LogLevel::Info, message, None);
-        }
+        Self::log_with_data(LogLevel::Info, message, None);
     }
 
     pub fn info_with_data(message: &str, data: Value) {
-        if cfg!(debug_assertions) {
-            // 在调试模式下记录信息
-            Self::log_with_data(LogLevel::Info, message, Some(data));
-        }
+        Self::log_with_data(LogLevel::Info, message, Some(data));
     }
 
     pub fn warn(message: &str) {
-        if cfg!(debug_assertions) {
-            // 在调试模式下记录警告
-            Self::log_with_data(LogLevel::Warn, message, None);
-        }
+        Self::log_with_data(LogLevel::Warn, message, None);
     }
 
     pub fn warn_with_data(message: &str, data: Value) {
-        if cfg!(debug_assertions) {
-            // 在调试模式下记录警告
-            Self::log_with_data(LogLevel::Warn, message, Some(data));
-        }
+        Self::log_with_data(LogLevel::Warn, message, Some(data));
     }
 
     pub fn error(message: &str) {
@@ -118,7 +195,38 @@ impl Logger {
         Self::log_with_data(LogLevel::Error, message, Some(data));
     }
 
+    /// 带类别的日志，总是写入缓冲区（分类日志由调用方显式打开，不受最低级别限制），
+    /// 控制台和日志事件仍按最低级别过滤
+    pub fn log_with_category(level: LogLevel, category: &'static str, message: &str, data: Value) {
+        push_record(LogRecord {
+            level,
+            timestamp: get_timestamp_millis(),
+            message: message.to_string(),
+            data: Some(data.clone()),
+            category: Some(category),
+        });
+        if level_to_u8(level) >= MIN_LOG_LEVEL.load(Ordering::Relaxed) {
+            Self::output(level, message, Some(data));
+        }
+    }
+
     fn log_with_data(level: LogLevel, message: &str, data: Option<Value>) {
+        if level_to_u8(level) < MIN_LOG_LEVEL.load(Ordering::Relaxed) {
+            return;
+        }
+        push_record(LogRecord {
+            level,
+            timestamp: get_timestamp_millis(),
+            message: message.to_string(),
+            data: data.clone(),
+            category: None,
+        });
+        Self::output(level, message, data);
+    }
+
+    // 输出到控制台并发出日志事件
+    fn output(level: LogLevel, message: &str, data: Option<Value>) {
+
         #[cfg(feature = "wasm")]
         {
             // WASM环境：使用浏览器控制台
@@ -133,12 +241,17 @@ impl Logger {
 
         #[cfg(not(feature = "wasm"))]
         {
-            // 非WASM环境：使用标准输出
-            match level {
-                LogLevel::Debug => println!("[DEBUG] {}", message),
-                LogLevel::Info => println!("[INFO] {}", message),
-                LogLevel::Warn => println!("[WARN] {}", message),
-                LogLevel::Error => eprintln!("[ERROR] {}", message),
+            // 非WASM环境：默认使用标准输出（错误走stderr）
+            let line = match level {
+                LogLevel::Debug => format!("[DEBUG] {}", message),
+                LogLevel::Info => format!("[INFO] {}", message),
+                LogLevel::Warn => format!("[WARN] {}", message),
+                LogLevel::Error => format!("[ERROR] {}", message),
+            };
+            match CONSOLE_LOG.load(std::sync::atomic::Ordering::Relaxed) {
+                0 if !matches!(level, LogLevel::Error) => println!("{}", line),
+                0 | 1 => eprintln!("{}", line),
+                _ => {}
             }
         }
 
@@ -239,4 +352,27 @@ macro_rules! log_error_trace {
     };
 }
 
-pub use {log_debug, log_info, log_warn, log_error, log_error_trace};
\ No newline at end of file
+pub use {log_debug, log_info, log_warn, log_error, log_error_trace};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_logs_keep_order_and_filter_by_level() {
+        let marker = uuid::Uuid::new_v4().to_string();
+        Logger::error_with_data(&format!("{} first", marker), serde_json::json!({"n": 1}));
+        Logger::error(&format!("{} second", marker));
+
+        // 其他测试并行写入日志，只看带标记的记录
+        let ours: Vec<LogRecord> = get_recent_logs(Some(LogLevel::Error), LOG_BUFFER_CAPACITY)
+            .into_iter()
+            .filter(|record| record.message.starts_with(&marker))
+            .collect();
+        assert_eq!(ours.len(), 2);
+        assert!(ours[0].message.ends_with("first"));
+        assert_eq!(ours[0].data, Some(serde_json::json!({"n": 1})));
+        assert!(ours[0].timestamp <= ours[1].timestamp);
+        assert_eq!(get_recent_logs(None, 1).len(), 1);
+    }
+}
\ No newline at end of file
@@ -2,9 +2,17 @@ pub mod error;
 pub mod logger;
 pub mod types;
 pub mod events;
+pub mod event_queue;
 pub mod dto;
+pub mod natural_sort;
+pub mod cancellation;
+pub mod panic;
 
 pub use error::*;
 pub use logger::*;
 pub use types::*;
-pub use events::*;
\ No newline at end of file
+pub use events::*;
+pub use event_queue::{EventChannelStats, SendOutcome};
+pub use natural_sort::{natural_cmp, sort_natural_by_key};
+pub use cancellation::{CancellationToken, OperationStatus, cancel_operation, register_operation};
+pub use panic::{install_panic_hook, report_panic};
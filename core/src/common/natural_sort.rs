@@ -0,0 +1,97 @@
+// Natural sort - 文件名自然排序，数字部分按数值比较（2.png排在10.png之前）
+use std::cmp::Ordering;
+
+// 全角数字也按数值处理
+fn digit_value(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => Some(c as u32 - '0' as u32),
+        '０'..='９' => Some(c as u32 - '０' as u32),
+        _ => None,
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Vec<u32> {
+    let mut digits = Vec::new();
+    while let Some(value) = chars.peek().and_then(|c| digit_value(*c)) {
+        digits.push(value);
+        chars.next();
+    }
+    digits
+}
+
+fn compare_numbers(a: &[u32], b: &[u32]) -> Ordering {
+    let a_significant = &a[a.iter().position(|d| *d != 0).unwrap_or(a.len())..];
+    let b_significant = &b[b.iter().position(|d| *d != 0).unwrap_or(b.len())..];
+    a_significant.len().cmp(&b_significant.len())
+        .then_with(|| a_significant.cmp(b_significant))
+}
+
+/// 自然顺序比较：数字段按数值、其它字符不区分大小写逐字比较
+/// 数值相同但补零不同时（p1 / p001），补零少的在前；完全无法区分时退回原始字符串比较，保证全序
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    let mut padding_order = Ordering::Equal;
+
+    loop {
+        let (ca, cb) = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return padding_order.then_with(|| a.cmp(b)),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) => (ca, cb),
+        };
+
+        if digit_value(ca).is_some() && digit_value(cb).is_some() {
+            let a_num = take_number(&mut a_chars);
+            let b_num = take_number(&mut b_chars);
+            let ordering = compare_numbers(&a_num, &b_num);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            if padding_order == Ordering::Equal {
+                padding_order = a_num.len().cmp(&b_num.len());
+            }
+            continue;
+        }
+
+        let ordering = ca.to_lowercase().cmp(cb.to_lowercase());
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        a_chars.next();
+        b_chars.next();
+    }
+}
+
+/// 按key自然排序（稳定排序）
+pub fn sort_natural_by_key<T, F>(items: &mut [T], key: F)
+where
+    F: Fn(&T) -> &str,
+{
+    items.sort_by(|a, b| natural_cmp(key(a), key(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        sort_natural_by_key(&mut names, |s| s.as_str());
+        names
+    }
+
+    #[test]
+    fn test_numeric_and_padding() {
+        assert_eq!(sorted(&["10.jpg", "2.jpg", "1.jpg"]), vec!["1.jpg", "2.jpg", "10.jpg"]);
+        assert_eq!(sorted(&["p010.jpg", "p9.jpg", "p001.jpg", "p1.jpg"]), vec!["p1.jpg", "p001.jpg", "p9.jpg", "p010.jpg"]);
+    }
+
+    #[test]
+    fn test_mixed_prefixes_and_unicode() {
+        assert_eq!(sorted(&["p002.jpg", "cover.jpg", "P001.jpg", "credits.png"]),
+            vec!["cover.jpg", "credits.png", "P001.jpg", "p002.jpg"]);
+        assert_eq!(sorted(&["第10话.png", "第２话.png", "第1话.png"]), vec!["第1话.png", "第２话.png", "第10话.png"]);
+        assert_eq!(natural_cmp("Ä2", "ä10"), Ordering::Less);
+    }
+}
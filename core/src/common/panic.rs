@@ -0,0 +1,110 @@
+// panic上报 - 记录带堆栈的错误日志，并发送system:panic让前端提示用户恢复
+// WASM的panic hook和桌面端安装的hook共用这里的处理
+use std::cell::Cell;
+use std::panic::PanicHookInfo;
+use crate::common::events::schema::{BusinessEvent, SystemPanicEvent};
+
+thread_local! {
+    // 上报过程中再次panic时不再上报，避免递归
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// panic的消息和位置（file:line:column）
+pub fn panic_details(info: &PanicHookInfo) -> (String, Option<String>) {
+    let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Unknown panic payload".to_string()
+    };
+    let location = info.location()
+        .map(|location| format!("{}:{}:{}", location.file(), location.line(), location.column()));
+    (message, location)
+}
+
+/// 记录panic并通知前端
+pub fn report_panic(info: &PanicHookInfo) {
+    if REPORTING.with(|reporting| reporting.replace(true)) {
+        return;
+    }
+
+    let (message, location) = panic_details(info);
+    let full_message = match &location {
+        Some(location) => format!("PANIC: {} at {}", message, location),
+        None => format!("PANIC: {}", message),
+    };
+
+    // 通过我们的logger系统记录，使用log_error_trace!来包含堆栈信息
+    crate::common::log_error_trace!("Rust panic occurred", serde_json::json!({
+        "panic_message": message,
+        "location": location,
+        "full_message": full_message,
+        "type": "rust_panic"
+    }));
+    let _ = SystemPanicEvent::new(message, location).emit();
+
+    REPORTING.with(|reporting| reporting.set(false));
+}
+
+/// 安装panic hook，保留原有hook的输出（桌面端启动时调用）
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        report_panic(info);
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::common::events::{Event, EventEmitter};
+    use crate::common::EVENT_SYSTEM;
+
+    struct PanicRecorder(Arc<Mutex<Vec<serde_json::Value>>>);
+
+    impl EventEmitter for PanicRecorder {
+        fn emit(&self, event: Event) -> Result<(), String> {
+            if event.event_name == "system:panic" {
+                self.0.lock().unwrap().push(event.data);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_panics_reported_as_system_panic_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        EVENT_SYSTEM.register_emitter("panic_test".to_string(), Box::new(PanicRecorder(events.clone())));
+
+        // hook是全局的，只处理本测试线程里的panic，其余交给原来的hook，结束后恢复
+        const THREAD_NAME: &str = "panic-report-test";
+        let previous = Arc::new(std::panic::take_hook());
+        let fallback = previous.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if std::thread::current().name() == Some(THREAD_NAME) {
+                report_panic(info);
+            } else {
+                fallback(info);
+            }
+        }));
+        let result = std::thread::Builder::new().name(THREAD_NAME.to_string()).spawn(|| {
+            assert!(std::panic::catch_unwind(|| panic!("static message")).is_err());
+            let page = 3;
+            assert!(std::panic::catch_unwind(|| panic!("page {} failed", page)).is_err());
+        }).unwrap().join();
+        drop(std::panic::take_hook());
+        if let Ok(previous) = Arc::try_unwrap(previous) {
+            std::panic::set_hook(previous);
+        }
+        result.unwrap();
+
+        let events = events.lock().unwrap();
+        let messages: Vec<_> = events.iter().filter_map(|data| data["message"].as_str()).collect();
+        assert!(messages.contains(&"static message") && messages.contains(&"page 3 failed"));
+        let location = events.iter().find(|data| data["message"] == "page 3 failed").unwrap()["location"].as_str().unwrap();
+        assert!(location.contains("panic.rs:"));
+    }
+}
@@ -39,6 +39,160 @@ impl fmt::Display for Language {
     }
 }
 
+// 缩略图栏的排序方式（固定的图片始终排在最前）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThumbnailOrder {
+    #[default]
+    Manual,      // 项目中的图片顺序
+    Name,        // 按文件名
+    Completion,  // 按翻译完成度，未完成的在前
+}
+
+// 阅读方向，日文漫画默认从右到左
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadingDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+impl ReadingDirection {
+    pub fn default_for(source_language: Language) -> Self {
+        match source_language {
+            Language::Japanese => ReadingDirection::RightToLeft,
+            _ => ReadingDirection::LeftToRight,
+        }
+    }
+}
+
+// 项目的标记类型（LabelPlus分组）预设
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkerTypePreset {
+    pub name: String,
+    pub overlay_text: bool,
+    pub horizontal: bool,
+    /// 对应的标记标签，旧项目没有此字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+// 项目默认使用的OCR/翻译服务，None表示使用全局设置
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<String>,
+    /// 在本项目中停用的服务ID，新注册的服务默认启用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+    /// 任务失败时依次改用的OCR服务，全部失败才算失败
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ocr_fallbacks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translation_fallbacks: Vec<String>,
+}
+
+impl ServiceDefaults {
+    pub fn is_enabled(&self, service_id: &str) -> bool {
+        !self.disabled.iter().any(|id| id == service_id)
+    }
+
+    /// 启用/停用服务，返回是否有变化
+    pub fn set_enabled(&mut self, service_id: &str, enabled: bool) -> bool {
+        if enabled == self.is_enabled(service_id) {
+            return false;
+        }
+        if enabled {
+            self.disabled.retain(|id| id != service_id);
+        } else {
+            self.disabled.push(service_id.to_string());
+            self.disabled.sort();
+        }
+        true
+    }
+}
+
+// 项目术语表，随翻译请求发给插件；插件校验译文后把问题作为TranslationWarning返回
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectGlossary {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<GlossaryEntry>,
+    /// 译文中不允许出现的词
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_terms: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub source: String,
+    pub target: String,
+}
+
+impl ProjectGlossary {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.forbidden_terms.is_empty()
+    }
+
+    /// 去掉首尾空白和空条目；同一原文出现多次时保留最后一条
+    pub fn normalized(self) -> Self {
+        let mut entries: Vec<GlossaryEntry> = Vec::new();
+        for entry in self.entries {
+            let source = entry.source.trim().to_string();
+            let target = entry.target.trim().to_string();
+            if source.is_empty() || target.is_empty() {
+                continue;
+            }
+            entries.retain(|existing| existing.source != source);
+            entries.push(GlossaryEntry { source, target });
+        }
+        let mut forbidden_terms: Vec<String> = Vec::new();
+        for term in self.forbidden_terms {
+            let term = term.trim().to_string();
+            if !term.is_empty() && !forbidden_terms.contains(&term) {
+                forbidden_terms.push(term);
+            }
+        }
+        Self { entries, forbidden_terms }
+    }
+}
+
+// 译文校验问题，与插件SDK的TranslationWarning保持一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranslationWarning {
+    MissingTerm { source: String, target: String },
+    ForbiddenTerm { term: String },
+}
+
+// OCR识别出的一行文本，坐标为发给插件的裁剪图中的像素，与插件SDK的OCRRegion保持一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrRegion {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+// 检测插件返回的候选区域，坐标为原图像素，与插件SDK的DetectedRegion保持一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedRegion {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
 // Type-safe ID wrappers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -0,0 +1,183 @@
+// 插件服务调用审计 - 第三方插件改坏数据时用来查它调用了什么，默认关闭
+// 记录写入日志缓冲区（类别为plugin_audit），敏感字段在记录前替换掉
+use crate::common::events::get_timestamp_millis;
+use crate::common::logger::{find_recent_logs, Logger};
+use crate::common::LogLevel;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Instant;
+
+pub const AUDIT_LOG_CATEGORY: &str = "plugin_audit";
+
+/// 参数序列化后超过此大小时只记录大小（图片数据等）
+pub const MAX_AUDITED_PARAMS_BYTES: usize = 4096;
+
+const REDACTED: &str = "[redacted]";
+
+// 不区分大小写和下划线，总是视为敏感的字段名
+const SENSITIVE_FIELD_NAMES: [&str; 2] = ["apikey", "password"];
+
+static AUDIT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // 插件配置schema中标记为password的字段 (plugin_id -> keys)
+    static ref SCHEMA_SENSITIVE_FIELDS: RwLock<HashMap<String, Vec<String>>> = RwLock::new(HashMap::new());
+}
+
+/// 一次插件服务调用的审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginAuditEntry {
+    pub plugin_id: String,
+    pub service: String,
+    pub method: String,
+    pub params_bytes: usize,
+    /// 脱敏后的参数，超过MAX_AUDITED_PARAMS_BYTES时为None
+    pub params: Option<Value>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub result_bytes: usize,
+    pub duration_ms: u64,
+    pub timestamp: u64,
+}
+
+pub fn set_audit_enabled(enabled: bool) {
+    AUDIT_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_audit_enabled() -> bool {
+    AUDIT_ENABLED.load(Ordering::Relaxed)
+}
+
+/// 从插件的配置schema中找出password类型的字段，记录参数时一并脱敏
+pub fn register_config_schema(plugin_id: &str, schema: &Value) {
+    let keys: Vec<String> = schema["sections"].as_array().into_iter().flatten()
+        .flat_map(|section| section["fields"].as_array().into_iter().flatten())
+        .filter(|field| field["field_type"] == "password")
+        .filter_map(|field| field["key"].as_str().map(str::to_string))
+        .collect();
+    if let Ok(mut fields) = SCHEMA_SENSITIVE_FIELDS.write() {
+        if keys.is_empty() {
+            fields.remove(plugin_id);
+        } else {
+            fields.insert(plugin_id.to_string(), keys);
+        }
+    }
+}
+
+fn is_sensitive(name: &str, schema_fields: &[String]) -> bool {
+    let normalized: String = name.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect();
+    SENSITIVE_FIELD_NAMES.contains(&normalized.as_str()) || schema_fields.iter().any(|field| field == name)
+}
+
+fn redact_value(value: &Value, schema_fields: &[String]) -> Value {
+    match value {
+        Value::Object(map) => {
+            // 配置服务的 {key, value} 形式：key是敏感字段时隐藏value
+            let keyed_secret = map.get("key").and_then(Value::as_str).is_some_and(|key| is_sensitive(key, schema_fields));
+            Value::Object(map.iter().map(|(name, item)| {
+                let hidden = is_sensitive(name, schema_fields) || (keyed_secret && name == "value");
+                let item = if hidden { Value::String(REDACTED.to_string()) } else { redact_value(item, schema_fields) };
+                (name.clone(), item)
+            }).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| redact_value(item, schema_fields)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// 把参数中的敏感字段替换为占位符
+pub fn redact(plugin_id: &str, params: &Value) -> Value {
+    let fields = SCHEMA_SENSITIVE_FIELDS.read();
+    let schema_fields = fields.as_ref().ok()
+        .and_then(|fields| fields.get(plugin_id))
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    redact_value(params, schema_fields)
+}
+
+/// 执行一次插件服务调用，审计打开时记录调用；关闭时只多一次判断
+pub fn audit_call(
+    plugin_id: &str,
+    service: &str,
+    method: &str,
+    params: &Value,
+    call: impl FnOnce() -> Result<Value, String>,
+) -> Result<Value, String> {
+    if !AUDIT_ENABLED.load(Ordering::Relaxed) {
+        return call();
+    }
+
+    let started = Instant::now();
+    let result = call();
+    let params_bytes = serde_json::to_vec(params).map(|bytes| bytes.len()).unwrap_or(0);
+    let entry = PluginAuditEntry {
+        plugin_id: plugin_id.to_string(),
+        service: service.to_string(),
+        method: method.to_string(),
+        params_bytes,
+        params: (params_bytes <= MAX_AUDITED_PARAMS_BYTES).then(|| redact(plugin_id, params)),
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+        result_bytes: result.as_ref().ok()
+            .and_then(|value| serde_json::to_vec(value).ok())
+            .map(|bytes| bytes.len())
+            .unwrap_or(0),
+        duration_ms: started.elapsed().as_millis() as u64,
+        timestamp: get_timestamp_millis(),
+    };
+    let level = if entry.success { LogLevel::Debug } else { LogLevel::Warn };
+    let message = format!("Plugin {} called {}.{}", plugin_id, service, method);
+    Logger::log_with_category(level, AUDIT_LOG_CATEGORY, &message, serde_json::to_value(&entry).unwrap_or(Value::Null));
+    result
+}
+
+/// 最近的审计记录，按时间顺序；plugin_id为None时返回所有插件的
+pub fn get_audit_log(plugin_id: Option<&str>, limit: usize) -> Vec<PluginAuditEntry> {
+    find_recent_logs(limit, |record| {
+        record.category == Some(AUDIT_LOG_CATEGORY)
+            && plugin_id.is_none_or(|id| record.data.as_ref().is_some_and(|data| data["plugin_id"] == id))
+    })
+    .into_iter()
+    .filter_map(|record| record.data.and_then(|data| serde_json::from_value(data).ok()))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_redacts_secrets_and_filters_by_plugin() {
+        let plugin_id = format!("audit-test-{}", uuid::Uuid::new_v4());
+        register_config_schema(&plugin_id, &json!({
+            "sections": [{ "title": "配置", "fields": [
+                { "key": "token", "label": "Token", "field_type": "password" },
+                { "key": "model", "label": "模型", "field_type": "text" }
+            ]}]
+        }));
+
+        // 关闭时不记录
+        audit_call(&plugin_id, "config", "get", &json!({}), || Ok(json!(null))).unwrap();
+        assert!(get_audit_log(Some(&plugin_id), 10).is_empty());
+
+        set_audit_enabled(true);
+        let params = json!({ "key": "token", "value": "secret", "nested": [{ "API_KEY": "sk-1", "model": "m" }] });
+        audit_call(&plugin_id, "config", "set", &params, || Ok(json!({ "success": true }))).unwrap();
+        let failed = audit_call(&plugin_id, "markers", "delete_marker", &json!({ "marker_id": "1" }), || Err("Marker not found".to_string()));
+        audit_call("other-plugin", "markers", "get_marker", &json!({}), || Ok(json!({}))).unwrap();
+        set_audit_enabled(false);
+        assert!(failed.is_err());
+
+        let log = get_audit_log(Some(&plugin_id), 10);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].params, Some(json!({ "key": "token", "value": REDACTED, "nested": [{ "API_KEY": REDACTED, "model": "m" }] })));
+        assert!(log[0].success && log[0].result_bytes > 0);
+        assert_eq!((log[1].method.as_str(), log[1].error.as_deref()), ("delete_marker", Some("Marker not found")));
+        assert_eq!(get_audit_log(Some(&plugin_id), 1)[0].method, "delete_marker");
+    }
+}
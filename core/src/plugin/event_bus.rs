@@ -165,16 +165,7 @@ impl UnifiedEventBus {
             DomainEvent::ProjectCreated(project_id, name) => {
                 // Note: We need to get the full project data from storage
                 // For now, create a minimal project representation
-                use crate::storage::Project;
-                use crate::common::Language;
-                let project = Project {
-                    id: project_id.clone(),
-                    name: name.clone(),
-                    image_ids: Vec::new(),
-                    file_path: None,
-                    source_language: Language::default_source(),
-                    target_language: Language::default_target(),
-                };
+                let project = crate::storage::Project::new(*project_id, name.clone());
                 Some(CoreEvent::ProjectCreated { 
                     project 
                 })
@@ -5,11 +5,13 @@ use std::sync::Arc;
 pub mod service_registry;
 pub mod event_bus;
 pub mod events;
+pub mod audit;
 
 // Re-export key types
 pub use service_registry::{ServiceRegistry, ServiceInterface, ServiceInfo, MethodInfo};
 pub use event_bus::{UnifiedEventBus, CoreEvent, EventFilter, PluginEventManager};
 pub use events::{PluginEvent, PluginEventType};
+pub use audit::PluginAuditEntry;
 
 /// Initialize the enhanced plugin system
 pub fn init_plugin_system() -> (Arc<ServiceRegistry>, Arc<UnifiedEventBus>) {
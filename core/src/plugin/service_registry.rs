@@ -49,13 +49,15 @@ impl ServiceRegistry {
         self.services.insert(name, service as Arc<dyn ServiceInterface>);
     }
 
-    /// 调用Service方法
-    pub fn call_service(&self, service_name: &str, method: &str, params: Value) -> Result<Value, String> {
-        let service = self.services
-            .get(service_name)
-            .ok_or_else(|| format!("Service '{}' not found", service_name))?;
-        
-        service.call(method, params)
+    /// 调用Service方法，plugin_id为发起调用的插件（用于审计）
+    pub fn call_service(&self, plugin_id: &str, service_name: &str, method: &str, params: Value) -> Result<Value, String> {
+        super::audit::audit_call(plugin_id, service_name, method, &params, || {
+            let service = self.services
+                .get(service_name)
+                .ok_or_else(|| format!("Service '{}' not found", service_name))?;
+
+            service.call(method, params.clone())
+        })
     }
 
     /// 获取Service
@@ -86,7 +88,7 @@ pub mod adapters {
     use super::*;
     use crate::service::{marker, project, image};
     use crate::storage::traits::Storage;
-    use crate::common::events::EVENT_SYSTEM;
+    use crate::common::events::schema::{BusinessEvent, NotificationClearEvent, NotificationDismissEvent, NotificationEvent};
     use uuid::Uuid;
     
     /// Marker Service适配器
@@ -113,13 +115,10 @@ pub mod adapters {
                         crate::common::ProjectId::from(project_id)
                     ).map_err(|e| format!("Failed to get project images: {}", e))?;
                     
-                    let storage = crate::storage::state::APP_STATE.markers
-                        .read()
-                        .map_err(|e| format!("Failed to read markers: {}", e))?;
-                    
                     let mut all_markers = Vec::new();
                     for image_id in image_ids {
-                        let markers = storage.get_by_image(&image_id);
+                        let markers = crate::storage::marker::get_image_markers_storage(image_id)
+                            .map_err(|e| format!("Failed to read markers: {}", e))?;
                         all_markers.extend(markers.iter().map(|m| m.to_dto()));
                     }
                     
@@ -146,24 +145,94 @@ pub mod adapters {
                     let translation = params["translation"]
                         .as_str()
                         .map(String::from);
+                    // 可选的越界处理方式，默认拒绝越界坐标
+                    let mode = if params["clamp"].as_bool().unwrap_or(false) {
+                        crate::storage::marker::CoordinateMode::Clamp
+                    } else {
+                        crate::storage::marker::CoordinateMode::Reject
+                    };
                     
-                    let marker_id = self.service.add_point_marker(image_id, x, y, translation);
+                    // 锁定的图片不能新建标记，错误为ImageLockedError的JSON
+                    crate::service::image::ensure_image_unlocked(crate::common::ImageId::from(image_id))
+                        .map_err(|e| e.to_json_string())?;
+                    let marker_id = self.service
+                        .add_point_marker_to_image(crate::common::ImageId::from(image_id), x, y, translation, mode)
+                        .map_err(|e| e.to_string())?;
                     Ok(serde_json::json!({
                         "id": marker_id,
-                        "success": marker_id.is_some()
+                        "success": true
                     }))
                 }
                 "update_marker" => {
                     let marker_id = params["marker_id"]
                         .as_u64()
                         .ok_or("marker_id required")? as u32;
-                    let translation = params["translation"]
-                        .as_str()
-                        .ok_or("translation required")?;
-                    
-                    let success = self.service.update_marker_translation(marker_id, translation.to_string());
+                    // SDK把要修改的字段放在data里，旧的调用方直接放在顶层
+                    let data = params.get("data").filter(|d| d.is_object()).unwrap_or(&params);
+                    let translation = data["translation"].as_str();
+                    let has_style = data.get("size_hint").is_some() || data.get("tag").is_some();
+                    if translation.is_none() && !has_style {
+                        return Err("translation, size_hint or tag required".to_string());
+                    }
+                    crate::service::image::ensure_marker_unlocked(crate::common::MarkerId::from(marker_id))
+                        .map_err(|e| e.to_json_string())?;
+
+                    let mut success = true;
+                    if let Some(translation) = translation {
+                        // 插件写入的译文在历史中记为机器翻译
+                        success &= self.service.update_marker_translation_from(
+                            crate::common::MarkerId::from(marker_id),
+                            translation.to_string(),
+                            crate::storage::TranslationOrigin::Machine,
+                        );
+                    }
+                    let id = crate::common::MarkerId::from(marker_id);
+                    if let Some(size_hint) = data.get("size_hint") {
+                        // null清除字号
+                        let size_hint = size_hint.as_f64().map(|v| v as f32);
+                        success &= self.service.update_marker_size_hint(id, size_hint).map_err(|e| e.to_string())?;
+                    }
+                    if let Some(tag) = data.get("tag") {
+                        let tag = tag.as_str().map(String::from);
+                        success &= self.service.update_marker_tag(id, tag).map_err(|e| e.to_string())?;
+                    }
                     Ok(serde_json::json!({"success": success}))
                 }
+                "get_translation_history" => {
+                    let marker_id = params["marker_id"]
+                        .as_u64()
+                        .or_else(|| params["marker_id"].as_str().and_then(|v| v.parse().ok()))
+                        .ok_or("marker_id required")? as u32;
+                    let history = crate::storage::translation_history::get_translation_history_storage(
+                        crate::common::MarkerId::from(marker_id)
+                    ).map_err(|e| e.to_string())?;
+                    Ok(serde_json::to_value(history).unwrap_or(serde_json::json!([])))
+                }
+                "move_marker_to_image" => {
+                    // SDK传字符串ID，其他调用方可能传数字
+                    let id = |key: &str| params[key].as_u64()
+                        .or_else(|| params[key].as_str().and_then(|v| v.parse().ok()))
+                        .map(|v| v as u32)
+                        .ok_or(format!("{} required", key));
+                    let marker_id = id("marker_id")?;
+                    let target_image_id = id("target_image_id")?;
+                    let geometry: crate::common::dto::marker::MarkerGeometryDTO = serde_json::from_value(params["geometry"].clone())
+                        .map_err(|e| format!("Invalid geometry: {}", e))?;
+                    let position = params["position"].as_u64().map(|v| v as u32);
+                    crate::service::image::ensure_marker_unlocked(crate::common::MarkerId::from(marker_id))
+                        .and_then(|_| crate::service::image::ensure_image_unlocked(crate::common::ImageId::from(target_image_id)))
+                        .map_err(|e| e.to_json_string())?;
+                    
+                    let moved = self.service
+                        .move_marker_to_image(
+                            crate::common::MarkerId::from(marker_id),
+                            crate::common::ImageId::from(target_image_id),
+                            crate::storage::marker::MarkerGeometry::from_dto(geometry),
+                            position,
+                        )
+                        .map_err(|e| e.to_string())?;
+                    Ok(serde_json::json!({"success": moved}))
+                }
                 "delete_marker" => {
                     let _marker_id = params["marker_id"]
                         .as_u64()
@@ -237,6 +306,50 @@ pub mod adapters {
                     ],
                     returns: "object".to_string(),
                 },
+                MethodInfo {
+                    name: "get_translation_history".to_string(),
+                    description: "Get the recent translations of a marker, oldest first".to_string(),
+                    params: vec![
+                        ParamInfo {
+                            name: "marker_id".to_string(),
+                            param_type: "string".to_string(),
+                            required: true,
+                            description: "Marker ID".to_string(),
+                        }
+                    ],
+                    returns: "TranslationHistoryEntry[]".to_string(),
+                },
+                MethodInfo {
+                    name: "move_marker_to_image".to_string(),
+                    description: "Move a marker to another image of the same project".to_string(),
+                    params: vec![
+                        ParamInfo {
+                            name: "marker_id".to_string(),
+                            param_type: "string".to_string(),
+                            required: true,
+                            description: "Marker ID".to_string(),
+                        },
+                        ParamInfo {
+                            name: "target_image_id".to_string(),
+                            param_type: "string".to_string(),
+                            required: true,
+                            description: "Target image ID".to_string(),
+                        },
+                        ParamInfo {
+                            name: "geometry".to_string(),
+                            param_type: "object".to_string(),
+                            required: true,
+                            description: "Geometry on the target image (percent)".to_string(),
+                        },
+                        ParamInfo {
+                            name: "position".to_string(),
+                            param_type: "number".to_string(),
+                            required: false,
+                            description: "Index on the target image, appended when omitted".to_string(),
+                        }
+                    ],
+                    returns: "object".to_string(),
+                },
                 MethodInfo {
                     name: "delete_marker".to_string(),
                     description: "Delete a marker".to_string(),
@@ -419,6 +532,15 @@ pub mod adapters {
                     let image_dto = self.service.get_image(image_id);
                     Ok(serde_json::to_value(image_dto).unwrap_or(serde_json::json!(null)))
                 }
+                "get_image_source_kind" => {
+                    let image_id = params["image_id"]
+                        .as_u64()
+                        .ok_or("image_id required")? as u32;
+                    
+                    let kind = self.service.get_image_source_kind(image_id)
+                        .ok_or_else(|| format!("Image {} not found", image_id))?;
+                    Ok(serde_json::to_value(kind).unwrap_or(serde_json::json!(null)))
+                }
                 "get_image_data" => {
                     let image_id = params["image_id"]
                         .as_u64()
@@ -432,6 +554,16 @@ pub mod adapters {
                     if let Some(image) = storage.get(&crate::common::ImageId::from(image_id)) {
                         // Return based on the image data type
                         match &image.data {
+                            // inline_data=true时通过字节缓存直接返回文件内容，插件不必自己读盘
+                            crate::storage::image_data::ImageData::FilePath(_) if params["inline_data"].as_bool() == Some(true) => {
+                                let data = crate::service::image::byte_cache::read_image_bytes(image)
+                                    .map_err(|e| format!("Failed to read image file: {}", e))?;
+                                Ok(serde_json::json!({
+                                    "type": "Binary",
+                                    "data": data.as_slice(),
+                                    "format": image.data.get_format().map(|f| f.extension()).unwrap_or("png")
+                                }))
+                            }
                             crate::storage::image_data::ImageData::FilePath(path) => {
                                 Ok(serde_json::json!({
                                     "type": "FilePath",
@@ -493,6 +625,19 @@ pub mod adapters {
                     ],
                     returns: "Image".to_string(),
                 },
+                MethodInfo {
+                    name: "get_image_source_kind".to_string(),
+                    description: "Get how the image bytes are stored (FilePath, Binary or SharedBuffer)".to_string(),
+                    params: vec![
+                        ParamInfo {
+                            name: "image_id".to_string(),
+                            param_type: "string".to_string(),
+                            required: true,
+                            description: "Image ID".to_string(),
+                        }
+                    ],
+                    returns: "ImageSourceKind".to_string(),
+                },
                 MethodInfo {
                     name: "get_image_data".to_string(),
                     description: "Get image data (file path or binary)".to_string(),
@@ -547,6 +692,21 @@ pub mod adapters {
 
                     Ok(serde_json::json!({"success": true}))
                 }
+                "register_detection_service" => {
+                    let plugin_id = params["plugin_id"]
+                        .as_str()
+                        .ok_or("plugin_id required")?;
+                    let service_info = serde_json::from_value::<crate::service::bunny::DetectionServiceInfo>(
+                        params["service_info"].clone()
+                    ).map_err(|e| format!("Invalid service info: {}", e))?;
+
+                    crate::service::bunny::BUNNY_SERVICE_REGISTRY
+                        .write()
+                        .map_err(|e| format!("Failed to acquire write lock: {}", e))?
+                        .register_detection_service(plugin_id.to_string(), service_info)?;
+
+                    Ok(serde_json::json!({"success": true}))
+                }
                 "unregister_service" => {
                     let service_id = params["service_id"]
                         .as_str()
@@ -585,6 +745,13 @@ pub mod adapters {
                         .get_translation_services();
                     Ok(serde_json::to_value(services).unwrap_or(serde_json::json!([])))
                 }
+                "get_detection_services" => {
+                    let services = crate::service::bunny::BUNNY_SERVICE_REGISTRY
+                        .read()
+                        .map_err(|e| format!("Failed to acquire read lock: {}", e))?
+                        .get_detection_services();
+                    Ok(serde_json::to_value(services).unwrap_or(serde_json::json!([])))
+                }
                 "get_plugin_for_service" => {
                     let service_id = params["service_id"]
                         .as_str()
@@ -641,6 +808,25 @@ pub mod adapters {
                     ],
                     returns: "object".to_string(),
                 },
+                MethodInfo {
+                    name: "register_detection_service".to_string(),
+                    description: "Register a bubble/text detection service from a plugin".to_string(),
+                    params: vec![
+                        ParamInfo {
+                            name: "plugin_id".to_string(),
+                            param_type: "string".to_string(),
+                            required: true,
+                            description: "Plugin ID".to_string(),
+                        },
+                        ParamInfo {
+                            name: "service_info".to_string(),
+                            param_type: "object".to_string(),
+                            required: true,
+                            description: "Detection service information".to_string(),
+                        }
+                    ],
+                    returns: "object".to_string(),
+                },
                 MethodInfo {
                     name: "unregister_service".to_string(),
                     description: "Unregister a bunny service".to_string(),
@@ -679,6 +865,12 @@ pub mod adapters {
                     params: vec![],
                     returns: "TranslationServiceInfo[]".to_string(),
                 },
+                MethodInfo {
+                    name: "get_detection_services".to_string(),
+                    description: "Get all registered detection services".to_string(),
+                    params: vec![],
+                    returns: "DetectionServiceInfo[]".to_string(),
+                },
                 MethodInfo {
                     name: "get_plugin_for_service".to_string(),
                     description: "Get the plugin ID that provides a service".to_string(),
@@ -758,22 +950,22 @@ pub mod adapters {
                 _ => "info".to_string(),
             };
 
-            let event_payload = serde_json::json!({
-                "id": id,
-                "title": payload.title,
-                "message": payload.message,
-                "level": normalized_level,
-                "toast": payload.toast.unwrap_or(true),
-                "sticky": payload.sticky.unwrap_or(false),
-                "auto_close": payload.auto_close,
-                "source": payload.source,
-                "actions": payload.actions,
-                "extra": payload.extra,
-                "from": "plugin"
-            });
-
-            EVENT_SYSTEM
-                .emit_business_event("ui:notification".to_string(), event_payload)
+            let actions = payload.actions
+                .map(|actions| serde_json::to_value(actions).unwrap_or(Value::Null));
+            NotificationEvent::new(
+                Some(id.clone()),
+                payload.title,
+                payload.message,
+                normalized_level,
+                Some(payload.toast.unwrap_or(true)),
+                Some(payload.sticky.unwrap_or(false)),
+                payload.auto_close,
+                payload.source,
+                actions,
+                payload.extra,
+                Some("plugin".to_string()),
+            )
+                .emit()
                 .map_err(|e| format!("Failed to emit notification event: {}", e))?;
 
             Ok(serde_json::json!({ "id": id }))
@@ -789,8 +981,8 @@ pub mod adapters {
                     self.emit_notification(payload)
                 }
                 "clear" => {
-                    EVENT_SYSTEM
-                        .emit_business_event("ui:notification:clear".to_string(), serde_json::json!({}))
+                    NotificationClearEvent::new()
+                        .emit()
                         .map_err(|e| format!("Failed to emit clear notification event: {}", e))?;
                     Ok(serde_json::json!({ "success": true }))
                 }
@@ -800,11 +992,8 @@ pub mod adapters {
                         .and_then(|value| value.as_str())
                         .ok_or_else(|| "Notification id is required".to_string())?;
 
-                    EVENT_SYSTEM
-                        .emit_business_event(
-                            "ui:notification:dismiss".to_string(),
-                            serde_json::json!({ "id": id })
-                        )
+                    NotificationDismissEvent::new(id.to_string())
+                        .emit()
                         .map_err(|e| format!("Failed to emit dismiss notification event: {}", e))?;
                     Ok(serde_json::json!({ "success": true }))
                 }
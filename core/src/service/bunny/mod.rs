@@ -4,18 +4,87 @@
 mod types;
 mod task_manager;
 
-pub use types::{OCRServiceInfo, TranslationServiceInfo, BUNNY_SERVICE_REGISTRY};
-pub use task_manager::{BunnyTask, TaskManager, TaskStatus, TaskType};
+pub use types::{DetectionServiceInfo, OCRServiceInfo, TranslationServiceInfo, BUNNY_SERVICE_REGISTRY};
+pub use task_manager::{
+    BunnyPipeline, BunnyTask, PipelineStage, TaskManager, TaskStatus, TaskType, TaskResultReceiver, TaskHistoryEntry, ServiceTaskMetrics,
+    ImageTaskProgress, IMAGE_PROGRESS_THROTTLE_MS, TASK_HISTORY_CAPACITY,
+};
 
-use crate::common::{MarkerId, ImageId, ProjectId, EVENT_SYSTEM};
+use crate::common::{CancellationToken, DetectedRegion, MarkerId, ImageId, OcrRegion, ProjectId, TranslationWarning};
+use crate::common::events::schema::{
+    BatchProgressEvent, BusinessEvent, DetectionCompletedEvent, MachineTranslationAcceptedEvent, MachineTranslationRejectedEvent, OcrCompletedEvent,
+    PipelineAdvancedEvent, PipelineCompletedEvent, PipelineFailedEvent, RequestPluginDetectionEvent, RequestPluginOcrEvent, RequestPluginTranslationEvent, TaskFailedEvent, TaskFellbackEvent,
+    TranslationCompletedEvent,
+};
 use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
 
 lazy_static! {
     pub static ref TASK_MANAGER: TaskManager = TaskManager::new();
 }
 
+/// 同步翻译的默认超时时间
+pub const SYNC_TRANSLATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 插件随机翻附带的metadata序列化后的最大字节数，超过时丢弃
+pub const MAX_TRANSLATION_METADATA_BYTES: usize = 2048;
+
 pub struct BunnyService;
 
+/// 批量接受机器翻译的结果，edited为机翻之后译文又被修改过而跳过的标记，locked为锁定的标记
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AcceptMachineTranslationReport {
+    pub accepted: Vec<MarkerId>,
+    pub edited: Vec<MarkerId>,
+    pub locked: Vec<MarkerId>,
+}
+
+/// 机器翻译结果，warnings为插件校验时发现的术语问题，metadata为插件附带的模型、提示词等信息
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationResult {
+    pub marker_id: MarkerId,
+    pub text: String,
+    pub service: Option<String>,
+    pub warnings: Vec<TranslationWarning>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// 只保留不超过MAX_TRANSLATION_METADATA_BYTES的JSON对象，其他情况当作插件没有提供
+/// 元数据只用于追查，不合格时丢弃而不是让整次翻译失败
+pub fn accept_translation_metadata(metadata: Option<serde_json::Value>) -> Option<serde_json::Value> {
+    let metadata = metadata.filter(|value| value.as_object().is_some_and(|object| !object.is_empty()))?;
+    let size = serde_json::to_vec(&metadata).map(|bytes| bytes.len()).unwrap_or(usize::MAX);
+    (size <= MAX_TRANSLATION_METADATA_BYTES).then_some(metadata)
+}
+
+/// 批量OCR中单个标记的结果，task_id和error二选一
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrBatchItem {
+    pub marker_id: MarkerId,
+    pub task_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 批量流水线中单个标记的结果，pipeline_id和error二选一
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineBatchItem {
+    pub marker_id: MarkerId,
+    pub pipeline_id: Option<String>,
+    pub error: Option<String>,
+}
+
+// 优先使用元数据中的尺寸，缺失时从图片数据中解析
+fn image_pixel_size(image_id: ImageId, image_data: &[u8]) -> Option<(u32, u32)> {
+    let image = crate::storage::image::get_image_storage(image_id).ok().flatten()?;
+    if let (Some(width), Some(height)) = (image.metadata.width, image.metadata.height) {
+        return Some((width, height));
+    }
+    let format = image.data.get_format()?;
+    crate::storage::dimension_extractor::extract_dimensions_from_bytes(image_data, &format)
+}
+
 impl BunnyService {
     pub fn new() -> Self {
         Self
@@ -28,6 +97,14 @@ impl BunnyService {
             .map_err(|e| format!("Failed to get project: {:?}", e))?
             .ok_or("Project not found")?;
 
+        let (cropped_image_data, pixel_geometry) = self.ocr_payload(marker_id, image_id)?;
+        let task_id = TASK_MANAGER.create_task(project_id, marker_id, image_id, TaskType::OCR, service_id.clone())?;
+        self.dispatch_ocr(&task_id, marker_id, service_id, project.source_language, cropped_image_data, pixel_geometry)?;
+        Ok(task_id)
+    }
+
+    // 按标记的几何裁剪图片，并算出标记在原图中的像素坐标
+    fn ocr_payload(&self, marker_id: MarkerId, image_id: ImageId) -> Result<(Vec<u8>, Option<crate::common::dto::marker::PixelGeometry>), String> {
         // Get marker info for geometry
         let marker = crate::storage::marker::get_marker_storage(marker_id)
             .map_err(|e| format!("Failed to get marker: {:?}", e))?
@@ -38,37 +115,242 @@ impl BunnyService {
         let full_image_data = image_service.get_image_binary_data(image_id.into())?;
 
         // Crop the image based on marker geometry
-        let (x, y, width, height) = match marker.geometry {
-            crate::storage::marker::MarkerGeometry::Point { x, y } => {
-                (x, y, None, None)
-            },
-            crate::storage::marker::MarkerGeometry::Rectangle { x, y, width, height } => {
-                (x, y, Some(width), Some(height))
+        let cropped_image_data = image_service.crop_image_region(&full_image_data, &marker.geometry)?;
+
+        // 由core计算像素坐标，插件无需知道图片尺寸
+        let pixel_geometry = image_pixel_size(image_id, &full_image_data)
+            .map(|(image_width, image_height)| marker.geometry.to_pixels(image_width, image_height));
+
+        Ok((cropped_image_data, pixel_geometry))
+    }
+
+    /// 新画的标记自动OCR：项目开启了auto_ocr_on_create且默认OCR服务已设置并启用时才发起
+    /// 返回任务ID；未触发或失败时返回None，不影响标记的创建
+    pub fn auto_ocr_for_new_marker(&self, marker_id: MarkerId, image_id: ImageId) -> Option<String> {
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id).ok().flatten()?;
+        let project = crate::storage::project::get_project_storage(project_id).ok().flatten()?;
+        if !project.auto_ocr_on_create {
+            return None;
+        }
+        let service_id = project.default_services.ocr.clone()
+            .filter(|id| project.default_services.is_enabled(id))?;
+        match self.request_ocr(marker_id, image_id, project_id, service_id) {
+            Ok(task_id) => Some(task_id),
+            Err(e) => {
+                crate::common::Logger::warn(&format!("Auto OCR for marker {} failed: {}", marker_id.0, e));
+                None
             }
-        };
+        }
+    }
 
-        let cropped_image_data = image_service.crop_image_region(&full_image_data, x, y, width, height)?;
+    /// 批量请求OCR：每张图片只读取一次，裁剪在线程池中并行完成，再按输入顺序创建任务
+    /// 单个标记失败不影响其他标记
+    pub fn request_ocr_batch(&self, marker_ids: Vec<MarkerId>, project_id: ProjectId, service_id: String) -> Result<Vec<OcrBatchItem>, String> {
+        let results = self.dispatch_ocr_batch(&marker_ids, project_id, service_id, None)?;
+        Ok(marker_ids.into_iter().zip(results)
+            .map(|(marker_id, result)| match result {
+                Ok(task_id) => OcrBatchItem { marker_id, task_id: Some(task_id), error: None },
+                Err(e) => OcrBatchItem { marker_id, task_id: None, error: Some(e) },
+            })
+            .collect())
+    }
 
-        // Create task
-        let task_id = TASK_MANAGER.create_task(marker_id, image_id, TaskType::OCR, service_id.clone())?;
+    /// 为标记创建OCR→翻译流水线并先发出OCR任务，OCR完成后自动用原文发起翻译，返回流水线ID
+    pub fn request_pipeline(&self, marker_id: MarkerId, image_id: ImageId, project_id: ProjectId, ocr_service: String, translation_service: String) -> Result<String, String> {
+        let project = crate::storage::project::get_project_storage(project_id)
+            .map_err(|e| format!("Failed to get project: {:?}", e))?
+            .ok_or("Project not found")?;
 
+        let (cropped_image_data, pixel_geometry) = self.ocr_payload(marker_id, image_id)?;
+        let (pipeline_id, task_id) = TASK_MANAGER.create_pipeline(project_id, marker_id, image_id, ocr_service.clone(), translation_service)?;
+        self.dispatch_ocr(&task_id, marker_id, ocr_service, project.source_language, cropped_image_data, pixel_geometry)?;
+        Ok(pipeline_id)
+    }
+
+    /// 为图片上的所有标记按序号创建流水线，裁剪方式同request_ocr_batch；单个标记失败不影响其他标记
+    pub fn request_pipeline_batch(&self, image_id: ImageId, ocr_service: String, translation_service: String) -> Result<Vec<PipelineBatchItem>, String> {
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("Image not in any project")?;
+        let mut markers = crate::storage::marker::get_image_markers_storage(image_id)
+            .map_err(|e| format!("Failed to get markers: {:?}", e))?;
+        markers.sort_by_key(|marker| marker.image_index);
+        let marker_ids: Vec<MarkerId> = markers.into_iter().map(|marker| marker.id).collect();
+
+        let results = self.dispatch_ocr_batch(&marker_ids, project_id, ocr_service, Some(&translation_service))?;
+        Ok(marker_ids.into_iter().zip(results)
+            .map(|(marker_id, result)| match result {
+                Ok(pipeline_id) => PipelineBatchItem { marker_id, pipeline_id: Some(pipeline_id), error: None },
+                Err(e) => PipelineBatchItem { marker_id, pipeline_id: None, error: Some(e) },
+            })
+            .collect())
+    }
+
+    // 批量发出OCR，返回与marker_ids一一对应的任务ID；给了翻译服务时每个标记创建流水线，返回流水线ID
+    fn dispatch_ocr_batch(&self, marker_ids: &[MarkerId], project_id: ProjectId, service_id: String, translation_service: Option<&str>) -> Result<Vec<Result<String, String>>, String> {
+        let project = crate::storage::project::get_project_storage(project_id)
+            .map_err(|e| format!("Failed to get project: {:?}", e))?
+            .ok_or("Project not found")?;
+
+        let mut items: Vec<Result<String, String>> = vec![Err(String::new()); marker_ids.len()];
+
+        // 按图片分组，保留每组内的输入顺序
+        let mut groups: Vec<(ImageId, Vec<(usize, crate::storage::marker::Marker)>)> = Vec::new();
+        for (index, marker_id) in marker_ids.iter().enumerate() {
+            match crate::storage::marker::get_marker_storage(*marker_id) {
+                Ok(Some(marker)) if project.image_ids.contains(&marker.image_id) => {
+                    match groups.iter_mut().find(|(image_id, _)| *image_id == marker.image_id) {
+                        Some((_, markers)) => markers.push((index, marker)),
+                        None => groups.push((marker.image_id, vec![(index, marker)])),
+                    }
+                }
+                Ok(Some(_)) => items[index] = Err("Marker does not belong to project".to_string()),
+                Ok(None) => items[index] = Err("Marker not found".to_string()),
+                Err(e) => items[index] = Err(format!("Failed to get marker: {:?}", e)),
+            }
+        }
+
+        let image_service = &crate::service::get_service().image_service;
+        for (image_id, markers) in groups {
+            let full_image_data = match image_service.get_image_binary_data(image_id.into()) {
+                Ok(data) => data,
+                Err(e) => {
+                    for (index, _) in &markers {
+                        items[*index] = Err(e.clone());
+                    }
+                    continue;
+                }
+            };
+            let image_size = image_pixel_size(image_id, &full_image_data);
+            let geometries = markers.iter().map(|(_, marker)| marker.geometry.clone()).collect();
+            let crops = image_service.crop_image_regions(&full_image_data, geometries);
+
+            for ((index, marker), crop) in markers.into_iter().zip(crops) {
+                let pixel_geometry = image_size
+                    .map(|(image_width, image_height)| marker.geometry.to_pixels(image_width, image_height));
+                items[index] = crop.and_then(|cropped| {
+                    let (id, task_id) = match translation_service {
+                        Some(translation_service) => TASK_MANAGER.create_pipeline(
+                            project_id, marker.id, image_id, service_id.clone(), translation_service.to_string(),
+                        )?,
+                        None => {
+                            let task_id = TASK_MANAGER.create_task(project_id, marker.id, image_id, TaskType::OCR, service_id.clone())?;
+                            (task_id.clone(), task_id)
+                        }
+                    };
+                    self.dispatch_ocr(&task_id, marker.id, service_id.clone(), project.source_language, cropped, pixel_geometry)?;
+                    Ok(id)
+                });
+            }
+        }
+
+        Ok(items)
+    }
+
+    // 把裁剪好的图片发给前端转交插件
+    fn dispatch_ocr(
+        &self,
+        task_id: &str,
+        marker_id: MarkerId,
+        service_id: String,
+        source_language: crate::common::Language,
+        cropped_image_data: Vec<u8>,
+        pixel_geometry: Option<crate::common::dto::marker::PixelGeometry>,
+    ) -> Result<(), String> {
         // Emit task created event
-        let task = TASK_MANAGER.get_task(&task_id)?.ok_or("Task not found")?;
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:task_created".to_string(), serde_json::json!(task));
+        let task = TASK_MANAGER.get_task(task_id)?.ok_or("Task not found")?;
+        let _ = task.created_event().emit();
 
         // Emit request to frontend to relay to plugin (with cropped image)
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:request_plugin_ocr".to_string(), serde_json::json!({
-            "task_id": task_id,
-            "marker_id": marker_id,
-            "cropped_image_data": cropped_image_data,
-            "image_format": "png",  // Cropped images are always PNG
-            "service_id": service_id,
-            "source_language": project.source_language,
-        }));
+        let _ = RequestPluginOcrEvent::new(
+            task_id.to_string(),
+            marker_id,
+            cropped_image_data,
+            "png".to_string(),  // Cropped images are always PNG
+            service_id,
+            source_language,
+            pixel_geometry,
+        ).emit();
+
+        Ok(())
+    }
 
+    /// 请求检测插件找出图片中的文本框，结果以bunny:detection_completed发出，不直接生成标记
+    pub fn request_detection(&self, image_id: ImageId, service_id: String) -> Result<String, String> {
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("Image not in any project")?;
+        let image_data = crate::service::get_service().image_service.get_image_binary_data(image_id.into())?;
+        let image_format = image::guess_format(&image_data).ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("png")
+            .to_string();
+        let (width, height) = image_pixel_size(image_id, &image_data).unwrap_or((0, 0));
+
+        // 检测任务不针对标记，marker_id为0（标记ID从1开始）
+        let task_id = TASK_MANAGER.create_task(project_id, MarkerId(0), image_id, TaskType::Detection, service_id.clone())?;
+        let task = TASK_MANAGER.get_task(&task_id)?.ok_or("Task not found")?;
+        let _ = task.created_event().emit();
+        let _ = RequestPluginDetectionEvent::new(task_id.clone(), image_id, image_data, image_format, width, height, service_id).emit();
         Ok(task_id)
     }
 
+    /// Handle detection result from plugin (via frontend relay)
+    pub fn handle_detection_completed(&self, task_id: String, model: String, regions: Vec<DetectedRegion>) -> Result<(), String> {
+        let task = TASK_MANAGER.get_task(&task_id)?.ok_or("Task not found")?;
+        if task.task_type != TaskType::Detection {
+            return Err(format!("Task {} is not a detection task", task_id));
+        }
+        // 桌面端宿主和前端都会转交结果，重复的直接忽略
+        if task.status == TaskStatus::Completed {
+            return Ok(());
+        }
+        TASK_MANAGER.complete_task(&task_id)?;
+        let _ = DetectionCompletedEvent::new(task_id, task.image_id, model, regions).emit();
+        Ok(())
+    }
+
+    /// 把检测到的区域（原图像素）生成矩形标记，整体为一次撤销；超出图片的部分截掉，空区域跳过
+    pub fn create_markers_from_detection(&self, image_id: ImageId, regions: Vec<DetectedRegion>) -> Result<Vec<MarkerId>, String> {
+        let service = crate::service::get_service();
+        let image = crate::storage::image::get_image_storage(image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Image {} not found", image_id.0))?;
+        let (Some(image_width), Some(image_height)) = (image.metadata.width, image.metadata.height) else {
+            return Err("图片尺寸未知，无法换算检测区域".to_string());
+        };
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Image not in any project".to_string())?;
+        let (image_width, image_height) = (image_width as f64, image_height as f64);
+
+        let guard = service.undo_redo_service.begin_group_guard(project_id, "按检测结果添加标记".to_string())
+            .map_err(|e| e.to_string())?;
+        let mut created = Vec::with_capacity(regions.len());
+        for region in &regions {
+            let left = region.x.clamp(0.0, image_width);
+            let top = region.y.clamp(0.0, image_height);
+            let right = (region.x + region.w).clamp(0.0, image_width);
+            let bottom = (region.y + region.h).clamp(0.0, image_height);
+            if right <= left || bottom <= top {
+                continue;
+            }
+            let marker_id = service.marker_service.add_rectangle_marker_to_image(
+                image_id,
+                left * 100.0 / image_width,
+                top * 100.0 / image_height,
+                (right - left) * 100.0 / image_width,
+                (bottom - top) * 100.0 / image_height,
+                None,
+                crate::storage::marker::CoordinateMode::Clamp,
+            ).map_err(|e| e.to_string())?;
+            service.event_bus.publish(crate::service::events::DomainEvent::MarkerAddedToImage(image_id, marker_id));
+            created.push(marker_id);
+        }
+        guard.finish().map_err(|e| e.to_string())?;
+        Ok(created)
+    }
+
     /// Request translation processing for a marker
     pub fn request_translation(&self, marker_id: MarkerId, image_id: ImageId, project_id: ProjectId, service_id: String, text: String) -> Result<String, String> {
         // Get project info to retrieve languages
@@ -77,77 +359,946 @@ impl BunnyService {
             .ok_or("Project not found")?;
 
         // Create task
-        let task_id = TASK_MANAGER.create_task(marker_id, image_id, TaskType::Translation, service_id.clone())?;
+        let task_id = TASK_MANAGER.create_task(project_id, marker_id, image_id, TaskType::Translation, service_id.clone())?;
+
+        self.dispatch_translation(&task_id, marker_id, image_id, &project, service_id, text)?;
 
+        Ok(task_id)
+    }
+
+    /// 翻译整个项目：为有OCR原文但译文为空的标记排队翻译任务，按图片顺序和标记序号
+    /// 没有原文的标记和已有进行中翻译任务的标记跳过；取消后停止排队，已排队的任务照常完成
+    pub fn translate_all_untranslated(&self, project_id: ProjectId, service_id: String, token: &CancellationToken) -> Result<Vec<String>, String> {
+        let project = crate::storage::project::get_project_storage(project_id)
+            .map_err(|e| format!("Failed to get project: {:?}", e))?
+            .ok_or("Project not found")?;
+
+        let pending: Vec<MarkerId> = TASK_MANAGER.get_all_tasks()?.into_iter()
+            .filter(|task| task.task_type == TaskType::Translation
+                && matches!(task.status, TaskStatus::Queued | TaskStatus::Processing))
+            .map(|task| task.marker_id)
+            .collect();
+
+        let mut candidates = Vec::new();
+        for image_id in &project.image_ids {
+            let mut markers = crate::storage::marker::get_image_markers_storage(*image_id)
+                .map_err(|e| format!("Failed to get markers: {:?}", e))?;
+            markers.sort_by_key(|marker| marker.image_index);
+            for marker in markers {
+                if !marker.translation.trim().is_empty() || pending.contains(&marker.id) {
+                    continue;
+                }
+                let original = crate::storage::bunny_cache::get_bunny_cache_storage(marker.id)
+                    .map_err(|e| format!("Failed to get bunny cache: {:?}", e))?
+                    .and_then(|cache| cache.original_text)
+                    .filter(|text| !text.trim().is_empty());
+                if let Some(text) = original {
+                    candidates.push((marker.id, marker.image_id, text));
+                }
+            }
+        }
+
+        let total = candidates.len();
+        let mut task_ids = Vec::new();
+        for (done, (marker_id, image_id, text)) in candidates.into_iter().enumerate() {
+            if token.is_cancelled() {
+                break;
+            }
+            let task_id = TASK_MANAGER.create_task(project_id, marker_id, image_id, TaskType::Translation, service_id.clone())?;
+            match self.dispatch_translation(&task_id, marker_id, image_id, &project, service_id.clone(), text) {
+                Ok(()) => task_ids.push(task_id),
+                Err(e) => {
+                    crate::common::Logger::warn(&format!("Failed to queue translation for marker {}: {}", marker_id.0, e));
+                    let _ = TASK_MANAGER.remove_task(&task_id);
+                }
+            }
+            let _ = BatchProgressEvent::new(project_id, done + 1, total).emit();
+        }
+
+        Ok(task_ids)
+    }
+
+    /// Translate text synchronously (for CLI/tests), blocks until the plugin replies
+    pub fn translate_sync(&self, marker_id: MarkerId, service_id: String, text: String) -> Result<String, String> {
+        self.translate_sync_with_timeout(marker_id, service_id, text, SYNC_TRANSLATION_TIMEOUT)
+    }
+
+    /// Same as translate_sync with a custom timeout
+    pub fn translate_sync_with_timeout(&self, marker_id: MarkerId, service_id: String, text: String, timeout: Duration) -> Result<String, String> {
+        let marker = crate::storage::marker::get_marker_storage(marker_id)
+            .map_err(|e| format!("Failed to get marker: {:?}", e))?
+            .ok_or("Marker not found")?;
+        let image_id = marker.image_id;
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)
+            .map_err(|e| format!("Failed to find project: {:?}", e))?
+            .ok_or("Project not found")?;
+        let project = crate::storage::project::get_project_storage(project_id)
+            .map_err(|e| format!("Failed to get project: {:?}", e))?
+            .ok_or("Project not found")?;
+
+        let task_id = TASK_MANAGER.create_task(project_id, marker_id, image_id, TaskType::Translation, service_id.clone())?;
+
+        // 先注册等待者再发出请求，避免结果先于注册到达
+        let receiver = TASK_MANAGER.register_waiter(&task_id)?;
+        if let Err(e) = self.dispatch_translation(&task_id, marker_id, image_id, &project, service_id, text) {
+            TASK_MANAGER.remove_waiter(&task_id);
+            return Err(e);
+        }
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                TASK_MANAGER.remove_waiter(&task_id);
+                let error = format!("Translation task timed out after {}ms", timeout.as_millis());
+                let _ = self.handle_task_failed(task_id, error.clone());
+                Err(error)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                Err("Translation task was dropped before completion".to_string())
+            }
+        }
+    }
+
+    fn dispatch_translation(&self, task_id: &str, marker_id: MarkerId, image_id: ImageId, project: &crate::storage::project::Project, service_id: String, text: String) -> Result<(), String> {
         // Emit task created event
-        let task = TASK_MANAGER.get_task(&task_id)?.ok_or("Task not found")?;
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:task_created".to_string(), serde_json::json!(task));
+        let task = TASK_MANAGER.get_task(task_id)?.ok_or("Task not found")?;
+        let _ = task.created_event().emit();
+        TASK_MANAGER.remember_source_text(task_id, text.clone());
+
+        // 上下文按项目的阅读方向收集，取不到时不影响翻译
+        let context = crate::service::marker::translation_context(marker_id, crate::service::marker::reading_order::DEFAULT_CONTEXT_MARKERS)
+            .unwrap_or_default();
 
         // Emit request to frontend to relay to plugin
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:request_plugin_translation".to_string(), serde_json::json!({
-            "task_id": task_id,
-            "marker_id": marker_id,
-            "image_id": image_id,
-            "service_id": service_id,
-            "text": text,
-            "source_language": project.source_language,
-            "target_language": project.target_language,
-        }));
+        let _ = RequestPluginTranslationEvent::new(
+            task_id.to_string(),
+            marker_id,
+            image_id,
+            service_id,
+            text,
+            project.source_language,
+            project.target_language,
+            project.glossary.clone(),
+            context,
+        ).emit();
 
-        Ok(task_id)
+        Ok(())
     }
 
     /// Handle OCR completion from plugin (via frontend relay)
-    pub fn handle_ocr_completed(&self, task_id: String, marker_id: MarkerId, text: String, model: String) -> Result<(), String> {
+    /// regions为插件返回的文本行位置，可用split_marker_by_ocr_regions把标记拆成每行一个
+    pub fn handle_ocr_completed(&self, task_id: String, marker_id: MarkerId, text: String, model: String, regions: Vec<OcrRegion>) -> Result<(), String> {
         // Update task status
         TASK_MANAGER.complete_task(&task_id)?;
+        let pipeline_id = TASK_MANAGER.get_task(&task_id)?.and_then(|task| task.parent_pipeline_id);
 
         // Update cache
         crate::storage::bunny_cache::update_original_text_storage(marker_id, text.clone(), model.clone())
             .map_err(|e| format!("Failed to update cache: {:?}", e))?;
+        crate::storage::bunny_cache::set_ocr_regions_storage(marker_id, regions.clone())
+            .map_err(|e| format!("Failed to update cache: {:?}", e))?;
+        crate::service::get_service().stats_service.notify_marker_text_changed(marker_id);
+
+        TASK_MANAGER.resolve_waiter(&task_id, Ok(text.clone()));
 
         // Emit completion event
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:ocr_completed".to_string(), serde_json::json!({
-            "task_id": task_id,
-            "marker_id": marker_id,
-            "original_text": text,
-            "model": model,
-        }));
+        let _ = OcrCompletedEvent::new(task_id.clone(), marker_id, text.clone(), model, regions).emit();
+
+        if let Some(pipeline_id) = pipeline_id {
+            self.advance_pipeline(&pipeline_id, task_id, text);
+        }
 
         Ok(())
     }
 
+    // 流水线OCR完成后用原文发起翻译，出错时流水线在OCR阶段失败
+    fn advance_pipeline(&self, pipeline_id: &str, ocr_task_id: String, text: String) {
+        let result = (|| {
+            if text.trim().is_empty() {
+                return Err("OCR没有识别出文字".to_string());
+            }
+            let pipeline = TASK_MANAGER.get_pipeline(pipeline_id)?.ok_or("Pipeline not found")?;
+            let project = crate::storage::project::get_project_storage(pipeline.project_id)
+                .map_err(|e| format!("Failed to get project: {:?}", e))?
+                .ok_or("Project not found")?;
+            let Some(translation_task_id) = TASK_MANAGER.advance_pipeline(pipeline_id)? else {
+                return Ok(());
+            };
+            self.dispatch_translation(&translation_task_id, pipeline.marker_id, pipeline.image_id, &project, pipeline.translation_service, text)?;
+            let _ = PipelineAdvancedEvent::new(pipeline_id.to_string(), pipeline.marker_id, ocr_task_id, translation_task_id).emit();
+            Ok(())
+        })();
+        if let Err(e) = result {
+            self.fail_pipeline(pipeline_id, e);
+        }
+    }
+
+    fn fail_pipeline(&self, pipeline_id: &str, error: String) {
+        if let Ok(Some(pipeline)) = TASK_MANAGER.fail_pipeline(pipeline_id, error.clone()) {
+            let _ = PipelineFailedEvent::new(pipeline.pipeline_id, pipeline.marker_id, pipeline.stage.as_str().to_string(), error).emit();
+        }
+    }
+
+    /// 按OCR文本行把矩形/多边形标记拆成每行一个矩形标记，原标记删除，整个拆分是一次撤销
+    /// 区域坐标相对于OCR时的裁剪图（像素），新标记按区域顺序排在原标记的位置，原文写入各自的缓存
+    pub fn split_marker_by_ocr_regions(&self, marker_id: MarkerId, regions: Vec<OcrRegion>) -> Result<Vec<MarkerId>, String> {
+        if regions.is_empty() {
+            return Err("没有可用于拆分的OCR区域".to_string());
+        }
+        crate::service::image::ensure_marker_unlocked(marker_id).map_err(|e| e.to_json_string())?;
+        let service = crate::service::get_service();
+        let marker = crate::storage::marker::get_marker_storage(marker_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Marker {} not found", marker_id.0))?;
+        if matches!(marker.geometry, crate::storage::marker::MarkerGeometry::Point { .. }) {
+            return Err("只能拆分矩形或多边形标记".to_string());
+        }
+        let image = crate::storage::image::get_image_storage(marker.image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Image {} not found", marker.image_id.0))?;
+        let (Some(image_width), Some(image_height)) = (image.metadata.width, image.metadata.height) else {
+            return Err("图片尺寸未知，无法换算OCR区域".to_string());
+        };
+        let project_id = crate::storage::project::find_project_by_image_storage(marker.image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Image not in any project".to_string())?;
+
+        // 与ocr_payload裁剪时的换算一致
+        let crate::common::dto::marker::PixelGeometry::Rectangle { x: crop_x, y: crop_y, width: crop_w, height: crop_h } =
+            marker.geometry.to_pixels(image_width, image_height)
+        else {
+            return Err("只能拆分矩形或多边形标记".to_string());
+        };
+        let model = crate::storage::bunny_cache::get_bunny_cache_storage(marker_id).ok().flatten()
+            .and_then(|cache| cache.last_ocr_model)
+            .unwrap_or_default();
+
+        let guard = service.undo_redo_service.begin_group_guard(project_id, "按OCR区域拆分标记".to_string())
+            .map_err(|e| e.to_string())?;
+        let mut children = Vec::with_capacity(regions.len());
+        for region in &regions {
+            // 截到裁剪范围内，超出的部分丢弃
+            let left = region.x.clamp(0.0, crop_w as f64);
+            let top = region.y.clamp(0.0, crop_h as f64);
+            let right = (region.x + region.w).clamp(0.0, crop_w as f64);
+            let bottom = (region.y + region.h).clamp(0.0, crop_h as f64);
+            if right <= left || bottom <= top {
+                continue;
+            }
+            let to_percent_x = |px: f64| (crop_x as f64 + px) * 100.0 / image_width as f64;
+            let to_percent_y = |px: f64| (crop_y as f64 + px) * 100.0 / image_height as f64;
+            let (x, y) = (to_percent_x(left), to_percent_y(top));
+            let (width, height) = (to_percent_x(right) - x, to_percent_y(bottom) - y);
+
+            let child = service.marker_service
+                .add_rectangle_marker_to_image(marker.image_id, x, y, width, height, None, crate::storage::marker::CoordinateMode::Clamp)
+                .map_err(|e| e.to_string())?;
+            service.event_bus.publish(crate::service::events::DomainEvent::MarkerAddedToImage(marker.image_id, child));
+            let _ = crate::storage::bunny_cache::update_original_text_storage(child, region.text.clone(), model.clone());
+            children.push(child);
+        }
+        if children.is_empty() {
+            return Err("OCR区域都不在标记范围内".to_string());
+        }
+
+        let parent_index = marker.image_index;
+        service.marker_service.remove_marker_with_undo(marker_id).map_err(|e| e.to_string())?;
+        for (offset, child) in children.iter().enumerate() {
+            service.marker_service.move_marker_order_with_undo(*child, parent_index + offset as u32)
+                .map_err(|e| e.to_string())?;
+        }
+        guard.finish().map_err(|e| e.to_string())?;
+        Ok(children)
+    }
+
     /// Handle translation completion from plugin (via frontend relay)
-    pub fn handle_translation_completed(&self, task_id: String, marker_id: MarkerId, translated_text: String, service: String) -> Result<(), String> {
+    pub fn handle_translation_completed(&self, task_id: String, marker_id: MarkerId, translated_text: String, service: String, warnings: Vec<TranslationWarning>, metadata: Option<serde_json::Value>) -> Result<(), String> {
         // Update task status
         TASK_MANAGER.complete_task(&task_id)?;
+        let pipeline_id = TASK_MANAGER.get_task(&task_id)?.and_then(|task| task.parent_pipeline_id);
 
         // Update cache
-        crate::storage::bunny_cache::update_machine_translation_storage(marker_id, translated_text.clone(), service.clone())
+        let metadata = accept_translation_metadata(metadata);
+        crate::storage::bunny_cache::update_machine_translation_storage(marker_id, translated_text.clone(), service.clone(), warnings.clone(), metadata)
             .map_err(|e| format!("Failed to update cache: {:?}", e))?;
 
+        TASK_MANAGER.resolve_waiter(&task_id, Ok(translated_text.clone()));
+
         // Emit completion event
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:translation_completed".to_string(), serde_json::json!({
-            "task_id": task_id,
-            "marker_id": marker_id,
-            "machine_translation": translated_text,
-            "service": service,
-        }));
+        let _ = TranslationCompletedEvent::new(task_id.clone(), marker_id, translated_text, service, warnings).emit();
+
+        if let Some(pipeline_id) = pipeline_id
+            && let Some(pipeline) = TASK_MANAGER.complete_pipeline(&pipeline_id)?
+        {
+            let _ = PipelineCompletedEvent::new(pipeline_id, pipeline.marker_id, task_id).emit();
+        }
 
         Ok(())
     }
 
+    /// 把缓存的机器翻译写入标记译文（可撤销，译文历史中记为机器翻译）
+    ///
+    /// 标记已有译文且在机翻之后被修改过时拒绝，避免覆盖人工修改；返回译文是否有变化
+    pub fn accept_machine_translation(&self, marker_id: MarkerId) -> Result<bool, String> {
+        crate::service::image::ensure_marker_unlocked(marker_id).map_err(|e| e.to_json_string())?;
+        let marker = crate::storage::marker::get_marker_storage(marker_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Marker {} not found", marker_id.0))?;
+        let (text, produced_at) = machine_translation_of(marker_id)?
+            .ok_or_else(|| format!("标记{}没有机器翻译", marker_id.0))?;
+        if edited_after(&marker, produced_at) {
+            return Err(format!("标记{}的译文在机器翻译之后被修改过，未覆盖", marker_id.0));
+        }
+        let changed = crate::service::get_service().marker_service.update_marker_translation_from(
+            marker_id, text, crate::storage::TranslationOrigin::Machine,
+        );
+        let _ = MachineTranslationAcceptedEvent::new(marker.image_id, vec![marker_id]).emit();
+        Ok(changed)
+    }
+
+    /// 接受图片上所有标记的机器翻译，整体为一次撤销；only_empty时只写入还没有译文的标记
+    pub fn accept_all_for_image(&self, image_id: ImageId, only_empty: bool) -> Result<AcceptMachineTranslationReport, String> {
+        let service = crate::service::get_service();
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Image not in any project".to_string())?;
+        let marker_ids = crate::storage::image::get_image_marker_ids_storage(image_id).map_err(|e| e.to_string())?;
+
+        let mut report = AcceptMachineTranslationReport::default();
+        let guard = service.undo_redo_service.begin_group_guard(project_id, "接受机器翻译".to_string())
+            .map_err(|e| e.to_string())?;
+        for marker_id in marker_ids {
+            let Some(marker) = crate::storage::marker::get_marker_storage(marker_id).map_err(|e| e.to_string())? else {
+                continue;
+            };
+            let Some((text, produced_at)) = machine_translation_of(marker_id)? else {
+                continue;
+            };
+            if only_empty && !marker.translation.trim().is_empty() {
+                continue;
+            }
+            if crate::service::image::ensure_marker_unlocked(marker_id).is_err() {
+                report.locked.push(marker_id);
+                continue;
+            }
+            if edited_after(&marker, produced_at) {
+                report.edited.push(marker_id);
+                continue;
+            }
+            service.marker_service.update_marker_translation_from(marker_id, text, crate::storage::TranslationOrigin::Machine);
+            report.accepted.push(marker_id);
+        }
+        guard.finish().map_err(|e| e.to_string())?;
+
+        if !report.accepted.is_empty() {
+            let _ = MachineTranslationAcceptedEvent::new(image_id, report.accepted.clone()).emit();
+        }
+        Ok(report)
+    }
+
+    /// 丢弃缓存的机器翻译（原文保留），返回是否有机翻被清除
+    pub fn reject_machine_translation(&self, marker_id: MarkerId) -> Result<bool, String> {
+        let cleared = crate::storage::bunny_cache::clear_machine_translation_storage(marker_id)
+            .map_err(|e| e.to_string())?;
+        if cleared {
+            let _ = MachineTranslationRejectedEvent::new(marker_id).emit();
+        }
+        Ok(cleared)
+    }
+
+    /// 标记的机器翻译和校验问题
+    pub fn get_translation_result(&self, marker_id: MarkerId) -> Result<Option<TranslationResult>, String> {
+        let cache = crate::storage::bunny_cache::get_bunny_cache_storage(marker_id)
+            .map_err(|e| format!("Failed to get bunny cache: {:?}", e))?;
+        Ok(cache.and_then(|cache| {
+            cache.machine_translation.map(|text| TranslationResult {
+                marker_id,
+                text,
+                service: cache.last_translation_service,
+                warnings: cache.translation_warnings,
+                metadata: cache.translation_metadata,
+            })
+        }))
+    }
+
     /// Handle task failure from plugin (via frontend relay)
+    ///
+    /// 项目配置了后备服务时改用下一个还没试过的服务重发，全部失败后才发出bunny:task_failed
     pub fn handle_task_failed(&self, task_id: String, error: String) -> Result<(), String> {
         // Update task status
         TASK_MANAGER.fail_task(&task_id, error.clone())?;
+        let source_text = TASK_MANAGER.take_source_text(&task_id);
+
+        if let Some(task) = TASK_MANAGER.get_task(&task_id)?
+            && let Some(next) = self.next_fallback_service(&task)
+        {
+            match self.dispatch_fallback(&task, next.clone(), source_text) {
+                Ok(new_task_id) => {
+                    TASK_MANAGER.transfer_waiter(&task_id, &new_task_id);
+                    let _ = TaskFellbackEvent::new(task_id, new_task_id, task.marker_id, task.service_id, next, error).emit();
+                    return Ok(());
+                }
+                Err(e) => crate::common::Logger::warn(&format!("Fallback to {} for task {} failed: {}", next, task_id, e)),
+            }
+        }
+
+        TASK_MANAGER.resolve_waiter(&task_id, Err(error.clone()));
+
+        if let Some(pipeline_id) = TASK_MANAGER.get_task(&task_id)?.and_then(|task| task.parent_pipeline_id) {
+            self.fail_pipeline(&pipeline_id, error.clone());
+        }
 
         // Emit failure event
-        let _ = EVENT_SYSTEM.emit_business_event("bunny:task_failed".to_string(), serde_json::json!({
-            "task_id": task_id,
-            "error": error,
-        }));
+        let _ = TaskFailedEvent::new(task_id, error).emit();
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    // 项目后备链中第一个还没试过且在项目中启用的服务
+    fn next_fallback_service(&self, task: &BunnyTask) -> Option<String> {
+        let project = crate::storage::project::get_project_storage(task.project_id).ok().flatten()?;
+        let services = &project.default_services;
+        let chain = match task.task_type {
+            TaskType::OCR => &services.ocr_fallbacks,
+            TaskType::Translation => &services.translation_fallbacks,
+            // 检测服务没有后备链
+            TaskType::Detection => return None,
+        };
+        chain.iter()
+            .find(|id| **id != task.service_id && !task.tried_services.contains(id) && services.is_enabled(id))
+            .cloned()
+    }
+
+    // 用同样的输入为失败的任务创建新任务并发给service_id
+    fn dispatch_fallback(&self, failed: &BunnyTask, service_id: String, source_text: Option<String>) -> Result<String, String> {
+        let project = crate::storage::project::get_project_storage(failed.project_id)
+            .map_err(|e| format!("Failed to get project: {:?}", e))?
+            .ok_or("Project not found")?;
+        match failed.task_type {
+            TaskType::OCR => {
+                let (cropped_image_data, pixel_geometry) = self.ocr_payload(failed.marker_id, failed.image_id)?;
+                let task_id = TASK_MANAGER.create_fallback_task(failed, service_id.clone())?;
+                self.dispatch_ocr(&task_id, failed.marker_id, service_id, project.source_language, cropped_image_data, pixel_geometry)
+                    .map(|_| task_id)
+            }
+            TaskType::Translation => {
+                let text = source_text.ok_or("Source text of the failed task is unavailable")?;
+                let task_id = TASK_MANAGER.create_fallback_task(failed, service_id.clone())?;
+                self.dispatch_translation(&task_id, failed.marker_id, failed.image_id, &project, service_id, text)
+                    .map(|_| task_id)
+            }
+            TaskType::Detection => Err("Detection tasks have no fallback".to_string()),
+        }
+    }
+}
+
+// 缓存中的机器翻译及其写入时间
+fn machine_translation_of(marker_id: MarkerId) -> Result<Option<(String, Option<u64>)>, String> {
+    let cache = crate::storage::bunny_cache::get_bunny_cache_storage(marker_id)
+        .map_err(|e| format!("Failed to get bunny cache: {:?}", e))?;
+    Ok(cache.and_then(|cache| cache.machine_translation.map(|text| (text, cache.machine_translated_at))))
+}
+
+// 当前译文是机翻之后非机翻来源写入的：译文历史中最后一条晚于机翻，且就是现在的译文（已被撤销的修改不算）
+// 旧文件中的机翻没有时间，无法判断时放行
+fn edited_after(marker: &crate::storage::marker::Marker, produced_at: Option<u64>) -> bool {
+    let Some(produced_at) = produced_at else {
+        return false;
+    };
+    if marker.translation.is_empty() {
+        return false;
+    }
+    let history = crate::storage::translation_history::get_translation_history_storage(marker.id).unwrap_or_default();
+    history.last().is_some_and(|entry| {
+        entry.timestamp > produced_at
+            && entry.origin != crate::storage::TranslationOrigin::Machine
+            && entry.translation == marker.translation
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::get_service;
+
+    fn create_marker() -> MarkerId {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_sync_test".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let marker_id = service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, None).unwrap();
+        MarkerId::from(marker_id)
+    }
+
+    fn wait_for_task(marker_id: MarkerId) -> String {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        loop {
+            let tasks: Vec<_> = TASK_MANAGER.get_all_tasks().unwrap()
+                .into_iter()
+                .filter(|t| t.marker_id == marker_id)
+                .collect();
+            if let Some(task) = tasks.iter().find(|t| t.status == TaskStatus::Queued) {
+                return task.task_id.clone();
+            }
+            if std::time::Instant::now() >= deadline {
+                let states: Vec<_> = tasks.iter().map(|t| (t.task_id.as_str(), t.status.as_str())).collect();
+                panic!("marker {} 的任务5秒内未进入队列，当前任务: {:?}", marker_id.0, states);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn translate_sync_returns_plugin_result() {
+        let marker_id = create_marker();
+        let responder = std::thread::spawn(move || {
+            let task_id = wait_for_task(marker_id);
+            BunnyService::new().handle_translation_completed(task_id, marker_id, "你好".to_string(), "dummy".to_string(), Vec::new(), None).unwrap();
+        });
+
+        let result = BunnyService::new().translate_sync_with_timeout(
+            marker_id, "dummy".to_string(), "hello".to_string(), Duration::from_secs(5)
+        );
+        responder.join().unwrap();
+        assert_eq!(result, Ok("你好".to_string()));
+    }
+
+    #[test]
+    fn translate_sync_propagates_plugin_error() {
+        let marker_id = create_marker();
+        let responder = std::thread::spawn(move || {
+            let task_id = wait_for_task(marker_id);
+            BunnyService::new().handle_task_failed(task_id, "quota exceeded".to_string()).unwrap();
+        });
+
+        let result = BunnyService::new().translate_sync_with_timeout(
+            marker_id, "dummy".to_string(), "hello".to_string(), Duration::from_secs(5)
+        );
+        responder.join().unwrap();
+        assert_eq!(result, Err("quota exceeded".to_string()));
+    }
+
+    #[test]
+    fn translate_sync_times_out() {
+        let marker_id = create_marker();
+        let result = BunnyService::new().translate_sync_with_timeout(
+            marker_id, "dummy".to_string(), "hello".to_string(), Duration::from_millis(20)
+        );
+        assert!(result.unwrap_err().contains("timed out"));
+    }
+
+    #[test]
+    fn ocr_batch_crops_in_order_and_reports_failures() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_batch_test".to_string()).unwrap();
+        let mut png = Vec::new();
+        image::RgbImage::new(200, 100)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, png, None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let first = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 20.0, 20.0, None).unwrap());
+        let second = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 80.0, 80.0, None).unwrap());
+
+        let items = BunnyService::new()
+            .request_ocr_batch(vec![second, MarkerId(u32::MAX), first], project_id, "batch-ocr".to_string())
+            .unwrap();
+        assert_eq!(items.iter().map(|item| item.marker_id).collect::<Vec<_>>(), vec![second, MarkerId(u32::MAX), first]);
+        assert_eq!(items[1].error.as_deref(), Some("Marker not found"));
+        for index in [0, 2] {
+            let task = TASK_MANAGER.get_task(items[index].task_id.as_ref().unwrap()).unwrap().unwrap();
+            assert_eq!(task.marker_id, items[index].marker_id);
+        }
+    }
+
+    #[test]
+    fn pipeline_translates_ocr_text_and_records_failed_stage() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_pipeline_test".to_string()).unwrap();
+        let mut png = Vec::new();
+        image::RgbImage::new(200, 100)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, png, None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let first = MarkerId::from(service.marker_service.add_rectangle_marker(image_id.0, 10.0, 10.0, 20.0, 20.0, None).unwrap());
+        let second = MarkerId::from(service.marker_service.add_rectangle_marker(image_id.0, 50.0, 50.0, 20.0, 20.0, None).unwrap());
+        let bunny = BunnyService::new();
+
+        let pipeline_id = bunny.request_pipeline(first, image_id, project_id, "ocr".to_string(), "mt".to_string()).unwrap();
+        let pipeline = TASK_MANAGER.get_pipeline(&pipeline_id).unwrap().unwrap();
+        assert_eq!((pipeline.stage, pipeline.status, pipeline.translation_task_id), (PipelineStage::Ocr, TaskStatus::Processing, None));
+        let ocr_task = TASK_MANAGER.get_task(&pipeline.ocr_task_id).unwrap().unwrap();
+        assert_eq!(ocr_task.parent_pipeline_id.as_deref(), Some(pipeline_id.as_str()));
+
+        // OCR完成后自动用原文发起翻译，重复的OCR结果不再发起
+        bunny.handle_ocr_completed(pipeline.ocr_task_id.clone(), first, "こんにちは".to_string(), "m".to_string(), Vec::new()).unwrap();
+        bunny.handle_ocr_completed(pipeline.ocr_task_id.clone(), first, "こんにちは".to_string(), "m".to_string(), Vec::new()).unwrap();
+        let pipeline = TASK_MANAGER.get_pipeline(&pipeline_id).unwrap().unwrap();
+        assert_eq!(pipeline.stage, PipelineStage::Translation);
+        let translation_task_id = pipeline.translation_task_id.unwrap();
+        let translation_task = TASK_MANAGER.get_task(&translation_task_id).unwrap().unwrap();
+        assert_eq!((translation_task.task_type, translation_task.service_id.as_str()), (TaskType::Translation, "mt"));
+        assert_eq!(TASK_MANAGER.take_source_text(&translation_task_id).as_deref(), Some("こんにちは"));
+        let translations = TASK_MANAGER.get_all_tasks().unwrap().into_iter()
+            .filter(|task| task.parent_pipeline_id.as_deref() == Some(pipeline_id.as_str()) && task.task_type == TaskType::Translation)
+            .count();
+        assert_eq!(translations, 1);
+
+        bunny.handle_translation_completed(translation_task_id, first, "你好".to_string(), "mt".to_string(), Vec::new(), None).unwrap();
+        assert_eq!(TASK_MANAGER.get_pipeline(&pipeline_id).unwrap().unwrap().status, TaskStatus::Completed);
+
+        // 批量按序号创建；OCR失败时流水线停在OCR阶段
+        let items = bunny.request_pipeline_batch(image_id, "ocr".to_string(), "mt".to_string()).unwrap();
+        assert_eq!(items.iter().map(|item| item.marker_id).collect::<Vec<_>>(), vec![first, second]);
+        let failed_id = items[1].pipeline_id.clone().unwrap();
+        let failed = TASK_MANAGER.get_pipeline(&failed_id).unwrap().unwrap();
+        bunny.handle_task_failed(failed.ocr_task_id, "engine down".to_string()).unwrap();
+        let failed = TASK_MANAGER.get_pipeline(&failed_id).unwrap().unwrap();
+        assert_eq!((failed.stage, failed.status, failed.error.as_deref()), (PipelineStage::Ocr, TaskStatus::Failed, Some("engine down")));
+        assert_eq!(TASK_MANAGER.get_project_pipelines(project_id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn auto_ocr_fires_only_for_drawn_rectangles() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_auto_ocr_test".to_string()).unwrap();
+        let mut png = Vec::new();
+        image::RgbImage::new(100, 100)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, png, None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let has_task = |marker_id: u32| TASK_MANAGER.get_all_tasks().unwrap()
+            .iter().any(|task| task.marker_id == MarkerId(marker_id));
+        let draw = |translation: Option<String>| crate::api::marker::add_rectangle_marker_to_image(
+            image_id.0, 10.0, 10.0, 30.0, 20.0, translation, None
+        ).unwrap().unwrap();
+
+        // 开启了但没有默认OCR服务时不触发
+        service.project_service.set_auto_ocr_on_create(project_id, true).unwrap();
+        assert!(!has_task(draw(None)));
+
+        crate::storage::project::update_project_with_storage(project_id, |project| {
+            project.default_services.ocr = Some("auto-ocr".to_string());
+        }).unwrap();
+        let drawn = draw(Some(String::new()));
+        let task = TASK_MANAGER.get_all_tasks().unwrap().into_iter().find(|t| t.marker_id == MarkerId(drawn)).unwrap();
+        assert_eq!(task.task_type, TaskType::OCR);
+        assert_eq!(task.service_id, "auto-ocr");
+
+        // 复制/移动带着译文，服务层和导入直接创建标记，都不触发
+        assert!(!has_task(draw(Some("译文".to_string()))));
+        assert!(!has_task(service.marker_service.add_rectangle_marker(image_id.0, 50.0, 50.0, 10.0, 10.0, None).unwrap()));
+
+        service.project_service.set_auto_ocr_on_create(project_id, false).unwrap();
+        assert!(!has_task(draw(None)));
+    }
+
+    #[test]
+    fn translate_all_queues_only_untranslated_markers_with_source() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_translate_all_test".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let add = |translation: Option<&str>, original: Option<&str>| {
+            let marker_id = MarkerId::from(service.marker_service
+                .add_point_marker(image_id.0, 10.0, 10.0, translation.map(str::to_string)).unwrap());
+            if let Some(text) = original {
+                crate::storage::bunny_cache::update_original_text_storage(marker_id, text.to_string(), "ocr".to_string()).unwrap();
+            }
+            marker_id
+        };
+        let untranslated = add(None, Some("こんにちは"));
+        let pending = add(None, Some("さようなら"));
+        add(Some("已翻译"), Some("ありがとう"));
+        add(None, None);
+        add(None, Some("  "));
+        TASK_MANAGER.create_task(project_id, pending, image_id, TaskType::Translation, "mt".to_string()).unwrap();
+
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        assert!(BunnyService::new().translate_all_untranslated(project_id, "mt".to_string(), &cancelled).unwrap().is_empty());
+
+        let task_ids = BunnyService::new()
+            .translate_all_untranslated(project_id, "mt".to_string(), &CancellationToken::new())
+            .unwrap();
+        assert_eq!(task_ids.len(), 1);
+        let task = TASK_MANAGER.get_task(&task_ids[0]).unwrap().unwrap();
+        assert_eq!((task.marker_id, task.task_type, task.service_id.as_str()), (untranslated, TaskType::Translation, "mt"));
+    }
+
+    #[test]
+    fn failed_translation_falls_back_to_next_service() {
+        let marker_id = create_marker();
+        let image_id = crate::storage::marker::get_marker_storage(marker_id).unwrap().unwrap().image_id;
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id).unwrap().unwrap();
+        get_service().project_service
+            .set_service_fallbacks(project_id, Vec::new(), vec!["mt-a".to_string(), "mt-b".to_string()])
+            .unwrap();
+
+        // A失败后改用B，同步调用方拿到B的结果
+        let responder = std::thread::spawn(move || {
+            let failed = wait_for_task(marker_id);
+            BunnyService::new().handle_task_failed(failed.clone(), "quota exceeded".to_string()).unwrap();
+            let retried = wait_for_task(marker_id);
+            let task = TASK_MANAGER.get_task(&retried).unwrap().unwrap();
+            assert_ne!(retried, failed);
+            assert_eq!((task.service_id.as_str(), task.tried_services.clone()), ("mt-b", vec!["mt-a".to_string()]));
+            BunnyService::new().handle_translation_completed(retried, marker_id, "你好".to_string(), "mt-b".to_string(), Vec::new(), None).unwrap();
+        });
+        let result = BunnyService::new().translate_sync_with_timeout(
+            marker_id, "mt-a".to_string(), "hello".to_string(), Duration::from_secs(5)
+        );
+        responder.join().unwrap();
+        assert_eq!(result, Ok("你好".to_string()));
+
+        // 整条链都失败时才返回错误
+        let responder = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let task_id = wait_for_task(marker_id);
+                BunnyService::new().handle_task_failed(task_id, "down".to_string()).unwrap();
+            }
+        });
+        let result = BunnyService::new().translate_sync_with_timeout(
+            marker_id, "mt-a".to_string(), "hello".to_string(), Duration::from_secs(5)
+        );
+        responder.join().unwrap();
+        assert_eq!(result, Err("down".to_string()));
+    }
+
+    #[test]
+    fn translation_warnings_are_cached_until_manual_edit() {
+        let marker_id = create_marker();
+        let task = TASK_MANAGER.create_task(ProjectId(1), marker_id, ImageId(1), TaskType::Translation, "mt".to_string()).unwrap();
+        let warnings = vec![TranslationWarning::MissingTerm { source: "ヒカリ".to_string(), target: "光".to_string() }];
+        BunnyService::new().handle_translation_completed(task, marker_id, "小光".to_string(), "mt".to_string(), warnings.clone(), None).unwrap();
+
+        let result = BunnyService::new().get_translation_result(marker_id).unwrap().unwrap();
+        assert_eq!(result.text, "小光");
+        assert_eq!(result.warnings, warnings);
+
+        // 手动修改机翻后不再保留插件的校验结果
+        crate::api::update_machine_translation(marker_id, "光".to_string(), "manual".to_string()).unwrap();
+        assert!(BunnyService::new().get_translation_result(marker_id).unwrap().unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn translation_metadata_is_cached_and_size_limited() {
+        let marker_id = create_marker();
+        let metadata = serde_json::json!({"model": "doubao-seed-1-6-250615", "prompt_hash": "9f2c", "plugin_version": "0.1.0"});
+        let task = TASK_MANAGER.create_task(ProjectId(1), marker_id, ImageId(1), TaskType::Translation, "mt".to_string()).unwrap();
+        BunnyService::new().handle_translation_completed(task, marker_id, "小光".to_string(), "mt".to_string(), Vec::new(), Some(metadata.clone())).unwrap();
+        assert_eq!(BunnyService::new().get_translation_result(marker_id).unwrap().unwrap().metadata, Some(metadata));
+
+        // 过大或不是对象的metadata被丢弃，译文照常保存
+        let oversized = serde_json::json!({"prompt": "x".repeat(MAX_TRANSLATION_METADATA_BYTES)});
+        let task = TASK_MANAGER.create_task(ProjectId(1), marker_id, ImageId(1), TaskType::Translation, "mt".to_string()).unwrap();
+        BunnyService::new().handle_translation_completed(task, marker_id, "光".to_string(), "mt".to_string(), Vec::new(), Some(oversized)).unwrap();
+        let result = BunnyService::new().get_translation_result(marker_id).unwrap().unwrap();
+        assert_eq!((result.text.as_str(), result.metadata), ("光", None));
+        assert_eq!(accept_translation_metadata(Some(serde_json::json!("model"))), None);
+    }
+
+    #[test]
+    fn glossary_is_normalized_when_set() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("glossary_test".to_string()).unwrap();
+        let glossary = crate::common::ProjectGlossary {
+            entries: vec![
+                crate::common::GlossaryEntry { source: " ヒカリ ".to_string(), target: "光".to_string() },
+                crate::common::GlossaryEntry { source: "".to_string(), target: "空".to_string() },
+                crate::common::GlossaryEntry { source: "ヒカリ".to_string(), target: "小光".to_string() },
+            ],
+            forbidden_terms: vec!["该死".to_string(), " 该死".to_string()],
+        };
+        assert!(crate::api::set_project_glossary(project_id.0, glossary));
+        let stored = crate::api::get_project_glossary(project_id.0).unwrap();
+        assert_eq!(stored.entries, vec![crate::common::GlossaryEntry { source: "ヒカリ".to_string(), target: "小光".to_string() }]);
+        assert_eq!(stored.forbidden_terms, vec!["该死"]);
+    }
+
+    #[test]
+    fn services_filtered_by_project() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_services_test".to_string()).unwrap();
+        let info = |id: &str| OCRServiceInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            version: "1.0".to_string(),
+            supported_languages: vec!["ja".to_string()],
+            supported_image_formats: vec!["png".to_string()],
+            max_image_size: None,
+        };
+        let mut registry = BUNNY_SERVICE_REGISTRY.write().unwrap();
+        registry.register_ocr_service("p".to_string(), info("project-ocr-a")).unwrap();
+        registry.register_ocr_service("p".to_string(), info("project-ocr-b")).unwrap();
+        drop(registry);
+
+        assert!(crate::api::set_service_enabled(project_id.0, "project-ocr-a".to_string(), false));
+        let ids = |project: Option<u32>| -> Vec<String> {
+            crate::api::get_available_ocr_services(project).iter()
+                .filter_map(|s| s["id"].as_str().filter(|id| id.starts_with("project-ocr")).map(str::to_string))
+                .collect()
+        };
+        assert_eq!(ids(Some(project_id.0)), vec!["project-ocr-b"]);
+        // 不传项目时不过滤
+        assert_eq!(ids(None).len(), 2);
+        assert!(crate::api::set_service_enabled(project_id.0, "project-ocr-a".to_string(), true));
+        assert_eq!(ids(Some(project_id.0)).len(), 2);
+        assert!(!crate::api::set_service_enabled(u32::MAX, "project-ocr-a".to_string(), false));
+    }
+
+    #[test]
+    fn task_history_is_bounded_and_per_project() {
+        let manager = TaskManager::new();
+        let project = ProjectId(9001);
+        let other = ProjectId(9002);
+        let ok = manager.create_task(project, MarkerId(1), ImageId(1), TaskType::OCR, "fast-ocr".to_string()).unwrap();
+        manager.complete_task(&ok).unwrap();
+        let failed = manager.create_task(project, MarkerId(2), ImageId(1), TaskType::OCR, "fast-ocr".to_string()).unwrap();
+        manager.fail_task(&failed, "plugin crashed".to_string()).unwrap();
+        let elsewhere = manager.create_task(other, MarkerId(3), ImageId(2), TaskType::Translation, "mt".to_string()).unwrap();
+        manager.complete_task(&elsewhere).unwrap();
+
+        let history = manager.get_task_history(project, None).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].task.task_id, failed);
+        assert_eq!(history[0].task.error.as_deref(), Some("plugin crashed"));
+        assert!(history[1].duration_ms.is_some());
+        assert_eq!(manager.get_task_history(project, Some(1)).unwrap().len(), 1);
+
+        let metrics = manager.get_task_metrics(project).unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!((metrics[0].completed, metrics[0].failed), (1, 1));
+        assert!(metrics[0].average_duration_ms.is_some());
+
+        for i in 0..TASK_HISTORY_CAPACITY {
+            let task_id = manager.create_task(other, MarkerId(i as u32), ImageId(2), TaskType::OCR, "fast-ocr".to_string()).unwrap();
+            manager.complete_task(&task_id).unwrap();
+        }
+        // 旧记录被挤出缓冲区
+        assert!(manager.get_task_history(project, None).unwrap().is_empty());
+        assert_eq!(manager.get_task_history(other, None).unwrap().len(), TASK_HISTORY_CAPACITY);
+
+        manager.clear_task_history(other).unwrap();
+        assert!(manager.get_task_history(other, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_marker_by_ocr_regions_is_one_undo_step() {
+        let service = get_service();
+        let project_id = service.project_service.create_project("bunny_split_test".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        crate::storage::image::update_image_dimensions_storage(image_id, 200, 100).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        // 左半幅，裁剪图为100x100像素
+        let parent = MarkerId::from(service.marker_service.add_rectangle_marker(image_id.0, 0.0, 0.0, 50.0, 100.0, None).unwrap());
+
+        let regions = vec![
+            OcrRegion { text: "一行".to_string(), x: 0.0, y: 0.0, w: 100.0, h: 40.0 },
+            OcrRegion { text: "二行".to_string(), x: 50.0, y: 50.0, w: 80.0, h: 40.0 },
+        ];
+        let children = BunnyService::new().split_marker_by_ocr_regions(parent, regions).unwrap();
+        assert_eq!(crate::storage::image::get_image_marker_ids_storage(image_id).unwrap(), children);
+
+        let geometry = |id: MarkerId| crate::storage::marker::get_marker_storage(id).unwrap().unwrap().geometry;
+        assert_eq!(geometry(children[0]), crate::storage::marker::MarkerGeometry::Rectangle { x: 0.0, y: 0.0, width: 50.0, height: 40.0 });
+        // 超出裁剪范围的部分被截掉
+        assert_eq!(geometry(children[1]), crate::storage::marker::MarkerGeometry::Rectangle { x: 25.0, y: 50.0, width: 25.0, height: 40.0 });
+        let cache = crate::storage::bunny_cache::get_bunny_cache_storage(children[1]).unwrap().unwrap();
+        assert_eq!(cache.original_text.as_deref(), Some("二行"));
+
+        service.undo_redo_service.undo(project_id.0);
+        assert_eq!(crate::storage::image::get_image_marker_ids_storage(image_id).unwrap(), vec![parent]);
+        // 撤销后子标记的缓存留给重做，测试结束时清掉，免得被完整性检查当成孤立缓存
+        for child in children {
+            crate::storage::bunny_cache::clear_bunny_cache_storage(child).unwrap();
+        }
+    }
+
+    #[test]
+    fn accept_machine_translation_respects_later_edits() {
+        let service = get_service();
+        let bunny = BunnyService::new();
+        let project_id = service.project_service.create_project("bunny_accept_test".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        let a = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, None).unwrap());
+        let b = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 20.0, 20.0, Some("人工".to_string())).unwrap());
+        let c = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 30.0, 30.0, None).unwrap());
+        for (id, text) in [(a, "机翻A"), (b, "机翻B"), (c, "机翻C")] {
+            crate::storage::bunny_cache::update_machine_translation_storage(id, text.to_string(), "mt".to_string(), Vec::new(), None).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(2));
+        service.marker_service.update_marker_translation(c.0, "改过".to_string());
+
+        assert!(bunny.accept_machine_translation(c).is_err());
+        assert!(bunny.accept_machine_translation(MarkerId(u32::MAX - 3)).is_err());
+        let report = bunny.accept_all_for_image(image_id, true).unwrap();
+        assert_eq!(report, AcceptMachineTranslationReport { accepted: vec![a], edited: Vec::new(), locked: Vec::new() });
+        let report = bunny.accept_all_for_image(image_id, false).unwrap();
+        assert_eq!((report.accepted, report.edited), (vec![a, b], vec![c]));
+
+        let translation = |id: MarkerId| crate::storage::marker::get_marker_storage(id).unwrap().unwrap().translation;
+        assert_eq!(translation(b), "机翻B");
+        let history = crate::storage::translation_history::get_translation_history_storage(b).unwrap();
+        assert_eq!(history.last().unwrap().origin, crate::storage::TranslationOrigin::Machine);
+        // 批量接受是一次撤销
+        service.undo_redo_service.undo(project_id.0);
+        assert_eq!(translation(b), "人工");
+        assert!(bunny.accept_machine_translation(b).unwrap());
+
+        assert!(bunny.reject_machine_translation(c).unwrap());
+        assert!(!bunny.reject_machine_translation(c).unwrap());
+        assert!(bunny.get_translation_result(c).unwrap().is_none());
+    }
+
+    #[test]
+    fn detection_results_become_markers_in_one_undo_step() {
+        let service = get_service();
+        let bunny = BunnyService::new();
+        let project_id = service.project_service.create_project("bunny_detect_test".to_string()).unwrap();
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(200, 100)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(crate::storage::ImageFormat::Png, png, None).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+
+        let task_id = bunny.request_detection(image_id, "yolo".to_string()).unwrap();
+        let task = TASK_MANAGER.get_task(&task_id).unwrap().unwrap();
+        assert_eq!((task.task_type, task.image_id), (TaskType::Detection, image_id));
+        let regions = vec![
+            DetectedRegion { x: 20.0, y: 10.0, w: 40.0, h: 30.0, score: Some(0.8), label: None },
+            DetectedRegion { x: 180.0, y: 50.0, w: 40.0, h: 20.0, score: None, label: Some("bubble".to_string()) },
+            DetectedRegion { x: 250.0, y: 0.0, w: 10.0, h: 10.0, score: None, label: None },
+        ];
+        bunny.handle_detection_completed(task_id.clone(), "yolo".to_string(), regions.clone()).unwrap();
+        // 重复转交的结果被忽略
+        bunny.handle_detection_completed(task_id, "yolo".to_string(), regions.clone()).unwrap();
+
+        let created = bunny.create_markers_from_detection(image_id, regions).unwrap();
+        assert_eq!(created.len(), 2);
+        let geometry = |id: MarkerId| crate::storage::marker::get_marker_storage(id).unwrap().unwrap().geometry;
+        assert_eq!(geometry(created[0]), crate::storage::marker::MarkerGeometry::Rectangle { x: 10.0, y: 10.0, width: 20.0, height: 30.0 });
+        assert_eq!(geometry(created[1]), crate::storage::marker::MarkerGeometry::Rectangle { x: 90.0, y: 50.0, width: 10.0, height: 20.0 });
+
+        service.undo_redo_service.undo(project_id.0);
+        assert!(crate::storage::image::get_image_marker_ids_storage(image_id).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,21 @@
 // Bunny task management system
-use crate::common::{MarkerId, ImageId, events::get_timestamp_millis};
+use crate::common::{MarkerId, ImageId, ProjectId, events::get_timestamp_millis};
+use crate::common::events::schema::{BusinessEvent, ImageProgressEvent, TaskCreatedEvent};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+
+/// 同步等待任务结果时使用的一次性通道
+pub type TaskResultReceiver = Receiver<Result<String, String>>;
+type TaskResultSender = SyncSender<Result<String, String>>;
+
+/// 历史记录最多保留的任务数，超出后丢弃最早的
+pub const TASK_HISTORY_CAPACITY: usize = 500;
+
+/// 同一图片两次bunny:image_progress之间的最短间隔，任务全部结束时的最后一次不受限制
+pub const IMAGE_PROGRESS_THROTTLE_MS: u64 = 250;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -13,16 +26,40 @@ pub enum TaskStatus {
     Failed,
 }
 
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Queued => "queued",
+            TaskStatus::Processing => "processing",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskType {
     OCR,
     Translation,
+    /// 整张图片的文本框检测，不针对某个标记
+    Detection,
+}
+
+impl TaskType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskType::OCR => "ocr",
+            TaskType::Translation => "translation",
+            TaskType::Detection => "detection",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BunnyTask {
     pub task_id: String,
+    pub project_id: ProjectId,
     pub marker_id: MarkerId,
     pub image_id: ImageId,
     pub task_type: TaskType,
@@ -32,11 +69,18 @@ pub struct BunnyTask {
     pub started_at: Option<u64>,
     pub completed_at: Option<u64>,
     pub error: Option<String>,
+    /// 同一请求中此前失败的服务，按尝试顺序
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tried_services: Vec<String>,
+    /// 属于OCR→翻译流水线时为流水线ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_pipeline_id: Option<String>,
 }
 
 impl BunnyTask {
     pub fn new(
         task_id: String,
+        project_id: ProjectId,
         marker_id: MarkerId,
         image_id: ImageId,
         task_type: TaskType,
@@ -44,6 +88,7 @@ impl BunnyTask {
     ) -> Self {
         Self {
             task_id,
+            project_id,
             marker_id,
             image_id,
             task_type,
@@ -53,6 +98,8 @@ impl BunnyTask {
             started_at: None,
             completed_at: None,
             error: None,
+            tried_services: Vec::new(),
+            parent_pipeline_id: None,
         }
     }
 
@@ -71,41 +118,219 @@ impl BunnyTask {
         self.error = Some(error);
         self.completed_at = Some(get_timestamp_millis());
     }
+
+    /// 从创建到结束的耗时（毫秒），未结束时为None
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.completed_at.map(|completed_at| completed_at.saturating_sub(self.created_at))
+    }
+
+    /// 任务创建时发给前端的bunny:task_created
+    pub fn created_event(&self) -> TaskCreatedEvent {
+        TaskCreatedEvent::new(
+            self.task_id.clone(),
+            self.project_id,
+            self.marker_id,
+            self.image_id,
+            self.task_type.as_str().to_string(),
+            self.service_id.clone(),
+            self.status.as_str().to_string(),
+            self.created_at,
+            self.started_at,
+            self.completed_at,
+            self.error.clone(),
+            self.tried_services.clone(),
+            self.parent_pipeline_id.clone(),
+        )
+    }
+}
+
+/// 已结束任务的历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHistoryEntry {
+    #[serde(flatten)]
+    pub task: BunnyTask,
+    /// 从创建到结束的总耗时（含插件排队）
+    pub duration_ms: Option<u64>,
+}
+
+impl From<BunnyTask> for TaskHistoryEntry {
+    fn from(task: BunnyTask) -> Self {
+        let duration_ms = task.duration_ms();
+        Self { task, duration_ms }
+    }
+}
+
+/// 按服务汇总的耗时统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceTaskMetrics {
+    pub service_id: String,
+    pub task_type: TaskType,
+    pub completed: usize,
+    pub failed: usize,
+    pub average_duration_ms: Option<u64>,
+    pub max_duration_ms: Option<u64>,
+}
+
+/// 流水线的阶段，先OCR再翻译
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineStage {
+    Ocr,
+    Translation,
+}
+
+impl PipelineStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PipelineStage::Ocr => "ocr",
+            PipelineStage::Translation => "translation",
+        }
+    }
+}
+
+/// 单个标记的OCR→翻译流水线：OCR完成后用识别出的原文自动发起翻译
+/// stage为当前所在的阶段，失败时停在失败的阶段；status不会是Queued
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BunnyPipeline {
+    pub pipeline_id: String,
+    pub project_id: ProjectId,
+    pub marker_id: MarkerId,
+    pub image_id: ImageId,
+    pub ocr_service: String,
+    pub translation_service: String,
+    pub stage: PipelineStage,
+    pub status: TaskStatus,
+    /// 各阶段当前的任务，改用后备服务时更新为重发的任务
+    pub ocr_task_id: String,
+    pub translation_task_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+/// 一张图片上当前任务的数量汇总，供缩略图显示进度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageTaskProgress {
+    pub queued: usize,
+    pub running: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+impl ImageTaskProgress {
+    pub fn total(&self) -> usize {
+        self.queued + self.running + self.done + self.failed
+    }
+
+    /// 没有排队或进行中的任务
+    pub fn is_settled(&self) -> bool {
+        self.queued == 0 && self.running == 0
+    }
+
+    fn count_mut(&mut self, status: &TaskStatus) -> &mut usize {
+        match status {
+            TaskStatus::Queued => &mut self.queued,
+            TaskStatus::Processing => &mut self.running,
+            TaskStatus::Completed => &mut self.done,
+            TaskStatus::Failed => &mut self.failed,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ImageProgressEntry {
+    progress: ImageTaskProgress,
+    last_emitted_at: Option<u64>,
+    // 被节流、等待下一次tick发出
+    pending: bool,
 }
 
 pub struct TaskManager {
     tasks: Arc<RwLock<HashMap<String, BunnyTask>>>,
+    pipelines: Arc<RwLock<HashMap<String, BunnyPipeline>>>,
+    // 已结束任务的环形缓冲，最新的在末尾
+    history: Arc<Mutex<VecDeque<TaskHistoryEntry>>>,
+    // 同步调用方等待的任务 (task_id -> oneshot sender)
+    waiters: Arc<Mutex<HashMap<String, TaskResultSender>>>,
+    // 进行中翻译任务的原文，换后备服务重发时使用
+    source_texts: Arc<Mutex<HashMap<String, String>>>,
+    // 同一毫秒内为同一标记创建多个任务时（后备重发）保证ID不重复
+    next_sequence: AtomicU64,
+    // 每张图片的任务计数，随任务状态变化更新
+    image_progress: Mutex<HashMap<ImageId, ImageProgressEntry>>,
 }
 
 impl TaskManager {
     pub fn new() -> Self {
         Self {
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            pipelines: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            waiters: Arc::new(Mutex::new(HashMap::new())),
+            source_texts: Arc::new(Mutex::new(HashMap::new())),
+            next_sequence: AtomicU64::new(0),
+            image_progress: Mutex::new(HashMap::new()),
         }
     }
 
     pub fn create_task(
         &self,
+        project_id: ProjectId,
+        marker_id: MarkerId,
+        image_id: ImageId,
+        task_type: TaskType,
+        service_id: String,
+    ) -> Result<String, String> {
+        self.create_linked_task(project_id, marker_id, image_id, task_type, service_id, None)
+    }
+
+    fn create_linked_task(
+        &self,
+        project_id: ProjectId,
         marker_id: MarkerId,
         image_id: ImageId,
         task_type: TaskType,
         service_id: String,
+        parent_pipeline_id: Option<String>,
     ) -> Result<String, String> {
         let task_id = format!(
-            "bunny_task_{}_{}_{:?}",
+            "bunny_task_{}_{}_{:?}_{}",
             get_timestamp_millis(),
             marker_id,
-            task_type
+            task_type,
+            self.next_sequence.fetch_add(1, Ordering::Relaxed)
         );
 
-        let task = BunnyTask::new(task_id.clone(), marker_id, image_id, task_type, service_id);
+        let mut task = BunnyTask::new(task_id.clone(), project_id, marker_id, image_id, task_type, service_id);
+        task.parent_pipeline_id = parent_pipeline_id;
 
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
         tasks.insert(task_id.clone(), task);
+        drop(tasks);
+        self.track_transition(image_id, None, Some(&TaskStatus::Queued));
 
         Ok(task_id)
     }
 
+    /// 为失败的任务创建改用service_id的新任务，记下已经失败的服务
+    pub fn create_fallback_task(&self, failed: &BunnyTask, service_id: String) -> Result<String, String> {
+        let task_id = self.create_linked_task(
+            failed.project_id, failed.marker_id, failed.image_id, failed.task_type.clone(), service_id, failed.parent_pipeline_id.clone(),
+        )?;
+        if let Some(pipeline_id) = &failed.parent_pipeline_id {
+            self.update_pipeline(pipeline_id, |pipeline| match pipeline.stage {
+                PipelineStage::Ocr => pipeline.ocr_task_id = task_id.clone(),
+                PipelineStage::Translation => pipeline.translation_task_id = Some(task_id.clone()),
+            })?;
+        }
+        let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(task) = tasks.get_mut(&task_id) {
+            task.tried_services = failed.tried_services.clone();
+            task.tried_services.push(failed.service_id.clone());
+        }
+        Ok(task_id)
+    }
+
     pub fn get_task(&self, task_id: &str) -> Result<Option<BunnyTask>, String> {
         let tasks = self.tasks.read().map_err(|e| format!("Lock error: {}", e))?;
         Ok(tasks.get(task_id).cloned())
@@ -114,7 +339,11 @@ impl TaskManager {
     pub fn start_task(&self, task_id: &str) -> Result<(), String> {
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
         if let Some(task) = tasks.get_mut(task_id) {
+            let from = task.status.clone();
             task.start();
+            let image_id = task.image_id;
+            drop(tasks);
+            self.track_transition(image_id, Some(&from), Some(&TaskStatus::Processing));
             Ok(())
         } else {
             Err(format!("Task not found: {}", task_id))
@@ -124,7 +353,13 @@ impl TaskManager {
     pub fn complete_task(&self, task_id: &str) -> Result<(), String> {
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
         if let Some(task) = tasks.get_mut(task_id) {
+            let from = task.status.clone();
             task.complete();
+            self.record_history(task.clone());
+            let image_id = task.image_id;
+            drop(tasks);
+            self.take_source_text(task_id);
+            self.track_transition(image_id, Some(&from), Some(&TaskStatus::Completed));
             Ok(())
         } else {
             Err(format!("Task not found: {}", task_id))
@@ -134,13 +369,84 @@ impl TaskManager {
     pub fn fail_task(&self, task_id: &str, error: String) -> Result<(), String> {
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
         if let Some(task) = tasks.get_mut(task_id) {
+            let from = task.status.clone();
             task.fail(error);
+            self.record_history(task.clone());
+            let image_id = task.image_id;
+            drop(tasks);
+            self.track_transition(image_id, Some(&from), Some(&TaskStatus::Failed));
             Ok(())
         } else {
             Err(format!("Task not found: {}", task_id))
         }
     }
 
+    fn record_history(&self, task: BunnyTask) {
+        if let Ok(mut history) = self.history.lock() {
+            if history.len() >= TASK_HISTORY_CAPACITY {
+                history.pop_front();
+            }
+            history.push_back(task.into());
+        }
+    }
+
+    /// 项目最近结束的任务，最新的在前；limit为None时返回全部保留的记录
+    pub fn get_task_history(&self, project_id: ProjectId, limit: Option<usize>) -> Result<Vec<TaskHistoryEntry>, String> {
+        let history = self.history.lock().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(history.iter()
+            .rev()
+            .filter(|entry| entry.task.project_id == project_id)
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    /// 按服务和任务类型汇总项目历史任务的成功数、失败数和耗时
+    pub fn get_task_metrics(&self, project_id: ProjectId) -> Result<Vec<ServiceTaskMetrics>, String> {
+        let history = self.history.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut metrics: Vec<ServiceTaskMetrics> = Vec::new();
+        let mut durations: Vec<Vec<u64>> = Vec::new();
+        for entry in history.iter().filter(|entry| entry.task.project_id == project_id) {
+            let task = &entry.task;
+            let index = match metrics.iter().position(|m| m.service_id == task.service_id && m.task_type == task.task_type) {
+                Some(index) => index,
+                None => {
+                    metrics.push(ServiceTaskMetrics {
+                        service_id: task.service_id.clone(),
+                        task_type: task.task_type.clone(),
+                        completed: 0,
+                        failed: 0,
+                        average_duration_ms: None,
+                        max_duration_ms: None,
+                    });
+                    durations.push(Vec::new());
+                    metrics.len() - 1
+                }
+            };
+            match task.status {
+                TaskStatus::Completed => {
+                    metrics[index].completed += 1;
+                    // 只统计成功任务的耗时，失败（如超时）的会拉偏平均值
+                    durations[index].extend(entry.duration_ms);
+                }
+                _ => metrics[index].failed += 1,
+            }
+        }
+        for (metric, durations) in metrics.iter_mut().zip(durations) {
+            if !durations.is_empty() {
+                metric.average_duration_ms = Some(durations.iter().sum::<u64>() / durations.len() as u64);
+                metric.max_duration_ms = durations.iter().max().copied();
+            }
+        }
+        Ok(metrics)
+    }
+
+    pub fn clear_task_history(&self, project_id: ProjectId) -> Result<(), String> {
+        let mut history = self.history.lock().map_err(|e| format!("Lock error: {}", e))?;
+        history.retain(|entry| entry.task.project_id != project_id);
+        Ok(())
+    }
+
     pub fn get_all_tasks(&self) -> Result<Vec<BunnyTask>, String> {
         let tasks = self.tasks.read().map_err(|e| format!("Lock error: {}", e))?;
         Ok(tasks.values().cloned().collect())
@@ -148,13 +454,302 @@ impl TaskManager {
 
     pub fn remove_task(&self, task_id: &str) -> Result<(), String> {
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
-        tasks.remove(task_id);
+        let removed = tasks.remove(task_id);
+        drop(tasks);
+        self.take_source_text(task_id);
+        if let Some(task) = removed {
+            self.track_transition(task.image_id, Some(&task.status), None);
+        }
         Ok(())
     }
 
     pub fn clear_all_tasks(&self) -> Result<(), String> {
         let mut tasks = self.tasks.write().map_err(|e| format!("Lock error: {}", e))?;
         tasks.clear();
+        self.pipelines.write().map_err(|e| format!("Lock error: {}", e))?.clear();
+        if let Ok(mut texts) = self.source_texts.lock() {
+            texts.clear();
+        }
+        if let Ok(mut progress) = self.image_progress.lock() {
+            progress.clear();
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// 图片上当前任务的计数，没有任务时全为0
+    pub fn get_image_task_progress(&self, image_id: ImageId) -> ImageTaskProgress {
+        self.image_progress.lock()
+            .ok()
+            .and_then(|progress| progress.get(&image_id).map(|entry| entry.progress))
+            .unwrap_or_default()
+    }
+
+    // 任务状态变化（from/to为None表示新建/移除）时更新计数并按节流发出进度事件
+    fn track_transition(&self, image_id: ImageId, from: Option<&TaskStatus>, to: Option<&TaskStatus>) {
+        if from.is_some() && from == to {
+            return;
+        }
+        if let Some(progress) = self.update_image_progress(image_id, from, to, get_timestamp_millis()) {
+            emit_image_progress(image_id, progress);
+        }
+    }
+
+    // 返回需要立即发出的进度，被节流时记为pending
+    fn update_image_progress(&self, image_id: ImageId, from: Option<&TaskStatus>, to: Option<&TaskStatus>, now: u64) -> Option<ImageTaskProgress> {
+        let mut images = self.image_progress.lock().ok()?;
+        let entry = images.entry(image_id).or_default();
+        // 上一轮任务全部结束后又有新任务时重新计数，进度从0开始
+        if from.is_none() && entry.progress.is_settled() {
+            entry.progress = ImageTaskProgress::default();
+        }
+        if let Some(status) = from {
+            let count = entry.progress.count_mut(status);
+            *count = count.saturating_sub(1);
+        }
+        if let Some(status) = to {
+            *entry.progress.count_mut(status) += 1;
+        }
+
+        // 任务被清理后不再发事件
+        if entry.progress.total() == 0 {
+            images.remove(&image_id);
+            return None;
+        }
+        // 移除任务只更新计数
+        to?;
+        let due = entry.last_emitted_at.is_none_or(|last| now.saturating_sub(last) >= IMAGE_PROGRESS_THROTTLE_MS);
+        if due || entry.progress.is_settled() {
+            entry.last_emitted_at = Some(now);
+            entry.pending = false;
+            Some(entry.progress)
+        } else {
+            entry.pending = true;
+            None
+        }
+    }
+
+    /// 发出被节流的进度事件，由宿主定时调用（WASM下为事件轮询，桌面端为后台线程）
+    pub fn tick(&self) {
+        for (image_id, progress) in self.take_due_progress(get_timestamp_millis()) {
+            emit_image_progress(image_id, progress);
+        }
+    }
+
+    fn take_due_progress(&self, now: u64) -> Vec<(ImageId, ImageTaskProgress)> {
+        let Ok(mut images) = self.image_progress.lock() else {
+            return Vec::new();
+        };
+        images.iter_mut()
+            .filter(|(_, entry)| entry.pending
+                && entry.last_emitted_at.is_none_or(|last| now.saturating_sub(last) >= IMAGE_PROGRESS_THROTTLE_MS))
+            .map(|(image_id, entry)| {
+                entry.pending = false;
+                entry.last_emitted_at = Some(now);
+                (*image_id, entry.progress)
+            })
+            .collect()
+    }
+
+    /// 创建流水线和它的OCR任务，返回(流水线ID, OCR任务ID)
+    pub fn create_pipeline(
+        &self,
+        project_id: ProjectId,
+        marker_id: MarkerId,
+        image_id: ImageId,
+        ocr_service: String,
+        translation_service: String,
+    ) -> Result<(String, String), String> {
+        let pipeline_id = format!(
+            "bunny_pipeline_{}_{}_{}",
+            get_timestamp_millis(),
+            marker_id,
+            self.next_sequence.fetch_add(1, Ordering::Relaxed)
+        );
+        let ocr_task_id = self.create_linked_task(
+            project_id, marker_id, image_id, TaskType::OCR, ocr_service.clone(), Some(pipeline_id.clone()),
+        )?;
+        let pipeline = BunnyPipeline {
+            pipeline_id: pipeline_id.clone(),
+            project_id,
+            marker_id,
+            image_id,
+            ocr_service,
+            translation_service,
+            stage: PipelineStage::Ocr,
+            status: TaskStatus::Processing,
+            ocr_task_id: ocr_task_id.clone(),
+            translation_task_id: None,
+            error: None,
+            created_at: get_timestamp_millis(),
+            completed_at: None,
+        };
+        self.pipelines.write().map_err(|e| format!("Lock error: {}", e))?.insert(pipeline_id.clone(), pipeline);
+        Ok((pipeline_id, ocr_task_id))
+    }
+
+    /// OCR阶段结束后进入翻译阶段，创建并返回翻译任务ID
+    /// 流水线已经进入翻译阶段或已结束时（重复的OCR结果）返回None
+    pub fn advance_pipeline(&self, pipeline_id: &str) -> Result<Option<String>, String> {
+        let pipeline = self.get_pipeline(pipeline_id)?.ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+        if pipeline.stage != PipelineStage::Ocr || pipeline.status != TaskStatus::Processing {
+            return Ok(None);
+        }
+        let task_id = self.create_linked_task(
+            pipeline.project_id, pipeline.marker_id, pipeline.image_id, TaskType::Translation,
+            pipeline.translation_service.clone(), Some(pipeline_id.to_string()),
+        )?;
+        self.update_pipeline(pipeline_id, |pipeline| {
+            pipeline.stage = PipelineStage::Translation;
+            pipeline.translation_task_id = Some(task_id.clone());
+        })?;
+        Ok(Some(task_id))
+    }
+
+    /// 流水线完成；已经结束的流水线（重复的结果）返回None
+    pub fn complete_pipeline(&self, pipeline_id: &str) -> Result<Option<BunnyPipeline>, String> {
+        self.finish_pipeline(pipeline_id, TaskStatus::Completed, None)
+    }
+
+    /// 流水线在当前阶段失败，后面的阶段不再执行
+    pub fn fail_pipeline(&self, pipeline_id: &str, error: String) -> Result<Option<BunnyPipeline>, String> {
+        self.finish_pipeline(pipeline_id, TaskStatus::Failed, Some(error))
+    }
+
+    fn finish_pipeline(&self, pipeline_id: &str, status: TaskStatus, error: Option<String>) -> Result<Option<BunnyPipeline>, String> {
+        let mut pipelines = self.pipelines.write().map_err(|e| format!("Lock error: {}", e))?;
+        let Some(pipeline) = pipelines.get_mut(pipeline_id).filter(|pipeline| pipeline.status == TaskStatus::Processing) else {
+            return Ok(None);
+        };
+        pipeline.status = status;
+        pipeline.error = error;
+        pipeline.completed_at = Some(get_timestamp_millis());
+        Ok(Some(pipeline.clone()))
+    }
+
+    pub fn get_pipeline(&self, pipeline_id: &str) -> Result<Option<BunnyPipeline>, String> {
+        let pipelines = self.pipelines.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(pipelines.get(pipeline_id).cloned())
+    }
+
+    /// 项目的流水线，最早创建的在前
+    pub fn get_project_pipelines(&self, project_id: ProjectId) -> Result<Vec<BunnyPipeline>, String> {
+        let pipelines = self.pipelines.read().map_err(|e| format!("Lock error: {}", e))?;
+        let mut result: Vec<BunnyPipeline> = pipelines.values()
+            .filter(|pipeline| pipeline.project_id == project_id)
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.pipeline_id.cmp(&b.pipeline_id)));
+        Ok(result)
+    }
+
+    fn update_pipeline(&self, pipeline_id: &str, update: impl FnOnce(&mut BunnyPipeline)) -> Result<Option<BunnyPipeline>, String> {
+        let mut pipelines = self.pipelines.write().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(pipelines.get_mut(pipeline_id).map(|pipeline| {
+            update(pipeline);
+            pipeline.clone()
+        }))
+    }
+
+    /// 记下翻译任务的原文，任务完成或被移除时丢弃
+    pub fn remember_source_text(&self, task_id: &str, text: String) {
+        if let Ok(mut texts) = self.source_texts.lock() {
+            texts.insert(task_id.to_string(), text);
+        }
+    }
+
+    pub fn take_source_text(&self, task_id: &str) -> Option<String> {
+        self.source_texts.lock().ok().and_then(|mut texts| texts.remove(task_id))
+    }
+
+    /// 注册一个等待者，任务完成或失败时通过返回的通道收到结果
+    pub fn register_waiter(&self, task_id: &str) -> Result<TaskResultReceiver, String> {
+        let (sender, receiver) = sync_channel(1);
+        let mut waiters = self.waiters.lock().map_err(|e| format!("Lock error: {}", e))?;
+        waiters.insert(task_id.to_string(), sender);
+        Ok(receiver)
+    }
+
+    /// 通知等待者任务结果（没有等待者时忽略）
+    pub fn resolve_waiter(&self, task_id: &str, result: Result<String, String>) {
+        let sender = match self.waiters.lock() {
+            Ok(mut waiters) => waiters.remove(task_id),
+            Err(_) => None,
+        };
+        if let Some(sender) = sender {
+            let _ = sender.try_send(result);
+        }
+    }
+
+    /// 任务改用后备服务重发时，等待者改为等待新任务
+    pub fn transfer_waiter(&self, from_task_id: &str, to_task_id: &str) {
+        if let Ok(mut waiters) = self.waiters.lock()
+            && let Some(sender) = waiters.remove(from_task_id)
+        {
+            waiters.insert(to_task_id.to_string(), sender);
+        }
+    }
+
+    pub fn remove_waiter(&self, task_id: &str) {
+        if let Ok(mut waiters) = self.waiters.lock() {
+            waiters.remove(task_id);
+        }
+    }
+}
+
+fn emit_image_progress(image_id: ImageId, progress: ImageTaskProgress) {
+    let _ = ImageProgressEvent::new(image_id, progress.done, progress.total(), progress.failed).emit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_progress_is_throttled_until_tick_and_settles_immediately() {
+        let manager = TaskManager::new();
+        let image = ImageId(7);
+        let queued = Some(&TaskStatus::Queued);
+
+        assert!(manager.update_image_progress(image, None, queued, 1_000).is_some());
+        // 250ms内的变化被节流
+        assert!(manager.update_image_progress(image, None, queued, 1_100).is_none());
+        assert!(manager.update_image_progress(image, queued, Some(&TaskStatus::Completed), 1_200).is_none());
+        assert!(manager.take_due_progress(1_200).is_empty());
+
+        let due = manager.take_due_progress(1_260);
+        assert_eq!(due, vec![(image, ImageTaskProgress { queued: 1, running: 0, done: 1, failed: 0 })]);
+        assert!(manager.take_due_progress(2_000).is_empty());
+
+        // 最后一个任务结束时不等节流
+        let last = manager.update_image_progress(image, queued, Some(&TaskStatus::Failed), 1_300).unwrap();
+        assert_eq!((last.done, last.failed, last.total()), (1, 1, 2));
+        assert!(manager.take_due_progress(5_000).is_empty());
+
+        // 新一轮任务重新计数
+        let next = manager.update_image_progress(image, None, queued, 5_000).unwrap();
+        assert_eq!(next.total(), 1);
+    }
+
+    #[test]
+    fn image_progress_follows_task_lifecycle_and_pruning() {
+        let manager = TaskManager::new();
+        let image = ImageId(3);
+        let first = manager.create_task(ProjectId(1), MarkerId(1), image, TaskType::OCR, "ocr".to_string()).unwrap();
+        let second = manager.create_task(ProjectId(1), MarkerId(2), image, TaskType::OCR, "ocr".to_string()).unwrap();
+        manager.start_task(&first).unwrap();
+        assert_eq!(manager.get_image_task_progress(image), ImageTaskProgress { queued: 1, running: 1, done: 0, failed: 0 });
+
+        manager.complete_task(&first).unwrap();
+        manager.fail_task(&second, "boom".to_string()).unwrap();
+        assert_eq!(manager.get_image_task_progress(image), ImageTaskProgress { queued: 0, running: 0, done: 1, failed: 1 });
+
+        manager.remove_task(&first).unwrap();
+        assert_eq!(manager.get_image_task_progress(image).total(), 1);
+        manager.remove_task(&second).unwrap();
+        assert_eq!(manager.get_image_task_progress(image), ImageTaskProgress::default());
+
+        manager.create_task(ProjectId(1), MarkerId(3), image, TaskType::OCR, "ocr".to_string()).unwrap();
+        manager.clear_all_tasks().unwrap();
+        assert_eq!(manager.get_image_task_progress(image), ImageTaskProgress::default());
+    }
+}
@@ -1,5 +1,5 @@
 // Re-export bunny types from plugin-sdk for use in core
-pub use bubblefish_plugin_sdk::{OCRServiceInfo, TranslationServiceInfo};
+pub use bubblefish_plugin_sdk::{DetectionServiceInfo, OCRServiceInfo, TranslationServiceInfo};
 
 use std::collections::HashMap;
 use std::sync::RwLock;
@@ -15,6 +15,7 @@ lazy_static! {
 pub struct BunnyServiceRegistry {
     ocr_services: HashMap<String, OCRServiceInfo>,
     translation_services: HashMap<String, TranslationServiceInfo>,
+    detection_services: HashMap<String, DetectionServiceInfo>,
     service_to_plugin: HashMap<String, String>,
 }
 
@@ -23,6 +24,7 @@ impl BunnyServiceRegistry {
         Self {
             ocr_services: HashMap::new(),
             translation_services: HashMap::new(),
+            detection_services: HashMap::new(),
             service_to_plugin: HashMap::new(),
         }
     }
@@ -31,6 +33,7 @@ impl BunnyServiceRegistry {
         let payload = serde_json::json!({
             "ocr": self.get_ocr_services(),
             "translation": self.get_translation_services(),
+            "detection": self.get_detection_services(),
         });
 
         let _ = EVENT_SYSTEM.emit_business_event(
@@ -53,10 +56,18 @@ impl BunnyServiceRegistry {
         Ok(())
     }
 
+    pub fn register_detection_service(&mut self, plugin_id: String, service_info: DetectionServiceInfo) -> Result<(), String> {
+        self.service_to_plugin.insert(service_info.id.clone(), plugin_id);
+        self.detection_services.insert(service_info.id.clone(), service_info);
+        self.emit_service_update_event();
+        Ok(())
+    }
+
     pub fn unregister_service(&mut self, service_id: &str) -> Result<(), String> {
         self.service_to_plugin.remove(service_id);
         self.ocr_services.remove(service_id);
         self.translation_services.remove(service_id);
+        self.detection_services.remove(service_id);
         self.emit_service_update_event();
         Ok(())
     }
@@ -72,6 +83,7 @@ impl BunnyServiceRegistry {
             self.service_to_plugin.remove(&service_id);
             self.ocr_services.remove(&service_id);
             self.translation_services.remove(&service_id);
+            self.detection_services.remove(&service_id);
         }
 
         self.emit_service_update_event();
@@ -101,6 +113,18 @@ impl BunnyServiceRegistry {
         }).collect()
     }
 
+    pub fn get_detection_services(&self) -> Vec<serde_json::Value> {
+        self.detection_services.iter().map(|(service_id, service_info)| {
+            let mut service_json = serde_json::to_value(service_info).unwrap_or(serde_json::json!({}));
+            if let Some(obj) = service_json.as_object_mut()
+                && let Some(plugin_id) = self.service_to_plugin.get(service_id)
+            {
+                obj.insert("plugin_id".to_string(), serde_json::json!(plugin_id));
+            }
+            service_json
+        }).collect()
+    }
+
     pub fn get_plugin_for_service(&self, service_id: &str) -> Option<String> {
         self.service_to_plugin.get(service_id).cloned()
     }
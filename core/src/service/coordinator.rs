@@ -48,6 +48,7 @@ impl ServiceCoordinator {
         event_bus.subscribe(marker_service.clone());
         event_bus.subscribe(undo_redo_service.clone());
         event_bus.subscribe(io_event_handler);
+        event_bus.subscribe(stats_service.clone());
         
         Self {
             event_bus,
@@ -11,6 +11,8 @@ pub enum DomainEvent {
     ProjectDeleted(ProjectId),
     ProjectNameUpdated(ProjectId, String),
     ProjectLanguagesUpdated(ProjectId, Language, Language),
+    // 阅读方向变化: (项目, 新方向)
+    ReadingDirectionUpdated(ProjectId, crate::common::ReadingDirection),
     
     // 临时项目相关事件
     OpeningProjectCreated(ProjectId, String),
@@ -23,6 +25,7 @@ pub enum DomainEvent {
     ImageDeleting(ImageId),  // 即将删除图片（预处理）
     ImageDeleted(ImageId),
     ImageUpdated(ImageId),
+    ImageReviewChanged(ProjectId, ImageId),
     
     // 标记相关事件
     MarkerAddedToImage(ImageId, MarkerId),
@@ -36,8 +39,14 @@ pub enum DomainEvent {
         old_geometry: (f64, f64, f64, f64),  // (x, y, width, height)
         new_geometry: (f64, f64, f64, f64)   // (x, y, width, height)
     },
+    PolygonGeometryUpdated { id: MarkerId, old_points: Vec<(f64, f64)>, new_points: Vec<(f64, f64)> },
     MarkerTranslationUpdated { id: MarkerId, old_trans: String, new_trans: String },
+    // 批量修改翻译（查找替换），作为一次撤销操作记录: (id, 旧翻译, 新翻译)
+    MarkerTranslationsBatchUpdated { project_id: ProjectId, changes: Vec<(MarkerId, String, String)> },
+    // 从嵌字数据导入翻译（可能同时新建标记），作为一次撤销操作记录
+    MarkersImported { project_id: ProjectId, changes: Vec<(MarkerId, String, String)>, created: Vec<crate::storage::marker::Marker> },
     MarkerStyleUpdated { id: MarkerId, old_style: crate::storage::marker::MarkerStyle, new_style: crate::storage::marker::MarkerStyle },
+    MarkerNoteUpdated { id: MarkerId, old_note: Option<String>, new_note: Option<String> },
     MarkerFullUpdated { 
         id: MarkerId, 
         old_position: (f64, f64), 
@@ -53,6 +62,18 @@ pub enum DomainEvent {
         old_index: u32,
         new_index: u32,
     },
+    // 标记序号批量改变（压缩为连续序号、按阅读顺序重新编号），每张图片: (图片, [(id, 旧序号, 新序号)])，整体作为一次撤销操作记录
+    MarkerIndicesCompacted { project_id: ProjectId, changes: Vec<(ImageId, crate::storage::marker::MarkerIndexChanges)> },
+    // 标记移到了同项目的另一张图片，两张图片上其他标记的序号: [(id, 序号)]，移动前/后
+    MarkerMovedToImage {
+        project_id: ProjectId,
+        old_marker: crate::storage::marker::Marker,
+        new_marker: crate::storage::marker::Marker,
+        old_indices: Vec<(MarkerId, u32)>,
+        new_indices: Vec<(MarkerId, u32)>,
+    },
+    // 一致性检查修复了项目数据，整体作为一次撤销操作记录
+    ProjectIntegrityRepaired { project_id: ProjectId, repair: crate::service::project::integrity::IntegrityRepair },
     
     // 文件解析事件
     ParseLabelplusRequested(ProjectId, String),  // 请求解析Labelplus文件
@@ -0,0 +1,220 @@
+// Image byte cache - 会话内缓存从磁盘读取的原图字节，来回切换页面时不必重复读文件
+// 缓存键: ImageId + 内容标识（校验和，文件图片再加大小和修改时间），超过容量时淘汰最久未访问的条目
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreResult, ImageId};
+use crate::common::events::schema::{BusinessEvent, ImageEvictedEvent};
+use crate::storage::image::Image;
+use crate::storage::image_data::ImageData;
+
+/// 默认容量（MB）；WASM端图片数据通常已在内存中，默认只留很小的缓存
+#[cfg(not(feature = "wasm"))]
+pub const DEFAULT_IMAGE_CACHE_LIMIT_MB: u64 = 256;
+#[cfg(feature = "wasm")]
+pub const DEFAULT_IMAGE_CACHE_LIMIT_MB: u64 = 16;
+
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageByteCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+}
+
+struct CacheEntry {
+    content_key: String,
+    data: Arc<Vec<u8>>,
+    last_access: u64,
+}
+
+struct ImageByteCache {
+    max_bytes: u64,
+    entries: HashMap<ImageId, CacheEntry>,
+    total_bytes: u64,
+    tick: u64,
+}
+
+impl ImageByteCache {
+    fn remove(&mut self, image_id: ImageId) -> bool {
+        match self.entries.remove(&image_id) {
+            Some(entry) => {
+                self.total_bytes = self.total_bytes.saturating_sub(entry.data.len() as u64);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 返回被淘汰的图片，由调用方在释放锁之后发出image:evicted
+    fn evict_to_fit(&mut self) -> Vec<ImageId> {
+        let mut evicted = Vec::new();
+        while self.total_bytes > self.max_bytes {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(id, _)| *id);
+            match oldest {
+                Some(id) => {
+                    self.remove(id);
+                    evicted.push(id);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+}
+
+fn emit_evicted(image_ids: &[ImageId], reason: &str) {
+    for image_id in image_ids {
+        let _ = ImageEvictedEvent::new(*image_id, reason.to_string()).emit();
+    }
+}
+
+static BYTE_CACHE: Lazy<RwLock<ImageByteCache>> = Lazy::new(|| RwLock::new(ImageByteCache {
+    max_bytes: DEFAULT_IMAGE_CACHE_LIMIT_MB * 1024 * 1024,
+    entries: HashMap::new(),
+    total_bytes: 0,
+    tick: 0,
+}));
+
+// 内容标识：图片被替换或文件在磁盘上被修改后标识会变化，旧条目自然失效
+pub(crate) fn content_key(image: &Image) -> Option<String> {
+    let mut key = image.metadata.checksum.clone().unwrap_or_default();
+    if let ImageData::FilePath(path) = &image.data {
+        let metadata = std::fs::metadata(path).ok()?;
+        let mtime = metadata.modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()?
+            .as_nanos();
+        key.push_str(&format!(":{}:{}", metadata.len(), mtime));
+    }
+    if key.is_empty() { None } else { Some(key) }
+}
+
+/// 读取图片原始字节，文件图片优先从缓存读取；内存中的Binary数据直接共享，不占用缓存
+pub fn read_image_bytes(image: &Image) -> CoreResult<Arc<Vec<u8>>> {
+    if let ImageData::Binary { data, .. } = &image.data {
+        return Ok(data.clone());
+    }
+
+    let image_id = image.metadata.id;
+    let key = content_key(image);
+    if let Some(key) = &key {
+        let mut cache = BYTE_CACHE.write()?;
+        cache.tick += 1;
+        let tick = cache.tick;
+        if let Some(entry) = cache.entries.get_mut(&image_id)
+            && &entry.content_key == key {
+            entry.last_access = tick;
+            CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+            return Ok(entry.data.clone());
+        }
+        // 文件在磁盘上变了，旧数据作废
+        let stale = cache.remove(image_id);
+        drop(cache);
+        if stale {
+            emit_evicted(&[image_id], "invalidated");
+        }
+    }
+
+    CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
+    let data = Arc::new(image.data.read_data()?);
+    if let Some(key) = key {
+        let mut cache = BYTE_CACHE.write()?;
+        // 单张超过容量的图片不缓存
+        if (data.len() as u64) <= cache.max_bytes {
+            cache.tick += 1;
+            let last_access = cache.tick;
+            cache.remove(image_id);
+            cache.total_bytes += data.len() as u64;
+            cache.entries.insert(image_id, CacheEntry { content_key: key, data: data.clone(), last_access });
+            let evicted = cache.evict_to_fit();
+            drop(cache);
+            emit_evicted(&evicted, "capacity");
+        }
+    }
+    Ok(data)
+}
+
+/// 图片被替换、文件变化或删除时调用，返回是否确实移除了缓存
+/// 内存中的图片不进缓存，但插件可能缓存了它的数据，所以总是发出image:evicted
+pub fn invalidate(image_id: ImageId) -> bool {
+    let removed = BYTE_CACHE.write().map(|mut cache| cache.remove(image_id)).unwrap_or(false);
+    emit_evicted(&[image_id], "invalidated");
+    removed
+}
+
+pub fn clear() -> CoreResult<usize> {
+    let mut cache = BYTE_CACHE.write()?;
+    let evicted: Vec<ImageId> = cache.entries.keys().copied().collect();
+    cache.entries.clear();
+    cache.total_bytes = 0;
+    drop(cache);
+    emit_evicted(&evicted, "cleared");
+    Ok(evicted.len())
+}
+
+/// 设置缓存容量（MB），0表示关闭缓存
+pub fn set_limit_mb(limit_mb: u64) -> CoreResult<()> {
+    let mut cache = BYTE_CACHE.write()?;
+    cache.max_bytes = limit_mb.saturating_mul(1024 * 1024);
+    let evicted = cache.evict_to_fit();
+    drop(cache);
+    emit_evicted(&evicted, "capacity");
+    Ok(())
+}
+
+pub fn stats() -> ImageByteCacheStats {
+    let (entries, total_bytes, max_bytes) = match BYTE_CACHE.read() {
+        Ok(cache) => (cache.entries.len(), cache.total_bytes, cache.max_bytes),
+        Err(_) => (0, 0, 0),
+    };
+    ImageByteCacheStats {
+        hits: CACHE_HITS.load(Ordering::Relaxed),
+        misses: CACHE_MISSES.load(Ordering::Relaxed),
+        entries,
+        total_bytes,
+        max_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_bytes_cached_until_changed() {
+        let dir = std::env::temp_dir().join(format!("bf_byte_cache_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("page.png");
+        std::fs::write(&path, b"first version").unwrap();
+
+        let image = Image::new_from_path(ImageId(900_001), path.clone());
+        let first = read_image_bytes(&image).unwrap();
+        let second = read_image_bytes(&image).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // 内容变化后（大小不同）不会命中旧条目
+        std::fs::write(&path, b"second, longer version").unwrap();
+        let third = read_image_bytes(&image).unwrap();
+        assert_eq!(third.as_slice(), b"second, longer version");
+
+        assert!(invalidate(image.metadata.id));
+        assert!(!invalidate(image.metadata.id));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_binary_images_bypass_cache() {
+        let image = Image::new_from_binary(ImageId(900_002), crate::storage::ImageFormat::Png, vec![1, 2, 3]);
+        let data = read_image_bytes(&image).unwrap();
+        assert_eq!(data.as_slice(), &[1, 2, 3]);
+        assert!(!invalidate(image.metadata.id));
+    }
+}
@@ -0,0 +1,138 @@
+// 导入时缩小超大图片 - 长边超过上限时按比例缩小后以Binary存储，原始尺寸记录在元数据中
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult};
+use crate::storage::ImageFormat;
+
+pub const DEFAULT_MAX_IMAGE_EDGE: u32 = 8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownscaleSettings {
+    /// 默认关闭，只有用户主动开启才会改动导入的图片
+    pub enabled: bool,
+    /// 长边上限（像素）
+    pub max_edge: u32,
+    /// 缩小后重新编码JPEG的质量，其他格式统一编码为PNG
+    pub jpeg_quality: u8,
+}
+
+impl Default for DownscaleSettings {
+    fn default() -> Self {
+        Self { enabled: false, max_edge: DEFAULT_MAX_IMAGE_EDGE, jpeg_quality: 90 }
+    }
+}
+
+static DOWNSCALE_SETTINGS: Lazy<RwLock<DownscaleSettings>> = Lazy::new(|| RwLock::new(DownscaleSettings::default()));
+
+pub fn get_downscale_settings() -> CoreResult<DownscaleSettings> {
+    Ok(*DOWNSCALE_SETTINGS.read()?)
+}
+
+pub fn set_downscale_settings(settings: DownscaleSettings) -> CoreResult<()> {
+    if settings.max_edge == 0 {
+        return Err(CoreError::ValidationFailed {
+            field: "max_edge".to_string(),
+            reason: "长边上限必须大于0".to_string(),
+        });
+    }
+    *DOWNSCALE_SETTINGS.write()? = DownscaleSettings {
+        jpeg_quality: settings.jpeg_quality.clamp(1, 100),
+        ..settings
+    };
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct DownscaledImage {
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub original_width: u32,
+    pub original_height: u32,
+}
+
+/// 按长边上限计算缩小后的尺寸，保持宽高比，不需要缩小时返回None
+pub fn target_dimensions(width: u32, height: u32, max_edge: u32) -> Option<(u32, u32)> {
+    let longest = width.max(height);
+    if max_edge == 0 || longest <= max_edge {
+        return None;
+    }
+    let ratio = max_edge as f64 / longest as f64;
+    let scale = |v: u32| ((v as f64 * ratio).round() as u32).max(1);
+    Some((scale(width), scale(height)))
+}
+
+/// 图片长边超过设置的上限时缩小并重新编码；未开启或无需缩小时返回None
+pub fn downscale_if_needed(data: &[u8], format: &ImageFormat, settings: &DownscaleSettings) -> CoreResult<Option<DownscaledImage>> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    // 先读文件头里的尺寸，没超限就不必解码
+    let Some(info) = crate::storage::dimension_extractor::extract_image_info_from_bytes(data, format) else {
+        return Ok(None);
+    };
+    let Some((width, height)) = target_dimensions(info.width, info.height, settings.max_edge) else {
+        return Ok(None);
+    };
+
+    let image = image::load_from_memory(data)
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))?;
+    let resized = image.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
+
+    let mut output = Vec::new();
+    let out_format = match format {
+        ImageFormat::Jpeg => {
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, settings.jpeg_quality)
+                .encode_image(&resized.to_rgb8())
+                .map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode image: {}", e)))?;
+            ImageFormat::Jpeg
+        }
+        _ => {
+            resized.write_to(&mut std::io::Cursor::new(&mut output), image::ImageFormat::Png)
+                .map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode image: {}", e)))?;
+            ImageFormat::Png
+        }
+    };
+
+    Ok(Some(DownscaledImage {
+        format: out_format,
+        data: output,
+        width,
+        height,
+        original_width: info.width,
+        original_height: info.height,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_dimensions_keeps_aspect_ratio() {
+        assert_eq!(target_dimensions(2000, 10000, 4000), Some((800, 4000)));
+        assert_eq!(target_dimensions(3000, 1000, 1500), Some((1500, 500)));
+        assert_eq!(target_dimensions(4000, 3000, 4000), None);
+        assert_eq!(target_dimensions(50000, 1, 1000), Some((1000, 1)));
+    }
+
+    #[test]
+    fn test_downscale_png() {
+        let mut data = Vec::new();
+        image::RgbaImage::from_pixel(300, 120, image::Rgba([10, 20, 30, 128]))
+            .write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+
+        let off = DownscaleSettings { max_edge: 100, ..Default::default() };
+        assert!(downscale_if_needed(&data, &ImageFormat::Png, &off).unwrap().is_none());
+
+        let on = DownscaleSettings { enabled: true, ..off };
+        let result = downscale_if_needed(&data, &ImageFormat::Png, &on).unwrap().unwrap();
+        assert_eq!((result.width, result.height), (100, 40));
+        assert_eq!((result.original_width, result.original_height), (300, 120));
+        let decoded = image::load_from_memory(&result.data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (100, 40));
+    }
+}
@@ -0,0 +1,151 @@
+// 图片重新编码 - 供导出页面图片使用，JPEG可指定质量，PNG可指定压缩级别
+// 其他格式没有可调的参数，传入quality时报错而不是静默忽略
+use image::{DynamicImage, ImageEncoder};
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use crate::common::{CoreError, CoreResult};
+use crate::storage::ImageFormat;
+
+/// 未指定时JPEG使用的质量
+pub const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/// quality参数的有效范围：JPEG为质量1-100，PNG为压缩级别0-9（0表示不压缩）；其他格式不支持
+pub fn quality_range(format: &ImageFormat) -> Option<(u8, u8)> {
+    match format {
+        ImageFormat::Jpeg => Some((1, 100)),
+        ImageFormat::Png => Some((0, 9)),
+        _ => None,
+    }
+}
+
+fn validate_quality(format: &ImageFormat, quality: Option<u8>) -> CoreResult<()> {
+    let Some(quality) = quality else { return Ok(()) };
+    match quality_range(format) {
+        Some((min, max)) if (min..=max).contains(&quality) => Ok(()),
+        Some((min, max)) => Err(CoreError::ValidationFailed {
+            field: "quality".to_string(),
+            reason: format!("{:?}的quality必须在{}到{}之间，收到{}", format, min, max, quality),
+        }),
+        None => Err(CoreError::ValidationFailed {
+            field: "quality".to_string(),
+            reason: format!("{:?}格式不支持quality参数", format),
+        }),
+    }
+}
+
+fn encode_error(e: image::ImageError) -> CoreError {
+    CoreError::ImageProcessingError(format!("Failed to encode image: {}", e))
+}
+
+/// 把已解码的图片编码为指定格式
+pub fn encode_dynamic_image(image: &DynamicImage, format: &ImageFormat, quality: Option<u8>) -> CoreResult<Vec<u8>> {
+    validate_quality(format, quality)?;
+    let mut output = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            // JPEG没有透明通道
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, quality.unwrap_or(DEFAULT_JPEG_QUALITY))
+                .encode_image(&image.to_rgb8())
+                .map_err(encode_error)?;
+        }
+        ImageFormat::Png => {
+            let compression = match quality {
+                None => CompressionType::Default,
+                Some(0) => CompressionType::Uncompressed,
+                Some(level) => CompressionType::Level(level),
+            };
+            let rgba = image.to_rgba8();
+            PngEncoder::new_with_quality(&mut output, compression, FilterType::Adaptive)
+                .write_image(rgba.as_raw(), rgba.width(), rgba.height(), image::ExtendedColorType::Rgba8)
+                .map_err(encode_error)?;
+        }
+        ImageFormat::Gif => write_with(image, image::ImageFormat::Gif, &mut output)?,
+        ImageFormat::Webp => write_with(image, image::ImageFormat::WebP, &mut output)?,
+        ImageFormat::Bmp => write_with(image, image::ImageFormat::Bmp, &mut output)?,
+    }
+    Ok(output)
+}
+
+fn write_with(image: &DynamicImage, format: image::ImageFormat, output: &mut Vec<u8>) -> CoreResult<()> {
+    // GIF和WebP编码器只接受RGBA8
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_to(&mut std::io::Cursor::new(output), format)
+        .map_err(encode_error)
+}
+
+/// 把RGBA8像素编码为PNG（系统剪贴板中的截图只给出像素数据）
+pub fn encode_rgba_png(width: u32, height: u32, rgba: &[u8]) -> CoreResult<Vec<u8>> {
+    if rgba.len() as u64 != width as u64 * height as u64 * 4 {
+        return Err(CoreError::ValidationFailed {
+            field: "rgba".to_string(),
+            reason: format!("像素数据长度{}与尺寸{}x{}不符", rgba.len(), width, height),
+        });
+    }
+    let mut output = Vec::new();
+    PngEncoder::new(&mut output)
+        .write_image(rgba, width, height, image::ExtendedColorType::Rgba8)
+        .map_err(encode_error)?;
+    Ok(output)
+}
+
+/// 解码图片字节并重新编码为指定格式
+pub fn encode_bytes(data: &[u8], format: &ImageFormat, quality: Option<u8>) -> CoreResult<Vec<u8>> {
+    // 先校验参数，避免解码大图后才报错
+    validate_quality(format, quality)?;
+    let image = image::load_from_memory(data)
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))?;
+    encode_dynamic_image(&image, format, quality)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_png() -> Vec<u8> {
+        // 带噪点的图片，不同质量/压缩级别的结果大小才有差别
+        let image = image::RgbaImage::from_fn(64, 64, |x, y| {
+            let v = ((x * 31 + y * 17) ^ (x * y)) as u8;
+            image::Rgba([v, v.wrapping_mul(3), 255 - v, 255])
+        });
+        let mut data = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_quality_is_honored() {
+        let data = sample_png();
+        let low = encode_bytes(&data, &ImageFormat::Jpeg, Some(10)).unwrap();
+        let high = encode_bytes(&data, &ImageFormat::Jpeg, Some(100)).unwrap();
+        assert!(low.len() < high.len());
+        assert_eq!(image::guess_format(&low).unwrap(), image::ImageFormat::Jpeg);
+
+        let stored = encode_bytes(&data, &ImageFormat::Png, Some(0)).unwrap();
+        let best = encode_bytes(&data, &ImageFormat::Png, Some(9)).unwrap();
+        assert!(best.len() < stored.len());
+        let decoded = image::load_from_memory(&best).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 64));
+
+        let webp = encode_bytes(&data, &ImageFormat::Webp, None).unwrap();
+        assert_eq!(image::guess_format(&webp).unwrap(), image::ImageFormat::WebP);
+    }
+
+    #[test]
+    fn test_invalid_quality_is_rejected() {
+        let data = sample_png();
+        for (format, quality) in [(ImageFormat::Jpeg, 0), (ImageFormat::Jpeg, 101), (ImageFormat::Png, 10), (ImageFormat::Bmp, 50)] {
+            match encode_bytes(&data, &format, Some(quality)) {
+                Err(CoreError::ValidationFailed { field, .. }) => assert_eq!(field, "quality"),
+                other => panic!("expected validation error for {:?} {}, got {:?}", format, quality, other.map(|d| d.len())),
+            }
+        }
+        assert!(matches!(encode_bytes(b"not an image", &ImageFormat::Png, None), Err(CoreError::ImageProcessingError(_))));
+    }
+
+    #[test]
+    fn test_rgba_png_round_trip() {
+        let rgba: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8 * 10).collect();
+        let png = encode_rgba_png(3, 2, &rgba).unwrap();
+        assert_eq!(image::load_from_memory(&png).unwrap().to_rgba8().into_raw(), rgba);
+        assert!(encode_rgba_png(3, 3, &rgba).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// 灰度图片检测 - 漫画页大多是灰度图却以RGB存储，解码后占三倍内存
+// 打开项目时抽样检查像素，各通道相差不超过容差即视为灰度；项目开启后Binary图片重新编码为8位灰度PNG
+use image::{DynamicImage, GenericImageView};
+use crate::common::{CoreError, CoreResult, ImageId, Logger};
+use crate::common::dto::image::ColorMode;
+use crate::storage::{ImageData, ImageFormat};
+
+/// 通道之间允许的最大差值，JPEG压缩会让灰度像素带上轻微色偏
+pub const GRAYSCALE_TOLERANCE: u8 = 8;
+
+// 每条边最多抽样的像素数
+const SAMPLES_PER_EDGE: u32 = 64;
+
+/// 抽样检查图片是否实际为灰度
+pub fn is_effectively_grayscale(image: &DynamicImage, tolerance: u8) -> bool {
+    if matches!(image, DynamicImage::ImageLuma8(_) | DynamicImage::ImageLumaA8(_) | DynamicImage::ImageLuma16(_) | DynamicImage::ImageLumaA16(_)) {
+        return true;
+    }
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return false;
+    }
+    let step_x = (width / SAMPLES_PER_EDGE).max(1);
+    let step_y = (height / SAMPLES_PER_EDGE).max(1);
+    (0..height).step_by(step_y as usize).all(|y| {
+        (0..width).step_by(step_x as usize).all(|x| {
+            let [r, g, b, _] = image.get_pixel(x, y).0;
+            r.max(g).max(b) - r.min(g).min(b) <= tolerance
+        })
+    })
+}
+
+/// 编码为8位灰度PNG，有透明通道时保留
+pub fn encode_grayscale_png(image: &DynamicImage) -> CoreResult<Vec<u8>> {
+    let gray = if image.color().has_alpha() {
+        DynamicImage::ImageLumaA8(image.to_luma_alpha8())
+    } else {
+        DynamicImage::ImageLuma8(image.to_luma8())
+    };
+    let mut output = Vec::new();
+    gray.write_to(&mut std::io::Cursor::new(&mut output), image::ImageFormat::Png)
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode grayscale image: {}", e)))?;
+    Ok(output)
+}
+
+/// 按灰度显示时解码后的像素数据少占的字节数
+pub fn estimated_savings_bytes(width: u32, height: u32, from: ColorMode, to: ColorMode) -> u64 {
+    width as u64 * height as u64 * from.channels().saturating_sub(to.channels()) as u64
+}
+
+/// 检测图片是否为灰度并记录到元数据，reencode时把Binary图片替换为灰度PNG（磁盘上的原图不动）
+/// 返回估算节省的内存字节数；文件头已经是灰度或无法读取的图片返回0
+pub fn optimize_grayscale(image_id: ImageId, reencode: bool) -> CoreResult<u64> {
+    let Some(image) = crate::storage::image::get_image_storage(image_id)? else {
+        return Ok(0);
+    };
+    // 文件头已经是灰度/索引色的不必解码，CMYK不做处理
+    let header_mode = image.metadata.color_mode;
+    if matches!(header_mode, Some(ColorMode::Grayscale | ColorMode::GrayscaleAlpha | ColorMode::Indexed | ColorMode::Cmyk)) {
+        return Ok(0);
+    }
+
+    let bytes = super::byte_cache::read_image_bytes(&image)?;
+    let decoded = image::load_from_memory(&bytes)
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))?;
+    if !is_effectively_grayscale(&decoded, GRAYSCALE_TOLERANCE) {
+        return Ok(0);
+    }
+
+    let has_alpha = decoded.color().has_alpha();
+    let (from, to) = if has_alpha {
+        (ColorMode::Rgba, ColorMode::GrayscaleAlpha)
+    } else {
+        (ColorMode::Rgb, ColorMode::Grayscale)
+    };
+    let (width, height) = decoded.dimensions();
+    crate::storage::image::set_image_color_mode_storage(image_id, to)?;
+
+    if reencode && let ImageData::Binary { .. } = image.data {
+        let data = encode_grayscale_png(&decoded)?;
+        // 校验和保留原图的，重新打开bf时仍能按原图匹配
+        crate::storage::image::update_image_data_storage(image_id, ImageData::Binary { format: ImageFormat::Png, data: std::sync::Arc::new(data) })?;
+        super::byte_cache::invalidate(image_id);
+        Logger::debug(&format!("Re-encoded image {} as grayscale PNG", image_id.0));
+    }
+
+    Ok(estimated_savings_bytes(width, height, header_mode.unwrap_or(from), to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    use crate::storage::image::{add_image_from_binary_storage, get_image_storage};
+
+    fn png_bytes(image: RgbImage) -> Vec<u8> {
+        let mut output = Vec::new();
+        DynamicImage::ImageRgb8(image).write_to(&mut std::io::Cursor::new(&mut output), image::ImageFormat::Png).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_detects_grayscale_within_tolerance() {
+        let gray = RgbImage::from_fn(100, 80, |x, _| Rgb([x as u8, x as u8 + 3, x as u8]));
+        assert!(is_effectively_grayscale(&DynamicImage::ImageRgb8(gray), GRAYSCALE_TOLERANCE));
+        let mut colored = RgbImage::from_pixel(100, 80, Rgb([128, 128, 128]));
+        colored.put_pixel(50, 40, Rgb([200, 40, 40]));
+        assert!(!is_effectively_grayscale(&DynamicImage::ImageRgb8(colored), GRAYSCALE_TOLERANCE));
+    }
+
+    #[test]
+    fn test_optimize_records_mode_and_reencodes_binary() {
+        let data = png_bytes(RgbImage::from_pixel(40, 20, Rgb([90, 90, 90])));
+        let original_size = data.len() as u64;
+        let kept = add_image_from_binary_storage(ImageFormat::Png, data.clone(), Some("kept.png".to_string())).unwrap();
+        let reencoded = add_image_from_binary_storage(ImageFormat::Png, data, Some("gray.png".to_string())).unwrap();
+
+        assert_eq!(optimize_grayscale(kept, false).unwrap(), 40 * 20 * 2);
+        let image = get_image_storage(kept).unwrap().unwrap();
+        assert_eq!(image.to_dto().metadata.color_mode, Some(ColorMode::Grayscale));
+        assert_eq!(image.data.get_size(), Some(original_size));
+
+        let checksum = get_image_storage(reencoded).unwrap().unwrap().metadata.checksum.clone();
+        assert_eq!(optimize_grayscale(reencoded, true).unwrap(), 40 * 20 * 2);
+        let image = get_image_storage(reencoded).unwrap().unwrap();
+        let ImageData::Binary { data, .. } = &image.data else { panic!("expected binary data") };
+        assert_eq!(image::load_from_memory(data).unwrap().color(), image::ColorType::L8);
+        assert_eq!(image.metadata.checksum, checksum);
+        // 已经是灰度的不再处理
+        assert_eq!(optimize_grayscale(reencoded, true).unwrap(), 0);
+    }
+}
@@ -0,0 +1,85 @@
+// 插件图片数据共享 - 同一张图片被多个插件调用（MD5、OCR等）时共用一份字节，不重复读盘、复制
+// native宿主可以把租借得到的指针和长度直接交给插件；租借期间Arc保证数据不被释放
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use crate::common::{CoreError, CoreResult, ImageId};
+use crate::storage::state::APP_STATE;
+
+/// 同时存在的租借上限，防止插件忘记归还导致图片一直留在内存里
+pub const MAX_IMAGE_LEASES: usize = 64;
+
+static NEXT_LEASE_ID: AtomicU64 = AtomicU64::new(1);
+static LEASES: Lazy<Mutex<HashMap<u64, Arc<Vec<u8>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 租借的图片字节，ptr在release_image_lease之前一直有效
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageLease {
+    pub lease_id: u64,
+    pub image_id: ImageId,
+    pub ptr: usize,
+    pub len: usize,
+    pub format: String,
+}
+
+/// 图片的原始字节：文件图片走字节缓存，内存中的图片直接共享同一个Arc
+pub fn shared_image_bytes(image_id: ImageId) -> CoreResult<Arc<Vec<u8>>> {
+    let image = APP_STATE.get_image(image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image {} not found", image_id.0)))?;
+    super::byte_cache::read_image_bytes(&image)
+}
+
+/// 租借图片字节，调用方用完后必须release_image_lease
+pub fn lease_image_bytes(image_id: ImageId) -> CoreResult<ImageLease> {
+    let image = APP_STATE.get_image(image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image {} not found", image_id.0)))?;
+    let format = image.data.get_format().map(|format| format.extension()).unwrap_or("png").to_string();
+    let data = super::byte_cache::read_image_bytes(&image)?;
+
+    let mut leases = LEASES.lock()?;
+    if leases.len() >= MAX_IMAGE_LEASES {
+        return Err(CoreError::ValidationFailed {
+            field: "lease".to_string(),
+            reason: format!("同时租借的图片数据超过{}份，请先归还", MAX_IMAGE_LEASES),
+        });
+    }
+    let lease_id = NEXT_LEASE_ID.fetch_add(1, Ordering::Relaxed);
+    let lease = ImageLease { lease_id, image_id, ptr: data.as_ptr() as usize, len: data.len(), format };
+    leases.insert(lease_id, data);
+    Ok(lease)
+}
+
+/// 归还租借，返回租借是否存在
+pub fn release_image_lease(lease_id: u64) -> bool {
+    LEASES.lock().map(|mut leases| leases.remove(&lease_id).is_some()).unwrap_or(false)
+}
+
+pub fn active_lease_count() -> usize {
+    LEASES.lock().map(|leases| leases.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::image::add_image_from_binary_storage;
+    use crate::storage::image_data::ImageFormat;
+
+    #[test]
+    fn test_lease_points_at_shared_bytes_until_released() {
+        let image_id = add_image_from_binary_storage(ImageFormat::Png, vec![9u8; 1024], None).unwrap();
+        let shared = shared_image_bytes(image_id).unwrap();
+        let lease = lease_image_bytes(image_id).unwrap();
+        // 租借的是同一份数据，没有复制
+        assert_eq!(lease.ptr, shared.as_ptr() as usize);
+        assert_eq!((lease.len, lease.format.as_str()), (1024, "png"));
+
+        let bytes = unsafe { std::slice::from_raw_parts(lease.ptr as *const u8, lease.len) };
+        assert!(bytes.iter().all(|b| *b == 9));
+
+        assert!(release_image_lease(lease.lease_id));
+        assert!(!release_image_lease(lease.lease_id));
+        assert!(lease_image_bytes(ImageId(u32::MAX)).is_err());
+    }
+}
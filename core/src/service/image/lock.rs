@@ -0,0 +1,58 @@
+// 图片锁定 - 嵌字完成的页面锁定后不能再新建、修改、删除或移动其中的标记
+// 读取、海兔缓存和导出不受影响；跨图片的批量操作跳过锁定的图片，并在报告中列出
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::common::{ImageId, MarkerId};
+
+/// 修改锁定图片上的标记时返回的错误，message为面向用户的提示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename = "image_locked")]
+pub struct ImageLockedError {
+    pub image_id: ImageId,
+    pub message: String,
+}
+
+impl ImageLockedError {
+    pub fn new(image_id: ImageId) -> Self {
+        let name = crate::storage::image::get_image_storage(image_id).ok().flatten()
+            .and_then(|image| image.metadata.name.clone())
+            .unwrap_or_else(|| format!("#{}", image_id.0));
+        Self {
+            image_id,
+            message: format!("图片{}已锁定，解锁后才能修改其中的标记", name),
+        }
+    }
+
+    /// 序列化为JSON字符串，通过API的String错误通道传给前端
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| self.message.clone())
+    }
+}
+
+impl fmt::Display for ImageLockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// 图片是否已锁定，图片不存在时视为未锁定
+pub fn is_image_locked(image_id: ImageId) -> bool {
+    crate::storage::image::get_image_storage(image_id).ok().flatten()
+        .is_some_and(|image| image.metadata.locked)
+}
+
+pub fn ensure_image_unlocked(image_id: ImageId) -> Result<(), ImageLockedError> {
+    if is_image_locked(image_id) {
+        Err(ImageLockedError::new(image_id))
+    } else {
+        Ok(())
+    }
+}
+
+/// 检查标记所在的图片；标记不存在时不报错，交给后续操作按原来的方式处理
+pub fn ensure_marker_unlocked(marker_id: MarkerId) -> Result<(), ImageLockedError> {
+    match crate::storage::marker::get_marker_storage(marker_id).ok().flatten() {
+        Some(marker) => ensure_image_unlocked(marker.image_id),
+        None => Ok(()),
+    }
+}
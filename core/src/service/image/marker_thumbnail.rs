@@ -0,0 +1,260 @@
+// Marker thumbnail - 翻译侧栏每个标记旁的小预览图，按标记区域裁剪后缩放编码为JPEG
+// 缓存键: MarkerId + 长边尺寸，条目记录裁剪时的几何和图片内容标识，任一变化即失效；超过容量时淘汰最久未访问的条目
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, MarkerId};
+use crate::common::dto::image::ColorMode;
+use crate::service::worker_pool::CPU_POOL;
+use crate::storage::image::Image;
+use crate::storage::marker::{Marker, MarkerGeometry};
+use crate::storage::state::APP_STATE;
+use super::byte_cache;
+
+/// 缓存容量上限（字节），预览图通常只有几KB
+pub const MARKER_THUMBNAIL_CACHE_MAX_BYTES: u64 = 16 * 1024 * 1024;
+/// 预览图长边上限（像素）
+pub const MAX_MARKER_THUMBNAIL_EDGE: u32 = 512;
+
+const MARKER_THUMBNAIL_QUALITY: u8 = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkerThumbnail {
+    pub marker_id: MarkerId,
+    pub data: Vec<u8>,
+}
+
+struct CacheEntry {
+    geometry: MarkerGeometry,
+    content_key: String,
+    data: Vec<u8>,
+    last_access: u64,
+}
+
+struct MarkerThumbnailCache {
+    max_bytes: u64,
+    entries: HashMap<(MarkerId, u32), CacheEntry>,
+    total_bytes: u64,
+    tick: u64,
+}
+
+impl MarkerThumbnailCache {
+    fn get(&mut self, key: (MarkerId, u32), geometry: &MarkerGeometry, content_key: &str) -> Option<Vec<u8>> {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&key)
+            && &entry.geometry == geometry && entry.content_key == content_key {
+            entry.last_access = tick;
+            return Some(entry.data.clone());
+        }
+        // 标记移动过或图片内容变了，旧预览作废
+        self.remove(key);
+        None
+    }
+
+    fn insert(&mut self, key: (MarkerId, u32), geometry: MarkerGeometry, content_key: String, data: Vec<u8>) {
+        if (data.len() as u64) > self.max_bytes {
+            return;
+        }
+        self.remove(key);
+        self.tick += 1;
+        self.total_bytes += data.len() as u64;
+        self.entries.insert(key, CacheEntry { geometry, content_key, data, last_access: self.tick });
+        self.evict_to_fit();
+    }
+
+    fn remove(&mut self, key: (MarkerId, u32)) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.total_bytes = self.total_bytes.saturating_sub(entry.data.len() as u64);
+        }
+    }
+
+    fn evict_to_fit(&mut self) {
+        while self.total_bytes > self.max_bytes {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => self.remove(key),
+                None => break,
+            }
+        }
+    }
+}
+
+static CACHE: Lazy<RwLock<MarkerThumbnailCache>> = Lazy::new(|| RwLock::new(MarkerThumbnailCache {
+    max_bytes: MARKER_THUMBNAIL_CACHE_MAX_BYTES,
+    entries: HashMap::new(),
+    total_bytes: 0,
+    tick: 0,
+}));
+
+fn validate_edge(max_edge: u32) -> CoreResult<()> {
+    if max_edge == 0 || max_edge > MAX_MARKER_THUMBNAIL_EDGE {
+        return Err(CoreError::ValidationFailed {
+            field: "max_edge_px".to_string(),
+            reason: format!("预览图长边必须在1到{}像素之间", MAX_MARKER_THUMBNAIL_EDGE),
+        });
+    }
+    Ok(())
+}
+
+fn get_image(image_id: ImageId) -> CoreResult<Arc<Image>> {
+    APP_STATE.get_image(image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image {} not found", image_id.0)))
+}
+
+fn decode_image(image: &Image) -> CoreResult<DynamicImage> {
+    let data = byte_cache::read_image_bytes(image)?;
+    image::load_from_memory(&data)
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))
+}
+
+fn render(img: &DynamicImage, geometry: &MarkerGeometry, max_edge: u32, grayscale: bool) -> CoreResult<Vec<u8>> {
+    let cropped = super::service::crop_decoded_region(img, geometry)
+        .map_err(CoreError::ImageProcessingError)?;
+    super::thumbnail::encode_thumbnail(&cropped, max_edge, MARKER_THUMBNAIL_QUALITY, grayscale)
+}
+
+fn is_grayscale(image: &Image) -> bool {
+    matches!(image.metadata.color_mode, Some(ColorMode::Grayscale | ColorMode::GrayscaleAlpha))
+}
+
+// 无法确定图片内容时（SharedBuffer尚未读取）不缓存
+fn cached(marker: &Marker, max_edge: u32, content_key: Option<&str>) -> Option<Vec<u8>> {
+    let mut cache = CACHE.write().ok()?;
+    cache.get((marker.id, max_edge), &marker.geometry, content_key?)
+}
+
+fn store(marker: &Marker, max_edge: u32, content_key: Option<&str>, data: &[u8]) {
+    if let (Some(content_key), Ok(mut cache)) = (content_key, CACHE.write()) {
+        cache.insert((marker.id, max_edge), marker.geometry.clone(), content_key.to_string(), data.to_vec());
+    }
+}
+
+/// 单个标记的预览图，命中缓存时不解码图片
+pub fn get_marker_thumbnail(marker_id: MarkerId, max_edge: u32) -> CoreResult<Vec<u8>> {
+    validate_edge(max_edge)?;
+    let marker = crate::storage::marker::get_marker_storage(marker_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Marker {} not found", marker_id.0)))?;
+    let image = get_image(marker.image_id)?;
+    let content_key = byte_cache::content_key(&image);
+    if let Some(data) = cached(&marker, max_edge, content_key.as_deref()) {
+        return Ok(data);
+    }
+
+    let data = render(&decode_image(&image)?, &marker.geometry, max_edge, is_grayscale(&image))?;
+    store(&marker, max_edge, content_key.as_deref(), &data);
+    Ok(data)
+}
+
+/// 图片上所有标记的预览图（按标记顺序），整页只解码一次，未命中缓存的标记并行裁剪
+/// 裁剪失败的标记（区域为空等）不出现在结果中
+pub fn get_marker_thumbnails(image_id: ImageId, max_edge: u32) -> CoreResult<Vec<MarkerThumbnail>> {
+    validate_edge(max_edge)?;
+    let image = get_image(image_id)?;
+    let markers = crate::storage::marker::get_image_markers_storage(image_id)?;
+    let content_key = byte_cache::content_key(&image);
+
+    let mut results: Vec<Option<Vec<u8>>> = markers.iter()
+        .map(|marker| cached(marker, max_edge, content_key.as_deref()))
+        .collect();
+    let missing: Vec<usize> = (0..markers.len()).filter(|&i| results[i].is_none()).collect();
+    if !missing.is_empty() {
+        let img = decode_image(&image)?;
+        let grayscale = is_grayscale(&image);
+        let rendered = CPU_POOL.map(missing.clone(), |i| render(&img, &markers[i].geometry, max_edge, grayscale));
+        for (i, data) in missing.into_iter().zip(rendered) {
+            match data {
+                Ok(data) => {
+                    store(&markers[i], max_edge, content_key.as_deref(), &data);
+                    results[i] = Some(data);
+                }
+                Err(e) => crate::common::Logger::warn(&format!("Failed to render thumbnail for marker {}: {}", markers[i].id.0, e)),
+            }
+        }
+    }
+
+    Ok(markers.iter().zip(results)
+        .filter_map(|(marker, data)| data.map(|data| MarkerThumbnail { marker_id: marker.id, data }))
+        .collect())
+}
+
+/// 清空预览图缓存，返回移除的条目数
+pub fn clear() -> usize {
+    CACHE.write().map(|mut cache| {
+        let count = cache.entries.len();
+        cache.entries.clear();
+        cache.total_bytes = 0;
+        count
+    }).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::image::add_image_from_binary_storage;
+    use crate::storage::image_data::ImageFormat;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        image::RgbImage::from_pixel(width, height, image::Rgb([200, 30, 30]))
+            .write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    fn add_marker(image_id: ImageId, geometry: MarkerGeometry) -> MarkerId {
+        let service = &crate::service::get_service().marker_service;
+        let id = match geometry {
+            MarkerGeometry::Point { x, y } => service.add_point_marker(image_id.0, x, y, None),
+            MarkerGeometry::Rectangle { x, y, width, height } => service.add_rectangle_marker(image_id.0, x, y, width, height, None),
+            MarkerGeometry::Polygon { points } => service.add_polygon_marker(image_id.0, points, None),
+        };
+        MarkerId::from(id.unwrap())
+    }
+
+    #[test]
+    fn test_marker_thumbnail_cached_until_geometry_changes() {
+        let image_id = add_image_from_binary_storage(ImageFormat::Png, png(400, 200), None).unwrap();
+        let marker_id = add_marker(image_id, MarkerGeometry::Rectangle { x: 10.0, y: 10.0, width: 50.0, height: 50.0 });
+
+        let data = get_marker_thumbnail(marker_id, 64).unwrap();
+        let decoded = image::load_from_memory(&data).unwrap();
+        // 200x100的裁剪区域缩放到长边64
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+        assert!(CACHE.read().unwrap().entries.contains_key(&(marker_id, 64)));
+
+        let moved = MarkerGeometry::Rectangle { x: 10.0, y: 10.0, width: 10.0, height: 50.0 };
+        crate::storage::marker::update_marker_geometry_storage(marker_id, moved).unwrap();
+        // 几何变化后缓存失效，按40x100的新区域重新裁剪
+        let decoded = image::load_from_memory(&get_marker_thumbnail(marker_id, 64).unwrap()).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (25, 64));
+
+        assert!(get_marker_thumbnail(marker_id, 0).is_err());
+    }
+
+    #[test]
+    fn test_batch_fills_cache_and_evicts_lru() {
+        let image_id = add_image_from_binary_storage(ImageFormat::Png, png(300, 300), None).unwrap();
+        let point = add_marker(image_id, MarkerGeometry::Point { x: 50.0, y: 50.0 });
+        let rect = add_marker(image_id, MarkerGeometry::Rectangle { x: 0.0, y: 0.0, width: 30.0, height: 30.0 });
+
+        let batch = get_marker_thumbnails(image_id, 48).unwrap();
+        assert_eq!(batch.iter().map(|t| t.marker_id).collect::<Vec<_>>(), vec![point, rect]);
+        // 批量结果已进缓存，单个获取得到同一份数据
+        assert_eq!(get_marker_thumbnail(rect, 48).unwrap(), batch[1].data);
+
+        let mut cache = MarkerThumbnailCache { max_bytes: 10, entries: HashMap::new(), total_bytes: 0, tick: 0 };
+        let geometry = MarkerGeometry::Point { x: 1.0, y: 1.0 };
+        cache.insert((point, 48), geometry.clone(), "a".to_string(), vec![0; 6]);
+        cache.insert((rect, 48), geometry.clone(), "a".to_string(), vec![0; 6]);
+        // 超出容量时淘汰最久未访问的条目
+        assert!(cache.get((point, 48), &geometry, "a").is_none());
+        assert!(cache.get((rect, 48), &geometry, "a").is_some());
+        assert!(cache.get((rect, 48), &geometry, "b").is_none());
+        assert_eq!(cache.total_bytes, 0);
+    }
+}
@@ -1,10 +1,29 @@
 // Image service模块
 mod service;
+pub mod byte_cache;
+pub mod downscale;
+pub mod encode;
+pub mod grayscale;
+pub mod image_lease;
+pub mod lock;
+pub mod marker_thumbnail;
+pub mod paste;
+pub mod remote;
 pub mod thumbnail;
+pub mod thumbnail_cache;
+pub mod validate;
 
 pub use service::{ImageService, ImageProcessingResult};
 pub use thumbnail::{
     ThumbnailData, ProcessingConfig, DEFAULT_THUMBNAIL_SIZE,
-    request_thumbnail, request_thumbnails_batch,
+    request_thumbnail, request_thumbnails_batch, get_thumbnail_order,
+    prefetch_around, DEFAULT_PREFETCH_RADIUS,
     get_thumbnail, has_thumbnail, clear_all_thumbnails
-};
\ No newline at end of file
+};
+pub use thumbnail_cache::{ThumbnailCacheStats, set_thumbnail_cache_dir};
+pub use byte_cache::{ImageByteCacheStats, DEFAULT_IMAGE_CACHE_LIMIT_MB};
+pub use image_lease::ImageLease;
+pub use marker_thumbnail::MarkerThumbnail;
+pub use lock::{ImageLockedError, is_image_locked, ensure_image_unlocked, ensure_marker_unlocked};
+pub use downscale::{DownscaleSettings, get_downscale_settings, set_downscale_settings};
+pub use validate::{ImportError, ImageCandidate, validate_image_candidate, validate_image_content, MAX_IMAGE_FILE_SIZE};
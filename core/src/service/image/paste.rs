@@ -0,0 +1,69 @@
+// 剪贴板粘贴的图片 - 只接受PNG/JPEG，自动生成不与项目内已有图片重名的文件名
+use std::path::Path;
+use crate::common::dto::image::ImageFormat;
+use super::validate::{validate_image_bytes, ImportError};
+
+/// 没有建议名称时使用的前缀，生成 pasted-001.png 这样的名称
+pub const PASTED_NAME_PREFIX: &str = "pasted";
+
+/// 校验粘贴的数据，按文件头识别格式，截图工具只会给出PNG或JPEG
+pub fn validate_pasted_image(data: &[u8]) -> Result<ImageFormat, ImportError> {
+    let format = match data {
+        [0x89, b'P', b'N', b'G', ..] => ImageFormat::Png,
+        [0xFF, 0xD8, 0xFF, ..] => ImageFormat::Jpeg,
+        _ => return Err(ImportError::NotAnImage {
+            name: "clipboard".to_string(),
+            message: "剪贴板内容不是PNG或JPEG图片".to_string(),
+        }),
+    };
+    validate_image_bytes(&format!("clipboard.{}", format.extension()), data)
+}
+
+/// 生成粘贴图片的文件名：扩展名以实际格式为准
+/// 建议名称不重名时直接使用，否则在后面加三位序号
+pub fn pasted_image_name(existing: &[String], suggested: Option<&str>, format: ImageFormat) -> String {
+    let extension = format.extension();
+    let stem = suggested
+        .map(|name| Path::new(name).file_stem().and_then(|s| s.to_str()).unwrap_or("").trim())
+        .filter(|stem| !stem.is_empty());
+    let taken = |name: &str| existing.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+    if let Some(stem) = stem {
+        let name = format!("{}.{}", stem, extension);
+        if !taken(&name) {
+            return name;
+        }
+    }
+    let stem = stem.unwrap_or(PASTED_NAME_PREFIX);
+    (1..)
+        .map(|n| format!("{}-{:03}.{}", stem, n, extension))
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pasted_names_avoid_collisions() {
+        let existing = vec!["pasted-001.png".to_string(), "PASTED-002.png".to_string(), "cover.jpg".to_string()];
+        assert_eq!(pasted_image_name(&existing, None, ImageFormat::Png), "pasted-003.png");
+        assert_eq!(pasted_image_name(&existing, Some("  "), ImageFormat::Jpeg), "pasted-001.jpg");
+        assert_eq!(pasted_image_name(&existing, Some("cover.png"), ImageFormat::Jpeg), "cover-001.jpg");
+        assert_eq!(pasted_image_name(&existing, Some("p12"), ImageFormat::Png), "p12.png");
+    }
+
+    #[test]
+    fn test_only_png_and_jpeg_accepted() {
+        let image = image::RgbImage::from_pixel(4, 4, image::Rgb([0, 0, 0]));
+        let mut png = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        assert_eq!(validate_pasted_image(&png), Ok(ImageFormat::Png));
+
+        let mut bmp = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut bmp), image::ImageFormat::Bmp).unwrap();
+        assert!(matches!(validate_pasted_image(&bmp), Err(ImportError::NotAnImage { .. })));
+        assert!(matches!(validate_pasted_image(&png[..png.len() / 2]), Err(ImportError::Corrupt { .. })));
+    }
+}
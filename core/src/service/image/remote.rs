@@ -0,0 +1,99 @@
+// 从URL下载的图片 - 根据文件头、Content-Type和URL扩展名确定格式，再走与本地文件相同的校验
+use std::path::Path;
+use crate::common::dto::image::ImageFormat;
+use super::validate::{validate_image_bytes, ImportError};
+
+/// URL中取不到文件名时使用的名称
+pub const REMOTE_IMAGE_NAME: &str = "remote-image";
+
+fn format_from_content_type(content_type: &str) -> Option<ImageFormat> {
+    // "image/jpeg; charset=binary" 只看分号前的部分
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        "image/gif" => Some(ImageFormat::Gif),
+        "image/webp" => Some(ImageFormat::Webp),
+        "image/bmp" | "image/x-ms-bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    }
+}
+
+fn format_from_magic(data: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(data).ok()? {
+        image::ImageFormat::Jpeg => Some(ImageFormat::Jpeg),
+        image::ImageFormat::Png => Some(ImageFormat::Png),
+        image::ImageFormat::Gif => Some(ImageFormat::Gif),
+        image::ImageFormat::WebP => Some(ImageFormat::Webp),
+        image::ImageFormat::Bmp => Some(ImageFormat::Bmp),
+        _ => None,
+    }
+}
+
+/// URL路径的最后一段（去掉查询参数和锚点），没有时返回REMOTE_IMAGE_NAME
+pub fn image_name_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or("");
+    // 跳过 scheme://host 部分，只看路径
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest.split_once('/').map_or("", |(_, p)| p));
+    path.rsplit('/')
+        .next()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(REMOTE_IMAGE_NAME)
+        .to_string()
+}
+
+/// 校验下载的数据，返回识别出的格式和带正确扩展名的文件名
+/// 格式以文件头为准，其次是Content-Type，最后是URL中的扩展名
+pub fn validate_remote_image(url: &str, content_type: Option<&str>, data: &[u8]) -> Result<(ImageFormat, String), ImportError> {
+    let name = image_name_from_url(url);
+    let stem = Path::new(&name).file_stem().and_then(|s| s.to_str()).unwrap_or(REMOTE_IMAGE_NAME);
+    let from_url = Path::new(&name).extension()
+        .and_then(|e| e.to_str())
+        .and_then(ImageFormat::from_extension);
+    let Some(hint) = format_from_magic(data)
+        .or_else(|| content_type.and_then(format_from_content_type))
+        .or(from_url)
+    else {
+        return Err(ImportError::NotAnImage {
+            name,
+            message: format!("{}：下载的内容不是图片", url),
+        });
+    };
+    let name = format!("{}.{}", stem, hint.extension());
+    let format = validate_image_bytes(&name, data)?;
+    Ok((format, format!("{}.{}", stem, format.extension())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png() -> Vec<u8> {
+        let mut data = Vec::new();
+        image::RgbaImage::new(2, 2).write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_name_ignores_query_and_host() {
+        assert_eq!(image_name_from_url("https://cdn.example.com/raw/p01.jpg?token=1#top"), "p01.jpg");
+        assert_eq!(image_name_from_url("https://cdn.example.com"), REMOTE_IMAGE_NAME);
+        assert_eq!(image_name_from_url("https://cdn.example.com/dir/"), REMOTE_IMAGE_NAME);
+    }
+
+    #[test]
+    fn test_format_prefers_file_header() {
+        // 扩展名和Content-Type都不对时仍按文件头识别
+        let (format, name) = validate_remote_image("https://x.test/page.jpg", Some("application/octet-stream"), &png()).unwrap();
+        assert_eq!(format, ImageFormat::Png);
+        assert_eq!(name, "page.png");
+
+        let (_, name) = validate_remote_image("https://x.test/render?id=3", Some("image/png"), &png()).unwrap();
+        assert_eq!(name, "render.png");
+
+        let error = validate_remote_image("https://x.test/index.html", Some("text/html; charset=utf-8"), b"<html></html>").unwrap_err();
+        assert_eq!(error.kind(), "not_an_image");
+        assert_eq!(format_from_content_type("IMAGE/JPEG; q=1"), Some(ImageFormat::Jpeg));
+    }
+}
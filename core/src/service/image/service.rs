@@ -1,12 +1,16 @@
 // Image Service - 处理图片相关的业务逻辑
 use std::sync::Arc;
-use std::path::PathBuf;
-use crate::common::{ImageId, MarkerId};
-use crate::common::dto::image::{ImageDTO, ImageDataDTO, ImageFormat as ImageFormatDTO};
+use std::path::{Path, PathBuf};
+use crate::common::{CoreResult, ImageId, MarkerId, ProjectId, ThumbnailOrder, EVENT_SYSTEM};
+use crate::common::dto::image::{ImageDTO, ImageDataDTO, ImageFormat as ImageFormatDTO, ImageMetadataDTO, ImageSourceKind, ImageViewState};
 use crate::common::dto::marker::MarkerDTO;
+use crate::common::events::schema::{BusinessEvent, ImageLockedChangedEvent, ImageRenamedEvent, ImageReviewChangedEvent, ImageViewRestoredEvent, NotificationEvent};
 use crate::storage::{ImageData, ImageFormat};
+use crate::storage::image::Image;
+use crate::storage::traits::Storage;
 use crate::service::events::{DomainEvent, EventBus, EventHandler};
-use rayon::prelude::*;
+use crate::service::worker_pool::CPU_POOL;
+use super::downscale;
 use md5::{Digest, Md5};
 
 pub struct ImageService {
@@ -30,6 +34,9 @@ impl ImageService {
     // === 图片创建操作 ===
     
     pub fn add_image_from_path(&self, path: PathBuf, name: Option<String>) -> Option<u32> {
+        if let Some(id) = self.add_downscaled_from_path(&path, name.clone()) {
+            return Some(id);
+        }
         match crate::storage::image::add_image_from_path_storage(path, name) {
             Ok(id) => Some(id.into()),
             Err(_) => None,
@@ -37,19 +44,61 @@ impl ImageService {
     }
     
     pub fn add_image_from_binary(&self, format: ImageFormatDTO, data: Vec<u8>, name: Option<String>) -> Option<u32> {
-        // 转换DTO的ImageFormat到storage的ImageFormat
-        let storage_format = match format {
-            ImageFormatDTO::Jpeg => ImageFormat::Jpeg,
-            ImageFormatDTO::Png => ImageFormat::Png,
-            ImageFormatDTO::Gif => ImageFormat::Gif,
-            ImageFormatDTO::Webp => ImageFormat::Webp,
-            ImageFormatDTO::Bmp => ImageFormat::Bmp,
-        };
+        let storage_format = to_storage_format(format);
+        let settings = downscale::get_downscale_settings().unwrap_or_default();
+        match downscale::downscale_if_needed(&data, &storage_format, &settings) {
+            Ok(Some(downscaled)) => return self.add_downscaled(downscaled, name),
+            Ok(None) => {}
+            Err(e) => crate::common::Logger::warn(&format!("Failed to downscale image, keeping original: {}", e)),
+        }
         match crate::storage::image::add_image_from_binary_storage(storage_format, data, name) {
             Ok(id) => Some(id.into()),
             Err(_) => None,
         }
     }
+
+    // 文件导入时只有超过长边上限才读入内存缩小，改为Binary存储
+    fn add_downscaled_from_path(&self, path: &Path, name: Option<String>) -> Option<u32> {
+        let settings = downscale::get_downscale_settings().ok()?;
+        if !settings.enabled {
+            return None;
+        }
+        let format = path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(ImageFormat::from_extension)?;
+        let (width, height) = crate::storage::dimension_extractor::extract_dimensions_from_file(&path.to_path_buf(), &format).ok()??;
+        downscale::target_dimensions(width, height, settings.max_edge)?;
+
+        let data = std::fs::read(path).ok()?;
+        match downscale::downscale_if_needed(&data, &format, &settings) {
+            Ok(Some(downscaled)) => {
+                let name = name.or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()));
+                self.add_downscaled(downscaled, name)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                crate::common::Logger::warn(&format!("Failed to downscale image {:?}, keeping original: {}", path, e));
+                None
+            }
+        }
+    }
+
+    fn add_downscaled(&self, downscaled: downscale::DownscaledImage, name: Option<String>) -> Option<u32> {
+        let original = (downscaled.original_width, downscaled.original_height);
+        let id = crate::storage::image::add_downscaled_image_storage(downscaled.format, downscaled.data, name.clone(), original).ok()?;
+        // 通过通用通知通道提示用户图片已被缩小
+        let label = name.clone().unwrap_or_else(|| format!("图片 {}", id.0));
+        let message = format!("{} 原始尺寸 {}x{}，已缩小为 {}x{}",
+            label, original.0, original.1, downscaled.width, downscaled.height);
+        let _ = NotificationEvent::simple("图片已缩小", message, "info", "image", Some(serde_json::json!({
+            "image_id": id.0,
+            "original_width": original.0,
+            "original_height": original.1,
+            "width": downscaled.width,
+            "height": downscaled.height,
+        }))).emit();
+        Some(id.into())
+    }
     
     // === 图片查询操作 ===
     
@@ -60,14 +109,28 @@ impl ImageService {
         }
     }
     
+    /// 只获取元数据（尺寸、DPI、颜色模式、字节大小），不复制图片数据
+    pub fn get_image_metadata(&self, image_id: u32) -> Option<ImageMetadataDTO> {
+        match crate::storage::image::get_image_storage(ImageId::from(image_id)) {
+            Ok(opt) => opt.map(|arc| arc.to_dto().metadata),
+            Err(_) => None,
+        }
+    }
+    
     pub fn get_image_binary_data(&self, image_id: u32) -> Result<Vec<u8>, String> {
         if let Some(image) = crate::storage::image::get_image_storage(ImageId::from(image_id)).ok().flatten() {
-            self.get_image_data_for_display(&image.data)
+            self.get_image_data_for_display(&image)
         } else {
             Err("Image not found".to_string())
         }
     }
     
+    /// 把图片重新编码为指定格式：JPEG的quality为质量(1-100)，PNG为压缩级别(0-9)，其他格式不接受quality
+    pub fn encode_image(&self, image_id: u32, format: ImageFormatDTO, quality: Option<u8>) -> Result<Vec<u8>, String> {
+        let data = super::image_lease::shared_image_bytes(ImageId::from(image_id)).map_err(|e| e.to_string())?;
+        super::encode::encode_bytes(&data, &to_storage_format(format), quality).map_err(|e| e.to_string())
+    }
+
     pub fn get_image_file_path(&self, image_id: u32) -> Option<String> {
         if let Some(image) = crate::storage::image::get_image_storage(ImageId::from(image_id)).ok().flatten() {
             match &image.data {
@@ -79,6 +142,15 @@ impl ImageService {
         }
     }
     
+    pub fn get_image_source_kind(&self, image_id: u32) -> Option<ImageSourceKind> {
+        let image = crate::storage::image::get_image_storage(ImageId::from(image_id)).ok().flatten()?;
+        Some(match &image.data {
+            ImageData::FilePath(_) => ImageSourceKind::FilePath,
+            ImageData::Binary { .. } => ImageSourceKind::Binary,
+            ImageData::SharedBuffer { .. } => ImageSourceKind::SharedBuffer,
+        })
+    }
+    
     pub fn get_image_mime_type(&self, image_id: u32) -> Option<String> {
         if let Some(image) = crate::storage::image::get_image_storage(ImageId::from(image_id)).ok().flatten() {
             self.get_image_mime_type_internal(&image.data)
@@ -120,10 +192,14 @@ impl ImageService {
                 buffer_id,
             },
         });
+        let replaces_data = storage_data.is_some();
         let result = match crate::storage::image::update_image_storage(ImageId::from(image_id), storage_data, name) {
             Ok(res) => res,
             Err(_) => false,
         };
+        if result && replaces_data {
+            super::byte_cache::invalidate(ImageId::from(image_id));
+        }
         
         if result {
             self.event_bus.publish(DomainEvent::ImageUpdated(ImageId::from(image_id)));
@@ -160,6 +236,9 @@ impl ImageService {
             Ok(res) => res,
             Err(_) => false,
         };
+        if result {
+            super::byte_cache::invalidate(ImageId::from(image_id));
+        }
         
         if result {
             self.event_bus.publish(DomainEvent::ImageUpdated(ImageId::from(image_id)));
@@ -181,6 +260,50 @@ impl ImageService {
         result
     }
     
+    /// 重命名项目中的图片，返回原来的名称（图片不在项目中时为None）；撤销/重做也走这里
+    /// 名称去掉首尾空白后不能为空，也不能与项目中的其它图片重名
+    pub fn rename_image(&self, image_id: ImageId, name: Option<String>) -> CoreResult<Option<Option<String>>> {
+        let Some(project_id) = crate::storage::project::find_project_by_image_storage(image_id)? else {
+            return Ok(None);
+        };
+        let name = name.map(|name| name.trim().to_string());
+        if let Some(name) = &name {
+            if name.is_empty() {
+                return Err(crate::common::CoreError::ValidationFailed {
+                    field: "name".to_string(),
+                    reason: "图片名称不能为空".to_string(),
+                });
+            }
+            let images = crate::storage::state::APP_STATE.images.read()?;
+            let duplicate = crate::storage::project::get_project_image_ids_storage(project_id)?.iter()
+                .filter(|id| **id != image_id)
+                .any(|id| images.get(id).and_then(|image| image.metadata.name.as_ref()) == Some(name));
+            if duplicate {
+                return Err(crate::common::CoreError::ValidationFailed {
+                    field: "name".to_string(),
+                    reason: format!("项目中已有名为 {} 的图片", name),
+                });
+            }
+        }
+        self.set_image_name(image_id, name)
+    }
+    
+    /// 不做校验直接写入名称并通知前端，撤销/重做恢复的是之前的状态，可能与其它图片重名
+    pub fn set_image_name(&self, image_id: ImageId, name: Option<String>) -> CoreResult<Option<Option<String>>> {
+        let Some(project_id) = crate::storage::project::find_project_by_image_storage(image_id)? else {
+            return Ok(None);
+        };
+        let Some(old) = crate::storage::image::get_image_storage(image_id)?.map(|image| image.metadata.name.clone()) else {
+            return Ok(None);
+        };
+        if old != name {
+            crate::storage::image::update_image_name_storage(image_id, name.clone())?;
+            let _ = ImageRenamedEvent::new(project_id, image_id, name).emit();
+            self.event_bus.publish(DomainEvent::ImageUpdated(image_id));
+        }
+        Ok(Some(old))
+    }
+    
     // === 图片删除操作 ===
     
     pub fn remove_image(&self, image_id: u32) -> bool {
@@ -188,6 +311,7 @@ impl ImageService {
             Ok(res) => res,
             Err(_) => false,
         };
+        super::byte_cache::invalidate(ImageId::from(image_id));
         
         if result {
             self.event_bus.publish(DomainEvent::ImageDeleted(ImageId::from(image_id)));
@@ -196,6 +320,180 @@ impl ImageService {
         result
     }
     
+    // === 缩略图栏排序 ===
+    
+    pub fn set_image_pinned(&self, image_id: u32, pinned: bool) -> bool {
+        let image_id = ImageId::from(image_id);
+        let project_id = match crate::storage::project::find_project_by_image_storage(image_id) {
+            Ok(Some(project_id)) => project_id,
+            _ => return false,
+        };
+        let result = crate::storage::project::set_image_pinned_storage(project_id, image_id, pinned)
+            .unwrap_or(false);
+        
+        if result {
+            self.emit_thumbnail_order_changed(project_id);
+        }
+        
+        result
+    }
+    
+    pub fn set_thumbnail_sort(&self, project_id: u32, order: ThumbnailOrder) -> bool {
+        let project_id = ProjectId::from(project_id);
+        let result = crate::storage::project::update_thumbnail_order_storage(project_id, order)
+            .unwrap_or(false);
+        
+        if result {
+            self.emit_thumbnail_order_changed(project_id);
+        }
+        
+        result
+    }
+    
+    // === 校对状态 ===
+    
+    /// 标记图片已审阅/未审阅，备注去掉首尾空白，为空时清除
+    pub fn set_image_reviewed(&self, image_id: u32, reviewed: bool, note: Option<String>) -> bool {
+        let image_id = ImageId::from(image_id);
+        let project_id = match crate::storage::project::find_project_by_image_storage(image_id) {
+            Ok(Some(project_id)) => project_id,
+            _ => return false,
+        };
+        let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+        let result = crate::storage::image::set_image_review_storage(image_id, reviewed, note.clone())
+            .unwrap_or(false);
+        
+        if result {
+            self.event_bus.publish(DomainEvent::ImageReviewChanged(project_id, image_id));
+            let _ = ImageReviewChangedEvent::new(project_id, image_id, reviewed, note).emit();
+        }
+        
+        result
+    }
+    
+    // === 锁定 ===
+    
+    /// 锁定/解锁图片并通知前端，返回原来的状态（图片不在项目中时为None）；撤销/重做也走这里
+    pub fn set_image_locked(&self, image_id: ImageId, locked: bool) -> CoreResult<Option<bool>> {
+        let Some(project_id) = crate::storage::project::find_project_by_image_storage(image_id)? else {
+            return Ok(None);
+        };
+        let old = crate::storage::image::set_image_locked_storage(image_id, locked)?;
+        if old.is_some_and(|old| old != locked) {
+            let _ = ImageLockedChangedEvent::new(project_id, image_id, locked).emit();
+        }
+        Ok(old)
+    }
+    
+    // === 查看状态 ===
+    
+    /// 保存图片的缩放/平移和选中标记，None清除；不标记项目修改，随下次保存写入bf
+    pub fn set_view_state(&self, image_id: ImageId, view_state: Option<ImageViewState>) -> CoreResult<bool> {
+        if let Some(state) = &view_state {
+            if !state.zoom.is_finite() || state.zoom <= 0.0 {
+                return Err(crate::common::CoreError::ValidationFailed {
+                    field: "zoom".to_string(),
+                    reason: format!("无效的缩放比例: {}", state.zoom),
+                });
+            }
+            if !state.pan_x.is_finite() || !state.pan_y.is_finite() {
+                return Err(crate::common::CoreError::ValidationFailed {
+                    field: "pan".to_string(),
+                    reason: "无效的平移位置".to_string(),
+                });
+            }
+        }
+        crate::storage::image::set_image_view_state_storage(image_id, view_state)
+    }
+    
+    /// 读取查看状态，选中的标记已删除或移到别的图片时去掉
+    pub fn get_view_state(&self, image_id: ImageId) -> CoreResult<Option<ImageViewState>> {
+        let Some(image) = crate::storage::image::get_image_storage(image_id)? else {
+            return Ok(None);
+        };
+        Ok(image.metadata.view_state.clone().map(|mut state| {
+            if state.last_selected_marker.is_some_and(|marker_id| !image.marker_ids.contains(&marker_id)) {
+                state.last_selected_marker = None;
+            }
+            state
+        }))
+    }
+    
+    /// 选中图片：有保存的查看状态时发送image:view_restored并返回
+    pub fn select_image(&self, image_id: ImageId) -> CoreResult<Option<ImageViewState>> {
+        let state = self.get_view_state(image_id)?;
+        if let Some(state) = &state
+            && let Some(project_id) = crate::storage::project::find_project_by_image_storage(image_id)?
+        {
+            let _ = ImageViewRestoredEvent::new(
+                project_id, image_id, state.zoom, state.pan_x, state.pan_y, state.last_selected_marker,
+            ).emit();
+        }
+        Ok(state)
+    }
+    
+    // 通知前端重新渲染缩略图栏
+    fn emit_thumbnail_order_changed(&self, project_id: ProjectId) {
+        let project = match crate::storage::project::get_project_storage(project_id) {
+            Ok(Some(project)) => project,
+            _ => return,
+        };
+        let order = super::thumbnail::get_thumbnail_order(project_id).unwrap_or_default();
+        let _ = EVENT_SYSTEM.emit_business_event(
+            "thumbnail_order_changed".to_string(),
+            serde_json::json!({
+                "project_id": project_id,
+                "thumbnail_order": project.thumbnail_order,
+                "pinned_images": project.pinned_images,
+                "image_order": order,
+            }),
+        );
+    }
+    
+    /// 图片文件在磁盘上被修改后重新读取：更新尺寸和校验和、重新生成缩略图并通知前端
+    /// 返回false表示不是文件图片、文件暂时不存在（原子替换过程中）或内容未变化
+    pub fn invalidate_image(&self, image_id: ImageId) -> crate::common::CoreResult<bool> {
+        let image = crate::storage::image::get_image_storage(image_id)?
+            .ok_or_else(|| crate::common::CoreError::NotFound(format!("Image with id {} not found", image_id.0)))?;
+        let ImageData::FilePath(path) = &image.data else {
+            return Ok(false);
+        };
+        
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(crate::common::CoreError::IoError(format!("Failed to read file: {}", e))),
+        };
+        let checksum = self.calculate_checksum(&data);
+        if image.metadata.checksum.as_deref() == Some(checksum.as_str()) {
+            return Ok(false);
+        }
+        
+        let info = image.data.get_format().and_then(|format| {
+            crate::storage::dimension_extractor::extract_image_info_from_bytes(&data, &format)
+        });
+        crate::storage::image::refresh_image_file_storage(image_id, info.as_ref(), data.len() as u64, checksum.clone())?;
+        
+        // 旧缩略图和缓存的原图字节作废，磁盘缓存的key包含修改时间，会自然失效
+        super::byte_cache::invalidate(image_id);
+        crate::storage::state::APP_STATE.thumbnails.write()?.remove(&image_id);
+        if let Err(e) = super::thumbnail::request_thumbnail(image_id) {
+            crate::common::Logger::warn(&format!("Failed to regenerate thumbnail for image {}: {}", image_id.0, e));
+        }
+        
+        self.event_bus.publish(DomainEvent::ImageUpdated(image_id));
+        let _ = EVENT_SYSTEM.emit_business_event(
+            "image:file_changed".to_string(),
+            serde_json::json!({
+                "image_id": image_id,
+                "width": info.as_ref().map(|i| i.width),
+                "height": info.as_ref().map(|i| i.height),
+                "checksum": checksum,
+            }),
+        );
+        Ok(true)
+    }
+    
     // === 标记相关操作 ===
     
     pub fn get_image_markers(&self, image_id: u32) -> Vec<MarkerDTO> {
@@ -242,19 +540,18 @@ impl ImageService {
     
     pub fn clear_all(&self) {
         let _ = crate::storage::image::clear_all_images_storage();
+        let _ = super::byte_cache::clear();
     }
     
     // === 实用工具函数 ===
     
-    /// 从ImageData获取数据用于显示
-    fn get_image_data_for_display(&self, image_data: &ImageData) -> Result<Vec<u8>, String> {
-        match image_data {
-            ImageData::Binary { data, .. } => Ok((**data).clone()),
-            ImageData::FilePath(path) => {
-                std::fs::read(path)
-                    .map_err(|e| format!("Failed to read file: {}", e))
-            }
+    /// 获取图片数据用于显示和裁剪，文件图片走字节缓存
+    fn get_image_data_for_display(&self, image: &Image) -> Result<Vec<u8>, String> {
+        match &image.data {
             ImageData::SharedBuffer { .. } => Err("Cannot get binary data from shared buffer".to_string()),
+            _ => super::byte_cache::read_image_bytes(image)
+                .map(|data| (*data).clone())
+                .map_err(|e| format!("Failed to read file: {}", e)),
         }
     }
     
@@ -307,91 +604,127 @@ impl ImageService {
     
     /// Process multiple images in parallel
     pub fn process_images_batch(&self, images: Vec<(Vec<u8>, ImageFormat)>) -> Vec<ImageProcessingResult> {
-        images
-            .into_par_iter()
-            .map(|(data, format)| self.process_image_parallel(&data, &format))
-            .collect()
+        CPU_POOL.map(images, |(data, format)| self.process_image_parallel(&data, &format))
+    }
+
+    /// 在线程池中并行裁剪同一张图片的多个区域，结果与geometries顺序一致
+    pub(crate) fn crop_image_regions(
+        &self,
+        image_data: &[u8],
+        geometries: Vec<crate::storage::marker::MarkerGeometry>,
+    ) -> Vec<Result<Vec<u8>, String>> {
+        CPU_POOL.map(geometries, |geometry| self.crop_image_region(image_data, &geometry))
     }
 
     /// Crop a region from an image and return as PNG bytes
     /// Coordinates are in percentage (0-100) relative to image dimensions
     /// For Point markers, uses a fixed percentage size (10% width/height) centered on the point
+    /// For Polygon markers, crops the bounding box and paints pixels outside the polygon white
     pub(crate) fn crop_image_region(
         &self,
         image_data: &[u8],
-        x: f64,
-        y: f64,
-        width: Option<f64>,
-        height: Option<f64>,
+        geometry: &crate::storage::marker::MarkerGeometry,
     ) -> Result<Vec<u8>, String> {
         use image::ImageFormat as ImgFormat;
 
         // Load the image
         let img = image::load_from_memory(image_data)
             .map_err(|e| format!("Failed to load image: {}", e))?;
+        let cropped = crop_decoded_region(&img, geometry)?;
 
-        let img_width = img.width() as f64;
-        let img_height = img.height() as f64;
-
-        // Convert percentage coordinates to pixel coordinates
-        let (crop_x, crop_y, crop_width, crop_height) = match (width, height) {
-            (Some(w), Some(h)) => {
-                // Rectangle marker: convert percentage to pixels
-                let px = (x / 100.0 * img_width).max(0.0) as u32;
-                let py = (y / 100.0 * img_height).max(0.0) as u32;
-                let pw = (w / 100.0 * img_width).max(1.0) as u32;
-                let ph = (h / 100.0 * img_height).max(1.0) as u32;
-                (px, py, pw, ph)
-            }
-            _ => {
-                // Point marker: use fixed percentage size (10% of width/height) centered on point
-                const POINT_CROP_PERCENT: f64 = 10.0;
-                let crop_size_w = (POINT_CROP_PERCENT / 100.0 * img_width).max(50.0) as u32; // at least 50px
-                let crop_size_h = (POINT_CROP_PERCENT / 100.0 * img_height).max(50.0) as u32;
+        // Encode as PNG
+        let mut buffer = Vec::new();
+        cropped
+            .write_to(&mut std::io::Cursor::new(&mut buffer), ImgFormat::Png)
+            .map_err(|e| format!("Failed to encode cropped image: {}", e))?;
 
-                let center_x = (x / 100.0 * img_width) as i32;
-                let center_y = (y / 100.0 * img_height) as i32;
+        Ok(buffer)
+    }
+}
 
-                let half_w = (crop_size_w / 2) as i32;
-                let half_h = (crop_size_h / 2) as i32;
+/// 在已解码的图片上裁剪标记区域，规则同crop_image_region；批量裁剪时整页只需解码一次
+pub(crate) fn crop_decoded_region(
+    img: &image::DynamicImage,
+    geometry: &crate::storage::marker::MarkerGeometry,
+) -> Result<image::DynamicImage, String> {
+    use crate::storage::marker::MarkerGeometry;
 
-                let px = (center_x - half_w).max(0) as u32;
-                let py = (center_y - half_h).max(0) as u32;
+    let img_width = img.width() as f64;
+    let img_height = img.height() as f64;
 
-                (px, py, crop_size_w, crop_size_h)
-            }
-        };
+    // Convert percentage coordinates to pixel coordinates
+    let (crop_x, crop_y, crop_width, crop_height) = match *geometry {
+        MarkerGeometry::Point { x, y } => {
+            // Point marker: use fixed percentage size (10% of width/height) centered on point
+            const POINT_CROP_PERCENT: f64 = 10.0;
+            let crop_size_w = (POINT_CROP_PERCENT / 100.0 * img_width).max(50.0) as u32; // at least 50px
+            let crop_size_h = (POINT_CROP_PERCENT / 100.0 * img_height).max(50.0) as u32;
 
-        // Ensure crop dimensions are within image bounds
-        let img_width_u32 = img.width();
-        let img_height_u32 = img.height();
+            let center_x = (x / 100.0 * img_width) as i32;
+            let center_y = (y / 100.0 * img_height) as i32;
 
-        let final_x = crop_x.min(img_width_u32.saturating_sub(1));
-        let final_y = crop_y.min(img_height_u32.saturating_sub(1));
-        let final_width = crop_width.min(img_width_u32 - final_x);
-        let final_height = crop_height.min(img_height_u32 - final_y);
+            let half_w = (crop_size_w / 2) as i32;
+            let half_h = (crop_size_h / 2) as i32;
 
-        crate::common::Logger::debug_with_data(
-            "Crop result",
-            serde_json::json!({
-                "crop_region_px": [final_x, final_y, final_width, final_height]
-            })
-        );
+            let px = (center_x - half_w).max(0) as u32;
+            let py = (center_y - half_h).max(0) as u32;
 
-        if final_width == 0 || final_height == 0 {
-            return Err("Crop region is empty or out of bounds".to_string());
+            (px, py, crop_size_w, crop_size_h)
         }
+        // Rectangle / polygon: convert the (bounding) rectangle to pixels
+        _ => match geometry.to_pixels(img.width(), img.height()) {
+            crate::common::dto::marker::PixelGeometry::Rectangle { x, y, width, height } => (x, y, width, height),
+            crate::common::dto::marker::PixelGeometry::Point { x, y } => (x, y, 1, 1),
+        },
+    };
 
-        // Crop the image
-        let cropped = img.crop_imm(final_x, final_y, final_width, final_height);
+    // Ensure crop dimensions are within image bounds
+    let img_width_u32 = img.width();
+    let img_height_u32 = img.height();
 
-        // Encode as PNG
-        let mut buffer = Vec::new();
-        cropped
-            .write_to(&mut std::io::Cursor::new(&mut buffer), ImgFormat::Png)
-            .map_err(|e| format!("Failed to encode cropped image: {}", e))?;
+    let final_x = crop_x.min(img_width_u32.saturating_sub(1));
+    let final_y = crop_y.min(img_height_u32.saturating_sub(1));
+    let final_width = crop_width.min(img_width_u32 - final_x);
+    let final_height = crop_height.min(img_height_u32 - final_y);
 
-        Ok(buffer)
+    crate::common::Logger::debug_with_data(
+        "Crop result",
+        serde_json::json!({
+            "crop_region_px": [final_x, final_y, final_width, final_height]
+        })
+    );
+
+    if final_width == 0 || final_height == 0 {
+        return Err("Crop region is empty or out of bounds".to_string());
+    }
+
+    // Crop the image
+    let mut cropped = img.crop_imm(final_x, final_y, final_width, final_height);
+
+    // 多边形外的像素涂白，避免相邻文字干扰OCR（按像素中心判断）
+    if let MarkerGeometry::Polygon { .. } = geometry {
+        let mut masked = cropped.to_rgba8();
+        for (px, py, pixel) in masked.enumerate_pixels_mut() {
+            let center_x = (final_x + px) as f64 + 0.5;
+            let center_y = (final_y + py) as f64 + 0.5;
+            if !geometry.contains(center_x / img_width * 100.0, center_y / img_height * 100.0) {
+                *pixel = image::Rgba([255, 255, 255, 255]);
+            }
+        }
+        cropped = image::DynamicImage::ImageRgba8(masked);
+    }
+
+    Ok(cropped)
+}
+
+// 转换DTO的ImageFormat到storage的ImageFormat
+fn to_storage_format(format: ImageFormatDTO) -> ImageFormat {
+    match format {
+        ImageFormatDTO::Jpeg => ImageFormat::Jpeg,
+        ImageFormatDTO::Png => ImageFormat::Png,
+        ImageFormatDTO::Gif => ImageFormat::Gif,
+        ImageFormatDTO::Webp => ImageFormat::Webp,
+        ImageFormatDTO::Bmp => ImageFormat::Bmp,
     }
 }
 
@@ -431,4 +764,45 @@ impl EventHandler for ImageService {
             _ => {}
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::storage::marker::MarkerGeometry;
+
+    #[test]
+    fn test_polygon_crop_masks_outside_white() {
+        let source = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 255])));
+        let mut data = Vec::new();
+        source.write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+
+        // 直角三角形：外接矩形的右上半部分在多边形外
+        let triangle = MarkerGeometry::Polygon { points: vec![(20.0, 20.0), (20.0, 60.0), (60.0, 60.0)] };
+        let cropped = crate::service::get_service().image_service.crop_image_region(&data, &triangle).unwrap();
+        let cropped = image::load_from_memory(&cropped).unwrap().to_rgba8();
+        assert_eq!(cropped.dimensions(), (40, 40));
+        assert_eq!(cropped.get_pixel(2, 37).0, [0, 0, 0, 255]);
+        assert_eq!(cropped.get_pixel(37, 2).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_view_state_validates_and_drops_stale_selection() {
+        use crate::common::dto::image::ImageViewState;
+        let service = &crate::service::get_service().image_service;
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("view.png".to_string())
+        ).unwrap();
+        let state = ImageViewState { zoom: 0.0, pan_x: 0.0, pan_y: 0.0, last_selected_marker: None };
+        assert!(service.set_view_state(image_id, Some(state)).is_err());
+
+        // 选中的标记不在这张图片上时读出为未选中
+        let stale = crate::common::MARKER_ID_GENERATOR.next();
+        let state = ImageViewState { zoom: 1.5, pan_x: 10.0, pan_y: -5.0, last_selected_marker: Some(stale) };
+        assert!(service.set_view_state(image_id, Some(state)).unwrap());
+        let restored = service.get_view_state(image_id).unwrap().unwrap();
+        assert_eq!(restored.zoom, 1.5);
+        assert_eq!(restored.last_selected_marker, None);
+
+        assert!(service.set_view_state(image_id, None).unwrap());
+        assert_eq!(service.get_view_state(image_id).unwrap(), None);
+    }
+}
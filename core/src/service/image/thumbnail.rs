@@ -1,16 +1,26 @@
 use crate::common::{CoreError, CoreResult};
-use crate::common::ImageId;
+use crate::common::{ImageId, ProjectId, ThumbnailOrder};
 use crate::storage::state::APP_STATE;
 use crate::storage::traits::Storage;
 use crate::storage::ImageFormat;
+use crate::common::dto::image::ColorMode;
+use super::thumbnail_cache;
 use crate::common::EVENT_SYSTEM;
+#[cfg(target_arch = "wasm32")]
 use rayon::prelude::*;
 use image::{DynamicImage, GenericImageView};
 use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
 use serde::{Deserialize, Serialize};
 
 pub const DEFAULT_THUMBNAIL_SIZE: u32 = 200;
 
+/// 默认预取当前图片前后各多少张
+pub const DEFAULT_PREFETCH_RADIUS: usize = 5;
+
+// 每次切换当前图片递增，旧的预取任务发现代数变化后停止
+static PREFETCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThumbnailData {
     pub image_id: ImageId,
@@ -90,12 +100,146 @@ pub fn request_thumbnail(image_id: ImageId) -> CoreResult<()> {
     Ok(())
 }
 
+/// 按项目的缩略图排序设置计算图片显示顺序：固定的图片在前，其余按排序方式排列
+pub fn get_thumbnail_order(project_id: ProjectId) -> CoreResult<Vec<ImageId>> {
+    let project = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+
+    let mut ordered: Vec<ImageId> = project.pinned_images.iter()
+        .filter(|id| project.image_ids.contains(id))
+        .copied()
+        .collect();
+    let mut rest: Vec<ImageId> = project.image_ids.iter()
+        .filter(|id| !project.pinned_images.contains(id))
+        .copied()
+        .collect();
+
+    match project.thumbnail_order {
+        ThumbnailOrder::Manual => {}
+        ThumbnailOrder::Name => {
+            let mut keyed = Vec::with_capacity(rest.len());
+            for id in rest {
+                let name = APP_STATE.get_image(id)?
+                    .and_then(|image| image.metadata.name.clone())
+                    .unwrap_or_default()
+                    .to_lowercase();
+                keyed.push((name, id));
+            }
+            // 稳定排序，同名图片保持项目顺序
+            keyed.sort_by(|a, b| a.0.cmp(&b.0));
+            rest = keyed.into_iter().map(|(_, id)| id).collect();
+        }
+        ThumbnailOrder::Completion => {
+            let mut keyed = Vec::with_capacity(rest.len());
+            for id in rest {
+                keyed.push((image_completion(id)?, id));
+            }
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            rest = keyed.into_iter().map(|(_, id)| id).collect();
+        }
+    }
+
+    ordered.extend(rest);
+    Ok(ordered)
+}
+
+// 已翻译标记占比，没有标记的图片视为未完成
+fn image_completion(image_id: ImageId) -> CoreResult<f64> {
+    let image = match APP_STATE.get_image(image_id)? {
+        Some(image) => image,
+        None => return Ok(0.0),
+    };
+    if image.marker_ids.is_empty() {
+        return Ok(0.0);
+    }
+    let markers = APP_STATE.markers.read_image(image_id)?;
+    let translated = image.marker_ids.iter()
+        .filter(|id| markers.get(id).is_some_and(|m| !m.translation.trim().is_empty()))
+        .count();
+    Ok(translated as f64 / image.marker_ids.len() as f64)
+}
+
+// 按所属项目的缩略图顺序排列请求，决定生成的优先级
+fn sort_by_thumbnail_order(image_ids: &mut [ImageId]) {
+    let project_id = match image_ids.first()
+        .and_then(|&id| crate::storage::project::find_project_by_image_storage(id).ok().flatten())
+    {
+        Some(project_id) => project_id,
+        None => return,
+    };
+    if let Ok(order) = get_thumbnail_order(project_id) {
+        image_ids.sort_by_key(|id| order.iter().position(|o| o == id).unwrap_or(usize::MAX));
+    }
+}
+
+/// 预取当前图片前后各radius张图片的缩略图（按缩略图栏顺序，距离近的优先）
+/// 再次调用时会取消上一轮尚未开始的预取
+pub fn prefetch_around(current_image_id: ImageId, radius: usize) -> CoreResult<()> {
+    let generation = PREFETCH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let project_id = crate::storage::project::find_project_by_image_storage(current_image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image with id {} not in any project", current_image_id.0)))?;
+    let order = get_thumbnail_order(project_id)?;
+    let targets = prefetch_targets(&order, current_image_id, radius);
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    rayon::spawn(move || {
+        for image_id in targets {
+            if PREFETCH_GENERATION.load(Ordering::SeqCst) != generation {
+                crate::common::Logger::debug(&format!("Thumbnail prefetch generation {} cancelled", generation));
+                return;
+            }
+
+            // 已有缩略图或正在生成的跳过
+            match APP_STATE.thumbnails.write() {
+                Ok(mut storage) => {
+                    if storage.contains(&image_id) {
+                        continue;
+                    }
+                    storage.insert(image_id, Vec::new()).ok();
+                }
+                Err(_) => return,
+            }
+
+            if let Err(e) = generate_thumbnail_internal(image_id) {
+                crate::common::Logger::error(&format!("Failed to prefetch thumbnail for image {}: {}", image_id, e));
+                if let Ok(mut storage) = APP_STATE.thumbnails.write() {
+                    storage.remove(&image_id);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// 按与当前图片的距离排列：当前、后1、前1、后2、前2...
+fn prefetch_targets(order: &[ImageId], current_image_id: ImageId, radius: usize) -> Vec<ImageId> {
+    let Some(index) = order.iter().position(|&id| id == current_image_id) else {
+        return Vec::new();
+    };
+    let mut targets = vec![current_image_id];
+    for distance in 1..=radius {
+        if let Some(&id) = order.get(index + distance) {
+            targets.push(id);
+        }
+        if let Some(&id) = index.checked_sub(distance).and_then(|i| order.get(i)) {
+            targets.push(id);
+        }
+    }
+    targets
+}
+
 // Batch request thumbnails
-pub fn request_thumbnails_batch(image_ids: Vec<ImageId>) -> CoreResult<()> {
+pub fn request_thumbnails_batch(mut image_ids: Vec<ImageId>) -> CoreResult<()> {
     if image_ids.is_empty() {
         return Ok(());
     }
     
+    sort_by_thumbnail_order(&mut image_ids);
+    
     let mut thumbnail_storage = APP_STATE.thumbnails.write()?;
     let mut pending_ids = Vec::new();
     
@@ -168,22 +312,25 @@ pub fn request_thumbnails_batch(image_ids: Vec<ImageId>) -> CoreResult<()> {
     #[cfg(not(target_arch = "wasm32"))]
     {
         // Native platforms can handle more parallelism
+        // 使用FIFO调度，保证按缩略图顺序优先生成
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(4)
             .build()
             .map_err(|e| CoreError::PlatformError(format!("Failed to create thread pool: {}", e)))?;
         
-        pool.install(|| {
-            pending_ids.par_iter().for_each(|&image_id| {
-                if let Err(e) = generate_thumbnail_internal(image_id) {
-                    crate::common::Logger::error(&format!("Failed to generate thumbnail for image {}: {}", image_id, e));
-                    
-                    // Clean up pending state
-                    if let Ok(mut storage) = APP_STATE.thumbnails.write() {
-                        storage.remove(&image_id);
+        pool.scope_fifo(|scope| {
+            for &image_id in &pending_ids {
+                scope.spawn_fifo(move |_| {
+                    if let Err(e) = generate_thumbnail_internal(image_id) {
+                        crate::common::Logger::error(&format!("Failed to generate thumbnail for image {}: {}", image_id, e));
+                        
+                        // Clean up pending state
+                        if let Ok(mut storage) = APP_STATE.thumbnails.write() {
+                            storage.remove(&image_id);
+                        }
                     }
-                }
-            });
+                });
+            }
         });
     }
     
@@ -191,56 +338,66 @@ pub fn request_thumbnails_batch(image_ids: Vec<ImageId>) -> CoreResult<()> {
 }
 
 // Generate thumbnail using fast_image_resize for better performance
-fn generate_thumbnail(data: &[u8], config: &ProcessingConfig) -> CoreResult<Vec<u8>> {
+fn generate_thumbnail(data: &[u8], config: &ProcessingConfig, grayscale: bool) -> CoreResult<Vec<u8>> {
     let img = image::load_from_memory(data)
         .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))?;
-    
+    encode_thumbnail(&img, config.thumbnail_size, config.jpeg_quality, grayscale)
+}
+
+/// 把已解码的图片缩放到长边不超过max_edge并编码为JPEG（标记预览图也用它）
+pub(crate) fn encode_thumbnail(img: &DynamicImage, max_edge: u32, quality: u8, grayscale: bool) -> CoreResult<Vec<u8>> {
     // Calculate new dimensions maintaining aspect ratio
     let (orig_width, orig_height) = img.dimensions();
     
     // If image is already smaller than thumbnail size, just re-encode as JPEG
-    if orig_width <= config.thumbnail_size && orig_height <= config.thumbnail_size {
-        // Convert to RGB and encode as JPEG for consistency
-        let rgb_img = img.to_rgb8();
+    if orig_width <= max_edge && orig_height <= max_edge {
+        // Convert to RGB (grayscale pages to L8) and encode as JPEG for consistency
+        let converted = if grayscale {
+            image::DynamicImage::ImageLuma8(img.to_luma8())
+        } else {
+            image::DynamicImage::ImageRgb8(img.to_rgb8())
+        };
         let mut output = Vec::new();
         let mut cursor = Cursor::new(&mut output);
-        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, config.jpeg_quality);
-        image::DynamicImage::ImageRgb8(rgb_img).write_with_encoder(encoder)
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, quality);
+        converted.write_with_encoder(encoder)
             .map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode thumbnail: {}", e)))?;
         return Ok(output);
     }
     
     // Calculate thumbnail dimensions
-    let ratio = (config.thumbnail_size as f32 / orig_width.max(orig_height) as f32).min(1.0);
-    let new_width = (orig_width as f32 * ratio) as u32;
-    let new_height = (orig_height as f32 * ratio) as u32;
+    let ratio = (max_edge as f32 / orig_width.max(orig_height) as f32).min(1.0);
+    let new_width = ((orig_width as f32 * ratio) as u32).max(1);
+    let new_height = ((orig_height as f32 * ratio) as u32).max(1);
     
     // Always use optimized fast_image_resize for all images
-    let thumbnail_data = generate_thumbnail_fast_resize_optimized(&img, new_width, new_height, config.jpeg_quality)?;
-    
-    Ok(thumbnail_data)
+    generate_thumbnail_fast_resize_optimized(img, new_width, new_height, quality, grayscale)
 }
 
 // Optimized thumbnail generation using fast_image_resize with minimal memory copies
-fn generate_thumbnail_fast_resize_optimized(img: &DynamicImage, new_width: u32, new_height: u32, quality: u8) -> CoreResult<Vec<u8>> {
+fn generate_thumbnail_fast_resize_optimized(img: &DynamicImage, new_width: u32, new_height: u32, quality: u8, grayscale: bool) -> CoreResult<Vec<u8>> {
     use fast_image_resize as fr;
     
     let (orig_width, orig_height) = img.dimensions();
     
-    // Directly work with RGB format to avoid alpha channel overhead
-    let rgb_img = img.to_rgb8();
+    // Directly work with RGB format to avoid alpha channel overhead; grayscale pages use a single channel
+    let (pixels, pixel_type, color_type) = if grayscale {
+        (img.to_luma8().into_raw(), fr::PixelType::U8, image::ExtendedColorType::L8)
+    } else {
+        (img.to_rgb8().into_raw(), fr::PixelType::U8x3, image::ExtendedColorType::Rgb8)
+    };
     let src_image = fr::images::Image::from_vec_u8(
         orig_width,
         orig_height,
-        rgb_img.into_raw(),
-        fr::PixelType::U8x3,  // Use RGB instead of RGBA
+        pixels,
+        pixel_type,
     ).map_err(|e| CoreError::ImageProcessingError(format!("Failed to create source image: {:?}", e)))?;
     
-    // Create destination image with RGB format
+    // Create destination image with the same pixel format
     let mut dst_image = fr::images::Image::new(
         new_width,
         new_height,
-        fr::PixelType::U8x3,  // Use RGB instead of RGBA
+        pixel_type,
     );
     
     // Use high-quality Convolution algorithm with Lanczos3 filter for better thumbnail quality
@@ -259,7 +416,7 @@ fn generate_thumbnail_fast_resize_optimized(img: &DynamicImage, new_width: u32,
         dst_image.buffer(),  // Use buffer directly
         new_width,
         new_height,
-        image::ExtendedColorType::Rgb8,
+        color_type,
     ).map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode thumbnail: {}", e)))?;
     
     Ok(output)
@@ -270,22 +427,50 @@ fn generate_thumbnail_internal(image_id: ImageId) -> CoreResult<()> {
     let image = APP_STATE.get_image(image_id)?
         .ok_or_else(|| CoreError::NotFound(format!("Image with id {} not found", image_id.0)))?;
     
-    // Read image data
-    let image_data = image.data.read_data()?;
-    
-    // Generate thumbnail
     let config = ProcessingConfig::default();
-    let thumbnail_data = generate_thumbnail(&image_data, &config)?;
     
-    // Update dimensions if needed
-    if image.metadata.width.is_none() || image.metadata.height.is_none() {
-        if let Some((width, height)) = image.data.get_format().and_then(|format| {
-            crate::storage::dimension_extractor::extract_dimensions_from_bytes(&image_data, &format)
-        }) {
-            crate::storage::image::update_image_dimensions_storage(image_id, width, height)?;
-        }
+    // 先查磁盘缓存（仅桌面端设置了缓存目录时生效）
+    let cache_key = thumbnail_cache::cache_key(&image, config.thumbnail_size);
+    let cached = cache_key.as_deref().and_then(thumbnail_cache::load);
+    
+    if cache_key.is_some() {
+        let stats = thumbnail_cache::stats();
+        crate::common::Logger::debug_with_data("Thumbnail cache lookup", serde_json::json!({
+            "image_id": image_id.0,
+            "hit": cached.is_some(),
+            "hits": stats.hits,
+            "misses": stats.misses
+        }));
     }
     
+    let thumbnail_data = match cached {
+        Some(data) => data,
+        None => {
+            // Read image data
+            let image_data = super::byte_cache::read_image_bytes(&image)?;
+            
+            // Generate thumbnail
+            let grayscale = matches!(image.metadata.color_mode, Some(ColorMode::Grayscale | ColorMode::GrayscaleAlpha));
+            let thumbnail_data = generate_thumbnail(&image_data, &config, grayscale)?;
+            
+            // Update dimensions and file info if needed
+            if image.metadata.width.is_none() || image.metadata.height.is_none() || image.metadata.size.is_none() {
+                if let Some(info) = image.data.get_format().and_then(|format| {
+                    crate::storage::dimension_extractor::extract_image_info_from_bytes(&image_data, &format)
+                }) {
+                    crate::storage::image::update_image_file_info_storage(image_id, &info, image_data.len() as u64)?;
+                }
+            }
+            
+            if let Some(key) = cache_key.as_deref()
+                && let Err(e) = thumbnail_cache::store(key, &thumbnail_data)
+            {
+                crate::common::Logger::warn(&format!("Failed to write thumbnail cache: {}", e));
+            }
+            thumbnail_data
+        }
+    };
+    
     let thumbnail = ThumbnailData {
         image_id,
         data: thumbnail_data.clone(),
@@ -342,4 +527,47 @@ pub fn clear_all_thumbnails() -> CoreResult<()> {
     let mut storage = APP_STATE.thumbnails.write()?;
     storage.clear();
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefetch_targets_nearest_first() {
+        let order: Vec<ImageId> = (1..=6).map(ImageId::from).collect();
+        let ids = |v: &[u32]| v.iter().map(|&id| ImageId::from(id)).collect::<Vec<_>>();
+
+        assert_eq!(prefetch_targets(&order, ImageId::from(3), 2), ids(&[3, 4, 2, 5, 1]));
+        assert_eq!(prefetch_targets(&order, ImageId::from(1), 2), ids(&[1, 2, 3]));
+        assert_eq!(prefetch_targets(&order, ImageId::from(6), 10), ids(&[6, 5, 4, 3, 2, 1]));
+        assert!(prefetch_targets(&order, ImageId::from(42), 2).is_empty());
+    }
+    use crate::storage::image::add_image_from_binary_storage;
+    use crate::storage::project::{
+        add_image_to_project_storage, create_project_storage, set_image_pinned_storage,
+        update_thumbnail_order_storage,
+    };
+
+    #[test]
+    fn test_thumbnail_order_pinned_first_then_sorted() {
+        let project_id = create_project_storage("thumbnail_order".to_string()).unwrap();
+        let mut ids = Vec::new();
+        for name in ["c.png", "a.png", "b.png"] {
+            let id = add_image_from_binary_storage(ImageFormat::Png, vec![0u8; 4], Some(name.to_string())).unwrap();
+            add_image_to_project_storage(project_id, id).unwrap();
+            ids.push(id);
+        }
+        let (c, a, b) = (ids[0], ids[1], ids[2]);
+
+        assert_eq!(get_thumbnail_order(project_id).unwrap(), vec![c, a, b]);
+
+        update_thumbnail_order_storage(project_id, ThumbnailOrder::Name).unwrap();
+        assert_eq!(get_thumbnail_order(project_id).unwrap(), vec![a, b, c]);
+
+        set_image_pinned_storage(project_id, c, true).unwrap();
+        assert_eq!(get_thumbnail_order(project_id).unwrap(), vec![c, a, b]);
+
+        set_image_pinned_storage(project_id, c, false).unwrap();
+        assert_eq!(get_thumbnail_order(project_id).unwrap(), vec![a, b, c]);
+    }
+}
@@ -0,0 +1,267 @@
+// Thumbnail disk cache - 桌面端把生成的缩略图持久化到应用数据目录，下次打开项目时直接读取
+// 缓存键: FilePath用路径+大小+修改时间，其它数据用内容MD5；超过容量上限时按最近访问时间淘汰
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use md5::{Digest, Md5};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreResult, Logger};
+use crate::storage::image::Image;
+use crate::storage::image_data::ImageData;
+
+/// 默认缓存容量上限（字节）
+pub const DEFAULT_THUMBNAIL_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+// 缓存文件头: magic + 数据长度(u32 LE)，长度不符视为损坏
+const CACHE_MAGIC: &[u8; 4] = b"BFTC";
+const CACHE_EXTENSION: &str = "thumb";
+
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThumbnailCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+}
+
+struct CacheEntry {
+    size: u64,
+    last_access: SystemTime,
+}
+
+struct ThumbnailDiskCache {
+    dir: Option<PathBuf>,
+    max_bytes: u64,
+    entries: HashMap<String, CacheEntry>,
+    total_bytes: u64,
+}
+
+impl ThumbnailDiskCache {
+    fn path_for(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.{}", key, CACHE_EXTENSION)))
+    }
+
+    fn remove_entry(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.total_bytes = self.total_bytes.saturating_sub(entry.size);
+        }
+        if let Some(path) = self.path_for(key) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    fn evict_to_fit(&mut self) {
+        while self.total_bytes > self.max_bytes {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.remove_entry(&key),
+                None => break,
+            }
+        }
+    }
+}
+
+static DISK_CACHE: Lazy<RwLock<ThumbnailDiskCache>> = Lazy::new(|| RwLock::new(ThumbnailDiskCache {
+    dir: None,
+    max_bytes: DEFAULT_THUMBNAIL_CACHE_MAX_BYTES,
+    entries: HashMap::new(),
+    total_bytes: 0,
+}));
+
+/// 设置缓存目录并索引已有的缓存文件（桌面端启动时调用）
+pub fn set_thumbnail_cache_dir(dir: PathBuf) -> CoreResult<()> {
+    std::fs::create_dir_all(&dir)?;
+
+    let mut entries = HashMap::new();
+    let mut total_bytes = 0;
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(CACHE_EXTENSION) {
+            continue;
+        }
+        let (Some(key), Ok(metadata)) = (path.file_stem().and_then(|s| s.to_str()), std::fs::metadata(&path)) else {
+            continue;
+        };
+        let last_access = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        total_bytes += metadata.len();
+        entries.insert(key.to_string(), CacheEntry { size: metadata.len(), last_access });
+    }
+
+    let mut cache = DISK_CACHE.write()?;
+    cache.dir = Some(dir);
+    cache.entries = entries;
+    cache.total_bytes = total_bytes;
+    cache.evict_to_fit();
+    Ok(())
+}
+
+pub fn set_thumbnail_cache_max_bytes(max_bytes: u64) -> CoreResult<()> {
+    let mut cache = DISK_CACHE.write()?;
+    cache.max_bytes = max_bytes;
+    cache.evict_to_fit();
+    Ok(())
+}
+
+/// 计算图片的缓存键，无法确定图片内容时返回None（不缓存）
+pub fn cache_key(image: &Image, thumbnail_size: u32) -> Option<String> {
+    let mut hasher = Md5::new();
+    match &image.data {
+        ImageData::FilePath(path) => {
+            let metadata = std::fs::metadata(path).ok()?;
+            let mtime = metadata.modified().ok()?
+                .duration_since(SystemTime::UNIX_EPOCH).ok()?
+                .as_nanos();
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update(metadata.len().to_le_bytes());
+            hasher.update(mtime.to_le_bytes());
+        }
+        ImageData::Binary { data, .. } => match &image.metadata.checksum {
+            Some(checksum) => hasher.update(checksum.as_bytes()),
+            None => hasher.update(data.as_slice()),
+        },
+        ImageData::SharedBuffer { .. } => hasher.update(image.metadata.checksum.as_ref()?.as_bytes()),
+    }
+    hasher.update(thumbnail_size.to_le_bytes());
+    // 检测出灰度后缩略图改为灰度，不能再用之前的彩色缓存
+    if let Some(color_mode) = image.metadata.color_mode {
+        hasher.update([color_mode.channels()]);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn decode_cache_file(bytes: &[u8]) -> Option<Vec<u8>> {
+    if bytes.len() < 8 || &bytes[0..4] != CACHE_MAGIC {
+        return None;
+    }
+    let length = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let data = &bytes[8..];
+    // JPEG必须以SOI开头、EOI结尾，防止写入中断留下的截断文件
+    if data.len() != length || length < 4 || data[0..2] != [0xFF, 0xD8] || data[length - 2..] != [0xFF, 0xD9] {
+        return None;
+    }
+    Some(data.to_vec())
+}
+
+fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// 读取缓存，命中时返回缩略图数据；损坏的缓存文件会被删除并视为未命中
+pub fn load(key: &str) -> Option<Vec<u8>> {
+    let path = {
+        let cache = DISK_CACHE.read().ok()?;
+        cache.path_for(key)?
+    };
+
+    let result = std::fs::read(&path).ok().and_then(|bytes| {
+        let decoded = decode_cache_file(&bytes);
+        if decoded.is_none() {
+            Logger::warn(&format!("Discarding corrupted thumbnail cache file: {:?}", path));
+        }
+        decoded
+    });
+
+    if let Ok(mut cache) = DISK_CACHE.write() {
+        match &result {
+            Some(_) => {
+                if let Some(entry) = cache.entries.get_mut(key) {
+                    entry.last_access = SystemTime::now();
+                }
+                touch(&path);
+            }
+            None => cache.remove_entry(key),
+        }
+    }
+
+    match result {
+        Some(data) => {
+            CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+            Some(data)
+        }
+        None => {
+            CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+}
+
+/// 写入缓存（先写临时文件再重命名，避免留下半截文件）
+pub fn store(key: &str, data: &[u8]) -> CoreResult<()> {
+    let mut cache = DISK_CACHE.write()?;
+    let Some(path) = cache.path_for(key) else {
+        return Ok(());
+    };
+
+    let mut bytes = Vec::with_capacity(data.len() + 8);
+    bytes.extend_from_slice(CACHE_MAGIC);
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, &bytes)?;
+    std::fs::rename(&tmp_path, &path)?;
+
+    if let Some(old) = cache.entries.insert(key.to_string(), CacheEntry {
+        size: bytes.len() as u64,
+        last_access: SystemTime::now(),
+    }) {
+        cache.total_bytes = cache.total_bytes.saturating_sub(old.size);
+    }
+    cache.total_bytes += bytes.len() as u64;
+    cache.evict_to_fit();
+    Ok(())
+}
+
+/// 清空磁盘缓存，返回删除的文件数
+pub fn clear() -> CoreResult<usize> {
+    let mut cache = DISK_CACHE.write()?;
+    let keys: Vec<String> = cache.entries.keys().cloned().collect();
+    for key in &keys {
+        cache.remove_entry(key);
+    }
+    cache.total_bytes = 0;
+    Ok(keys.len())
+}
+
+pub fn stats() -> ThumbnailCacheStats {
+    let (entries, total_bytes, max_bytes) = match DISK_CACHE.read() {
+        Ok(cache) => (cache.entries.len(), cache.total_bytes, cache.max_bytes),
+        Err(_) => (0, 0, 0),
+    };
+    ThumbnailCacheStats {
+        hits: CACHE_HITS.load(Ordering::Relaxed),
+        misses: CACHE_MISSES.load(Ordering::Relaxed),
+        entries,
+        total_bytes,
+        max_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrupted_cache_file_rejected() {
+        let jpeg = vec![0xFF, 0xD8, 1, 2, 3, 0xFF, 0xD9];
+        let mut bytes = CACHE_MAGIC.to_vec();
+        bytes.extend_from_slice(&(jpeg.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&jpeg);
+
+        assert_eq!(decode_cache_file(&bytes), Some(jpeg));
+        // 截断的文件
+        assert_eq!(decode_cache_file(&bytes[..bytes.len() - 2]), None);
+        assert_eq!(decode_cache_file(b"junk"), None);
+    }
+}
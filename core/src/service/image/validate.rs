@@ -0,0 +1,217 @@
+// 导入前的图片校验 - 在添加到项目之前给出具体的失败原因（扩展名、文件头、能否解码、大小）
+use std::fmt;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::common::dto::image::ImageFormat;
+
+/// 单张图片文件的最大字节数
+pub const MAX_IMAGE_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// 图片导入失败的原因，message为面向用户的提示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ImportError {
+    /// 扩展名或文件格式不在支持列表中
+    UnsupportedFormat { name: String, message: String },
+    /// 文件头是图片但无法完整解码（截断、损坏）
+    Corrupt { name: String, message: String },
+    /// 超过文件大小限制
+    TooLarge { name: String, size: u64, limit: u64, message: String },
+    /// 文件内容不是图片（比如改了扩展名的文本文件）
+    NotAnImage { name: String, message: String },
+}
+
+impl ImportError {
+    fn unsupported(name: &str, detail: &str) -> Self {
+        Self::UnsupportedFormat {
+            name: name.to_string(),
+            message: format!("{}：不支持的图片格式{}，请使用 PNG、JPG、GIF、BMP 或 WebP", name, detail),
+        }
+    }
+
+    fn corrupt(name: &str, detail: impl fmt::Display) -> Self {
+        Self::Corrupt {
+            name: name.to_string(),
+            message: format!("{}：图片文件已损坏或不完整（{}）", name, detail),
+        }
+    }
+
+    fn too_large(name: &str, size: u64) -> Self {
+        Self::TooLarge {
+            name: name.to_string(),
+            size,
+            limit: MAX_IMAGE_FILE_SIZE,
+            message: format!("{}：文件过大（{} MB），单张图片不能超过 {} MB",
+                name, size / 1024 / 1024, MAX_IMAGE_FILE_SIZE / 1024 / 1024),
+        }
+    }
+
+    fn not_an_image(name: &str) -> Self {
+        Self::NotAnImage {
+            name: name.to_string(),
+            message: format!("{}：文件内容不是图片", name),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnsupportedFormat { .. } => "unsupported_format",
+            Self::Corrupt { .. } => "corrupt",
+            Self::TooLarge { .. } => "too_large",
+            Self::NotAnImage { .. } => "not_an_image",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::UnsupportedFormat { message, .. }
+            | Self::Corrupt { message, .. }
+            | Self::TooLarge { message, .. }
+            | Self::NotAnImage { message, .. } => message,
+        }
+    }
+
+    /// 序列化为JSON字符串，通过API的String错误通道传给前端
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| self.message().to_string())
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// 待校验的图片：文件路径或已读入的数据（Web端）
+pub enum ImageCandidate<'a> {
+    Path(&'a Path),
+    Bytes { name: &'a str, data: &'a [u8] },
+}
+
+fn format_from_magic(data: &[u8]) -> Option<Result<ImageFormat, image::ImageFormat>> {
+    let detected = image::guess_format(data).ok()?;
+    Some(match detected {
+        image::ImageFormat::Jpeg => Ok(ImageFormat::Jpeg),
+        image::ImageFormat::Png => Ok(ImageFormat::Png),
+        image::ImageFormat::Gif => Ok(ImageFormat::Gif),
+        image::ImageFormat::WebP => Ok(ImageFormat::Webp),
+        image::ImageFormat::Bmp => Ok(ImageFormat::Bmp),
+        other => Err(other),
+    })
+}
+
+fn check_extension(name: &str) -> Result<(), ImportError> {
+    let ext = Path::new(name).extension().and_then(|e| e.to_str()).unwrap_or("");
+    if ImageFormat::from_extension(ext).is_none() {
+        let detail = if ext.is_empty() { String::new() } else { format!(" .{}", ext) };
+        return Err(ImportError::unsupported(name, &detail));
+    }
+    Ok(())
+}
+
+/// 校验数据是否为可导入的图片，返回按文件头识别出的格式
+pub fn validate_image_bytes(name: &str, data: &[u8]) -> Result<ImageFormat, ImportError> {
+    check_extension(name)?;
+    validate_image_content(name, data)
+}
+
+/// 只按内容校验，不检查扩展名（粘贴、拖入的数据可能没有文件名）
+pub fn validate_image_content(name: &str, data: &[u8]) -> Result<ImageFormat, ImportError> {
+    if data.len() as u64 > MAX_IMAGE_FILE_SIZE {
+        return Err(ImportError::too_large(name, data.len() as u64));
+    }
+
+    // 以文件头为准，扩展名与实际格式不一致时仍可导入
+    let format = match format_from_magic(data) {
+        Some(Ok(format)) => format,
+        Some(Err(other)) => return Err(ImportError::unsupported(name, &format!(" {:?}", other))),
+        None => return Err(ImportError::not_an_image(name)),
+    };
+
+    // 完整解码一次，截断的文件在这里才会暴露
+    let image_format = image::ImageFormat::from_extension(format.extension())
+        .ok_or_else(|| ImportError::unsupported(name, ""))?;
+    image::load_from_memory_with_format(data, image_format)
+        .map_err(|e| ImportError::corrupt(name, e))?;
+    Ok(format)
+}
+
+pub fn validate_image_path(path: &Path) -> Result<ImageFormat, ImportError> {
+    let name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    check_extension(&name)?;
+
+    // 先看文件大小，避免把超大的文件读进内存
+    let size = std::fs::metadata(path)
+        .map_err(|e| ImportError::corrupt(&name, e))?
+        .len();
+    if size > MAX_IMAGE_FILE_SIZE {
+        return Err(ImportError::too_large(&name, size));
+    }
+    let data = std::fs::read(path).map_err(|e| ImportError::corrupt(&name, e))?;
+    validate_image_bytes(&name, &data)
+}
+
+pub fn validate_image_candidate(candidate: ImageCandidate<'_>) -> Result<ImageFormat, ImportError> {
+    match candidate {
+        ImageCandidate::Path(path) => validate_image_path(path),
+        ImageCandidate::Bytes { name, data } => validate_image_bytes(name, data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_bytes() -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(16, 16, image::Rgb([200, 100, 50]));
+        let mut data = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_valid_png_passes() {
+        assert_eq!(validate_image_bytes("p01.png", &png_bytes()), Ok(ImageFormat::Png));
+        // 扩展名写错但内容是PNG
+        assert_eq!(validate_image_bytes("p01.jpg", &png_bytes()), Ok(ImageFormat::Png));
+    }
+
+    #[test]
+    fn test_renamed_text_file() {
+        let text = "这是一个文本文件\n".repeat(10);
+        assert!(matches!(validate_image_bytes("notes.txt", text.as_bytes()), Err(ImportError::UnsupportedFormat { .. })));
+        let err = validate_image_bytes("notes.png", text.as_bytes()).unwrap_err();
+        assert!(matches!(err, ImportError::NotAnImage { .. }));
+        assert!(err.message().contains("notes.png"));
+    }
+
+    #[test]
+    fn test_truncated_png() {
+        let data = png_bytes();
+        let err = validate_image_bytes("broken.png", &data[..data.len() / 2]).unwrap_err();
+        assert!(matches!(err, ImportError::Corrupt { .. }));
+
+        let json: serde_json::Value = serde_json::from_str(&err.to_json_string()).unwrap();
+        assert_eq!(json["kind"], "corrupt");
+        assert_eq!(json["name"], "broken.png");
+    }
+
+    #[test]
+    fn test_path_candidate() {
+        let dir = std::env::temp_dir().join(format!("bf_validate_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("page.png");
+        std::fs::write(&path, png_bytes()).unwrap();
+        assert!(validate_image_candidate(ImageCandidate::Path(&path)).is_ok());
+
+        let renamed = dir.join("readme.png");
+        std::fs::write(&renamed, b"hello").unwrap();
+        assert!(matches!(validate_image_candidate(ImageCandidate::Path(&renamed)), Err(ImportError::NotAnImage { .. })));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
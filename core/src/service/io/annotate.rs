@@ -0,0 +1,418 @@
+// Annotated export - 导出带标记编号的嵌字参考图，每次只解码一张图片以控制内存
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, ProjectId, ReadingDirection};
+use crate::common::dto::marker::PixelGeometry;
+use crate::common::events::schema::{AnnotatedExportProgressEvent, BusinessEvent};
+use crate::storage::marker::Marker;
+use crate::storage::state::APP_STATE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotateFormat {
+    #[default]
+    Jpeg,
+    Png,
+}
+
+impl AnnotateFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AnnotateFormat::Jpeg => "jpg",
+            AnnotateFormat::Png => "png",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnotateOptions {
+    /// 标记和编号的大小倍数，1.0时按图片短边自适应
+    pub scale: f32,
+    /// 标记颜色，"#RRGGBB"
+    pub color: String,
+    /// 标记的不透明度 0-1
+    pub opacity: f32,
+    pub format: AnnotateFormat,
+    pub jpeg_quality: u8,
+    /// 按项目的阅读方向重新编号（只影响导出的图片，不修改标记序号）；默认使用标记自身的序号，与文本导出一致
+    pub reading_order: bool,
+}
+
+impl Default for AnnotateOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            color: "#FF3B30".to_string(),
+            opacity: 0.85,
+            format: AnnotateFormat::Jpeg,
+            jpeg_quality: 90,
+            reading_order: false,
+        }
+    }
+}
+
+impl AnnotateOptions {
+    fn rgb(&self) -> CoreResult<Rgb<u8>> {
+        let hex = self.color.trim_start_matches('#');
+        let invalid = || CoreError::ValidationFailed {
+            field: "color".to_string(),
+            reason: format!("无效的颜色: {}", self.color),
+        };
+        if hex.len() != 6 {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+    }
+}
+
+// 5x7点阵数字字体，每行低5位从左到右
+const DIGIT_GLYPHS: [[u8; 7]; 10] = [
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+];
+// A-Z，用于绘制标记标签
+const LETTER_GLYPHS: [[u8; 7]; 26] = [
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+];
+const GLYPH_WIDTH: i64 = 5;
+const GLYPH_HEIGHT: i64 = 7;
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// 在RGB图片上按不透明度混合绘制
+struct Canvas {
+    image: RgbImage,
+    // 描边宽度（像素）
+    thickness: i64,
+}
+
+impl Canvas {
+    fn blend(&mut self, x: i64, y: i64, color: Rgb<u8>, alpha: f32) {
+        if x < 0 || y < 0 || x >= self.image.width() as i64 || y >= self.image.height() as i64 {
+            return;
+        }
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        for c in 0..3 {
+            pixel.0[c] = (pixel.0[c] as f32 * (1.0 - alpha) + color.0[c] as f32 * alpha).round() as u8;
+        }
+    }
+
+    fn fill_rect(&mut self, x: i64, y: i64, width: i64, height: i64, color: Rgb<u8>, alpha: f32) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, color, alpha);
+            }
+        }
+    }
+
+    fn stroke_rect(&mut self, x: i64, y: i64, width: i64, height: i64, color: Rgb<u8>, alpha: f32) {
+        let t = self.thickness.min(width / 2).min(height / 2).max(1);
+        self.fill_rect(x, y, width, t, color, alpha);
+        self.fill_rect(x, y + height - t, width, t, color, alpha);
+        self.fill_rect(x, y + t, t, height - 2 * t, color, alpha);
+        self.fill_rect(x + width - t, y + t, t, height - 2 * t, color, alpha);
+    }
+
+    /// 以(cx, cy)为圆心填充半径在[inner, outer]之间的圆环，inner为0时为实心圆
+    fn fill_ring(&mut self, cx: i64, cy: i64, inner: f32, outer: f32, color: Rgb<u8>, alpha: f32) {
+        let r = outer.ceil() as i64;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let d = ((dx * dx + dy * dy) as f32).sqrt();
+                if d <= outer && d >= inner {
+                    self.blend(cx + dx, cy + dy, color, alpha);
+                }
+            }
+        }
+    }
+
+    fn text_size(text: &str, pixel: i64) -> (i64, i64) {
+        let n = text.len() as i64;
+        ((n * (GLYPH_WIDTH + 1) - 1) * pixel, GLYPH_HEIGHT * pixel)
+    }
+
+    /// 绘制数字、大写字母和空格，其他字符留空
+    fn draw_text(&mut self, text: &str, x: i64, y: i64, pixel: i64, color: Rgb<u8>, alpha: f32) {
+        for (i, c) in text.bytes().enumerate() {
+            let glyph = match c {
+                b'0'..=b'9' => &DIGIT_GLYPHS[(c - b'0') as usize],
+                b'A'..=b'Z' => &LETTER_GLYPHS[(c - b'A') as usize],
+                _ => continue,
+            };
+            let gx = x + i as i64 * (GLYPH_WIDTH + 1) * pixel;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        self.fill_rect(gx + col * pixel, y + row as i64 * pixel, pixel, pixel, color, alpha);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 标签只能用点阵字体绘制ASCII字母和数字，其他字符去掉；全部去掉时不显示
+fn tag_label(marker: &Marker) -> Option<String> {
+    let tag: String = marker.style.tag.as_deref()?
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+        .collect::<String>()
+        .trim()
+        .to_ascii_uppercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// 在解码后的图片上绘制标记和编号，有标签时显示在编号旁边
+pub fn draw_markers(image: RgbImage, markers: &[Marker], options: &AnnotateOptions) -> CoreResult<RgbImage> {
+    let color = options.rgb()?;
+    let alpha = options.opacity.clamp(0.0, 1.0);
+    let (width, height) = image.dimensions();
+    // 按短边自适应，保证小图和大图上的编号看起来差不多大
+    let unit = (width.min(height) as f32 / 800.0).max(0.5) * options.scale.max(0.1);
+    let pixel = (3.0 * unit).round().max(1.0) as i64;
+    let thickness = (3.0 * unit).round().max(1.0) as i64;
+    let mut canvas = Canvas { image, thickness };
+
+    let padding = pixel * 2;
+    for marker in markers {
+        let number = marker.image_index.to_string();
+        let tag = tag_label(marker);
+        let (text_w, text_h) = Canvas::text_size(&number, pixel);
+        match marker.geometry.to_pixels(width, height) {
+            PixelGeometry::Point { x, y } => {
+                let (cx, cy) = (x as i64, y as i64);
+                let radius = (text_w.max(text_h) as f32 / 2.0 + 3.0 * pixel as f32).max(12.0 * unit);
+                canvas.fill_ring(cx, cy, 0.0, radius, WHITE, alpha * 0.8);
+                canvas.fill_ring(cx, cy, radius - thickness as f32, radius, color, alpha);
+                canvas.draw_text(&number, cx - text_w / 2, cy - text_h / 2, pixel, color, alpha);
+                // 标签放在圆右侧的色块里
+                if let Some(tag) = &tag {
+                    let (tag_w, tag_h) = Canvas::text_size(tag, pixel);
+                    let chip_x = cx + radius.ceil() as i64 + pixel;
+                    let chip_y = cy - tag_h / 2 - padding;
+                    canvas.fill_rect(chip_x, chip_y, tag_w + padding * 2, tag_h + padding * 2, color, alpha);
+                    canvas.draw_text(tag, chip_x + padding, chip_y + padding, pixel, WHITE, 1.0);
+                }
+            }
+            PixelGeometry::Rectangle { x, y, width: w, height: h } => {
+                let (x, y) = (x as i64, y as i64);
+                canvas.stroke_rect(x, y, w as i64, h as i64, color, alpha);
+                // 编号和标签放在左上角的色块里
+                let label = match &tag {
+                    Some(tag) => format!("{} {}", number, tag),
+                    None => number,
+                };
+                let (label_w, _) = Canvas::text_size(&label, pixel);
+                canvas.fill_rect(x, y, label_w + padding * 2, text_h + padding * 2, color, alpha);
+                canvas.draw_text(&label, x + padding, y + padding, pixel, WHITE, 1.0);
+            }
+        }
+    }
+
+    Ok(canvas.image)
+}
+
+fn encode(image: &RgbImage, options: &AnnotateOptions) -> CoreResult<Vec<u8>> {
+    let mut output = Vec::new();
+    let result = match options.format {
+        AnnotateFormat::Jpeg => {
+            let quality = options.jpeg_quality.clamp(1, 100);
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, quality).encode_image(image)
+        }
+        AnnotateFormat::Png => image.write_to(&mut std::io::Cursor::new(&mut output), image::ImageFormat::Png),
+    };
+    result.map_err(|e| CoreError::ImageProcessingError(format!("Failed to encode annotated image: {}", e)))?;
+    Ok(output)
+}
+
+/// 导出文件名：原图名去掉扩展名后加上_annotated
+fn output_name(name: Option<&str>, position: usize, format: AnnotateFormat) -> String {
+    let stem = name
+        .map(|n| std::path::Path::new(n).file_stem().and_then(|s| s.to_str()).unwrap_or(n).to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| format!("image_{:03}", position + 1));
+    format!("{}_annotated.{}", stem, format.extension())
+}
+
+/// 按阅读顺序排列标记，并把副本的序号改为在阅读顺序中的位置
+fn number_in_reading_order(markers: Vec<Marker>, direction: ReadingDirection) -> Vec<Marker> {
+    let order = crate::service::marker::reading_order(&markers, direction);
+    let mut by_id: std::collections::HashMap<_, _> = markers.into_iter().map(|m| (m.id, m)).collect();
+    order.into_iter()
+        .filter_map(|id| by_id.remove(&id))
+        .zip(1u32..)
+        .map(|(mut marker, index)| {
+            marker.image_index = index;
+            marker
+        })
+        .collect()
+}
+
+/// 生成单张图片的参考图，返回(文件名, 编码后的数据)
+pub fn annotate_image(image_id: ImageId, position: usize, options: &AnnotateOptions) -> CoreResult<(String, Vec<u8>)> {
+    let image = APP_STATE.get_image(image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image with id {} not found", image_id.0)))?;
+    let name = image.metadata.name.clone().or_else(|| match &image.data {
+        crate::storage::ImageData::FilePath(path) => path.file_name().map(|n| n.to_string_lossy().to_string()),
+        _ => None,
+    });
+
+    let decoded = {
+        let data = image.data.read_data()?;
+        image::load_from_memory(&data)
+            .map_err(|e| CoreError::ImageProcessingError(format!("Failed to decode image: {}", e)))?
+            .to_rgb8()
+    };
+
+    let mut markers = crate::storage::marker::get_image_markers_storage(image_id)?;
+    if options.reading_order {
+        let direction = crate::service::marker::reading_order::direction_for_image(image_id)?;
+        markers = number_in_reading_order(markers, direction);
+    } else {
+        markers.sort_by_key(|m| m.image_index);
+    }
+    let annotated = draw_markers(decoded, &markers, options)?;
+    Ok((output_name(name.as_deref(), position, options.format), encode(&annotated, options)?))
+}
+
+fn emit_progress(project_id: ProjectId, current: usize, total: usize, name: &str) {
+    let _ = AnnotatedExportProgressEvent::new(project_id, current, total, name.to_string()).emit();
+}
+
+/// 逐张生成项目所有图片的参考图，返回内存中的结果（WASM端由JS打包下载）
+pub fn export_annotated_images(project_id: ProjectId, options: &AnnotateOptions) -> CoreResult<Vec<(String, Vec<u8>)>> {
+    let image_ids = project_image_ids(project_id)?;
+    let total = image_ids.len();
+    let mut results = Vec::with_capacity(total);
+    for (position, image_id) in image_ids.into_iter().enumerate() {
+        let (name, data) = annotate_image(image_id, position, options)?;
+        emit_progress(project_id, position + 1, total, &name);
+        results.push((name, data));
+    }
+    Ok(results)
+}
+
+/// 逐张生成并直接写入目录（桌面端），每张写完即释放，返回写入的文件路径
+#[cfg(not(feature = "wasm"))]
+pub fn export_annotated_images_to_dir(project_id: ProjectId, dir: &std::path::Path, options: &AnnotateOptions) -> CoreResult<Vec<String>> {
+    let image_ids = project_image_ids(project_id)?;
+    std::fs::create_dir_all(dir)?;
+    let total = image_ids.len();
+    let mut written = Vec::with_capacity(total);
+    for (position, image_id) in image_ids.into_iter().enumerate() {
+        let (name, data) = annotate_image(image_id, position, options)?;
+        let path = dir.join(&name);
+        std::fs::write(&path, data)?;
+        emit_progress(project_id, position + 1, total, &name);
+        written.push(path.to_string_lossy().to_string());
+    }
+    Ok(written)
+}
+
+fn project_image_ids(project_id: ProjectId) -> CoreResult<Vec<ImageId>> {
+    Ok(APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?
+        .image_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::MarkerId;
+
+    #[test]
+    fn test_draw_markers_paints_point_and_rectangle() {
+        let image = RgbImage::from_pixel(400, 300, Rgb([0, 0, 0]));
+        let markers = vec![
+            Marker::new_point(MarkerId(1), ImageId(1), 25.0, 50.0, 1),
+            Marker::new_rectangle(MarkerId(2), ImageId(1), 50.0, 10.0, 40.0, 60.0, 12),
+        ];
+        let options = AnnotateOptions { opacity: 1.0, color: "#00FF00".to_string(), ..Default::default() };
+        let annotated = draw_markers(image, &markers, &options).unwrap();
+
+        // 圆心附近为白底，圆环和矩形边框为标记颜色，远处保持原样
+        assert_eq!(annotated.get_pixel(100, 150).0[0], annotated.get_pixel(100, 150).0[2]);
+        assert!(annotated.pixels().any(|p| p.0 == [0, 255, 0]));
+        assert_eq!(annotated.get_pixel(200 + 80, 30 + 90).0, [0, 0, 0]);
+        assert_eq!(annotated.get_pixel(200 + 80, 30).0, [0, 255, 0]);
+        assert_eq!(annotated.get_pixel(5, 5).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_tag_is_drawn_next_to_number() {
+        let blank = RgbImage::from_pixel(400, 300, Rgb([0, 0, 0]));
+        let mut marker = Marker::new_point(MarkerId(1), ImageId(1), 25.0, 50.0, 1);
+        let options = AnnotateOptions { opacity: 1.0, color: "#00FF00".to_string(), ..Default::default() };
+        let painted_right = |image: &RgbImage| (0..300).any(|y| (140..400).any(|x| image.get_pixel(x, y).0 != [0, 0, 0]));
+
+        let plain = draw_markers(blank.clone(), std::slice::from_ref(&marker), &options).unwrap();
+        assert!(!painted_right(&plain));
+
+        marker.style.tag = Some("sfx".to_string());
+        assert_eq!(tag_label(&marker).as_deref(), Some("SFX"));
+        let tagged = draw_markers(blank, std::slice::from_ref(&marker), &options).unwrap();
+        assert!(painted_right(&tagged));
+
+        marker.style.tag = Some("拟声".to_string());
+        assert_eq!(tag_label(&marker), None);
+    }
+
+    #[test]
+    fn test_options_and_names() {
+        let options: AnnotateOptions = serde_json::from_str(r##"{"color":"#102030","format":"png"}"##).unwrap();
+        assert_eq!(options.rgb().unwrap().0, [0x10, 0x20, 0x30]);
+        assert_eq!(options.format, AnnotateFormat::Png);
+        assert_eq!(options.scale, 1.0);
+        assert!(AnnotateOptions { color: "red".to_string(), ..Default::default() }.rgb().is_err());
+
+        assert_eq!(output_name(Some("p01.png"), 0, AnnotateFormat::Jpeg), "p01_annotated.jpg");
+        assert_eq!(output_name(None, 4, AnnotateFormat::Png), "image_005_annotated.png");
+    }
+
+    #[test]
+    fn test_reading_order_numbers_copies_only() {
+        // 序号1在左、序号2在右，从右到左读时导出编号互换
+        let markers = vec![
+            Marker::new_point(MarkerId(1), ImageId(1), 20.0, 10.0, 1),
+            Marker::new_point(MarkerId(2), ImageId(1), 70.0, 10.0, 2),
+        ];
+        let numbered = number_in_reading_order(markers.clone(), ReadingDirection::RightToLeft);
+        let pairs: Vec<(u32, u32)> = numbered.iter().map(|m| (m.id.0, m.image_index)).collect();
+        assert_eq!(pairs, vec![(2, 1), (1, 2)]);
+        assert_eq!(markers[0].image_index, 1);
+    }
+}
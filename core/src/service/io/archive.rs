@@ -0,0 +1,231 @@
+// Archive import - 从zip/cbz压缩包读取整话图片，在内存中逐个解压，不落盘
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+use zip::result::ZipError;
+use crate::common::dto::image::ImageFormat;
+use crate::common::{sort_natural_by_key, OperationStatus};
+
+/// 支持作为图片来源的压缩包扩展名
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "cbz"];
+
+pub fn is_archive_extension(ext: &str) -> bool {
+    ARCHIVE_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveErrorKind {
+    /// 不是合法的zip或数据损坏
+    Corrupt,
+    /// 压缩包或其中的图片需要密码
+    PasswordProtected,
+    /// 使用了不支持的压缩方式
+    Unsupported,
+    /// 压缩包中没有图片
+    NoImages,
+}
+
+/// 单个压缩包的结构化错误，前端据此提示具体原因
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveError {
+    pub archive: String,
+    pub kind: ArchiveErrorKind,
+    pub message: String,
+}
+
+impl ArchiveError {
+    fn new(archive: &str, kind: ArchiveErrorKind, message: impl Into<String>) -> Self {
+        Self { archive: archive.to_string(), kind, message: message.into() }
+    }
+
+    fn from_zip(archive: &str, err: ZipError) -> Self {
+        let kind = match &err {
+            ZipError::UnsupportedArchive(msg) if *msg == ZipError::PASSWORD_REQUIRED => ArchiveErrorKind::PasswordProtected,
+            ZipError::InvalidPassword => ArchiveErrorKind::PasswordProtected,
+            ZipError::UnsupportedArchive(_) => ArchiveErrorKind::Unsupported,
+            _ => ArchiveErrorKind::Corrupt,
+        };
+        Self::new(archive, kind, err.to_string())
+    }
+
+    /// 序列化为JSON字符串，通过API的String错误通道传给前端
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| self.to_string())
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Archive '{}' ({:?}): {}", self.archive, self.kind, self.message)
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+/// 压缩包中的一张图片
+#[derive(Debug, Clone)]
+pub struct ArchiveImageEntry {
+    index: usize,
+    /// 压缩包内的完整路径
+    pub path: String,
+    /// 作为图片名称使用的文件名
+    pub name: String,
+    pub format: ImageFormat,
+}
+
+/// 压缩包导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveImportReport {
+    pub project_id: u32,
+    /// 按导入顺序排列的图片名
+    pub added_images: Vec<String>,
+    /// 非图片条目（已跳过）
+    pub skipped_entries: Vec<String>,
+    /// 读取或添加失败的图片条目及原因
+    pub failed_entries: Vec<(String, String)>,
+    /// 被取消时只导入了added_images中的图片，临时项目仍然保留，由调用方决定完成还是删除
+    #[serde(default)]
+    pub status: OperationStatus,
+}
+
+// macOS压缩时附带的元数据和隐藏文件不算作内容
+fn is_hidden_entry(path: &str) -> bool {
+    path.split('/').any(|part| part.starts_with('.') || part == "__MACOSX")
+}
+
+/// 内存中的压缩包读取器
+pub struct ArchiveReader {
+    archive_name: String,
+    archive: ZipArchive<Cursor<Vec<u8>>>,
+    images: Vec<ArchiveImageEntry>,
+    skipped: Vec<String>,
+}
+
+impl ArchiveReader {
+    /// 打开压缩包并列出图片条目（自然排序），此时不解压任何数据
+    pub fn open(data: Vec<u8>, archive_name: &str) -> Result<Self, ArchiveError> {
+        let mut archive = ZipArchive::new(Cursor::new(data))
+            .map_err(|e| ArchiveError::from_zip(archive_name, e))?;
+
+        let mut images = Vec::new();
+        let mut skipped = Vec::new();
+        for index in 0..archive.len() {
+            let entry = archive.by_index_raw(index)
+                .map_err(|e| ArchiveError::from_zip(archive_name, e))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let path = entry.name().to_string();
+            let format = Path::new(&path).extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(ImageFormat::from_extension);
+            match format {
+                Some(format) if !is_hidden_entry(&path) => {
+                    if entry.encrypted() {
+                        return Err(ArchiveError::new(archive_name, ArchiveErrorKind::PasswordProtected,
+                            format!("Entry '{}' is password protected", path)));
+                    }
+                    let name = Path::new(&path).file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(&path)
+                        .to_string();
+                    images.push(ArchiveImageEntry { index, path, name, format });
+                }
+                _ => skipped.push(path),
+            }
+        }
+
+        if images.is_empty() {
+            return Err(ArchiveError::new(archive_name, ArchiveErrorKind::NoImages, "No image entries found"));
+        }
+
+        sort_natural_by_key(&mut images, |entry| entry.path.as_str());
+
+        // 不同目录下的同名图片改用完整路径区分
+        let names: Vec<String> = images.iter().map(|entry| entry.name.clone()).collect();
+        for entry in images.iter_mut() {
+            if names.iter().filter(|name| **name == entry.name).count() > 1 {
+                entry.name = entry.path.clone();
+            }
+        }
+
+        Ok(Self {
+            archive_name: archive_name.to_string(),
+            archive,
+            images,
+            skipped,
+        })
+    }
+
+    pub fn images(&self) -> &[ArchiveImageEntry] {
+        &self.images
+    }
+
+    pub fn skipped_entries(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// 解压单个图片条目
+    pub fn read_image(&mut self, entry: &ArchiveImageEntry) -> Result<Vec<u8>, ArchiveError> {
+        let mut file = self.archive.by_index(entry.index)
+            .map_err(|e| ArchiveError::from_zip(&self.archive_name, e))?;
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)
+            .map_err(|e| ArchiveError::new(&self.archive_name, ArchiveErrorKind::Corrupt,
+                format!("Failed to read '{}': {}", entry.path, e)))?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_natural_order_and_skipped_entries() {
+        let data = build_zip(&[
+            ("ch1/10.png", b"ten"),
+            ("ch1/2.png", b"two"),
+            ("ch1/readme.txt", b"notes"),
+            ("__MACOSX/ch1/._2.png", b"meta"),
+            ("ch1/1.JPG", b"one"),
+        ]);
+        let mut reader = ArchiveReader::open(data, "ch1.cbz").unwrap();
+        let names: Vec<String> = reader.images().iter().map(|e| e.name.clone()).collect();
+        assert_eq!(names, vec!["1.JPG", "2.png", "10.png"]);
+        assert_eq!(reader.skipped_entries(), &["ch1/readme.txt".to_string(), "__MACOSX/ch1/._2.png".to_string()]);
+
+        let entry = reader.images()[1].clone();
+        assert_eq!(reader.read_image(&entry).unwrap(), b"two");
+    }
+
+    #[test]
+    fn test_corrupt_and_empty_archives() {
+        let err = ArchiveReader::open(b"not a zip".to_vec(), "bad.zip").err().unwrap();
+        assert_eq!(err.kind, ArchiveErrorKind::Corrupt);
+        assert_eq!(err.archive, "bad.zip");
+
+        let err = ArchiveReader::open(build_zip(&[("a.txt", b"x")]), "text.zip").err().unwrap();
+        assert_eq!(err.kind, ArchiveErrorKind::NoImages);
+
+        // 截断的压缩包
+        let data = build_zip(&[("1.png", b"one")]);
+        let err = ArchiveReader::open(data[..data.len() / 2].to_vec(), "cut.zip").err().unwrap();
+        assert_eq!(err.kind, ArchiveErrorKind::Corrupt);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::common::{CoreError, CoreResult};
+use crate::service::project::integrity::{IntegrityIssue, IntegrityIssueKind};
 use crate::storage::state::APP_STATE;
 use crate::storage::traits::Storage;
+use crate::storage::marker::MarkerStyle;
 use flate2::write::GzEncoder;
 use flate2::read::GzDecoder;
 use flate2::Compression;
@@ -9,6 +11,8 @@ use std::collections::HashMap;
 use std::io::{Write, Read};
 use tar::{Archive, Builder};
 
+pub use super::bf_schema::schema;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectMetadata {
@@ -19,6 +23,27 @@ pub struct ProjectMetadata {
     pub source_language: Option<crate::common::Language>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_language: Option<crate::common::Language>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_order: Option<crate::common::ThumbnailOrder>,
+    // 固定图片在images.json中的下标
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_images: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading_direction: Option<crate::common::ReadingDirection>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marker_types: Vec<crate::common::MarkerTypePreset>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_services: Option<crate::common::ServiceDefaults>,
+    #[serde(default, skip_serializing_if = "crate::common::ProjectGlossary::is_empty")]
+    pub glossary: crate::common::ProjectGlossary,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_ocr_on_create: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keep_translation_history: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub grayscale_reencode: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,13 +52,65 @@ pub struct StyleEntry {
     #[serde(rename = "overlayText")]
     overlay_text: bool,
     horizontal: bool,
+    // 字号和标签是后加的可选字段，旧文件没有，旧版本读取时忽略
+    #[serde(rename = "sizeHint", default, skip_serializing_if = "Option::is_none")]
+    size_hint: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+}
+
+impl StyleEntry {
+    fn new(id: String, style: &MarkerStyle) -> Self {
+        Self {
+            id,
+            overlay_text: style.overlay_text,
+            horizontal: style.horizontal,
+            size_hint: style.size_hint,
+            tag: style.tag.clone(),
+        }
+    }
+
+    fn to_style(&self) -> MarkerStyle {
+        MarkerStyle {
+            overlay_text: self.overlay_text,
+            horizontal: self.horizontal,
+            size_hint: self.size_hint,
+            tag: self.tag.clone(),
+        }
+    }
+}
+
+// 相同样式的标记共用一个styles.json条目
+type StyleKey = (bool, bool, Option<u32>, Option<String>);
+
+fn style_key(style: &MarkerStyle) -> StyleKey {
+    (style.overlay_text, style.horizontal, style.size_hint.map(f32::to_bits), style.tag.clone())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageEntry {
     pub filename: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    checksum: Option<String>,
+    pub checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    reviewed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    review_note: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    locked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    view_state: Option<ViewStateEntry>,
+}
+
+// 标记ID每次打开都不同，选中的标记按在图片标记列表中的位置保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewStateEntry {
+    zoom: f64,
+    pan_x: f64,
+    pan_y: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selected_marker: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,15 +120,50 @@ pub enum MarkerEntry {
         position: [f64; 2],
         style: String,
         text: String,
+        // 译者备注是后加的可选字段
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
     },
     Rectangle {
         position: [f64; 2],
         size: [f64; 2],
         style: String,
         text: String,
+        // 译者备注是后加的可选字段
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+    },
+    // 格式版本1.1新增
+    Polygon {
+        points: Vec<[f64; 2]>,
+        style: String,
+        text: String,
+        // 译者备注是后加的可选字段
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
     },
 }
 
+// bf格式版本：1.1新增多边形标记。只有包含多边形的项目才写1.1，其它项目仍写1.0，旧版本照常打开
+const BF_FORMAT_VERSION: &str = "1.0";
+const BF_POLYGON_FORMAT_VERSION: &str = "1.1";
+// 本版本能完整读取的最高格式版本，更新的文件中无法识别的标记会被跳过并记录警告
+const BF_SUPPORTED_FORMAT_VERSION: (u32, u32) = (1, 1);
+
+fn parse_format_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+    Some((major.trim().parse().ok()?, minor.trim().parse().ok()?))
+}
+
+// 解析单个标记，无法识别的类型（更新版本写入）或无效的多边形返回None
+fn parse_marker_entry(value: serde_json::Value) -> Option<MarkerEntry> {
+    match serde_json::from_value::<MarkerEntry>(value) {
+        Ok(MarkerEntry::Polygon { points, .. }) if points.len() < crate::storage::marker::MIN_POLYGON_POINTS => None,
+        Ok(entry) => Some(entry),
+        Err(_) => None,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BunnyCacheEntry {
@@ -65,6 +177,21 @@ pub struct BunnyCacheEntry {
     pub last_ocr_model: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_translation_service: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translation_warnings: Vec<crate::common::TranslationWarning>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation_metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_translated_at: Option<u64>,
+}
+
+// translation_history.json的条目，只在项目开启keep_translation_history时写入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationHistoryBfEntry {
+    pub page_index: usize,
+    pub marker_index: usize,
+    pub entries: Vec<crate::storage::TranslationHistoryEntry>,
 }
 
 pub fn save_project_to_path(project_id: crate::common::ProjectId, path: &str) -> CoreResult<Vec<u8>> {
@@ -79,18 +206,41 @@ pub fn save_project_to_path(project_id: crate::common::ProjectId, path: &str) ->
 pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>> {
     let project = APP_STATE.get_project(project_id)?
         .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    save_project_images(&project, &project.image_ids)
+}
+
+/// 只导出指定的图片（如一个章节）到bf，保持它们在项目中的先后顺序；项目设置和术语表照常写入
+pub fn save_project_range(project_id: crate::common::ProjectId, image_ids: &[crate::common::ImageId]) -> CoreResult<Vec<u8>> {
+    let project = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    let image_ids = super::project_data::export_image_range(project_id, image_ids)?;
+    save_project_images(&project, &image_ids)
+}
+
+fn save_project_images(project: &crate::storage::project::Project, image_ids: &[crate::common::ImageId]) -> CoreResult<Vec<u8>> {
     
     let current_datetime = chrono::Utc::now().to_rfc3339();
     
     // 1. Create metadata.json
-    let metadata = ProjectMetadata {
-        format_version: "1.0".to_string(),
+    let mut metadata = ProjectMetadata {
+        format_version: BF_FORMAT_VERSION.to_string(),
         export_date: current_datetime,
         project_name: project.name.clone(),
         source_language: Some(project.source_language),
         target_language: Some(project.target_language),
+        thumbnail_order: Some(project.thumbnail_order),
+        pinned_images: project.pinned_images.iter()
+            .filter_map(|pinned| image_ids.iter().position(|id| id == pinned))
+            .collect(),
+        reading_direction: Some(project.reading_direction),
+        marker_types: project.marker_types.clone(),
+        default_services: Some(project.default_services.clone()),
+        glossary: project.glossary.clone(),
+        notes: project.notes.clone(),
+        auto_ocr_on_create: project.auto_ocr_on_create,
+        keep_translation_history: project.keep_translation_history,
+        grayscale_reencode: project.grayscale_reencode,
     };
-    let metadata_json = serde_json::to_string_pretty(&metadata)?;
     
     // 2. Collect all unique styles and create styles.json
     let mut style_map = HashMap::new();
@@ -98,24 +248,20 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
     let mut styles_list = Vec::new();
     
     // Iterate through all images and markers to collect unique styles
-    for image_id in &project.image_ids {
+    for image_id in image_ids {
         // Get image to access its marker_ids
         if let Ok(Some(image)) = APP_STATE.get_image(*image_id) {
+            let markers_storage = APP_STATE.markers.read_image(*image_id)?;
             for marker_id in &image.marker_ids {
                 // Get marker from storage
-                let markers_storage = APP_STATE.markers.read()?;
                 if let Some(marker) = markers_storage.get(marker_id) {
-                    let style_key = (marker.style.overlay_text, marker.style.horizontal);
+                    let style_key = style_key(&marker.style);
                     
                     if !style_map.contains_key(&style_key) {
                         let style_id = style_id_counter.to_string();
                         style_map.insert(style_key, style_id.clone());
                         
-                        styles_list.push(StyleEntry {
-                            id: style_id,
-                            overlay_text: marker.style.overlay_text,
-                            horizontal: marker.style.horizontal,
-                        });
+                        styles_list.push(StyleEntry::new(style_id, &marker.style));
                         
                         style_id_counter += 1;
                     }
@@ -126,19 +272,15 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
     
     // If no styles found, add a default style
     if styles_list.is_empty() {
-        styles_list.push(StyleEntry {
-            id: "0".to_string(),
-            overlay_text: false,
-            horizontal: false,
-        });
-        style_map.insert((false, false), "0".to_string());
+        styles_list.push(StyleEntry::new("0".to_string(), &MarkerStyle::default()));
+        style_map.insert(style_key(&MarkerStyle::default()), "0".to_string());
     }
     
     let styles_json = serde_json::to_string_pretty(&styles_list)?;
     
     // 3. Create images.json
     let mut images_list = Vec::new();
-    for (index, image_id) in project.image_ids.iter().enumerate() {
+    for (index, image_id) in image_ids.iter().enumerate() {
         if let Ok(Some(image)) = APP_STATE.get_image(*image_id) {
             // Use the original image name if available, otherwise fallback to page_xxx.jpg
             let filename = if let Some(ref name) = image.metadata.name {
@@ -151,6 +293,16 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
             images_list.push(ImageEntry {
                 filename,
                 checksum,
+                reviewed: image.metadata.reviewed,
+                review_note: image.metadata.review_note.clone(),
+                locked: image.metadata.locked,
+                view_state: image.metadata.view_state.as_ref().map(|state| ViewStateEntry {
+                    zoom: state.zoom,
+                    pan_x: state.pan_x,
+                    pan_y: state.pan_y,
+                    selected_marker: state.last_selected_marker
+                        .and_then(|marker_id| image.marker_ids.iter().position(|id| *id == marker_id)),
+                }),
             });
         }
     }
@@ -159,17 +311,16 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
     // 4. Create markers.json
     let mut markers_list = Vec::new();
     
-    for image_id in &project.image_ids {
+    for image_id in image_ids {
         let mut image_markers = Vec::new();
         
         // Get image to access its marker_ids
         if let Ok(Some(image)) = APP_STATE.get_image(*image_id) {
+            let markers_storage = APP_STATE.markers.read_image(*image_id)?;
             for marker_id in &image.marker_ids {
                 // Get marker from storage
-                let markers_storage = APP_STATE.markers.read()?;
                 if let Some(marker) = markers_storage.get(marker_id) {
-                    let style_key = (marker.style.overlay_text, marker.style.horizontal);
-                    let style_id = style_map.get(&style_key)
+                    let style_id = style_map.get(&style_key(&marker.style))
                         .ok_or_else(|| CoreError::Internal("Style ID not found".to_string()))?;
                     
                     // Create marker entry based on geometry type
@@ -179,6 +330,7 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
                                 position: [*x, *y],
                                 style: style_id.clone(),
                                 text: marker.translation.clone(),
+                                note: marker.note.clone(),
                             }
                         }
                         crate::storage::marker::MarkerGeometry::Rectangle { x, y, width, height } => {
@@ -187,6 +339,16 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
                                 size: [*width, *height],
                                 style: style_id.clone(),
                                 text: marker.translation.clone(),
+                                note: marker.note.clone(),
+                            }
+                        }
+                        crate::storage::marker::MarkerGeometry::Polygon { points } => {
+                            metadata.format_version = BF_POLYGON_FORMAT_VERSION.to_string();
+                            MarkerEntry::Polygon {
+                                points: points.iter().map(|&(x, y)| [x, y]).collect(),
+                                style: style_id.clone(),
+                                text: marker.translation.clone(),
+                                note: marker.note.clone(),
                             }
                         }
                     };
@@ -199,12 +361,13 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
     }
     
     let markers_json = serde_json::to_string_pretty(&markers_list)?;
+    let metadata_json = serde_json::to_string_pretty(&metadata)?;
 
     // 5. Create bunny_cache.json
     let mut bunny_cache_list = Vec::new();
     let bunny_cache_storage = APP_STATE.bunny_cache.read()?;
 
-    for (page_index, image_id) in project.image_ids.iter().enumerate() {
+    for (page_index, image_id) in image_ids.iter().enumerate() {
         if let Ok(Some(image)) = APP_STATE.get_image(*image_id) {
             for (marker_index, marker_id) in image.marker_ids.iter().enumerate() {
                 if let Some(cache_data) = bunny_cache_storage.get(marker_id) {
@@ -216,6 +379,9 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
                             machine_translation: cache_data.machine_translation.clone(),
                             last_ocr_model: cache_data.last_ocr_model.clone(),
                             last_translation_service: cache_data.last_translation_service.clone(),
+                            translation_warnings: cache_data.translation_warnings.clone(),
+                            translation_metadata: cache_data.translation_metadata.clone(),
+                            machine_translated_at: cache_data.machine_translated_at,
                         });
                     }
                 }
@@ -226,7 +392,22 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
 
     let bunny_cache_json = serde_json::to_string_pretty(&bunny_cache_list)?;
 
-    // 6. Create tar archive with gzip compression
+    // 6. Create translation_history.json (only when the project keeps history)
+    let mut history_list = Vec::new();
+    if project.keep_translation_history {
+        for (page_index, image_id) in image_ids.iter().enumerate() {
+            if let Ok(Some(image)) = APP_STATE.get_image(*image_id) {
+                for (marker_index, marker_id) in image.marker_ids.iter().enumerate() {
+                    let entries = crate::storage::translation_history::get_translation_history_storage(*marker_id)?;
+                    if !entries.is_empty() {
+                        history_list.push(TranslationHistoryBfEntry { page_index, marker_index, entries });
+                    }
+                }
+            }
+        }
+    }
+
+    // 7. Create tar archive with gzip compression
     let tar_gz_data = Vec::new();
     let encoder = GzEncoder::new(tar_gz_data, Compression::default());
     let mut tar = Builder::new(encoder);
@@ -237,6 +418,9 @@ pub fn save_project(project_id: crate::common::ProjectId) -> CoreResult<Vec<u8>>
     add_json_to_tar(&mut tar, "images.json", images_json.as_bytes())?;
     add_json_to_tar(&mut tar, "markers.json", markers_json.as_bytes())?;
     add_json_to_tar(&mut tar, "bunny_cache.json", bunny_cache_json.as_bytes())?;
+    if !history_list.is_empty() {
+        add_json_to_tar(&mut tar, "translation_history.json", serde_json::to_string(&history_list)?.as_bytes())?;
+    }
     
     // Finish writing the tar archive
     let encoder = tar.into_inner()?;
@@ -264,42 +448,21 @@ pub struct BfProjectData {
     pub markers: Vec<Vec<MarkerEntry>>,
     #[serde(default)]
     pub bunny_cache: Vec<BunnyCacheEntry>,
+    #[serde(default)]
+    pub translation_history: Vec<TranslationHistoryBfEntry>,
 }
 
 pub fn parse_bf_file(data: &[u8]) -> CoreResult<BfProjectData> {
-    // Decompress the gzip data
-    let mut decoder = GzDecoder::new(data);
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)
-        .map_err(|e| CoreError::ValidationFailed {
-            field: "bf_file".to_string(),
-            reason: format!("Failed to decompress: {}", e),
-        })?;
-    
-    // Parse tar archive
-    let mut archive = Archive::new(&decompressed[..]);
-    let mut metadata_json = None;
-    let mut styles_json = None;
-    let mut images_json = None;
-    let mut markers_json = None;
-    let mut bunny_cache_json = None;
-
-    for entry_result in archive.entries()? {
-        let mut entry = entry_result?;
-        let path = entry.path()?.to_string_lossy().to_string();
-
-        let mut content = String::new();
-        entry.read_to_string(&mut content)?;
-
-        match path.as_str() {
-            "metadata.json" => metadata_json = Some(content),
-            "styles.json" => styles_json = Some(content),
-            "images.json" => images_json = Some(content),
-            "markers.json" => markers_json = Some(content),
-            "bunny_cache.json" => bunny_cache_json = Some(content),
-            _ => {}
-        }
-    }
+    let mut entries = read_bf_entries(data).map_err(|e| CoreError::ValidationFailed {
+        field: "bf_file".to_string(),
+        reason: e.message().to_string(),
+    })?;
+    let metadata_json = entries.remove("metadata.json");
+    let styles_json = entries.remove("styles.json");
+    let images_json = entries.remove("images.json");
+    let markers_json = entries.remove("markers.json");
+    let bunny_cache_json = entries.remove("bunny_cache.json");
+    let translation_history_json = entries.remove("translation_history.json");
     
     // Parse JSON files
     let metadata: ProjectMetadata = serde_json::from_str(
@@ -326,13 +489,41 @@ pub fn parse_bf_file(data: &[u8]) -> CoreResult<BfProjectData> {
             })?
     )?;
     
-    let markers: Vec<Vec<MarkerEntry>> = serde_json::from_str(
+    let newer_format = parse_format_version(&metadata.format_version)
+        .is_none_or(|version| version > BF_SUPPORTED_FORMAT_VERSION);
+    if newer_format {
+        crate::common::Logger::warn(&format!(
+            "BF file format version {} is newer than supported, unknown content will be skipped",
+            metadata.format_version
+        ));
+    }
+
+    // 逐个解析标记，跳过无法识别的标记而不是让整个文件打开失败
+    let raw_markers: Vec<Vec<serde_json::Value>> = serde_json::from_str(
         markers_json.as_ref()
             .ok_or_else(|| CoreError::ValidationFailed {
                 field: "markers.json".to_string(),
                 reason: "Missing markers.json in BF file".to_string(),
             })?
     )?;
+    let mut skipped_markers = 0;
+    let markers: Vec<Vec<MarkerEntry>> = raw_markers.into_iter()
+        .map(|entries| entries.into_iter()
+            .filter_map(|entry| {
+                let parsed = parse_marker_entry(entry);
+                if parsed.is_none() {
+                    skipped_markers += 1;
+                }
+                parsed
+            })
+            .collect())
+        .collect();
+    if skipped_markers > 0 {
+        crate::common::Logger::warn(&format!(
+            "Skipped {} unsupported markers in BF file (format version {})",
+            skipped_markers, metadata.format_version
+        ));
+    }
     
     // Validate data consistency
     if markers.len() != images.len() {
@@ -350,12 +541,226 @@ pub fn parse_bf_file(data: &[u8]) -> CoreResult<BfProjectData> {
         Vec::new()
     };
 
+    // translation_history.json is optional, unreadable history is dropped
+    let translation_history: Vec<TranslationHistoryBfEntry> = translation_history_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
     Ok(BfProjectData {
         metadata,
         styles,
         images,
         markers,
         bunny_cache,
+        translation_history,
+    })
+}
+
+impl MarkerEntry {
+    fn style(&self) -> &str {
+        match self {
+            MarkerEntry::Point { style, .. } | MarkerEntry::Rectangle { style, .. } | MarkerEntry::Polygon { style, .. } => style,
+        }
+    }
+}
+
+/// 打开bf文件时的轻量一致性检查（不涉及存储），结果作为警告附在临时项目信息中
+pub fn check_bf_integrity(data: &BfProjectData) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+
+    let mut seen = std::collections::HashSet::new();
+    for image in &data.images {
+        if !seen.insert(image.filename.as_str()) {
+            issues.push(IntegrityIssue::new(
+                IntegrityIssueKind::BfDuplicateImage,
+                format!("图片 {} 在文件中出现多次，只会加载第一张", image.filename),
+            ));
+        }
+    }
+
+    let style_ids: std::collections::HashSet<&str> = data.styles.iter().map(|style| style.id.as_str()).collect();
+    let mut unknown_styles: Vec<(&str, usize)> = Vec::new();
+    for marker in data.markers.iter().flatten() {
+        let style = marker.style();
+        if style_ids.contains(style) {
+            continue;
+        }
+        match unknown_styles.iter_mut().find(|(id, _)| *id == style) {
+            Some((_, count)) => *count += 1,
+            None => unknown_styles.push((style, 1)),
+        }
+    }
+    for (style, count) in unknown_styles {
+        issues.push(IntegrityIssue::new(
+            IntegrityIssueKind::BfUnknownStyle,
+            format!("{} 个标记使用了未定义的样式 {}，将使用默认样式", count, style),
+        ));
+    }
+
+    for entry in &data.bunny_cache {
+        let in_range = data.markers.get(entry.page_index)
+            .is_some_and(|markers| entry.marker_index < markers.len());
+        if !in_range {
+            issues.push(IntegrityIssue::new(
+                IntegrityIssueKind::BfDanglingBunnyCache,
+                format!("第 {} 页第 {} 个标记不存在，对应的OCR/翻译缓存将被忽略", entry.page_index + 1, entry.marker_index + 1),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// bf文件校验失败的原因，message为面向用户的提示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BfFormatError {
+    /// 文件头不是gzip，不是bf文件
+    NotBfFile { message: String },
+    /// 文件不完整（下载或复制中断）
+    Truncated { message: String },
+    /// 压缩数据或tar包已损坏
+    Corrupt { message: String },
+    /// 缺少必需的文件
+    MissingEntry { entry: String, message: String },
+    /// 文件内容无法解析
+    InvalidEntry { entry: String, message: String },
+    /// 各文件之间的数据不一致
+    Inconsistent { message: String },
+}
+
+impl BfFormatError {
+    fn missing(entry: &str) -> Self {
+        Self::MissingEntry {
+            entry: entry.to_string(),
+            message: format!("项目文件缺少 {}", entry),
+        }
+    }
+
+    fn invalid(entry: &str, detail: impl std::fmt::Display) -> Self {
+        Self::InvalidEntry {
+            entry: entry.to_string(),
+            message: format!("项目文件中的 {} 无法解析（{}）", entry, detail),
+        }
+    }
+
+    // 读取压缩数据或tar条目时的IO错误，数据提前结束视为截断
+    fn from_io(error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::UnexpectedEof {
+            Self::Truncated { message: "项目文件不完整，可能在下载或复制时被截断".to_string() }
+        } else {
+            Self::Corrupt { message: format!("项目文件已损坏（{}）", error) }
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotBfFile { .. } => "not_bf_file",
+            Self::Truncated { .. } => "truncated",
+            Self::Corrupt { .. } => "corrupt",
+            Self::MissingEntry { .. } => "missing_entry",
+            Self::InvalidEntry { .. } => "invalid_entry",
+            Self::Inconsistent { .. } => "inconsistent",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotBfFile { message }
+            | Self::Truncated { message }
+            | Self::Corrupt { message }
+            | Self::MissingEntry { message, .. }
+            | Self::InvalidEntry { message, .. }
+            | Self::Inconsistent { message } => message,
+        }
+    }
+
+    /// 序列化为JSON字符串，通过API的String错误通道传给前端
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| self.message().to_string())
+    }
+}
+
+impl std::fmt::Display for BfFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for BfFormatError {}
+
+/// bf文件的概要信息，打开前给用户确认
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BfSummary {
+    pub project_name: String,
+    pub format_version: String,
+    /// 格式版本比当前程序支持的更新，无法识别的内容会被跳过
+    pub newer_format: bool,
+    pub export_date: String,
+    pub image_count: usize,
+    pub marker_count: usize,
+    /// 无法识别、打开时会被跳过的标记数
+    pub skipped_markers: usize,
+    pub images: Vec<String>,
+}
+
+// 解压并读出tar包中的所有文件
+pub(crate) fn read_bf_entries(data: &[u8]) -> Result<HashMap<String, String>, BfFormatError> {
+    if !data.starts_with(&[0x1f, 0x8b]) {
+        return Err(BfFormatError::NotBfFile { message: "文件不是BubbleFish项目文件".to_string() });
+    }
+    let mut decompressed = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut decompressed).map_err(BfFormatError::from_io)?;
+
+    let mut entries = HashMap::new();
+    let mut archive = Archive::new(&decompressed[..]);
+    for entry_result in archive.entries().map_err(BfFormatError::from_io)? {
+        let mut entry = entry_result.map_err(BfFormatError::from_io)?;
+        let path = entry.path().map_err(BfFormatError::from_io)?.to_string_lossy().to_string();
+        let mut content = String::new();
+        entry.read_to_string(&mut content).map_err(BfFormatError::from_io)?;
+        entries.insert(path, content);
+    }
+    Ok(entries)
+}
+
+fn parse_bf_entry<T: serde::de::DeserializeOwned>(entries: &HashMap<String, String>, name: &str) -> Result<T, BfFormatError> {
+    let content = entries.get(name).ok_or_else(|| BfFormatError::missing(name))?;
+    serde_json::from_str(content).map_err(|e| BfFormatError::invalid(name, e))
+}
+
+/// 校验bf文件并统计内容，不写入APP_STATE，用于打开前的预检
+pub fn validate_bf(data: &[u8]) -> Result<BfSummary, BfFormatError> {
+    let entries = read_bf_entries(data)?;
+    let metadata: ProjectMetadata = parse_bf_entry(&entries, "metadata.json")?;
+    let _styles: Vec<StyleEntry> = parse_bf_entry(&entries, "styles.json")?;
+    let images: Vec<ImageEntry> = parse_bf_entry(&entries, "images.json")?;
+    let raw_markers: Vec<Vec<serde_json::Value>> = parse_bf_entry(&entries, "markers.json")?;
+
+    if raw_markers.len() != images.len() {
+        return Err(BfFormatError::Inconsistent {
+            message: format!("项目文件中的标记页数（{}）与图片数（{}）不一致", raw_markers.len(), images.len()),
+        });
+    }
+
+    let marker_count = raw_markers.iter().map(Vec::len).sum();
+    let skipped_markers = raw_markers.into_iter()
+        .flatten()
+        .filter(|entry| parse_marker_entry(entry.clone()).is_none())
+        .count();
+    let newer_format = parse_format_version(&metadata.format_version)
+        .is_none_or(|version| version > BF_SUPPORTED_FORMAT_VERSION);
+
+    Ok(BfSummary {
+        project_name: metadata.project_name,
+        format_version: metadata.format_version,
+        newer_format,
+        export_date: metadata.export_date,
+        image_count: images.len(),
+        marker_count,
+        skipped_markers,
+        images: images.into_iter().map(|image| image.filename).collect(),
     })
 }
 
@@ -364,7 +769,7 @@ pub fn import_bf_data_direct(
     bf_data: BfProjectData,
 ) -> CoreResult<()> {
     use crate::common::{ImageId, MarkerId, MARKER_ID_GENERATOR};
-    use crate::storage::marker::{Marker, MarkerStyle};
+    use crate::storage::marker::Marker;
     use std::sync::Arc;
     
     // Get project images to match with BF file image names
@@ -419,10 +824,7 @@ pub fn import_bf_data_direct(
     // Build style ID to MarkerStyle mapping
     let mut style_map: HashMap<String, MarkerStyle> = HashMap::new();
     for style in &bf_data.styles {
-        style_map.insert(style.id.clone(), MarkerStyle {
-            overlay_text: style.overlay_text,
-            horizontal: style.horizontal,
-        });
+        style_map.insert(style.id.clone(), style.to_style());
     }
     
     // Import markers for each image
@@ -438,18 +840,16 @@ pub fn import_bf_data_direct(
                 let marker_id = MARKER_ID_GENERATOR.next();
                 
                 // Get style and text based on marker type
-                let (style_id, text) = match bf_marker {
-                    MarkerEntry::Point { style, text, .. } => (style, text),
-                    MarkerEntry::Rectangle { style, text, .. } => (style, text),
+                let (style_id, text, note) = match bf_marker {
+                    MarkerEntry::Point { style, text, note, .. } => (style, text, note),
+                    MarkerEntry::Rectangle { style, text, note, .. } => (style, text, note),
+                    MarkerEntry::Polygon { style, text, note, .. } => (style, text, note),
                 };
                 
                 // Get style from style map
                 let style = style_map.get(style_id)
                     .cloned()
-                    .unwrap_or(MarkerStyle {
-                        overlay_text: false,
-                        horizontal: false,
-                    });
+                    .unwrap_or_default();
                 
                 // Create geometry based on marker type
                 let geometry = match bf_marker {
@@ -467,6 +867,11 @@ pub fn import_bf_data_direct(
                             height: size[1],
                         }
                     }
+                    MarkerEntry::Polygon { points, .. } => {
+                        crate::storage::marker::MarkerGeometry::Polygon {
+                            points: points.iter().map(|point| (point[0], point[1])).collect(),
+                        }
+                    }
                 };
                 
                 let marker = Marker {
@@ -476,11 +881,11 @@ pub fn import_bf_data_direct(
                     translation: text.clone(),
                     style,
                     image_index: (marker_index + 1) as u32,
+                    note: note.clone(),
                 };
                 
                 // Insert directly into storage
-                marker_storage.markers.insert(marker_id, marker);
-                marker_storage.by_image.entry(*image_id).or_default().push(marker_id);
+                marker_storage.insert_with_image(marker)?;
                 marker_ids_for_image.push(marker_id);
             }
             
@@ -496,21 +901,52 @@ pub fn import_bf_data_direct(
     
     // Update all images with their new markers in a single operation
     let mut image_storage = APP_STATE.images.write()?;
-    for (image_id, marker_ids) in image_updates {
-        if let Some(image_arc) = image_storage.get_mut(&image_id) {
+    for (image_id, marker_ids) in &image_updates {
+        if let Some(image_arc) = image_storage.get_mut(image_id) {
             // We need to clone the Arc to modify the image
             if let Some(image) = Arc::get_mut(image_arc) {
-                image.marker_ids.extend(marker_ids);
+                image.marker_ids.extend(marker_ids.iter().copied());
             } else {
                 // If we can't get mutable access, we need to clone
                 let mut image = (**image_arc).clone();
-                image.marker_ids.extend(marker_ids);
+                image.marker_ids.extend(marker_ids.iter().copied());
                 *image_arc = Arc::new(image);
             }
         }
     }
     drop(image_storage);
 
+    // Restore pinned thumbnails
+    if !bf_data.metadata.pinned_images.is_empty() {
+        let pinned: Vec<ImageId> = bf_data.metadata.pinned_images.iter()
+            .filter_map(|&index| image_mapping.get(index).copied().flatten())
+            .collect();
+        for image_id in pinned {
+            crate::storage::project::set_image_pinned_storage(project_id, image_id, true)?;
+        }
+    }
+
+    // Restore review state
+    for (bf_image, image_id) in bf_data.images.iter().zip(image_mapping.iter()) {
+        let Some(image_id) = image_id else { continue };
+        if bf_image.reviewed || bf_image.review_note.is_some() {
+            crate::storage::image::set_image_review_storage(*image_id, bf_image.reviewed, bf_image.review_note.clone())?;
+        }
+        if bf_image.locked {
+            crate::storage::image::set_image_locked_storage(*image_id, true)?;
+        }
+        if let Some(entry) = &bf_image.view_state {
+            let last_selected_marker = entry.selected_marker
+                .and_then(|index| image_updates.get(image_id).and_then(|ids| ids.get(index)).copied());
+            crate::storage::image::set_image_view_state_storage(*image_id, Some(crate::common::dto::image::ImageViewState {
+                zoom: entry.zoom,
+                pan_x: entry.pan_x,
+                pan_y: entry.pan_y,
+                last_selected_marker,
+            }))?;
+        }
+    }
+
     // Import bunny cache data
     if !bf_data.bunny_cache.is_empty() {
         let mut bunny_cache_storage = APP_STATE.bunny_cache.write()?;
@@ -528,6 +964,9 @@ pub fn import_bf_data_direct(
                             cache_data.machine_translation = cache_entry.machine_translation;
                             cache_data.last_ocr_model = cache_entry.last_ocr_model;
                             cache_data.last_translation_service = cache_entry.last_translation_service;
+                            cache_data.translation_warnings = cache_entry.translation_warnings;
+                            cache_data.translation_metadata = cache_entry.translation_metadata;
+                            cache_data.machine_translated_at = cache_entry.machine_translated_at;
 
                             let _ = bunny_cache_storage.insert(marker_id, cache_data);
                         }
@@ -539,5 +978,223 @@ pub fn import_bf_data_direct(
         drop(bunny_cache_storage);
     }
 
+    // Restore translation history
+    for history_entry in bf_data.translation_history {
+        let marker_id = image_mapping.get(history_entry.page_index).copied().flatten()
+            .and_then(|image_id| APP_STATE.get_image(image_id).ok().flatten())
+            .and_then(|image| image.marker_ids.get(history_entry.marker_index).copied());
+        if let Some(marker_id) = marker_id {
+            crate::storage::translation_history::set_translation_history_storage(marker_id, history_entry.entries)?;
+        }
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::marker::{Marker, MarkerGeometry};
+
+    fn project_with_marker(geometry: MarkerGeometry) -> crate::common::ProjectId {
+        let project_id = crate::storage::project::create_project_storage("bf_polygon".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("p1.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        let marker_id = crate::common::MARKER_ID_GENERATOR.next();
+        let mut marker = Marker::new_point(marker_id, image_id, 0.0, 0.0, 1);
+        marker.geometry = geometry;
+        APP_STATE.markers.write().unwrap().insert_with_image(marker).unwrap();
+        crate::storage::image::add_marker_to_image_storage(image_id, marker_id).unwrap();
+        project_id
+    }
+
+    #[test]
+    fn test_polygon_roundtrip_and_format_version() {
+        let point_project = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let data = parse_bf_file(&save_project(point_project).unwrap()).unwrap();
+        assert_eq!(data.metadata.format_version, BF_FORMAT_VERSION);
+
+        let points = vec![(10.0, 10.0), (40.0, 20.0), (30.0, 50.0)];
+        let polygon_project = project_with_marker(MarkerGeometry::Polygon { points: points.clone() });
+        let data = parse_bf_file(&save_project(polygon_project).unwrap()).unwrap();
+        assert_eq!(data.metadata.format_version, BF_POLYGON_FORMAT_VERSION);
+        assert!(matches!(&data.markers[0][..], [MarkerEntry::Polygon { points: saved, .. }] if saved == &vec![[10.0, 10.0], [40.0, 20.0], [30.0, 50.0]]));
+    }
+
+    #[test]
+    fn test_style_size_hint_and_tag_roundtrip() {
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let image_id = crate::storage::project::get_project_image_ids_storage(project_id).unwrap()[0];
+        let marker_id = crate::storage::marker::get_image_markers_storage(image_id).unwrap()[0].id;
+        let style = MarkerStyle { size_hint: Some(1.5), tag: Some("SFX".to_string()), ..MarkerStyle::new(true, false) };
+        crate::storage::marker::update_marker_style_storage(marker_id, style.clone()).unwrap();
+
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert_eq!(data.styles.len(), 1);
+        assert_eq!(data.styles[0].to_style(), style);
+
+        // 旧文件的样式没有这两个字段
+        let old: StyleEntry = serde_json::from_str(r#"{"id":"0","overlayText":true,"horizontal":false}"#).unwrap();
+        assert_eq!(old.to_style(), MarkerStyle::new(true, false));
+        assert!(!serde_json::to_string(&old).unwrap().contains("sizeHint"));
+    }
+
+    #[test]
+    fn test_marker_note_roundtrip() {
+        let project_id = project_with_marker(MarkerGeometry::Rectangle { x: 1.0, y: 2.0, width: 3.0, height: 4.0 });
+        let image_id = crate::storage::project::get_project_image_ids_storage(project_id).unwrap()[0];
+        let marker_id = crate::storage::marker::get_image_markers_storage(image_id).unwrap()[0].id;
+        APP_STATE.markers.write().unwrap().get_mut(&marker_id).unwrap().note = Some("直译".to_string());
+
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        let target = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        import_bf_data_direct(target, data).unwrap();
+        let target_image = crate::storage::project::get_project_image_ids_storage(target).unwrap()[0];
+        let notes: Vec<Option<String>> = crate::storage::marker::get_image_markers_storage(target_image).unwrap()
+            .into_iter().map(|m| m.note).collect();
+        assert!(notes.contains(&Some("直译".to_string())));
+
+        // 没有备注的标记不写note字段
+        let entry: MarkerEntry = serde_json::from_str(r#"{"type":"point","position":[1.0,2.0],"style":"0","text":""}"#).unwrap();
+        assert!(!serde_json::to_string(&entry).unwrap().contains("note"));
+    }
+
+    #[test]
+    fn test_review_state_roundtrip() {
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let image_id = crate::storage::project::get_project_image_ids_storage(project_id).unwrap()[0];
+        crate::storage::image::set_image_review_storage(image_id, true, Some("字体偏大".to_string())).unwrap();
+        crate::storage::image::set_image_locked_storage(image_id, true).unwrap();
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert!(data.images[0].reviewed);
+        assert!(data.images[0].locked);
+
+        let target = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        import_bf_data_direct(target, data).unwrap();
+        let target_image = crate::storage::project::get_project_image_ids_storage(target).unwrap()[0];
+        let metadata = crate::storage::image::get_image_storage(target_image).unwrap().unwrap().metadata.clone();
+        assert!(metadata.reviewed);
+        assert_eq!(metadata.review_note.as_deref(), Some("字体偏大"));
+        assert!(metadata.locked);
+
+        // 未审阅、未锁定、没有查看状态的图片不写这些字段，旧文件读取为未审阅、未锁定
+        let entry: ImageEntry = serde_json::from_str(r#"{"filename":"p1.png"}"#).unwrap();
+        assert!(!entry.reviewed);
+        assert_eq!(serde_json::to_string(&entry).unwrap(), r#"{"filename":"p1.png"}"#);
+    }
+
+    #[test]
+    fn test_range_export_writes_only_selected_images() {
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let second = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("p2.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, second).unwrap();
+        let marker_id = crate::common::MARKER_ID_GENERATOR.next();
+        let mut marker = Marker::new_point(marker_id, second, 0.0, 0.0, 1);
+        marker.geometry = MarkerGeometry::Rectangle { x: 5.0, y: 6.0, width: 7.0, height: 8.0 };
+        APP_STATE.markers.write().unwrap().insert_with_image(marker).unwrap();
+        crate::storage::image::add_marker_to_image_storage(second, marker_id).unwrap();
+        crate::storage::project::set_image_pinned_storage(project_id, second, true).unwrap();
+
+        let data = parse_bf_file(&save_project_range(project_id, &[second]).unwrap()).unwrap();
+        assert_eq!(data.images.len(), 1);
+        assert_eq!(data.images[0].filename, "p2.png");
+        assert_eq!(data.markers.len(), 1);
+        assert!(matches!(data.markers[0][..], [MarkerEntry::Rectangle { .. }]));
+        // 固定的图片按导出范围内的位置记录
+        assert_eq!(data.metadata.pinned_images, vec![0]);
+    }
+
+    #[test]
+    fn test_view_state_roundtrip_maps_selected_marker() {
+        use crate::common::dto::image::ImageViewState;
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let image_id = crate::storage::project::get_project_image_ids_storage(project_id).unwrap()[0];
+        let marker_id = crate::storage::marker::get_image_markers_storage(image_id).unwrap()[0].id;
+        crate::storage::image::set_image_view_state_storage(image_id, Some(ImageViewState {
+            zoom: 2.5, pan_x: -40.0, pan_y: 12.0, last_selected_marker: Some(marker_id),
+        })).unwrap();
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert_eq!(data.images[0].view_state.as_ref().unwrap().selected_marker, Some(0));
+
+        let target = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        import_bf_data_direct(target, data).unwrap();
+        let target_image = crate::storage::project::get_project_image_ids_storage(target).unwrap()[0];
+        let image = crate::storage::image::get_image_storage(target_image).unwrap().unwrap();
+        let state = image.metadata.view_state.clone().unwrap();
+        assert_eq!((state.zoom, state.pan_x, state.pan_y), (2.5, -40.0, 12.0));
+        let selected = state.last_selected_marker.unwrap();
+        // 目标图片原有一个标记，导入的标记排在它后面
+        assert_eq!(image.marker_ids.last(), Some(&selected));
+    }
+
+    #[test]
+    fn test_translation_history_saved_only_when_enabled() {
+        use crate::storage::translation_history::{get_translation_history_storage, record_translation_storage};
+        use crate::storage::TranslationOrigin;
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let image_id = crate::storage::project::get_project_image_ids_storage(project_id).unwrap()[0];
+        let marker_id = crate::storage::marker::get_image_markers_storage(image_id).unwrap()[0].id;
+        record_translation_storage(marker_id, "", "机翻", TranslationOrigin::Machine).unwrap();
+        record_translation_storage(marker_id, "机翻", "人工", TranslationOrigin::Manual).unwrap();
+
+        assert!(parse_bf_file(&save_project(project_id).unwrap()).unwrap().translation_history.is_empty());
+
+        crate::storage::project::update_project_with_storage(project_id, |project| project.keep_translation_history = true).unwrap();
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert!(data.metadata.keep_translation_history);
+        assert_eq!(data.translation_history.len(), 1);
+        assert_eq!(data.translation_history[0].entries.len(), 2);
+
+        let target = crate::storage::project::create_project_storage("bf_history".to_string()).unwrap();
+        let target_image = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("p1.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(target, target_image).unwrap();
+        import_bf_data_direct(target, data).unwrap();
+        let restored = crate::storage::marker::get_image_markers_storage(target_image).unwrap()[0].id;
+        let history = get_translation_history_storage(restored).unwrap();
+        assert_eq!(history.iter().map(|e| e.origin).collect::<Vec<_>>(), vec![TranslationOrigin::Machine, TranslationOrigin::Manual]);
+    }
+
+    #[test]
+    fn test_project_notes_saved_in_metadata() {
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert!(data.metadata.notes.is_empty());
+        assert!(!serde_json::to_string(&data.metadata).unwrap().contains("notes"));
+
+        crate::storage::project::update_project_notes_storage(project_id, "敬称保留".to_string()).unwrap();
+        let data = parse_bf_file(&save_project(project_id).unwrap()).unwrap();
+        assert_eq!(data.metadata.notes, "敬称保留");
+    }
+
+    #[test]
+    fn test_validate_bf_summary_and_truncation() {
+        let project_id = project_with_marker(MarkerGeometry::Point { x: 1.0, y: 2.0 });
+        let data = save_project(project_id).unwrap();
+        let summary = validate_bf(&data).unwrap();
+        assert_eq!((summary.image_count, summary.marker_count, summary.skipped_markers), (1, 1, 0));
+        assert_eq!(summary.images, vec!["p1.png".to_string()]);
+        assert!(!summary.newer_format);
+
+        let truncated = &data[..data.len() / 2];
+        assert!(matches!(validate_bf(truncated), Err(BfFormatError::Truncated { .. })));
+        assert!(parse_bf_file(truncated).is_err());
+        assert!(matches!(validate_bf(b"not a project"), Err(BfFormatError::NotBfFile { .. })));
+    }
+
+    #[test]
+    fn test_unknown_marker_entries_skipped() {
+        let text = || "t".to_string();
+        assert!(parse_marker_entry(serde_json::json!({ "type": "point", "position": [1.0, 2.0], "style": "0", "text": text() })).is_some());
+        // 更新版本的标记类型、顶点不足的多边形
+        assert!(parse_marker_entry(serde_json::json!({ "type": "ellipse", "center": [1.0, 2.0], "style": "0", "text": text() })).is_none());
+        assert!(parse_marker_entry(serde_json::json!({ "type": "polygon", "points": [[1.0, 2.0], [3.0, 4.0]], "style": "0", "text": text() })).is_none());
+        assert_eq!(parse_format_version("1.1"), Some((1, 1)));
+        assert_eq!(parse_format_version("2"), Some((2, 0)));
+        assert_eq!(parse_format_version("x"), None);
+    }
+}
@@ -0,0 +1,521 @@
+// bf格式的JSON Schema - 供不链接本crate的第三方工具读写bf文件
+// 与bf.rs的序列化结构一一对应：改动bf.rs中写入的字段时必须同步修改这里，测试会校验两者一致
+// 仓库中的core/schema/bf.schema.json由测试生成：UPDATE_BF_SCHEMA=1 cargo test -p bubblefish-core bf_schema
+use serde_json::{json, Value};
+
+/// bf格式的JSON Schema（draft 2020-12）
+///
+/// bf文件是gzip压缩的tar包，schema描述的是以包内文件路径为键、文件内容（JSON）为值的对象
+pub fn schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "BubbleFish project file (.bf)",
+        "description": "A .bf file is a gzip-compressed tar archive of JSON files. Each property is one file in the archive, keyed by its path. Readers should ignore unknown fields; markers of unknown types (written by a newer formatVersion) are skipped.",
+        "type": "object",
+        "properties": {
+            "metadata.json": { "$ref": "#/$defs/metadata" },
+            "styles.json": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/style" },
+                "minItems": 1
+            },
+            "images.json": {
+                "description": "Pages in project order",
+                "type": "array",
+                "items": { "$ref": "#/$defs/image" }
+            },
+            "markers.json": {
+                "description": "One marker list per entry of images.json, in the same order",
+                "type": "array",
+                "items": { "type": "array", "items": { "$ref": "#/$defs/marker" } }
+            },
+            "bunny_cache.json": {
+                "description": "OCR text and machine translations; optional",
+                "type": "array",
+                "items": { "$ref": "#/$defs/bunnyCacheEntry" }
+            },
+            "translation_history.json": {
+                "description": "Only present when metadata.keepTranslationHistory is set",
+                "type": "array",
+                "items": { "$ref": "#/$defs/translationHistory" }
+            }
+        },
+        "required": ["metadata.json", "styles.json", "images.json", "markers.json"],
+        "additionalProperties": false,
+        "$defs": {
+            "metadata": {
+                "type": "object",
+                "properties": {
+                    "formatVersion": {
+                        "description": "\"1.0\", or \"1.1\" when the project contains polygon markers",
+                        "type": "string",
+                        "pattern": "^[0-9]+\\.[0-9]+$"
+                    },
+                    "exportDate": { "type": "string", "format": "date-time" },
+                    "projectName": { "type": "string" },
+                    "sourceLanguage": { "$ref": "#/$defs/language" },
+                    "targetLanguage": { "$ref": "#/$defs/language" },
+                    "thumbnailOrder": { "enum": ["manual", "name", "completion"] },
+                    "pinnedImages": {
+                        "description": "Indices into images.json",
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0 }
+                    },
+                    "readingDirection": { "enum": ["leftToRight", "rightToLeft"] },
+                    "markerTypes": { "type": "array", "items": { "$ref": "#/$defs/markerTypePreset" } },
+                    "defaultServices": { "$ref": "#/$defs/serviceDefaults" },
+                    "glossary": { "$ref": "#/$defs/glossary" },
+                    "notes": { "type": "string" },
+                    "autoOcrOnCreate": { "type": "boolean" },
+                    "keepTranslationHistory": { "type": "boolean" },
+                    "grayscaleReencode": { "type": "boolean" }
+                },
+                "required": ["formatVersion", "exportDate", "projectName"],
+                "additionalProperties": false
+            },
+            "language": { "enum": ["japanese", "english", "simplifiedChinese", "traditionalChinese"] },
+            "markerTypePreset": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "overlayText": { "type": "boolean" },
+                    "horizontal": { "type": "boolean" },
+                    "tag": { "type": "string" }
+                },
+                "required": ["name", "overlayText", "horizontal"],
+                "additionalProperties": false
+            },
+            "serviceDefaults": {
+                "type": "object",
+                "properties": {
+                    "ocr": { "type": "string" },
+                    "translation": { "type": "string" },
+                    "disabled": { "type": "array", "items": { "type": "string" } },
+                    "ocrFallbacks": { "type": "array", "items": { "type": "string" } },
+                    "translationFallbacks": { "type": "array", "items": { "type": "string" } }
+                },
+                "additionalProperties": false
+            },
+            "glossary": {
+                "type": "object",
+                "properties": {
+                    "entries": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "source": { "type": "string" },
+                                "target": { "type": "string" }
+                            },
+                            "required": ["source", "target"],
+                            "additionalProperties": false
+                        }
+                    },
+                    "forbiddenTerms": { "type": "array", "items": { "type": "string" } }
+                },
+                "additionalProperties": false
+            },
+            "style": {
+                "type": "object",
+                "properties": {
+                    "id": { "description": "Referenced by marker.style", "type": "string" },
+                    "overlayText": { "type": "boolean" },
+                    "horizontal": { "type": "boolean" },
+                    "sizeHint": { "type": "number" },
+                    "tag": { "type": "string" }
+                },
+                "required": ["id", "overlayText", "horizontal"],
+                "additionalProperties": false
+            },
+            "image": {
+                "type": "object",
+                "properties": {
+                    "filename": { "description": "Image file name, resolved next to the .bf file", "type": "string" },
+                    "checksum": { "type": "string", "pattern": "^md5:[0-9a-f]{32}$" },
+                    "reviewed": { "type": "boolean" },
+                    "review_note": { "type": "string" },
+                    "locked": { "type": "boolean" },
+                    "view_state": { "$ref": "#/$defs/viewState" }
+                },
+                "required": ["filename"],
+                "additionalProperties": false
+            },
+            "viewState": {
+                "type": "object",
+                "properties": {
+                    "zoom": { "type": "number" },
+                    "panX": { "type": "number" },
+                    "panY": { "type": "number" },
+                    "selectedMarker": {
+                        "description": "Index into this page's marker list",
+                        "type": "integer",
+                        "minimum": 0
+                    }
+                },
+                "required": ["zoom", "panX", "panY"],
+                "additionalProperties": false
+            },
+            "vec2": {
+                "description": "Percentages of the image width and height",
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": 2,
+                "maxItems": 2
+            },
+            "marker": {
+                "oneOf": [
+                    { "$ref": "#/$defs/pointMarker" },
+                    { "$ref": "#/$defs/rectangleMarker" },
+                    { "$ref": "#/$defs/polygonMarker" }
+                ]
+            },
+            "pointMarker": {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "point" },
+                    "position": { "$ref": "#/$defs/vec2" },
+                    "style": { "type": "string" },
+                    "text": { "type": "string" },
+                    "note": { "type": "string" }
+                },
+                "required": ["type", "position", "style", "text"],
+                "additionalProperties": false
+            },
+            "rectangleMarker": {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "rectangle" },
+                    "position": { "$ref": "#/$defs/vec2" },
+                    "size": { "$ref": "#/$defs/vec2" },
+                    "style": { "type": "string" },
+                    "text": { "type": "string" },
+                    "note": { "type": "string" }
+                },
+                "required": ["type", "position", "size", "style", "text"],
+                "additionalProperties": false
+            },
+            "polygonMarker": {
+                "description": "Format version 1.1",
+                "type": "object",
+                "properties": {
+                    "type": { "const": "polygon" },
+                    "points": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/vec2" },
+                        "minItems": crate::storage::marker::MIN_POLYGON_POINTS
+                    },
+                    "style": { "type": "string" },
+                    "text": { "type": "string" },
+                    "note": { "type": "string" }
+                },
+                "required": ["type", "points", "style", "text"],
+                "additionalProperties": false
+            },
+            "bunnyCacheEntry": {
+                "type": "object",
+                "properties": {
+                    "pageIndex": { "type": "integer", "minimum": 0 },
+                    "markerIndex": { "type": "integer", "minimum": 0 },
+                    "originalText": { "type": "string" },
+                    "machineTranslation": { "type": "string" },
+                    "lastOcrModel": { "type": "string" },
+                    "lastTranslationService": { "type": "string" },
+                    "translationWarnings": { "type": "array", "items": { "$ref": "#/$defs/translationWarning" } },
+                    "translationMetadata": { "description": "Opaque data attached by the translation plugin" },
+                    "machineTranslatedAt": { "description": "Unix time in milliseconds", "type": "integer", "minimum": 0 }
+                },
+                "required": ["pageIndex", "markerIndex"],
+                "additionalProperties": false
+            },
+            "translationWarning": {
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "const": "missing_term" },
+                            "source": { "type": "string" },
+                            "target": { "type": "string" }
+                        },
+                        "required": ["kind", "source", "target"],
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "const": "forbidden_term" },
+                            "term": { "type": "string" }
+                        },
+                        "required": ["kind", "term"],
+                        "additionalProperties": false
+                    }
+                ]
+            },
+            "translationHistory": {
+                "type": "object",
+                "properties": {
+                    "pageIndex": { "type": "integer", "minimum": 0 },
+                    "markerIndex": { "type": "integer", "minimum": 0 },
+                    "entries": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "translation": { "type": "string" },
+                                "timestamp": { "description": "Unix time in milliseconds", "type": "integer", "minimum": 0 },
+                                "origin": { "enum": ["manual", "machine", "import"] }
+                            },
+                            "required": ["translation", "timestamp", "origin"],
+                            "additionalProperties": false
+                        }
+                    }
+                },
+                "required": ["pageIndex", "markerIndex", "entries"],
+                "additionalProperties": false
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::dto::image::ImageViewState;
+    use crate::common::*;
+    use crate::storage::marker::{Marker, MarkerGeometry, MarkerStyle};
+    use crate::storage::state::APP_STATE;
+    use crate::storage::translation_history::{TranslationHistoryEntry, TranslationOrigin};
+    use std::collections::HashSet;
+
+    // 测试用的最小校验器，只支持schema()中用到的关键字；pattern/format只作说明，这里不检查
+    // seen记录校验时经过的属性声明，用来确认样例覆盖了schema中的每个字段
+    fn validate(root: &Value, path: &str, schema: &Value, value: &Value, at: &str, seen: &mut HashSet<String>) -> Result<(), String> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let def_path = reference.trim_start_matches('#');
+            let def = root.pointer(def_path).ok_or_else(|| format!("unresolved $ref {}", reference))?;
+            return validate(root, def_path, def, value, at, seen);
+        }
+        if let Some(branches) = schema.get("oneOf").and_then(Value::as_array) {
+            let mut matched = Vec::new();
+            let mut errors = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                let mut branch_seen = HashSet::new();
+                match validate(root, &format!("{}/oneOf/{}", path, i), branch, value, at, &mut branch_seen) {
+                    Ok(()) => matched.push(branch_seen),
+                    Err(e) => errors.push(e),
+                }
+            }
+            if matched.len() != 1 {
+                return Err(format!("{}: matches {} of the oneOf branches ({})", at, matched.len(), errors.join("; ")));
+            }
+            seen.extend(matched.pop().unwrap());
+        }
+        if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+            let ok = match expected {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "boolean" => value.is_boolean(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "number" => value.is_number(),
+                _ => return Err(format!("unsupported type {}", expected)),
+            };
+            if !ok {
+                return Err(format!("{}: expected {}, got {}", at, expected, value));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.contains(value) {
+            return Err(format!("{}: {} is not one of {:?}", at, value, options));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value {
+            return Err(format!("{}: expected {}, got {}", at, expected, value));
+        }
+        if let (Some(minimum), Some(number)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64())
+            && number < minimum {
+            return Err(format!("{}: {} is below {}", at, number, minimum));
+        }
+        if let Some(items) = value.as_array() {
+            let len = items.len() as u64;
+            if schema.get("minItems").and_then(Value::as_u64).is_some_and(|min| len < min)
+                || schema.get("maxItems").and_then(Value::as_u64).is_some_and(|max| len > max) {
+                return Err(format!("{}: unexpected length {}", at, len));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(root, &format!("{}/items", path), item_schema, item, &format!("{}[{}]", at, i), seen)?;
+                }
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                let key = required.as_str().unwrap();
+                if !object.contains_key(key) {
+                    return Err(format!("{}: missing required {}", at, key));
+                }
+            }
+            for (key, field) in object {
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(field_schema) => {
+                        let field_path = format!("{}/properties/{}", path, key);
+                        validate(root, &field_path, field_schema, field, &format!("{}.{}", at, key), seen)?;
+                        seen.insert(field_path);
+                    }
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        return Err(format!("{}: unexpected field {}", at, key));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn declared_properties(path: &str, schema: &Value, declared: &mut Vec<String>) {
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, field) in properties {
+                let field_path = format!("{}/properties/{}", path, key);
+                declared_properties(&field_path, field, declared);
+                declared.push(field_path);
+            }
+        }
+        if let Some(items) = schema.get("items") {
+            declared_properties(&format!("{}/items", path), items, declared);
+        }
+        for (i, branch) in schema.get("oneOf").and_then(Value::as_array).into_iter().flatten().enumerate() {
+            declared_properties(&format!("{}/oneOf/{}", path, i), branch, declared);
+        }
+        for (name, def) in schema.get("$defs").and_then(Value::as_object).into_iter().flatten() {
+            declared_properties(&format!("/$defs/{}", name), def, declared);
+        }
+    }
+
+    fn add_marker(image_id: ImageId, geometry: MarkerGeometry, translation: &str, note: Option<&str>) -> MarkerId {
+        let marker_id = MARKER_ID_GENERATOR.next();
+        let mut marker = Marker::new_point(marker_id, image_id, 0.0, 0.0, 1);
+        marker.geometry = geometry;
+        marker.translation = translation.to_string();
+        marker.note = note.map(str::to_string);
+        APP_STATE.markers.write().unwrap().insert_with_image(marker).unwrap();
+        crate::storage::image::add_marker_to_image_storage(image_id, marker_id).unwrap();
+        marker_id
+    }
+
+    // 每个可选字段都有值的项目，序列化后应覆盖schema中的所有字段
+    fn sample_project() -> ProjectId {
+        use crate::storage::image::*;
+        let project_id = crate::storage::project::create_project_storage("bf_schema".to_string()).unwrap();
+        let mut image_ids = Vec::new();
+        for name in ["01.png", "02.png"] {
+            let image_id = add_image_from_binary_storage(crate::storage::ImageFormat::Png, vec![0u8; 16], Some(name.to_string())).unwrap();
+            crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+            image_ids.push(image_id);
+        }
+        let page = image_ids[0];
+        let point = add_marker(page, MarkerGeometry::Point { x: 10.0, y: 20.0 }, "译文", Some("备注"));
+        let rect = add_marker(page, MarkerGeometry::Rectangle { x: 1.0, y: 2.0, width: 3.0, height: 4.0 }, "", Some("框"));
+        add_marker(page, MarkerGeometry::Polygon { points: vec![(10.0, 10.0), (40.0, 20.0), (30.0, 50.0)] }, "", Some("多边形"));
+        let style = MarkerStyle { size_hint: Some(1.5), tag: Some("SFX".to_string()), ..MarkerStyle::new(true, false) };
+        crate::storage::marker::update_marker_style_storage(rect, style).unwrap();
+
+        set_image_review_storage(page, true, Some("已校对".to_string())).unwrap();
+        set_image_locked_storage(page, true).unwrap();
+        set_image_view_state_storage(page, Some(ImageViewState { zoom: 1.5, pan_x: 10.0, pan_y: -5.0, last_selected_marker: Some(rect) })).unwrap();
+
+        crate::storage::project::update_project_with_storage(project_id, |project| {
+            project.source_language = Language::Japanese;
+            project.target_language = Language::SimplifiedChinese;
+            project.thumbnail_order = ThumbnailOrder::Completion;
+            project.pinned_images = vec![image_ids[1]];
+            project.reading_direction = ReadingDirection::RightToLeft;
+            project.marker_types = vec![MarkerTypePreset { name: "框外".to_string(), overlay_text: false, horizontal: true, tag: Some("SFX".to_string()) }];
+            project.default_services = ServiceDefaults {
+                ocr: Some("ocr".to_string()),
+                translation: Some("mt".to_string()),
+                disabled: vec!["old".to_string()],
+                ocr_fallbacks: vec!["ocr2".to_string()],
+                translation_fallbacks: vec!["mt2".to_string()],
+            };
+            project.glossary = ProjectGlossary {
+                entries: vec![GlossaryEntry { source: "先輩".to_string(), target: "前辈".to_string() }],
+                forbidden_terms: vec!["学长".to_string()],
+            };
+            project.notes = "项目备注".to_string();
+            project.auto_ocr_on_create = true;
+            project.keep_translation_history = true;
+            project.grayscale_reencode = true;
+        }).unwrap();
+
+        crate::storage::bunny_cache::update_original_text_storage(point, "先輩".to_string(), "ocr".to_string()).unwrap();
+        crate::storage::bunny_cache::update_machine_translation_storage(point, "学长".to_string(), "mt".to_string(), vec![
+            TranslationWarning::MissingTerm { source: "先輩".to_string(), target: "前辈".to_string() },
+            TranslationWarning::ForbiddenTerm { term: "学长".to_string() },
+        ], Some(json!({ "model": "dummy" }))).unwrap();
+        crate::storage::translation_history::set_translation_history_storage(point, vec![
+            TranslationHistoryEntry { translation: "译文".to_string(), timestamp: 1, origin: TranslationOrigin::Manual },
+        ]).unwrap();
+        project_id
+    }
+
+    // 把bf包中的文件读成{路径: JSON}
+    fn bf_as_json(data: &[u8]) -> Value {
+        let entries = super::super::bf::read_bf_entries(data).unwrap();
+        Value::Object(entries.into_iter()
+            .map(|(name, content)| (name, serde_json::from_str(&content).unwrap()))
+            .collect())
+    }
+
+    #[test]
+    fn test_sample_project_matches_bf_schema() {
+        let schema = schema();
+        let data = super::super::bf::save_project(sample_project()).unwrap();
+        let files = bf_as_json(&data);
+        let mut seen = HashSet::new();
+        validate(&schema, "", &schema, &files, "bf", &mut seen).unwrap();
+
+        let mut declared = Vec::new();
+        declared_properties("", &schema, &mut declared);
+        let missing: Vec<&String> = declared.iter().filter(|path| !seen.contains(*path)).collect();
+        assert!(missing.is_empty(), "schema declares fields the serializer never wrote: {:?}", missing);
+
+        // 读回的结构再序列化，与文件内容一致
+        let parsed = super::super::bf::parse_bf_file(&data).unwrap();
+        for (name, part) in [
+            ("metadata.json", serde_json::to_value(&parsed.metadata)),
+            ("styles.json", serde_json::to_value(&parsed.styles)),
+            ("images.json", serde_json::to_value(&parsed.images)),
+            ("markers.json", serde_json::to_value(&parsed.markers)),
+            ("bunny_cache.json", serde_json::to_value(&parsed.bunny_cache)),
+            ("translation_history.json", serde_json::to_value(&parsed.translation_history)),
+        ] {
+            assert_eq!(part.unwrap(), files[name], "{} does not round-trip", name);
+        }
+    }
+
+    #[test]
+    fn test_bf_schema_rejects_unknown_and_missing_fields() {
+        let schema = schema();
+        let mut files = bf_as_json(&super::super::bf::save_project(sample_project()).unwrap());
+        let mut seen = HashSet::new();
+        files["markers.json"][0][0]["color"] = json!("red");
+        assert!(validate(&schema, "", &schema, &files, "bf", &mut seen).unwrap_err().contains("unexpected field color"));
+
+        files["markers.json"][0][0].as_object_mut().unwrap().remove("color");
+        files["images.json"][0].as_object_mut().unwrap().remove("filename");
+        assert!(validate(&schema, "", &schema, &files, "bf", &mut seen).unwrap_err().contains("missing required filename"));
+    }
+
+    #[test]
+    fn test_bf_schema_file_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schema/bf.schema.json");
+        let generated = format!("{}\n", serde_json::to_string_pretty(&schema()).unwrap());
+        if std::env::var("UPDATE_BF_SCHEMA").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &generated).unwrap();
+            return;
+        }
+        let existing = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(existing, generated, "schema/bf.schema.json is stale, rerun with UPDATE_BF_SCHEMA=1");
+    }
+}
@@ -0,0 +1,107 @@
+// 项目内容哈希 - 对标记、样式、译文和图片顺序做规范化序列化后计算MD5
+// 不包含会随会话变化的内部ID，外部工具可据此判断项目自上次导出后是否有改动
+use md5::{Digest, Md5};
+use serde_json::json;
+use crate::common::{CoreError, CoreResult, ProjectId};
+use crate::storage::marker::Marker;
+use crate::storage::state::APP_STATE;
+
+/// 参与哈希的单张图片：图片名（按项目中的顺序）及其标记
+pub struct HashedImage {
+    pub name: String,
+    pub markers: Vec<Marker>,
+}
+
+fn canonical_marker(marker: &Marker) -> CoreResult<String> {
+    let value = json!({
+        "index": marker.image_index,
+        "geometry": marker.geometry,
+        "translation": marker.translation,
+        "style": marker.style,
+    });
+    serde_json::to_string(&value).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+/// 规范化序列化：图片按项目顺序，标记按序号排序（序号相同时按内容排序），与存储中的遍历顺序无关
+pub fn canonical_bytes(images: &[HashedImage]) -> CoreResult<Vec<u8>> {
+    let mut out = Vec::new();
+    for image in images {
+        let mut markers = image.markers.iter()
+            .map(|m| canonical_marker(m).map(|s| (m.image_index, s)))
+            .collect::<CoreResult<Vec<_>>>()?;
+        markers.sort();
+
+        let name = serde_json::to_string(&image.name).map_err(|e| CoreError::SerializationError(e.to_string()))?;
+        out.extend_from_slice(b"image ");
+        out.extend_from_slice(name.as_bytes());
+        out.push(b'\n');
+        for (_, marker) in markers {
+            out.extend_from_slice(marker.as_bytes());
+            out.push(b'\n');
+        }
+    }
+    Ok(out)
+}
+
+pub fn hash_images(images: &[HashedImage]) -> CoreResult<String> {
+    let mut hasher = Md5::new();
+    hasher.update(canonical_bytes(images)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 计算项目的内容哈希，内容不变时哈希在不同会话、不同平台间保持一致
+pub fn project_content_hash(project_id: ProjectId) -> CoreResult<String> {
+    let project = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    let mut images = Vec::with_capacity(project.image_ids.len());
+    for (position, image_id) in project.image_ids.iter().enumerate() {
+        let name = APP_STATE.get_image(*image_id)?
+            .and_then(|image| image.metadata.name.clone())
+            .unwrap_or_else(|| format!("image_{:03}", position + 1));
+        let markers = crate::storage::marker::get_image_markers_storage(*image_id)?;
+        images.push(HashedImage { name, markers });
+    }
+    hash_images(&images)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{ImageId, MarkerId};
+
+    fn marker(id: u32, image_id: u32, index: u32, translation: &str) -> Marker {
+        let mut marker = Marker::new_rectangle(MarkerId(id), ImageId(image_id), 0.1 * index as f64, 0.2, 0.3, 0.1, index);
+        marker.translation = translation.to_string();
+        marker
+    }
+
+    fn images(markers: Vec<Marker>) -> Vec<HashedImage> {
+        vec![
+            HashedImage { name: "p01.png".to_string(), markers },
+            HashedImage { name: "p02.png".to_string(), markers: Vec::new() },
+        ]
+    }
+
+    #[test]
+    fn test_hash_ignores_ids_and_storage_order() {
+        let a = images(vec![marker(1, 1, 1, "你好"), marker(2, 1, 2, "世界"), marker(3, 1, 2, "重复序号")]);
+        // 同样的内容，不同的ID和遍历顺序
+        let b = images(vec![marker(30, 9, 2, "重复序号"), marker(10, 9, 1, "你好"), marker(20, 9, 2, "世界")]);
+        assert_eq!(hash_images(&a).unwrap(), hash_images(&b).unwrap());
+    }
+
+    #[test]
+    fn test_hash_detects_content_changes() {
+        let base = hash_images(&images(vec![marker(1, 1, 1, "你好")])).unwrap();
+        assert_ne!(base, hash_images(&images(vec![marker(1, 1, 1, "你好！")])).unwrap());
+
+        let mut styled = marker(1, 1, 1, "你好");
+        styled.style.horizontal = true;
+        assert_ne!(base, hash_images(&images(vec![styled])).unwrap());
+
+        // 图片顺序变化
+        let mut reordered = images(vec![marker(1, 1, 1, "你好")]);
+        reordered.reverse();
+        assert_ne!(base, hash_images(&reordered).unwrap());
+    }
+}
@@ -22,6 +22,12 @@ impl EventHandler for IoEventHandler {
             DomainEvent::ParseBfRequested(project_id, data) => {
                 handle_parse_bf(*project_id, data.clone());
             },
+            DomainEvent::OpeningProjectFinalized(project_id) => {
+                // 项目文件夹被移动过时，记录的图片路径可能已不存在
+                if let Err(e) = super::relink::report_missing_images(*project_id) {
+                    Logger::warn(&format!("Failed to check missing images: {}", e));
+                }
+            },
             _ => {} // 忽略其他事件
         }
     }
@@ -157,7 +163,23 @@ fn handle_parse_bf(project_id: ProjectId, data: Vec<u8>) {
                 if let Some(target_lang) = bf_data.metadata.target_language {
                     opening_project.project.target_language = target_lang;
                 }
+                if let Some(thumbnail_order) = bf_data.metadata.thumbnail_order {
+                    opening_project.project.thumbnail_order = thumbnail_order;
+                }
+                // 旧文件没有阅读方向时按源语言决定
+                opening_project.project.reading_direction = bf_data.metadata.reading_direction
+                    .unwrap_or_else(|| crate::common::ReadingDirection::default_for(opening_project.project.source_language));
+                opening_project.project.marker_types = bf_data.metadata.marker_types.clone();
+                if let Some(default_services) = bf_data.metadata.default_services.clone() {
+                    opening_project.project.default_services = default_services;
+                }
+                opening_project.project.glossary = bf_data.metadata.glossary.clone();
+                opening_project.project.notes = bf_data.metadata.notes.clone();
+                opening_project.project.auto_ocr_on_create = bf_data.metadata.auto_ocr_on_create;
+                opening_project.project.keep_translation_history = bf_data.metadata.keep_translation_history;
+                opening_project.project.grayscale_reencode = bf_data.metadata.grayscale_reencode;
                 
+                opening_project.warnings = super::bf::check_bf_integrity(&bf_data);
                 opening_project.bf_data = Some(bf_data);
                 opening_project.required_images = required_images.clone();
                 opening_project.pending_images = required_images;
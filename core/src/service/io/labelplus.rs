@@ -1,4 +1,5 @@
-use crate::common::{CoreError, CoreResult};
+use crate::common::{CoreError, CoreResult, Logger};
+use crate::common::events::schema::{BusinessEvent, ImportCompleteEvent, ImportProgressEvent};
 use crate::common::{ProjectId, ImageId, MarkerId, MARKER_ID_GENERATOR};
 use crate::storage::state::APP_STATE;
 use crate::storage::traits::Storage;
@@ -11,6 +12,9 @@ use serde::{Serialize, Deserialize};
 pub struct MarkerTypeStyleConfig {
     pub overlay_text: bool,
     pub horizontal: bool,
+    /// 标记标签（如"SFX"），导入时赋给标记，导出时优先匹配标签相同的类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 impl Default for MarkerTypeStyleConfig {
@@ -18,6 +22,7 @@ impl Default for MarkerTypeStyleConfig {
         Self {
             overlay_text: false,
             horizontal: false,
+            tag: None,
         }
     }
 }
@@ -47,13 +52,14 @@ impl Default for MarkerTypeStyleMapping {
 // 获取默认的样式映射配置
 // 在这里配置类型名称到样式的映射
 // 优先匹配排在前面的
-fn get_default_style_mapping() -> MarkerTypeStyleMapping {
+pub fn get_default_style_mapping() -> MarkerTypeStyleMapping {
     let type_mappings = vec![
         MarkerTypeMapping {
             name: "inside+vertical".to_string(),
             style: MarkerTypeStyleConfig {
                 overlay_text: false,
                 horizontal: false,
+                tag: None,
             },
         },
 
@@ -62,6 +68,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
             style: MarkerTypeStyleConfig {
                 overlay_text: true,
                 horizontal: false,
+                tag: None,
             },
         },
 
@@ -70,6 +77,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
             style: MarkerTypeStyleConfig {
                 overlay_text: false,
                 horizontal: true,
+                tag: None,
             },
         },
 
@@ -78,6 +86,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
             style: MarkerTypeStyleConfig {
                 overlay_text: true,
                 horizontal: true,
+                tag: None,
             },
         },
 
@@ -87,6 +96,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
             style: MarkerTypeStyleConfig {
                 overlay_text: true,
                 horizontal: false,
+                tag: None,
             },
         },
         // 框内 - 兼容旧版本, 只在导入时使用
@@ -95,6 +105,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
             style: MarkerTypeStyleConfig {
                 overlay_text: false,
                 horizontal: false,
+                tag: None,
             },
         },
     ];
@@ -104,6 +115,7 @@ fn get_default_style_mapping() -> MarkerTypeStyleMapping {
         fallback_style: MarkerTypeStyleConfig {
             overlay_text: false,
             horizontal: false,
+            tag: None,
         },
     }
 }
@@ -121,6 +133,9 @@ pub struct LabelplusMarker {
     pub y: f64,
     pub type_id: u32,
     pub translation: String,
+    /// 译者备注，写在项目注释块中（见NOTE_PREFIX），LabelPlus本身会忽略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +145,37 @@ pub struct LabelplusData {
     pub image_order: Vec<String>,  // 保持图片的原始顺序
 }
 
+// 标记备注行：写在项目注释块中，LabelPlus只把注释当作项目说明，不会显示到译文里
+// 格式为前缀 + JSON，JSON转义换行，图片名和备注中的任意字符都不影响解析
+pub const NOTE_PREFIX: &str = "#bubblefish-note ";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NoteLine {
+    image: String,
+    index: u32,
+    note: String,
+}
+
+fn parse_note_line(line: &str) -> Option<NoteLine> {
+    serde_json::from_str(line.strip_prefix(NOTE_PREFIX)?).ok()
+}
+
+fn format_note_line(image: &str, index: u32, note: &str) -> String {
+    let line = NoteLine { image: image.to_string(), index, note: note.to_string() };
+    format!("{}{}", NOTE_PREFIX, serde_json::to_string(&line).unwrap_or_default())
+}
+
+// 图片分隔行：>>>>>>>>[图片名]<<<<<<<<
+fn is_image_header(line: &str) -> bool {
+    line.starts_with(">>>>>>>>[") && line.ends_with("]<<<<<<<<")
+}
+
+// 标记分隔行：----------------[序号]----------------[x,y,类型]
+// 只有完整的分隔行才结束译文，以相同前缀开头的译文行原样保留
+fn is_marker_header(line: &str) -> bool {
+    line.starts_with("----------------[") && line.contains("]----------------[") && line.ends_with(']')
+}
+
 pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
     // Remove UTF-8 BOM if present
     let content = if content.starts_with('\u{feff}') {
@@ -196,8 +242,12 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
     }
     idx += 1;
 
-    // Skip project comments until we find image separator
-    while idx < lines.len() && !lines[idx].starts_with(">>>>>>>>") {
+    // Skip project comments until we find image separator，其中的标记备注行留到最后匹配
+    let mut note_lines = Vec::new();
+    while idx < lines.len() && !is_image_header(lines[idx]) {
+        if let Some(note) = parse_note_line(lines[idx]) {
+            note_lines.push(note);
+        }
         idx += 1;
     }
 
@@ -211,7 +261,7 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
         let line = lines[idx];
         
         // Check for image separator
-        if line.starts_with(">>>>>>>>[") && line.ends_with("]<<<<<<<<") {
+        if is_image_header(line) {
             // Save previous image markers (even if empty)
             if let Some(img_name) = current_image.take() {
                 // Always insert the image, even with empty markers
@@ -229,7 +279,7 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
         }
         
         // Check for marker separator
-        if line.starts_with("----------------[") && line.contains("]----------------[") {
+        if is_marker_header(line) {
             // Parse marker info
             let parts: Vec<&str> = line.split("]----------------[").collect();
             if parts.len() != 2 {
@@ -287,7 +337,7 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
             
             while idx < lines.len() {
                 let next_line = lines[idx];
-                if next_line.starts_with("----------------[") || next_line.starts_with(">>>>>>>>") {
+                if is_marker_header(next_line) || is_image_header(next_line) {
                     break;
                 }
                 translation_lines.push(next_line);
@@ -307,6 +357,7 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
                 y,
                 type_id,
                 translation,
+                note: None,
             });
             
             continue;
@@ -321,6 +372,15 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
         markers_by_image.insert(img_name, current_markers);
     }
 
+    // 备注按图片名和标记序号对应，找不到标记的备注丢弃
+    for line in note_lines {
+        if let Some(marker) = markers_by_image.get_mut(&line.image)
+            .and_then(|markers| markers.iter_mut().find(|m| m.image_index == line.index))
+        {
+            marker.note = Some(line.note);
+        }
+    }
+
     Ok(LabelplusData {
         marker_types,
         markers_by_image,
@@ -328,114 +388,205 @@ pub fn parse_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
     })
 }
 
-// Direct import without triggering undo/redo
-pub fn import_labelplus_data_direct(
-    project_id: ProjectId,
-    labelplus_data: LabelplusData,
-) -> CoreResult<()> {
-    // 使用内置的默认样式映射
-    let style_mapping = Some(get_default_style_mapping());
-    // Get project images to match with translation file image names
+// LabelPlus坐标应为归一化值(0-1)，出现超过该阈值的值时视为像素坐标
+pub const PIXEL_COORDINATE_THRESHOLD: f64 = 1.5;
+
+/// 检测一张图片的标记是否使用了像素坐标而不是归一化坐标
+pub fn is_pixel_coordinates(markers: &[LabelplusMarker]) -> bool {
+    markers.iter().any(|m| m.x > PIXEL_COORDINATE_THRESHOLD || m.y > PIXEL_COORDINATE_THRESHOLD)
+}
+
+/// LabelPlus坐标的原点：大多数文件以左上角为(0,0)，部分变体以其他角为原点或y轴向上
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelplusCoordinateOrigin {
+    #[default]
+    TopLeft,
+    TopRight,
+    /// y轴向上
+    BottomLeft,
+    BottomRight,
+}
+
+impl LabelplusCoordinateOrigin {
+    /// 把以该原点表示的百分比坐标转换为以左上角为原点
+    pub fn to_top_left(self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            Self::TopLeft => (x, y),
+            Self::TopRight => (100.0 - x, y),
+            Self::BottomLeft => (x, 100.0 - y),
+            Self::BottomRight => (100.0 - x, 100.0 - y),
+        }
+    }
+}
+
+/// 将一张图片的LabelPlus坐标转换为百分比坐标(0-100)
+/// 像素坐标会根据图片尺寸自动归一化；无法获取尺寸时只能限制到图片范围内
+pub fn labelplus_to_percentage(markers: &[LabelplusMarker], dimensions: Option<(u32, u32)>, origin: LabelplusCoordinateOrigin) -> Vec<(f64, f64)> {
+    let scale = match (is_pixel_coordinates(markers), dimensions) {
+        (true, Some((width, height))) if width > 0 && height > 0 => (100.0 / width as f64, 100.0 / height as f64),
+        _ => (100.0, 100.0),
+    };
+
+    markers.iter()
+        .map(|m| {
+            let (x, y) = origin.to_top_left(m.x * scale.0, m.y * scale.1);
+            match (MarkerGeometry::Point { x, y }).clamped() {
+                MarkerGeometry::Point { x, y } => (x, y),
+                other => other.anchor(),
+            }
+        })
+        .collect()
+}
+
+/// 按某个原点导入时第一个标记的位置，用于导入前让用户确认坐标是否对齐
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelplusOriginPreview {
+    pub image: String,
+    /// 翻译文件中的序号
+    pub index: u32,
+    /// 导入后的百分比坐标(0-100)，以左上角为原点
+    pub x: f64,
+    pub y: f64,
+    /// 像素坐标需要图片尺寸才能换算，没有尺寸时位置只是限制到图片范围内的近似值
+    pub pixel_coordinates: bool,
+}
+
+/// 按文件中的图片顺序找到第一个标记，计算它按origin导入后的位置
+pub fn preview_labelplus_origin(
+    labelplus_data: &LabelplusData,
+    origin: LabelplusCoordinateOrigin,
+    dimensions: impl Fn(&str) -> Option<(u32, u32)>,
+) -> Option<LabelplusOriginPreview> {
+    let (image, markers) = labelplus_data.image_order.iter()
+        .find_map(|image| labelplus_data.markers_by_image.get(image)
+            .filter(|markers| !markers.is_empty())
+            .map(|markers| (image, markers)))?;
+    let first = markers.iter()
+        .enumerate()
+        .min_by_key(|(_, m)| m.image_index)
+        .map(|(i, _)| i)?;
+    let pixel_coordinates = is_pixel_coordinates(markers);
+    let positions = labelplus_to_percentage(markers, dimensions(image), origin);
+    let (x, y) = positions[first];
+    Some(LabelplusOriginPreview {
+        image: image.clone(),
+        index: markers[first].image_index,
+        x,
+        y,
+        pixel_coordinates,
+    })
+}
+
+// 项目中的图片：名称 -> (ImageId, 原图尺寸)，用于和翻译文件中的图片名匹配
+type ImagesByName = HashMap<String, (ImageId, Option<(u32, u32)>)>;
+
+fn project_images_by_name(project_id: ProjectId) -> CoreResult<ImagesByName> {
     let project_storage = APP_STATE.projects.read()?;
     let project = project_storage.get(&project_id)
         .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
     let image_ids = project.image_ids.clone();
     drop(project_storage);
 
-    // Build image name to ID mapping
-    let mut image_name_to_id = HashMap::new();
+    // 与导出一样给重名图片加后缀，同名时后面的图片不会被前面的覆盖
     let image_storage = APP_STATE.images.read()?;
+    let mut names = Vec::new();
+    let mut entries = Vec::new();
     for image_id in &image_ids {
         if let Some(image) = image_storage.get(image_id) {
-            let name = image.metadata.name.clone().unwrap_or_else(|| format!("image_{}", image_id.0));
-            image_name_to_id.insert(name, *image_id);
+            names.push(image.metadata.name.clone().unwrap_or_else(|| format!("image_{}", image_id.0)));
+            entries.push((*image_id, image.metadata.source_dimensions()));
         }
     }
-    drop(image_storage);
+    Ok(crate::storage::image::dedupe_image_names(&names).into_iter().zip(entries).collect())
+}
 
-    // Build type_id to name mapping for style lookup (types are numbered 1-9)
-    let mut type_id_to_name: HashMap<u32, String> = HashMap::new();
-    for marker_type in &labelplus_data.marker_types {
-        type_id_to_name.insert(marker_type.id, marker_type.name.clone());
+// 计算一张图片上标记的百分比坐标，像素坐标按图片尺寸归一化
+fn image_marker_positions(image_id: ImageId, image_name: &str, markers: &[LabelplusMarker], dimensions: Option<(u32, u32)>, origin: LabelplusCoordinateOrigin) -> Vec<(f64, f64)> {
+    let dimensions = if is_pixel_coordinates(markers) {
+        let dimensions = dimensions.or_else(|| {
+            APP_STATE.get_image(image_id).ok().flatten().and_then(|image| image.data.get_dimensions())
+        });
+        Logger::warn_with_data(
+            "LabelPlus文件使用了像素坐标，已按图片尺寸自动归一化",
+            serde_json::json!({
+                "image": image_name,
+                "dimensions": dimensions,
+            })
+        );
+        dimensions
+    } else {
+        None
+    };
+    labelplus_to_percentage(markers, dimensions, origin)
+}
+
+// 项目自定义的标记类型排在默认映射前面，优先匹配
+fn project_style_mapping(project_id: ProjectId) -> CoreResult<MarkerTypeStyleMapping> {
+    let mut mapping = get_default_style_mapping();
+    if let Some(project) = APP_STATE.get_project(project_id)? {
+        let custom = project.marker_types.into_iter().map(|preset| MarkerTypeMapping {
+            name: preset.name,
+            style: MarkerTypeStyleConfig {
+                overlay_text: preset.overlay_text,
+                horizontal: preset.horizontal,
+                tag: preset.tag,
+            },
+        });
+        mapping.type_mappings.splice(0..0, custom);
     }
-    
-    // Import markers for each image
+    Ok(mapping)
+}
+
+// 按类型名称查找样式（先匹配的优先），找不到时使用fallback样式
+fn marker_style(type_id_to_name: &HashMap<u32, String>, mapping: &MarkerTypeStyleMapping, type_id: u32) -> MarkerStyle {
+    let style_config = type_id_to_name.get(&type_id)
+        .and_then(|type_name| mapping.type_mappings.iter().find(|m| &m.name == type_name))
+        .map(|m| &m.style)
+        .unwrap_or(&mapping.fallback_style);
+    MarkerStyle {
+        overlay_text: style_config.overlay_text,
+        horizontal: style_config.horizontal,
+        tag: style_config.tag.clone(),
+        ..MarkerStyle::default()
+    }
+}
+
+// 导出时区分类型的样式：框内外、横竖排和标签
+type ExportStyleKey = (bool, bool, Option<String>);
+
+fn export_style_key(style: &MarkerStyle) -> ExportStyleKey {
+    (style.overlay_text, style.horizontal, style.tag.clone())
+}
+
+// 先找框内外、横竖排和标签都相同的类型；没有时退回不带标签的同样式类型
+fn export_type_name(mapping: &MarkerTypeStyleMapping, key: &ExportStyleKey) -> Option<String> {
+    let same_layout = |m: &&MarkerTypeMapping| m.style.overlay_text == key.0 && m.style.horizontal == key.1;
+    mapping.type_mappings.iter()
+        .filter(same_layout)
+        .find(|m| m.style.tag == key.2)
+        .or_else(|| mapping.type_mappings.iter().filter(same_layout).find(|m| m.style.tag.is_none()))
+        .map(|m| m.name.clone())
+}
+
+fn type_names(labelplus_data: &LabelplusData) -> HashMap<u32, String> {
+    labelplus_data.marker_types.iter()
+        .map(|marker_type| (marker_type.id, marker_type.name.clone()))
+        .collect()
+}
+
+// 把新标记写入存储并追加到图片的标记列表（不经过撤销系统）
+fn insert_markers_direct(new_markers: Vec<Marker>) -> CoreResult<()> {
     let mut marker_storage = APP_STATE.markers.write()?;
     let mut image_updates: HashMap<ImageId, Vec<MarkerId>> = HashMap::new();
-    
-    for (image_name, markers) in labelplus_data.markers_by_image {
-        if let Some(&image_id) = image_name_to_id.get(&image_name) {
-            let mut marker_ids_for_image = Vec::new();
-            
-            // Import markers for this image
-            for trans_marker in markers {
-                let marker_id = MARKER_ID_GENERATOR.next();
-                
-                // Convert normalized coordinates (0-1) to percentage (0-100)
-                // Frontend uses percentage coordinates, not pixel coordinates
-                let percentage_x = trans_marker.x * 100.0;
-                let percentage_y = trans_marker.y * 100.0;
-                
-                // Determine style based on type mapping
-                let style = if let Some(ref mapping) = style_mapping {
-                    // Try to find the type name for this type_id
-                    if let Some(type_name) = type_id_to_name.get(&trans_marker.type_id) {
-                        // Try to find a style mapping for this type name (first match wins)
-                        let style_config = mapping.type_mappings.iter()
-                            .find(|m| &m.name == type_name)
-                            .map(|m| &m.style);
-                        
-                        if let Some(style_config) = style_config {
-                            MarkerStyle {
-                                overlay_text: style_config.overlay_text,
-                                horizontal: style_config.horizontal,
-                            }
-                        } else {
-                            // Use fallback style if no mapping found
-                            MarkerStyle {
-                                overlay_text: mapping.fallback_style.overlay_text,
-                                horizontal: mapping.fallback_style.horizontal,
-                            }
-                        }
-                    } else {
-                        // Type ID not found, use fallback
-                        MarkerStyle {
-                            overlay_text: mapping.fallback_style.overlay_text,
-                            horizontal: mapping.fallback_style.horizontal,
-                        }
-                    }
-                } else {
-                    // No style mapping provided, use default behavior
-                    MarkerStyle {
-                        overlay_text: trans_marker.type_id == 0,
-                        horizontal: false,
-                    }
-                };
-                
-                // LabelPlus格式只支持点型标记
-                let marker = Marker {
-                    id: marker_id,
-                    image_id,
-                    geometry: MarkerGeometry::Point { x: percentage_x, y: percentage_y },
-                    translation: trans_marker.translation,
-                    style,
-                    image_index: trans_marker.image_index,
-                };
-                
-                // Insert directly into storage
-                marker_storage.markers.insert(marker_id, marker);
-                marker_storage.by_image.entry(image_id).or_default().push(marker_id);
-                marker_ids_for_image.push(marker_id);
-            }
-            
-            // Collect marker IDs to update images later
-            image_updates.insert(image_id, marker_ids_for_image);
-        }
+    for marker in new_markers {
+        let (marker_id, image_id) = (marker.id, marker.image_id);
+        marker_storage.insert_with_image(marker)?;
+        image_updates.entry(image_id).or_default().push(marker_id);
     }
-    
     // Release marker storage lock before updating images
     drop(marker_storage);
-    
+
     // Update all images with their new markers in a single operation
     let mut image_storage = APP_STATE.images.write()?;
     for (image_id, marker_ids) in image_updates {
@@ -451,25 +602,236 @@ pub fn import_labelplus_data_direct(
             }
         }
     }
-    drop(image_storage);
-    
     Ok(())
 }
 
+// Direct import without triggering undo/redo
+// origin在换算为百分比坐标前应用，纠正以其他角为原点的文件
+pub fn import_labelplus_data_direct(
+    project_id: ProjectId,
+    labelplus_data: LabelplusData,
+    origin: LabelplusCoordinateOrigin,
+) -> CoreResult<()> {
+    import_labelplus_markers(project_id, labelplus_data, origin).map(|_| ())
+}
+
+/// 导入LabelPlus数据并返回新建的标记，由调用方决定是否记录撤销
+/// 每处理完一张图片发送io:import_progress，结束时发送io:import_complete
+/// 锁定的图片不写入，图片名列在io:import_complete的locked_images中
+pub fn import_labelplus_markers(
+    project_id: ProjectId,
+    labelplus_data: LabelplusData,
+    origin: LabelplusCoordinateOrigin,
+) -> CoreResult<Vec<Marker>> {
+    // 项目的标记类型加上内置的默认样式映射
+    let style_mapping = project_style_mapping(project_id)?;
+    let images = project_images_by_name(project_id)?;
+    // Build type_id to name mapping for style lookup (types are numbered 1-9)
+    let type_id_to_name = type_names(&labelplus_data);
+    let mut markers_by_image = labelplus_data.markers_by_image;
+
+    let total = labelplus_data.image_order.len();
+    let mut new_markers = Vec::new();
+    let mut unmatched_images = Vec::new();
+    let mut locked_images = Vec::new();
+    // 按文件中的图片顺序处理，进度才有意义
+    for (done, image_name) in labelplus_data.image_order.into_iter().enumerate() {
+        if let Some(markers) = markers_by_image.remove(&image_name) {
+            match images.get(&image_name) {
+                Some(&(image_id, _)) if !markers.is_empty() && crate::service::image::is_image_locked(image_id) => {
+                    locked_images.push(image_name);
+                }
+                Some(&(image_id, dimensions)) => {
+                    // Convert normalized coordinates (0-1) to percentage (0-100)
+                    // Frontend uses percentage coordinates, not pixel coordinates
+                    let positions = image_marker_positions(image_id, &image_name, &markers, dimensions, origin);
+
+                    for (trans_marker, (percentage_x, percentage_y)) in markers.into_iter().zip(positions) {
+                        // LabelPlus格式只支持点型标记
+                        new_markers.push(Marker {
+                            id: MARKER_ID_GENERATOR.next(),
+                            image_id,
+                            geometry: MarkerGeometry::Point { x: percentage_x, y: percentage_y },
+                            translation: trans_marker.translation,
+                            style: marker_style(&type_id_to_name, &style_mapping, trans_marker.type_id),
+                            image_index: trans_marker.image_index,
+                            note: trans_marker.note,
+                        });
+                    }
+                }
+                None if !markers.is_empty() => unmatched_images.push(image_name),
+                None => {}
+            }
+        }
+        let _ = ImportProgressEvent::new(project_id, done + 1, total).emit();
+    }
+
+    insert_markers_direct(new_markers.clone())?;
+    let _ = ImportCompleteEvent::new(project_id, new_markers.len(), unmatched_images, locked_images).emit();
+    Ok(new_markers)
+}
+
+// 合并导入时判断重复的坐标容差（百分比）
+const DUPLICATE_POSITION_EPSILON: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelplusMergeOptions {
+    /// 跳过与已有标记坐标、类型都相同的标记，避免同一个文件导入两次
+    pub skip_duplicates: bool,
+    pub origin: LabelplusCoordinateOrigin,
+}
+
+impl Default for LabelplusMergeOptions {
+    fn default() -> Self {
+        Self { skip_duplicates: true, origin: LabelplusCoordinateOrigin::TopLeft }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelplusSkippedMarker {
+    pub image: String,
+    /// 翻译文件中的序号
+    pub index: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LabelplusMergeReport {
+    pub added: usize,
+    pub added_marker_ids: Vec<MarkerId>,
+    pub skipped: Vec<LabelplusSkippedMarker>,
+    /// 项目中找不到的图片名，其中的标记全部跳过
+    pub unmatched_images: Vec<String>,
+    /// 已锁定的图片名，其中的标记全部跳过
+    pub locked_images: Vec<String>,
+}
+
+fn is_duplicate(existing: &[Marker], x: f64, y: f64, style: &MarkerStyle) -> bool {
+    existing.iter().any(|marker| match marker.geometry {
+        MarkerGeometry::Point { x: ex, y: ey } => {
+            (ex - x).abs() < DUPLICATE_POSITION_EPSILON
+                && (ey - y).abs() < DUPLICATE_POSITION_EPSILON
+                && marker.style == *style
+        }
+        MarkerGeometry::Rectangle { .. } | MarkerGeometry::Polygon { .. } => false,
+    })
+}
+
+/// 把LabelPlus数据合并到已有标记中（例如按章节拆分的多个翻译文件）
+/// 按图片名匹配，新标记的序号接在图片已有标记之后；锁定的图片跳过并列在报告的locked_images中
+pub fn import_labelplus_data_merge(
+    project_id: ProjectId,
+    labelplus_data: LabelplusData,
+    options: &LabelplusMergeOptions,
+) -> CoreResult<LabelplusMergeReport> {
+    let style_mapping = project_style_mapping(project_id)?;
+    let images = project_images_by_name(project_id)?;
+    let type_id_to_name = type_names(&labelplus_data);
+    let mut markers_by_image = labelplus_data.markers_by_image;
+
+    let mut report = LabelplusMergeReport::default();
+    let mut new_markers = Vec::new();
+    // 按文件中的图片顺序处理，保证结果稳定
+    for image_name in labelplus_data.image_order {
+        let Some(mut markers) = markers_by_image.remove(&image_name) else {
+            continue;
+        };
+        let Some(&(image_id, dimensions)) = images.get(&image_name) else {
+            if !markers.is_empty() {
+                report.skipped.extend(markers.iter().map(|m| LabelplusSkippedMarker {
+                    image: image_name.clone(),
+                    index: m.image_index,
+                    reason: "image not found".to_string(),
+                }));
+                report.unmatched_images.push(image_name);
+            }
+            continue;
+        };
+        if crate::service::image::is_image_locked(image_id) {
+            if !markers.is_empty() {
+                report.skipped.extend(markers.iter().map(|m| LabelplusSkippedMarker {
+                    image: image_name.clone(),
+                    index: m.image_index,
+                    reason: "image locked".to_string(),
+                }));
+                report.locked_images.push(image_name);
+            }
+            continue;
+        }
+
+        markers.sort_by_key(|m| m.image_index);
+        let positions = image_marker_positions(image_id, &image_name, &markers, dimensions, options.origin);
+        let mut existing = crate::storage::marker::get_image_markers_storage(image_id)?;
+        let mut next_index = existing.iter().map(|m| m.image_index).max().unwrap_or(0) + 1;
+
+        for (trans_marker, (x, y)) in markers.into_iter().zip(positions) {
+            let style = marker_style(&type_id_to_name, &style_mapping, trans_marker.type_id);
+            if options.skip_duplicates && is_duplicate(&existing, x, y, &style) {
+                report.skipped.push(LabelplusSkippedMarker {
+                    image: image_name.clone(),
+                    index: trans_marker.image_index,
+                    reason: "duplicate".to_string(),
+                });
+                continue;
+            }
+            let marker = Marker {
+                id: MARKER_ID_GENERATOR.next(),
+                image_id,
+                geometry: MarkerGeometry::Point { x, y },
+                translation: trans_marker.translation,
+                style,
+                image_index: next_index,
+                note: trans_marker.note,
+            };
+            next_index += 1;
+            report.added_marker_ids.push(marker.id);
+            // 同一文件内的重复也能检测到
+            existing.push(marker.clone());
+            new_markers.push(marker);
+        }
+    }
+
+    report.added = new_markers.len();
+    insert_markers_direct(new_markers)?;
+    Ok(report)
+}
+
 // Validate labelplus file without importing
 pub fn validate_labelplus_file(content: &str) -> CoreResult<LabelplusData> {
     parse_labelplus_file(content)
 }
 
 // Export project data to labelplus format
+/// 项目备注写入注释块的行；看起来像图片分隔行或标记备注的行前加空格，避免被误解析
+fn notes_comment_lines(notes: &str) -> Vec<String> {
+    notes.lines()
+        .map(|line| if is_image_header(line) || line.starts_with(NOTE_PREFIX) { format!(" {}", line) } else { line.to_string() })
+        .collect()
+}
+
 pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
+    let image_ids = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?
+        .image_ids
+        .clone();
+    export_labelplus_images(project_id, image_ids)
+}
+
+/// 只导出指定的图片（如一个章节），保持它们在项目中的先后顺序；类型编号按这些图片用到的样式分配
+pub fn export_labelplus_data_range(project_id: ProjectId, image_ids: &[ImageId]) -> CoreResult<String> {
+    let image_ids = super::project_data::export_image_range(project_id, image_ids)?;
+    export_labelplus_images(project_id, image_ids)
+}
+
+fn export_labelplus_images(project_id: ProjectId, image_ids: Vec<ImageId>) -> CoreResult<String> {
     // Get project data
     let project_storage = APP_STATE.projects.read()?;
     let project = project_storage.get(&project_id)
         .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
-    let image_ids = project.image_ids.clone();
     let source_language = project.source_language;
     let target_language = project.target_language;
+    let notes = project.notes.clone();
     drop(project_storage);
 
     // Get markers and build type mappings
@@ -477,14 +839,14 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
     let mut markers_by_image: HashMap<String, Vec<LabelplusMarker>> = HashMap::new();
     
     // Collect all unique marker styles
-    let mut unique_styles: Vec<(bool, bool)> = Vec::new();
-    let mut style_to_type_id: HashMap<(bool, bool), u32> = HashMap::new();
+    let mut unique_styles: Vec<ExportStyleKey> = Vec::new();
+    let mut style_to_type_id: HashMap<ExportStyleKey, u32> = HashMap::new();
     
     for image_id in &image_ids {
-        if let Some(marker_ids) = marker_storage.by_image.get(image_id) {
+        if let Some(marker_ids) = marker_storage.image(image_id).by_image.get(image_id).map(|index| index.ids()) {
             for marker_id in marker_ids {
-                if let Some(marker) = marker_storage.markers.get(marker_id) {
-                    let style_key = (marker.style.overlay_text, marker.style.horizontal);
+                if let Some(marker) = marker_storage.get(marker_id) {
+                    let style_key = export_style_key(&marker.style);
                     if !unique_styles.contains(&style_key) {
                         unique_styles.push(style_key);
                     }
@@ -494,7 +856,7 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
     }
     
     // Assign type IDs (1-9) to unique styles
-    let style_mapping = get_default_style_mapping();
+    let style_mapping = project_style_mapping(project_id)?;
     let mut type_id_to_name: Vec<String> = Vec::new();
     
     for (index, style_key) in unique_styles.iter().enumerate() {
@@ -504,14 +866,12 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
             9u32  // All styles beyond 9 use type ID 9
         };
         
-        style_to_type_id.insert(*style_key, type_id);
+        style_to_type_id.insert(style_key.clone(), type_id);
         
         // Only add type names up to 9
         if type_id_to_name.len() < 9 {
             // Find matching type name from style mapping
-            let found_name = style_mapping.type_mappings.iter()
-                .find(|m| m.style.overlay_text == style_key.0 && m.style.horizontal == style_key.1)
-                .map(|m| m.name.clone());
+            let found_name = export_type_name(&style_mapping, style_key);
             
             let type_name = if index >= 8 && unique_styles.len() > 9 {
                 // If this is type 9 and there are more than 9 types, name it "others"
@@ -529,59 +889,70 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
     if unique_styles.is_empty() {
         type_id_to_name.push("fallback".to_string());
         // Map the fallback style to type ID 1
-        style_to_type_id.insert((false, false), 1);
+        style_to_type_id.insert((false, false, None), 1);
     }
     
     // Get image names and collect markers
     let image_storage = APP_STATE.images.read()?;
     let mut ordered_images: Vec<(String, ImageId)> = Vec::new();
     
-    for image_id in &image_ids {
-        if let Some(image) = image_storage.get(image_id) {
-            let name = image.metadata.name.clone()
-                .unwrap_or_else(|| format!("{:02}.jpeg", ordered_images.len()));
-            ordered_images.push((name.clone(), *image_id));
+    // 导出的图片名必须唯一，否则同名图片的标记会互相覆盖
+    let existing: Vec<(ImageId, String)> = image_ids.iter()
+        .filter_map(|image_id| image_storage.get(image_id).map(|image| (*image_id, image.metadata.name.clone())))
+        .enumerate()
+        .map(|(position, (image_id, name))| (image_id, name.unwrap_or_else(|| format!("{:02}.jpeg", position))))
+        .collect();
+    let names: Vec<String> = existing.iter().map(|(_, name)| name.clone()).collect();
+    let export_names = crate::storage::image::dedupe_image_names(&names);
+    
+    for ((image_id, _), name) in existing.iter().zip(export_names) {
+        ordered_images.push((name.clone(), *image_id));
+        
+        // Collect markers for this image
+        if let Some(marker_ids) = marker_storage.image(image_id).by_image.get(image_id).map(|index| index.ids()) {
+            let mut image_markers = Vec::new();
             
-            // Collect markers for this image
-            if let Some(marker_ids) = marker_storage.by_image.get(image_id) {
-                let mut image_markers = Vec::new();
-                
-                for marker_id in marker_ids {
-                    if let Some(marker) = marker_storage.markers.get(marker_id) {
-                        // 根据marker的geometry类型导出不同的坐标
-                        let (export_x, export_y) = match &marker.geometry {
-                            MarkerGeometry::Point { x, y } => (*x, *y),
-                            MarkerGeometry::Rectangle { x, y, width, .. } => {
-                                // 矩形上边中点坐标
-                                (x + width / 2.0, *y)
-                            }
-                        };
-                        
-                        // Convert percentage coordinates (0-100) to normalized (0-1)
-                        let normalized_x = export_x / 100.0;
-                        let normalized_y = export_y / 100.0;
-                        
-                        // Get type ID for this marker's style
-                        let style_key = (marker.style.overlay_text, marker.style.horizontal);
-                        let type_id = *style_to_type_id.get(&style_key).unwrap_or(&1);
-                        
-                        image_markers.push(LabelplusMarker {
-                            image_index: marker.image_index,
-                            x: normalized_x,
-                            y: normalized_y,
-                            type_id,
-                            translation: marker.translation.clone(),
-                        });
-                    }
-                }
-                
-                // Sort markers by image_index to maintain order
-                image_markers.sort_by_key(|m| m.image_index);
-                
-                if !image_markers.is_empty() {
-                    markers_by_image.insert(name, image_markers);
+            for marker_id in marker_ids {
+                if let Some(marker) = marker_storage.get(marker_id) {
+                    // 根据marker的geometry类型导出不同的坐标
+                    let (export_x, export_y) = match &marker.geometry {
+                        MarkerGeometry::Point { x, y } => (*x, *y),
+                        MarkerGeometry::Rectangle { x, y, width, .. } => {
+                            // 矩形上边中点坐标
+                            (x + width / 2.0, *y)
+                        }
+                        // LabelPlus只有点，多边形取外接矩形上边中点
+                        MarkerGeometry::Polygon { .. } => {
+                            let (x, y, width, _) = marker.geometry.bounding_box();
+                            (x + width / 2.0, y)
+                        }
+                    };
+                    
+                    // Convert percentage coordinates (0-100) to normalized (0-1)
+                    let normalized_x = export_x / 100.0;
+                    let normalized_y = export_y / 100.0;
+                    
+                    // Get type ID for this marker's style
+                    let style_key = export_style_key(&marker.style);
+                    let type_id = *style_to_type_id.get(&style_key).unwrap_or(&1);
+                    
+                    image_markers.push(LabelplusMarker {
+                        image_index: marker.image_index,
+                        x: normalized_x,
+                        y: normalized_y,
+                        type_id,
+                        translation: marker.translation.clone(),
+                        note: marker.note.clone(),
+                    });
                 }
             }
+            
+            // Sort markers by image_index to maintain order
+            image_markers.sort_by_key(|m| m.image_index);
+            
+            if !image_markers.is_empty() {
+                markers_by_image.insert(name, image_markers);
+            }
         }
     }
     
@@ -609,6 +980,21 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
     // Write optional project comment with language info
     output.push_str(&format!("Exported from Bubblefish | Source: {} | Target: {}\r\n", 
         source_language, target_language));
+    for line in notes_comment_lines(&notes) {
+        output.push_str(&line);
+        output.push_str("\r\n");
+    }
+    // 标记备注按导出后的序号记录
+    for (image_name, _) in &ordered_images {
+        let Some(markers) = markers_by_image.get(image_name) else { continue };
+        for (index, marker) in markers.iter().enumerate() {
+            if let Some(note) = &marker.note {
+                output.push_str(&format_note_line(image_name, (index + 1) as u32, note));
+                output.push_str("\r\n");
+            }
+        }
+    }
+    output.push_str("\r\n");
     
     // Write image data
     for (image_name, _) in ordered_images {
@@ -635,9 +1021,221 @@ pub fn export_labelplus_data(project_id: ProjectId) -> CoreResult<String> {
                     output.push_str(line);
                     output.push_str("\r\n");
                 }
+                // 与LabelPlus一致，每条译文后空一行（解析时会去掉译文结尾的空行）
+                output.push_str("\r\n");
             }
         }
     }
     
     Ok(output)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NORMALIZED_FIXTURE: &str = "1,0\n-\n框内\n框外\n-\n\n>>>>>>>>[page_01.png]<<<<<<<<\n----------------[1]----------------[0.25,0.5,1]\n你好\n\n----------------[2]----------------[1.0,0.999,2]\n世界\n";
+    const PIXEL_FIXTURE: &str = "1,0\n-\n框内\n框外\n-\n\n>>>>>>>>[page_02.png]<<<<<<<<\n----------------[1]----------------[200,300,1]\n你好\n\n----------------[2]----------------[800,1200,2]\n世界\n";
+
+    fn markers(content: &str, image: &str) -> Vec<LabelplusMarker> {
+        parse_labelplus_file(content).unwrap().markers_by_image.remove(image).unwrap()
+    }
+
+    #[test]
+    fn test_normalized_coordinates_detected() {
+        let markers = markers(NORMALIZED_FIXTURE, "page_01.png");
+        assert!(!is_pixel_coordinates(&markers));
+
+        // 归一化坐标不受图片尺寸影响
+        let positions = labelplus_to_percentage(&markers, Some((800, 1200)), LabelplusCoordinateOrigin::TopLeft);
+        assert_eq!(positions, vec![(25.0, 50.0), (100.0, 99.9)]);
+    }
+
+    #[test]
+    fn test_pixel_coordinates_normalized_with_dimensions() {
+        let markers = markers(PIXEL_FIXTURE, "page_02.png");
+        assert!(is_pixel_coordinates(&markers));
+
+        let positions = labelplus_to_percentage(&markers, Some((800, 1200)), LabelplusCoordinateOrigin::TopLeft);
+        assert_eq!(positions, vec![(25.0, 25.0), (100.0, 100.0)]);
+    }
+
+    #[test]
+    fn test_tagged_styles_map_to_type_names() {
+        let mut mapping = get_default_style_mapping();
+        mapping.type_mappings.insert(0, MarkerTypeMapping {
+            name: "SFX".to_string(),
+            style: MarkerTypeStyleConfig { overlay_text: true, horizontal: true, tag: Some("sfx".to_string()) },
+        });
+        let name = |style: MarkerStyle| export_type_name(&mapping, &export_style_key(&style));
+        let tagged = |tag: &str| MarkerStyle { tag: Some(tag.to_string()), ..MarkerStyle::new(true, true) };
+        assert_eq!(name(tagged("sfx")).as_deref(), Some("SFX"));
+        // 没有对应标签的类型时按框内外、横竖排匹配
+        assert_eq!(name(tagged("thought")).as_deref(), Some("overlay+horizontal"));
+        assert_eq!(name(MarkerStyle::new(true, true)).as_deref(), Some("overlay+horizontal"));
+
+        let config = MarkerTypeStyleMapping { type_mappings: mapping.type_mappings.clone(), ..Default::default() };
+        let type_names = HashMap::from([(3, "SFX".to_string())]);
+        assert_eq!(marker_style(&type_names, &config, 3).tag.as_deref(), Some("sfx"));
+    }
+
+    #[test]
+    fn test_coordinate_origin_variants() {
+        use LabelplusCoordinateOrigin::*;
+        let normalized = markers(NORMALIZED_FIXTURE, "page_01.png");
+        let cases = [
+            (TopLeft, (25.0, 50.0)),
+            (TopRight, (75.0, 50.0)),
+            (BottomLeft, (25.0, 50.0)),
+            (BottomRight, (75.0, 50.0)),
+        ];
+        for (origin, expected) in cases {
+            assert_eq!(labelplus_to_percentage(&normalized, None, origin)[0], expected, "{:?}", origin);
+        }
+        // 第二个标记在(1.0, 0.999)，翻转后贴近另一侧的边
+        let second = |origin| {
+            let (x, y) = labelplus_to_percentage(&normalized, None, origin)[1];
+            ((x * 10.0).round() / 10.0, (y * 10.0).round() / 10.0)
+        };
+        assert_eq!(second(TopLeft), (100.0, 99.9));
+        assert_eq!(second(TopRight), (0.0, 99.9));
+        assert_eq!(second(BottomLeft), (100.0, 0.1));
+        assert_eq!(second(BottomRight), (0.0, 0.1));
+
+        // 像素坐标先按图片尺寸归一化再翻转
+        let pixel = markers(PIXEL_FIXTURE, "page_02.png");
+        assert_eq!(labelplus_to_percentage(&pixel, Some((800, 1200)), BottomLeft), vec![(25.0, 75.0), (100.0, 0.0)]);
+        assert_eq!(labelplus_to_percentage(&pixel, Some((800, 1200)), BottomRight), vec![(75.0, 75.0), (0.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_origin_preview_uses_first_marker() {
+        let data = parse_labelplus_file(PIXEL_FIXTURE).unwrap();
+        let preview = preview_labelplus_origin(&data, LabelplusCoordinateOrigin::TopRight, |_| Some((800, 1200))).unwrap();
+        assert_eq!(preview, LabelplusOriginPreview {
+            image: "page_02.png".to_string(),
+            index: 1,
+            x: 75.0,
+            y: 25.0,
+            pixel_coordinates: true,
+        });
+        assert_eq!(serde_json::to_value(LabelplusCoordinateOrigin::BottomLeft).unwrap(), "bottom_left");
+    }
+
+    #[test]
+    fn test_pixel_coordinates_without_dimensions_are_clamped() {
+        let markers = markers(PIXEL_FIXTURE, "page_02.png");
+        let positions = labelplus_to_percentage(&markers, None, LabelplusCoordinateOrigin::TopLeft);
+        assert!(positions.iter().all(|&(x, y)| (0.0..=100.0).contains(&x) && (0.0..=100.0).contains(&y)));
+    }
+
+    #[test]
+    fn test_import_pixel_fixture_uses_image_dimensions() {
+        let project_id = crate::storage::project::create_project_storage("lp_pixel_import".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("page_02.png".to_string())
+        ).unwrap();
+        crate::storage::image::update_image_dimensions_storage(image_id, 800, 1200).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+
+        import_labelplus_data_direct(project_id, parse_labelplus_file(PIXEL_FIXTURE).unwrap(), LabelplusCoordinateOrigin::TopLeft).unwrap();
+
+        let imported = crate::storage::marker::get_image_markers_storage(image_id).unwrap();
+        let geometries: Vec<MarkerGeometry> = imported.into_iter().map(|m| m.geometry).collect();
+        assert_eq!(geometries, vec![
+            MarkerGeometry::Point { x: 25.0, y: 25.0 },
+            MarkerGeometry::Point { x: 100.0, y: 100.0 },
+        ]);
+    }
+
+    #[test]
+    fn test_merge_appends_indices_and_skips_duplicates() {
+        let project_id = crate::storage::project::create_project_storage("lp_merge_import".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("page_01.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        import_labelplus_data_direct(project_id, parse_labelplus_file(NORMALIZED_FIXTURE).unwrap(), LabelplusCoordinateOrigin::TopLeft).unwrap();
+
+        // 第二个文件：一个重复的标记、一个新标记、一张项目中没有的图片
+        let chapter_two = "1,0\n-\n框内\n框外\n-\n\n>>>>>>>>[page_01.png]<<<<<<<<\n----------------[1]----------------[0.25,0.5,1]\n你好\n\n----------------[2]----------------[0.6,0.7,1]\n新的\n\n>>>>>>>>[page_09.png]<<<<<<<<\n----------------[1]----------------[0.1,0.1,1]\n缺图\n";
+        let options = LabelplusMergeOptions::default();
+        let report = import_labelplus_data_merge(project_id, parse_labelplus_file(chapter_two).unwrap(), &options).unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(report.unmatched_images, vec!["page_09.png".to_string()]);
+        assert_eq!(report.skipped.iter().map(|s| s.reason.as_str()).collect::<Vec<_>>(), vec!["duplicate", "image not found"]);
+
+        let mut markers = crate::storage::marker::get_image_markers_storage(image_id).unwrap();
+        markers.sort_by_key(|m| m.image_index);
+        assert_eq!(markers.iter().map(|m| m.image_index).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(markers[2].translation, "新的");
+
+        // 关闭重复检测时全部追加
+        let options = LabelplusMergeOptions { skip_duplicates: false, ..Default::default() };
+        let report = import_labelplus_data_merge(project_id, parse_labelplus_file(chapter_two).unwrap(), &options).unwrap();
+        assert_eq!(report.added, 2);
+        assert_eq!(crate::storage::marker::get_image_markers_storage(image_id).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_import_and_merge_skip_locked_images() {
+        let project_id = crate::storage::project::create_project_storage("lp_locked".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("page_01.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        crate::storage::image::set_image_locked_storage(image_id, true).unwrap();
+
+        let created = import_labelplus_markers(project_id, parse_labelplus_file(NORMALIZED_FIXTURE).unwrap(), LabelplusCoordinateOrigin::TopLeft).unwrap();
+        assert!(created.is_empty());
+
+        let report = import_labelplus_data_merge(project_id, parse_labelplus_file(NORMALIZED_FIXTURE).unwrap(), &LabelplusMergeOptions::default()).unwrap();
+        assert_eq!(report.added, 0);
+        assert_eq!(report.locked_images, vec!["page_01.png".to_string()]);
+        assert!(report.skipped.iter().all(|s| s.reason == "image locked"));
+        assert!(crate::storage::marker::get_image_markers_storage(image_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_notes_written_to_comment_block() {
+        let project_id = crate::storage::project::create_project_storage("lp_notes".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("page_01.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        let notes = "敬称保留原文\n>>>>>>>>[page_02.png]<<<<<<<<".to_string();
+        crate::storage::project::update_project_notes_storage(project_id, notes).unwrap();
+
+        let exported = export_labelplus_data(project_id).unwrap();
+        let comment = exported.split(">>>>>>>>[page_01.png]<<<<<<<<").next().unwrap();
+        assert!(comment.contains("敬称保留原文\r\n"));
+        assert!(comment.contains("\r\n >>>>>>>>[page_02.png]<<<<<<<<\r\n"));
+        // 备注中像图片分隔行的内容不会变成图片
+        assert_eq!(parse_labelplus_file(&exported).unwrap().image_order, vec!["page_01.png".to_string()]);
+    }
+
+    #[test]
+    fn test_marker_notes_round_trip_through_comment_block() {
+        let project_id = crate::storage::project::create_project_storage("lp_marker_notes".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], Some("page_01.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        let mut with_note = Marker::point_with_translation(MARKER_ID_GENERATOR.next(), image_id, 10.0, 10.0, "你好".to_string(), 1);
+        with_note.note = Some("谐音梗\n原文：ハロー".to_string());
+        let plain = Marker::point_with_translation(MARKER_ID_GENERATOR.next(), image_id, 20.0, 20.0, "再见".to_string(), 2);
+        insert_markers_direct(vec![with_note, plain]).unwrap();
+
+        let exported = export_labelplus_data(project_id).unwrap();
+        // 备注只出现在注释块，不混进译文
+        let (comment, body) = exported.split_once(">>>>>>>>[page_01.png]<<<<<<<<").unwrap();
+        assert!(comment.contains(NOTE_PREFIX));
+        assert!(!body.contains("谐音梗"));
+
+        let parsed = parse_labelplus_file(&exported).unwrap();
+        let markers = &parsed.markers_by_image["page_01.png"];
+        assert_eq!(markers[0].translation, "你好");
+        assert_eq!(markers[0].note.as_deref(), Some("谐音梗\n原文：ハロー"));
+        assert_eq!(markers[1].note, None);
+        assert_eq!(parse_note_line("#bubblefish-note {broken"), None);
+    }
+}
@@ -0,0 +1,268 @@
+// LabelPlus解析/导出的回归测试：真实导出文件的golden测试、导出->解析->导出的不动点测试和随机输入测试
+// 更新golden文件：UPDATE_LABELPLUS_GOLDEN=1 cargo test -p bubblefish-core labelplus_tests
+use super::labelplus::*;
+use crate::common::{CoreError, ImageId, ProjectId};
+
+const LEGACY_CRLF_BOM: &str = include_str!("fixtures/labelplus/legacy_crlf_bom.txt");
+const LF_TEN_TYPES: &str = include_str!("fixtures/labelplus/lf_ten_types.txt");
+
+// 固定种子的xorshift，保证失败可以复现
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+fn project_with_images(name: &str, images: &[String]) -> (ProjectId, Vec<ImageId>) {
+    let project_id = crate::storage::project::create_project_storage(name.to_string()).unwrap();
+    let image_ids = images.iter()
+        .map(|image| {
+            let image_id = crate::storage::image::add_image_from_binary_storage(
+                crate::storage::ImageFormat::Png, vec![0u8; 16], Some(image.clone())
+            ).unwrap();
+            crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+            image_id
+        })
+        .collect();
+    (project_id, image_ids)
+}
+
+// 把文件导入到一个只包含同名图片的新项目，再导出
+fn import_then_export(content: &str) -> String {
+    let data = parse_labelplus_file(content).unwrap();
+    let (project_id, _) = project_with_images("lp_roundtrip", &data.image_order);
+    import_labelplus_data_direct(project_id, data, LabelplusCoordinateOrigin::TopLeft).unwrap();
+    export_labelplus_data(project_id).unwrap()
+}
+
+fn assert_golden(fixture: &str, actual: &str) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/service/io/fixtures/labelplus")
+        .join(fixture);
+    if std::env::var("UPDATE_LABELPLUS_GOLDEN").is_ok() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, expected, "{} is stale, rerun with UPDATE_LABELPLUS_GOLDEN=1", fixture);
+}
+
+#[test]
+fn test_legacy_fixture_parsed() {
+    let data = parse_labelplus_file(LEGACY_CRLF_BOM).unwrap();
+    let names: Vec<&str> = data.marker_types.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["框内", "框外"]);
+    assert_eq!(data.image_order, vec!["001.jpg", "002.jpg", "003.jpg"]);
+    assert!(data.markers_by_image["002.jpg"].is_empty());
+
+    let first = &data.markers_by_image["001.jpg"];
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0].translation, "あいうえお→啊");
+    // 译文中间的空行保留，结尾的空行去掉
+    assert_eq!(first[1].translation, "第一行\n\n第三行");
+    assert_eq!((first[1].x, first[1].y, first[1].type_id), (0.6642857142857143, 0.2, 2));
+
+    // 只有完整的分隔行才是标记/图片，看起来相近的译文行要原样保留
+    let last = &data.markers_by_image["003.jpg"];
+    assert_eq!(last.len(), 1);
+    assert_eq!(last[0].translation, "----------------[注] 这一行不是标记\n>>>>>>>> 这一行也不是图片");
+}
+
+#[test]
+fn test_ten_types_fixture_parsed() {
+    let data = parse_labelplus_file(LF_TEN_TYPES).unwrap();
+    // 第10个类型超出LabelPlus的上限，被丢弃
+    assert_eq!(data.marker_types.len(), 9);
+    assert_eq!(data.image_order, vec!["page_1.png", "page_2.png"]);
+    let markers = &data.markers_by_image["page_1.png"];
+    assert_eq!(markers[0].type_id, 9);
+    assert_eq!(markers[1].translation, "精度");
+    assert!(data.markers_by_image["page_2.png"].is_empty());
+}
+
+#[test]
+fn test_golden_exports() {
+    assert_golden("legacy_crlf_bom.export.txt", &import_then_export(LEGACY_CRLF_BOM));
+    assert_golden("lf_ten_types.export.txt", &import_then_export(LF_TEN_TYPES));
+}
+
+#[test]
+fn test_crlf_and_lf_parse_identically() {
+    let lf = LEGACY_CRLF_BOM.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let from_crlf = parse_labelplus_file(LEGACY_CRLF_BOM).unwrap();
+    let from_lf = parse_labelplus_file(&lf).unwrap();
+    assert_eq!(from_crlf.image_order, from_lf.image_order);
+    for image in &from_crlf.image_order {
+        let a: Vec<&str> = from_crlf.markers_by_image[image].iter().map(|m| m.translation.as_str()).collect();
+        let b: Vec<&str> = from_lf.markers_by_image[image].iter().map(|m| m.translation.as_str()).collect();
+        assert_eq!(a, b);
+    }
+}
+
+const TRANSLATION_LINES: &[&str] = &[
+    "", "", "你好", "こんにちは", "  前后空格  ", "----------------[注]", ">>>>>>>> 旁白", "1,0", "-", "中文，标点！",
+];
+
+fn random_translation(rng: &mut Rng) -> String {
+    (0..rng.below(5)).map(|_| rng.pick(TRANSLATION_LINES)).collect::<Vec<_>>().join("\n")
+}
+
+fn random_labelplus_file(rng: &mut Rng) -> String {
+    let type_names = ["框内", "框外", "inside+horizontal", "overlay+horizontal", "自定义"];
+    let type_count = 1 + rng.below(type_names.len());
+    let mut content = String::from("1,0\n-\n");
+    for name in &type_names[..type_count] {
+        content.push_str(name);
+        content.push('\n');
+    }
+    content.push_str("-\n备注\n\n");
+    for image in 0..1 + rng.below(4) {
+        content.push_str(&format!(">>>>>>>>[p{:02}.png]<<<<<<<<\n", image));
+        for index in 0..rng.below(5) {
+            content.push_str(&format!(
+                "----------------[{}]----------------[{},{},{}]\n",
+                index + 1,
+                rng.unit(),
+                rng.unit(),
+                1 + rng.below(type_count)
+            ));
+            content.push_str(&random_translation(rng));
+            content.push_str("\n\n");
+        }
+    }
+    content
+}
+
+#[test]
+fn test_export_parse_export_is_fixpoint() {
+    let mut rng = Rng(0x5eed_1abe_u64);
+    for _ in 0..40 {
+        let source = random_labelplus_file(&mut rng);
+        let first = import_then_export(&source);
+        let second = import_then_export(&first);
+        assert_eq!(first, second, "not a fixpoint for input:\n{}", source);
+    }
+}
+
+#[test]
+fn test_exported_coordinates_keep_precision() {
+    let mut rng = Rng(0xc00d_5eed_u64);
+    let mut content = String::from("1,0\n-\n框内\n-\n>>>>>>>>[p.png]<<<<<<<<\n");
+    let mut expected = Vec::new();
+    for index in 0..50 {
+        let (x, y) = (rng.unit(), rng.unit());
+        expected.push((x, y));
+        content.push_str(&format!("----------------[{}]----------------[{},{},1]\n\n", index + 1, x, y));
+    }
+
+    let exported = import_then_export(&content);
+    let markers = parse_labelplus_file(&exported).unwrap().markers_by_image.remove("p.png").unwrap();
+    assert_eq!(markers.len(), expected.len());
+    for (marker, (x, y)) in markers.iter().zip(expected) {
+        assert!((marker.x - x).abs() < 1e-9 && (marker.y - y).abs() < 1e-9, "{} {} vs {} {}", marker.x, marker.y, x, y);
+    }
+}
+
+#[test]
+fn test_shuffled_lines_never_panic() {
+    let lines: Vec<&str> = LEGACY_CRLF_BOM.lines()
+        .chain(LF_TEN_TYPES.lines())
+        .chain([
+            "----------------[x]----------------[0.1,0.2,1]",
+            "----------------[1]----------------[0.1,0.2]",
+            "----------------[1]----------------[a,0.2,1]",
+            "----------------[1]----------------[0.1,0.2,99]",
+            ">>>>>>>>[]<<<<<<<<",
+            "\u{feff}",
+        ])
+        .collect();
+    let mut rng = Rng(0xf422_u64);
+    for _ in 0..500 {
+        let mut shuffled = lines.clone();
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, rng.below(i + 1));
+        }
+        // 保留合法的文件头，让解析器走到后面的分支
+        let keep_header = rng.below(2) == 0;
+        let take = rng.below(shuffled.len());
+        let mut content = if keep_header { String::from("1,0\n-\n框内\n-\n") } else { String::new() };
+        content.push_str(&shuffled[..take].join(if rng.below(2) == 0 { "\n" } else { "\r\n" }));
+
+        match parse_labelplus_file(&content) {
+            Ok(_) | Err(CoreError::ValidationFailed { .. }) => {}
+            Err(other) => panic!("unexpected error {:?} for input:\n{}", other, content),
+        }
+    }
+}
+
+#[test]
+fn test_import_recorded_as_single_undo() {
+    let service = crate::service::get_service();
+    let data = parse_labelplus_file(LEGACY_CRLF_BOM).unwrap();
+    // 003.jpg不在项目中，其中的标记不会导入
+    let (project_id, image_ids) = project_with_images("lp_undo", &data.image_order[..2]);
+    service.io_service.import_labelplus(project_id, LEGACY_CRLF_BOM).unwrap();
+    assert_eq!(crate::storage::marker::get_image_markers_storage(image_ids[0]).unwrap().len(), 2);
+
+    assert!(service.undo_redo_service.undo(project_id.0).success);
+    assert!(!service.undo_redo_service.can_undo(project_id).unwrap());
+    assert!(crate::storage::marker::get_image_markers_storage(image_ids[0]).unwrap().is_empty());
+    assert!(service.undo_redo_service.redo(project_id.0).success);
+    assert_eq!(crate::storage::marker::get_image_markers_storage(image_ids[0]).unwrap().len(), 2);
+}
+
+#[test]
+fn test_range_export_keeps_project_order_and_renumbers_types() {
+    let content = "1,0\n-\n框内\n框外\n-\n>>>>>>>>[a.png]<<<<<<<<\n----------------[1]----------------[0.1,0.1,1]\n甲\n\n>>>>>>>>[b.png]<<<<<<<<\n----------------[1]----------------[0.2,0.2,2]\n乙\n\n>>>>>>>>[c.png]<<<<<<<<\n----------------[1]----------------[0.3,0.3,2]\n丙\n";
+    let data = parse_labelplus_file(content).unwrap();
+    let (project_id, image_ids) = project_with_images("lp_range", &data.image_order);
+    import_labelplus_data_direct(project_id, data, LabelplusCoordinateOrigin::TopLeft).unwrap();
+
+    let exported = parse_labelplus_file(&export_labelplus_data_range(project_id, &[image_ids[2], image_ids[1]]).unwrap()).unwrap();
+    assert_eq!(exported.image_order, vec!["b.png", "c.png"]);
+    // 章节里只用到框外，编号从1开始
+    assert_eq!(exported.marker_types.len(), 1);
+    assert_eq!(exported.markers_by_image["b.png"][0].type_id, 1);
+    assert_eq!(exported.markers_by_image["c.png"][0].translation, "丙");
+
+    assert!(export_labelplus_data_range(project_id, &[]).is_err());
+    let (_, other_images) = project_with_images("lp_range_other", &["x.png".to_string()]);
+    assert!(matches!(export_labelplus_data_range(project_id, &other_images), Err(CoreError::NotFound(_))));
+}
+
+#[test]
+fn test_duplicate_image_names_export_separately() {
+    // 旧项目直接写入的同名图片，导出时第二张按加后缀的名称导出，标记不会互相覆盖
+    let (project_id, image_ids) = project_with_images("lp_duplicate_names", &["01.png".to_string(), "01.png".to_string()]);
+    for (image_id, text) in image_ids.iter().zip(["甲", "乙"]) {
+        crate::service::get_service().marker_service
+            .add_point_marker(image_id.0, 10.0, 10.0, Some(text.to_string())).unwrap();
+    }
+    let exported = parse_labelplus_file(&export_labelplus_data(project_id).unwrap()).unwrap();
+    assert_eq!(exported.image_order, vec!["01.png", "01 (2).png"]);
+    assert_eq!(exported.markers_by_image["01 (2).png"][0].translation, "乙");
+
+    // 导入时按同样的名称匹配回第二张图片
+    let (target, target_images) = project_with_images("lp_duplicate_names_target", &["01.png".to_string(), "01.png".to_string()]);
+    import_labelplus_data_direct(target, exported, LabelplusCoordinateOrigin::TopLeft).unwrap();
+    let markers = crate::storage::marker::get_image_markers_storage(target_images[1]).unwrap();
+    assert_eq!(markers.len(), 1);
+    assert_eq!(markers[0].translation, "乙");
+}
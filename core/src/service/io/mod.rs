@@ -1,9 +1,19 @@
 // IO Service模块 - 处理项目数据的导入导出
+pub mod annotate;
+pub mod archive;
 pub mod bf;
+pub mod bf_schema;
+pub mod content_hash;
 pub mod labelplus;
 pub mod project_data;
+pub mod recovery;
+pub mod relink;
 pub mod service;
+pub mod typesetting;
 pub mod event_handler;
 
+#[cfg(test)]
+mod labelplus_tests;
+
 pub use service::IOService;
 pub use event_handler::IoEventHandler;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::common::{CoreError, CoreResult};
+use crate::common::{CoreError, CoreResult, ImageId, ProjectId};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -99,4 +99,19 @@ impl ProjectData {
             ProjectData::SharedBuffer { .. } => 0,
         }
     }
-}
\ No newline at end of file
+}
+/// 按章节导出时要写入的图片：按项目中的顺序排列，重复的ID只算一次
+pub fn export_image_range(project_id: ProjectId, image_ids: &[ImageId]) -> CoreResult<Vec<ImageId>> {
+    let project = crate::storage::state::APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    if image_ids.is_empty() {
+        return Err(CoreError::ValidationFailed {
+            field: "image_ids".to_string(),
+            reason: "没有选择要导出的图片".to_string(),
+        });
+    }
+    if let Some(missing) = image_ids.iter().find(|id| !project.image_ids.contains(id)) {
+        return Err(CoreError::NotFound(format!("Image {} is not in project {}", missing.0, project_id.0)));
+    }
+    Ok(project.image_ids.iter().filter(|id| image_ids.contains(id)).copied().collect())
+}
@@ -0,0 +1,82 @@
+// 崩溃恢复快照 - WASM worker崩溃（大图OOM等）后，前端用最近一次快照重建项目
+// 快照就是不含图片的bf数据（项目元数据+标记+缓存），导入时图片以占位形式加入，之后由用户重新链接
+use crate::common::{CoreError, CoreResult, Logger, ProjectId};
+use crate::service::get_service;
+use super::bf::{parse_bf_file, save_project};
+
+/// 导出项目的恢复快照，格式与bf文件相同，可以直接另存为.bf打开
+pub fn export_recovery_snapshot(project_id: ProjectId) -> CoreResult<Vec<u8>> {
+    save_project(project_id)
+}
+
+/// 从快照重建项目，返回新项目ID；所有图片都是丢失状态，打开完成后照常发送image:missing
+pub fn import_recovery_snapshot(data: Vec<u8>) -> CoreResult<ProjectId> {
+    let bf_data = parse_bf_file(&data)?;
+    let opening = &get_service().opening_project_service;
+    let project_id = opening.create_opening_project_from_binary(data, "bf".to_string(), bf_data.metadata.project_name.clone())
+        .map_err(CoreError::Internal)?;
+
+    // 快照中同名的图片只需要一张占位
+    let mut seen = std::collections::HashSet::new();
+    for image in bf_data.images.iter().filter(|image| seen.insert(image.filename.as_str())) {
+        let checksum = image.checksum.as_ref().map(|cs| cs.strip_prefix("md5:").unwrap_or(cs).to_string());
+        let image_id = crate::storage::image::add_placeholder_image_storage(image.filename.clone(), checksum)?;
+        opening.add_image_to_opening_project(project_id, image_id.0, Some(image.filename.clone()), None);
+    }
+
+    if !opening.finalize_opening_project(project_id) {
+        opening.delete_opening_project(project_id);
+        return Err(CoreError::Internal("Failed to finalize recovered project".to_string()));
+    }
+
+    Logger::info_with_data(
+        "从恢复快照重建项目",
+        serde_json::json!({
+            "project_id": project_id,
+            "image_count": seen.len()
+        })
+    );
+    Ok(ProjectId::from(project_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::marker::{add_point_marker_to_image, get_marker_info};
+    use crate::storage::image::add_image_from_binary_storage;
+    use crate::storage::project::{add_image_to_project_storage, create_project_storage, get_project_image_ids_storage};
+    use crate::storage::image_data::ImageFormat;
+
+    #[test]
+    fn test_snapshot_roundtrip_uses_placeholder_images() {
+        let project_id = create_project_storage("恢复测试".to_string()).unwrap();
+        let image_id = add_image_from_binary_storage(ImageFormat::Png, vec![1, 2, 3], Some("p1.png".to_string())).unwrap();
+        add_image_to_project_storage(project_id, image_id).unwrap();
+        let marker_id = add_point_marker_to_image(image_id.0, 0.25, 0.5, Some("译文".to_string()), None).unwrap().unwrap();
+        let checksum = crate::storage::image::get_image_storage(image_id).unwrap().unwrap().metadata.checksum.clone();
+
+        let snapshot = export_recovery_snapshot(project_id).unwrap();
+        let recovered = import_recovery_snapshot(snapshot).unwrap();
+        assert_ne!(recovered, project_id);
+
+        let image_ids = get_project_image_ids_storage(recovered).unwrap();
+        assert_eq!(image_ids.len(), 1);
+        let image = crate::storage::image::get_image_storage(image_ids[0]).unwrap().unwrap();
+        assert_eq!(image.metadata.name.as_deref(), Some("p1.png"));
+        assert_eq!(image.metadata.checksum, checksum);
+        assert!(matches!(image.data, crate::storage::ImageData::FilePath(_)));
+
+        let original = get_marker_info(marker_id).unwrap();
+        let restored = get_marker_info(image.marker_ids[0].0).unwrap();
+        assert_eq!(restored.translation, original.translation);
+        assert_eq!(restored.geometry, original.geometry);
+
+        let missing = super::super::relink::find_missing_images(recovered).unwrap();
+        assert_eq!(missing.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_snapshot_rejected() {
+        assert!(import_recovery_snapshot(b"not a snapshot".to_vec()).is_err());
+    }
+}
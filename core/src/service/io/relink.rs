@@ -0,0 +1,123 @@
+// 丢失图片的检查与重新链接 - 项目文件夹被移动后，记录的图片路径指向不存在的文件
+// 加载完成后检查一次并通知前端，用户选择新位置后逐张重新链接
+use std::path::{Path, PathBuf};
+use md5::{Digest, Md5};
+use crate::common::{CoreError, CoreResult, ImageId, ProjectId, Logger};
+use crate::common::dto::image::{MissingImageDTO, RelinkResultDTO};
+use crate::common::events::schema::{BusinessEvent, ImageMissingEvent, ImageRelinkedEvent};
+use crate::service::events::{DomainEvent, EventBus};
+use crate::service::image::{validate_image_candidate, ImageCandidate};
+use crate::storage::ImageData;
+use crate::storage::traits::Storage;
+
+/// 项目中文件路径已不存在的图片，按项目中的图片顺序
+pub fn find_missing_images(project_id: ProjectId) -> CoreResult<Vec<MissingImageDTO>> {
+    let mut missing = Vec::new();
+    for image_id in crate::storage::project::get_project_image_ids_storage(project_id)? {
+        let Some(image) = crate::storage::image::get_image_storage(image_id)? else { continue };
+        if let ImageData::FilePath(path) = &image.data
+            && !path.is_file()
+        {
+            missing.push(MissingImageDTO {
+                image_id,
+                name: image.metadata.name.clone(),
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+    Ok(missing)
+}
+
+/// 加载后的检查：有丢失的图片时发送image:missing
+pub fn report_missing_images(project_id: ProjectId) -> CoreResult<Vec<MissingImageDTO>> {
+    let missing = find_missing_images(project_id)?;
+    if !missing.is_empty() {
+        Logger::warn_with_data(
+            "项目中有图片文件不存在",
+            serde_json::json!({
+                "project_id": project_id,
+                "missing_count": missing.len()
+            })
+        );
+        let _ = ImageMissingEvent::new(project_id, missing.clone()).emit();
+    }
+    Ok(missing)
+}
+
+/// 把文件图片重新链接到新路径：校验新文件、更新尺寸和校验和、重新生成缩略图
+/// 原来没有记录校验和时无法比较，视为一致
+pub fn relink_image(event_bus: &EventBus, image_id: ImageId, new_path: &Path) -> CoreResult<RelinkResultDTO> {
+    let image = crate::storage::image::get_image_storage(image_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Image with id {} not found", image_id.0)))?;
+    if !matches!(image.data, ImageData::FilePath(_)) {
+        return Err(CoreError::ValidationFailed {
+            field: "image_id".to_string(),
+            reason: "只有来自文件的图片可以重新链接".to_string(),
+        });
+    }
+
+    validate_image_candidate(ImageCandidate::Path(new_path))
+        .map_err(|e| CoreError::ValidationFailed { field: "path".to_string(), reason: e.message().to_string() })?;
+    let data = std::fs::read(new_path)
+        .map_err(|e| CoreError::IoError(format!("Failed to read file: {}", e)))?;
+    let checksum = format!("{:x}", Md5::digest(&data));
+    let checksum_matches = image.metadata.checksum.as_deref().is_none_or(|old| old == checksum);
+
+    let new_data = ImageData::FilePath(PathBuf::from(new_path));
+    let info = new_data.get_format().and_then(|format| {
+        crate::storage::dimension_extractor::extract_image_info_from_bytes(&data, &format)
+    });
+    crate::storage::image::update_image_data_storage(image_id, new_data)?;
+    crate::storage::image::refresh_image_file_storage(image_id, info.as_ref(), data.len() as u64, checksum)?;
+
+    // 丢失期间可能缓存了失败的结果，原图字节和缩略图都重新生成
+    crate::service::image::byte_cache::invalidate(image_id);
+    crate::storage::state::APP_STATE.thumbnails.write()?.remove(&image_id);
+    if let Err(e) = crate::service::image::request_thumbnail(image_id) {
+        Logger::warn(&format!("Failed to regenerate thumbnail for image {}: {}", image_id.0, e));
+    }
+
+    let path = new_path.to_string_lossy().to_string();
+    event_bus.publish(DomainEvent::ImageUpdated(image_id));
+    let _ = ImageRelinkedEvent::new(image_id, path.clone(), checksum_matches).emit();
+    Ok(RelinkResultDTO { image_id, path, checksum_matches })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::image::{add_image_from_path_storage, get_image_storage};
+    use crate::storage::project::{add_image_to_project_storage, create_project_storage};
+
+    #[test]
+    fn test_missing_image_can_be_relinked() {
+        let dir = std::env::temp_dir().join(format!("bf_relink_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("moved")).unwrap();
+        let original = dir.join("p01.png");
+        image::RgbImage::new(4, 3).save_with_format(&original, image::ImageFormat::Png).unwrap();
+
+        let project_id = create_project_storage("relink".to_string()).unwrap();
+        let image_id = add_image_from_path_storage(original.clone(), Some("p01.png".to_string())).unwrap();
+        add_image_to_project_storage(project_id, image_id).unwrap();
+        assert!(find_missing_images(project_id).unwrap().is_empty());
+
+        let moved = dir.join("moved").join("p01.png");
+        std::fs::rename(&original, &moved).unwrap();
+        let missing = find_missing_images(project_id).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].image_id, image_id);
+
+        let event_bus = EventBus::new();
+        assert!(relink_image(&event_bus, image_id, &dir.join("none.png")).is_err());
+        let result = relink_image(&event_bus, image_id, &moved).unwrap();
+        assert!(result.checksum_matches);
+        assert!(find_missing_images(project_id).unwrap().is_empty());
+        assert!(matches!(&get_image_storage(image_id).unwrap().unwrap().data, ImageData::FilePath(p) if p == &moved));
+
+        // 内容不同的文件也能链接，但会标记出来
+        image::RgbImage::new(8, 6).save_with_format(&original, image::ImageFormat::Png).unwrap();
+        assert!(!relink_image(&event_bus, image_id, &original).unwrap().checksum_matches);
+        assert_eq!(get_image_storage(image_id).unwrap().unwrap().metadata.width, Some(8));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
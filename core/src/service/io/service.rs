@@ -4,7 +4,6 @@ use crate::common::{ProjectId, CoreResult};
 use crate::service::events::EventBus;
 
 pub struct IOService {
-    #[allow(dead_code)]
     event_bus: Arc<EventBus>,
 }
 
@@ -46,13 +45,54 @@ impl IOService {
     // LabelPlus格式导入
     pub fn import_labelplus(&self, project_id: ProjectId, content: &str) -> CoreResult<()> {
         let data = super::labelplus::validate_labelplus_file(content)?;
-        super::labelplus::import_labelplus_data_direct(project_id, data)?;
-        
-        // 导入成功，标记已通过marker服务处理
-        
+        let created = super::labelplus::import_labelplus_markers(project_id, data, Default::default())?;
+        // 整个导入作为一次撤销操作
+        if !created.is_empty() {
+            self.event_bus.publish(crate::service::events::DomainEvent::MarkersImported { project_id, changes: Vec::new(), created });
+        }
         Ok(())
     }
 
+    // LabelPlus合并导入：追加到已有标记之后，用于导入按章节拆分的多个翻译文件
+    pub fn import_labelplus_merge(&self, project_id: ProjectId, content: &str, options: &super::labelplus::LabelplusMergeOptions) -> CoreResult<super::labelplus::LabelplusMergeReport> {
+        let data = super::labelplus::validate_labelplus_file(content)?;
+        let report = super::labelplus::import_labelplus_data_merge(project_id, data, options)?;
+        if report.added > 0 {
+            crate::service::get_service().stats_service.notify_text_changed(project_id);
+        }
+        Ok(report)
+    }
+
+    // 项目内容哈希，用于判断自上次导出后是否有改动
+    pub fn content_hash(&self, project_id: ProjectId) -> CoreResult<String> {
+        super::content_hash::project_content_hash(project_id)
+    }
+
+    // 预检bf文件：校验格式并统计图片和标记数，不创建项目
+    pub fn validate_bf(&self, data: &[u8]) -> Result<super::bf::BfSummary, super::bf::BfFormatError> {
+        super::bf::validate_bf(data)
+    }
+
+    // 项目中文件已不存在的图片
+    pub fn get_missing_images(&self, project_id: ProjectId) -> CoreResult<Vec<crate::common::dto::image::MissingImageDTO>> {
+        super::relink::find_missing_images(project_id)
+    }
+
+    // 导出恢复快照（不含图片的bf数据）
+    pub fn export_recovery_snapshot(&self, project_id: ProjectId) -> CoreResult<Vec<u8>> {
+        super::recovery::export_recovery_snapshot(project_id)
+    }
+
+    // 从恢复快照重建项目，图片以占位形式加入
+    pub fn import_recovery_snapshot(&self, data: Vec<u8>) -> CoreResult<ProjectId> {
+        super::recovery::import_recovery_snapshot(data)
+    }
+
+    // 把丢失的图片重新链接到新的文件路径
+    pub fn relink_image(&self, image_id: crate::common::ImageId, new_path: &std::path::Path) -> CoreResult<crate::common::dto::image::RelinkResultDTO> {
+        super::relink::relink_image(&self.event_bus, image_id, new_path)
+    }
+
     // 验证LabelPlus文件
     pub fn validate_labelplus(&self, content: &str) -> CoreResult<super::labelplus::LabelplusData> {
         super::labelplus::validate_labelplus_file(content)
@@ -0,0 +1,686 @@
+// Typesetting data export/import - 导出供PS嵌字脚本使用的CSV/JSON（坐标为像素），并可把改过的译文导回
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, MarkerId, ProjectId};
+use crate::common::dto::marker::PixelGeometry;
+use crate::storage::marker::{MarkerGeometry, MarkerStyle};
+use crate::storage::state::APP_STATE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TypesettingFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl TypesettingFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TypesettingFormat::Csv => "csv",
+            TypesettingFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypesettingOptions {
+    /// 跳过译文为空的标记
+    pub skip_empty: bool,
+    /// CSV开头写入UTF-8 BOM，Excel需要它才能正确识别中文
+    pub bom: bool,
+}
+
+impl Default for TypesettingOptions {
+    fn default() -> Self {
+        Self { skip_empty: false, bom: true }
+    }
+}
+
+/// 每个标记一行，点标记的width/height为空
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypesettingRow {
+    pub image: String,
+    pub index: u32,
+    pub shape: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub translation: String,
+    pub overlay_text: bool,
+    pub horizontal: bool,
+    /// 字号倍率，旧版导出的数据没有此列
+    #[serde(default)]
+    pub size_hint: Option<f32>,
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+const CSV_HEADER: [&str; 12] = [
+    "image", "index", "shape", "x", "y", "width", "height", "translation", "overlay_text", "horizontal", "size_hint", "tag",
+];
+// 前10列必须存在，之后的列是后来加的，导入旧文件时可以没有
+const REQUIRED_COLUMNS: usize = 10;
+
+/// 按图片顺序、再按image_index收集所有标记
+pub fn collect_typesetting_rows(project_id: ProjectId, options: &TypesettingOptions) -> CoreResult<Vec<TypesettingRow>> {
+    let project = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+
+    let mut rows = Vec::new();
+    for (position, image_id) in project.image_ids.iter().enumerate() {
+        let Some(image) = APP_STATE.get_image(*image_id)? else { continue };
+        let image_name = image.metadata.name.clone()
+            .unwrap_or_else(|| format!("image_{:03}", position + 1));
+        // 导入时缩小过的图片按原图尺寸换算，与PS中打开的原图对应
+        let Some((width, height)) = image.metadata.source_dimensions() else {
+            return Err(CoreError::ValidationFailed {
+                field: "image".to_string(),
+                reason: format!("图片 {} 缺少尺寸信息，无法换算像素坐标", image_name),
+            });
+        };
+
+        let mut markers = crate::storage::marker::get_image_markers_storage(*image_id)?;
+        markers.sort_by_key(|m| m.image_index);
+        for marker in markers {
+            if options.skip_empty && marker.translation.trim().is_empty() {
+                continue;
+            }
+            let (shape, x, y, w, h) = match marker.geometry.to_pixels(width, height) {
+                PixelGeometry::Point { x, y } => ("point", x, y, None, None),
+                PixelGeometry::Rectangle { x, y, width, height } => ("rectangle", x, y, Some(width), Some(height)),
+            };
+            rows.push(TypesettingRow {
+                image: image_name.clone(),
+                index: marker.image_index,
+                shape: shape.to_string(),
+                x,
+                y,
+                width: w,
+                height: h,
+                translation: marker.translation,
+                overlay_text: marker.style.overlay_text,
+                horizontal: marker.style.horizontal,
+                size_hint: marker.style.size_hint,
+                tag: marker.style.tag,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+/// 译文中的换行转义为字面量\n，反斜杠转义为\\，PS脚本按行读取时不会断行
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// escape_text 的逆操作，未知的转义序列原样保留
+pub fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+// RFC 4180：包含逗号、引号或换行的字段用引号包裹，引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) || value.starts_with(' ') || value.ends_with(' ') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn rows_to_csv(rows: &[TypesettingRow], options: &TypesettingOptions) -> String {
+    let mut output = String::new();
+    if options.bom {
+        output.push('\u{feff}');
+    }
+    output.push_str(&CSV_HEADER.join(","));
+    output.push_str("\r\n");
+
+    let optional = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_default();
+    for row in rows {
+        let fields = [
+            csv_field(&row.image),
+            row.index.to_string(),
+            row.shape.clone(),
+            row.x.to_string(),
+            row.y.to_string(),
+            optional(row.width),
+            optional(row.height),
+            csv_field(&escape_text(&row.translation)),
+            row.overlay_text.to_string(),
+            row.horizontal.to_string(),
+            row.size_hint.map(|v| v.to_string()).unwrap_or_default(),
+            csv_field(row.tag.as_deref().unwrap_or("")),
+        ];
+        output.push_str(&fields.join(","));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+pub fn rows_to_json(rows: &[TypesettingRow]) -> CoreResult<String> {
+    serde_json::to_string_pretty(rows)
+        .map_err(|e| CoreError::SerializationError(format!("Failed to serialize typesetting data: {}", e)))
+}
+
+/// 导出项目的嵌字数据
+pub fn export_typesetting_data(project_id: ProjectId, format: TypesettingFormat, options: &TypesettingOptions) -> CoreResult<String> {
+    let rows = collect_typesetting_rows(project_id, options)?;
+    match format {
+        TypesettingFormat::Csv => Ok(rows_to_csv(&rows, options)),
+        TypesettingFormat::Json => rows_to_json(&rows),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypesettingImportOptions {
+    /// 序号对不上时按位置匹配的容差（像素，横纵方向分别比较）
+    pub tolerance_px: u32,
+    /// 找不到对应标记的行新建标记；默认只更新已有标记
+    pub create_missing: bool,
+}
+
+impl Default for TypesettingImportOptions {
+    fn default() -> Self {
+        Self { tolerance_px: 8, create_missing: false }
+    }
+}
+
+/// 未能导入的行，line为CSV中该记录起始的行号（JSON为数组下标+1）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRowIssue {
+    pub line: usize,
+    pub image: Option<String>,
+    pub index: Option<u32>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypesettingImportReport {
+    pub updated: usize,
+    pub unchanged: usize,
+    pub created: usize,
+    pub changed_marker_ids: Vec<MarkerId>,
+    pub unmatched: Vec<ImportRowIssue>,
+    pub malformed: Vec<ImportRowIssue>,
+    /// 有要导入的行但已锁定、没有写入的图片
+    #[serde(default)]
+    pub skipped_image_ids: Vec<ImageId>,
+}
+
+struct CsvRecord {
+    line: usize,
+    fields: Vec<String>,
+    unterminated: bool,
+}
+
+// RFC 4180解析：去掉BOM，兼容CRLF/LF，引号内可以包含逗号和换行
+fn parse_csv_records(content: &str) -> Vec<CsvRecord> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                fields.push(std::mem::take(&mut field));
+                // 跳过空行
+                if fields.len() > 1 || !fields[0].is_empty() {
+                    records.push(CsvRecord { line: record_line, fields: std::mem::take(&mut fields), unterminated: false });
+                }
+                fields.clear();
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes || !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(CsvRecord { line: record_line, fields, unterminated: in_quotes });
+    }
+    records
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" | "" => Some(false),
+        _ => None,
+    }
+}
+
+fn csv_to_rows(content: &str, report: &mut TypesettingImportReport) -> CoreResult<Vec<(usize, TypesettingRow)>> {
+    let mut records = parse_csv_records(content).into_iter();
+    let header = records.next().ok_or_else(|| CoreError::ValidationFailed {
+        field: "content".to_string(),
+        reason: "嵌字数据为空".to_string(),
+    })?;
+
+    // 按表头名称定位列，允许调整列顺序或增加额外的列
+    let columns: HashMap<&str, usize> = header.fields.iter().enumerate()
+        .map(|(i, name)| (name.trim(), i))
+        .collect();
+    let mut positions = [None; CSV_HEADER.len()];
+    for (i, (slot, name)) in positions.iter_mut().zip(CSV_HEADER).enumerate() {
+        *slot = columns.get(name).copied();
+        if slot.is_none() && i < REQUIRED_COLUMNS {
+            return Err(CoreError::ValidationFailed {
+                field: "content".to_string(),
+                reason: format!("缺少列 {}", name),
+            });
+        }
+    }
+
+    let mut rows = Vec::new();
+    for record in records {
+        let get = |column: usize| positions[column].and_then(|p| record.fields.get(p)).map(|v| v.as_str());
+        let issue = |reason: String| ImportRowIssue {
+            line: record.line,
+            image: get(0).map(|v| v.to_string()),
+            index: get(1).and_then(|v| v.trim().parse().ok()),
+            reason,
+        };
+        if record.unterminated {
+            report.malformed.push(issue("引号未闭合".to_string()));
+            continue;
+        }
+        match record_to_row(&get) {
+            Ok(row) => rows.push((record.line, row)),
+            Err(reason) => report.malformed.push(issue(reason)),
+        }
+    }
+    Ok(rows)
+}
+
+fn record_to_row<'a>(get: &impl Fn(usize) -> Option<&'a str>) -> Result<TypesettingRow, String> {
+    let field = |column: usize| get(column).ok_or_else(|| format!("缺少字段 {}", CSV_HEADER[column]));
+    let number = |column: usize| -> Result<u32, String> {
+        let value = field(column)?;
+        value.trim().parse().map_err(|_| format!("{} 不是有效的数字: {}", CSV_HEADER[column], value))
+    };
+    let optional = |column: usize| -> Result<Option<u32>, String> {
+        match get(column).map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(_) => number(column).map(Some),
+        }
+    };
+    let flag = |column: usize| -> Result<bool, String> {
+        let value = get(column).unwrap_or("");
+        parse_bool(value).ok_or_else(|| format!("{} 不是有效的布尔值: {}", CSV_HEADER[column], value))
+    };
+    let size_hint = match get(10).map(str::trim) {
+        None | Some("") => None,
+        Some(value) => Some(value.parse::<f32>().map_err(|_| format!("size_hint 不是有效的数字: {}", value))?),
+    };
+    let tag = get(11).map(str::trim).filter(|v| !v.is_empty()).map(|v| v.to_string());
+
+    let image = field(0)?.to_string();
+    if image.trim().is_empty() {
+        return Err("图片名为空".to_string());
+    }
+    Ok(TypesettingRow {
+        image,
+        index: number(1)?,
+        shape: field(2)?.trim().to_string(),
+        x: number(3)?,
+        y: number(4)?,
+        width: optional(5)?,
+        height: optional(6)?,
+        translation: unescape_text(field(7)?),
+        overlay_text: flag(8)?,
+        horizontal: flag(9)?,
+        size_hint,
+        tag,
+    })
+}
+
+fn json_to_rows(content: &str, report: &mut TypesettingImportReport) -> CoreResult<Vec<(usize, TypesettingRow)>> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| CoreError::SerializationError(format!("Failed to parse typesetting data: {}", e)))?;
+    let mut rows = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        let line = i + 1;
+        let image = value.get("image").and_then(|v| v.as_str()).map(|v| v.to_string());
+        let index = value.get("index").and_then(|v| v.as_u64()).map(|v| v as u32);
+        match serde_json::from_value::<TypesettingRow>(value) {
+            Ok(row) => rows.push((line, row)),
+            Err(e) => report.malformed.push(ImportRowIssue { line, image, index, reason: e.to_string() }),
+        }
+    }
+    Ok(rows)
+}
+
+fn row_geometry(row: &TypesettingRow) -> PixelGeometry {
+    match (row.width, row.height) {
+        (Some(width), Some(height)) if row.shape != "point" => PixelGeometry::Rectangle { x: row.x, y: row.y, width, height },
+        _ => PixelGeometry::Point { x: row.x, y: row.y },
+    }
+}
+
+struct ImageTarget {
+    id: ImageId,
+    width: u32,
+    height: u32,
+    // (marker_id, image_index, 像素左上角)
+    markers: Vec<(MarkerId, u32, (u32, u32))>,
+}
+
+fn anchor(geometry: &PixelGeometry) -> (u32, u32) {
+    match *geometry {
+        PixelGeometry::Point { x, y } | PixelGeometry::Rectangle { x, y, .. } => (x, y),
+    }
+}
+
+fn load_image_targets(project_id: ProjectId) -> CoreResult<HashMap<String, ImageTarget>> {
+    let project = APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    let mut targets = HashMap::new();
+    for (position, image_id) in project.image_ids.iter().enumerate() {
+        let Some(image) = APP_STATE.get_image(*image_id)? else { continue };
+        let Some((width, height)) = image.metadata.source_dimensions() else { continue };
+        let name = image.metadata.name.clone()
+            .unwrap_or_else(|| format!("image_{:03}", position + 1));
+        let markers = crate::storage::marker::get_image_markers_storage(*image_id)?
+            .into_iter()
+            .map(|m| (m.id, m.image_index, anchor(&m.geometry.to_pixels(width, height))))
+            .collect();
+        // 同名图片只取第一张，与导出时的行对应
+        targets.entry(name).or_insert(ImageTarget { id: *image_id, width, height, markers });
+    }
+    Ok(targets)
+}
+
+/// 把嵌字数据中的译文按图片名+序号（序号对不上时按位置）写回已有标记
+///
+/// 坏行和匹配不上的行记录在报告中，不会中断导入；只有设置了create_missing才会新建标记，从不删除标记。
+/// 锁定的图片不写入，记在报告的skipped_image_ids中。
+pub fn import_typesetting_data(
+    project_id: ProjectId,
+    content: &str,
+    format: TypesettingFormat,
+    options: &TypesettingImportOptions,
+) -> CoreResult<TypesettingImportReport> {
+    let mut report = TypesettingImportReport::default();
+    let rows = match format {
+        TypesettingFormat::Csv => csv_to_rows(content, &mut report)?,
+        TypesettingFormat::Json => json_to_rows(content, &mut report)?,
+    };
+    let targets = load_image_targets(project_id)?;
+    let tolerance = options.tolerance_px;
+    let within = |a: (u32, u32), b: (u32, u32)| a.0.abs_diff(b.0) <= tolerance && a.1.abs_diff(b.1) <= tolerance;
+
+    let mut claimed: HashSet<MarkerId> = HashSet::new();
+    let mut updates = Vec::new();
+    let mut creations = Vec::new();
+    for (line, row) in rows {
+        let unmatched = |reason: &str| ImportRowIssue {
+            line,
+            image: Some(row.image.clone()),
+            index: Some(row.index),
+            reason: reason.to_string(),
+        };
+        let Some(target) = targets.get(&row.image) else {
+            report.unmatched.push(unmatched("项目中没有这张图片"));
+            continue;
+        };
+        if crate::service::image::is_image_locked(target.id) {
+            if !report.skipped_image_ids.contains(&target.id) {
+                report.skipped_image_ids.push(target.id);
+            }
+            continue;
+        }
+
+        let position = anchor(&row_geometry(&row));
+        let by_index = target.markers.iter()
+            .find(|(id, index, at)| *index == row.index && within(*at, position) && !claimed.contains(id));
+        let by_position = || target.markers.iter()
+            .filter(|(id, _, at)| within(*at, position) && !claimed.contains(id))
+            .min_by_key(|(_, _, at)| at.0.abs_diff(position.0).max(at.1.abs_diff(position.1)));
+
+        match by_index.or_else(by_position) {
+            Some((marker_id, _, _)) => {
+                claimed.insert(*marker_id);
+                updates.push((*marker_id, row.translation));
+            }
+            None if options.create_missing => {
+                let geometry = MarkerGeometry::from_pixels(&row_geometry(&row), target.width, target.height);
+                let style = MarkerStyle {
+                    overlay_text: row.overlay_text,
+                    horizontal: row.horizontal,
+                    size_hint: row.size_hint,
+                    tag: row.tag.clone(),
+                };
+                creations.push((target.id, geometry, row.translation, style));
+            }
+            None => report.unmatched.push(unmatched("找不到序号和位置都对应的标记")),
+        }
+    }
+
+    let matched = updates.len();
+    let services = crate::service::get_service();
+    let (changed, created) = services.marker_service.apply_imported_translations(project_id, updates, creations)?;
+    report.updated = changed.len();
+    report.unchanged = matched - changed.len();
+    report.created = created.len();
+    report.changed_marker_ids = changed;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_csv(content: &str) -> Vec<Vec<String>> {
+        parse_csv_records(content).into_iter().map(|record| record.fields).collect()
+    }
+
+    fn row(image: &str, translation: &str) -> TypesettingRow {
+        TypesettingRow {
+            image: image.to_string(),
+            index: 1,
+            shape: "rectangle".to_string(),
+            x: 10,
+            y: 20,
+            width: Some(30),
+            height: Some(40),
+            translation: translation.to_string(),
+            overlay_text: false,
+            horizontal: true,
+            size_hint: None,
+            tag: None,
+        }
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        for text in ["", "普通文本", "第一行\n第二行", "a\\nb", "tab\there", "末尾\\", "C:\\path\\n"] {
+            assert_eq!(unescape_text(&escape_text(text)), text, "round trip failed for {:?}", text);
+        }
+        assert_eq!(escape_text("a\r\nb"), "a\\nb");
+        assert!(!escape_text("行1\n行2\r\n行3").contains(['\n', '\r']));
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let rows = vec![
+            row("p01.png", "他说：\"你好, 世界\"\n换行"),
+            row("a,b.png", " 前后空格 "),
+            TypesettingRow { shape: "point".to_string(), width: None, height: None, ..row("p02.jpg", "") },
+        ];
+        let csv = rows_to_csv(&rows, &TypesettingOptions::default());
+        assert!(csv.starts_with('\u{feff}'));
+        assert_eq!(csv.matches("\r\n").count(), rows.len() + 1);
+        assert_eq!(csv.matches('\n').count(), rows.len() + 1);
+
+        let records = parse_csv(&csv);
+        assert_eq!(records[0], CSV_HEADER);
+        for (record, expected) in records[1..].iter().zip(&rows) {
+            assert_eq!(record.len(), CSV_HEADER.len());
+            assert_eq!(record[0], expected.image);
+            assert_eq!(unescape_text(&record[7]), expected.translation);
+            assert_eq!(record[5], expected.width.map(|w| w.to_string()).unwrap_or_default());
+        }
+
+        let no_bom = rows_to_csv(&rows, &TypesettingOptions { bom: false, ..Default::default() });
+        assert!(no_bom.starts_with("image,"));
+    }
+
+    #[test]
+    fn test_csv_style_columns_round_trip() {
+        let rows = vec![
+            TypesettingRow { size_hint: Some(1.5), tag: Some("SFX".to_string()), ..row("p01.png", "轰") },
+            row("p01.png", "无标签"),
+        ];
+        let csv = rows_to_csv(&rows, &TypesettingOptions::default());
+        let parsed: Vec<TypesettingRow> = csv_to_rows(&csv, &mut TypesettingImportReport::default()).unwrap()
+            .into_iter().map(|(_, row)| row).collect();
+        assert_eq!(parsed, rows);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let rows = vec![row("p01.png", "多行\n\"引号\"")];
+        let parsed: Vec<TypesettingRow> = serde_json::from_str(&rows_to_json(&rows).unwrap()).unwrap();
+        assert_eq!(parsed, rows);
+    }
+
+    // 1000x500 的图片：序号1为点(100,100)，序号2为矩形(500,250,200,100)
+    fn setup_project() -> (ProjectId, ImageId) {
+        let mut data = Vec::new();
+        image::RgbImage::from_pixel(1000, 500, image::Rgb([255, 255, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+        let project_id = crate::storage::project::create_project_storage("typesetting".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, data, Some("p01.png".to_string())
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        let service = crate::service::get_service();
+        service.marker_service.add_point_marker(image_id.0, 10.0, 20.0, Some("旧1".to_string())).unwrap();
+        service.marker_service.add_rectangle_marker(image_id.0, 50.0, 50.0, 20.0, 20.0, Some("旧2".to_string())).unwrap();
+        (project_id, image_id)
+    }
+
+    fn translations(image_id: ImageId) -> Vec<String> {
+        let mut markers = crate::storage::marker::get_image_markers_storage(image_id).unwrap();
+        markers.sort_by_key(|m| m.image_index);
+        markers.into_iter().map(|m| m.translation).collect()
+    }
+
+    const FIXTURE: &str = concat!(
+        "\u{feff}image,index,shape,x,y,width,height,translation,overlay_text,horizontal\r\n",
+        "p01.png,1,point,100,100,,,\"第一行\\n\"\"引号\"\", 逗号\",false,false\r\n",
+        "p01.png,5,rectangle,503,248,200,100,\"真正的\n多行单元格\",false,true\r\n",
+        "p01.png,abc,point,1,1,,,坏行,false,false\r\n",
+        "p02.png,1,point,0,0,,,没有这张图,false,false\r\n",
+        "p01.png,3,point,900,450,,,新标记,true,false\r\n",
+    );
+
+    #[test]
+    fn test_import_matches_by_index_and_position() {
+        let (project_id, image_id) = setup_project();
+        let report = import_typesetting_data(project_id, FIXTURE, TypesettingFormat::Csv, &TypesettingImportOptions::default()).unwrap();
+
+        assert_eq!((report.updated, report.unchanged, report.created), (2, 0, 0));
+        assert_eq!(translations(image_id), ["第一行\n\"引号\", 逗号", "真正的\n多行单元格"]);
+        assert_eq!(report.malformed.len(), 1);
+        assert_eq!(report.malformed[0].line, 5);
+        let unmatched: Vec<usize> = report.unmatched.iter().map(|issue| issue.line).collect();
+        assert_eq!(unmatched, [6, 7]);
+
+        // 整次导入只占一步撤销
+        let service = crate::service::get_service();
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(translations(image_id), ["旧1", "旧2"]);
+    }
+
+    #[test]
+    fn test_import_create_missing_is_undoable() {
+        let (project_id, image_id) = setup_project();
+        let options = TypesettingImportOptions { create_missing: true, ..Default::default() };
+        let report = import_typesetting_data(project_id, FIXTURE, TypesettingFormat::Csv, &options).unwrap();
+        assert_eq!((report.updated, report.created), (2, 1));
+        assert_eq!(report.unmatched.len(), 1);
+
+        let markers = crate::storage::marker::get_image_markers_storage(image_id).unwrap();
+        let created = markers.iter().find(|m| m.translation == "新标记").unwrap();
+        assert_eq!(created.image_index, 3);
+        assert!(created.style.overlay_text);
+
+        let service = crate::service::get_service();
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(translations(image_id), ["旧1", "旧2"]);
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert_eq!(translations(image_id).len(), 3);
+    }
+
+    #[test]
+    fn test_import_json_and_broken_csv() {
+        let (project_id, image_id) = setup_project();
+        let json = r#"[
+            {"image":"p01.png","index":2,"shape":"rectangle","x":500,"y":250,"width":200,"height":100,"translation":"旧2","overlay_text":false,"horizontal":false},
+            {"image":"p01.png","index":1,"translation":"缺字段"}
+        ]"#;
+        let report = import_typesetting_data(project_id, json, TypesettingFormat::Json, &TypesettingImportOptions::default()).unwrap();
+        assert_eq!((report.updated, report.unchanged), (0, 1));
+        assert_eq!(report.malformed[0].line, 2);
+        assert_eq!(report.malformed[0].index, Some(1));
+
+        let broken = "image,index,shape,x,y,width,height,translation,overlay_text,horizontal\np01.png,1,point,100,100,,,\"未闭合,false,false\n";
+        let report = import_typesetting_data(project_id, broken, TypesettingFormat::Csv, &TypesettingImportOptions::default()).unwrap();
+        assert_eq!(report.malformed.len(), 1);
+        assert_eq!(translations(image_id), ["旧1", "旧2"]);
+
+        assert!(import_typesetting_data(project_id, "image,index\n", TypesettingFormat::Csv, &TypesettingImportOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+// Keymap - 菜单快捷键配置，macOS原生菜单和Windows虚拟菜单栏共用同一份
+// 桌面端持久化到应用数据目录，WASM端通过JS提供的回调持久化
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+#[cfg(not(feature = "wasm"))]
+use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, Logger, EVENT_SYSTEM};
+
+/// 可配置的操作：(菜单id, 显示名称, 默认快捷键)
+pub const DEFAULT_KEYMAP: &[(&str, &str, Option<&str>)] = &[
+    ("new-project", "新建项目", Some("CmdOrCtrl+N")),
+    ("open-project", "打开项目", Some("CmdOrCtrl+O")),
+    ("save", "保存", Some("CmdOrCtrl+S")),
+    ("save-as", "另存为...", Some("CmdOrCtrl+Shift+S")),
+    ("undo", "撤销", Some("CmdOrCtrl+Z")),
+    ("redo", "重做", Some("CmdOrCtrl+Shift+Z")),
+    ("prev-marker", "上一个标记", Some("Shift+Tab")),
+    ("next-marker", "下一个标记", Some("Tab")),
+    ("prev-image", "上一张图片", Some("CmdOrCtrl+Left")),
+    ("next-image", "下一张图片", Some("CmdOrCtrl+Right")),
+    ("minimize", "最小化", Some("CmdOrCtrl+M")),
+    ("maximize", "最大化", None),
+    ("debug", "调试窗口", Some("CmdOrCtrl+Shift+D")),
+    ("quit", "退出Bubblefish", Some("CmdOrCtrl+Q")),
+];
+
+const MODIFIER_ORDER: &[&str] = &["CmdOrCtrl", "Cmd", "Ctrl", "Alt", "Shift"];
+
+const NAMED_KEYS: &[&str] = &[
+    "Tab", "Enter", "Space", "Escape", "Backspace", "Delete", "Insert",
+    "Left", "Right", "Up", "Down", "Home", "End", "PageUp", "PageDown",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: String,
+    pub label: String,
+    /// None表示未绑定快捷键
+    pub accelerator: Option<String>,
+    pub default_accelerator: Option<String>,
+}
+
+/// 修改单个快捷键失败的原因，message为面向用户的提示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AcceleratorError {
+    /// 没有这个菜单项，或者该菜单项不能设置快捷键
+    UnknownItem { item_id: String, message: String },
+    /// 快捷键格式无效
+    Invalid { item_id: String, accelerator: String, message: String },
+    /// 与其他菜单项的快捷键冲突
+    Conflict { item_id: String, accelerator: String, conflicting_item: String, message: String },
+    /// 保存配置失败
+    Storage { message: String },
+}
+
+impl AcceleratorError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownItem { .. } => "unknown_item",
+            Self::Invalid { .. } => "invalid",
+            Self::Conflict { .. } => "conflict",
+            Self::Storage { .. } => "storage",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::UnknownItem { message, .. }
+            | Self::Invalid { message, .. }
+            | Self::Conflict { message, .. }
+            | Self::Storage { message } => message,
+        }
+    }
+
+    /// 序列化为JSON字符串，通过Tauri命令的错误返回给前端
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| self.message().to_string())
+    }
+}
+
+impl std::fmt::Display for AcceleratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AcceleratorError {}
+
+fn invalid(action: &str, reason: String) -> CoreError {
+    CoreError::ValidationFailed { field: action.to_string(), reason }
+}
+
+fn canonical_modifier(token: &str) -> Option<&'static str> {
+    match token.to_lowercase().as_str() {
+        "cmdorctrl" | "commandorcontrol" => Some("CmdOrCtrl"),
+        "cmd" | "command" | "super" | "meta" => Some("Cmd"),
+        "ctrl" | "control" => Some("Ctrl"),
+        "alt" | "option" => Some("Alt"),
+        "shift" => Some("Shift"),
+        _ => None,
+    }
+}
+
+fn canonical_key(token: &str) -> Option<String> {
+    let mut chars = token.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (c.is_ascii_alphanumeric() || "-=[];',./`\\".contains(c)).then(|| c.to_ascii_uppercase().to_string());
+    }
+    if let Some(named) = NAMED_KEYS.iter().find(|k| k.eq_ignore_ascii_case(token)) {
+        return Some(named.to_string());
+    }
+    let lower = token.to_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok())
+        && (1..=24).contains(&n)
+    {
+        return Some(format!("F{}", n));
+    }
+    match lower.as_str() {
+        "esc" => Some("Escape".to_string()),
+        "return" => Some("Enter".to_string()),
+        "arrowleft" => Some("Left".to_string()),
+        "arrowright" => Some("Right".to_string()),
+        "arrowup" => Some("Up".to_string()),
+        "arrowdown" => Some("Down".to_string()),
+        _ => None,
+    }
+}
+
+/// 规范化快捷键字符串（修饰键按固定顺序，键名统一大小写），如"shift+ctrl+s" -> "Ctrl+Shift+S"
+pub fn normalize_accelerator(accelerator: &str) -> Result<String, String> {
+    let tokens: Vec<&str> = accelerator.split('+').map(|t| t.trim()).collect();
+    let (key_token, modifier_tokens) = tokens.split_last().ok_or("快捷键不能为空")?;
+
+    let mut modifiers = Vec::new();
+    for token in modifier_tokens {
+        let modifier = canonical_modifier(token).ok_or_else(|| format!("未知的修饰键: '{}'", token))?;
+        if modifiers.contains(&modifier) {
+            return Err(format!("重复的修饰键: '{}'", token));
+        }
+        modifiers.push(modifier);
+    }
+    if modifiers.contains(&"CmdOrCtrl") && (modifiers.contains(&"Cmd") || modifiers.contains(&"Ctrl")) {
+        return Err("CmdOrCtrl不能与Cmd或Ctrl同时使用".to_string());
+    }
+    modifiers.sort_by_key(|m| MODIFIER_ORDER.iter().position(|o| o == m));
+
+    let key = canonical_key(key_token).ok_or_else(|| format!("无效的按键: '{}'", key_token))?;
+    modifiers.push(&key);
+    Ok(modifiers.join("+"))
+}
+
+// 在macOS上CmdOrCtrl即Cmd，其他平台上即Ctrl，两种解析下任一相同都算冲突
+fn platform_forms(accelerator: &str) -> [String; 2] {
+    [
+        accelerator.replace("CmdOrCtrl", "Cmd"),
+        accelerator.replace("CmdOrCtrl", "Ctrl"),
+    ]
+}
+
+// 与给定快捷键冲突的其他操作
+fn conflicting_action(bindings: &BTreeMap<String, Option<String>>, action: &str, accelerator: &str) -> Option<String> {
+    let forms = platform_forms(accelerator);
+    bindings.iter()
+        .filter(|(other, _)| other.as_str() != action)
+        .find(|(_, accel)| accel.as_ref().is_some_and(|a| platform_forms(a).iter().any(|form| forms.contains(form))))
+        .map(|(other, _)| other.clone())
+}
+
+/// 检查快捷键冲突，返回冲突描述列表
+pub fn find_conflicts(bindings: &BTreeMap<String, Option<String>>) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    let bound: Vec<(&String, &String)> = bindings.iter()
+        .filter_map(|(action, accel)| accel.as_ref().map(|a| (action, a)))
+        .collect();
+    for (i, (action_a, accel_a)) in bound.iter().enumerate() {
+        let forms_a = platform_forms(accel_a);
+        for (action_b, accel_b) in &bound[i + 1..] {
+            let forms_b = platform_forms(accel_b);
+            if forms_a.iter().any(|form| forms_b.contains(form)) {
+                conflicts.push(format!("'{}' 与 '{}' 的快捷键冲突: {} / {}", action_a, action_b, accel_a, accel_b));
+            }
+        }
+    }
+    conflicts
+}
+
+pub struct KeymapStore {
+    /// 用户修改过的绑定，未出现的操作使用默认值
+    overrides: BTreeMap<String, Option<String>>,
+    #[cfg(not(feature = "wasm"))]
+    path: Option<PathBuf>,
+}
+
+impl KeymapStore {
+    fn new() -> Self {
+        Self {
+            overrides: BTreeMap::new(),
+            #[cfg(not(feature = "wasm"))]
+            path: None,
+        }
+    }
+
+    fn resolved(&self) -> BTreeMap<String, Option<String>> {
+        DEFAULT_KEYMAP.iter()
+            .map(|(action, _, default)| {
+                let accel = match self.overrides.get(*action) {
+                    Some(accel) => accel.clone(),
+                    None => default.map(|a| a.to_string()),
+                };
+                (action.to_string(), accel)
+            })
+            .collect()
+    }
+
+    fn bindings(&self) -> Vec<KeyBinding> {
+        DEFAULT_KEYMAP.iter()
+            .map(|(action, label, default)| KeyBinding {
+                action: action.to_string(),
+                label: label.to_string(),
+                accelerator: match self.overrides.get(*action) {
+                    Some(accel) => accel.clone(),
+                    None => default.map(|a| a.to_string()),
+                },
+                default_accelerator: default.map(|a| a.to_string()),
+            })
+            .collect()
+    }
+
+    /// 校验并合并新的绑定，任何一项无效或存在冲突时整体不生效
+    fn apply(&mut self, changes: HashMap<String, Option<String>>) -> CoreResult<()> {
+        let mut overrides = self.overrides.clone();
+        for (action, accel) in changes {
+            let Some((_, _, default)) = DEFAULT_KEYMAP.iter().find(|(a, _, _)| *a == action) else {
+                return Err(invalid(&action, format!("未知的操作: '{}'", action)));
+            };
+            let accel = match accel.filter(|a| !a.trim().is_empty()) {
+                Some(a) => Some(normalize_accelerator(&a).map_err(|reason| invalid(&action, reason))?),
+                None => None,
+            };
+            // 与默认值相同时不记录，以便后续默认值调整能生效
+            if accel.as_deref() == *default {
+                overrides.remove(&action);
+            } else {
+                overrides.insert(action, accel);
+            }
+        }
+
+        let candidate = Self {
+            overrides,
+            #[cfg(not(feature = "wasm"))]
+            path: None,
+        };
+        let conflicts = find_conflicts(&candidate.resolved());
+        if !conflicts.is_empty() {
+            return Err(invalid("keymap", conflicts.join("; ")));
+        }
+        self.overrides = candidate.overrides;
+        Ok(())
+    }
+
+    /// 修改单个操作的快捷键，失败时给出结构化的原因
+    fn set_one(&mut self, action: &str, accelerator: Option<String>) -> Result<(), AcceleratorError> {
+        if !DEFAULT_KEYMAP.iter().any(|(a, _, _)| *a == action) {
+            return Err(AcceleratorError::UnknownItem {
+                item_id: action.to_string(),
+                message: format!("菜单项 '{}' 不能设置快捷键", action),
+            });
+        }
+        let accelerator = match accelerator.filter(|a| !a.trim().is_empty()) {
+            Some(raw) => Some(normalize_accelerator(&raw).map_err(|message| AcceleratorError::Invalid {
+                item_id: action.to_string(),
+                accelerator: raw,
+                message,
+            })?),
+            None => None,
+        };
+        if let Some(accel) = &accelerator
+            && let Some(other) = conflicting_action(&self.resolved(), action, accel)
+        {
+            let label = DEFAULT_KEYMAP.iter().find(|(a, _, _)| *a == other).map(|(_, l, _)| *l).unwrap_or(other.as_str());
+            return Err(AcceleratorError::Conflict {
+                item_id: action.to_string(),
+                accelerator: accel.clone(),
+                message: format!("快捷键 {} 已被 \"{}\" 使用", accel, label),
+                conflicting_item: other,
+            });
+        }
+        self.apply(HashMap::from([(action.to_string(), accelerator)]))
+            .map_err(|e| AcceleratorError::Storage { message: e.to_string() })
+    }
+
+    fn persist(&self) -> CoreResult<()> {
+        let json = serde_json::to_string_pretty(&self.overrides)?;
+
+        #[cfg(not(feature = "wasm"))]
+        {
+            if let Some(path) = &self.path {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, json)?;
+            }
+        }
+
+        #[cfg(feature = "wasm")]
+        KEYMAP_PERSIST_CALLBACK.with(|cb| {
+            if let Some(callback) = cb.borrow().as_ref() {
+                callback(json);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// 从持久化的JSON加载，无效或冲突的条目会被丢弃并给出警告
+    fn load_json(&mut self, json: &str) -> CoreResult<Vec<String>> {
+        let saved: HashMap<String, Option<String>> = serde_json::from_str(json)?;
+        let mut warnings = Vec::new();
+        self.overrides.clear();
+        for (action, accel) in saved {
+            let change = HashMap::from([(action.clone(), accel)]);
+            if let Err(e) = self.apply(change) {
+                warnings.push(format!("快捷键 '{}' 已恢复默认: {}", action, e));
+            }
+        }
+        for warning in &warnings {
+            Logger::warn(warning);
+        }
+        Ok(warnings)
+    }
+}
+
+pub static KEYMAP_STORE: Lazy<RwLock<KeymapStore>> = Lazy::new(|| RwLock::new(KeymapStore::new()));
+
+// WASM端的持久化回调，参数为用户修改过的绑定JSON
+#[cfg(feature = "wasm")]
+type KeymapPersistCallback = Box<dyn Fn(String)>;
+
+#[cfg(feature = "wasm")]
+thread_local! {
+    static KEYMAP_PERSIST_CALLBACK: std::cell::RefCell<Option<KeymapPersistCallback>> = std::cell::RefCell::new(None);
+}
+
+#[cfg(feature = "wasm")]
+pub fn set_keymap_persist_callback<F>(callback: F)
+where
+    F: Fn(String) + 'static,
+{
+    KEYMAP_PERSIST_CALLBACK.with(|cb| {
+        *cb.borrow_mut() = Some(Box::new(callback));
+    });
+}
+
+/// 设置快捷键配置文件并加载（桌面端）
+#[cfg(not(feature = "wasm"))]
+pub fn set_keymap_path(path: PathBuf) -> CoreResult<Vec<String>> {
+    let json = if path.is_file() { Some(std::fs::read_to_string(&path)?) } else { None };
+    let mut store = KEYMAP_STORE.write()?;
+    store.path = Some(path);
+    match json {
+        Some(json) => store.load_json(&json),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 从JSON加载快捷键配置（WASM端由JS在启动时传入持久化的数据）
+pub fn load_keymap_json(json: &str) -> CoreResult<Vec<String>> {
+    KEYMAP_STORE.write()?.load_json(json)
+}
+
+fn notify_changed(bindings: &[KeyBinding]) {
+    let _ = EVENT_SYSTEM.emit_business_event(
+        "keymap:changed".to_string(),
+        serde_json::json!({ "bindings": bindings }),
+    );
+}
+
+pub fn get_keymap() -> CoreResult<Vec<KeyBinding>> {
+    Ok(KEYMAP_STORE.read()?.bindings())
+}
+
+/// 当前操作绑定的快捷键（供原生菜单构建使用）
+pub fn accelerator_for(action: &str) -> Option<String> {
+    KEYMAP_STORE.read().ok()?.resolved().get(action).cloned().flatten()
+}
+
+/// 修改部分操作的快捷键，值为None表示取消绑定
+pub fn set_keymap(changes: HashMap<String, Option<String>>) -> CoreResult<Vec<KeyBinding>> {
+    let bindings = {
+        let mut store = KEYMAP_STORE.write()?;
+        store.apply(changes)?;
+        store.persist()?;
+        store.bindings()
+    };
+    notify_changed(&bindings);
+    Ok(bindings)
+}
+
+/// 修改单个菜单项的快捷键，accelerator为None表示取消绑定；与其他菜单项冲突时拒绝
+pub fn set_accelerator(item_id: &str, accelerator: Option<String>) -> Result<Vec<KeyBinding>, AcceleratorError> {
+    let bindings = {
+        let mut store = KEYMAP_STORE.write().map_err(|e| AcceleratorError::Storage { message: e.to_string() })?;
+        store.set_one(item_id, accelerator)?;
+        store.persist().map_err(|e| AcceleratorError::Storage { message: e.to_string() })?;
+        store.bindings()
+    };
+    notify_changed(&bindings);
+    Ok(bindings)
+}
+
+pub fn reset_keymap() -> CoreResult<Vec<KeyBinding>> {
+    let bindings = {
+        let mut store = KEYMAP_STORE.write()?;
+        store.overrides.clear();
+        store.persist()?;
+        store.bindings()
+    };
+    notify_changed(&bindings);
+    Ok(bindings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_accelerator() {
+        assert_eq!(normalize_accelerator("shift+ctrl+s").unwrap(), "Ctrl+Shift+S");
+        assert_eq!(normalize_accelerator("CommandOrControl + arrowleft").unwrap(), "CmdOrCtrl+Left");
+        assert_eq!(normalize_accelerator("Option+f5").unwrap(), "Alt+F5");
+        assert!(normalize_accelerator("Hyper+S").is_err());
+        assert!(normalize_accelerator("Ctrl+Ctrl+S").is_err());
+        assert!(normalize_accelerator("CmdOrCtrl+Ctrl+S").is_err());
+        assert!(normalize_accelerator("Ctrl+").is_err());
+    }
+
+    #[test]
+    fn test_apply_rejects_conflicts() {
+        let mut store = KeymapStore::new();
+        // CmdOrCtrl+S 在Windows上与 Ctrl+S 冲突
+        let err = store.apply(HashMap::from([("next-marker".to_string(), Some("Ctrl+S".to_string()))]));
+        assert!(err.is_err());
+        assert!(store.overrides.is_empty());
+
+        // 先解绑保存再占用，应当成功
+        store.apply(HashMap::from([
+            ("save".to_string(), None),
+            ("next-marker".to_string(), Some("ctrl+s".to_string())),
+        ])).unwrap();
+        let resolved = store.resolved();
+        assert_eq!(resolved["next-marker"].as_deref(), Some("Ctrl+S"));
+        assert_eq!(resolved["save"], None);
+
+        // 改回默认值时不再记录为覆盖
+        store.apply(HashMap::from([
+            ("next-marker".to_string(), Some("Tab".to_string())),
+            ("save".to_string(), Some("CmdOrCtrl+S".to_string())),
+        ])).unwrap();
+        assert!(store.overrides.is_empty());
+        assert!(store.apply(HashMap::from([("unknown".to_string(), None)])).is_err());
+    }
+
+    #[test]
+    fn test_set_one_reports_structured_errors() {
+        let mut store = KeymapStore::new();
+        assert!(matches!(store.set_one("translation", None), Err(AcceleratorError::UnknownItem { .. })));
+        assert!(matches!(store.set_one("save", Some("Hyper+S".to_string())), Err(AcceleratorError::Invalid { .. })));
+        match store.set_one("debug", Some("ctrl+q".to_string())) {
+            Err(AcceleratorError::Conflict { conflicting_item, accelerator, .. }) => {
+                assert_eq!(conflicting_item, "quit");
+                assert_eq!(accelerator, "Ctrl+Q");
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        store.set_one("debug", Some("F12".to_string())).unwrap();
+        assert_eq!(store.resolved()["debug"].as_deref(), Some("F12"));
+    }
+
+    #[test]
+    fn test_load_json_drops_invalid_entries() {
+        let mut store = KeymapStore::new();
+        let warnings = store.load_json(r#"{"save":"Ctrl+Hyper","undo":"Alt+U"}"#).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(store.resolved()["undo"].as_deref(), Some("Alt+U"));
+        assert_eq!(store.resolved()["save"].as_deref(), Some("CmdOrCtrl+S"));
+    }
+}
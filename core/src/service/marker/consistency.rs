@@ -0,0 +1,259 @@
+// Translation consistency - 翻译一致性检查（空翻译、同一原文不同译法、多余空白）
+// 检查项通过ConsistencyCheck trait组合，UI只负责渲染返回的问题列表
+use serde::{Deserialize, Serialize};
+use crate::common::MarkerId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCategory {
+    /// OCR原文不为空但翻译为空
+    EmptyTranslation,
+    /// 相同（或足够相似）的原文被翻译成了不同的文本
+    InconsistentTranslation,
+    /// 翻译首尾有空白
+    TrailingWhitespace,
+    /// 翻译中有连续空格
+    DuplicateWhitespace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationIssue {
+    pub category: IssueCategory,
+    /// 涉及的marker，InconsistentTranslation会包含同组所有marker
+    pub marker_ids: Vec<MarkerId>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ConsistencyConfig {
+    /// 两段原文的相似度（0~1）达到该值即视为"同一原文"，1.0表示忽略空白后完全相同
+    pub source_similarity: f64,
+}
+
+impl Default for ConsistencyConfig {
+    fn default() -> Self {
+        Self { source_similarity: 1.0 }
+    }
+}
+
+/// 参与检查的一条翻译
+#[derive(Debug, Clone)]
+pub struct TranslationEntry {
+    pub marker_id: MarkerId,
+    pub translation: String,
+    /// bunny缓存中的OCR原文
+    pub original_text: Option<String>,
+}
+
+pub trait ConsistencyCheck: Send + Sync {
+    fn check(&self, entries: &[TranslationEntry], config: &ConsistencyConfig) -> Vec<TranslationIssue>;
+}
+
+pub struct EmptyTranslationCheck;
+pub struct InconsistentTranslationCheck;
+pub struct WhitespaceCheck;
+
+/// 默认启用的检查项
+pub fn default_checks() -> Vec<Box<dyn ConsistencyCheck>> {
+    vec![
+        Box::new(EmptyTranslationCheck),
+        Box::new(InconsistentTranslationCheck),
+        Box::new(WhitespaceCheck),
+    ]
+}
+
+/// 依次运行所有检查项
+pub fn analyze(entries: &[TranslationEntry], config: &ConsistencyConfig, checks: &[Box<dyn ConsistencyCheck>]) -> Vec<TranslationIssue> {
+    checks.iter().flat_map(|check| check.check(entries, config)).collect()
+}
+
+// 去掉所有空白，避免OCR换行差异影响比较
+fn normalize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// 基于编辑距离的相似度，1.0表示完全相同
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / max_len as f64
+}
+
+impl ConsistencyCheck for EmptyTranslationCheck {
+    fn check(&self, entries: &[TranslationEntry], _config: &ConsistencyConfig) -> Vec<TranslationIssue> {
+        entries.iter()
+            .filter(|entry| entry.translation.trim().is_empty())
+            .filter(|entry| entry.original_text.as_deref().is_some_and(|text| !text.trim().is_empty()))
+            .map(|entry| TranslationIssue {
+                category: IssueCategory::EmptyTranslation,
+                marker_ids: vec![entry.marker_id],
+                message: "原文已识别但翻译为空".to_string(),
+            })
+            .collect()
+    }
+}
+
+impl ConsistencyCheck for InconsistentTranslationCheck {
+    fn check(&self, entries: &[TranslationEntry], config: &ConsistencyConfig) -> Vec<TranslationIssue> {
+        let candidates: Vec<(&TranslationEntry, String, String)> = entries.iter()
+            .filter_map(|entry| {
+                let source = normalize(entry.original_text.as_deref()?);
+                let translation = normalize(&entry.translation);
+                (!source.is_empty() && !translation.is_empty()).then_some((entry, source, translation))
+            })
+            .collect();
+
+        // 按原文相似度分组，每组以第一条为代表
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (index, (_, source, _)) in candidates.iter().enumerate() {
+            let group = groups.iter_mut().find(|group| {
+                let representative = &candidates[group[0]].1;
+                representative == source || similarity(representative, source) >= config.source_similarity
+            });
+            match group {
+                Some(group) => group.push(index),
+                None => groups.push(vec![index]),
+            }
+        }
+
+        groups.into_iter()
+            .filter(|group| {
+                let first = &candidates[group[0]].2;
+                group.iter().any(|&index| &candidates[index].2 != first)
+            })
+            .map(|group| {
+                let mut renderings: Vec<&str> = Vec::new();
+                for &index in &group {
+                    let translation = candidates[index].0.translation.trim();
+                    if !renderings.contains(&translation) {
+                        renderings.push(translation);
+                    }
+                }
+                TranslationIssue {
+                    category: IssueCategory::InconsistentTranslation,
+                    marker_ids: group.iter().map(|&index| candidates[index].0.marker_id).collect(),
+                    message: format!("原文「{}」有{}种译法: {}",
+                        candidates[group[0]].0.original_text.as_deref().unwrap_or_default().trim(),
+                        renderings.len(),
+                        renderings.join(" / ")),
+                }
+            })
+            .collect()
+    }
+}
+
+impl ConsistencyCheck for WhitespaceCheck {
+    fn check(&self, entries: &[TranslationEntry], _config: &ConsistencyConfig) -> Vec<TranslationIssue> {
+        let mut issues = Vec::new();
+        for entry in entries {
+            let text = &entry.translation;
+            if text.trim().is_empty() {
+                continue;
+            }
+            if text.trim() != text {
+                issues.push(TranslationIssue {
+                    category: IssueCategory::TrailingWhitespace,
+                    marker_ids: vec![entry.marker_id],
+                    message: "翻译首尾有多余空白".to_string(),
+                });
+            }
+            // 换行是排版需要，只检查行内的连续空格（含全角空格）
+            let chars: Vec<char> = text.chars().collect();
+            if chars.windows(2).any(|pair| pair.iter().all(|c| c.is_whitespace() && *c != '\n' && *c != '\r')) {
+                issues.push(TranslationIssue {
+                    category: IssueCategory::DuplicateWhitespace,
+                    marker_ids: vec![entry.marker_id],
+                    message: "翻译中有连续空格".to_string(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u32, translation: &str, original: Option<&str>) -> TranslationEntry {
+        TranslationEntry {
+            marker_id: MarkerId::from(id),
+            translation: translation.to_string(),
+            original_text: original.map(|s| s.to_string()),
+        }
+    }
+
+    fn categories(issues: &[TranslationIssue]) -> Vec<IssueCategory> {
+        issues.iter().map(|issue| issue.category).collect()
+    }
+
+    #[test]
+    fn test_empty_translation_with_ocr_text() {
+        let entries = vec![
+            entry(1, "", Some("こんにちは")),
+            entry(2, "  ", Some("さようなら")),
+            entry(3, "", None),
+            entry(4, "", Some("  ")),
+        ];
+        let issues = EmptyTranslationCheck.check(&entries, &ConsistencyConfig::default());
+        let ids: Vec<MarkerId> = issues.iter().flat_map(|issue| issue.marker_ids.clone()).collect();
+        assert_eq!(ids, vec![MarkerId::from(1), MarkerId::from(2)]);
+    }
+
+    #[test]
+    fn test_inconsistent_translation_grouping() {
+        let entries = vec![
+            entry(1, "你好", Some("こんにちは")),
+            entry(2, "您好", Some("こんに\nちは")),
+            entry(3, "你好", Some("こんにちは")),
+            entry(4, "再见", Some("さようなら")),
+            entry(5, "再见", Some("さようなら")),
+            entry(6, "你好啊", Some("こんにちは!")),
+        ];
+
+        let exact = InconsistentTranslationCheck.check(&entries, &ConsistencyConfig::default());
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].marker_ids, vec![MarkerId::from(1), MarkerId::from(2), MarkerId::from(3)]);
+
+        // 放宽阈值后，"こんにちは!"也归入同一组
+        let fuzzy = InconsistentTranslationCheck.check(&entries, &ConsistencyConfig { source_similarity: 0.8 });
+        assert_eq!(fuzzy.len(), 1);
+        assert_eq!(fuzzy[0].marker_ids.len(), 4);
+    }
+
+    #[test]
+    fn test_whitespace_issues() {
+        let entries = vec![
+            entry(1, "你好 ", None),
+            entry(2, "你好  世界", None),
+            entry(3, "你好\n\n世界", None),
+            entry(4, "你好\u{3000}\u{3000}世界", None),
+            entry(5, "正常", None),
+        ];
+        let issues = WhitespaceCheck.check(&entries, &ConsistencyConfig::default());
+        assert_eq!(categories(&issues), vec![
+            IssueCategory::TrailingWhitespace,
+            IssueCategory::DuplicateWhitespace,
+            IssueCategory::DuplicateWhitespace,
+        ]);
+        assert_eq!(issues[2].marker_ids, vec![MarkerId::from(4)]);
+    }
+}
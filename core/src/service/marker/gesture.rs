@@ -0,0 +1,67 @@
+// Marker drag gesture - 拖拽过程中的中间更新只写存储，不记录撤销，事件节流
+use crate::common::ImageId;
+use crate::common::events::get_timestamp_millis;
+use crate::storage::marker::MarkerGeometry;
+
+/// 拖拽期间MarkerUpdated事件的最小间隔（毫秒）
+pub const DRAG_EVENT_INTERVAL_MS: u64 = 50;
+
+#[derive(Debug, Clone)]
+pub struct DragGesture {
+    pub image_id: ImageId,
+    /// 拖拽开始时的几何，结束时与最终几何一起记录为一次撤销操作
+    pub start_geometry: MarkerGeometry,
+    last_emit_at: Option<u64>,
+    /// 节流期间被跳过的事件，结束时补发
+    pending_emit: bool,
+}
+
+impl DragGesture {
+    pub fn new(image_id: ImageId, start_geometry: MarkerGeometry) -> Self {
+        Self {
+            image_id,
+            start_geometry,
+            last_emit_at: None,
+            pending_emit: false,
+        }
+    }
+
+    /// 判断当前是否允许发出MarkerUpdated事件，允许时刷新节流时间
+    pub fn try_emit(&mut self) -> bool {
+        self.try_emit_at(get_timestamp_millis())
+    }
+
+    fn try_emit_at(&mut self, now: u64) -> bool {
+        let allowed = match self.last_emit_at {
+            Some(last) => now.saturating_sub(last) >= DRAG_EVENT_INTERVAL_MS,
+            None => true,
+        };
+        if allowed {
+            self.last_emit_at = Some(now);
+            self.pending_emit = false;
+        } else {
+            self.pending_emit = true;
+        }
+        allowed
+    }
+
+    pub fn has_pending_emit(&self) -> bool {
+        self.pending_emit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_throttled_within_interval() {
+        let mut gesture = DragGesture::new(ImageId::from(1), MarkerGeometry::Point { x: 0.0, y: 0.0 });
+        assert!(gesture.try_emit_at(1000));
+        assert!(!gesture.try_emit_at(1010));
+        assert!(!gesture.try_emit_at(1049));
+        assert!(gesture.has_pending_emit());
+        assert!(gesture.try_emit_at(1050));
+        assert!(!gesture.has_pending_emit());
+    }
+}
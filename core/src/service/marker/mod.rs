@@ -1,4 +1,12 @@
 // Marker service模块
 mod service;
+mod gesture;
+mod replace;
+pub mod consistency;
+pub mod reading_order;
 
-pub use service::MarkerService;
\ No newline at end of file
+pub use service::{MarkerService, emit_markers_renumbered};
+pub use gesture::{DragGesture, DRAG_EVENT_INTERVAL_MS};
+pub use replace::{ReplaceReport, ReplaceOptions, replace_text};
+pub use reading_order::{reading_order, image_reading_order, translation_context};
+pub use consistency::{ConsistencyCheck, ConsistencyConfig, IssueCategory, TranslationIssue};
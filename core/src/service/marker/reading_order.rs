@@ -0,0 +1,145 @@
+// 阅读顺序 - 按项目的阅读方向把图片上的标记排成从上到下、逐行从左到右（或从右到左）的顺序
+// 只计算派生的顺序，不修改存储中的标记；重新编号由MarkerService::renumber_by_reading_order完成
+use crate::common::{CoreResult, ImageId, MarkerId, ReadingDirection};
+use crate::storage::marker::{self as storage, Marker};
+
+/// 顶边相差不超过此值（百分比坐标）的标记视为同一行
+pub const ROW_TOLERANCE: f64 = 5.0;
+
+/// 翻译上下文默认收集的前文条数
+pub const DEFAULT_CONTEXT_MARKERS: usize = 3;
+
+/// 把标记按阅读顺序排列，返回排好的ID
+///
+/// 先按顶边分行，行内按方向排序：从左到右看左边缘，从右到左看右边缘；位置相同时按现有序号。
+pub fn reading_order(markers: &[Marker], direction: ReadingDirection) -> Vec<MarkerId> {
+    let mut keyed: Vec<(f64, f64, u32, MarkerId)> = markers.iter()
+        .map(|marker| {
+            let (x, y, width, _) = marker.geometry.bounding_box();
+            let column = match direction {
+                ReadingDirection::LeftToRight => x,
+                // 取负值后统一按升序排列
+                ReadingDirection::RightToLeft => -(x + width),
+            };
+            (y, column, marker.image_index, marker.id)
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut rows: Vec<Vec<(f64, f64, u32, MarkerId)>> = Vec::new();
+    let mut row_top = f64::NEG_INFINITY;
+    for entry in keyed {
+        if entry.0 - row_top > ROW_TOLERANCE {
+            row_top = entry.0;
+            rows.push(Vec::new());
+        }
+        if let Some(row) = rows.last_mut() {
+            row.push(entry);
+        }
+    }
+
+    rows.into_iter()
+        .flat_map(|mut row| {
+            row.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)));
+            row.into_iter().map(|(_, _, _, id)| id)
+        })
+        .collect()
+}
+
+/// 图片所在项目的阅读方向，图片不属于任何项目时为默认方向
+pub fn direction_for_image(image_id: ImageId) -> CoreResult<ReadingDirection> {
+    Ok(crate::storage::project::find_project_by_image_storage(image_id)?
+        .and_then(|project_id| crate::storage::project::get_project_storage(project_id).ok().flatten())
+        .map(|project| project.reading_direction)
+        .unwrap_or_default())
+}
+
+/// 按所在项目的阅读方向排列图片上的标记
+pub fn image_reading_order(image_id: ImageId) -> CoreResult<Vec<MarkerId>> {
+    let markers = storage::get_image_markers_storage(image_id)?;
+    Ok(reading_order(&markers, direction_for_image(image_id)?))
+}
+
+/// 按阅读顺序的下一个（backward时为上一个）标记；current为None时返回第一个（最后一个），到头时返回None
+pub fn next_in_reading_order(order: &[MarkerId], current: Option<MarkerId>, backward: bool) -> Option<MarkerId> {
+    let position = current.and_then(|id| order.iter().position(|m| *m == id));
+    match (position, backward) {
+        (None, false) => order.first().copied(),
+        (None, true) => order.last().copied(),
+        (Some(i), false) => order.get(i + 1).copied(),
+        (Some(i), true) => i.checked_sub(1).and_then(|i| order.get(i)).copied(),
+    }
+}
+
+/// 翻译上下文：按阅读顺序排在标记之前的最多limit条原文（没有OCR结果时用译文），按阅读顺序排列
+pub fn translation_context(marker_id: MarkerId, limit: usize) -> CoreResult<Vec<String>> {
+    let Some(marker) = storage::get_marker_storage(marker_id)? else {
+        return Ok(Vec::new());
+    };
+    let order = image_reading_order(marker.image_id)?;
+    let Some(position) = order.iter().position(|id| *id == marker_id) else {
+        return Ok(Vec::new());
+    };
+
+    let mut context = Vec::new();
+    for id in order[..position].iter().rev() {
+        if context.len() >= limit {
+            break;
+        }
+        let original = crate::storage::bunny_cache::get_bunny_cache_storage(*id)?
+            .and_then(|cache| cache.original_text)
+            .filter(|text| !text.trim().is_empty());
+        let text = match original {
+            Some(text) => text,
+            None => storage::get_marker_storage(*id)?.map(|m| m.translation).unwrap_or_default(),
+        };
+        if !text.trim().is_empty() {
+            context.push(text);
+        }
+    }
+    context.reverse();
+    Ok(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(id: u32, index: u32, x: f64, y: f64) -> Marker {
+        Marker::new_point(MarkerId(id), ImageId(1), x, y, index)
+    }
+
+    #[test]
+    fn test_rows_follow_direction() {
+        // 第一行有三个标记（顶边相差在容差内），第二行一个
+        let markers = vec![
+            marker(1, 1, 10.0, 10.0),
+            marker(2, 2, 80.0, 12.0),
+            marker(3, 3, 45.0, 8.0),
+            marker(4, 4, 50.0, 60.0),
+        ];
+        let ids = |order: Vec<MarkerId>| order.into_iter().map(|id| id.0).collect::<Vec<_>>();
+        assert_eq!(ids(reading_order(&markers, ReadingDirection::LeftToRight)), vec![1, 3, 2, 4]);
+        assert_eq!(ids(reading_order(&markers, ReadingDirection::RightToLeft)), vec![2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_rtl_uses_right_edge() {
+        // 左边缘更靠左但更宽的矩形，右边缘在最右边，从右到左时应排在前面
+        let wide = Marker::new_rectangle(MarkerId(1), ImageId(1), 20.0, 10.0, 70.0, 10.0, 1);
+        let narrow = marker(2, 2, 60.0, 10.0);
+        let order = reading_order(&[narrow, wide], ReadingDirection::RightToLeft);
+        assert_eq!(order, vec![MarkerId(1), MarkerId(2)]);
+    }
+
+    #[test]
+    fn test_next_in_reading_order() {
+        let order = vec![MarkerId(3), MarkerId(1), MarkerId(2)];
+        assert_eq!(next_in_reading_order(&order, None, false), Some(MarkerId(3)));
+        assert_eq!(next_in_reading_order(&order, None, true), Some(MarkerId(2)));
+        assert_eq!(next_in_reading_order(&order, Some(MarkerId(1)), false), Some(MarkerId(2)));
+        assert_eq!(next_in_reading_order(&order, Some(MarkerId(1)), true), Some(MarkerId(3)));
+        assert_eq!(next_in_reading_order(&order, Some(MarkerId(2)), false), None);
+        assert_eq!(next_in_reading_order(&order, Some(MarkerId(3)), true), None);
+    }
+}
@@ -0,0 +1,88 @@
+// Find & replace - 项目范围内替换标记翻译文本
+use serde::{Deserialize, Serialize};
+use crate::common::{ImageId, MarkerId, OperationStatus};
+
+/// replace_in_translations的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplaceReport {
+    /// 翻译被修改的marker，UI据此高亮
+    pub changed_marker_ids: Vec<MarkerId>,
+    /// 替换的总次数（一个marker中可能有多处）
+    pub replacements: usize,
+    /// 被取消时只包含取消前处理过的图片中的替换
+    #[serde(default)]
+    pub status: OperationStatus,
+    /// 有匹配但已锁定、没有替换的图片
+    #[serde(default)]
+    pub skipped_image_ids: Vec<ImageId>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplaceOptions {
+    pub case_sensitive: bool,
+    /// 匹配两侧不能是字母、数字或下划线（对CJK文本基本不适用）
+    pub whole_word: bool,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+/// 替换text中所有find，返回新文本和替换次数
+pub fn replace_text(text: &str, find: &str, replace: &str, options: ReplaceOptions) -> (String, usize) {
+    let haystack: Vec<char> = text.chars().collect();
+    let needle: Vec<char> = find.chars().collect();
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return (text.to_string(), 0);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut i = 0;
+    while i < haystack.len() {
+        let matched = i + needle.len() <= haystack.len()
+            && needle.iter().enumerate().all(|(j, c)| chars_equal(haystack[i + j], *c, options.case_sensitive))
+            && (!options.whole_word || {
+                let before_ok = i == 0 || !is_word_char(haystack[i - 1]);
+                let after_ok = i + needle.len() == haystack.len() || !is_word_char(haystack[i + needle.len()]);
+                before_ok && after_ok
+            });
+
+        if matched {
+            result.push_str(replace);
+            count += 1;
+            i += needle.len();
+        } else {
+            result.push(haystack[i]);
+            i += 1;
+        }
+    }
+
+    (result, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_case_and_word() {
+        let sensitive = ReplaceOptions { case_sensitive: true, whole_word: false };
+        let insensitive = ReplaceOptions { case_sensitive: false, whole_word: false };
+        let whole = ReplaceOptions { case_sensitive: false, whole_word: true };
+
+        assert_eq!(replace_text("Rin and rin", "rin", "Len", sensitive), ("Rin and Len".to_string(), 1));
+        assert_eq!(replace_text("Rin and rin", "rin", "Len", insensitive), ("Len and Len".to_string(), 2));
+        assert_eq!(replace_text("Rin, Rina", "rin", "Len", whole), ("Len, Rina".to_string(), 1));
+        assert_eq!(replace_text("小明说：小明来了", "小明", "阿明", sensitive), ("阿明说：阿明来了".to_string(), 2));
+        assert_eq!(replace_text("abc", "", "x", sensitive), ("abc".to_string(), 0));
+    }
+}
@@ -1,65 +1,60 @@
 // Marker Service - 处理标记相关的业务逻辑
-use std::sync::Arc;
-use crate::common::{CoreResult, ImageId, MarkerId, MARKER_ID_GENERATOR};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use crate::common::{CancellationToken, CoreError, CoreResult, ImageId, MarkerId, OperationStatus, ProjectId, EVENT_SYSTEM, MARKER_ID_GENERATOR};
 use crate::common::dto::marker::MarkerDTO;
-use crate::storage::marker::{self as storage, Marker, MarkerStyle, MarkerGeometry};
+use crate::storage::marker::{self as storage, Marker, MarkerStyle, MarkerGeometry, CoordinateMode};
 use crate::storage::state::APP_STATE;
 use crate::storage::traits::Storage;
+use crate::storage::translation_history::{self, TranslationOrigin};
 // Removed direct undo_redo imports - now using event system
 use crate::service::events::{DomainEvent, EventBus, EventHandler};
+use super::gesture::DragGesture;
+use super::replace::{replace_text, ReplaceOptions, ReplaceReport};
+use super::consistency::{self, ConsistencyConfig, TranslationEntry, TranslationIssue};
 
 pub struct MarkerService {
     event_bus: Arc<EventBus>,
+    drag_gestures: RwLock<HashMap<MarkerId, DragGesture>>,
 }
 
 impl MarkerService {
     pub fn new(event_bus: Arc<EventBus>) -> Self {
-        Self { event_bus }
+        Self {
+            event_bus,
+            drag_gestures: RwLock::new(HashMap::new()),
+        }
     }
     
     // === 标记创建操作 ===
     
-    // 点型marker
-    pub fn add_point_marker(&self, image_id: u32, x: f64, y: f64, translation: Option<String>) -> Option<u32> {
-        match self.add_point_marker_to_image(ImageId::from(image_id), x, y, translation) {
-            Ok(id) => Some(id.into()),
-            Err(_) => None,
-        }
+    // 点型marker，越界坐标返回ValidationFailed
+    pub fn add_point_marker(&self, image_id: u32, x: f64, y: f64, translation: Option<String>) -> CoreResult<u32> {
+        self.add_point_marker_to_image(ImageId::from(image_id), x, y, translation, CoordinateMode::Reject)
+            .map(u32::from)
     }
     
-    // 矩形型marker
-    pub fn add_rectangle_marker(&self, image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> Option<u32> {
-        match self.add_rectangle_marker_to_image(ImageId::from(image_id), x, y, width, height, translation) {
-            Ok(id) => Some(id.into()),
-            Err(_) => None,
-        }
+    // 矩形型marker，越界坐标返回ValidationFailed
+    pub fn add_rectangle_marker(&self, image_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> CoreResult<u32> {
+        self.add_rectangle_marker_to_image(ImageId::from(image_id), x, y, width, height, translation, CoordinateMode::Reject)
+            .map(u32::from)
     }
     
     // Business logic with undo/redo support for point markers
-    pub fn add_point_marker_to_image(&self, image_id: ImageId, x: f64, y: f64, translation: Option<String>) -> CoreResult<MarkerId> {
+    pub fn add_point_marker_to_image(&self, image_id: ImageId, x: f64, y: f64, translation: Option<String>, mode: CoordinateMode) -> CoreResult<MarkerId> {
+        let geometry = MarkerGeometry::Point { x, y }.checked(mode)?;
         let id = MARKER_ID_GENERATOR.next();
         
-        // Get next image index by finding the highest current index
-        let marker_storage = APP_STATE.markers.read()?;
-        let markers = marker_storage.get_by_image(&image_id);
-        let image_index = if markers.is_empty() {
-            1
-        } else {
-            // Find the highest image_index and add 1
-            markers.iter()
-                .map(|m| m.image_index)
-                .max()
-                .unwrap_or(0) + 1
-        };
-        drop(marker_storage);
+        // Next image index follows the highest current one
+        let image_index = APP_STATE.markers.read_image(image_id)?.next_index(&image_id);
         
-        let marker = match translation {
+        let mut marker = match translation {
             Some(trans) => Marker::point_with_translation(id, image_id, x, y, trans, image_index),
             None => Marker::new_point(id, image_id, x, y, image_index),
         };
+        marker.geometry = geometry;
         
-        let mut storage = APP_STATE.markers.write()?;
-        storage.insert_with_image(marker.clone())?;
+        APP_STATE.markers.insert_with_image(marker.clone())?;
         
         // Update image marker list
         crate::storage::image::add_marker_to_image_storage(image_id, id)?;
@@ -70,30 +65,20 @@ impl MarkerService {
     }
     
     // Business logic with undo/redo support for rectangle markers
-    pub fn add_rectangle_marker_to_image(&self, image_id: ImageId, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> CoreResult<MarkerId> {
+    pub fn add_rectangle_marker_to_image(&self, image_id: ImageId, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: CoordinateMode) -> CoreResult<MarkerId> {
+        let geometry = MarkerGeometry::Rectangle { x, y, width, height }.checked(mode)?;
         let id = MARKER_ID_GENERATOR.next();
         
-        // Get next image index by finding the highest current index
-        let marker_storage = APP_STATE.markers.read()?;
-        let markers = marker_storage.get_by_image(&image_id);
-        let image_index = if markers.is_empty() {
-            1
-        } else {
-            // Find the highest image_index and add 1
-            markers.iter()
-                .map(|m| m.image_index)
-                .max()
-                .unwrap_or(0) + 1
-        };
-        drop(marker_storage);
+        // Next image index follows the highest current one
+        let image_index = APP_STATE.markers.read_image(image_id)?.next_index(&image_id);
         
-        let marker = match translation {
+        let mut marker = match translation {
             Some(trans) => Marker::rectangle_with_translation(id, image_id, x, y, width, height, trans, image_index),
             None => Marker::new_rectangle(id, image_id, x, y, width, height, image_index),
         };
+        marker.geometry = geometry;
         
-        let mut storage = APP_STATE.markers.write()?;
-        storage.insert_with_image(marker.clone())?;
+        APP_STATE.markers.insert_with_image(marker.clone())?;
         
         // Update image marker list
         crate::storage::image::add_marker_to_image_storage(image_id, id)?;
@@ -103,6 +88,28 @@ impl MarkerService {
         Ok(id)
     }
     
+    // 多边形marker（斜向文字），越界坐标或顶点不足返回ValidationFailed
+    pub fn add_polygon_marker(&self, image_id: u32, points: Vec<(f64, f64)>, translation: Option<String>) -> CoreResult<u32> {
+        self.add_polygon_marker_to_image(ImageId::from(image_id), points, translation, CoordinateMode::Reject)
+            .map(u32::from)
+    }
+    
+    pub fn add_polygon_marker_to_image(&self, image_id: ImageId, points: Vec<(f64, f64)>, translation: Option<String>, mode: CoordinateMode) -> CoreResult<MarkerId> {
+        let MarkerGeometry::Polygon { points } = (MarkerGeometry::Polygon { points }).checked(mode)? else {
+            unreachable!("checked keeps the geometry variant");
+        };
+        let id = MARKER_ID_GENERATOR.next();
+        
+        let image_index = APP_STATE.markers.read_image(image_id)?.next_index(&image_id);
+        
+        let marker = Marker::polygon_with_translation(id, image_id, points, translation.unwrap_or_default(), image_index);
+        APP_STATE.markers.insert_with_image(marker)?;
+        
+        crate::storage::image::add_marker_to_image_storage(image_id, id)?;
+        
+        Ok(id)
+    }
+    
     // === 标记查询操作 ===
     
     pub fn get_marker(&self, marker_id: u32) -> Option<MarkerDTO> {
@@ -140,22 +147,31 @@ impl MarkerService {
     
     // === 标记更新操作 ===
     
-    // 点型marker位置更新
-    pub fn update_point_marker_position(&self, marker_id: u32, x: f64, y: f64) -> bool {
-        let result = match self.update_point_marker_position_with_undo(MarkerId::from(marker_id), x, y) {
-            Ok(res) => res,
-            Err(_) => false,
-        };
+    // 点型marker位置更新，mode决定越界坐标是拒绝还是限制到图片范围内
+    pub fn update_point_marker_position(&self, marker_id: u32, x: f64, y: f64, mode: CoordinateMode) -> CoreResult<bool> {
+        let result = self.update_point_marker_position_checked(MarkerId::from(marker_id), x, y, mode)?;
         
-        if result {
+        if result && self.should_emit_marker_updated(MarkerId::from(marker_id)) {
             self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
         }
         
-        result
+        Ok(result)
+    }
+    
+    /// 校验坐标后更新点型marker位置（撤销重做直接调用_with_undo，恢复的是已存储的值）
+    /// 拖拽手势进行中时只写存储，撤销在end_marker_drag时统一记录
+    pub fn update_point_marker_position_checked(&self, id: MarkerId, x: f64, y: f64, mode: CoordinateMode) -> CoreResult<bool> {
+        match (MarkerGeometry::Point { x, y }).checked(mode)? {
+            geometry if self.is_dragging(id) => self.set_drag_geometry(id, geometry),
+            MarkerGeometry::Point { x, y } => self.update_point_marker_position_with_undo(id, x, y),
+            _ => Ok(false),
+        }
     }
     
     pub fn update_point_marker_position_with_undo(&self, id: MarkerId, x: f64, y: f64) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 确保是点型marker
             if let MarkerGeometry::Point { x: old_x, y: old_y } = marker.geometry {
@@ -181,21 +197,28 @@ impl MarkerService {
     }
     
     // 矩形型marker几何更新
-    pub fn update_rectangle_marker_geometry(&self, marker_id: u32, x: f64, y: f64, width: f64, height: f64) -> bool {
-        let result = match self.update_rectangle_marker_geometry_with_undo(MarkerId::from(marker_id), x, y, width, height) {
-            Ok(res) => res,
-            Err(_) => false,
-        };
+    pub fn update_rectangle_marker_geometry(&self, marker_id: u32, x: f64, y: f64, width: f64, height: f64, mode: CoordinateMode) -> CoreResult<bool> {
+        let result = self.update_rectangle_marker_geometry_checked(MarkerId::from(marker_id), x, y, width, height, mode)?;
         
-        if result {
+        if result && self.should_emit_marker_updated(MarkerId::from(marker_id)) {
             self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
         }
         
-        result
+        Ok(result)
+    }
+    
+    pub fn update_rectangle_marker_geometry_checked(&self, id: MarkerId, x: f64, y: f64, width: f64, height: f64, mode: CoordinateMode) -> CoreResult<bool> {
+        match (MarkerGeometry::Rectangle { x, y, width, height }).checked(mode)? {
+            geometry if self.is_dragging(id) => self.set_drag_geometry(id, geometry),
+            MarkerGeometry::Rectangle { x, y, width, height } => self.update_rectangle_marker_geometry_with_undo(id, x, y, width, height),
+            _ => Ok(false),
+        }
     }
     
     pub fn update_rectangle_marker_geometry_with_undo(&self, id: MarkerId, x: f64, y: f64, width: f64, height: f64) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 确保是矩形型marker
             if let MarkerGeometry::Rectangle { x: old_x, y: old_y, width: old_width, height: old_height } = marker.geometry {
@@ -220,21 +243,94 @@ impl MarkerService {
         }
     }
     
-    pub fn update_marker_translation(&self, marker_id: u32, translation: String) -> bool {
-        let result = match self.update_marker_translation_with_undo(MarkerId::from(marker_id), translation) {
-            Ok(res) => res,
-            Err(_) => false,
+    // 多边形marker顶点更新
+    pub fn update_polygon_marker_geometry(&self, marker_id: u32, points: Vec<(f64, f64)>, mode: CoordinateMode) -> CoreResult<bool> {
+        let result = self.update_polygon_marker_geometry_checked(MarkerId::from(marker_id), points, mode)?;
+        
+        if result && self.should_emit_marker_updated(MarkerId::from(marker_id)) {
+            self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
+        }
+        
+        Ok(result)
+    }
+    
+    pub fn update_polygon_marker_geometry_checked(&self, id: MarkerId, points: Vec<(f64, f64)>, mode: CoordinateMode) -> CoreResult<bool> {
+        match (MarkerGeometry::Polygon { points }).checked(mode)? {
+            geometry if self.is_dragging(id) => self.set_drag_geometry(id, geometry),
+            MarkerGeometry::Polygon { points } => self.update_polygon_marker_geometry_with_undo(id, points),
+            _ => Ok(false),
+        }
+    }
+    
+    pub fn update_polygon_marker_geometry_with_undo(&self, id: MarkerId, points: Vec<(f64, f64)>) -> CoreResult<bool> {
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
         };
+        let Some(marker) = storage_guard.get_mut(&id) else {
+            return Ok(false);
+        };
+        // 确保是多边形marker
+        let MarkerGeometry::Polygon { points: old_points } = &marker.geometry else {
+            return Ok(false);
+        };
+        let old_points = old_points.clone();
+        marker.geometry = MarkerGeometry::Polygon { points: points.clone() };
+        drop(storage_guard);
+        
+        self.event_bus.publish(DomainEvent::PolygonGeometryUpdated {
+            id,
+            old_points,
+            new_points: points,
+        });
+        
+        Ok(true)
+    }
+    
+    pub fn update_marker_translation(&self, marker_id: u32, translation: String) -> bool {
+        self.update_marker_translation_from(MarkerId::from(marker_id), translation, TranslationOrigin::Manual)
+    }
+    
+    /// 修改译文并按来源记入译文历史
+    pub fn update_marker_translation_from(&self, id: MarkerId, translation: String, origin: TranslationOrigin) -> bool {
+        let old = self.get_marker_internal(id.0).map(|marker| marker.translation);
+        let new = translation.clone();
+        let result = self.update_marker_translation_with_undo(id, translation).unwrap_or(false);
         
         if result {
-            self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
+            if let Some(old) = old {
+                let _ = translation_history::record_translation_storage(id, &old, &new, origin);
+            }
+            self.event_bus.publish(DomainEvent::MarkerUpdated(id));
         }
         
         result
     }
     
+    /// 把译文改回历史中的某一条（下标从0开始，最旧的在前），本身可撤销
+    pub fn revert_translation(&self, id: MarkerId, history_index: usize) -> CoreResult<bool> {
+        let history = translation_history::get_translation_history_storage(id)?;
+        let entry = history.get(history_index).ok_or_else(|| CoreError::ValidationFailed {
+            field: "history_index".to_string(),
+            reason: format!("标记{}只有{}条译文历史，没有第{}条", id.0, history.len(), history_index),
+        })?;
+        // 作为单独一次撤销，不与前后输入的译文合并
+        let project_id = match self.get_marker_internal(id.0) {
+            Some(marker) => crate::service::get_service().project_service.find_project_by_image(marker.image_id)?,
+            None => None,
+        };
+        let Some(project_id) = project_id else {
+            return Ok(self.update_marker_translation_from(id, entry.translation.clone(), TranslationOrigin::Manual));
+        };
+        let guard = crate::service::get_service().undo_redo_service.begin_group_guard(project_id, "回退译文".to_string())?;
+        let result = self.update_marker_translation_from(id, entry.translation.clone(), TranslationOrigin::Manual);
+        guard.finish()?;
+        Ok(result)
+    }
+    
     pub fn update_marker_translation_with_undo(&self, id: MarkerId, translation: String) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             let old_trans = marker.translation.clone();
             let new_trans = translation.clone();
@@ -255,26 +351,89 @@ impl MarkerService {
         }
     }
     
-    pub fn update_marker_style(&self, marker_id: u32, overlay_text: bool, horizontal: bool) -> bool {
-        let style = MarkerStyle {
-            overlay_text,
-            horizontal,
+    /// 设置译者备注，None或空字符串清除；可撤销
+    pub fn update_marker_note(&self, id: MarkerId, note: Option<String>) -> CoreResult<bool> {
+        let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+        let Some(current) = storage::get_marker_storage(id)?.map(|marker| marker.note) else {
+            return Ok(false);
         };
-        
-        let result = match self.update_marker_style_with_undo(MarkerId::from(marker_id), style) {
-            Ok(res) => res,
-            Err(_) => false,
+        if current == note {
+            return Ok(true);
+        }
+        let result = self.update_marker_note_with_undo(id, note)?;
+        if result {
+            self.event_bus.publish(DomainEvent::MarkerUpdated(id));
+        }
+        Ok(result)
+    }
+    
+    pub fn update_marker_note_with_undo(&self, id: MarkerId, note: Option<String>) -> CoreResult<bool> {
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
         };
+        let Some(marker) = storage_guard.get_mut(&id) else {
+            return Ok(false);
+        };
+        let old_note = std::mem::replace(&mut marker.note, note.clone());
+        drop(storage_guard);
         
+        self.event_bus.publish(DomainEvent::MarkerNoteUpdated { id, old_note, new_note: note });
+        Ok(true)
+    }
+    
+    pub fn update_marker_style(&self, marker_id: u32, overlay_text: bool, horizontal: bool) -> bool {
+        // 只改这两项，字号和标签保持不变
+        self.edit_marker_style(MarkerId::from(marker_id), |style| {
+            style.overlay_text = overlay_text;
+            style.horizontal = horizontal;
+        }).unwrap_or(false)
+    }
+
+    /// 设置相对字号（1.0为正常），None清除
+    pub fn update_marker_size_hint(&self, id: MarkerId, size_hint: Option<f32>) -> CoreResult<bool> {
+        let (min, max) = storage::SIZE_HINT_RANGE;
+        if let Some(hint) = size_hint && !(min..=max).contains(&hint) {
+            return Err(CoreError::ValidationFailed {
+                field: "size_hint".to_string(),
+                reason: format!("相对字号必须在{}到{}之间", min, max),
+            });
+        }
+        self.edit_marker_style(id, |style| style.size_hint = size_hint)
+    }
+
+    /// 设置分类标签（如thought、shout、narration），空字符串等同于清除
+    pub fn update_marker_tag(&self, id: MarkerId, tag: Option<String>) -> CoreResult<bool> {
+        let tag = tag.map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty());
+        if let Some(tag) = &tag && tag.chars().count() > storage::MAX_TAG_LENGTH {
+            return Err(CoreError::ValidationFailed {
+                field: "tag".to_string(),
+                reason: format!("标签不能超过{}个字符", storage::MAX_TAG_LENGTH),
+            });
+        }
+        self.edit_marker_style(id, |style| style.tag = tag)
+    }
+
+    // 在当前样式上修改后经撤销系统写回；样式没有变化时不产生撤销记录
+    fn edit_marker_style(&self, id: MarkerId, edit: impl FnOnce(&mut MarkerStyle)) -> CoreResult<bool> {
+        let Some(current) = storage::get_marker_storage(id)?.map(|marker| marker.style) else {
+            return Ok(false);
+        };
+        let mut style = current.clone();
+        edit(&mut style);
+        if style == current {
+            return Ok(true);
+        }
+        let result = self.update_marker_style_with_undo(id, style)?;
         if result {
-            self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
+            self.event_bus.publish(DomainEvent::MarkerUpdated(id));
         }
-        
-        result
+        Ok(result)
     }
     
     pub fn update_marker_style_with_undo(&self, id: MarkerId, style: MarkerStyle) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             let old_style = marker.style.clone();
             let new_style = style.clone();
@@ -305,7 +464,9 @@ impl MarkerService {
     }
     
     pub fn move_marker_order_with_undo(&self, id: MarkerId, new_index: u32) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         
         // Get the marker and its current index
         let marker = storage_guard.get(&id).cloned();
@@ -322,28 +483,8 @@ impl MarkerService {
             return Ok(true);
         }
         
-        // Get all markers for this image
-        let marker_ids = storage_guard.by_image.get(&image_id).cloned().unwrap_or_default();
-        
-        // Adjust indices for other markers
-        for marker_id in &marker_ids {
-            if let Some(other_marker) = storage_guard.markers.get_mut(marker_id) {
-                if other_marker.id == id {
-                    // This is the marker we're moving
-                    other_marker.image_index = new_index;
-                } else if old_index < new_index {
-                    // Moving forward: shift markers in between backwards
-                    if other_marker.image_index > old_index && other_marker.image_index <= new_index {
-                        other_marker.image_index -= 1;
-                    }
-                } else {
-                    // Moving backward: shift markers in between forwards
-                    if other_marker.image_index >= new_index && other_marker.image_index < old_index {
-                        other_marker.image_index += 1;
-                    }
-                }
-            }
-        }
+        // Markers in between shift by one; only that range of the image index is touched
+        storage_guard.move_to_index(id, new_index);
         
         drop(storage_guard);
         
@@ -358,37 +499,163 @@ impl MarkerService {
         Ok(true)
     }
     
+    /// 把图片的标记序号压缩为连续的1..N（保持当前顺序），作为一次撤销操作，返回改了序号的标记数
+    pub fn compact_indices(&self, image_id: ImageId) -> CoreResult<usize> {
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)?
+            .ok_or_else(|| CoreError::NotFound(format!("Project for image {} not found", image_id.0)))?;
+        self.compact_images(project_id, &[image_id])
+    }
+    
+    /// 压缩项目内所有图片的标记序号，整体作为一次撤销操作；锁定的图片保持原序号
+    pub fn compact_project_indices(&self, project_id: ProjectId) -> CoreResult<usize> {
+        let image_ids: Vec<ImageId> = crate::storage::project::get_project_image_ids_storage(project_id)?
+            .into_iter()
+            .filter(|image_id| !crate::service::image::is_image_locked(*image_id))
+            .collect();
+        self.compact_images(project_id, &image_ids)
+    }
+    
+    /// 按项目的阅读方向给图片的标记重新编号为1..N，作为一次撤销操作，返回改了序号的标记数
+    pub fn renumber_by_reading_order(&self, image_id: ImageId) -> CoreResult<usize> {
+        let project_id = crate::storage::project::find_project_by_image_storage(image_id)?
+            .ok_or_else(|| CoreError::NotFound(format!("Project for image {} not found", image_id.0)))?;
+        let order = super::reading_order::image_reading_order(image_id)?;
+        
+        let mut storage_guard = APP_STATE.markers.write()?;
+        let changes = storage::apply_image_index_order(storage_guard.image_mut(&image_id), &order);
+        drop(storage_guard);
+        
+        let count = changes.len();
+        if count > 0 {
+            self.event_bus.publish(DomainEvent::MarkerIndicesCompacted { project_id, changes: vec![(image_id, changes)] });
+            emit_markers_renumbered(&[image_id]);
+        }
+        Ok(count)
+    }
+    
+    /// 按阅读顺序选择下一个（backward时为上一个）标记，current为None时从头（尾）开始
+    pub fn select_next_marker(&self, image_id: ImageId, current: Option<MarkerId>, backward: bool) -> CoreResult<Option<MarkerId>> {
+        let order = super::reading_order::image_reading_order(image_id)?;
+        Ok(super::reading_order::next_in_reading_order(&order, current, backward))
+    }
+    
+    fn compact_images(&self, project_id: ProjectId, image_ids: &[ImageId]) -> CoreResult<usize> {
+        let mut storage_guard = APP_STATE.markers.write()?;
+        let changes: Vec<(ImageId, storage::MarkerIndexChanges)> = image_ids.iter()
+            .map(|image_id| (*image_id, storage::compact_image_indices(storage_guard.image_mut(image_id), *image_id)))
+            .filter(|(_, changes)| !changes.is_empty())
+            .collect();
+        drop(storage_guard);
+        
+        let count = changes.iter().map(|(_, changes)| changes.len()).sum();
+        if !changes.is_empty() {
+            let changed_images: Vec<ImageId> = changes.iter().map(|(image_id, _)| *image_id).collect();
+            self.event_bus.publish(DomainEvent::MarkerIndicesCompacted { project_id, changes });
+            emit_markers_renumbered(&changed_images);
+        }
+        Ok(count)
+    }
+    
+    /// 把标记移到同一项目的另一张图片（跨页拼图拆分后标错页时使用）
+    ///
+    /// 原图片剩下的标记重新编号；标记追加到目标图片末尾，指定position时插入到该序号并后移之后的标记。
+    /// 译文、样式和海兔缓存保持不变，整个移动作为一次撤销操作。
+    pub fn move_marker_to_image(&self, id: MarkerId, target_image_id: ImageId, geometry: MarkerGeometry, position: Option<u32>) -> CoreResult<bool> {
+        let geometry = geometry.checked(CoordinateMode::Reject)?;
+        let Some(source_image_id) = APP_STATE.markers.locate(id)? else {
+            return Ok(false);
+        };
+        if source_image_id == target_image_id {
+            return Err(CoreError::ValidationFailed {
+                field: "target_image_id".to_string(),
+                reason: "标记已经在这张图片上".to_string(),
+            });
+        }
+        let project_id = crate::storage::project::find_project_by_image_storage(source_image_id)?
+            .ok_or_else(|| CoreError::NotFound(format!("Project for image {} not found", source_image_id.0)))?;
+        if crate::storage::project::find_project_by_image_storage(target_image_id)? != Some(project_id) {
+            return Err(CoreError::ValidationFailed {
+                field: "target_image_id".to_string(),
+                reason: "只能在同一项目的图片之间移动标记".to_string(),
+            });
+        }
+        if let Some(gesture) = self.drag_gestures.write()?.remove(&id) {
+            self.finalize_drag(id, gesture);
+        }
+        
+        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(old_marker) = storage_guard.get(&id).cloned() else {
+            return Ok(false);
+        };
+        let old_source = storage::image_marker_indices(&storage_guard, source_image_id, id);
+        let old_target = storage::image_marker_indices(&storage_guard, target_image_id, id);
+        
+        // 原图片按当前顺序重新编号为1..N
+        let mut new_source = old_source.clone();
+        new_source.sort_by_key(|&(marker_id, index)| (index, marker_id.0));
+        for (i, entry) in new_source.iter_mut().enumerate() {
+            entry.1 = (i + 1) as u32;
+        }
+        // 目标图片插入位置及之后的标记后移一位
+        let next_index = old_target.iter().map(|&(_, index)| index).max().unwrap_or(0) + 1;
+        let index = position.map_or(next_index, |position| position.clamp(1, next_index));
+        let new_target = old_target.iter()
+            .map(|&(marker_id, i)| (marker_id, if i >= index { i + 1 } else { i }));
+        
+        let mut new_marker = old_marker.clone();
+        new_marker.image_id = target_image_id;
+        new_marker.image_index = index;
+        new_marker.geometry = geometry;
+        let old_indices: Vec<(MarkerId, u32)> = old_source.into_iter().chain(old_target.iter().copied()).collect();
+        let new_indices: Vec<(MarkerId, u32)> = new_source.into_iter().chain(new_target).collect();
+        storage::relocate_marker(&mut storage_guard, new_marker.clone(), &new_indices)?;
+        drop(storage_guard);
+        
+        crate::storage::image::remove_marker_from_image_storage(source_image_id, id)?;
+        crate::storage::image::add_marker_to_image_storage(target_image_id, id)?;
+        
+        self.event_bus.publish(DomainEvent::MarkerMovedToImage { project_id, old_marker, new_marker, old_indices, new_indices });
+        self.event_bus.publish(DomainEvent::MarkerUpdated(id));
+        emit_markers_renumbered(&[source_image_id, target_image_id]);
+        Ok(true)
+    }
+    
     // 点型marker完整更新
-    pub fn update_point_marker_full(&self, marker_id: u32, x: f64, y: f64, translation: Option<String>) -> bool {
-        if let Some(current) = self.get_marker_internal(marker_id) {
-            // 检查是否是点型marker
-            if let MarkerGeometry::Point { .. } = current.geometry {
-                let result = match self.update_point_marker_with_undo(
-                    MarkerId::from(marker_id),
-                    x,
-                    y,
-                    translation.unwrap_or(current.translation),
-                    current.style
-                ) {
-                    Ok(res) => res,
-                    Err(_) => false,
-                };
-                
-                if result {
-                    self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
-                }
-                
-                result
-            } else {
-                false
+    pub fn update_point_marker_full(&self, marker_id: u32, x: f64, y: f64, translation: Option<String>, mode: CoordinateMode) -> CoreResult<bool> {
+        let Some(current) = self.get_marker_internal(marker_id) else {
+            return Ok(false);
+        };
+        // 检查是否是点型marker
+        if !matches!(current.geometry, MarkerGeometry::Point { .. }) {
+            return Ok(false);
+        }
+        let MarkerGeometry::Point { x, y } = (MarkerGeometry::Point { x, y }).checked(mode)? else {
+            unreachable!("checked keeps the geometry variant");
+        };
+        let result = self.update_point_marker_with_undo(
+            MarkerId::from(marker_id),
+            x,
+            y,
+            translation.clone().unwrap_or(current.translation.clone()),
+            current.style
+        )?;
+        
+        if result {
+            if let Some(new) = &translation {
+                let _ = translation_history::record_translation_storage(
+                    MarkerId::from(marker_id), &current.translation, new, TranslationOrigin::Manual,
+                );
             }
-        } else {
-            false
+            self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
         }
+        
+        Ok(result)
     }
     
     pub fn update_point_marker_with_undo(&self, id: MarkerId, x: f64, y: f64, translation: String, style: MarkerStyle) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 检查是否是点型marker
             if let MarkerGeometry::Point { x: old_x, y: old_y } = marker.geometry {
@@ -426,38 +693,44 @@ impl MarkerService {
     }
     
     // 矩形型marker完整更新
-    pub fn update_rectangle_marker_full(&self, marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>) -> bool {
-        if let Some(current) = self.get_marker_internal(marker_id) {
-            // 检查是否是矩形型marker
-            if let MarkerGeometry::Rectangle { .. } = current.geometry {
-                let result = match self.update_rectangle_marker_with_undo(
-                    MarkerId::from(marker_id),
-                    x,
-                    y,
-                    width,
-                    height,
-                    translation.unwrap_or(current.translation),
-                    current.style
-                ) {
-                    Ok(res) => res,
-                    Err(_) => false,
-                };
-                
-                if result {
-                    self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
-                }
-                
-                result
-            } else {
-                false
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_rectangle_marker_full(&self, marker_id: u32, x: f64, y: f64, width: f64, height: f64, translation: Option<String>, mode: CoordinateMode) -> CoreResult<bool> {
+        let Some(current) = self.get_marker_internal(marker_id) else {
+            return Ok(false);
+        };
+        // 检查是否是矩形型marker
+        if !matches!(current.geometry, MarkerGeometry::Rectangle { .. }) {
+            return Ok(false);
+        }
+        let MarkerGeometry::Rectangle { x, y, width, height } = (MarkerGeometry::Rectangle { x, y, width, height }).checked(mode)? else {
+            unreachable!("checked keeps the geometry variant");
+        };
+        let result = self.update_rectangle_marker_with_undo(
+            MarkerId::from(marker_id),
+            x,
+            y,
+            width,
+            height,
+            translation.clone().unwrap_or(current.translation.clone()),
+            current.style
+        )?;
+        
+        if result {
+            if let Some(new) = &translation {
+                let _ = translation_history::record_translation_storage(
+                    MarkerId::from(marker_id), &current.translation, new, TranslationOrigin::Manual,
+                );
             }
-        } else {
-            false
+            self.event_bus.publish(DomainEvent::MarkerUpdated(MarkerId::from(marker_id)));
         }
+        
+        Ok(result)
     }
     
     pub fn update_rectangle_marker_with_undo(&self, id: MarkerId, x: f64, y: f64, width: f64, height: f64, translation: String, style: MarkerStyle) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 检查是否是矩形型marker
             if let MarkerGeometry::Rectangle { x: old_x, y: old_y, .. } = marker.geometry {
@@ -494,6 +767,277 @@ impl MarkerService {
         }
     }
     
+    // === 查找替换 ===
+    
+    /// 在项目所有图片的标记翻译中查找替换，整体记录为一次撤销操作；锁定的图片跳过并记在报告中
+    pub fn replace_in_translations(&self, project_id: ProjectId, find: &str, replace: &str, case_sensitive: bool, whole_word: bool, token: &CancellationToken) -> CoreResult<ReplaceReport> {
+        if find.is_empty() {
+            return Err(CoreError::ValidationFailed {
+                field: "find".to_string(),
+                reason: "查找内容不能为空".to_string(),
+            });
+        }
+        
+        let options = ReplaceOptions { case_sensitive, whole_word };
+        let image_ids = crate::storage::project::get_project_image_ids_storage(project_id)?;
+        let mut report = ReplaceReport::default();
+        let mut changes = Vec::new();
+        
+        for image_id in image_ids {
+            // 按图片检查取消，已替换的部分照常提交（仍为一次撤销操作）
+            if token.is_cancelled() {
+                report.status = OperationStatus::Cancelled;
+                break;
+            }
+            let locked = crate::service::image::is_image_locked(image_id);
+            // 逐张图片加锁，替换期间其他图片的标记仍可读写
+            let mut storage_guard = APP_STATE.markers.write_image(image_id)?;
+            let marker_ids = storage_guard.image_marker_ids(&image_id).to_vec();
+            if locked {
+                let matched = marker_ids.iter()
+                    .filter_map(|id| storage_guard.get(id))
+                    .any(|marker| replace_text(&marker.translation, find, replace, options).0 != marker.translation);
+                if matched {
+                    report.skipped_image_ids.push(image_id);
+                }
+                continue;
+            }
+            for marker_id in marker_ids {
+                if let Some(marker) = storage_guard.get_mut(&marker_id) {
+                    let (new_trans, count) = replace_text(&marker.translation, find, replace, options);
+                    if count > 0 && new_trans != marker.translation {
+                        let old_trans = std::mem::replace(&mut marker.translation, new_trans.clone());
+                        changes.push((marker_id, old_trans, new_trans));
+                        report.changed_marker_ids.push(marker_id);
+                        report.replacements += count;
+                    }
+                }
+            }
+        }
+        
+        if !changes.is_empty() {
+            for (marker_id, old_trans, new_trans) in &changes {
+                translation_history::record_translation_storage(*marker_id, old_trans, new_trans, TranslationOrigin::Manual)?;
+            }
+            self.event_bus.publish(DomainEvent::MarkerTranslationsBatchUpdated { project_id, changes });
+            for marker_id in &report.changed_marker_ids {
+                self.event_bus.publish(DomainEvent::MarkerUpdated(*marker_id));
+            }
+        }
+        
+        Ok(report)
+    }
+
+    /// 批量写入导入的翻译，并按需新建标记（序号接在图片现有标记之后），整体记录为一次撤销操作
+    pub fn apply_imported_translations(
+        &self,
+        project_id: ProjectId,
+        updates: Vec<(MarkerId, String)>,
+        creations: Vec<(ImageId, MarkerGeometry, String, MarkerStyle)>,
+    ) -> CoreResult<(Vec<MarkerId>, Vec<MarkerId>)> {
+        let mut changes = Vec::new();
+        let mut created = Vec::new();
+
+        let mut storage_guard = APP_STATE.markers.write()?;
+        for (marker_id, new_trans) in updates {
+            if let Some(marker) = storage_guard.get_mut(&marker_id)
+                && marker.translation != new_trans
+            {
+                let old_trans = std::mem::replace(&mut marker.translation, new_trans.clone());
+                changes.push((marker_id, old_trans, new_trans));
+            }
+        }
+        for (image_id, geometry, translation, style) in creations {
+            let image_index = storage_guard.image(&image_id).next_index(&image_id);
+            let mut marker = Marker::new_point(MARKER_ID_GENERATOR.next(), image_id, 0.0, 0.0, image_index);
+            marker.geometry = geometry.clamped();
+            marker.translation = translation;
+            marker.style = style;
+            storage_guard.insert_with_image(marker.clone())?;
+            created.push(marker);
+        }
+        drop(storage_guard);
+
+        for marker in &created {
+            crate::storage::image::add_marker_to_image_storage(marker.image_id, marker.id)?;
+        }
+
+        for (marker_id, old_trans, new_trans) in &changes {
+            translation_history::record_translation_storage(*marker_id, old_trans, new_trans, TranslationOrigin::Import)?;
+        }
+        for marker in &created {
+            translation_history::record_translation_storage(marker.id, "", &marker.translation, TranslationOrigin::Import)?;
+        }
+
+        let changed_ids: Vec<MarkerId> = changes.iter().map(|(id, _, _)| *id).collect();
+        let created_ids: Vec<MarkerId> = created.iter().map(|m| m.id).collect();
+        if !changes.is_empty() || !created.is_empty() {
+            self.event_bus.publish(DomainEvent::MarkersImported { project_id, changes, created });
+            for marker_id in &changed_ids {
+                self.event_bus.publish(DomainEvent::MarkerUpdated(*marker_id));
+            }
+        }
+
+        Ok((changed_ids, created_ids))
+    }
+
+    /// 检查项目内翻译的一致性，按图片顺序、图片内按marker顺序返回问题列表
+    pub fn analyze_translations(&self, project_id: ProjectId, config: &ConsistencyConfig) -> CoreResult<Vec<TranslationIssue>> {
+        let image_ids = crate::storage::project::get_project_image_ids_storage(project_id)?;
+        let mut entries = Vec::new();
+        {
+            for image_id in image_ids {
+                let storage_guard = APP_STATE.markers.read_image(image_id)?;
+                let bunny_cache = APP_STATE.bunny_cache.read()?;
+                for marker in storage_guard.get_by_image(&image_id) {
+                    entries.push(TranslationEntry {
+                        marker_id: marker.id,
+                        translation: marker.translation.clone(),
+                        original_text: bunny_cache.get(&marker.id).and_then(|cache| cache.original_text.clone()),
+                    });
+                }
+            }
+        }
+        
+        Ok(consistency::analyze(&entries, config, &consistency::default_checks()))
+    }
+    
+    // === 拖拽手势 ===
+    
+    /// 开始拖拽手势，记录起始几何。同一marker已有手势时先结束旧手势
+    pub fn begin_marker_drag(&self, id: MarkerId) -> CoreResult<bool> {
+        if let Some(previous) = self.drag_gestures.write()?.remove(&id) {
+            self.finalize_drag(id, previous);
+        }
+        
+        let Some(marker) = storage::get_marker_storage(id)? else {
+            return Ok(false);
+        };
+        self.drag_gestures.write()?.insert(id, DragGesture::new(marker.image_id, marker.geometry));
+        Ok(true)
+    }
+    
+    /// 结束拖拽手势：写入最终几何，并把起点到终点的变化记录为一次撤销操作
+    /// 没有进行中的手势时等同于一次普通的几何更新
+    pub fn end_marker_drag(&self, id: MarkerId, final_geometry: MarkerGeometry) -> CoreResult<bool> {
+        let Some(gesture) = self.drag_gestures.write()?.remove(&id) else {
+            return match final_geometry {
+                MarkerGeometry::Point { x, y } => self.update_point_marker_position_checked(id, x, y, CoordinateMode::Reject),
+                MarkerGeometry::Rectangle { x, y, width, height } => {
+                    self.update_rectangle_marker_geometry_checked(id, x, y, width, height, CoordinateMode::Reject)
+                }
+                MarkerGeometry::Polygon { points } => self.update_polygon_marker_geometry_checked(id, points, CoordinateMode::Reject),
+            };
+        };
+        
+        // 最终几何无效时仍然结束手势，保留最后一次有效的中间位置
+        let applied = final_geometry.checked(CoordinateMode::Reject)
+            .and_then(|geometry| self.set_drag_geometry(id, geometry));
+        self.finalize_drag(id, gesture);
+        applied
+    }
+    
+    pub fn is_dragging(&self, id: MarkerId) -> bool {
+        self.drag_gestures.read().map(|g| g.contains_key(&id)).unwrap_or(false)
+    }
+    
+    /// 自动结束指定图片上的所有手势（marker删除、图片清空等情况）
+    pub fn finalize_drags_for_image(&self, image_id: ImageId) {
+        self.finalize_drags_where(|gesture| gesture.image_id == image_id);
+    }
+    
+    /// 自动结束指定项目内的所有手势（项目关闭时）
+    pub fn finalize_drags_for_project(&self, project_id: ProjectId) {
+        let Ok(image_ids) = crate::storage::project::get_project_image_ids_storage(project_id) else {
+            return;
+        };
+        self.finalize_drags_where(|gesture| image_ids.contains(&gesture.image_id));
+    }
+    
+    pub fn finalize_all_drags(&self) {
+        self.finalize_drags_where(|_| true);
+    }
+    
+    fn finalize_drags_where<F: Fn(&DragGesture) -> bool>(&self, predicate: F) {
+        let finished: Vec<(MarkerId, DragGesture)> = match self.drag_gestures.write() {
+            Ok(mut gestures) => {
+                let ids: Vec<MarkerId> = gestures.iter()
+                    .filter(|(_, gesture)| predicate(gesture))
+                    .map(|(id, _)| *id)
+                    .collect();
+                ids.into_iter().filter_map(|id| gestures.remove(&id).map(|g| (id, g))).collect()
+            }
+            Err(_) => return,
+        };
+        
+        for (id, gesture) in finished {
+            self.finalize_drag(id, gesture);
+        }
+    }
+    
+    /// 手势期间只写存储，不发布撤销事件
+    fn set_drag_geometry(&self, id: MarkerId, geometry: MarkerGeometry) -> CoreResult<bool> {
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
+        match storage_guard.get_mut(&id) {
+            Some(marker) if std::mem::discriminant(&marker.geometry) == std::mem::discriminant(&geometry) => {
+                marker.geometry = geometry;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+    
+    /// 手势进行中时按DRAG_EVENT_INTERVAL_MS节流MarkerUpdated事件
+    fn should_emit_marker_updated(&self, id: MarkerId) -> bool {
+        match self.drag_gestures.write() {
+            Ok(mut gestures) => gestures.get_mut(&id).map(|g| g.try_emit()).unwrap_or(true),
+            Err(_) => true,
+        }
+    }
+    
+    /// 以当前存储的几何为终点记录撤销，并补发被节流掉的事件
+    fn finalize_drag(&self, id: MarkerId, gesture: DragGesture) {
+        let current = storage::get_marker_storage(id).ok().flatten().map(|m| m.geometry);
+        let Some(current) = current else {
+            return;
+        };
+        
+        let changed = current != gesture.start_geometry;
+        match (&gesture.start_geometry, &current) {
+            (MarkerGeometry::Point { x: old_x, y: old_y }, MarkerGeometry::Point { x, y }) if changed => {
+                self.event_bus.publish(DomainEvent::PointMarkerPositionUpdated {
+                    id,
+                    old_pos: (*old_x, *old_y),
+                    new_pos: (*x, *y),
+                });
+            }
+            (
+                MarkerGeometry::Rectangle { x: old_x, y: old_y, width: old_width, height: old_height },
+                MarkerGeometry::Rectangle { x, y, width, height },
+            ) if changed => {
+                self.event_bus.publish(DomainEvent::RectangleGeometryUpdated {
+                    id,
+                    old_geometry: (*old_x, *old_y, *old_width, *old_height),
+                    new_geometry: (*x, *y, *width, *height),
+                });
+            }
+            (MarkerGeometry::Polygon { points: old_points }, MarkerGeometry::Polygon { points }) if changed => {
+                self.event_bus.publish(DomainEvent::PolygonGeometryUpdated {
+                    id,
+                    old_points: old_points.clone(),
+                    new_points: points.clone(),
+                });
+            }
+            _ => {}
+        }
+        
+        if changed || gesture.has_pending_emit() {
+            self.event_bus.publish(DomainEvent::MarkerUpdated(id));
+        }
+    }
+    
     // === 标记删除操作 ===
     
     pub fn remove_marker(&self, marker_id: u32) -> bool {
@@ -510,7 +1054,13 @@ impl MarkerService {
     }
     
     pub fn remove_marker_with_undo(&self, id: MarkerId) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        if let Some(gesture) = self.drag_gestures.write()?.remove(&id) {
+            self.finalize_drag(id, gesture);
+        }
+        
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         if let Some(marker) = storage_guard.remove_with_cleanup(&id) {
             let image_id = marker.image_id;
             
@@ -540,26 +1090,22 @@ impl MarkerService {
     }
     
     pub fn clear_image_markers_with_undo(&self, image_id: ImageId) -> CoreResult<()> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        self.finalize_drags_for_image(image_id);
+        
+        let mut storage_guard = APP_STATE.markers.write_image(image_id)?;
         let mut removed_markers = Vec::new();
         let mut marker_ids_to_clear = Vec::new();
 
-        if let Some(marker_ids) = storage_guard.by_image.get(&image_id).cloned() {
-            for marker_id in marker_ids {
-                marker_ids_to_clear.push(marker_id);
-                if let Some(marker) = storage_guard.markers.remove(&marker_id) {
-                    removed_markers.push(marker);
-                }
-            }
-            storage_guard.by_image.remove(&image_id);
-        }
+        marker_ids_to_clear.extend(storage_guard.image_marker_ids(&image_id).iter().copied());
+        removed_markers.extend(storage_guard.remove_image(&image_id));
 
         // Drop lock before publishing event
         drop(storage_guard);
 
-        // Clear bunny cache for all removed markers
+        // Clear bunny cache and translation history for all removed markers
         for marker_id in marker_ids_to_clear {
             let _ = crate::storage::bunny_cache::clear_bunny_cache_storage(marker_id);
+            let _ = translation_history::clear_translation_history_storage(marker_id);
         }
 
         // Publish event with markers data for undo/redo if any markers were removed
@@ -577,18 +1123,11 @@ impl MarkerService {
     /// Used when deleting images - the markers are moved into the undo/redo action
     /// If the action is dropped (e.g., overwritten), the markers are automatically freed
     pub fn take_image_markers(&self, image_id: ImageId) -> CoreResult<Vec<Marker>> {
-        let mut storage_guard = APP_STATE.markers.write()?;
-        let mut removed_markers = Vec::new();
+        self.finalize_drags_for_image(image_id);
         
-        // Remove markers from storage and collect them
-        if let Some(marker_ids) = storage_guard.by_image.get(&image_id).cloned() {
-            for marker_id in marker_ids {
-                if let Some(marker) = storage_guard.markers.remove(&marker_id) {
-                    removed_markers.push(marker);  // Transfer ownership
-                }
-            }
-            storage_guard.by_image.remove(&image_id);
-        }
+        let mut storage_guard = APP_STATE.markers.write_image(image_id)?;
+        // Remove markers from storage and collect them (ownership transferred)
+        let removed_markers = storage_guard.remove_image(&image_id);
         
         drop(storage_guard);
         
@@ -608,12 +1147,8 @@ impl MarkerService {
         
         let mut storage_guard = APP_STATE.markers.write()?;
         for marker in &markers {
-            let image_id = marker.image_id;
-            let marker_id = marker.id;
-            
             // Insert marker back into storage (clone to transfer ownership)
-            storage_guard.markers.insert(marker_id, marker.clone());
-            storage_guard.by_image.entry(image_id).or_default().push(marker_id);
+            storage_guard.insert_with_image(marker.clone())?;
         }
         drop(storage_guard);
         
@@ -636,7 +1171,9 @@ impl MarkerService {
     }
     
     fn convert_rectangle_to_point_with_undo(&self, id: MarkerId) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 只能转换矩形marker
@@ -648,8 +1185,8 @@ impl MarkerService {
                 let new_x = x + width / 2.0;
                 let new_y = *y;
                 
-                // 创建新的点型geometry
-                let new_geometry = MarkerGeometry::Point { x: new_x, y: new_y };
+                // 创建新的点型geometry（旧数据可能越界，转换结果限制到图片范围内）
+                let new_geometry = MarkerGeometry::Point { x: new_x, y: new_y }.checked(CoordinateMode::Clamp)?;
                 marker.geometry = new_geometry;
                 
                 let new_marker = marker.clone();
@@ -690,7 +1227,9 @@ impl MarkerService {
     }
     
     fn convert_point_to_rectangle_with_undo(&self, id: MarkerId) -> CoreResult<bool> {
-        let mut storage_guard = APP_STATE.markers.write()?;
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
         
         if let Some(marker) = storage_guard.get_mut(&id) {
             // 只能转换点型marker
@@ -726,7 +1265,7 @@ impl MarkerService {
                     y: rect_y, 
                     width: rect_width, 
                     height: rect_height 
-                };
+                }.checked(CoordinateMode::Clamp)?;
                 marker.geometry = new_geometry;
                 
                 let new_marker = marker.clone();
@@ -758,6 +1297,120 @@ impl MarkerService {
         }
     }
     
+    /// 将多边形marker转换为其外接矩形
+    pub fn convert_polygon_to_rectangle(&self, marker_id: u32) -> bool {
+        self.convert_polygon_to_rectangle_with_undo(MarkerId::from(marker_id)).unwrap_or(false)
+    }
+    
+    fn convert_polygon_to_rectangle_with_undo(&self, id: MarkerId) -> CoreResult<bool> {
+        let Some(mut storage_guard) = APP_STATE.markers.write_marker(id)? else {
+            return Ok(false);
+        };
+        let Some(marker) = storage_guard.get_mut(&id) else {
+            return Ok(false);
+        };
+        // 只能转换多边形marker
+        if !matches!(marker.geometry, MarkerGeometry::Polygon { .. }) {
+            return Ok(false);
+        }
+        let old_marker = marker.clone();
+        let image_id = marker.image_id;
+        marker.geometry = marker.geometry.to_bounding_rectangle().checked(CoordinateMode::Clamp)?;
+        let new_marker = marker.clone();
+        drop(storage_guard);
+        
+        // 记录转换动作用于撤销重做
+        let project_id = crate::service::get_service().project_service.find_project_by_image(image_id)?;
+        if let Some(project_id) = project_id {
+            let action = crate::service::undo_redo::UndoRedoAction::new(
+                crate::service::undo_redo::ActionType::ConvertPolygonToRectangle {
+                    marker_id: id,
+                    old_marker,
+                    new_marker,
+                },
+                project_id,
+            );
+            let _ = crate::service::get_service().undo_redo_service.record_action(action);
+        }
+        
+        self.event_bus.publish(DomainEvent::MarkerUpdated(id));
+        Ok(true)
+    }
+    
+    // === 标记合并 ===
+    
+    /// 把同一图片上的多个矩形标记合并为它们的外接矩形，是按OCR区域拆分的逆操作，整体为一次撤销
+    /// 译文按阅读顺序用换行拼接（空译文跳过），新标记排在原标记中最靠前的位置
+    pub fn merge_rectangles(&self, ids: &[MarkerId]) -> CoreResult<MarkerId> {
+        let mut markers: Vec<Marker> = Vec::with_capacity(ids.len());
+        for id in ids {
+            if markers.iter().any(|marker| marker.id == *id) {
+                continue;
+            }
+            let marker = storage::get_marker_storage(*id)?
+                .ok_or_else(|| CoreError::NotFound(format!("Marker {}", id.0)))?;
+            markers.push(marker);
+        }
+        if markers.len() < 2 {
+            return Err(CoreError::ValidationFailed {
+                field: "ids".to_string(),
+                reason: "至少选择两个矩形标记才能合并".to_string(),
+            });
+        }
+        let image_id = markers[0].image_id;
+        if markers.iter().any(|marker| marker.image_id != image_id) {
+            return Err(CoreError::ValidationFailed {
+                field: "ids".to_string(),
+                reason: "只能合并同一图片上的标记".to_string(),
+            });
+        }
+        if let Some(other) = markers.iter().find(|marker| !matches!(marker.geometry, MarkerGeometry::Rectangle { .. })) {
+            let kind = match other.geometry {
+                MarkerGeometry::Point { .. } => "点型",
+                _ => "多边形",
+            };
+            return Err(CoreError::ValidationFailed {
+                field: "ids".to_string(),
+                reason: format!("只能合并矩形标记，{}号标记是{}标记", other.image_index, kind),
+            });
+        }
+        
+        let (mut left, mut top, mut right, mut bottom) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for marker in &markers {
+            if let MarkerGeometry::Rectangle { x, y, width, height } = marker.geometry {
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x + width);
+                bottom = bottom.max(y + height);
+            }
+        }
+        let order = super::reading_order::reading_order(&markers, super::reading_order::direction_for_image(image_id)?);
+        let translation = order.iter()
+            .filter_map(|id| markers.iter().find(|marker| marker.id == *id))
+            .map(|marker| marker.translation.as_str())
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let first_index = markers.iter().map(|marker| marker.image_index).min().unwrap_or(1);
+        
+        let service = crate::service::get_service();
+        let guard = match service.project_service.find_project_by_image(image_id)? {
+            Some(project_id) => Some(service.undo_redo_service.begin_group_guard(project_id, "合并矩形标记".to_string())?),
+            None => None,
+        };
+        let translation = (!translation.is_empty()).then_some(translation);
+        let merged = self.add_rectangle_marker_to_image(image_id, left, top, right - left, bottom - top, translation, CoordinateMode::Clamp)?;
+        self.event_bus.publish(DomainEvent::MarkerAddedToImage(image_id, merged));
+        for marker in &markers {
+            self.remove_marker_with_undo(marker.id)?;
+        }
+        self.move_marker_order_with_undo(merged, first_index)?;
+        if let Some(guard) = guard {
+            guard.finish()?;
+        }
+        Ok(merged)
+    }
+    
     // === 清理操作 ===
     
     pub fn clear_all(&self) {
@@ -782,14 +1435,27 @@ impl MarkerService {
     }
 }
 
+/// 通知前端这些图片的标记序号已变化，需要重新加载
+pub fn emit_markers_renumbered(image_ids: &[ImageId]) {
+    let _ = EVENT_SYSTEM.emit_business_event(
+        "MarkersRenumbered".to_string(),
+        serde_json::json!({ "image_ids": image_ids.iter().map(|id| id.0).collect::<Vec<u32>>() }),
+    );
+}
+
 // 实现事件处理器
 impl EventHandler for MarkerService {
     fn handle(&self, event: &DomainEvent) {
         match event {
             // 清空所有数据时，清理所有标记
             DomainEvent::AllDataClearing => {
+                self.finalize_all_drags();
                 self.clear_all();
             },
+            // 项目关闭时结束其中未完成的拖拽手势
+            DomainEvent::ProjectDeleting(project_id) => {
+                self.finalize_drags_for_project(*project_id);
+            },
             // 清空图片标记的请求
             DomainEvent::ImageMarkersClearing(image_id) => {
                 self.clear_image_markers(image_id.0);
@@ -797,4 +1463,387 @@ impl EventHandler for MarkerService {
             _ => {}
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{CoreError, ReadingDirection};
+    use crate::service::get_service;
+
+    fn create_image() -> ImageId {
+        crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_out_of_range_point_rejected() {
+        let service = get_service();
+        let image_id = create_image();
+
+        let result = service.marker_service.add_point_marker_to_image(image_id, 250.0, -30.0, None, CoordinateMode::Reject);
+        assert!(matches!(result, Err(CoreError::ValidationFailed { .. })));
+        assert!(service.marker_service.add_point_marker(image_id.0, f64::NAN, 10.0, None).is_err());
+        assert!(service.marker_service.get_markers_for_image(image_id.0).is_empty());
+    }
+
+    #[test]
+    fn test_out_of_range_point_clamped() {
+        let service = get_service();
+        let image_id = create_image();
+
+        let id = service.marker_service.add_point_marker_to_image(image_id, 250.0, -30.0, None, CoordinateMode::Clamp).unwrap();
+        let marker = service.marker_service.get_marker_internal(id.0).unwrap();
+        assert_eq!(marker.geometry, MarkerGeometry::Point { x: 100.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_api_returns_validation_error_and_honours_mode() {
+        use crate::api::marker::{add_point_marker_to_image, update_point_marker_position};
+        let image_id = create_image();
+
+        // 校验信息原样返回给调用方，而不是Ok(None)
+        let error = add_point_marker_to_image(image_id.0, 250.0, 10.0, None, None).unwrap_err();
+        assert!(error.contains("Validation failed"), "{}", error);
+        let id = add_point_marker_to_image(image_id.0, 250.0, 10.0, None, Some(CoordinateMode::Clamp)).unwrap().unwrap();
+
+        assert!(update_point_marker_position(id, 50.0, -1.0, None).is_err());
+        assert!(update_point_marker_position(id, 50.0, -1.0, Some(CoordinateMode::Clamp)).unwrap());
+        assert_eq!(get_service().marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Point { x: 50.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_rectangle_update_validated() {
+        let service = get_service();
+        let image_id = create_image();
+        let id = service.marker_service.add_rectangle_marker(image_id.0, 10.0, 10.0, 20.0, 20.0, None).unwrap();
+
+        // 超出右边界
+        let error = service.marker_service.update_rectangle_marker_geometry(id, 90.0, 10.0, 20.0, 20.0, CoordinateMode::Reject).unwrap_err();
+        assert!(matches!(error, CoreError::ValidationFailed { .. }));
+        assert!(service.marker_service.update_rectangle_marker_full(id, 10.0, 10.0, -5.0, 20.0, None, CoordinateMode::Reject).is_err());
+        assert!(service.marker_service.update_rectangle_marker_geometry(id, 80.0, 10.0, 20.0, 20.0, CoordinateMode::Reject).unwrap());
+
+        let clamped = service.marker_service
+            .update_rectangle_marker_geometry_checked(MarkerId::from(id), 90.0, 95.0, 20.0, 20.0, CoordinateMode::Clamp)
+            .unwrap();
+        assert!(clamped);
+        let marker = service.marker_service.get_marker_internal(id).unwrap();
+        assert_eq!(marker.geometry, MarkerGeometry::Rectangle { x: 90.0, y: 95.0, width: 10.0, height: 5.0 });
+    }
+
+    fn create_project_image() -> (ProjectId, ImageId) {
+        let project_id = crate::storage::project::create_project_storage("drag".to_string()).unwrap();
+        let image_id = create_image();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        (project_id, image_id)
+    }
+
+    #[test]
+    fn test_drag_gesture_records_single_undo() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let id = service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, None).unwrap();
+
+        assert!(service.marker_service.begin_marker_drag(MarkerId::from(id)).unwrap());
+        for step in 1..=10 {
+            assert!(service.marker_service.update_point_marker_position(id, 10.0 + step as f64, 10.0, CoordinateMode::Reject).unwrap());
+        }
+        // 中间更新只写存储，不产生撤销记录
+        assert!(!service.undo_redo_service.can_undo(project_id).unwrap());
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Point { x: 20.0, y: 10.0 });
+
+        assert!(service.marker_service.end_marker_drag(MarkerId::from(id), MarkerGeometry::Point { x: 30.0, y: 40.0 }).unwrap());
+        assert!(!service.marker_service.is_dragging(MarkerId::from(id)));
+        assert!(service.undo_redo_service.can_undo(project_id).unwrap());
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Point { x: 10.0, y: 10.0 });
+        assert!(!service.undo_redo_service.can_undo(project_id).unwrap());
+    }
+
+    #[test]
+    fn test_drag_gesture_finalized_on_delete() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let id = service.marker_service.add_rectangle_marker(image_id.0, 10.0, 10.0, 20.0, 20.0, None).unwrap();
+
+        service.marker_service.begin_marker_drag(MarkerId::from(id)).unwrap();
+        assert!(service.marker_service.update_rectangle_marker_geometry(id, 30.0, 30.0, 20.0, 20.0, CoordinateMode::Reject).unwrap());
+        assert!(service.marker_service.remove_marker(id));
+        assert!(!service.marker_service.is_dragging(MarkerId::from(id)));
+
+        // 撤销删除后再撤销拖拽，回到拖拽前的位置
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Rectangle { x: 30.0, y: 30.0, width: 20.0, height: 20.0 });
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Rectangle { x: 10.0, y: 10.0, width: 20.0, height: 20.0 });
+    }
+
+    #[test]
+    fn test_polygon_marker_update_and_convert_undo() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        assert!(service.marker_service.add_polygon_marker(image_id.0, vec![(10.0, 10.0), (20.0, 20.0)], None).is_err());
+
+        let original = vec![(10.0, 10.0), (40.0, 20.0), (30.0, 50.0)];
+        let id = service.marker_service.add_polygon_marker(image_id.0, original.clone(), None).unwrap();
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        let moved = vec![(15.0, 10.0), (45.0, 20.0), (35.0, 50.0), (12.0, 40.0)];
+        assert!(service.marker_service.update_polygon_marker_geometry(id, moved.clone(), CoordinateMode::Reject).unwrap());
+        assert!(service.marker_service.update_polygon_marker_geometry(id, vec![(0.0, 0.0), (150.0, 0.0), (0.0, 10.0)], CoordinateMode::Reject).is_err());
+        assert!(service.marker_service.convert_polygon_to_rectangle(id));
+        assert_eq!(
+            service.marker_service.get_marker_internal(id).unwrap().geometry,
+            MarkerGeometry::Rectangle { x: 12.0, y: 10.0, width: 33.0, height: 40.0 }
+        );
+        // 矩形接口不能修改多边形，反之亦然
+        assert!(!service.marker_service.update_polygon_marker_geometry(id, original.clone(), CoordinateMode::Reject).unwrap());
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Polygon { points: moved });
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Polygon { points: original });
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert!(matches!(service.marker_service.get_marker_internal(id).unwrap().geometry, MarkerGeometry::Rectangle { .. }));
+    }
+
+    #[test]
+    fn test_size_hint_and_tag_validated_and_undoable() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let id = service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, None).unwrap();
+        let marker_id = MarkerId::from(id);
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        assert!(service.marker_service.update_marker_size_hint(marker_id, Some(1.5)).unwrap());
+        assert!(service.marker_service.update_marker_tag(marker_id, Some("  sfx ".to_string())).unwrap());
+        for bad in [0.1, 10.0, f32::NAN] {
+            assert!(matches!(service.marker_service.update_marker_size_hint(marker_id, Some(bad)), Err(CoreError::ValidationFailed { .. })));
+        }
+        assert!(service.marker_service.update_marker_tag(marker_id, Some("x".repeat(33))).is_err());
+        // 修改框内外/横竖排不影响字号和标签
+        assert!(service.marker_service.update_marker_style(id, true, true));
+        let style = service.marker_service.get_marker_internal(id).unwrap().style;
+        assert_eq!((style.size_hint, style.tag.as_deref()), (Some(1.5), Some("sfx")));
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        let style = service.marker_service.get_marker_internal(id).unwrap().style;
+        assert_eq!((style.size_hint, style.tag), (Some(1.5), None));
+        assert!(service.marker_service.update_marker_tag(marker_id, Some(String::new())).unwrap());
+    }
+
+    #[test]
+    fn test_move_marker_to_image_renumbers_and_undoes() {
+        let service = get_service();
+        let (project_id, source) = create_project_image();
+        let target = create_image();
+        crate::storage::project::add_image_to_project_storage(project_id, target).unwrap();
+        let a: Vec<u32> = (0..3).map(|i| service.marker_service.add_point_marker(source.0, 10.0 * (i + 1) as f64, 10.0, Some(format!("a{}", i + 1))).unwrap()).collect();
+        let b: Vec<u32> = (0..2).map(|i| service.marker_service.add_point_marker(target.0, 10.0 * (i + 1) as f64, 10.0, None).unwrap()).collect();
+        crate::storage::bunny_cache::update_original_text_storage(MarkerId::from(a[1]), "OCR".to_string(), "model".to_string()).unwrap();
+        service.undo_redo_service.clear_project_history(project_id.0);
+        let indices = |image_id: ImageId| -> Vec<(u32, u32)> {
+            let mut markers = crate::storage::marker::get_image_markers_storage(image_id).unwrap();
+            markers.sort_by_key(|m| m.image_index);
+            markers.into_iter().map(|m| (m.id.0, m.image_index)).collect()
+        };
+
+        let geometry = MarkerGeometry::Point { x: 50.0, y: 60.0 };
+        assert!(service.marker_service.move_marker_to_image(MarkerId::from(a[1]), target, geometry.clone(), Some(1)).unwrap());
+        assert_eq!(indices(source), [(a[0], 1), (a[2], 2)]);
+        assert_eq!(indices(target), [(a[1], 1), (b[0], 2), (b[1], 3)]);
+        let moved = service.marker_service.get_marker_internal(a[1]).unwrap();
+        assert_eq!((moved.geometry, moved.translation.as_str()), (geometry, "a2"));
+        assert!(crate::storage::image::get_image_storage(target).unwrap().unwrap().marker_ids.contains(&MarkerId::from(a[1])));
+        let cache = crate::storage::bunny_cache::get_bunny_cache_storage(MarkerId::from(a[1])).unwrap().unwrap();
+        assert_eq!(cache.original_text.as_deref(), Some("OCR"));
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(indices(source), [(a[0], 1), (a[1], 2), (a[2], 3)]);
+        assert_eq!(indices(target), [(b[0], 1), (b[1], 2)]);
+        assert!(!crate::storage::image::get_image_storage(target).unwrap().unwrap().marker_ids.contains(&MarkerId::from(a[1])));
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert_eq!(indices(target), [(a[1], 1), (b[0], 2), (b[1], 3)]);
+
+        // 不同项目的图片、同一张图片、越界坐标都被拒绝
+        let (_, other) = create_project_image();
+        let point = MarkerGeometry::Point { x: 1.0, y: 1.0 };
+        assert!(matches!(service.marker_service.move_marker_to_image(MarkerId::from(a[0]), other, point.clone(), None), Err(CoreError::ValidationFailed { .. })));
+        assert!(service.marker_service.move_marker_to_image(MarkerId::from(a[0]), source, point, None).is_err());
+        assert!(service.marker_service.move_marker_to_image(MarkerId::from(a[0]), target, MarkerGeometry::Point { x: 150.0, y: 1.0 }, None).is_err());
+    }
+
+    #[test]
+    fn test_replace_in_translations_single_undo() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let (_, other_image) = create_project_image();
+        crate::storage::project::add_image_to_project_storage(project_id, other_image).unwrap();
+        let a = service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, Some("Rin: hi Rina".to_string())).unwrap();
+        let b = service.marker_service.add_point_marker(other_image.0, 10.0, 10.0, Some("rin!".to_string())).unwrap();
+        let c = service.marker_service.add_point_marker(other_image.0, 20.0, 10.0, Some("nobody".to_string())).unwrap();
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        let report = service.marker_service.replace_in_translations(project_id, "rin", "Len", false, true, &CancellationToken::new()).unwrap();
+        assert_eq!(report.replacements, 2);
+        assert_eq!(report.changed_marker_ids, vec![MarkerId::from(a), MarkerId::from(b)]);
+        assert_eq!(service.marker_service.get_marker_internal(a).unwrap().translation, "Len: hi Rina");
+        assert_eq!(service.marker_service.get_marker_internal(c).unwrap().translation, "nobody");
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert!(!service.undo_redo_service.can_undo(project_id).unwrap());
+        assert_eq!(service.marker_service.get_marker_internal(a).unwrap().translation, "Rin: hi Rina");
+        assert_eq!(service.marker_service.get_marker_internal(b).unwrap().translation, "rin!");
+
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert_eq!(service.marker_service.get_marker_internal(b).unwrap().translation, "Len!");
+        assert!(service.marker_service.replace_in_translations(project_id, "", "x", true, false, &CancellationToken::new()).is_err());
+
+        // 已取消的令牌：不做任何替换，状态为Cancelled
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        let report = service.marker_service.replace_in_translations(project_id, "Len", "Rin", true, false, &cancelled).unwrap();
+        assert_eq!(report.status, OperationStatus::Cancelled);
+        assert!(report.changed_marker_ids.is_empty());
+        assert_eq!(service.marker_service.get_marker_internal(a).unwrap().translation, "Len: hi Rina");
+    }
+
+    #[test]
+    fn test_analyze_translations_uses_bunny_cache() {
+        use crate::service::marker::IssueCategory;
+        use crate::storage::bunny_cache::update_original_text_storage;
+
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let a = service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, Some("你好".to_string())).unwrap();
+        let b = service.marker_service.add_point_marker(image_id.0, 20.0, 10.0, Some("您好".to_string())).unwrap();
+        let c = service.marker_service.add_point_marker(image_id.0, 30.0, 10.0, None).unwrap();
+        for (id, text) in [(a, "こんにちは"), (b, "こんにちは"), (c, "はい")] {
+            update_original_text_storage(MarkerId::from(id), text.to_string(), "test".to_string()).unwrap();
+        }
+
+        let issues = service.marker_service.analyze_translations(project_id, &ConsistencyConfig::default()).unwrap();
+        let found: Vec<(IssueCategory, Vec<MarkerId>)> = issues.into_iter().map(|i| (i.category, i.marker_ids)).collect();
+        assert_eq!(found, vec![
+            (IssueCategory::EmptyTranslation, vec![MarkerId::from(c)]),
+            (IssueCategory::InconsistentTranslation, vec![MarkerId::from(a), MarkerId::from(b)]),
+        ]);
+    }
+
+    #[test]
+    fn test_compact_indices_dense_and_undoable() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let ids: Vec<u32> = (0..4)
+            .map(|i| service.marker_service.add_point_marker(image_id.0, 10.0 * i as f64, 10.0, None).unwrap())
+            .collect();
+        // 制造有空缺、从3开始的序号
+        {
+            let mut storage_guard = APP_STATE.markers.write().unwrap();
+            for (id, index) in ids.iter().zip([3, 7, 8, 20]) {
+                storage_guard.get_mut(&MarkerId::from(*id)).unwrap().image_index = index;
+            }
+        }
+        let indices = |service: &crate::service::ServiceCoordinator| -> Vec<u32> {
+            ids.iter().map(|id| service.marker_service.get_marker_internal(*id).unwrap().image_index).collect()
+        };
+
+        assert_eq!(service.marker_service.compact_indices(image_id).unwrap(), 4);
+        assert_eq!(indices(&service), vec![1, 2, 3, 4]);
+        // 已经是连续序号时不做改动
+        assert_eq!(service.marker_service.compact_project_indices(project_id).unwrap(), 0);
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(indices(&service), vec![3, 7, 8, 20]);
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert_eq!(indices(&service), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_merge_rectangles_is_one_undo_step() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        crate::storage::project::update_reading_direction_storage(project_id, ReadingDirection::RightToLeft).unwrap();
+        let point = service.marker_service.add_point_marker(image_id.0, 5.0, 5.0, None).unwrap();
+        let left = service.marker_service.add_rectangle_marker(image_id.0, 10.0, 10.0, 20.0, 10.0, Some("左".to_string())).unwrap();
+        let right = service.marker_service.add_rectangle_marker(image_id.0, 50.0, 15.0, 20.0, 30.0, Some("右".to_string())).unwrap();
+        let empty = service.marker_service.add_rectangle_marker(image_id.0, 40.0, 60.0, 10.0, 10.0, None).unwrap();
+        let ids = |ids: &[u32]| ids.iter().map(|id| MarkerId::from(*id)).collect::<Vec<_>>();
+
+        let mixed = service.marker_service.merge_rectangles(&ids(&[left, point]));
+        assert!(matches!(mixed, Err(CoreError::ValidationFailed { .. })));
+        assert!(service.marker_service.merge_rectangles(&ids(&[left, left])).is_err());
+
+        let merged = service.marker_service.merge_rectangles(&ids(&[left, right, empty])).unwrap();
+        let marker = service.marker_service.get_marker_internal(merged.0).unwrap();
+        assert_eq!(marker.geometry, MarkerGeometry::Rectangle { x: 10.0, y: 10.0, width: 60.0, height: 60.0 });
+        // 从右到左阅读，空译文跳过
+        assert_eq!(marker.translation, "右\n左");
+        assert_eq!(marker.image_index, 2);
+        assert_eq!(crate::storage::image::get_image_marker_ids_storage(image_id).unwrap(), vec![MarkerId::from(point), merged]);
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        let indices: Vec<u32> = [point, left, right, empty].iter()
+            .map(|id| service.marker_service.get_marker_internal(*id).unwrap().image_index)
+            .collect();
+        assert_eq!(indices, vec![1, 2, 3, 4]);
+        assert!(service.marker_service.get_marker_internal(merged.0).is_none());
+    }
+
+    #[test]
+    fn test_renumber_follows_reading_direction() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        crate::storage::project::update_reading_direction_storage(project_id, ReadingDirection::LeftToRight).unwrap();
+        // 按从左到右的顺序添加同一行的三个标记
+        let ids: Vec<u32> = [10.0, 50.0, 90.0].iter()
+            .map(|x| service.marker_service.add_point_marker(image_id.0, *x, 20.0, None).unwrap())
+            .collect();
+        let indices = |service: &crate::service::ServiceCoordinator| -> Vec<u32> {
+            ids.iter().map(|id| service.marker_service.get_marker_internal(*id).unwrap().image_index).collect()
+        };
+
+        // 切换方向只改变派生的顺序，不动标记序号
+        assert!(crate::api::project::set_reading_direction(project_id.0, ReadingDirection::RightToLeft));
+        assert_eq!(indices(&service), vec![1, 2, 3]);
+        let first = service.marker_service.select_next_marker(image_id, None, false).unwrap();
+        assert_eq!(first, Some(MarkerId::from(ids[2])));
+        let next = service.marker_service.select_next_marker(image_id, first, false).unwrap();
+        assert_eq!(next, Some(MarkerId::from(ids[1])));
+
+        assert_eq!(service.marker_service.renumber_by_reading_order(image_id).unwrap(), 2);
+        assert_eq!(indices(&service), vec![3, 2, 1]);
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(indices(&service), vec![1, 2, 3]);
+
+        // 撤销方向设置
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        let direction = service.project_service.get_reading_direction(project_id).unwrap();
+        assert_eq!(direction, Some(ReadingDirection::LeftToRight));
+    }
+
+    #[test]
+    fn test_marker_note_is_undoable() {
+        let service = get_service();
+        let (project_id, image_id) = create_project_image();
+        let id = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, Some("译文".to_string())).unwrap());
+        let note = |service: &crate::service::ServiceCoordinator| service.marker_service.get_marker_by_id(id).unwrap().unwrap().note;
+
+        assert!(service.marker_service.update_marker_note(id, Some("  双关，直译为X ".to_string())).unwrap());
+        assert_eq!(note(&service).as_deref(), Some("双关，直译为X"));
+        assert_eq!(service.marker_service.get_marker_by_id(id).unwrap().unwrap().translation, "译文");
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(note(&service), None);
+        assert!(service.undo_redo_service.redo(project_id.0).success);
+        assert_eq!(note(&service).as_deref(), Some("双关，直译为X"));
+
+        // 空字符串清除备注
+        assert!(service.marker_service.update_marker_note(id, Some(" ".to_string())).unwrap());
+        assert_eq!(note(&service), None);
+    }
+}
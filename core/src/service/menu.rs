@@ -0,0 +1,186 @@
+// 菜单定义 - macOS原生菜单和Windows/Linux虚拟菜单栏共用同一份菜单结构，快捷键来自keymap
+// 桌面端启动时按此构建原生菜单，前端按此显示虚拟菜单和注册快捷键
+use serde::{Deserialize, Serialize};
+use crate::common::CoreResult;
+use super::keymap::{self, DEFAULT_KEYMAP};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuItemKind {
+    /// 普通菜单项，点击后发出菜单事件
+    Action,
+    /// 带勾选状态的菜单项（面板开关）
+    Check,
+    /// 子菜单，子项在items中
+    Submenu,
+    /// 系统提供的编辑操作（剪切、复制、粘贴、全选）
+    Predefined,
+    Separator,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuItemDefinition {
+    pub id: String,
+    /// 显示文本的key，label为默认文本
+    pub label_key: String,
+    pub label: String,
+    pub kind: MenuItemKind,
+    /// 只有keymap中的操作有快捷键，None表示不可配置或未绑定
+    pub default_accelerator: Option<String>,
+    pub accelerator: Option<String>,
+    pub enabled: bool,
+    #[serde(default)]
+    pub items: Vec<MenuItemDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuSection {
+    pub id: String,
+    pub label_key: String,
+    pub label: String,
+    pub items: Vec<MenuItemDefinition>,
+}
+
+struct ItemSpec {
+    id: &'static str,
+    label: &'static str,
+    kind: MenuItemKind,
+    items: &'static [ItemSpec],
+}
+
+const fn item(id: &'static str, label: &'static str) -> ItemSpec {
+    ItemSpec { id, label, kind: MenuItemKind::Action, items: &[] }
+}
+
+const fn check(id: &'static str, label: &'static str) -> ItemSpec {
+    ItemSpec { id, label, kind: MenuItemKind::Check, items: &[] }
+}
+
+const fn predefined(id: &'static str, label: &'static str) -> ItemSpec {
+    ItemSpec { id, label, kind: MenuItemKind::Predefined, items: &[] }
+}
+
+const fn submenu(id: &'static str, label: &'static str, items: &'static [ItemSpec]) -> ItemSpec {
+    ItemSpec { id, label, kind: MenuItemKind::Submenu, items }
+}
+
+const SEPARATOR: ItemSpec = ItemSpec { id: "separator", label: "", kind: MenuItemKind::Separator, items: &[] };
+
+/// 最近打开列表为空时的占位项，不可点击
+pub const RECENT_EMPTY_ID: &str = "recent-empty";
+
+// (分区id, 显示名称, 菜单项)，macOS上第一个分区显示为应用菜单
+const MENU: &[(&str, &str, &[ItemSpec])] = &[
+    ("more", "更多", &[
+        item("snapshots", "快照"),
+        SEPARATOR,
+        item("version-info", "关于"),
+        item("software-license", "软件许可"),
+        SEPARATOR,
+        item("quit", "退出Bubblefish"),
+    ]),
+    ("file", "文件", &[
+        item("new-project", "新建项目"),
+        item("open-project", "打开项目"),
+        // 最近打开的项目由前端动态插入到占位项的位置
+        submenu("recent-projects", "最近打开", &[
+            item(RECENT_EMPTY_ID, "暂无最近打开的项目"),
+            SEPARATOR,
+            item("clear-recent", "清空最近打开"),
+        ]),
+        SEPARATOR,
+        item("save", "保存"),
+        item("save-as", "另存为..."),
+        item("save-as-copy", "另存为副本..."),
+        submenu("export-submenu", "导出", &[
+            item("export-labelplus", "Labelplus文件"),
+            item("export-annotated", "嵌字参考图"),
+            item("export-typesetting-csv", "嵌字数据 (CSV)"),
+            item("export-typesetting-json", "嵌字数据 (JSON)"),
+        ]),
+        item("import-typesetting", "导入嵌字数据..."),
+    ]),
+    ("edit", "编辑", &[
+        item("undo", "撤销"),
+        item("redo", "重做"),
+        SEPARATOR,
+        predefined("cut", "剪切"),
+        predefined("copy", "复制"),
+        predefined("paste", "粘贴"),
+        predefined("select-all", "全选"),
+        SEPARATOR,
+        item("prev-marker", "上一个标记"),
+        item("next-marker", "下一个标记"),
+    ]),
+    ("view", "视图", &[
+        item("prev-image", "上一张图片"),
+        item("next-image", "下一张图片"),
+        SEPARATOR,
+        item("minimize", "最小化"),
+        item("maximize", "最大化"),
+        SEPARATOR,
+        check("translation", "翻译"),
+        check("thumbnail", "缩略图"),
+        check("dictionary", "词库"),
+        check("project-config", "项目配置"),
+        SEPARATOR,
+        item("debug", "调试窗口"),
+    ]),
+];
+
+fn build_item(spec: &ItemSpec) -> MenuItemDefinition {
+    let default_accelerator = DEFAULT_KEYMAP.iter()
+        .find(|(action, _, _)| *action == spec.id)
+        .and_then(|(_, _, default)| default.map(|a| a.to_string()));
+    let accelerator = match spec.kind {
+        MenuItemKind::Action => keymap::accelerator_for(spec.id),
+        _ => None,
+    };
+    MenuItemDefinition {
+        id: spec.id.to_string(),
+        label_key: format!("menu.{}", spec.id),
+        label: spec.label.to_string(),
+        kind: spec.kind,
+        default_accelerator,
+        accelerator,
+        enabled: spec.id != RECENT_EMPTY_ID,
+        items: spec.items.iter().map(build_item).collect(),
+    }
+}
+
+/// 完整的菜单结构，快捷键为当前生效的配置
+pub fn get_menu_definition() -> CoreResult<Vec<MenuSection>> {
+    Ok(MENU.iter()
+        .map(|(id, label, items)| MenuSection {
+            id: id.to_string(),
+            label_key: format!("menu.{}", id),
+            label: label.to_string(),
+            items: items.iter().map(build_item).collect(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_ids(items: &[MenuItemDefinition], out: &mut Vec<String>) {
+        for item in items {
+            if item.kind == MenuItemKind::Action {
+                out.push(item.id.clone());
+            }
+            action_ids(&item.items, out);
+        }
+    }
+
+    #[test]
+    fn test_every_keymap_action_is_in_menu() {
+        let mut ids = Vec::new();
+        for section in get_menu_definition().unwrap() {
+            action_ids(&section.items, &mut ids);
+        }
+        for (action, _, _) in DEFAULT_KEYMAP {
+            assert!(ids.iter().any(|id| id == action), "'{}' is not in the menu", action);
+        }
+    }
+}
@@ -9,6 +9,9 @@ pub mod stats;
 pub mod undo_redo;
 pub mod io;
 pub mod bunny;
+pub mod keymap;
+pub mod menu;
+pub mod worker_pool;
 
 // 导出主要接口
 pub use coordinator::ServiceCoordinator;
@@ -5,16 +5,19 @@ use crate::storage::traits::Storage;
 use crate::service::events::{DomainEvent, EventBus};
 use super::core::{OpeningProject, OPENING_PROJECTS};
 use crate::common::dto::opening_project::OpeningProjectDTO;
+use crate::common::events::schema::{BusinessEvent, NotificationEvent, OpeningProjectCancelledEvent, OpeningProjectProgressEvent};
 use std::sync::Arc;
 use std::path::PathBuf;
 
 /// 创建空的临时项目
 pub fn create_empty_opening_project(
     project_name: String,
+    template: Option<String>,
     event_bus: Arc<EventBus>
 ) -> CoreResult<ProjectId> {
     log_function_call("create_empty_opening_project", Some(serde_json::json!({
-        "project_name": &project_name
+        "project_name": &project_name,
+        "template": &template
     })));
     
     // 验证项目名称
@@ -26,9 +29,16 @@ pub fn create_empty_opening_project(
     }
     
     // 创建空的OpeningProject
-    let opening_project = OpeningProject::empty(project_name.clone());
+    let mut opening_project = OpeningProject::empty(project_name.clone());
     let project_id = opening_project.project.id;
     
+    // 套用模板中的项目设置
+    if let Some(template_name) = template {
+        let template = crate::service::project::template::get_template(&template_name)?
+            .ok_or_else(|| CoreError::NotFound(format!("Template '{}' not found", template_name)))?;
+        template.apply_to(&mut opening_project.project);
+    }
+    
     // 存储到全局存储中
     OPENING_PROJECTS.insert(project_id, opening_project)?;
     
@@ -45,6 +55,35 @@ pub fn create_empty_opening_project(
     Ok(project_id)
 }
 
+/// 创建需要指定图片列表的临时项目（压缩包导入时图片随后直接加入）
+pub fn create_opening_project_with_images(
+    image_names: Vec<String>,
+    project_name: String,
+    event_bus: Arc<EventBus>
+) -> CoreResult<ProjectId> {
+    log_function_call("create_opening_project_with_images", Some(serde_json::json!({
+        "project_name": &project_name,
+        "image_count": image_names.len()
+    })));
+    
+    // 验证项目名称
+    if project_name.trim().is_empty() {
+        return Err(CoreError::ValidationFailed {
+            field: "project_name".to_string(),
+            reason: "项目名称不能为空".to_string(),
+        });
+    }
+    
+    let opening_project = OpeningProject::new(project_name.clone(), image_names);
+    let project_id = opening_project.project.id;
+    
+    OPENING_PROJECTS.insert(project_id, opening_project)?;
+    
+    event_bus.publish(DomainEvent::OpeningProjectCreated(project_id, project_name));
+    
+    Ok(project_id)
+}
+
 /// 通过Labelplus内容创建临时项目
 pub fn create_opening_project_with_labelplus(
     labelplus_content: String,
@@ -88,7 +127,7 @@ pub fn create_opening_project_with_labelplus(
 }
 
 /// 通过BF文件创建临时项目（带文件路径）
-#[cfg(feature = "tauri")]
+#[cfg(not(feature = "wasm"))]
 pub fn create_opening_project_with_bf_and_path(
     bf_data: Vec<u8>,
     project_name: String,
@@ -200,6 +239,7 @@ pub fn get_opening_project_info(project_id: ProjectId) -> CoreResult<Option<Open
             pending_images: opening_project.pending_images.clone(),
             uploaded_images,
             is_complete: opening_project.is_complete(),
+            warnings: opening_project.warnings.clone(),
         };
         
         Ok(Some(info))
@@ -209,23 +249,57 @@ pub fn get_opening_project_info(project_id: ProjectId) -> CoreResult<Option<Open
 }
 
 /// 添加图片到临时项目
+/// 返回图片是否加入了临时项目；临时项目已被取消或不存在时，删除刚加入存储的图片并返回false
 pub fn add_image_to_opening_project(
     project_id: ProjectId,
     image_id: ImageId,
     name: Option<String>,
     path: Option<PathBuf>
-) -> CoreResult<()> {
-    OPENING_PROJECTS.get_mut(project_id, |opening_project| {
-        let img_name = name.as_ref().map(|n| n.clone())
+) -> CoreResult<bool> {
+    let Some(reencode) = OPENING_PROJECTS.get_mut(project_id, |opening_project| opening_project.project.grayscale_reencode)? else {
+        let _ = crate::storage::image::delete_image_storage(image_id);
+        return Ok(false);
+    };
+    // 灰度检测失败（占位图片、无法解码）不影响打开
+    let savings = crate::service::image::grayscale::optimize_grayscale(image_id, reencode).unwrap_or_else(|e| {
+        Logger::debug(&format!("Skipped grayscale detection for image {}: {}", image_id.0, e));
+        0
+    });
+
+    let progress = OPENING_PROJECTS.get_mut(project_id, |opening_project| {
+        let mut img_name = name.as_ref().map(|n| n.clone())
             .or_else(|| path.as_ref().and_then(|p| p.file_name()).and_then(|n| n.to_str()).map(|s| s.to_string()))
             .unwrap_or_else(|| format!("image_{}", image_id.0));
         
+        // 新建项目中来自不同文件夹的同名图片加序号后缀，否则后上传的会顶替先上传的
+        if opening_project.required_images.is_empty() && opening_project.uploaded_images.contains_key(&img_name) {
+            let taken: std::collections::HashSet<String> = opening_project.uploaded_images.keys().cloned().collect();
+            let unique = crate::storage::image::unique_image_name(&img_name, &taken);
+            if let Ok(Some(_)) = crate::storage::image::rename_duplicate_image_storage(image_id, unique.clone()) {
+                let mut issue = crate::service::project::integrity::duplicate_name_issue(image_id, &img_name, &unique);
+                issue.fixed = true;
+                opening_project.warnings.push(issue);
+                img_name = unique;
+            }
+        }
+        
         if opening_project.is_image_required(&img_name) {
             opening_project.mark_image_uploaded(img_name, image_id);
         }
+        opening_project.grayscale_savings_bytes += savings;
+        let total = opening_project.required_images.len();
+        (total - opening_project.pending_images.len().min(total), total, opening_project.grayscale_savings_bytes)
     })?;
     
-    Ok(())
+    let Some((uploaded, total, savings_bytes)) = progress else {
+        let _ = crate::storage::image::delete_image_storage(image_id);
+        return Ok(false);
+    };
+    if total > 0 {
+        let _ = OpeningProjectProgressEvent::new(project_id, uploaded, total, savings_bytes).emit();
+    }
+    
+    Ok(true)
 }
 
 /// 清理临时项目的图片
@@ -301,19 +375,18 @@ pub fn finalize_opening_project(
         "project_id": project_id
     })));
     
-    // 从临时存储中取出项目
+    // 先检查是否所有需要的图片都已上传，再从临时存储中取出项目
+    // 取出后取消请求找不到项目，finalize已经开始时以finalize为准
+    match OPENING_PROJECTS.get_mut(project_id, |opening_project| opening_project.is_complete())? {
+        None => return Ok(false),
+        Some(false) => return Err(CoreError::ValidationFailed {
+            field: "images".to_string(),
+            reason: "还有图片未上传完成".to_string(),
+        }),
+        Some(true) => {}
+    }
+    
     if let Some(mut opening_project) = OPENING_PROJECTS.remove(project_id)? {
-        // 检查是否所有需要的图片都已上传
-        if !opening_project.is_complete() {
-            // 如果没有完成，放回临时存储
-            OPENING_PROJECTS.insert(project_id, opening_project)?;
-            
-            return Err(CoreError::ValidationFailed {
-                field: "images".to_string(),
-                reason: "还有图片未上传完成".to_string(),
-            });
-        }
-        
         // 准备项目数据
         let project = opening_project.prepare_finalize();
         let image_count = project.image_ids.len();
@@ -327,7 +400,7 @@ pub fn finalize_opening_project(
         // 直接导入数据（因为项目已经从OPENING_PROJECTS移除，事件处理器无法访问）
         // 如果有labelplus数据，导入标记
         if let Some(labelplus_data) = opening_project.labelplus_data {
-            if let Err(e) = crate::service::io::labelplus::import_labelplus_data_direct(project_id, labelplus_data) {
+            if let Err(e) = crate::service::io::labelplus::import_labelplus_data_direct(project_id, labelplus_data, opening_project.labelplus_origin) {
                 Logger::error_with_data(
                     "导入标记数据失败",
                     serde_json::json!({
@@ -365,6 +438,33 @@ pub fn finalize_opening_project(
             }
         }
         
+        // 项目中可能已有同名图片，打开时加序号后缀，之后按名称导出/导入才不会混淆
+        // 放在导入标记之后，导入时仍按文件中的名称匹配图片
+        match crate::service::project::integrity::dedupe_project_image_names(project_id) {
+            Ok(renamed) if !renamed.is_empty() => {
+                let messages: Vec<String> = renamed.iter().map(|issue| issue.message.clone()).collect();
+                Logger::warn_with_data(
+                    "打开项目时为重名图片加了序号后缀",
+                    serde_json::json!({
+                        "project_id": project_id,
+                        "issues": messages
+                    })
+                );
+                let _ = NotificationEvent::simple(
+                    "重名图片已改名", messages.join("\n"), "warning", "project",
+                    Some(serde_json::to_value(&renamed).unwrap_or_default()),
+                ).emit();
+            }
+            Ok(_) => {}
+            Err(e) => Logger::error_with_data(
+                "检查重名图片失败",
+                serde_json::json!({
+                    "project_id": project_id,
+                    "error": e.to_string()
+                })
+            ),
+        }
+        
         // 发布事件
         event_bus.publish(DomainEvent::OpeningProjectFinalized(project_id));
         
@@ -383,6 +483,46 @@ pub fn finalize_opening_project(
     }
 }
 
+/// 取消正在打开的项目：停止逐张处理图片，删除已加入的图片并发送opening_project:cancelled
+/// 与finalize竞争时谁先从临时存储中取出项目谁生效，finalize已经开始时返回false
+pub fn cancel_opening_project(
+    project_id: ProjectId,
+    event_bus: Arc<EventBus>
+) -> CoreResult<bool> {
+    log_function_call("cancel_opening_project", Some(serde_json::json!({
+        "project_id": project_id
+    })));
+    
+    let Some(opening_project) = OPENING_PROJECTS.remove(project_id)? else {
+        return Ok(false);
+    };
+    opening_project.cancellation.cancel();
+    
+    // 已加入项目的图片和只记录在uploaded_images中的图片都要删除
+    let mut image_ids = opening_project.project.image_ids.clone();
+    for image_id in opening_project.uploaded_images.values() {
+        if !image_ids.contains(image_id) {
+            image_ids.push(*image_id);
+        }
+    }
+    for image_id in &image_ids {
+        let _ = crate::storage::image::delete_image_storage(*image_id);
+    }
+    
+    event_bus.publish(DomainEvent::OpeningProjectDeleted(project_id));
+    let _ = OpeningProjectCancelledEvent::new(project_id, image_ids.len()).emit();
+    
+    Logger::info_with_data(
+        "已取消打开项目",
+        serde_json::json!({
+            "project_id": project_id,
+            "removed_images": image_ids.len()
+        })
+    );
+    
+    Ok(true)
+}
+
 /// 删除临时项目
 pub fn delete_opening_project(
     project_id: ProjectId,
@@ -413,4 +553,81 @@ pub fn delete_opening_project(
     } else {
         Ok(false)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::time::Duration;
+    use crate::storage::image::{add_image_from_path_storage, get_image_storage};
+
+    fn upload(project_id: ProjectId, name: &str) -> (ImageId, bool) {
+        let image_id = add_image_from_path_storage(PathBuf::from(name), Some(name.to_string())).unwrap();
+        (image_id, add_image_to_opening_project(project_id, image_id, Some(name.to_string()), None).unwrap())
+    }
+
+    fn image_exists(image_id: ImageId) -> bool {
+        get_image_storage(image_id).unwrap().is_some()
+    }
+
+    #[test]
+    fn test_cancel_while_uploading_removes_all_images() {
+        let event_bus = Arc::new(EventBus::new());
+        let names: Vec<String> = (0..20).map(|i| format!("p{:02}.png", i)).collect();
+        let project_id = create_opening_project_with_images(names.clone(), "cancel".to_string(), event_bus.clone()).unwrap();
+
+        // 逐张上传之间有延迟，中途取消
+        let uploader = std::thread::spawn(move || {
+            let mut uploaded = Vec::new();
+            for name in &names {
+                let (image_id, attached) = upload(project_id, name);
+                uploaded.push(image_id);
+                if !attached {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            uploaded
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cancel_opening_project(project_id, event_bus.clone()).unwrap());
+        let uploaded = uploader.join().unwrap();
+
+        assert!(uploaded.len() < 20);
+        assert!(uploaded.iter().all(|&image_id| !image_exists(image_id)));
+        assert!(!OPENING_PROJECTS.exists(project_id));
+        assert!(!finalize_opening_project(project_id, event_bus.clone()).unwrap());
+        assert!(!cancel_opening_project(project_id, event_bus).unwrap());
+    }
+
+    #[test]
+    fn test_cancel_racing_finalize_has_one_winner() {
+        let event_bus = Arc::new(EventBus::new());
+        for round in 0..20 {
+            let project_id = create_opening_project_with_images(vec!["a.png".to_string()], "race".to_string(), event_bus.clone()).unwrap();
+            let (image_id, attached) = upload(project_id, "a.png");
+            assert!(attached);
+
+            let barrier = Arc::new(Barrier::new(2));
+            let finalizer = {
+                let (barrier, event_bus) = (barrier.clone(), event_bus.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    finalize_opening_project(project_id, event_bus).unwrap()
+                })
+            };
+            barrier.wait();
+            // 交替让取消稍晚一点到达
+            if round % 2 == 1 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            let cancelled = cancel_opening_project(project_id, event_bus.clone()).unwrap();
+            let finalized = finalizer.join().unwrap();
+
+            assert_ne!(cancelled, finalized);
+            let project = crate::storage::project::get_project_storage(project_id).unwrap();
+            assert_eq!(project.is_some(), finalized);
+            assert_eq!(image_exists(image_id), finalized);
+        }
+    }
+}
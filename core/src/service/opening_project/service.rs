@@ -2,7 +2,11 @@
 use std::sync::Arc;
 use std::path::PathBuf;
 use crate::service::events::{EventBus};
+use crate::common::{CancellationToken, OperationStatus};
 use crate::common::dto::opening_project::OpeningProjectDTO;
+use crate::service::io::archive::{self, ArchiveImportReport, ArchiveReader};
+use crate::service::io::labelplus::{LabelplusCoordinateOrigin, LabelplusOriginPreview, preview_labelplus_origin};
+use crate::storage::state::APP_STATE;
 use super::handlers;
 
 pub struct OpeningProjectService {
@@ -16,14 +20,14 @@ impl OpeningProjectService {
     
     // === 创建临时项目 ===
     
-    pub fn create_empty_opening_project(&self, project_name: String) -> Result<u32, String> {
-        match handlers::create_empty_opening_project(project_name, self.event_bus.clone()) {
+    pub fn create_empty_opening_project(&self, project_name: String, template: Option<String>) -> Result<u32, String> {
+        match handlers::create_empty_opening_project(project_name, template, self.event_bus.clone()) {
             Ok(project_id) => Ok(project_id.0),
             Err(e) => Err(e.to_string()),
         }
     }
     
-    #[cfg(feature = "tauri")]
+    #[cfg(not(feature = "wasm"))]
     pub fn create_opening_project_from_path(&self, path: PathBuf, project_name: String) -> Result<u32, String> {
         // 根据文件扩展名判断文件类型
         let extension = path.extension()
@@ -43,6 +47,10 @@ impl OpeningProjectService {
                     .map_err(|e| format!("Failed to read LabelPlus file: {}", e))?;
                 self.create_opening_project_from_labelplus(labelplus_content, project_name)
             },
+            ext if archive::is_archive_extension(ext) => {
+                self.create_opening_project_from_archive_path(path, project_name, &CancellationToken::new())
+                    .map(|report| report.project_id)
+            },
             _ => Err(format!("Unsupported file type: .{}", extension))
         }
     }
@@ -57,10 +65,87 @@ impl OpeningProjectService {
                     .map_err(|e| format!("Invalid UTF-8: {}", e))?;
                 self.create_opening_project_from_labelplus(labelplus_content, project_name)
             },
+            ext if archive::is_archive_extension(ext) => {
+                let archive_name = format!("{}.{}", project_name, ext);
+                self.create_opening_project_from_archive(data, archive_name, project_name, &CancellationToken::new())
+                    .map(|report| report.project_id)
+            },
             _ => Err(format!("Unsupported file type: .{}", extension))
         }
     }
     
+    /// 从zip/cbz压缩包创建临时项目，图片按自然顺序直接加入项目
+    /// 压缩包无法打开时返回序列化的ArchiveError，此时不会创建项目
+    /// 每张图片之前检查取消令牌，被取消时返回已导入部分的报告
+    pub fn create_opening_project_from_archive(&self, data: Vec<u8>, archive_name: String, project_name: String, token: &CancellationToken) -> Result<ArchiveImportReport, String> {
+        let mut reader = ArchiveReader::open(data, &archive_name)
+            .map_err(|e| e.to_json_string())?;
+        
+        let image_names: Vec<String> = reader.images().iter().map(|entry| entry.name.clone()).collect();
+        let project_id = handlers::create_opening_project_with_images(image_names, project_name, self.event_bus.clone())
+            .map_err(|e| e.to_string())?;
+        
+        let mut report = ArchiveImportReport {
+            project_id: project_id.0,
+            skipped_entries: reader.skipped_entries().to_vec(),
+            ..Default::default()
+        };
+        
+        // 除了operation_id的令牌，也可以通过cancel_opening_project取消（此时已导入的图片会被删除）
+        let opening_token = super::core::OPENING_PROJECTS.get(project_id)
+            .ok()
+            .flatten()
+            .map(|opening_project| opening_project.cancellation)
+            .unwrap_or_default();
+        
+        let services = crate::service::get_service();
+        for entry in reader.images().to_vec() {
+            if token.is_cancelled() || opening_token.is_cancelled() {
+                report.status = OperationStatus::Cancelled;
+                break;
+            }
+            let data = match reader.read_image(&entry) {
+                Ok(data) => data,
+                Err(e) => {
+                    report.failed_entries.push((entry.path.clone(), e.message));
+                    continue;
+                }
+            };
+            match services.image_service.add_image_from_binary(entry.format, data, Some(entry.name.clone())) {
+                Some(image_id) => {
+                    if !self.add_image_to_opening_project(project_id.0, image_id, Some(entry.name.clone()), None) {
+                        report.status = OperationStatus::Cancelled;
+                        break;
+                    }
+                    self.event_bus.publish(crate::service::events::DomainEvent::ImageAddedToProject(project_id, image_id.into()));
+                    report.added_images.push(entry.name);
+                }
+                None => report.failed_entries.push((entry.path, "Failed to add image".to_string())),
+            }
+        }
+        
+        crate::common::Logger::info_with_data("从压缩包创建临时项目", serde_json::json!({
+            "project_id": report.project_id,
+            "archive": archive_name,
+            "added": report.added_images.len(),
+            "skipped": report.skipped_entries.len(),
+            "failed": report.failed_entries.len(),
+            "status": report.status
+        }));
+        
+        Ok(report)
+    }
+    
+    #[cfg(not(feature = "wasm"))]
+    pub fn create_opening_project_from_archive_path(&self, path: PathBuf, project_name: String, token: &CancellationToken) -> Result<ArchiveImportReport, String> {
+        let archive_name = path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+        let data = std::fs::read(&path)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        self.create_opening_project_from_archive(data, archive_name, project_name, token)
+    }
+    
     fn create_opening_project_from_bf(&self, data: Vec<u8>, project_name: String) -> Result<u32, String> {
         match handlers::create_opening_project_with_bf(data, project_name, self.event_bus.clone()) {
             Ok(project_id) => Ok(project_id.0),
@@ -68,7 +153,7 @@ impl OpeningProjectService {
         }
     }
     
-    #[cfg(feature = "tauri")]
+    #[cfg(not(feature = "wasm"))]
     fn create_opening_project_from_bf_with_path(&self, data: Vec<u8>, project_name: String, file_path: Option<String>) -> Result<u32, String> {
         match handlers::create_opening_project_with_bf_and_path(data, project_name, file_path, self.event_bus.clone()) {
             Ok(project_id) => Ok(project_id.0),
@@ -96,13 +181,14 @@ impl OpeningProjectService {
         super::core::OPENING_PROJECTS.exists(project_id.into())
     }
     
-    pub fn add_image_to_opening_project(&self, project_id: u32, image_id: u32, name: Option<String>, path: Option<PathBuf>) {
-        let _ = handlers::add_image_to_opening_project(
+    /// 临时项目已被取消时图片会被删除，返回false
+    pub fn add_image_to_opening_project(&self, project_id: u32, image_id: u32, name: Option<String>, path: Option<PathBuf>) -> bool {
+        handlers::add_image_to_opening_project(
             project_id.into(),
             image_id.into(),
             name,
             path
-        );
+        ).unwrap_or(false)
     }
     
     pub fn flush_opening_project_images(&self, project_id: u32) -> bool {
@@ -112,6 +198,35 @@ impl OpeningProjectService {
         }
     }
     
+    pub fn set_preserve_order(&self, project_id: u32, preserve_order: bool) -> bool {
+        matches!(
+            super::core::OPENING_PROJECTS.get_mut(project_id.into(), |opening_project| {
+                opening_project.preserve_order = preserve_order;
+            }),
+            Ok(Some(()))
+        )
+    }
+    
+    pub fn set_labelplus_origin(&self, project_id: u32, origin: LabelplusCoordinateOrigin) -> bool {
+        matches!(
+            super::core::OPENING_PROJECTS.get_mut(project_id.into(), |opening_project| {
+                opening_project.labelplus_origin = origin;
+            }),
+            Ok(Some(()))
+        )
+    }
+    
+    // 已加入的图片按其尺寸换算像素坐标
+    pub fn preview_labelplus_origin(&self, project_id: u32, origin: LabelplusCoordinateOrigin) -> Option<LabelplusOriginPreview> {
+        let opening_project = super::core::OPENING_PROJECTS.get(project_id.into()).ok().flatten()?;
+        let labelplus_data = opening_project.labelplus_data.as_ref()?;
+        preview_labelplus_origin(labelplus_data, origin, |image_name| {
+            opening_project.uploaded_images.get(image_name)
+                .and_then(|image_id| APP_STATE.get_image(*image_id).ok().flatten())
+                .and_then(|image| image.metadata.source_dimensions())
+        })
+    }
+    
     pub fn finalize_opening_project(&self, project_id: u32) -> bool {
         match handlers::finalize_opening_project(project_id.into(), self.event_bus.clone()) {
             Ok(result) => result,
@@ -119,6 +234,10 @@ impl OpeningProjectService {
         }
     }
     
+    pub fn cancel_opening_project(&self, project_id: u32) -> bool {
+        handlers::cancel_opening_project(project_id.into(), self.event_bus.clone()).unwrap_or(false)
+    }
+    
     pub fn delete_opening_project(&self, project_id: u32) -> bool {
         match handlers::delete_opening_project(project_id.into(), self.event_bus.clone()) {
             Ok(result) => result,
@@ -0,0 +1,231 @@
+// 复制项目 - 生成与原项目互不影响的独立副本（设置、图片、标记、翻译缓存、译文历史）
+// 图片的二进制数据通过Arc共享，不复制字节；SharedBuffer和文件图片一样只复制句柄，buffer归前端所有，核心不释放
+// 内存统计按图片计，副本与原图各算一份，超出上限时整个复制失败；副本没有文件路径，撤销记录为空
+use std::collections::HashMap;
+use crate::common::{CoreError, CoreResult, ImageId, MarkerId, ProjectId, Logger};
+use crate::common::{IMAGE_ID_GENERATOR, MARKER_ID_GENERATOR, PROJECT_ID_GENERATOR};
+use crate::common::events::schema::{BusinessEvent, ProjectCreatedEvent};
+use crate::service::events::{DomainEvent, EventBus};
+use crate::storage::image::Image;
+use crate::storage::state::{ImageStorage, APP_STATE};
+use crate::storage::traits::Storage;
+
+/// 复制项目，返回新项目的ID
+pub fn duplicate_project(event_bus: &EventBus, project_id: ProjectId, new_name: String) -> CoreResult<ProjectId> {
+    if new_name.trim().is_empty() {
+        return Err(CoreError::ValidationFailed {
+            field: "new_name".to_string(),
+            reason: "项目名称不能为空".to_string(),
+        });
+    }
+    let source = crate::storage::project::get_project_storage(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+
+    // 先在读锁下准备好副本的图片和标记，再分别写入
+    let mut image_map: HashMap<ImageId, ImageId> = HashMap::new();
+    let mut marker_map: HashMap<MarkerId, MarkerId> = HashMap::new();
+    let mut images = Vec::new();
+    let mut markers = Vec::new();
+    {
+        let image_storage = APP_STATE.images.read()?;
+        let marker_storage = APP_STATE.markers.read()?;
+        for image_id in &source.image_ids {
+            let Some(image) = image_storage.get(image_id) else { continue };
+            let new_image_id = IMAGE_ID_GENERATOR.next();
+            image_map.insert(*image_id, new_image_id);
+
+            // 按索引中的顺序复制标记，保持同序号时的先后
+            for marker_id in marker_storage.image_marker_ids(image_id) {
+                let Some(marker) = marker_storage.get(marker_id) else { continue };
+                let new_marker_id = MARKER_ID_GENERATOR.next();
+                marker_map.insert(*marker_id, new_marker_id);
+                let mut copy = marker.clone();
+                copy.id = new_marker_id;
+                copy.image_id = new_image_id;
+                markers.push(copy);
+            }
+
+            // Image::clone只增加图片数据Arc的引用计数
+            let mut copy: Image = (**image).clone();
+            copy.metadata.id = new_image_id;
+            copy.marker_ids = image.marker_ids.iter()
+                .filter_map(|marker_id| marker_map.get(marker_id).copied())
+                .collect();
+            images.push(copy);
+        }
+    }
+
+    {
+        let mut image_storage = APP_STATE.images.write()?;
+        insert_images(&mut image_storage, images)?;
+    }
+    {
+        let mut marker_storage = APP_STATE.markers.write()?;
+        for marker in markers {
+            marker_storage.insert_with_image(marker)?;
+        }
+    }
+    {
+        let mut bunny_cache = APP_STATE.bunny_cache.write()?;
+        let copies: Vec<_> = marker_map.iter()
+            .filter_map(|(old_id, new_id)| bunny_cache.get(old_id).map(|cache| {
+                let mut copy = cache.clone();
+                copy.marker_id = *new_id;
+                copy
+            }))
+            .collect();
+        for cache in copies {
+            bunny_cache.insert(cache.marker_id, cache)?;
+        }
+    }
+    {
+        let mut history = APP_STATE.translation_history.write()?;
+        let copies: Vec<_> = marker_map.iter()
+            .filter_map(|(old_id, new_id)| history.entries.get(old_id).map(|entries| (*new_id, entries.clone())))
+            .collect();
+        history.entries.extend(copies);
+    }
+    {
+        let mut thumbnails = APP_STATE.thumbnails.write()?;
+        let copies: Vec<_> = image_map.iter()
+            .filter_map(|(old_id, new_id)| thumbnails.get(old_id).map(|thumbnail| (*new_id, thumbnail.clone())))
+            .collect();
+        for (image_id, thumbnail) in copies {
+            thumbnails.insert(image_id, thumbnail)?;
+        }
+    }
+
+    let new_project_id = PROJECT_ID_GENERATOR.next();
+    let mut project = source.clone();
+    project.id = new_project_id;
+    project.name = new_name.clone();
+    project.file_path = None;
+    project.image_ids = source.image_ids.iter().filter_map(|id| image_map.get(id).copied()).collect();
+    project.pinned_images = source.pinned_images.iter().filter_map(|id| image_map.get(id).copied()).collect();
+    APP_STATE.projects.write()?.insert(new_project_id, project)?;
+
+    event_bus.publish(DomainEvent::ProjectCreated(new_project_id, new_name.clone()));
+    let _ = ProjectCreatedEvent::new(new_project_id, new_name, Some(project_id)).emit();
+
+    Logger::info_with_data("复制项目", serde_json::json!({
+        "source_project_id": project_id,
+        "project_id": new_project_id,
+        "images": image_map.len(),
+        "markers": marker_map.len()
+    }));
+
+    Ok(new_project_id)
+}
+
+// 先按总量检查内存上限，避免只写入一部分副本图片
+fn insert_images(storage: &mut ImageStorage, images: Vec<Image>) -> CoreResult<()> {
+    let requested: usize = images.iter().map(Image::estimated_size).sum();
+    let available = storage.max_memory.saturating_sub(storage.current_memory);
+    if requested > available {
+        return Err(CoreError::MemoryLimitExceeded { requested, available });
+    }
+    for image in images {
+        storage.insert_with_memory_check(image.metadata.id, image)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::storage::image::{add_image_from_binary_storage, get_image_storage};
+    use crate::storage::image_data::{ImageData, ImageFormat};
+    use crate::storage::marker::{get_image_markers_storage, Marker};
+    use crate::storage::project::{add_image_to_project_storage, create_project_storage, get_project_storage};
+
+    fn binary_data(image_id: ImageId) -> Arc<Vec<u8>> {
+        match &get_image_storage(image_id).unwrap().unwrap().data {
+            ImageData::Binary { data, .. } => data.clone(),
+            _ => panic!("expected binary image"),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_shares_image_bytes_and_copies_markers() {
+        let project_id = create_project_storage("original".to_string()).unwrap();
+        crate::storage::project::update_project_with_storage(project_id, |project| {
+            project.file_path = Some("/tmp/original.bf".to_string());
+        }).unwrap();
+        let image_id = add_image_from_binary_storage(ImageFormat::Png, vec![7u8; 4096], Some("p01.png".to_string())).unwrap();
+        add_image_to_project_storage(project_id, image_id).unwrap();
+        let marker_id = MARKER_ID_GENERATOR.next();
+        {
+            let mut markers = APP_STATE.markers.write().unwrap();
+            markers.insert_with_image(Marker::point_with_translation(marker_id, image_id, 0.5, 0.5, "原文".to_string(), 1)).unwrap();
+        }
+        crate::storage::image::add_marker_to_image_storage(image_id, marker_id).unwrap();
+        crate::storage::bunny_cache::update_original_text_storage(marker_id, "OCR".to_string(), "model".to_string()).unwrap();
+
+        let copy_id = duplicate_project(&EventBus::new(), project_id, "copy".to_string()).unwrap();
+        let copy = get_project_storage(copy_id).unwrap().unwrap();
+        assert_eq!(copy.name, "copy");
+        assert!(copy.file_path.is_none());
+        assert_eq!(copy.image_ids.len(), 1);
+        let copy_image_id = copy.image_ids[0];
+        assert_ne!(copy_image_id, image_id);
+
+        // 两张图片指向同一份字节
+        assert!(Arc::ptr_eq(&binary_data(image_id), &binary_data(copy_image_id)));
+
+        let copy_markers = get_image_markers_storage(copy_image_id).unwrap();
+        assert_eq!(copy_markers.len(), 1);
+        assert_ne!(copy_markers[0].id, marker_id);
+        assert_eq!(copy_markers[0].translation, "原文");
+        assert_eq!(get_image_storage(copy_image_id).unwrap().unwrap().marker_ids, vec![copy_markers[0].id]);
+        let cache = crate::storage::bunny_cache::get_bunny_cache_storage(copy_markers[0].id).unwrap().unwrap();
+        assert_eq!(cache.original_text.as_deref(), Some("OCR"));
+
+        // 修改副本不影响原项目
+        crate::storage::marker::update_marker_translation_storage(copy_markers[0].id, "副本".to_string()).unwrap();
+        assert_eq!(crate::storage::marker::get_marker_storage(marker_id).unwrap().unwrap().translation, "原文");
+    }
+
+    #[test]
+    fn test_duplicate_keeps_shared_buffer_handle_and_copies_history() {
+        use crate::storage::translation_history::{get_translation_history_storage, record_translation_storage, TranslationOrigin};
+        let project_id = create_project_storage("shared".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_shared_buffer_storage(ImageFormat::Png, 90_001, None).unwrap();
+        add_image_to_project_storage(project_id, image_id).unwrap();
+        let marker_id = MARKER_ID_GENERATOR.next();
+        APP_STATE.markers.write().unwrap()
+            .insert_with_image(Marker::point_with_translation(marker_id, image_id, 0.5, 0.5, "新".to_string(), 1)).unwrap();
+        crate::storage::image::add_marker_to_image_storage(image_id, marker_id).unwrap();
+        record_translation_storage(marker_id, "旧", "新", TranslationOrigin::Manual).unwrap();
+
+        let copy_id = duplicate_project(&EventBus::new(), project_id, "copy".to_string()).unwrap();
+        let copy = get_project_storage(copy_id).unwrap().unwrap();
+        assert!(matches!(get_image_storage(copy.image_ids[0]).unwrap().unwrap().data, ImageData::SharedBuffer { buffer_id: 90_001, .. }));
+        // 删除原图片不影响副本
+        crate::storage::image::delete_image_storage(image_id).unwrap();
+        assert!(get_image_storage(copy.image_ids[0]).unwrap().is_some());
+
+        let copy_marker = get_image_markers_storage(copy.image_ids[0]).unwrap()[0].id;
+        assert_eq!(get_translation_history_storage(copy_marker).unwrap(), get_translation_history_storage(marker_id).unwrap());
+        record_translation_storage(copy_marker, "新", "副本", TranslationOrigin::Manual).unwrap();
+        assert_ne!(get_translation_history_storage(copy_marker).unwrap(), get_translation_history_storage(marker_id).unwrap());
+    }
+
+    #[test]
+    fn test_copies_count_toward_memory_limit() {
+        let image = |id: u32| Image::new_from_binary(ImageId(id), ImageFormat::Png, vec![0u8; 1000]);
+        let size = image(1).estimated_size();
+        let mut storage = ImageStorage::new(size * 3);
+        storage.insert_with_memory_check(ImageId(1), image(1)).unwrap();
+
+        // 副本共享字节也按各自的大小计入
+        insert_images(&mut storage, vec![image(2)]).unwrap();
+        assert_eq!(storage.current_memory_usage(), size * 2);
+
+        // 放不下全部副本时一张也不写入
+        let err = insert_images(&mut storage, vec![image(3), image(4)]).unwrap_err();
+        assert!(matches!(err, CoreError::MemoryLimitExceeded { .. }));
+        assert!(!storage.contains(&ImageId(3)));
+        assert_eq!(storage.current_memory_usage(), size * 2);
+    }
+}
@@ -0,0 +1,273 @@
+// 查找重复图片 - 生肉常有重复的页面，用感知哈希（dHash）比较图片，汉明距离不超过阈值的归为一组
+// 有缩略图时在缩略图上计算，避免完整解码；哈希缓存在图片元数据上，图片数据变化时清空
+use std::sync::atomic::{AtomicUsize, Ordering};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, ProjectId, Logger};
+use crate::common::events::schema::{BusinessEvent, DuplicateScanProgressEvent};
+use crate::service::worker_pool::CPU_POOL;
+use crate::storage::image::Image;
+use crate::storage::state::APP_STATE;
+use crate::storage::traits::Storage;
+
+/// 默认阈值：64位哈希中最多有几位不同仍视为重复
+pub const DEFAULT_DUPLICATE_THRESHOLD: u32 = 6;
+
+/// 阈值上限，再大就会把不相关的页面归到一起
+pub const MAX_DUPLICATE_THRESHOLD: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateImage {
+    pub image_id: ImageId,
+    pub name: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub marker_count: usize,
+    /// 与组内第一张图片的相似度，0-1
+    pub similarity: f32,
+}
+
+/// 一组重复图片，按项目中的顺序排列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub images: Vec<DuplicateImage>,
+}
+
+/// 删除重复图片时每组保留哪一张
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKeepStrategy {
+    /// 项目中最靠前的一张
+    #[default]
+    First,
+    /// 标记最多的一张，相同时取靠前的
+    MostMarkers,
+    /// 分辨率最高的一张，相同时取靠前的
+    Largest,
+}
+
+/// 差值哈希：缩小到9x8灰度图，每行相邻像素比较得到64位
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.thumbnail_exact(9, 8).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+pub fn similarity(a: u64, b: u64) -> f32 {
+    1.0 - (a ^ b).count_ones() as f32 / 64.0
+}
+
+fn compute_hash(image: &Image) -> CoreResult<u64> {
+    let thumbnail = APP_STATE.thumbnails.read()?
+        .get(&image.metadata.id)
+        .filter(|data| !data.is_empty())
+        .cloned();
+    let decoded = match thumbnail {
+        Some(data) => image::load_from_memory(&data),
+        None => image::load_from_memory(&crate::service::image::byte_cache::read_image_bytes(image)?),
+    };
+    decoded
+        .map(|decoded| dhash(&decoded))
+        .map_err(|e| CoreError::ImageProcessingError(format!("Failed to load image: {}", e)))
+}
+
+/// 按项目顺序分组：每张图片加入第一个与组首距离不超过阈值的组
+fn group_hashes(hashes: &[(ImageId, u64)], threshold: u32) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, (_, hash)) in hashes.iter().enumerate() {
+        match groups.iter_mut().find(|group| (hashes[group[0]].1 ^ hash).count_ones() <= threshold) {
+            Some(group) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// 查找项目中的重复图片，threshold为允许的最大汉明距离
+/// 计算在线程池中进行，每算完一张发送一次进度事件；无法读取的图片跳过
+pub fn find_duplicate_images(project_id: ProjectId, threshold: Option<u32>) -> CoreResult<Vec<DuplicateGroup>> {
+    let threshold = threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    if threshold > MAX_DUPLICATE_THRESHOLD {
+        return Err(CoreError::ValidationFailed {
+            field: "threshold".to_string(),
+            reason: format!("阈值不能超过{}", MAX_DUPLICATE_THRESHOLD),
+        });
+    }
+    let project = crate::storage::project::get_project_storage(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+    let images: Vec<_> = {
+        let storage = APP_STATE.images.read()?;
+        project.image_ids.iter().filter_map(|id| storage.get(id).cloned()).collect()
+    };
+
+    let total = images.len();
+    let done = AtomicUsize::new(0);
+    let results = CPU_POOL.map(images.clone(), |image| {
+        let result = match image.metadata.perceptual_hash {
+            Some(hash) => Ok((hash, false)),
+            None => compute_hash(&image).map(|hash| (hash, true)),
+        };
+        let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = DuplicateScanProgressEvent::new(project_id, done, total).emit();
+        result
+    });
+
+    let mut hashes = Vec::with_capacity(total);
+    for (image, result) in images.iter().zip(results) {
+        match result {
+            Ok((hash, computed)) => {
+                if computed {
+                    crate::storage::image::set_image_perceptual_hash_storage(image.metadata.id, hash)?;
+                }
+                hashes.push((image.metadata.id, hash));
+            }
+            Err(e) => Logger::warn(&format!("Skipping image {} in duplicate scan: {}", image.metadata.id, e)),
+        }
+    }
+
+    let groups = group_hashes(&hashes, threshold)
+        .into_iter()
+        .map(|group| {
+            let first = hashes[group[0]].1;
+            let images = group.into_iter()
+                .filter_map(|i| {
+                    let (image_id, hash) = hashes[i];
+                    let image = images.iter().find(|image| image.metadata.id == image_id)?;
+                    Some(DuplicateImage {
+                        image_id,
+                        name: image.metadata.name.clone(),
+                        width: image.metadata.width,
+                        height: image.metadata.height,
+                        marker_count: image.marker_ids.len(),
+                        similarity: similarity(first, hash),
+                    })
+                })
+                .collect();
+            DuplicateGroup { images }
+        })
+        .collect();
+    Ok(groups)
+}
+
+/// 每组按策略保留一张，返回要删除的图片
+/// 要删除的图片带有标记时，除非force为true否则报错，不删除任何图片
+pub fn plan_duplicate_removal(groups: &[DuplicateGroup], keep: DuplicateKeepStrategy, force: bool) -> CoreResult<Vec<ImageId>> {
+    let mut removed = Vec::new();
+    for group in groups {
+        let pixels = |image: &DuplicateImage| image.width.unwrap_or(0) as u64 * image.height.unwrap_or(0) as u64;
+        // max_by_key在相同时取最后一个，先反转才能取靠前的
+        let kept = match keep {
+            DuplicateKeepStrategy::First => group.images.first(),
+            DuplicateKeepStrategy::MostMarkers => group.images.iter().rev().max_by_key(|image| image.marker_count),
+            DuplicateKeepStrategy::Largest => group.images.iter().rev().max_by_key(|image| pixels(image)),
+        };
+        let Some(kept) = kept else { continue };
+        removed.extend(group.images.iter().filter(|image| image.image_id != kept.image_id));
+    }
+
+    let with_markers = removed.iter().filter(|image| image.marker_count > 0).count();
+    if with_markers > 0 && !force {
+        return Err(CoreError::ValidationFailed {
+            field: "force".to_string(),
+            reason: format!("{}张要删除的重复图片带有标记，确认删除请使用force", with_markers),
+        });
+    }
+    Ok(removed.into_iter().map(|image| image.image_id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(seed: u32, noise: bool) -> DynamicImage {
+        // 横向渐变加上按seed变化的条纹，noise只改动少量像素
+        DynamicImage::ImageLuma8(image::GrayImage::from_fn(90, 80, |x, y| {
+            let base = ((x * 2 + (y / 10) * seed * 37) % 256) as u8;
+            image::Luma([if noise && x % 30 == 0 && y % 40 == 0 { base.wrapping_add(40) } else { base }])
+        }))
+    }
+
+    fn duplicate(image_id: u32, marker_count: usize, size: u32) -> DuplicateImage {
+        DuplicateImage {
+            image_id: ImageId::from(image_id),
+            name: None,
+            width: Some(size),
+            height: Some(size),
+            marker_count,
+            similarity: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_near_identical_pages_are_grouped() {
+        let a = dhash(&page(1, false));
+        let a_noisy = dhash(&page(1, true));
+        let b = dhash(&page(5, false));
+        assert!((a ^ a_noisy).count_ones() <= DEFAULT_DUPLICATE_THRESHOLD);
+        assert!((a ^ b).count_ones() > DEFAULT_DUPLICATE_THRESHOLD);
+
+        let hashes = vec![(ImageId::from(1), a), (ImageId::from(2), b), (ImageId::from(3), a_noisy)];
+        assert_eq!(group_hashes(&hashes, DEFAULT_DUPLICATE_THRESHOLD), vec![vec![0, 2]]);
+        assert_eq!(similarity(a, a), 1.0);
+    }
+
+    #[test]
+    fn test_removal_plan_respects_strategy_and_markers() {
+        let groups = vec![DuplicateGroup { images: vec![duplicate(1, 0, 100), duplicate(2, 3, 200), duplicate(3, 3, 50)] }];
+        let ids = |v: &[u32]| v.iter().map(|&id| ImageId::from(id)).collect::<Vec<_>>();
+
+        assert!(matches!(
+            plan_duplicate_removal(&groups, DuplicateKeepStrategy::MostMarkers, false),
+            Err(CoreError::ValidationFailed { ref field, .. }) if field == "force"
+        ));
+        assert_eq!(plan_duplicate_removal(&groups, DuplicateKeepStrategy::MostMarkers, true).unwrap(), ids(&[1, 3]));
+        assert_eq!(plan_duplicate_removal(&groups, DuplicateKeepStrategy::Largest, true).unwrap(), ids(&[1, 3]));
+        assert_eq!(plan_duplicate_removal(&groups, DuplicateKeepStrategy::First, true).unwrap(), ids(&[2, 3]));
+    }
+
+    #[test]
+    fn test_remove_duplicates_is_one_undoable_action() {
+        use crate::service::get_service;
+        use crate::storage::project::{add_image_to_project_storage, create_project_storage, get_project_storage};
+        let encode = |image: DynamicImage| {
+            let mut data = Vec::new();
+            image.write_to(&mut std::io::Cursor::new(&mut data), image::ImageFormat::Png).unwrap();
+            data
+        };
+        let service = get_service();
+        let project_id = create_project_storage("duplicates".to_string()).unwrap();
+        let mut ids = Vec::new();
+        for image in [page(1, false), page(5, false), page(1, true)] {
+            let id = crate::storage::image::add_image_from_binary_storage(crate::storage::ImageFormat::Png, encode(image), None).unwrap();
+            add_image_to_project_storage(project_id, id).unwrap();
+            ids.push(id);
+        }
+        service.marker_service.add_point_marker(ids[2].0, 10.0, 10.0, None).unwrap();
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        let groups = find_duplicate_images(project_id, None).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].images.iter().map(|image| image.image_id).collect::<Vec<_>>(), vec![ids[0], ids[2]]);
+        assert!(crate::storage::image::get_image_storage(ids[0]).unwrap().unwrap().metadata.perceptual_hash.is_some());
+
+        // 重复的那张带有标记，不强制时不删除
+        assert!(crate::api::project::remove_duplicate_images(project_id.0, DuplicateKeepStrategy::First, None, false).is_err());
+        assert_eq!(get_project_storage(project_id).unwrap().unwrap().image_ids.len(), 3);
+
+        assert_eq!(crate::api::project::remove_duplicate_images(project_id.0, DuplicateKeepStrategy::First, None, true), Ok(vec![ids[2].0]));
+        assert_eq!(get_project_storage(project_id).unwrap().unwrap().image_ids, vec![ids[0], ids[1]]);
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(get_project_storage(project_id).unwrap().unwrap().image_ids, ids);
+        assert_eq!(crate::storage::marker::get_image_markers_storage(ids[2]).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,438 @@
+// 项目数据一致性检查 - 崩溃或插件写入出错后，图片、标记、缓存之间可能残留互相指向不存在对象的记录
+// 修复模式只处理可以安全修复的问题（丢弃悬空ID、重新编号重复序号），作为一次撤销操作记录
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, MarkerId, ProjectId, Logger};
+use crate::common::events::schema::{BusinessEvent, ImageRenamedEvent};
+use crate::service::events::{DomainEvent, EventBus};
+use crate::storage::marker::{self as marker_storage, MarkerIndexChanges};
+use crate::storage::state::APP_STATE;
+use crate::storage::traits::Storage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// 项目的图片列表中有不存在的图片
+    MissingImage,
+    /// 图片的marker_ids中有不存在的标记
+    DanglingMarkerId,
+    /// 标记按图片索引中有不存在的标记
+    DanglingIndexEntry,
+    /// 标记在图片的标记索引中，但不在图片的marker_ids中
+    UnlistedMarker,
+    /// 标记记录的图片与所在的图片不一致（可能属于其它项目）
+    MarkerImageMismatch,
+    /// 同一张图片上有多个标记使用相同序号
+    DuplicateImageIndex,
+    /// 项目中有同名图片，修复时给后出现的加序号后缀
+    DuplicateImageName,
+    /// 翻译缓存对应的标记已不存在
+    OrphanBunnyCache,
+    // 以下为打开bf文件时对文件内容的检查
+    /// 同名图片重复出现，只会加载第一张
+    BfDuplicateImage,
+    /// 翻译缓存指向不存在的页或标记
+    BfDanglingBunnyCache,
+    /// 标记使用了未定义的样式
+    BfUnknownStyle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub message: String,
+    pub image_id: Option<ImageId>,
+    pub marker_id: Option<MarkerId>,
+    /// 修复模式下可以自动修复
+    pub fixable: bool,
+    /// 已在本次检查中修复
+    pub fixed: bool,
+}
+
+impl IntegrityIssue {
+    pub fn new(kind: IntegrityIssueKind, message: String) -> Self {
+        let fixable = matches!(
+            kind,
+            IntegrityIssueKind::MissingImage
+                | IntegrityIssueKind::DanglingMarkerId
+                | IntegrityIssueKind::DanglingIndexEntry
+                | IntegrityIssueKind::UnlistedMarker
+                | IntegrityIssueKind::DuplicateImageIndex
+                | IntegrityIssueKind::DuplicateImageName
+                | IntegrityIssueKind::OrphanBunnyCache
+        );
+        Self { kind, message, image_id: None, marker_id: None, fixable, fixed: false }
+    }
+
+    pub fn at(mut self, image_id: Option<ImageId>, marker_id: Option<MarkerId>) -> Self {
+        self.image_id = image_id;
+        self.marker_id = marker_id;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub project_id: ProjectId,
+    pub issues: Vec<IntegrityIssue>,
+    /// 是否执行了修复（issues中fixed为false的是无法修复的问题）
+    pub repaired: bool,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn unfixed(&self) -> impl Iterator<Item = &IntegrityIssue> {
+        self.issues.iter().filter(|issue| !issue.fixed)
+    }
+}
+
+/// 一次修复的全部改动，撤销时整体恢复
+/// 列表改动为 (图片, 旧列表, 新列表)，改名为 (图片, 旧名称, 新名称)
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityRepair {
+    pub image_order: Option<(Vec<ImageId>, Vec<ImageId>)>,
+    pub image_names: Vec<(ImageId, Option<String>, String)>,
+    pub image_marker_ids: Vec<(ImageId, Vec<MarkerId>, Vec<MarkerId>)>,
+    pub index_entries: Vec<(ImageId, Vec<MarkerId>, Vec<MarkerId>)>,
+    pub index_changes: Vec<(ImageId, MarkerIndexChanges)>,
+}
+
+impl IntegrityRepair {
+    pub fn is_empty(&self) -> bool {
+        self.image_order.is_none()
+            && self.image_names.is_empty()
+            && self.image_marker_ids.is_empty()
+            && self.index_entries.is_empty()
+            && self.index_changes.is_empty()
+    }
+}
+
+/// 检查项目的图片、标记和翻译缓存，repair为true时修复可以安全修复的问题
+pub fn check_integrity(event_bus: &EventBus, project_id: ProjectId, repair: bool) -> CoreResult<IntegrityReport> {
+    if !crate::storage::project::project_exists_storage(project_id)? {
+        return Err(CoreError::NotFound(format!("Project with id {} not found", project_id.0)));
+    }
+    let image_ids = crate::storage::project::get_project_image_ids_storage(project_id)?;
+    let project_images: HashSet<ImageId> = image_ids.iter().copied().collect();
+
+    let mut issues = Vec::new();
+    let mut change = IntegrityRepair::default();
+
+    // 1. 项目图片列表，以及每张图片的marker_ids
+    let mut existing_images = Vec::new();
+    let mut image_marker_ids = HashMap::new();
+    {
+        let images = APP_STATE.images.read()?;
+        for image_id in &image_ids {
+            match images.get(image_id) {
+                Some(image) => {
+                    existing_images.push(*image_id);
+                    image_marker_ids.insert(*image_id, image.marker_ids.clone());
+                }
+                None => issues.push(IntegrityIssue::new(
+                    IntegrityIssueKind::MissingImage,
+                    format!("项目图片列表中的图片 {} 不存在", image_id.0),
+                ).at(Some(*image_id), None)),
+            }
+        }
+    }
+    if existing_images.len() != image_ids.len() {
+        change.image_order = Some((image_ids.clone(), existing_images.clone()));
+    }
+    for (image_id, old_name, new_name) in duplicate_image_names(&existing_images)? {
+        issues.push(duplicate_name_issue(image_id, &old_name, &new_name));
+        change.image_names.push((image_id, Some(old_name), new_name));
+    }
+
+    // 2. 标记与图片的互相引用、重复序号
+    {
+        let markers = APP_STATE.markers.read()?;
+        for image_id in &existing_images {
+            let listed = &image_marker_ids[image_id];
+            let indexed = markers.image_marker_ids(image_id).to_vec();
+
+            let mut kept_listed = Vec::with_capacity(listed.len());
+            for marker_id in listed {
+                if markers.contains(marker_id) {
+                    kept_listed.push(*marker_id);
+                } else {
+                    issues.push(IntegrityIssue::new(
+                        IntegrityIssueKind::DanglingMarkerId,
+                        format!("图片 {} 引用了不存在的标记 {}", image_id.0, marker_id.0),
+                    ).at(Some(*image_id), Some(*marker_id)));
+                }
+            }
+
+            let mut kept_indexed = Vec::with_capacity(indexed.len());
+            let mut by_index: HashMap<u32, Vec<MarkerId>> = HashMap::new();
+            for marker_id in &indexed {
+                let Some(marker) = markers.get(marker_id) else {
+                    issues.push(IntegrityIssue::new(
+                        IntegrityIssueKind::DanglingIndexEntry,
+                        format!("图片 {} 的标记索引中有不存在的标记 {}", image_id.0, marker_id.0),
+                    ).at(Some(*image_id), Some(*marker_id)));
+                    continue;
+                };
+                kept_indexed.push(*marker_id);
+                if marker.image_id != *image_id {
+                    let owner = if project_images.contains(&marker.image_id) { "项目中的另一张图片" } else { "不在项目中的图片" };
+                    issues.push(IntegrityIssue::new(
+                        IntegrityIssueKind::MarkerImageMismatch,
+                        format!("标记 {} 位于图片 {} 下，但记录的图片为{} {}", marker_id.0, image_id.0, owner, marker.image_id.0),
+                    ).at(Some(*image_id), Some(*marker_id)));
+                    continue;
+                }
+                if !kept_listed.contains(marker_id) {
+                    issues.push(IntegrityIssue::new(
+                        IntegrityIssueKind::UnlistedMarker,
+                        format!("标记 {} 不在图片 {} 的标记列表中", marker_id.0, image_id.0),
+                    ).at(Some(*image_id), Some(*marker_id)));
+                    kept_listed.push(*marker_id);
+                }
+                by_index.entry(marker.image_index).or_default().push(*marker_id);
+            }
+
+            let mut duplicates: Vec<(u32, Vec<MarkerId>)> = by_index.into_iter()
+                .filter(|(_, ids)| ids.len() > 1)
+                .collect();
+            duplicates.sort_by_key(|(index, _)| *index);
+            for (index, ids) in &duplicates {
+                issues.push(IntegrityIssue::new(
+                    IntegrityIssueKind::DuplicateImageIndex,
+                    format!(
+                        "图片 {} 上的标记 {} 使用了相同的序号 {}",
+                        image_id.0,
+                        ids.iter().map(|id| id.0.to_string()).collect::<Vec<_>>().join(", "),
+                        index
+                    ),
+                ).at(Some(*image_id), ids.first().copied()));
+            }
+
+            if &kept_listed != listed {
+                change.image_marker_ids.push((*image_id, listed.clone(), kept_listed));
+            }
+            if kept_indexed != indexed {
+                change.index_entries.push((*image_id, indexed, kept_indexed));
+            }
+            if !duplicates.is_empty() {
+                change.index_changes.push((*image_id, Vec::new()));
+            }
+        }
+    }
+
+    // 3. 翻译缓存：缓存不属于任何项目，检查所有对应标记已不存在的条目
+    let orphan_cache: Vec<MarkerId> = {
+        let markers = APP_STATE.markers.read()?;
+        let cache = APP_STATE.bunny_cache.read()?;
+        let mut orphans: Vec<MarkerId> = cache.cache.keys()
+            .filter(|marker_id| !markers.contains(marker_id))
+            .copied()
+            .collect();
+        orphans.sort_by_key(|id| id.0);
+        orphans
+    };
+    for marker_id in &orphan_cache {
+        issues.push(IntegrityIssue::new(
+            IntegrityIssueKind::OrphanBunnyCache,
+            format!("标记 {} 已不存在，但仍有OCR/翻译缓存", marker_id.0),
+        ).at(None, Some(*marker_id)));
+    }
+
+    if repair {
+        apply_repair(project_id, &mut change)?;
+        for marker_id in &orphan_cache {
+            crate::storage::bunny_cache::clear_bunny_cache_storage(*marker_id)?;
+        }
+        for issue in issues.iter_mut().filter(|issue| issue.fixable) {
+            issue.fixed = true;
+        }
+        for (image_id, _, new_name) in &change.image_names {
+            let _ = ImageRenamedEvent::new(project_id, *image_id, Some(new_name.clone())).emit();
+        }
+        if !change.is_empty() {
+            let changed_images: Vec<ImageId> = change.image_marker_ids.iter().map(|(id, _, _)| *id)
+                .chain(change.index_entries.iter().map(|(id, _, _)| *id))
+                .chain(change.index_changes.iter().map(|(id, _)| *id))
+                .collect();
+            event_bus.publish(DomainEvent::ProjectIntegrityRepaired { project_id, repair: change.clone() });
+            crate::service::marker::emit_markers_renumbered(&changed_images);
+        }
+    }
+
+    if !issues.is_empty() {
+        Logger::warn_with_data(
+            "项目数据存在不一致",
+            serde_json::json!({
+                "project_id": project_id,
+                "issue_count": issues.len(),
+                "repaired": repair
+            })
+        );
+    }
+
+    Ok(IntegrityReport { project_id, issues, repaired: repair })
+}
+
+/// 按项目顺序找出重名的图片，返回 (图片, 原名称, 加后缀后的名称)，第一张保持原名
+pub fn duplicate_image_names(image_ids: &[ImageId]) -> CoreResult<Vec<(ImageId, String, String)>> {
+    let named: Vec<(ImageId, String)> = {
+        let images = APP_STATE.images.read()?;
+        image_ids.iter()
+            .filter_map(|id| images.get(id).and_then(|image| image.metadata.name.clone()).map(|name| (*id, name)))
+            .collect()
+    };
+    let names: Vec<String> = named.iter().map(|(_, name)| name.clone()).collect();
+    Ok(named.into_iter()
+        .zip(crate::storage::image::dedupe_image_names(&names))
+        .filter(|((_, name), unique)| name != unique)
+        .map(|((id, name), unique)| (id, name, unique))
+        .collect())
+}
+
+pub fn duplicate_name_issue(image_id: ImageId, old_name: &str, new_name: &str) -> IntegrityIssue {
+    IntegrityIssue::new(
+        IntegrityIssueKind::DuplicateImageName,
+        format!("项目中有多张名为 {} 的图片，图片 {} 改名为 {}", old_name, image_id.0, new_name),
+    ).at(Some(image_id), None)
+}
+
+/// 打开项目时给重名的图片加后缀（不记录撤销），返回修复记录供打开流程报告
+pub fn dedupe_project_image_names(project_id: ProjectId) -> CoreResult<Vec<IntegrityIssue>> {
+    let image_ids = crate::storage::project::get_project_image_ids_storage(project_id)?;
+    let mut issues = Vec::new();
+    for (image_id, old_name, new_name) in duplicate_image_names(&image_ids)? {
+        crate::storage::image::rename_duplicate_image_storage(image_id, new_name.clone())?;
+        let mut issue = duplicate_name_issue(image_id, &old_name, &new_name);
+        issue.fixed = true;
+        issues.push(issue);
+    }
+    Ok(issues)
+}
+
+// 写入修复结果，重复序号在这里才压缩并记下实际的序号变化
+fn apply_repair(project_id: ProjectId, change: &mut IntegrityRepair) -> CoreResult<()> {
+    if let Some((_, new_order)) = &change.image_order {
+        let mut projects = APP_STATE.projects.write()?;
+        if let Some(project) = projects.get_mut(&project_id) {
+            project.image_ids = new_order.clone();
+        }
+    }
+    for (image_id, _, new_name) in &change.image_names {
+        crate::storage::image::rename_duplicate_image_storage(*image_id, new_name.clone())?;
+    }
+    set_image_marker_ids(change.image_marker_ids.iter().map(|(id, _, new_ids)| (*id, new_ids)))?;
+    set_index_entries(change.index_entries.iter().map(|(id, _, new_ids)| (*id, new_ids)))?;
+
+    let mut markers = APP_STATE.markers.write()?;
+    for (image_id, changes) in change.index_changes.iter_mut() {
+        *changes = marker_storage::compact_image_indices(markers.image_mut(image_id), *image_id);
+    }
+    Ok(())
+}
+
+/// 覆盖图片的marker_ids（修复及其撤销）
+pub fn set_image_marker_ids<'a>(lists: impl Iterator<Item = (ImageId, &'a Vec<MarkerId>)>) -> CoreResult<()> {
+    let mut images = APP_STATE.images.write()?;
+    for (image_id, marker_ids) in lists {
+        if let Some(image_arc) = images.get_mut(&image_id) {
+            std::sync::Arc::make_mut(image_arc).marker_ids = marker_ids.clone();
+        }
+    }
+    Ok(())
+}
+
+/// 覆盖标记按图片索引（修复及其撤销）
+pub fn set_index_entries<'a>(lists: impl Iterator<Item = (ImageId, &'a Vec<MarkerId>)>) -> CoreResult<()> {
+    let mut markers = APP_STATE.markers.write()?;
+    for (image_id, marker_ids) in lists {
+        markers.image_mut(&image_id).set_index_entries(image_id, marker_ids.clone());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::get_service;
+    use crate::storage::bunny_cache::BunnyCacheData;
+
+    fn kinds(report: &IntegrityReport) -> Vec<IntegrityIssueKind> {
+        report.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_repair_is_single_undoable_action() {
+        let service = get_service();
+        let project_id = crate::storage::project::create_project_storage("integrity".to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        let a = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 10.0, 10.0, None).unwrap());
+        let b = MarkerId::from(service.marker_service.add_point_marker(image_id.0, 20.0, 20.0, None).unwrap());
+        assert!(check_integrity(&service.event_bus, project_id, false).unwrap().is_clean());
+        service.undo_redo_service.clear_project_history(project_id.0);
+
+        // 制造悬空引用和重复序号
+        let dangling = MarkerId::from(u32::MAX - 7);
+        let missing_image = ImageId::from(u32::MAX - 7);
+        crate::storage::image::add_marker_to_image_storage(image_id, dangling).unwrap();
+        APP_STATE.markers.write().unwrap().get_mut(&b).unwrap().image_index = 1;
+        APP_STATE.bunny_cache.write().unwrap().cache.insert(dangling, BunnyCacheData::new(dangling));
+        APP_STATE.projects.write().unwrap().get_mut(&project_id).unwrap().image_ids.push(missing_image);
+
+        let report = check_integrity(&service.event_bus, project_id, false).unwrap();
+        let found = kinds(&report);
+        for kind in [IntegrityIssueKind::MissingImage, IntegrityIssueKind::DanglingMarkerId,
+                     IntegrityIssueKind::DuplicateImageIndex, IntegrityIssueKind::OrphanBunnyCache] {
+            assert!(found.contains(&kind), "{:?} not reported", kind);
+        }
+        assert!(report.unfixed().count() == report.issues.len());
+
+        let report = check_integrity(&service.event_bus, project_id, true).unwrap();
+        assert!(report.repaired && report.unfixed().next().is_none());
+        assert!(check_integrity(&service.event_bus, project_id, false).unwrap().is_clean());
+        let indices: Vec<u32> = [a, b].iter()
+            .map(|id| APP_STATE.markers.read().unwrap().get(id).unwrap().image_index)
+            .collect();
+        assert_eq!(indices, vec![1, 2]);
+
+        // 缓存条目不参与撤销，其余改动一次撤销全部恢复
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert!(!service.undo_redo_service.can_undo(project_id).unwrap());
+        let found = kinds(&check_integrity(&service.event_bus, project_id, false).unwrap());
+        assert!(found.contains(&IntegrityIssueKind::MissingImage));
+        assert!(found.contains(&IntegrityIssueKind::DanglingMarkerId));
+        assert!(found.contains(&IntegrityIssueKind::DuplicateImageIndex));
+        assert!(!found.contains(&IntegrityIssueKind::OrphanBunnyCache));
+    }
+
+    #[test]
+    fn test_duplicate_image_names_repaired_and_undone() {
+        let service = get_service();
+        let project_id = crate::storage::project::create_project_storage("integrity_names".to_string()).unwrap();
+        let image_ids: Vec<ImageId> = ["01.png", "01 (2).png", "01.png"].iter().map(|name| {
+            let image_id = crate::storage::image::add_image_from_binary_storage(
+                crate::storage::ImageFormat::Png, vec![0u8; 16], Some(name.to_string())
+            ).unwrap();
+            // 直接写入存储，模拟旧版本留下的重名
+            crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+            image_id
+        }).collect();
+        let name = |id: ImageId| APP_STATE.get_image(id).unwrap().unwrap().metadata.name.clone().unwrap();
+
+        let report = check_integrity(&service.event_bus, project_id, false).unwrap();
+        assert_eq!(kinds(&report), vec![IntegrityIssueKind::DuplicateImageName]);
+        assert_eq!(report.issues[0].image_id, Some(image_ids[2]));
+
+        check_integrity(&service.event_bus, project_id, true).unwrap();
+        assert_eq!(name(image_ids[2]), "01 (3).png");
+        assert!(check_integrity(&service.event_bus, project_id, false).unwrap().is_clean());
+
+        assert!(service.undo_redo_service.undo(project_id.0).success);
+        assert_eq!(name(image_ids[2]), "01.png");
+    }
+}
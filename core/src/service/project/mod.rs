@@ -1,4 +1,12 @@
 // Project service模块
 mod service;
+pub mod template;
+pub mod snapshot;
+pub mod integrity;
+pub mod duplicate;
+pub mod duplicate_images;
 
-pub use service::ProjectService;
\ No newline at end of file
+pub use service::ProjectService;
+pub use template::{ProjectTemplate, TemplateList, TEMPLATE_SCHEMA_VERSION};
+pub use integrity::{IntegrityIssue, IntegrityIssueKind, IntegrityReport};
+pub use duplicate_images::{DuplicateGroup, DuplicateImage, DuplicateKeepStrategy, DEFAULT_DUPLICATE_THRESHOLD};
@@ -1,11 +1,13 @@
 // Project Service - 处理项目相关的业务逻辑
 use std::sync::Arc;
-use crate::common::{CoreResult, ProjectId, ImageId, Language};
+use crate::common::{CoreError, CoreResult, ProjectId, ImageId, Language, ProjectGlossary, ReadingDirection, EVENT_SYSTEM};
+use crate::common::events::schema::{BusinessEvent, ProjectSettingsChangedEvent, GlossaryChangedEvent, ReadingDirectionChangedEvent, ServicesChangedEvent};
 use crate::common::dto::project::ProjectDTO;
 use crate::common::dto::image::ImageDTO;
 use crate::storage::project::{self as storage};
 use crate::storage::state::APP_STATE;
 use crate::service::events::{DomainEvent, EventBus, EventHandler};
+use super::template::{self, ProjectTemplate, TemplateList};
 
 pub struct ProjectService {
     event_bus: Arc<EventBus>,
@@ -92,6 +94,166 @@ impl ProjectService {
         storage::update_project_languages_storage(id, source_language, target_language)
     }
     
+    pub fn get_reading_direction(&self, project_id: ProjectId) -> CoreResult<Option<ReadingDirection>> {
+        Ok(storage::get_project_storage(project_id)?.map(|project| project.reading_direction))
+    }
+    
+    /// 设置阅读方向，返回原来的方向（项目不存在时为None）
+    ///
+    /// 只改项目设置，标记的序号不变；需要按新方向编号时调用renumber_by_reading_order
+    pub fn set_reading_direction(&self, project_id: ProjectId, direction: ReadingDirection) -> CoreResult<Option<ReadingDirection>> {
+        let old = self.set_reading_direction_core(project_id, direction)?;
+        if old.is_some() {
+            self.event_bus.publish(DomainEvent::ReadingDirectionUpdated(project_id, direction));
+        }
+        Ok(old)
+    }
+    
+    /// 设置阅读方向并通知前端，撤销/重做也走这里
+    pub fn set_reading_direction_core(&self, project_id: ProjectId, direction: ReadingDirection) -> CoreResult<Option<ReadingDirection>> {
+        let old = storage::update_reading_direction_storage(project_id, direction)?;
+        if old.is_some() {
+            let _ = ReadingDirectionChangedEvent::new(project_id, direction).emit();
+        }
+        Ok(old)
+    }
+    
+    pub fn get_notes(&self, project_id: ProjectId) -> CoreResult<Option<String>> {
+        Ok(storage::get_project_storage(project_id)?.map(|project| project.notes))
+    }
+    
+    /// 设置项目备注，去掉首尾空白；项目不存在时返回false
+    pub fn set_notes(&self, project_id: ProjectId, notes: String) -> CoreResult<bool> {
+        let notes = notes.trim().to_string();
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.notes != notes;
+            project.notes = notes.clone();
+        })?;
+        if changed {
+            let _ = ProjectSettingsChangedEvent::new(project_id, "notes".to_string(), serde_json::json!(notes)).emit();
+        }
+        Ok(exists)
+    }
+    
+    pub fn get_auto_ocr_on_create(&self, project_id: ProjectId) -> CoreResult<Option<bool>> {
+        Ok(storage::get_project_storage(project_id)?.map(|project| project.auto_ocr_on_create))
+    }
+    
+    /// 开关新建矩形标记时的自动OCR，项目不存在时返回false
+    pub fn set_auto_ocr_on_create(&self, project_id: ProjectId, enabled: bool) -> CoreResult<bool> {
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.auto_ocr_on_create != enabled;
+            project.auto_ocr_on_create = enabled;
+        })?;
+        if changed {
+            let _ = ProjectSettingsChangedEvent::new(project_id, "auto_ocr_on_create".to_string(), serde_json::json!(enabled)).emit();
+        }
+        Ok(exists)
+    }
+    
+    pub fn get_keep_translation_history(&self, project_id: ProjectId) -> CoreResult<Option<bool>> {
+        Ok(storage::get_project_storage(project_id)?.map(|project| project.keep_translation_history))
+    }
+    
+    /// 开关是否把译文历史保存到bf中，项目不存在时返回false
+    pub fn set_keep_translation_history(&self, project_id: ProjectId, enabled: bool) -> CoreResult<bool> {
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.keep_translation_history != enabled;
+            project.keep_translation_history = enabled;
+        })?;
+        if changed {
+            let _ = ProjectSettingsChangedEvent::new(project_id, "keep_translation_history".to_string(), serde_json::json!(enabled)).emit();
+        }
+        Ok(exists)
+    }
+    
+    pub fn get_grayscale_reencode(&self, project_id: ProjectId) -> CoreResult<Option<bool>> {
+        Ok(storage::get_project_storage(project_id)?.map(|project| project.grayscale_reencode))
+    }
+    
+    /// 开关打开项目时灰度图片的重新编码，下次打开项目时生效，项目不存在时返回false
+    pub fn set_grayscale_reencode(&self, project_id: ProjectId, enabled: bool) -> CoreResult<bool> {
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.grayscale_reencode != enabled;
+            project.grayscale_reencode = enabled;
+        })?;
+        if changed {
+            let _ = ProjectSettingsChangedEvent::new(project_id, "grayscale_reencode".to_string(), serde_json::json!(enabled)).emit();
+        }
+        Ok(exists)
+    }
+    
+    /// 开关桌面端对项目图片文件的监听，桌面端收到事件后启动或停止watcher
+    pub fn set_file_watch(&self, project_id: ProjectId, enabled: bool) -> CoreResult<bool> {
+        let result = storage::update_watch_files_storage(project_id, enabled)?;
+        if result {
+            let _ = EVENT_SYSTEM.emit_business_event(
+                "project:file_watch_changed".to_string(),
+                serde_json::json!({ "project_id": project_id, "enabled": enabled }),
+            );
+        }
+        Ok(result)
+    }
+    
+    /// 在项目中启用/停用某个OCR/翻译服务，服务注册仍是全局的，这里只影响该项目可选的服务
+    pub fn set_service_enabled(&self, project_id: ProjectId, service_id: &str, enabled: bool) -> CoreResult<bool> {
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.default_services.set_enabled(service_id, enabled);
+        })?;
+        if changed {
+            let _ = ServicesChangedEvent::new(project_id, service_id.to_string(), enabled).emit();
+        }
+        Ok(exists)
+    }
+    
+    /// 设置OCR/翻译失败时依次改用的服务，去掉空白和重复的ID
+    pub fn set_service_fallbacks(&self, project_id: ProjectId, ocr: Vec<String>, translation: Vec<String>) -> CoreResult<bool> {
+        let normalize = |ids: Vec<String>| {
+            let mut result: Vec<String> = Vec::new();
+            for id in ids.into_iter().map(|id| id.trim().to_string()) {
+                if !id.is_empty() && !result.contains(&id) {
+                    result.push(id);
+                }
+            }
+            result
+        };
+        let (ocr, translation) = (normalize(ocr), normalize(translation));
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            let services = &mut project.default_services;
+            changed = services.ocr_fallbacks != ocr || services.translation_fallbacks != translation;
+            services.ocr_fallbacks = ocr.clone();
+            services.translation_fallbacks = translation.clone();
+        })?;
+        if changed {
+            let _ = ProjectSettingsChangedEvent::new(
+                project_id,
+                "service_fallbacks".to_string(),
+                serde_json::json!({ "ocr": ocr, "translation": translation }),
+            ).emit();
+        }
+        Ok(exists)
+    }
+    
+    /// 替换项目术语表，之后发出的翻译请求会带上新的术语表
+    pub fn set_glossary(&self, project_id: ProjectId, glossary: ProjectGlossary) -> CoreResult<bool> {
+        let glossary = glossary.normalized();
+        let mut changed = false;
+        let exists = storage::update_project_with_storage(project_id, |project| {
+            changed = project.glossary != glossary;
+            project.glossary = glossary.clone();
+        })?;
+        if changed {
+            let _ = GlossaryChangedEvent::new(project_id, glossary.entries.len(), glossary.forbidden_terms.len()).emit();
+        }
+        Ok(exists)
+    }
+    
     pub fn delete_project(&self, project_id: u32) -> bool {
         match self.delete_project_core(ProjectId::from(project_id)) {
             Ok(res) => res,
@@ -118,6 +280,40 @@ impl ProjectService {
         storage::project_count_storage()
     }
     
+    // === 模板操作 ===
+    
+    /// 将项目设置（不含图片和标记）保存为模板
+    pub fn save_as_template(&self, project_id: ProjectId, name: String) -> CoreResult<ProjectTemplate> {
+        let project = storage::get_project_storage(project_id)?
+            .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))?;
+        template::save_template(ProjectTemplate::from_project(name, &project))
+    }
+    
+    /// 将模板设置套用到已存在的项目
+    pub fn apply_template(&self, project_id: ProjectId, name: &str) -> CoreResult<bool> {
+        let template = template::get_template(name)?
+            .ok_or_else(|| CoreError::NotFound(format!("Template '{}' not found", name)))?;
+        storage::update_project_with_storage(project_id, |project| template.apply_to(project))
+    }
+    
+    /// 按模板新建项目（语言、阅读方向、标记类型、默认服务和术语表都取自模板）
+    pub fn create_from_template(&self, name: String, template_name: &str) -> CoreResult<ProjectId> {
+        let template = template::get_template(template_name)?
+            .ok_or_else(|| CoreError::NotFound(format!("Template '{}' not found", template_name)))?;
+        let project_id = storage::create_project_storage(name.clone())?;
+        storage::update_project_with_storage(project_id, |project| template.apply_to(project))?;
+        self.event_bus.publish(DomainEvent::ProjectCreated(project_id, name));
+        Ok(project_id)
+    }
+    
+    pub fn list_templates(&self) -> CoreResult<TemplateList> {
+        template::list_templates()
+    }
+    
+    pub fn delete_template(&self, name: &str) -> CoreResult<bool> {
+        template::delete_template(name)
+    }
+    
     // === 图片相关操作 ===
     
     pub fn get_project_images(&self, project_id: u32) -> Vec<ImageDTO> {
@@ -156,7 +352,10 @@ impl ProjectService {
         }
     }
     
+    /// 与项目中已有图片重名时先给新图片的名称加序号后缀
     pub fn add_image_to_project_core(&self, project_id: ProjectId, image_id: ImageId) -> CoreResult<bool> {
+        let others = storage::get_project_image_ids_storage(project_id)?;
+        crate::storage::image::make_image_name_unique_storage(image_id, &others)?;
         storage::add_image_to_project_storage(project_id, image_id)
     }
     
@@ -183,6 +382,21 @@ impl ProjectService {
         storage::reorder_project_images_storage(project_id, new_order)
     }
     
+    /// 检查项目数据一致性，repair为true时修复可以安全修复的问题（一次撤销操作）
+    pub fn check_integrity(&self, project_id: ProjectId, repair: bool) -> CoreResult<super::IntegrityReport> {
+        super::integrity::check_integrity(&self.event_bus, project_id, repair)
+    }
+    
+    /// 复制为独立的新项目（无文件路径，撤销记录为空）
+    pub fn duplicate_project(&self, project_id: ProjectId, new_name: String) -> CoreResult<ProjectId> {
+        super::duplicate::duplicate_project(&self.event_bus, project_id, new_name)
+    }
+    
+    /// 按感知哈希查找重复图片，threshold为允许的最大汉明距离
+    pub fn find_duplicate_images(&self, project_id: ProjectId, threshold: Option<u32>) -> CoreResult<Vec<super::DuplicateGroup>> {
+        super::duplicate_images::find_duplicate_images(project_id, threshold)
+    }
+    
     pub fn find_project_by_image(&self, image_id: ImageId) -> CoreResult<Option<ProjectId>> {
         storage::find_project_by_image_storage(image_id)
     }
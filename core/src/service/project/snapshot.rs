@@ -0,0 +1,557 @@
+// 命名快照（还原点）- 保存项目的标记、设置、术语表和海兔缓存（不含图片），之后可以还原到该状态
+// 还原是一次可撤销的操作；内容没有变化时不重复创建；每个项目保留的快照数有上限，超出时删除最旧的
+// 桌面端压缩后保存到应用数据目录，WASM端通过JS提供的回调持久化
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+#[cfg(not(feature = "wasm"))]
+use std::path::PathBuf;
+use std::sync::RwLock;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use md5::{Digest, Md5};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, ImageId, Logger, ProjectId, MARKER_ID_GENERATOR};
+use crate::common::events::get_timestamp_millis;
+use crate::common::events::schema::{BusinessEvent, SnapshotRestoredEvent};
+use crate::service::get_service;
+use crate::service::undo_redo::{ActionType, UndoRedoAction};
+use crate::storage::bunny_cache::BunnyCacheData;
+use crate::storage::marker::Marker;
+use crate::storage::project::Project;
+use crate::storage::state::APP_STATE;
+use crate::storage::traits::Storage;
+
+/// 每个项目默认保留的快照数
+pub const DEFAULT_MAX_SNAPSHOTS_PER_PROJECT: usize = 20;
+
+/// 快照中的一张图片，name用于在其他会话中找回对应的图片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotImage {
+    pub image_id: ImageId,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub markers: Vec<Marker>,
+}
+
+/// 快照保存的项目状态，也是还原操作撤销记录中的新旧状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// 项目设置和术语表；名称、图片列表、文件路径等还原时保留当前值
+    pub project: Project,
+    /// 按项目中的图片顺序
+    pub images: Vec<SnapshotImage>,
+    pub bunny_cache: Vec<BunnyCacheData>,
+}
+
+impl ProjectState {
+    pub fn marker_count(&self) -> usize {
+        self.images.iter().map(|image| image.markers.len()).sum()
+    }
+
+    /// 内容哈希，用于跳过与上一个快照相同的快照；serde_json的对象按键排序，哈希与HashMap的遍历顺序无关
+    pub fn content_hash(&self) -> CoreResult<String> {
+        let value = serde_json::to_value(self)?;
+        let mut hasher = Md5::new();
+        hasher.update(serde_json::to_vec(&value)?);
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub name: String,
+    pub project_name: String,
+    /// 项目的bf路径，之后的会话按它找回快照所属的项目
+    #[serde(default)]
+    pub file_path: Option<String>,
+    pub created_at: u64,
+    pub content_hash: String,
+    pub marker_count: usize,
+    /// 压缩后的字节数
+    #[serde(skip_deserializing)]
+    pub size: usize,
+    /// 本次会话中创建快照的项目，从磁盘加载的快照没有
+    #[serde(skip)]
+    pub session_project: Option<ProjectId>,
+}
+
+impl SnapshotInfo {
+    fn belongs_to(&self, project: &Project) -> bool {
+        self.session_project == Some(project.id)
+            || (self.file_path.is_some() && self.file_path == project.file_path)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    info: SnapshotInfo,
+    state: ProjectState,
+}
+
+struct SnapshotRecord {
+    info: SnapshotInfo,
+    /// gzip压缩的SnapshotFile JSON，也是持久化的内容
+    data: Vec<u8>,
+}
+
+fn encode_snapshot(file: &SnapshotFile) -> CoreResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(file)?)?;
+    Ok(encoder.finish()?)
+}
+
+fn decode_snapshot(data: &[u8]) -> CoreResult<SnapshotFile> {
+    let mut json = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut json)
+        .map_err(|e| CoreError::InvalidFormat { expected: "gzip snapshot".to_string(), found: e.to_string() })?;
+    let mut file: SnapshotFile = serde_json::from_slice(&json)?;
+    file.info.size = data.len();
+    Ok(file)
+}
+
+pub struct SnapshotStore {
+    /// 按创建顺序
+    records: Vec<SnapshotRecord>,
+    limit: usize,
+    #[cfg(not(feature = "wasm"))]
+    dir: Option<PathBuf>,
+}
+
+impl SnapshotStore {
+    fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            limit: DEFAULT_MAX_SNAPSHOTS_PER_PROJECT,
+            #[cfg(not(feature = "wasm"))]
+            dir: None,
+        }
+    }
+
+    fn add_loaded(&mut self, data: Vec<u8>) -> CoreResult<SnapshotInfo> {
+        let info = decode_snapshot(&data)?.info;
+        self.records.retain(|record| record.info.id != info.id);
+        self.records.push(SnapshotRecord { info: info.clone(), data });
+        self.records.sort_by_key(|record| record.info.created_at);
+        Ok(info)
+    }
+
+    /// data为None表示删除
+    fn persist(&self, id: &str, data: Option<&[u8]>) -> CoreResult<()> {
+        #[cfg(not(feature = "wasm"))]
+        {
+            if let Some(dir) = &self.dir {
+                std::fs::create_dir_all(dir)?;
+                let path = dir.join(format!("{}.snap", id));
+                match data {
+                    Some(data) => std::fs::write(path, data)?,
+                    None => {
+                        if path.exists() {
+                            std::fs::remove_file(path)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            SNAPSHOT_PERSIST_CALLBACK.with(|cb| {
+                if let Some(callback) = cb.borrow().as_ref() {
+                    callback(id.to_string(), data.map(|data| data.to_vec()));
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, id: &str) -> CoreResult<bool> {
+        let before = self.records.len();
+        self.records.retain(|record| record.info.id != id);
+        if self.records.len() == before {
+            return Ok(false);
+        }
+        self.persist(id, None)?;
+        Ok(true)
+    }
+
+    /// 项目的快照超过上限时删除最旧的
+    fn prune(&mut self, project: &Project) -> CoreResult<Vec<String>> {
+        let owned: Vec<String> = self.records.iter()
+            .filter(|record| record.info.belongs_to(project))
+            .map(|record| record.info.id.clone())
+            .collect();
+        let excess = owned.len().saturating_sub(self.limit);
+        let removed: Vec<String> = owned.into_iter().take(excess).collect();
+        for id in &removed {
+            self.remove(id)?;
+        }
+        Ok(removed)
+    }
+}
+
+pub static SNAPSHOT_STORE: Lazy<RwLock<SnapshotStore>> = Lazy::new(|| RwLock::new(SnapshotStore::new()));
+
+// WASM端的持久化回调，参数为快照ID和压缩后的快照（删除时为None）
+#[cfg(feature = "wasm")]
+type SnapshotPersistCallback = Box<dyn Fn(String, Option<Vec<u8>>)>;
+
+#[cfg(feature = "wasm")]
+thread_local! {
+    static SNAPSHOT_PERSIST_CALLBACK: std::cell::RefCell<Option<SnapshotPersistCallback>> = std::cell::RefCell::new(None);
+}
+
+#[cfg(feature = "wasm")]
+pub fn set_snapshot_persist_callback<F>(callback: F)
+where
+    F: Fn(String, Option<Vec<u8>>) + 'static,
+{
+    SNAPSHOT_PERSIST_CALLBACK.with(|cb| {
+        *cb.borrow_mut() = Some(Box::new(callback));
+    });
+}
+
+/// 设置快照目录并加载其中的快照（桌面端），返回无法读取的文件
+#[cfg(not(feature = "wasm"))]
+pub fn set_snapshot_dir(dir: PathBuf) -> CoreResult<Vec<String>> {
+    let mut store = SNAPSHOT_STORE.write()?;
+    let mut warnings = Vec::new();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("snap") {
+                continue;
+            }
+            if let Err(e) = store.add_loaded(std::fs::read(&path)?) {
+                warnings.push(format!("无法读取快照 {}: {}", path.display(), e));
+            }
+        }
+    }
+    for warning in &warnings {
+        Logger::warn(warning);
+    }
+    store.dir = Some(dir);
+    Ok(warnings)
+}
+
+/// 加载一个持久化的快照（WASM端启动时由JS逐个传入）
+pub fn load_snapshot_data(data: Vec<u8>) -> CoreResult<SnapshotInfo> {
+    SNAPSHOT_STORE.write()?.add_loaded(data)
+}
+
+/// 每个项目保留的快照数，只影响之后创建的快照
+pub fn set_snapshot_limit(limit: usize) -> CoreResult<()> {
+    SNAPSHOT_STORE.write()?.limit = limit.max(1);
+    Ok(())
+}
+
+fn get_project(project_id: ProjectId) -> CoreResult<Project> {
+    APP_STATE.get_project(project_id)?
+        .ok_or_else(|| CoreError::NotFound(format!("Project with id {} not found", project_id.0)))
+}
+
+/// 读取项目当前的状态
+pub fn capture_project_state(project_id: ProjectId) -> CoreResult<ProjectState> {
+    let project = get_project(project_id)?;
+    let mut images = Vec::with_capacity(project.image_ids.len());
+    let mut bunny_cache = Vec::new();
+    for image_id in &project.image_ids {
+        let markers = crate::storage::marker::get_image_markers_storage(*image_id)?;
+        for marker in &markers {
+            if let Some(cache) = crate::storage::bunny_cache::get_bunny_cache_storage(marker.id)? {
+                bunny_cache.push(cache);
+            }
+        }
+        let name = APP_STATE.get_image(*image_id)?.and_then(|image| image.metadata.name.clone());
+        images.push(SnapshotImage { image_id: *image_id, name, markers });
+    }
+    Ok(ProjectState { project, images, bunny_cache })
+}
+
+/// 用state替换项目当前的标记、设置和海兔缓存；state中不在项目里的图片被忽略
+pub fn apply_project_state(project_id: ProjectId, state: &ProjectState) -> CoreResult<()> {
+    let marker_service = &get_service().marker_service;
+    let project = get_project(project_id)?;
+    for image_id in &project.image_ids {
+        for marker in marker_service.take_image_markers(*image_id)? {
+            crate::storage::bunny_cache::clear_bunny_cache_storage(marker.id)?;
+        }
+    }
+
+    {
+        let mut projects = APP_STATE.projects.write()?;
+        if let Some(current) = projects.get_mut(&project_id) {
+            *current = Project {
+                id: current.id,
+                name: std::mem::take(&mut current.name),
+                image_ids: std::mem::take(&mut current.image_ids),
+                file_path: current.file_path.take(),
+                pinned_images: std::mem::take(&mut current.pinned_images),
+                watch_files: current.watch_files,
+                ..state.project.clone()
+            };
+        }
+    }
+
+    let images: HashSet<ImageId> = project.image_ids.iter().copied().collect();
+    let markers: Vec<Marker> = state.images.iter()
+        .filter(|image| images.contains(&image.image_id))
+        .flat_map(|image| image.markers.iter().cloned())
+        .collect();
+    let restored: HashSet<_> = markers.iter().map(|marker| marker.id).collect();
+    marker_service.restore_image_markers(markers)?;
+
+    let mut cache = APP_STATE.bunny_cache.write()?;
+    for entry in state.bunny_cache.iter().filter(|entry| restored.contains(&entry.marker_id)) {
+        cache.insert(entry.marker_id, entry.clone())?;
+    }
+    drop(cache);
+
+    get_service().stats_service.notify_text_changed(project_id);
+    Ok(())
+}
+
+/// 把快照中的状态对应到项目当前的图片：本次会话的快照按图片ID，其余按图片名
+/// 标记换成新ID，以免与撤销栈中记录的标记冲突；返回对应不上的图片数
+fn resolve_state(project: &Project, state: ProjectState, same_session: bool) -> CoreResult<(ProjectState, usize)> {
+    let mut by_name: HashMap<String, ImageId> = HashMap::new();
+    for image_id in project.image_ids.iter().rev() {
+        if let Some(name) = APP_STATE.get_image(*image_id)?.and_then(|image| image.metadata.name.clone()) {
+            by_name.insert(name, *image_id);
+        }
+    }
+
+    let mut used = HashSet::new();
+    let mut new_ids = HashMap::new();
+    let mut images = Vec::new();
+    let mut unmatched = 0;
+    for image in state.images {
+        let target = if same_session && project.image_ids.contains(&image.image_id) {
+            Some(image.image_id)
+        } else {
+            image.name.as_ref().and_then(|name| by_name.get(name).copied())
+        };
+        let Some(target) = target.filter(|target| used.insert(*target)) else {
+            unmatched += 1;
+            continue;
+        };
+        let markers = image.markers.into_iter()
+            .map(|mut marker| {
+                let id = MARKER_ID_GENERATOR.next();
+                new_ids.insert(marker.id, id);
+                marker.id = id;
+                marker.image_id = target;
+                marker
+            })
+            .collect();
+        images.push(SnapshotImage { image_id: target, name: image.name, markers });
+    }
+
+    let bunny_cache = state.bunny_cache.into_iter()
+        .filter_map(|mut entry| {
+            entry.marker_id = *new_ids.get(&entry.marker_id)?;
+            Some(entry)
+        })
+        .collect();
+    Ok((ProjectState { project: state.project, images, bunny_cache }, unmatched))
+}
+
+/// 创建快照；内容与该项目最近一个快照相同时不创建，直接返回那个快照
+pub fn create_snapshot(project_id: ProjectId, name: String) -> CoreResult<SnapshotInfo> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(CoreError::ValidationFailed {
+            field: "name".to_string(),
+            reason: "快照名称不能为空".to_string(),
+        });
+    }
+    let state = capture_project_state(project_id)?;
+    let content_hash = state.content_hash()?;
+
+    let mut store = SNAPSHOT_STORE.write()?;
+    if let Some(latest) = store.records.iter().rev().find(|record| record.info.belongs_to(&state.project))
+        && latest.info.content_hash == content_hash
+    {
+        Logger::debug(&format!("Project {} unchanged since snapshot {}", project_id.0, latest.info.id));
+        return Ok(latest.info.clone());
+    }
+
+    let mut info = SnapshotInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        project_name: state.project.name.clone(),
+        file_path: state.project.file_path.clone(),
+        created_at: get_timestamp_millis(),
+        content_hash,
+        marker_count: state.marker_count(),
+        size: 0,
+        session_project: Some(project_id),
+    };
+    let project = state.project.clone();
+    let file = SnapshotFile { info: info.clone(), state };
+    let data = encode_snapshot(&file)?;
+    info.size = data.len();
+
+    store.persist(&info.id, Some(&data))?;
+    store.records.push(SnapshotRecord { info: info.clone(), data });
+    let removed = store.prune(&project)?;
+    if !removed.is_empty() {
+        Logger::debug(&format!("Removed {} old snapshots of project {}", removed.len(), project_id.0));
+    }
+    Ok(info)
+}
+
+/// 项目的快照，最新的在前
+pub fn list_snapshots(project_id: ProjectId) -> CoreResult<Vec<SnapshotInfo>> {
+    let project = get_project(project_id)?;
+    let store = SNAPSHOT_STORE.read()?;
+    Ok(store.records.iter().rev()
+        .filter(|record| record.info.belongs_to(&project))
+        .map(|record| record.info.clone())
+        .collect())
+}
+
+/// 还原到快照，作为一次撤销记录；图片不在快照中时其标记被清空
+pub fn restore_snapshot(project_id: ProjectId, snapshot_id: &str) -> CoreResult<()> {
+    let project = get_project(project_id)?;
+    let file = {
+        let store = SNAPSHOT_STORE.read()?;
+        let record = store.records.iter()
+            .find(|record| record.info.id == snapshot_id && record.info.belongs_to(&project))
+            .ok_or_else(|| CoreError::NotFound(format!("Snapshot {} not found", snapshot_id)))?;
+        decode_snapshot(&record.data)?
+    };
+
+    let same_session = file.info.session_project == Some(project_id);
+    let (new_state, unmatched) = resolve_state(&project, file.state, same_session)?;
+    if unmatched > 0 {
+        Logger::warn(&format!("{} images in snapshot {} are not in the project, their markers are skipped", unmatched, snapshot_id));
+    }
+    let old_state = capture_project_state(project_id)?;
+    apply_project_state(project_id, &new_state)?;
+
+    let action = UndoRedoAction::new(ActionType::RestoreSnapshot {
+        snapshot_id: file.info.id.clone(),
+        name: file.info.name.clone(),
+        old_state: Box::new(old_state),
+        new_state: Box::new(new_state),
+        undone: false,
+    }, project_id).with_description(format!("还原快照 {}", file.info.name));
+    get_service().undo_redo_service.record_action(action)?;
+
+    let _ = SnapshotRestoredEvent::new(project_id, file.info.id, file.info.name, false).emit();
+    Ok(())
+}
+
+pub fn delete_snapshot(snapshot_id: &str) -> CoreResult<bool> {
+    SNAPSHOT_STORE.write()?.remove(snapshot_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::MarkerId;
+    use crate::storage::ImageFormat;
+
+    fn setup(name: &str) -> (ProjectId, ImageId) {
+        let service = get_service();
+        let project_id = service.project_service.create_project(name.to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(ImageFormat::Png, vec![0u8; 16], Some(format!("{}.png", name))).unwrap();
+        service.project_service.add_image_to_project(project_id.0, image_id.0);
+        (project_id, image_id)
+    }
+
+    fn translations(image_id: ImageId) -> Vec<String> {
+        crate::storage::marker::get_image_markers_storage(image_id).unwrap()
+            .into_iter()
+            .map(|marker| marker.translation)
+            .collect()
+    }
+
+    #[test]
+    fn test_restore_is_undoable_and_unchanged_snapshots_deduplicated() {
+        let service = get_service();
+        let (project_id, image_id) = setup("snapshot_restore");
+        let marker_id = service.marker_service.add_point_marker(image_id.0, 0.5, 0.5, Some("初稿".to_string())).unwrap();
+        crate::storage::bunny_cache::update_original_text_storage(MarkerId::from(marker_id), "原文".to_string(), "ocr".to_string()).unwrap();
+        service.project_service.set_notes(project_id, "敬称保留".to_string()).unwrap();
+
+        let first = create_snapshot(project_id, "审校前".to_string()).unwrap();
+        let again = create_snapshot(project_id, "没有改动".to_string()).unwrap();
+        assert_eq!(again.id, first.id);
+        assert_eq!(first.marker_count, 1);
+
+        service.marker_service.update_marker_translation(marker_id, "改稿".to_string());
+        service.marker_service.add_point_marker(image_id.0, 0.1, 0.1, Some("新增".to_string())).unwrap();
+        service.project_service.set_notes(project_id, String::new()).unwrap();
+        let second = create_snapshot(project_id, "审校后".to_string()).unwrap();
+        assert_ne!(second.id, first.id);
+        assert_eq!(list_snapshots(project_id).unwrap().iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), [second.id.as_str(), first.id.as_str()]);
+
+        restore_snapshot(project_id, &first.id).unwrap();
+        assert_eq!(translations(image_id), ["初稿"]);
+        assert_eq!(APP_STATE.get_project(project_id).unwrap().unwrap().notes, "敬称保留");
+        let restored = crate::storage::image::get_image_marker_ids_storage(image_id).unwrap();
+        let cache = crate::storage::bunny_cache::get_bunny_cache_storage(restored[0]).unwrap().unwrap();
+        assert_eq!(cache.original_text.as_deref(), Some("原文"));
+
+        service.undo_redo_service.undo(project_id.0);
+        assert_eq!(translations(image_id), ["改稿", "新增"]);
+        assert_eq!(APP_STATE.get_project(project_id).unwrap().unwrap().notes, "");
+        service.undo_redo_service.redo(project_id.0);
+        assert_eq!(translations(image_id), ["初稿"]);
+
+        assert!(delete_snapshot(&first.id).unwrap());
+        assert!(!delete_snapshot(&first.id).unwrap());
+        assert!(restore_snapshot(project_id, &first.id).is_err());
+        delete_snapshot(&second.id).unwrap();
+    }
+
+    #[test]
+    fn test_snapshots_capped_per_project() {
+        let service = get_service();
+        let (project_id, image_id) = setup("snapshot_cap");
+        let marker_id = service.marker_service.add_point_marker(image_id.0, 0.5, 0.5, None).unwrap();
+        let mut created = Vec::new();
+        for n in 0..=DEFAULT_MAX_SNAPSHOTS_PER_PROJECT {
+            service.marker_service.update_marker_translation(marker_id, format!("第{}稿", n));
+            created.push(create_snapshot(project_id, format!("v{}", n)).unwrap().id);
+        }
+        let listed = list_snapshots(project_id).unwrap();
+        assert_eq!(listed.len(), DEFAULT_MAX_SNAPSHOTS_PER_PROJECT);
+        assert!(!listed.iter().any(|s| s.id == created[0]));
+        for id in created {
+            delete_snapshot(&id).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_loaded_snapshot_matches_images_by_name() {
+        let service = get_service();
+        let (project_id, image_id) = setup("snapshot_reload");
+        service.marker_service.add_point_marker(image_id.0, 0.5, 0.5, Some("旧会话".to_string())).unwrap();
+        let mut project = APP_STATE.get_project(project_id).unwrap().unwrap();
+        project.file_path = Some("/tmp/snapshot_reload.bf".to_string());
+        APP_STATE.projects.write().unwrap().insert(project_id, project).unwrap();
+        let info = create_snapshot(project_id, "上次".to_string()).unwrap();
+
+        // 模拟重新启动：快照从持久化数据加载，项目和图片换了新ID
+        let data = SNAPSHOT_STORE.read().unwrap().records.iter().find(|r| r.info.id == info.id).unwrap().data.clone();
+        delete_snapshot(&info.id).unwrap();
+        let loaded = load_snapshot_data(data).unwrap();
+        assert_eq!(loaded.session_project, None);
+
+        let (reopened, new_image) = setup("snapshot_reload");
+        let mut project = APP_STATE.get_project(reopened).unwrap().unwrap();
+        project.file_path = Some("/tmp/snapshot_reload.bf".to_string());
+        APP_STATE.projects.write().unwrap().insert(reopened, project).unwrap();
+
+        assert!(list_snapshots(reopened).unwrap().iter().any(|s| s.id == info.id));
+        restore_snapshot(reopened, &info.id).unwrap();
+        assert_eq!(translations(new_image), ["旧会话"]);
+        delete_snapshot(&info.id).unwrap();
+    }
+}
@@ -0,0 +1,437 @@
+// Project templates - 保存项目设置（不含图片和标记），新建项目时套用
+// 包括语言、阅读方向、标记类型、默认服务和术语表；内置模板总是可用且不能覆盖或删除
+// 样式映射和嵌字约束（框内/框外、横排/竖排、标签）都在项目的标记类型里，随marker_types保存，项目没有其他嵌字设置
+// 桌面端持久化到应用数据目录，WASM端通过JS提供的回调持久化
+use std::collections::HashMap;
+use std::sync::RwLock;
+#[cfg(not(feature = "wasm"))]
+use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::common::{CoreError, CoreResult, Language, Logger, MarkerTypePreset, ProjectGlossary, ReadingDirection, ServiceDefaults};
+use crate::storage::project::Project;
+use crate::common::events::get_timestamp_millis;
+
+/// 模板JSON的当前schema版本
+pub const TEMPLATE_SCHEMA_VERSION: u32 = 1;
+
+// 当前版本已知的字段，其余字段会被忽略并给出警告
+const KNOWN_TEMPLATE_FIELDS: &[&str] = &[
+    "schema_version",
+    "name",
+    "source_language",
+    "target_language",
+    "created_at",
+    "reading_direction",
+    "marker_types",
+    "default_services",
+    "glossary",
+    "builtin",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    pub name: String,
+    #[serde(default = "Language::default_source")]
+    pub source_language: Language,
+    #[serde(default = "Language::default_target")]
+    pub target_language: Language,
+    #[serde(default)]
+    pub created_at: u64,
+    /// 缺省时按源语言决定
+    #[serde(default)]
+    pub reading_direction: Option<ReadingDirection>,
+    /// 标记类型到样式的映射，导出LabelPlus和嵌字数据时使用
+    #[serde(default)]
+    pub marker_types: Vec<MarkerTypePreset>,
+    #[serde(default)]
+    pub default_services: ServiceDefaults,
+    #[serde(default)]
+    pub glossary: ProjectGlossary,
+    /// 内置模板，只在列表中返回，不会持久化
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+fn default_schema_version() -> u32 {
+    TEMPLATE_SCHEMA_VERSION
+}
+
+/// list_templates的返回值，warnings包含加载时忽略的未知字段等信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateList {
+    pub templates: Vec<ProjectTemplate>,
+    pub warnings: Vec<String>,
+}
+
+/// 解析单个模板JSON，返回模板和警告列表
+pub fn parse_template(json: &str) -> CoreResult<(ProjectTemplate, Vec<String>)> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let object = value.as_object().ok_or_else(|| CoreError::InvalidFormat {
+        expected: "template object".to_string(),
+        found: value.to_string(),
+    })?;
+
+    let name = object.get("name").and_then(|v| v.as_str()).unwrap_or("<unnamed>").to_string();
+    let mut warnings = Vec::new();
+    for key in object.keys() {
+        if !KNOWN_TEMPLATE_FIELDS.contains(&key.as_str()) {
+            warnings.push(format!("模板 '{}' 中的未知字段 '{}' 已忽略", name, key));
+        }
+    }
+
+    let template: ProjectTemplate = serde_json::from_value(value)?;
+    if template.schema_version > TEMPLATE_SCHEMA_VERSION {
+        warnings.push(format!(
+            "模板 '{}' 的schema版本 {} 高于当前支持的版本 {}",
+            template.name, template.schema_version, TEMPLATE_SCHEMA_VERSION
+        ));
+    }
+
+    Ok((template, warnings))
+}
+
+/// 内置模板，总是出现在模板列表中
+pub fn builtin_templates() -> Vec<ProjectTemplate> {
+    let manga = |name: &str, target_language: Language| {
+        let mut template = ProjectTemplate::new(name.to_string(), Language::Japanese, target_language);
+        template.created_at = 0;
+        template.reading_direction = Some(ReadingDirection::RightToLeft);
+        template.marker_types = default_marker_types();
+        template.builtin = true;
+        template
+    };
+    vec![
+        manga("JP→EN manga", Language::English),
+        manga("JP→简中", Language::SimplifiedChinese),
+    ]
+}
+
+fn builtin_template(name: &str) -> Option<ProjectTemplate> {
+    builtin_templates().into_iter().find(|t| t.name == name)
+}
+
+// LabelPlus默认的框内/框外分组
+fn default_marker_types() -> Vec<MarkerTypePreset> {
+    crate::service::io::labelplus::get_default_style_mapping()
+        .type_mappings
+        .into_iter()
+        .map(|mapping| MarkerTypePreset {
+            name: mapping.name,
+            overlay_text: mapping.style.overlay_text,
+            horizontal: mapping.style.horizontal,
+            tag: mapping.style.tag,
+        })
+        .collect()
+}
+
+pub struct TemplateStore {
+    templates: HashMap<String, ProjectTemplate>,
+    warnings: Vec<String>,
+    #[cfg(not(feature = "wasm"))]
+    dir: Option<PathBuf>,
+}
+
+impl TemplateStore {
+    fn new() -> Self {
+        Self {
+            templates: HashMap::new(),
+            warnings: Vec::new(),
+            #[cfg(not(feature = "wasm"))]
+            dir: None,
+        }
+    }
+
+    fn validate_name(name: &str) -> CoreResult<()> {
+        if name.trim().is_empty() {
+            return Err(CoreError::ValidationFailed {
+                field: "name".to_string(),
+                reason: "模板名称不能为空".to_string(),
+            });
+        }
+        if name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']) || name.starts_with('.') {
+            return Err(CoreError::ValidationFailed {
+                field: "name".to_string(),
+                reason: format!("模板名称包含非法字符: {}", name),
+            });
+        }
+        if builtin_template(name).is_some() {
+            return Err(CoreError::ValidationFailed {
+                field: "name".to_string(),
+                reason: format!("不能覆盖内置模板: {}", name),
+            });
+        }
+        Ok(())
+    }
+
+    /// 从JSON数组加载模板（覆盖当前内存中的模板）
+    fn load_from_json_list(&mut self, entries: Vec<String>) -> Vec<String> {
+        let mut warnings = Vec::new();
+        self.templates.clear();
+        for entry in entries {
+            match parse_template(&entry) {
+                Ok((template, mut entry_warnings)) => {
+                    warnings.append(&mut entry_warnings);
+                    self.templates.insert(template.name.clone(), template);
+                }
+                Err(e) => warnings.push(format!("无法解析模板: {}", e)),
+            }
+        }
+        for warning in &warnings {
+            Logger::warn(warning);
+        }
+        self.warnings = warnings.clone();
+        warnings
+    }
+
+    fn persist(&self, name: &str) -> CoreResult<()> {
+        #[cfg(not(feature = "wasm"))]
+        {
+            if let Some(dir) = &self.dir {
+                std::fs::create_dir_all(dir)?;
+                let path = dir.join(format!("{}.json", name));
+                match self.templates.get(name) {
+                    Some(template) => std::fs::write(path, serde_json::to_string_pretty(template)?)?,
+                    None => {
+                        if path.exists() {
+                            std::fs::remove_file(path)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            let _ = name;
+            let json = serde_json::to_string(&self.templates.values().collect::<Vec<_>>())?;
+            TEMPLATE_PERSIST_CALLBACK.with(|cb| {
+                if let Some(callback) = cb.borrow().as_ref() {
+                    callback(json);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+pub static TEMPLATE_STORE: Lazy<RwLock<TemplateStore>> = Lazy::new(|| RwLock::new(TemplateStore::new()));
+
+// WASM端的持久化回调，参数为所有模板的JSON数组
+#[cfg(feature = "wasm")]
+type TemplatePersistCallback = Box<dyn Fn(String)>;
+
+#[cfg(feature = "wasm")]
+thread_local! {
+    static TEMPLATE_PERSIST_CALLBACK: std::cell::RefCell<Option<TemplatePersistCallback>> = std::cell::RefCell::new(None);
+}
+
+#[cfg(feature = "wasm")]
+pub fn set_template_persist_callback<F>(callback: F)
+where
+    F: Fn(String) + 'static,
+{
+    TEMPLATE_PERSIST_CALLBACK.with(|cb| {
+        *cb.borrow_mut() = Some(Box::new(callback));
+    });
+}
+
+/// 设置模板目录并从中加载已有模板（桌面端）
+#[cfg(not(feature = "wasm"))]
+pub fn set_template_dir(dir: PathBuf) -> CoreResult<Vec<String>> {
+    let mut entries = Vec::new();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                entries.push(std::fs::read_to_string(&path)?);
+            }
+        }
+    }
+
+    let mut store = TEMPLATE_STORE.write()?;
+    store.dir = Some(dir);
+    Ok(store.load_from_json_list(entries))
+}
+
+/// 从JSON数组加载模板（WASM端由JS在启动时传入持久化的数据）
+pub fn load_templates_json(json: &str) -> CoreResult<Vec<String>> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(json)?;
+    let entries = values.into_iter().map(|v| v.to_string()).collect();
+    let mut store = TEMPLATE_STORE.write()?;
+    Ok(store.load_from_json_list(entries))
+}
+
+pub fn save_template(template: ProjectTemplate) -> CoreResult<ProjectTemplate> {
+    TemplateStore::validate_name(&template.name)?;
+    let mut store = TEMPLATE_STORE.write()?;
+    let name = template.name.clone();
+    store.templates.insert(name.clone(), template.clone());
+    store.persist(&name)?;
+    Ok(template)
+}
+
+pub fn get_template(name: &str) -> CoreResult<Option<ProjectTemplate>> {
+    if let Some(template) = builtin_template(name) {
+        return Ok(Some(template));
+    }
+    let store = TEMPLATE_STORE.read()?;
+    Ok(store.templates.get(name).cloned())
+}
+
+pub fn list_templates() -> CoreResult<TemplateList> {
+    let store = TEMPLATE_STORE.read()?;
+    let mut user_templates: Vec<ProjectTemplate> = store.templates.values()
+        .filter(|t| builtin_template(&t.name).is_none())
+        .cloned()
+        .collect();
+    user_templates.sort_by(|a, b| a.name.cmp(&b.name));
+    // 内置模板排在前面
+    let mut templates = builtin_templates();
+    templates.append(&mut user_templates);
+    Ok(TemplateList {
+        templates,
+        warnings: store.warnings.clone(),
+    })
+}
+
+pub fn delete_template(name: &str) -> CoreResult<bool> {
+    if builtin_template(name).is_some() {
+        return Err(CoreError::ValidationFailed {
+            field: "name".to_string(),
+            reason: format!("不能删除内置模板: {}", name),
+        });
+    }
+    let mut store = TEMPLATE_STORE.write()?;
+    if store.templates.remove(name).is_none() {
+        return Ok(false);
+    }
+    store.persist(name)?;
+    Ok(true)
+}
+
+impl ProjectTemplate {
+    pub fn new(name: String, source_language: Language, target_language: Language) -> Self {
+        Self {
+            schema_version: TEMPLATE_SCHEMA_VERSION,
+            name,
+            source_language,
+            target_language,
+            created_at: get_timestamp_millis(),
+            reading_direction: None,
+            marker_types: Vec::new(),
+            default_services: ServiceDefaults::default(),
+            glossary: ProjectGlossary::default(),
+            builtin: false,
+        }
+    }
+
+    /// 从项目当前的设置生成模板
+    pub fn from_project(name: String, project: &Project) -> Self {
+        let mut template = Self::new(name, project.source_language, project.target_language);
+        template.reading_direction = Some(project.reading_direction);
+        template.marker_types = project.marker_types.clone();
+        template.default_services = project.default_services.clone();
+        template.glossary = project.glossary.clone();
+        template
+    }
+
+    /// 把模板设置写入项目
+    pub fn apply_to(&self, project: &mut Project) {
+        project.source_language = self.source_language;
+        project.target_language = self.target_language;
+        project.reading_direction = self.reading_direction
+            .unwrap_or_else(|| ReadingDirection::default_for(self.source_language));
+        project.marker_types = self.marker_types.clone();
+        project.default_services = self.default_services.clone();
+        project.glossary = self.glossary.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template_unknown_fields_warn() {
+        let json = r#"{"schema_version":1,"name":"manga","source_language":"japanese","target_language":"simplifiedChinese","glossary":{"forbiddenTerms":["哦"]},"font_presets":[]}"#;
+        let (template, warnings) = parse_template(json).unwrap();
+        assert_eq!(template.name, "manga");
+        assert_eq!(template.source_language, Language::Japanese);
+        assert_eq!(template.glossary.forbidden_terms, vec!["哦".to_string()]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("font_presets"));
+    }
+
+    #[test]
+    fn test_parse_template_newer_schema_warns() {
+        let json = r#"{"schema_version":99,"name":"future"}"#;
+        let (template, warnings) = parse_template(json).unwrap();
+        assert_eq!(template.schema_version, 99);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_save_list_delete_template() {
+        let template = ProjectTemplate::new("test_roundtrip".to_string(), Language::English, Language::TraditionalChinese);
+        save_template(template).unwrap();
+
+        let loaded = get_template("test_roundtrip").unwrap().unwrap();
+        assert_eq!(loaded.target_language, Language::TraditionalChinese);
+        assert!(list_templates().unwrap().templates.iter().any(|t| t.name == "test_roundtrip"));
+
+        assert!(delete_template("test_roundtrip").unwrap());
+        assert!(!delete_template("test_roundtrip").unwrap());
+        assert!(save_template(ProjectTemplate::new("../bad".to_string(), Language::English, Language::English)).is_err());
+    }
+
+    #[test]
+    fn test_style_mapping_survives_json_round_trip() {
+        let mut project = Project::new(crate::common::ProjectId(9003), "p".to_string());
+        project.marker_types = vec![
+            MarkerTypePreset { name: "SFX".to_string(), overlay_text: true, horizontal: true, tag: Some("sfx".to_string()) },
+            MarkerTypePreset { name: "旁白".to_string(), overlay_text: false, horizontal: false, tag: None },
+        ];
+        let json = serde_json::to_string(&ProjectTemplate::from_project("style".to_string(), &project)).unwrap();
+        let (template, warnings) = parse_template(&json).unwrap();
+        assert!(warnings.is_empty());
+
+        let mut other = Project::new(crate::common::ProjectId(9004), "q".to_string());
+        template.apply_to(&mut other);
+        assert_eq!(other.marker_types, project.marker_types);
+    }
+
+    #[test]
+    fn test_builtin_templates_reserved_and_applied() {
+        let names: Vec<String> = list_templates().unwrap().templates.into_iter()
+            .filter(|t| t.builtin)
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["JP→EN manga", "JP→简中"]);
+        assert!(save_template(ProjectTemplate::new("JP→简中".to_string(), Language::English, Language::English)).is_err());
+        assert!(delete_template("JP→EN manga").is_err());
+
+        let template = get_template("JP→EN manga").unwrap().unwrap();
+        let mut project = Project::new(crate::common::ProjectId(9001), "p".to_string());
+        project.reading_direction = ReadingDirection::LeftToRight;
+        template.apply_to(&mut project);
+        assert_eq!(project.target_language, Language::English);
+        assert_eq!(project.reading_direction, ReadingDirection::RightToLeft);
+        assert_eq!(project.marker_types, default_marker_types());
+
+        // 保存为用户模板再套用，设置保持一致
+        project.default_services.ocr = Some("manga-ocr".to_string());
+        project.glossary.entries.push(crate::common::GlossaryEntry { source: "先輩".to_string(), target: "前辈".to_string() });
+        let saved = save_template(ProjectTemplate::from_project("test_from_project".to_string(), &project)).unwrap();
+        assert!(!saved.builtin);
+        let mut other = Project::new(crate::common::ProjectId(9002), "q".to_string());
+        get_template("test_from_project").unwrap().unwrap().apply_to(&mut other);
+        assert_eq!(other.default_services.ocr.as_deref(), Some("manga-ocr"));
+        assert_eq!(other.marker_types, project.marker_types);
+        assert_eq!(other.glossary, project.glossary);
+        delete_template("test_from_project").unwrap();
+    }
+}
@@ -1,4 +1,6 @@
 // Stats service模块
 mod service;
+pub mod text;
 
-pub use service::StatsService;
\ No newline at end of file
+pub use service::StatsService;
+pub use text::{TextCountConfig, TextCount, TextStats, ImageTextStats, count_text};
@@ -1,13 +1,26 @@
 // Stats Service - 处理统计相关的业务逻辑
-use crate::common::ProjectId;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::common::{CoreResult, ImageId, MarkerId, ProjectId};
+use crate::common::events::schema::{BusinessEvent, TextStatsChangedEvent};
 use crate::api::image;
-use crate::api::stats::{ProjectStats, SingleProjectStats};
+use crate::api::stats::{CoreHealth, ProjectStats, SingleProjectStats};
+use crate::service::events::{DomainEvent, EventHandler};
+use crate::storage::state::APP_STATE;
+use crate::storage::traits::Storage;
+use super::text::{count_text, ImageTextStats, TextCountConfig, TextStats};
 
-pub struct StatsService;
+static HEALTH_TICK: AtomicU64 = AtomicU64::new(0);
+
+pub struct StatsService {
+    text_config: RwLock<TextCountConfig>,
+}
 
 impl StatsService {
     pub fn new() -> Self {
-        Self
+        Self {
+            text_config: RwLock::new(TextCountConfig::default()),
+        }
     }
     
     pub fn get_overall_stats(&self) -> ProjectStats {
@@ -47,4 +60,135 @@ impl StatsService {
             None
         }
     }
-}
\ No newline at end of file
+    
+    /// 心跳：每次调用tick加一，并带上内存占用，前端据此判断worker是否还活着
+    pub fn health(&self) -> CoreHealth {
+        let services = crate::service::get_service();
+        // wasm32上为线性内存大小，其它平台没有对应的值
+        #[cfg(target_arch = "wasm32")]
+        let linear_memory_bytes = Some(core::arch::wasm32::memory_size::<0>() as u64 * 65536);
+        #[cfg(not(target_arch = "wasm32"))]
+        let linear_memory_bytes = None;
+        CoreHealth {
+            tick: HEALTH_TICK.fetch_add(1, Ordering::Relaxed) + 1,
+            linear_memory_bytes,
+            image_cache_bytes: crate::service::image::byte_cache::stats().total_bytes,
+            image_storage_bytes: APP_STATE.images.read().map(|images| images.current_memory_usage() as u64).unwrap_or(0),
+            marker_count: services.marker_service.marker_count().unwrap_or(0),
+        }
+    }
+    
+    // === 字数统计 ===
+    
+    pub fn text_count_config(&self) -> TextCountConfig {
+        self.text_config.read().map(|config| *config).unwrap_or_default()
+    }
+    
+    pub fn set_text_count_config(&self, config: TextCountConfig) -> CoreResult<()> {
+        *self.text_config.write()? = config;
+        Ok(())
+    }
+    
+    /// 统计项目的原文（bunny缓存中的OCR结果）和译文字数，按图片顺序给出明细
+    pub fn text_stats(&self, project_id: ProjectId) -> CoreResult<TextStats> {
+        let config = self.text_count_config();
+        let image_ids = crate::storage::project::get_project_image_ids_storage(project_id)?;
+        
+        let mut stats = TextStats::default();
+        for image_id in image_ids {
+            // 只锁住当前图片所在的分片，统计期间其他图片仍可编辑
+            let markers = APP_STATE.markers.read_image(image_id)?;
+            let bunny_cache = APP_STATE.bunny_cache.read()?;
+            let mut image_stats = ImageTextStats::new(image_id);
+            image_stats.reviewed = APP_STATE.get_image(image_id)?.is_some_and(|image| image.metadata.reviewed);
+            for marker_id in markers.image_marker_ids(&image_id) {
+                let Some(marker) = markers.get(marker_id) else { continue };
+                let source = bunny_cache.get(marker_id)
+                    .and_then(|cache| cache.original_text.as_deref())
+                    .map(|text| count_text(text, &config))
+                    .unwrap_or_default();
+                image_stats.add(source, count_text(&marker.translation, &config));
+            }
+            stats.push_image(image_stats);
+        }
+        Ok(stats)
+    }
+    
+    /// 通知前端项目字数有变化，由前端按需重新拉取text_stats
+    pub fn notify_text_changed(&self, project_id: ProjectId) {
+        let _ = TextStatsChangedEvent::new(project_id).emit();
+    }
+    
+    pub fn notify_marker_text_changed(&self, marker_id: MarkerId) {
+        let image_id = match crate::storage::marker::get_marker_storage(marker_id) {
+            Ok(Some(marker)) => marker.image_id,
+            _ => return,
+        };
+        self.notify_image_text_changed(image_id);
+    }
+    
+    fn notify_image_text_changed(&self, image_id: ImageId) {
+        if let Ok(Some(project_id)) = crate::storage::project::find_project_by_image_storage(image_id) {
+            self.notify_text_changed(project_id);
+        }
+    }
+}
+
+impl EventHandler for StatsService {
+    fn handle(&self, event: &DomainEvent) {
+        match event {
+            DomainEvent::MarkerTranslationUpdated { id, .. }
+            | DomainEvent::MarkerFullUpdated { id, .. } => self.notify_marker_text_changed(*id),
+            DomainEvent::MarkerTranslationsBatchUpdated { project_id, .. }
+            | DomainEvent::MarkersImported { project_id, .. }
+            | DomainEvent::MarkerMovedToImage { project_id, .. } => self.notify_text_changed(*project_id),
+            DomainEvent::MarkerAddedToImage(image_id, _)
+            | DomainEvent::MarkerRemovedFromImage(image_id, _, _)
+            | DomainEvent::ImageMarkersCleared(image_id, _) => self.notify_image_text_changed(*image_id),
+            DomainEvent::ImageAddedToProject(project_id, _)
+            | DomainEvent::ImageRemovedFromProject(project_id, _)
+            | DomainEvent::ImageReviewChanged(project_id, _) => self.notify_text_changed(*project_id),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::get_service;
+    use crate::storage::bunny_cache::update_original_text_storage;
+
+    #[test]
+    fn test_text_stats_per_image() {
+        let service = get_service();
+        let project_id = crate::storage::project::create_project_storage("stats".to_string()).unwrap();
+        let mut image_ids = Vec::new();
+        for _ in 0..2 {
+            let image_id = crate::storage::image::add_image_from_binary_storage(
+                crate::storage::ImageFormat::Png, vec![0u8; 16], None
+            ).unwrap();
+            crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+            image_ids.push(image_id);
+        }
+        let a = service.marker_service.add_point_marker(image_ids[0].0, 10.0, 10.0, Some("Hello there".to_string())).unwrap();
+        service.marker_service.add_point_marker(image_ids[1].0, 10.0, 10.0, Some("你好".to_string())).unwrap();
+        update_original_text_storage(MarkerId::from(a), "こんにちは".to_string(), "test".to_string()).unwrap();
+
+        let stats = service.stats_service.text_stats(project_id).unwrap();
+        assert_eq!((stats.source_chars, stats.source_words), (5, 5));
+        assert_eq!((stats.translated_chars, stats.translated_words), (12, 4));
+        assert_eq!(stats.per_image.len(), 2);
+        assert_eq!(stats.per_image[1].translated_chars, 2);
+        assert_eq!(stats.per_image[1].source_chars, 0);
+
+        // 校对状态计入每张图片的进度，空白备注视为没有备注
+        assert!(service.image_service.set_image_reviewed(image_ids[1].0, true, Some("  ".to_string())));
+        let stats = service.stats_service.text_stats(project_id).unwrap();
+        assert_eq!(stats.reviewed_images, 1);
+        assert!(!stats.per_image[0].reviewed && stats.per_image[1].reviewed);
+        let metadata = service.image_service.get_image_metadata(image_ids[1].0).unwrap();
+        assert!(metadata.reviewed);
+        assert_eq!(metadata.review_note, None);
+    }
+}
@@ -0,0 +1,136 @@
+// Text stats - 原文/译文的字数统计（按字数计费和进度展示）
+use serde::{Deserialize, Serialize};
+use crate::common::ImageId;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TextCountConfig {
+    /// true时每个CJK字符计为一个词；false时连续的CJK字符整体计为一个词
+    pub cjk_char_as_word: bool,
+}
+
+impl Default for TextCountConfig {
+    fn default() -> Self {
+        Self { cjk_char_as_word: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextCount {
+    /// 非空白字符数（按Unicode码位）
+    pub chars: usize,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageTextStats {
+    pub image_id: ImageId,
+    pub source_chars: usize,
+    pub translated_chars: usize,
+    pub source_words: usize,
+    pub translated_words: usize,
+    /// 图片是否已校对
+    pub reviewed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TextStats {
+    pub source_chars: usize,
+    pub translated_chars: usize,
+    pub source_words: usize,
+    pub translated_words: usize,
+    pub reviewed_images: usize,
+    pub per_image: Vec<ImageTextStats>,
+}
+
+impl ImageTextStats {
+    pub fn new(image_id: ImageId) -> Self {
+        Self { image_id, source_chars: 0, translated_chars: 0, source_words: 0, translated_words: 0, reviewed: false }
+    }
+
+    pub fn add(&mut self, source: TextCount, translated: TextCount) {
+        self.source_chars += source.chars;
+        self.source_words += source.words;
+        self.translated_chars += translated.chars;
+        self.translated_words += translated.words;
+    }
+}
+
+impl TextStats {
+    pub fn push_image(&mut self, image: ImageTextStats) {
+        self.source_chars += image.source_chars;
+        self.translated_chars += image.translated_chars;
+        self.source_words += image.source_words;
+        self.translated_words += image.translated_words;
+        self.reviewed_images += image.reviewed as usize;
+        self.per_image.push(image);
+    }
+}
+
+/// 中日韩文字（汉字、假名、谚文及其标点以外的表意字符）
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x31F0..=0x31FF   // 片假名扩展
+        | 0x3400..=0x4DBF   // CJK扩展A
+        | 0x4E00..=0x9FFF   // CJK统一表意文字
+        | 0xAC00..=0xD7AF   // 谚文音节
+        | 0xF900..=0xFAFF   // CJK兼容表意文字
+        | 0xFF66..=0xFF9F   // 半角片假名
+        | 0x20000..=0x2FA1F // CJK扩展B及以后
+    )
+}
+
+/// 统计一段文本的字数和词数。标点不单独计词，CJK字符按配置计词
+pub fn count_text(text: &str, config: &TextCountConfig) -> TextCount {
+    let mut count = TextCount::default();
+    // 当前是否在一个拉丁词 / 连续CJK段中
+    let mut in_word = false;
+    let mut in_cjk_run = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_word = false;
+            in_cjk_run = false;
+            continue;
+        }
+        count.chars += 1;
+
+        if is_cjk(c) {
+            in_word = false;
+            if config.cjk_char_as_word || !in_cjk_run {
+                count.words += 1;
+            }
+            in_cjk_run = true;
+        } else if c.is_alphanumeric() || c == '\'' || c == '-' || c == '_' {
+            in_cjk_run = false;
+            if !in_word && c.is_alphanumeric() {
+                count.words += 1;
+                in_word = true;
+            }
+        } else {
+            // 标点结束当前的词
+            in_word = false;
+            in_cjk_run = false;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_latin_and_cjk() {
+        let per_char = TextCountConfig::default();
+        let per_run = TextCountConfig { cjk_char_as_word: false };
+
+        assert_eq!(count_text("Don't stop, Rin-chan!", &per_char), TextCount { chars: 19, words: 3 });
+        assert_eq!(count_text("你好，世界", &per_char), TextCount { chars: 5, words: 4 });
+        assert_eq!(count_text("你好，世界", &per_run), TextCount { chars: 5, words: 2 });
+        assert_eq!(count_text("こんにちはRin", &per_run), TextCount { chars: 8, words: 2 });
+        assert_eq!(count_text("  \n ", &per_char), TextCount::default());
+        // 扩展B的汉字按一个字符计
+        assert_eq!(count_text("𠀀", &per_char), TextCount { chars: 1, words: 1 });
+    }
+}
@@ -1,5 +1,5 @@
 // Action types and structures for undo/redo
-use crate::common::{CoreResult, ImageId, MarkerId, ProjectId, Language};
+use crate::common::{CoreResult, ImageId, MarkerId, ProjectId, Language, ReadingDirection};
 use crate::storage::marker::{Marker, MarkerStyle};
 use crate::storage::image::Image;
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,7 @@ pub enum ActionType {
         old_target: Language, 
         new_target: Language 
     },
+    UpdateReadingDirection { old_direction: ReadingDirection, new_direction: ReadingDirection },
     
     // Image actions
     AddImage { 
@@ -31,6 +32,7 @@ pub enum ActionType {
         new_order: Vec<ImageId> 
     },
     UpdateImage { id: ImageId, old_name: Option<String>, new_name: Option<String> },
+    SetImageLocked { id: ImageId, locked: bool },
     
     // Marker actions
     AddMarker { marker: Marker },
@@ -50,12 +52,29 @@ pub enum ActionType {
         old_geometry: (f64, f64, f64, f64),  // (x, y, width, height)
         new_geometry: (f64, f64, f64, f64)   // (x, y, width, height)
     },
+    UpdatePolygonGeometry { id: MarkerId, old_points: Vec<(f64, f64)>, new_points: Vec<(f64, f64)> },
     UpdateMarkerTranslation { id: MarkerId, old_trans: String, new_trans: String },
+    UpdateMarkerNote { id: MarkerId, old_note: Option<String>, new_note: Option<String> },
     UpdateMarkerStyle { id: MarkerId, old_style: MarkerStyle, new_style: MarkerStyle },
     UpdateMarkerOrder { id: MarkerId, image_id: ImageId, old_index: u32, new_index: u32 },
+    // 批量设置标记序号（压缩序号）: (id, 旧序号, 新序号)
+    SetMarkerIndices { image_id: ImageId, changes: crate::storage::marker::MarkerIndexChanges },
+    // 一致性修复：覆盖图片的marker_ids / 标记按图片索引
+    SetImageMarkerIds { image_id: ImageId, old_ids: Vec<MarkerId>, new_ids: Vec<MarkerId> },
+    SetMarkerIndexEntries { image_id: ImageId, old_ids: Vec<MarkerId>, new_ids: Vec<MarkerId> },
+    // 标记移到另一张图片，同时记录两张图片上其他标记的序号: (id, 序号)
+    MoveMarkerToImage {
+        old_marker: Marker,
+        new_marker: Marker,
+        old_indices: Vec<(MarkerId, u32)>,
+        new_indices: Vec<(MarkerId, u32)>,
+    },
     
     // Batch operations
     ClearImageMarkers { image_id: ImageId, markers: Vec<Marker> },
+    BatchUpdateTranslations { changes: Vec<(MarkerId, String, String)> },  // (id, old, new)
+    // 多个操作作为一次撤销记录，按顺序执行、逆序撤销
+    Composite { actions: Vec<ActionType> },
     
     // Marker type conversions
     ConvertRectangleToPoint { 
@@ -68,29 +87,80 @@ pub enum ActionType {
         old_marker: Marker,
         new_marker: Marker,
     },
+    // 撤销时old/new互换，仍然是同一个动作
+    ConvertPolygonToRectangle {
+        marker_id: MarkerId,
+        old_marker: Marker,
+        new_marker: Marker,
+    },
+
+    // 还原命名快照：整体替换项目的标记、设置和海兔缓存
+    RestoreSnapshot {
+        snapshot_id: String,
+        name: String,
+        old_state: Box<crate::service::project::snapshot::ProjectState>,
+        new_state: Box<crate::service::project::snapshot::ProjectState>,
+        undone: bool,
+    },
 }
 
 impl ActionType {
+    /// 连续修改同一标记的位置/译文时把next并入self（保留self的旧值、取next的新值），能合并时返回true
+    pub fn coalesce(&mut self, next: &ActionType) -> bool {
+        match (self, next) {
+            (ActionType::UpdatePointMarkerPosition { id, new_pos, .. },
+             ActionType::UpdatePointMarkerPosition { id: next_id, new_pos: next_pos, .. }) if id == next_id => {
+                *new_pos = *next_pos;
+            }
+            (ActionType::UpdateRectangleGeometry { id, new_geometry, .. },
+             ActionType::UpdateRectangleGeometry { id: next_id, new_geometry: next_geometry, .. }) if id == next_id => {
+                *new_geometry = *next_geometry;
+            }
+            (ActionType::UpdatePolygonGeometry { id, new_points, .. },
+             ActionType::UpdatePolygonGeometry { id: next_id, new_points: next_points, .. }) if id == next_id => {
+                new_points.clone_from(next_points);
+            }
+            (ActionType::UpdateMarkerTranslation { id, new_trans, .. },
+             ActionType::UpdateMarkerTranslation { id: next_id, new_trans: next_trans, .. }) if id == next_id => {
+                new_trans.clone_from(next_trans);
+            }
+            _ => return false,
+        }
+        true
+    }
+
     /// Get the string name of the action type for display purposes
     pub fn get_action_name(&self) -> &'static str {
         match self {
             ActionType::UpdateProjectName { .. } => "UpdateProjectName",
             ActionType::UpdateProjectLanguages { .. } => "UpdateProjectLanguages",
+            ActionType::UpdateReadingDirection { .. } => "UpdateReadingDirection",
             ActionType::AddImage { .. } => "AddImage",
             ActionType::RemoveImage { .. } => "RemoveImage",
             ActionType::ReorderImages { .. } => "ReorderImages",
             ActionType::UpdateImage { .. } => "UpdateImage",
+            ActionType::SetImageLocked { .. } => "SetImageLocked",
             ActionType::AddMarker { .. } => "AddMarker",
             ActionType::RemoveMarker { .. } => "RemoveMarker",
             ActionType::UpdateMarker { .. } => "UpdateMarker",
             ActionType::UpdatePointMarkerPosition { .. } => "UpdatePointMarkerPosition",
             ActionType::UpdateRectangleGeometry { .. } => "UpdateRectangleGeometry",
+            ActionType::UpdatePolygonGeometry { .. } => "UpdatePolygonGeometry",
             ActionType::UpdateMarkerTranslation { .. } => "UpdateMarkerTranslation",
+            ActionType::UpdateMarkerNote { .. } => "UpdateMarkerNote",
             ActionType::UpdateMarkerStyle { .. } => "UpdateMarkerStyle",
             ActionType::UpdateMarkerOrder { .. } => "UpdateMarkerOrder",
+            ActionType::SetMarkerIndices { .. } => "SetMarkerIndices",
+            ActionType::SetImageMarkerIds { .. } => "SetImageMarkerIds",
+            ActionType::SetMarkerIndexEntries { .. } => "SetMarkerIndexEntries",
+            ActionType::MoveMarkerToImage { .. } => "MoveMarkerToImage",
             ActionType::ClearImageMarkers { .. } => "ClearImageMarkers",
+            ActionType::BatchUpdateTranslations { .. } => "BatchUpdateTranslations",
+            ActionType::Composite { .. } => "Composite",
             ActionType::ConvertRectangleToPoint { .. } => "ConvertRectangleToPoint",
             ActionType::ConvertPointToRectangle { .. } => "ConvertPointToRectangle",
+            ActionType::ConvertPolygonToRectangle { .. } => "ConvertPolygonToRectangle",
+            ActionType::RestoreSnapshot { .. } => "RestoreSnapshot",
         }
     }
 }
@@ -102,6 +172,9 @@ pub struct UndoRedoAction {
     pub project_id: ProjectId,
     #[cfg(not(target_arch = "wasm32"))]
     pub timestamp: u64,
+    // 撤销分组的说明，显示在撤销菜单中
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 impl UndoRedoAction {
@@ -115,6 +188,7 @@ impl UndoRedoAction {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            description: None,
         }
     }
     
@@ -128,13 +202,20 @@ impl UndoRedoAction {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            description: None,
         }
     }
 
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
     /// Get the location (image_id, marker_id) affected by this action
     pub fn get_affected_location(&self) -> (Option<ImageId>, Option<MarkerId>) {
         match &self.action_type {
-            ActionType::UpdateProjectName { .. } | ActionType::UpdateProjectLanguages { .. } => {
+            ActionType::UpdateProjectName { .. } | ActionType::UpdateProjectLanguages { .. }
+            | ActionType::UpdateReadingDirection { .. } => {
                 // Project metadata changes don't affect specific images or markers
                 (None, None)
             }
@@ -148,15 +229,16 @@ impl UndoRedoAction {
                 // Reordering affects multiple images, not a specific one
                 (None, None)
             }
-            ActionType::UpdateImage { id, .. } => {
+            ActionType::UpdateImage { id, .. } | ActionType::SetImageLocked { id, .. } => {
                 (Some(*id), None)
             }
             ActionType::AddMarker { marker } | ActionType::RemoveMarker { marker } => {
                 (Some(marker.image_id), Some(marker.id))
             }
             ActionType::UpdateMarker { id, .. } | ActionType::UpdatePointMarkerPosition { id, .. }
-            | ActionType::UpdateRectangleGeometry { id, .. }
+            | ActionType::UpdateRectangleGeometry { id, .. } | ActionType::UpdatePolygonGeometry { id, .. }
             | ActionType::UpdateMarkerTranslation { id, .. } | ActionType::UpdateMarkerStyle { id, .. }
+            | ActionType::UpdateMarkerNote { id, .. }
             | ActionType::UpdateMarkerOrder { id, .. } => {
                 // We need to get the image_id from the marker
                 let services = crate::service::get_service();
@@ -166,11 +248,20 @@ impl UndoRedoAction {
                     (None, Some(*id))
                 }
             }
-            ActionType::ClearImageMarkers { image_id, .. } => {
+            ActionType::ClearImageMarkers { image_id, .. } | ActionType::SetMarkerIndices { image_id, .. }
+            | ActionType::SetImageMarkerIds { image_id, .. } | ActionType::SetMarkerIndexEntries { image_id, .. } => {
                 (Some(*image_id), None)
             }
+            ActionType::BatchUpdateTranslations { .. } | ActionType::Composite { .. }
+            | ActionType::RestoreSnapshot { .. } => {
+                // Affects markers across multiple images
+                (None, None)
+            }
             ActionType::ConvertRectangleToPoint { old_marker, .. } | 
-            ActionType::ConvertPointToRectangle { old_marker, .. } => {
+            ActionType::ConvertPointToRectangle { old_marker, .. } |
+            ActionType::ConvertPolygonToRectangle { old_marker, .. } |
+            ActionType::MoveMarkerToImage { old_marker, .. } => {
+                // 移动的撤销把标记放回old_marker所在的图片
                 (Some(old_marker.image_id), Some(old_marker.id))
             }
         }
@@ -179,7 +270,8 @@ impl UndoRedoAction {
     /// Get the project_id from the action by looking up which project contains the affected image
     pub fn get_project_id_from_action(action_type: &ActionType) -> CoreResult<Option<ProjectId>> {
         let image_id = match action_type {
-            ActionType::UpdateProjectName { .. } | ActionType::UpdateProjectLanguages { .. } => {
+            ActionType::UpdateProjectName { .. } | ActionType::UpdateProjectLanguages { .. }
+            | ActionType::UpdateReadingDirection { .. } => {
                 // Project metadata changes don't have an associated image_id
                 // The project_id should be provided when creating the action
                 return Ok(None);
@@ -194,15 +286,16 @@ impl UndoRedoAction {
                 // Reordering doesn't affect a specific image, project_id should be provided
                 return Ok(None);
             }
-            ActionType::UpdateImage { id, .. } => {
+            ActionType::UpdateImage { id, .. } | ActionType::SetImageLocked { id, .. } => {
                 Some(*id)
             }
             ActionType::AddMarker { marker } | ActionType::RemoveMarker { marker } => {
                 Some(marker.image_id)
             }
             ActionType::UpdateMarker { id, .. } | ActionType::UpdatePointMarkerPosition { id, .. }
-            | ActionType::UpdateRectangleGeometry { id, .. }
+            | ActionType::UpdateRectangleGeometry { id, .. } | ActionType::UpdatePolygonGeometry { id, .. }
             | ActionType::UpdateMarkerTranslation { id, .. } | ActionType::UpdateMarkerStyle { id, .. }
+            | ActionType::UpdateMarkerNote { id, .. }
             | ActionType::UpdateMarkerOrder { id, .. } => {
                 // We need to get the image_id from the marker
                 let services = crate::service::get_service();
@@ -212,11 +305,26 @@ impl UndoRedoAction {
                     None
                 }
             }
-            ActionType::ClearImageMarkers { image_id, .. } => {
+            ActionType::ClearImageMarkers { image_id, .. } | ActionType::SetMarkerIndices { image_id, .. }
+            | ActionType::SetImageMarkerIds { image_id, .. } | ActionType::SetMarkerIndexEntries { image_id, .. } => {
                 Some(*image_id)
             }
+            ActionType::BatchUpdateTranslations { .. } | ActionType::RestoreSnapshot { .. } => {
+                // Spans multiple images, project_id should be provided
+                return Ok(None);
+            }
+            ActionType::Composite { actions } => {
+                for sub_action in actions {
+                    if let Some(project_id) = Self::get_project_id_from_action(sub_action)? {
+                        return Ok(Some(project_id));
+                    }
+                }
+                return Ok(None);
+            }
             ActionType::ConvertRectangleToPoint { old_marker, .. } | 
-            ActionType::ConvertPointToRectangle { old_marker, .. } => {
+            ActionType::ConvertPointToRectangle { old_marker, .. } |
+            ActionType::ConvertPolygonToRectangle { old_marker, .. } |
+            ActionType::MoveMarkerToImage { old_marker, .. } => {
                 Some(old_marker.image_id)
             }
         };
@@ -3,5 +3,5 @@ mod service;
 mod actions;
 mod performer;
 
-pub use service::UndoRedoService;
+pub use service::{UndoGroupGuard, UndoRedoService};
 pub use actions::{ActionType, UndoRedoAction};
\ No newline at end of file
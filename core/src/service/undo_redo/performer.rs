@@ -1,5 +1,6 @@
 // Performer module for executing undo/redo operations
-use crate::common::{CoreResult, EVENT_SYSTEM};
+use crate::common::{CoreResult, MarkerId, EVENT_SYSTEM};
+use crate::common::events::schema::{BusinessEvent, SnapshotRestoredEvent};
 use crate::storage::state::APP_STATE;
 use crate::storage::marker::{self, MarkerGeometry};
 use crate::storage::traits::Storage;
@@ -48,6 +49,14 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_target: *old_target,
             }, action.project_id))
         }
+        ActionType::UpdateReadingDirection { old_direction, new_direction } => {
+            let services = crate::service::get_service();
+            services.project_service.set_reading_direction_core(action.project_id, *old_direction)?;
+            Ok(UndoRedoAction::with_id(action.id, ActionType::UpdateReadingDirection {
+                old_direction: *new_direction,
+                new_direction: *old_direction,
+            }, action.project_id))
+        }
         ActionType::AddMarker { marker } => {
             // Undo add by removing
             let services = crate::service::get_service();
@@ -56,7 +65,7 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
         }
         ActionType::RemoveMarker { marker } => {
             // Undo remove by adding back with correct index
-            let mut storage = APP_STATE.markers.write()?;
+            let mut storage = APP_STATE.markers.write_image(marker.image_id)?;
             marker::insert_marker_at_index(&mut storage, marker.clone())?;
             drop(storage);
             
@@ -68,10 +77,7 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
             let current_marker = crate::storage::marker::get_marker_storage(*id)?.unwrap();
             
             // Get current position from geometry
-            let current_pos = match &current_marker.geometry {
-                MarkerGeometry::Point { x, y } => (*x, *y),
-                MarkerGeometry::Rectangle { x, y, .. } => (*x, *y),
-            };
+            let current_pos = current_marker.geometry.anchor();
             
             // Update based on marker type
             match current_marker.geometry {
@@ -81,6 +87,11 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 MarkerGeometry::Rectangle { width, height, .. } => {
                     services.marker_service.update_rectangle_marker_with_undo(*id, old_position.0, old_position.1, width, height, old_translation.clone(), old_style.clone())?;
                 }
+                // 多边形的顶点由UpdatePolygonGeometry记录，这里只恢复翻译和样式
+                MarkerGeometry::Polygon { .. } => {
+                    services.marker_service.update_marker_translation_with_undo(*id, old_translation.clone())?;
+                    services.marker_service.update_marker_style_with_undo(*id, old_style.clone())?;
+                }
             }
             
             Ok(UndoRedoAction::with_id(action.id, ActionType::UpdateMarker {
@@ -105,6 +116,7 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 MarkerGeometry::Rectangle { width, height, .. } => {
                     services.marker_service.update_rectangle_marker_geometry_with_undo(*id, old_pos.0, old_pos.1, width, height)?;
                 }
+                MarkerGeometry::Polygon { .. } => {}
             }
             
             Ok(UndoRedoAction::with_id(action.id, ActionType::UpdatePointMarkerPosition {
@@ -124,6 +136,15 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_geometry: *old_geometry,
             }, action.project_id))
         }
+        ActionType::UpdatePolygonGeometry { id, old_points, new_points } => {
+            let services = crate::service::get_service();
+            services.marker_service.update_polygon_marker_geometry_with_undo(*id, old_points.clone())?;
+            Ok(UndoRedoAction::with_id(action.id, ActionType::UpdatePolygonGeometry {
+                id: *id,
+                old_points: new_points.clone(),
+                new_points: old_points.clone(),
+            }, action.project_id))
+        }
         ActionType::UpdateMarkerTranslation { id, old_trans, new_trans } => {
             let services = crate::service::get_service();
             services.marker_service.update_marker_translation_with_undo(*id, old_trans.clone())?;
@@ -133,6 +154,15 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_trans: old_trans.clone(),
             }, action.project_id))
         }
+        ActionType::UpdateMarkerNote { id, old_note, new_note } => {
+            let services = crate::service::get_service();
+            services.marker_service.update_marker_note_with_undo(*id, old_note.clone())?;
+            Ok(UndoRedoAction::with_id(action.id, ActionType::UpdateMarkerNote {
+                id: *id,
+                old_note: new_note.clone(),
+                new_note: old_note.clone(),
+            }, action.project_id))
+        }
         ActionType::UpdateMarkerStyle { id, old_style, new_style } => {
             let services = crate::service::get_service();
             services.marker_service.update_marker_style_with_undo(*id, old_style.clone())?;
@@ -153,9 +183,94 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_index: *old_index,
             }, action.project_id))
         }
+        ActionType::SetMarkerIndices { image_id, changes } => {
+            // Restore old indices and let the frontend reload the image markers
+            let old_indices: Vec<(MarkerId, u32)> = changes.iter().map(|&(id, old_index, _)| (id, old_index)).collect();
+            APP_STATE.markers.write_image(*image_id)?.set_image_indices(&old_indices);
+            crate::service::marker::emit_markers_renumbered(&[*image_id]);
+            
+            Ok(UndoRedoAction::with_id(action.id, ActionType::SetMarkerIndices {
+                image_id: *image_id,
+                changes: changes.iter()
+                    .map(|(id, old_index, new_index)| (*id, *new_index, *old_index))
+                    .collect(),
+            }, action.project_id))
+        }
+        ActionType::SetImageMarkerIds { image_id, old_ids, new_ids } => {
+            crate::service::project::integrity::set_image_marker_ids(std::iter::once((*image_id, old_ids)))?;
+            crate::service::marker::emit_markers_renumbered(&[*image_id]);
+            Ok(UndoRedoAction::with_id(action.id, ActionType::SetImageMarkerIds {
+                image_id: *image_id,
+                old_ids: new_ids.clone(),
+                new_ids: old_ids.clone(),
+            }, action.project_id))
+        }
+        ActionType::SetMarkerIndexEntries { image_id, old_ids, new_ids } => {
+            crate::service::project::integrity::set_index_entries(std::iter::once((*image_id, old_ids)))?;
+            crate::service::marker::emit_markers_renumbered(&[*image_id]);
+            Ok(UndoRedoAction::with_id(action.id, ActionType::SetMarkerIndexEntries {
+                image_id: *image_id,
+                old_ids: new_ids.clone(),
+                new_ids: old_ids.clone(),
+            }, action.project_id))
+        }
+        ActionType::MoveMarkerToImage { old_marker, new_marker, old_indices, new_indices } => {
+            // 把标记放回原图片，两张图片上其他标记的序号一起恢复
+            let mut storage = APP_STATE.markers.write()?;
+            let previous = marker::relocate_marker(&mut storage, old_marker.clone(), old_indices)?;
+            drop(storage);
+            if let Some(previous) = previous {
+                crate::storage::image::remove_marker_from_image_storage(previous, old_marker.id)?;
+            }
+            crate::storage::image::add_marker_to_image_storage(old_marker.image_id, old_marker.id)?;
+            crate::service::marker::emit_markers_renumbered(&[new_marker.image_id, old_marker.image_id]);
+            
+            Ok(UndoRedoAction::with_id(action.id, ActionType::MoveMarkerToImage {
+                old_marker: new_marker.clone(),
+                new_marker: old_marker.clone(),
+                old_indices: new_indices.clone(),
+                new_indices: old_indices.clone(),
+            }, action.project_id))
+        }
+        ActionType::BatchUpdateTranslations { changes } => {
+            // Restore old translations of every changed marker
+            let mut storage = APP_STATE.markers.write()?;
+            for (id, old_trans, _) in changes {
+                if let Some(marker) = storage.get_mut(id) {
+                    marker.translation = old_trans.clone();
+                }
+            }
+            drop(storage);
+            
+            Ok(UndoRedoAction::with_id(action.id, ActionType::BatchUpdateTranslations {
+                changes: changes.iter()
+                    .map(|(id, old_trans, new_trans)| (*id, new_trans.clone(), old_trans.clone()))
+                    .collect(),
+            }, action.project_id))
+        }
+        ActionType::Composite { actions } => {
+            // Undo sub actions in reverse order, the inverse composite replays them forward again
+            let mut reversed = Vec::with_capacity(actions.len());
+            for sub_action in actions.iter().rev() {
+                let sub = UndoRedoAction::with_id(action.id, sub_action.clone(), action.project_id);
+                reversed.push(perform_undo(&sub)?.action_type);
+            }
+            Ok(UndoRedoAction::with_id(action.id, ActionType::Composite { actions: reversed }, action.project_id))
+        }
+        ActionType::RestoreSnapshot { snapshot_id, name, old_state, new_state, undone } => {
+            crate::service::project::snapshot::apply_project_state(action.project_id, old_state)?;
+            let _ = SnapshotRestoredEvent::new(action.project_id, snapshot_id.clone(), name.clone(), !undone).emit();
+            Ok(UndoRedoAction::with_id(action.id, ActionType::RestoreSnapshot {
+                snapshot_id: snapshot_id.clone(),
+                name: name.clone(),
+                old_state: new_state.clone(),
+                new_state: old_state.clone(),
+                undone: !undone,
+            }, action.project_id))
+        }
         ActionType::ClearImageMarkers { image_id, markers } => {
             // Restore all markers with their original image_index
-            let mut storage = APP_STATE.markers.write()?;
+            let mut storage = APP_STATE.markers.write_image(*image_id)?;
             
             // Sort markers by image_index to restore them in order
             let mut sorted_markers = markers.clone();
@@ -274,13 +389,30 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_order: old_order.clone(),
             }, action.project_id))
         }
+        ActionType::UpdateImage { id, old_name, new_name } => {
+            let services = crate::service::get_service();
+            services.image_service.set_image_name(*id, old_name.clone())?;
+            Ok(UndoRedoAction::with_id(action.id, ActionType::UpdateImage {
+                id: *id,
+                old_name: new_name.clone(),
+                new_name: old_name.clone(),
+            }, action.project_id))
+        }
+        ActionType::SetImageLocked { id, locked } => {
+            let services = crate::service::get_service();
+            services.image_service.set_image_locked(*id, !*locked)?;
+            Ok(UndoRedoAction::with_id(action.id, ActionType::SetImageLocked {
+                id: *id,
+                locked: !*locked,
+            }, action.project_id))
+        }
         ActionType::ConvertRectangleToPoint { marker_id, old_marker, new_marker } => {
             // Undo by restoring the old rectangle marker
-            let mut marker_storage = APP_STATE.markers.write()?;
-            if let Some(marker) = marker_storage.get_mut(marker_id) {
+            if let Some(mut marker_storage) = APP_STATE.markers.write_marker(*marker_id)?
+                && let Some(marker) = marker_storage.get_mut(marker_id)
+            {
                 *marker = old_marker.clone();
             }
-            drop(marker_storage);
             
             // Send marker update event
             let _ = EVENT_SYSTEM.emit_business_event(
@@ -300,11 +432,11 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
         }
         ActionType::ConvertPointToRectangle { marker_id, old_marker, new_marker } => {
             // Undo by restoring the old point marker
-            let mut marker_storage = APP_STATE.markers.write()?;
-            if let Some(marker) = marker_storage.get_mut(marker_id) {
+            if let Some(mut marker_storage) = APP_STATE.markers.write_marker(*marker_id)?
+                && let Some(marker) = marker_storage.get_mut(marker_id)
+            {
                 *marker = old_marker.clone();
             }
-            drop(marker_storage);
             
             // Send marker update event
             let _ = EVENT_SYSTEM.emit_business_event(
@@ -322,7 +454,33 @@ pub fn perform_undo(action: &UndoRedoAction) -> CoreResult<UndoRedoAction> {
                 new_marker: old_marker.clone(),
             }, action.project_id))
         }
-        _ => Ok(action.clone()), // For now, just return the same action for unimplemented types
+        ActionType::ConvertPolygonToRectangle { marker_id, old_marker, new_marker } => {
+            if let Some(mut marker_storage) = APP_STATE.markers.write_marker(*marker_id)?
+                && let Some(marker) = marker_storage.get_mut(marker_id)
+            {
+                *marker = old_marker.clone();
+            }
+            
+            let conversion_type = if matches!(old_marker.geometry, MarkerGeometry::Polygon { .. }) {
+                "RectangleToPolygon"
+            } else {
+                "PolygonToRectangle"
+            };
+            let _ = EVENT_SYSTEM.emit_business_event(
+                "MarkerConverted".to_string(),
+                serde_json::json!({
+                    "marker_id": marker_id.0,
+                    "image_id": old_marker.image_id.0,
+                    "conversion_type": conversion_type
+                })
+            );
+            
+            Ok(UndoRedoAction::with_id(action.id, ActionType::ConvertPolygonToRectangle {
+                marker_id: *marker_id,
+                old_marker: new_marker.clone(),
+                new_marker: old_marker.clone(),
+            }, action.project_id))
+        }
     }
 }
 
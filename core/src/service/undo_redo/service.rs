@@ -2,7 +2,9 @@
 use std::sync::Arc;
 use std::collections::{HashMap, VecDeque};
 use std::sync::RwLock;
-use crate::common::{CoreResult, ProjectId, ImageId, MarkerId};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::common::{CoreError, CoreResult, ProjectId, ImageId, MarkerId};
+use crate::common::events::get_timestamp_millis;
 use crate::api::undo_redo::UndoRedoResult;
 use crate::service::events::{DomainEvent, EventBus, EventHandler};
 use crate::common::EVENT_SYSTEM;
@@ -12,10 +14,28 @@ use uuid::Uuid;
 use once_cell::sync::Lazy;
 
 const MAX_UNDO_HISTORY: usize = 100;
+/// 默认合并窗口：拖动标记、连续输入译文时间隔小于此值的修改合并为一次撤销
+pub const DEFAULT_COALESCE_WINDOW_MS: u64 = 500;
+
+// 测试可以固定当前线程看到的时间，合并与否不受机器负载影响
+#[cfg(test)]
+thread_local! {
+    static FIXED_NOW: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+fn now_millis() -> u64 {
+    #[cfg(test)]
+    if let Some(now) = FIXED_NOW.get() {
+        return now;
+    }
+    get_timestamp_millis()
+}
 
 pub struct ProjectUndoRedoStack {
     pub undo_stack: VecDeque<UndoRedoAction>,
     pub redo_stack: VecDeque<UndoRedoAction>,
+    // 栈顶操作最近一次记录（或合并）的时间，None表示栈顶不再接受合并
+    pub last_recorded_at: Option<u64>,
 }
 
 impl ProjectUndoRedoStack {
@@ -23,7 +43,24 @@ impl ProjectUndoRedoStack {
         Self {
             undo_stack: VecDeque::with_capacity(MAX_UNDO_HISTORY),
             redo_stack: VecDeque::with_capacity(MAX_UNDO_HISTORY),
+            last_recorded_at: None,
+        }
+    }
+
+    // 与栈顶是同一目标的连续修改且在窗口内时并入栈顶，换新的commit id以免被当成已保存
+    fn try_coalesce(&mut self, action: &UndoRedoAction, now: u64, window_ms: u64) -> bool {
+        let within_window = self.last_recorded_at.is_some_and(|at| now.saturating_sub(at) <= window_ms);
+        if window_ms == 0 || !within_window {
+            return false;
+        }
+        let Some(top) = self.undo_stack.back_mut() else {
+            return false;
+        };
+        if top.description.is_some() || action.description.is_some() || !top.action_type.coalesce(&action.action_type) {
+            return false;
         }
+        top.id = action.id;
+        true
     }
     
     pub fn get_current_commit_id(&self) -> Option<Uuid> {
@@ -31,9 +68,33 @@ impl ProjectUndoRedoStack {
     }
 }
 
+/// 进行中的撤销分组，期间记录的操作在结束时合并为一次撤销
+pub struct ActionGroup {
+    pub description: String,
+    // 嵌套的begin_group只增加层数，最外层结束时才入栈
+    pub depth: usize,
+    pub actions: Vec<ActionType>,
+}
+
+impl ActionGroup {
+    fn into_action(mut self, project_id: ProjectId) -> Option<UndoRedoAction> {
+        let action_type = match self.actions.len() {
+            0 => return None,
+            1 => self.actions.remove(0),
+            _ => ActionType::Composite { actions: self.actions },
+        };
+        Some(UndoRedoAction::new(action_type, project_id).with_description(self.description))
+    }
+}
+
 pub struct UndoRedoStack {
     pub project_stacks: RwLock<HashMap<ProjectId, ProjectUndoRedoStack>>,
     pub is_undoing: RwLock<bool>,
+    // 项目上次保存时的commit，与当前commit不同即有未保存的修改
+    pub saved_commits: RwLock<HashMap<ProjectId, Option<Uuid>>>,
+    // 每个项目最多一个进行中的分组
+    pub groups: RwLock<HashMap<ProjectId, ActionGroup>>,
+    pub coalesce_window_ms: AtomicU64,
 }
 
 impl UndoRedoStack {
@@ -41,6 +102,9 @@ impl UndoRedoStack {
         Self {
             project_stacks: RwLock::new(HashMap::new()),
             is_undoing: RwLock::new(false),
+            saved_commits: RwLock::new(HashMap::new()),
+            groups: RwLock::new(HashMap::new()),
+            coalesce_window_ms: AtomicU64::new(DEFAULT_COALESCE_WINDOW_MS),
         }
     }
     
@@ -53,6 +117,11 @@ impl UndoRedoStack {
     }
     
     pub fn push_action(&self, action: UndoRedoAction) -> CoreResult<()> {
+        self.push_action_at(action, now_millis())
+    }
+
+    // now只用于判断是否与栈顶合并
+    fn push_action_at(&self, action: UndoRedoAction, now: u64) -> CoreResult<()> {
         // Don't record actions while undoing/redoing
         let is_undoing = self.is_undoing.read()?;
         if *is_undoing {
@@ -60,12 +129,24 @@ impl UndoRedoStack {
         }
         
         let project_id = action.project_id;
+        // 分组期间只收集操作，结束分组时作为一次撤销记录入栈
+        if let Some(group) = self.groups.write()?.get_mut(&project_id) {
+            group.actions.push(action.action_type);
+            return Ok(());
+        }
         self.get_or_create_project_stack(project_id)?;
         
+        let window_ms = self.coalesce_window_ms.load(Ordering::Relaxed);
         let mut stacks = self.project_stacks.write()?;
         if let Some(project_stack) = stacks.get_mut(&project_id) {
             // Clear redo stack when new action is performed
             project_stack.redo_stack.clear();
+
+            let coalesced = project_stack.try_coalesce(&action, now, window_ms);
+            project_stack.last_recorded_at = Some(now);
+            if coalesced {
+                return Ok(());
+            }
             
             // Add to undo stack
             project_stack.undo_stack.push_back(action);
@@ -98,7 +179,10 @@ impl UndoRedoStack {
         
         let mut stacks = self.project_stacks.write()?;
         Ok(stacks.get_mut(&project_id)
-            .and_then(|s| s.undo_stack.pop_back()))
+            .and_then(|s| {
+                s.last_recorded_at = None;
+                s.undo_stack.pop_back()
+            }))
     }
     
     pub fn push_redo_action(&self, project_id: ProjectId, action: UndoRedoAction) -> CoreResult<()> {
@@ -120,6 +204,7 @@ impl UndoRedoStack {
     pub fn push_to_undo_stack(&self, project_id: ProjectId, action: UndoRedoAction) -> CoreResult<()> {
         let mut stacks = self.project_stacks.write()?;
         if let Some(project_stack) = stacks.get_mut(&project_id) {
+            project_stack.last_recorded_at = None;
             project_stack.undo_stack.push_back(action);
         }
         Ok(())
@@ -143,14 +228,67 @@ impl UndoRedoStack {
     pub fn clear_all(&self) -> CoreResult<()> {
         let mut stacks = self.project_stacks.write()?;
         stacks.clear();
+        self.groups.write()?.clear();
         Ok(())
     }
+
+    /// 开始分组，已有分组时并入外层分组（外层的说明生效），返回当前层数
+    pub fn begin_group(&self, project_id: ProjectId, description: String) -> CoreResult<usize> {
+        let mut groups = self.groups.write()?;
+        let group = groups.entry(project_id)
+            .or_insert_with(|| ActionGroup { description, depth: 0, actions: Vec::new() });
+        group.depth += 1;
+        Ok(group.depth)
+    }
+
+    /// 结束一层分组，最外层结束时取出分组
+    pub fn end_group(&self, project_id: ProjectId) -> CoreResult<Option<ActionGroup>> {
+        let mut groups = self.groups.write()?;
+        let group = groups.get_mut(&project_id)
+            .ok_or_else(|| CoreError::NotFound(format!("No open undo group for project {}", project_id.0)))?;
+        group.depth -= 1;
+        if group.depth > 0 {
+            return Ok(None);
+        }
+        Ok(groups.remove(&project_id))
+    }
+
+    /// 不论嵌套层数取出整个分组
+    pub fn take_group(&self, project_id: ProjectId) -> CoreResult<Option<ActionGroup>> {
+        Ok(self.groups.write()?.remove(&project_id))
+    }
+
+    pub fn is_grouping(&self, project_id: ProjectId) -> CoreResult<bool> {
+        Ok(self.groups.read()?.contains_key(&project_id))
+    }
     
     pub fn get_current_commit_id(&self, project_id: ProjectId) -> CoreResult<Option<Uuid>> {
         let stacks = self.project_stacks.read()?;
         Ok(stacks.get(&project_id)
             .and_then(|s| s.get_current_commit_id()))
     }
+
+    pub fn mark_saved(&self, project_id: ProjectId) -> CoreResult<()> {
+        let current = self.get_current_commit_id(project_id)?;
+        self.saved_commits.write()?.insert(project_id, current);
+        // 保存后的修改不并入保存前的操作，撤销一次回到保存时的状态
+        if let Some(project_stack) = self.project_stacks.write()?.get_mut(&project_id) {
+            project_stack.last_recorded_at = None;
+        }
+        Ok(())
+    }
+
+    /// 设置合并窗口（毫秒），0表示不合并
+    pub fn set_coalesce_window(&self, window_ms: u64) {
+        self.coalesce_window_ms.store(window_ms, Ordering::Relaxed);
+    }
+
+    // 从未保存过的项目以打开时的状态（没有commit）为基准
+    pub fn has_unsaved_changes(&self, project_id: ProjectId) -> CoreResult<bool> {
+        let current = self.get_current_commit_id(project_id)?;
+        let saved = self.saved_commits.read()?.get(&project_id).copied().flatten();
+        Ok(current != saved)
+    }
 }
 
 // Global undo/redo stack
@@ -222,6 +360,7 @@ impl UndoRedoService {
     
     pub fn clear_project_history(&self, project_id: u32) {
         let project_id = ProjectId::from(project_id);
+        let _ = UNDO_REDO_STACK.take_group(project_id);
         let _ = UNDO_REDO_STACK.clear(project_id);
         self.emit_undo_redo_state(project_id, false, false, None);
     }
@@ -230,21 +369,97 @@ impl UndoRedoService {
         let _ = UNDO_REDO_STACK.clear_all();
     }
 
+    /// 记录项目已保存，之后的修改才算未保存
+    pub fn mark_saved(&self, project_id: ProjectId) -> CoreResult<()> {
+        UNDO_REDO_STACK.mark_saved(project_id)
+    }
+
+    pub fn has_unsaved_changes(&self, project_id: ProjectId) -> bool {
+        UNDO_REDO_STACK.has_unsaved_changes(project_id).unwrap_or(false)
+    }
+
+    /// 连续修改同一标记的位置/译文时，间隔小于window_ms的合并为一次撤销，0表示不合并
+    pub fn set_coalesce_window(&self, window_ms: u64) {
+        UNDO_REDO_STACK.set_coalesce_window(window_ms);
+    }
+
+    pub fn coalesce_window(&self) -> u64 {
+        UNDO_REDO_STACK.coalesce_window_ms.load(Ordering::Relaxed)
+    }
+
     // Record action through event
     pub fn record_action(&self, action: UndoRedoAction) -> CoreResult<()> {
         let project_id = action.project_id;
         UNDO_REDO_STACK.push_action(action)?;
         
-        // Emit undo/redo state change event
+        // 分组期间不发送，结束分组时发送一次
+        if UNDO_REDO_STACK.is_grouping(project_id)? {
+            return Ok(());
+        }
+        self.emit_current_state(project_id)
+    }
+
+    // === 撤销分组 ===
+
+    /// 开始撤销分组：到end_group为止记录的操作合并为一次撤销
+    /// 嵌套调用会展开到最外层分组中，不报错；任意一层abort_group都会放弃整个分组
+    pub fn begin_group(&self, project_id: ProjectId, description: String) -> CoreResult<()> {
+        UNDO_REDO_STACK.begin_group(project_id, description).map(|_| ())
+    }
+
+    /// 结束一层分组，最外层结束时入栈并发送一次状态变化事件
+    pub fn end_group(&self, project_id: ProjectId) -> CoreResult<()> {
+        if let Some(group) = UNDO_REDO_STACK.end_group(project_id)? {
+            self.commit_group(project_id, group)?;
+        }
+        Ok(())
+    }
+
+    /// 放弃分组并撤回分组内已做的修改，没有进行中的分组时返回false
+    pub fn abort_group(&self, project_id: ProjectId) -> CoreResult<bool> {
+        let Some(group) = UNDO_REDO_STACK.take_group(project_id)? else {
+            return Ok(false);
+        };
+        if let Some(action) = group.into_action(project_id) {
+            UNDO_REDO_STACK.set_undoing(true)?;
+            let result = perform_undo(&action);
+            UNDO_REDO_STACK.set_undoing(false)?;
+            result?;
+        }
+        Ok(true)
+    }
+
+    /// 开始分组并返回守卫，守卫没有finish就被丢弃时（出错提前返回）自动abort_group
+    pub fn begin_group_guard(&self, project_id: ProjectId, description: String) -> CoreResult<UndoGroupGuard> {
+        self.begin_group(project_id, description)?;
+        Ok(UndoGroupGuard { project_id, finished: false })
+    }
+
+    fn commit_group(&self, project_id: ProjectId, group: ActionGroup) -> CoreResult<()> {
+        if let Some(action) = group.into_action(project_id) {
+            UNDO_REDO_STACK.push_action(action)?;
+        }
+        self.emit_current_state(project_id)
+    }
+
+    // 撤销/重做时还没结束的分组先入栈，撤销的是整个分组
+    fn close_open_group(&self, project_id: ProjectId) -> CoreResult<()> {
+        if let Some(group) = UNDO_REDO_STACK.take_group(project_id)? {
+            self.commit_group(project_id, group)?;
+        }
+        Ok(())
+    }
+
+    fn emit_current_state(&self, project_id: ProjectId) -> CoreResult<()> {
         let can_undo = UNDO_REDO_STACK.can_undo(project_id)?;
         let can_redo = UNDO_REDO_STACK.can_redo(project_id)?;
         let current_commit_id = UNDO_REDO_STACK.get_current_commit_id(project_id)?;
         self.emit_undo_redo_state(project_id, can_undo, can_redo, current_commit_id);
-        
         Ok(())
     }
 
     fn perform_undo(&self, project_id: ProjectId) -> CoreResult<Option<(Option<ImageId>, Option<MarkerId>)>> {
+        self.close_open_group(project_id)?;
         UNDO_REDO_STACK.get_or_create_project_stack(project_id)?;
         
         let action = UNDO_REDO_STACK.pop_undo_action(project_id)?;
@@ -259,7 +474,8 @@ impl UndoRedoService {
             // Clear undoing flag
             UNDO_REDO_STACK.set_undoing(false)?;
             
-            let reversed_action = result?;
+            let mut reversed_action = result?;
+            reversed_action.description = action.description.clone();
             
             UNDO_REDO_STACK.push_redo_action(project_id, reversed_action)?;
             
@@ -276,6 +492,7 @@ impl UndoRedoService {
     }
     
     fn perform_redo(&self, project_id: ProjectId) -> CoreResult<Option<(Option<ImageId>, Option<MarkerId>)>> {
+        self.close_open_group(project_id)?;
         UNDO_REDO_STACK.get_or_create_project_stack(project_id)?;
         
         let action = UNDO_REDO_STACK.pop_redo_action(project_id)?;
@@ -290,7 +507,8 @@ impl UndoRedoService {
             // Clear undoing flag
             UNDO_REDO_STACK.set_undoing(false)?;
             
-            let reversed_action = result?;
+            let mut reversed_action = result?;
+            reversed_action.description = action.description.clone();
             
             UNDO_REDO_STACK.push_to_undo_stack(project_id, reversed_action)?;
             
@@ -308,14 +526,14 @@ impl UndoRedoService {
 
     fn emit_undo_redo_state(&self, project_id: ProjectId, can_undo: bool, can_redo: bool, current_commit_id: Option<Uuid>) {
         // Get the name of the action that can be undone
-        let undo_action_name = if can_undo {
+        let (undo_action_name, undo_description) = if can_undo {
             let stacks = UNDO_REDO_STACK.project_stacks.read().unwrap();
             stacks.get(&project_id)
                 .and_then(|s| s.undo_stack.back())
-                .map(|action| action.action_type.get_action_name())
-                .unwrap_or("none")
+                .map(|action| (action.action_type.get_action_name(), action.description.clone()))
+                .unwrap_or(("none", None))
         } else {
-            "none"
+            ("none", None)
         };
         
         let _ = EVENT_SYSTEM.emit_business_event(
@@ -323,6 +541,7 @@ impl UndoRedoService {
             serde_json::json!({
                 "project_id": project_id.0,
                 "undo_action_name": undo_action_name,
+                "undo_description": undo_description,
                 "can_redo": can_redo,
                 "current_commit_id": current_commit_id.map(|id| id.to_string())
             })
@@ -339,6 +558,29 @@ impl UndoRedoService {
     }
 }
 
+/// 撤销分组守卫：finish结束分组，未finish就被丢弃时放弃分组并撤回修改
+pub struct UndoGroupGuard {
+    project_id: ProjectId,
+    finished: bool,
+}
+
+impl UndoGroupGuard {
+    pub fn finish(mut self) -> CoreResult<()> {
+        self.finished = true;
+        crate::service::get_service().undo_redo_service.end_group(self.project_id)
+    }
+}
+
+impl Drop for UndoGroupGuard {
+    fn drop(&mut self) {
+        if !self.finished
+            && let Ok(services) = crate::service::try_get_service()
+        {
+            let _ = services.undo_redo_service.abort_group(self.project_id);
+        }
+    }
+}
+
 // 实现事件处理器，监听需要记录的操作
 impl EventHandler for UndoRedoService {
     fn handle(&self, event: &DomainEvent) {
@@ -391,6 +633,22 @@ impl EventHandler for UndoRedoService {
                     }
                 }
             },
+            DomainEvent::PolygonGeometryUpdated { id, old_points, new_points } => {
+                if let Ok(services) = crate::service::try_get_service()
+                    && let Ok(Some(marker)) = services.marker_service.get_marker_by_id(*id)
+                    && let Ok(Some(project_id)) = services.project_service.find_project_by_image(marker.image_id)
+                {
+                    let action = UndoRedoAction::new(
+                        ActionType::UpdatePolygonGeometry {
+                            id: *id,
+                            old_points: old_points.clone(),
+                            new_points: new_points.clone(),
+                        },
+                        project_id
+                    );
+                    let _ = self.record_action(action);
+                }
+            },
             DomainEvent::PointMarkerPositionUpdated { id, old_pos, new_pos } => {
                 // Record position update
                 if let Ok(services) = crate::service::try_get_service() {
@@ -427,6 +685,18 @@ impl EventHandler for UndoRedoService {
                     }
                 }
             },
+            DomainEvent::MarkerNoteUpdated { id, old_note, new_note } => {
+                if let Ok(services) = crate::service::try_get_service()
+                    && let Ok(Some(marker)) = services.marker_service.get_marker_by_id(*id)
+                    && let Ok(Some(project_id)) = services.project_service.find_project_by_image(marker.image_id)
+                {
+                    let action = UndoRedoAction::new(
+                        ActionType::UpdateMarkerNote { id: *id, old_note: old_note.clone(), new_note: new_note.clone() },
+                        project_id
+                    );
+                    let _ = self.record_action(action);
+                }
+            },
             DomainEvent::MarkerStyleUpdated { id, old_style, new_style } => {
                 // Record style update
                 if let Ok(services) = crate::service::try_get_service() {
@@ -484,6 +754,73 @@ impl EventHandler for UndoRedoService {
                     }
                 }
             },
+            DomainEvent::MarkerIndicesCompacted { project_id, changes } if !changes.is_empty() => {
+                // One image is a single action, a project-wide compaction is undone as a whole
+                let mut actions: Vec<ActionType> = changes.iter()
+                    .map(|(image_id, changes)| ActionType::SetMarkerIndices { image_id: *image_id, changes: changes.clone() })
+                    .collect();
+                let action_type = if actions.len() == 1 {
+                    actions.remove(0)
+                } else {
+                    ActionType::Composite { actions }
+                };
+                let _ = self.record_action(UndoRedoAction::new(action_type, *project_id));
+            },
+            DomainEvent::MarkerMovedToImage { project_id, old_marker, new_marker, old_indices, new_indices } => {
+                // 跨图片移动连同两张图片的重新编号作为一次撤销操作
+                let action = UndoRedoAction::new(
+                    ActionType::MoveMarkerToImage {
+                        old_marker: old_marker.clone(),
+                        new_marker: new_marker.clone(),
+                        old_indices: old_indices.clone(),
+                        new_indices: new_indices.clone(),
+                    },
+                    *project_id,
+                );
+                let _ = self.record_action(action);
+            },
+            DomainEvent::ProjectIntegrityRepaired { project_id, repair } => {
+                // 图片列表、标记列表和序号的修复作为一次撤销操作
+                let mut actions = Vec::new();
+                if let Some((old_order, new_order)) = &repair.image_order {
+                    actions.push(ActionType::ReorderImages { old_order: old_order.clone(), new_order: new_order.clone() });
+                }
+                for (image_id, old_name, new_name) in &repair.image_names {
+                    actions.push(ActionType::UpdateImage { id: *image_id, old_name: old_name.clone(), new_name: Some(new_name.clone()) });
+                }
+                for (image_id, old_ids, new_ids) in &repair.image_marker_ids {
+                    actions.push(ActionType::SetImageMarkerIds { image_id: *image_id, old_ids: old_ids.clone(), new_ids: new_ids.clone() });
+                }
+                for (image_id, old_ids, new_ids) in &repair.index_entries {
+                    actions.push(ActionType::SetMarkerIndexEntries { image_id: *image_id, old_ids: old_ids.clone(), new_ids: new_ids.clone() });
+                }
+                for (image_id, changes) in &repair.index_changes {
+                    actions.push(ActionType::SetMarkerIndices { image_id: *image_id, changes: changes.clone() });
+                }
+                if !actions.is_empty() {
+                    let _ = self.record_action(UndoRedoAction::new(ActionType::Composite { actions }, *project_id));
+                }
+            },
+            DomainEvent::MarkerTranslationsBatchUpdated { project_id, changes } if !changes.is_empty() => {
+                // Record find & replace as a single action
+                let action = UndoRedoAction::new(
+                    ActionType::BatchUpdateTranslations { changes: changes.clone() },
+                    *project_id
+                );
+                let _ = self.record_action(action);
+            },
+            DomainEvent::MarkersImported { project_id, changes, created } if !changes.is_empty() || !created.is_empty() => {
+                // Translation changes and created markers are undone together
+                let batch = ActionType::BatchUpdateTranslations { changes: changes.clone() };
+                let action_type = if created.is_empty() {
+                    batch
+                } else {
+                    let mut actions = vec![batch];
+                    actions.extend(created.iter().map(|marker| ActionType::AddMarker { marker: marker.clone() }));
+                    ActionType::Composite { actions }
+                };
+                let _ = self.record_action(UndoRedoAction::new(action_type, *project_id));
+            },
             DomainEvent::ImageMarkersCleared(image_id, markers) => {
                 // Record clear image markers action
                 if let Ok(services) = crate::service::try_get_service() {
@@ -506,4 +843,195 @@ impl EventHandler for UndoRedoService {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::marker::{add_point_marker_to_image, update_marker_style, update_marker_translation};
+    use crate::storage::marker::{get_marker_storage, MarkerStyle};
+
+    fn project_with_image(name: &str) -> (ProjectId, ImageId) {
+        let project_id = crate::storage::project::create_project_storage(name.to_string()).unwrap();
+        let image_id = crate::storage::image::add_image_from_binary_storage(
+            crate::storage::ImageFormat::Png, vec![0u8; 16], None
+        ).unwrap();
+        crate::storage::project::add_image_to_project_storage(project_id, image_id).unwrap();
+        (project_id, image_id)
+    }
+
+    #[test]
+    fn test_group_undone_as_one_action() {
+        let service = crate::service::get_service();
+        let (project_id, image_id) = project_with_image("undo_group");
+        let undo_service = &service.undo_redo_service;
+
+        undo_service.begin_group(project_id, "粘贴译文".to_string()).unwrap();
+        let marker_id = MarkerId::from(add_point_marker_to_image(image_id.0, 10.0, 10.0, None, None).unwrap().unwrap());
+        // 嵌套的分组并入外层
+        undo_service.begin_group(project_id, "内层".to_string()).unwrap();
+        assert!(update_marker_translation(marker_id.0, "译文".to_string()).unwrap());
+        undo_service.end_group(project_id).unwrap();
+        assert!(!undo_service.can_undo(project_id).unwrap());
+        assert!(update_marker_style(marker_id.0, true, true).unwrap());
+        undo_service.end_group(project_id).unwrap();
+        assert!(undo_service.end_group(project_id).is_err());
+
+        {
+            let stacks = UNDO_REDO_STACK.project_stacks.read().unwrap();
+            let stack = &stacks[&project_id].undo_stack;
+            assert_eq!(stack.len(), 1);
+            assert_eq!(stack[0].description.as_deref(), Some("粘贴译文"));
+            assert!(matches!(&stack[0].action_type, ActionType::Composite { actions } if actions.len() == 3));
+        }
+
+        assert!(undo_service.undo(project_id.0).success);
+        assert!(get_marker_storage(marker_id).unwrap().is_none());
+        assert!(!undo_service.can_undo(project_id).unwrap());
+
+        assert!(undo_service.redo(project_id.0).success);
+        let marker = get_marker_storage(marker_id).unwrap().unwrap();
+        assert_eq!(marker.translation, "译文");
+        assert_eq!(marker.style, MarkerStyle::new(true, true));
+    }
+
+    #[test]
+    fn test_abandoned_group_rolls_back() {
+        let service = crate::service::get_service();
+        let (project_id, image_id) = project_with_image("undo_group_abort");
+        let undo_service = &service.undo_redo_service;
+
+        // 守卫没有finish就被丢弃，分组内的修改被撤回且不留撤销记录
+        {
+            let _guard = undo_service.begin_group_guard(project_id, "批量机翻".to_string()).unwrap();
+            add_point_marker_to_image(image_id.0, 10.0, 10.0, Some("a".to_string()), None).unwrap();
+        }
+        assert!(crate::storage::marker::get_image_markers_storage(image_id).unwrap().is_empty());
+        assert!(!undo_service.can_undo(project_id).unwrap());
+        assert!(!undo_service.abort_group(project_id).unwrap());
+
+        // 未结束的分组在撤销前入栈
+        undo_service.begin_group(project_id, "未结束".to_string()).unwrap();
+        add_point_marker_to_image(image_id.0, 10.0, 10.0, None, None).unwrap();
+        assert!(undo_service.undo(project_id.0).success);
+        assert!(crate::storage::marker::get_image_markers_storage(image_id).unwrap().is_empty());
+        assert!(!UNDO_REDO_STACK.is_grouping(project_id).unwrap());
+    }
+
+    #[test]
+    fn test_unsaved_changes_follow_saved_commit() {
+        let stack = UndoRedoStack::new();
+        let project_id = ProjectId(9001);
+        let action = || UndoRedoAction::new(ActionType::UpdateProjectName { old_name: "a".to_string(), new_name: "b".to_string() }, project_id);
+        assert!(!stack.has_unsaved_changes(project_id).unwrap());
+
+        stack.push_action(action()).unwrap();
+        assert!(stack.has_unsaved_changes(project_id).unwrap());
+        stack.mark_saved(project_id).unwrap();
+        assert!(!stack.has_unsaved_changes(project_id).unwrap());
+
+        // 撤销到保存点之前也算未保存
+        let undone = stack.pop_undo_action(project_id).unwrap().unwrap();
+        assert!(stack.has_unsaved_changes(project_id).unwrap());
+        stack.push_to_undo_stack(project_id, undone).unwrap();
+        assert!(!stack.has_unsaved_changes(project_id).unwrap());
+    }
+
+    #[test]
+    fn test_rapid_moves_coalesce_into_one_undo() {
+        use crate::api::marker::update_point_marker_position;
+        use crate::storage::marker::MarkerGeometry;
+        let undo_service = &crate::service::get_service().undo_redo_service;
+        let (project_id, image_id) = project_with_image("undo_coalesce");
+        let marker_id = add_point_marker_to_image(image_id.0, 10.0, 10.0, None, None).unwrap().unwrap();
+        let other_id = add_point_marker_to_image(image_id.0, 50.0, 50.0, None, None).unwrap().unwrap();
+        let undo_len = || UNDO_REDO_STACK.project_stacks.read().unwrap()[&project_id].undo_stack.len();
+        let position = |id: u32| match get_marker_storage(MarkerId(id)).unwrap().unwrap().geometry {
+            MarkerGeometry::Point { x, y } => (x, y),
+            _ => unreachable!(),
+        };
+        let before = undo_len();
+        FIXED_NOW.set(Some(1_000));
+
+        for step in 1..=10 {
+            assert!(update_point_marker_position(marker_id, 10.0 + step as f64, 20.0, None).unwrap());
+        }
+        assert_eq!(undo_len(), before + 1);
+        // 另一个标记的修改不并入
+        update_point_marker_position(other_id, 60.0, 60.0, None).unwrap();
+        update_marker_translation(marker_id, "一".to_string()).unwrap();
+        update_marker_translation(marker_id, "一二".to_string()).unwrap();
+        assert_eq!(undo_len(), before + 3);
+
+        assert!(undo_service.undo(project_id.0).success);
+        assert_eq!(get_marker_storage(MarkerId(marker_id)).unwrap().unwrap().translation, "");
+        assert!(undo_service.undo(project_id.0).success);
+        assert!(undo_service.undo(project_id.0).success);
+        assert_eq!(position(marker_id), (10.0, 10.0));
+        assert!(undo_service.redo(project_id.0).success);
+        assert_eq!(position(marker_id), (20.0, 20.0));
+
+        // 撤销后的新修改不并入重做回来的操作
+        update_point_marker_position(marker_id, 30.0, 30.0, None).unwrap();
+        assert_eq!(undo_len(), before + 2);
+    }
+
+    #[test]
+    fn test_coalescing_respects_window_and_save_point() {
+        let stack = UndoRedoStack::new();
+        let project_id = ProjectId(9002);
+        let move_to = |x: f64| UndoRedoAction::new(
+            ActionType::UpdatePointMarkerPosition { id: MarkerId(1), old_pos: (x - 1.0, 0.0), new_pos: (x, 0.0) },
+            project_id,
+        );
+        let undo_len = || stack.project_stacks.read().unwrap()[&project_id].undo_stack.len();
+
+        stack.push_action_at(move_to(1.0), 1_000).unwrap();
+        stack.push_action_at(move_to(2.0), 1_000 + DEFAULT_COALESCE_WINDOW_MS).unwrap();
+        assert_eq!(undo_len(), 1);
+        {
+            let stacks = stack.project_stacks.read().unwrap();
+            assert!(matches!(stacks[&project_id].undo_stack[0].action_type,
+                ActionType::UpdatePointMarkerPosition { old_pos: (0.0, 0.0), new_pos: (2.0, 0.0), .. }));
+        }
+
+        // 保存后的修改单独撤销，合并过的操作也算未保存
+        stack.mark_saved(project_id).unwrap();
+        stack.push_action_at(move_to(3.0), 2_000).unwrap();
+        assert_eq!(undo_len(), 2);
+        stack.push_action_at(move_to(4.0), 2_100).unwrap();
+        assert_eq!(undo_len(), 2);
+        assert!(stack.has_unsaved_changes(project_id).unwrap());
+
+        // 间隔超过窗口时单独撤销，窗口从上一次合并的时间算起
+        stack.push_action_at(move_to(5.0), 2_100 + DEFAULT_COALESCE_WINDOW_MS + 1).unwrap();
+        assert_eq!(undo_len(), 3);
+
+        stack.set_coalesce_window(0);
+        stack.push_action_at(move_to(6.0), 2_700).unwrap();
+        assert_eq!(undo_len(), 4);
+    }
+
+    #[test]
+    fn test_revert_translation_is_undoable() {
+        use crate::api::marker::{get_translation_history, revert_translation};
+        use crate::storage::TranslationOrigin;
+        let (project_id, image_id) = project_with_image("translation_history");
+        let marker_id = add_point_marker_to_image(image_id.0, 10.0, 10.0, None, None).unwrap().unwrap();
+        update_marker_translation(marker_id, "初稿".to_string()).unwrap();
+        update_marker_translation(marker_id, "校对稿".to_string()).unwrap();
+        let history = get_translation_history(marker_id);
+        assert_eq!(history.iter().map(|e| e.translation.as_str()).collect::<Vec<_>>(), vec!["初稿", "校对稿"]);
+        assert!(history.iter().all(|e| e.origin == TranslationOrigin::Manual));
+
+        assert_eq!(revert_translation(marker_id, 0), Ok(true));
+        assert_eq!(get_marker_storage(MarkerId(marker_id)).unwrap().unwrap().translation, "初稿");
+        assert_eq!(get_translation_history(marker_id).len(), 3);
+        assert!(revert_translation(marker_id, 3).is_err());
+
+        // 撤销回退恢复校对稿，撤销本身不写入历史
+        crate::api::undo_redo::undo(project_id.0);
+        assert_eq!(get_marker_storage(MarkerId(marker_id)).unwrap().unwrap().translation, "校对稿");
+        assert_eq!(get_translation_history(marker_id).len(), 3);
+    }
+}
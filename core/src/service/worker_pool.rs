@@ -0,0 +1,101 @@
+// CPU密集工作的线程池 - 裁剪、哈希、预处理等批量操作在这里并行执行，不占用调用方线程做完再逐个处理
+// 线程数有上限，WASM端使用init_thread_pool创建的Worker；线程池建不起来时（如没有开启跨源隔离）退回顺序执行
+use std::sync::atomic::{AtomicUsize, Ordering};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// 原生平台的线程数上限
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_WORKERS: usize = 4;
+/// WASM端每个Worker都有独立的内存开销，线程数更少
+#[cfg(target_arch = "wasm32")]
+pub const MAX_WORKERS: usize = 2;
+
+/// 同时排队的后台任务上限，超过时spawn拒绝新任务
+pub const MAX_PENDING_JOBS: usize = 256;
+
+pub struct WorkerPool {
+    pool: Option<rayon::ThreadPool>,
+    pending: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerPoolStats {
+    /// 0表示线程池不可用，任务在调用方线程顺序执行
+    pub threads: usize,
+    pub pending_jobs: usize,
+}
+
+impl WorkerPool {
+    pub fn new(max_workers: usize) -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .clamp(1, max_workers.max(1));
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("bf-worker-{}", index))
+            .build();
+        if let Err(e) = &pool {
+            crate::common::Logger::warn(&format!("Worker pool unavailable, running sequentially: {}", e));
+        }
+        Self { pool: pool.ok(), pending: AtomicUsize::new(0) }
+    }
+
+    /// 对每一项并行执行f，结果保持输入顺序；调用方阻塞到全部完成
+    pub fn map<T, R, F>(&self, items: Vec<T>, f: F) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> R + Sync + Send,
+    {
+        match &self.pool {
+            Some(pool) if items.len() > 1 => pool.install(|| items.into_par_iter().map(f).collect()),
+            _ => items.into_iter().map(f).collect(),
+        }
+    }
+
+    /// 在后台执行任务，不等待结果；排队的任务达到上限时返回false
+    pub fn spawn<F>(&'static self, job: F) -> bool
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let Some(pool) = &self.pool else {
+            job();
+            return true;
+        };
+        if self.pending.fetch_add(1, Ordering::SeqCst) >= MAX_PENDING_JOBS {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+        pool.spawn(move || {
+            job();
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        });
+        true
+    }
+
+    pub fn stats(&self) -> WorkerPoolStats {
+        WorkerPoolStats {
+            threads: self.pool.as_ref().map_or(0, |pool| pool.current_num_threads()),
+            pending_jobs: self.pending.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// 图片和海兔服务共用的线程池，首次使用时创建（WASM端需在init_thread_pool之后）
+pub static CPU_POOL: Lazy<WorkerPool> = Lazy::new(|| WorkerPool::new(MAX_WORKERS));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_order() {
+        let pool = WorkerPool::new(3);
+        let result = pool.map((0..100).collect(), |n: u32| n * 2);
+        assert_eq!(result, (0..100).map(|n| n * 2).collect::<Vec<_>>());
+        assert!(pool.stats().threads <= 3);
+    }
+}
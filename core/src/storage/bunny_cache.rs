@@ -1,4 +1,4 @@
-use crate::common::{CoreResult, MarkerId};
+use crate::common::{events::get_timestamp_millis, CoreResult, MarkerId, OcrRegion, TranslationWarning};
 use crate::storage::traits::Storage;
 use crate::storage::state::APP_STATE;
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,18 @@ pub struct BunnyCacheData {
     pub machine_translation: Option<String>,
     pub last_ocr_model: Option<String>,
     pub last_translation_service: Option<String>,
+    /// 插件校验机器翻译时发现的术语问题
+    #[serde(default)]
+    pub translation_warnings: Vec<TranslationWarning>,
+    /// 插件随机翻附带的元数据（模型、提示词哈希等），用于事后追查译文来源
+    #[serde(default)]
+    pub translation_metadata: Option<serde_json::Value>,
+    /// 最近一次OCR返回的文本行位置，插件不支持时为空
+    #[serde(default)]
+    pub ocr_regions: Vec<OcrRegion>,
+    /// 机器翻译写入的时间（毫秒），接受机翻前用来判断译文是否在此之后被改过
+    #[serde(default)]
+    pub machine_translated_at: Option<u64>,
 }
 
 impl BunnyCacheData {
@@ -21,6 +33,10 @@ impl BunnyCacheData {
             machine_translation: None,
             last_ocr_model: None,
             last_translation_service: None,
+            translation_warnings: Vec::new(),
+            translation_metadata: None,
+            ocr_regions: Vec::new(),
+            machine_translated_at: None,
         }
     }
 }
@@ -88,26 +104,61 @@ pub fn update_original_text_storage(
     Ok(())
 }
 
+/// 替换标记最近一次OCR的文本行位置
+pub fn set_ocr_regions_storage(marker_id: MarkerId, regions: Vec<OcrRegion>) -> CoreResult<()> {
+    let mut storage = APP_STATE.bunny_cache.write()?;
+    if let Some(cache_data) = storage.get_mut(&marker_id) {
+        cache_data.ocr_regions = regions;
+    } else if !regions.is_empty() {
+        let mut cache_data = BunnyCacheData::new(marker_id);
+        cache_data.ocr_regions = regions;
+        storage.insert(marker_id, cache_data)?;
+    }
+    Ok(())
+}
+
 pub fn update_machine_translation_storage(
     marker_id: MarkerId,
     text: String,
     service: String,
+    warnings: Vec<TranslationWarning>,
+    metadata: Option<serde_json::Value>,
 ) -> CoreResult<()> {
     let mut storage = APP_STATE.bunny_cache.write()?;
 
     if let Some(cache_data) = storage.get_mut(&marker_id) {
         cache_data.machine_translation = Some(text);
         cache_data.last_translation_service = Some(service);
+        cache_data.translation_warnings = warnings;
+        cache_data.translation_metadata = metadata;
+        cache_data.machine_translated_at = Some(get_timestamp_millis());
     } else {
         let mut cache_data = BunnyCacheData::new(marker_id);
         cache_data.machine_translation = Some(text);
         cache_data.last_translation_service = Some(service);
+        cache_data.translation_warnings = warnings;
+        cache_data.translation_metadata = metadata;
+        cache_data.machine_translated_at = Some(get_timestamp_millis());
         storage.insert(marker_id, cache_data)?;
     }
 
     Ok(())
 }
 
+/// 只清掉机器翻译及其校验结果，原文保留；返回清除前是否有机翻
+pub fn clear_machine_translation_storage(marker_id: MarkerId) -> CoreResult<bool> {
+    let mut storage = APP_STATE.bunny_cache.write()?;
+    let Some(cache_data) = storage.get_mut(&marker_id) else {
+        return Ok(false);
+    };
+    let had = cache_data.machine_translation.take().is_some();
+    cache_data.last_translation_service = None;
+    cache_data.translation_warnings.clear();
+    cache_data.translation_metadata = None;
+    cache_data.machine_translated_at = None;
+    Ok(had)
+}
+
 pub fn clear_bunny_cache_storage(marker_id: MarkerId) -> CoreResult<()> {
     let mut storage = APP_STATE.bunny_cache.write()?;
     storage.remove(&marker_id);
@@ -1,7 +1,17 @@
 use crate::storage::ImageFormat;
+use crate::common::dto::image::ColorMode;
 use std::path::PathBuf;
 use std::io::Read;
 
+/// 从文件头读取的图片信息，DPI和颜色模式在格式不提供时为None
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub dpi: Option<(u32, u32)>,
+    pub color_mode: Option<ColorMode>,
+    pub bit_depth: Option<u8>,
+}
 
 pub fn extract_dimensions_from_bytes(data: &[u8], format: &ImageFormat) -> Option<(u32, u32)> {
     match format {
@@ -13,6 +23,25 @@ pub fn extract_dimensions_from_bytes(data: &[u8], format: &ImageFormat) -> Optio
     }
 }
 
+pub fn extract_image_info_from_bytes(data: &[u8], format: &ImageFormat) -> Option<ImageInfo> {
+    let (width, height) = extract_dimensions_from_bytes(data, format)?;
+    let mut info = ImageInfo { width, height, ..Default::default() };
+    match format {
+        ImageFormat::Png => read_png_info(data, &mut info),
+        ImageFormat::Jpeg => read_jpeg_info(data, &mut info),
+        ImageFormat::Gif => {
+            info.color_mode = Some(ColorMode::Indexed);
+            // 全局颜色表大小字段表示每个像素的位数
+            if let Some(packed) = data.get(10) {
+                info.bit_depth = Some((packed & 0x07) + 1);
+            }
+        }
+        ImageFormat::Webp => read_webp_info(data, &mut info),
+        ImageFormat::Bmp => read_bmp_info(data, &mut info),
+    }
+    Some(info)
+}
+
 pub fn extract_dimensions_from_file(path: &PathBuf, format: &ImageFormat) -> std::io::Result<Option<(u32, u32)>> {
     use std::fs::File;
     
@@ -148,3 +177,179 @@ fn extract_bmp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
     None
 }
 
+// 像素/米 换算为 DPI
+fn ppm_to_dpi(ppm: u32) -> u32 {
+    (ppm as f64 * 0.0254).round() as u32
+}
+
+fn read_png_info(data: &[u8], info: &mut ImageInfo) {
+    if data.len() < 26 {
+        return;
+    }
+    info.bit_depth = Some(data[24]);
+    info.color_mode = match data[25] {
+        0 => Some(ColorMode::Grayscale),
+        2 => Some(ColorMode::Rgb),
+        3 => Some(ColorMode::Indexed),
+        4 => Some(ColorMode::GrayscaleAlpha),
+        6 => Some(ColorMode::Rgba),
+        _ => None,
+    };
+
+    // pHYs块位于IDAT之前
+    let mut pos = 8;
+    while pos + 8 <= data.len() {
+        let length = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        if chunk_type == b"IDAT" || chunk_type == b"IEND" {
+            break;
+        }
+        if chunk_type == b"pHYs" && length >= 9 && pos + 17 <= data.len() {
+            let body = &data[pos + 8..pos + 17];
+            let x = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+            let y = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+            // 单位为1时表示像素/米，为0时只表示宽高比
+            if body[8] == 1 && x > 0 && y > 0 {
+                info.dpi = Some((ppm_to_dpi(x), ppm_to_dpi(y)));
+            }
+            break;
+        }
+        pos += 12 + length;
+    }
+}
+
+fn read_jpeg_info(data: &[u8], info: &mut ImageInfo) {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = &data[(pos + 4).min(data.len())..(pos + 2 + length).min(data.len())];
+
+        // APP0 JFIF: 密度单位 1=英寸 2=厘米
+        if marker == 0xE0 && segment.len() >= 12 && &segment[0..5] == b"JFIF\0" {
+            let x = u16::from_be_bytes([segment[8], segment[9]]) as u32;
+            let y = u16::from_be_bytes([segment[10], segment[11]]) as u32;
+            info.dpi = match segment[7] {
+                1 if x > 0 && y > 0 => Some((x, y)),
+                2 if x > 0 && y > 0 => Some(((x as f64 * 2.54).round() as u32, (y as f64 * 2.54).round() as u32)),
+                _ => info.dpi,
+            };
+        }
+
+        if (0xC0..=0xCF).contains(&marker) && marker != 0xC4 && marker != 0xC8 && marker != 0xCC {
+            if segment.len() >= 6 {
+                info.bit_depth = Some(segment[0]);
+                info.color_mode = match segment[5] {
+                    1 => Some(ColorMode::Grayscale),
+                    3 => Some(ColorMode::Rgb),
+                    4 => Some(ColorMode::Cmyk),
+                    _ => None,
+                };
+            }
+            return;
+        }
+        pos += 2 + length;
+    }
+}
+
+fn read_webp_info(data: &[u8], info: &mut ImageInfo) {
+    if data.len() < 30 {
+        return;
+    }
+    info.bit_depth = Some(8);
+    info.color_mode = match &data[12..16] {
+        b"VP8X" => Some(if data[20] & 0x10 != 0 { ColorMode::Rgba } else { ColorMode::Rgb }),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes([data[21], data[22], data[23], data[24]]);
+            Some(if bits & (1 << 28) != 0 { ColorMode::Rgba } else { ColorMode::Rgb })
+        }
+        _ => Some(ColorMode::Rgb),
+    };
+}
+
+fn read_bmp_info(data: &[u8], info: &mut ImageInfo) {
+    if data.len() < 46 {
+        return;
+    }
+    let bits_per_pixel = u16::from_le_bytes([data[28], data[29]]);
+    info.color_mode = match bits_per_pixel {
+        1 | 4 | 8 => Some(ColorMode::Indexed),
+        24 => Some(ColorMode::Rgb),
+        32 => Some(ColorMode::Rgba),
+        _ => None,
+    };
+    info.bit_depth = match bits_per_pixel {
+        24 | 32 => Some(8),
+        1 | 4 | 8 => Some(bits_per_pixel as u8),
+        _ => None,
+    };
+    let x = i32::from_le_bytes([data[38], data[39], data[40], data[41]]);
+    let y = i32::from_le_bytes([data[42], data[43], data[44], data[45]]);
+    if x > 0 && y > 0 {
+        info.dpi = Some((ppm_to_dpi(x as u32), ppm_to_dpi(y as u32)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_with_phys(color_type: u8, ppm: Option<u32>) -> Vec<u8> {
+        let mut data = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        data.extend_from_slice(&13u32.to_be_bytes());
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&640u32.to_be_bytes());
+        data.extend_from_slice(&480u32.to_be_bytes());
+        data.extend_from_slice(&[8, color_type, 0, 0, 0]);
+        data.extend_from_slice(&[0; 4]); // CRC
+        if let Some(ppm) = ppm {
+            data.extend_from_slice(&9u32.to_be_bytes());
+            data.extend_from_slice(b"pHYs");
+            data.extend_from_slice(&ppm.to_be_bytes());
+            data.extend_from_slice(&ppm.to_be_bytes());
+            data.push(1);
+            data.extend_from_slice(&[0; 4]);
+        }
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(b"IDAT");
+        data
+    }
+
+    #[test]
+    fn test_png_info() {
+        let info = extract_image_info_from_bytes(&png_with_phys(6, Some(11811)), &ImageFormat::Png).unwrap();
+        assert_eq!((info.width, info.height), (640, 480));
+        assert_eq!(info.color_mode, Some(ColorMode::Rgba));
+        assert_eq!(info.bit_depth, Some(8));
+        assert_eq!(info.dpi, Some((300, 300)));
+
+        // 没有pHYs时DPI为空
+        let info = extract_image_info_from_bytes(&png_with_phys(0, None), &ImageFormat::Png).unwrap();
+        assert_eq!(info.color_mode, Some(ColorMode::Grayscale));
+        assert_eq!(info.dpi, None);
+    }
+
+    #[test]
+    fn test_jpeg_info() {
+        let mut data = vec![0xFF, 0xD8];
+        // APP0 JFIF, 72x72 DPI
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10]);
+        data.extend_from_slice(b"JFIF\0");
+        data.extend_from_slice(&[1, 1, 1, 0, 72, 0, 72, 0, 0]);
+        // SOF0: 8位, 100x200, 1个分量
+        data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 8, 0, 200, 0, 100, 1, 1, 0x11, 0]);
+        data.extend_from_slice(&[0xFF, 0xD9, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let info = extract_image_info_from_bytes(&data, &ImageFormat::Jpeg).unwrap();
+        assert_eq!((info.width, info.height), (100, 200));
+        assert_eq!(info.color_mode, Some(ColorMode::Grayscale));
+        assert_eq!(info.dpi, Some((72, 72)));
+    }
+}
@@ -1,10 +1,12 @@
 use crate::common::CoreResult;
 use crate::common::{ImageId, MarkerId, IMAGE_ID_GENERATOR};
-use crate::common::dto::image::{ImageDTO, ImageMetadataDTO, ImageDataDTO, ImageFormat as ImageFormatDTO};
+use crate::common::dto::image::{ImageDTO, ImageMetadataDTO, ImageDataDTO, ImageFormat as ImageFormatDTO, ImageViewState, ColorMode};
+use crate::storage::dimension_extractor::{self, ImageInfo};
 use crate::storage::traits::Storage;
 use crate::storage::state::APP_STATE;
 use crate::storage::image_data::{ImageData, ImageFormat};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -18,6 +20,51 @@ pub struct ImageMetadata {
     pub format: Option<ImageFormat>,
     pub size: Option<u64>,
     pub checksum: Option<String>,
+    #[serde(default)]
+    pub dpi: Option<(u32, u32)>,
+    #[serde(default)]
+    pub color_mode: Option<ColorMode>,
+    #[serde(default)]
+    pub bit_depth: Option<u8>,
+    // 导入时被缩小的图片记录原始尺寸，像素坐标按原图换算
+    #[serde(default)]
+    pub original_width: Option<u32>,
+    #[serde(default)]
+    pub original_height: Option<u32>,
+    // 查找重复图片用的感知哈希（dHash），图片数据变化时清空
+    #[serde(default)]
+    pub perceptual_hash: Option<u64>,
+    // 校对状态：已审阅的页面在缩略图栏显示勾选，审阅者可留备注
+    #[serde(default)]
+    pub reviewed: bool,
+    #[serde(default)]
+    pub review_note: Option<String>,
+    // 嵌字完成后锁定，锁定的图片上的标记不能再修改
+    #[serde(default)]
+    pub locked: bool,
+    // 查看状态只随bf保存，不进入labelplus导出
+    #[serde(default)]
+    pub view_state: Option<ImageViewState>,
+    // 与项目中其它图片重名而加了序号后缀时，记录原来的文件名
+    #[serde(default)]
+    pub original_name: Option<String>,
+}
+
+impl ImageMetadata {
+    /// 写入从文件头读取的尺寸、DPI和颜色信息
+    pub fn apply_info(&mut self, info: &ImageInfo) {
+        self.width = Some(info.width);
+        self.height = Some(info.height);
+        self.dpi = info.dpi;
+        self.color_mode = info.color_mode;
+        self.bit_depth = info.bit_depth;
+    }
+
+    /// 原图的像素尺寸：缩小过的图片返回缩小前的尺寸
+    pub fn source_dimensions(&self) -> Option<(u32, u32)> {
+        self.original_width.zip(self.original_height)
+            .or_else(|| self.width.zip(self.height))
+    }
 }
 
 // Main Image structure
@@ -33,13 +80,13 @@ impl Image {
         let data = ImageData::FilePath(path.clone());
         
         // Try to read file and process it in parallel
-        let (dimensions, checksum) = if let Ok(file_data) = std::fs::read(&path) {
+        let (info, checksum) = if let Ok(file_data) = std::fs::read(&path) {
             if let Some(format) = data.get_format() {
                 // Process image to extract dimensions and checksum
                         use md5::{Digest, Md5};
                 
-                let (dimensions, checksum) = rayon::join(
-                    || crate::storage::dimension_extractor::extract_dimensions_from_bytes(&file_data, &format),
+                let (info, checksum) = rayon::join(
+                    || dimension_extractor::extract_image_info_from_bytes(&file_data, &format),
                     || {
                         let mut hasher = Md5::new();
                         hasher.update(&file_data);
@@ -48,7 +95,7 @@ impl Image {
                 );
                 
                 let processing_result = (
-                    dimensions,
+                    info,
                     Some(checksum)
                 );
                 (
@@ -56,16 +103,16 @@ impl Image {
                     processing_result.1
                 )
             } else {
-                (data.get_dimensions(), None)
+                (data.get_info(), None)
             }
         } else {
-            (data.get_dimensions(), None)
+            (data.get_info(), None)
         };
         
-        if let Some((width, height)) = dimensions {
+        if let Some(info) = &info {
             crate::common::Logger::info(&format!(
                 "Successfully extracted dimensions from file: {}x{} (path: {:?})",
-                width, height, path
+                info.width, info.height, path
             ));
         }
         
@@ -76,16 +123,32 @@ impl Image {
             ));
         }
         
+        let mut metadata = ImageMetadata {
+            id,
+            name: None,
+            width: None,
+            height: None,
+            format: data.get_format(),
+            size: data.get_size(),
+            checksum,
+            dpi: None,
+            color_mode: None,
+            bit_depth: None,
+            original_width: None,
+            original_height: None,
+            perceptual_hash: None,
+            reviewed: false,
+            review_note: None,
+            locked: false,
+            view_state: None,
+            original_name: None,
+        };
+        if let Some(info) = &info {
+            metadata.apply_info(info);
+        }
+        
         Self {
-            metadata: ImageMetadata {
-                id,
-                name: None,
-                width: dimensions.map(|(w, _)| w),
-                height: dimensions.map(|(_, h)| h),
-                format: data.get_format(),
-                size: data.get_size(),
-                checksum,
-            },
+            metadata,
             data,
             marker_ids: Vec::new(),
         }
@@ -95,8 +158,8 @@ impl Image {
         // Process image to extract dimensions and checksum in parallel
         use md5::{Digest, Md5};
         
-        let (dimensions, checksum) = rayon::join(
-            || crate::storage::dimension_extractor::extract_dimensions_from_bytes(&data, &format),
+        let (info, checksum) = rayon::join(
+            || dimension_extractor::extract_image_info_from_bytes(&data, &format),
             || {
                 let mut hasher = Md5::new();
                 hasher.update(&data);
@@ -104,10 +167,10 @@ impl Image {
             },
         );
         
-        if let Some((width, height)) = dimensions {
+        if let Some(info) = &info {
             crate::common::Logger::info(&format!(
                 "Successfully extracted dimensions from binary data: {}x{} (format: {:?})",
-                width, height, format
+                info.width, info.height, format
             ));
         }
         
@@ -120,16 +183,32 @@ impl Image {
         
         let image_data = ImageData::Binary { format, data: Arc::new(data) };
         
+        let mut metadata = ImageMetadata {
+            id,
+            name: None,
+            width: None,
+            height: None,
+            format: Some(format),
+            size: image_data.get_size(),
+            checksum,
+            dpi: None,
+            color_mode: None,
+            bit_depth: None,
+            original_width: None,
+            original_height: None,
+            perceptual_hash: None,
+            reviewed: false,
+            review_note: None,
+            locked: false,
+            view_state: None,
+            original_name: None,
+        };
+        if let Some(info) = &info {
+            metadata.apply_info(info);
+        }
+        
         Self {
-            metadata: ImageMetadata {
-                id,
-                name: None,
-                width: dimensions.map(|(w, _)| w),
-                height: dimensions.map(|(_, h)| h),
-                format: Some(format),
-                size: image_data.get_size(),
-                checksum,
-            },
+            metadata,
             data: image_data,
             marker_ids: Vec::new(),
         }
@@ -151,6 +230,17 @@ impl Image {
                 format: data.get_format(),
                 size: data.get_size(),
                 checksum: None, // Will be calculated when buffer is accessed
+                dpi: None,
+                color_mode: None,
+                bit_depth: None,
+                original_width: None,
+                original_height: None,
+                perceptual_hash: None,
+                reviewed: false,
+                review_note: None,
+                locked: false,
+                view_state: None,
+                original_name: None,
             },
             data,
             marker_ids: Vec::new(),
@@ -162,12 +252,29 @@ impl Image {
         self
     }
 
+    /// 记录缩小前的原始尺寸
+    pub fn with_original_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.metadata.original_width = Some(width);
+        self.metadata.original_height = Some(height);
+        self
+    }
+
     pub fn with_dimensions(mut self, width: u32, height: u32) -> Self {
         self.metadata.width = Some(width);
         self.metadata.height = Some(height);
         self
     }
 
+    /// 图片数据替换时同步格式、字节大小以及文件头信息
+    fn refresh_data_metadata(&mut self, data: &ImageData) {
+        self.metadata.format = data.get_format();
+        self.metadata.size = data.get_size();
+        self.metadata.perceptual_hash = None;
+        if let Some(info) = data.get_info() {
+            self.metadata.apply_info(&info);
+        }
+    }
+
     pub fn id(&self) -> ImageId {
         self.metadata.id
     }
@@ -204,6 +311,16 @@ impl Image {
                 }),
                 size: self.metadata.size,
                 checksum: self.metadata.checksum.clone(),
+                dpi_x: self.metadata.dpi.map(|(x, _)| x),
+                dpi_y: self.metadata.dpi.map(|(_, y)| y),
+                color_mode: self.metadata.color_mode,
+                bit_depth: self.metadata.bit_depth,
+                original_width: self.metadata.original_width,
+                original_height: self.metadata.original_height,
+                reviewed: self.metadata.reviewed,
+                review_note: self.metadata.review_note.clone(),
+                locked: self.metadata.locked,
+                original_name: self.metadata.original_name.clone(),
             },
             data: match &self.data {
                 ImageData::FilePath(path) => ImageDataDTO::FilePath(path.clone()),
@@ -248,6 +365,17 @@ impl Image {
                 }),
                 size: dto.metadata.size,
                 checksum: dto.metadata.checksum,
+                dpi: dto.metadata.dpi_x.zip(dto.metadata.dpi_y),
+                color_mode: dto.metadata.color_mode,
+                bit_depth: dto.metadata.bit_depth,
+                original_width: dto.metadata.original_width,
+                original_height: dto.metadata.original_height,
+                perceptual_hash: None,
+                reviewed: dto.metadata.reviewed,
+                review_note: dto.metadata.review_note,
+                locked: dto.metadata.locked,
+                view_state: None,
+                original_name: dto.metadata.original_name,
             },
             data: match dto.data {
                 ImageDataDTO::FilePath(path) => ImageData::FilePath(path),
@@ -302,6 +430,32 @@ pub fn add_image_from_binary_storage(format: ImageFormat, data: Vec<u8>, name: O
     Ok(id)
 }
 
+/// 添加占位图片：只有名称和校验和，数据指向同名的（不存在的）文件路径，之后由用户重新链接
+pub fn add_placeholder_image_storage(name: String, checksum: Option<String>) -> CoreResult<ImageId> {
+    let id = IMAGE_ID_GENERATOR.next();
+    let mut image = Image::new_from_path(id, PathBuf::from(&name)).with_name(name);
+    if image.metadata.checksum.is_none() {
+        image.metadata.checksum = checksum;
+    }
+
+    let mut storage = APP_STATE.images.write()?;
+    storage.insert_with_memory_check(id, image)?;
+    Ok(id)
+}
+
+/// 添加导入时缩小过的图片，同时记录原始尺寸
+pub fn add_downscaled_image_storage(format: ImageFormat, data: Vec<u8>, name: Option<String>, original: (u32, u32)) -> CoreResult<ImageId> {
+    let id = IMAGE_ID_GENERATOR.next();
+    let mut image = Image::new_from_binary(id, format, data).with_original_dimensions(original.0, original.1);
+    if let Some(n) = name {
+        image = image.with_name(n);
+    }
+    
+    let mut storage = APP_STATE.images.write()?;
+    storage.insert_with_memory_check(id, image)?;
+    Ok(id)
+}
+
 pub fn add_image_from_shared_buffer_storage(format: ImageFormat, buffer_id: u32, name: Option<String>) -> CoreResult<ImageId> {
     let id = IMAGE_ID_GENERATOR.next();
     let mut image = Image::new_from_shared_buffer(id, format, buffer_id);
@@ -328,6 +482,7 @@ pub fn update_image_data_storage(id: ImageId, data: ImageData) -> CoreResult<boo
     if let Some(image_arc) = storage.get_mut(&id) {
         // Use Arc::make_mut for COW optimization
         let image = Arc::make_mut(image_arc);
+        image.refresh_data_metadata(&data);
         image.data = data;
         Ok(true)
     } else {
@@ -346,13 +501,84 @@ pub fn update_image_name_storage(id: ImageId, name: Option<String>) -> CoreResul
     }
 }
 
+/// 给重名的图片名加序号后缀："01.png" -> "01 (2).png"
+pub fn suffixed_image_name(name: &str, n: usize) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{} ({}){}", &name[..dot], n, &name[dot..]),
+        _ => format!("{} ({})", name, n),
+    }
+}
+
+/// 返回不在taken中的名称，不重复时原样返回
+pub fn unique_image_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (2..).map(|n| suffixed_image_name(name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded suffix search")
+}
+
+/// 按顺序给重复的名称加后缀，第一次出现的保持原名，后缀不会与列表中的其它名称冲突
+pub fn dedupe_image_names(names: &[String]) -> Vec<String> {
+    let mut taken: HashSet<String> = names.iter().cloned().collect();
+    let mut seen = HashSet::new();
+    names.iter().map(|name| {
+        if seen.insert(name.clone()) {
+            return name.clone();
+        }
+        let unique = unique_image_name(name, &taken);
+        taken.insert(unique.clone());
+        seen.insert(unique.clone());
+        unique
+    }).collect()
+}
+
+/// 图片与others中的图片重名时改为加后缀的名称，返回改名后的名称，没有重名时返回None
+pub fn make_image_name_unique_storage(id: ImageId, others: &[ImageId]) -> CoreResult<Option<String>> {
+    let mut storage = APP_STATE.images.write()?;
+    let Some(name) = storage.get(&id).and_then(|image| image.metadata.name.clone()) else {
+        return Ok(None);
+    };
+    let taken: HashSet<String> = others.iter()
+        .filter(|other| **other != id)
+        .filter_map(|other| storage.get(other).and_then(|image| image.metadata.name.clone()))
+        .collect();
+    if !taken.contains(&name) {
+        return Ok(None);
+    }
+    let unique = unique_image_name(&name, &taken);
+    if let Some(image_arc) = storage.get_mut(&id) {
+        rename_duplicate(Arc::make_mut(image_arc), unique.clone());
+    }
+    Ok(Some(unique))
+}
+
+/// 为消除重名而改名，返回旧名称（图片不存在时为None）
+pub fn rename_duplicate_image_storage(id: ImageId, name: String) -> CoreResult<Option<Option<String>>> {
+    let mut storage = APP_STATE.images.write()?;
+    Ok(storage.get_mut(&id).map(|image_arc| {
+        let image = Arc::make_mut(image_arc);
+        let old = image.metadata.name.clone();
+        rename_duplicate(image, name);
+        old
+    }))
+}
+
+// 第一次改名时在original_name中保留原名
+fn rename_duplicate(image: &mut Image, name: String) {
+    if image.metadata.original_name.is_none() {
+        image.metadata.original_name = image.metadata.name.take();
+    }
+    image.metadata.name = Some(name);
+}
+
 pub fn update_image_storage(id: ImageId, data: Option<ImageData>, name: Option<String>) -> CoreResult<bool> {
     let mut storage = APP_STATE.images.write()?;
     if let Some(image_arc) = storage.get_mut(&id) {
         let image = Arc::make_mut(image_arc);
         if let Some(d) = data {
-            image.metadata.format = d.get_format();
-            image.metadata.size = d.get_size();
+            image.refresh_data_metadata(&d);
             image.data = d;
         }
         image.metadata.name = name;
@@ -374,6 +600,90 @@ pub fn update_image_dimensions_storage(id: ImageId, width: u32, height: u32) ->
     }
 }
 
+/// 延迟读取到数据后补全文件头信息和字节大小（SharedBuffer创建时无法读取）
+pub fn update_image_file_info_storage(id: ImageId, info: &ImageInfo, size: u64) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    if let Some(image_arc) = storage.get_mut(&id) {
+        let image = Arc::make_mut(image_arc);
+        image.metadata.apply_info(info);
+        image.metadata.size.get_or_insert(size);
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 文件在磁盘上被替换后，用新读取的信息覆盖尺寸、大小和校验和
+pub fn refresh_image_file_storage(id: ImageId, info: Option<&ImageInfo>, size: u64, checksum: String) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    if let Some(image_arc) = storage.get_mut(&id) {
+        let image = Arc::make_mut(image_arc);
+        if let Some(info) = info {
+            image.metadata.apply_info(info);
+        }
+        image.metadata.size = Some(size);
+        image.metadata.checksum = Some(checksum);
+        image.metadata.perceptual_hash = None;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 记录检测出的实际颜色模式（文件头为RGB但内容是灰度的图片）
+pub fn set_image_color_mode_storage(id: ImageId, color_mode: ColorMode) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    if let Some(image_arc) = storage.get_mut(&id) {
+        let image = Arc::make_mut(image_arc);
+        image.metadata.color_mode = Some(color_mode);
+        image.metadata.bit_depth = Some(8);
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 缓存计算好的感知哈希
+pub fn set_image_perceptual_hash_storage(id: ImageId, hash: u64) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    if let Some(image_arc) = storage.get_mut(&id) {
+        Arc::make_mut(image_arc).metadata.perceptual_hash = Some(hash);
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 设置校对状态，备注为None时清除备注
+pub fn set_image_review_storage(id: ImageId, reviewed: bool, note: Option<String>) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    if let Some(image_arc) = storage.get_mut(&id) {
+        let image = Arc::make_mut(image_arc);
+        image.metadata.reviewed = reviewed;
+        image.metadata.review_note = note;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 设置查看状态，None清除；图片不存在时返回false
+pub fn set_image_view_state_storage(id: ImageId, view_state: Option<ImageViewState>) -> CoreResult<bool> {
+    let mut storage = APP_STATE.images.write()?;
+    Ok(storage.get_mut(&id).map(|image_arc| {
+        Arc::make_mut(image_arc).metadata.view_state = view_state;
+    }).is_some())
+}
+
+/// 设置锁定状态，返回原来的状态（图片不存在时为None）
+pub fn set_image_locked_storage(id: ImageId, locked: bool) -> CoreResult<Option<bool>> {
+    let mut storage = APP_STATE.images.write()?;
+    Ok(storage.get_mut(&id).map(|image_arc| {
+        let image = Arc::make_mut(image_arc);
+        std::mem::replace(&mut image.metadata.locked, locked)
+    }))
+}
+
 pub fn delete_image_storage(id: ImageId) -> CoreResult<bool> {
     let mut storage = APP_STATE.images.write()?;
     Ok(storage.remove(&id).is_some())
@@ -103,6 +103,20 @@ impl ImageData {
         }
     }
 
+    pub fn get_info(&self) -> Option<crate::storage::dimension_extractor::ImageInfo> {
+        let format = self.get_format()?;
+        match self {
+            ImageData::Binary { data, .. } => {
+                crate::storage::dimension_extractor::extract_image_info_from_bytes(data, &format)
+            },
+            ImageData::FilePath(path) => {
+                let data = std::fs::read(path).ok()?;
+                crate::storage::dimension_extractor::extract_image_info_from_bytes(&data, &format)
+            },
+            ImageData::SharedBuffer { .. } => None,
+        }
+    }
+
     pub fn get_dimensions(&self) -> Option<(u32, u32)> {
         match self {
             ImageData::Binary { data, format } => {
@@ -1,8 +1,8 @@
-use crate::common::CoreResult;
+use crate::common::{CoreError, CoreResult};
 use crate::common::{MarkerId, ImageId};
-use crate::common::dto::marker::{MarkerDTO, MarkerStyleDTO, MarkerGeometryDTO};
+use crate::common::dto::marker::{polygon_bounding_box, MarkerDTO, MarkerStyleDTO, MarkerGeometryDTO, PixelGeometry};
 use crate::storage::traits::Storage;
-use crate::storage::state::{APP_STATE, MarkerStorage};
+use crate::storage::state::{APP_STATE, MarkerStorage, MarkersWriteGuard};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -10,6 +10,183 @@ use serde::{Deserialize, Serialize};
 pub enum MarkerGeometry {
     Point { x: f64, y: f64 },
     Rectangle { x: f64, y: f64, width: f64, height: f64 },
+    /// 斜向文字用的多边形（旋转矩形也用它表示），至少3个顶点
+    Polygon { points: Vec<(f64, f64)> },
+}
+
+/// 多边形最少的顶点数
+pub const MIN_POLYGON_POINTS: usize = 3;
+
+impl MarkerGeometry {
+    pub fn to_dto(&self) -> MarkerGeometryDTO {
+        match self {
+            MarkerGeometry::Point { x, y } => MarkerGeometryDTO::Point { x: *x, y: *y },
+            MarkerGeometry::Rectangle { x, y, width, height } => MarkerGeometryDTO::Rectangle { x: *x, y: *y, width: *width, height: *height },
+            MarkerGeometry::Polygon { points } => MarkerGeometryDTO::Polygon { points: points.clone() },
+        }
+    }
+
+    pub fn from_dto(dto: MarkerGeometryDTO) -> Self {
+        match dto {
+            MarkerGeometryDTO::Point { x, y } => MarkerGeometry::Point { x, y },
+            MarkerGeometryDTO::Rectangle { x, y, width, height } => MarkerGeometry::Rectangle { x, y, width, height },
+            MarkerGeometryDTO::Polygon { points } => MarkerGeometry::Polygon { points },
+        }
+    }
+
+    /// 外接矩形 (x, y, width, height)；点的外接矩形宽高为0
+    pub fn bounding_box(&self) -> (f64, f64, f64, f64) {
+        match self {
+            MarkerGeometry::Point { x, y } => (*x, *y, 0.0, 0.0),
+            MarkerGeometry::Rectangle { x, y, width, height } => (*x, *y, *width, *height),
+            MarkerGeometry::Polygon { points } => polygon_bounding_box(points),
+        }
+    }
+
+    /// 多边形退化为外接矩形，其它几何原样返回
+    pub fn to_bounding_rectangle(&self) -> Self {
+        match self {
+            MarkerGeometry::Polygon { points } => {
+                let (x, y, width, height) = polygon_bounding_box(points);
+                MarkerGeometry::Rectangle { x, y, width, height }
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// 锚点：点本身，矩形和多边形取外接矩形左上角
+    pub fn anchor(&self) -> (f64, f64) {
+        let (x, y, _, _) = self.bounding_box();
+        (x, y)
+    }
+
+    /// 百分比坐标 (x, y) 是否在多边形内（奇偶规则）；非多边形按外接矩形判断
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        match self {
+            MarkerGeometry::Polygon { points } => {
+                let mut inside = false;
+                let mut j = points.len().wrapping_sub(1);
+                for (i, &(xi, yi)) in points.iter().enumerate() {
+                    let (xj, yj) = points[j];
+                    if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+                inside
+            }
+            _ => {
+                let (bx, by, width, height) = self.bounding_box();
+                x >= bx && x <= bx + width && y >= by && y <= by + height
+            }
+        }
+    }
+
+    /// 转换为像素坐标，舍入规则见 MarkerGeometryDTO::to_pixels
+    pub fn to_pixels(&self, image_width: u32, image_height: u32) -> PixelGeometry {
+        self.to_dto().to_pixels(image_width, image_height)
+    }
+
+    pub fn from_pixels(pixels: &PixelGeometry, image_width: u32, image_height: u32) -> Self {
+        Self::from_dto(MarkerGeometryDTO::from_pixels(pixels, image_width, image_height))
+    }
+
+    /// 检查坐标是否在 0-100 范围内（矩形需整体位于图片内）
+    pub fn validate(&self) -> CoreResult<()> {
+        fn check(field: &str, value: f64, max: f64) -> CoreResult<()> {
+            if !value.is_finite() || value < -COORDINATE_EPSILON || value > max + COORDINATE_EPSILON {
+                return Err(CoreError::ValidationFailed {
+                    field: field.to_string(),
+                    reason: format!("坐标 {} 超出范围 0-{}", value, max),
+                });
+            }
+            Ok(())
+        }
+
+        match *self {
+            MarkerGeometry::Point { x, y } => {
+                check("x", x, 100.0)?;
+                check("y", y, 100.0)
+            }
+            MarkerGeometry::Rectangle { x, y, width, height } => {
+                check("x", x, 100.0)?;
+                check("y", y, 100.0)?;
+                check("width", width, 100.0 - x)?;
+                check("height", height, 100.0 - y)
+            }
+            MarkerGeometry::Polygon { ref points } => {
+                if points.len() < MIN_POLYGON_POINTS {
+                    return Err(CoreError::ValidationFailed {
+                        field: "points".to_string(),
+                        reason: format!("多边形至少需要 {} 个顶点，实际 {} 个", MIN_POLYGON_POINTS, points.len()),
+                    });
+                }
+                for &(x, y) in points {
+                    check("x", x, 100.0)?;
+                    check("y", y, 100.0)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 将坐标限制到 0-100 范围内，矩形会被裁剪到图片内
+    pub fn clamped(&self) -> Self {
+        match *self {
+            MarkerGeometry::Point { x, y } => MarkerGeometry::Point {
+                x: x.clamp(0.0, 100.0),
+                y: y.clamp(0.0, 100.0),
+            },
+            MarkerGeometry::Rectangle { x, y, width, height } => {
+                let x = x.clamp(0.0, 100.0);
+                let y = y.clamp(0.0, 100.0);
+                MarkerGeometry::Rectangle {
+                    x,
+                    y,
+                    width: width.clamp(0.0, 100.0 - x),
+                    height: height.clamp(0.0, 100.0 - y),
+                }
+            }
+            MarkerGeometry::Polygon { ref points } => MarkerGeometry::Polygon {
+                points: points.iter().map(|&(x, y)| (x.clamp(0.0, 100.0), y.clamp(0.0, 100.0))).collect(),
+            },
+        }
+    }
+
+    /// 按指定模式处理越界坐标；非有限值（NaN/Infinity）在任何模式下都会被拒绝
+    pub fn checked(self, mode: CoordinateMode) -> CoreResult<Self> {
+        let finite = match &self {
+            MarkerGeometry::Point { x, y } => x.is_finite() && y.is_finite(),
+            MarkerGeometry::Rectangle { x, y, width, height } => {
+                x.is_finite() && y.is_finite() && width.is_finite() && height.is_finite()
+            }
+            MarkerGeometry::Polygon { points } => points.iter().all(|(x, y)| x.is_finite() && y.is_finite()),
+        };
+        match mode {
+            // 顶点数不足时限制坐标也没用，仍然要报错
+            CoordinateMode::Clamp if finite => {
+                let clamped = self.clamped();
+                clamped.validate()?;
+                Ok(clamped)
+            }
+            _ => {
+                self.validate()?;
+                Ok(self)
+            }
+        }
+    }
+}
+
+// 浮点误差容忍度（百分比）
+const COORDINATE_EPSILON: f64 = 1e-6;
+
+/// 坐标越界时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoordinateMode {
+    #[default]
+    Reject,  // 返回ValidationFailed
+    Clamp,   // 限制到图片范围内
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,17 +195,36 @@ pub struct MarkerStyle {
     pub overlay_text: bool,
     #[serde(rename = "horizontal")]
     pub horizontal: bool,
+    /// 相对字号，1.0为正常大小；None表示不指定，嵌字时使用默认字号
+    #[serde(rename = "sizeHint", default, skip_serializing_if = "Option::is_none")]
+    pub size_hint: Option<f32>,
+    /// 分类标签，如"thought"、"shout"、"narration"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
+/// size_hint的有效范围
+pub const SIZE_HINT_RANGE: (f32, f32) = (0.25, 4.0);
+/// tag的最大长度（字符）
+pub const MAX_TAG_LENGTH: usize = 32;
+
 impl Default for MarkerStyle {
     fn default() -> Self {
         Self {
             overlay_text: false,
             horizontal: false,
+            size_hint: None,
+            tag: None,
         }
     }
 }
 
+impl MarkerStyle {
+    pub fn new(overlay_text: bool, horizontal: bool) -> Self {
+        Self { overlay_text, horizontal, ..Self::default() }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Marker {
     pub id: MarkerId,
@@ -38,6 +234,9 @@ pub struct Marker {
     pub style: MarkerStyle,
     #[serde(rename = "imageIndex")]
     pub image_index: u32,
+    /// 译者备注（双关、直译等），与译文分开保存，不参与嵌字
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 impl Marker {
@@ -49,6 +248,7 @@ impl Marker {
             translation: String::new(),
             style: MarkerStyle::default(),
             image_index,
+            note: None,
         }
     }
 
@@ -60,6 +260,7 @@ impl Marker {
             translation: String::new(),
             style: MarkerStyle::default(),
             image_index,
+            note: None,
         }
     }
 
@@ -71,6 +272,7 @@ impl Marker {
             translation,
             style: MarkerStyle::default(),
             image_index,
+            note: None,
         }
     }
 
@@ -82,6 +284,19 @@ impl Marker {
             translation,
             style: MarkerStyle::default(),
             image_index,
+            note: None,
+        }
+    }
+
+    pub fn polygon_with_translation(id: MarkerId, image_id: ImageId, points: Vec<(f64, f64)>, translation: String, image_index: u32) -> Self {
+        Self {
+            id,
+            image_id,
+            geometry: MarkerGeometry::Polygon { points },
+            translation,
+            style: MarkerStyle::default(),
+            image_index,
+            note: None,
         }
     }
 
@@ -89,18 +304,16 @@ impl Marker {
         MarkerDTO {
             id: self.id,
             image_id: self.image_id,
-            geometry: match &self.geometry {
-                MarkerGeometry::Point { x, y } => MarkerGeometryDTO::Point { x: *x, y: *y },
-                MarkerGeometry::Rectangle { x, y, width, height } => {
-                    MarkerGeometryDTO::Rectangle { x: *x, y: *y, width: *width, height: *height }
-                }
-            },
+            geometry: self.geometry.to_dto(),
             translation: self.translation.clone(),
             style: MarkerStyleDTO {
                 overlay_text: self.style.overlay_text,
                 horizontal: self.style.horizontal,
+                size_hint: self.style.size_hint,
+                tag: self.style.tag.clone(),
             },
             image_index: self.image_index,
+            note: self.note.clone(),
         }
     }
 
@@ -108,139 +321,172 @@ impl Marker {
         Self {
             id: dto.id,
             image_id: dto.image_id,
-            geometry: match dto.geometry {
-                MarkerGeometryDTO::Point { x, y } => MarkerGeometry::Point { x, y },
-                MarkerGeometryDTO::Rectangle { x, y, width, height } => {
-                    MarkerGeometry::Rectangle { x, y, width, height }
-                }
-            },
+            geometry: MarkerGeometry::from_dto(dto.geometry),
             translation: dto.translation,
             style: MarkerStyle {
                 overlay_text: dto.style.overlay_text,
                 horizontal: dto.style.horizontal,
+                size_hint: dto.style.size_hint,
+                tag: dto.style.tag,
             },
             image_index: dto.image_index,
+            note: dto.note,
         }
     }
 }
 
 // Basic storage operations for markers (no business logic)
+// 单个marker和单张图片的操作只锁住所在的分片
 pub fn get_marker_storage(id: MarkerId) -> CoreResult<Option<Marker>> {
-    let storage = APP_STATE.markers.read()?;
-    Ok(storage.get(&id).cloned())
+    Ok(APP_STATE.markers.read_marker(id)?.and_then(|storage| storage.get(&id).cloned()))
 }
 
 pub fn get_image_markers_storage(image_id: ImageId) -> CoreResult<Vec<Marker>> {
-    let storage = APP_STATE.markers.read()?;
+    let storage = APP_STATE.markers.read_image(image_id)?;
     Ok(storage.get_by_image(&image_id).into_iter().cloned().collect())
 }
 
-pub fn update_marker_geometry_storage(id: MarkerId, geometry: MarkerGeometry) -> CoreResult<(bool, Option<ImageId>)> {
-    let mut storage = APP_STATE.markers.write()?;
-    if let Some(marker) = storage.get_mut(&id) {
-        let image_id = marker.image_id;
-        marker.geometry = geometry;
-        Ok((true, Some(image_id)))
-    } else {
-        Ok((false, None))
+// 修改单个marker，返回是否找到以及所属图片
+fn update_marker_with(id: MarkerId, f: impl FnOnce(&mut Marker)) -> CoreResult<(bool, Option<ImageId>)> {
+    let Some(mut storage) = APP_STATE.markers.write_marker(id)? else {
+        return Ok((false, None));
+    };
+    match storage.get_mut(&id) {
+        Some(marker) => {
+            f(marker);
+            Ok((true, Some(marker.image_id)))
+        }
+        None => Ok((false, None)),
     }
 }
 
+pub fn update_marker_geometry_storage(id: MarkerId, geometry: MarkerGeometry) -> CoreResult<(bool, Option<ImageId>)> {
+    update_marker_with(id, |marker| marker.geometry = geometry)
+}
+
 pub fn update_marker_translation_storage(id: MarkerId, translation: String) -> CoreResult<(bool, Option<ImageId>)> {
-    let mut storage = APP_STATE.markers.write()?;
-    if let Some(marker) = storage.get_mut(&id) {
-        let image_id = marker.image_id;
-        marker.translation = translation;
-        Ok((true, Some(image_id)))
-    } else {
-        Ok((false, None))
-    }
+    update_marker_with(id, |marker| marker.translation = translation)
 }
 
 pub fn update_marker_style_storage(id: MarkerId, style: MarkerStyle) -> CoreResult<(bool, Option<ImageId>)> {
-    let mut storage = APP_STATE.markers.write()?;
-    if let Some(marker) = storage.get_mut(&id) {
-        let image_id = marker.image_id;
-        marker.style = style;
-        Ok((true, Some(image_id)))
-    } else {
-        Ok((false, None))
-    }
+    update_marker_with(id, |marker| marker.style = style)
 }
 
 pub fn update_marker_storage(id: MarkerId, geometry: MarkerGeometry, translation: String, style: MarkerStyle) -> CoreResult<(bool, Option<ImageId>)> {
-    let mut storage = APP_STATE.markers.write()?;
-    if let Some(marker) = storage.get_mut(&id) {
-        let image_id = marker.image_id;
+    update_marker_with(id, |marker| {
         marker.geometry = geometry;
         marker.translation = translation;
         marker.style = style;
-        Ok((true, Some(image_id)))
-    } else {
-        Ok((false, None))
-    }
+    })
 }
 
 pub fn clear_all_markers_storage() -> CoreResult<()> {
     let mut storage = APP_STATE.markers.write()?;
     storage.clear();
+    drop(storage);
+    APP_STATE.translation_history.write()?.clear();
     Ok(())
 }
 
 pub fn marker_count_storage() -> CoreResult<usize> {
-    let storage = APP_STATE.markers.read()?;
-    Ok(storage.iter().count())
+    APP_STATE.markers.len()
 }
 
 pub fn marker_exists_storage(id: MarkerId) -> CoreResult<bool> {
-    let storage = APP_STATE.markers.read()?;
-    Ok(storage.contains(&id))
+    Ok(APP_STATE.markers.locate(id)?.is_some())
 }
 
 // Helper function to insert marker at specific index and adjust others
 pub fn insert_marker_at_index(storage: &mut MarkerStorage, marker: Marker) -> CoreResult<()> {
-    let image_id = marker.image_id;
-    let target_index = marker.image_index;
-    
-    // First, adjust image_index for markers that should come after the inserted marker
-    if let Some(marker_ids) = storage.by_image.get(&image_id).cloned() {
-        for marker_id in marker_ids {
-            if let Some(existing_marker) = storage.markers.get_mut(&marker_id) {
-                if existing_marker.image_index >= target_index {
-                    existing_marker.image_index += 1;
-                }
-            }
-        }
-    }
-    
-    // Then insert the marker with its original index
-    storage.insert_with_image(marker)?;
-    
+    storage.insert_at_index(marker)?;
     Ok(())
 }
 
 // Helper function to renumber markers after deletion
 pub fn renumber_image_markers(storage: &mut MarkerStorage, image_id: ImageId) -> CoreResult<()> {
-    // Get all marker IDs for this image
-    if let Some(marker_ids) = storage.by_image.get(&image_id).cloned() {
-        // Sort markers by their current image_index
-        let mut markers_with_index: Vec<(MarkerId, u32)> = marker_ids
-            .iter()
-            .filter_map(|&id| {
-                storage.markers.get(&id).map(|m| (id, m.image_index))
-            })
-            .collect();
-        
-        // Sort by current image_index
-        markers_with_index.sort_by_key(|&(_, index)| index);
-        
-        // Renumber them sequentially starting from 1
-        for (new_index, (marker_id, _)) in markers_with_index.into_iter().enumerate() {
-            if let Some(marker) = storage.markers.get_mut(&marker_id) {
-                marker.image_index = (new_index + 1) as u32;
-            }
-        }
-    }
-    
+    storage.renumber(&image_id);
     Ok(())
-}
\ No newline at end of file
+}
+
+pub use crate::storage::state::MarkerIndexChanges;
+
+/// 把图片的标记序号压缩为连续的1..N（保持当前顺序，序号相同时按ID）
+pub fn compact_image_indices(storage: &mut MarkerStorage, image_id: ImageId) -> MarkerIndexChanges {
+    storage.renumber(&image_id)
+}
+
+/// 按order中的先后把标记序号设为1..N（不在order中的标记不变），返回变化
+pub fn apply_image_index_order(storage: &mut MarkerStorage, order: &[MarkerId]) -> MarkerIndexChanges {
+    let changes: MarkerIndexChanges = order.iter().zip(1u32..)
+        .filter_map(|(marker_id, new_index)| storage.markers.get(marker_id)
+            .filter(|marker| marker.image_index != new_index)
+            .map(|marker| (*marker_id, marker.image_index, new_index)))
+        .collect();
+    let indices: Vec<(MarkerId, u32)> = changes.iter().map(|&(id, _, new_index)| (id, new_index)).collect();
+    storage.set_image_indices(&indices);
+    changes
+}
+
+/// 图片上除except以外各标记的序号，按索引中的顺序
+pub fn image_marker_indices(storage: &MarkersWriteGuard, image_id: ImageId, except: MarkerId) -> Vec<(MarkerId, u32)> {
+    storage.get_by_image(&image_id).into_iter()
+        .filter(|marker| marker.id != except)
+        .map(|marker| (marker.id, marker.image_index))
+        .collect()
+}
+
+/// 把标记放到marker.image_id对应的图片上，并按indices设置两张图片上其他标记的序号
+///
+/// 跨图片移动和它的撤销/重做共用；返回标记原来所在的图片，
+/// 标记不存在时不做修改并返回None。图片的marker_ids由调用方更新。
+pub fn relocate_marker(storage: &mut MarkersWriteGuard, marker: Marker, indices: &[(MarkerId, u32)]) -> CoreResult<Option<ImageId>> {
+    let Some(previous) = storage.remove_with_cleanup(&marker.id) else {
+        return Ok(None);
+    };
+    storage.insert_with_image(marker)?;
+    storage.set_image_indices(indices);
+    Ok(Some(previous.image_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_geometry_bounds() {
+        assert!(MarkerGeometry::Point { x: 0.0, y: 100.0 }.validate().is_ok());
+        assert!(MarkerGeometry::Point { x: 250.0, y: 10.0 }.validate().is_err());
+        assert!(MarkerGeometry::Point { x: 10.0, y: f64::INFINITY }.validate().is_err());
+        assert!(MarkerGeometry::Rectangle { x: 50.0, y: 50.0, width: 50.0, height: 50.0 }.validate().is_ok());
+        assert!(MarkerGeometry::Rectangle { x: 50.0, y: 50.0, width: 60.0, height: 10.0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_checked_modes() {
+        let geometry = MarkerGeometry::Rectangle { x: -10.0, y: 20.0, width: 30.0, height: 200.0 };
+        assert!(geometry.clone().checked(CoordinateMode::Reject).is_err());
+        assert_eq!(
+            geometry.checked(CoordinateMode::Clamp).unwrap(),
+            MarkerGeometry::Rectangle { x: 0.0, y: 20.0, width: 30.0, height: 80.0 }
+        );
+        assert!(MarkerGeometry::Point { x: f64::NAN, y: 0.0 }.checked(CoordinateMode::Clamp).is_err());
+    }
+
+    #[test]
+    fn test_polygon_geometry() {
+        let polygon = MarkerGeometry::Polygon { points: vec![(10.0, 10.0), (50.0, 20.0), (40.0, 60.0), (5.0, 40.0)] };
+        assert!(polygon.validate().is_ok());
+        assert_eq!(polygon.bounding_box(), (5.0, 10.0, 45.0, 50.0));
+        assert_eq!(polygon.to_bounding_rectangle(), MarkerGeometry::Rectangle { x: 5.0, y: 10.0, width: 45.0, height: 50.0 });
+        assert!(polygon.contains(30.0, 30.0));
+        // 在外接矩形内但在多边形外
+        assert!(!polygon.contains(48.0, 55.0));
+
+        assert!(MarkerGeometry::Polygon { points: vec![(0.0, 0.0), (10.0, 10.0)] }.validate().is_err());
+        assert!(MarkerGeometry::Polygon { points: vec![(0.0, 0.0), (10.0, 10.0)] }.checked(CoordinateMode::Clamp).is_err());
+        assert_eq!(
+            MarkerGeometry::Polygon { points: vec![(-5.0, 0.0), (110.0, 10.0), (50.0, 50.0)] }.checked(CoordinateMode::Clamp).unwrap(),
+            MarkerGeometry::Polygon { points: vec![(0.0, 0.0), (100.0, 10.0), (50.0, 50.0)] }
+        );
+    }
+}
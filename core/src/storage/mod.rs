@@ -9,12 +9,13 @@ pub mod dimension_extractor;
 pub mod opening_project;
 pub mod undo_redo;
 pub mod bunny_cache;
+pub mod translation_history;
 
 // Re-export commonly used items
 pub use traits::Storage;
 pub use state::{
     APP_STATE, AppState,
-    ProjectStorage, ImageStorage, MarkerStorage, ThumbnailStorage
+    ProjectStorage, ImageStorage, MarkerStorage, ShardedMarkerStorage, ThumbnailStorage
 };
 pub use project::Project;
 pub use marker::{Marker, MarkerStyle};
@@ -24,4 +25,5 @@ pub use opening_project::{OpeningProject, OpeningProjectStorage, OPENING_PROJECT
 pub use undo_redo::{
     ActionType, UndoRedoAction, ProjectUndoRedoStack, UndoRedoStack, UNDO_REDO_STACK
 };
-pub use bunny_cache::{BunnyCacheData, BunnyCacheStorage};
\ No newline at end of file
+pub use bunny_cache::{BunnyCacheData, BunnyCacheStorage};
+pub use translation_history::{TranslationHistoryEntry, TranslationOrigin};
\ No newline at end of file
@@ -1,7 +1,8 @@
-use crate::common::{CoreError, CoreResult, ProjectId, ImageId, PROJECT_ID_GENERATOR};
+use crate::common::{CancellationToken, CoreError, CoreResult, ProjectId, ImageId, PROJECT_ID_GENERATOR, sort_natural_by_key};
 use crate::storage::project::Project;
-use crate::service::io::labelplus::LabelplusData;
+use crate::service::io::labelplus::{LabelplusCoordinateOrigin, LabelplusData};
 use crate::service::io::bf::BfProjectData;
+use crate::service::project::integrity::IntegrityIssue;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -31,8 +32,23 @@ pub struct OpeningProject {
     pub uploaded_images: HashMap<String, ImageId>,
     /// labelplus原始数据
     pub labelplus_data: Option<LabelplusData>,
+    /// 导入labelplus标记时使用的坐标原点
+    #[serde(default)]
+    pub labelplus_origin: LabelplusCoordinateOrigin,
     /// bf原始数据
     pub bf_data: Option<BfProjectData>,
+    /// 保持图片的上传顺序（拖拽排序后上传），否则finalize时按文件名自然排序
+    #[serde(default)]
+    pub preserve_order: bool,
+    /// 打开文件时检查出的问题，不影响打开
+    #[serde(default)]
+    pub warnings: Vec<IntegrityIssue>,
+    /// 已加入的图片中检测为灰度后估算节省的内存字节数
+    #[serde(default)]
+    pub grayscale_savings_bytes: u64,
+    /// 取消打开时设置，逐张处理图片的流程据此停止
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
 }
 
 impl OpeningProject {
@@ -46,7 +62,12 @@ impl OpeningProject {
             pending_images: required_images,
             uploaded_images: HashMap::new(),
             labelplus_data: None,
+            labelplus_origin: LabelplusCoordinateOrigin::TopLeft,
             bf_data: None,
+            preserve_order: false,
+            warnings: Vec::new(),
+            grayscale_savings_bytes: 0,
+            cancellation: CancellationToken::new(),
         }
     }
     
@@ -123,6 +144,23 @@ impl OpeningProject {
                 }
             }
             self.project.image_ids = ordered_image_ids;
+        } else if !self.preserve_order {
+            // 新建项目没有指定顺序，按文件名自然排序
+            let mut named: Vec<(String, ImageId)> = self.uploaded_images.iter()
+                .map(|(name, &id)| (name.clone(), id))
+                .collect();
+            sort_natural_by_key(&mut named, |(name, _)| name.as_str());
+            let mut ordered_image_ids: Vec<ImageId> = named.into_iter()
+                .map(|(_, id)| id)
+                .filter(|id| self.project.image_ids.contains(id))
+                .collect();
+            // 没有名字记录的图片保持原顺序放在最后
+            for &id in &self.project.image_ids {
+                if !ordered_image_ids.contains(&id) {
+                    ordered_image_ids.push(id);
+                }
+            }
+            self.project.image_ids = ordered_image_ids;
         }
         
         self.project.clone()
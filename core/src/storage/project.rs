@@ -1,5 +1,5 @@
 use crate::common::CoreResult;
-use crate::common::{ProjectId, ImageId, PROJECT_ID_GENERATOR, Language};
+use crate::common::{ProjectId, ImageId, PROJECT_ID_GENERATOR, Language, ThumbnailOrder, ReadingDirection, MarkerTypePreset, ServiceDefaults, ProjectGlossary};
 use crate::common::dto::project::ProjectDTO;
 use crate::storage::traits::Storage;
 use crate::storage::state::APP_STATE;
@@ -17,6 +17,38 @@ pub struct Project {
     pub source_language: Language,
     #[serde(rename = "targetLanguage", default = "Language::default_target")]
     pub target_language: Language,
+    #[serde(rename = "thumbnailOrder", default)]
+    pub thumbnail_order: ThumbnailOrder,
+    #[serde(rename = "pinnedImages", default)]
+    pub pinned_images: Vec<ImageId>,
+    /// 桌面端是否监听图片文件变化
+    #[serde(rename = "watchFiles", default = "default_watch_files")]
+    pub watch_files: bool,
+    #[serde(rename = "readingDirection", default)]
+    pub reading_direction: ReadingDirection,
+    /// 项目自定义的标记类型，为空时使用LabelPlus默认分组
+    #[serde(rename = "markerTypes", default)]
+    pub marker_types: Vec<MarkerTypePreset>,
+    #[serde(rename = "defaultServices", default)]
+    pub default_services: ServiceDefaults,
+    #[serde(default)]
+    pub glossary: ProjectGlossary,
+    /// 项目备注（敬称处理方针、章节背景等），LabelPlus导出时写入项目注释
+    #[serde(default)]
+    pub notes: String,
+    /// 新画的矩形标记是否自动用项目默认OCR服务识别
+    #[serde(rename = "autoOcrOnCreate", default)]
+    pub auto_ocr_on_create: bool,
+    /// 是否把译文历史保存到bf中，默认关闭以免文件变大；历史本身总是保留在内存中
+    #[serde(rename = "keepTranslationHistory", default)]
+    pub keep_translation_history: bool,
+    /// 打开项目时把检测为灰度的Binary图片重新编码为8位灰度PNG，默认关闭
+    #[serde(rename = "grayscaleReencode", default)]
+    pub grayscale_reencode: bool,
+}
+
+pub(crate) fn default_watch_files() -> bool {
+    true
 }
 
 impl Project {
@@ -28,6 +60,17 @@ impl Project {
             file_path: None,
             source_language: Language::default_source(),
             target_language: Language::default_target(),
+            thumbnail_order: ThumbnailOrder::default(),
+            pinned_images: Vec::new(),
+            watch_files: true,
+            reading_direction: ReadingDirection::default_for(Language::default_source()),
+            marker_types: Vec::new(),
+            default_services: ServiceDefaults::default(),
+            glossary: ProjectGlossary::default(),
+            notes: String::new(),
+            auto_ocr_on_create: false,
+            keep_translation_history: false,
+            grayscale_reencode: false,
         }
     }
 
@@ -39,6 +82,17 @@ impl Project {
             file_path: self.file_path.clone(),
             source_language: self.source_language,
             target_language: self.target_language,
+            thumbnail_order: self.thumbnail_order,
+            pinned_images: self.pinned_images.clone(),
+            watch_files: self.watch_files,
+            reading_direction: self.reading_direction,
+            marker_types: self.marker_types.clone(),
+            default_services: self.default_services.clone(),
+            glossary: self.glossary.clone(),
+            notes: self.notes.clone(),
+            auto_ocr_on_create: self.auto_ocr_on_create,
+            keep_translation_history: self.keep_translation_history,
+            grayscale_reencode: self.grayscale_reencode,
         }
     }
 
@@ -50,6 +104,17 @@ impl Project {
             file_path: dto.file_path,
             source_language: dto.source_language,
             target_language: dto.target_language,
+            thumbnail_order: dto.thumbnail_order,
+            pinned_images: dto.pinned_images,
+            watch_files: dto.watch_files,
+            reading_direction: dto.reading_direction,
+            marker_types: dto.marker_types,
+            default_services: dto.default_services,
+            glossary: dto.glossary,
+            notes: dto.notes,
+            auto_ocr_on_create: dto.auto_ocr_on_create,
+            keep_translation_history: dto.keep_translation_history,
+            grayscale_reencode: dto.grayscale_reencode,
         }
     }
 }
@@ -99,6 +164,7 @@ pub fn remove_image_from_project_storage(project_id: ProjectId, image_id: ImageI
     let mut storage = APP_STATE.projects.write()?;
     if let Some(project) = storage.get_mut(&project_id) {
         project.image_ids.retain(|&id| id != image_id);
+        project.pinned_images.retain(|&id| id != image_id);
         Ok(true)
     } else {
         Ok(false)
@@ -116,6 +182,8 @@ pub fn reorder_project_images_storage(project_id: ProjectId, new_order: Vec<Imag
     let mut storage = APP_STATE.projects.write()?;
     if let Some(project) = storage.get_mut(&project_id) {
         project.image_ids = new_order;
+        let image_ids = &project.image_ids;
+        project.pinned_images.retain(|id| image_ids.contains(id));
         Ok(true)
     } else {
         Ok(false)
@@ -136,6 +204,7 @@ pub fn clear_project_images_storage(project_id: ProjectId) -> CoreResult<bool> {
     let mut storage = APP_STATE.projects.write()?;
     if let Some(project) = storage.get_mut(&project_id) {
         project.image_ids.clear();
+        project.pinned_images.clear();
         Ok(true)
     } else {
         Ok(false)
@@ -186,4 +255,75 @@ pub fn update_project_languages_storage(
     } else {
         Ok(false)
     }
-}
\ No newline at end of file
+}
+
+pub fn update_thumbnail_order_storage(id: ProjectId, order: ThumbnailOrder) -> CoreResult<bool> {
+    let mut storage = APP_STATE.projects.write()?;
+    if let Some(project) = storage.get_mut(&id) {
+        project.thumbnail_order = order;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+pub fn update_watch_files_storage(id: ProjectId, enabled: bool) -> CoreResult<bool> {
+    let mut storage = APP_STATE.projects.write()?;
+    if let Some(project) = storage.get_mut(&id) {
+        project.watch_files = enabled;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 设置阅读方向，返回原来的方向；项目不存在时返回None
+pub fn update_reading_direction_storage(id: ProjectId, direction: ReadingDirection) -> CoreResult<Option<ReadingDirection>> {
+    let mut storage = APP_STATE.projects.write()?;
+    Ok(storage.get_mut(&id).map(|project| std::mem::replace(&mut project.reading_direction, direction)))
+}
+
+/// 设置项目备注，项目不存在时返回false
+pub fn update_project_notes_storage(id: ProjectId, notes: String) -> CoreResult<bool> {
+    let mut storage = APP_STATE.projects.write()?;
+    if let Some(project) = storage.get_mut(&id) {
+        project.notes = notes;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 在写锁内修改项目，用于一次更新多个设置（如套用模板）
+pub fn update_project_with_storage<F>(id: ProjectId, update: F) -> CoreResult<bool>
+where
+    F: FnOnce(&mut Project),
+{
+    let mut storage = APP_STATE.projects.write()?;
+    if let Some(project) = storage.get_mut(&id) {
+        update(project);
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// 固定/取消固定图片，固定的图片按固定的先后顺序排列
+pub fn set_image_pinned_storage(project_id: ProjectId, image_id: ImageId, pinned: bool) -> CoreResult<bool> {
+    let mut storage = APP_STATE.projects.write()?;
+    if let Some(project) = storage.get_mut(&project_id) {
+        if !project.image_ids.contains(&image_id) {
+            return Ok(false);
+        }
+        if pinned {
+            if !project.pinned_images.contains(&image_id) {
+                project.pinned_images.push(image_id);
+            }
+        } else {
+            project.pinned_images.retain(|&id| id != image_id);
+        }
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
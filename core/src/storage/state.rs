@@ -5,7 +5,7 @@ use crate::storage::project::Project;
 use crate::storage::marker::Marker;
 use crate::storage::image::Image;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 // Project Storage
 #[derive(Debug, Default)]
@@ -145,49 +145,268 @@ impl Storage<ImageId, Arc<Image>> for ImageStorage {
     }
 }
 
+/// 标记序号变化: (id, 旧序号, 新序号)
+pub type MarkerIndexChanges = Vec<(MarkerId, u32, u32)>;
+
+/// 单张图片的标记顺序：order按image_index排列（序号相同时按插入先后），positions记录每个标记在order中的位置
+/// 追加和按ID查位置为O(1)，中间插入、删除和移动只挪动受影响的区间
+#[derive(Debug, Default, Clone)]
+pub struct ImageMarkerIndex {
+    order: Vec<MarkerId>,
+    positions: HashMap<MarkerId, usize>,
+}
+
+impl ImageMarkerIndex {
+    fn from_ids(order: Vec<MarkerId>) -> Self {
+        let mut index = Self { order, positions: HashMap::new() };
+        index.reposition(0);
+        index
+    }
+
+    pub fn ids(&self) -> &[MarkerId] {
+        &self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn position(&self, id: &MarkerId) -> Option<usize> {
+        self.positions.get(id).copied()
+    }
+
+    fn insert_at(&mut self, position: usize, id: MarkerId) {
+        self.order.insert(position, id);
+        if position + 1 == self.order.len() {
+            self.positions.insert(id, position);
+        } else {
+            self.reposition(position);
+        }
+    }
+
+    fn remove(&mut self, id: &MarkerId) -> Option<usize> {
+        let position = self.positions.remove(id)?;
+        self.order.remove(position);
+        self.reposition(position);
+        Some(position)
+    }
+
+    // 更新from之后各标记的位置
+    fn reposition(&mut self, from: usize) {
+        for (position, id) in self.order.iter().enumerate().skip(from) {
+            self.positions.insert(*id, position);
+        }
+    }
+}
+
 // Marker Storage
+// by_image中的顺序与标记的image_index保持一致，修改序号应通过下面的方法而不是直接写字段
 #[derive(Debug, Default)]
 pub struct MarkerStorage {
     pub(crate) markers: HashMap<MarkerId, Marker>,
-    pub(crate) by_image: HashMap<ImageId, Vec<MarkerId>>,
+    pub(crate) by_image: HashMap<ImageId, ImageMarkerIndex>,
 }
 
 impl MarkerStorage {
+    /// 按marker.image_index放入图片的顺序中，不改动其他标记的序号；序号最大时为O(1)追加
     pub fn insert_with_image(&mut self, marker: Marker) -> CoreResult<MarkerId> {
         let id = marker.id;
         let image_id = marker.image_id;
+        let image_index = marker.image_index;
+        if let Some(previous) = self.markers.insert(id, marker)
+            && let Some(index) = self.by_image.get_mut(&previous.image_id)
+        {
+            index.remove(&id);
+        }
         
-        self.markers.insert(id, marker);
-        self.by_image.entry(image_id).or_default().push(id);
+        let position = self.upper_position(&image_id, image_index);
+        self.by_image.entry(image_id).or_default().insert_at(position, id);
+        self.debug_check_index(&image_id);
         
         Ok(id)
     }
 
+    /// 插入到marker.image_index的位置，该序号及之后的标记后移一位
+    pub fn insert_at_index(&mut self, marker: Marker) -> CoreResult<MarkerId> {
+        let image_id = marker.image_id;
+        let start = self.lower_position(&image_id, marker.image_index);
+        let shifted: Vec<MarkerId> = self.image_marker_ids(&image_id)[start..].to_vec();
+        for id in shifted {
+            if let Some(other) = self.markers.get_mut(&id) {
+                other.image_index += 1;
+            }
+        }
+        self.insert_with_image(marker)
+    }
+
     pub fn get_by_image(&self, image_id: &ImageId) -> Vec<&Marker> {
-        self.by_image
-            .get(image_id)
-            .map(|ids| {
-                ids.iter()
-                    .filter_map(|id| self.markers.get(id))
-                    .collect()
-            })
-            .unwrap_or_default()
+        self.image_marker_ids(image_id)
+            .iter()
+            .filter_map(|id| self.markers.get(id))
+            .collect()
+    }
+
+    /// 图片的标记ID，按序号排列
+    pub fn image_marker_ids(&self, image_id: &ImageId) -> &[MarkerId] {
+        self.by_image.get(image_id).map(ImageMarkerIndex::ids).unwrap_or_default()
+    }
+
+    /// 图片上新标记的序号（当前最大序号+1），O(1)
+    pub fn next_index(&self, image_id: &ImageId) -> u32 {
+        self.image_marker_ids(image_id).iter().rev()
+            .find_map(|id| self.markers.get(id))
+            .map_or(1, |marker| marker.image_index + 1)
     }
 
     pub fn remove_with_cleanup(&mut self, id: &MarkerId) -> Option<Marker> {
-        if let Some(marker) = self.markers.remove(id) {
-            // Clean up by_image index
-            if let Some(ids) = self.by_image.get_mut(&marker.image_id) {
-                ids.retain(|&mid| mid != *id);
-                if ids.is_empty() {
-                    self.by_image.remove(&marker.image_id);
+        let marker = self.markers.remove(id)?;
+        if let Some(index) = self.by_image.get_mut(&marker.image_id) {
+            index.remove(id);
+            if index.is_empty() {
+                self.by_image.remove(&marker.image_id);
+            }
+        }
+        Some(marker)
+    }
+
+    /// 移除图片的全部标记，按序号返回
+    pub fn remove_image(&mut self, image_id: &ImageId) -> Vec<Marker> {
+        let Some(index) = self.by_image.remove(image_id) else {
+            return Vec::new();
+        };
+        index.order.iter().filter_map(|id| self.markers.remove(id)).collect()
+    }
+
+    /// 把标记移到new_index，两个序号之间的标记依次前移或后移一位，返回所有序号变化
+    /// 只挪动两个位置之间的区间；标记不存在时返回None
+    pub fn move_to_index(&mut self, id: MarkerId, new_index: u32) -> Option<MarkerIndexChanges> {
+        let marker = self.markers.get(&id)?;
+        let (image_id, old_index) = (marker.image_id, marker.image_index);
+        let from = self.by_image.get(&image_id)?.position(&id)?;
+        let mut changes = Vec::new();
+        if old_index == new_index {
+            return Some(changes);
+        }
+
+        let range = if old_index < new_index {
+            from..self.upper_position(&image_id, new_index)
+        } else {
+            self.lower_position(&image_id, new_index)..from + 1
+        };
+        let ids = self.by_image.get_mut(&image_id)?;
+        for other_id in &ids.order[range.clone()] {
+            let Some(other) = self.markers.get_mut(other_id) else { continue };
+            let index = if *other_id == id {
+                new_index
+            } else if old_index < new_index && other.image_index > old_index {
+                other.image_index - 1
+            } else if old_index > new_index && other.image_index < old_index {
+                other.image_index + 1
+            } else {
+                continue;
+            };
+            changes.push((*other_id, other.image_index, index));
+            other.image_index = index;
+        }
+        if old_index < new_index {
+            ids.order[range.clone()].rotate_left(1);
+        } else {
+            ids.order[range.clone()].rotate_right(1);
+        }
+        ids.reposition(range.start);
+        self.debug_check_index(&image_id);
+        Some(changes)
+    }
+
+    /// 把图片的序号压缩为连续的1..N（保持当前顺序，序号相同时按ID），返回变化
+    pub fn renumber(&mut self, image_id: &ImageId) -> MarkerIndexChanges {
+        let Some(index) = self.by_image.get_mut(image_id) else {
+            return Vec::new();
+        };
+        // 已排好序时稳定排序只需一趟
+        let markers = &self.markers;
+        index.order.sort_by_key(|id| (markers.get(id).map_or(u32::MAX, |m| m.image_index), id.0));
+        index.reposition(0);
+
+        let mut changes = Vec::new();
+        let mut next = 1;
+        for id in &index.order {
+            let Some(marker) = self.markers.get_mut(id) else { continue };
+            if marker.image_index != next {
+                changes.push((*id, marker.image_index, next));
+                marker.image_index = next;
+            }
+            next += 1;
+        }
+        self.debug_check_index(image_id);
+        changes
+    }
+
+    /// 直接设置若干标记的序号（撤销/重做、跨图片移动），然后重新排列涉及的图片
+    pub fn set_image_indices(&mut self, indices: &[(MarkerId, u32)]) {
+        let mut images = Vec::new();
+        for (id, index) in indices {
+            if let Some(marker) = self.markers.get_mut(id) {
+                marker.image_index = *index;
+                if !images.contains(&marker.image_id) {
+                    images.push(marker.image_id);
                 }
             }
-            Some(marker)
+        }
+        for image_id in images {
+            if let Some(index) = self.by_image.get_mut(&image_id) {
+                let markers = &self.markers;
+                index.order.sort_by_key(|id| markers.get(id).map_or(u32::MAX, |m| m.image_index));
+                index.reposition(0);
+            }
+            self.debug_check_index(&image_id);
+        }
+    }
+
+    /// 覆盖图片的索引条目，不检查条目是否存在（完整性修复及其撤销）
+    pub fn set_index_entries(&mut self, image_id: ImageId, marker_ids: Vec<MarkerId>) {
+        if marker_ids.is_empty() {
+            self.by_image.remove(&image_id);
         } else {
-            None
+            self.by_image.insert(image_id, ImageMarkerIndex::from_ids(marker_ids));
         }
     }
+
+    // 第一个序号不小于image_index的位置（二分查找）
+    fn lower_position(&self, image_id: &ImageId, image_index: u32) -> usize {
+        self.image_marker_ids(image_id)
+            .partition_point(|id| self.markers.get(id).is_some_and(|m| m.image_index < image_index))
+    }
+
+    // 第一个序号大于image_index的位置，追加时直接返回末尾
+    fn upper_position(&self, image_id: &ImageId, image_index: u32) -> usize {
+        let ids = self.image_marker_ids(image_id);
+        match ids.last().and_then(|id| self.markers.get(id)) {
+            Some(last) if last.image_index <= image_index => ids.len(),
+            _ => ids.partition_point(|id| self.markers.get(id).is_some_and(|m| m.image_index <= image_index)),
+        }
+    }
+
+    /// 索引与标记字段是否一致：位置表与顺序对应，顺序按序号排列，标记属于该图片
+    pub fn index_is_consistent(&self, image_id: &ImageId) -> bool {
+        let Some(index) = self.by_image.get(image_id) else {
+            return true;
+        };
+        let positions_match = index.positions.len() == index.order.len()
+            && index.order.iter().enumerate().all(|(position, id)| index.positions.get(id) == Some(&position));
+        let markers: Vec<&Marker> = index.order.iter().filter_map(|id| self.markers.get(id)).collect();
+        positions_match
+            && markers.iter().all(|marker| marker.image_id == *image_id)
+            && markers.windows(2).all(|pair| pair[0].image_index <= pair[1].image_index)
+    }
+
+    fn debug_check_index(&self, image_id: &ImageId) {
+        debug_assert!(self.index_is_consistent(image_id), "marker index of image {} out of sync", image_id.0);
+    }
 }
 
 impl Storage<MarkerId, Marker> for MarkerStorage {
@@ -224,6 +443,245 @@ impl Storage<MarkerId, Marker> for MarkerStorage {
     }
 }
 
+/// 标记分片数，同一张图片的标记总在同一个分片
+pub const MARKER_SHARDS: usize = 16;
+
+fn shard_index(image_id: &ImageId, shard_count: usize) -> usize {
+    image_id.0 as usize % shard_count
+}
+
+// Sharded Marker Storage - 按图片分片加锁，批量编辑一张图片时不阻塞其他图片标记的读取
+// 每个分片是一个完整的MarkerStorage；跨图片的操作用read()/write()按顺序锁住全部分片
+#[derive(Debug)]
+pub struct ShardedMarkerStorage {
+    shards: Vec<RwLock<MarkerStorage>>,
+    // marker所在分片的缓存，只作为查找提示，使用前总会校验
+    locations: RwLock<HashMap<MarkerId, usize>>,
+}
+
+impl Default for ShardedMarkerStorage {
+    fn default() -> Self {
+        Self::new(MARKER_SHARDS)
+    }
+}
+
+impl ShardedMarkerStorage {
+    pub fn new(shard_count: usize) -> Self {
+        Self {
+            shards: (0..shard_count.max(1)).map(|_| RwLock::new(MarkerStorage::default())).collect(),
+            locations: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 只锁住图片所在的分片
+    pub fn read_image(&self, image_id: ImageId) -> CoreResult<RwLockReadGuard<'_, MarkerStorage>> {
+        Ok(self.shards[shard_index(&image_id, self.shards.len())].read()?)
+    }
+
+    pub fn write_image(&self, image_id: ImageId) -> CoreResult<RwLockWriteGuard<'_, MarkerStorage>> {
+        Ok(self.shards[shard_index(&image_id, self.shards.len())].write()?)
+    }
+
+    // 查找marker所在的分片：先试缓存的位置，再逐个分片查找
+    fn find_shard(&self, id: &MarkerId) -> CoreResult<Option<usize>> {
+        let hint = self.locations.read()?.get(id).copied();
+        if let Some(index) = hint && self.shards[index].read()?.contains(id) {
+            return Ok(Some(index));
+        }
+        for (index, shard) in self.shards.iter().enumerate() {
+            if Some(index) == hint {
+                continue;
+            }
+            let found = shard.read()?.contains(id);
+            if found {
+                self.locations.write()?.insert(*id, index);
+                return Ok(Some(index));
+            }
+        }
+        if hint.is_some() {
+            self.locations.write()?.remove(id);
+        }
+        Ok(None)
+    }
+
+    /// marker所属的图片
+    pub fn locate(&self, id: MarkerId) -> CoreResult<Option<ImageId>> {
+        Ok(self.read_marker(id)?.and_then(|shard| shard.get(&id).map(|marker| marker.image_id)))
+    }
+
+    /// 锁住marker所在的分片，marker不存在时返回None
+    pub fn read_marker(&self, id: MarkerId) -> CoreResult<Option<RwLockReadGuard<'_, MarkerStorage>>> {
+        while let Some(index) = self.find_shard(&id)? {
+            let guard = self.shards[index].read()?;
+            // 定位之后marker可能被移动或删除，确认后再返回
+            if guard.contains(&id) {
+                return Ok(Some(guard));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn write_marker(&self, id: MarkerId) -> CoreResult<Option<RwLockWriteGuard<'_, MarkerStorage>>> {
+        while let Some(index) = self.find_shard(&id)? {
+            let guard = self.shards[index].write()?;
+            if guard.contains(&id) {
+                return Ok(Some(guard));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 插入marker并记住它所在的分片
+    pub fn insert_with_image(&self, marker: Marker) -> CoreResult<MarkerId> {
+        let index = shard_index(&marker.image_id, self.shards.len());
+        let id = self.shards[index].write()?.insert_with_image(marker)?;
+        self.locations.write()?.insert(id, index);
+        Ok(id)
+    }
+
+    /// 按分片顺序锁住全部分片，用于跨图片的操作（加载、导出、完整性检查等）
+    pub fn read(&self) -> CoreResult<MarkersReadGuard<'_>> {
+        let shards = self.shards.iter()
+            .map(|shard| shard.read())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MarkersReadGuard { shards })
+    }
+
+    pub fn write(&self) -> CoreResult<MarkersWriteGuard<'_>> {
+        let shards = self.shards.iter()
+            .map(|shard| shard.write())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MarkersWriteGuard { shards, locations: &self.locations })
+    }
+
+    pub fn len(&self) -> CoreResult<usize> {
+        let mut count = 0;
+        for shard in &self.shards {
+            count += shard.read()?.markers.len();
+        }
+        Ok(count)
+    }
+
+    pub fn is_empty(&self) -> CoreResult<bool> {
+        Ok(self.len()? == 0)
+    }
+}
+
+/// 锁住全部分片的只读视图
+pub struct MarkersReadGuard<'a> {
+    shards: Vec<RwLockReadGuard<'a, MarkerStorage>>,
+}
+
+impl MarkersReadGuard<'_> {
+    /// 图片所在的分片
+    pub fn image(&self, image_id: &ImageId) -> &MarkerStorage {
+        &self.shards[shard_index(image_id, self.shards.len())]
+    }
+
+    pub fn get(&self, id: &MarkerId) -> Option<&Marker> {
+        self.shards.iter().find_map(|shard| shard.markers.get(id))
+    }
+
+    pub fn contains(&self, id: &MarkerId) -> bool {
+        self.shards.iter().any(|shard| shard.markers.contains_key(id))
+    }
+
+    pub fn get_by_image(&self, image_id: &ImageId) -> Vec<&Marker> {
+        self.image(image_id).get_by_image(image_id)
+    }
+
+    /// 图片的标记ID，按索引中的顺序
+    pub fn image_marker_ids(&self, image_id: &ImageId) -> &[MarkerId] {
+        self.image(image_id).image_marker_ids(image_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&MarkerId, &Marker)> {
+        self.shards.iter().flat_map(|shard| shard.markers.iter())
+    }
+}
+
+/// 锁住全部分片的可写视图，实现与单个MarkerStorage相同的Storage接口
+pub struct MarkersWriteGuard<'a> {
+    shards: Vec<RwLockWriteGuard<'a, MarkerStorage>>,
+    locations: &'a RwLock<HashMap<MarkerId, usize>>,
+}
+
+impl MarkersWriteGuard<'_> {
+    /// 图片所在的分片，供renumber_image_markers等按图片操作的函数使用
+    pub fn image_mut(&mut self, image_id: &ImageId) -> &mut MarkerStorage {
+        let index = shard_index(image_id, self.shards.len());
+        &mut self.shards[index]
+    }
+
+    pub fn image(&self, image_id: &ImageId) -> &MarkerStorage {
+        &self.shards[shard_index(image_id, self.shards.len())]
+    }
+
+    pub fn insert_with_image(&mut self, marker: Marker) -> CoreResult<MarkerId> {
+        let image_id = marker.image_id;
+        self.image_mut(&image_id).insert_with_image(marker)
+    }
+
+    /// 设置若干标记的序号，标记可以在不同的分片
+    pub fn set_image_indices(&mut self, indices: &[(MarkerId, u32)]) {
+        for shard in self.shards.iter_mut() {
+            shard.set_image_indices(indices);
+        }
+    }
+
+    pub fn get_by_image(&self, image_id: &ImageId) -> Vec<&Marker> {
+        self.image(image_id).get_by_image(image_id)
+    }
+
+    pub fn remove_with_cleanup(&mut self, id: &MarkerId) -> Option<Marker> {
+        self.shards.iter_mut().find_map(|shard| shard.remove_with_cleanup(id))
+    }
+}
+
+impl Storage<MarkerId, Marker> for MarkersWriteGuard<'_> {
+    type Iter<'b> = Box<dyn Iterator<Item = (&'b MarkerId, &'b Marker)> + 'b> where Self: 'b;
+
+    // 与MarkerStorage::insert一样不更新by_image索引；marker换了图片时从原分片移除
+    fn insert(&mut self, key: MarkerId, value: Marker) -> CoreResult<()> {
+        let index = shard_index(&value.image_id, self.shards.len());
+        for (other, shard) in self.shards.iter_mut().enumerate() {
+            if other != index {
+                shard.markers.remove(&key);
+            }
+        }
+        self.shards[index].insert(key, value)
+    }
+
+    fn get(&self, key: &MarkerId) -> Option<&Marker> {
+        self.shards.iter().find_map(|shard| shard.markers.get(key))
+    }
+
+    fn get_mut(&mut self, key: &MarkerId) -> Option<&mut Marker> {
+        self.shards.iter_mut().find_map(|shard| shard.markers.get_mut(key))
+    }
+
+    fn remove(&mut self, key: &MarkerId) -> Option<Marker> {
+        self.shards.iter_mut().find_map(|shard| shard.markers.remove(key))
+    }
+
+    fn contains(&self, key: &MarkerId) -> bool {
+        self.shards.iter().any(|shard| shard.markers.contains_key(key))
+    }
+
+    fn clear(&mut self) {
+        for shard in self.shards.iter_mut() {
+            shard.clear();
+        }
+        if let Ok(mut locations) = self.locations.write() {
+            locations.clear();
+        }
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        Box::new(self.shards.iter().flat_map(|shard| shard.markers.iter()))
+    }
+}
+
 // Thumbnail Storage
 #[derive(Debug, Default)]
 pub struct ThumbnailStorage {
@@ -267,9 +725,10 @@ impl Storage<ImageId, Vec<u8>> for ThumbnailStorage {
 pub struct AppState {
     pub projects: Arc<RwLock<ProjectStorage>>,
     pub images: Arc<RwLock<ImageStorage>>,
-    pub markers: Arc<RwLock<MarkerStorage>>,
+    pub markers: Arc<ShardedMarkerStorage>,
     pub thumbnails: Arc<RwLock<ThumbnailStorage>>,
     pub bunny_cache: Arc<RwLock<super::bunny_cache::BunnyCacheStorage>>,
+    pub translation_history: Arc<RwLock<super::translation_history::TranslationHistoryStorage>>,
 }
 
 impl AppState {
@@ -277,9 +736,10 @@ impl AppState {
         Self {
             projects: Arc::new(RwLock::new(ProjectStorage::default())),
             images: Arc::new(RwLock::new(ImageStorage::default())),
-            markers: Arc::new(RwLock::new(MarkerStorage::default())),
+            markers: Arc::new(ShardedMarkerStorage::default()),
             thumbnails: Arc::new(RwLock::new(ThumbnailStorage::default())),
             bunny_cache: Arc::new(RwLock::new(super::bunny_cache::BunnyCacheStorage::default())),
+            translation_history: Arc::new(RwLock::new(super::translation_history::TranslationHistoryStorage::default())),
         }
     }
 
@@ -287,9 +747,10 @@ impl AppState {
         Self {
             projects: Arc::new(RwLock::new(ProjectStorage::default())),
             images: Arc::new(RwLock::new(ImageStorage::new(max_memory))),
-            markers: Arc::new(RwLock::new(MarkerStorage::default())),
+            markers: Arc::new(ShardedMarkerStorage::default()),
             thumbnails: Arc::new(RwLock::new(ThumbnailStorage::default())),
             bunny_cache: Arc::new(RwLock::new(super::bunny_cache::BunnyCacheStorage::default())),
+            translation_history: Arc::new(RwLock::new(super::translation_history::TranslationHistoryStorage::default())),
         }
     }
 
@@ -305,7 +766,7 @@ impl AppState {
     }
 
     pub fn get_markers_for_image(&self, image_id: ImageId) -> CoreResult<Vec<Marker>> {
-        let storage = self.markers.read()?;
+        let storage = self.markers.read_image(image_id)?;
         Ok(storage.get_by_image(&image_id).into_iter().cloned().collect())
     }
 
@@ -315,6 +776,7 @@ impl AppState {
         self.markers.write()?.clear();
         self.thumbnails.write()?.clear();
         self.bunny_cache.write()?.clear();
+        self.translation_history.write()?.clear();
         Ok(())
     }
 }
@@ -327,4 +789,160 @@ impl Default for AppState {
 
 // Global app state instance
 use once_cell::sync::Lazy;
-pub static APP_STATE: Lazy<AppState> = Lazy::new(AppState::new);
\ No newline at end of file
+pub static APP_STATE: Lazy<AppState> = Lazy::new(AppState::new);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn marker(id: u32, image_id: u32, index: u32) -> Marker {
+        Marker::new_point(MarkerId(id), ImageId(image_id), 0.5, 0.5, index)
+    }
+
+    // 固定种子的xorshift，保证失败可以复现
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_random_edits_keep_indices_contiguous() {
+        let mut storage = MarkerStorage::default();
+        // 每张图片期望的标记顺序
+        let mut models: Vec<Vec<MarkerId>> = vec![Vec::new(); 3];
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        let mut next_id = 1;
+        for step in 0..3000 {
+            let image = rng.below(models.len());
+            let image_id = ImageId(image as u32 + 1);
+            let model = &mut models[image];
+            match rng.below(4) {
+                0 => {
+                    let id = MarkerId(next_id);
+                    next_id += 1;
+                    storage.insert_with_image(marker(id.0, image_id.0, storage.next_index(&image_id))).unwrap();
+                    model.push(id);
+                }
+                1 => {
+                    let id = MarkerId(next_id);
+                    next_id += 1;
+                    let position = rng.below(model.len() + 1);
+                    storage.insert_at_index(marker(id.0, image_id.0, position as u32 + 1)).unwrap();
+                    model.insert(position, id);
+                }
+                2 if !model.is_empty() => {
+                    let id = model.remove(rng.below(model.len()));
+                    let position = rng.below(model.len() + 1);
+                    storage.move_to_index(id, position as u32 + 1).unwrap();
+                    model.insert(position, id);
+                }
+                _ if !model.is_empty() => {
+                    let id = model.remove(rng.below(model.len()));
+                    storage.remove_with_cleanup(&id).unwrap();
+                    storage.renumber(&image_id);
+                }
+                _ => {}
+            }
+
+            assert!(storage.index_is_consistent(&image_id), "step {}", step);
+            let markers = storage.get_by_image(&image_id);
+            assert_eq!(markers.iter().map(|m| m.id).collect::<Vec<_>>(), *model, "step {}", step);
+            assert!(markers.iter().zip(1..).all(|(m, index)| m.image_index == index), "step {}", step);
+            assert_eq!(storage.next_index(&image_id), model.len() as u32 + 1);
+        }
+        assert_eq!(storage.markers.len(), models.iter().map(Vec::len).sum::<usize>());
+    }
+
+    #[test]
+    fn test_move_and_set_indices_report_changes() {
+        let mut storage = MarkerStorage::default();
+        for id in 1..=4 {
+            storage.insert_with_image(marker(id, 1, id)).unwrap();
+        }
+        let changes = storage.move_to_index(MarkerId(4), 2).unwrap();
+        assert_eq!(changes, vec![(MarkerId(2), 2, 3), (MarkerId(3), 3, 4), (MarkerId(4), 4, 2)]);
+        assert_eq!(storage.image_marker_ids(&ImageId(1)), [MarkerId(1), MarkerId(4), MarkerId(2), MarkerId(3)]);
+
+        // 撤销时直接写回旧序号
+        let old: Vec<(MarkerId, u32)> = changes.iter().map(|&(id, old, _)| (id, old)).collect();
+        storage.set_image_indices(&old);
+        assert_eq!(storage.image_marker_ids(&ImageId(1)), [MarkerId(1), MarkerId(2), MarkerId(3), MarkerId(4)]);
+        assert!(storage.index_is_consistent(&ImageId(1)));
+    }
+
+    #[test]
+    fn test_concurrent_edits_keep_index_consistent() {
+        let storage = Arc::new(ShardedMarkerStorage::new(4));
+        let handles: Vec<_> = (0..8u32).map(|image| {
+            let storage = storage.clone();
+            std::thread::spawn(move || {
+                for n in 0..200u32 {
+                    let id = image * 1000 + n;
+                    storage.insert_with_image(marker(id, image, n + 1)).unwrap();
+                    // 同时修改和读取刚插入的标记
+                    storage.write_marker(MarkerId(id)).unwrap().unwrap()
+                        .get_mut(&MarkerId(id)).unwrap().translation = format!("{}", id);
+                    assert_eq!(storage.locate(MarkerId(id)).unwrap(), Some(ImageId(image)));
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(storage.len().unwrap(), 1600);
+        let all = storage.read().unwrap();
+        for image in 0..8u32 {
+            let markers = all.get_by_image(&ImageId(image));
+            assert_eq!(markers.len(), 200);
+            assert!(markers.iter().all(|m| m.image_id == ImageId(image) && m.translation == m.id.0.to_string()));
+        }
+        assert_eq!(all.iter().count(), 1600);
+    }
+
+    #[test]
+    fn test_batch_write_on_one_image_does_not_block_another() {
+        let storage = Arc::new(ShardedMarkerStorage::new(4));
+        storage.insert_with_image(marker(1, 1, 1)).unwrap();
+        storage.insert_with_image(marker(2, 2, 1)).unwrap();
+
+        let batch = storage.write_image(ImageId(1)).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let reader = storage.clone();
+        std::thread::spawn(move || {
+            let count = reader.read_image(ImageId(2)).unwrap().get_by_image(&ImageId(2)).len();
+            let located = reader.locate(MarkerId(2)).unwrap();
+            tx.send((count, located)).unwrap();
+        });
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), (1, Some(ImageId(2))));
+        drop(batch);
+    }
+
+    #[test]
+    fn test_marker_moved_between_shards_is_still_found() {
+        let storage = ShardedMarkerStorage::new(4);
+        storage.insert_with_image(marker(7, 1, 1)).unwrap();
+        assert_eq!(storage.locate(MarkerId(7)).unwrap(), Some(ImageId(1)));
+
+        // 通过全量视图换到另一张图片，缓存的位置失效
+        {
+            let mut all = storage.write().unwrap();
+            all.remove_with_cleanup(&MarkerId(7)).unwrap();
+            all.insert_with_image(marker(7, 2, 1)).unwrap();
+        }
+        assert_eq!(storage.locate(MarkerId(7)).unwrap(), Some(ImageId(2)));
+        assert!(storage.read_image(ImageId(1)).unwrap().get_by_image(&ImageId(1)).is_empty());
+
+        storage.write().unwrap().clear();
+        assert!(storage.write_marker(MarkerId(7)).unwrap().is_none());
+        assert!(storage.is_empty().unwrap());
+    }
+}
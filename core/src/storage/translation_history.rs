@@ -0,0 +1,117 @@
+// 译文历史 - 每个标记最近的若干次译文（带时间和来源），与撤销栈无关，供查看和回退
+// 只在用户修改、插件写入和导入时记录；撤销/重做只是恢复旧值，不算新的历史
+use crate::common::{events::get_timestamp_millis, CoreResult, MarkerId};
+use crate::storage::state::APP_STATE;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 每个标记保留的历史条数，超出时丢弃最旧的
+pub const MAX_TRANSLATION_HISTORY: usize = 10;
+
+/// 译文的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationOrigin {
+    Manual,
+    /// 插件写入（机器翻译等）
+    Machine,
+    /// LabelPlus/嵌字数据导入，或开始记录前就已有的译文
+    Import,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationHistoryEntry {
+    pub translation: String,
+    pub timestamp: u64,
+    pub origin: TranslationOrigin,
+}
+
+#[derive(Debug, Default)]
+pub struct TranslationHistoryStorage {
+    pub(crate) entries: HashMap<MarkerId, VecDeque<TranslationHistoryEntry>>,
+}
+
+impl TranslationHistoryStorage {
+    fn push(&mut self, marker_id: MarkerId, entry: TranslationHistoryEntry) {
+        let history = self.entries.entry(marker_id).or_default();
+        history.push_back(entry);
+        while history.len() > MAX_TRANSLATION_HISTORY {
+            history.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// 记录一次译文修改，old为修改前的译文；没有变化时不记录
+///
+/// 标记第一次被修改时先记下原来的译文（来源记为import），这样打开旧文件后第一次修改前的译文也能找回
+pub fn record_translation_storage(marker_id: MarkerId, old: &str, new: &str, origin: TranslationOrigin) -> CoreResult<()> {
+    if old == new {
+        return Ok(());
+    }
+    let timestamp = get_timestamp_millis();
+    let mut storage = APP_STATE.translation_history.write()?;
+    if !storage.entries.contains_key(&marker_id) && !old.is_empty() {
+        storage.push(marker_id, TranslationHistoryEntry {
+            translation: old.to_string(),
+            timestamp,
+            origin: TranslationOrigin::Import,
+        });
+    }
+    storage.push(marker_id, TranslationHistoryEntry { translation: new.to_string(), timestamp, origin });
+    Ok(())
+}
+
+/// 标记的译文历史，最旧的在前
+pub fn get_translation_history_storage(marker_id: MarkerId) -> CoreResult<Vec<TranslationHistoryEntry>> {
+    let storage = APP_STATE.translation_history.read()?;
+    Ok(storage.entries.get(&marker_id).map(|history| history.iter().cloned().collect()).unwrap_or_default())
+}
+
+/// 直接设置标记的历史（打开bf时恢复），超出上限的部分丢弃最旧的
+pub fn set_translation_history_storage(marker_id: MarkerId, entries: Vec<TranslationHistoryEntry>) -> CoreResult<()> {
+    let mut storage = APP_STATE.translation_history.write()?;
+    storage.entries.remove(&marker_id);
+    for entry in entries {
+        storage.push(marker_id, entry);
+    }
+    Ok(())
+}
+
+/// 删除标记时一起清掉，保证历史占用的内存随标记数量有界
+pub fn clear_translation_history_storage(marker_id: MarkerId) -> CoreResult<()> {
+    APP_STATE.translation_history.write()?.entries.remove(&marker_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_seeded() {
+        let marker_id = MarkerId(u32::MAX - 7);
+        record_translation_storage(marker_id, "原译", "原译", TranslationOrigin::Manual).unwrap();
+        assert!(get_translation_history_storage(marker_id).unwrap().is_empty());
+
+        record_translation_storage(marker_id, "原译", "v0", TranslationOrigin::Manual).unwrap();
+        let history = get_translation_history_storage(marker_id).unwrap();
+        assert_eq!(history[0].translation, "原译");
+        assert_eq!(history[0].origin, TranslationOrigin::Import);
+        assert_eq!(history[1].origin, TranslationOrigin::Manual);
+
+        for i in 1..=20 {
+            record_translation_storage(marker_id, &format!("v{}", i - 1), &format!("v{}", i), TranslationOrigin::Machine).unwrap();
+        }
+        let history = get_translation_history_storage(marker_id).unwrap();
+        assert_eq!(history.len(), MAX_TRANSLATION_HISTORY);
+        assert_eq!(history.last().unwrap().translation, "v20");
+        assert_eq!(history[0].translation, "v11");
+
+        clear_translation_history_storage(marker_id).unwrap();
+        assert!(get_translation_history_storage(marker_id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+// 拖入窗口的文件 - 按类型分类后发给前端确认，确认后由下面的命令交给core已有的打开/导入流程
+// 不支持的文件给出和上传组件相同的ImportError，前端按同一种方式显示
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tauri::{DragDropEvent, Emitter, Runtime, Window, WindowEvent};
+use bubblefish_core::service::image::ImportError;
+use bubblefish_core::service::io::archive::is_archive_extension;
+use bubblefish_core::service::io::labelplus::{LabelplusMergeOptions, LabelplusMergeReport};
+
+pub const FILES_DROPPED_EVENT: &str = "app:files_dropped";
+
+/// 拖入的一组文件整体是什么
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropClassification {
+    /// 一个bf文件或图片压缩包
+    Project,
+    /// 一个LabelPlus翻译文件
+    Translation,
+    /// 只有图片
+    Images,
+    /// 多种文件混在一起，前端提示用户分开拖入
+    Mixed,
+    /// 没有可用的文件
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DroppedKind {
+    Project,
+    Translation,
+    Image,
+}
+
+/// 不支持的文件及原因
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedFile {
+    pub path: String,
+    pub error: ImportError,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilesDroppedPayload {
+    pub classification: DropClassification,
+    /// 可用的文件，拖入文件夹时为其中的图片（按文件名排序）
+    pub paths: Vec<String>,
+    pub rejected: Vec<RejectedFile>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DroppedImagesReport {
+    pub added_image_ids: Vec<u32>,
+    pub rejected: Vec<RejectedFile>,
+}
+
+fn dropped_kind(path: &Path) -> Option<DroppedKind> {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "bf" => Some(DroppedKind::Project),
+        "txt" | "lp" => Some(DroppedKind::Translation),
+        ext if is_archive_extension(ext) => Some(DroppedKind::Project),
+        ext if bubblefish_core::common::dto::image::ImageFormat::from_extension(ext).is_some() => Some(DroppedKind::Image),
+        _ => None,
+    }
+}
+
+// 用上传组件的校验得到同样的错误，扩展名不支持时不会读取文件
+fn rejection(path: &str) -> ImportError {
+    bubblefish_core::api::validate_image_file(path)
+        .err()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| {
+            let name = Path::new(path).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string());
+            ImportError::UnsupportedFormat { message: format!("{}：不支持的文件类型", name), name }
+        })
+}
+
+// 文件夹只取第一层的图片，和导入图片一样按文件名自然排序
+fn images_in_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && dropped_kind(path) == Some(DroppedKind::Image))
+            .collect())
+        .unwrap_or_default();
+    bubblefish_core::common::natural_sort::sort_natural_by_key(&mut images, |path| {
+        path.file_name().and_then(|name| name.to_str()).unwrap_or("")
+    });
+    images
+}
+
+/// 对拖入的路径分类
+pub fn classify_dropped_paths(paths: &[PathBuf]) -> FilesDroppedPayload {
+    let mut accepted: Vec<(DroppedKind, String)> = Vec::new();
+    let mut rejected = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            accepted.extend(images_in_dir(path).into_iter()
+                .map(|image| (DroppedKind::Image, image.to_string_lossy().to_string())));
+            continue;
+        }
+        let path_str = path.to_string_lossy().to_string();
+        match dropped_kind(path) {
+            Some(kind) => accepted.push((kind, path_str)),
+            None => rejected.push(RejectedFile { error: rejection(&path_str), path: path_str }),
+        }
+    }
+
+    let classification = match accepted.as_slice() {
+        [] => DropClassification::Unsupported,
+        [(DroppedKind::Project, _)] => DropClassification::Project,
+        [(DroppedKind::Translation, _)] => DropClassification::Translation,
+        files if files.iter().all(|(kind, _)| *kind == DroppedKind::Image) => DropClassification::Images,
+        _ => DropClassification::Mixed,
+    };
+    FilesDroppedPayload {
+        classification,
+        paths: accepted.into_iter().map(|(_, path)| path).collect(),
+        rejected,
+    }
+}
+
+/// 窗口事件：文件拖放到窗口上时分类并通知该窗口的前端
+pub fn handle_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    if let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+        let payload = classify_dropped_paths(paths);
+        log::info!("Files dropped on {}: {:?}, {} rejected", window.label(), payload.classification, payload.rejected.len());
+        if let Err(e) = window.emit_to(window.label(), FILES_DROPPED_EVENT, &payload) {
+            log::error!("Failed to emit {} event: {}", FILES_DROPPED_EVENT, e);
+        }
+    }
+}
+
+/// 打开拖入的项目文件：预检后走和双击打开文件相同的流程，bf的预检失败时错误为BfFormatError的JSON
+#[tauri::command]
+pub async fn open_dropped_project(window: tauri::WebviewWindow, path: String) -> Result<(), String> {
+    match dropped_kind(Path::new(&path)) {
+        Some(DroppedKind::Project) | Some(DroppedKind::Translation) => {}
+        _ => return Err(rejection(&path).to_json_string()),
+    }
+    if path.to_lowercase().ends_with(".bf") {
+        bubblefish_core::api::io::validate_bf_file(&path)?;
+    } else if !Path::new(&path).is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    window.emit_to(window.label(), "open-file", &path)
+        .map_err(|e| format!("Failed to emit open-file event: {}", e))
+}
+
+/// 把拖入的翻译文件合并到已打开的项目，跳过重复的标记
+#[tauri::command]
+pub async fn import_dropped_translation(project_id: u32, path: String) -> Result<LabelplusMergeReport, String> {
+    if dropped_kind(Path::new(&path)) != Some(DroppedKind::Translation) {
+        return Err(rejection(&path).to_json_string());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read LabelPlus file: {}", e))?;
+    bubblefish_core::api::io::validate_labelplus_file(&content)?;
+    bubblefish_core::api::io::import_labelplus_merge(project_id, &content, LabelplusMergeOptions::default())
+}
+
+/// 把拖入的图片逐张校验后加入项目，校验失败的图片不影响其他图片
+#[tauri::command]
+pub async fn add_dropped_images(project_id: u32, paths: Vec<String>) -> Result<DroppedImagesReport, String> {
+    if bubblefish_core::api::project::get_project_info(project_id).is_none() {
+        return Err(format!("Project {} not found", project_id));
+    }
+    let mut report = DroppedImagesReport::default();
+    for path in paths {
+        if let Err(json) = bubblefish_core::api::validate_image_file(&path) {
+            let error = serde_json::from_str(&json).unwrap_or_else(|_| rejection(&path));
+            report.rejected.push(RejectedFile { path, error });
+            continue;
+        }
+        match bubblefish_core::api::image::add_image_from_path_to_project(project_id, PathBuf::from(&path)) {
+            Some(image_id) => report.added_image_ids.push(image_id),
+            None => {
+                let name = Path::new(&path).file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                let error = ImportError::Corrupt { message: format!("{}：添加到项目失败", name), name };
+                report.rejected.push(RejectedFile { path, error });
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_dropped_paths() {
+        let single = |path: &str| classify_dropped_paths(&[PathBuf::from(path)]).classification;
+        assert_eq!(single("/tmp/chapter.bf"), DropClassification::Project);
+        assert_eq!(single("/tmp/chapter.ZIP"), DropClassification::Project);
+        assert_eq!(single("/tmp/translation.txt"), DropClassification::Translation);
+        assert_eq!(single("/tmp/page.png"), DropClassification::Images);
+
+        let mixed = classify_dropped_paths(&[PathBuf::from("/tmp/chapter.bf"), PathBuf::from("/tmp/page.png")]);
+        assert_eq!(mixed.classification, DropClassification::Mixed);
+
+        let unsupported = classify_dropped_paths(&[PathBuf::from("/tmp/notes.docx")]);
+        assert_eq!(unsupported.classification, DropClassification::Unsupported);
+        assert!(unsupported.paths.is_empty());
+        assert_eq!(unsupported.rejected.len(), 1);
+        assert_eq!(unsupported.rejected[0].error.kind(), "unsupported_format");
+    }
+
+    #[test]
+    fn test_dropped_folder_yields_sorted_images() {
+        let dir = std::env::temp_dir().join(format!("bf_file_drop_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.png", "a10.jpg", "a2.png", "readme.md", "nested/c.png"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        // 只取第一层的图片，按文件名自然排序，其他文件直接忽略
+        let payload = classify_dropped_paths(std::slice::from_ref(&dir));
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(payload.classification, DropClassification::Images);
+        let names: Vec<_> = payload.paths.iter()
+            .map(|path| Path::new(path).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["a2.png", "a10.jpg", "b.png"]);
+        assert!(payload.rejected.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+// 项目图片文件监听 - 清稿在外部覆盖图片后自动刷新，每个打开的项目一个watcher
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+
+// 连续写入合并为一次刷新的时间窗口
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+// 原子替换（删除后重命名）时文件可能短暂不存在，等待重试
+const MISSING_RETRY_INTERVAL: Duration = Duration::from_millis(200);
+const MISSING_RETRY_COUNT: u32 = 5;
+
+#[derive(Default)]
+pub struct FileWatchManager {
+    watchers: Mutex<HashMap<u32, Debouncer<RecommendedWatcher>>>,
+}
+
+// macOS的FSEvents返回真实路径（如/private/var），统一规范化后再比较
+fn normalize_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    // 文件暂时不存在时只规范化目录部分
+    match (path.parent().and_then(|dir| dir.canonicalize().ok()), path.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl FileWatchManager {
+    /// 按项目当前的文件图片重建watcher，返回监听的图片数；项目关闭了监听时停止
+    pub fn watch_project(&self, project_id: u32) -> Result<usize, String> {
+        let Some(project) = bubblefish_core::api::get_project_info(project_id) else {
+            self.unwatch_project(project_id);
+            return Err(format!("Project {} not found", project_id));
+        };
+        if !project.watch_files {
+            self.unwatch_project(project_id);
+            return Ok(0);
+        }
+
+        let targets: HashMap<PathBuf, u32> = project.image_ids.iter()
+            .filter_map(|id| {
+                bubblefish_core::api::get_image_file_path(id.0)
+                    .map(|path| (normalize_path(Path::new(&path)), id.0))
+            })
+            .collect();
+        if targets.is_empty() {
+            self.unwatch_project(project_id);
+            return Ok(0);
+        }
+
+        // 监听图片所在目录而不是文件本身，原子替换后文件的inode变化也能收到事件
+        let dirs: HashSet<PathBuf> = targets.keys()
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+        let count = targets.len();
+
+        let mut debouncer = new_debouncer(DEBOUNCE_INTERVAL, move |result: DebounceEventResult| {
+            match result {
+                Ok(events) => {
+                    let changed: HashSet<(&PathBuf, u32)> = events.iter()
+                        .filter_map(|event| targets.get_key_value(&normalize_path(&event.path)))
+                        .map(|(path, id)| (path, *id))
+                        .collect();
+                    for (path, image_id) in changed {
+                        refresh_image(image_id, path);
+                    }
+                }
+                Err(e) => log::warn!("File watcher error for project {}: {}", project_id, e),
+            }
+        }).map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+        for dir in &dirs {
+            if let Err(e) = debouncer.watcher().watch(dir, RecursiveMode::NonRecursive) {
+                log::warn!("Failed to watch directory {:?}: {}", dir, e);
+            }
+        }
+
+        self.watchers.lock()
+            .map_err(|e| format!("Failed to lock file watchers: {}", e))?
+            .insert(project_id, debouncer);
+        log::info!("Watching {} image files in {} directories for project {}", count, dirs.len(), project_id);
+        Ok(count)
+    }
+
+    /// 停止项目的watcher（项目关闭或关闭监听时）
+    pub fn unwatch_project(&self, project_id: u32) -> bool {
+        self.watchers.lock()
+            .map(|mut watchers| watchers.remove(&project_id).is_some())
+            .unwrap_or(false)
+    }
+}
+
+fn refresh_image(image_id: u32, path: &Path) {
+    for _ in 0..=MISSING_RETRY_COUNT {
+        if path.exists() {
+            match bubblefish_core::api::invalidate_image(image_id) {
+                Ok(true) => log::info!("Reloaded image {} after file change: {:?}", image_id, path),
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to reload image {}: {}", image_id, e),
+            }
+            return;
+        }
+        std::thread::sleep(MISSING_RETRY_INTERVAL);
+    }
+    log::warn!("Image file is missing after change: {:?}", path);
+}
@@ -1,14 +1,28 @@
-use tauri::{Manager, Emitter, Listener, WebviewUrl, WebviewWindowBuilder};
-#[cfg(target_os = "macos")]
-use tauri::TitleBarStyle;
-use tauri::menu::{MenuBuilder, SubmenuBuilder, MenuItemBuilder, CheckMenuItemBuilder};
+use tauri::{Manager, Emitter, Listener};
 
 mod plugin_loader;
-use plugin_loader::{init_plugin_loader, get_plugin_loader, PluginMetadata};
+use plugin_loader::{init_plugin_loader, get_plugin_loader, NetworkSettings, PluginMetadata};
 
 mod plugin_storage;
 use plugin_storage::{PluginStorage, StoredPluginInfo};
 
+mod file_watcher;
+use file_watcher::FileWatchManager;
+
+mod file_drop;
+use file_drop::{open_dropped_project, import_dropped_translation, add_dropped_images};
+
+mod menu_events;
+use menu_events::emit_menu_event;
+
+mod native_menu;
+
+mod windows;
+
+mod quit;
+use quit::confirm_quit;
+use windows::{WindowProjects, open_project_window, get_window_project, set_window_project};
+
 
 // 使用 bubblefish_core 的通用绑定
 // 这会自动生成所有必要的 Tauri 命令和回调设置
@@ -40,6 +54,26 @@ async fn open_image_file_dialog(app_handle: tauri::AppHandle) -> Result<Option<S
     }
 }
 
+// 读取系统剪贴板中的图片，编码为PNG返回；剪贴板中没有图片时返回None
+// 截图工具放进剪贴板的是像素数据，webview的paste事件在部分平台上拿不到
+// 必须是async命令：在主线程读取剪贴板可能在Linux上死锁
+#[tauri::command]
+async fn read_clipboard_image(app_handle: tauri::AppHandle) -> Result<Option<Vec<u8>>, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let image = match app_handle.clipboard().read_image() {
+        Ok(image) => image,
+        Err(e) => {
+            // 剪贴板为空或内容不是图片时也是错误，不区分
+            log::debug!("No image in clipboard: {}", e);
+            return Ok(None);
+        }
+    };
+    bubblefish_core::service::image::encode::encode_rgba_png(image.width(), image.height(), image.rgba())
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
 // 更新菜单项选中状态
 #[tauri::command]
 async fn update_menu_checked_state(app_handle: tauri::AppHandle, menu_id: String, checked: bool) -> Result<(), String> {
@@ -198,22 +232,83 @@ async fn open_multiple_image_files_dialog(app_handle: tauri::AppHandle) -> Resul
     }
 }
 
-// 保存项目到文件（带路径）
-#[tauri::command]
-async fn save_project_to_path(project_id: u32, file_path: String) -> Result<(), String> {
+// 写入项目文件，成功后更新项目路径并记录为已保存（退出确认据此判断未保存的修改）
+fn write_project_file(project_id: u32, file_path: &str) -> Result<(), String> {
     // 获取项目数据
     let data = bubblefish_core::api::io::save_project(project_id)?;
     
     // 写入文件
-    std::fs::write(&file_path, data)
+    std::fs::write(file_path, data)
         .map_err(|e| format!("Failed to save file: {}", e))?;
     
     // 更新项目的文件路径
-    bubblefish_core::api::io::update_project_file_path(project_id, Some(file_path.clone()))?;
+    bubblefish_core::api::io::update_project_file_path(project_id, Some(file_path.to_string()))?;
+    bubblefish_core::api::undo_redo::mark_project_saved(project_id);
     
     Ok(())
 }
 
+// 保存项目到文件（带路径），返回写入的项目ID
+// as_copy为true时另存为独立副本：复制出新项目写入该路径，原项目的路径和未保存状态不变
+#[tauri::command]
+async fn save_project_to_path(project_id: u32, file_path: String, as_copy: Option<bool>) -> Result<u32, String> {
+    if !as_copy.unwrap_or(false) {
+        write_project_file(project_id, &file_path)?;
+        return Ok(project_id);
+    }
+
+    let name = std::path::Path::new(&file_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    let copy_id = bubblefish_core::api::project::duplicate_project(project_id, name)?;
+    // 写入失败时前端拿不到副本ID，删掉副本免得残留在核心里
+    if let Err(e) = write_project_file(copy_id, &file_path) {
+        bubblefish_core::api::project::delete_project(copy_id);
+        return Err(e);
+    }
+    Ok(copy_id)
+}
+
+// 选择保存位置并导出嵌字数据（CSV/JSON），返回保存的路径，用户取消时返回None
+#[tauri::command]
+async fn save_typesetting_data(
+    app_handle: tauri::AppHandle,
+    project_id: u32,
+    format: bubblefish_core::service::io::typesetting::TypesettingFormat,
+    options: Option<bubblefish_core::service::io::typesetting::TypesettingOptions>,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+    use std::sync::mpsc;
+
+    let content = bubblefish_core::api::io::export_typesetting_data(project_id, format, options.unwrap_or_default())?;
+    let project_name = bubblefish_core::api::project::get_project_info(project_id)
+        .map(|project| project.name)
+        .unwrap_or_else(|| "project".to_string());
+    let extension = format.extension();
+
+    let (tx, rx) = mpsc::channel();
+    app_handle.dialog()
+        .file()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("{}_typesetting.{}", project_name, extension))
+        .set_title("导出嵌字数据")
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    match rx.recv() {
+        Ok(Some(file_path)) => {
+            let path = file_path.to_string();
+            std::fs::write(&path, content)
+                .map_err(|e| format!("Failed to save file: {}", e))?;
+            Ok(Some(path))
+        },
+        Ok(None) => Ok(None), // 用户取消了选择
+        Err(_) => Err("Dialog communication error".to_string())
+    }
+}
+
 // 获取项目的文件路径
 #[tauri::command]
 async fn get_project_file_path(project_id: u32) -> Result<Option<String>, String> {
@@ -225,6 +320,72 @@ async fn get_project_file_path(project_id: u32) -> Result<Option<String>, String
     }
 }
 
+// 在文件管理器中显示文件（Finder/资源管理器/Linux文件管理器中选中该文件）
+#[tauri::command]
+async fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    let path = bubblefish_core::api::io::resolve_existing_path(&path)?;
+    reveal_path(&path)
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_path(path: &str) -> Result<(), String> {
+    std::process::Command::new("open")
+        .args(["-R", path])
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open Finder: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+fn reveal_path(path: &str) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    // explorer需要整体的 /select,"路径" 参数，不能被自动加引号；成功时退出码也非0，不检查退出码
+    std::process::Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", path))
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open Explorer: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn reveal_path(path: &str) -> Result<(), String> {
+    use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+    const PATH_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'?').add(b'<').add(b'>');
+    let uri = format!("file://{}", utf8_percent_encode(path, PATH_SET));
+
+    // 优先使用FileManager1接口选中文件（Nautilus/Dolphin/Nemo等支持）
+    let selected = std::process::Command::new("dbus-send")
+        .args([
+            "--session",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{}", uri),
+            "string:",
+        ])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if selected {
+        return Ok(());
+    }
+
+    // 回退为打开所在目录
+    let path = std::path::Path::new(path);
+    let dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    std::process::Command::new("xdg-open")
+        .arg(dir)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open file manager: {}", e))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn reveal_path(_path: &str) -> Result<(), String> {
+    Err("当前系统不支持在文件管理器中显示".to_string())
+}
+
 // 读取文件内容
 #[tauri::command]
 async fn read_file_content(file_path: String) -> Result<String, String> {
@@ -296,10 +457,27 @@ async fn scan_directory_for_images(file_path: String, required_images: Vec<Strin
         Err(e) => return Err(format!("无法读取目录: {}", e))
     }
     
+    // read_dir的顺序由文件系统决定，按文件名自然排序
+    bubblefish_core::common::sort_natural_by_key(&mut found_images, |path| {
+        path.rsplit(['/', '\\']).next().unwrap_or(path)
+    });
+    
     Ok(found_images)
 }
 
 // 获取应用信息
+// 开始监听项目图片文件（打开项目或图片列表变化后调用，会重建watcher）
+#[tauri::command]
+async fn watch_project_files(watchers: tauri::State<'_, FileWatchManager>, project_id: u32) -> Result<usize, String> {
+    watchers.watch_project(project_id)
+}
+
+// 停止监听项目图片文件（关闭项目时调用）
+#[tauri::command]
+async fn unwatch_project_files(watchers: tauri::State<'_, FileWatchManager>, project_id: u32) -> Result<bool, String> {
+    Ok(watchers.unwatch_project(project_id))
+}
+
 #[tauri::command]
 async fn get_app_info() -> Result<String, String> {
     Ok("Bubblefish Desktop App v0.1.0".to_string())
@@ -313,9 +491,9 @@ async fn check_file_exists(path: String) -> Result<bool, String> {
 
 // 打开最近的项目
 #[tauri::command]
-async fn open_recent_project(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
-    // 发送事件到前端
-    if let Err(e) = app_handle.emit("open-recent-file", &path) {
+async fn open_recent_project(window: tauri::WebviewWindow, path: String) -> Result<(), String> {
+    // 发送事件到调用方窗口
+    if let Err(e) = window.emit_to(window.label(), "open-recent-file", &path) {
         return Err(format!("Failed to emit open-recent-file event: {}", e));
     }
     Ok(())
@@ -578,6 +756,45 @@ async fn list_native_plugins() -> Result<Vec<PluginMetadata>, String> {
     }
 }
 
+/// 插件事件/消息回调的时限（毫秒），0表示不限制
+#[tauri::command]
+async fn set_plugin_call_timeout(timeout_ms: u64) -> Result<(), String> {
+    if let Some(loader) = get_plugin_loader() {
+        loader.set_call_timeout_ms(timeout_ms);
+        Ok(())
+    } else {
+        Err("Plugin loader not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_plugin_call_timeout() -> Result<u64, String> {
+    if let Some(loader) = get_plugin_loader() {
+        Ok(loader.call_timeout_ms())
+    } else {
+        Err("Plugin loader not initialized".to_string())
+    }
+}
+
+/// 原生插件HTTP请求使用的代理和证书设置
+#[tauri::command]
+async fn get_network_settings() -> Result<NetworkSettings, String> {
+    if let Some(loader) = get_plugin_loader() {
+        Ok(loader.network_settings())
+    } else {
+        Err("Plugin loader not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+async fn set_network_settings(settings: NetworkSettings) -> Result<(), String> {
+    if let Some(loader) = get_plugin_loader() {
+        loader.set_network_settings(settings)
+    } else {
+        Err("Plugin loader not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 async fn send_message_to_plugin(to: String, from: String, message: serde_json::Value) -> Result<(), String> {
     if let Some(loader) = get_plugin_loader() {
@@ -709,6 +926,9 @@ async fn set_plugin_config(app_handle: tauri::AppHandle, plugin_id: String, conf
     }
 
     let config_file = config_dir.join(format!("{}.json", plugin_id));
+    let old_config = fs::read_to_string(&config_file).ok()
+        .and_then(|config_str| serde_json::from_str(&config_str).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
 
     let config_str = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
@@ -716,6 +936,7 @@ async fn set_plugin_config(app_handle: tauri::AppHandle, plugin_id: String, conf
     fs::write(&config_file, config_str)
         .map_err(|e| format!("Failed to write config file: {}", e))?;
 
+    plugin_loader::notify_config_changed(&plugin_id, &old_config, &config);
     Ok(())
 }
 
@@ -731,6 +952,12 @@ pub fn run() {
   let pending_file_clone = pending_file.clone();
   
   tauri::Builder::default()
+    .manage(FileWatchManager::default())
+    .manage(WindowProjects::default())
+    .on_window_event(|window, event| {
+        file_drop::handle_window_event(window, event);
+        windows::handle_window_event(window, event);
+    })
     .setup(move |app| {
       // 在 setup 中检查是否有待打开的文件
       // 使用 listen 监听前端准备就绪的信号，而不是固定延迟
@@ -740,7 +967,7 @@ pub fn run() {
           if let Ok(mut guard) = pending_file_ready.0.lock() {
               if let Some(file_path) = guard.take() {
                   log::info!("Frontend ready, emitting open-file event for: {}", file_path);
-                  if let Err(e) = app_handle_ready.emit("open-file", &file_path) {
+                  if let Err(e) = app_handle_ready.emit_to(windows::MAIN_WINDOW_LABEL, "open-file", &file_path) {
                       log::error!("Failed to emit open-file event: {}", e);
                   }
               }
@@ -772,6 +999,9 @@ pub fn run() {
       // 初始化进程插件（用于重启应用）
       app.handle().plugin(tauri_plugin_process::init())?;
 
+      // 初始化剪贴板插件（读取粘贴的截图）
+      app.handle().plugin(tauri_plugin_clipboard_manager::init())?;
+
       // 使用 core 模块的自动回调设置
       bubblefish_core::tauri::setup_all_core_callbacks(app.handle().clone());
       
@@ -781,7 +1011,34 @@ pub fn run() {
           "tauri".to_string(),
           Box::new(event_emitter)
       );
+
+      // panic时记录日志并通知前端显示恢复对话框
+      bubblefish_core::common::install_panic_hook();
+
+      // 定时发出被节流的海兔任务进度（core本身没有定时器）
+      std::thread::spawn(|| loop {
+          std::thread::sleep(std::time::Duration::from_millis(100));
+          bubblefish_core::api::bunny::tick_bunny_progress();
+      });
       
+      // 项目关闭或开启图片文件监听时同步watcher
+      let app_handle_watch = app.handle().clone();
+      app.listen("core-business", move |event| {
+          let Ok(value) = serde_json::from_str::<serde_json::Value>(event.payload()) else { return };
+          if value["event_name"] != "project:file_watch_changed" {
+              return;
+          }
+          let Some(project_id) = value["data"]["project_id"].as_u64() else { return };
+          let watchers = app_handle_watch.state::<FileWatchManager>();
+          if value["data"]["enabled"].as_bool().unwrap_or(false) {
+              if let Err(e) = watchers.watch_project(project_id as u32) {
+                  log::error!("Failed to watch project files: {}", e);
+              }
+          } else {
+              watchers.unwatch_project(project_id as u32);
+          }
+      });
+
       // 初始化插件加载器
       init_plugin_loader(app.handle().clone());
 
@@ -831,38 +1088,23 @@ pub fn run() {
       }
 
 
-      // 创建主窗口，根据操作系统使用不同的标题栏样式
-      let window_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
-        .title("")  // 设置标题为空，使用自定义标题栏，如果标题栏有文字，在macOS上会遮挡
-        .inner_size(1200.0, 800.0)
-        .min_inner_size(800.0, 600.0)
-        .resizable(true)
-        .center();
-
-      // 根据操作系统设置不同的标题栏样式和装饰
-      let window_builder = if cfg!(target_os = "macos") {
-        // macOS: 使用 Overlay 模式解决渲染遮挡问题
-        #[cfg(target_os = "macos")]
-        let window_builder = window_builder
-          .decorations(true)
-          .title_bar_style(TitleBarStyle::Overlay);
-        #[cfg(not(target_os = "macos"))]
-        let window_builder = window_builder.decorations(true);
-        window_builder
-      } else if cfg!(target_os = "windows") {
-        // Windows: 启用装饰，不使用 macOS 特有的 title_bar_style
-        window_builder
-          .decorations(false)
-      } else {
-        // Linux: 禁用装饰，使用完全自定义标题栏
-        window_builder.decorations(false)
-      };
-
-      let _window = window_builder.build()?;
+      // 创建主窗口，标题栏样式和上次的大小/位置见 windows::build_window
+      let _window = windows::build_window(app.handle(), windows::MAIN_WINDOW_LABEL)?;
 
       // 只为 macOS 创建系统原生菜单，Windows 使用虚拟菜单栏
       if cfg!(target_os = "macos") {
-        create_native_menu(app)?;
+        native_menu::create_native_menu(app)?;
+
+        // 快捷键配置变更时更新菜单（Windows虚拟菜单栏由前端监听同一事件）
+        let app_handle_keymap = app.handle().clone();
+        app.listen("core-business", move |event| {
+            let is_keymap_event = serde_json::from_str::<serde_json::Value>(event.payload())
+                .map(|value| value["event_name"] == "keymap:changed")
+                .unwrap_or(false);
+            if is_keymap_event {
+                native_menu::apply_keymap_to_menu(&app_handle_keymap);
+            }
+        });
       }
       
       Ok(())
@@ -870,11 +1112,24 @@ pub fn run() {
     .invoke_handler(bubblefish_core::generate_all_commands![
         open_image_file_dialog,
         open_multiple_image_files_dialog,
+        read_clipboard_image,
         open_text_file_dialog,
         save_project_to_path,
         get_project_file_path,
+        save_typesetting_data,
         read_file_content,
         scan_directory_for_images,
+        watch_project_files,
+        unwatch_project_files,
+        emit_menu_event,
+        open_project_window,
+        get_window_project,
+        set_window_project,
+        confirm_quit,
+        reveal_in_file_manager,
+        open_dropped_project,
+        import_dropped_translation,
+        add_dropped_images,
         get_app_info,
         check_file_exists,
         open_recent_project,
@@ -890,6 +1145,10 @@ pub fn run() {
         call_plugin_service,
         enable_native_plugin,
         list_native_plugins,
+        set_plugin_call_timeout,
+        get_plugin_call_timeout,
+        get_network_settings,
+        set_network_settings,
         send_message_to_plugin,
         upload_plugin,
         upload_plugin_from_path,
@@ -901,7 +1160,7 @@ pub fn run() {
     ])
     .build(tauri::generate_context!())
     .expect("error while building tauri application")
-    .run(move |#[cfg_attr(not(target_os = "macos"), allow(unused_variables))] app_handle, event| match event {
+    .run(move |app_handle, event| match event {
         // 处理文件打开事件（仅macOS支持Opened事件）
         #[cfg(target_os = "macos")]
         tauri::RunEvent::Opened { urls, .. } => {
@@ -935,7 +1194,7 @@ pub fn run() {
                     } else {
                         // 如果窗口已存在，立即发送事件（前端应该已经准备好了）
                         log::info!("Window exists, emitting open-file event immediately for: {}", decoded_path);
-                        if let Err(e) = app_handle.emit("open-file", &decoded_path) {
+                        if let Err(e) = app_handle.emit_to(windows::MAIN_WINDOW_LABEL, "open-file", &decoded_path) {
                             log::error!("Failed to emit open-file event: {}", e);
                         }
                     }
@@ -943,235 +1202,10 @@ pub fn run() {
                 }
             }
         }
+        tauri::RunEvent::ExitRequested { .. } => {
+            windows::save_all_window_states(app_handle);
+        }
         _ => {}
     });
 }
 
-fn create_native_menu(app: &mut tauri::App) -> Result<(), tauri::Error> {
-    // 创建导出子菜单
-    let export_submenu = SubmenuBuilder::new(app, "导出")
-        .id("export-submenu")
-        .item(&MenuItemBuilder::new("Labelplus文件")
-            .id("export-labelplus")
-            .build(app)?)
-        .build()?;
-
-    // 创建最近打开子菜单
-    let recent_submenu = SubmenuBuilder::new(app, "最近打开")
-        .id("recent-projects")
-        .item(&MenuItemBuilder::new("暂无最近打开的项目")
-            .id("recent-empty")
-            .enabled(false)
-            .build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::new("清空最近打开")
-            .id("clear-recent")
-            .build(app)?)
-        .build()?;
-
-    // 创建文件菜单
-    let file_menu = SubmenuBuilder::new(app, "文件")
-        .item(&MenuItemBuilder::new("新建项目")
-            .id("new-project")
-            .accelerator("CmdOrCtrl+N")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("打开项目")
-            .id("open-project")
-            .accelerator("CmdOrCtrl+O")
-            .build(app)?)
-        .item(&recent_submenu)
-        .separator()
-        .item(&MenuItemBuilder::new("保存")
-            .id("save")
-            .accelerator("CmdOrCtrl+S")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("另存为...")
-            .id("save-as")
-            .accelerator("CmdOrCtrl+Shift+S")
-            .build(app)?)
-        .item(&export_submenu)
-        .separator()
-        .build()?;
-
-    // 创建编辑菜单
-    use tauri::menu::PredefinedMenuItem;
-    let edit_menu = SubmenuBuilder::new(app, "编辑")
-        .item(&MenuItemBuilder::new("撤销")
-            .id("undo")
-            .accelerator("CmdOrCtrl+Z")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("重做")
-            .id("redo")
-            .accelerator("CmdOrCtrl+Shift+Z")
-            .build(app)?)
-        .separator()
-        .item(&PredefinedMenuItem::cut(app, None)?)
-        .item(&PredefinedMenuItem::copy(app, None)?)
-        .item(&PredefinedMenuItem::paste(app, None)?)
-        .item(&PredefinedMenuItem::select_all(app, None)?)
-        .separator()
-        .item(&MenuItemBuilder::new("上一个标记")
-            .id("prev-marker")
-            .accelerator("Shift+Tab")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("下一个标记")
-            .id("next-marker")
-            .accelerator("Tab")
-            .build(app)?)
-        .build()?;
-
-    // 创建视图菜单
-    let window_menu = SubmenuBuilder::new(app, "视图")
-        .item(&MenuItemBuilder::new("上一张图片")
-            .id("prev-image")
-            .accelerator("CmdOrCtrl+Left")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("下一张图片")
-            .id("next-image")
-            .accelerator("CmdOrCtrl+Right")
-            .build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::new("最小化")
-            .id("minimize")
-            .accelerator("CmdOrCtrl+M")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("最大化")
-            .id("maximize")
-            .build(app)?)
-        .separator()
-        .item(&CheckMenuItemBuilder::new("翻译")
-            .id("translation")
-            .build(app)?)
-        .item(&CheckMenuItemBuilder::new("缩略图")
-            .id("thumbnail")
-            .build(app)?)
-        .item(&CheckMenuItemBuilder::new("词库")
-            .id("dictionary")
-            .build(app)?)
-        .item(&CheckMenuItemBuilder::new("项目配置")
-            .id("project-config")
-            .build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::new("调试窗口")
-            .id("debug")
-            .accelerator("CmdOrCtrl+Shift+D")
-            .build(app)?)
-        .build()?;
-
-    // 创建更多菜单
-    let more_menu = SubmenuBuilder::new(app, "更多")
-        .item(&MenuItemBuilder::new("快照")
-            .id("snapshots")
-            .build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::new("关于")
-            .id("version-info")
-            .build(app)?)
-        .item(&MenuItemBuilder::new("软件许可")
-            .id("software-license")
-            .build(app)?)
-        .separator()
-        .item(&MenuItemBuilder::new("退出Bubblefish")
-            .id("quit")
-            .accelerator("Cmd+Q")
-            .build(app)?)
-        .build()?;
-
-    // 构建主菜单
-    let menu = MenuBuilder::new(app)
-        .items(&[&more_menu,&file_menu, &edit_menu, &window_menu])
-        .build()?;
-
-    // 设置应用菜单
-    app.set_menu(menu)?;
-
-    // 添加菜单事件处理器
-    app.on_menu_event(move |app, event| {
-        let event_id = event.id().0.as_str();
-        
-        // 处理清空最近打开
-        if event_id == "clear-recent" {
-            if let Err(e) = app.emit("menu:file:clear-recent", ()) {
-                eprintln!("Failed to emit clear-recent event: {}", e);
-            }
-            return;
-        }
-        
-        // 处理最近打开的项目
-        if event_id.starts_with("recent-") && event_id != "recent-empty" && event_id != "recent-projects" {
-            // 打开最近的项目（需要从菜单项获取路径）
-            // 这里我们发送事件索引，前端会处理
-            if let Err(e) = app.emit("menu:file:open-recent", event_id) {
-                eprintln!("Failed to emit open-recent event: {}", e);
-            }
-            return;
-        }
-        
-        let event_name = match event_id {
-            "new-project" => "menu:file:new-project",
-            "open-project" => "menu:file:open-project",
-            "save" => "menu:file:save",
-            "save-as" => "menu:file:save-as",
-            "export-labelplus" => "menu:file:export",
-            "quit" => "menu:more:quit",
-            "undo" => "menu:edit:undo",
-            "redo" => "menu:edit:redo",
-            "prev-marker" => "menu:edit:prev-marker",
-            "next-marker" => "menu:edit:next-marker",
-            "prev-image" => "menu:view:prev-image",
-            "next-image" => "menu:view:next-image",
-            "minimize" => "menu:window:minimize",
-            "maximize" => "menu:window:maximize",
-            "translation" => "menu:window:translation",
-            "thumbnail" => "menu:window:thumbnail",
-            "dictionary" => "menu:window:dictionary",
-            "project-config" => "menu:window:project-config",
-            "debug" => "menu:window:debug",
-            "snapshots" => "menu:more:snapshots",
-            "version-info" => "menu:more:version-info",
-            "software-license" => "menu:more:software-license",
-            _ => {
-                if cfg!(debug_assertions) {
-                    println!("Unknown menu event: {}", event.id().0);
-                }
-                return;
-            }
-        };
-
-        // 发送事件到前端
-        if let Err(e) = app.emit(event_name, ()) {
-            eprintln!("Failed to emit menu event {}: {}", event_name, e);
-        }
-
-        // 处理一些系统级的菜单操作
-        match event.id().0.as_str() {
-            "minimize" => {
-                // 尝试不同的窗口名称
-                let window_labels = ["main", "main-window", ""];
-                for label in &window_labels {
-                    if let Some(window) = app.get_webview_window(label) {
-                        let _ = window.minimize();
-                        break;
-                    }
-                }
-            }
-            "maximize" => {
-                // 尝试不同的窗口名称
-                let window_labels = ["main", "main-window", ""];
-                for label in &window_labels {
-                    if let Some(window) = app.get_webview_window(label) {
-                        let _ = if window.is_maximized().unwrap_or(false) {
-                            window.unmaximize()
-                        } else {
-                            window.maximize()
-                        };
-                        break;
-                    }
-                }
-            }
-            _ => {}
-        }
-    });
-
-    Ok(())
-}
@@ -0,0 +1,117 @@
+// 菜单事件映射 - macOS原生菜单和Windows前端虚拟菜单共用，保证两端发出相同的事件名
+use tauri::{Emitter, Manager, WebviewWindow};
+
+/// 菜单项id -> 前端事件名
+pub const MENU_EVENTS: &[(&str, &str)] = &[
+    ("new-project", "menu:file:new-project"),
+    ("open-project", "menu:file:open-project"),
+    ("save", "menu:file:save"),
+    ("save-as", "menu:file:save-as"),
+    ("save-as-copy", "menu:file:save-as-copy"),
+    ("export-labelplus", "menu:file:export"),
+    ("export-annotated", "menu:file:export-annotated"),
+    ("export-typesetting-csv", "menu:file:export-typesetting-csv"),
+    ("export-typesetting-json", "menu:file:export-typesetting-json"),
+    ("import-typesetting", "menu:file:import-typesetting"),
+    ("clear-recent", "menu:file:clear-recent"),
+    ("quit", "menu:more:quit"),
+    ("undo", "menu:edit:undo"),
+    ("redo", "menu:edit:redo"),
+    ("prev-marker", "menu:edit:prev-marker"),
+    ("next-marker", "menu:edit:next-marker"),
+    ("prev-image", "menu:view:prev-image"),
+    ("next-image", "menu:view:next-image"),
+    ("minimize", "menu:window:minimize"),
+    ("maximize", "menu:window:maximize"),
+    ("translation", "menu:window:translation"),
+    ("thumbnail", "menu:window:thumbnail"),
+    ("dictionary", "menu:window:dictionary"),
+    ("project-config", "menu:window:project-config"),
+    ("debug", "menu:window:debug"),
+    ("snapshots", "menu:more:snapshots"),
+    ("version-info", "menu:more:version-info"),
+    ("software-license", "menu:more:software-license"),
+];
+
+pub fn menu_event_name(id: &str) -> Option<&'static str> {
+    MENU_EVENTS.iter().find(|(menu_id, _)| *menu_id == id).map(|(_, event)| *event)
+}
+
+// 最近打开的项目菜单项为 recent-<index>，占位项除外
+fn is_recent_project_item(id: &str) -> bool {
+    id.starts_with("recent-") && id != "recent-empty" && id != "recent-projects"
+}
+
+/// 按菜单项id向目标窗口发送对应的前端事件，并对该窗口执行最小化/最大化等系统级操作
+/// 多窗口时事件只发给目标窗口，避免所有窗口同时响应
+pub fn dispatch_menu_event<R: tauri::Runtime>(window: &WebviewWindow<R>, id: &str) -> Result<(), String> {
+    // 退出先检查未保存的项目，见 quit::request_quit
+    if id == "quit" {
+        crate::quit::request_quit(window.app_handle());
+        return Ok(());
+    }
+
+    let label = window.label();
+    if let Some(event_name) = menu_event_name(id) {
+        window.emit_to(label, event_name, ())
+            .map_err(|e| format!("Failed to emit menu event {}: {}", event_name, e))?;
+    } else if is_recent_project_item(id) {
+        // 发送菜单项id，前端从中解析索引
+        window.emit_to(label, "menu:file:open-recent", id)
+            .map_err(|e| format!("Failed to emit open-recent event: {}", e))?;
+        return Ok(());
+    } else {
+        return Err(format!("Unknown menu event: {}", id));
+    }
+
+    match id {
+        "minimize" => {
+            let _ = window.minimize();
+        }
+        "maximize" => {
+            let _ = if window.is_maximized().unwrap_or(false) {
+                window.unmaximize()
+            } else {
+                window.maximize()
+            };
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Windows前端虚拟菜单调用，与原生菜单走同一映射，事件发回调用方窗口
+#[tauri::command]
+pub fn emit_menu_event(window: WebviewWindow, id: String) -> Result<(), String> {
+    dispatch_menu_event(&window, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_menu_ids_map_to_unique_events() {
+        assert_eq!(menu_event_name("save-as-copy"), Some("menu:file:save-as-copy"));
+        assert_eq!(menu_event_name("export-labelplus"), Some("menu:file:export"));
+        assert_eq!(menu_event_name("recent-0"), None);
+        assert_eq!(menu_event_name("no-such-item"), None);
+
+        // 菜单项id和事件名都不能重复，否则两端会有一项永远匹配不到
+        let ids: HashSet<_> = MENU_EVENTS.iter().map(|(id, _)| *id).collect();
+        let events: HashSet<_> = MENU_EVENTS.iter().map(|(_, event)| *event).collect();
+        assert_eq!(ids.len(), MENU_EVENTS.len());
+        assert_eq!(events.len(), MENU_EVENTS.len());
+        assert!(MENU_EVENTS.iter().all(|(_, event)| event.starts_with("menu:")));
+    }
+
+    #[test]
+    fn test_recent_project_items() {
+        assert!(is_recent_project_item("recent-0"));
+        assert!(is_recent_project_item("recent-12"));
+        assert!(!is_recent_project_item("recent-empty"));
+        assert!(!is_recent_project_item("recent-projects"));
+        assert!(!is_recent_project_item("clear-recent"));
+    }
+}
@@ -0,0 +1,91 @@
+// macOS原生菜单 - 按core的菜单定义构建，Windows/Linux的虚拟菜单栏由前端按同一份定义显示
+use bubblefish_core::service::menu::{get_menu_definition, MenuItemDefinition, MenuItemKind};
+use tauri::menu::{CheckMenuItemBuilder, IsMenuItem, MenuBuilder, MenuItemBuilder, Submenu, SubmenuBuilder};
+use tauri::{AppHandle, Manager, Runtime};
+use crate::menu_events::dispatch_menu_event;
+use crate::windows;
+
+fn build_submenu<R: Runtime, M: Manager<R>>(manager: &M, id: &str, label: &str, items: &[MenuItemDefinition]) -> tauri::Result<Submenu<R>> {
+    let mut builder = SubmenuBuilder::with_id(manager, id, label);
+    for item in items {
+        builder = match item.kind {
+            MenuItemKind::Separator => builder.separator(),
+            MenuItemKind::Action => {
+                let mut item_builder = MenuItemBuilder::with_id(item.id.as_str(), &item.label).enabled(item.enabled);
+                if let Some(accelerator) = &item.accelerator {
+                    item_builder = item_builder.accelerator(accelerator);
+                }
+                builder.item(&item_builder.build(manager)?)
+            }
+            MenuItemKind::Check => builder.item(&CheckMenuItemBuilder::with_id(item.id.as_str(), &item.label).build(manager)?),
+            MenuItemKind::Submenu => builder.item(&build_submenu(manager, &item.id, &item.label, &item.items)?),
+            MenuItemKind::Predefined => match item.id.as_str() {
+                "cut" => builder.cut(),
+                "copy" => builder.copy(),
+                "paste" => builder.paste(),
+                "select-all" => builder.select_all(),
+                _ => builder,
+            },
+        };
+    }
+    builder.build()
+}
+
+/// 按菜单定义创建应用菜单，菜单事件发给当前获得焦点的窗口
+pub fn create_native_menu(app: &mut tauri::App) -> tauri::Result<()> {
+    let sections = get_menu_definition().unwrap_or_default();
+    let submenus = sections.iter()
+        .map(|section| build_submenu(&*app, &section.id, &section.label, &section.items))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<tauri::Wry>> = submenus.iter().map(|submenu| submenu as &dyn IsMenuItem<tauri::Wry>).collect();
+    let menu = MenuBuilder::new(app).items(&items).build()?;
+    app.set_menu(menu)?;
+
+    app.on_menu_event(move |app, event| {
+        // 映射和系统级操作见 menu_events，Windows虚拟菜单通过emit_menu_event走同一路径
+        // 原生菜单是应用级的，事件发给当前获得焦点的窗口
+        let Some(window) = windows::focused_window(app) else { return };
+        if let Err(e) = dispatch_menu_event(&window, event.id().0.as_str())
+            && cfg!(debug_assertions)
+        {
+            println!("{}", e);
+        }
+    });
+
+    Ok(())
+}
+
+fn apply_accelerators<R: Runtime>(submenu: &Submenu<R>, items: &[MenuItemDefinition]) {
+    for item in items {
+        let Some(native) = submenu.get(item.id.as_str()) else { continue };
+        match item.kind {
+            MenuItemKind::Action => {
+                if let Some(menu_item) = native.as_menuitem()
+                    && let Err(e) = menu_item.set_accelerator(item.accelerator.as_deref())
+                {
+                    log::error!("Failed to update accelerator for {}: {}", item.id, e);
+                }
+            }
+            MenuItemKind::Submenu => {
+                if let Some(child) = native.as_submenu() {
+                    apply_accelerators(child, &item.items);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 快捷键配置变更后同步原生菜单
+/// 只更新已有菜单项的快捷键而不重建菜单，保留最近打开列表和面板的勾选状态
+pub fn apply_keymap_to_menu<R: Runtime>(app_handle: &AppHandle<R>) {
+    let Some(menu) = app_handle.menu() else { return };
+    let Ok(sections) = get_menu_definition() else { return };
+    for section in &sections {
+        if let Some(native) = menu.get(section.id.as_str())
+            && let Some(submenu) = native.as_submenu()
+        {
+            apply_accelerators(submenu, &section.items);
+        }
+    }
+}
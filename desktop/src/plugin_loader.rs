@@ -2,11 +2,15 @@ use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::Duration;
 use libloading::{Library, Symbol};
 use serde_json::Value;
 use tauri::Manager;
 
+use bubblefish_core::common::events::schema::{BusinessEvent, PluginTimeoutEvent};
+use bubblefish_core::plugin::audit;
 use bubblefish_core::plugin::service_registry::adapters::NotificationServiceAdapter;
 use bubblefish_core::plugin::ServiceInterface;
 
@@ -30,7 +34,8 @@ pub struct HostCallbacks {
 
 /// Loaded plugin instance
 struct LoadedPlugin {
-    library: Library,
+    // 超时的调用线程可能还在库里运行，库要等它结束后才能释放
+    library: Arc<Library>,
     metadata: PluginMetadata,
     enabled: bool,
 }
@@ -51,19 +56,216 @@ pub struct PluginMetadata {
     pub subscribed_events: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config_schema: Option<serde_json::Value>,
+    /// 插件构建时的接口版本（SDK的PLUGIN_API_VERSION），旧SDK构建的插件为0
+    #[serde(default)]
+    pub api_version: u32,
+    /// 宿主拒绝加载时的原因，只出现在list_native_plugins返回的未加载插件中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_error: Option<String>,
+    /// 调用超时后被停用的原因，重新启用时清除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unhealthy: Option<String>,
+}
+
+/// 宿主支持的插件接口版本范围，与plugin-sdk的PLUGIN_API_VERSION对应
+pub const MIN_PLUGIN_API_VERSION: u32 = 1;
+pub const MAX_PLUGIN_API_VERSION: u32 = 1;
+
+/// 检查插件的接口版本，不在支持范围内时返回说明原因的错误
+fn check_plugin_api_version(api_version: u32) -> Result<(), String> {
+    if api_version == 0 {
+        return Err(format!(
+            "Plugin was built with an SDK that predates plugin API versioning; rebuild it against plugin API v{}",
+            MAX_PLUGIN_API_VERSION
+        ));
+    }
+    if !(MIN_PLUGIN_API_VERSION..=MAX_PLUGIN_API_VERSION).contains(&api_version) {
+        return Err(format!(
+            "Plugin targets plugin API v{}, but this host supports v{}-v{}; rebuild it with a compatible SDK",
+            api_version, MIN_PLUGIN_API_VERSION, MAX_PLUGIN_API_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// 插件事件/消息回调的默认时限，阻塞的网络请求超过它时插件被停用
+pub const DEFAULT_PLUGIN_CALL_TIMEOUT_MS: u64 = 30_000;
+
+/// 原生插件的网络设置，通过network服务提供，插件SDK据此创建HTTP客户端
+/// 没有设置代理时插件使用系统代理
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct NetworkSettings {
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl NetworkSettings {
+    // 去掉空白项，空字符串视为未设置
+    fn normalized(self) -> Self {
+        let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Self {
+            proxy_url: non_empty(self.proxy_url),
+            no_proxy: self.no_proxy.into_iter()
+                .map(|host| host.trim().to_string())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            ca_cert_path: non_empty(self.ca_cert_path),
+            timeout_secs: self.timeout_secs.filter(|secs| *secs > 0),
+        }
+    }
+}
+
+enum CallOutcome {
+    Finished(i32),
+    TimedOut,
+    // 线程在回调中panic
+    Crashed,
+}
+
+/// 在名为thread_name的工作线程中执行回调，最多等待timeout_ms；0表示不限制，直接在调用线程执行
+/// 卡住的线程无法终止，只能丢下不管；回调持有库的引用，库不会在它结束前被释放
+fn call_with_timeout(
+    timeout_ms: u64,
+    thread_name: String,
+    call: impl FnOnce() -> i32 + Send + 'static,
+) -> Result<CallOutcome, String> {
+    if timeout_ms == 0 {
+        return Ok(CallOutcome::Finished(call()));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::Builder::new()
+        .name(thread_name)
+        .spawn(move || {
+            let _ = sender.send(call());
+        })
+        .map_err(|e| format!("Failed to spawn plugin call thread: {}", e))?;
+
+    Ok(match receiver.recv_timeout(Duration::from_millis(timeout_ms)) {
+        Ok(result) => CallOutcome::Finished(result),
+        Err(mpsc::RecvTimeoutError::Timeout) => CallOutcome::TimedOut,
+        Err(mpsc::RecvTimeoutError::Disconnected) => CallOutcome::Crashed,
+    })
+}
+
+/// 把插件标记为不健康并停用，返回更新后的元数据；插件不存在时返回None
+/// 不调用plugin_deactivate，插件已经卡住，再调用可能同样阻塞
+fn disable_unhealthy(plugins: &mut HashMap<String, LoadedPlugin>, plugin_id: &str, reason: String) -> Option<PluginMetadata> {
+    let plugin = plugins.get_mut(plugin_id)?;
+    plugin.enabled = false;
+    plugin.metadata.unhealthy = Some(reason);
+    Some(plugin.metadata.clone())
 }
 
 /// Plugin loader manages all native plugins
 pub struct PluginLoader {
     plugins: Arc<Mutex<HashMap<String, LoadedPlugin>>>,
+    // 因接口版本不兼容而没有加载的插件 (plugin_id -> 带load_error的元数据)
+    rejected: Arc<Mutex<HashMap<String, PluginMetadata>>>,
+    // 插件回调的时限（毫秒），0表示不限制，直接在调用线程执行
+    call_timeout_ms: AtomicU64,
+    network_settings: Mutex<NetworkSettings>,
     _app_handle: tauri::AppHandle,
 }
 
 impl PluginLoader {
     pub fn new(app_handle: tauri::AppHandle) -> Self {
-        Self {
+        let loader = Self {
             plugins: Arc::new(Mutex::new(HashMap::new())),
+            rejected: Arc::new(Mutex::new(HashMap::new())),
+            call_timeout_ms: AtomicU64::new(DEFAULT_PLUGIN_CALL_TIMEOUT_MS),
+            network_settings: Mutex::new(NetworkSettings::default()),
             _app_handle: app_handle,
+        };
+        *loader.network_settings.lock().unwrap() = loader.load_network_settings();
+        loader
+    }
+
+    pub fn set_call_timeout_ms(&self, timeout_ms: u64) {
+        self.call_timeout_ms.store(timeout_ms, Ordering::Relaxed);
+    }
+
+    pub fn call_timeout_ms(&self) -> u64 {
+        self.call_timeout_ms.load(Ordering::Relaxed)
+    }
+
+    /// 在工作线程中执行插件回调，超过时限时停用插件并返回错误
+    fn run_plugin_call(
+        &self,
+        plugin_id: &str,
+        operation: &str,
+        call: impl FnOnce() -> i32 + Send + 'static,
+    ) -> Result<i32, String> {
+        let timeout_ms = self.call_timeout_ms();
+        match call_with_timeout(timeout_ms, format!("plugin-{}-{}", plugin_id, operation), call)? {
+            CallOutcome::Finished(result) => Ok(result),
+            CallOutcome::TimedOut => {
+                self.mark_timed_out(plugin_id, operation, timeout_ms);
+                Err(format!("Plugin {} timed out after {}ms handling {}", plugin_id, timeout_ms, operation))
+            }
+            CallOutcome::Crashed => Err(format!("Plugin {} crashed while handling {}", plugin_id, operation)),
+        }
+    }
+
+    fn mark_timed_out(&self, plugin_id: &str, operation: &str, timeout_ms: u64) {
+        log::error!("Plugin {} timed out after {}ms handling {}, disabling it", plugin_id, timeout_ms, operation);
+        let reason = format!("{} timed out after {}ms", operation, timeout_ms);
+        let Some(metadata_snapshot) = disable_unhealthy(&mut self.plugins.lock().unwrap(), plugin_id, reason) else {
+            return;
+        };
+
+        self.save_plugin_states();
+        let _ = PluginTimeoutEvent::new(plugin_id.to_string(), operation.to_string(), timeout_ms).emit();
+        self.emit_plugin_event("timed_out", Some(plugin_id.to_string()), Some(metadata_snapshot));
+    }
+
+    pub fn network_settings(&self) -> NetworkSettings {
+        self.network_settings.lock().unwrap().clone()
+    }
+
+    /// 保存网络设置，插件的下一次请求按新设置重建HTTP客户端
+    pub fn set_network_settings(&self, settings: NetworkSettings) -> Result<(), String> {
+        let settings = settings.normalized();
+        let path = self.get_network_settings_path()?;
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let content = serde_json::to_string_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize network settings: {}", e))?;
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to save network settings: {}", e))?;
+        *self.network_settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    fn get_network_settings_path(&self) -> Result<PathBuf, String> {
+        let app_data_dir = self._app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+        Ok(app_data_dir.join("network_settings.json"))
+    }
+
+    fn load_network_settings(&self) -> NetworkSettings {
+        let Ok(path) = self.get_network_settings_path() else {
+            return NetworkSettings::default();
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return NetworkSettings::default();
+        };
+        match serde_json::from_str::<NetworkSettings>(&content) {
+            Ok(settings) => settings.normalized(),
+            Err(e) => {
+                log::warn!("Failed to parse network settings: {}", e);
+                NetworkSettings::default()
+            }
         }
     }
 
@@ -314,12 +516,47 @@ impl PluginLoader {
     }
     
     fn load_plugin_from_path(&self, path: &Path) -> Result<PluginMetadata, String> {
+        // Generate plugin ID from filename
+        // Remove lib prefix and _plugin suffix to get the actual plugin name
+        let file_stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+
+        // Extract plugin ID from filename
+        // e.g. "libdummy_ocr_plugin.dylib" -> "dummy-ocr-plugin"
+        let plugin_id = file_stem
+            .strip_prefix("lib")
+            .unwrap_or(file_stem)
+            .replace('_', "-");
 
         unsafe {
             // Load the dynamic library
             let library = Library::new(path)
                 .map_err(|e| format!("Failed to load plugin library: {}", e))?;
 
+            // 先检查接口版本再调用其他导出函数，避免旧SDK构建的插件因ABI不一致崩溃
+            let api_version = library
+                .get::<extern "C" fn() -> u32>(b"plugin_api_version")
+                .map(|version| version())
+                .unwrap_or(0);
+            if let Err(e) = check_plugin_api_version(api_version) {
+                let error = format!("{}: {}", path.display(), e);
+                self.rejected.lock().unwrap().insert(plugin_id.clone(), PluginMetadata {
+                    id: plugin_id.clone(),
+                    name: plugin_id.clone(),
+                    version: String::new(),
+                    description: String::new(),
+                    author: String::new(),
+                    subscribed_events: Vec::new(),
+                    config_schema: None,
+                    api_version,
+                    load_error: Some(error.clone()),
+                    unhealthy: None,
+                });
+                return Err(error);
+            }
+
             // Set host callbacks
             let set_callbacks: Symbol<extern "C" fn(HostCallbacks)> = library
                 .get(b"plugin_set_host_callbacks")
@@ -339,20 +576,6 @@ impl PluginLoader {
                 .get(b"plugin_init")
                 .map_err(|e| format!("Failed to find plugin_init: {}", e))?;
 
-            // Generate plugin ID from filename
-            // Remove lib prefix and _plugin suffix to get the actual plugin name
-            let file_stem = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown");
-            
-            // Extract plugin ID from filename
-            // e.g. "libdummy_ocr_plugin.dylib" -> "dummy-ocr-plugin"
-            let plugin_id = file_stem
-                .strip_prefix("lib")
-                .unwrap_or(file_stem)
-                .replace('_', "-");
-
             let plugin_id_c = CString::new(plugin_id.clone())
                 .map_err(|e| format!("Invalid plugin ID: {}", e))?;
 
@@ -372,8 +595,11 @@ impl PluginLoader {
             }
 
             let metadata_str = CStr::from_ptr(metadata_ptr).to_string_lossy();
-            let metadata: PluginMetadata = serde_json::from_str(&metadata_str)
+            let mut metadata: PluginMetadata = serde_json::from_str(&metadata_str)
                 .map_err(|e| format!("Failed to parse plugin metadata: {}", e))?;
+            metadata.api_version = api_version;
+            metadata.load_error = None;
+            metadata.unhealthy = None;
 
             // Free the metadata string
             let free_string: Symbol<extern "C" fn(*mut c_char)> = library
@@ -410,6 +636,12 @@ impl PluginLoader {
 
             let event_metadata = metadata.clone();
             let event_plugin_id = event_metadata.id.clone();
+            {
+                let mut rejected = self.rejected.lock().unwrap();
+                rejected.remove(&plugin_id);
+                rejected.remove(&event_plugin_id);
+            }
+            audit::register_config_schema(&metadata.id, metadata.config_schema.as_ref().unwrap_or(&Value::Null));
 
             // Load saved plugin states to determine if this plugin should be enabled
             drop(plugins); // Drop the lock before calling load_plugin_states
@@ -421,7 +653,7 @@ impl PluginLoader {
             plugins.insert(
                 stored_id,
                 LoadedPlugin {
-                    library,
+                    library: Arc::new(library),
                     metadata: metadata.clone(),
                     enabled,
                 },
@@ -466,29 +698,32 @@ impl PluginLoader {
 
     /// Dispatch event to plugin
     pub fn dispatch_event(&self, plugin_id: &str, event: &Value) -> Result<(), String> {
-        let plugins = self.plugins.lock().unwrap();
-        
-        if let Some(plugin) = plugins.get(plugin_id) {
+        let (library, on_event) = {
+            let plugins = self.plugins.lock().unwrap();
+            let Some(plugin) = plugins.get(plugin_id) else { return Ok(()) };
             if !plugin.enabled {
                 return Ok(());
             }
+            let on_event: extern "C" fn(*const c_char) -> i32 = unsafe {
+                *plugin.library
+                    .get::<extern "C" fn(*const c_char) -> i32>(b"plugin_on_event")
+                    .map_err(|e| format!("Failed to find plugin_on_event: {}", e))?
+            };
+            (plugin.library.clone(), on_event)
+        };
 
-            unsafe {
-                let on_event: Symbol<extern "C" fn(*const c_char) -> i32> = plugin.library
-                    .get(b"plugin_on_event")
-                    .map_err(|e| format!("Failed to find plugin_on_event: {}", e))?;
-
-                let event_json = serde_json::to_string(event)
-                    .map_err(|e| format!("Failed to serialize event: {}", e))?;
-                
-                let event_c = CString::new(event_json)
-                    .map_err(|e| format!("Invalid event JSON: {}", e))?;
-
-                let result = on_event(event_c.as_ptr());
-                if result != 0 {
-                    return Err(format!("Event handling failed with code: {}", result));
-                }
-            }
+        let event_json = serde_json::to_string(event)
+            .map_err(|e| format!("Failed to serialize event: {}", e))?;
+        let event_c = CString::new(event_json)
+            .map_err(|e| format!("Invalid event JSON: {}", e))?;
+
+        // 调用期间不持有插件表的锁，插件回调宿主服务时不会死锁
+        let result = self.run_plugin_call(plugin_id, "event", move || {
+            let _library = library;
+            on_event(event_c.as_ptr())
+        })?;
+        if result != 0 {
+            return Err(format!("Event handling failed with code: {}", result));
         }
 
         Ok(())
@@ -496,31 +731,33 @@ impl PluginLoader {
 
     /// Send message to plugin
     pub fn send_message(&self, to: &str, from: &str, message: &Value) -> Result<(), String> {
-        let plugins = self.plugins.lock().unwrap();
-
-        if let Some(plugin) = plugins.get(to) {
+        let (library, on_message) = {
+            let plugins = self.plugins.lock().unwrap();
+            let Some(plugin) = plugins.get(to) else { return Ok(()) };
             if !plugin.enabled {
                 return Ok(());
             }
+            let on_message: extern "C" fn(*const c_char, *const c_char) -> i32 = unsafe {
+                *plugin.library
+                    .get::<extern "C" fn(*const c_char, *const c_char) -> i32>(b"plugin_on_message")
+                    .map_err(|e| format!("Failed to find plugin_on_message: {}", e))?
+            };
+            (plugin.library.clone(), on_message)
+        };
 
-            unsafe {
-                let on_message: Symbol<extern "C" fn(*const c_char, *const c_char) -> i32> = 
-                    plugin.library
-                        .get(b"plugin_on_message")
-                        .map_err(|e| format!("Failed to find plugin_on_message: {}", e))?;
-
-                let from_c = CString::new(from)
-                    .map_err(|e| format!("Invalid from string: {}", e))?;
-                let message_json = serde_json::to_string(message)
-                    .map_err(|e| format!("Failed to serialize message: {}", e))?;
-                let message_c = CString::new(message_json)
-                    .map_err(|e| format!("Invalid message JSON: {}", e))?;
-
-                let result = on_message(from_c.as_ptr(), message_c.as_ptr());
-                if result != 0 {
-                    return Err(format!("Message handling failed with code: {}", result));
-                }
-            }
+        let from_c = CString::new(from)
+            .map_err(|e| format!("Invalid from string: {}", e))?;
+        let message_json = serde_json::to_string(message)
+            .map_err(|e| format!("Failed to serialize message: {}", e))?;
+        let message_c = CString::new(message_json)
+            .map_err(|e| format!("Invalid message JSON: {}", e))?;
+
+        let result = self.run_plugin_call(to, "message", move || {
+            let _library = library;
+            on_message(from_c.as_ptr(), message_c.as_ptr())
+        })?;
+        if result != 0 {
+            return Err(format!("Message handling failed with code: {}", result));
         }
 
         Ok(())
@@ -544,6 +781,7 @@ impl PluginLoader {
                     }
                 }
                 plugin.enabled = true;
+                plugin.metadata.unhealthy = None;
                 changed = true;
             } else if !enabled && plugin.enabled {
                 // Deactivate plugin
@@ -578,22 +816,24 @@ impl PluginLoader {
     }
 
     /// Get list of loaded plugins
+    /// 已加载的插件，以及因接口版本不兼容被拒绝的插件（带load_error）
     pub fn list_plugins(&self) -> Vec<PluginMetadata> {
         let plugins = self.plugins.lock().unwrap();
-        plugins.values().map(|p| p.metadata.clone()).collect()
+        let mut list: Vec<PluginMetadata> = plugins.values().map(|p| p.metadata.clone()).collect();
+        list.extend(self.rejected.lock().unwrap().values().cloned());
+        list
     }
 
     /// Call service from plugin
     pub fn call_plugin_service(
         &self,
-        _plugin_id: &str,
+        plugin_id: &str,
         service: &str,
         method: &str,
         params: &Value,
     ) -> Result<Value, String> {
-        // This would call into the Core module's service system
-        // For now, we'll implement a basic version
-        match service {
+        // 审计打开时记录每次调用（参数脱敏）
+        audit::audit_call(plugin_id, service, method, params, || match service {
             "markers" => self.handle_marker_service(method, params),
             "images" => self.handle_image_service(method, params),
             "project" => self.handle_project_service(method, params),
@@ -602,7 +842,15 @@ impl PluginLoader {
             "notifications" => self.handle_notifications_service(method, params),
             "events" => self.handle_events_service(method, params),
             "config" => self.handle_config_service(method, params),
+            "network" => self.handle_network_service(method),
             _ => Err(format!("Unknown service: {}", service)),
+        })
+    }
+
+    fn handle_network_service(&self, method: &str) -> Result<Value, String> {
+        match method {
+            "get_settings" => serde_json::to_value(self.network_settings()).map_err(|e| e.to_string()),
+            _ => Err(format!("Unknown network method: {}", method)),
         }
     }
 
@@ -632,12 +880,9 @@ impl PluginLoader {
                                 if let Some(marker) = marker::get_marker_info(marker_id.into()) {
                                     // Convert to plugin SDK's expected format
                                     // Extract x, y from geometry enum
-                                    let (x, y) = match &marker.geometry {
-                                        MarkerGeometryDTO::Point { x, y } => (*x, *y),
-                                        MarkerGeometryDTO::Rectangle { x, y, .. } => (*x, *y),
-                                    };
+                                    let (x, y) = marker_anchor(&marker.geometry);
                                     
-                                    let marker_json = serde_json::json!({
+                                    let mut marker_json = serde_json::json!({
                                         "id": marker_id.to_string(),
                                         "image_id": image_id.to_string(),
                                         "text": "", // MarkerDTO doesn't have text field
@@ -645,7 +890,12 @@ impl PluginLoader {
                                         "marker_type": "text", // Default type
                                         "x": x,
                                         "y": y,
+                                        "size_hint": marker.style.size_hint,
+                                        "tag": marker.style.tag,
                                     });
+                                    if let MarkerGeometryDTO::Polygon { points } = &marker.geometry {
+                                        marker_json["points"] = serde_json::json!(points);
+                                    }
                                     all_markers.push(marker_json);
                                 }
                             }
@@ -668,12 +918,9 @@ impl PluginLoader {
                 use bubblefish_core::common::dto::marker::MarkerGeometryDTO;
                 
                 // Extract x, y from geometry enum
-                let (x, y) = match &marker.geometry {
-                    MarkerGeometryDTO::Point { x, y } => (*x, *y),
-                    MarkerGeometryDTO::Rectangle { x, y, .. } => (*x, *y),
-                };
+                let (x, y) = marker_anchor(&marker.geometry);
                 
-                let marker_json = serde_json::json!({
+                let mut marker_json = serde_json::json!({
                     "id": marker_id.to_string(),
                     "image_id": marker.image_id.to_string(),
                     "text": "", // MarkerDTO doesn't have text field
@@ -681,39 +928,104 @@ impl PluginLoader {
                     "marker_type": "text", // Default type
                     "x": x,
                     "y": y,
+                    "size_hint": marker.style.size_hint,
+                    "tag": marker.style.tag,
                 });
+                if let MarkerGeometryDTO::Polygon { points } = &marker.geometry {
+                    marker_json["points"] = serde_json::json!(points);
+                }
                 
                 Ok(marker_json)
             }
+            "get_translation_history" => {
+                let marker_id = params["marker_id"].as_str()
+                    .ok_or("Missing marker_id")?
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())?;
+                Ok(serde_json::to_value(marker::get_translation_history(marker_id)).unwrap_or(serde_json::json!([])))
+            }
+            "move_marker_to_image" => {
+                let parse_id = |key: &str| params[key].as_str()
+                    .ok_or(format!("Missing {}", key))?
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string());
+                let geometry: MarkerGeometryDTO = serde_json::from_value(params["geometry"].clone())
+                    .map_err(|e| format!("Invalid geometry: {}", e))?;
+                let position = params["position"].as_u64().map(|p| p as u32);
+                
+                let moved = marker::move_marker_to_image(parse_id("marker_id")?, parse_id("target_image_id")?, geometry, position)?;
+                Ok(serde_json::json!({ "success": moved }))
+            }
             _ => Err(format!("Unknown marker method: {}", method)),
         }
     }
 
     fn handle_image_service(&self, method: &str, params: &Value) -> Result<Value, String> {
         use bubblefish_core::api::image;
+        use bubblefish_core::common::dto::image::ImageSourceKind;
         
         match method {
+            "get_image_source_kind" => {
+                let image_id = params["image_id"].as_str()
+                    .ok_or("Missing image_id")?
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())?;
+                
+                let kind = image::get_image_source_kind(image_id)
+                    .ok_or("Image not found")?;
+                
+                serde_json::to_value(kind).map_err(|e| e.to_string())
+            }
             "get_image_data" => {
                 let image_id = params["image_id"].as_str()
                     .ok_or("Missing image_id")?
                     .parse::<u32>()
                     .map_err(|e| e.to_string())?;
                 
-                // Get image info to verify it exists
-                let _image_info = image::get_image_info(image_id)
+                // 根据实际的存储方式返回对应的ImageData
+                let kind = image::get_image_source_kind(image_id)
                     .ok_or("Image not found")?;
                 
-                // Get the actual file path from Core
-                let file_path = image::get_image_file_path(image_id)
-                    .ok_or("Image file path not found")?;
+                match kind {
+                    ImageSourceKind::FilePath => {
+                        let file_path = image::get_image_file_path(image_id)
+                            .ok_or("Image file path not found")?;
+                        
+                        Ok(serde_json::json!({
+                            "type": "FilePath",
+                            "path": file_path
+                        }))
+                    }
+                    ImageSourceKind::Binary | ImageSourceKind::SharedBuffer => {
+                        // 共享core中的字节，不先复制一份再序列化
+                        let data = image::get_image_bytes_shared(image_id)?;
+                        let format = image::get_image_mime_type(image_id)
+                            .and_then(|mime| mime.strip_prefix("image/").map(String::from))
+                            .unwrap_or_else(|| "png".to_string());
+                        
+                        Ok(serde_json::json!({
+                            "type": "Binary",
+                            "data": data.as_slice(),
+                            "format": format
+                        }))
+                    }
+                }
+            }
+            "lease_image_data" => {
+                // 插件与宿主在同一进程内，返回core缓存数据的指针，插件直接读取不复制
+                let image_id = params["image_id"].as_str()
+                    .ok_or("Missing image_id")?
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())?;
                 
-                // Construct ImageData enum with real path
-                let image_data = serde_json::json!({
-                    "type": "FilePath",
-                    "path": file_path
-                });
+                let lease = image::lease_image_data(image_id)?;
+                serde_json::to_value(lease).map_err(|e| e.to_string())
+            }
+            "release_image_data" => {
+                let lease_id = params["lease_id"].as_u64()
+                    .ok_or("Missing lease_id")?;
                 
-                Ok(image_data)
+                Ok(serde_json::json!({ "released": image::release_image_lease(lease_id) }))
             }
             "get_image" => {
                 let image_id = params["image_id"].as_str()
@@ -724,9 +1036,18 @@ impl PluginLoader {
                 let image_info = image::get_image_info(image_id)
                     .ok_or("Image not found")?;
                 
-                // Get the actual file path
-                let file_path = image::get_image_file_path(image_id)
-                    .unwrap_or_else(|| format!("./images/{}.png", image_id));
+                // 只有FilePath类型的图片才有真实路径，其他类型返回结构化错误
+                let file_path = match image::get_image_source_kind(image_id) {
+                    Some(ImageSourceKind::FilePath) => image::get_image_file_path(image_id)
+                        .ok_or("Image file path not found")?,
+                    Some(kind) => return Err(serde_json::json!({
+                        "error": "image_not_on_disk",
+                        "source_kind": kind,
+                        "image_id": image_id,
+                        "message": "image is stored as in-memory binary; use get_image_data"
+                    }).to_string()),
+                    None => return Err("Image not found".to_string()),
+                };
                 
                 // Convert to plugin SDK's expected format
                 let image = serde_json::json!({
@@ -774,7 +1095,7 @@ impl PluginLoader {
 
     fn handle_bunny_service(&self, method: &str, params: &Value) -> Result<Value, String> {
         use bubblefish_core::api::bunny;
-        use bubblefish_core::service::bunny::{OCRServiceInfo, TranslationServiceInfo, BUNNY_SERVICE_REGISTRY};
+        use bubblefish_core::service::bunny::{DetectionServiceInfo, OCRServiceInfo, TranslationServiceInfo, BUNNY_SERVICE_REGISTRY};
 
         match method {
             "register_ocr_service" => {
@@ -807,6 +1128,20 @@ impl PluginLoader {
 
                 Ok(serde_json::json!({"success": true}))
             }
+            "register_detection_service" => {
+                let plugin_id = params["plugin_id"].as_str()
+                    .ok_or("Missing plugin_id")?;
+                let service_info: DetectionServiceInfo = serde_json::from_value(
+                    params["service_info"].clone()
+                ).map_err(|e| format!("Invalid service info: {}", e))?;
+
+                BUNNY_SERVICE_REGISTRY
+                    .write()
+                    .map_err(|e| format!("Failed to acquire write lock: {}", e))?
+                    .register_detection_service(plugin_id.to_string(), service_info)?;
+
+                Ok(serde_json::json!({"success": true}))
+            }
             "unregister_service" => {
                 let service_id = params["service_id"].as_str()
                     .ok_or("Missing service_id")?;
@@ -819,11 +1154,19 @@ impl PluginLoader {
                 Ok(serde_json::json!({"success": true}))
             }
             "get_ocr_services" => {
-                let services = bunny::get_available_ocr_services();
+                // 可选的project_id，只返回该项目启用的服务
+                let project_id = params["project_id"].as_u64().map(|id| id as u32);
+                let services = bunny::get_available_ocr_services(project_id);
                 Ok(serde_json::to_value(services).unwrap_or(serde_json::json!([])))
             }
             "get_translation_services" => {
-                let services = bunny::get_available_translation_services();
+                let project_id = params["project_id"].as_u64().map(|id| id as u32);
+                let services = bunny::get_available_translation_services(project_id);
+                Ok(serde_json::to_value(services).unwrap_or(serde_json::json!([])))
+            }
+            "get_detection_services" => {
+                let project_id = params["project_id"].as_u64().map(|id| id as u32);
+                let services = bunny::get_available_detection_services(project_id);
                 Ok(serde_json::to_value(services).unwrap_or(serde_json::json!([])))
             }
             _ => Err(format!("Unknown bunny method: {}", method)),
@@ -849,11 +1192,16 @@ impl PluginLoader {
                         // Get marker_id from task
                         if let Ok(Some(task)) = bubblefish_core::service::bunny::TASK_MANAGER.get_task(task_id) {
                             // Handle OCR completion
+                            // 文本行位置，不支持的插件没有这个字段
+                            let regions = data.get("regions")
+                                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                                .unwrap_or_default();
                             bubblefish_core::api::bunny::handle_ocr_completed(
                                 task_id.to_string(),
                                 task.marker_id,
                                 text.to_string(),
-                                model.to_string()
+                                model.to_string(),
+                                regions
                             ).map_err(|e| format!("Failed to handle OCR completion: {}", e))?;
                             log::debug!("OCR result handled for task {}", task_id);
                         } else {
@@ -869,17 +1217,40 @@ impl PluginLoader {
                         // Get marker_id from task
                         if let Ok(Some(task)) = bubblefish_core::service::bunny::TASK_MANAGER.get_task(task_id) {
                             // Handle translation completion
+                            // 插件的术语校验结果，旧插件没有这个字段
+                            let warnings = data.get("warnings")
+                                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                                .unwrap_or_default();
+                            let metadata = data.get("metadata").cloned();
                             bubblefish_core::api::bunny::handle_translation_completed(
                                 task_id.to_string(),
                                 task.marker_id,
                                 translated_text.to_string(),
-                                service.to_string()
+                                service.to_string(),
+                                warnings,
+                                metadata
                             ).map_err(|e| format!("Failed to handle translation completion: {}", e))?;
                             log::debug!("Translation result handled for task {}", task_id);
                         } else {
                             log::warn!("Task {} not found when handling translation result", task_id);
                         }
                     }
+                } else if event_name == "plugin:detect_result" {
+                    if let Some(task_id) = data.get("task_id").and_then(|v| v.as_str()) {
+                        let model = data.get("model").and_then(|v| v.as_str()).unwrap_or_default();
+                        let regions = data.get("regions")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok())
+                            .unwrap_or_default();
+                        bubblefish_core::api::bunny::handle_detection_completed(task_id.to_string(), model.to_string(), regions)
+                            .map_err(|e| format!("Failed to handle detection result: {}", e))?;
+                        log::debug!("Detection result handled for task {}", task_id);
+                    }
+                } else if event_name == "plugin:detect_error" {
+                    if let Some(task_id) = data.get("task_id").and_then(|v| v.as_str()) {
+                        let error = data.get("error").and_then(|v| v.as_str()).unwrap_or("Detection failed");
+                        bubblefish_core::api::bunny::handle_task_failed(task_id.to_string(), error.to_string())
+                            .map_err(|e| format!("Failed to handle detection error: {}", e))?;
+                    }
                 }
 
                 // Emit the event to frontend as well
@@ -936,6 +1307,11 @@ impl PluginLoader {
         }
 
         let config_file = config_dir.join(format!("{}.json", plugin_id));
+        let read_config = || -> Value {
+            fs::read_to_string(&config_file).ok()
+                .and_then(|config_str| serde_json::from_str(&config_str).ok())
+                .unwrap_or_else(|| serde_json::json!({}))
+        };
 
         match method {
             "get" => {
@@ -967,6 +1343,7 @@ impl PluginLoader {
                 let config = params.get("config");
                 let key = params.get("key").and_then(|v| v.as_str());
                 let value = params.get("value");
+                let old_config = read_config();
 
                 if let Some(config) = config {
                     // Set entire config
@@ -976,15 +1353,7 @@ impl PluginLoader {
                         .map_err(|e| format!("Failed to write config file: {}", e))?;
                 } else if let (Some(key), Some(value)) = (key, value) {
                     // Update single key
-                    let mut config = if config_file.exists() {
-                        let config_str = fs::read_to_string(&config_file)
-                            .map_err(|e| format!("Failed to read config file: {}", e))?;
-                        serde_json::from_str(&config_str)
-                            .unwrap_or_else(|_| serde_json::json!({}))
-                    } else {
-                        serde_json::json!({})
-                    };
-
+                    let mut config = old_config.clone();
                     config[key] = value.clone();
 
                     let config_str = serde_json::to_string_pretty(&config)
@@ -995,12 +1364,15 @@ impl PluginLoader {
                     return Err("Either config or key-value pair required".to_string());
                 }
 
+                notify_config_changed(plugin_id, &old_config, &read_config());
                 Ok(serde_json::json!({"success": true}))
             }
             "delete" => {
                 if config_file.exists() {
+                    let old_config = read_config();
                     fs::remove_file(&config_file)
                         .map_err(|e| format!("Failed to delete config file: {}", e))?;
+                    notify_config_changed(plugin_id, &old_config, &serde_json::json!({}));
                 }
                 Ok(serde_json::json!({"success": true}))
             }
@@ -1012,6 +1384,56 @@ impl PluginLoader {
 // Global plugin loader instance
 static PLUGIN_LOADER: OnceLock<Arc<PluginLoader>> = OnceLock::new();
 
+/// 新旧配置中值不同的键，逐个生成ConfigChanged事件（被删除的键值为null）
+fn config_changed_events(plugin_id: &str, old: &Value, new: &Value) -> Vec<Value> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| serde_json::json!({
+            "ConfigChanged": {
+                "plugin_id": plugin_id,
+                "key": key,
+                "value": new.get(key).cloned().unwrap_or(Value::Null)
+            }
+        }))
+        .collect()
+}
+
+/// 把配置变化只发给所属插件；在新线程里发送，插件自己写配置时不会重入
+pub fn notify_config_changed(plugin_id: &str, old: &Value, new: &Value) {
+    let events = config_changed_events(plugin_id, old, new);
+    if events.is_empty() {
+        return;
+    }
+    let plugin_id = plugin_id.to_string();
+    std::thread::spawn(move || {
+        let Some(loader) = get_plugin_loader() else { return };
+        for event in &events {
+            if let Err(e) = loader.dispatch_event(&plugin_id, event) {
+                eprintln!("[PluginLoader] Failed to deliver config change to {}: {}", plugin_id, e);
+            }
+        }
+    });
+}
+
+// 插件看到的标记坐标：点本身，矩形和多边形取（外接）矩形左上角
+fn marker_anchor(geometry: &bubblefish_core::common::dto::marker::MarkerGeometryDTO) -> (f64, f64) {
+    use bubblefish_core::common::dto::marker::{polygon_bounding_box, MarkerGeometryDTO};
+    match geometry {
+        MarkerGeometryDTO::Point { x, y } => (*x, *y),
+        MarkerGeometryDTO::Rectangle { x, y, .. } => (*x, *y),
+        MarkerGeometryDTO::Polygon { points } => {
+            let (x, y, _, _) = polygon_bounding_box(points);
+            (x, y)
+        }
+    }
+}
+
 pub fn init_plugin_loader(app_handle: tauri::AppHandle) {
     let _ = PLUGIN_LOADER.set(Arc::new(PluginLoader::new(app_handle)));
 }
@@ -1107,4 +1529,77 @@ extern "C" fn host_log_message(level: i32, message: *const c_char) {
             _ => println!("[Plugin] {}", message),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn blocking_call(block_ms: u64) -> impl FnOnce() -> i32 + Send + 'static {
+        move || {
+            std::thread::sleep(Duration::from_millis(block_ms));
+            7
+        }
+    }
+
+    #[cfg(unix)]
+    fn loaded_plugin(id: &str) -> LoadedPlugin {
+        LoadedPlugin {
+            library: Arc::new(libloading::os::unix::Library::this().into()),
+            metadata: PluginMetadata {
+                id: id.to_string(),
+                name: id.to_string(),
+                version: "0.1.0".to_string(),
+                description: String::new(),
+                author: String::new(),
+                subscribed_events: Vec::new(),
+                config_schema: None,
+                api_version: MAX_PLUGIN_API_VERSION,
+                load_error: None,
+                unhealthy: None,
+            },
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_blocking_call_times_out() {
+        let started = Instant::now();
+        let outcome = call_with_timeout(20, "plugin-test-event".to_string(), blocking_call(2_000)).unwrap();
+        assert!(matches!(outcome, CallOutcome::TimedOut));
+        // 不等卡住的回调结束
+        assert!(started.elapsed() < Duration::from_millis(1_500));
+
+        let outcome = call_with_timeout(2_000, "plugin-test-event".to_string(), blocking_call(0)).unwrap();
+        assert!(matches!(outcome, CallOutcome::Finished(7)));
+        let outcome = call_with_timeout(2_000, "plugin-test-event".to_string(), || panic!("plugin bug")).unwrap();
+        assert!(matches!(outcome, CallOutcome::Crashed));
+    }
+
+    #[test]
+    fn test_zero_timeout_runs_on_caller_thread_without_limit() {
+        let caller = std::thread::current().id();
+        let outcome = call_with_timeout(0, "plugin-test-event".to_string(), move || {
+            std::thread::sleep(Duration::from_millis(50));
+            if std::thread::current().id() == caller { 7 } else { -1 }
+        }).unwrap();
+        assert!(matches!(outcome, CallOutcome::Finished(7)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timed_out_plugin_is_disabled() {
+        let mut plugins = HashMap::new();
+        plugins.insert("slow".to_string(), loaded_plugin("slow"));
+        plugins.insert("other".to_string(), loaded_plugin("other"));
+
+        // 和run_plugin_call一样，回调超时后停用插件
+        let outcome = call_with_timeout(20, "plugin-slow-event".to_string(), blocking_call(2_000)).unwrap();
+        assert!(matches!(outcome, CallOutcome::TimedOut));
+        let metadata = disable_unhealthy(&mut plugins, "slow", "event timed out after 20ms".to_string()).unwrap();
+        assert_eq!(metadata.unhealthy.as_deref(), Some("event timed out after 20ms"));
+        assert!(!plugins["slow"].enabled);
+        assert!(plugins["other"].enabled && plugins["other"].metadata.unhealthy.is_none());
+        assert!(disable_unhealthy(&mut plugins, "missing", String::new()).is_none());
+    }
+}
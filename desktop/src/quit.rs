@@ -0,0 +1,87 @@
+// 退出确认 - 关闭主窗口或菜单退出（Cmd+Q）时，有未保存的项目先由前端确认：全部保存、不保存或取消
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use crate::windows::MAIN_WINDOW_LABEL;
+
+pub const CONFIRM_QUIT_EVENT: &str = "app:confirm_quit";
+
+#[derive(Debug, Clone, Serialize)]
+struct DirtyProject {
+    id: u32,
+    name: String,
+    file_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ConfirmQuitPayload {
+    dirty_projects: Vec<DirtyProject>,
+}
+
+fn dirty_projects() -> Vec<DirtyProject> {
+    bubblefish_core::api::get_unsaved_project_ids()
+        .into_iter()
+        .filter_map(bubblefish_core::api::get_project_info)
+        .map(|project| DirtyProject { id: project.id.0, name: project.name, file_path: project.file_path })
+        .collect()
+}
+
+/// 请求退出应用：没有未保存的修改时直接退出，否则通知主窗口的前端确认，等待confirm_quit
+pub fn request_quit<R: Runtime>(app: &AppHandle<R>) {
+    let dirty_projects = dirty_projects();
+    if dirty_projects.is_empty() {
+        app.exit(0);
+        return;
+    }
+    if let Err(e) = app.emit_to(MAIN_WINDOW_LABEL, CONFIRM_QUIT_EVENT, ConfirmQuitPayload { dirty_projects }) {
+        log::error!("Failed to emit confirm quit event: {}", e);
+    }
+}
+
+// 没有保存路径的项目弹出另存为对话框，用户取消时返回None
+fn prompt_save_path(app: &AppHandle, project_name: &str) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    app.dialog()
+        .file()
+        .add_filter("BubbleFish Project", &["bf"])
+        .set_file_name(format!("{}.bf", project_name))
+        .set_title(format!("保存项目 \"{}\"", project_name))
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    match rx.recv() {
+        Ok(file_path) => Ok(file_path.map(|path| path.to_string())),
+        Err(_) => Err("Dialog communication error".to_string())
+    }
+}
+
+/// 前端对退出确认的回应：discard直接退出；save_all先保存所有未保存的项目再退出；都为false时取消退出
+/// 返回是否退出。保存失败时不退出并返回错误，用户取消另存为时也不退出
+#[tauri::command]
+pub async fn confirm_quit(app: AppHandle, save_all: bool, discard: bool) -> Result<bool, String> {
+    if discard {
+        app.exit(0);
+        return Ok(true);
+    }
+    if !save_all {
+        return Ok(false);
+    }
+
+    for project in dirty_projects() {
+        let path = match project.file_path {
+            Some(path) => path,
+            None => match prompt_save_path(&app, &project.name)? {
+                Some(path) => path,
+                None => return Ok(false),
+            },
+        };
+        crate::write_project_file(project.id, &path)
+            .map_err(|e| format!("项目 \"{}\" 保存失败：{}", project.name, e))?;
+    }
+
+    app.exit(0);
+    Ok(true)
+}
@@ -0,0 +1,228 @@
+// 多窗口管理 - 主窗口之外可以为项目单独开窗口，窗口大小/位置保存到应用数据目录，下次启动时恢复
+// core的数据是全局共享的，每个窗口显示哪个项目由这里的 窗口label -> 项目ID 映射记录
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Window, WindowEvent};
+#[cfg(target_os = "macos")]
+use tauri::TitleBarStyle;
+
+pub const MAIN_WINDOW_LABEL: &str = "main";
+const PROJECT_WINDOW_PREFIX: &str = "project-";
+const WINDOW_STATE_FILE: &str = "window_states.json";
+
+/// 窗口label -> 窗口中显示的项目ID
+#[derive(Default)]
+pub struct WindowProjects(Mutex<HashMap<String, u32>>);
+
+impl WindowProjects {
+    pub fn get(&self, label: &str) -> Option<u32> {
+        self.0.lock().ok().and_then(|map| map.get(label).copied())
+    }
+
+    pub fn set(&self, label: &str, project_id: Option<u32>) {
+        if let Ok(mut map) = self.0.lock() {
+            match project_id {
+                Some(project_id) => { map.insert(label.to_string(), project_id); }
+                None => { map.remove(label); }
+            }
+        }
+    }
+}
+
+/// 窗口的逻辑坐标和大小
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    #[serde(default)]
+    maximized: bool,
+}
+
+fn state_file<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(WINDOW_STATE_FILE))
+}
+
+fn load_states<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, WindowGeometry> {
+    state_file(app)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn window_geometry<R: Runtime>(window: &Window<R>) -> Option<WindowGeometry> {
+    let scale = window.scale_factor().ok()?;
+    let maximized = window.is_maximized().unwrap_or(false);
+    // 最小化时拿到的位置没有意义
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+    let size = window.inner_size().ok()?.to_logical::<f64>(scale);
+    Some(WindowGeometry { x: position.x, y: position.y, width: size.width, height: size.height, maximized })
+}
+
+// 项目窗口的label每次启动都不同，统一保存在同一个键下，新开的项目窗口沿用上次的大小
+fn state_key(label: &str) -> &str {
+    if label.starts_with(PROJECT_WINDOW_PREFIX) { PROJECT_WINDOW_PREFIX } else { label }
+}
+
+fn save_window_state<R: Runtime>(window: &Window<R>) {
+    let app = window.app_handle();
+    let Some(geometry) = window_geometry(window) else { return };
+    let Some(path) = state_file(app) else { return };
+    let mut states = load_states(app);
+    states.insert(state_key(window.label()).to_string(), geometry);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string_pretty(&states) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(&path, content) {
+                log::error!("Failed to save window state: {}", e);
+            }
+        }
+        Err(e) => log::error!("Failed to serialize window state: {}", e),
+    }
+}
+
+/// 退出应用时保存所有窗口的状态（直接退出不会触发窗口的CloseRequested）
+pub fn save_all_window_states<R: Runtime>(app: &AppHandle<R>) {
+    for window in app.webview_windows().values() {
+        let webview: &tauri::Webview<R> = window.as_ref();
+        save_window_state(&webview.window());
+    }
+}
+
+/// 创建窗口：所有窗口使用相同的标题栏配置，有保存的状态时恢复大小和位置
+pub fn build_window<R: Runtime, M: Manager<R>>(manager: &M, label: &str) -> tauri::Result<WebviewWindow<R>> {
+    let saved = load_states(manager.app_handle()).get(state_key(label)).copied();
+
+    let window_builder = WebviewWindowBuilder::new(manager, label, WebviewUrl::default())
+        .title("")  // 设置标题为空，使用自定义标题栏，如果标题栏有文字，在macOS上会遮挡
+        .min_inner_size(800.0, 600.0)
+        .resizable(true);
+    let window_builder = match saved {
+        Some(geometry) => window_builder
+            .inner_size(geometry.width.max(800.0), geometry.height.max(600.0))
+            .position(geometry.x, geometry.y)
+            .maximized(geometry.maximized),
+        None => window_builder.inner_size(1200.0, 800.0).center(),
+    };
+
+    // 根据操作系统设置不同的标题栏样式和装饰
+    let window_builder = if cfg!(target_os = "macos") {
+        // macOS: 使用 Overlay 模式解决渲染遮挡问题
+        #[cfg(target_os = "macos")]
+        let window_builder = window_builder
+          .decorations(true)
+          .title_bar_style(TitleBarStyle::Overlay);
+        #[cfg(not(target_os = "macos"))]
+        let window_builder = window_builder.decorations(true);
+        window_builder
+    } else {
+        // Windows/Linux: 禁用装饰，使用完全自定义标题栏
+        window_builder.decorations(false)
+    };
+
+    window_builder.build()
+}
+
+/// 当前获得焦点的窗口，菜单事件只发给它；都没有焦点时退回主窗口
+pub fn focused_window<R: Runtime>(app: &AppHandle<R>) -> Option<WebviewWindow<R>> {
+    let windows = app.webview_windows();
+    windows.values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| windows.get(MAIN_WINDOW_LABEL))
+        .or_else(|| windows.values().next())
+        .cloned()
+}
+
+/// 窗口事件：关闭前保存大小/位置，销毁后清理窗口的项目映射
+/// 关闭主窗口即退出应用，有未保存的项目时先取消关闭，由前端确认
+pub fn handle_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    match event {
+        WindowEvent::CloseRequested { api, .. } => {
+            save_window_state(window);
+            if window.label() == MAIN_WINDOW_LABEL {
+                api.prevent_close();
+                crate::quit::request_quit(window.app_handle());
+            }
+        }
+        WindowEvent::Destroyed => {
+            window.state::<WindowProjects>().set(window.label(), None);
+        }
+        _ => {}
+    }
+}
+
+/// 为项目打开单独的窗口，窗口已存在时只聚焦，返回窗口label
+/// 在同步命令里创建窗口会在Windows上死锁，所以是async
+#[tauri::command]
+pub async fn open_project_window(app: AppHandle, project_id: u32) -> Result<String, String> {
+    if bubblefish_core::api::get_project_info(project_id).is_none() {
+        return Err(format!("Project {} not found", project_id));
+    }
+    let label = format!("{}{}", PROJECT_WINDOW_PREFIX, project_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(label);
+    }
+    // 先登记映射，新窗口的前端启动后通过get_window_project读取
+    app.state::<WindowProjects>().set(&label, Some(project_id));
+    if let Err(e) = build_window(&app, &label) {
+        app.state::<WindowProjects>().set(&label, None);
+        return Err(format!("Failed to open project window: {}", e));
+    }
+    Ok(label)
+}
+
+/// 调用方窗口当前显示的项目
+#[tauri::command]
+pub fn get_window_project(window: WebviewWindow, projects: tauri::State<'_, WindowProjects>) -> Option<u32> {
+    projects.get(window.label())
+}
+
+/// 窗口切换项目时由前端同步，None表示没有打开项目
+#[tauri::command]
+pub fn set_window_project(window: WebviewWindow, projects: tauri::State<'_, WindowProjects>, project_id: Option<u32>) {
+    projects.set(window.label(), project_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_project_mapping() {
+        let projects = WindowProjects::default();
+        projects.set("project-3", Some(3));
+        projects.set(MAIN_WINDOW_LABEL, Some(1));
+        assert_eq!(projects.get("project-3"), Some(3));
+        projects.set(MAIN_WINDOW_LABEL, Some(2));
+        assert_eq!(projects.get(MAIN_WINDOW_LABEL), Some(2));
+
+        // 窗口关闭或不再显示项目时移除映射
+        projects.set("project-3", None);
+        assert_eq!(projects.get("project-3"), None);
+        assert_eq!(projects.get("project-9"), None);
+    }
+
+    #[test]
+    fn test_project_windows_share_saved_state() {
+        assert_eq!(state_key("project-3"), PROJECT_WINDOW_PREFIX);
+        assert_eq!(state_key("project-17"), PROJECT_WINDOW_PREFIX);
+        assert_eq!(state_key(MAIN_WINDOW_LABEL), MAIN_WINDOW_LABEL);
+        assert_eq!(state_key("debug"), "debug");
+
+        // 旧的状态文件没有maximized字段
+        let states: HashMap<String, WindowGeometry> =
+            serde_json::from_str(r#"{"main":{"x":10.0,"y":20.0,"width":1280.0,"height":720.0}}"#).unwrap();
+        let main = states[MAIN_WINDOW_LABEL];
+        assert_eq!((main.x, main.width), (10.0, 1280.0));
+        assert!(!main.maximized);
+    }
+}
@@ -1,8 +1,9 @@
 // Core adapter for both Tauri and WASM environments
 import { isTauri } from './tauri';
 import { browser } from '$app/environment';
-import type { ImageMetadata, ImageFormat, Marker, TranslationProject, OpeningProjectInfo, UndoRedoResult, Language } from '../types';
+import type { AcceleratorError, AnnotatedImage, AnnotateOptions, BfFormatError, BfSummary, BfValidation, CommandInfo, CoordinateMode, CoreHealth, DownscaleSettings, EventChannelStats, FrontendEventFilterStats, ImageImportError, IntegrityReport, DuplicateGroup, DuplicateKeepStrategy, LogRecord, LogRecordLevel, PluginAuditEntry, MissingImage, RelinkResult, LabelplusCoordinateOrigin, LabelplusMergeOptions, LabelplusMergeReport, LabelplusOriginPreview, TypesettingFormat, TypesettingOptions, TypesettingImportOptions, TypesettingImportReport, ArchiveImportReport, ImageMetadata, ImageSourceKind, ImageViewState, KeyBinding, MarkerThumbnail, MenuSection, ImageFormat, Marker, MarkerGeometry, ReplaceReport, TextStats, TranslationHistoryEntry, TranslationIssue, TranslationProject, OpeningProjectInfo, UndoRedoResult, Language, ProjectTemplate, ProjectGlossary, ReadingDirection, SnapshotInfo, TemplateList } from '../types';
 import { eventSystem, type LogEvent } from './events';
+import type { AcceptMachineTranslationReport, BunnyPipeline, DetectedRegion, OcrBatchItem, OcrRegion, PipelineBatchItem, ImageTaskProgress, ServiceTaskMetrics, TaskHistoryEntry, TranslationMetadata, TranslationResult, TranslationWarning } from '../types/bunny';
 import type { WasmWorkerMessage, WasmWorkerResponse, WasmWorkerEvent } from '../workers/wasmWorker';
 import { fetchWasmResource, transferWasmToWorker } from '../utils/wasmLoader';
 
@@ -12,11 +13,17 @@ export interface WasmModule {
 	wasm_create_empty_opening_project(project_name: string): number | null;
 	wasm_create_opening_project_from_binary(data: Uint8Array, file_extension: string, project_name: string): number | null;
 	wasm_create_opening_project_from_shared_buffer(file_extension: string, project_name: string): Promise<number | null>;
+	wasm_create_opening_project_from_url(url: string, project_name: string): Promise<number>;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_create_opening_project_from_archive(data: Uint8Array, archive_name: string, project_name: string): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_create_opening_project_from_archive_shared_buffer(archive_name: string, project_name: string): Promise<any>;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_opening_project_info(project_id: number): any;
 	wasm_flush_opening_project_images(project_id: number): boolean;
 	wasm_finalize_opening_project(project_id: number): boolean;
 	wasm_delete_opening_project(project_id: number): boolean;
+	wasm_cancel_opening_project(opening_id: number): boolean;
 	
 	// Project methods
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
@@ -24,14 +31,59 @@ export interface WasmModule {
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_all_projects_info(): any;
 	wasm_update_project_name(project_id: number, name: string): boolean;
+	wasm_set_project_file_watch(project_id: number, enabled: boolean): boolean;
+	wasm_set_service_enabled(project_id: number, service_id: string, enabled: boolean): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_get_project_glossary(project_id: number): any;
+	wasm_set_project_glossary(project_id: number, glossary: ProjectGlossary): boolean;
+	wasm_set_service_fallbacks(project_id: number, ocr: string[], translation: string[]): boolean;
+	wasm_get_project_notes(project_id: number): string | undefined;
+	wasm_set_project_notes(project_id: number, notes: string): boolean;
+	wasm_get_auto_ocr_on_create(project_id: number): boolean | undefined;
+	wasm_set_auto_ocr_on_create(project_id: number, enabled: boolean): boolean;
+	wasm_get_keep_translation_history(project_id: number): boolean | undefined;
+	wasm_set_keep_translation_history(project_id: number, enabled: boolean): boolean;
+	wasm_get_grayscale_reencode(project_id: number): boolean | undefined;
+	wasm_set_grayscale_reencode(project_id: number, enabled: boolean): boolean;
+	wasm_get_reading_direction(project_id: number): ReadingDirection | null;
+	wasm_set_reading_direction(project_id: number, direction: ReadingDirection): boolean;
 	wasm_delete_project(project_id: number): boolean;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_check_integrity(project_id: number, repair: boolean): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_duplicate_project(project_id: number, new_name: string): any;
+	wasm_find_duplicate_images(project_id: number, threshold?: number): DuplicateGroup[];
+	wasm_remove_duplicate_images(project_id: number, keep_strategy: DuplicateKeepStrategy, threshold: number | undefined, force: boolean): Uint32Array;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_save_as_template(project_id: number, name: string): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_create_project_from_template(name: string, template: string): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_list_templates(): any;
+	wasm_delete_template(name: string): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_create_snapshot(project_id: number, name: string): any;
+	wasm_list_snapshots(project_id: number): SnapshotInfo[] | null;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_restore_snapshot(project_id: number, snapshot_id: string): any;
+	wasm_delete_snapshot(snapshot_id: string): boolean;
+	wasm_set_snapshot_limit(limit: number): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_project_images(project_id: number): any;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_project_images_metadata(project_id: number): any;
 	wasm_add_image_from_binary_to_project(project_id: number, format_str: string, data: Uint8Array, name?: string | null): number | undefined;
 	wasm_init_shared_buffer(buffer: SharedArrayBuffer): void;
 	wasm_add_image_from_shared_buffer(project_id: number, name?: string | null): Promise<number>;
+	wasm_add_image_from_clipboard_bytes(project_id: number, data: Uint8Array, suggested_name?: string | null): number;
+	wasm_add_image_from_clipboard_shared_buffer(project_id: number, suggested_name?: string | null): Promise<number>;
+	wasm_add_image_from_bytes(project_id: number, data: Uint8Array, name?: string | null): number;
+	wasm_set_image_reviewed(image_id: number, reviewed: boolean, note?: string | null): boolean;
+	wasm_set_image_locked(image_id: number, locked: boolean): boolean;
+	wasm_rename_image(image_id: number, new_name: string): boolean;
+	wasm_set_image_view_state(image_id: number, view_state: ImageViewState | null): boolean;
+	wasm_get_image_view_state(image_id: number): ImageViewState | null;
+	wasm_select_image(image_id: number): ImageViewState | null;
 	wasm_cleanup_orphaned_images(): number;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_image_info(image_id: number): any;
@@ -41,33 +93,91 @@ export interface WasmModule {
 	wasm_reorder_project_images(project_id: number, image_ids: Uint32Array): boolean;
 	wasm_get_image_binary_data(image_id: number): Uint8Array;
 	wasm_get_image_mime_type(image_id: number): string | undefined;
+	wasm_encode_image(image_id: number, format_str: string, quality?: number | null): Uint8Array;
+	wasm_get_marker_thumbnail(marker_id: number, max_edge_px: number): Uint8Array;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_get_marker_thumbnails(image_id: number, max_edge_px: number): any;
+	// 返回[marker_id, offset, length, ...]，图片数据在共享缓冲区的数据区
+	wasm_write_marker_thumbnails_to_shared_buffer(image_id: number, max_edge_px: number): Uint32Array;
+	wasm_get_image_metadata(image_id: number): ImageMetadata | null;
+	wasm_get_image_source_kind(image_id: number): ImageSourceKind | null;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_image_markers(image_id: number): any;
 	// 点型marker
-	wasm_add_point_marker_to_image(image_id: number, x: number, y: number, translation?: string | null): number | undefined;
+	wasm_add_point_marker_to_image(image_id: number, x: number, y: number, translation?: string | null, mode?: CoordinateMode | null): number | undefined;
 	// 矩形型marker
-	wasm_add_rectangle_marker_to_image(image_id: number, x: number, y: number, width: number, height: number, translation?: string | null): number | undefined;
+	wasm_add_rectangle_marker_to_image(image_id: number, x: number, y: number, width: number, height: number, translation?: string | null, mode?: CoordinateMode | null): number | undefined;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_marker_info(marker_id: number): any;
 	// 点型marker位置更新
-	wasm_update_point_marker_position(marker_id: number, x: number, y: number): boolean;
+	wasm_update_point_marker_position(marker_id: number, x: number, y: number, mode?: CoordinateMode | null): boolean;
 	// 矩形型marker几何更新
-	wasm_update_rectangle_marker_geometry(marker_id: number, x: number, y: number, width: number, height: number): boolean;
+	wasm_update_rectangle_marker_geometry(marker_id: number, x: number, y: number, width: number, height: number, mode?: CoordinateMode | null): boolean;
+	// 拖拽手势
+	wasm_begin_marker_drag(marker_id: number): boolean;
+	wasm_end_marker_drag(marker_id: number, x: number, y: number, width?: number, height?: number): boolean;
 	wasm_update_marker_translation(marker_id: number, translation: string): boolean;
+	wasm_move_marker_to_image(marker_id: number, target_image_id: number, geometry: MarkerGeometry, position?: number | null): boolean;
 	wasm_update_marker_style(marker_id: number, overlay_text: boolean, horizontal: boolean): boolean;
+	wasm_update_marker_size_hint(marker_id: number, size_hint?: number | null): boolean;
+	wasm_update_marker_tag(marker_id: number, tag?: string | null): boolean;
+	wasm_update_marker_note(marker_id: number, note?: string | null): boolean;
+	wasm_get_translation_history(marker_id: number): TranslationHistoryEntry[];
+	wasm_revert_translation(marker_id: number, history_index: number): boolean;
 	wasm_move_marker_order(marker_id: number, new_index: number): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_compact_marker_indices(image_id: number): any;
+	wasm_renumber_markers_by_reading_order(image_id: number): number;
+	wasm_merge_rectangle_markers(marker_ids: Uint32Array): number;
+	wasm_select_next_marker(image_id: number, current_marker_id: number | undefined, backward: boolean): number | undefined;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_compact_project_marker_indices(project_id: number): any;
 	// 点型marker完整更新
-	wasm_update_point_marker_full(marker_id: number, x: number, y: number, translation?: string | null): boolean;
+	wasm_update_point_marker_full(marker_id: number, x: number, y: number, translation?: string | null, mode?: CoordinateMode | null): boolean;
 	// 矩形型marker完整更新
-	wasm_update_rectangle_marker_full(marker_id: number, x: number, y: number, width: number, height: number, translation?: string | null): boolean;
+	wasm_update_rectangle_marker_full(marker_id: number, x: number, y: number, width: number, height: number, translation?: string | null, mode?: CoordinateMode | null): boolean;
 	wasm_remove_marker_from_image(image_id: number, marker_id: number): boolean;
 	wasm_clear_image_markers(image_id: number): boolean;
 	wasm_convert_rectangle_to_point_marker(marker_id: number): boolean;
 	wasm_convert_point_to_rectangle_marker(marker_id: number): boolean;
+	// 多边形marker，顶点为扁平数组 [x0, y0, x1, y1, ...]
+	wasm_add_polygon_marker_to_image(image_id: number, points: Float64Array, translation?: string | null, mode?: CoordinateMode | null): number | undefined;
+	wasm_update_polygon_marker_geometry(marker_id: number, points: Float64Array, mode?: CoordinateMode | null): boolean;
+	wasm_end_polygon_marker_drag(marker_id: number, points: Float64Array): boolean;
+	wasm_convert_polygon_to_rectangle_marker(marker_id: number): boolean;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_stats(): any;
+	wasm_get_event_channel_stats(): EventChannelStats | null;
+	wasm_set_frontend_event_filter(patterns: string[]): boolean;
+	wasm_get_frontend_event_filter_stats(): FrontendEventFilterStats | null;
+	wasm_is_cross_origin_isolated(): boolean;
+	wasm_get_health(): CoreHealth | null;
+	wasm_export_recovery_snapshot(project_id: number): Uint8Array | null;
+	wasm_import_recovery_snapshot(data: Uint8Array): number | null;
+	wasm_get_recent_logs(level_filter: LogRecordLevel | null, limit: number): LogRecord[];
+	wasm_set_log_level(level: LogRecordLevel): boolean;
+	wasm_get_log_level(): LogRecordLevel;
+	wasm_set_audit_enabled(enabled: boolean): void;
+	wasm_is_audit_enabled(): boolean;
+	wasm_get_plugin_audit_log(plugin_id?: string | null, limit?: number | null): PluginAuditEntry[];
+	wasm_cancel_operation(operation_id: string): boolean;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_project_stats(project_id: number): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_get_text_stats(project_id: number): any;
+	wasm_set_text_count_config(cjk_char_as_word: boolean): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_get_keymap(): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_set_keymap(bindings: Record<string, string | null>): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_reset_keymap(): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_load_keymap(json: string): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_get_menu_definition(): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_set_accelerator(item_id: string, accelerator: string | null): any;
 	wasm_clear_all_data(): void;
 	wasm_clear_project_data(project_id: number): boolean;
 	
@@ -77,7 +187,15 @@ export interface WasmModule {
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_redo(project_id: number): any;
 	wasm_clear_undo_redo_history(project_id: number): void;
+	wasm_begin_undo_group(project_id: number, description: string): void;
+	wasm_end_undo_group(project_id: number): void;
+	wasm_abort_undo_group(project_id: number): boolean;
 	wasm_clear_all_undo_redo_history(): void;
+	wasm_set_undo_coalesce_window(window_ms: number): void;
+	wasm_get_undo_coalesce_window(): number;
+	wasm_mark_project_saved(project_id: number): boolean;
+	wasm_has_unsaved_changes(project_id: number): boolean;
+	wasm_get_missing_images(project_id: number): MissingImage[];
 	
 	// 缩略图相关方法
 	wasm_request_thumbnail(image_id: number): string;
@@ -85,6 +203,7 @@ export interface WasmModule {
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_get_thumbnail(image_id: number): any;
 	wasm_has_thumbnail(image_id: number): boolean;
+	wasm_prefetch_thumbnails_around(image_id: number, radius?: number): string;
 	
 	// 事件系统方法
 	wasm_init_event_system(): void;
@@ -101,10 +220,31 @@ export interface WasmModule {
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_validate_labelplus_file(content: string): any;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_validate_bf_data(data: Uint8Array): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_import_labelplus_data(project_id: number, content: string): any;
 	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_import_labelplus_merge(project_id: number, content: string, options: LabelplusMergeOptions | null): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
 	wasm_export_labelplus_data(project_id: number): any;
+	wasm_set_opening_project_labelplus_origin(project_id: number, origin: LabelplusCoordinateOrigin): boolean;
+	wasm_preview_opening_project_labelplus_origin(project_id: number, origin: LabelplusCoordinateOrigin): LabelplusOriginPreview | null;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_export_labelplus_data_range(project_id: number, image_ids: Uint32Array | number[]): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_save_project_range(project_id: number, image_ids: Uint32Array | number[]): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_export_annotated_images(project_id: number, options: AnnotateOptions | null): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_export_typesetting_data(project_id: number, format: TypesettingFormat, options: TypesettingOptions | null): any;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_import_typesetting_data(project_id: number, content: string, format: TypesettingFormat, options: TypesettingImportOptions | null): any;
 	wasm_update_project_file_path(project_id: number, file_path: string | null): boolean;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_validate_image_data(name: string, data: Uint8Array): any;
+	wasm_get_downscale_settings(): DownscaleSettings | null;
+	// eslint-disable-next-line @typescript-eslint/no-explicit-any
+	wasm_set_downscale_settings(settings: DownscaleSettings): any;
 }
 
 // 辅助函数：将 ImageFormat 转换为字符串（用于后端API）
@@ -119,16 +259,62 @@ function imageFormatToString(format: ImageFormat): string {
 	}
 }
 
+// 辅助函数：解析core返回的ImportError JSON（{ kind, name, message }）
+function parseImportError(error: unknown, fallbackName: string): ImageImportError {
+	const raw = error instanceof Error ? error.message : String(error);
+	try {
+		const parsed = JSON.parse(raw) as { kind: ImageImportError['kind']; name?: string; message: string };
+		return { kind: parsed.kind, name: parsed.name ?? fallbackName, error: parsed.message };
+	} catch {
+		return { kind: 'corrupt', name: fallbackName, error: raw };
+	}
+}
+
+// 辅助函数：解析core返回的BfFormatError JSON（{ kind, message }），读取文件失败时是普通文本
+// 辅助函数：Tauri命令返回的AcceleratorError JSON
+function parseAcceleratorError(error: unknown): AcceleratorError {
+	const raw = error instanceof Error ? error.message : String(error);
+	try {
+		return JSON.parse(raw) as AcceleratorError;
+	} catch {
+		return { kind: 'storage', message: raw };
+	}
+}
+
+function parseBfFormatError(error: unknown): BfFormatError {
+	const raw = error instanceof Error ? error.message : String(error);
+	try {
+		const parsed = JSON.parse(raw) as BfFormatError;
+		return { kind: parsed.kind, message: parsed.message };
+	} catch {
+		return { kind: 'corrupt', message: raw };
+	}
+}
+
+// 辅助函数：多边形顶点展开为 [x0, y0, x1, y1, ...]（WASM无法直接接收元组数组）
+function flattenPoints(points: unknown): Float64Array {
+	return new Float64Array((points as [number, number][]).flat());
+}
+
 // 项目接口
 export interface ProjectAPI {
 	// Opening project (temporary project) methods
 	createEmptyOpeningProject(projectName: string): Promise<number | null>;
 	createOpeningProjectFromBinary(data: Uint8Array, fileExtension: string, projectName: string): Promise<number | null>;
 	createOpeningProjectFromPath(path: string, projectName: string): Promise<number | null>;
+	// 下载URL指向的图片创建临时项目（仅Web端），失败时抛出带原因的错误（如被CORS阻止）
+	createOpeningProjectFromUrl(url: string, projectName: string): Promise<number>;
+	createOpeningProjectFromArchive(data: Uint8Array, archiveName: string, projectName: string, operationId?: string): Promise<ArchiveImportReport>;
 	getOpeningProjectInfo(projectId: number): Promise<OpeningProjectInfo | null>;
 	flushOpeningProjectImages(projectId: number): Promise<boolean>;
 	finalizeOpeningProject(projectId: number): Promise<boolean>;
+	setOpeningProjectPreserveOrder(projectId: number, preserveOrder: boolean): Promise<boolean>;
+	// 设置导入labelplus标记时的坐标原点，在finalize时生效
+	setOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<boolean>;
+	// 预览按某个坐标原点导入时第一个标记的位置，没有labelplus数据时为null
+	previewOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<LabelplusOriginPreview | null>;
 	deleteOpeningProject(projectId: number): Promise<boolean>;
+	cancelOpeningProject(openingId: number): Promise<boolean>;
 	
 	// Regular project methods
 	getProjectInfo(projectId: number): Promise<TranslationProject | null>;
@@ -136,6 +322,44 @@ export interface ProjectAPI {
 	updateProjectName(projectId: number, name: string): Promise<boolean>;
 	updateProjectLanguages(projectId: number, sourceLanguage: Language, targetLanguage: Language): Promise<boolean>;
 	deleteProject(projectId: number): Promise<boolean>;
+	setProjectFileWatch(projectId: number, enabled: boolean): Promise<boolean>;
+	// 在项目中启用/停用OCR/翻译服务，保存在bf中
+	setServiceEnabled(projectId: number, serviceId: string, enabled: boolean): Promise<boolean>;
+	// 任务失败时依次改用的服务，传空列表关闭
+	setServiceFallbacks(projectId: number, ocr: string[], translation: string[]): Promise<boolean>;
+	getProjectGlossary(projectId: number): Promise<ProjectGlossary | null>;
+	setProjectGlossary(projectId: number, glossary: ProjectGlossary): Promise<boolean>;
+	getProjectNotes(projectId: number): Promise<string | null>;
+	setProjectNotes(projectId: number, notes: string): Promise<boolean>;
+	// 新画的矩形标记自动OCR，使用项目默认OCR服务
+	getAutoOcrOnCreate(projectId: number): Promise<boolean>;
+	setAutoOcrOnCreate(projectId: number, enabled: boolean): Promise<boolean>;
+	// 译文历史是否写入bf，关闭时历史只保留在内存中
+	getKeepTranslationHistory(projectId: number): Promise<boolean>;
+	setKeepTranslationHistory(projectId: number, enabled: boolean): Promise<boolean>;
+	// 打开项目时把灰度图片重新编码为灰度PNG，默认关闭
+	getGrayscaleReencode(projectId: number): Promise<boolean>;
+	setGrayscaleReencode(projectId: number, enabled: boolean): Promise<boolean>;
+	getReadingDirection(projectId: number): Promise<ReadingDirection | null>;
+	setReadingDirection(projectId: number, direction: ReadingDirection): Promise<boolean>;
+	// 检查图片、标记和翻译缓存的引用是否一致，repair为true时修复可安全修复的问题（可撤销）
+	checkIntegrity(projectId: number, repair: boolean): Promise<IntegrityReport>;
+	duplicateProject(projectId: number, newName: string): Promise<number>;
+	// 查找重复图片（感知哈希），计算中发送image:duplicate_scan_progress
+	findDuplicateImages(projectId: number, threshold?: number): Promise<DuplicateGroup[]>;
+	// 删除重复图片（一次撤销），重复图片带标记时需force，返回删除的图片ID
+	removeDuplicateImages(projectId: number, keepStrategy: DuplicateKeepStrategy, threshold?: number, force?: boolean): Promise<number[]>;
+	saveAsTemplate(projectId: number, name: string): Promise<ProjectTemplate>;
+	createProjectFromTemplate(name: string, template: string): Promise<number>;
+	listTemplates(): Promise<TemplateList>;
+	deleteTemplate(name: string): Promise<boolean>;
+	// 命名快照：内容与上一个快照相同时返回上一个快照；还原可撤销，发送project:snapshot_restored
+	createSnapshot(projectId: number, name: string): Promise<SnapshotInfo>;
+	listSnapshots(projectId: number): Promise<SnapshotInfo[]>;
+	restoreSnapshot(projectId: number, snapshotId: string): Promise<void>;
+	deleteSnapshot(snapshotId: string): Promise<boolean>;
+	// 每个项目保留的快照数（默认20）
+	setSnapshotLimit(limit: number): Promise<void>;
 	getProjectImages(projectId: number): Promise<ImageMetadata[]>;
 	getProjectImagesMetadata(projectId: number): Promise<ImageMetadata[]>;
 }
@@ -149,15 +373,50 @@ export interface ImageAPI {
 		name?: string
 	): Promise<number | null>;
 	addImageFromPath(projectId: number, path: string): Promise<number | null>;
+	// 粘贴的PNG/JPEG截图追加为新页面，名称由core生成，失败时抛出ImageImportError
+	addImageFromClipboard(projectId: number, data: Uint8Array, suggestedName?: string): Promise<number>;
+	// 粘贴、拖入的图片数据追加为新页面，格式按文件头识别，失败时抛出ImageImportError
+	addImageFromBytes(projectId: number, data: Uint8Array, name?: string): Promise<number>;
+	// 读取系统剪贴板中的图片（PNG），没有图片或Web端时返回null
+	readClipboardImage(): Promise<Uint8Array | null>;
+	// 导入前校验，通过时返回null
+	validateImageFile(path: string): Promise<ImageImportError | null>;
+	validateImageData(name: string, data: Uint8Array): Promise<ImageImportError | null>;
+	getDownscaleSettings(): Promise<DownscaleSettings | null>;
+	setDownscaleSettings(settings: DownscaleSettings): Promise<void>;
 	getImageInfo(imageId: number): Promise<ImageMetadata | null>;
 	getImageBinaryData(imageId: number): Promise<Uint8Array | null>;
 	getImageMimeType(imageId: number): Promise<string | null>;
+	// 重新编码图片（导出用）：JPEG的quality为1-100，PNG为压缩级别0-9；参数无效时抛出错误
+	encodeImage(imageId: number, format: ImageFormat, quality?: number): Promise<Uint8Array>;
+	// 标记的预览小图（JPEG，长边不超过maxEdgePx），core按标记几何和图片内容缓存
+	getMarkerThumbnail(markerId: number, maxEdgePx: number): Promise<Uint8Array>;
+	// 图片上所有标记的预览小图（按标记顺序），整页只解码一次
+	getMarkerThumbnails(imageId: number, maxEdgePx: number): Promise<MarkerThumbnail[]>;
+	getImageMetadata(imageId: number): Promise<ImageMetadata | null>;
 	getImageFilePath(imageId: number): Promise<string | null>;
+	// 图片数据的存储方式，图片不存在时返回null
+	getImageSourceKind(imageId: number): Promise<ImageSourceKind | null>;
 	updateImageInfo(imageId: number, name?: string): Promise<boolean>;
+	// 校对状态，空白备注会被清除
+	setImageReviewed(imageId: number, reviewed: boolean, note?: string | null): Promise<boolean>;
+	// 锁定后修改其中标记的调用返回image_locked错误，可撤销
+	setImageLocked(imageId: number, locked: boolean): Promise<boolean>;
+	// 重命名图片，可撤销；名称为空或与项目中其它图片重名时报错
+	renameImage(imageId: number, newName: string): Promise<boolean>;
+	// 保存缩放/平移和选中标记，null清除；不标记项目修改
+	setImageViewState(imageId: number, viewState: ImageViewState | null): Promise<boolean>;
+	getImageViewState(imageId: number): Promise<ImageViewState | null>;
+	// 切换到图片，有保存的查看状态时发送image:view_restored并返回
+	selectImage(imageId: number): Promise<ImageViewState | null>;
 	updateImageDataFromBinary(imageId: number, format: ImageFormat, data: Uint8Array): Promise<boolean>;
 	removeImageFromProject(projectId: number, imageId: number): Promise<boolean>;
 	reorderProjectImages(projectId: number, imageIds: number[]): Promise<boolean>;
+	reorderImagesNatural(projectId: number, operationId?: string): Promise<boolean>;
 	getImageMarkers(imageId: number): Promise<Marker[]>;
+	// 文件已不存在的图片，以及重新链接到新路径（仅桌面端）
+	getMissingImages(projectId: number): Promise<MissingImage[]>;
+	relinkImage(imageId: number, path: string): Promise<RelinkResult>;
 }
 
 // 标记接口
@@ -184,6 +443,14 @@ export interface MarkerAPI {
 	updateRectangleMarkerGeometry(markerId: number, x: number, y: number, width: number, height: number): Promise<boolean>;
 	updateRectangleMarkerFull(markerId: number, x: number, y: number, width: number, height: number, translation?: string): Promise<boolean>;
 	
+	// 多边形marker
+	addPolygonMarkerToImage(imageId: number, points: [number, number][], translation?: string): Promise<number | null>;
+	updatePolygonMarkerGeometry(markerId: number, points: [number, number][]): Promise<boolean>;
+	
+	// 拖拽手势：begin与end之间的位置更新只记录一次撤销
+	beginMarkerDrag(markerId: number): Promise<boolean>;
+	endMarkerDrag(markerId: number, finalGeometry: MarkerGeometry): Promise<boolean>;
+	
 	// 通用接口
 	getMarkerInfo(markerId: number): Promise<Marker | null>;
 	getMarkersForImage(imageId: number): Promise<Marker[]>;
@@ -193,17 +460,68 @@ export interface MarkerAPI {
 		overlayText: boolean,
 		horizontal: boolean
 	): Promise<boolean>;
+	// 字号倍率和标签，传null清除
+	updateMarkerSizeHint(markerId: number, sizeHint: number | null): Promise<boolean>;
+	updateMarkerTag(markerId: number, tag: string | null): Promise<boolean>;
+	updateMarkerNote(markerId: number, note: string | null): Promise<boolean>;
+	// 最近的译文（最旧的在前），按下标回退，回退可撤销
+	getTranslationHistory(markerId: number): Promise<TranslationHistoryEntry[]>;
+	revertTranslation(markerId: number, historyIndex: number): Promise<boolean>;
 	moveMarkerOrder(markerId: number, newIndex: number): Promise<boolean>;
+	// 移到同项目的另一张图片，geometry为目标图片上的百分比坐标，position为空时追加到末尾
+	moveMarkerToImage(markerId: number, targetImageId: number, geometry: MarkerGeometry, position?: number): Promise<boolean>;
+	compactMarkerIndices(imageId: number): Promise<number>;
+	renumberMarkersByReadingOrder(imageId: number): Promise<number>;
+	// 把多个矩形标记合并为外接矩形（一次撤销），译文按阅读顺序拼接，返回新标记ID
+	mergeRectangleMarkers(markerIds: number[]): Promise<number>;
+	selectNextMarker(imageId: number, currentMarkerId: number | null, backward: boolean): Promise<number | null>;
+	compactProjectMarkerIndices(projectId: number): Promise<number>;
 	removeMarkerFromImage(imageId: number, markerId: number): Promise<boolean>;
 	clearImageMarkers(imageId: number): Promise<boolean>;
 	convertRectangleToPointMarker(markerId: number): Promise<boolean>;
 	convertPointToRectangleMarker(markerId: number): Promise<boolean>;
+	convertPolygonToRectangleMarker(markerId: number): Promise<boolean>;
+	
+	// 项目范围查找替换翻译
+	replaceInTranslations(
+		projectId: number,
+		find: string,
+		replace: string,
+		caseSensitive: boolean,
+		wholeWord: boolean,
+		operationId?: string
+	): Promise<ReplaceReport>;
+	analyzeTranslations(projectId: number, sourceSimilarity?: number): Promise<TranslationIssue[]>;
 }
 
 // 统计和清理接口
 export interface UtilityAPI {
 	getStats(): Promise<unknown>;
 	getProjectStats(projectId: number): Promise<unknown>;
+	getEventChannelStats(): Promise<EventChannelStats | null>;
+	// 只接收匹配的业务事件（精确名称或'bunny:*'），空数组恢复为全部接收；错误、通知、项目生命周期事件总是收到
+	setFrontendEventFilter(patterns: string[]): Promise<void>;
+	getFrontendEventFilterStats(): Promise<FrontendEventFilterStats | null>;
+	// Web端页面是否跨源隔离（SharedArrayBuffer可用），不隔离时core发送system:cross_origin_isolation_unavailable；桌面端总是true
+	isCrossOriginIsolated(): Promise<boolean>;
+	// 心跳，tick不再增长说明WASM worker已崩溃
+	getHealth(): Promise<CoreHealth | null>;
+	// 恢复快照：不含图片的项目数据，导入后图片为丢失状态
+	exportRecoverySnapshot(projectId: number): Promise<Uint8Array | null>;
+	importRecoverySnapshot(data: Uint8Array): Promise<number | null>;
+	// 后端内存中最近的日志，levelFilter为最低级别
+	getRecentLogs(levelFilter: LogRecordLevel | null, limit: number): Promise<LogRecord[]>;
+	setLogLevel(level: LogRecordLevel): Promise<void>;
+	getLogLevel(): Promise<LogRecordLevel>;
+	// 插件服务调用审计，默认关闭
+	setAuditEnabled(enabled: boolean): Promise<void>;
+	isAuditEnabled(): Promise<boolean>;
+	getPluginAuditLog(pluginId: string | null, limit?: number): Promise<PluginAuditEntry[]>;
+	// 取消传入了operationId的导入/重排/替换（仅桌面端有效，Web端Worker执行期间收不到取消请求）
+	cancelOperation(operationId: string): Promise<boolean>;
+	getCommandManifest(): Promise<CommandInfo[]>;
+	getTextStats(projectId: number): Promise<TextStats>;
+	setTextCountConfig(cjkCharAsWord: boolean): Promise<void>;
 	clearAllData(): Promise<void>;
 	clearProjectData(projectId: number): Promise<boolean>;
 	cleanupOrphanedImages(): Promise<number>;
@@ -214,6 +532,7 @@ export interface ThumbnailAPI {
 	requestThumbnail(imageId: number): Promise<string>;
 	getThumbnail(imageId: number): Promise<unknown>;
 	hasThumbnail(imageId: number): Promise<boolean>;
+	prefetchThumbnailsAround(imageId: number, radius?: number): Promise<string>;
 }
 
 // 撤销重做接口
@@ -221,37 +540,95 @@ export interface UndoRedoAPI {
 	undo(projectId: number): Promise<UndoRedoResult>;
 	redo(projectId: number): Promise<UndoRedoResult>;
 	clearUndoRedoHistory(projectId: number): Promise<void>;
+	// 撤销分组：begin到end之间的操作合并为一次撤销，嵌套调用并入最外层；abort撤回分组内的修改
+	beginUndoGroup(projectId: number, description: string): Promise<void>;
+	endUndoGroup(projectId: number): Promise<void>;
+	abortUndoGroup(projectId: number): Promise<boolean>;
+	markProjectSaved(projectId: number): Promise<boolean>;
+	hasUnsavedChanges(projectId: number): Promise<boolean>;
 	clearAllUndoRedoHistory(): Promise<void>;
+	// 连续修改同一标记的位置或译文、间隔小于窗口（毫秒）时合并为一次撤销，0表示不合并
+	setUndoCoalesceWindow(windowMs: number): Promise<void>;
+	getUndoCoalesceWindow(): Promise<number>;
+}
+
+// 快捷键配置接口
+export interface KeymapAPI {
+	getKeymap(): Promise<KeyBinding[]>;
+	setKeymap(bindings: Record<string, string | null>): Promise<KeyBinding[]>;
+	resetKeymap(): Promise<KeyBinding[]>;
+	loadKeymap(json: string): Promise<string[]>;
+	getMenuDefinition(): Promise<MenuSection[]>;
+	// 修改单个菜单项的快捷键，冲突或无效时返回error
+	setAccelerator(itemId: string, accelerator: string | null): Promise<{ sections?: MenuSection[]; error?: AcceleratorError }>;
 }
 
 // LabelPlus文件接口
 export interface LabelplusFileAPI {
+	validateBfFile(path: string): Promise<BfValidation>;
+	validateBfData(data: Uint8Array): Promise<BfValidation>;
 	validateLabelplusFile(content: string): Promise<{ error?: string; data?: unknown }>;
 	importLabelplusData(projectId: number, content: string): Promise<{ error?: string }>;
+	importLabelplusMerge(projectId: number, content: string, options?: LabelplusMergeOptions): Promise<LabelplusMergeReport>;
 	exportLabelplusData(projectId: number): Promise<{ content?: string; error?: string }>;
+	// 只导出指定图片（如一个章节），按项目中的顺序
+	exportLabelplusDataRange(projectId: number, imageIds: number[]): Promise<{ content?: string; error?: string }>;
+	exportTypesettingData(projectId: number, format: TypesettingFormat, options?: TypesettingOptions): Promise<string>;
+	importTypesettingData(projectId: number, content: string, format: TypesettingFormat, options?: TypesettingImportOptions): Promise<TypesettingImportReport>;
+	exportAnnotatedImages(projectId: number, options?: AnnotateOptions): Promise<AnnotatedImage[]>;
+	exportAnnotatedImagesToDir(projectId: number, dir: string, options?: AnnotateOptions): Promise<string[]>;
 	saveProject(projectId: number): Promise<{ data?: number[]; error?: string }>;
+	// 只把指定图片导出为bf，不改变项目的保存路径和保存状态
+	saveProjectRange(projectId: number, imageIds: number[]): Promise<{ data?: number[]; error?: string }>;
 	updateProjectFilePath(projectId: number, filePath: string | null): Promise<boolean>;
 }
 
 // Bunny (海兔) API 接口
 export interface BunnyAPI {
 	requestOCR(markerId: number, imageId: number, projectId: number, serviceId: string): Promise<string>;
+	requestOcrBatch(markerIds: number[], projectId: number, serviceId: string): Promise<OcrBatchItem[]>;
+	// OCR完成后自动用原文机翻，返回流水线ID；进度见bunny:pipeline_advanced/completed/failed
+	requestPipeline(markerId: number, imageId: number, projectId: number, ocrService: string, translationService: string): Promise<string>;
+	requestPipelineBatch(imageId: number, ocrService: string, translationService: string): Promise<PipelineBatchItem[]>;
+	getPipeline(pipelineId: string): Promise<BunnyPipeline | null>;
+	getProjectPipelines(projectId: number): Promise<BunnyPipeline[]>;
+	// 翻译项目中有原文没译文的标记，返回排队的任务ID；传入operationId时可用cancelOperation停止排队
+	translateAllUntranslated(projectId: number, serviceId: string, operationId?: string): Promise<string[]>;
 	requestTranslation(markerId: number, imageId: number, projectId: number, serviceId: string, text: string): Promise<string>;
-	handleOCRCompleted(taskId: string, markerId: number, text: string, model: string): Promise<void>;
-	handleTranslationCompleted(taskId: string, markerId: number, translatedText: string, service: string): Promise<void>;
+	handleOCRCompleted(taskId: string, markerId: number, text: string, model: string, regions?: OcrRegion[] | null): Promise<void>;
+	// 按OCR文本行把标记拆成多个矩形标记，不传regions时使用最近一次OCR的结果
+	splitMarkerByOcrRegions(markerId: number, regions?: OcrRegion[] | null): Promise<number[]>;
+	handleTranslationCompleted(taskId: string, markerId: number, translatedText: string, service: string, warnings?: TranslationWarning[], metadata?: TranslationMetadata | null): Promise<void>;
 	handleTaskFailed(taskId: string, error: string): Promise<void>;
 	cancelBunnyTask(taskId: string): Promise<boolean>;
 	clearAllBunnyTasks(): Promise<boolean>;
 	getBunnyTaskStatus(taskId: string): Promise<unknown | null>;
 	getBunnyQueuedTasks(projectId?: number): Promise<unknown[]>;
 	getOCRResult(markerId: number): Promise<string | null>;
-	getTranslationResult(markerId: number): Promise<string | null>;
-	getAvailableOCRServices(): Promise<OCRServiceInfo[]>;
-	getAvailableTranslationServices(): Promise<TranslationServiceInfo[]>;
+	getTranslationResult(markerId: number): Promise<TranslationResult | null>;
+	// 把机翻写入译文（可撤销），译文在机翻之后被修改过时报错
+	acceptMachineTranslation(markerId: number): Promise<boolean>;
+	// 接受图片上所有标记的机翻，整体一次撤销
+	acceptAllForImage(imageId: number, onlyEmpty: boolean): Promise<AcceptMachineTranslationReport>;
+	rejectMachineTranslation(markerId: number): Promise<boolean>;
+	// 传入projectId时只返回该项目启用的服务
+	getAvailableOCRServices(projectId?: number): Promise<OCRServiceInfo[]>;
+	getAvailableDetectionServices(projectId?: number): Promise<DetectionServiceInfo[]>;
+	// 请求检测插件找出图片中的文本框，结果以bunny:detection_completed发出
+	requestDetection(imageId: number, serviceId: string): Promise<string>;
+	handleDetectionCompleted(taskId: string, model: string, regions: DetectedRegion[]): Promise<void>;
+	// 把检测区域生成矩形标记，整体一次撤销
+	createMarkersFromDetection(imageId: number, regions: DetectedRegion[]): Promise<number[]>;
+	getAvailableTranslationServices(projectId?: number): Promise<TranslationServiceInfo[]>;
 	getBunnyCache(markerId: number): Promise<BunnyCacheData | null>;
 	updateOriginalText(markerId: number, text: string, model: string): Promise<void>;
 	updateMachineTranslation(markerId: number, text: string, service: string): Promise<void>;
 	clearBunnyCache(markerId: number): Promise<void>;
+	getTaskHistory(projectId: number, limit?: number): Promise<TaskHistoryEntry[]>;
+	getTaskMetrics(projectId: number): Promise<ServiceTaskMetrics[]>;
+	// 图片上排队/进行中/完成/失败的任务数
+	getImageTaskProgress(imageId: number): Promise<ImageTaskProgress>;
+	clearTaskHistory(projectId: number): Promise<void>;
 }
 
 // Service info types for plugin-provided services
@@ -263,6 +640,13 @@ export interface OCRServiceInfo {
 	supported_image_formats: string[];
 }
 
+export interface DetectionServiceInfo {
+	id: string;
+	name: string;
+	plugin_id: string;
+	supported_image_formats: string[];
+}
+
 export interface TranslationServiceInfo {
 	id: string;
 	name: string;
@@ -278,10 +662,12 @@ export interface BunnyCacheData {
 	machine_translation?: string;
 	last_ocr_model?: string;
 	last_translation_service?: string;
+	translation_warnings?: TranslationWarning[];
+	translation_metadata?: TranslationMetadata | null;
 }
 
 // 综合API接口
-export interface CoreAPI extends ProjectAPI, ImageAPI, MarkerAPI, UtilityAPI, UndoRedoAPI, LabelplusFileAPI, BunnyAPI {}
+export interface CoreAPI extends ProjectAPI, ImageAPI, MarkerAPI, UtilityAPI, UndoRedoAPI, KeymapAPI, LabelplusFileAPI, BunnyAPI {}
 
 // 定义后端调用的参数类型
 interface BackendCallParams {
@@ -345,6 +731,9 @@ abstract class BaseCoreAPI implements CoreAPI {
 				height,
 				format,
 				size: rawImage.size,
+				reviewed: rawImage.reviewed ?? false,
+				review_note: rawImage.review_note ?? null,
+				locked: rawImage.locked ?? false,
 				markers: [] // 标记会单独加载
 			};
 		});
@@ -374,6 +763,26 @@ abstract class BaseCoreAPI implements CoreAPI {
 		});
 	}
 
+	async createOpeningProjectFromUrl(url: string, projectName: string): Promise<number> {
+		return this.callBackend<number>('create_opening_project_from_url', {
+			url: url.trim(),
+			projectName: projectName.trim()
+		});
+	}
+
+	async createOpeningProjectFromArchive(data: Uint8Array, archiveName: string, projectName: string, operationId?: string): Promise<ArchiveImportReport> {
+		const result = await this.callBackend<ArchiveImportReport | { error: string }>('create_opening_project_from_archive', {
+			data: Array.from(data),
+			archiveName,
+			projectName: projectName.trim(),
+			operationId: operationId ?? null
+		});
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to open archive');
+		}
+		return result;
+	}
+
 	async getOpeningProjectInfo(projectId: number): Promise<OpeningProjectInfo | null> {
 		return this.callBackend<OpeningProjectInfo | null>('get_opening_project_info', { projectId });
 	}
@@ -386,10 +795,26 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<boolean>('finalize_opening_project', { projectId });
 	}
 
+	async setOpeningProjectPreserveOrder(projectId: number, preserveOrder: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_opening_project_preserve_order', { projectId, preserveOrder });
+	}
+
+	async setOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<boolean> {
+		return this.callBackend<boolean>('set_opening_project_labelplus_origin', { projectId, origin });
+	}
+
+	async previewOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<LabelplusOriginPreview | null> {
+		return this.callBackend<LabelplusOriginPreview | null>('preview_opening_project_labelplus_origin', { projectId, origin });
+	}
+
 	async deleteOpeningProject(projectId: number): Promise<boolean> {
 		return this.callBackend<boolean>('delete_opening_project', { projectId });
 	}
 
+	async cancelOpeningProject(openingId: number): Promise<boolean> {
+		return this.callBackend<boolean>('cancel_opening_project', { openingId });
+	}
+
 	// 正式项目相关
 
 	async getProjectInfo(projectId: number): Promise<TranslationProject | null> {
@@ -408,6 +833,146 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<boolean>('update_project_languages', { projectId, sourceLanguage, targetLanguage });
 	}
 
+	async setProjectFileWatch(projectId: number, enabled: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_project_file_watch', { projectId, enabled });
+	}
+
+	async setServiceEnabled(projectId: number, serviceId: string, enabled: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_service_enabled', { projectId, serviceId, enabled });
+	}
+
+	async getProjectGlossary(projectId: number): Promise<ProjectGlossary | null> {
+		return this.callBackend<ProjectGlossary | null>('get_project_glossary', { projectId });
+	}
+
+	async setServiceFallbacks(projectId: number, ocr: string[], translation: string[]): Promise<boolean> {
+		return this.callBackend<boolean>('set_service_fallbacks', { projectId, ocr, translation });
+	}
+
+	async setProjectGlossary(projectId: number, glossary: ProjectGlossary): Promise<boolean> {
+		return this.callBackend<boolean>('set_project_glossary', { projectId, glossary });
+	}
+
+	async getProjectNotes(projectId: number): Promise<string | null> {
+		const notes = await this.callBackend<string | null | undefined>('get_project_notes', { projectId });
+		return notes ?? null;
+	}
+
+	async setProjectNotes(projectId: number, notes: string): Promise<boolean> {
+		return this.callBackend<boolean>('set_project_notes', { projectId, notes });
+	}
+
+	async getAutoOcrOnCreate(projectId: number): Promise<boolean> {
+		const enabled = await this.callBackend<boolean | null | undefined>('get_auto_ocr_on_create', { projectId });
+		return enabled ?? false;
+	}
+
+	async setAutoOcrOnCreate(projectId: number, enabled: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_auto_ocr_on_create', { projectId, enabled });
+	}
+
+	async getKeepTranslationHistory(projectId: number): Promise<boolean> {
+		const enabled = await this.callBackend<boolean | null | undefined>('get_keep_translation_history', { projectId });
+		return enabled ?? false;
+	}
+
+	async setKeepTranslationHistory(projectId: number, enabled: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_keep_translation_history', { projectId, enabled });
+	}
+
+	async getGrayscaleReencode(projectId: number): Promise<boolean> {
+		const enabled = await this.callBackend<boolean | null | undefined>('get_grayscale_reencode', { projectId });
+		return enabled ?? false;
+	}
+
+	async setGrayscaleReencode(projectId: number, enabled: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_grayscale_reencode', { projectId, enabled });
+	}
+
+	async getReadingDirection(projectId: number): Promise<ReadingDirection | null> {
+		return this.callBackend<ReadingDirection | null>('get_reading_direction', { projectId });
+	}
+
+	async setReadingDirection(projectId: number, direction: ReadingDirection): Promise<boolean> {
+		return this.callBackend<boolean>('set_reading_direction', { projectId, direction });
+	}
+
+	async checkIntegrity(projectId: number, repair: boolean): Promise<IntegrityReport> {
+		const result = await this.callBackend<IntegrityReport | { error: string } | null>('check_integrity', { projectId, repair });
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to check project integrity');
+		}
+		return result;
+	}
+
+	async duplicateProject(projectId: number, newName: string): Promise<number> {
+		const result = await this.callBackend<number | { error: string }>('duplicate_project', { projectId, newName });
+		if (typeof result !== 'number') {
+			throw new Error(result?.error ?? 'Failed to duplicate project');
+		}
+		return result;
+	}
+
+	async findDuplicateImages(projectId: number, threshold?: number): Promise<DuplicateGroup[]> {
+		return this.callBackend<DuplicateGroup[]>('find_duplicate_images', { projectId, threshold });
+	}
+
+	async removeDuplicateImages(projectId: number, keepStrategy: DuplicateKeepStrategy, threshold?: number, force = false): Promise<number[]> {
+		const removed = await this.callBackend<number[] | Uint32Array>('remove_duplicate_images', { projectId, keepStrategy, threshold, force });
+		return Array.from(removed);
+	}
+
+	async saveAsTemplate(projectId: number, name: string): Promise<ProjectTemplate> {
+		const result = await this.callBackend<ProjectTemplate | { error: string }>('save_as_template', { projectId, name });
+		if (result && 'error' in result) {
+			throw new Error(result.error);
+		}
+		return result as ProjectTemplate;
+	}
+
+	async createProjectFromTemplate(name: string, template: string): Promise<number> {
+		const result = await this.callBackend<number | { error: string }>('create_project_from_template', { name, template });
+		if (result && typeof result === 'object' && 'error' in result) {
+			throw new Error(result.error);
+		}
+		return result as number;
+	}
+
+	async listTemplates(): Promise<TemplateList> {
+		return (await this.callBackend<TemplateList | null>('list_templates')) ?? { templates: [], warnings: [] };
+	}
+
+	async deleteTemplate(name: string): Promise<boolean> {
+		return this.callBackend<boolean>('delete_template', { name });
+	}
+
+	async createSnapshot(projectId: number, name: string): Promise<SnapshotInfo> {
+		const result = await this.callBackend<SnapshotInfo | { error: string }>('create_snapshot', { projectId, name });
+		if (result && 'error' in result) {
+			throw new Error(result.error);
+		}
+		return result as SnapshotInfo;
+	}
+
+	async listSnapshots(projectId: number): Promise<SnapshotInfo[]> {
+		return (await this.callBackend<SnapshotInfo[] | null>('list_snapshots', { projectId })) ?? [];
+	}
+
+	async restoreSnapshot(projectId: number, snapshotId: string): Promise<void> {
+		const result = await this.callBackend<unknown>('restore_snapshot', { projectId, snapshotId });
+		if (result && typeof result === 'object' && 'error' in result) {
+			throw new Error((result as { error: string }).error);
+		}
+	}
+
+	async deleteSnapshot(snapshotId: string): Promise<boolean> {
+		return this.callBackend<boolean>('delete_snapshot', { snapshotId });
+	}
+
+	async setSnapshotLimit(limit: number): Promise<void> {
+		await this.callBackend<unknown>('set_snapshot_limit', { limit });
+	}
+
 	async deleteProject(projectId: number): Promise<boolean> {
 		return this.callBackend<boolean>('delete_project', { projectId });
 	}
@@ -437,6 +1002,35 @@ abstract class BaseCoreAPI implements CoreAPI {
 		});
 	}
 
+	async addImageFromClipboard(projectId: number, data: Uint8Array, suggestedName?: string): Promise<number> {
+		try {
+			return await this.callBackend<number>('add_image_from_clipboard_bytes', {
+				projectId,
+				bytes: Array.from(data),
+				suggestedName
+			});
+		} catch (error) {
+			throw parseImportError(error, suggestedName ?? 'clipboard');
+		}
+	}
+
+	async addImageFromBytes(projectId: number, data: Uint8Array, name?: string): Promise<number> {
+		try {
+			return await this.callBackend<number>('add_image_from_bytes', {
+				projectId,
+				bytes: Array.from(data),
+				name
+			});
+		} catch (error) {
+			throw parseImportError(error, name ?? 'image');
+		}
+	}
+
+	// Web端只能从paste事件拿到剪贴板数据
+	async readClipboardImage(): Promise<Uint8Array | null> {
+		return null;
+	}
+
 	async addImageFromPath(projectId: number, path: string): Promise<number | null> {
 		// 默认实现，子类可以覆盖
 		return this.callBackend<number | null>('add_image_from_path_to_project', {
@@ -445,6 +1039,40 @@ abstract class BaseCoreAPI implements CoreAPI {
 		});
 	}
 
+	async validateImageFile(path: string): Promise<ImageImportError | null> {
+		try {
+			await this.callBackend<unknown>('validate_image_file', { path });
+			return null;
+		} catch (error) {
+			return parseImportError(error, path);
+		}
+	}
+
+	async validateImageData(name: string, data: Uint8Array): Promise<ImageImportError | null> {
+		try {
+			// Tauri的IPC需要普通数组
+			const result = await this.callBackend<unknown>('validate_image_data', { name, data: isTauri() ? Array.from(data) : data });
+			// WASM返回 { error, kind, name }，Tauri通过异常返回
+			if (result && typeof result === 'object' && 'error' in result) {
+				return result as ImageImportError;
+			}
+			return null;
+		} catch (error) {
+			return parseImportError(error, name);
+		}
+	}
+
+	async getDownscaleSettings(): Promise<DownscaleSettings | null> {
+		return this.callBackend<DownscaleSettings | null>('get_downscale_settings', {});
+	}
+
+	async setDownscaleSettings(settings: DownscaleSettings): Promise<void> {
+		const result = await this.callBackend<{ error?: string } | null>('set_downscale_settings', { settings });
+		if (result && typeof result === 'object' && result.error) {
+			throw new Error(result.error);
+		}
+	}
+
 	async getImageInfo(imageId: number): Promise<ImageMetadata | null> {
 		return this.callBackend<ImageMetadata | null>('get_image_info', { imageId });
 	}
@@ -459,6 +1087,25 @@ abstract class BaseCoreAPI implements CoreAPI {
 		}
 	}
 
+	async encodeImage(imageId: number, format: ImageFormat, quality?: number): Promise<Uint8Array> {
+		const data = await this.callBackend<number[] | Uint8Array>('encode_image', {
+			imageId,
+			formatStr: imageFormatToString(format),
+			quality: quality ?? null
+		});
+		return new Uint8Array(data);
+	}
+
+	async getMarkerThumbnail(markerId: number, maxEdgePx: number): Promise<Uint8Array> {
+		const data = await this.callBackend<number[] | Uint8Array>('get_marker_thumbnail', { markerId, maxEdgePx });
+		return new Uint8Array(data);
+	}
+
+	async getMarkerThumbnails(imageId: number, maxEdgePx: number): Promise<MarkerThumbnail[]> {
+		const thumbnails = await this.callBackend<{ marker_id: number; data: number[] | Uint8Array }[]>('get_marker_thumbnails', { imageId, maxEdgePx });
+		return thumbnails.map((thumbnail) => ({ marker_id: thumbnail.marker_id, data: new Uint8Array(thumbnail.data) }));
+	}
+
 	async getImageMimeType(imageId: number): Promise<string | null> {
 		try {
 			return await this.callBackend<string | null>('get_image_mime_type', { imageId });
@@ -468,6 +1115,15 @@ abstract class BaseCoreAPI implements CoreAPI {
 		}
 	}
 
+	async getImageMetadata(imageId: number): Promise<ImageMetadata | null> {
+		try {
+			return await this.callBackend<ImageMetadata | null>('get_image_metadata', { imageId });
+		} catch (error) {
+			console.error('Failed to get image metadata:', error);
+			return null;
+		}
+	}
+
 	async getImageFilePath(imageId: number): Promise<string | null> {
 		// 默认实现，子类可以覆盖
 		try {
@@ -478,10 +1134,51 @@ abstract class BaseCoreAPI implements CoreAPI {
 		}
 	}
 
+	async getImageSourceKind(imageId: number): Promise<ImageSourceKind | null> {
+		try {
+			return await this.callBackend<ImageSourceKind | null>('get_image_source_kind', { imageId });
+		} catch (error) {
+			console.error('Failed to get image source kind:', error);
+			return null;
+		}
+	}
+
+	async getMissingImages(projectId: number): Promise<MissingImage[]> {
+		return (await this.callBackend<MissingImage[] | null>('get_missing_images', { projectId })) ?? [];
+	}
+
+	async relinkImage(imageId: number, path: string): Promise<RelinkResult> {
+		return this.callBackend<RelinkResult>('relink_image', { imageId, path });
+	}
+
 	async updateImageInfo(imageId: number, name?: string): Promise<boolean> {
 		return this.callBackend<boolean>('update_image_info', { imageId, name });
 	}
 
+	async setImageReviewed(imageId: number, reviewed: boolean, note?: string | null): Promise<boolean> {
+		return this.callBackend<boolean>('set_image_reviewed', { imageId, reviewed, note: note ?? null });
+	}
+
+	async setImageLocked(imageId: number, locked: boolean): Promise<boolean> {
+		return this.callBackend<boolean>('set_image_locked', { imageId, locked });
+	}
+
+	async renameImage(imageId: number, newName: string): Promise<boolean> {
+		return this.callBackend<boolean>('rename_image', { imageId, newName });
+	}
+
+	async setImageViewState(imageId: number, viewState: ImageViewState | null): Promise<boolean> {
+		return this.callBackend<boolean>('set_image_view_state', { imageId, viewState });
+	}
+
+	async getImageViewState(imageId: number): Promise<ImageViewState | null> {
+		return this.callBackend<ImageViewState | null>('get_image_view_state', { imageId });
+	}
+
+	async selectImage(imageId: number): Promise<ImageViewState | null> {
+		return this.callBackend<ImageViewState | null>('select_image', { imageId });
+	}
+
 	async updateImageDataFromBinary(
 		imageId: number,
 		format: ImageFormat,
@@ -502,6 +1199,10 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<boolean>('reorder_project_images', { projectId, imageIds });
 	}
 
+	async reorderImagesNatural(projectId: number, operationId?: string): Promise<boolean> {
+		return this.callBackend<boolean>('reorder_images_natural', { projectId, operationId: operationId ?? null });
+	}
+
 	async getImageMarkers(imageId: number): Promise<Marker[]> {
 		return this.callBackend<Marker[]>('get_image_markers', { imageId });
 	}
@@ -511,9 +1212,10 @@ abstract class BaseCoreAPI implements CoreAPI {
 		imageId: number,
 		x: number,
 		y: number,
-		translation?: string
+		translation?: string,
+		mode?: CoordinateMode
 	): Promise<number | null> {
-		return this.callBackend<number | null>('add_point_marker_to_image', { imageId, x, y, translation });
+		return this.callBackend<number | null>('add_point_marker_to_image', { imageId, x, y, translation, mode: mode ?? null });
 	}
 
 	async getMarkerInfo(markerId: number): Promise<Marker | null> {
@@ -524,8 +1226,16 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<Marker[]>('get_markers_for_image', { imageId });
 	}
 
-	async updatePointMarkerPosition(markerId: number, x: number, y: number): Promise<boolean> {
-		return this.callBackend<boolean>('update_point_marker_position', { markerId, x, y });
+	async updatePointMarkerPosition(markerId: number, x: number, y: number, mode?: CoordinateMode): Promise<boolean> {
+		return this.callBackend<boolean>('update_point_marker_position', { markerId, x, y, mode: mode ?? null });
+	}
+
+	async beginMarkerDrag(markerId: number): Promise<boolean> {
+		return this.callBackend<boolean>('begin_marker_drag', { markerId });
+	}
+
+	async endMarkerDrag(markerId: number, finalGeometry: MarkerGeometry): Promise<boolean> {
+		return this.callBackend<boolean>('end_marker_drag', { markerId, finalGeometry });
 	}
 
 	async updateMarkerTranslation(markerId: number, translation: string): Promise<boolean> {
@@ -544,17 +1254,72 @@ abstract class BaseCoreAPI implements CoreAPI {
 		});
 	}
 
+	async updateMarkerSizeHint(markerId: number, sizeHint: number | null): Promise<boolean> {
+		return this.callBackend<boolean>('update_marker_size_hint', { markerId, sizeHint });
+	}
+
+	async updateMarkerTag(markerId: number, tag: string | null): Promise<boolean> {
+		return this.callBackend<boolean>('update_marker_tag', { markerId, tag });
+	}
+
+	async updateMarkerNote(markerId: number, note: string | null): Promise<boolean> {
+		return this.callBackend<boolean>('update_marker_note', { markerId, note });
+	}
+
+	async getTranslationHistory(markerId: number): Promise<TranslationHistoryEntry[]> {
+		return this.callBackend<TranslationHistoryEntry[]>('get_translation_history', { markerId });
+	}
+
+	async revertTranslation(markerId: number, historyIndex: number): Promise<boolean> {
+		return this.callBackend<boolean>('revert_translation', { markerId, historyIndex });
+	}
+
 	async moveMarkerOrder(markerId: number, newIndex: number): Promise<boolean> {
 		return this.callBackend<boolean>('move_marker_order', { markerId, newIndex });
 	}
 
+	async moveMarkerToImage(markerId: number, targetImageId: number, geometry: MarkerGeometry, position?: number): Promise<boolean> {
+		return this.callBackend<boolean>('move_marker_to_image', {
+			markerId,
+			targetImageId,
+			geometry,
+			position: position ?? null
+		});
+	}
+
+	async compactMarkerIndices(imageId: number): Promise<number> {
+		return this.callBackend<number>('compact_marker_indices', { imageId });
+	}
+
+	async renumberMarkersByReadingOrder(imageId: number): Promise<number> {
+		return this.callBackend<number>('renumber_markers_by_reading_order', { imageId });
+	}
+
+	async mergeRectangleMarkers(markerIds: number[]): Promise<number> {
+		return this.callBackend<number>('merge_rectangle_markers', { markerIds });
+	}
+
+	async selectNextMarker(imageId: number, currentMarkerId: number | null, backward: boolean): Promise<number | null> {
+		const result = await this.callBackend<number | null | undefined>('select_next_marker', {
+			imageId,
+			currentMarkerId: currentMarkerId ?? undefined,
+			backward
+		});
+		return result ?? null;
+	}
+
+	async compactProjectMarkerIndices(projectId: number): Promise<number> {
+		return this.callBackend<number>('compact_project_marker_indices', { projectId });
+	}
+
 	async updatePointMarkerFull(
 		markerId: number,
 		x: number,
 		y: number,
-		translation?: string
+		translation?: string,
+		mode?: CoordinateMode
 	): Promise<boolean> {
-		return this.callBackend<boolean>('update_point_marker_full', { markerId, x, y, translation });
+		return this.callBackend<boolean>('update_point_marker_full', { markerId, x, y, translation, mode: mode ?? null });
 	}
 
 	// 矩形型marker
@@ -564,9 +1329,10 @@ abstract class BaseCoreAPI implements CoreAPI {
 		y: number,
 		width: number,
 		height: number,
-		translation?: string
+		translation?: string,
+		mode?: CoordinateMode
 	): Promise<number | null> {
-		return this.callBackend<number | null>('add_rectangle_marker_to_image', { imageId, x, y, width, height, translation });
+		return this.callBackend<number | null>('add_rectangle_marker_to_image', { imageId, x, y, width, height, translation, mode: mode ?? null });
 	}
 
 	async updateRectangleMarkerGeometry(
@@ -574,9 +1340,10 @@ abstract class BaseCoreAPI implements CoreAPI {
 		x: number,
 		y: number,
 		width: number,
-		height: number
+		height: number,
+		mode?: CoordinateMode
 	): Promise<boolean> {
-		return this.callBackend<boolean>('update_rectangle_marker_geometry', { markerId, x, y, width, height });
+		return this.callBackend<boolean>('update_rectangle_marker_geometry', { markerId, x, y, width, height, mode: mode ?? null });
 	}
 
 	async updateRectangleMarkerFull(
@@ -585,9 +1352,19 @@ abstract class BaseCoreAPI implements CoreAPI {
 		y: number,
 		width: number,
 		height: number,
-		translation?: string
+		translation?: string,
+		mode?: CoordinateMode
 	): Promise<boolean> {
-		return this.callBackend<boolean>('update_rectangle_marker_full', { markerId, x, y, width, height, translation });
+		return this.callBackend<boolean>('update_rectangle_marker_full', { markerId, x, y, width, height, translation, mode: mode ?? null });
+	}
+
+	// 多边形marker
+	async addPolygonMarkerToImage(imageId: number, points: [number, number][], translation?: string, mode?: CoordinateMode): Promise<number | null> {
+		return this.callBackend<number | null>('add_polygon_marker_to_image', { imageId, points, translation, mode: mode ?? null });
+	}
+
+	async updatePolygonMarkerGeometry(markerId: number, points: [number, number][], mode?: CoordinateMode): Promise<boolean> {
+		return this.callBackend<boolean>('update_polygon_marker_geometry', { markerId, points, mode: mode ?? null });
 	}
 
 	async removeMarkerFromImage(imageId: number, markerId: number): Promise<boolean> {
@@ -606,6 +1383,43 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<boolean>('convert_point_to_rectangle_marker', { markerId });
 	}
 
+	async convertPolygonToRectangleMarker(markerId: number): Promise<boolean> {
+		return this.callBackend<boolean>('convert_polygon_to_rectangle_marker', { markerId });
+	}
+
+	async replaceInTranslations(
+		projectId: number,
+		find: string,
+		replace: string,
+		caseSensitive: boolean,
+		wholeWord: boolean,
+		operationId?: string
+	): Promise<ReplaceReport> {
+		const result = await this.callBackend<ReplaceReport | { error: string }>('replace_in_translations', {
+			projectId,
+			find,
+			replace,
+			caseSensitive,
+			wholeWord,
+			operationId: operationId ?? null
+		});
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to replace in translations');
+		}
+		return result;
+	}
+
+	async analyzeTranslations(projectId: number, sourceSimilarity?: number): Promise<TranslationIssue[]> {
+		const result = await this.callBackend<TranslationIssue[] | { error: string }>('analyze_translations', {
+			projectId,
+			sourceSimilarity
+		});
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to analyze translations');
+		}
+		return result;
+	}
+
 	// 统计和清理相关
 	async getStats(): Promise<unknown> {
 		return this.callBackend<unknown>('get_stats');
@@ -615,6 +1429,88 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<unknown>('get_project_stats', { projectId });
 	}
 
+	async getEventChannelStats(): Promise<EventChannelStats | null> {
+		return this.callBackend<EventChannelStats | null>('get_event_channel_stats');
+	}
+
+	async setFrontendEventFilter(patterns: string[]): Promise<void> {
+		await this.callBackend('set_frontend_event_filter', { patterns });
+	}
+
+	async getFrontendEventFilterStats(): Promise<FrontendEventFilterStats | null> {
+		return this.callBackend<FrontendEventFilterStats | null>('get_frontend_event_filter_stats');
+	}
+
+	async isCrossOriginIsolated(): Promise<boolean> {
+		if (isTauri()) {
+			return true;
+		}
+		return this.callBackend<boolean>('is_cross_origin_isolated');
+	}
+
+	async getHealth(): Promise<CoreHealth | null> {
+		return this.callBackend<CoreHealth | null>('get_health');
+	}
+
+	async exportRecoverySnapshot(projectId: number): Promise<Uint8Array | null> {
+		const result = await this.callBackend<Uint8Array | number[] | null>('export_recovery_snapshot', { projectId });
+		// WASM返回Uint8Array，Tauri返回数组
+		if (Array.isArray(result)) return new Uint8Array(result);
+		return result ?? null;
+	}
+
+	async importRecoverySnapshot(data: Uint8Array): Promise<number | null> {
+		return this.callBackend<number | null>('import_recovery_snapshot', { data: isTauri() ? Array.from(data) : data });
+	}
+
+	async getRecentLogs(levelFilter: LogRecordLevel | null, limit: number): Promise<LogRecord[]> {
+		return (await this.callBackend<LogRecord[] | null>('get_recent_logs', { levelFilter, limit })) ?? [];
+	}
+
+	async setLogLevel(level: LogRecordLevel): Promise<void> {
+		await this.callBackend('set_log_level', { level });
+	}
+
+	async getLogLevel(): Promise<LogRecordLevel> {
+		return (await this.callBackend<LogRecordLevel | null>('get_log_level')) ?? 'Debug';
+	}
+
+	async setAuditEnabled(enabled: boolean): Promise<void> {
+		await this.callBackend('set_audit_enabled', { enabled });
+	}
+
+	async isAuditEnabled(): Promise<boolean> {
+		return (await this.callBackend<boolean | null>('is_audit_enabled')) ?? false;
+	}
+
+	async getPluginAuditLog(pluginId: string | null, limit?: number): Promise<PluginAuditEntry[]> {
+		return (await this.callBackend<PluginAuditEntry[] | null>('get_plugin_audit_log', { pluginId, limit: limit ?? null })) ?? [];
+	}
+
+	async cancelOperation(operationId: string): Promise<boolean> {
+		return this.callBackend<boolean>('cancel_operation', { operationId });
+	}
+
+	// 命令清单只有桌面端有（Web端直接调用WASM导出函数）
+	async getCommandManifest(): Promise<CommandInfo[]> {
+		if (!isTauri()) {
+			return [];
+		}
+		return this.callBackend<CommandInfo[]>('get_command_manifest');
+	}
+
+	async getTextStats(projectId: number): Promise<TextStats> {
+		const result = await this.callBackend<TextStats | { error: string }>('get_text_stats', { projectId });
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to get text stats');
+		}
+		return result;
+	}
+
+	async setTextCountConfig(cjkCharAsWord: boolean): Promise<void> {
+		await this.callBackend<void>('set_text_count_config', { cjkCharAsWord });
+	}
+
 	async clearAllData(): Promise<void> {
 		await this.callBackend<void>('clear_all_data');
 	}
@@ -641,11 +1537,104 @@ abstract class BaseCoreAPI implements CoreAPI {
 	async clearUndoRedoHistory(projectId: number): Promise<void> {
 		await this.callBackend<void>('clear_undo_redo_history', { projectId });
 	}
+
+	async beginUndoGroup(projectId: number, description: string): Promise<void> {
+		await this.callBackend<void>('begin_undo_group', { projectId, description });
+	}
+
+	async endUndoGroup(projectId: number): Promise<void> {
+		await this.callBackend<void>('end_undo_group', { projectId });
+	}
+
+	async abortUndoGroup(projectId: number): Promise<boolean> {
+		return this.callBackend<boolean>('abort_undo_group', { projectId });
+	}
 	
 	async clearAllUndoRedoHistory(): Promise<void> {
 		await this.callBackend<void>('clear_all_undo_redo_history');
 	}
+
+	async setUndoCoalesceWindow(windowMs: number): Promise<void> {
+		await this.callBackend<void>('set_undo_coalesce_window', { windowMs });
+	}
+
+	async getUndoCoalesceWindow(): Promise<number> {
+		return this.callBackend<number>('get_undo_coalesce_window');
+	}
+
+	async markProjectSaved(projectId: number): Promise<boolean> {
+		return this.callBackend<boolean>('mark_project_saved', { projectId });
+	}
+
+	async hasUnsavedChanges(projectId: number): Promise<boolean> {
+		return this.callBackend<boolean>('has_unsaved_changes', { projectId });
+	}
+
+	// 快捷键配置相关
+	async getKeymap(): Promise<KeyBinding[]> {
+		return (await this.callBackend<KeyBinding[] | null>('get_keymap')) ?? [];
+	}
+
+	async setKeymap(bindings: Record<string, string | null>): Promise<KeyBinding[]> {
+		const result = await this.callBackend<KeyBinding[] | { error: string }>('set_keymap', { bindings });
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to set keymap');
+		}
+		return result;
+	}
+
+	async resetKeymap(): Promise<KeyBinding[]> {
+		return (await this.callBackend<KeyBinding[] | null>('reset_keymap')) ?? [];
+	}
+
+	async loadKeymap(json: string): Promise<string[]> {
+		const result = await this.callBackend<string[] | { error: string }>('load_keymap', { json });
+		if (!result || 'error' in result) {
+			throw new Error(result?.error ?? 'Failed to load keymap');
+		}
+		return result;
+	}
 	
+	// 菜单定义
+	async getMenuDefinition(): Promise<MenuSection[]> {
+		return (await this.callBackend<MenuSection[] | null>('get_menu_definition')) ?? [];
+	}
+
+	async setAccelerator(itemId: string, accelerator: string | null): Promise<{ sections?: MenuSection[]; error?: AcceleratorError }> {
+		try {
+			// WASM返回 { error, kind }，Tauri通过异常返回
+			const result = await this.callBackend<MenuSection[] | { error: string; kind: AcceleratorError['kind']; conflicting_item?: string }>('set_accelerator', { itemId, accelerator });
+			if (result && 'error' in result) {
+				return { error: { kind: result.kind, message: result.error, conflicting_item: result.conflicting_item } };
+			}
+			return { sections: result ?? [] };
+		} catch (error) {
+			return { error: parseAcceleratorError(error) };
+		}
+	}
+
+	// bf文件预检，打开前确认内容
+	async validateBfFile(path: string): Promise<BfValidation> {
+		try {
+			return { summary: await this.callBackend<BfSummary>('validate_bf_file', { path }) };
+		} catch (error) {
+			return { error: parseBfFormatError(error) };
+		}
+	}
+
+	async validateBfData(data: Uint8Array): Promise<BfValidation> {
+		try {
+			// WASM返回 { error, kind }，Tauri通过异常返回
+			const result = await this.callBackend<BfSummary | { error: string; kind: BfFormatError['kind'] }>('validate_bf_data', { data: isTauri() ? Array.from(data) : data });
+			if (result && 'error' in result) {
+				return { error: { kind: result.kind, message: result.error } };
+			}
+			return { summary: result };
+		} catch (error) {
+			return { error: parseBfFormatError(error) };
+		}
+	}
+
 	// LabelPlus文件相关
 	async validateLabelplusFile(content: string): Promise<{ error?: string; data?: unknown }> {
 		const result = await this.callBackend<unknown>('validate_labelplus_file', { content });
@@ -666,8 +1655,24 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return { error: res?.error || 'Import failed' };
 	}
 
+	// 合并导入到已有标记之后，用于按章节拆分的多个翻译文件
+	async importLabelplusMerge(projectId: number, content: string, options?: LabelplusMergeOptions): Promise<LabelplusMergeReport> {
+		const result = await this.callBackend<LabelplusMergeReport | { error: string }>('import_labelplus_merge', { projectId, content, options: options ?? null });
+		if (result && 'error' in result) {
+			throw new Error(result.error);
+		}
+		return result as LabelplusMergeReport;
+	}
+
 	async exportLabelplusData(projectId: number): Promise<{ content?: string; error?: string }> {
-		const result = await this.callBackend<unknown>('export_labelplus_data', { projectId });
+		return this.toLabelplusResult(await this.callBackend<unknown>('export_labelplus_data', { projectId }));
+	}
+
+	async exportLabelplusDataRange(projectId: number, imageIds: number[]): Promise<{ content?: string; error?: string }> {
+		return this.toLabelplusResult(await this.callBackend<unknown>('export_labelplus_data_range', { projectId, imageIds }));
+	}
+
+	private toLabelplusResult(result: unknown): { content?: string; error?: string } {
 		if (typeof result === 'string' && !result.startsWith('{')) {
 			// If result is a plain string, it's the content
 			return { content: result };
@@ -679,8 +1684,45 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return { content: res as string };
 	}
 
+	async exportTypesettingData(projectId: number, format: TypesettingFormat, options?: TypesettingOptions): Promise<string> {
+		const result = await this.callBackend<string | { error: string }>('export_typesetting_data', { projectId, format, options: options ?? null });
+		if (typeof result === 'object' && result?.error) {
+			throw new Error(result.error);
+		}
+		return result as string;
+	}
+
+	async importTypesettingData(projectId: number, content: string, format: TypesettingFormat, options?: TypesettingImportOptions): Promise<TypesettingImportReport> {
+		const result = await this.callBackend<TypesettingImportReport | { error: string }>('import_typesetting_data', { projectId, content, format, options: options ?? null });
+		if (result && 'error' in result) {
+			throw new Error(result.error);
+		}
+		return result as TypesettingImportReport;
+	}
+
+	// 返回内存中的参考图，Web端由JS打包下载
+	async exportAnnotatedImages(projectId: number, options?: AnnotateOptions): Promise<AnnotatedImage[]> {
+		const result = await this.callBackend<AnnotatedImage[] | { error: string }>('export_annotated_images', { projectId, options: options ?? null });
+		if (result && !Array.isArray(result) && result.error) {
+			throw new Error(result.error);
+		}
+		return result as AnnotatedImage[];
+	}
+
+	// 仅桌面端：逐张写入目录，返回写入的文件路径
+	async exportAnnotatedImagesToDir(projectId: number, dir: string, options?: AnnotateOptions): Promise<string[]> {
+		return this.callBackend<string[]>('export_annotated_images', { projectId, dir, options: options ?? null });
+	}
+
 	async saveProject(projectId: number): Promise<{ data?: number[]; error?: string }> {
-		const result = await this.callBackend<unknown>('save_project', { projectId });
+		return this.toProjectBytes(await this.callBackend<unknown>('save_project', { projectId }));
+	}
+
+	async saveProjectRange(projectId: number, imageIds: number[]): Promise<{ data?: number[]; error?: string }> {
+		return this.toProjectBytes(await this.callBackend<unknown>('save_project_range', { projectId, imageIds }));
+	}
+
+	private toProjectBytes(result: unknown): { data?: number[]; error?: string } {
 		const res = result as { error?: string } | Uint8Array | number[];
 		if (typeof res === 'object' && 'error' in res && res.error) {
 			return { error: res.error };
@@ -704,16 +1746,60 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<string>('request_ocr', { markerId, imageId, projectId, serviceId });
 	}
 
+	async requestOcrBatch(markerIds: number[], projectId: number, serviceId: string): Promise<OcrBatchItem[]> {
+		const result = await this.callBackend<OcrBatchItem[] | { error: string } | null>('request_ocr_batch', { markerIds, projectId, serviceId });
+		if (!Array.isArray(result)) {
+			throw new Error(result?.error ?? 'Failed to request batch OCR');
+		}
+		return result;
+	}
+
+	async requestPipeline(markerId: number, imageId: number, projectId: number, ocrService: string, translationService: string): Promise<string> {
+		const result = await this.callBackend<string | { error: string }>('request_pipeline', { markerId, imageId, projectId, ocrService, translationService });
+		if (typeof result !== 'string') {
+			throw new Error(result?.error ?? 'Failed to request pipeline');
+		}
+		return result;
+	}
+
+	async requestPipelineBatch(imageId: number, ocrService: string, translationService: string): Promise<PipelineBatchItem[]> {
+		const result = await this.callBackend<PipelineBatchItem[] | { error: string } | null>('request_pipeline_batch', { imageId, ocrService, translationService });
+		if (!Array.isArray(result)) {
+			throw new Error(result?.error ?? 'Failed to request batch pipeline');
+		}
+		return result;
+	}
+
+	async getPipeline(pipelineId: string): Promise<BunnyPipeline | null> {
+		return (await this.callBackend<BunnyPipeline | null>('get_pipeline', { pipelineId })) ?? null;
+	}
+
+	async getProjectPipelines(projectId: number): Promise<BunnyPipeline[]> {
+		return (await this.callBackend<BunnyPipeline[] | null>('get_project_pipelines', { projectId })) ?? [];
+	}
+
+	async translateAllUntranslated(projectId: number, serviceId: string, operationId?: string): Promise<string[]> {
+		const result = await this.callBackend<string[] | { error: string } | null>('translate_all_untranslated', { projectId, serviceId, operationId: operationId ?? null });
+		if (!Array.isArray(result)) {
+			throw new Error(result?.error ?? 'Failed to translate untranslated markers');
+		}
+		return result;
+	}
+
 	async requestTranslation(markerId: number, imageId: number, projectId: number, serviceId: string, text: string): Promise<string> {
 		return this.callBackend<string>('request_translation', { markerId, imageId, projectId, serviceId, text });
 	}
 
-	async handleOCRCompleted(taskId: string, markerId: number, text: string, model: string): Promise<void> {
-		await this.callBackend<void>('handle_ocr_completed', { taskId, markerId, text, model });
+	async handleOCRCompleted(taskId: string, markerId: number, text: string, model: string, regions?: OcrRegion[] | null): Promise<void> {
+		await this.callBackend<void>('handle_ocr_completed', { taskId, markerId, text, model, regions: regions ?? null });
 	}
 
-	async handleTranslationCompleted(taskId: string, markerId: number, translatedText: string, service: string): Promise<void> {
-		await this.callBackend<void>('handle_translation_completed', { taskId, markerId, translatedText, service });
+	async splitMarkerByOcrRegions(markerId: number, regions?: OcrRegion[] | null): Promise<number[]> {
+		return this.callBackend<number[]>('split_marker_by_ocr_regions', { markerId, regions: regions ?? null });
+	}
+
+	async handleTranslationCompleted(taskId: string, markerId: number, translatedText: string, service: string, warnings: TranslationWarning[] = [], metadata: TranslationMetadata | null = null): Promise<void> {
+		await this.callBackend<void>('handle_translation_completed', { taskId, markerId, translatedText, service, warnings, metadata });
 	}
 
 	async handleTaskFailed(taskId: string, error: string): Promise<void> {
@@ -740,39 +1826,88 @@ abstract class BaseCoreAPI implements CoreAPI {
 		return this.callBackend<string | null>('get_ocr_result', { markerId });
 	}
 
-	async getTranslationResult(markerId: number): Promise<string | null> {
-		return this.callBackend<string | null>('get_translation_result', { markerId });
+	async getTranslationResult(markerId: number): Promise<TranslationResult | null> {
+		return this.callBackend<TranslationResult | null>('get_translation_result', { markerId });
+	}
+
+	async acceptMachineTranslation(markerId: number): Promise<boolean> {
+		return this.callBackend<boolean>('accept_machine_translation', { markerId });
+	}
+
+	async acceptAllForImage(imageId: number, onlyEmpty: boolean): Promise<AcceptMachineTranslationReport> {
+		return this.callBackend<AcceptMachineTranslationReport>('accept_all_for_image', { imageId, onlyEmpty });
+	}
+
+	async rejectMachineTranslation(markerId: number): Promise<boolean> {
+		return this.callBackend<boolean>('reject_machine_translation', { markerId });
 	}
 
-	async getAvailableOCRServices(): Promise<OCRServiceInfo[]> {
+	async getAvailableOCRServices(projectId?: number): Promise<OCRServiceInfo[]> {
 		// For WASM, get services from pluginBridge; for Tauri, get from backend
 		if (isTauri()) {
-			return this.callBackend<OCRServiceInfo[]>('get_available_ocr_services', {});
+			return this.callBackend<OCRServiceInfo[]>('get_available_ocr_services', { projectId });
 		} else {
 			// Call pluginBridge to get locally registered services
 			const { pluginBridge } = await import('../services/pluginBridge');
-			return pluginBridge.handleServiceCall({
+			const services: OCRServiceInfo[] = await pluginBridge.handleServiceCall({
 				pluginId: 'core',
 				service: 'bunny',
 				method: 'get_ocr_services',
 				params: {}
 			});
+			return this.filterProjectServices(services, projectId);
 		}
 	}
 
-	async getAvailableTranslationServices(): Promise<TranslationServiceInfo[]> {
+	async getAvailableDetectionServices(projectId?: number): Promise<DetectionServiceInfo[]> {
+		if (isTauri()) {
+			return this.callBackend<DetectionServiceInfo[]>('get_available_detection_services', { projectId });
+		} else {
+			const { pluginBridge } = await import('../services/pluginBridge');
+			const services: DetectionServiceInfo[] = await pluginBridge.handleServiceCall({
+				pluginId: 'core',
+				service: 'bunny',
+				method: 'get_detection_services',
+				params: {}
+			});
+			return this.filterProjectServices(services, projectId);
+		}
+	}
+
+	async requestDetection(imageId: number, serviceId: string): Promise<string> {
+		return this.callBackend<string>('request_detection', { imageId, serviceId });
+	}
+
+	async handleDetectionCompleted(taskId: string, model: string, regions: DetectedRegion[]): Promise<void> {
+		await this.callBackend<void>('handle_detection_completed', { taskId, model, regions });
+	}
+
+	async createMarkersFromDetection(imageId: number, regions: DetectedRegion[]): Promise<number[]> {
+		return this.callBackend<number[]>('create_markers_from_detection', { imageId, regions });
+	}
+
+	// Web端的服务注册在前端，按项目停用列表过滤
+	private async filterProjectServices<T extends { id: string }>(services: T[], projectId?: number): Promise<T[]> {
+		if (projectId === undefined) return services;
+		const project = await this.getProjectInfo(projectId);
+		const disabled = new Set(project?.defaultServices?.disabled ?? []);
+		return (services || []).filter(service => !disabled.has(service.id));
+	}
+
+	async getAvailableTranslationServices(projectId?: number): Promise<TranslationServiceInfo[]> {
 		// For WASM, get services from pluginBridge; for Tauri, get from backend
 		if (isTauri()) {
-			return this.callBackend<TranslationServiceInfo[]>('get_available_translation_services', {});
+			return this.callBackend<TranslationServiceInfo[]>('get_available_translation_services', { projectId });
 		} else {
 			// Call pluginBridge to get locally registered services
 			const { pluginBridge } = await import('../services/pluginBridge');
-			return pluginBridge.handleServiceCall({
+			const services: TranslationServiceInfo[] = await pluginBridge.handleServiceCall({
 				pluginId: 'core',
 				service: 'bunny',
 				method: 'get_translation_services',
 				params: {}
 			});
+			return this.filterProjectServices(services, projectId);
 		}
 	}
 
@@ -791,6 +1926,22 @@ abstract class BaseCoreAPI implements CoreAPI {
 	async clearBunnyCache(markerId: number): Promise<void> {
 		await this.callBackend<void>('clear_bunny_cache', { markerId });
 	}
+
+	async getTaskHistory(projectId: number, limit?: number): Promise<TaskHistoryEntry[]> {
+		return await this.callBackend<TaskHistoryEntry[]>('get_task_history', { projectId, limit: limit ?? null });
+	}
+
+	async getTaskMetrics(projectId: number): Promise<ServiceTaskMetrics[]> {
+		return await this.callBackend<ServiceTaskMetrics[]>('get_task_metrics', { projectId });
+	}
+
+	async getImageTaskProgress(imageId: number): Promise<ImageTaskProgress> {
+		return await this.callBackend<ImageTaskProgress>('get_image_task_progress', { imageId });
+	}
+
+	async clearTaskHistory(projectId: number): Promise<void> {
+		await this.callBackend<void>('clear_task_history', { projectId });
+	}
 }
 
 // Tauri implementation
@@ -805,6 +1956,18 @@ class TauriCoreAPI extends BaseCoreAPI {
 		return this.callBackend<boolean>('reorder_project_images', { projectId, imageIds });
 	}
 
+	// 截图工具放入剪贴板的图片在webview的paste事件中可能拿不到，由桌面端读取系统剪贴板
+	async readClipboardImage(): Promise<Uint8Array | null> {
+		const { invoke } = await import('@tauri-apps/api/core');
+		const data = await invoke<number[] | null>('read_clipboard_image');
+		return data ? new Uint8Array(data) : null;
+	}
+
+	async createOpeningProjectFromUrl(): Promise<number> {
+		// 桌面端直接选择本地文件
+		throw new Error('createOpeningProjectFromUrl not supported in Tauri environment');
+	}
+
 	async cleanupOrphanedImages(): Promise<number> {
 		// Tauri版本中，图片数据由后端管理，不需要清理IndexedDB
 		console.warn('cleanupOrphanedImages not implemented for Tauri environment');
@@ -929,32 +2092,68 @@ class WasmCoreAPI extends BaseCoreAPI {
 				const data = params.data as number[];
 				return fn[method](params.imageId, params.formatStr, new Uint8Array(data));
 			}
+			case 'wasm_add_image_from_clipboard_bytes': {
+				const data = params.bytes as number[];
+				return fn[method](params.projectId, new Uint8Array(data), params.suggestedName);
+			}
+			case 'wasm_add_image_from_bytes': {
+				const data = params.bytes as number[];
+				return fn[method](params.projectId, new Uint8Array(data), params.name);
+			}
 			case 'wasm_add_point_marker_to_image':
-				return fn[method](params.imageId, params.x, params.y, params.translation) ?? null;
+				return fn[method](params.imageId, params.x, params.y, params.translation, params.mode) ?? null;
 			case 'wasm_add_rectangle_marker_to_image':
-				return fn[method](params.imageId, params.x, params.y, params.width, params.height, params.translation) ?? null;
+				return fn[method](params.imageId, params.x, params.y, params.width, params.height, params.translation, params.mode) ?? null;
 			case 'wasm_update_point_marker_position':
-				return fn[method](params.markerId, params.x, params.y);
+				return fn[method](params.markerId, params.x, params.y, params.mode);
 			case 'wasm_update_rectangle_marker_geometry':
-				return fn[method](params.markerId, params.x, params.y, params.width, params.height);
+				return fn[method](params.markerId, params.x, params.y, params.width, params.height, params.mode);
+			case 'wasm_add_polygon_marker_to_image':
+				return fn[method](params.imageId, flattenPoints(params.points), params.translation, params.mode) ?? null;
+			case 'wasm_update_polygon_marker_geometry':
+				return fn[method](params.markerId, flattenPoints(params.points), params.mode);
+			case 'wasm_end_marker_drag': {
+				const geometry = params.finalGeometry as MarkerGeometry;
+				if (geometry.type === 'polygon') {
+					return fn.wasm_end_polygon_marker_drag(params.markerId, flattenPoints(geometry.points));
+				}
+				return geometry.type === 'rectangle'
+					? fn[method](params.markerId, geometry.x, geometry.y, geometry.width, geometry.height)
+					: fn[method](params.markerId, geometry.x, geometry.y);
+			}
 			case 'wasm_update_marker_style':
 				return fn[method](params.markerId, params.overlayText, params.horizontal);
 			case 'wasm_move_marker_order':
 				return fn[method](params.markerId, params.newIndex);
 			case 'wasm_update_point_marker_full':
-				return fn[method](params.markerId, params.x, params.y, params.translation);
+				return fn[method](params.markerId, params.x, params.y, params.translation, params.mode);
 			case 'wasm_update_rectangle_marker_full':
-				return fn[method](params.markerId, params.x, params.y, params.width, params.height, params.translation);
+				return fn[method](params.markerId, params.x, params.y, params.width, params.height, params.translation, params.mode);
 			case 'wasm_reorder_project_images': {
 				const imageIds = params.imageIds as number[];
 				return fn[method](params.projectId, new Uint32Array(imageIds));
 			}
+			case 'wasm_request_ocr_batch': {
+				const markerIds = params.markerIds as number[];
+				return fn[method](new Uint32Array(markerIds), params.projectId, params.serviceId);
+			}
 			case 'wasm_validate_labelplus_file':
 				return fn[method](params.content);
 			case 'wasm_import_labelplus_data':
 				return fn[method](params.projectId, params.content);
+			case 'wasm_import_labelplus_merge':
+				return fn[method](params.projectId, params.content, params.options);
 			case 'wasm_export_labelplus_data':
 				return fn[method](params.projectId);
+			case 'wasm_export_labelplus_data_range':
+			case 'wasm_save_project_range':
+				return fn[method](params.projectId, new Uint32Array(params.imageIds as number[]));
+			case 'wasm_export_annotated_images':
+				return fn[method](params.projectId, params.options);
+			case 'wasm_export_typesetting_data':
+				return fn[method](params.projectId, params.format, params.options);
+			case 'wasm_import_typesetting_data':
+				return fn[method](params.projectId, params.content, params.format, params.options);
 			default: {
 				// 对于其他双参数方法，使用通用处理
 				const values = Object.values(params);
@@ -991,6 +2190,11 @@ class WasmCoreAPI extends BaseCoreAPI {
 		return null;
 	}
 
+	async relinkImage(): Promise<RelinkResult> {
+		// WASM版本的图片都保存在内存中，不会丢失
+		throw new Error('relinkImage not supported in WASM environment');
+	}
+
 	async cleanupOrphanedImages(): Promise<number> {
 		try {
 			const wasm = await this.initWasm();
@@ -1146,22 +2350,39 @@ class WasmWorkerAdapter extends BaseCoreAPI implements ThumbnailAPI {
 				return await this.callWorkerMethod<T>(method, params.projectId, params.formatStr, params.data, params.name);
 			case 'wasm_update_image_data_from_binary':
 				return await this.callWorkerMethod<T>(method, params.imageId, params.formatStr, params.data);
+			case 'wasm_add_image_from_clipboard_bytes':
+				return await this.callWorkerMethod<T>(method, params.projectId, params.bytes, params.suggestedName);
+			case 'wasm_add_image_from_bytes':
+				return await this.callWorkerMethod<T>(method, params.projectId, params.bytes, params.name);
 			case 'wasm_add_point_marker_to_image':
-				return await this.callWorkerMethod<T>(method, params.imageId, params.x, params.y, params.translation);
+				return await this.callWorkerMethod<T>(method, params.imageId, params.x, params.y, params.translation, params.mode);
 			case 'wasm_add_rectangle_marker_to_image':
-				return await this.callWorkerMethod<T>(method, params.imageId, params.x, params.y, params.width, params.height, params.translation);
+				return await this.callWorkerMethod<T>(method, params.imageId, params.x, params.y, params.width, params.height, params.translation, params.mode);
 			case 'wasm_update_point_marker_position':
-				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y);
+				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.mode);
 			case 'wasm_update_rectangle_marker_geometry':
-				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.width, params.height);
+				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.width, params.height, params.mode);
+			case 'wasm_add_polygon_marker_to_image':
+				return await this.callWorkerMethod<T>(method, params.imageId, flattenPoints(params.points), params.translation, params.mode);
+			case 'wasm_update_polygon_marker_geometry':
+				return await this.callWorkerMethod<T>(method, params.markerId, flattenPoints(params.points), params.mode);
+			case 'wasm_end_marker_drag': {
+				const geometry = params.finalGeometry as MarkerGeometry;
+				if (geometry.type === 'polygon') {
+					return await this.callWorkerMethod<T>('wasm_end_polygon_marker_drag', params.markerId, flattenPoints(geometry.points));
+				}
+				return geometry.type === 'rectangle'
+					? await this.callWorkerMethod<T>(method, params.markerId, geometry.x, geometry.y, geometry.width, geometry.height)
+					: await this.callWorkerMethod<T>(method, params.markerId, geometry.x, geometry.y);
+			}
 			case 'wasm_update_marker_style':
 				return await this.callWorkerMethod<T>(method, params.markerId, params.overlayText, params.horizontal);
 			case 'wasm_move_marker_order':
 				return await this.callWorkerMethod<T>(method, params.markerId, params.newIndex);
 			case 'wasm_update_point_marker_full':
-				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.translation);
+				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.translation, params.mode);
 			case 'wasm_update_rectangle_marker_full':
-				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.width, params.height, params.translation);
+				return await this.callWorkerMethod<T>(method, params.markerId, params.x, params.y, params.width, params.height, params.translation, params.mode);
 			case 'wasm_reorder_project_images':
 				return await this.callWorkerMethod<T>(method, params.projectId, params.imageIds);
 			default: {
@@ -1194,8 +2415,22 @@ class WasmWorkerAdapter extends BaseCoreAPI implements ThumbnailAPI {
 			
 			await this.callWorkerMethod('wasm_init_shared_buffer', stream.getSharedArrayBuffer());
 			
+			// 压缩包走单独的入口，返回导入报告
+			const extension = fileExtension.toLowerCase();
+			if (extension === 'zip' || extension === 'cbz') {
+				const [, report] = await Promise.all([
+					stream.writeImageData(tempId, ImageFormatEnum.ARCHIVE, data),
+					this.callWorkerMethod<ArchiveImportReport | null>(
+						'wasm_create_opening_project_from_archive_shared_buffer',
+						`${projectName.trim()}.${extension}`,
+						projectName.trim()
+					)
+				]);
+				return report?.project_id ?? null;
+			}
+			
 			// Determine format enum for SharedArrayBuffer
-			const formatEnum = fileExtension.toLowerCase() === 'bf' ? ImageFormatEnum.BF : ImageFormatEnum.LABELPLUS;
+			const formatEnum = extension === 'bf' ? ImageFormatEnum.BF : ImageFormatEnum.LABELPLUS;
 			const writePromise = stream.writeImageData(tempId, formatEnum, data);
 			
 			const [, result] = await Promise.all([
@@ -1263,6 +2498,49 @@ class WasmWorkerAdapter extends BaseCoreAPI implements ThumbnailAPI {
 		}
 	}
 
+	async addImageFromClipboard(projectId: number, data: Uint8Array, suggestedName?: string): Promise<number> {
+		// 小截图直接传，大的走SharedArrayBuffer
+		if (data.length < 1024 * 1024) {
+			return super.addImageFromClipboard(projectId, data, suggestedName);
+		}
+		const { getOrCreateStream, ImageFormatEnum } = await import('./streaming/imageStream');
+		const stream = getOrCreateStream(data.length + 1024 * 1024);
+		const tempImageId = Date.now() + Math.floor(Math.random() * 1000);
+		await this.callWorkerMethod('wasm_init_shared_buffer', stream.getSharedArrayBuffer());
+		try {
+			const [, imageId] = await Promise.all([
+				// 格式由core按文件头识别，这里的格式只用于流的头部
+				stream.writeImageData(tempImageId, ImageFormatEnum.PNG, data),
+				this.callWorkerMethod<number>('wasm_add_image_from_clipboard_shared_buffer', projectId, suggestedName)
+			]);
+			return imageId;
+		} catch (error) {
+			throw parseImportError(error, suggestedName ?? 'clipboard');
+		}
+	}
+
+	async getMarkerThumbnails(imageId: number, maxEdgePx: number): Promise<MarkerThumbnail[]> {
+		// 整页的预览图由Worker一次写入SharedArrayBuffer，不经过postMessage逐个复制
+		const { getOrCreateStream } = await import('./streaming/imageStream');
+		const stream = getOrCreateStream();
+		await this.callWorkerMethod('wasm_init_shared_buffer', stream.getSharedArrayBuffer());
+		let layout: ArrayLike<number>;
+		try {
+			layout = await this.callWorkerMethod<ArrayLike<number>>('wasm_write_marker_thumbnails_to_shared_buffer', imageId, maxEdgePx);
+		} catch (error) {
+			// 超出缓冲区大小时退回普通调用
+			console.warn('Marker thumbnails did not fit the shared buffer:', error);
+			return super.getMarkerThumbnails(imageId, maxEdgePx);
+		}
+		const buffer = stream.getDataBuffer();
+		const thumbnails: MarkerThumbnail[] = [];
+		for (let i = 0; i + 2 < layout.length; i += 3) {
+			const offset = layout[i + 1];
+			thumbnails.push({ marker_id: layout[i], data: buffer.slice(offset, offset + layout[i + 2]) });
+		}
+		return thumbnails;
+	}
+
 	async addImageFromPath(): Promise<number | null> {
 		console.warn('addImageFromPath not supported in WASM Worker environment');
 		return null;
@@ -1272,6 +2550,10 @@ class WasmWorkerAdapter extends BaseCoreAPI implements ThumbnailAPI {
 		return null;
 	}
 
+	async relinkImage(): Promise<RelinkResult> {
+		throw new Error('relinkImage not supported in WASM Worker environment');
+	}
+
 	async cleanupOrphanedImages(): Promise<number> {
 		try {
 			return await this.callWorkerMethod<number>('wasm_cleanup_orphaned_images');
@@ -1310,6 +2592,16 @@ class WasmWorkerAdapter extends BaseCoreAPI implements ThumbnailAPI {
 		}
 	}
 
+	async prefetchThumbnailsAround(imageId: number, radius?: number): Promise<string> {
+		try {
+			const result = await this.callWorkerMethod<string>('wasm_prefetch_thumbnails_around', imageId, radius);
+			return result || 'ok';
+		} catch (error) {
+			console.error('WASM Worker prefetch thumbnails failed:', error);
+			throw error;
+		}
+	}
+
 	// 辅助方法
 	private imageFormatToEnum(format: ImageFormat): number {
 		switch (format) {
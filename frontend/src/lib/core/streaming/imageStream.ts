@@ -34,7 +34,8 @@ export enum ImageFormatEnum {
 	WEBP = 3,
 	BMP = 4,
 	LABELPLUS = 10,
-	BF = 11
+	BF = 11,
+	ARCHIVE = 12
 }
 
 export class SharedArrayBufferStream {
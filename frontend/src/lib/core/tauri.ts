@@ -42,6 +42,9 @@ export const tauriAPI: TauriAPI = {
 				filters: [{
 					name: 'Project Files',
 					extensions: ['bf', 'txt', 'lp']
+				}, {
+					name: 'Comic Archives',
+					extensions: ['zip', 'cbz']
 				}]
 			});
 			return selected as string | null;
@@ -2,11 +2,12 @@
 import { get } from 'svelte/store';
 import { bunnyStore } from '../stores/bunnyStore';
 import type { BunnyTask } from '../types/bunny';
+import type { RequestPluginDetectionEvent, RequestPluginOcrEvent, RequestPluginTranslationEvent } from '../types/events.generated';
 import { eventService } from './eventService';
 import { coreAPI } from '../core/adapter';
 import { currentImageId } from './imageService';
 import { pluginService } from './pluginService';
-import { projectStore } from '../stores/projectStore';
+import { projectStore, currentProject } from '../stores/projectStore';
 import { notificationStore } from '../stores/notificationStore';
 
 class BunnyService {
@@ -73,7 +74,9 @@ class BunnyService {
 						// Extract marker_id from task
 						const task = get(bunnyStore).tasks.get(data.task_id);
 						if (task) {
-							await coreAPI.handleOCRCompleted(data.task_id, task.markerId, data.text, data.model);
+							// 能给出文本行位置的插件会附带regions
+							const regions = Array.isArray(data.regions) ? data.regions : null;
+							await coreAPI.handleOCRCompleted(data.task_id, task.markerId, data.text, data.model, regions);
 							eventService.debug(`Relayed OCR result to backend for task ${data.task_id}`);
 						} else {
 							eventService.warn(`Task ${data.task_id} not found when handling OCR result`);
@@ -90,7 +93,10 @@ class BunnyService {
 						// Extract marker_id from task
 						const task = get(bunnyStore).tasks.get(data.task_id);
 						if (task) {
-							await coreAPI.handleTranslationCompleted(data.task_id, task.markerId, data.translated_text, data.service);
+							const warnings = Array.isArray(data.warnings) ? data.warnings : [];
+							// 插件可附带模型、提示词哈希等元数据，大小由core限制
+							const metadata = data.metadata && typeof data.metadata === 'object' ? data.metadata : null;
+							await coreAPI.handleTranslationCompleted(data.task_id, task.markerId, data.translated_text, data.service, warnings, metadata);
 							eventService.debug(`Relayed translation result to backend for task ${data.task_id}`);
 						} else {
 							eventService.warn(`Task ${data.task_id} not found when handling translation result`);
@@ -101,6 +107,27 @@ class BunnyService {
 				}
 				break;
 
+			case 'plugin:detect_result':
+				if (data.task_id && Array.isArray(data.regions)) {
+					try {
+						await coreAPI.handleDetectionCompleted(data.task_id, data.model ?? '', data.regions);
+					} catch (error) {
+						eventService.error(`Failed to relay detection result to backend`, error);
+					}
+				}
+				break;
+
+			case 'plugin:detect_error':
+				if (data.task_id) {
+					const errorMessage = typeof data.error === 'string' ? data.error : 'Unknown plugin error';
+					try {
+						await coreAPI.handleTaskFailed(data.task_id, errorMessage);
+					} catch (error) {
+						eventService.error(`Failed to relay detection error for task ${data.task_id}`, error);
+					}
+				}
+				break;
+
 			case 'plugin:translation_error':
 				if (data.task_id) {
 					const errorMessage =
@@ -182,6 +209,16 @@ class BunnyService {
 				await this.relayTranslationRequestToPlugin(data);
 				break;
 
+			case 'bunny:request_plugin_detection':
+				await this.relayDetectionRequestToPlugin(data);
+				break;
+
+			case 'bunny:detection_completed':
+				// 候选框先交给界面确认，确认后调用createMarkersFromDetection
+				bunnyStore.setDetections(data.image_id, data.regions ?? []);
+				eventService.info(`Detection found ${data.regions?.length ?? 0} regions on image ${data.image_id}`);
+				break;
+
 			case 'bunny:ocr_completed':
 				if (data.marker_id !== undefined && data.original_text !== undefined) {
 					// Update original text (even if empty)
@@ -202,7 +239,7 @@ class BunnyService {
 			case 'bunny:translation_completed':
 				if (data.marker_id !== undefined && data.machine_translation !== undefined) {
 					// Update machine translation (even if empty)
-					bunnyStore.setMachineTranslation(data.marker_id, data.machine_translation, data.service);
+					bunnyStore.setMachineTranslation(data.marker_id, data.machine_translation, data.service, data.warnings ?? []);
 
 					// Update task status
 					if (data.task_id) {
@@ -216,6 +253,48 @@ class BunnyService {
 				}
 				break;
 
+			case 'bunny:machine_translation_accepted':
+				for (const markerId of data.marker_ids ?? []) {
+					bunnyStore.setMachineTranslationReviewed(markerId, true);
+				}
+				break;
+
+			case 'bunny:machine_translation_rejected':
+				bunnyStore.setMachineTranslationReviewed(data.marker_id, false);
+				break;
+
+			case 'bunny:task_fellback':
+				// 原任务失败，已改用后备服务重发（新任务另有task_created事件）
+				bunnyStore.updateTask(data.task_id, {
+					status: 'failed',
+					error: data.error,
+					completedAt: Date.now()
+				});
+				eventService.info(`Task ${data.task_id} failed on ${data.from}, retrying with ${data.to}`);
+				break;
+
+			case 'bunny:pipeline_advanced':
+			case 'bunny:pipeline_completed':
+			case 'bunny:pipeline_failed': {
+				// 事件只带ID，阶段和状态以core为准
+				const pipeline = await coreAPI.getPipeline(data.pipeline_id);
+				if (pipeline) {
+					bunnyStore.setPipeline(pipeline);
+				}
+				if (eventName === 'bunny:pipeline_failed') {
+					eventService.error(`Pipeline ${data.pipeline_id} failed at ${data.stage}`, data.error);
+				}
+				break;
+			}
+
+			case 'bunny:image_progress':
+				bunnyStore.setImageProgress(data.image_id, { done: data.done, total: data.total, failed: data.failed });
+				break;
+
+			case 'bunny:batch_progress':
+				eventService.debug(`Batch translation queued ${data.done}/${data.total}`);
+				break;
+
 			case 'bunny:task_failed':
 				if (data.task_id && data.error) {
 					bunnyStore.updateTask(data.task_id, {
@@ -230,8 +309,8 @@ class BunnyService {
 	}
 
 	// Relay OCR request from backend to plugin
-	private async relayOCRRequestToPlugin(data: any) {
-		const { task_id, cropped_image_data, image_format, service_id, source_language } = data;
+	private async relayOCRRequestToPlugin(data: RequestPluginOcrEvent) {
+		const { task_id, cropped_image_data, image_format, service_id, source_language, pixel_geometry } = data;
 
 		try {
 			// Get the plugin_id for the service
@@ -243,16 +322,27 @@ class BunnyService {
 			}
 
 			// Send message to plugin with cropped image data from backend
-			const message = {
+			const message: Record<string, unknown> = {
 				type: 'ocr_request',
 				task_id: task_id,
 				image_data: cropped_image_data,  // Already cropped by backend
 				image_format: image_format,      // Always "png" from backend
 				options: {
-					source_language: source_language
+					source_language: source_language,
+					pixel_geometry: pixel_geometry ?? null
 				}
 			};
 
+			// WASM插件从SharedArrayBuffer读取图片，不再把字节数组复制进postMessage
+			if (pluginService.canReadStagedImages(serviceInfo.plugin_id)) {
+				const { sharedBufferHandler } = await import('./sharedBufferHandler');
+				const imageRef = `ocr:${task_id}`;
+				if (sharedBufferHandler.stageImage(imageRef, Uint8Array.from(cropped_image_data))) {
+					message.image_ref = imageRef;
+					message.image_data = [];
+				}
+			}
+
 			await pluginService.sendPluginMessage('bunny', serviceInfo.plugin_id, message);
 
 			eventService.debug(`Relayed OCR request to plugin ${serviceInfo.plugin_id}`);
@@ -263,9 +353,40 @@ class BunnyService {
 		}
 	}
 
+	// Relay detection request from backend to plugin
+	private async relayDetectionRequestToPlugin(data: RequestPluginDetectionEvent) {
+		const { task_id, service_id } = data;
+
+		try {
+			const detectionServices = await coreAPI.getAvailableDetectionServices();
+			const serviceInfo = detectionServices.find(s => s.id === service_id);
+
+			if (!serviceInfo || !this.isPluginEnabled(serviceInfo.plugin_id)) {
+				throw new Error(`Detection service '${service_id}' not found`);
+			}
+
+			const message = {
+				type: 'detect_request',
+				task_id,
+				service_id,
+				image_data: data.image_data,
+				image_format: data.image_format,
+				width: data.width,
+				height: data.height
+			};
+
+			await pluginService.sendPluginMessage('bunny', serviceInfo.plugin_id, message);
+
+			eventService.debug(`Relayed detection request to plugin ${serviceInfo.plugin_id}`);
+		} catch (error) {
+			eventService.error(`Failed to relay detection request to plugin`, error);
+			await coreAPI.handleTaskFailed(task_id, error instanceof Error ? error.message : String(error));
+		}
+	}
+
 	// Relay translation request from backend to plugin
-	private async relayTranslationRequestToPlugin(data: any) {
-		const { task_id, service_id, text, source_language, target_language } = data;
+	private async relayTranslationRequestToPlugin(data: RequestPluginTranslationEvent) {
+		const { task_id, service_id, text, source_language, target_language, glossary, context } = data;
 
 		try {
 			// Get the plugin_id for the service
@@ -283,7 +404,10 @@ class BunnyService {
 				text: text,
 				options: {
 					source_language: source_language,
-					target_language: target_language
+					target_language: target_language,
+					glossary: glossary?.entries ?? [],
+					forbidden_terms: glossary?.forbiddenTerms ?? [],
+					context: context ?? []
 				}
 			};
 
@@ -297,6 +421,17 @@ class BunnyService {
 		}
 	}
 
+	// 全局设置为 default 时使用项目模板中设置的默认服务
+	private resolveOcrModel(model?: string): string {
+		const configured = model || get(bunnyStore).settings.ocrModel;
+		return configured === 'default' ? get(currentProject)?.defaultServices?.ocr || configured : configured;
+	}
+
+	private resolveTranslationService(service?: string): string {
+		const configured = service || get(bunnyStore).settings.translationService;
+		return configured === 'default' ? get(currentProject)?.defaultServices?.translation || configured : configured;
+	}
+
 	// Public API - simplified to just call backend
 
 	async requestOCR(markerId: number, model?: string): Promise<string> {
@@ -310,7 +445,7 @@ class BunnyService {
 			throw new Error('No project selected');
 		}
 
-		const ocrModel = model || get(bunnyStore).settings.ocrModel;
+		const ocrModel = this.resolveOcrModel(model);
 
 		// Simply call backend - backend will handle everything including task creation
 		const taskId = await coreAPI.requestOCR(markerId, imageId, projectId, ocrModel);
@@ -318,6 +453,26 @@ class BunnyService {
 		return taskId;
 	}
 
+	// OCR后自动机翻，返回流水线ID
+	async requestPipeline(markerId: number, model?: string, service?: string): Promise<string> {
+		const imageId = get(currentImageId);
+		if (!imageId) {
+			throw new Error('No image selected');
+		}
+
+		const projectId = get(projectStore).currentProjectId;
+		if (!projectId) {
+			throw new Error('No project selected');
+		}
+
+		const pipelineId = await coreAPI.requestPipeline(markerId, imageId, projectId, this.resolveOcrModel(model), this.resolveTranslationService(service));
+		const pipeline = await coreAPI.getPipeline(pipelineId);
+		if (pipeline) {
+			bunnyStore.setPipeline(pipeline);
+		}
+		return pipelineId;
+	}
+
 	async requestTranslation(markerId: number, text?: string, service?: string): Promise<string> {
 		// Get text from marker data if not provided
 		const markerData = get(bunnyStore).markerData.get(markerId);
@@ -337,8 +492,7 @@ class BunnyService {
 			throw new Error('No project selected');
 		}
 
-		const settings = get(bunnyStore).settings;
-		const translationService = service || settings.translationService;
+		const translationService = this.resolveTranslationService(service);
 
 		// Simply call backend - backend will handle everything including task creation
 		const taskId = await coreAPI.requestTranslation(markerId, imageId, projectId, translationService, textToTranslate);
@@ -347,20 +501,30 @@ class BunnyService {
 	}
 
 	async requestBatchOCR(markerIds: number[], model?: string): Promise<string[]> {
+		const projectId = get(projectStore).currentProjectId;
+		if (!projectId) {
+			throw new Error('No project selected');
+		}
+
 		const taskIds: string[] = [];
-		const ocrModel = model || get(bunnyStore).settings.ocrModel;
+		const ocrModel = this.resolveOcrModel(model);
 		const batchSize = get(bunnyStore).settings.batchSize;
 
+		// 每批一次调用，裁剪由core的线程池并行完成
 		for (let i = 0; i < markerIds.length; i += batchSize) {
 			const batch = markerIds.slice(i, i + batchSize);
 
-			for (const markerId of batch) {
-				try {
-					const taskId = await this.requestOCR(markerId, ocrModel);
-					taskIds.push(taskId);
-				} catch (error) {
-					eventService.error(`Failed to request OCR for marker ${markerId}`, error);
+			try {
+				const items = await coreAPI.requestOcrBatch(batch, projectId, ocrModel);
+				for (const item of items) {
+					if (item.task_id) {
+						taskIds.push(item.task_id);
+					} else {
+						eventService.error(`Failed to request OCR for marker ${item.marker_id}`, item.error);
+					}
 				}
+			} catch (error) {
+				eventService.error('Failed to request batch OCR', error);
 			}
 
 			// Small delay between batches to avoid overwhelming the system
@@ -372,9 +536,18 @@ class BunnyService {
 		return taskIds;
 	}
 
+	// 翻译整个项目中有原文没译文的标记，由core排队
+	async translateAllUntranslated(service?: string): Promise<string[]> {
+		const projectId = get(projectStore).currentProjectId;
+		if (!projectId) {
+			throw new Error('No project selected');
+		}
+		return coreAPI.translateAllUntranslated(projectId, this.resolveTranslationService(service));
+	}
+
 	async requestBatchTranslation(markerIds: number[], service?: string): Promise<string[]> {
 		const taskIds: string[] = [];
-		const translationService = service || get(bunnyStore).settings.translationService;
+		const translationService = this.resolveTranslationService(service);
 		const batchSize = get(bunnyStore).settings.batchSize;
 
 		for (let i = 0; i < markerIds.length; i += batchSize) {
@@ -437,7 +610,8 @@ class BunnyService {
 						bunnyStore.setMachineTranslation(
 							markerId,
 							cacheData.machine_translation,
-							cacheData.last_translation_service || 'unknown'
+							cacheData.last_translation_service || 'unknown',
+							cacheData.translation_warnings ?? []
 						);
 					}
 				}
@@ -2,15 +2,23 @@
 // This service listens to store changes and triggers appropriate side effects
 // following the reactive programming paradigm
 
+import { get } from 'svelte/store';
+import { coreAPI } from '../core/adapter';
 import { currentImage } from '../stores/imageStore';
-import { markerService } from './markerService';
+import { imageViewerStore } from '../stores/imageViewerStore';
+import { markerService, selectedMarkerId } from './markerService';
 import { imageViewerService } from './imageViewerService';
 import { snapshotService } from './snapshotService';
 
+// 缩放/平移停下后才保存查看状态
+const VIEW_STATE_SAVE_DELAY_MS = 500;
+
 class CoordinatorService {
 	private unsubscribers: Array<() => void> = [];
 	private lastImageId: number | null = null;
 	private initialized = false;
+	private viewStateTimer: ReturnType<typeof setTimeout> | null = null;
+	private pendingViewStateSave: (() => void) | null = null;
 
 	async initialize() {
 		if (this.initialized) return;
@@ -28,6 +36,8 @@ class CoordinatorService {
 		// Subscribe to current image changes
 		const unsubscribeImage = currentImage.subscribe(async (image) => {
 			if (image && image.id !== this.lastImageId) {
+				// 先保存上一张图片的查看状态
+				this.flushViewStateSave();
 				this.lastImageId = image.id;
 				
 				// Trigger side effects for image change
@@ -36,13 +46,24 @@ class CoordinatorService {
 		});
 		this.unsubscribers.push(unsubscribeImage);
 
+		// 缩放/平移或选中标记变化时保存当前图片的查看状态
+		this.unsubscribers.push(imageViewerStore.subscribe(() => this.scheduleViewStateSave()));
+		this.unsubscribers.push(selectedMarkerId.subscribe(() => this.scheduleViewStateSave()));
+
 		console.log('CoordinatorService initialized');
 	}
 
 	private async handleImageChange(imageId: number) {
 		try {
+			// 取回保存的查看状态，图片加载后由ImageViewer应用
+			const viewState = await coreAPI.selectImage(imageId).catch(() => null);
+			imageViewerService.restoreViewState(viewState);
+
 			// Load markers for the new image
 			await markerService.loadImageMarkers(imageId);
+			if (viewState?.last_selected_marker != null) {
+				markerService.setSelectedMarker(viewState.last_selected_marker);
+			}
 			
 			// Request transform reset for new image
 			// The actual centering will be handled by ImageViewer when image loads
@@ -52,7 +73,32 @@ class CoordinatorService {
 		}
 	}
 
+	private scheduleViewStateSave() {
+		const imageId = this.lastImageId;
+		if (imageId === null) return;
+		const { scale, viewX, viewY } = get(imageViewerStore);
+		const viewState = { zoom: scale, pan_x: viewX, pan_y: viewY, last_selected_marker: get(selectedMarkerId) };
+		this.pendingViewStateSave = () => {
+			coreAPI.setImageViewState(imageId, viewState).catch((error) => {
+				console.error('Failed to save view state:', error);
+			});
+		};
+		if (this.viewStateTimer) clearTimeout(this.viewStateTimer);
+		this.viewStateTimer = setTimeout(() => this.flushViewStateSave(), VIEW_STATE_SAVE_DELAY_MS);
+	}
+
+	private flushViewStateSave() {
+		if (this.viewStateTimer) {
+			clearTimeout(this.viewStateTimer);
+			this.viewStateTimer = null;
+		}
+		const save = this.pendingViewStateSave;
+		this.pendingViewStateSave = null;
+		save?.();
+	}
+
 	destroy() {
+		this.flushViewStateSave();
 		// Cleanup all subscriptions
 		this.unsubscribers.forEach(unsubscribe => unsubscribe());
 		this.unsubscribers = [];
@@ -0,0 +1,52 @@
+import { writable, get } from 'svelte/store';
+import { coreAPI } from '../core/adapter';
+import type { DownscaleSettings } from '../types';
+
+// 导入时缩小超大图片的设置，保存在localStorage，启动时同步给core
+const STORAGE_KEY = 'downscaleSettings';
+
+const DEFAULT_SETTINGS: DownscaleSettings = {
+	enabled: false,
+	max_edge: 8000,
+	jpeg_quality: 90
+};
+
+const settingsStore = writable<DownscaleSettings>({ ...DEFAULT_SETTINGS });
+
+function loadSaved(): DownscaleSettings {
+	if (typeof localStorage === 'undefined') return { ...DEFAULT_SETTINGS };
+	try {
+		const stored = localStorage.getItem(STORAGE_KEY);
+		return stored ? { ...DEFAULT_SETTINGS, ...JSON.parse(stored) } : { ...DEFAULT_SETTINGS };
+	} catch {
+		return { ...DEFAULT_SETTINGS };
+	}
+}
+
+let initialized = false;
+
+export const downscaleService = {
+	settings: { subscribe: settingsStore.subscribe },
+
+	async init() {
+		if (initialized) return;
+		initialized = true;
+
+		const saved = loadSaved();
+		settingsStore.set(saved);
+		try {
+			await coreAPI.setDownscaleSettings(saved);
+		} catch (error) {
+			console.error('Failed to apply downscale settings:', error);
+		}
+	},
+
+	async update(patch: Partial<DownscaleSettings>) {
+		const next = { ...get(settingsStore), ...patch };
+		await coreAPI.setDownscaleSettings(next);
+		settingsStore.set(next);
+		if (typeof localStorage !== 'undefined') {
+			localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
+		}
+	}
+};
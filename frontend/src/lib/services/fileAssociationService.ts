@@ -7,17 +7,18 @@ export const fileAssociationService = {
 		if (!platformService.isTauri()) return;
 		
 		try {
-			const { listen, emit } = await import('@tauri-apps/api/event');
+			const { emit } = await import('@tauri-apps/api/event');
+			const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
 			
-			// 监听文件打开事件（从命令行参数或文件关联打开）
-			await listen<string>('open-file', async (event) => {
+			// 监听文件打开事件（从命令行参数、文件关联或拖入窗口打开），后端只发给对应的窗口
+			await getCurrentWebviewWindow().listen<string>('open-file', async (event) => {
 				const filePath = event.payload;
 				console.log('Received open-file event:', filePath);
 				
-				if (filePath && filePath.endsWith('.bf')) {
+				if (filePath && /\.(bf|txt|lp|zip|cbz)$/i.test(filePath)) {
 					console.log('Opening project file:', filePath);
 					// 记录到最近打开
-					recentProjectsService.addRecentProject(filePath, 'bf');
+					recentProjectsService.addRecentProject(filePath, recentProjectsService.getProjectType(filePath));
 					// 立即显示模态框，不需要延迟
 					modalStore.showModal('openProject', {
 						initialFilePath: filePath,
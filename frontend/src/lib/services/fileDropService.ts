@@ -0,0 +1,155 @@
+import { get } from 'svelte/store';
+import { modalStore } from '../stores/modalStore';
+import { notificationStore } from '../stores/notificationStore';
+import { platformService } from './platformService';
+import { currentProject } from './projectService';
+import { imageService, currentImageId } from './imageService';
+import { markerService } from './markerService';
+import type { LabelplusMergeReport } from '../types';
+
+type DropClassification = 'project' | 'translation' | 'images' | 'mixed' | 'unsupported';
+
+interface RejectedFile {
+	path: string;
+	error: { kind: string; name: string; message: string };
+}
+
+interface FilesDroppedPayload {
+	classification: DropClassification;
+	paths: string[];
+	rejected: RejectedFile[];
+}
+
+interface DroppedImagesReport {
+	added_image_ids: number[];
+	rejected: RejectedFile[];
+}
+
+function fileName(path: string): string {
+	return path.replace(/\\/g, '/').split('/').pop() || path;
+}
+
+// 后端的错误可能是ImportError/BfFormatError的JSON
+function errorMessage(error: unknown): string {
+	const text = error instanceof Error ? error.message : String(error);
+	try {
+		const parsed = JSON.parse(text);
+		if (parsed && typeof parsed.message === 'string') return parsed.message;
+	} catch {
+		// 普通字符串错误
+	}
+	return text;
+}
+
+function notifyRejected(rejected: RejectedFile[]) {
+	if (rejected.length === 0) return;
+	notificationStore.notify({
+		title: `${rejected.length} 个文件无法导入`,
+		message: rejected.map(file => file.error.message).join('\n'),
+		level: 'warning'
+	});
+}
+
+function notifyError(title: string, error: unknown) {
+	notificationStore.notify({ title, message: errorMessage(error), level: 'error' });
+}
+
+async function invokeCommand<T>(command: string, args: Record<string, unknown>): Promise<T> {
+	const { invoke } = await import('@tauri-apps/api/core');
+	return invoke<T>(command, args);
+}
+
+async function openProject(path: string) {
+	try {
+		await invokeCommand('open_dropped_project', { path });
+	} catch (error) {
+		notifyError('无法打开项目文件', error);
+	}
+}
+
+async function importTranslation(projectId: number, path: string) {
+	try {
+		const report = await invokeCommand<LabelplusMergeReport>('import_dropped_translation', { projectId, path });
+		await imageService.refreshProjectImages(projectId);
+		const imageId = get(currentImageId);
+		if (imageId !== null) {
+			await markerService.loadImageMarkers(imageId);
+		}
+		notificationStore.notify({
+			message: `已导入 ${report.added} 个标记${report.skipped.length > 0 ? `，跳过 ${report.skipped.length} 个` : ''}`,
+			level: 'success'
+		});
+	} catch (error) {
+		notifyError('导入翻译失败', error);
+	}
+}
+
+async function addImages(projectId: number, paths: string[]) {
+	try {
+		const report = await invokeCommand<DroppedImagesReport>('add_dropped_images', { projectId, paths });
+		if (report.added_image_ids.length > 0) {
+			await imageService.refreshProjectImages(projectId);
+		}
+		notifyRejected(report.rejected);
+	} catch (error) {
+		notifyError('添加图片失败', error);
+	}
+}
+
+function confirm(title: string, message: string, confirmText: string, onConfirm: () => void) {
+	modalStore.showModal('confirm', { title, message, confirmText, cancelText: '取消', onConfirm });
+}
+
+function handleDrop(payload: FilesDroppedPayload) {
+	notifyRejected(payload.rejected);
+	const project = get(currentProject);
+	const [first] = payload.paths;
+
+	switch (payload.classification) {
+		case 'project':
+			confirm('打开项目', `打开 ${fileName(first)}？`, '打开', () => openProject(first));
+			break;
+		case 'translation':
+			if (project) {
+				confirm('导入翻译', `把 ${fileName(first)} 中的翻译合并到项目 "${project.name}"？`, '导入',
+					() => importTranslation(project.id, first));
+			} else {
+				confirm('打开项目', `从翻译文件 ${fileName(first)} 创建项目？`, '打开', () => openProject(first));
+			}
+			break;
+		case 'images':
+			if (project) {
+				confirm('添加图片', `把 ${payload.paths.length} 张图片添加到项目 "${project.name}"？`, '添加',
+					() => addImages(project.id, payload.paths));
+			} else {
+				notificationStore.notify({ message: '请先新建或打开项目，再拖入图片', level: 'warning' });
+			}
+			break;
+		case 'mixed':
+			notificationStore.notify({
+				message: '一次只能拖入一个项目文件、一个翻译文件或一组图片',
+				level: 'warning'
+			});
+			break;
+		case 'unsupported':
+			break;
+	}
+}
+
+export const fileDropService = {
+	async init() {
+		if (!platformService.isTauri()) return;
+
+		try {
+			const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+			// 后端只发给文件拖入的窗口
+			await getCurrentWebviewWindow().listen<FilesDroppedPayload>('app:files_dropped', (event) => {
+				// 打开着模态框时由其中的上传组件处理拖入的文件
+				if (get(modalStore).activeModal !== null) return;
+				handleDrop(event.payload);
+			});
+		} catch (error) {
+			console.error('Failed to initialize file drop service:', error);
+		}
+	}
+};
@@ -3,10 +3,11 @@ import { coreAPI } from '../core/adapter';
 import { imageStore } from '../stores/imageStore';
 import { errorStore } from '../stores/errorStore';
 import { loadingStore } from '../stores/loadingStore';
-import type { ImageMetadata, ImageFormat } from '../types';
+import type { ImageMetadata, ImageFormat, MissingImage, RelinkResult } from '../types';
 import { derived } from 'svelte/store';
 import { eventSystem } from '../core/events';
 import type { BusinessEvent } from '../core/events';
+import { thumbnailService } from './thumbnailService';
 
 // Export read-only store subscriptions for components
 export const images = derived(imageStore, $store => $store.images);
@@ -15,6 +16,13 @@ export const currentImage = derived(imageStore, $store =>
   $store.images.find(img => img.id === $store.currentImageId) || null
 );
 
+// 选中图片变化时预取前后的缩略图
+currentImageId.subscribe(imageId => {
+  if (imageId !== null) {
+    thumbnailService.prefetchAround(imageId);
+  }
+});
+
 // Export the raw store for debug purposes only
 export { imageStore as imageStoreRaw };
 
@@ -124,6 +132,42 @@ export const imageService = {
 		}
 	},
 
+	// 粘贴截图（Ctrl+V）作为新页面追加到项目末尾，并切换到新页面
+	async addImageFromClipboard(projectId: number, data: Uint8Array, suggestedName?: string): Promise<number | null> {
+		const taskId = loadingStore.startTask('addImage');
+		try {
+			const imageId = await coreAPI.addImageFromClipboard(projectId, data, suggestedName);
+			await this.refreshProjectImages(projectId);
+			imageStore.setCurrentImage(imageId);
+			return imageId;
+		} catch (error) {
+			const message = error && typeof error === 'object' && 'error' in error ? String(error.error) : '粘贴图片失败';
+			errorStore.setError(message);
+			return null;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 处理全局paste事件：输入框内的粘贴和不含图片的剪贴板不处理
+	async handlePasteEvent(event: ClipboardEvent, projectId: number | null): Promise<number | null> {
+		if (projectId === null) return null;
+		const target = event.target as HTMLElement | null;
+		if (target && (target.isContentEditable || ['INPUT', 'TEXTAREA'].includes(target.tagName))) return null;
+		const file = Array.from(event.clipboardData?.files ?? []).find(f => f.type === 'image/png' || f.type === 'image/jpeg');
+		if (!file) {
+			// 桌面端截图工具放入的图片可能不在paste事件中，改为读取系统剪贴板（Web端返回null）
+			if (event.clipboardData?.types.includes('text/plain')) return null;
+			const data = await coreAPI.readClipboardImage();
+			return data ? this.addImageFromClipboard(projectId, data) : null;
+		}
+		event.preventDefault();
+		const data = new Uint8Array(await file.arrayBuffer());
+		// 截图的文件名通常是image.png，没有意义，交给core生成pasted-001.png
+		const suggestedName = file.name && file.name !== 'image.png' ? file.name : undefined;
+		return this.addImageFromClipboard(projectId, data, suggestedName);
+	},
+
 	async addImageFromPath(projectId: number, path: string): Promise<number | null> {
 		const taskId = loadingStore.startTask('addImage');
 		try {
@@ -176,6 +220,33 @@ export const imageService = {
 		}
 	},
 
+	async setImageReviewed(imageId: number, reviewed: boolean, note?: string | null): Promise<boolean> {
+		try {
+			// 缩略图栏的勾选由image:review_changed事件更新
+			return await coreAPI.setImageReviewed(imageId, reviewed, note);
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to set image review state';
+			errorStore.setError(message);
+			throw error;
+		}
+	},
+
+	async setImageLocked(imageId: number, locked: boolean): Promise<boolean> {
+		try {
+			// 缩略图栏的锁由image:locked_changed事件更新
+			return await coreAPI.setImageLocked(imageId, locked);
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to set image lock';
+			errorStore.setError(message);
+			throw error;
+		}
+	},
+
+	async renameImage(imageId: number, newName: string): Promise<boolean> {
+		// 缩略图栏的名称由image:renamed事件更新，重名等错误交给调用方提示
+		return coreAPI.renameImage(imageId, newName);
+	},
+
 	async reorderImages(projectId: number, imageIds: number[]): Promise<boolean> {
 		const taskId = loadingStore.startTask('reorderImages');
 		try {
@@ -193,6 +264,60 @@ export const imageService = {
 		}
 	},
 
+	// 按文件名自然排序（1, 2, 10），可撤销
+	async reorderImagesNatural(projectId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('reorderImages');
+		try {
+			const success = await coreAPI.reorderImagesNatural(projectId);
+			if (success) {
+				await this.refreshProjectImages(projectId);
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to reorder images';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 项目文件夹被移动后，文件已不存在的图片
+	async getMissingImages(projectId: number): Promise<MissingImage[]> {
+		return coreAPI.getMissingImages(projectId);
+	},
+
+	async relinkImage(imageId: number, path: string): Promise<RelinkResult> {
+		return coreAPI.relinkImage(imageId, path);
+	},
+
+	// 在选择的文件夹中按文件名（不含扩展名）查找丢失的图片并逐张重新链接（仅桌面端）
+	async relinkFromFolder(folder: string, missing: MissingImage[]): Promise<{ relinked: RelinkResult[]; notFound: MissingImage[] }> {
+		const { tauriAPI } = await import('../core/tauri');
+		const { join } = await import('@tauri-apps/api/path');
+		const fileName = (image: MissingImage) => image.name ?? image.path.split(/[/\\]/).pop() ?? '';
+		const stem = (name: string) => name.replace(/\.[^./\\]+$/, '');
+
+		// scan_directory_for_images扫描给定文件所在的目录
+		const candidates = await tauriAPI.scanDirectoryForImages(await join(folder, fileName(missing[0])), missing.map(fileName));
+		const relinked: RelinkResult[] = [];
+		const notFound: MissingImage[] = [];
+		for (const image of missing) {
+			const candidate = candidates.find((path) => stem(path.split(/[/\\]/).pop() ?? '') === stem(fileName(image)));
+			if (!candidate) {
+				notFound.push(image);
+				continue;
+			}
+			try {
+				relinked.push(await coreAPI.relinkImage(image.image_id, candidate));
+			} catch (error) {
+				console.error(`Failed to relink image ${image.image_id}:`, error);
+				notFound.push(image);
+			}
+		}
+		return { relinked, notFound };
+	},
+
 	// Navigation
 	setCurrentImage(imageId: number): void {
 		imageStore.setCurrentImage(imageId);
@@ -215,6 +340,11 @@ export const imageService = {
 		return imageLoader.getImageUrl(imageId);
 	},
 
+	// Page info (DPI, color mode, byte size) fetched from core without image data
+	async getImageMetadata(imageId: number): Promise<ImageMetadata | null> {
+		return coreAPI.getImageMetadata(imageId);
+	},
+
 	// Store getters (for components)
 	getCurrentImage(): ImageMetadata | null {
 		return imageStore.getCurrentImage();
@@ -281,6 +411,42 @@ export const imageService = {
 				const data = event.data as { project_id: number; image_id: number };
 				// Remove the image from the store
 				imageStore.removeImage(data.image_id);
+			} else if (event.event_name === 'image:missing') {
+				// 打开项目的流程结束后会自己检查，这里只处理没有其他对话框时的情况
+				const data = event.data as { project_id: number; images: MissingImage[] };
+				import('./modalService').then(({ modalService }) => {
+					if (modalService.isModalActive(null)) {
+						modalService.showModal('relinkImages', { projectId: data.project_id, missingImages: data.images });
+					}
+				});
+			} else if (event.event_name === 'image:relinked') {
+				const data = event.data as { image_id: number };
+				if (imageStore.getCurrentImageId() === data.image_id) {
+					import('./imageLoaderService').then(({ imageLoaderService }) => {
+						imageLoaderService.loadImage(data.image_id);
+					});
+				}
+			} else if (event.event_name === 'image:review_changed') {
+				const data = event.data as { image_id: number; reviewed: boolean; review_note: string | null };
+				imageStore.updateImage(data.image_id, { reviewed: data.reviewed, review_note: data.review_note });
+			} else if (event.event_name === 'image:locked_changed') {
+				const data = event.data as { image_id: number; locked: boolean };
+				imageStore.updateImage(data.image_id, { locked: data.locked });
+			} else if (event.event_name === 'image:renamed') {
+				const data = event.data as { image_id: number; name: string | null };
+				imageStore.updateImage(data.image_id, { name: data.name ?? undefined });
+			} else if (event.event_name === 'image:file_changed') {
+				// 图片文件在磁盘上被替换，更新尺寸并重新加载画布
+				const data = event.data as { image_id: number; width: number | null; height: number | null };
+				imageStore.updateImage(data.image_id, {
+					...(data.width !== null && { width: data.width }),
+					...(data.height !== null && { height: data.height })
+				});
+				if (imageStore.getCurrentImageId() === data.image_id) {
+					import('./imageLoaderService').then(({ imageLoaderService }) => {
+						imageLoaderService.loadImage(data.image_id);
+					});
+				}
 			}
 		});
 
@@ -1,6 +1,7 @@
 import { writable, derived, get } from 'svelte/store';
 import { imageViewerActions, imageViewerStore } from '../stores/imageViewerStore';
 import type { TransformState, ZoomMode } from '../stores/imageViewerStore';
+import type { ImageViewState } from '../types';
 
 // Export the raw store for debug purposes only
 export { imageViewerStore as imageViewerStoreRaw };
@@ -37,6 +38,9 @@ class ImageViewerService {
 		dynamicMaxScale: ZOOM_CONSTANTS.DEFAULT_MAX_SCALE
 	});
 
+	// 切换图片时从核心取回的查看状态，图片加载后代替适应屏幕应用
+	private pendingViewState: ImageViewState | null = null;
+
 	// Public readable stores
 	public isDragging = derived(this.viewerState, $state => $state.isDragging);
 	public isTransitioning = derived(this.viewerState, $state => $state.isTransitioning);
@@ -245,6 +249,22 @@ class ImageViewerService {
 		this.requestRecenter();
 	}
 
+	/**
+	 * Apply a saved view state on the next image load instead of the zoom mode
+	 */
+	restoreViewState(state: ImageViewState | null): void {
+		this.pendingViewState = state;
+	}
+
+	/**
+	 * Take the pending view state, if any
+	 */
+	takePendingViewState(): ImageViewState | null {
+		const state = this.pendingViewState;
+		this.pendingViewState = null;
+		return state;
+	}
+
 	/**
 	 * Request recenter
 	 */
@@ -0,0 +1,162 @@
+import { writable, derived, get } from 'svelte/store';
+import { coreAPI } from '../core/adapter';
+import { isTauri } from '../core/tauri';
+import { eventSystem } from '../core/events';
+import type { BusinessEvent } from '../core/events';
+import type { AcceleratorError, KeyBinding, MenuItemDefinition, MenuSection } from '../types';
+import { keyboardShortcutService, type Shortcut } from './keyboardShortcutService';
+
+// Web端快捷键配置保存在localStorage，桌面端由core保存在应用数据目录
+const STORAGE_KEY = 'keymap';
+
+const bindingsStore = writable<KeyBinding[]>([]);
+// core的菜单定义，虚拟菜单栏的文本和快捷键都从这里读取
+const menuStore = writable<MenuSection[]>([]);
+
+// 菜单项id -> 显示文本
+export const menuLabels = derived(menuStore, ($sections) => {
+	const labels: Record<string, string> = {};
+	const collect = (items: MenuItemDefinition[]) => {
+		for (const item of items) {
+			labels[item.id] = item.label;
+			collect(item.items);
+		}
+	};
+	for (const section of $sections) {
+		labels[section.id] = section.label;
+		collect(section.items);
+	}
+	return labels;
+});
+
+// action -> accelerator 映射，供菜单显示
+export const keymap = derived(bindingsStore, ($bindings) => {
+	const map: Record<string, string | null> = {};
+	for (const binding of $bindings) {
+		map[binding.action] = binding.accelerator;
+	}
+	return map;
+});
+
+function persistWeb(bindings: KeyBinding[]) {
+	if (isTauri() || typeof localStorage === 'undefined') return;
+	const overrides: Record<string, string | null> = {};
+	for (const binding of bindings) {
+		if (binding.accelerator !== binding.default_accelerator) {
+			overrides[binding.action] = binding.accelerator;
+		}
+	}
+	localStorage.setItem(STORAGE_KEY, JSON.stringify(overrides));
+}
+
+let initialized = false;
+
+export const keymapService = {
+	bindings: { subscribe: bindingsStore.subscribe },
+	menu: { subscribe: menuStore.subscribe },
+
+	async init() {
+		if (initialized) return;
+		initialized = true;
+
+		if (!isTauri() && typeof localStorage !== 'undefined') {
+			const stored = localStorage.getItem(STORAGE_KEY);
+			if (stored) {
+				try {
+					const warnings = await coreAPI.loadKeymap(stored);
+					warnings.forEach((warning) => console.warn(warning));
+				} catch (error) {
+					console.error('Failed to load keymap:', error);
+				}
+			}
+		}
+
+		bindingsStore.set(await coreAPI.getKeymap());
+		menuStore.set(await coreAPI.getMenuDefinition());
+
+		// 任一端修改快捷键后同步（包括macOS原生菜单触发的修改）
+		eventSystem.addBusinessEventHandler((event: BusinessEvent) => {
+			if (event.event_name === 'keymap:changed') {
+				const data = event.data as { bindings?: KeyBinding[] };
+				if (data?.bindings) {
+					bindingsStore.set(data.bindings);
+				}
+				coreAPI.getMenuDefinition().then((sections) => menuStore.set(sections));
+			}
+		});
+	},
+
+	// accelerator为null表示取消绑定；冲突或无效时抛出错误
+	async setBinding(action: string, accelerator: string | null) {
+		const bindings = await coreAPI.setKeymap({ [action]: accelerator });
+		bindingsStore.set(bindings);
+		persistWeb(bindings);
+		return bindings;
+	},
+
+	// 修改单个菜单项的快捷键，冲突时返回的error中有占用该快捷键的菜单项
+	async setAccelerator(itemId: string, accelerator: string | null): Promise<AcceleratorError | null> {
+		const { sections, error } = await coreAPI.setAccelerator(itemId, accelerator);
+		if (error) return error;
+		if (sections) menuStore.set(sections);
+		const bindings = await coreAPI.getKeymap();
+		bindingsStore.set(bindings);
+		persistWeb(bindings);
+		return null;
+	},
+
+	async reset() {
+		const bindings = await coreAPI.resetKeymap();
+		bindingsStore.set(bindings);
+		persistWeb(bindings);
+		return bindings;
+	},
+
+	getAccelerator(action: string): string | null {
+		return get(keymap)[action] ?? null;
+	},
+
+	// 把快捷键字符串转为键盘快捷键注册参数，CmdOrCtrl在macOS上为Cmd，其他平台为Ctrl
+	toShortcut(accelerator: string, handler: Shortcut['handler']): Shortcut | null {
+		const tokens = accelerator.split('+');
+		const key = tokens.pop();
+		if (!key) return null;
+		const keyNames: Record<string, string> = { Left: 'ArrowLeft', Right: 'ArrowRight', Up: 'ArrowUp', Down: 'ArrowDown', Space: ' ' };
+		const isMac = keyboardShortcutService.isMacPlatform();
+		const ctrl = tokens.includes('CmdOrCtrl') || (isMac ? tokens.includes('Cmd') : tokens.includes('Ctrl'));
+		return {
+			key: keyNames[key] ?? key.toLowerCase(),
+			ctrl,
+			shift: tokens.includes('Shift'),
+			alt: tokens.includes('Alt'),
+			handler
+		};
+	},
+
+	// 按平台格式化快捷键显示，如 CmdOrCtrl+Shift+S -> Ctrl+Shift+S / ⌘⇧S
+	formatAccelerator(accelerator: string | null | undefined): string {
+		if (!accelerator) return '';
+		const { modifierKey, shiftKey, altKey, keySeparator } = keyboardShortcutService.getModifierSymbols();
+		const isMac = keyboardShortcutService.isMacPlatform();
+		const keyNames: Record<string, string> = { Left: '←', Right: '→', Up: '↑', Down: '↓' };
+		return accelerator
+			.split('+')
+			.map((token) => {
+				switch (token) {
+					case 'CmdOrCtrl':
+						return modifierKey;
+					case 'Cmd':
+						return isMac ? '⌘' : 'Win';
+					case 'Ctrl':
+						return isMac ? '⌃' : 'Ctrl';
+					case 'Alt':
+						return altKey;
+					case 'Shift':
+						return shiftKey;
+					default:
+						return keyNames[token] ?? token;
+				}
+			})
+			.join(keySeparator);
+	}
+};
@@ -4,7 +4,7 @@ import { markerStore } from '../stores/markerStore';
 import { errorStore } from '../stores/errorStore';
 import { loadingStore } from '../stores/loadingStore';
 import { eventService } from './eventService';
-import type { Marker } from '../types';
+import type { Marker, MarkerGeometry, ReplaceReport, TranslationIssue } from '../types';
 import { derived } from 'svelte/store';
 
 // Export read-only store subscriptions for components
@@ -132,6 +132,43 @@ export const markerService = {
 		markerStore.updateMarkerGeometry(markerId, x, y, width, height);
 	},
 
+	// 开始拖拽手势：之后的中间位置只写入后端存储，撤销在结束时统一记录
+	async beginMarkerDrag(markerId: number): Promise<boolean> {
+		try {
+			return await coreAPI.beginMarkerDrag(markerId);
+		} catch (error) {
+			console.error('Failed to begin marker drag:', error);
+			return false;
+		}
+	},
+
+	// 拖拽中的位置更新（乐观更新UI，后端更新不等待结果，拖出图片边缘时限制到边缘）
+	dragMarkerTo(markerId: number, geometry: MarkerGeometry): void {
+		if (geometry.type === 'point') {
+			markerStore.updateMarkerPosition(markerId, geometry.x, geometry.y);
+			void coreAPI.updatePointMarkerPosition(markerId, geometry.x, geometry.y, 'clamp');
+		} else {
+			markerStore.updateMarkerGeometry(markerId, geometry.x, geometry.y, geometry.width, geometry.height);
+			void coreAPI.updateRectangleMarkerGeometry(markerId, geometry.x, geometry.y, geometry.width, geometry.height, 'clamp');
+		}
+	},
+
+	// 结束拖拽手势，传入初始几何即可取消本次拖拽
+	async endMarkerDrag(markerId: number, finalGeometry: MarkerGeometry, imageId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('updateMarker');
+		try {
+			const success = await coreAPI.endMarkerDrag(markerId, finalGeometry);
+			await this.loadImageMarkers(imageId);
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to finish marker drag';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
 	// Update rectangle marker geometry
 	async updateRectangleMarkerGeometry(markerId: number, x: number, y: number, width: number, height: number, imageId: number): Promise<boolean> {
 		const taskId = loadingStore.startTask('updateMarkerGeometry');
@@ -150,6 +187,41 @@ export const markerService = {
 		}
 	},
 
+	// Project-wide find & replace; returns changed marker ids so the UI can highlight them
+	async replaceInTranslations(
+		projectId: number,
+		find: string,
+		replace: string,
+		options: { caseSensitive: boolean; wholeWord: boolean },
+		imageId: number | null
+	): Promise<ReplaceReport> {
+		const taskId = loadingStore.startTask('replaceInTranslations');
+		try {
+			const report = await coreAPI.replaceInTranslations(projectId, find, replace, options.caseSensitive, options.wholeWord);
+			if (imageId !== null && report.changed_marker_ids.length > 0) {
+				await this.loadImageMarkers(imageId);
+			}
+			return report;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to replace in translations';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 翻译一致性检查，返回问题列表由UI渲染
+	async analyzeTranslations(projectId: number, sourceSimilarity?: number): Promise<TranslationIssue[]> {
+		try {
+			return await coreAPI.analyzeTranslations(projectId, sourceSimilarity);
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to analyze translations';
+			errorStore.setError(message);
+			return [];
+		}
+	},
+
 	// Update marker translation
 	async updateMarkerTranslation(markerId: number, translation: string, imageId: number): Promise<boolean> {
 		const taskId = loadingStore.startTask('updateMarker');
@@ -187,6 +259,78 @@ export const markerService = {
 		}
 	},
 
+	// 更新字号倍率，null清除
+	async updateMarkerSizeHint(markerId: number, sizeHint: number | null, imageId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('updateMarker');
+		try {
+			const success = await coreAPI.updateMarkerSizeHint(markerId, sizeHint);
+			if (success) {
+				await this.loadImageMarkers(imageId);
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to update marker size hint';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 更新标签，null或空字符串清除
+	async updateMarkerTag(markerId: number, tag: string | null, imageId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('updateMarker');
+		try {
+			const success = await coreAPI.updateMarkerTag(markerId, tag);
+			if (success) {
+				await this.loadImageMarkers(imageId);
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to update marker tag';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 更新译者备注，null或空字符串清除
+	async updateMarkerNote(markerId: number, note: string | null, imageId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('updateMarker');
+		try {
+			const success = await coreAPI.updateMarkerNote(markerId, note);
+			if (success) {
+				await this.loadImageMarkers(imageId);
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to update marker note';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 回退到译文历史中的某一条，回退本身可以撤销
+	async revertTranslation(markerId: number, historyIndex: number, imageId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('updateMarker');
+		try {
+			const success = await coreAPI.revertTranslation(markerId, historyIndex);
+			if (success) {
+				await this.loadImageMarkers(imageId);
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to revert translation';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
 	// Move marker order within image
 	async moveMarkerOrder(markerId: number, newIndex: number, imageId: number): Promise<boolean> {
 		const taskId = loadingStore.startTask('moveMarker');
@@ -205,6 +349,42 @@ export const markerService = {
 		}
 	},
 
+	// 把标记序号压缩为连续的1..N，projectId不为空时处理整个项目
+	async compactMarkerIndices(imageId: number, projectId?: number): Promise<number> {
+		const taskId = loadingStore.startTask('compactMarkers');
+		try {
+			const count = projectId !== undefined
+				? await coreAPI.compactProjectMarkerIndices(projectId)
+				: await coreAPI.compactMarkerIndices(imageId);
+			if (count > 0) {
+				await this.loadImageMarkers(imageId);
+			}
+			return count;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to compact marker indices';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
+	// 按项目的阅读方向给图片的标记重新编号（可撤销）
+	async renumberByReadingOrder(imageId: number): Promise<number> {
+		try {
+			const count = await coreAPI.renumberMarkersByReadingOrder(imageId);
+			if (count > 0) {
+				await this.loadImageMarkers(imageId);
+			}
+			return count;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to renumber markers';
+			errorStore.setError(message);
+			throw error;
+		}
+	},
+
+
 	// Remove a marker
 	async removeMarker(imageId: number, markerId: number): Promise<boolean> {
 		const taskId = loadingStore.startTask('removeMarker');
@@ -286,6 +466,42 @@ export const markerService = {
 		}
 	},
 
+	// 把多个矩形标记合并为外接矩形（可撤销），返回新标记ID
+	async mergeRectangles(imageId: number, markerIds: number[]): Promise<number> {
+		try {
+			const mergedId = await coreAPI.mergeRectangleMarkers(markerIds);
+			await this.loadImageMarkers(imageId);
+			markerStore.setSelectedMarker(mergedId);
+			return mergedId;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : String(error);
+			errorStore.setError(message);
+			throw error;
+		}
+	},
+
+	// Convert polygon marker to its bounding rectangle
+	async convertPolygonToRectangle(markerId: number): Promise<boolean> {
+		const taskId = loadingStore.startTask('convertMarker');
+		try {
+			const success = await coreAPI.convertPolygonToRectangleMarker(markerId);
+			if (success) {
+				// 重新加载当前图片的markers
+				const currentImageIdValue = get(currentImageId);
+				if (currentImageIdValue !== null) {
+					await this.loadImageMarkers(currentImageIdValue);
+				}
+			}
+			return success;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : 'Failed to convert marker';
+			errorStore.setError(message);
+			throw error;
+		} finally {
+			loadingStore.endTask(taskId);
+		}
+	},
+
 	// Batch operations
 	async duplicateMarker(markerId: number, imageId: number): Promise<number | null> {
 		const marker = markerStore.getMarkerById(markerId);
@@ -404,89 +620,65 @@ export const markerService = {
 		return markerStore.hasMarkers();
 	},
 
-	// Navigate to next marker
-	navigateToNextMarker(): void {
-		const markersValue = get(markers);
+	// Navigate to next marker (reading order follows the project's reading direction)
+	async navigateToNextMarker(): Promise<void> {
 		const imagesValue = get(images);
-		const currentImageIndexValue = get(currentImageIndex);
-		const isLastPage = currentImageIndexValue === imagesValue.length;
-		
-		if (markersValue.length === 0) {
-			if (!isLastPage) {
-				// Import imageService dynamically to avoid circular dependency
-				import('./imageService').then(({ imageService }) => {
-					imageService.nextImage();
-				});
-			}
-			return;
-		}
-		
-		const currentSelected = get(selectedMarker);
-		const currentSelectedId = currentSelected?.id || null;
-		const sortedMarkers = [...markersValue].sort((a, b) => a.imageIndex - b.imageIndex);
-		
-		if (!currentSelectedId) {
-			this.setSelectedMarker(sortedMarkers[0].id);
-		} else {
-			const currentIndex = sortedMarkers.findIndex(m => m.id === currentSelectedId);
-			if (currentIndex === -1 || currentIndex === sortedMarkers.length - 1) {
-				if (!isLastPage) {
-					import('./imageService').then(({ imageService }) => {
-						imageService.nextImage();
-						setTimeout(() => {
-							const newMarkers = get(markers);
-							if (newMarkers.length > 0) {
-								const firstMarker = [...newMarkers].sort((a, b) => a.imageIndex - b.imageIndex)[0];
-								this.setSelectedMarker(firstMarker.id);
-							}
-						});
-					});
-				}
-			} else {
-				this.setSelectedMarker(sortedMarkers[currentIndex + 1].id);
+		const isLastPage = get(currentImageIndex) === imagesValue.length;
+		const imageId = get(currentImageId);
+		const currentSelectedId = get(selectedMarker)?.id ?? null;
+
+		const nextId = imageId !== null && get(markers).length > 0
+			? await this.selectNextMarker(imageId, currentSelectedId, false)
+			: null;
+		if (nextId !== null) {
+			this.setSelectedMarker(nextId);
+		} else if (!isLastPage) {
+			// Import imageService dynamically to avoid circular dependency
+			const { imageService } = await import('./imageService');
+			imageService.nextImage();
+			if (currentSelectedId !== null) {
+				setTimeout(() => this.selectEdgeMarker(false));
 			}
 		}
 	},
 
 	// Navigate to previous marker
-	navigateToPrevMarker(): void {
-		const markersValue = get(markers);
-		const currentImageIndexValue = get(currentImageIndex);
-		const isFirstPage = currentImageIndexValue === 1;
-		
-		if (markersValue.length === 0) {
-			if (!isFirstPage) {
-				import('./imageService').then(({ imageService }) => {
-					imageService.prevImage();
-				});
+	async navigateToPrevMarker(): Promise<void> {
+		const isFirstPage = get(currentImageIndex) === 1;
+		const imageId = get(currentImageId);
+		const currentSelectedId = get(selectedMarker)?.id ?? null;
+
+		const prevId = imageId !== null && get(markers).length > 0
+			? await this.selectNextMarker(imageId, currentSelectedId, true)
+			: null;
+		if (prevId !== null) {
+			this.setSelectedMarker(prevId);
+		} else if (!isFirstPage) {
+			const { imageService } = await import('./imageService');
+			imageService.prevImage();
+			if (currentSelectedId !== null) {
+				setTimeout(() => this.selectEdgeMarker(true));
 			}
-			return;
 		}
-		
-		const currentSelected = get(selectedMarker);
-		const currentSelectedId = currentSelected?.id || null;
-		const sortedMarkers = [...markersValue].sort((a, b) => a.imageIndex - b.imageIndex);
-		
-		if (!currentSelectedId) {
-			this.setSelectedMarker(sortedMarkers[sortedMarkers.length - 1].id);
-		} else {
-			const currentIndex = sortedMarkers.findIndex(m => m.id === currentSelectedId);
-			if (currentIndex === -1 || currentIndex === 0) {
-				if (!isFirstPage) {
-					import('./imageService').then(({ imageService }) => {
-						imageService.prevImage();
-						setTimeout(() => {
-							const newMarkers = get(markers);
-							if (newMarkers.length > 0) {
-								const sortedNewMarkers = [...newMarkers].sort((a, b) => a.imageIndex - b.imageIndex);
-								this.setSelectedMarker(sortedNewMarkers[sortedNewMarkers.length - 1].id);
-							}
-						});
-					});
-				}
-			} else {
-				this.setSelectedMarker(sortedMarkers[currentIndex - 1].id);
-			}
+	},
+
+	// 选中当前图片按阅读顺序的第一个（last为true时最后一个）标记
+	async selectEdgeMarker(last: boolean): Promise<void> {
+		const imageId = get(currentImageId);
+		if (imageId === null || get(markers).length === 0) return;
+		const markerId = await this.selectNextMarker(imageId, null, last);
+		if (markerId !== null) {
+			this.setSelectedMarker(markerId);
+		}
+	},
+
+	// 按阅读顺序的下一个/上一个标记，到头时返回null
+	async selectNextMarker(imageId: number, currentMarkerId: number | null, backward: boolean): Promise<number | null> {
+		try {
+			return await coreAPI.selectNextMarker(imageId, currentMarkerId, backward);
+		} catch (error) {
+			eventService.error('Failed to select next marker', error);
+			return null;
 		}
 	},
 
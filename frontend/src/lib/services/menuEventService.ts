@@ -1,4 +1,4 @@
-import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { platformService } from './platformService';
 import { modalStore } from './modalService';
 import { projectService, currentProjectId, projects } from './projectService';
@@ -7,7 +7,6 @@ import { markerService } from './markerService';
 import { undoRedoService } from './undoRedoService';
 import { layoutActions } from './layoutService';
 import { menuService } from './menuService';
-import { windowService } from './windowService';
 import { get } from 'svelte/store';
 
 type UnlistenFn = () => void;
@@ -15,86 +14,110 @@ type UnlistenFn = () => void;
 class MenuEventService {
 	private unlisteners: UnlistenFn[] = [];
 
+	// macOS原生菜单和Windows虚拟菜单都由后端发出相同的事件
 	async initialize() {
-		if (!platformService.isTauri() || platformService.getPlatform() !== 'macos') {
+		if (!platformService.isTauri()) {
 			return;
 		}
 
 		try {
+			// 后端只把菜单事件发给目标窗口，在当前窗口上监听
+			const currentWindow = getCurrentWebviewWindow();
+
 			// File menu events
-			this.unlisteners.push(await listen('menu:file:new-project', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:file:new-project', () => {
 				this.handleNewProject();
 			}));
 
-			this.unlisteners.push(await listen('menu:file:open-project', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:file:open-project', () => {
 				this.handleOpenProject();
 			}));
 
-			this.unlisteners.push(await listen('menu:file:save', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:file:save', () => {
 				this.handleSave();
 			}));
 
-			this.unlisteners.push(await listen('menu:file:save-as', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:file:save-as', () => {
 				this.handleSaveAs();
 			}));
 
-			this.unlisteners.push(await listen('menu:file:export', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:file:save-as-copy', () => {
+				this.handleSaveAs(true);
+			}));
+
+			this.unlisteners.push(await currentWindow.listen('menu:file:export', () => {
 				this.handleExport();
 			}));
 
+			this.unlisteners.push(await currentWindow.listen('menu:file:export-annotated', () => {
+				this.handleExportAnnotated();
+			}));
+
+			this.unlisteners.push(await currentWindow.listen('menu:file:export-typesetting-csv', () => {
+				this.handleExportTypesetting('csv');
+			}));
+
+			this.unlisteners.push(await currentWindow.listen('menu:file:export-typesetting-json', () => {
+				this.handleExportTypesetting('json');
+			}));
+
+			this.unlisteners.push(await currentWindow.listen('menu:file:import-typesetting', () => {
+				this.handleImportTypesetting();
+			}));
+
 			// Edit menu events
-			this.unlisteners.push(await listen('menu:edit:undo', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:edit:undo', () => {
 				this.handleUndo();
 			}));
 
-			this.unlisteners.push(await listen('menu:edit:redo', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:edit:redo', () => {
 				this.handleRedo();
 			}));
 
-			this.unlisteners.push(await listen('menu:edit:next-marker', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:edit:next-marker', () => {
 				this.handleNextMarker();
 			}));
 
-			this.unlisteners.push(await listen('menu:edit:prev-marker', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:edit:prev-marker', () => {
 				this.handlePrevMarker();
 			}));
 
 			// Window menu events
-			this.unlisteners.push(await listen('menu:window:minimize', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:minimize', () => {
 				// Window minimize is handled by backend
 			}));
 
-			this.unlisteners.push(await listen('menu:window:maximize', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:maximize', () => {
 				// Window maximize is handled by backend
 			}));
 
-			this.unlisteners.push(await listen('menu:window:translation', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:translation', () => {
 				this.handleToggleTranslation();
 			}));
 
-			this.unlisteners.push(await listen('menu:window:thumbnail', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:thumbnail', () => {
 				this.handleToggleThumbnail();
 			}));
 
-			this.unlisteners.push(await listen('menu:window:dictionary', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:dictionary', () => {
 				this.handleToggleDictionary();
 			}));
 
-			this.unlisteners.push(await listen('menu:window:project-config', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:window:project-config', () => {
 				this.handleToggleProjectConfig();
 			}));
 
 			// View menu events
-			this.unlisteners.push(await listen('menu:view:prev-image', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:view:prev-image', () => {
 				this.handlePrevImage();
 			}));
 
-			this.unlisteners.push(await listen('menu:view:next-image', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:view:next-image', () => {
 				this.handleNextImage();
 			}));
 
 			// Project menu events
-			this.unlisteners.push(await listen('menu:project:select', (event: { payload: unknown }) => {
+			this.unlisteners.push(await currentWindow.listen('menu:project:select', (event: { payload: unknown }) => {
 				const projectIndex = event.payload;
 				if (typeof projectIndex === 'number') {
 					this.handleSelectProject(projectIndex);
@@ -102,22 +125,18 @@ class MenuEventService {
 			}));
 
 			// More menu events
-			this.unlisteners.push(await listen('menu:more:snapshots', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:more:snapshots', () => {
 				this.handleShowSnapshots();
 			}));
 
-			this.unlisteners.push(await listen('menu:more:version-info', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:more:version-info', () => {
 				this.handleShowAbout();
 			}));
 
-			this.unlisteners.push(await listen('menu:more:software-license', () => {
+			this.unlisteners.push(await currentWindow.listen('menu:more:software-license', () => {
 				this.handleShowLicense();
 			}));
 
-			this.unlisteners.push(await listen('menu:more:quit', async () => {
-				await this.handleQuit();
-			}));
-
 		} catch (error) {
 			console.error('Error setting up menu listeners:', error);
 		}
@@ -128,6 +147,19 @@ class MenuEventService {
 		this.unlisteners = [];
 	}
 
+	/**
+	 * 由前端虚拟菜单触发菜单项，经后端映射为与原生菜单相同的事件
+	 */
+	async emitMenuEvent(id: string) {
+		try {
+			const { invoke } = await import('@tauri-apps/api/core');
+			await invoke('emit_menu_event', { id });
+		} catch (error) {
+			console.error(`Failed to emit menu event ${id}:`, error);
+			menuService.closeAllMenus();
+		}
+	}
+
 	// File menu handlers
 	private handleNewProject() {
 		const projectsValue = get(projects);
@@ -151,10 +183,10 @@ class MenuEventService {
 		menuService.closeAllMenus();
 	}
 
-	private async handleSaveAs() {
+	private async handleSaveAs(asCopy = false) {
 		const projectId = get(currentProjectId);
 		if (projectId) {
-			await projectService.handleSaveAs(projectId);
+			await projectService.handleSaveAs(projectId, asCopy);
 		}
 		menuService.closeAllMenus();
 	}
@@ -167,6 +199,30 @@ class MenuEventService {
 		menuService.closeAllMenus();
 	}
 
+	private async handleExportAnnotated() {
+		const projectId = get(currentProjectId);
+		if (projectId) {
+			await projectService.handleExportAnnotatedImages(projectId);
+		}
+		menuService.closeAllMenus();
+	}
+
+	private async handleExportTypesetting(format: 'csv' | 'json') {
+		const projectId = get(currentProjectId);
+		if (projectId) {
+			await projectService.handleExportTypesetting(projectId, format);
+		}
+		menuService.closeAllMenus();
+	}
+
+	private async handleImportTypesetting() {
+		menuService.closeAllMenus();
+		const projectId = get(currentProjectId);
+		if (projectId) {
+			await projectService.handleImportTypesetting(projectId);
+		}
+	}
+
 	// Edit menu handlers
 	private async handleUndo() {
 		try {
@@ -252,10 +308,6 @@ class MenuEventService {
 		modalStore.showModal('license');
 		menuService.closeAllMenus();
 	}
-
-	private async handleQuit() {
-		await windowService.closeWindow();
-	}
 }
 
 export const menuEventService = new MenuEventService();
\ No newline at end of file
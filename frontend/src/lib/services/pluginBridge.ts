@@ -31,6 +31,7 @@ class PluginBridge {
 
     // Local service registries for WASM plugins
     private ocrServices: Map<string, any> = new Map();
+    private detectionServices: Map<string, any> = new Map();
     private translationServices: Map<string, any> = new Map();
     private serviceToPlugin: Map<string, string> = new Map();
 
@@ -88,9 +89,29 @@ class PluginBridge {
                     if (data.style) {
                         await coreAPI.updateMarkerStyle(marker_id, data.style.overlayText, data.style.horizontal);
                     }
+                    // 字号和标签，null或空字符串表示清除
+                    const sizeHint = 'size_hint' in data ? data.size_hint : data.style?.sizeHint;
+                    if (sizeHint !== undefined) {
+                        await coreAPI.updateMarkerSizeHint(marker_id, sizeHint);
+                    }
+                    const tag = 'tag' in data ? data.tag : data.style?.tag;
+                    if (tag !== undefined) {
+                        await coreAPI.updateMarkerTag(marker_id, tag || null);
+                    }
                     return { success: true };
                 }
                 
+                case 'move_marker_to_image': {
+                    const { marker_id, target_image_id, geometry, position } = params;
+                    const success = await coreAPI.moveMarkerToImage(
+                        Number(marker_id),
+                        Number(target_image_id),
+                        geometry,
+                        position ?? undefined
+                    );
+                    return { success };
+                }
+                
                 case 'delete_marker': {
                     const { marker_id, image_id } = params;
                     // Need image_id to delete marker
@@ -131,7 +152,8 @@ class PluginBridge {
                         image_ids: [], // Will be fetched separately if needed
                         file_path: null,
                         source_language: fullProject.sourceLanguage || 'japanese',
-                        target_language: fullProject.targetLanguage || 'simplifiedChinese'
+                        target_language: fullProject.targetLanguage || 'simplifiedChinese',
+                        reading_direction: fullProject.readingDirection || 'leftToRight'
                     };
                 }
                 
@@ -209,6 +231,20 @@ class PluginBridge {
                     return result;
                 }
                 
+                case 'get_image_source_kind': {
+                    const { image_id } = params;
+                    
+                    const imageId = typeof image_id === 'string' 
+                        ? parseInt(image_id, 10) 
+                        : image_id;
+                    
+                    const kind = await coreAPI.getImageSourceKind(imageId);
+                    if (!kind) {
+                        throw new Error(`Image ${imageId} not found`);
+                    }
+                    return kind;
+                }
+                
                 case 'get_image_data': {
                     const { image_id } = params;
                     
@@ -217,13 +253,9 @@ class PluginBridge {
                         ? parseInt(image_id, 10) 
                         : image_id;
                     
-                    if (isTauri()) {
-                        // Desktop: 返回文件路径，避免大数据复制
-                        const filePath = await coreAPI.getImageFilePath(imageId);
-                        if (!filePath) {
-                            throw new Error(`No file path available for image ${imageId} in desktop mode`);
-                        }
-                        
+                    // Desktop: 有文件路径时返回路径，避免大数据复制
+                    const filePath = isTauri() ? await coreAPI.getImageFilePath(imageId) : null;
+                    if (filePath) {
                         return {
                             type: 'FilePath',
                             path: filePath
@@ -263,6 +295,19 @@ class PluginBridge {
                     }
                 }
                 
+                // 原始字节，经SharedArrayBuffer通道返回时插件直接读取共享内存（get_image_shared）
+                case 'get_image_bytes': {
+                    const imageId = typeof params.image_id === 'string'
+                        ? parseInt(params.image_id, 10)
+                        : params.image_id;
+
+                    const imageData = await coreAPI.getImageBinaryData(imageId);
+                    if (!imageData) {
+                        throw new Error(`Failed to get binary data for image ${imageId}`);
+                    }
+                    return imageData instanceof Uint8Array ? imageData : new Uint8Array(imageData);
+                }
+                
                 case 'add_image': {
                     const { data } = params;
                     // 这里需要实际的图片添加API
@@ -447,6 +492,21 @@ class PluginBridge {
                     }
                 }
 
+                case 'register_detection_service': {
+                    const pluginId = params.plugin_id;
+                    const serviceInfo = params.service_info;
+
+                    if (isTauri()) {
+                        // Tauri: Services are registered in backend by plugin loader
+                        return { success: true };
+                    } else {
+                        const serviceWithPluginId = { ...serviceInfo, plugin_id: pluginId };
+                        this.detectionServices.set(serviceInfo.id, serviceWithPluginId);
+                        this.serviceToPlugin.set(serviceInfo.id, pluginId);
+                        return { success: true };
+                    }
+                }
+
                 case 'unregister_service': {
                     const serviceId = params.service_id;
 
@@ -457,6 +517,7 @@ class PluginBridge {
                         // WASM: Unregister locally
                         this.ocrServices.delete(serviceId);
                         this.translationServices.delete(serviceId);
+                        this.detectionServices.delete(serviceId);
                         this.serviceToPlugin.delete(serviceId);
                         return { success: true };
                     }
@@ -474,6 +535,10 @@ class PluginBridge {
                     return Array.from(this.translationServices.values());
                 }
 
+                case 'get_detection_services': {
+                    return Array.from(this.detectionServices.values());
+                }
+
                 default:
                     throw new Error(`Unknown bunny method: ${method}`);
             }
@@ -513,9 +578,8 @@ class PluginBridge {
                 }
 
                 case 'subscribe': {
-                    // Note: Subscription is handled differently for plugins
-                    // They will get config updates through plugin messages
-                    return { success: true, message: 'Use plugin message events for config updates' };
+                    // 插件无需订阅：配置变化总会以ConfigChanged事件发给所属插件
+                    return { success: true, message: 'Config updates are delivered as ConfigChanged events' };
                 }
 
                 default:
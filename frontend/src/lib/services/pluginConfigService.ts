@@ -38,9 +38,12 @@ interface PluginConfigState {
 
 const STORAGE_KEY_PREFIX = 'plugin_config_';
 
+type ConfigValueListener = (pluginId: string, key: string, value: any) => void;
+
 class PluginConfigService {
     private configs = writable<PluginConfigState>({});
     private changeListeners = new Map<string, Set<(config: PluginConfig) => void>>();
+    private valueListeners = new Set<ConfigValueListener>();
 
     constructor() {
         // Load saved configs on initialization
@@ -94,6 +97,8 @@ class PluginConfigService {
      * Set configuration for a plugin
      */
     async setConfig(pluginId: string, config: PluginConfig) {
+        const oldConfig = this.getConfig(pluginId);
+
         // Always save to localStorage for frontend access
         const storageKey = `${STORAGE_KEY_PREFIX}${pluginId}`;
         localStorage.setItem(storageKey, JSON.stringify(config));
@@ -115,6 +120,7 @@ class PluginConfigService {
 
         // Notify listeners
         this.notifyListeners(pluginId, config);
+        this.notifyValueChanges(pluginId, oldConfig, config);
     }
 
     /**
@@ -130,6 +136,8 @@ class PluginConfigService {
      * Delete configuration for a plugin
      */
     deleteConfig(pluginId: string) {
+        const oldConfig = this.getConfig(pluginId);
+
         // Remove from localStorage
         const storageKey = `${STORAGE_KEY_PREFIX}${pluginId}`;
         localStorage.removeItem(storageKey);
@@ -143,6 +151,7 @@ class PluginConfigService {
 
         // Notify listeners with empty config
         this.notifyListeners(pluginId, {});
+        this.notifyValueChanges(pluginId, oldConfig, {});
     }
 
     /**
@@ -252,6 +261,26 @@ class PluginConfigService {
         }
     }
 
+    /**
+     * Subscribe to individual value changes of any plugin (removed keys report null)
+     */
+    onConfigValueChanged(callback: ConfigValueListener): () => void {
+        this.valueListeners.add(callback);
+        return () => {
+            this.valueListeners.delete(callback);
+        };
+    }
+
+    // 逐个比较新旧配置的键，只通知值发生变化的键
+    private notifyValueChanges(pluginId: string, oldConfig: PluginConfig, newConfig: PluginConfig) {
+        const keys = new Set([...Object.keys(oldConfig), ...Object.keys(newConfig)]);
+        for (const key of keys) {
+            if (JSON.stringify(oldConfig[key]) === JSON.stringify(newConfig[key])) continue;
+            const value = newConfig[key] ?? null;
+            this.valueListeners.forEach(callback => callback(pluginId, key, value));
+        }
+    }
+
     /**
      * Export all plugin configurations
      */
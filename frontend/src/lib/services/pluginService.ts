@@ -1,12 +1,15 @@
 import { writable, derived, get } from 'svelte/store';
 import { pluginBridge } from './pluginBridge';
+import { coreAPI } from '../core/adapter';
 import { platformService } from './platformService';
 import { pluginStorageService } from './pluginStorageService';
 import { eventService } from './eventService';
 import { invoke } from '@tauri-apps/api/core';
 import { fetchWasmResource } from '../utils/wasmLoader';
+import { notificationStore } from '../stores/notificationStore';
+import type { CrossOriginIsolationUnavailableEvent, PluginTimeoutEvent } from '../types/events.generated';
 
-import type { ConfigSchema } from './pluginConfigService';
+import { pluginConfigService, type ConfigSchema } from './pluginConfigService';
 
 export interface PluginMetadata {
     id: string;
@@ -16,6 +19,12 @@ export interface PluginMetadata {
     author: string;
     subscribed_events: string[];
     config_schema?: ConfigSchema;
+    // 构建插件时的接口版本，原生插件由宿主检查
+    api_version?: number;
+    // 宿主拒绝加载的原因（接口版本不兼容）
+    load_error?: string;
+    // 回调超时被停用的原因，重新启用时清除
+    unhealthy?: string;
 }
 
 export type PluginSource = 'builtin' | 'uploaded' | 'external';  // external for future use (e.g., from URL)
@@ -67,6 +76,8 @@ interface PluginStates {
 class PluginService {
     private plugins = writable<Map<string, PluginInfo>>(new Map());
     private workers = new Map<string, Worker>();
+    // 拿到SharedArrayBuffer的插件，其余插件运行在降级模式
+    private sharedBufferPlugins = new Set<string>();
     private serviceCallHandlers = new Map<Worker, Map<number, any>>();
     private pluginEventsUnsubscribe?: () => void;
 
@@ -109,6 +120,19 @@ class PluginService {
         pluginBridge.subscribeToEvent('*', (event) => {
             this.dispatchEventToPlugins(event);
         });
+        pluginConfigService.onConfigValueChanged((pluginId, key, value) => {
+            this.dispatchConfigChanged(pluginId, key, value);
+        });
+    }
+
+    // 配置变化只发给所属插件；桌面端的原生插件由set_plugin_config在后端通知
+    private dispatchConfigChanged(pluginId: string, key: string, value: any) {
+        const plugin = get(this.plugins).get(pluginId);
+        if (!plugin?.enabled || (plugin.isNative && platformService.isTauri())) return;
+        plugin.worker?.postMessage({
+            type: 'DISPATCH_EVENT',
+            event: { ConfigChanged: { plugin_id: pluginId, key, value } }
+        });
     }
 
     private subscribeToPluginEvents() {
@@ -121,6 +145,26 @@ class PluginService {
                 this.handleBackendPluginChange(event.data).catch(error => {
                     console.error('[PluginService] Failed to handle plugin change event:', error);
                 });
+            } else if (event.event_name === 'plugin:timeout') {
+                // 停用后的插件列表由随后的plugins:changed同步
+                const data = event.data as PluginTimeoutEvent;
+                const name = get(this.plugins).get(data.plugin_id)?.metadata.name ?? data.plugin_id;
+                notificationStore.notify({
+                    title: '插件已停用',
+                    message: `${name} 处理${data.operation === 'message' ? '消息' : '事件'}超过 ${Math.round(data.timeout_ms / 1000)} 秒未响应，已被停用`,
+                    level: 'warning',
+                    toast: true
+                });
+            } else if (event.event_name === 'system:cross_origin_isolation_unavailable') {
+                const data = event.data as CrossOriginIsolationUnavailableEvent;
+                console.warn('[PluginService] Running without cross-origin isolation, degraded:', data.degraded);
+                notificationStore.notify({
+                    id: 'cross-origin-isolation',
+                    title: '性能受限模式',
+                    message: '当前页面未启用跨源隔离（服务器缺少 COOP/COEP 响应头），多线程处理已关闭，插件改用较慢的消息通道，部分插件功能可能不可用',
+                    level: 'warning',
+                    toast: true
+                });
             }
         });
     }
@@ -384,17 +428,7 @@ class PluginService {
         this.workers.set(pluginId, worker);
         this.serviceCallHandlers.set(worker, new Map());
         
-        // Create SharedArrayBuffer (required)
-        if (typeof SharedArrayBuffer === 'undefined') {
-            throw new Error('SharedArrayBuffer is not supported in this environment. Please ensure CORS headers are properly configured.');
-        }
-        
-        // Import and initialize SharedBufferHandler
-        const { sharedBufferHandler } = await import('./sharedBufferHandler');
-        const sharedBuffer = sharedBufferHandler.getBuffer();
-        
-        // Start monitoring requests if not already started
-        sharedBufferHandler.start();
+        const sharedBuffer = await this.getPluginSharedBuffer(pluginId);
         
         // Fetch WASM bytes in main thread (so service worker can intercept)
         const wasmBytes = await fetchWasmResource(wasmBgUrl);
@@ -449,6 +483,20 @@ class PluginService {
         });
     }
 
+    // 没有跨源隔离时SharedArrayBuffer不可用，插件以降级模式加载：服务调用改为异步消息（拿不到返回值），图片按数组传递
+    private async getPluginSharedBuffer(pluginId: string): Promise<SharedArrayBuffer | undefined> {
+        const isolated = await coreAPI.isCrossOriginIsolated();
+        if (!isolated || typeof SharedArrayBuffer === 'undefined') {
+            this.sharedBufferPlugins.delete(pluginId);
+            console.warn(`[PluginService] SharedArrayBuffer unavailable, loading ${pluginId} without synchronous service calls`);
+            return undefined;
+        }
+        const { sharedBufferHandler } = await import('./sharedBufferHandler');
+        sharedBufferHandler.start();
+        this.sharedBufferPlugins.add(pluginId);
+        return sharedBufferHandler.getBuffer();
+    }
+
     private handleWorkerMessage(pluginId: string, worker: Worker, event: MessageEvent) {
         const { type, ...data } = event.data;
         
@@ -552,6 +600,7 @@ class PluginService {
                 setTimeout(() => {
                     worker.terminate();
                     this.workers.delete(pluginId);
+                    this.sharedBufferPlugins.delete(pluginId);
                     this.serviceCallHandlers.delete(worker);
                 }, 100);
             }
@@ -1018,17 +1067,7 @@ class PluginService {
             this.workers.set(pluginId, worker);
             this.serviceCallHandlers.set(worker, new Map());
             
-            // Create SharedArrayBuffer (required)
-            if (typeof SharedArrayBuffer === 'undefined') {
-                throw new Error('SharedArrayBuffer is not supported in this environment. Please ensure CORS headers are properly configured.');
-            }
-            
-            // Import and initialize SharedBufferHandler
-            const { sharedBufferHandler } = await import('./sharedBufferHandler');
-            const sharedBuffer = sharedBufferHandler.getBuffer();
-            
-            // Start monitoring requests if not already started
-            sharedBufferHandler.start();
+            const sharedBuffer = await this.getPluginSharedBuffer(pluginId);
             
             // Transfer WASM bytes to worker
             const transferableBuffer = wasmBytes.slice(0);
@@ -1203,6 +1242,11 @@ class PluginService {
     }
 
     // Send message from one plugin to another
+    // 插件运行在Worker中（WASM）且拿到了SharedArrayBuffer，可以通过共享通道读取暂存的图片
+    canReadStagedImages(pluginId: string): boolean {
+        return this.workers.has(pluginId) && this.sharedBufferPlugins.has(pluginId);
+    }
+
     async sendPluginMessage(from: string, to: string, message: any) {
         const plugins = get(this.plugins);
         const targetPlugin = plugins.get(to);
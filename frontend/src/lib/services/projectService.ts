@@ -7,7 +7,7 @@ import { markerStore } from '../stores/markerStore';
 import { imageViewerActions } from '../stores/imageViewerStore';
 import { progressManager, type ProgressController } from '../utils/progressManager';
 import { get } from 'svelte/store';
-import type { TranslationProject, ImageFormat, Language } from '../types';
+import type { AnnotateOptions, BfValidation, LabelplusCoordinateOrigin, LabelplusOriginPreview, TypesettingFormat, TypesettingOptions, TypesettingImportOptions, TypesettingImportReport, ArchiveImportReport, IntegrityReport, TranslationProject, ImageFormat, Language, ReadingDirection } from '../types';
 import { eventService } from './eventService';
 import type { BusinessEvent } from '../core/events';
 
@@ -90,10 +90,63 @@ class ProjectService {
 					sourceLanguage: data.source_language,
 					targetLanguage: data.target_language
 				});
+			} else if (event.event_name === 'ImageAddedToProject') {
+				// 新增的图片也需要加入文件监听
+				const data = event.data as { project_id: number };
+				if (data.project_id === projectStore.getCurrentProjectId()) {
+					this.watchProjectFiles(data.project_id);
+				}
+			} else if (event.event_name === 'project:file_watch_changed') {
+				const data = event.data as { project_id: number; enabled: boolean };
+				projectStore.updateProject(data.project_id, { watchFiles: data.enabled });
+			} else if (event.event_name === 'project:settings_changed') {
+				const data = event.data as { project_id: number; setting: string; value: unknown };
+				if (data.setting === 'notes') {
+					projectStore.updateProject(data.project_id, { notes: data.value as string });
+				} else if (data.setting === 'auto_ocr_on_create') {
+					projectStore.updateProject(data.project_id, { autoOcrOnCreate: data.value as boolean });
+				} else if (data.setting === 'service_fallbacks') {
+					const fallbacks = data.value as { ocr: string[]; translation: string[] };
+					const project = get(projectStore).projects.find((p) => p.id === data.project_id);
+					projectStore.updateProject(data.project_id, {
+						defaultServices: { ...project?.defaultServices, ocrFallbacks: fallbacks.ocr, translationFallbacks: fallbacks.translation }
+					});
+				} else if (data.setting === 'keep_translation_history') {
+					projectStore.updateProject(data.project_id, { keepTranslationHistory: data.value as boolean });
+				} else if (data.setting === 'grayscale_reencode') {
+					projectStore.updateProject(data.project_id, { grayscaleReencode: data.value as boolean });
+				}
+			} else if (event.event_name === 'project:reading_direction_changed') {
+				const data = event.data as { project_id: number; reading_direction: ReadingDirection };
+				projectStore.updateProject(data.project_id, { readingDirection: data.reading_direction });
+			} else if (event.event_name === 'project:snapshot_restored') {
+				// 还原（或撤销还原）替换了标记和设置，重新读取
+				const data = event.data as { project_id: number };
+				if (data.project_id === projectStore.getCurrentProjectId()) {
+					this.reloadRestoredProject(data.project_id);
+				}
+			} else if (event.event_name === 'system:panic') {
+				// core出错后提示用户工作已保存在自动快照中，并提供另存为导出
+				const data = event.data as { message: string; location: string | null };
+				import('./modalService').then(({ modalService }) => {
+					modalService.showModal('panicRecovery', { panicMessage: data.message, panicLocation: data.location });
+				});
 			}
 		});
 	}
 
+	private async reloadRestoredProject(projectId: number): Promise<void> {
+		const project = await coreAPI.getProjectInfo(projectId);
+		if (project) {
+			projectStore.updateProject(projectId, project);
+		}
+		const currentImageId = imageStore.getCurrentImageId();
+		if (currentImageId !== null) {
+			const { markerService } = await import('./markerService');
+			await markerService.loadImageMarkers(currentImageId);
+		}
+	}
+
 	/**
 	 * 清理资源
 	 */
@@ -159,6 +212,25 @@ class ProjectService {
 			// 3. 导入翻译数据 (70% - 85%进度)
 			if (translationFileContent) {
 				progressController.update({ progress: 75, subtitle: '正在导入翻译数据...' });
+				// core每处理完一张图片发送一次进度，结束时发送导入统计
+				let unmatchedImages: string[] = [];
+				let lockedImages: string[] = [];
+				const unsubscribe = eventService.onBusinessEvent((event: BusinessEvent) => {
+					if (event.event_name === 'io:import_progress') {
+						const data = event.data as { project_id: number; done: number; total: number };
+						if (data.project_id !== projectId || data.total === 0) return;
+						progressController.update({
+							progress: 75 + Math.round((data.done / data.total) * 10),
+							subtitle: `正在导入翻译数据 ${data.done}/${data.total}`
+						});
+					} else if (event.event_name === 'io:import_complete') {
+						const data = event.data as { project_id: number; unmatched_images: string[]; locked_images: string[] };
+						if (data.project_id === projectId) {
+							unmatchedImages = data.unmatched_images;
+							lockedImages = data.locked_images;
+						}
+					}
+				});
 				try {
 					const importResult = await coreAPI.importLabelplusData(projectId, translationFileContent);
 					if (importResult.error) {
@@ -166,10 +238,18 @@ class ProjectService {
 						// 不中断流程，继续执行
 					} else {
 						progressController.update({ progress: 85, subtitle: '翻译数据导入成功' });
+						if (unmatchedImages.length > 0) {
+							console.warn('翻译文件中以下图片未找到，其标记未导入:', unmatchedImages);
+						}
+						if (lockedImages.length > 0) {
+							console.warn('以下图片已锁定，其标记未导入:', lockedImages);
+						}
 					}
 				} catch (error) {
 					console.error('翻译数据导入异常:', error);
 					// 不中断流程，继续执行
+				} finally {
+					unsubscribe();
 				}
 			} else {
 				progressController.update({ progress: 85 });
@@ -291,7 +371,7 @@ class ProjectService {
 		// 优先使用文件路径（桌面版）- 性能更好
 		if (image.path) {
 			console.log(`📁 Uploading image via file path: ${image.path}`);
-			return await coreAPI.addImageFromPath(projectId, image.path);
+			return await this.addImageFromPath(projectId, image.path);
 		}
 		// 处理Web版的原始文件
 		else if (image.file) {
@@ -299,7 +379,7 @@ class ProjectService {
 			const arrayBuffer = await image.file.arrayBuffer();
 			const binaryData = new Uint8Array(arrayBuffer);
 			const format = this.normalizeImageFormat(image.file.type);
-			return await coreAPI.addImageFromBinary(projectId, format, binaryData, image.name);
+			return await this.addImageFromBinary(projectId, format, binaryData, image.name);
 		}
 
 		return null;
@@ -394,6 +474,7 @@ class ProjectService {
 				imageViewerActions.resetTransform();
 
 				projectStore.setCurrentProject(projectId, project.name);
+				this.watchProjectFiles(projectId);
 
 				// 加载当前图片的标记（如果有图片的话）
 				if (images.length > 0) {
@@ -450,6 +531,82 @@ class ProjectService {
 		}
 	}
 
+	/**
+	 * 设置项目备注，store由project:settings_changed事件更新
+	 */
+	async setProjectNotes(projectId: number, notes: string): Promise<boolean> {
+		try {
+			return await coreAPI.setProjectNotes(projectId, notes);
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update project notes'));
+			return false;
+		}
+	}
+
+	/**
+	 * 开关新建矩形标记时的自动OCR，store由project:settings_changed事件更新
+	 */
+	async setAutoOcrOnCreate(projectId: number, enabled: boolean): Promise<boolean> {
+		try {
+			return await coreAPI.setAutoOcrOnCreate(projectId, enabled);
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update auto OCR setting'));
+			return false;
+		}
+	}
+
+	/**
+	 * 设置OCR/翻译失败时依次改用的服务，store由project:settings_changed事件更新
+	 */
+	async setServiceFallbacks(projectId: number, ocr: string[], translation: string[]): Promise<boolean> {
+		try {
+			return await coreAPI.setServiceFallbacks(projectId, ocr, translation);
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update service fallbacks'));
+			return false;
+		}
+	}
+
+	/**
+	 * 开关译文历史随bf保存，store由project:settings_changed事件更新
+	 */
+	async setKeepTranslationHistory(projectId: number, enabled: boolean): Promise<boolean> {
+		try {
+			return await coreAPI.setKeepTranslationHistory(projectId, enabled);
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update translation history setting'));
+			return false;
+		}
+	}
+
+	/**
+	 * 开关打开项目时灰度图片的重新编码，下次打开项目时生效
+	 */
+	async setGrayscaleReencode(projectId: number, enabled: boolean): Promise<boolean> {
+		try {
+			return await coreAPI.setGrayscaleReencode(projectId, enabled);
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update grayscale setting'));
+			return false;
+		}
+	}
+
+	/**
+	 * 设置阅读方向（可撤销），只影响标记的阅读顺序，不修改标记序号
+	 */
+	async setReadingDirection(projectId: number, direction: ReadingDirection): Promise<boolean> {
+		try {
+			const success = await coreAPI.setReadingDirection(projectId, direction);
+			if (success) {
+				projectStore.updateProject(projectId, { readingDirection: direction });
+			}
+			return success;
+		} catch (error) {
+			errorStore.setError(error instanceof Error ? error : new Error('Failed to update reading direction'));
+			return false;
+		}
+	}
+
 	/**
 	 * 删除项目
 	 */
@@ -457,6 +614,7 @@ class ProjectService {
 		try {
 			const success = await coreAPI.deleteProject(projectId);
 			if (success) {
+				this.unwatchProjectFiles(projectId);
 				const currentProjectId = projectStore.getCurrentProjectId();
 				projectStore.removeProject(projectId);
 
@@ -474,6 +632,43 @@ class ProjectService {
 		}
 	}
 
+	/**
+	 * 开关项目图片文件监听（桌面端在外部修改图片后自动刷新）
+	 */
+	async setProjectFileWatch(projectId: number, enabled: boolean): Promise<boolean> {
+		try {
+			return await coreAPI.setProjectFileWatch(projectId, enabled);
+		} catch (error) {
+			console.error('Failed to set project file watch:', error);
+			return false;
+		}
+	}
+
+	/**
+	 * 监听项目图片文件（Tauri桌面端），重复调用会按当前图片列表重建
+	 */
+	private async watchProjectFiles(projectId: number): Promise<void> {
+		const { platformService } = await import('./platformService');
+		if (!platformService.isTauri()) return;
+		try {
+			const { invoke } = await import('@tauri-apps/api/core');
+			await invoke('watch_project_files', { projectId });
+		} catch (error) {
+			console.error('Failed to watch project files:', error);
+		}
+	}
+
+	private async unwatchProjectFiles(projectId: number): Promise<void> {
+		const { platformService } = await import('./platformService');
+		if (!platformService.isTauri()) return;
+		try {
+			const { invoke } = await import('@tauri-apps/api/core');
+			await invoke('unwatch_project_files', { projectId });
+		} catch (error) {
+			console.error('Failed to unwatch project files:', error);
+		}
+	}
+
 	/**
 	 * 获取当前项目
 	 */
@@ -540,12 +735,13 @@ class ProjectService {
 
 	/**
 	 * 保存项目到指定路径（Tauri桌面端）
+	 * asCopy为true时写入的是复制出的独立新项目，savedProjectId为新项目ID
 	 */
-	async saveProjectToPath(projectId: number, filePath: string): Promise<{ success: boolean; error?: string }> {
+	async saveProjectToPath(projectId: number, filePath: string, asCopy = false): Promise<{ success: boolean; savedProjectId?: number; error?: string }> {
 		try {
 			const { invoke } = await import('@tauri-apps/api/core');
-			await invoke('save_project_to_path', { projectId, filePath });
-			return { success: true };
+			const savedProjectId = await invoke<number>('save_project_to_path', { projectId, filePath, asCopy });
+			return { success: true, savedProjectId };
 		} catch (error) {
 			console.error('Failed to save project to path:', error);
 			const errorMessage = error instanceof Error ? error.message : 'Unknown error';
@@ -567,6 +763,31 @@ class ProjectService {
 		}
 	}
 
+	/**
+	 * 在系统文件管理器中显示文件（Tauri桌面端）
+	 */
+	async revealInFileManager(path: string): Promise<{ success: boolean; error?: string }> {
+		try {
+			const { invoke } = await import('@tauri-apps/api/core');
+			await invoke('reveal_in_file_manager', { path });
+			return { success: true };
+		} catch (error) {
+			console.error('Failed to reveal in file manager:', error);
+			return { success: false, error: error instanceof Error ? error.message : String(error) };
+		}
+	}
+
+	/**
+	 * 在文件管理器中显示项目文件，项目尚未保存时返回错误
+	 */
+	async revealProjectFile(projectId: number): Promise<{ success: boolean; error?: string }> {
+		const filePath = await this.getProjectFilePath(projectId);
+		if (!filePath) {
+			return { success: false, error: '项目尚未保存到文件' };
+		}
+		return this.revealInFileManager(filePath);
+	}
+
 	/**
 	 * 更新项目的文件路径
 	 */
@@ -593,6 +814,21 @@ class ProjectService {
 		return coreAPI.createOpeningProjectFromBinary(data, extension, projectName);
 	}
 
+	/**
+	 * 创建临时项目（从zip/cbz压缩包），返回包含跳过条目的导入报告
+	 * 压缩包损坏或加密时抛出的错误消息为序列化的ArchiveError
+	 */
+	async createOpeningProjectFromArchive(data: Uint8Array, archiveName: string, projectName: string): Promise<ArchiveImportReport> {
+		return coreAPI.createOpeningProjectFromArchive(data, archiveName, projectName);
+	}
+
+	/**
+	 * 检查项目数据一致性，repair为true时修复可安全修复的问题，修复可以撤销
+	 */
+	async checkIntegrity(projectId: number, repair = false): Promise<IntegrityReport> {
+		return coreAPI.checkIntegrity(projectId, repair);
+	}
+
 	/**
 	 * 获取临时项目信息
 	 */
@@ -607,6 +843,14 @@ class ProjectService {
 		await coreAPI.deleteOpeningProject(projectId);
 	}
 
+	/**
+	 * 取消正在打开的项目，已加入的图片会被删除
+	 * 返回false表示项目已经开始创建，以创建完成为准
+	 */
+	async cancelOpeningProject(openingId: number): Promise<boolean> {
+		return coreAPI.cancelOpeningProject(openingId);
+	}
+
 	/**
 	 * 处理关闭项目（包含未保存检查）
 	 */
@@ -719,8 +963,9 @@ class ProjectService {
 
 	/**
 	 * 处理另存为操作
+	 * asCopy为true时（桌面端）另存为独立副本，之后的修改不影响原项目；Web端下载的文件本身就是副本
 	 */
-	async handleSaveAs(projectId: number): Promise<void> {
+	async handleSaveAs(projectId: number, asCopy = false): Promise<void> {
 		const { platformService } = await import('./platformService');
 		const { undoRedoActions } = await import('./undoRedoService');
 		
@@ -752,8 +997,14 @@ class ProjectService {
 				});
 				
 				if (filePath) {
-					const saveResult = await this.saveProjectToPath(projectId, filePath);
-					if (saveResult.success) {
+					const saveResult = await this.saveProjectToPath(projectId, filePath, asCopy);
+					if (saveResult.success && asCopy && saveResult.savedProjectId !== undefined) {
+						// 副本作为新项目打开，原项目保持原来的路径和未保存状态
+						console.log(`✅ Saved independent copy to ${filePath}`);
+						await this.loadProjects();
+						await this.setCurrentProject(saveResult.savedProjectId);
+						undoRedoActions.markProjectAsSaved(saveResult.savedProjectId);
+					} else if (saveResult.success) {
 						console.log(`✅ Saved project to ${filePath}`);
 						undoRedoActions.markProjectAsSaved(projectId);
 					} else {
@@ -839,10 +1090,259 @@ class ProjectService {
 		}
 	}
 
+	/**
+	 * 只导出选中的图片（如一个章节），按项目中的顺序；bf导出不改变项目的保存路径和保存状态
+	 */
+	async handleExportRange(projectId: number, imageIds: number[], format: 'labelplus' | 'bf'): Promise<void> {
+		const { platformService } = await import('./platformService');
+
+		try {
+			let content: string | Uint8Array;
+			if (format === 'labelplus') {
+				const result = await coreAPI.exportLabelplusDataRange(projectId, imageIds);
+				if (result.error || result.content === undefined) {
+					console.error('Export failed:', result.error);
+					return;
+				}
+				content = result.content;
+			} else {
+				const result = await coreAPI.saveProjectRange(projectId, imageIds);
+				if (result.error || !result.data) {
+					console.error('Export failed:', result.error);
+					return;
+				}
+				content = new Uint8Array(result.data);
+			}
+
+			const project = get(projects).find(p => p.id === projectId);
+			const baseName = `${project?.name || 'project'}_${imageIds.length}p`;
+			const defaultFileName = format === 'labelplus' ? `${baseName}_labelplus.txt` : `${baseName}.bf`;
+
+			if (platformService.isTauri()) {
+				const { save } = await import('@tauri-apps/plugin-dialog');
+				const { writeFile, writeTextFile } = await import('@tauri-apps/plugin-fs');
+				const filePath = await save({
+					defaultPath: defaultFileName,
+					filters: [format === 'labelplus'
+						? { name: 'LabelPlus Translation File', extensions: ['txt'] }
+						: { name: 'BubbleFish Project', extensions: ['bf'] }]
+				});
+				if (filePath) {
+					if (typeof content === 'string') {
+						await writeTextFile(filePath, content);
+					} else {
+						await writeFile(filePath, content);
+					}
+					console.log(`✅ Exported ${imageIds.length} images to ${filePath}`);
+				}
+			} else {
+				const blob = typeof content === 'string'
+					? new Blob([content], { type: 'text/plain;charset=utf-8' })
+					: new Blob([content], { type: 'application/octet-stream' });
+				const url = URL.createObjectURL(blob);
+				const a = document.createElement('a');
+				a.href = url;
+				a.download = defaultFileName;
+
+				document.body.appendChild(a);
+				a.click();
+				document.body.removeChild(a);
+				URL.revokeObjectURL(url);
+
+				console.log(`✅ Exported ${imageIds.length} images to ${defaultFileName}`);
+			}
+		} catch (error) {
+			console.error('Export failed:', error);
+		}
+	}
+
+	/**
+	 * 导出嵌字数据（PS嵌字脚本使用的CSV/JSON）
+	 */
+	async handleExportTypesetting(projectId: number, format: TypesettingFormat, options?: TypesettingOptions): Promise<void> {
+		const { platformService } = await import('./platformService');
+
+		try {
+			if (platformService.isTauri()) {
+				// 桌面端由后端弹出保存对话框并写入文件
+				const { invoke } = await import('@tauri-apps/api/core');
+				const filePath = await invoke<string | null>('save_typesetting_data', { projectId, format, options: options ?? null });
+				if (filePath) {
+					console.log(`✅ Exported typesetting data to ${filePath}`);
+				}
+				return;
+			}
+
+			const content = await coreAPI.exportTypesettingData(projectId, format, options);
+			const project = get(projects).find(p => p.id === projectId);
+			const fileName = `${project?.name || 'project'}_typesetting.${format}`;
+			const mimeType = format === 'csv' ? 'text/csv;charset=utf-8' : 'application/json;charset=utf-8';
+			const blob = new Blob([content], { type: mimeType });
+			const url = URL.createObjectURL(blob);
+			const a = document.createElement('a');
+			a.href = url;
+			a.download = fileName;
+
+			document.body.appendChild(a);
+			a.click();
+			document.body.removeChild(a);
+			URL.revokeObjectURL(url);
+
+			console.log(`✅ Exported typesetting data to ${fileName}`);
+		} catch (error) {
+			console.error('Export typesetting data failed:', error);
+			errorStore.setError(error instanceof Error ? error : new Error(String(error)));
+		}
+	}
+
+	/**
+	 * 把改过译文的嵌字CSV/JSON导回当前项目
+	 * 只更新已有标记的翻译（除非设置create_missing），坏行和匹配不上的行汇总后提示
+	 */
+	async handleImportTypesetting(projectId: number, options?: TypesettingImportOptions): Promise<TypesettingImportReport | null> {
+		const { platformService } = await import('./platformService');
+
+		let fileName: string;
+		let content: string;
+		try {
+			if (platformService.isTauri()) {
+				const { open } = await import('@tauri-apps/plugin-dialog');
+				const selected = await open({
+					multiple: false,
+					filters: [{ name: 'Typesetting Data', extensions: ['csv', 'json'] }]
+				});
+				if (typeof selected !== 'string') return null;
+				const { tauriAPI } = await import('../core/tauri');
+				fileName = selected;
+				content = await tauriAPI.readFileContent(selected);
+			} else {
+				const file = await new Promise<File | null>((resolve) => {
+					const input = document.createElement('input');
+					input.type = 'file';
+					input.accept = '.csv,.json';
+					input.onchange = () => resolve(input.files?.[0] ?? null);
+					input.oncancel = () => resolve(null);
+					input.click();
+				});
+				if (!file) return null;
+				fileName = file.name;
+				content = await file.text();
+			}
+		} catch (error) {
+			console.error('Read typesetting data failed:', error);
+			errorStore.setError(error instanceof Error ? error : new Error(String(error)));
+			return null;
+		}
+
+		const format: TypesettingFormat = fileName.toLowerCase().endsWith('.json') ? 'json' : 'csv';
+		try {
+			const report = await coreAPI.importTypesettingData(projectId, content, format, options);
+
+			const currentImageId = imageStore.getCurrentImageId();
+			if (currentImageId !== null && (report.updated > 0 || report.created > 0)) {
+				const { markerService } = await import('./markerService');
+				await markerService.loadImageMarkers(currentImageId);
+			}
+
+			const issues = [...report.malformed, ...report.unmatched]
+				.sort((a, b) => a.line - b.line)
+				.map((issue) => `第${issue.line}行：${issue.reason}`);
+			const summary = `更新 ${report.updated} 条，未变化 ${report.unchanged} 条` +
+				(report.created > 0 ? `，新建 ${report.created} 个标记` : '');
+			const { notificationStore } = await import('../stores/notificationStore');
+			notificationStore.notify({
+				title: '导入嵌字数据',
+				message: issues.length > 0 ? `${summary}\n${issues.length} 行未导入：\n${issues.slice(0, 10).join('\n')}` : summary,
+				level: issues.length > 0 ? 'warning' : 'success',
+				toast: true,
+				sticky: issues.length > 0
+			});
+			return report;
+		} catch (error) {
+			console.error('Import typesetting data failed:', error);
+			errorStore.setError(error instanceof Error ? error : new Error(String(error)));
+			return null;
+		}
+	}
+
+	/**
+	 * 导出嵌字参考图（标记编号绘制在图片上）
+	 * 桌面端选择目录后由core逐张写入，Web端打包为zip下载
+	 */
+	async handleExportAnnotatedImages(projectId: number, options?: AnnotateOptions): Promise<void> {
+		const { platformService } = await import('./platformService');
+		const project = get(projects).find(p => p.id === projectId);
+		const projectName = project?.name || 'project';
+
+		let dir: string | null = null;
+		if (platformService.isTauri()) {
+			const { open } = await import('@tauri-apps/plugin-dialog');
+			const selected = await open({ directory: true, title: '选择参考图导出目录' });
+			if (typeof selected !== 'string') return;
+			dir = selected;
+		}
+
+		const progressController = progressManager.start({
+			id: `export-annotated-${Date.now()}`,
+			title: `导出嵌字参考图"${projectName}"`,
+			subtitle: '正在准备...'
+		});
+		// core每处理完一张图片发送一次进度事件
+		const unsubscribe = eventService.onBusinessEvent((event: BusinessEvent) => {
+			if (event.event_name !== 'io:annotated_export_progress') return;
+			const data = event.data as { project_id: number; current: number; total: number; name: string };
+			if (data.project_id !== projectId || data.total === 0) return;
+			progressController.update({
+				progress: Math.round((data.current / data.total) * 90),
+				subtitle: `${data.current}/${data.total} ${data.name}`
+			});
+		});
+
+		try {
+			if (dir) {
+				const written = await coreAPI.exportAnnotatedImagesToDir(projectId, dir, options);
+				console.log(`✅ Exported ${written.length} annotated images to ${dir}`);
+			} else {
+				const images = await coreAPI.exportAnnotatedImages(projectId, options);
+				progressController.update({ progress: 90, subtitle: '正在打包...' });
+				const { default: JSZip } = await import('jszip');
+				const zip = new JSZip();
+				for (const image of images) {
+					zip.file(image.name, image.data);
+				}
+				const blob = await zip.generateAsync({ type: 'blob' });
+				const fileName = `${projectName}_annotated.zip`;
+				const url = URL.createObjectURL(blob);
+				const a = document.createElement('a');
+				a.href = url;
+				a.download = fileName;
+
+				document.body.appendChild(a);
+				a.click();
+				document.body.removeChild(a);
+				URL.revokeObjectURL(url);
+
+				console.log(`✅ Exported ${images.length} annotated images to ${fileName}`);
+			}
+			progressController.complete();
+		} catch (error) {
+			console.error('Export annotated images failed:', error);
+			progressController.cancel();
+			errorStore.setError(error instanceof Error ? error : new Error(String(error)));
+		} finally {
+			unsubscribe();
+		}
+	}
+
 	/**
 	 * 添加图片（从路径）
 	 */
 	async addImageFromPath(projectId: number, filePath: string): Promise<number | null> {
+		// 先校验，失败时抛出具体原因（格式不支持、文件损坏等）而不是笼统的失败
+		const invalid = await coreAPI.validateImageFile(filePath);
+		if (invalid) {
+			throw new Error(invalid.error);
+		}
 		return coreAPI.addImageFromPath(projectId, filePath);
 	}
 
@@ -850,6 +1350,10 @@ class ProjectService {
 	 * 添加图片（从二进制数据）
 	 */
 	async addImageFromBinary(projectId: number, format: ImageFormat, data: Uint8Array, imageName: string): Promise<number | null> {
+		const invalid = await coreAPI.validateImageData(imageName, data);
+		if (invalid) {
+			throw new Error(invalid.error);
+		}
 		return coreAPI.addImageFromBinary(projectId, format, data, imageName);
 	}
 
@@ -860,6 +1364,27 @@ class ProjectService {
 		await coreAPI.flushOpeningProjectImages(projectId);
 	}
 
+	/**
+	 * 设置临时项目是否保持上传顺序（拖拽排序后上传时使用），默认按文件名自然排序
+	 */
+	async setOpeningProjectPreserveOrder(projectId: number, preserveOrder: boolean): Promise<boolean> {
+		return coreAPI.setOpeningProjectPreserveOrder(projectId, preserveOrder);
+	}
+
+	/**
+	 * 设置临时项目导入labelplus标记时的坐标原点，用于纠正以其他角为原点的翻译文件
+	 */
+	async setOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<boolean> {
+		return coreAPI.setOpeningProjectLabelplusOrigin(projectId, origin);
+	}
+
+	/**
+	 * 预览按某个坐标原点导入时第一个标记的位置
+	 */
+	async previewOpeningProjectLabelplusOrigin(projectId: number, origin: LabelplusCoordinateOrigin): Promise<LabelplusOriginPreview | null> {
+		return coreAPI.previewOpeningProjectLabelplusOrigin(projectId, origin);
+	}
+
 	/**
 	 * 完成临时项目
 	 */
@@ -874,6 +1399,20 @@ class ProjectService {
 		return coreAPI.createEmptyOpeningProject(projectName);
 	}
 
+	/**
+	 * 打开前预检磁盘上的bf文件（桌面端）
+	 */
+	async validateBfFile(filePath: string): Promise<BfValidation> {
+		return coreAPI.validateBfFile(filePath);
+	}
+
+	/**
+	 * 预检bf文件数据（Web端）
+	 */
+	async validateBfData(data: Uint8Array): Promise<BfValidation> {
+		return coreAPI.validateBfData(data);
+	}
+
 	/**
 	 * 验证 Labelplus 文件
 	 */
@@ -8,16 +8,18 @@ export const recentMenuService = {
 		if (platformService.getPlatform() !== 'macos') return;
 		
 		try {
-			const { listen } = await import('@tauri-apps/api/event');
+			// 菜单事件只发给目标窗口，需要在当前窗口上监听
+			const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+			const currentWindow = getCurrentWebviewWindow();
 			
 			// 监听清空最近打开
-			await listen('menu:file:clear-recent', async () => {
+			await currentWindow.listen('menu:file:clear-recent', async () => {
 				console.log('Clearing recent projects from menu');
 				recentProjectsService.clearRecentProjects();
 			});
 			
 			// 监听打开最近项目
-			await listen<string>('menu:file:open-recent', async (event) => {
+			await currentWindow.listen<string>('menu:file:open-recent', async (event) => {
 				const eventId = event.payload;
 				console.log('Opening recent project from menu:', eventId);
 				
@@ -33,7 +35,7 @@ export const recentMenuService = {
 			});
 			
 			// 监听 open-recent-file 事件（从后端直接发送的）
-			await listen<string>('open-recent-file', async (event) => {
+			await currentWindow.listen<string>('open-recent-file', async (event) => {
 				const filePath = event.payload;
 				console.log('Opening recent file:', filePath);
 				await recentProjectsService.openRecentProject(filePath);
@@ -26,8 +26,15 @@ export class SharedBufferHandler {
     private readonly OFFSET_TOTAL_SIZE = 4;
     private readonly OFFSET_CHUNK_SIZE = 5;
     private readonly OFFSET_ERROR_CODE = 6;
-    // @ts-ignore - Reserved for future use
-    private readonly OFFSET_RESERVED = 7;
+    private readonly OFFSET_PAYLOAD_KIND = 7;
+
+    // Payload kinds: raw bytes are written contiguously so plugins can read them through a view
+    private readonly PAYLOAD_JSON = 0;
+    private readonly PAYLOAD_BINARY = 1;
+
+    // Images staged for plugins (e.g. cropped OCR input), read once via images.read_staged_image
+    private stagedImages = new Map<string, Uint8Array>();
+    private readonly STAGED_IMAGE_TTL_MS = 60 * 1000;
 
     // Constants
     private readonly HEADER_SIZE = 32; // 8 x 4 bytes
@@ -121,6 +128,24 @@ export class SharedBufferHandler {
         }
     }
 
+    /**
+     * Stage image bytes for a plugin to read over the shared buffer instead of postMessage.
+     * The entry is removed once read, or after a timeout if the plugin never asks for it.
+     * Returns false when the image is too large for the buffer; send it by message instead.
+     */
+    stageImage(imageRef: string, data: Uint8Array): boolean {
+        if (data.length >= this.ringBufferSize) {
+            return false;
+        }
+        this.stagedImages.set(imageRef, data);
+        setTimeout(() => {
+            if (this.stagedImages.get(imageRef) === data) {
+                this.stagedImages.delete(imageRef);
+            }
+        }, this.STAGED_IMAGE_TTL_MS);
+        return true;
+    }
+
     /**
      * Read data from ring buffer
      */
@@ -189,6 +214,11 @@ export class SharedBufferHandler {
      * Write data to ring buffer
      */
     private async writeToRingBuffer(result: any, requestId: number) {
+        if (result instanceof Uint8Array) {
+            this.writeBinary(result, requestId);
+            return;
+        }
+
         // Serialize response
         const responseText = JSON.stringify(result);
         const responseBytes = new TextEncoder().encode(responseText);
@@ -201,6 +231,7 @@ export class SharedBufferHandler {
         Atomics.store(this.headerArray, this.OFFSET_REQUEST_ID, requestId);
         Atomics.store(this.headerArray, this.OFFSET_TOTAL_SIZE, responseBytes.length);
         Atomics.store(this.headerArray, this.OFFSET_CHUNK_SIZE, this.DEFAULT_CHUNK_SIZE);
+        Atomics.store(this.headerArray, this.OFFSET_PAYLOAD_KIND, this.PAYLOAD_JSON);
         
         const totalSize = responseBytes.length;
         let written = 0;
@@ -258,10 +289,40 @@ export class SharedBufferHandler {
         Atomics.notify(this.headerArray, this.OFFSET_STATUS, 1);
     }
 
+    /**
+     * Write raw bytes in one piece from the start of the ring buffer; the plugin reads
+     * them through a view once the status turns to response
+     */
+    private writeBinary(data: Uint8Array, requestId: number) {
+        if (data.length >= this.ringBufferSize) {
+            throw new Error(`Payload of ${data.length} bytes does not fit the shared buffer`);
+        }
+
+        this.dataArray.set(data, this.dataStartOffset);
+
+        Atomics.store(this.headerArray, this.OFFSET_READER_POS, 0);
+        Atomics.store(this.headerArray, this.OFFSET_WRITER_POS, data.length);
+        Atomics.store(this.headerArray, this.OFFSET_REQUEST_ID, requestId);
+        Atomics.store(this.headerArray, this.OFFSET_TOTAL_SIZE, data.length);
+        Atomics.store(this.headerArray, this.OFFSET_PAYLOAD_KIND, this.PAYLOAD_BINARY);
+
+        Atomics.store(this.headerArray, this.OFFSET_STATUS, this.STATUS_RESPONSE);
+        Atomics.notify(this.headerArray, this.OFFSET_STATUS, 1);
+    }
+
     /**
      * Call service through plugin bridge
      */
     private async callService(service: string, method: string, params: any): Promise<any> {
+        if (service === 'images' && method === 'read_staged_image') {
+            const data = this.stagedImages.get(params?.image_ref);
+            if (!data) {
+                throw new Error(`No staged image '${params?.image_ref}'`);
+            }
+            this.stagedImages.delete(params.image_ref);
+            return data;
+        }
+
         const request = {
             pluginId: 'shared-buffer',
             service,
@@ -294,6 +355,7 @@ export class SharedBufferHandler {
         Atomics.store(this.headerArray, this.OFFSET_TOTAL_SIZE, writeSize);
         Atomics.store(this.headerArray, this.OFFSET_WRITER_POS, writeSize);
         Atomics.store(this.headerArray, this.OFFSET_ERROR_CODE, 1);
+        Atomics.store(this.headerArray, this.OFFSET_PAYLOAD_KIND, this.PAYLOAD_JSON);
         Atomics.store(this.headerArray, this.OFFSET_STATUS, this.STATUS_ERROR);
         
         // Notify waiting thread
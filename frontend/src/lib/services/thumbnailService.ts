@@ -223,6 +223,43 @@ class ThumbnailService {
 		}
 	}
 
+	/**
+	 * 预取当前图片前后的缩略图，切换图片时后端会取消上一轮预取
+	 * @param imageId 当前图片ID
+	 * @param radius 前后各预取多少张，不传时使用后端默认值
+	 */
+	async prefetchAround(imageId: number, radius?: number): Promise<void> {
+		try {
+			if (isTauri()) {
+				const { invoke } = await import('@tauri-apps/api/core');
+				await invoke('tauri_prefetch_thumbnails_around', { imageId, radius: radius ?? null });
+			} else {
+				const { coreAPI } = await import('$lib/core/adapter');
+				if ('prefetchThumbnailsAround' in coreAPI && typeof coreAPI.prefetchThumbnailsAround === 'function') {
+					await (coreAPI as { prefetchThumbnailsAround: (id: number, radius?: number) => Promise<string> }).prefetchThumbnailsAround(imageId, radius);
+				} else {
+					const wasmModule = await import('$lib/wasm-pkg');
+					wasmModule.wasm_prefetch_thumbnails_around(imageId, radius);
+				}
+			}
+		} catch (error) {
+			// 预取只是优化，失败不影响正常使用
+			console.debug(`Failed to prefetch thumbnails around image ${imageId}:`, error);
+		}
+	}
+
+	/**
+	 * 清空桌面端缩略图磁盘缓存（Web端只有内存缓存，直接返回0）
+	 * @returns 删除的缓存文件数
+	 */
+	async clearDiskCache(): Promise<number> {
+		if (!isTauri()) {
+			return 0;
+		}
+		const { invoke } = await import('@tauri-apps/api/core');
+		return await invoke<number>('tauri_clear_thumbnail_cache');
+	}
+
 	/**
 	 * 清理缩略图缓存
 	 * @param imageId 可选的图片ID，如果不提供则清理所有
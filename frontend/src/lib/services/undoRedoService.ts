@@ -21,16 +21,23 @@ const actionNameMap: Record<string, string> = {
     'UpdateMarkerOrder': '调整顺序',
     'UpdatePointMarkerPosition': '移动标记',
     'UpdateRectangleGeometry': '调整矩形',
+    'UpdatePolygonGeometry': '调整多边形',
     'UpdateMarker': '更新标记',
     'ClearImageMarkers': '清空标记',
     'ConvertRectangleToPoint': '矩形转点',
     'ConvertPointToRectangle': '点转矩形',
+    'ConvertPolygonToRectangle': '多边形转矩形',
+    'MoveMarkerToImage': '移到其他图片',
     'AddImage': '添加图片',
     'RemoveImage': '删除图片',
     'ReorderImages': '重排图片',
     'UpdateImage': '更新图片',
     'UpdateProjectName': '重命名项目',
-    'UpdateProjectLanguages': '更改语言设置'
+    'UpdateProjectLanguages': '更改语言设置',
+    'UpdateReadingDirection': '更改阅读方向',
+    'UpdateMarkerNote': '修改备注',
+    'SetImageLocked': '锁定图片',
+    'Composite': '批量操作'
 };
 
 export interface UndoRedoService {
@@ -39,6 +46,7 @@ export interface UndoRedoService {
     clearHistory(): Promise<void>;
     clearHistoryForProject(projectId: number): Promise<void>;
     clearAllHistory(): Promise<void>;
+    withGroup<T>(projectId: number, description: string, operation: () => Promise<T>): Promise<T>;
     initialize(): () => void;
     getUndoActionDisplayName(actionName: string | null): string | null;
     setBeforeUndoRedoCallback(callback: (() => Promise<void>) | null): void;
@@ -60,6 +68,7 @@ class UndoRedoServiceImpl implements UndoRedoService {
                 const data = event.data as {
                     project_id: number;
                     undo_action_name?: string;  // New field
+                    undo_description?: string | null;  // 撤销分组的说明
                     can_undo?: boolean;  // Keep for backward compatibility
                     can_redo: boolean;
                     current_commit_id: string | null;
@@ -67,7 +76,9 @@ class UndoRedoServiceImpl implements UndoRedoService {
                 
                 // Handle both new and old format for backward compatibility
                 let undoActionName: string | null;
-                if (data.undo_action_name !== undefined) {
+                if (data.undo_action_name !== undefined && data.undo_action_name !== 'none' && data.undo_description) {
+                    undoActionName = data.undo_description;
+                } else if (data.undo_action_name !== undefined) {
                     // New format with action name
                     undoActionName = data.undo_action_name === 'none' ? null : data.undo_action_name;
                 } else if (data.can_undo !== undefined) {
@@ -178,6 +189,21 @@ class UndoRedoServiceImpl implements UndoRedoService {
         }
     }
 
+    // 多次调用作为一次撤销记录；operation抛出错误时撤回已做的修改
+    async withGroup<T>(projectId: number, description: string, operation: () => Promise<T>): Promise<T> {
+        await coreAPI.beginUndoGroup(projectId, description);
+        try {
+            const result = await operation();
+            await coreAPI.endUndoGroup(projectId);
+            return result;
+        } catch (error) {
+            await coreAPI.abortUndoGroup(projectId).catch((abortError) => {
+                console.error('Failed to abort undo group:', abortError);
+            });
+            throw error;
+        }
+    }
+
     private async handleUndoRedoResult(result: UndoRedoResult): Promise<void> {
         const currentImageId = get(imageStore).currentImageId;
         
@@ -1,11 +1,24 @@
 import { writable, get } from 'svelte/store';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { invoke } from '@tauri-apps/api/core';
+import { coreAPI } from '../core/adapter';
 import { platformService } from './platformService';
 import { projects, currentProjectId } from './projectService';
 import { modalStore } from './modalService';
 import { undoRedoActions } from './undoRedoService';
 import { projectService } from './projectService';
 
+// 主窗口之外的项目窗口，label为 project-<id>
+const PROJECT_WINDOW_PREFIX = 'project-';
+
+// 退出确认事件中的未保存项目
+export interface DirtyProject {
+	id: number;
+	name: string;
+	file_path: string | null;
+}
+
 interface WindowState {
 	isMaximized: boolean;
 	appWindow: ReturnType<typeof getCurrentWindow> | undefined;
@@ -28,24 +41,53 @@ function createWindowService() {
 				await checkWindowState();
 			});
 
-			await appWindow.onCloseRequested(async (event) => {
-				const hasAnyUnsaved = get(projects).some(project => {
-					const projectState = undoRedoActions.getProjectState(project.id);
-					return projectState.hasUnsaved;
-				});
-
-				if (hasAnyUnsaved) {
-					event.preventDefault();
-					await closeWindow();
-				}
+			// 关闭主窗口和退出菜单由后端检查未保存的项目，需要确认时发出此事件
+			await getCurrentWebviewWindow().listen<{ dirty_projects: DirtyProject[] }>('app:confirm_quit', (event) => {
+				modalStore.showModal('confirmQuit', { dirtyProjects: event.payload.dirty_projects });
 			});
 
 			await checkWindowState();
+			await syncWindowProject();
 		} catch (error) {
 			console.error('Failed to initialize window service:', error);
 		}
 	}
 
+	function isProjectWindow(): boolean {
+		const state = get(windowState);
+		return !!state.appWindow?.label.startsWith(PROJECT_WINDOW_PREFIX);
+	}
+
+	// 项目窗口启动时打开后端登记的项目，之后切换项目时同步给后端的 窗口 -> 项目 映射
+	async function syncWindowProject() {
+		if (isProjectWindow()) {
+			const projectId = await invoke<number | null>('get_window_project');
+			if (projectId !== null) {
+				await projectService.loadProjects();
+				await projectService.setCurrentProject(projectId);
+			}
+		}
+
+		currentProjectId.subscribe((projectId) => {
+			invoke('set_window_project', { projectId }).catch((error) => {
+				console.error('Failed to sync window project:', error);
+			});
+		});
+	}
+
+	/**
+	 * 在新窗口中打开项目，窗口已存在时聚焦该窗口
+	 */
+	async function openProjectWindow(projectId: number) {
+		if (!platformService.isTauri()) return;
+
+		try {
+			await invoke('open_project_window', { projectId });
+		} catch (error) {
+			console.error('Failed to open project window:', error);
+		}
+	}
+
 	async function checkWindowState() {
 		const state = get(windowState);
 		if (state.appWindow) {
@@ -78,75 +120,27 @@ function createWindowService() {
 		}
 	}
 
+	// 关闭窗口：后端拦截主窗口的关闭，有未保存的项目时先确认再退出应用
 	async function closeWindow() {
 		const state = get(windowState);
-		if (!state.appWindow) return;
-
-		const unsavedProjects = get(projects).filter(project => {
-			const projectState = undoRedoActions.getProjectState(project.id);
-			return projectState.hasUnsaved;
-		});
-
-		if (unsavedProjects.length > 0) {
-			const savedProjects = get(projects).filter(project => {
-				const projectState = undoRedoActions.getProjectState(project.id);
-				return !projectState.hasUnsaved;
-			});
-
-			for (const project of savedProjects) {
-				await performCloseProject(project.id);
-			}
-
-			if (unsavedProjects.length > 0) {
-				await projectService.setCurrentProject(unsavedProjects[0].id);
-
-				modalStore.showModal('confirm', {
-					title: '关闭应用',
-					message: `项目 "${unsavedProjects[0].name}" 有未保存的修改。确定要关闭吗？`,
-					confirmText: '关闭',
-					cancelText: '取消',
-					onConfirm: async () => {
-						await performCloseProject(unsavedProjects[0].id);
-
-						const remainingUnsaved = get(projects).filter(project => {
-							const projectState = undoRedoActions.getProjectState(project.id);
-							return projectState.hasUnsaved;
-						});
-
-						if (remainingUnsaved.length > 0) {
-							await closeWindow();
-						} else {
-							if (state.appWindow) {
-								await state.appWindow.close();
-							}
-						}
-					}
-				});
-			}
-		} else {
+		if (state.appWindow) {
 			await state.appWindow.close();
 		}
 	}
 
-	async function performCloseProject(projectId: number) {
+	/**
+	 * 回应退出确认：discard直接退出，saveAll保存所有未保存的项目后退出，都为false时取消退出
+	 * 保存失败时应用保持打开并抛出错误，已保存成功的项目同步标记为已保存
+	 */
+	async function confirmQuit(saveAll: boolean, discard: boolean, dirtyProjectIds: number[] = []): Promise<boolean> {
 		try {
-			if (get(currentProjectId) === projectId) {
-				await projectService.setCurrentProject(null);
-			}
-
-			const success = await projectService.deleteProject(projectId);
-			if (success) {
-				const currentProjects = get(projects);
-				projectService.setProjects(currentProjects.filter((p) => p.id !== projectId));
-				undoRedoActions.clearProjectState(projectId);
-
-				const remainingProjects = get(projects).filter(p => p.id !== projectId);
-				if (remainingProjects.length > 0 && get(currentProjectId) === null) {
-					await projectService.setCurrentProject(remainingProjects[0].id);
+			return await invoke<boolean>('confirm_quit', { saveAll, discard });
+		} finally {
+			for (const projectId of dirtyProjectIds) {
+				if (!(await coreAPI.hasUnsavedChanges(projectId))) {
+					undoRedoActions.markProjectAsSaved(projectId);
 				}
 			}
-		} catch (error) {
-			console.error('Failed to close project:', error);
 		}
 	}
 
@@ -177,8 +171,9 @@ function createWindowService() {
 		minimizeWindow,
 		maximizeWindow,
 		closeWindow,
-		setupBeforeUnloadHandler,
-		performCloseProject
+		confirmQuit,
+		openProjectWindow,
+		setupBeforeUnloadHandler
 	};
 }
 
@@ -1,5 +1,11 @@
 import { writable, derived, get } from 'svelte/store';
-import type { BunnyTask, BunnyMarkerData, BunnySettings, BunnyQueueStatus } from '../types/bunny';
+import type { BunnyPipeline, BunnyTask, BunnyMarkerData, BunnySettings, BunnyQueueStatus, DetectedRegion, TranslationWarning } from '../types/bunny';
+
+export interface ImageProgress {
+	done: number;
+	total: number;
+	failed: number;
+}
 
 interface BunnyState {
 	// Selection state
@@ -8,6 +14,12 @@ interface BunnyState {
 	// Data storage
 	markerData: Map<number, BunnyMarkerData>;
 	tasks: Map<string, BunnyTask>;
+	// 检测插件给出、还没生成标记的候选框，按图片
+	detections: Map<number, DetectedRegion[]>;
+	// OCR→翻译流水线，用于显示两段进度
+	pipelines: Map<string, BunnyPipeline>;
+	// bunny:image_progress给出的每张图片任务进度
+	imageProgress: Map<number, ImageProgress>;
 	
 	// Settings
 	settings: BunnySettings;
@@ -22,6 +34,9 @@ function createBunnyStore() {
 		selectedMarkerIds: new Set(),
 		markerData: new Map(),
 		tasks: new Map(),
+		detections: new Map(),
+		pipelines: new Map(),
+		imageProgress: new Map(),
 		settings: {
 			ocrModel: 'default',
 			translationService: 'default',
@@ -121,16 +136,53 @@ function createBunnyStore() {
 			});
 		},
 
-		setMachineTranslation(markerId: number, translation: string, service?: string) {
+		setMachineTranslation(markerId: number, translation: string, service?: string, warnings: TranslationWarning[] = []) {
 			update(state => {
 				const data = state.markerData.get(markerId) || { markerId };
 				data.machineTranslation = translation;
+				data.translationWarnings = warnings;
+				data.machineTranslationAccepted = undefined;
 				if (service) data.lastTranslationService = service;
 				state.markerData.set(markerId, data);
 				return state;
 			});
 		},
 		
+		setDetections(imageId: number, regions: DetectedRegion[]) {
+			update(state => {
+				if (regions.length > 0) {
+					state.detections.set(imageId, regions);
+				} else {
+					state.detections.delete(imageId);
+				}
+				return state;
+			});
+		},
+
+		setPipeline(pipeline: BunnyPipeline) {
+			update(state => {
+				state.pipelines.set(pipeline.pipeline_id, pipeline);
+				return state;
+			});
+		},
+
+		// 机翻被接受或拒绝后移出待审列表
+		setMachineTranslationReviewed(markerId: number, accepted: boolean) {
+			update(state => {
+				const data = state.markerData.get(markerId);
+				if (!data) return state;
+				if (accepted) {
+					data.machineTranslationAccepted = true;
+				} else {
+					data.machineTranslation = undefined;
+					data.translationWarnings = [];
+					data.machineTranslationAccepted = undefined;
+				}
+				state.markerData.set(markerId, data);
+				return state;
+			});
+		},
+
 		setMarkerTaskId(markerId: number, taskId: string, taskType: 'ocr' | 'translation') {
 			update(state => {
 				const data = state.markerData.get(markerId) || { markerId };
@@ -151,6 +203,13 @@ function createBunnyStore() {
 			});
 		},
 		
+		setImageProgress(imageId: number, progress: ImageProgress) {
+			update(state => {
+				state.imageProgress.set(imageId, progress);
+				return state;
+			});
+		},
+		
 		// Settings
 		updateSettings(updates: Partial<BunnySettings>) {
 			update(state => {
@@ -187,6 +246,7 @@ function createBunnyStore() {
 		clearAllTasks() {
 			update(state => {
 				state.tasks.clear();
+				state.imageProgress.clear();
 				// Clear task IDs from marker data
 				state.markerData.forEach(data => {
 					delete data.ocrTaskId;
@@ -207,6 +267,7 @@ export const markerData = derived(bunnyStore, $store => $store.markerData);
 export const tasks = derived(bunnyStore, $store => $store.tasks);
 export const bunnySettings = derived(bunnyStore, $store => $store.settings);
 export const isProcessing = derived(bunnyStore, $store => $store.isProcessing);
+export const imageProgress = derived(bunnyStore, $store => $store.imageProgress);
 
 // Derived queue status
 export const queueStatus = derived(bunnyStore, $store => {
@@ -1,6 +1,6 @@
 import { writable, get } from 'svelte/store';
 
-export type ModalType = 'newProject' | 'openProject' | 'about' | 'license' | 'confirm' | 'snapshot' | 'settings' | null;
+export type ModalType = 'newProject' | 'openProject' | 'about' | 'license' | 'confirm' | 'confirmQuit' | 'relinkImages' | 'panicRecovery' | 'glossary' | 'snapshot' | 'settings' | null;
 
 export interface ModalData {
 	defaultName?: string;
@@ -17,12 +17,21 @@ export interface MarkerStyle {
 	overlayText: boolean;
 	/** 是否是横排的文字 */
 	horizontal: boolean;
+	/** 字号倍率，未设置时使用默认字号 */
+	sizeHint?: number | null;
+	/** 标记标签，如"SFX" */
+	tag?: string | null;
 }
 
 // Marker几何类型
 export type MarkerGeometry = 
 	| { type: 'point'; x: number; y: number }
-	| { type: 'rectangle'; x: number; y: number; width: number; height: number };
+	| { type: 'rectangle'; x: number; y: number; width: number; height: number }
+	/** 斜向文字用的多边形，顶点为百分比坐标 [x, y]，至少3个 */
+	| { type: 'polygon'; points: [number, number][] };
+
+/** 越界坐标的处理方式：reject返回错误，clamp限制到图片范围内 */
+export type CoordinateMode = 'reject' | 'clamp';
 
 export interface Marker {
 	id: number;
@@ -31,6 +40,15 @@ export interface Marker {
 	translation?: string;
 	style: MarkerStyle;
 	imageIndex: number;
+	// 译者备注，与译文分开保存
+	note?: string | null;
+}
+
+// 标记的一条译文历史，与撤销栈无关
+export interface TranslationHistoryEntry {
+	translation: string;
+	timestamp: number;
+	origin: 'manual' | 'machine' | 'import';
 }
 
 // 图片格式枚举，与后端保持一致
@@ -44,6 +62,133 @@ export interface ImageMetadata {
 	height?: number; // 对应后端的 Option<u32>
 	format?: ImageFormat; // 对应后端的 Option<ImageFormat>
 	size?: number; // 对应后端的 Option<u64>，前端使用 number 表示
+	dpi_x?: number; // 格式不记录DPI时为空
+	dpi_y?: number;
+	color_mode?: ColorMode;
+	bit_depth?: number;
+	original_width?: number; // 导入时被缩小的图片的原始尺寸
+	original_height?: number;
+	reviewed?: boolean; // 已校对的页面在缩略图栏显示勾选
+	review_note?: string | null;
+	locked?: boolean; // 锁定后不能修改其中的标记，缩略图栏显示锁
+	original_name?: string | null; // 与项目中其它图片重名而加了序号后缀时的原文件名
+}
+
+// 图片的查看状态，切换回图片时恢复，随bf保存
+export interface ImageViewState {
+	zoom: number;
+	pan_x: number;
+	pan_y: number;
+	last_selected_marker: number | null;
+}
+
+// 标记的预览小图（JPEG），翻译侧栏显示在译文旁
+export interface MarkerThumbnail {
+	marker_id: number;
+	data: Uint8Array;
+}
+
+// 导入时缩小超大图片（默认关闭）
+export interface DownscaleSettings {
+	enabled: boolean;
+	max_edge: number;
+	jpeg_quality: number;
+}
+
+// WASM事件通道统计，与后端 EventChannelStats 保持一致
+export interface EventChannelStats {
+	queued: number;
+	capacity: number;
+	high_water: number;
+	peak_queued: number;
+	sent: number;
+	delivered: number;
+	dropped: number;
+	would_block: number;
+	chunked: number;
+	largest_event_bytes: number;
+}
+
+// 前端事件过滤状态，与后端 FrontendEventFilterStats 保持一致；patterns为null表示全部发送
+export interface FrontendEventFilterStats {
+	patterns: string[] | null;
+	suppressed_total: number;
+	suppressed_by_event: Record<string, number>;
+}
+
+// 核心心跳，与后端 CoreHealth 保持一致；linearMemoryBytes仅WASM有值
+export interface CoreHealth {
+	tick: number;
+	linearMemoryBytes: number | null;
+	imageCacheBytes: number;
+	imageStorageBytes: number;
+	markerCount: number;
+}
+
+// 后端内存中保留的日志，与后端 LogRecord 保持一致
+export type LogRecordLevel = 'Debug' | 'Info' | 'Warn' | 'Error';
+
+export interface LogRecord {
+	level: LogRecordLevel;
+	timestamp: number;
+	message: string;
+	data: unknown | null;
+	// 分类日志（如plugin_audit）的类别
+	category?: string;
+}
+
+// 插件服务调用审计记录，与后端 PluginAuditEntry 保持一致
+export interface PluginAuditEntry {
+	plugin_id: string;
+	service: string;
+	method: string;
+	params_bytes: number;
+	// 脱敏后的参数，过大时为null
+	params: unknown | null;
+	success: boolean;
+	error: string | null;
+	result_bytes: number;
+	duration_ms: number;
+	timestamp: number;
+}
+
+// 桌面端命令清单，与后端 CommandInfo 保持一致
+export interface CommandParam {
+	name: string;
+	type: string;
+}
+
+export interface CommandInfo {
+	name: string;
+	params: CommandParam[];
+	description: string;
+	enabled: boolean;
+}
+
+// 图片颜色模式，与后端 ColorMode 枚举保持一致
+export type ColorMode = 'Grayscale' | 'GrayscaleAlpha' | 'Rgb' | 'Rgba' | 'Indexed' | 'Cmyk';
+
+// 项目范围查找替换的结果
+// 可取消操作的结束状态，cancelled时结果只包含取消前完成的部分
+export type OperationStatus = 'completed' | 'cancelled';
+
+export interface ReplaceReport {
+	changed_marker_ids: number[];
+	replacements: number;
+	status: OperationStatus;
+	skipped_image_ids: number[]; // 有匹配但已锁定的图片
+}
+
+export type TranslationIssueCategory =
+	| 'empty_translation'
+	| 'inconsistent_translation'
+	| 'trailing_whitespace'
+	| 'duplicate_whitespace';
+
+export interface TranslationIssue {
+	category: TranslationIssueCategory;
+	marker_ids: number[];
+	message: string;
 }
 
 // 图片数据类型，匹配后端的 ImageData 枚举结构
@@ -53,6 +198,9 @@ export type ImageData =
 	| { type: 'Url'; url: string }
 	| { type: 'SharedBuffer'; format: ImageFormat; buffer_id: number };
 
+// 图片数据的存储方式，匹配后端的 ImageSourceKind
+export type ImageSourceKind = 'FilePath' | 'Binary' | 'SharedBuffer';
+
 // 完整的图片结构，匹配后端的 Image
 export interface Image {
 	metadata: ImageMetadata;
@@ -65,6 +213,274 @@ export interface TranslationProject {
 	name: string;
 	sourceLanguage?: Language;
 	targetLanguage?: Language;
+	// 桌面端是否监听图片文件变化
+	watchFiles?: boolean;
+	readingDirection?: ReadingDirection;
+	// 项目自定义的标记类型，为空时使用LabelPlus默认分组
+	markerTypes?: MarkerTypePreset[];
+	defaultServices?: ServiceDefaults;
+	glossary?: ProjectGlossary;
+	// 项目备注，LabelPlus导出时写入项目注释
+	notes?: string;
+	// 新画的矩形标记自动用默认OCR服务识别
+	autoOcrOnCreate?: boolean;
+	// 译文历史是否随bf保存
+	keepTranslationHistory?: boolean;
+	// 打开项目时灰度图片重新编码为灰度PNG
+	grayscaleReencode?: boolean;
+}
+
+// 项目术语表，翻译时交给插件约束并校验译文
+export interface ProjectGlossary {
+	entries?: GlossaryEntry[];
+	forbiddenTerms?: string[];
+}
+
+export interface GlossaryEntry {
+	source: string;
+	target: string;
+}
+
+export type ReadingDirection = 'leftToRight' | 'rightToLeft';
+
+export interface MarkerTypePreset {
+	name: string;
+	overlayText: boolean;
+	horizontal: boolean;
+	tag?: string;
+}
+
+// 项目默认使用的OCR/翻译服务，缺省时使用全局设置
+export interface ServiceDefaults {
+	ocr?: string;
+	translation?: string;
+	// 在本项目中停用的服务ID
+	disabled?: string[];
+	// 任务失败时依次改用的服务
+	ocrFallbacks?: string[];
+	translationFallbacks?: string[];
+}
+
+// 项目模板，builtin为内置模板（不能覆盖或删除）
+export interface ProjectTemplate {
+	schema_version: number;
+	name: string;
+	source_language: Language;
+	target_language: Language;
+	created_at: number;
+	reading_direction: ReadingDirection | null;
+	marker_types: MarkerTypePreset[];
+	default_services: ServiceDefaults;
+	glossary: ProjectGlossary;
+	builtin: boolean;
+}
+
+export interface TemplateList {
+	templates: ProjectTemplate[];
+	warnings: string[];
+}
+
+// 命名快照（还原点），只保存标记、设置、术语表和海兔缓存，不含图片
+export interface SnapshotInfo {
+	id: string;
+	name: string;
+	project_name: string;
+	file_path: string | null;
+	created_at: number;
+	content_hash: string;
+	marker_count: number;
+	// 压缩后的字节数
+	size: number;
+}
+
+// 压缩包（zip/cbz）导入报告
+export interface ArchiveImportReport {
+	project_id: number;
+	added_images: string[];
+	skipped_entries: string[];
+	failed_entries: [string, string][];
+	status: OperationStatus;
+}
+
+// 图片导入前校验失败的原因，error为面向用户的提示
+export interface ImageImportError {
+	kind: 'unsupported_format' | 'corrupt' | 'too_large' | 'not_an_image';
+	name: string;
+	error: string;
+}
+
+// 文件已不存在的图片（项目文件夹被移动后），path为项目中记录的原路径
+export interface MissingImage {
+	image_id: number;
+	name: string | null;
+	path: string;
+}
+
+// 重新链接图片的结果，checksum_matches为false表示新文件与原文件内容不同
+export interface RelinkResult {
+	image_id: number;
+	path: string;
+	checksum_matches: boolean;
+}
+
+// bf项目文件的概要，打开前给用户确认（不创建项目）
+export interface BfSummary {
+	projectName: string;
+	formatVersion: string;
+	newerFormat: boolean;
+	exportDate: string;
+	imageCount: number;
+	markerCount: number;
+	skippedMarkers: number;
+	images: string[];
+}
+
+// bf项目文件预检失败的原因，message为面向用户的提示
+export interface BfFormatError {
+	kind: 'not_bf_file' | 'truncated' | 'corrupt' | 'missing_entry' | 'invalid_entry' | 'inconsistent';
+	message: string;
+}
+
+export type BfValidation = { summary: BfSummary; error?: undefined } | { summary?: undefined; error: BfFormatError };
+
+// 压缩包无法打开时的结构化错误（序列化在错误消息中）
+export interface ArchiveError {
+	archive: string;
+	kind: 'corrupt' | 'password_protected' | 'unsupported' | 'no_images';
+	message: string;
+}
+
+// 单张图片的字数统计
+export interface ImageTextStats {
+	image_id: number;
+	source_chars: number;
+	translated_chars: number;
+	source_words: number;
+	translated_words: number;
+	reviewed: boolean;
+}
+
+// 项目字数统计（原文来自OCR结果，译文来自标记）
+export interface TextStats {
+	source_chars: number;
+	translated_chars: number;
+	source_words: number;
+	translated_words: number;
+	reviewed_images: number;
+	per_image: ImageTextStats[];
+}
+
+// 菜单快捷键绑定，accelerator为null表示未绑定
+export interface KeyBinding {
+	action: string;
+	label: string;
+	accelerator: string | null;
+	default_accelerator: string | null;
+}
+
+// core定义的菜单结构，macOS原生菜单和虚拟菜单栏共用，label为默认显示文本
+export type MenuItemKind = 'action' | 'check' | 'submenu' | 'predefined' | 'separator';
+
+export interface MenuItemDefinition {
+	id: string;
+	label_key: string;
+	label: string;
+	kind: MenuItemKind;
+	default_accelerator: string | null;
+	accelerator: string | null;
+	enabled: boolean;
+	items: MenuItemDefinition[];
+}
+
+export interface MenuSection {
+	id: string;
+	label_key: string;
+	label: string;
+	items: MenuItemDefinition[];
+}
+
+// 修改快捷键失败的原因，conflicting_item为已占用该快捷键的菜单项
+export interface AcceleratorError {
+	kind: 'unknown_item' | 'invalid' | 'conflict' | 'storage';
+	message: string;
+	conflicting_item?: string;
+}
+
+// 嵌字参考图导出选项，缺省字段使用core的默认值
+export interface AnnotateOptions {
+	scale?: number;
+	color?: string; // "#RRGGBB"
+	opacity?: number; // 0-1
+	format?: 'jpeg' | 'png';
+	jpeg_quality?: number;
+	reading_order?: boolean; // 按项目阅读方向编号，不修改标记序号
+}
+
+// 嵌字数据导出（PS脚本用的CSV/JSON）
+export type TypesettingFormat = 'csv' | 'json';
+
+export interface TypesettingOptions {
+	skip_empty?: boolean;
+	bom?: boolean; // 仅CSV，默认写入BOM
+}
+
+export interface TypesettingImportOptions {
+	tolerance_px?: number; // 序号对不上时按位置匹配的容差，默认8像素
+	create_missing?: boolean; // 为匹配不上的行新建标记
+}
+
+export interface ImportRowIssue {
+	line: number;
+	image: string | null;
+	index: number | null;
+	reason: string;
+}
+
+export interface TypesettingImportReport {
+	updated: number;
+	unchanged: number;
+	created: number;
+	changed_marker_ids: number[];
+	unmatched: ImportRowIssue[];
+	malformed: ImportRowIssue[];
+	skipped_image_ids: number[]; // 已锁定、没有写入的图片
+}
+
+// LabelPlus坐标的原点，部分变体以其他角为(0,0)或y轴向上
+export type LabelplusCoordinateOrigin = 'top_left' | 'top_right' | 'bottom_left' | 'bottom_right';
+
+export interface LabelplusMergeOptions {
+	skip_duplicates?: boolean;
+	origin?: LabelplusCoordinateOrigin;
+}
+
+// 按某个原点导入时第一个标记的位置（百分比坐标，以左上角为原点）
+export interface LabelplusOriginPreview {
+	image: string;
+	index: number;
+	x: number;
+	y: number;
+	pixel_coordinates: boolean; // 像素坐标在图片加载前只是近似位置
+}
+
+export interface LabelplusSkippedMarker {
+	image: string;
+	index: number;
+	reason: string;
+}
+
+export interface LabelplusMergeReport {
+	added: number;
+	added_marker_ids: number[];
+	skipped: LabelplusSkippedMarker[];
+	unmatched_images: string[];
+	// 已锁定而跳过的图片
+	locked_images: string[];
+}
+
+export interface AnnotatedImage {
+	name: string;
+	data: Uint8Array;
 }
 
 export interface OpeningProjectInfo {
@@ -74,8 +490,54 @@ export interface OpeningProjectInfo {
 	pendingImages: string[];
 	uploadedImages: string[];
 	isComplete: boolean;
+	// 打开文件时检查出的数据问题，不影响打开
+	warnings: IntegrityIssue[];
+}
+
+// 项目数据一致性检查，与后端 IntegrityReport 保持一致
+export type IntegrityIssueKind =
+	| 'missing_image'
+	| 'dangling_marker_id'
+	| 'dangling_index_entry'
+	| 'unlisted_marker'
+	| 'marker_image_mismatch'
+	| 'duplicate_image_index'
+	| 'orphan_bunny_cache'
+	| 'bf_duplicate_image'
+	| 'bf_dangling_bunny_cache'
+	| 'bf_unknown_style';
+
+export interface IntegrityIssue {
+	kind: IntegrityIssueKind;
+	message: string;
+	image_id: number | null;
+	marker_id: number | null;
+	fixable: boolean;
+	fixed: boolean;
 }
 
+export interface IntegrityReport {
+	project_id: number;
+	issues: IntegrityIssue[];
+	repaired: boolean;
+}
+
+// 重复图片分组，similarity为与组内第一张的相似度（0-1）
+export interface DuplicateImage {
+	image_id: number;
+	name: string | null;
+	width: number | null;
+	height: number | null;
+	marker_count: number;
+	similarity: number;
+}
+
+export interface DuplicateGroup {
+	images: DuplicateImage[];
+}
+
+export type DuplicateKeepStrategy = 'first' | 'most_markers' | 'largest';
+
 export interface ImageFile {
 	file?: File; // Optional for Tauri desktop version
 	name: string;
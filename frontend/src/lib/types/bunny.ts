@@ -15,6 +15,70 @@ export interface BunnyTask {
 	completedAt?: number;
 }
 
+// 批量OCR中单个标记的结果，task_id和error二选一
+export interface OcrBatchItem {
+	marker_id: number;
+	task_id: string | null;
+	error: string | null;
+}
+
+// OCR→翻译流水线（字段与core一致，snake_case），stage为当前阶段，失败时停在失败的阶段
+export interface BunnyPipeline {
+	pipeline_id: string;
+	project_id: number;
+	marker_id: number;
+	image_id: number;
+	ocr_service: string;
+	translation_service: string;
+	stage: 'ocr' | 'translation';
+	status: 'processing' | 'completed' | 'failed';
+	ocr_task_id: string;
+	translation_task_id: string | null;
+	error: string | null;
+	created_at: number;
+	completed_at: number | null;
+}
+
+// 批量流水线中单个标记的结果，pipeline_id和error二选一
+export interface PipelineBatchItem {
+	marker_id: number;
+	pipeline_id: string | null;
+	error: string | null;
+}
+
+// 核心记录的已结束任务（字段与TaskManager一致，snake_case）
+export interface TaskHistoryEntry {
+	task_id: string;
+	project_id: number;
+	marker_id: number;
+	image_id: number;
+	task_type: 'ocr' | 'translation';
+	service_id: string;
+	status: 'completed' | 'failed';
+	created_at: number;
+	started_at: number | null;
+	completed_at: number | null;
+	error: string | null;
+	duration_ms: number | null;
+}
+
+export interface ServiceTaskMetrics {
+	service_id: string;
+	task_type: 'ocr' | 'translation';
+	completed: number;
+	failed: number;
+	average_duration_ms: number | null;
+	max_duration_ms: number | null;
+}
+
+// 图片上当前任务的数量，对应core的ImageTaskProgress
+export interface ImageTaskProgress {
+	queued: number;
+	running: number;
+	done: number;
+	failed: number;
+}
+
 export interface BunnyMarkerData {
 	markerId: number;
 	originalText?: string;
@@ -23,6 +87,51 @@ export interface BunnyMarkerData {
 	translationTaskId?: string;
 	lastOCRModel?: string;
 	lastTranslationService?: string;
+	// 插件校验机器翻译时发现的术语问题
+	translationWarnings?: TranslationWarning[];
+	// 机翻已被接受为译文，新的机翻到达时清除
+	machineTranslationAccepted?: boolean;
+}
+
+export type TranslationWarning =
+	| { kind: 'missing_term'; source: string; target: string }
+	| { kind: 'forbidden_term'; term: string };
+
+// OCR返回的一行文本，坐标是发给插件的裁剪图中的像素
+export interface OcrRegion {
+	text: string;
+	x: number;
+	y: number;
+	w: number;
+	h: number;
+}
+
+export interface TranslationResult {
+	marker_id: number;
+	text: string;
+	service: string | null;
+	warnings: TranslationWarning[];
+	// 插件附带的模型、提示词哈希等信息，旧插件没有
+	metadata: TranslationMetadata | null;
+}
+
+export type TranslationMetadata = Record<string, unknown>;
+
+// 检测插件返回的候选框，坐标为原图像素
+export interface DetectedRegion {
+	x: number;
+	y: number;
+	w: number;
+	h: number;
+	score?: number;
+	label?: string;
+}
+
+// 批量接受机翻的结果：edited为机翻后译文被人工修改过而跳过的标记，locked为锁定的标记
+export interface AcceptMachineTranslationReport {
+	accepted: number[];
+	edited: number[];
+	locked: number[];
 }
 
 export interface BunnySettings {
@@ -0,0 +1,504 @@
+// 由 core/src/common/events/schema.rs 生成，请勿手动修改
+// 更新：UPDATE_EVENT_TYPES=1 cargo test -p bubblefish-core event_types
+
+export const EVENT_SCHEMA_VERSION = 1;
+
+export type EventLanguage = 'japanese' | 'english' | 'simplifiedChinese' | 'traditionalChinese';
+
+/** bunny:request_plugin_ocr */
+export interface RequestPluginOcrEvent {
+	schema_version: number;
+	task_id: string;
+	marker_id: number;
+	cropped_image_data: number[];
+	image_format: string;
+	service_id: string;
+	source_language: EventLanguage;
+	pixel_geometry: { type: 'point' | 'rectangle'; x: number; y: number; width?: number; height?: number } | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:request_plugin_translation */
+export interface RequestPluginTranslationEvent {
+	schema_version: number;
+	task_id: string;
+	marker_id: number;
+	image_id: number;
+	service_id: string;
+	text: string;
+	source_language: EventLanguage;
+	target_language: EventLanguage;
+	glossary: { entries?: { source: string; target: string }[]; forbiddenTerms?: string[] };
+	context: string[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:ocr_completed */
+export interface OcrCompletedEvent {
+	schema_version: number;
+	task_id: string;
+	marker_id: number;
+	original_text: string;
+	model: string;
+	regions: { text: string; x: number; y: number; w: number; h: number }[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:translation_completed */
+export interface TranslationCompletedEvent {
+	schema_version: number;
+	task_id: string;
+	marker_id: number;
+	machine_translation: string;
+	service: string;
+	warnings: ({ kind: 'missing_term'; source: string; target: string } | { kind: 'forbidden_term'; term: string })[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:request_plugin_detection */
+export interface RequestPluginDetectionEvent {
+	schema_version: number;
+	task_id: string;
+	image_id: number;
+	image_data: number[];
+	image_format: string;
+	width: number;
+	height: number;
+	service_id: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:detection_completed */
+export interface DetectionCompletedEvent {
+	schema_version: number;
+	task_id: string;
+	image_id: number;
+	model: string;
+	regions: { x: number; y: number; w: number; h: number; score?: number; label?: string }[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:machine_translation_accepted */
+export interface MachineTranslationAcceptedEvent {
+	schema_version: number;
+	image_id: number;
+	marker_ids: number[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:machine_translation_rejected */
+export interface MachineTranslationRejectedEvent {
+	schema_version: number;
+	marker_id: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:task_created */
+export interface TaskCreatedEvent {
+	schema_version: number;
+	task_id: string;
+	project_id: number;
+	marker_id: number;
+	image_id: number;
+	task_type: 'ocr' | 'translation' | 'detection';
+	service_id: string;
+	status: 'queued' | 'processing' | 'completed' | 'failed';
+	created_at: number;
+	started_at: number | null;
+	completed_at: number | null;
+	error: string | null;
+	tried_services: string[];
+	parent_pipeline_id: string | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:task_failed */
+export interface TaskFailedEvent {
+	schema_version: number;
+	task_id: string;
+	error: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:task_fellback */
+export interface TaskFellbackEvent {
+	schema_version: number;
+	task_id: string;
+	new_task_id: string;
+	marker_id: number;
+	from: string;
+	to: string;
+	error: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:pipeline_advanced */
+export interface PipelineAdvancedEvent {
+	schema_version: number;
+	pipeline_id: string;
+	marker_id: number;
+	ocr_task_id: string;
+	translation_task_id: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:pipeline_completed */
+export interface PipelineCompletedEvent {
+	schema_version: number;
+	pipeline_id: string;
+	marker_id: number;
+	translation_task_id: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:pipeline_failed */
+export interface PipelineFailedEvent {
+	schema_version: number;
+	pipeline_id: string;
+	marker_id: number;
+	stage: 'ocr' | 'translation';
+	error: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:batch_progress */
+export interface BatchProgressEvent {
+	schema_version: number;
+	project_id: number;
+	done: number;
+	total: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** bunny:image_progress */
+export interface ImageProgressEvent {
+	schema_version: number;
+	image_id: number;
+	done: number;
+	total: number;
+	failed: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** ui:notification */
+export interface NotificationEvent {
+	schema_version: number;
+	id: string | null;
+	title: string | null;
+	message: string;
+	level: 'info' | 'success' | 'warning' | 'error';
+	toast: boolean | null;
+	sticky: boolean | null;
+	auto_close: number | null;
+	source: string | null;
+	actions: { label: string; href?: string }[] | null;
+	extra: Record<string, unknown> | null;
+	from: string | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** ui:notification:dismiss */
+export interface NotificationDismissEvent {
+	schema_version: number;
+	id: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** ui:notification:clear */
+export interface NotificationClearEvent {
+	schema_version: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** stats:text_stats_changed */
+export interface TextStatsChangedEvent {
+	schema_version: number;
+	project_id: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** io:annotated_export_progress */
+export interface AnnotatedExportProgressEvent {
+	schema_version: number;
+	project_id: number;
+	current: number;
+	total: number;
+	name: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** io:import_progress */
+export interface ImportProgressEvent {
+	schema_version: number;
+	project_id: number;
+	done: number;
+	total: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** io:import_complete */
+export interface ImportCompleteEvent {
+	schema_version: number;
+	project_id: number;
+	imported_markers: number;
+	unmatched_images: string[];
+	locked_images: string[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:missing */
+export interface ImageMissingEvent {
+	schema_version: number;
+	project_id: number;
+	images: { image_id: number; name: string | null; path: string }[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:relinked */
+export interface ImageRelinkedEvent {
+	schema_version: number;
+	image_id: number;
+	path: string;
+	checksum_matches: boolean;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:evicted */
+export interface ImageEvictedEvent {
+	schema_version: number;
+	image_id: number;
+	reason: string;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:review_changed */
+export interface ImageReviewChangedEvent {
+	schema_version: number;
+	project_id: number;
+	image_id: number;
+	reviewed: boolean;
+	review_note: string | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:locked_changed */
+export interface ImageLockedChangedEvent {
+	schema_version: number;
+	project_id: number;
+	image_id: number;
+	locked: boolean;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:renamed */
+export interface ImageRenamedEvent {
+	schema_version: number;
+	project_id: number;
+	image_id: number;
+	name: string | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:view_restored */
+export interface ImageViewRestoredEvent {
+	schema_version: number;
+	project_id: number;
+	image_id: number;
+	zoom: number;
+	pan_x: number;
+	pan_y: number;
+	last_selected_marker: number | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** image:duplicate_scan_progress */
+export interface DuplicateScanProgressEvent {
+	schema_version: number;
+	project_id: number;
+	done: number;
+	total: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:created */
+export interface ProjectCreatedEvent {
+	schema_version: number;
+	project_id: number;
+	name: string;
+	source_project_id: number | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:reading_direction_changed */
+export interface ReadingDirectionChangedEvent {
+	schema_version: number;
+	project_id: number;
+	reading_direction: 'leftToRight' | 'rightToLeft';
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:settings_changed */
+export interface ProjectSettingsChangedEvent {
+	schema_version: number;
+	project_id: number;
+	setting: string;
+	value: unknown;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:services_changed */
+export interface ServicesChangedEvent {
+	schema_version: number;
+	project_id: number;
+	service_id: string;
+	enabled: boolean;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:glossary_changed */
+export interface GlossaryChangedEvent {
+	schema_version: number;
+	project_id: number;
+	entries: number;
+	forbidden_terms: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** opening_project:progress */
+export interface OpeningProjectProgressEvent {
+	schema_version: number;
+	project_id: number;
+	uploaded: number;
+	total: number;
+	estimated_savings_bytes: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** opening_project:cancelled */
+export interface OpeningProjectCancelledEvent {
+	schema_version: number;
+	project_id: number;
+	removed_images: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** plugin:timeout */
+export interface PluginTimeoutEvent {
+	schema_version: number;
+	plugin_id: string;
+	operation: string;
+	timeout_ms: number;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** system:panic */
+export interface SystemPanicEvent {
+	schema_version: number;
+	message: string;
+	location: string | null;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** project:snapshot_restored */
+export interface SnapshotRestoredEvent {
+	schema_version: number;
+	project_id: number;
+	snapshot_id: string;
+	name: string;
+	undone: boolean;
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+/** system:cross_origin_isolation_unavailable */
+export interface CrossOriginIsolationUnavailableEvent {
+	schema_version: number;
+	degraded: string[];
+	// 新版本可能增加字段
+	[key: string]: unknown;
+}
+
+export interface BusinessEventPayloads {
+	'bunny:request_plugin_ocr': RequestPluginOcrEvent;
+	'bunny:request_plugin_translation': RequestPluginTranslationEvent;
+	'bunny:ocr_completed': OcrCompletedEvent;
+	'bunny:translation_completed': TranslationCompletedEvent;
+	'bunny:request_plugin_detection': RequestPluginDetectionEvent;
+	'bunny:detection_completed': DetectionCompletedEvent;
+	'bunny:machine_translation_accepted': MachineTranslationAcceptedEvent;
+	'bunny:machine_translation_rejected': MachineTranslationRejectedEvent;
+	'bunny:task_created': TaskCreatedEvent;
+	'bunny:task_failed': TaskFailedEvent;
+	'bunny:task_fellback': TaskFellbackEvent;
+	'bunny:pipeline_advanced': PipelineAdvancedEvent;
+	'bunny:pipeline_completed': PipelineCompletedEvent;
+	'bunny:pipeline_failed': PipelineFailedEvent;
+	'bunny:batch_progress': BatchProgressEvent;
+	'bunny:image_progress': ImageProgressEvent;
+	'ui:notification': NotificationEvent;
+	'ui:notification:dismiss': NotificationDismissEvent;
+	'ui:notification:clear': NotificationClearEvent;
+	'stats:text_stats_changed': TextStatsChangedEvent;
+	'io:annotated_export_progress': AnnotatedExportProgressEvent;
+	'io:import_progress': ImportProgressEvent;
+	'io:import_complete': ImportCompleteEvent;
+	'image:missing': ImageMissingEvent;
+	'image:relinked': ImageRelinkedEvent;
+	'image:evicted': ImageEvictedEvent;
+	'image:review_changed': ImageReviewChangedEvent;
+	'image:locked_changed': ImageLockedChangedEvent;
+	'image:renamed': ImageRenamedEvent;
+	'image:view_restored': ImageViewRestoredEvent;
+	'image:duplicate_scan_progress': DuplicateScanProgressEvent;
+	'project:created': ProjectCreatedEvent;
+	'project:reading_direction_changed': ReadingDirectionChangedEvent;
+	'project:settings_changed': ProjectSettingsChangedEvent;
+	'project:services_changed': ServicesChangedEvent;
+	'project:glossary_changed': GlossaryChangedEvent;
+	'opening_project:progress': OpeningProjectProgressEvent;
+	'opening_project:cancelled': OpeningProjectCancelledEvent;
+	'plugin:timeout': PluginTimeoutEvent;
+	'system:panic': SystemPanicEvent;
+	'project:snapshot_restored': SnapshotRestoredEvent;
+	'system:cross_origin_isolation_unavailable': CrossOriginIsolationUnavailableEvent;
+}
@@ -59,16 +59,16 @@ class PluginWorker {
             
             // Initialize SharedArrayBuffer FIRST (before plugin.init)
             // This is required so service calls during init() will work
-            if (!sharedBuffer) {
-                throw new Error('SharedArrayBuffer is required but not provided');
-            }
-            
-            if (!instance.init_shared_buffer) {
-                throw new Error('Plugin does not support SharedArrayBuffer');
+            // 没有跨源隔离时不提供共享缓冲区，SDK改为异步发送服务调用（拿不到返回值）
+            if (sharedBuffer) {
+                if (!instance.init_shared_buffer) {
+                    throw new Error('Plugin does not support SharedArrayBuffer');
+                }
+                instance.init_shared_buffer(sharedBuffer);
+            } else {
+                console.warn(`[Worker] Loading ${pluginId} without SharedArrayBuffer, service calls are asynchronous`);
             }
             
-            instance.init_shared_buffer(sharedBuffer);
-            
             // NOW initialize the plugin
             // The SharedArrayBuffer channel is ready for service calls
             instance.init(pluginId);
@@ -192,6 +192,13 @@ self.addEventListener('message', async (event) => {
             break;
             
             
+        case 'SERVICE_CALL_RESPONSE':
+            // 降级模式下的异步服务调用，插件不等待结果，只记录失败
+            if (data.error) {
+                console.warn('[Worker] Async service call failed:', data.error);
+            }
+            break;
+            
         case 'ACTIVATE_PLUGIN':
             worker.activatePlugin(data.pluginId);
             break;
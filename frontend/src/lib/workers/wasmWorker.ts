@@ -34,6 +34,35 @@ let wasmBytesBuffer: ArrayBuffer | null = null;
 // 定义全局函数供WASM调用（在Worker上下文中）
 declare const self: DedicatedWorkerGlobalScope;
 
+// 超过帧大小上限的事件在核心被拆成 core:event_chunk 续传帧，这里按id拼回原事件
+const CHUNK_EVENT_NAME = 'core:event_chunk';
+const pendingChunks = new Map<number, { eventName: string; parts: string[]; received: number }>();
+
+function reassembleChunk(chunk: Record<string, unknown>): { eventName: string; payload: unknown } | null {
+	const id = chunk.id as number;
+	const total = chunk.total as number;
+	let pending = pendingChunks.get(id);
+	if (!pending) {
+		pending = { eventName: chunk.event_name as string, parts: new Array(total), received: 0 };
+		pendingChunks.set(id, pending);
+	}
+	const seq = chunk.seq as number;
+	if (pending.parts[seq] === undefined) {
+		pending.parts[seq] = chunk.payload as string;
+		pending.received++;
+	}
+	if (pending.received < total) {
+		return null;
+	}
+	pendingChunks.delete(id);
+	try {
+		return { eventName: pending.eventName, payload: JSON.parse(pending.parts.join('')) };
+	} catch (error) {
+		console.error(`Failed to reassemble chunked event ${pending.eventName}:`, error);
+		return null;
+	}
+}
+
 // 初始化WASM模块
 async function initWasmInWorker(): Promise<boolean> {
 	try {
@@ -128,6 +157,15 @@ async function initWasmInWorker(): Promise<boolean> {
 							eventPayload = data.event_data;
 						}
 						
+						if (eventName === CHUNK_EVENT_NAME && eventPayload) {
+							const assembled = reassembleChunk(eventPayload as Record<string, unknown>);
+							if (!assembled) {
+								return;
+							}
+							eventName = assembled.eventName;
+							eventPayload = assembled.payload;
+						}
+
 						if (eventName && eventPayload) {
 							// 检查是否是日志事件
 							if (eventName === 'core-log') {
@@ -1,8 +1,10 @@
 use bubblefish_plugin_sdk::{
     Plugin, PluginContext, ServiceProxyManager, CoreEvent, PluginMetadata,
     plugin_metadata_with_config, export_plugin,
-    ConfigSchema, ConfigField, SelectOption, ConfigValidation,
-    NotificationLevel, NotificationPayload, NotificationAction
+    ConfigSchema, ConfigKey, ConfigCache,
+    NotificationLevel, NotificationPayload, NotificationAction,
+    GlossaryEntry, GlossaryCheckOptions, GlossaryStrictness, TranslationWarning,
+    relevant_entries, validate_translation,
 };
 use serde_json::Value;
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,58 @@ const DEFAULT_MODEL: &str = "doubao-seed-1-6-250615";
 
 const DEFAULT_SYSTEM_PROMPT: &str = "你是一个专业的{source_lang}到{target_lang}的漫画翻译。请只输出翻译后的文本，不要任何解释或额外信息。";
 
+// 配置项，schema和读取共用
+const API_KEY: ConfigKey<String> = ConfigKey::new("api_key");
+const MODEL: ConfigKey<String> = ConfigKey::new("model");
+const SYSTEM_PROMPT: ConfigKey<String> = ConfigKey::new("system_prompt");
+const GLOSSARY_STRICTNESS: ConfigKey<String> = ConfigKey::new("glossary_strictness");
+const GLOSSARY_ON_VIOLATION: ConfigKey<String> = ConfigKey::new("glossary_on_violation");
+const GLOSSARY_FOLD_VARIANTS: ConfigKey<bool> = ConfigKey::new("glossary_fold_variants");
+
+fn config_schema() -> ConfigSchema {
+    ConfigSchema::builder()
+        .field(API_KEY.password("API Key")
+            .required()
+            .with_placeholder("请输入 Doubao API Key")
+            .with_help("从 Volcano Engine 控制台获取的 API Key")
+            .min_length(10))
+        .field(MODEL.select("模型选择", &[
+                ("doubao-seed-1-6-250615", "Doubao Seed 1.6"),
+                ("doubao-seed-1-6-flash-250828", "Doubao Seed 1.6 Flash"),
+                ("doubao-1-5-pro-32k-250115", "Doubao 1.5 Pro 32K"),
+            ])
+            .with_default(DEFAULT_MODEL)
+            .with_help("选择要使用的 Doubao 模型"))
+        .field(SYSTEM_PROMPT.textarea("系统提示词")
+            .with_placeholder("自定义翻译提示词，使用 {source_lang} 和 {target_lang} 作为占位符")
+            .with_help("留空使用默认提示词。可以使用 {source_lang} 和 {target_lang} 占位符")
+            .with_default(DEFAULT_SYSTEM_PROMPT))
+        .field(GLOSSARY_STRICTNESS.select("术语检查", &[
+                ("present", "使用过译法即可"),
+                ("count", "译法次数不少于原文"),
+                ("off", "不检查术语"),
+            ])
+            .with_default("present")
+            .with_help("检查原文中出现的项目术语是否按术语表翻译；禁用词总是检查"))
+        .field(GLOSSARY_ON_VIOLATION.select("术语不符时", &[
+                ("retry", "提示模型修正并重试一次"),
+                ("warn", "只在结果中标记警告"),
+            ])
+            .with_default("retry")
+            .with_help("重试后仍不符合的问题会作为警告随机翻保存"))
+        .field(GLOSSARY_FOLD_VARIANTS.switch("忽略假名和异体字差异")
+            .with_help("开启后平假名与片假名、全角与半角、髙/高等常见异体字视为相同"))
+        .build()
+}
+
+// 提示词模板的FNV-1a哈希，不依赖Rust版本，不同平台和版本的结果一致
+fn prompt_hash(template: &str) -> String {
+    let hash = template.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
 fn normalize_language_name(lang: &str) -> &str {
     match lang {
         "english" => "英语",
@@ -23,12 +77,66 @@ fn normalize_language_name(lang: &str) -> &str {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 struct ChatMessage {
     role: String,
     content: String,
 }
 
+impl ChatMessage {
+    fn new(role: &str, content: impl Into<String>) -> Self {
+        Self { role: role.to_string(), content: content.into() }
+    }
+}
+
+/// 一次翻译请求的术语约束：术语表、禁用词和校验方式
+struct GlossaryCheck {
+    source_text: String,
+    glossary: Vec<GlossaryEntry>,
+    forbidden_terms: Vec<String>,
+    options: GlossaryCheckOptions,
+    /// 校验不通过时是否带上纠正提示重试一次，否则只附带warnings
+    retry_on_violation: bool,
+}
+
+impl GlossaryCheck {
+    fn is_empty(&self) -> bool {
+        self.glossary.is_empty() && self.forbidden_terms.is_empty()
+    }
+
+    /// 追加在系统提示词后的术语约束，只列出原文中出现的术语
+    fn prompt_section(&self) -> String {
+        let mut section = String::new();
+        let entries = relevant_entries(&self.source_text, &self.glossary, &self.options);
+        if !entries.is_empty() {
+            section.push_str("\n\n术语表（原文中出现时必须使用对应译法）：");
+            for entry in entries {
+                section.push_str(&format!("\n- {} → {}", entry.source, entry.target));
+            }
+        }
+        if !self.forbidden_terms.is_empty() {
+            section.push_str("\n\n禁用词（译文中不得出现）：");
+            for term in &self.forbidden_terms {
+                section.push_str(&format!("\n- {}", term));
+            }
+        }
+        section
+    }
+
+    fn validate(&self, translated_text: &str) -> Vec<TranslationWarning> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        validate_translation(&self.source_text, translated_text, &self.glossary, &self.forbidden_terms, &self.options)
+    }
+
+    /// 重试时发给模型的纠正消息
+    fn corrective_message(warnings: &[TranslationWarning]) -> String {
+        let problems: Vec<String> = warnings.iter().map(|warning| format!("- {}", warning.message())).collect();
+        format!("上面的译文不符合术语要求：\n{}\n请修正后重新输出完整译文，仍然只输出译文。", problems.join("\n"))
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ThinkingConfig {
     #[serde(rename = "type")]
@@ -60,6 +168,8 @@ struct ChatResponse {
 pub struct DoubaoTranslationPlugin {
     context: Option<PluginContext>,
     services: Option<ServiceProxyManager>,
+    // 配置值缓存，收到ConfigChanged时更新，修改设置后下一次翻译即生效
+    config: ConfigCache,
 }
 
 impl DoubaoTranslationPlugin {
@@ -67,6 +177,7 @@ impl DoubaoTranslationPlugin {
         Self {
             context: None,
             services: None,
+            config: ConfigCache::default(),
         }
     }
 
@@ -110,10 +221,7 @@ impl DoubaoTranslationPlugin {
     fn get_api_key(&self) -> Result<String, String> {
         if let Some(ctx) = &self.context {
             // Get API key from config service
-            match ctx.call_service("config", "get", serde_json::json!({
-                "plugin_id": ctx.plugin_id.clone(),
-                "key": "api_key"
-            })) {
+            match self.config.get(ctx, API_KEY.key()) {
                 Ok(value) => {
                     if let Some(api_key) = value.as_str() {
                         if !api_key.is_empty() {
@@ -149,46 +257,57 @@ impl DoubaoTranslationPlugin {
     }
 
     fn get_model(&self) -> String {
-        if let Some(ctx) = &self.context {
-            // Get model from config service, fallback to default
-            match ctx.call_service("config", "get", serde_json::json!({
-                "plugin_id": ctx.plugin_id.clone(),
-                "key": "model"
-            })) {
-                Ok(value) => {
-                    if let Some(model) = value.as_str() {
-                        if !model.is_empty() {
-                            return model.to_string();
-                        }
-                    }
-                    DEFAULT_MODEL.to_string()
-                }
-                Err(_) => DEFAULT_MODEL.to_string()
-            }
-        } else {
-            DEFAULT_MODEL.to_string()
-        }
+        self.config_value(MODEL).unwrap_or_else(|| DEFAULT_MODEL.to_string())
     }
 
     fn get_system_prompt_template(&self) -> String {
-        if let Some(ctx) = &self.context {
-            // Get custom prompt from config service, fallback to default
-            match ctx.call_service("config", "get", serde_json::json!({
-                "plugin_id": ctx.plugin_id.clone(),
-                "key": "system_prompt"
-            })) {
-                Ok(value) => {
-                    if let Some(prompt) = value.as_str() {
-                        if !prompt.is_empty() {
-                            return prompt.to_string();
-                        }
-                    }
-                    DEFAULT_SYSTEM_PROMPT.to_string()
-                }
-                Err(_) => DEFAULT_SYSTEM_PROMPT.to_string()
-            }
-        } else {
-            DEFAULT_SYSTEM_PROMPT.to_string()
+        self.config_value(SYSTEM_PROMPT).unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string())
+    }
+
+    // 随译文交给Core的元数据，事后可以区分译文来自哪个模型和提示词
+    fn translation_metadata(&self) -> Value {
+        serde_json::json!({
+            "model": self.get_model(),
+            "prompt_hash": prompt_hash(&self.get_system_prompt_template()),
+            "plugin_version": env!("CARGO_PKG_VERSION"),
+            "glossary_strictness": self.config_value(GLOSSARY_STRICTNESS).unwrap_or_else(|| "present".to_string()),
+        })
+    }
+
+    fn config_value<T: bubblefish_plugin_sdk::ConfigValue>(&self, key: ConfigKey<T>) -> Option<T> {
+        self.config.value(self.context.as_ref()?, key)
+    }
+
+    fn build_glossary_check(&self, text: &str, options: Option<&Value>) -> GlossaryCheck {
+        let glossary = options
+            .and_then(|o| o.get("glossary"))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let forbidden_terms = options
+            .and_then(|o| o.get("forbidden_terms"))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let check_options = GlossaryCheckOptions {
+            strictness: GlossaryStrictness::from_config(&self.config_value(GLOSSARY_STRICTNESS).unwrap_or_default()),
+            fold_variants: self.config_value(GLOSSARY_FOLD_VARIANTS).unwrap_or(false),
+            ..GlossaryCheckOptions::default()
+        };
+        GlossaryCheck {
+            source_text: text.to_string(),
+            glossary,
+            forbidden_terms,
+            options: check_options,
+            retry_on_violation: self.config_value(GLOSSARY_ON_VIOLATION).as_deref() != Some("warn"),
+        }
+    }
+
+    // 插件实例的副本，供异步任务使用
+    #[cfg(target_arch = "wasm32")]
+    fn handle(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            services: self.services.clone(),
+            config: self.config.clone(),
         }
     }
 
@@ -201,32 +320,27 @@ impl DoubaoTranslationPlugin {
             .replace("{target_lang}", target)
     }
 
+    // 模型每次请求时从配置缓存读取
+    fn chat_request(&self, messages: &[ChatMessage]) -> ChatRequest {
+        ChatRequest {
+            model: self.get_model(),
+            messages: messages.to_vec(),
+            thinking: ThinkingConfig {
+                thinking_type: "disabled".to_string(),
+            },
+        }
+    }
+
     #[cfg(target_arch = "wasm32")]
-    async fn call_doubao_api(&self, system_prompt: &str, user_text: &str) -> Result<String, String> {
+    async fn call_doubao_api(&self, messages: &[ChatMessage]) -> Result<String, String> {
         use wasm_bindgen::JsValue;
         use wasm_bindgen_futures::JsFuture;
         use web_sys::{Request, RequestInit, RequestMode, Response, Headers};
 
         // Get API key first
         let api_key = self.get_api_key()?;
-        let model = self.get_model();
-
-        let request_body = ChatRequest {
-            model,
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: system_prompt.to_string(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: user_text.to_string(),
-                }
-            ],
-            thinking: ThinkingConfig {
-                thinking_type: "disabled".to_string(),
-            },
-        };
+
+        let request_body = self.chat_request(messages);
 
         let body_json = serde_json::to_string(&request_body)
             .map_err(|e| format!("Failed to serialize request: {}", e))?;
@@ -269,29 +383,15 @@ impl DoubaoTranslationPlugin {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn call_doubao_api_sync(&self, system_prompt: &str, user_text: &str) -> Result<String, String> {
+    fn call_doubao_api_sync(&self, messages: &[ChatMessage]) -> Result<String, String> {
         // Get API key first
         let api_key = self.get_api_key()?;
-        let model = self.get_model();
-
-        let client = reqwest::blocking::Client::new();
-
-        let request_body = ChatRequest {
-            model,
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: system_prompt.to_string(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: user_text.to_string(),
-                }
-            ],
-            thinking: ThinkingConfig {
-                thinking_type: "disabled".to_string(),
-            },
-        };
+
+        // 按宿主的网络设置使用代理和自定义证书，设置无效时SDK已经推送过通知
+        let ctx = self.context.as_ref().ok_or("Plugin context not initialized")?;
+        let client = bubblefish_plugin_sdk::http::http_client(ctx)?;
+
+        let request_body = self.chat_request(messages);
 
         let response = client
             .post(API_ENDPOINT)
@@ -311,6 +411,64 @@ impl DoubaoTranslationPlugin {
             .ok_or_else(|| "No response from API".to_string())
     }
 
+    /// 翻译并校验术语，违反时按配置带纠正提示重试一次；返回译文和仍未解决的问题
+    #[cfg(target_arch = "wasm32")]
+    async fn translate_checked(&self, system_prompt: &str, check: &GlossaryCheck) -> Result<(String, Vec<TranslationWarning>), String> {
+        let mut messages = vec![
+            ChatMessage::new("system", format!("{}{}", system_prompt, check.prompt_section())),
+            ChatMessage::new("user", check.source_text.clone()),
+        ];
+        let translated_text = self.call_doubao_api(&messages).await?;
+        let warnings = check.validate(&translated_text);
+        if warnings.is_empty() || !check.retry_on_violation {
+            return Ok((translated_text, warnings));
+        }
+
+        self.log(&format!("Glossary check failed ({} problems), retrying once", warnings.len()));
+        messages.push(ChatMessage::new("assistant", translated_text.clone()));
+        messages.push(ChatMessage::new("user", GlossaryCheck::corrective_message(&warnings)));
+        match self.call_doubao_api(&messages).await {
+            Ok(retried) => {
+                let retried_warnings = check.validate(&retried);
+                Ok((retried, retried_warnings))
+            }
+            // 重试失败时保留第一次的译文
+            Err(e) => {
+                self.log(&format!("Glossary retry failed: {}", e));
+                Ok((translated_text, warnings))
+            }
+        }
+    }
+
+    /// 同translate_checked，原生平台的同步版本
+    #[cfg(not(target_arch = "wasm32"))]
+    fn translate_checked_sync(&self, system_prompt: &str, check: &GlossaryCheck) -> Result<(String, Vec<TranslationWarning>), String> {
+        let mut messages = vec![
+            ChatMessage::new("system", format!("{}{}", system_prompt, check.prompt_section())),
+            ChatMessage::new("user", check.source_text.clone()),
+        ];
+        let translated_text = self.call_doubao_api_sync(&messages)?;
+        let warnings = check.validate(&translated_text);
+        if warnings.is_empty() || !check.retry_on_violation {
+            return Ok((translated_text, warnings));
+        }
+
+        self.log(&format!("Glossary check failed ({} problems), retrying once", warnings.len()));
+        messages.push(ChatMessage::new("assistant", translated_text.clone()));
+        messages.push(ChatMessage::new("user", GlossaryCheck::corrective_message(&warnings)));
+        match self.call_doubao_api_sync(&messages) {
+            Ok(retried) => {
+                let retried_warnings = check.validate(&retried);
+                Ok((retried, retried_warnings))
+            }
+            // 重试失败时保留第一次的译文
+            Err(e) => {
+                self.log(&format!("Glossary retry failed: {}", e));
+                Ok((translated_text, warnings))
+            }
+        }
+    }
+
     fn handle_translation_request(&self, message: Value) {
         let ctx = match &self.context {
             Some(c) => c.clone(),
@@ -339,23 +497,27 @@ impl DoubaoTranslationPlugin {
             text.len(), source_lang, target_lang));
 
         let system_prompt = self.build_system_prompt(source_lang, target_lang);
-        let user_text = text.to_string();
+        let check = self.build_glossary_check(text, options);
+        // 按发出请求时的配置记录，翻译途中修改设置不影响
+        let metadata = self.translation_metadata();
 
         #[cfg(target_arch = "wasm32")]
         {
-            let self_clone = Self::new();
+            let self_clone = self.handle();
             let ctx_clone = ctx.clone();
             let task_id_clone = task_id.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                match self_clone.call_doubao_api(&system_prompt, &user_text).await {
-                    Ok(translated_text) => {
+                match self_clone.translate_checked(&system_prompt, &check).await {
+                    Ok((translated_text, warnings)) => {
                         self_clone.log(&format!("Translation successful: {}", translated_text));
 
                         let event = serde_json::json!({
                             "task_id": task_id_clone,
                             "translated_text": translated_text,
-                            "service": "doubao-translate"
+                            "service": "doubao-translate",
+                            "warnings": warnings,
+                            "metadata": metadata
                         });
 
                         let _ = ctx_clone.call_service("events", "emit_business_event", serde_json::json!({
@@ -392,14 +554,16 @@ impl DoubaoTranslationPlugin {
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            match self.call_doubao_api_sync(&system_prompt, &user_text) {
-                Ok(translated_text) => {
+            match self.translate_checked_sync(&system_prompt, &check) {
+                Ok((translated_text, warnings)) => {
                     self.log(&format!("Translation successful: {}", translated_text));
 
                     let event = serde_json::json!({
                         "task_id": task_id,
                         "translated_text": translated_text,
-                        "service": "doubao-translate"
+                        "service": "doubao-translate",
+                        "warnings": warnings,
+                        "metadata": metadata
                     });
 
                     let _ = ctx.call_service("events", "emit_business_event", serde_json::json!({
@@ -437,6 +601,7 @@ impl DoubaoTranslationPlugin {
 
 impl Plugin for DoubaoTranslationPlugin {
     fn init(&mut self, context: PluginContext, services: ServiceProxyManager) -> Result<(), String> {
+        self.config = ConfigCache::new(context.plugin_id.clone());
         self.context = Some(context.clone());
         self.services = Some(services);
 
@@ -465,7 +630,10 @@ impl Plugin for DoubaoTranslationPlugin {
         Ok(())
     }
 
-    fn on_core_event(&mut self, _event: &CoreEvent) -> Result<(), String> {
+    fn on_core_event(&mut self, event: &CoreEvent) -> Result<(), String> {
+        if self.config.handle_event(event) {
+            self.log("Config updated");
+        }
         Ok(())
     }
 
@@ -503,42 +671,107 @@ impl Plugin for DoubaoTranslationPlugin {
     }
 
     fn get_metadata(&self) -> PluginMetadata {
-        let config_schema = ConfigSchema::simple(vec![
+        let config_schema = config_schema();
+        plugin_metadata_with_config!(config_schema, "*")
+    }
+}
+
+export_plugin!(DoubaoTranslationPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLUGIN_ID: &str = "doubao-translation-plugin";
+
+    fn model_changed(model: &str) -> CoreEvent {
+        CoreEvent::ConfigChanged {
+            plugin_id: PLUGIN_ID.to_string(),
+            key: "model".to_string(),
+            value: Value::from(model),
+        }
+    }
+
+    #[test]
+    fn model_change_applies_to_next_request_without_reinit() {
+        let mut plugin = DoubaoTranslationPlugin::new();
+        plugin.context = Some(PluginContext::new(PLUGIN_ID.to_string()));
+        plugin.config = ConfigCache::new(PLUGIN_ID);
+        let messages = [ChatMessage::new("user", "こんにちは")];
+
+        plugin.on_core_event(&model_changed("doubao-seed-1-6-flash-250615")).unwrap();
+        assert_eq!(plugin.chat_request(&messages).model, "doubao-seed-1-6-flash-250615");
+
+        plugin.on_core_event(&model_changed("doubao-1-5-pro-32k-250115")).unwrap();
+        assert_eq!(plugin.chat_request(&messages).model, "doubao-1-5-pro-32k-250115");
+
+        // 清空后退回默认模型
+        plugin.on_core_event(&model_changed("")).unwrap();
+        assert_eq!(plugin.chat_request(&messages).model, DEFAULT_MODEL);
+    }
+
+    // 迁移到ConfigKey之前手写的schema，生成的JSON必须与它逐字节一致，已保存的配置才能照常读取
+    fn hand_written_schema() -> ConfigSchema {
+        use bubblefish_plugin_sdk::{ConfigField, ConfigValidation, SelectOption};
+        ConfigSchema::simple(vec![
             ConfigField::password("api_key", "API Key")
                 .required()
                 .with_placeholder("请输入 Doubao API Key")
                 .with_help("从 Volcano Engine 控制台获取的 API Key")
                 .with_validation(vec![ConfigValidation::MinLength(10)]),
-
-            ConfigField::select(
-                "model",
-                "模型选择",
-                vec![
-                    SelectOption {
-                        value: "doubao-seed-1-6-250615".to_string(),
-                        label: "Doubao Seed 1.6".to_string()
-                    },
-                    SelectOption {
-                        value: "doubao-seed-1-6-flash-250828".to_string(),
-                        label: "Doubao Seed 1.6 Flash".to_string()
-                    },
-                    SelectOption {
-                        value: "doubao-1-5-pro-32k-250115".to_string(),
-                        label: "Doubao 1.5 Pro 32K".to_string()
-                    }
-                ]
-            )
+            ConfigField::select("model", "模型选择", vec![
+                SelectOption::new("doubao-seed-1-6-250615", "Doubao Seed 1.6"),
+                SelectOption::new("doubao-seed-1-6-flash-250828", "Doubao Seed 1.6 Flash"),
+                SelectOption::new("doubao-1-5-pro-32k-250115", "Doubao 1.5 Pro 32K"),
+            ])
             .with_default("doubao-seed-1-6-250615")
             .with_help("选择要使用的 Doubao 模型"),
-
             ConfigField::textarea("system_prompt", "系统提示词")
                 .with_placeholder("自定义翻译提示词，使用 {source_lang} 和 {target_lang} 作为占位符")
                 .with_help("留空使用默认提示词。可以使用 {source_lang} 和 {target_lang} 占位符")
                 .with_default(DEFAULT_SYSTEM_PROMPT),
-        ]);
+            ConfigField::select("glossary_strictness", "术语检查", vec![
+                SelectOption::new("present", "使用过译法即可"),
+                SelectOption::new("count", "译法次数不少于原文"),
+                SelectOption::new("off", "不检查术语"),
+            ])
+            .with_default("present")
+            .with_help("检查原文中出现的项目术语是否按术语表翻译；禁用词总是检查"),
+            ConfigField::select("glossary_on_violation", "术语不符时", vec![
+                SelectOption::new("retry", "提示模型修正并重试一次"),
+                SelectOption::new("warn", "只在结果中标记警告"),
+            ])
+            .with_default("retry")
+            .with_help("重试后仍不符合的问题会作为警告随机翻保存"),
+            ConfigField::switch("glossary_fold_variants", "忽略假名和异体字差异")
+                .with_help("开启后平假名与片假名、全角与半角、髙/高等常见异体字视为相同"),
+        ])
+    }
 
-        plugin_metadata_with_config!(config_schema, "*")
+    #[test]
+    fn config_schema_json_unchanged() {
+        let schema = config_schema();
+        assert!(schema.validate().is_ok());
+        assert_eq!(serde_json::to_string(&schema).unwrap(), serde_json::to_string(&hand_written_schema()).unwrap());
     }
-}
 
-export_plugin!(DoubaoTranslationPlugin);
\ No newline at end of file
+    #[test]
+    fn metadata_follows_effective_config() {
+        let mut plugin = DoubaoTranslationPlugin::new();
+        plugin.context = Some(PluginContext::new(PLUGIN_ID.to_string()));
+        plugin.config = ConfigCache::new(PLUGIN_ID);
+        plugin.on_core_event(&model_changed("doubao-1-5-pro-32k-250115")).unwrap();
+
+        let metadata = plugin.translation_metadata();
+        assert_eq!(metadata["model"], "doubao-1-5-pro-32k-250115");
+        assert_eq!(metadata["prompt_hash"], prompt_hash(DEFAULT_SYSTEM_PROMPT));
+        assert_eq!(metadata["plugin_version"], env!("CARGO_PKG_VERSION"));
+
+        plugin.on_core_event(&CoreEvent::ConfigChanged {
+            plugin_id: PLUGIN_ID.to_string(),
+            key: "system_prompt".to_string(),
+            value: Value::from("把{source_lang}译成{target_lang}"),
+        }).unwrap();
+        assert_ne!(plugin.translation_metadata()["prompt_hash"], metadata["prompt_hash"]);
+    }
+}
@@ -72,15 +72,14 @@ impl Plugin for DummyOCRPlugin {
                     .unwrap_or("")
                     .to_string();
 
-                // Extract image data
-                let image_data = message.get("image_data")
-                    .and_then(|v| v.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_u64().map(|n| n as u8))
-                            .collect::<Vec<u8>>()
-                    })
-                    .unwrap_or_else(Vec::new);
+                // Extract image data (from the shared buffer when the frontend staged it there)
+                let image_data = match &self.context {
+                    Some(ctx) => bubblefish_plugin_sdk::ocr_request_image(ctx, &message).unwrap_or_else(|e| {
+                        self.log(&format!("Failed to read OCR image: {}", e));
+                        Vec::new()
+                    }),
+                    None => Vec::new(),
+                };
 
                 // Extract image format (always "png" from backend after cropping)
                 let image_format = message.get("image_format")
@@ -221,4 +220,34 @@ impl DummyOCRPlugin {
 }
 
 // Export the plugin
-export_plugin!(DummyOCRPlugin);
\ No newline at end of file
+export_plugin!(DummyOCRPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bubblefish_plugin_sdk::testing::PluginHarness;
+    use serde_json::json;
+
+    #[test]
+    fn ocr_request_emits_result_sized_by_image() {
+        let mut harness = PluginHarness::new(DummyOCRPlugin::new(), "dummy-ocr-plugin");
+        harness.init().unwrap();
+        assert_eq!(harness.host().calls_to("bunny", "register_ocr_service").len(), 1);
+        // 订阅了所有事件
+        assert!(harness.dispatch_event(&CoreEvent::SystemReady).unwrap());
+
+        harness.send_message("bunny", json!({
+            "type": "ocr_request",
+            "task_id": "o1",
+            "image_data": vec![0u8; 16],
+            "options": { "source_language": "ja" }
+        })).unwrap();
+
+        let events = harness.host().emitted_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name, "plugin:ocr_result");
+        let text = events[0].data["text"].as_str().unwrap();
+        assert!(text.starts_with("Short text:"));
+        assert!(text.contains("Language: ja") && text.contains("Image size: 16 bytes"));
+    }
+}
@@ -38,6 +38,7 @@ impl Plugin for DummyTranslationPlugin {
             let service_info = serde_json::json!({
                 "id": "dummy-translate",
                 "name": "Dummy Translation Service",
+                "version": "1.0.0",
                 "plugin_id": ctx.plugin_id.clone(),
                 "source_languages": ["en", "zh", "ja", "auto"],
                 "target_languages": ["en", "zh", "ja", "ko", "fr", "de"],
@@ -195,4 +196,39 @@ impl DummyTranslationPlugin {
 }
 
 // Export the plugin
-export_plugin!(DummyTranslationPlugin);
\ No newline at end of file
+export_plugin!(DummyTranslationPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bubblefish_plugin_sdk::testing::PluginHarness;
+    use serde_json::json;
+
+    #[test]
+    fn registers_service_and_emits_translation_result() {
+        let mut harness = PluginHarness::new(DummyTranslationPlugin::new(), "dummy-translation-plugin");
+        harness.init().unwrap();
+
+        let register = harness.host().calls_to("bunny", "register_translation_service");
+        assert_eq!(register.len(), 1);
+        assert_eq!(register[0].params["service_info"]["id"], "dummy-translate");
+
+        harness.send_message("bunny", json!({
+            "type": "translation_request",
+            "task_id": "t1",
+            "text": "你好",
+            "options": { "source_language": "zh", "target_language": "en" }
+        })).unwrap();
+        // 其他类型的消息不产生结果
+        harness.send_message("bunny", json!({ "type": "ocr_request", "task_id": "t2" })).unwrap();
+
+        let events = harness.host().emitted_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_name, "plugin:translation_result");
+        assert_eq!(events[0].data["task_id"], "t1");
+        assert!(events[0].data["translated_text"].as_str().unwrap().starts_with("Hello\n"));
+
+        let host = harness.shutdown().unwrap();
+        assert_eq!(host.calls_to("bunny", "unregister_service")[0].params, json!({ "service_id": "dummy-translate" }));
+    }
+}
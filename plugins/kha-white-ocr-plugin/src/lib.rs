@@ -346,14 +346,24 @@ impl Plugin for KhaWhiteOCRPlugin {
                     .unwrap_or("")
                     .to_string();
 
-                let image_data = message.get("image_data")
-                    .and_then(|v| v.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_u64().map(|n| n as u8))
-                            .collect::<Vec<u8>>()
-                    })
-                    .unwrap_or_else(Vec::new);
+                let image_data = match &self.context {
+                    Some(ctx) => match bubblefish_plugin_sdk::ocr_request_image(ctx, &message) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            let error_msg = format!("Failed to read OCR image: {}", e);
+                            self.log(&error_msg);
+                            let _ = ctx.call_service("events", "emit_business_event", serde_json::json!({
+                                "event_name": "plugin:ocr_error",
+                                "data": {
+                                    "task_id": task_id,
+                                    "error": error_msg
+                                }
+                            }));
+                            return Ok(());
+                        }
+                    },
+                    None => Vec::new(),
+                };
 
                 self.log(&format!("Processing OCR for {} bytes", image_data.len()));
 
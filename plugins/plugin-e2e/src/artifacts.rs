@@ -0,0 +1,44 @@
+// 编译测试用的原生插件 - 用单独的target目录，不和外层cargo test争用构建锁
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+static BUILT: Mutex<Option<HashMap<String, PathBuf>>> = Mutex::new(None);
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+/// 插件库的文件名：dummy-ocr-plugin -> libdummy_ocr_plugin.so / dummy_ocr_plugin.dll
+pub fn plugin_library_name(package: &str) -> String {
+    format!("{}{}{}", std::env::consts::DLL_PREFIX, package.replace('-', "_"), std::env::consts::DLL_SUFFIX)
+}
+
+/// 以native特性编译工作区中的插件包，返回动态库路径；同一进程中每个包只编译一次
+pub fn build_native_plugin(package: &str) -> Result<PathBuf, String> {
+    let mut built = BUILT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let built = built.get_or_insert_with(HashMap::new);
+    if let Some(path) = built.get(package) {
+        return Ok(path.clone());
+    }
+
+    let target_dir = workspace_root().join("target").join("plugin-e2e");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .current_dir(workspace_root())
+        .args(["build", "--quiet", "-p", package, "--features", "native", "--target-dir"])
+        .arg(&target_dir)
+        .output()
+        .map_err(|e| format!("Failed to run cargo for {}: {}", package, e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to build {}:\n{}", package, String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let path = target_dir.join("debug").join(plugin_library_name(package));
+    if !path.is_file() {
+        return Err(format!("Built {} but {} does not exist", package, path.display()));
+    }
+    built.insert(package.to_string(), path.clone());
+    Ok(path)
+}
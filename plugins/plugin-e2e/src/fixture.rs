@@ -0,0 +1,121 @@
+// 测试用的内存项目 - 用core的API建项目、生成PNG图片并添加标记，drop时删除项目
+use std::io::Cursor;
+use bubblefish_core::api;
+use bubblefish_core::common::dto::image::ImageFormat;
+use bubblefish_core::common::{ImageId, MarkerId, ProjectId};
+
+/// 标记坐标与core一致，为图片宽高的百分比
+#[derive(Debug, Clone, Copy)]
+pub enum FixtureMarker {
+    Point { x: f64, y: f64 },
+    Rect { x: f64, y: f64, width: f64, height: f64 },
+}
+
+struct ImageSpec {
+    width: u32,
+    height: u32,
+    markers: Vec<FixtureMarker>,
+}
+
+/// 项目构建器，point/rect添加到最近一次image添加的图片上
+pub struct ProjectBuilder {
+    name: String,
+    images: Vec<ImageSpec>,
+}
+
+impl ProjectBuilder {
+    /// 添加一张纯黑PNG图片
+    pub fn image(mut self, width: u32, height: u32) -> Self {
+        self.images.push(ImageSpec { width, height, markers: Vec::new() });
+        self
+    }
+
+    pub fn point(self, x: f64, y: f64) -> Self {
+        self.marker(FixtureMarker::Point { x, y })
+    }
+
+    pub fn rect(self, x: f64, y: f64, width: f64, height: f64) -> Self {
+        self.marker(FixtureMarker::Rect { x, y, width, height })
+    }
+
+    fn marker(mut self, marker: FixtureMarker) -> Self {
+        self.images.last_mut()
+            .expect("add an image before adding markers")
+            .markers.push(marker);
+        self
+    }
+
+    pub fn build(self) -> Result<FixtureProject, String> {
+        let project_id = bubblefish_core::service::get_service().project_service
+            .create_project(self.name)
+            .map_err(|e| e.to_string())?;
+        // 先创建FixtureProject，中途失败时drop会删除已建的项目
+        let mut fixture = FixtureProject { project_id, images: Vec::new() };
+        for (index, spec) in self.images.into_iter().enumerate() {
+            let name = format!("{:02}.png", index + 1);
+            let image_id = api::image::add_image_from_binary_to_project(project_id.0, ImageFormat::Png, png_bytes(spec.width, spec.height)?, Some(name.clone()))
+                .ok_or_else(|| format!("Failed to add image {}", name))?;
+            let mut marker_ids = Vec::new();
+            for marker in spec.markers {
+                let marker_id = match marker {
+                    FixtureMarker::Point { x, y } => api::marker::add_point_marker_to_image(image_id, x, y, None, None)?,
+                    FixtureMarker::Rect { x, y, width, height } => api::marker::add_rectangle_marker_to_image(image_id, x, y, width, height, None, None)?,
+                }.ok_or_else(|| format!("Failed to add marker to {}", name))?;
+                marker_ids.push(MarkerId::from(marker_id));
+            }
+            fixture.images.push(FixtureImage { image_id: ImageId::from(image_id), marker_ids });
+        }
+        Ok(fixture)
+    }
+}
+
+fn png_bytes(width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    image::RgbImage::new(width, height)
+        .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(data)
+}
+
+#[derive(Debug, Clone)]
+pub struct FixtureImage {
+    pub image_id: ImageId,
+    pub marker_ids: Vec<MarkerId>,
+}
+
+/// 已创建的项目，图片和标记按添加顺序排列
+#[derive(Debug)]
+pub struct FixtureProject {
+    pub project_id: ProjectId,
+    pub images: Vec<FixtureImage>,
+}
+
+impl FixtureProject {
+    pub fn builder(name: &str) -> ProjectBuilder {
+        ProjectBuilder { name: name.to_string(), images: Vec::new() }
+    }
+
+    /// 第image张图片的第marker个标记
+    pub fn marker(&self, image: usize, marker: usize) -> (ImageId, MarkerId) {
+        let fixture_image = &self.images[image];
+        (fixture_image.image_id, fixture_image.marker_ids[marker])
+    }
+
+    /// 对标记发起OCR，返回任务ID
+    pub fn request_ocr(&self, image: usize, marker: usize, service_id: &str) -> Result<String, String> {
+        let (image_id, marker_id) = self.marker(image, marker);
+        api::bunny::request_ocr(marker_id, image_id, self.project_id, service_id.to_string())
+    }
+
+    /// 对标记发起OCR+翻译流水线，返回流水线ID
+    pub fn request_pipeline(&self, image: usize, marker: usize, ocr_service: &str, translation_service: &str) -> Result<String, String> {
+        let (image_id, marker_id) = self.marker(image, marker);
+        api::bunny::request_pipeline(marker_id, image_id, self.project_id, ocr_service.to_string(), translation_service.to_string())
+    }
+}
+
+impl Drop for FixtureProject {
+    fn drop(&mut self) {
+        api::project::delete_project(self.project_id.0);
+    }
+}
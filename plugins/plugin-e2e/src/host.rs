@@ -0,0 +1,487 @@
+// 进程内的原生插件宿主 - 插件直接调用core，替代桌面端加载器和前端的bunny中转
+// 插件的回调是extern "C"函数，拿不到宿主实例，所以宿主是进程内唯一的全局单例
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use libloading::Library;
+use serde_json::Value;
+
+use bubblefish_core::api::bunny;
+use bubblefish_core::common::{Event, EventEmitter, EventType, EVENT_SYSTEM};
+use bubblefish_core::plugin::ServiceRegistry;
+use bubblefish_core::service::bunny::TASK_MANAGER;
+
+/// 宿主支持的插件接口版本范围，与桌面端加载器一致
+pub const MIN_PLUGIN_API_VERSION: u32 = 1;
+pub const MAX_PLUGIN_API_VERSION: u32 = 1;
+
+/// Callbacks provided to plugins（内存布局必须与插件SDK一致）
+#[repr(C)]
+pub struct HostCallbacks {
+    pub call_service: extern "C" fn(
+        plugin_id: *const c_char,
+        service: *const c_char,
+        method: *const c_char,
+        params: *const c_char,
+    ) -> *mut c_char,
+    pub read_image_file: extern "C" fn(
+        file_path: *const c_char,
+        data_ptr: *mut *mut u8,
+        data_len: *mut usize,
+    ) -> i32,
+    pub free_host_memory: extern "C" fn(ptr: *mut c_void),
+    pub log_message: extern "C" fn(level: i32, message: *const c_char),
+}
+
+/// 已加载插件的元数据
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LoadedPluginInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub subscribed_events: Vec<String>,
+}
+
+struct LoadedPlugin {
+    library: Arc<Library>,
+    info: LoadedPluginInfo,
+}
+
+pub struct InProcessHost {
+    registry: Arc<ServiceRegistry>,
+    plugins: Mutex<HashMap<String, LoadedPlugin>>,
+    // 插件配置只保存在内存中
+    configs: Mutex<HashMap<String, Value>>,
+    events: Mutex<Receiver<(String, Value)>>,
+    logs: Mutex<Vec<String>>,
+    session: Mutex<()>,
+}
+
+static HOST: OnceLock<InProcessHost> = OnceLock::new();
+
+// 把core的业务事件转到pump_until；不能在emit里直接调用插件，插件回调时会重入事件系统
+struct EventRelay {
+    sender: Mutex<Sender<(String, Value)>>,
+}
+
+impl EventEmitter for EventRelay {
+    fn emit(&self, event: Event) -> Result<(), String> {
+        if matches!(event.event_type, EventType::Business) {
+            let _ = self.sender.lock().unwrap().send((event.event_name, event.data));
+        }
+        Ok(())
+    }
+}
+
+impl InProcessHost {
+    /// 全局宿主，第一次调用时初始化core的插件服务并订阅事件
+    pub fn global() -> &'static InProcessHost {
+        HOST.get_or_init(|| {
+            let (registry, _event_bus) = bubblefish_core::plugin::init_plugin_system();
+            let (sender, receiver) = mpsc::channel();
+            EVENT_SYSTEM.register_emitter("plugin-e2e".to_string(), Box::new(EventRelay { sender: Mutex::new(sender) }));
+            InProcessHost {
+                registry,
+                plugins: Mutex::new(HashMap::new()),
+                configs: Mutex::new(HashMap::new()),
+                events: Mutex::new(receiver),
+                logs: Mutex::new(Vec::new()),
+                session: Mutex::new(()),
+            }
+        })
+    }
+
+    /// 独占宿主开始一个测试；同一测试二进制中的测试并行运行，会话之间串行，并丢弃之前残留的事件
+    pub fn session() -> HostSession {
+        let host = Self::global();
+        let guard = host.session.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        while host.events.lock().unwrap().try_recv().is_ok() {}
+        host.logs.lock().unwrap().clear();
+        HostSession { host, _guard: guard }
+    }
+
+    fn load_plugin(&self, path: &Path) -> Result<LoadedPluginInfo, String> {
+        // 与桌面端相同，从文件名推导插件ID：libdummy_ocr_plugin.so -> dummy-ocr-plugin
+        let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+        let plugin_id = file_stem.strip_prefix("lib").unwrap_or(file_stem).replace('_', "-");
+        // 插件激活时会注册服务，重复加载会重复注册，所以每个插件只加载一次
+        if let Some(plugin) = self.plugins.lock().unwrap().get(&plugin_id) {
+            return Ok(plugin.info.clone());
+        }
+
+        unsafe {
+            let library = Library::new(path)
+                .map_err(|e| format!("Failed to load plugin library {}: {}", path.display(), e))?;
+
+            let api_version = library.get::<extern "C" fn() -> u32>(b"plugin_api_version")
+                .map(|api_version| api_version())
+                .unwrap_or(0);
+            if !(MIN_PLUGIN_API_VERSION..=MAX_PLUGIN_API_VERSION).contains(&api_version) {
+                return Err(format!(
+                    "Plugin API v{} is not supported (host supports v{}-v{})",
+                    api_version, MIN_PLUGIN_API_VERSION, MAX_PLUGIN_API_VERSION
+                ));
+            }
+
+            let set_callbacks = *library.get::<extern "C" fn(HostCallbacks)>(b"plugin_set_host_callbacks")
+                .map_err(|e| format!("Failed to find plugin_set_host_callbacks: {}", e))?;
+            set_callbacks(HostCallbacks {
+                call_service: host_call_service,
+                read_image_file: host_read_image_file,
+                free_host_memory: host_free_host_memory,
+                log_message: host_log_message,
+            });
+
+            let plugin_id_c = CString::new(plugin_id).map_err(|e| format!("Invalid plugin ID: {}", e))?;
+            let init = *library.get::<extern "C" fn(*const c_char) -> i32>(b"plugin_init")
+                .map_err(|e| format!("Failed to find plugin_init: {}", e))?;
+            let result = init(plugin_id_c.as_ptr());
+            if result != 0 {
+                return Err(format!("Plugin initialization failed with code: {}", result));
+            }
+
+            let get_metadata = *library.get::<extern "C" fn() -> *mut c_char>(b"plugin_get_metadata")
+                .map_err(|e| format!("Failed to find plugin_get_metadata: {}", e))?;
+            let free_string = *library.get::<extern "C" fn(*mut c_char)>(b"plugin_free_string")
+                .map_err(|e| format!("Failed to find plugin_free_string: {}", e))?;
+            let metadata_ptr = get_metadata();
+            if metadata_ptr.is_null() {
+                return Err("Failed to get plugin metadata".to_string());
+            }
+            let metadata_str = CStr::from_ptr(metadata_ptr).to_string_lossy().into_owned();
+            free_string(metadata_ptr);
+            let info: LoadedPluginInfo = serde_json::from_str(&metadata_str)
+                .map_err(|e| format!("Failed to parse plugin metadata: {}", e))?;
+
+            // 激活时插件会通过call_service注册OCR/翻译服务，这里不能持有plugins锁
+            let activate = *library.get::<extern "C" fn() -> i32>(b"plugin_activate")
+                .map_err(|e| format!("Failed to find plugin_activate: {}", e))?;
+            let result = activate();
+            if result != 0 {
+                return Err(format!("Plugin activation failed with code: {}", result));
+            }
+
+            self.plugins.lock().unwrap().insert(info.id.clone(), LoadedPlugin { library: Arc::new(library), info: info.clone() });
+            Ok(info)
+        }
+    }
+
+    fn plugin_library(&self, plugin_id: &str) -> Result<Arc<Library>, String> {
+        self.plugins.lock().unwrap().get(plugin_id)
+            .map(|plugin| plugin.library.clone())
+            .ok_or_else(|| format!("Plugin '{}' is not loaded", plugin_id))
+    }
+
+    // 调用期间不持有插件表的锁，插件回调宿主服务时不会死锁
+    fn send_message(&self, to: &str, from: &str, message: &Value) -> Result<(), String> {
+        let library = self.plugin_library(to)?;
+        let from_c = CString::new(from).map_err(|e| format!("Invalid from string: {}", e))?;
+        let message_c = CString::new(message.to_string()).map_err(|e| format!("Invalid message JSON: {}", e))?;
+        let result = unsafe {
+            let on_message = *library.get::<extern "C" fn(*const c_char, *const c_char) -> i32>(b"plugin_on_message")
+                .map_err(|e| format!("Failed to find plugin_on_message: {}", e))?;
+            on_message(from_c.as_ptr(), message_c.as_ptr())
+        };
+        if result != 0 {
+            return Err(format!("Message handling failed with code: {}", result));
+        }
+        Ok(())
+    }
+
+    fn dispatch_event(&self, plugin_id: &str, event: &Value) -> Result<(), String> {
+        let library = self.plugin_library(plugin_id)?;
+        let event_c = CString::new(event.to_string()).map_err(|e| format!("Invalid event JSON: {}", e))?;
+        let result = unsafe {
+            let on_event = *library.get::<extern "C" fn(*const c_char) -> i32>(b"plugin_on_event")
+                .map_err(|e| format!("Failed to find plugin_on_event: {}", e))?;
+            on_event(event_c.as_ptr())
+        };
+        if result != 0 {
+            return Err(format!("Event handling failed with code: {}", result));
+        }
+        Ok(())
+    }
+
+    // 业务事件以CoreEvent::Custom的形式发给订阅了它的插件，和前端的pluginService一致
+    fn dispatch_to_subscribers(&self, event_name: &str, data: &Value) {
+        let subscribers: Vec<String> = self.plugins.lock().unwrap().values()
+            .filter(|plugin| plugin.info.subscribed_events.iter().any(|event| event == event_name || event == "*"))
+            .map(|plugin| plugin.info.id.clone())
+            .collect();
+        let event = serde_json::json!({ "Custom": { "event_type": event_name, "data": data } });
+        for plugin_id in subscribers {
+            if let Err(e) = self.dispatch_event(&plugin_id, &event) {
+                self.log(format!("[host] failed to dispatch {} to {}: {}", event_name, plugin_id, e));
+            }
+        }
+    }
+
+    // 代替前端bunnyService：把core的请求转给提供该服务的插件，转发失败时任务失败
+    fn relay_bunny_request(&self, event_name: &str, data: &Value) {
+        let (services, message) = match event_name {
+            "bunny:request_plugin_ocr" => (bunny::get_available_ocr_services(None), serde_json::json!({
+                "type": "ocr_request",
+                "task_id": data["task_id"],
+                "image_data": data["cropped_image_data"],
+                "image_format": data["image_format"],
+                "options": {
+                    "source_language": data["source_language"],
+                    "pixel_geometry": data["pixel_geometry"],
+                },
+            })),
+            "bunny:request_plugin_translation" => (bunny::get_available_translation_services(None), serde_json::json!({
+                "type": "translation_request",
+                "task_id": data["task_id"],
+                "text": data["text"],
+                "options": {
+                    "source_language": data["source_language"],
+                    "target_language": data["target_language"],
+                    "glossary": data["glossary"].get("entries").cloned().unwrap_or_else(|| serde_json::json!([])),
+                    "forbidden_terms": data["glossary"].get("forbiddenTerms").cloned().unwrap_or_else(|| serde_json::json!([])),
+                    "context": data["context"],
+                },
+            })),
+            "bunny:request_plugin_detection" => (bunny::get_available_detection_services(None), serde_json::json!({
+                "type": "detect_request",
+                "task_id": data["task_id"],
+                "service_id": data["service_id"],
+                "image_data": data["image_data"],
+                "image_format": data["image_format"],
+                "width": data["width"],
+                "height": data["height"],
+            })),
+            _ => return,
+        };
+        let task_id = data["task_id"].as_str().unwrap_or_default().to_string();
+        let service_id = data["service_id"].as_str().unwrap_or_default();
+        let result = services.iter()
+            .find(|service| service["id"].as_str() == Some(service_id))
+            .and_then(|service| service["plugin_id"].as_str())
+            .ok_or_else(|| format!("Service '{}' not found", service_id))
+            .and_then(|plugin_id| self.send_message(plugin_id, "bunny", &message));
+        if let Err(e) = result {
+            self.log(format!("[host] failed to relay {}: {}", event_name, e));
+            let _ = bunny::handle_task_failed(task_id, e);
+        }
+    }
+
+    fn call_service(&self, plugin_id: &str, service: &str, method: &str, params: &Value) -> Result<Value, String> {
+        match service {
+            "events" => self.handle_events_service(method, params),
+            "config" => self.handle_config_service(plugin_id, method, params),
+            _ => self.registry.call_service(plugin_id, service, method, params.clone()),
+        }
+    }
+
+    fn handle_events_service(&self, method: &str, params: &Value) -> Result<Value, String> {
+        match method {
+            "emit_business_event" => {
+                let event_name = params["event_name"].as_str().ok_or("Missing event_name")?;
+                let data = &params["data"];
+                let task = data["task_id"].as_str()
+                    .and_then(|task_id| TASK_MANAGER.get_task(task_id).ok().flatten());
+                // 插件的结果事件直接交给core处理，和桌面端一致
+                match (event_name, task) {
+                    ("plugin:ocr_result", Some(task)) => {
+                        let text = data["text"].as_str().unwrap_or_default().to_string();
+                        let model = data["model"].as_str().unwrap_or_default().to_string();
+                        let regions = serde_json::from_value(data["regions"].clone()).unwrap_or_default();
+                        bunny::handle_ocr_completed(task.task_id.clone(), task.marker_id, text, model, regions)?;
+                    }
+                    ("plugin:translation_result", Some(task)) => {
+                        let text = data["translated_text"].as_str().unwrap_or_default().to_string();
+                        let service = data["service"].as_str().unwrap_or_default().to_string();
+                        let warnings = serde_json::from_value(data["warnings"].clone()).unwrap_or_default();
+                        let metadata = data.get("metadata").cloned();
+                        bunny::handle_translation_completed(task.task_id.clone(), task.marker_id, text, service, warnings, metadata)?;
+                    }
+                    ("plugin:detect_result", Some(task)) => {
+                        let model = data["model"].as_str().unwrap_or_default().to_string();
+                        let regions = serde_json::from_value(data["regions"].clone()).unwrap_or_default();
+                        bunny::handle_detection_completed(task.task_id.clone(), model, regions)?;
+                    }
+                    ("plugin:translation_error" | "plugin:detect_error", Some(task)) => {
+                        let error = data["error"].as_str().unwrap_or("Plugin task failed").to_string();
+                        bunny::handle_task_failed(task.task_id.clone(), error)?;
+                    }
+                    _ => {}
+                }
+                EVENT_SYSTEM.emit_business_event(event_name.to_string(), data.clone())?;
+                Ok(serde_json::json!({"success": true}))
+            }
+            "emit_log_event" => {
+                let message = params["message"].as_str().ok_or("Missing message")?;
+                self.log(format!("[{}] {}", params["level"].as_str().unwrap_or("info"), message));
+                Ok(serde_json::json!({"success": true}))
+            }
+            _ => Err(format!("Unknown events method: {}", method)),
+        }
+    }
+
+    fn handle_config_service(&self, plugin_id: &str, method: &str, params: &Value) -> Result<Value, String> {
+        let mut configs = self.configs.lock().unwrap();
+        let config = configs.entry(plugin_id.to_string()).or_insert_with(|| serde_json::json!({}));
+        match method {
+            "get" => Ok(match params["key"].as_str() {
+                Some(key) => config.get(key).cloned().unwrap_or(Value::Null),
+                None => config.clone(),
+            }),
+            "set" => {
+                if let Some(new_config) = params.get("config") {
+                    *config = new_config.clone();
+                } else if let (Some(key), Some(value)) = (params["key"].as_str(), params.get("value")) {
+                    config[key] = value.clone();
+                } else {
+                    return Err("Either config or key-value pair required".to_string());
+                }
+                Ok(serde_json::json!({"success": true}))
+            }
+            "delete" => {
+                *config = serde_json::json!({});
+                Ok(serde_json::json!({"success": true}))
+            }
+            _ => Err(format!("Unknown config method: {}", method)),
+        }
+    }
+
+    fn log(&self, message: String) {
+        self.logs.lock().unwrap().push(message);
+    }
+}
+
+/// 一个测试对宿主的独占访问，drop时释放给下一个测试
+pub struct HostSession {
+    host: &'static InProcessHost,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl HostSession {
+    /// 加载并激活插件；已加载的插件直接返回其元数据
+    pub fn load_plugin(&self, path: &Path) -> Result<LoadedPluginInfo, String> {
+        self.host.load_plugin(path)
+    }
+
+    /// 设置插件配置，插件下次读取配置时生效
+    pub fn set_plugin_config(&self, plugin_id: &str, config: Value) {
+        self.host.configs.lock().unwrap().insert(plugin_id.to_string(), config);
+    }
+
+    /// 处理core发出的事件直到predicate匹配，返回匹配的事件(名称, 数据)
+    /// 每个事件先转给订阅它的插件，bunny的插件请求再转给提供服务的插件
+    pub fn pump_until(&self, timeout: Duration, mut predicate: impl FnMut(&str, &Value) -> bool) -> Result<(String, Value), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let received = self.host.events.lock().unwrap().recv_timeout(remaining);
+            let (event_name, data) = match received {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Err(format!(
+                    "Timed out after {:?} waiting for event; plugin log:\n{}",
+                    timeout,
+                    self.plugin_logs().join("\n")
+                )),
+                Err(RecvTimeoutError::Disconnected) => return Err("Event relay disconnected".to_string()),
+            };
+            self.host.dispatch_to_subscribers(&event_name, &data);
+            self.host.relay_bunny_request(&event_name, &data);
+            if predicate(&event_name, &data) {
+                return Ok((event_name, data));
+            }
+        }
+    }
+
+    /// 等待任务结束：返回bunny:*_completed事件，任务失败时返回错误
+    pub fn wait_for_task(&self, task_id: &str, timeout: Duration) -> Result<(String, Value), String> {
+        let (event_name, data) = self.pump_until(timeout, |event_name, data| {
+            data["task_id"].as_str() == Some(task_id)
+                && matches!(event_name, "bunny:ocr_completed" | "bunny:translation_completed" | "bunny:detection_completed" | "bunny:task_failed")
+        })?;
+        if event_name == "bunny:task_failed" {
+            return Err(format!("Task {} failed: {}", task_id, data["error"].as_str().unwrap_or_default()));
+        }
+        Ok((event_name, data))
+    }
+
+    /// 本次会话中插件输出的日志
+    pub fn plugin_logs(&self) -> Vec<String> {
+        self.host.logs.lock().unwrap().clone()
+    }
+}
+
+// Host callback implementations
+extern "C" fn host_call_service(
+    plugin_id: *const c_char,
+    service: *const c_char,
+    method: *const c_char,
+    params: *const c_char,
+) -> *mut c_char {
+    let (plugin_id, service, method, params_str) = unsafe {
+        (
+            CStr::from_ptr(plugin_id).to_string_lossy().into_owned(),
+            CStr::from_ptr(service).to_string_lossy().into_owned(),
+            CStr::from_ptr(method).to_string_lossy().into_owned(),
+            CStr::from_ptr(params).to_string_lossy().into_owned(),
+        )
+    };
+    let Ok(params) = serde_json::from_str::<Value>(&params_str) else {
+        return std::ptr::null_mut();
+    };
+    let Some(host) = HOST.get() else {
+        return std::ptr::null_mut();
+    };
+    match host.call_service(&plugin_id, &service, &method, &params) {
+        Ok(result) => CString::new(result.to_string())
+            .map(CString::into_raw)
+            .unwrap_or(std::ptr::null_mut()),
+        Err(e) => {
+            host.log(format!("[host] {} called {}.{}: {}", plugin_id, service, method, e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+extern "C" fn host_read_image_file(
+    file_path: *const c_char,
+    data_ptr: *mut *mut u8,
+    data_len: *mut usize,
+) -> i32 {
+    unsafe {
+        let file_path = CStr::from_ptr(file_path).to_string_lossy();
+        let Ok(data) = std::fs::read(&*file_path) else {
+            return -1;
+        };
+        // 插件通过free_host_memory释放，必须用malloc分配
+        let allocated = libc::malloc(data.len()) as *mut u8;
+        if allocated.is_null() {
+            return -1;
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), allocated, data.len());
+        *data_ptr = allocated;
+        *data_len = data.len();
+        0
+    }
+}
+
+extern "C" fn host_free_host_memory(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        unsafe { libc::free(ptr) };
+    }
+}
+
+extern "C" fn host_log_message(level: i32, message: *const c_char) {
+    let message = unsafe { CStr::from_ptr(message).to_string_lossy().into_owned() };
+    let label = match level {
+        0 => "DEBUG",
+        1 => "INFO",
+        2 => "WARN",
+        _ => "ERROR",
+    };
+    if let Some(host) = HOST.get() {
+        host.log(format!("[{}] {}", label, message));
+    }
+}
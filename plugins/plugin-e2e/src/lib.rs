@@ -0,0 +1,27 @@
+//! End-to-end test harness for native plugins
+//!
+//! [`InProcessHost`] loads compiled plugins (`cdylib`) into the test process and implements the
+//! native plugin ABI directly against `bubblefish_core`: service calls go to the core
+//! `ServiceRegistry`, plugin results are handed to the bunny API the same way the desktop loader
+//! does, and [`HostSession::pump_until`] relays the core's `EVENT_SYSTEM` into `plugin_on_message`
+//! (OCR/translation/detection requests) and `plugin_on_event`.
+//!
+//! ```no_run
+//! use bubblefish_plugin_e2e::{build_native_plugin, FixtureProject, InProcessHost};
+//!
+//! let session = InProcessHost::session();
+//! session.load_plugin(&build_native_plugin("dummy-ocr-plugin").unwrap()).unwrap();
+//! let fixture = FixtureProject::builder("e2e").image(200, 100).point(50.0, 50.0).build().unwrap();
+//! let task_id = fixture.request_ocr(0, 0, "dummy-ocr").unwrap();
+//! session.wait_for_task(&task_id, std::time::Duration::from_secs(10)).unwrap();
+//! ```
+//!
+//! Plugins are built with [`build_native_plugin`] into a separate target directory, so the first
+//! test run compiles them once.
+pub mod artifacts;
+pub mod fixture;
+pub mod host;
+
+pub use artifacts::build_native_plugin;
+pub use fixture::{FixtureImage, FixtureMarker, FixtureProject, ProjectBuilder};
+pub use host::{HostSession, InProcessHost, LoadedPluginInfo};
@@ -0,0 +1,55 @@
+// 把编译好的dummy插件加载到进程内，走完core发起任务 -> 插件处理 -> core写回缓存的完整流程
+use std::time::Duration;
+use bubblefish_core::api::bunny;
+use bubblefish_plugin_e2e::{build_native_plugin, FixtureProject, InProcessHost};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn ocr_request_is_answered_by_the_plugin() {
+    let session = InProcessHost::session();
+    let plugin = session.load_plugin(&build_native_plugin("dummy-ocr-plugin").unwrap()).unwrap();
+    assert_eq!(plugin.id, "dummy-ocr-plugin");
+    assert!(bunny::get_available_ocr_services(None).iter().any(|service| service["id"] == "dummy-ocr"));
+
+    let fixture = FixtureProject::builder("e2e ocr").image(200, 100).rect(10.0, 10.0, 50.0, 50.0).build().unwrap();
+    let task_id = fixture.request_ocr(0, 0, "dummy-ocr").unwrap();
+    let (event_name, data) = session.wait_for_task(&task_id, TIMEOUT).unwrap();
+    assert_eq!(event_name, "bunny:ocr_completed");
+
+    let (_, marker_id) = fixture.marker(0, 0);
+    let cache = bunny::get_bunny_cache(marker_id).unwrap().expect("OCR result should be cached");
+    assert_eq!(cache.original_text.as_deref(), data["original_text"].as_str());
+    assert!(cache.original_text.unwrap().starts_with("Short text:"));
+}
+
+#[test]
+fn unknown_service_fails_the_task() {
+    let session = InProcessHost::session();
+    session.load_plugin(&build_native_plugin("dummy-ocr-plugin").unwrap()).unwrap();
+
+    let fixture = FixtureProject::builder("e2e missing").image(64, 64).point(50.0, 50.0).build().unwrap();
+    // 任务由core接受，但没有插件提供该服务，转发时任务失败
+    match fixture.request_ocr(0, 0, "no-such-ocr") {
+        Ok(task_id) => assert!(session.wait_for_task(&task_id, TIMEOUT).unwrap_err().contains("no-such-ocr")),
+        Err(e) => assert!(e.contains("no-such-ocr"), "{}", e),
+    }
+}
+
+#[test]
+fn pipeline_runs_ocr_then_translation() {
+    let session = InProcessHost::session();
+    session.load_plugin(&build_native_plugin("dummy-ocr-plugin").unwrap()).unwrap();
+    session.load_plugin(&build_native_plugin("dummy-translation-plugin").unwrap()).unwrap();
+
+    let fixture = FixtureProject::builder("e2e pipeline").image(200, 100).rect(10.0, 10.0, 50.0, 50.0).build().unwrap();
+    let pipeline_id = fixture.request_pipeline(0, 0, "dummy-ocr", "dummy-translate").unwrap();
+    session.pump_until(TIMEOUT, |event_name, data| {
+        event_name == "bunny:pipeline_completed" && data["pipeline_id"].as_str() == Some(pipeline_id.as_str())
+    }).unwrap();
+
+    let (_, marker_id) = fixture.marker(0, 0);
+    let cache = bunny::get_bunny_cache(marker_id).unwrap().unwrap();
+    assert!(cache.original_text.unwrap().starts_with("Short text:"));
+    assert!(cache.machine_translation.unwrap().contains("Short text:"));
+}
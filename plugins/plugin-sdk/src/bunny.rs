@@ -1,24 +1,82 @@
 use serde::{Deserialize, Serialize};
+use crate::PluginContext;
 
 // OCR and Translation Options for plugins
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OCROptions {
     pub source_language: Option<String>,
+    /// 标记在原图中的像素坐标，由Core计算（图片尺寸未知时为None）
+    #[serde(default)]
+    pub pixel_geometry: Option<PixelGeometry>,
 }
 
 impl Default for OCROptions {
     fn default() -> Self {
         Self {
             source_language: None,
+            pixel_geometry: None,
         }
     }
 }
 
+/// 像素坐标下的标记几何，与Core的PixelGeometry保持一致
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PixelGeometry {
+    Point { x: u32, y: u32 },
+    Rectangle { x: u32, y: u32, width: u32, height: u32 },
+}
+
+/// ocr_request消息中的图片字节
+/// WASM插件优先从共享缓冲区读取前端暂存的图片（消息带image_ref时），否则取image_data数组
+pub fn ocr_request_image(_ctx: &PluginContext, message: &serde_json::Value) -> Result<Vec<u8>, String> {
+    #[cfg(feature = "wasm")]
+    if let Some(image_ref) = message.get("image_ref").and_then(|v| v.as_str())
+        && crate::shared_buffer::is_shared_channel_ready()
+    {
+        return crate::ImageServiceProxy::new(_ctx.clone()).get_staged_image_shared(image_ref).map(|view| view.to_vec());
+    }
+    let bytes = message.get("image_data")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_u64().map(|n| n as u8)).collect())
+        .unwrap_or_default();
+    Ok(bytes)
+}
+
+/// plugin:ocr_result事件的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OCRResult {
+    pub task_id: String,
+    pub text: String,
+    pub model: String,
+    /// 文本行位置，不支持的插件不填；Core可按它把标记拆成每行一个
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<OCRRegion>>,
+}
+
+/// 一行文本及其位置，坐标是发给插件的裁剪图中的像素
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OCRRegion {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationOptions {
     pub source_language: Option<String>,
     pub target_language: String,
+    /// 项目术语表，可用validate_translation检查译文是否遵守
+    #[serde(default)]
+    pub glossary: Vec<crate::GlossaryEntry>,
+    #[serde(default)]
+    pub forbidden_terms: Vec<String>,
+    /// 按项目阅读方向排在待翻译文本之前的几条原文，从前到后
+    #[serde(default)]
+    pub context: Vec<String>,
 }
 
 // Service Info structures for registration
@@ -44,11 +102,97 @@ pub struct TranslationServiceInfo {
     pub max_text_length: Option<usize>,
 }
 
+/// 文本框/气泡检测服务，给定整张图片返回候选矩形
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionServiceInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub supported_image_formats: Vec<String>,
+    pub max_image_size: Option<usize>,
+}
+
 /// Registration info for bunny services
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BunnyServiceRegistration {
     OCR(OCRServiceInfo),
     Translation(TranslationServiceInfo),
+    Detection(DetectionServiceInfo),
+}
+
+/// type为detect_request的插件消息，image_data是整张图片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectRequest {
+    pub task_id: String,
+    pub service_id: String,
+    pub image_data: Vec<u8>,
+    pub image_format: String,
+    /// 图片像素尺寸，Core不知道时为0
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+}
+
+/// 检测到的一个区域，坐标是原图像素
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedRegion {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+    /// 置信度0~1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// 区域类别，如bubble、text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// plugin:detect_result事件的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectResult {
+    pub task_id: String,
+    pub model: String,
+    pub regions: Vec<DetectedRegion>,
+}
+
+/// 检测服务提供者，插件在on_plugin_message中把消息交给handle_detect_request即可
+pub trait DetectionProvider {
+    fn service_info(&self) -> DetectionServiceInfo;
+
+    fn detect(&mut self, request: &DetectRequest) -> Result<Vec<DetectedRegion>, String>;
+}
+
+/// 向Core注册检测服务
+pub fn register_detection_service(ctx: &PluginContext, info: &DetectionServiceInfo) -> Result<(), String> {
+    ctx.call_service("bunny", "register_detection_service", serde_json::json!({
+        "plugin_id": ctx.plugin_id,
+        "service_info": info,
+    }))?;
+    Ok(())
+}
+
+/// 处理detect_request消息：不是检测请求时返回false；检测成功发出plugin:detect_result，失败发出plugin:detect_error
+pub fn handle_detect_request<P: DetectionProvider + ?Sized>(
+    provider: &mut P,
+    ctx: &PluginContext,
+    message: &serde_json::Value,
+) -> Result<bool, String> {
+    if message.get("type").and_then(|v| v.as_str()) != Some("detect_request") {
+        return Ok(false);
+    }
+    let request: DetectRequest = serde_json::from_value(message.clone())
+        .map_err(|e| format!("Invalid detect_request: {}", e))?;
+    let (event_name, data) = match provider.detect(&request) {
+        Ok(regions) => {
+            let result = DetectResult { task_id: request.task_id, model: request.service_id, regions };
+            ("plugin:detect_result", serde_json::to_value(result).map_err(|e| e.to_string())?)
+        }
+        Err(error) => ("plugin:detect_error", serde_json::json!({ "task_id": request.task_id, "error": error })),
+    };
+    ctx.call_service("events", "emit_business_event", serde_json::json!({ "event_name": event_name, "data": data }))?;
+    Ok(true)
 }
 
 /// Events emitted by bunny service providers
@@ -59,4 +203,62 @@ pub enum BunnyProviderEvent {
     ProcessingStarted { task_id: String, service_id: String },
     ProcessingProgress { task_id: String, progress: u8 },
     ProcessingCompleted { task_id: String, success: bool },
-}
\ No newline at end of file
+}
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockHost;
+    use serde_json::json;
+
+    struct FixedDetector;
+
+    impl DetectionProvider for FixedDetector {
+        fn service_info(&self) -> DetectionServiceInfo {
+            DetectionServiceInfo {
+                id: "fixed".to_string(),
+                name: "Fixed".to_string(),
+                version: "0.1.0".to_string(),
+                supported_image_formats: vec!["png".to_string()],
+                max_image_size: None,
+            }
+        }
+
+        fn detect(&mut self, request: &DetectRequest) -> Result<Vec<DetectedRegion>, String> {
+            if request.width == 0 {
+                return Err("unknown size".to_string());
+            }
+            Ok(vec![DetectedRegion { x: 0.0, y: 0.0, w: request.width as f64 / 2.0, h: 10.0, score: Some(0.9), label: None }])
+        }
+    }
+
+    #[test]
+    fn detect_request_emits_result_or_error() {
+        let host = MockHost::new();
+        let ctx = host.context("detector");
+        let mut detector = FixedDetector;
+        register_detection_service(&ctx, &detector.service_info()).unwrap();
+        assert_eq!(host.calls_to("bunny", "register_detection_service")[0].params["service_info"]["id"], "fixed");
+
+        assert!(!handle_detect_request(&mut detector, &ctx, &json!({ "type": "ocr_request" })).unwrap());
+        let request = json!({ "type": "detect_request", "task_id": "t1", "service_id": "fixed", "image_data": [1, 2], "image_format": "png", "width": 40, "height": 30 });
+        assert!(handle_detect_request(&mut detector, &ctx, &request).unwrap());
+        let request = json!({ "type": "detect_request", "task_id": "t2", "service_id": "fixed", "image_data": [], "image_format": "png" });
+        assert!(handle_detect_request(&mut detector, &ctx, &request).unwrap());
+
+        let events = host.emitted_events();
+        assert_eq!(events[0].event_name, "plugin:detect_result");
+        assert_eq!(events[0].data["regions"][0]["w"], 20.0);
+        assert_eq!(events[1], crate::testing::EmittedEvent {
+            event_name: "plugin:detect_error".to_string(),
+            data: json!({ "task_id": "t2", "error": "unknown size" }),
+        });
+    }
+
+    #[test]
+    fn ocr_request_image_reads_byte_array() {
+        let ctx = MockHost::new().context("ocr");
+        let request = json!({ "type": "ocr_request", "task_id": "t1", "image_data": [137, 80, 78] });
+        assert_eq!(ocr_request_image(&ctx, &request).unwrap(), vec![137, 80, 78]);
+        assert!(ocr_request_image(&ctx, &json!({ "type": "ocr_request" })).unwrap().is_empty());
+    }
+}
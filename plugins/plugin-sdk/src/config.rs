@@ -1,4 +1,9 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use crate::{CoreEvent, PluginContext};
 
 /// Type of configuration field
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +24,12 @@ pub struct SelectOption {
     pub label: String,
 }
 
+impl SelectOption {
+    pub fn new(value: impl Into<String>, label: impl Into<String>) -> Self {
+        Self { value: value.into(), label: label.into() }
+    }
+}
+
 /// Validation rules for configuration fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -85,6 +96,8 @@ pub struct ConfigSection {
     pub fields: Vec<ConfigField>,
 }
 
+const DEFAULT_SECTION_TITLE: &str = "配置";
+
 /// Complete configuration schema for a plugin
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigSchema {
@@ -97,12 +110,67 @@ impl ConfigSchema {
     pub fn simple(fields: Vec<ConfigField>) -> Self {
         Self {
             sections: vec![ConfigSection {
-                title: "配置".to_string(),
+                title: DEFAULT_SECTION_TITLE.to_string(),
                 description: None,
                 fields,
             }],
         }
     }
+
+    /// Start building a schema; fields added before any `section` go into the default section
+    pub fn builder() -> ConfigSchemaBuilder {
+        ConfigSchemaBuilder::default()
+    }
+
+    /// Check for mistakes the type system can't catch: duplicate keys and select
+    /// defaults that aren't one of the options
+    pub fn validate(&self) -> Result<(), String> {
+        let mut keys = HashSet::new();
+        for field in self.sections.iter().flat_map(|section| &section.fields) {
+            if !keys.insert(field.key.as_str()) {
+                return Err(format!("Duplicate config key: {}", field.key));
+            }
+            if let (Some(options), Some(default)) = (&field.options, &field.default_value)
+                && !options.iter().any(|option| &option.value == default)
+            {
+                return Err(format!("Default value {:?} of {} is not one of its options", default, field.key));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for a `ConfigSchema`, see `ConfigKey` for typed fields
+#[derive(Debug, Default)]
+pub struct ConfigSchemaBuilder {
+    sections: Vec<ConfigSection>,
+}
+
+impl ConfigSchemaBuilder {
+    /// Start a new section; following fields go into it
+    pub fn section(mut self, title: impl Into<String>, description: Option<&str>) -> Self {
+        self.sections.push(ConfigSection {
+            title: title.into(),
+            description: description.map(str::to_string),
+            fields: Vec::new(),
+        });
+        self
+    }
+
+    /// Add a field to the current section
+    pub fn field(mut self, field: impl Into<ConfigField>) -> Self {
+        if self.sections.is_empty() {
+            self = self.section(DEFAULT_SECTION_TITLE, None);
+        }
+        if let Some(section) = self.sections.last_mut() {
+            section.fields.push(field.into());
+        }
+        self
+    }
+
+    pub fn build(self) -> ConfigSchema {
+        ConfigSchema { sections: self.sections }
+    }
 }
 
 /// Helper builders for common field types
@@ -232,4 +300,347 @@ impl ConfigField {
         self.validation = Some(validation);
         self
     }
-}
\ No newline at end of file
+}
+
+/// A type a config value can be read as
+///
+/// Missing and empty values read as `None`, so callers fall back to their own default.
+pub trait ConfigValue: Sized {
+    fn from_config(value: &Value) -> Option<Self>;
+
+    /// How a default of this type is stored in the schema
+    fn to_default(&self) -> String;
+}
+
+impl ConfigValue for String {
+    /// Switch fields stored as booleans read as "true"/"false"
+    fn from_config(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) if !s.is_empty() => Some(s.clone()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    fn to_default(&self) -> String {
+        self.clone()
+    }
+}
+
+impl ConfigValue for bool {
+    fn from_config(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn to_default(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ConfigValue for f64 {
+    fn from_config(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn to_default(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Typed name of a config value, shared by the schema field and the code reading it
+///
+/// Field constructors are only available for matching value types (a switch needs a
+/// `ConfigKey<bool>`), so defaults and reads can't disagree with the field type:
+///
+/// ```
+/// use bubblefish_plugin_sdk::{ConfigKey, ConfigSchema};
+///
+/// const API_KEY: ConfigKey<String> = ConfigKey::new("api_key");
+/// const STREAM: ConfigKey<bool> = ConfigKey::new("stream");
+///
+/// let schema = ConfigSchema::builder()
+///     .field(API_KEY.password("API Key").required().min_length(10))
+///     .field(STREAM.switch("流式输出").with_default(true))
+///     .build();
+/// assert!(schema.validate().is_ok());
+/// ```
+pub struct ConfigKey<T> {
+    key: &'static str,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for ConfigKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ConfigKey<T> {}
+
+impl<T> ConfigKey<T> {
+    pub const fn new(key: &'static str) -> Self {
+        Self { key, _value: PhantomData }
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    fn typed(&self, field: ConfigField) -> TypedConfigField<T> {
+        TypedConfigField { field, _value: PhantomData }
+    }
+}
+
+impl ConfigKey<String> {
+    pub fn text(&self, label: impl Into<String>) -> TypedConfigField<String> {
+        self.typed(ConfigField::text(self.key, label))
+    }
+
+    pub fn password(&self, label: impl Into<String>) -> TypedConfigField<String> {
+        self.typed(ConfigField::password(self.key, label))
+    }
+
+    pub fn textarea(&self, label: impl Into<String>) -> TypedConfigField<String> {
+        self.typed(ConfigField::textarea(self.key, label))
+    }
+
+    /// Options are `(value, label)` pairs
+    pub fn select(&self, label: impl Into<String>, options: &[(&str, &str)]) -> TypedConfigField<String> {
+        let options = options.iter().map(|(value, label)| SelectOption::new(*value, *label)).collect();
+        self.typed(ConfigField::select(self.key, label, options))
+    }
+}
+
+impl ConfigKey<bool> {
+    pub fn switch(&self, label: impl Into<String>) -> TypedConfigField<bool> {
+        self.typed(ConfigField::switch(self.key, label))
+    }
+}
+
+impl ConfigKey<f64> {
+    pub fn number(&self, label: impl Into<String>) -> TypedConfigField<f64> {
+        self.typed(ConfigField::number(self.key, label))
+    }
+}
+
+/// A `ConfigField` whose default has the type of its `ConfigKey`
+#[derive(Debug, Clone)]
+pub struct TypedConfigField<T> {
+    field: ConfigField,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T: ConfigValue> TypedConfigField<T> {
+    pub fn required(mut self) -> Self {
+        self.field.required = true;
+        self
+    }
+
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.field.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.field.help_text = Some(help.into());
+        self
+    }
+
+    pub fn with_default(mut self, default: impl Into<T>) -> Self {
+        self.field.default_value = Some(default.into().to_default());
+        self
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.field.disabled = true;
+        self
+    }
+
+    fn push_validation(mut self, rule: ConfigValidation) -> Self {
+        self.field.validation.get_or_insert_with(Vec::new).push(rule);
+        self
+    }
+}
+
+impl TypedConfigField<String> {
+    pub fn min_length(self, min: usize) -> Self {
+        self.push_validation(ConfigValidation::MinLength(min))
+    }
+
+    pub fn max_length(self, max: usize) -> Self {
+        self.push_validation(ConfigValidation::MaxLength(max))
+    }
+
+    pub fn pattern(self, pattern: impl Into<String>) -> Self {
+        self.push_validation(ConfigValidation::Pattern(pattern.into()))
+    }
+}
+
+impl TypedConfigField<f64> {
+    pub fn min(self, min: f64) -> Self {
+        self.push_validation(ConfigValidation::Min(min))
+    }
+
+    pub fn max(self, max: f64) -> Self {
+        self.push_validation(ConfigValidation::Max(max))
+    }
+}
+
+impl<T> From<TypedConfigField<T>> for ConfigField {
+    fn from(typed: TypedConfigField<T>) -> Self {
+        typed.field
+    }
+}
+
+/// Cached view of a plugin's config values
+///
+/// Values are fetched from the host's config service on first use and kept until a
+/// `ConfigChanged` event for the same plugin arrives, so a new value takes effect on the
+/// next read without reinitializing the plugin. Clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigCache {
+    plugin_id: String,
+    values: Arc<Mutex<HashMap<String, Value>>>,
+}
+
+impl ConfigCache {
+    pub fn new(plugin_id: impl Into<String>) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            values: Arc::default(),
+        }
+    }
+
+    /// Get a config value, asking the host's config service on a cache miss
+    pub fn get(&self, ctx: &PluginContext, key: &str) -> Result<Value, String> {
+        self.get_or_fetch(key, |key| {
+            ctx.call_service("config", "get", serde_json::json!({
+                "plugin_id": self.plugin_id.clone(),
+                "key": key
+            }))
+        })
+    }
+
+    /// Get a config value, calling `fetch` on a cache miss; failed fetches are not cached
+    pub fn get_or_fetch(
+        &self,
+        key: &str,
+        fetch: impl FnOnce(&str) -> Result<Value, String>,
+    ) -> Result<Value, String> {
+        if let Some(value) = self.lock().get(key) {
+            return Ok(value.clone());
+        }
+        let value = fetch(key)?;
+        self.lock().insert(key.to_string(), value.clone());
+        Ok(value)
+    }
+
+    /// Get a non-empty string value; switch fields stored as booleans become "true"/"false"
+    pub fn get_string(&self, ctx: &PluginContext, key: &str) -> Option<String> {
+        String::from_config(&self.get(ctx, key).ok()?)
+    }
+
+    /// Get a typed value; `None` when unset, empty, or unreadable as `T`
+    pub fn value<T: ConfigValue>(&self, ctx: &PluginContext, key: ConfigKey<T>) -> Option<T> {
+        T::from_config(&self.get(ctx, key.key()).ok()?)
+    }
+
+    /// Apply a `ConfigChanged` event for this plugin; returns whether the event was used
+    pub fn handle_event(&self, event: &CoreEvent) -> bool {
+        match event {
+            CoreEvent::ConfigChanged { plugin_id, key, value } if *plugin_id == self.plugin_id => {
+                self.lock().insert(key.clone(), value.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop all cached values
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Value>> {
+        self.values.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(plugin_id: &str, key: &str, value: Value) -> CoreEvent {
+        CoreEvent::ConfigChanged { plugin_id: plugin_id.to_string(), key: key.to_string(), value }
+    }
+
+    #[test]
+    fn cache_fetches_once_and_updates_on_config_changed() {
+        let cache = ConfigCache::new("doubao");
+        let mut fetches = 0;
+        let mut fetch = |key: &str| {
+            fetches += 1;
+            Ok(Value::String(format!("{}-v1", key)))
+        };
+        assert_eq!(cache.get_or_fetch("model", &mut fetch).unwrap(), "model-v1");
+        assert_eq!(cache.get_or_fetch("model", &mut fetch).unwrap(), "model-v1");
+        assert_eq!(fetches, 1);
+
+        // 其他插件的配置变化不影响本插件
+        assert!(!cache.handle_event(&changed("other", "model", Value::from("x"))));
+        assert!(cache.handle_event(&changed("doubao", "model", Value::from("model-v2"))));
+        let value = cache.get_or_fetch("model", |_| Err("should not fetch".to_string())).unwrap();
+        assert_eq!(value, "model-v2");
+
+        cache.clear();
+        assert!(cache.get_or_fetch("model", |_| Err("offline".to_string())).is_err());
+    }
+
+    #[test]
+    fn typed_builder_matches_hand_written_fields() {
+        const API_KEY: ConfigKey<String> = ConfigKey::new("api_key");
+        const MODEL: ConfigKey<String> = ConfigKey::new("model");
+        const FOLD: ConfigKey<bool> = ConfigKey::new("fold");
+        const RETRIES: ConfigKey<f64> = ConfigKey::new("retries");
+
+        let typed = ConfigSchema::builder()
+            .field(API_KEY.password("API Key").required().min_length(10))
+            .field(MODEL.select("模型", &[("a", "A"), ("b", "B")]).with_default("b"))
+            .field(FOLD.switch("忽略差异").with_default(true))
+            .field(RETRIES.number("重试次数").with_default(2).min(0.0))
+            .build();
+        let hand_written = ConfigSchema::simple(vec![
+            ConfigField::password("api_key", "API Key").required().with_validation(vec![ConfigValidation::MinLength(10)]),
+            ConfigField::select("model", "模型", vec![SelectOption::new("a", "A"), SelectOption::new("b", "B")]).with_default("b"),
+            ConfigField::switch("fold", "忽略差异").with_default("true"),
+            ConfigField::number("retries", "重试次数").with_default("2").with_validation(vec![ConfigValidation::Min(0.0)]),
+        ]);
+        assert_eq!(serde_json::to_string(&typed).unwrap(), serde_json::to_string(&hand_written).unwrap());
+        assert!(typed.validate().is_ok());
+
+        let bad_default = ConfigSchema::builder().field(MODEL.select("模型", &[("a", "A")]).with_default("c")).build();
+        assert!(bad_default.validate().is_err());
+        let duplicate = ConfigSchema::builder().field(MODEL.text("1")).section("更多", None).field(MODEL.text("2")).build();
+        assert!(duplicate.validate().is_err());
+
+        assert_eq!(bool::from_config(&Value::from("true")), Some(true));
+        assert_eq!(f64::from_config(&Value::from(" 3 ")), Some(3.0));
+        assert_eq!(String::from_config(&Value::from("")), None);
+    }
+
+    #[test]
+    fn config_changed_round_trips_as_core_event() {
+        let json = serde_json::json!({"ConfigChanged": {"plugin_id": "p", "key": "k", "value": true}});
+        let event: CoreEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(event.event_type(), "ConfigChanged");
+        assert!(ConfigCache::new("p").handle_event(&event));
+    }
+}
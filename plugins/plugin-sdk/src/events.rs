@@ -36,6 +36,9 @@ pub enum CoreEvent {
     SystemReady,
     SystemShutdown,
     
+    // 配置事件 - 只发送给配置所属的插件，value为更新后的值（删除时为null）
+    ConfigChanged { plugin_id: String, key: String, value: Value },
+    
     // 自定义事件
     Custom { event_type: String, data: Value },
 }
@@ -64,6 +67,7 @@ impl CoreEvent {
             CoreEvent::StatsUpdated { .. } => "StatsUpdated",
             CoreEvent::SystemReady => "SystemReady",
             CoreEvent::SystemShutdown => "SystemShutdown",
+            CoreEvent::ConfigChanged { .. } => "ConfigChanged",
             CoreEvent::Custom { event_type, .. } => event_type,
         }
     }
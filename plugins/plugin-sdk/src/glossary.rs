@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+// 术语表校验 - 检查译文是否遵守项目术语表和禁用词，供各翻译插件共用
+
+/// 术语表条目：原文中出现source时，译文应使用target（与Core的GlossaryEntry保持一致）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub source: String,
+    pub target: String,
+}
+
+/// 术语检查的严格程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlossaryStrictness {
+    /// 不检查术语，只检查禁用词
+    Off,
+    /// 原文中出现的术语，译文中至少出现一次对应译法
+    #[default]
+    Present,
+    /// 译法出现的次数不少于原文中术语出现的次数
+    Count,
+}
+
+impl GlossaryStrictness {
+    /// 解析配置值，无法识别时使用默认值
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "off" => Self::Off,
+            "count" => Self::Count,
+            _ => Self::Present,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryCheckOptions {
+    pub strictness: GlossaryStrictness,
+    /// 以字母或数字开头/结尾的词只在词边界处匹配（"cat"不匹配"category"），对中日文无影响
+    pub word_boundaries: bool,
+    pub case_sensitive: bool,
+    /// 平假名与片假名、全角与半角字母、常见异体字（髙/高等）视为相同
+    pub fold_variants: bool,
+}
+
+impl Default for GlossaryCheckOptions {
+    fn default() -> Self {
+        Self {
+            strictness: GlossaryStrictness::Present,
+            word_boundaries: true,
+            case_sensitive: false,
+            fold_variants: false,
+        }
+    }
+}
+
+/// 校验发现的问题，随plugin:translation_result的warnings字段交给Core（与Core的TranslationWarning保持一致）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranslationWarning {
+    /// 原文出现了术语，译文没有使用规定的译法
+    MissingTerm { source: String, target: String },
+    /// 译文中出现了禁用词
+    ForbiddenTerm { term: String },
+}
+
+impl TranslationWarning {
+    pub fn message(&self) -> String {
+        match self {
+            Self::MissingTerm { source, target } => format!("术语「{}」应译为「{}」", source, target),
+            Self::ForbiddenTerm { term } => format!("译文中出现了禁用词「{}」", term),
+        }
+    }
+}
+
+// 常见的异体字，只在fold_variants时使用
+const KANJI_VARIANTS: &[(char, char)] = &[
+    ('髙', '高'),
+    ('﨑', '崎'),
+    ('邊', '辺'),
+    ('邉', '辺'),
+    ('齋', '斎'),
+    ('齊', '斎'),
+];
+
+fn fold_char(c: char, options: &GlossaryCheckOptions, out: &mut Vec<char>) {
+    let c = if options.fold_variants {
+        match c {
+            // 片假名转平假名
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            // 全角ASCII转半角
+            '！'..='～' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => KANJI_VARIANTS.iter().find(|(from, _)| *from == c).map_or(c, |(_, to)| *to),
+        }
+    } else {
+        c
+    };
+    if options.case_sensitive {
+        out.push(c);
+    } else {
+        out.extend(c.to_lowercase());
+    }
+}
+
+fn normalize(text: &str, options: &GlossaryCheckOptions) -> Vec<char> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        fold_char(c, options, &mut out);
+    }
+    out
+}
+
+// 拉丁、希腊、西里尔字母和数字算作单词字符，中日文没有词边界
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && (c as u32) < 0x2E80
+}
+
+// 不重叠地统计needle出现的次数
+fn count_normalized(haystack: &[char], needle: &[char], word_boundaries: bool) -> usize {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return 0;
+    }
+    let check_start = word_boundaries && is_word_char(needle[0]);
+    let check_end = word_boundaries && is_word_char(needle[needle.len() - 1]);
+    let mut count = 0;
+    let mut i = 0;
+    while i + needle.len() <= haystack.len() {
+        let end = i + needle.len();
+        let matched = haystack[i..end] == *needle
+            && !(check_start && i > 0 && is_word_char(haystack[i - 1]))
+            && !(check_end && end < haystack.len() && is_word_char(haystack[end]));
+        if matched {
+            count += 1;
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    count
+}
+
+/// 按选项的匹配规则统计term在text中出现的次数
+pub fn count_term(text: &str, term: &str, options: &GlossaryCheckOptions) -> usize {
+    count_normalized(&normalize(text, options), &normalize(term.trim(), options), options.word_boundaries)
+}
+
+/// 原文中出现了的术语，用于只把相关条目放进提示词
+pub fn relevant_entries<'a>(
+    source_text: &str,
+    glossary: &'a [GlossaryEntry],
+    options: &GlossaryCheckOptions,
+) -> Vec<&'a GlossaryEntry> {
+    let text = normalize(source_text, options);
+    glossary.iter()
+        .filter(|entry| !entry.source.trim().is_empty())
+        .filter(|entry| count_normalized(&text, &normalize(entry.source.trim(), options), options.word_boundaries) > 0)
+        .collect()
+}
+
+/// 校验译文：原文出现的术语是否使用了规定译法、是否出现禁用词
+pub fn validate_translation(
+    source_text: &str,
+    translated_text: &str,
+    glossary: &[GlossaryEntry],
+    forbidden_terms: &[String],
+    options: &GlossaryCheckOptions,
+) -> Vec<TranslationWarning> {
+    let source = normalize(source_text, options);
+    let translated = normalize(translated_text, options);
+    let mut warnings = Vec::new();
+
+    if options.strictness != GlossaryStrictness::Off {
+        for entry in glossary {
+            let target = normalize(entry.target.trim(), options);
+            if target.is_empty() {
+                continue;
+            }
+            let expected = count_normalized(&source, &normalize(entry.source.trim(), options), options.word_boundaries);
+            if expected == 0 {
+                continue;
+            }
+            let found = count_normalized(&translated, &target, options.word_boundaries);
+            let satisfied = match options.strictness {
+                GlossaryStrictness::Count => found >= expected,
+                _ => found > 0,
+            };
+            if !satisfied {
+                warnings.push(TranslationWarning::MissingTerm {
+                    source: entry.source.clone(),
+                    target: entry.target.clone(),
+                });
+            }
+        }
+    }
+
+    for term in forbidden_terms {
+        if count_normalized(&translated, &normalize(term.trim(), options), options.word_boundaries) > 0 {
+            warnings.push(TranslationWarning::ForbiddenTerm { term: term.clone() });
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str, target: &str) -> GlossaryEntry {
+        GlossaryEntry { source: source.to_string(), target: target.to_string() }
+    }
+
+    #[test]
+    fn word_boundaries_apply_to_latin_terms_only() {
+        let options = GlossaryCheckOptions::default();
+        assert_eq!(count_term("category cat", "cat", &options), 1);
+        assert_eq!(count_term("Cat, CAT!", "cat", &options), 2);
+        // 中日文没有词边界，按子串匹配
+        assert_eq!(count_term("東京タワーと東京駅", "東京", &options), 2);
+
+        let loose = GlossaryCheckOptions { word_boundaries: false, ..options };
+        assert_eq!(count_term("category cat", "cat", &loose), 2);
+        let strict_case = GlossaryCheckOptions { case_sensitive: true, ..options };
+        assert_eq!(count_term("Cat cat", "cat", &strict_case), 1);
+    }
+
+    #[test]
+    fn variants_fold_only_when_enabled() {
+        let options = GlossaryCheckOptions::default();
+        assert_eq!(count_term("ひかり", "ヒカリ", &options), 0);
+        assert_eq!(count_term("髙橋さん", "高橋", &options), 0);
+
+        let folded = GlossaryCheckOptions { fold_variants: true, ..options };
+        assert_eq!(count_term("ひかり", "ヒカリ", &folded), 1);
+        assert_eq!(count_term("髙橋さん", "高橋", &folded), 1);
+        assert_eq!(count_term("ＡＢＣ", "abc", &folded), 1);
+    }
+
+    #[test]
+    fn missing_terms_respect_strictness() {
+        let glossary = vec![entry("ヒカリ", "光"), entry("東京", "东京"), entry("", "空")];
+        let source = "ヒカリ、ヒカリ！";
+        let translated = "光，等等！";
+
+        let options = GlossaryCheckOptions::default();
+        assert!(validate_translation(source, translated, &glossary, &[], &options).is_empty());
+
+        let count = GlossaryCheckOptions { strictness: GlossaryStrictness::Count, ..options };
+        assert_eq!(
+            validate_translation(source, translated, &glossary, &[], &count),
+            vec![TranslationWarning::MissingTerm { source: "ヒカリ".to_string(), target: "光".to_string() }]
+        );
+
+        let off = GlossaryCheckOptions { strictness: GlossaryStrictness::Off, ..options };
+        assert!(validate_translation(source, "等等", &glossary, &[], &off).is_empty());
+        assert_eq!(relevant_entries(source, &glossary, &options), vec![&glossary[0]]);
+    }
+
+    #[test]
+    fn forbidden_terms_are_reported_even_when_glossary_is_off() {
+        let options = GlossaryCheckOptions { strictness: GlossaryStrictness::Off, ..Default::default() };
+        let forbidden = vec!["hell".to_string(), "该死".to_string()];
+        let warnings = validate_translation("", "Hello, 该死的", &[], &forbidden, &options);
+        assert_eq!(warnings, vec![TranslationWarning::ForbiddenTerm { term: "该死".to_string() }]);
+        assert_eq!(warnings[0].message(), "译文中出现了禁用词「该死」");
+    }
+}
@@ -0,0 +1,143 @@
+//! Shared HTTP client for native plugins
+//!
+//! [`http_client`] builds a `reqwest::blocking::Client` from the host's `network` service
+//! (proxy URL, no-proxy list, custom CA certificate) and caches it until the settings change.
+//! Without a custom proxy the system proxy (`HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`, and the OS
+//! settings on Windows/macOS) is used. Invalid settings push one configuration notification
+//! instead of failing every request with an opaque error.
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::PluginContext;
+use crate::services::{NotificationLevel, NotificationPayload, NotificationServiceProxy};
+
+/// 请求总时限，比宿主的插件回调时限（30秒）短，超时按请求失败处理而不是停用插件
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 25;
+pub const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+const INVALID_SETTINGS_NOTIFICATION_ID: &str = "plugin:network:invalid-settings";
+
+/// Network settings provided by the host's `network` service
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    /// `http://`, `https://` or `socks5://` proxy for all requests; None uses the system proxy
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// Hosts that bypass the proxy, e.g. `localhost`, `.internal.example.com`, `10.0.0.0/8`
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// PEM or DER certificate trusted in addition to the system roots
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl NetworkSettings {
+    /// Read the settings from the host; hosts without the `network` service give the defaults
+    pub fn load(context: &PluginContext) -> Self {
+        context.call_service("network", "get_settings", json!({}))
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn build_client(&self) -> Result<reqwest::blocking::Client, String> {
+        let timeout = self.timeout_secs.filter(|secs| *secs > 0).unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+        let mut builder = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS));
+
+        if let Some(proxy_url) = self.proxy_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+            let proxy = reqwest::Proxy::all(proxy_url)
+                .map_err(|e| format!("Invalid proxy URL '{}': {}", proxy_url, e))?
+                .no_proxy(reqwest::NoProxy::from_string(&self.no_proxy.join(",")));
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = self.ca_cert_path.as_deref().filter(|path| !path.is_empty()) {
+            let bytes = std::fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate '{}': {}", path, e))?;
+            let certificate = reqwest::Certificate::from_pem(&bytes)
+                .or_else(|_| reqwest::Certificate::from_der(&bytes))
+                .map_err(|e| format!("Invalid CA certificate '{}': {}", path, e))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+struct CachedClient {
+    settings: NetworkSettings,
+    client: Result<reqwest::blocking::Client, String>,
+}
+
+static CLIENT_CACHE: Mutex<Option<CachedClient>> = Mutex::new(None);
+
+/// HTTP client built from the host's current network settings, cached until they change
+///
+/// When the settings are invalid every call returns the same error, and the user is notified
+/// once per distinct setting.
+pub fn http_client(context: &PluginContext) -> Result<reqwest::blocking::Client, String> {
+    let settings = NetworkSettings::load(context);
+    let mut cache = CLIENT_CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(cached) = cache.as_ref().filter(|cached| cached.settings == settings) {
+        return cached.client.clone();
+    }
+
+    let client = settings.build_client();
+    if let Err(error) = &client {
+        notify_invalid_settings(context, error);
+    }
+    let result = client.clone();
+    *cache = Some(CachedClient { settings, client });
+    result
+}
+
+fn notify_invalid_settings(context: &PluginContext, error: &str) {
+    let payload = NotificationPayload {
+        id: Some(INVALID_SETTINGS_NOTIFICATION_ID.to_string()),
+        title: Some("网络代理设置无效".to_string()),
+        message: format!("插件无法发送网络请求，请在设置中检查代理和证书：{}", error),
+        level: NotificationLevel::Error,
+        toast: Some(true),
+        sticky: Some(true),
+        source: Some(context.plugin_id.clone()),
+        ..Default::default()
+    };
+    let _ = NotificationServiceProxy::new(context.clone()).push(payload);
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockHost;
+
+    #[test]
+    fn invalid_proxy_notifies_once_and_valid_settings_rebuild() {
+        let host = MockHost::new();
+        host.respond_with("network", "get_settings", json!({ "proxy_url": "not a url" }));
+        let context = host.context("p");
+
+        let error = http_client(&context).unwrap_err();
+        assert!(error.contains("Invalid proxy URL"), "{}", error);
+        assert!(http_client(&context).is_err());
+        assert_eq!(host.calls_to("notifications", "push").len(), 1);
+
+        host.respond_with("network", "get_settings", json!({
+            "proxy_url": "http://proxy.example.com:8080",
+            "no_proxy": ["localhost", ".internal"],
+        }));
+        assert!(http_client(&context).is_ok());
+        assert_eq!(host.calls_to("notifications", "push").len(), 1);
+    }
+
+    #[test]
+    fn missing_network_service_uses_defaults() {
+        let host = MockHost::new();
+        assert_eq!(NetworkSettings::load(&host.context("p")), NetworkSettings::default());
+        assert!(NetworkSettings::default().build_client().is_ok());
+    }
+}
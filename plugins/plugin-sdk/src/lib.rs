@@ -5,6 +5,7 @@ pub mod services;
 pub mod events;
 pub mod bunny;
 pub mod config;
+pub mod glossary;
 
 #[cfg(feature = "wasm")]
 pub mod shared_buffer;
@@ -12,14 +13,25 @@ pub mod shared_buffer;
 #[cfg(feature = "native")]
 pub mod native;
 
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub use services::*;
 pub use events::*;
 pub use bunny::*;
 pub use config::*;
+pub use glossary::*;
 
 #[cfg(feature = "wasm")]
 pub use shared_buffer::*;
 
+/// 插件与宿主之间的接口版本，HostCallbacks、导出函数或元数据格式有不兼容的改动时加一
+/// 宿主只加载版本在其支持范围内的插件
+pub const PLUGIN_API_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
     pub id: String,
@@ -30,6 +42,9 @@ pub struct PluginMetadata {
     pub subscribed_events: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config_schema: Option<ConfigSchema>,
+    /// 构建插件时的PLUGIN_API_VERSION，由plugin_metadata!填写；旧SDK构建的插件没有此字段
+    #[serde(default)]
+    pub api_version: u32,
 }
 
 /// 增强的Plugin trait - 支持完整的服务访问和事件系统
@@ -68,6 +83,7 @@ macro_rules! plugin_metadata {
             author: env!("CARGO_PKG_AUTHORS").to_string(),
             subscribed_events: vec![],
             config_schema: None,
+            api_version: $crate::PLUGIN_API_VERSION,
         }
     };
     ($($event:expr),* $(,)?) => {
@@ -79,6 +95,7 @@ macro_rules! plugin_metadata {
             author: env!("CARGO_PKG_AUTHORS").to_string(),
             subscribed_events: vec![$($event.to_string()),*],
             config_schema: None,
+            api_version: $crate::PLUGIN_API_VERSION,
         }
     };
 }
@@ -95,6 +112,7 @@ macro_rules! plugin_metadata_with_config {
             author: env!("CARGO_PKG_AUTHORS").to_string(),
             subscribed_events: vec![],
             config_schema: Some($schema),
+            api_version: $crate::PLUGIN_API_VERSION,
         }
     };
     ($schema:expr, $($event:expr),* $(,)?) => {
@@ -106,6 +124,7 @@ macro_rules! plugin_metadata_with_config {
             author: env!("CARGO_PKG_AUTHORS").to_string(),
             subscribed_events: vec![$($event.to_string()),*],
             config_schema: Some($schema),
+            api_version: $crate::PLUGIN_API_VERSION,
         }
     };
 }
@@ -150,6 +169,12 @@ macro_rules! export_plugin {
             pub fn init_shared_buffer(&self, buffer: JsValue) -> Result<(), JsValue> {
                 use js_sys::SharedArrayBuffer;
                 
+                // 没有跨源隔离时宿主不提供共享缓冲区，服务调用退回异步消息
+                if buffer.is_undefined() || buffer.is_null() {
+                    web_sys::console::warn_1(&"SharedArrayBuffer unavailable, service calls are sent asynchronously".into());
+                    return Ok(());
+                }
+                
                 let shared_buffer = buffer.dyn_into::<SharedArrayBuffer>()
                     .map_err(|_| JsValue::from_str("SharedArrayBuffer is required but not provided"))?;
                     
@@ -426,4 +451,21 @@ macro_rules! export_plugin {
             }
         }
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_carries_api_version() {
+        let metadata = plugin_metadata!();
+        assert_eq!(metadata.api_version, PLUGIN_API_VERSION);
+
+        // 旧SDK构建的插件元数据没有api_version
+        let old: PluginMetadata = serde_json::from_value(serde_json::json!({
+            "id": "old", "name": "old", "version": "0.1.0", "description": "", "author": "", "subscribed_events": []
+        })).unwrap();
+        assert_eq!(old.api_version, 0);
+    }
+}
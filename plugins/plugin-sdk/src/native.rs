@@ -30,6 +30,12 @@ pub struct HostCallbacks {
 
 static mut HOST_CALLBACKS: Option<HostCallbacks> = None;
 
+/// 插件构建时的接口版本，宿主在设置回调和初始化之前读取，版本不兼容时不再调用其他导出函数
+#[unsafe(no_mangle)]
+pub extern "C" fn plugin_api_version() -> u32 {
+    crate::PLUGIN_API_VERSION
+}
+
 /// Set the host callbacks (called during plugin initialization)
 #[unsafe(no_mangle)]
 pub extern "C" fn plugin_set_host_callbacks(callbacks: HostCallbacks) {
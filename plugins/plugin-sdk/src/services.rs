@@ -10,19 +10,39 @@ pub trait ServiceProxy {
 #[derive(Clone)]
 pub struct PluginContext {
     pub plugin_id: String,
+    /// 测试时代替宿主处理服务调用
+    #[cfg(feature = "testing")]
+    mock_host: Option<crate::testing::MockHost>,
 }
 
 impl PluginContext {
     pub fn new(plugin_id: String) -> Self {
         Self {
             plugin_id,
+            #[cfg(feature = "testing")]
+            mock_host: None,
         }
     }
 
+    #[cfg(feature = "testing")]
+    pub(crate) fn with_mock_host(plugin_id: String, host: crate::testing::MockHost) -> Self {
+        Self { plugin_id, mock_host: Some(host) }
+    }
+
     /// 调用Core服务
     pub fn call_service(&self, _service: &str, _method: &str, _params: Value) -> Result<Value, String> {
+        #[cfg(feature = "testing")]
+        if let Some(host) = &self.mock_host {
+            return host.handle(&self.plugin_id, _service, _method, _params);
+        }
+
         #[cfg(feature = "wasm")]
         {
+            // 没有共享缓冲区时只能异步发送，调用方拿到null
+            if !crate::shared_buffer::is_shared_channel_ready() {
+                return crate::shared_buffer::post_service_call(&self.plugin_id, _service, _method, &_params)
+                    .map(|_| Value::Null);
+            }
             return crate::shared_buffer::call_service_sync(_service, _method, &_params)
                 .map_err(|e| format!("Service call failed: {}", e));
         }
@@ -92,6 +112,39 @@ impl MarkerServiceProxy {
         Ok(())
     }
 
+    /// 把标记移到同一项目的另一张图片，position为空时追加到目标图片末尾；整个移动可以一次撤销
+    pub fn move_marker_to_image(
+        &self,
+        marker_id: &str,
+        target_image_id: &str,
+        geometry: MarkerGeometry,
+        position: Option<u32>,
+    ) -> Result<bool, String> {
+        let result = self.context.call_service(
+            "markers",
+            "move_marker_to_image",
+            json!({
+                "marker_id": marker_id,
+                "target_image_id": target_image_id,
+                "geometry": geometry,
+                "position": position
+            })
+        )?;
+
+        Ok(result["success"].as_bool().unwrap_or(false))
+    }
+
+    /// 标记最近的译文（最旧的在前），origin为"manual"、"machine"或"import"
+    pub fn get_translation_history(&self, marker_id: &str) -> Result<Vec<TranslationHistoryEntry>, String> {
+        let result = self.context.call_service(
+            "markers",
+            "get_translation_history",
+            json!({ "marker_id": marker_id })
+        )?;
+
+        serde_json::from_value(result).map_err(|e| e.to_string())
+    }
+
     pub fn delete_marker(&self, marker_id: &str) -> Result<(), String> {
         self.context.call_service(
             "markers",
@@ -188,21 +241,63 @@ impl ImageServiceProxy {
         Ok(serde_json::from_value(result).map_err(|e| e.to_string())?)
     }
     
+    /// 借用图像的原始字节（仅native）：直接指向Core缓存中的同一份数据，不经过JSON复制
+    /// 同一张图片被多个插件读取时只读盘一次；返回值释放时自动归还，Core发出image:evicted后应重新获取
+    #[cfg(feature = "native")]
+    pub fn get_image_data_shared(&self, image_id: &str) -> Result<SharedImageData, String> {
+        let result = self.context.call_service(
+            "images",
+            "lease_image_data",
+            json!({ "image_id": image_id })
+        )?;
+        
+        let lease: ImageLease = serde_json::from_value(result).map_err(|e| e.to_string())?;
+        if lease.ptr == 0 && lease.len > 0 {
+            return Err(format!("Invalid image lease for image {}", image_id));
+        }
+        Ok(SharedImageData { lease, context: self.context.clone() })
+    }
+    
+    /// 通过SharedArrayBuffer通道读取图像字节（仅WASM），不经过JSON数组和postMessage复制
+    /// 返回的视图直接指向共享缓冲区，下一次服务调用会覆盖，需要保留时用to_vec复制一份
+    /// 图片超过共享缓冲区大小时返回错误，可以改用get_image_binary
+    #[cfg(feature = "wasm")]
+    pub fn get_image_shared(&self, image_id: &str) -> Result<SharedImageView, String> {
+        crate::shared_buffer::call_service_binary_sync("images", "get_image_bytes", &json!({ "image_id": image_id }))
+            .map(|view| SharedImageView { view })
+    }
+
+    /// 读取前端暂存在共享缓冲区中的图片（仅WASM），如OCR请求的裁剪图，image_ref见请求消息
+    #[cfg(feature = "wasm")]
+    pub fn get_staged_image_shared(&self, image_ref: &str) -> Result<SharedImageView, String> {
+        crate::shared_buffer::call_service_binary_sync("images", "read_staged_image", &json!({ "image_ref": image_ref }))
+            .map(|view| SharedImageView { view })
+    }
+    
+    /// 获取图像数据的存储方式
+    pub fn get_image_source_kind(&self, image_id: &str) -> Result<ImageSourceKind, String> {
+        let result = self.context.call_service(
+            "images",
+            "get_image_source_kind",
+            json!({ "image_id": image_id })
+        )?;
+        
+        serde_json::from_value(result).map_err(|e| e.to_string())
+    }
+    
     /// 获取图像的二进制数据
-    /// 对于FilePath类型，会通过Core API读取文件内容
-    /// 对于Binary类型，直接返回数据
+    /// 先查询存储方式：FilePath类型通过文件服务读取文件内容，
+    /// Binary/SharedBuffer类型直接从get_image_data取回数据
     pub fn get_image_binary(&self, image_id: &str) -> Result<Vec<u8>, String> {
-        let image_data = self.get_image_data(image_id)?;
-        
-        match image_data {
-            ImageData::FilePath { path: _ } => {
-                // 在桌面端，通过文件服务读取文件内容
-                // 传递image_id，让服务端通过Core API获取数据
-                self.read_image_file(image_id)
-            }
-            ImageData::Binary { data, .. } => {
-                // 在Web端，直接返回二进制数据
-                Ok(data)
+        match self.get_image_source_kind(image_id)? {
+            ImageSourceKind::FilePath => self.read_image_file(image_id),
+            ImageSourceKind::Binary | ImageSourceKind::SharedBuffer => {
+                match self.get_image_data(image_id)? {
+                    ImageData::Binary { data, .. } => Ok(data),
+                    ImageData::FilePath { .. } => {
+                        Err(format!("Image {} reported in-memory storage but returned a file path", image_id))
+                    }
+                }
             }
         }
     }
@@ -349,6 +444,22 @@ pub struct Marker {
     pub marker_type: String,
     pub x: f64,
     pub y: f64,
+    /// 多边形标记的顶点（百分比坐标），此时x/y为外接矩形左上角
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<Vec<(f64, f64)>>,
+    /// 字号倍率，未设置时为None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_hint: Option<f32>,
+    /// 标记标签（如"SFX"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationHistoryEntry {
+    pub translation: String,
+    pub timestamp: u64,
+    pub origin: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -358,6 +469,9 @@ pub struct Project {
     pub description: String,
     pub created_at: String,
     pub updated_at: String,
+    /// 阅读方向："leftToRight"或"rightToLeft"，旧版本宿主不提供
+    #[serde(default, alias = "readingDirection")]
+    pub reading_direction: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -376,6 +490,104 @@ pub enum ImageData {
     Binary { data: Vec<u8>, format: String },
 }
 
+/// 宿主租借的图像字节，与Core的ImageLease保持一致
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Deserialize)]
+struct ImageLease {
+    lease_id: u64,
+    ptr: usize,
+    len: usize,
+    format: String,
+}
+
+/// 借用的图像字节，生命周期内数据由宿主保证有效，drop时归还
+#[cfg(feature = "native")]
+pub struct SharedImageData {
+    lease: ImageLease,
+    context: PluginContext,
+}
+
+#[cfg(feature = "native")]
+impl SharedImageData {
+    pub fn as_slice(&self) -> &[u8] {
+        if self.lease.len == 0 {
+            return &[];
+        }
+        // 宿主在租借归还之前持有数据的引用，指针和长度一直有效
+        unsafe { std::slice::from_raw_parts(self.lease.ptr as *const u8, self.lease.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lease.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lease.len == 0
+    }
+
+    pub fn format(&self) -> &str {
+        &self.lease.format
+    }
+}
+
+#[cfg(feature = "native")]
+impl AsRef<[u8]> for SharedImageData {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "native")]
+impl Drop for SharedImageData {
+    fn drop(&mut self) {
+        let _ = self.context.call_service(
+            "images",
+            "release_image_data",
+            json!({ "lease_id": self.lease.lease_id })
+        );
+    }
+}
+
+/// 共享缓冲区中图像字节的视图，在插件下一次服务调用之前有效
+#[cfg(feature = "wasm")]
+pub struct SharedImageView {
+    view: js_sys::Uint8Array,
+}
+
+#[cfg(feature = "wasm")]
+impl SharedImageView {
+    pub fn len(&self) -> usize {
+        self.view.length() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.view.length() == 0
+    }
+
+    /// 复制到WASM内存，一次批量复制
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.view.to_vec()
+    }
+
+    /// 复制到已有的缓冲区，长度必须与视图相同
+    pub fn copy_to(&self, dst: &mut [u8]) {
+        self.view.copy_to(dst)
+    }
+
+    /// 直接交给JS（如OffscreenCanvas、ONNX Runtime Web）使用，不经过WASM内存
+    pub fn as_uint8_array(&self) -> &js_sys::Uint8Array {
+        &self.view
+    }
+}
+
+/// 图像数据的存储方式，与Core的ImageSourceKind保持一致
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ImageSourceKind {
+    FilePath,
+    Binary,
+    SharedBuffer,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMarkerRequest {
     pub image_id: String,
@@ -386,12 +598,26 @@ pub struct CreateMarkerRequest {
     pub y: f64,
 }
 
+/// 百分比坐标（0-100）下的标记几何，与Core的MarkerGeometryDTO保持一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MarkerGeometry {
+    Point { x: f64, y: f64 },
+    Rectangle { x: f64, y: f64, width: f64, height: f64 },
+    Polygon { points: Vec<(f64, f64)> },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateMarkerRequest {
     pub text: Option<String>,
     pub translation: Option<String>,
     pub x: Option<f64>,
     pub y: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_hint: Option<f32>,
+    /// 传空字符串清除标签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -459,3 +685,51 @@ pub struct NotificationPayload {
     pub extra: Option<Value>,
 }
 
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockHost;
+
+    fn images(host: &MockHost, kind: &str, data: Value) -> ImageServiceProxy {
+        host.respond_with("images", "get_image_source_kind", json!(kind))
+            .respond_with("images", "get_image_data", data);
+        ImageServiceProxy::new(host.context("p"))
+    }
+
+    #[test]
+    fn in_memory_kinds_read_image_data() {
+        for kind in ["Binary", "SharedBuffer"] {
+            let host = MockHost::new();
+            let proxy = images(&host, kind, json!({ "type": "Binary", "data": [1, 2, 3], "format": "png" }));
+            assert_eq!(proxy.get_image_source_kind("7").unwrap(), serde_json::from_value(json!(kind)).unwrap());
+            assert_eq!(proxy.get_image_binary("7").unwrap(), vec![1, 2, 3]);
+            assert!(host.calls_to("files", "read_binary").is_empty());
+        }
+
+        // 声明为内存数据却返回路径时报错，不去读文件
+        let host = MockHost::new();
+        let proxy = images(&host, "SharedBuffer", json!({ "type": "FilePath", "path": "/tmp/p.png" }));
+        assert!(proxy.get_image_binary("7").is_err());
+    }
+
+    #[test]
+    fn file_path_kind_reads_the_file() {
+        let host = MockHost::new();
+        let proxy = images(&host, "FilePath", json!({ "type": "FilePath", "path": "/tmp/p.png" }));
+        host.respond_with("files", "read_binary", json!([9, 8, 7]));
+        assert_eq!(proxy.get_image_source_kind("7").unwrap(), ImageSourceKind::FilePath);
+
+        // native通过宿主回调直接读文件（MockHost不提供回调），其他平台走文件服务
+        #[cfg(feature = "native")]
+        {
+            assert!(proxy.get_image_binary("7").is_err());
+            assert!(host.calls_to("files", "read_binary").is_empty());
+        }
+        #[cfg(not(feature = "native"))]
+        {
+            assert_eq!(proxy.get_image_binary("7").unwrap(), vec![9, 8, 7]);
+            assert_eq!(host.calls_to("files", "read_binary")[0].params, json!({ "image_id": "7" }));
+        }
+    }
+}
@@ -13,8 +13,11 @@ use std::sync::atomic::{AtomicI32, Ordering};
 ///   [16-19] : Total data size
 ///   [20-23] : Chunk size for transfers
 ///   [24-27] : Error code
-///   [28-31] : Reserved
+///   [28-31] : Response payload kind (0=JSON, 1=raw bytes)
 /// [32-...] : Ring buffer for data
+///
+/// Raw byte responses (image data) are written contiguously from the start of the
+/// ring buffer, so the plugin can read them through a view without copying.
 pub struct SharedBufferChannel {
     buffer: SharedArrayBuffer,
     header_array: Int32Array,
@@ -38,7 +41,11 @@ const OFFSET_READER_POS: u32 = 3;
 const OFFSET_TOTAL_SIZE: u32 = 4;
 const OFFSET_CHUNK_SIZE: u32 = 5;
 const OFFSET_ERROR_CODE: u32 = 6;
-const _OFFSET_RESERVED: u32 = 7;
+const OFFSET_PAYLOAD_KIND: u32 = 7;
+
+// Payload kinds
+const PAYLOAD_JSON: i32 = 0;
+const PAYLOAD_BINARY: i32 = 1;
 
 // Sizes
 const HEADER_SIZE: u32 = 32;  // 8 x 4 bytes
@@ -100,6 +107,45 @@ impl SharedBufferChannel {
     
     /// Send synchronous request and wait for response
     pub fn call_sync(&self, service: &str, method: &str, params: &serde_json::Value) -> Result<serde_json::Value, String> {
+        self.send_request(service, method, params)?;
+        
+        if self.atomics_load(OFFSET_PAYLOAD_KIND) != PAYLOAD_JSON {
+            self.set_status(STATUS_IDLE);
+            return Err(format!("{}.{} returned raw bytes, expected JSON", service, method));
+        }
+        
+        // Read response using ring buffer
+        let response = self.read_data_streaming();
+        
+        // Reset status to idle
+        self.set_status(STATUS_IDLE);
+        
+        response
+    }
+    
+    /// Send synchronous request whose response is raw bytes
+    ///
+    /// The returned array is a view into the shared buffer and stays valid only until
+    /// the next call on this channel; copy it with `to_vec` to keep the data.
+    pub fn call_binary_sync(&self, service: &str, method: &str, params: &serde_json::Value) -> Result<Uint8Array, String> {
+        self.send_request(service, method, params)?;
+        
+        let kind = self.atomics_load(OFFSET_PAYLOAD_KIND);
+        let total_size = self.atomics_load(OFFSET_TOTAL_SIZE).max(0) as u32;
+        // Host writes the status only after all bytes are in place
+        self.set_status(STATUS_IDLE);
+        
+        if kind != PAYLOAD_BINARY {
+            return Err(format!("{}.{} did not return raw bytes", service, method));
+        }
+        if total_size >= self.ring_buffer_size {
+            return Err(format!("Response of {} bytes does not fit the shared buffer", total_size));
+        }
+        Ok(Uint8Array::new_with_byte_offset_and_length(&self.buffer, self.data_start_offset, total_size))
+    }
+    
+    /// Write request and wait until the host has responded
+    fn send_request(&self, service: &str, method: &str, params: &serde_json::Value) -> Result<(), String> {
         // Generate unique request ID
         let request_id = self.request_id.fetch_add(1, Ordering::SeqCst);
         
@@ -129,16 +175,8 @@ impl SharedBufferChannel {
         // Notify main thread
         self.atomics_notify(OFFSET_STATUS);
         
-        // Wait for response
-        self.wait_for_response(request_id)?;
-        
-        // Read response using ring buffer
-        let response = self.read_data_streaming()?;
-        
-        // Reset status to idle
-        self.set_status(STATUS_IDLE);
-        
-        Ok(response)
+        // Wait for response; after an error the channel goes back to idle for the next call
+        self.wait_for_response(request_id).inspect_err(|_| self.set_status(STATUS_IDLE))
     }
     
     /// Write data using ring buffer streaming
@@ -413,6 +451,39 @@ pub fn init_shared_channel(buffer: SharedArrayBuffer) {
     });
 }
 
+/// Whether [`init_shared_channel`] succeeded; without cross-origin isolation the host
+/// provides no buffer and service calls go through [`post_service_call`]
+pub fn is_shared_channel_ready() -> bool {
+    SHARED_CHANNEL.with(|channel| channel.borrow().is_some())
+}
+
+/// Send a service call to the host as a worker message without waiting for the result
+///
+/// Fallback for pages without cross-origin isolation: calls that only notify the host
+/// (emitting events, registering services) still work, but return values are lost.
+pub fn post_service_call(plugin_id: &str, service: &str, method: &str, params: &serde_json::Value) -> Result<(), String> {
+    #[wasm_bindgen(inline_js = "
+        export function post_service_call(plugin_id, service, method, params_json) {
+            self.postMessage({
+                type: 'SERVICE_CALL',
+                callId: null,
+                pluginId: plugin_id,
+                service,
+                method,
+                params: JSON.parse(params_json)
+            });
+        }
+    ")]
+    extern "C" {
+        #[wasm_bindgen(catch)]
+        fn post_service_call(plugin_id: &str, service: &str, method: &str, params_json: &str) -> Result<(), JsValue>;
+    }
+
+    let params_json = serde_json::to_string(params).map_err(|e| e.to_string())?;
+    post_service_call(plugin_id, service, method, &params_json)
+        .map_err(|e| format!("Failed to post {}.{}: {:?}", service, method, e))
+}
+
 /// Use SharedBuffer channel for synchronous calls
 pub fn call_service_sync(service: &str, method: &str, params: &serde_json::Value) -> Result<serde_json::Value, String> {
     SHARED_CHANNEL.with(|channel| {
@@ -422,4 +493,15 @@ pub fn call_service_sync(service: &str, method: &str, params: &serde_json::Value
             None => Err("SharedBuffer channel not initialized".to_string())
         }
     })
+}
+
+/// Use SharedBuffer channel for calls returning raw bytes, see [`SharedBufferChannel::call_binary_sync`]
+pub fn call_service_binary_sync(service: &str, method: &str, params: &serde_json::Value) -> Result<Uint8Array, String> {
+    SHARED_CHANNEL.with(|channel| {
+        let channel_ref = channel.borrow();
+        match channel_ref.as_ref() {
+            Some(ch) => ch.call_binary_sync(service, method, params),
+            None => Err("SharedBuffer channel not initialized".to_string())
+        }
+    })
 }
\ No newline at end of file
@@ -0,0 +1,251 @@
+//! In-memory host for unit-testing plugins without the app
+//!
+//! [`PluginHarness`] initializes a plugin against a [`MockHost`] instead of the real core:
+//! every service call the plugin makes is recorded, answered by a stub registered with
+//! [`MockHost::respond`] (or `null` when none matches), and events emitted through
+//! `events.emit_business_event` can be read back with [`MockHost::emitted_events`].
+//! The test then drives the plugin with core events and plugin messages.
+//!
+//! Enable the `testing` feature for tests only:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! bubblefish-plugin-sdk = { path = "../plugin-sdk", features = ["testing"] }
+//! ```
+//!
+//! ```
+//! use bubblefish_plugin_sdk::testing::PluginHarness;
+//! use bubblefish_plugin_sdk::*;
+//! use serde_json::{json, Value};
+//!
+//! struct Echo { context: Option<PluginContext> }
+//!
+//! impl Plugin for Echo {
+//!     fn init(&mut self, context: PluginContext, _services: ServiceProxyManager) -> Result<(), String> {
+//!         self.context = Some(context);
+//!         Ok(())
+//!     }
+//!     fn on_core_event(&mut self, _event: &CoreEvent) -> Result<(), String> { Ok(()) }
+//!     fn on_plugin_message(&mut self, _from: &str, message: Value) -> Result<(), String> {
+//!         let ctx = self.context.as_ref().ok_or("not initialized")?;
+//!         ctx.call_service("events", "emit_business_event", json!({ "event_name": "echo", "data": message }))?;
+//!         Ok(())
+//!     }
+//!     fn on_activate(&mut self) -> Result<(), String> { Ok(()) }
+//!     fn on_deactivate(&mut self) -> Result<(), String> { Ok(()) }
+//!     fn destroy(&mut self) {}
+//!     fn get_metadata(&self) -> PluginMetadata { plugin_metadata!() }
+//! }
+//!
+//! let mut harness = PluginHarness::new(Echo { context: None }, "echo");
+//! harness.init().unwrap();
+//! harness.send_message("frontend", json!({ "text": "hi" })).unwrap();
+//! assert_eq!(harness.host().emitted_events()[0].data, json!({ "text": "hi" }));
+//! ```
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use serde_json::Value;
+use crate::{CoreEvent, Plugin, PluginContext, ServiceProxyManager};
+
+type Responder = Arc<dyn Fn(&Value) -> Result<Value, String> + Send + Sync>;
+
+/// One service call made by the plugin
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceCall {
+    pub plugin_id: String,
+    pub service: String,
+    pub method: String,
+    pub params: Value,
+}
+
+/// An event the plugin emitted through `events.emit_business_event`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmittedEvent {
+    pub event_name: String,
+    pub data: Value,
+}
+
+#[derive(Default)]
+struct MockHostState {
+    calls: Vec<ServiceCall>,
+    responders: HashMap<(String, String), Responder>,
+    config: HashMap<String, Value>,
+}
+
+/// Fake core that records service calls; clones share the same state
+#[derive(Clone, Default)]
+pub struct MockHost {
+    state: Arc<Mutex<MockHostState>>,
+}
+
+impl std::fmt::Debug for MockHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockHost").field("calls", &self.lock().calls.len()).finish()
+    }
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A context whose service calls go to this host
+    pub fn context(&self, plugin_id: impl Into<String>) -> PluginContext {
+        PluginContext::with_mock_host(plugin_id.into(), self.clone())
+    }
+
+    /// Answer `service.method` with `responder`, replacing any earlier stub
+    pub fn respond(
+        &self,
+        service: &str,
+        method: &str,
+        responder: impl Fn(&Value) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> &Self {
+        self.lock().responders.insert((service.to_string(), method.to_string()), Arc::new(responder));
+        self
+    }
+
+    /// Answer `service.method` with a fixed value
+    pub fn respond_with(&self, service: &str, method: &str, value: Value) -> &Self {
+        self.respond(service, method, move |_| Ok(value.clone()))
+    }
+
+    /// Value returned by `config.get` for `key`; unset keys read as `null`
+    pub fn set_config(&self, key: &str, value: Value) -> &Self {
+        self.lock().config.insert(key.to_string(), value);
+        self
+    }
+
+    /// All recorded calls, oldest first
+    pub fn calls(&self) -> Vec<ServiceCall> {
+        self.lock().calls.clone()
+    }
+
+    pub fn calls_to(&self, service: &str, method: &str) -> Vec<ServiceCall> {
+        self.lock().calls.iter()
+            .filter(|call| call.service == service && call.method == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Events emitted through `events.emit_business_event`, oldest first
+    pub fn emitted_events(&self) -> Vec<EmittedEvent> {
+        self.calls_to("events", "emit_business_event").into_iter()
+            .map(|call| EmittedEvent {
+                event_name: call.params["event_name"].as_str().unwrap_or_default().to_string(),
+                data: call.params["data"].clone(),
+            })
+            .collect()
+    }
+
+    /// Forget recorded calls; stubs and config are kept
+    pub fn clear_calls(&self) {
+        self.lock().calls.clear();
+    }
+
+    pub(crate) fn handle(&self, plugin_id: &str, service: &str, method: &str, params: Value) -> Result<Value, String> {
+        let responder = {
+            let mut state = self.lock();
+            state.calls.push(ServiceCall {
+                plugin_id: plugin_id.to_string(),
+                service: service.to_string(),
+                method: method.to_string(),
+                params: params.clone(),
+            });
+            match state.responders.get(&(service.to_string(), method.to_string())) {
+                Some(responder) => responder.clone(),
+                None if (service, method) == ("config", "get") => {
+                    let key = params["key"].as_str().unwrap_or_default();
+                    return Ok(state.config.get(key).cloned().unwrap_or(Value::Null));
+                }
+                None => return Ok(Value::Null),
+            }
+        };
+        // 锁外调用，stub里可以再查询host
+        responder(&params)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockHostState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Drives a plugin the way the host does, against a [`MockHost`]
+pub struct PluginHarness<P: Plugin> {
+    pub plugin: P,
+    plugin_id: String,
+    host: MockHost,
+}
+
+impl<P: Plugin> PluginHarness<P> {
+    pub fn new(plugin: P, plugin_id: impl Into<String>) -> Self {
+        Self::with_host(plugin, plugin_id, MockHost::new())
+    }
+
+    /// Use a host prepared with stubs before `init`
+    pub fn with_host(plugin: P, plugin_id: impl Into<String>, host: MockHost) -> Self {
+        Self { plugin, plugin_id: plugin_id.into(), host }
+    }
+
+    pub fn host(&self) -> &MockHost {
+        &self.host
+    }
+
+    pub fn context(&self) -> PluginContext {
+        self.host.context(self.plugin_id.clone())
+    }
+
+    /// `init` followed by `on_activate`, as the host does when loading an enabled plugin
+    pub fn init(&mut self) -> Result<(), String> {
+        let context = self.context();
+        self.plugin.init(context.clone(), ServiceProxyManager::new(context))?;
+        self.plugin.on_activate()
+    }
+
+    /// Deliver a core event; like the host, only events in `subscribed_events` (or `"*"`) reach the
+    /// plugin, and the return value tells whether it was delivered
+    pub fn dispatch_event(&mut self, event: &CoreEvent) -> Result<bool, String> {
+        let subscribed = self.plugin.get_metadata().subscribed_events;
+        if !subscribed.iter().any(|name| name == "*" || event.matches(name)) {
+            return Ok(false);
+        }
+        self.plugin.on_core_event(event)?;
+        Ok(true)
+    }
+
+    pub fn send_message(&mut self, from: &str, message: Value) -> Result<(), String> {
+        self.plugin.on_plugin_message(from, message)
+    }
+
+    /// `on_deactivate` followed by `destroy`
+    pub fn shutdown(mut self) -> Result<MockHost, String> {
+        self.plugin.on_deactivate()?;
+        self.plugin.destroy();
+        Ok(self.host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn host_records_calls_and_answers_stubs() {
+        let host = MockHost::new();
+        host.respond("markers", "get_marker", |params| Ok(json!({ "id": params["marker_id"] })))
+            .set_config("model", json!("m1"));
+        let ctx = host.context("p");
+
+        assert_eq!(ctx.call_service("markers", "get_marker", json!({ "marker_id": "7" })).unwrap(), json!({ "id": "7" }));
+        assert_eq!(ctx.call_service("config", "get", json!({ "plugin_id": "p", "key": "model" })).unwrap(), json!("m1"));
+        assert_eq!(ctx.call_service("stats", "get_stats", json!({})).unwrap(), Value::Null);
+        ctx.call_service("events", "emit_business_event", json!({ "event_name": "done", "data": 1 })).unwrap();
+
+        assert_eq!(host.calls().len(), 4);
+        assert_eq!(host.calls_to("markers", "get_marker")[0].plugin_id, "p");
+        assert_eq!(host.emitted_events(), vec![EmittedEvent { event_name: "done".to_string(), data: json!(1) }]);
+        host.clear_calls();
+        assert!(host.calls().is_empty());
+    }
+}